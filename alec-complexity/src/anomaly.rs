@@ -6,8 +6,8 @@
 
 //! Anomaly detection and event emission.
 
-use crate::config::AnomalyConfig;
-use crate::delta::ZScores;
+use crate::config::{AnomalyConfig, ChannelConfig};
+use crate::delta::{ChannelZScore, ZScores};
 use crate::event::{ComplexityEvent, EventSeverity, EventType};
 use crate::structure::StructureBreak;
 use std::collections::HashMap;
@@ -19,6 +19,10 @@ pub struct AnomalyDetector {
     condition_start_ms: HashMap<EventType, u64>,
     /// Tracks last event emission time for cooldown.
     last_event_ms: HashMap<EventType, u64>,
+    /// Persistence start of each channel's anomaly condition.
+    channel_condition_start_ms: HashMap<String, u64>,
+    /// Last channel anomaly emitted per channel, for cooldown.
+    channel_last_event_ms: HashMap<String, u64>,
 }
 
 impl AnomalyDetector {
@@ -27,6 +31,8 @@ impl AnomalyDetector {
             config,
             condition_start_ms: HashMap::new(),
             last_event_ms: HashMap::new(),
+            channel_condition_start_ms: HashMap::new(),
+            channel_last_event_ms: HashMap::new(),
         }
     }
 
@@ -103,22 +109,110 @@ impl AnomalyDetector {
 
         // Check criticality shift
         if self.config.events.criticality_shift {
-            if let Some((old_top, new_top)) = criticality_change {
-                if self.check_cooldown(EventType::CriticalityShift, timestamp_ms) {
-                    events.push(ComplexityEvent::criticality_shift(
-                        timestamp_ms,
-                        old_top,
-                        new_top,
-                    ));
-                    self.last_event_ms
-                        .insert(EventType::CriticalityShift, timestamp_ms);
+            match criticality_change {
+                Some((old_top, new_top)) => {
+                    if self.persisted(EventType::CriticalityShift, timestamp_ms)
+                        && self.check_cooldown(EventType::CriticalityShift, timestamp_ms)
+                    {
+                        events.push(ComplexityEvent::criticality_shift(
+                            timestamp_ms,
+                            old_top,
+                            new_top,
+                        ));
+                        self.condition_start_ms.remove(&EventType::CriticalityShift);
+                        self.last_event_ms
+                            .insert(EventType::CriticalityShift, timestamp_ms);
+                    }
+                }
+                None => {
+                    self.condition_start_ms.remove(&EventType::CriticalityShift);
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Evaluate per-channel z-scores against the channel thresholds.
+    ///
+    /// Both directions count (|z|). Persistence and cooldown are those of
+    /// the anomaly config, kept per channel.
+    pub fn evaluate_channels(
+        &mut self,
+        channels: &[ChannelZScore],
+        config: &ChannelConfig,
+        timestamp_ms: u64,
+    ) -> Vec<ComplexityEvent> {
+        if !self.config.enabled || !self.config.events.channel_entropy_anomaly {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        // Channels below the threshold, or absent, lose their persistence
+        let mut held = HashMap::new();
+        for channel in channels {
+            if channel.z.abs() < config.z_threshold_warn {
+                continue;
+            }
+            let start_ms = self
+                .channel_condition_start_ms
+                .get(&channel.channel_id)
+                .copied()
+                .unwrap_or(timestamp_ms);
+            held.insert(channel.channel_id.clone(), start_ms);
+
+            if timestamp_ms.saturating_sub(start_ms) < self.config.persistence_ms {
+                continue;
+            }
+            if let Some(&last_ms) = self.channel_last_event_ms.get(&channel.channel_id) {
+                if timestamp_ms.saturating_sub(last_ms) < self.config.cooldown_ms {
+                    continue;
                 }
             }
+
+            let severity = if channel.z.abs() >= config.z_threshold_crit {
+                EventSeverity::Critical
+            } else {
+                EventSeverity::Warning
+            };
+            let threshold = config.z_threshold_warn.copysign(channel.z);
+            events.push(ComplexityEvent::channel_entropy_anomaly(
+                timestamp_ms,
+                severity,
+                &channel.channel_id,
+                channel.z,
+                threshold,
+            ));
+            self.channel_last_event_ms
+                .insert(channel.channel_id.clone(), timestamp_ms);
         }
+        self.channel_condition_start_ms = held;
 
         events
     }
 
+    /// Z-score event types whose condition is at or beyond the warning
+    /// threshold, ignoring persistence and cooldown.
+    pub fn held_conditions(&self, z_scores: &ZScores) -> Vec<EventType> {
+        let warn = self.config.z_threshold_warn;
+        let mut held = Vec::new();
+        if z_scores.h_bytes >= warn {
+            held.push(EventType::PayloadEntropySpike);
+        }
+        if z_scores
+            .tc
+            .unwrap_or(0.0)
+            .max(z_scores.h_joint.unwrap_or(0.0))
+            >= warn
+        {
+            held.push(EventType::ComplexitySurge);
+        }
+        if z_scores.r.is_some_and(|z_r| z_r <= -warn) {
+            held.push(EventType::RedundancyDrop);
+        }
+        held
+    }
+
     /// Check z-score based anomaly with persistence and cooldown.
     fn check_z_score_event(
         &mut self,
@@ -152,14 +246,7 @@ impl AnomalyDetector {
         }
 
         // Check persistence
-        let start_ms = *self
-            .condition_start_ms
-            .entry(event_type)
-            .or_insert(timestamp_ms);
-
-        let persisted = timestamp_ms.saturating_sub(start_ms) >= self.config.persistence_ms;
-
-        if !persisted {
+        if !self.persisted(event_type, timestamp_ms) {
             return None;
         }
 
@@ -199,6 +286,16 @@ impl AnomalyDetector {
         Some(event)
     }
 
+    /// Record that the condition for `event_type` holds and check whether
+    /// it has held for the persistence time.
+    fn persisted(&mut self, event_type: EventType, timestamp_ms: u64) -> bool {
+        let start_ms = *self
+            .condition_start_ms
+            .entry(event_type)
+            .or_insert(timestamp_ms);
+        timestamp_ms.saturating_sub(start_ms) >= self.config.persistence_ms
+    }
+
     /// Check if cooldown has passed for event type.
     fn check_cooldown(&self, event_type: EventType, timestamp_ms: u64) -> bool {
         if let Some(&last_ms) = self.last_event_ms.get(&event_type) {
@@ -212,6 +309,8 @@ impl AnomalyDetector {
     pub fn reset(&mut self) {
         self.condition_start_ms.clear();
         self.last_event_ms.clear();
+        self.channel_condition_start_ms.clear();
+        self.channel_last_event_ms.clear();
     }
 }
 
@@ -335,6 +434,23 @@ mod tests {
         assert!(events.len() >= 3); // At least payload, complexity, redundancy
     }
 
+    #[test]
+    fn test_held_conditions() {
+        let detector = AnomalyDetector::new(create_test_config());
+
+        let z_scores = ZScores {
+            h_bytes: 2.5,
+            h_joint: Some(1.0),
+            r: Some(-2.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            detector.held_conditions(&z_scores),
+            vec![EventType::PayloadEntropySpike, EventType::RedundancyDrop]
+        );
+        assert!(detector.held_conditions(&ZScores::default()).is_empty());
+    }
+
     #[test]
     fn test_structure_break_event() {
         let mut config = create_test_config();
@@ -344,6 +460,8 @@ mod tests {
         let break_info = StructureBreak {
             changed_edges: vec![],
             total_change: 0.5,
+            added_channels: vec![],
+            removed_channels: vec![],
         };
 
         let events = detector.evaluate(&ZScores::default(), Some(&break_info), None, 1000);
@@ -393,4 +511,31 @@ mod tests {
         let events = detector.evaluate(&z_scores, None, None, 1000);
         assert!(events.is_empty()); // Persistence reset
     }
+
+    #[test]
+    fn test_channel_cooldown_per_channel() {
+        let mut detector = AnomalyDetector::new(create_test_config());
+        let config = ChannelConfig::default();
+        let z = |channel_id: &str, z: f64| ChannelZScore {
+            channel_id: channel_id.to_string(),
+            h: 0.0,
+            delta: 0.0,
+            z,
+        };
+
+        // "a" drops, persisting from t=0
+        let events = detector.evaluate_channels(&[z("a", -3.5)], &config, 0);
+        assert!(events.is_empty());
+        let events = detector.evaluate_channels(&[z("a", -3.5)], &config, 1000);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].severity, EventSeverity::Warning);
+
+        // "a" is cooling down, "b" is not
+        let both = [z("a", -5.0), z("b", 4.5)];
+        assert!(detector.evaluate_channels(&both, &config, 1500).is_empty());
+        let events = detector.evaluate_channels(&both, &config, 2500);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].severity, EventSeverity::Critical);
+        assert!(events[0].message.contains("Channel b"));
+    }
 }
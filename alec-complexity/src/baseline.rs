@@ -7,7 +7,33 @@
 //! Baseline building and management.
 
 use crate::config::{BaselineConfig, BaselineUpdateMode};
+use crate::history::MetricKind;
+use crate::input::ChannelEntropy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Name of the profile used until another one is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Scale turning a median absolute deviation into a standard deviation
+/// estimate for normally distributed data.
+const MAD_SCALE: f64 = 1.4826;
+
+/// Time window whose inputs are kept out of the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExclusionWindow {
+    /// First excluded timestamp (ms).
+    pub start_ms: u64,
+    /// End of the window (ms, exclusive).
+    pub end_ms: u64,
+}
+
+impl ExclusionWindow {
+    /// Whether `timestamp_ms` falls inside the window.
+    pub fn contains(&self, timestamp_ms: u64) -> bool {
+        (self.start_ms..self.end_ms).contains(&timestamp_ms)
+    }
+}
 
 /// Current state of the baseline.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +52,9 @@ pub struct FieldStats {
     pub(crate) sum: f64,
     #[serde(skip)]
     pub(crate) sum_sq: f64,
+    /// Samples kept while building in robust mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) samples: Vec<f64>,
 }
 
 impl FieldStats {
@@ -40,6 +69,27 @@ impl FieldStats {
         self.recompute();
     }
 
+    /// Add a sample and keep it for [`FieldStats::apply_robust`].
+    pub fn add_retained_sample(&mut self, value: f64) {
+        self.add_sample(value);
+        self.samples.push(value);
+    }
+
+    /// Replace mean and std with the median and scaled MAD of the kept
+    /// samples, then drop them. Does nothing without kept samples.
+    pub fn apply_robust(&mut self) {
+        if self.samples.is_empty() {
+            return;
+        }
+        let mut samples = std::mem::take(&mut self.samples);
+        let center = median(&mut samples);
+        for value in samples.iter_mut() {
+            *value = (*value - center).abs();
+        }
+        self.mean = center;
+        self.std = MAD_SCALE * median(&mut samples);
+    }
+
     pub fn update_ema(&mut self, value: f64, alpha: f64) {
         self.mean = alpha * value + (1.0 - alpha) * self.mean;
         let variance = (value - self.mean).powi(2);
@@ -71,6 +121,17 @@ impl FieldStats {
     }
 }
 
+/// Median of non-empty `values`, reordering them.
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid]) / 2.0
+    }
+}
+
 /// Complete baseline state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Baseline {
@@ -80,6 +141,9 @@ pub struct Baseline {
     pub h_joint: FieldStats,
     pub h_bytes: FieldStats,
     pub r: Option<FieldStats>,
+    /// Entropy statistics per channel, when channel tracking is enabled.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, FieldStats>,
     start_time_ms: u64,
     valid_signal_count: u32,
 }
@@ -97,11 +161,44 @@ impl Baseline {
             } else {
                 None
             },
+            channels: BTreeMap::new(),
             start_time_ms: 0,
             valid_signal_count: 0,
         }
     }
 
+    /// Locked baseline holding the statistics of `summary`.
+    ///
+    /// Fails if `track_r` is set and the summary has no R statistics.
+    pub fn from_summary(summary: &BaselineSummary, track_r: bool) -> Result<Self, String> {
+        if track_r && summary.r.is_none() {
+            return Err("missing statistics for metric r".to_string());
+        }
+        // Fields need two samples to be scored
+        let count = summary.sample_count.max(2);
+        let field = |stats: &MetricSummary| FieldStats {
+            mean: stats.mean,
+            std: stats.std,
+            count,
+            ..Default::default()
+        };
+        Ok(Self {
+            state: BaselineState::Locked,
+            build_progress: 1.0,
+            tc: field(&summary.tc),
+            h_joint: field(&summary.h_joint),
+            h_bytes: field(&summary.h_bytes),
+            r: if track_r {
+                summary.r.as_ref().map(field)
+            } else {
+                None
+            },
+            channels: BTreeMap::new(),
+            start_time_ms: 0,
+            valid_signal_count: count.min(u32::MAX as u64) as u32,
+        })
+    }
+
     pub fn start(&mut self, timestamp_ms: u64) {
         self.start_time_ms = timestamp_ms;
     }
@@ -115,21 +212,61 @@ impl Baseline {
         timestamp_ms: u64,
         config: &BaselineConfig,
     ) {
-        self.h_bytes.add_sample(h_bytes);
+        let add = if config.robust {
+            FieldStats::add_retained_sample
+        } else {
+            FieldStats::add_sample
+        };
+        add(&mut self.h_bytes, h_bytes);
 
         if let (Some(tc_val), Some(hj_val)) = (tc, h_joint) {
-            self.tc.add_sample(tc_val);
-            self.h_joint.add_sample(hj_val);
+            add(&mut self.tc, tc_val);
+            add(&mut self.h_joint, hj_val);
             self.valid_signal_count += 1;
         }
 
         if let (Some(ref mut r_stats), Some(r_val)) = (&mut self.r, r) {
-            r_stats.add_sample(r_val);
+            add(r_stats, r_val);
         }
 
         self.update_progress(timestamp_ms, config);
     }
 
+    /// Add the entropy of each channel, tracking at most `max_channels`
+    /// channels. Returns how many channels were ignored for the limit.
+    pub fn add_channel_samples(
+        &mut self,
+        channels: &[ChannelEntropy],
+        max_channels: usize,
+        config: &BaselineConfig,
+    ) -> usize {
+        let mut ignored = 0;
+        for channel in channels {
+            if !self.channels.contains_key(&channel.channel_id)
+                && self.channels.len() >= max_channels
+            {
+                ignored += 1;
+                continue;
+            }
+            let stats = self.channels.entry(channel.channel_id.clone()).or_default();
+            if config.robust {
+                stats.add_retained_sample(channel.h);
+            } else {
+                stats.add_sample(channel.h);
+            }
+        }
+        ignored
+    }
+
+    /// EMA update of the tracked channels present in `channels`.
+    pub fn update_channels_ema(&mut self, channels: &[ChannelEntropy], alpha: f64) {
+        for channel in channels {
+            if let Some(stats) = self.channels.get_mut(&channel.channel_id) {
+                stats.update_ema(channel.h, alpha);
+            }
+        }
+    }
+
     pub fn update_ema(
         &mut self,
         tc: Option<f64>,
@@ -161,9 +298,20 @@ impl Baseline {
         time_elapsed && enough_samples
     }
 
+    /// Lock the baseline. Fields built in robust mode switch to their
+    /// median and MAD.
     pub fn lock(&mut self) {
         self.state = BaselineState::Locked;
         self.build_progress = 1.0;
+        self.tc.apply_robust();
+        self.h_joint.apply_robust();
+        self.h_bytes.apply_robust();
+        if let Some(r) = &mut self.r {
+            r.apply_robust();
+        }
+        for stats in self.channels.values_mut() {
+            stats.apply_robust();
+        }
     }
 
     pub fn is_ready(&self) -> bool {
@@ -191,6 +339,103 @@ impl Baseline {
     }
 }
 
+/// Nominal mean and standard deviation of one metric.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub mean: f64,
+    pub std: f64,
+}
+
+/// Per-metric nominal statistics, enough to lock a baseline without
+/// building it from samples (e.g. those of a generated dataset).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSummary {
+    pub tc: MetricSummary,
+    pub h_joint: MetricSummary,
+    pub h_bytes: MetricSummary,
+    /// Required when the engine computes R.
+    pub r: Option<MetricSummary>,
+    /// Samples behind the statistics, 0 when unknown.
+    #[serde(default)]
+    pub sample_count: u64,
+}
+
+impl BaselineSummary {
+    /// Metrics every summary carries.
+    pub const REQUIRED: [MetricKind; 3] = [MetricKind::Tc, MetricKind::HJoint, MetricKind::HBytes];
+
+    /// Build a summary from means and standard deviations keyed by metric
+    /// name ([`MetricKind::as_str`]), as carried by dataset manifests.
+    ///
+    /// `tc`, `h_joint` and `h_bytes` are required, `r` is optional. Fails
+    /// on a missing or unknown metric, a non-finite value or a negative
+    /// std. A zero std is accepted; see [`Self::zero_std_metrics`].
+    pub fn from_dataset_stats(
+        means: &BTreeMap<String, f64>,
+        stds: &BTreeMap<String, f64>,
+    ) -> Result<Self, String> {
+        if let Some(name) = means
+            .keys()
+            .chain(stds.keys())
+            .find(|name| !MetricKind::ALL.iter().any(|m| m.as_str() == name.as_str()))
+        {
+            return Err(format!("unknown metric {name}"));
+        }
+
+        let metric = |kind: MetricKind| -> Result<Option<MetricSummary>, String> {
+            let name = kind.as_str();
+            let (mean, std) = match (means.get(name), stds.get(name)) {
+                (None, None) => return Ok(None),
+                (Some(mean), Some(std)) => (*mean, *std),
+                _ => return Err(format!("metric {name} needs both a mean and a std")),
+            };
+            if !mean.is_finite() || !std.is_finite() {
+                return Err(format!("metric {name} has a non-finite statistic"));
+            }
+            if std < 0.0 {
+                return Err(format!("metric {name} has a negative std ({std})"));
+            }
+            Ok(Some(MetricSummary { mean, std }))
+        };
+        let required = |kind: MetricKind| {
+            metric(kind)?.ok_or_else(|| format!("missing statistics for metric {}", kind.as_str()))
+        };
+
+        Ok(Self {
+            tc: required(MetricKind::Tc)?,
+            h_joint: required(MetricKind::HJoint)?,
+            h_bytes: required(MetricKind::HBytes)?,
+            r: metric(MetricKind::R)?,
+            sample_count: 0,
+        })
+    }
+
+    /// Set the number of samples behind the statistics.
+    pub fn with_sample_count(mut self, sample_count: u64) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Metrics whose std is zero. The baseline accepts them, but they are
+    /// never scored, so they raise no event.
+    pub fn zero_std_metrics(&self) -> Vec<MetricKind> {
+        MetricKind::ALL
+            .into_iter()
+            .filter(|kind| self.get(*kind).is_some_and(|stats| stats.std == 0.0))
+            .collect()
+    }
+
+    /// Statistics of one metric, if present.
+    pub fn get(&self, kind: MetricKind) -> Option<&MetricSummary> {
+        match kind {
+            MetricKind::Tc => Some(&self.tc),
+            MetricKind::HJoint => Some(&self.h_joint),
+            MetricKind::HBytes => Some(&self.h_bytes),
+            MetricKind::R => self.r.as_ref(),
+        }
+    }
+}
+
 /// Builder for baseline (manages lifecycle).
 pub struct BaselineBuilder {
     config: BaselineConfig,
@@ -248,6 +493,28 @@ impl BaselineBuilder {
         }
     }
 
+    /// Process the channel entropies of a sample, before
+    /// [`Self::process`] so the sample that locks the baseline counts.
+    ///
+    /// Returns how many channels were ignored for the `max_channels` limit
+    /// (only while building; channels first seen after the lock are not
+    /// scored).
+    pub fn process_channels(&mut self, channels: &[ChannelEntropy], max_channels: usize) -> usize {
+        match self.baseline.state {
+            BaselineState::Building => {
+                self.baseline
+                    .add_channel_samples(channels, max_channels, &self.config)
+            }
+            BaselineState::Locked => {
+                if let BaselineUpdateMode::Ema { alpha } = &self.config.update_mode {
+                    self.baseline
+                        .update_channels_ema(channels, *alpha as f64 * 0.01);
+                }
+                0
+            }
+        }
+    }
+
     pub fn baseline(&self) -> &Baseline {
         &self.baseline
     }
@@ -264,6 +531,141 @@ impl BaselineBuilder {
         self.baseline = baseline;
         self.initialized = true;
     }
+
+    /// Lock the baseline immediately. Returns true if it was building.
+    pub fn force_lock(&mut self) -> bool {
+        if self.baseline.is_ready() {
+            return false;
+        }
+        self.baseline.lock();
+        self.initialized = true;
+        true
+    }
+}
+
+/// Persisted state of all named baseline profiles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineProfilesState {
+    /// Name of the active profile.
+    pub active: String,
+    /// Baseline of every known profile, keyed by name.
+    pub profiles: BTreeMap<String, Baseline>,
+}
+
+/// Named baseline profiles, one per operating mode.
+///
+/// Only the active profile receives samples; the others keep their
+/// state (building or locked) untouched until selected again.
+pub struct BaselineProfiles {
+    config: BaselineConfig,
+    track_r: bool,
+    active: String,
+    profiles: BTreeMap<String, BaselineBuilder>,
+}
+
+impl BaselineProfiles {
+    /// Create a set holding only the [`DEFAULT_PROFILE`], active.
+    pub fn new(config: BaselineConfig, track_r: bool) -> Self {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(
+            DEFAULT_PROFILE.to_string(),
+            BaselineBuilder::new(config.clone(), track_r),
+        );
+        Self {
+            config,
+            track_r,
+            active: DEFAULT_PROFILE.to_string(),
+            profiles,
+        }
+    }
+
+    /// Name of the active profile.
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    /// Builder of the active profile.
+    pub fn active(&self) -> &BaselineBuilder {
+        &self.profiles[&self.active]
+    }
+
+    /// Mutable builder of the active profile.
+    pub fn active_mut(&mut self) -> &mut BaselineBuilder {
+        self.profiles
+            .get_mut(&self.active)
+            .expect("active profile always exists")
+    }
+
+    /// Select the active profile, creating it on first use.
+    /// Returns true if the active profile changed.
+    pub fn set_active(&mut self, name: &str) -> bool {
+        if self.active == name {
+            return false;
+        }
+        if !self.profiles.contains_key(name) {
+            self.profiles.insert(
+                name.to_string(),
+                BaselineBuilder::new(self.config.clone(), self.track_r),
+            );
+        }
+        self.active = name.to_string();
+        true
+    }
+
+    /// Baseline of a profile by name.
+    pub fn get(&self, name: &str) -> Option<&Baseline> {
+        self.profiles.get(name).map(|b| b.baseline())
+    }
+
+    /// Names of all known profiles, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    /// Restart building the active profile from scratch.
+    pub fn reset_active(&mut self) {
+        let builder = BaselineBuilder::new(self.config.clone(), self.track_r);
+        *self.active_mut() = builder;
+    }
+
+    /// Lock a profile immediately. Returns true if it was building,
+    /// false if it was already locked or does not exist.
+    pub fn lock(&mut self, name: &str) -> bool {
+        self.profiles
+            .get_mut(name)
+            .map(|b| b.force_lock())
+            .unwrap_or(false)
+    }
+
+    /// Export every profile.
+    pub fn export(&self) -> BaselineProfilesState {
+        BaselineProfilesState {
+            active: self.active.clone(),
+            profiles: self
+                .profiles
+                .iter()
+                .map(|(name, b)| (name.clone(), b.export()))
+                .collect(),
+        }
+    }
+
+    /// Replace all profiles with an exported state. An active profile
+    /// missing from the map is created empty.
+    pub fn import(&mut self, state: BaselineProfilesState) {
+        self.profiles.clear();
+        for (name, baseline) in state.profiles {
+            let mut builder = BaselineBuilder::new(self.config.clone(), self.track_r);
+            builder.import(baseline);
+            self.profiles.insert(name, builder);
+        }
+        self.active = state.active;
+        if !self.profiles.contains_key(&self.active) {
+            self.profiles.insert(
+                self.active.clone(),
+                BaselineBuilder::new(self.config.clone(), self.track_r),
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -302,6 +704,21 @@ mod tests {
         assert!(stats.is_valid());
     }
 
+    #[test]
+    fn test_field_stats_robust() {
+        let mut stats = FieldStats::new();
+        for value in [10.0, 11.0, 9.0, 10.5, 1000.0] {
+            stats.add_retained_sample(value);
+        }
+        assert!(stats.mean > 100.0);
+
+        stats.apply_robust();
+        assert_eq!(stats.mean, 10.5);
+        assert!((stats.std - 1.4826 * 0.5).abs() < 1e-9);
+        assert_eq!(stats.count, 5);
+        assert!(stats.samples.is_empty());
+    }
+
     #[test]
     fn test_baseline_new() {
         let baseline = Baseline::new(true);
@@ -428,4 +845,106 @@ mod tests {
             builder2.baseline().h_bytes.count
         );
     }
+
+    fn stats(pairs: &[(&str, f64)]) -> BTreeMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_summary_from_dataset_stats() {
+        let means = stats(&[("tc", 1.0), ("h_joint", 2.0), ("h_bytes", 3.0), ("r", 0.5)]);
+        let stds = stats(&[("tc", 0.1), ("h_joint", 0.2), ("h_bytes", 0.3), ("r", 0.0)]);
+        let summary = BaselineSummary::from_dataset_stats(&means, &stds).unwrap();
+        assert_eq!(
+            summary.h_bytes,
+            MetricSummary {
+                mean: 3.0,
+                std: 0.3
+            }
+        );
+        assert_eq!(summary.zero_std_metrics(), vec![MetricKind::R]);
+
+        let baseline =
+            Baseline::from_summary(&summary.clone().with_sample_count(50), true).unwrap();
+        assert!(baseline.is_ready());
+        assert_eq!(baseline.tc.count, 50);
+        assert!(baseline.tc.is_valid());
+        assert!(!baseline.r.unwrap().is_valid());
+        assert!(Baseline::from_summary(&summary, false).unwrap().r.is_none());
+
+        let mut no_r = summary;
+        no_r.r = None;
+        assert!(Baseline::from_summary(&no_r, true).is_err());
+    }
+
+    #[test]
+    fn test_summary_validation() {
+        let means = stats(&[("tc", 1.0), ("h_joint", 2.0), ("h_bytes", 3.0)]);
+        let stds = stats(&[("tc", 0.1), ("h_joint", 0.2), ("h_bytes", 0.3)]);
+        assert!(BaselineSummary::from_dataset_stats(&means, &stds).is_ok());
+
+        let mut missing = means.clone();
+        missing.remove("h_joint");
+        let mut missing_stds = stds.clone();
+        missing_stds.remove("h_joint");
+        let err = BaselineSummary::from_dataset_stats(&missing, &missing_stds).unwrap_err();
+        assert!(err.contains("h_joint"));
+        assert!(BaselineSummary::from_dataset_stats(&missing, &stds).is_err());
+
+        let mut negative = stds.clone();
+        negative.insert("tc".to_string(), -0.1);
+        assert!(BaselineSummary::from_dataset_stats(&means, &negative).is_err());
+
+        let mut unknown = means.clone();
+        unknown.insert("entropy".to_string(), 1.0);
+        assert!(BaselineSummary::from_dataset_stats(&unknown, &stds).is_err());
+
+        let mut nan = means;
+        nan.insert("tc".to_string(), f64::NAN);
+        assert!(BaselineSummary::from_dataset_stats(&nan, &stds).is_err());
+    }
+
+    #[test]
+    fn test_profiles_are_independent() {
+        let config = BaselineConfig {
+            build_time_ms: 0,
+            min_valid_snapshots: 2,
+            ..Default::default()
+        };
+
+        let mut profiles = BaselineProfiles::new(config, false);
+        assert_eq!(profiles.active_name(), DEFAULT_PROFILE);
+
+        assert!(profiles.set_active("idle"));
+        profiles
+            .active_mut()
+            .process(Some(1.0), Some(2.0), 3.0, None, 0);
+        profiles
+            .active_mut()
+            .process(Some(1.0), Some(2.0), 3.0, None, 100);
+        assert!(profiles.get("idle").unwrap().is_ready());
+
+        assert!(profiles.set_active("load"));
+        assert!(!profiles.set_active("load"));
+        profiles
+            .active_mut()
+            .process(Some(1.0), Some(2.0), 8.0, None, 200);
+        assert!(!profiles.get("load").unwrap().is_ready());
+        assert!(profiles.get("idle").unwrap().is_ready());
+        assert_eq!(profiles.get("idle").unwrap().h_bytes.count, 2);
+
+        assert!(profiles.lock("load"));
+        assert!(!profiles.lock("load"));
+        assert!(!profiles.lock("missing"));
+
+        let state = profiles.export();
+        assert_eq!(state.active, "load");
+        assert_eq!(state.profiles.len(), 3);
+
+        let mut restored = BaselineProfiles::new(BaselineConfig::default(), false);
+        restored.import(state);
+        assert_eq!(restored.active_name(), "load");
+        assert!(restored.get("load").unwrap().is_ready());
+        assert_eq!(restored.names(), vec!["default", "idle", "load"]);
+    }
 }
@@ -0,0 +1,1108 @@
+// ALEC Complexity - Standalone complexity monitoring
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Compact binary encoding of [`ComplexitySnapshot`] for constrained uplinks.
+//!
+//! A typical locked snapshot (stats, deltas, z-scores, 5 events, S-lite
+//! over 5 channels) fits in under 100 bytes, a small fraction of its JSON.
+//! The encoding is lossy:
+//!
+//! - Stats, deltas, z-scores and channel entropies are i16 fixed-point with
+//!   `precision` decimal places (default 2): the error is at most
+//!   `0.5 * 10^-precision`, and values outside the i16 range saturate
+//!   (±327.67 at precision 2).
+//! - Baseline progress and S-lite edge weights are quantized to u8 over
+//!   0.0 to 1.0: the error is at most `1/510`.
+//! - Events keep their type, severity, status and timestamp. Ids are
+//!   dropped, the message is replaced by the event type name and details
+//!   by [`EventDetails::None`].
+//! - Channel names are replaced by `ch0`, `ch1`, ... unless
+//!   [`BinaryOptions::channel_names`] is set. The S-lite change summary,
+//!   the criticality ranking and the per-channel z-scores are dropped.
+//!
+//! # Format (version 1)
+//!
+//! Integers are LEB128 varints, signed ones zigzag-encoded. Timestamps of
+//! events and S-lite are deltas from the snapshot timestamp.
+//!
+//! ```text
+//! version(1) precision(1) presence(1) timestamp sample_count progress(1)
+//! [profile] [stats] [deltas] [z_scores] flags [extra flags] events [s_lite]
+//! ```
+
+use crate::baseline::DEFAULT_PROFILE;
+use crate::delta::{Deltas, ZScores};
+use crate::event::{ComplexityEvent, EventDetails, EventSeverity, EventStatus, EventType};
+use crate::input::ChannelEntropy;
+use crate::snapshot::{BaselineStats, BaselineSummary, ComplexitySnapshot, SNAPSHOT_VERSION};
+use crate::structure::{SLite, SLiteEdge};
+use std::fmt;
+
+/// Version of the binary format.
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// Default number of decimal places of fixed-point fields.
+pub const DEFAULT_PRECISION: u8 = 2;
+
+/// Highest supported number of decimal places.
+pub const MAX_PRECISION: u8 = 4;
+
+// Presence bits
+const HAS_LOCKED: u8 = 0x01;
+const HAS_STATS: u8 = 0x02;
+const HAS_DELTAS: u8 = 0x04;
+const HAS_Z_SCORES: u8 = 0x08;
+const HAS_S_LITE: u8 = 0x10;
+const HAS_PROFILE: u8 = 0x20;
+const HAS_EXTRA_FLAGS: u8 = 0x40;
+
+// Known flags
+const FLAG_BASELINE_BUILDING: u8 = 0x01;
+const FLAG_BASELINE_LOCKED: u8 = 0x02;
+const FLAG_SMOOTHING_EMA: u8 = 0x04;
+const FLAG_STRUCTURE_BREAK_DETECTED: u8 = 0x08;
+const FLAG_STRUCTURE_BREAK_SKIPPED: u8 = 0x10;
+const FLAG_ANOMALY_DETECTION_ENABLED: u8 = 0x20;
+
+const SMOOTHING_PREFIX: &str = "SMOOTHING_EMA_";
+
+// S-lite bits
+const S_LITE_NAMES: u8 = 0x01;
+const S_LITE_PAIRWISE: u8 = 0x02;
+
+/// Options of the binary encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryOptions {
+    /// Decimal places of fixed-point fields (clamped to [`MAX_PRECISION`]).
+    pub precision: u8,
+    /// Include S-lite channel names.
+    pub channel_names: bool,
+}
+
+impl Default for BinaryOptions {
+    fn default() -> Self {
+        Self {
+            precision: DEFAULT_PRECISION,
+            channel_names: false,
+        }
+    }
+}
+
+/// Error decoding a binary snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotDecodeError {
+    /// The input ended while reading at `offset`.
+    Truncated { offset: usize },
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// Malformed content at `offset`.
+    Invalid { offset: usize, reason: &'static str },
+}
+
+impl fmt::Display for SnapshotDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotDecodeError::Truncated { offset } => {
+                write!(f, "snapshot truncated at byte {}", offset)
+            }
+            SnapshotDecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot format version {}", version)
+            }
+            SnapshotDecodeError::Invalid { offset, reason } => {
+                write!(f, "invalid snapshot at byte {}: {}", offset, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotDecodeError {}
+
+impl ComplexitySnapshot {
+    /// Encode to the compact binary format with default options.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with(&BinaryOptions::default())
+    }
+
+    /// Encode to the compact binary format.
+    pub fn to_bytes_with(&self, options: &BinaryOptions) -> Vec<u8> {
+        let precision = options.precision.min(MAX_PRECISION);
+        let mut w = Writer {
+            buf: Vec::with_capacity(96),
+            scale: scale(precision),
+        };
+
+        let profile = &self.baseline.profile;
+        let (known_flags, alpha, extra_flags) = split_flags(&self.flags);
+
+        let mut presence = 0;
+        if self.is_baseline_locked() {
+            presence |= HAS_LOCKED;
+        }
+        if self.baseline.stats.is_some() {
+            presence |= HAS_STATS;
+        }
+        if self.deltas.is_some() {
+            presence |= HAS_DELTAS;
+        }
+        if self.z_scores.is_some() {
+            presence |= HAS_Z_SCORES;
+        }
+        if self.s_lite.is_some() {
+            presence |= HAS_S_LITE;
+        }
+        if profile != DEFAULT_PROFILE {
+            presence |= HAS_PROFILE;
+        }
+        if !extra_flags.is_empty() {
+            presence |= HAS_EXTRA_FLAGS;
+        }
+
+        w.u8(BINARY_FORMAT_VERSION);
+        w.u8(precision);
+        w.u8(presence);
+        w.varint(self.timestamp_ms);
+        w.varint(self.baseline.sample_count as u64);
+        w.unit(self.baseline.progress);
+        if presence & HAS_PROFILE != 0 {
+            w.str(profile);
+        }
+
+        if let Some(stats) = &self.baseline.stats {
+            let optional = [
+                stats.tc_mean,
+                stats.tc_std,
+                stats.h_joint_mean,
+                stats.h_joint_std,
+                stats.r_mean,
+                stats.r_std,
+            ];
+            w.u8(mask(&optional));
+            w.fixed(stats.h_bytes_mean);
+            w.fixed(stats.h_bytes_std);
+            w.optionals(&optional);
+        }
+        if let Some(deltas) = &self.deltas {
+            w.metrics(deltas.h_bytes, &[deltas.tc, deltas.h_joint, deltas.r]);
+        }
+        if let Some(z) = &self.z_scores {
+            w.metrics(z.h_bytes, &[z.tc, z.h_joint, z.r]);
+        }
+
+        w.u8(known_flags);
+        if let Some(alpha) = alpha {
+            w.u8((alpha * 100.0).round().clamp(0.0, 255.0) as u8);
+        }
+        if presence & HAS_EXTRA_FLAGS != 0 {
+            w.varint(extra_flags.len() as u64);
+            for flag in extra_flags {
+                w.str(flag);
+            }
+        }
+
+        w.varint(self.events.len() as u64);
+        for event in &self.events {
+            w.u8(event_type_code(event.event_type) << 4
+                | severity_code(event.severity) << 2
+                | status_code(event.status));
+            w.delta(self.timestamp_ms, event.timestamp_ms);
+        }
+
+        if let Some(s_lite) = &self.s_lite {
+            w.s_lite(s_lite, self.timestamp_ms, options.channel_names);
+        }
+
+        w.buf
+    }
+
+    /// Decode from the compact binary format.
+    ///
+    /// Fields dropped by the encoding are filled as described in the
+    /// [module documentation](crate::binary).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotDecodeError> {
+        let mut r = Reader {
+            bytes,
+            pos: 0,
+            scale: 1.0,
+        };
+
+        let version = r.u8()?;
+        if version != BINARY_FORMAT_VERSION {
+            return Err(SnapshotDecodeError::UnsupportedVersion(version));
+        }
+        let precision = r.u8()?;
+        if precision > MAX_PRECISION {
+            return Err(r.invalid("precision out of range"));
+        }
+        r.scale = scale(precision);
+        let presence = r.u8()?;
+        if presence & 0x80 != 0 {
+            return Err(r.invalid("unknown presence bits"));
+        }
+
+        let timestamp_ms = r.varint()?;
+        let sample_count = r.varint()? as usize;
+        let progress = r.unit()?;
+        let profile = if presence & HAS_PROFILE != 0 {
+            r.str()?
+        } else {
+            DEFAULT_PROFILE.to_string()
+        };
+
+        let stats = if presence & HAS_STATS != 0 {
+            let mask = r.u8()?;
+            let h_bytes_mean = r.fixed()?;
+            let h_bytes_std = r.fixed()?;
+            let [tc_mean, tc_std, h_joint_mean, h_joint_std, r_mean, r_std] =
+                r.optionals::<6>(mask)?;
+            Some(BaselineStats {
+                tc_mean,
+                tc_std,
+                h_joint_mean,
+                h_joint_std,
+                h_bytes_mean,
+                h_bytes_std,
+                r_mean,
+                r_std,
+            })
+        } else {
+            None
+        };
+        let deltas = if presence & HAS_DELTAS != 0 {
+            let (h_bytes, [tc, h_joint, r]) = r.metrics()?;
+            Some(Deltas {
+                tc,
+                h_joint,
+                h_bytes,
+                r,
+            })
+        } else {
+            None
+        };
+        let z_scores = if presence & HAS_Z_SCORES != 0 {
+            let (h_bytes, [tc, h_joint, r]) = r.metrics()?;
+            Some(ZScores {
+                tc,
+                h_joint,
+                h_bytes,
+                r,
+            })
+        } else {
+            None
+        };
+
+        let known_flags = r.u8()?;
+        let mut flags = Vec::new();
+        for (bit, name) in KNOWN_FLAGS {
+            if known_flags & bit == 0 {
+                continue;
+            }
+            if bit == FLAG_SMOOTHING_EMA {
+                let alpha = r.u8()? as f64 / 100.0;
+                flags.push(format!("{}{:.2}", SMOOTHING_PREFIX, alpha));
+            } else {
+                flags.push(name.to_string());
+            }
+        }
+        if presence & HAS_EXTRA_FLAGS != 0 {
+            let count = r.count(1)?;
+            for _ in 0..count {
+                flags.push(r.str()?);
+            }
+        }
+
+        let event_count = r.count(2)?;
+        let mut events = Vec::with_capacity(event_count);
+        for _ in 0..event_count {
+            let packed = r.u8()?;
+            let event_type =
+                event_type_from_code(packed >> 4).ok_or_else(|| r.invalid("unknown event type"))?;
+            let severity = severity_from_code((packed >> 2) & 0x03)
+                .ok_or_else(|| r.invalid("unknown event severity"))?;
+            let status = status_from_code(packed & 0x03);
+            let event_timestamp = r.delta(timestamp_ms)?;
+            let mut event = ComplexityEvent::new(
+                event_type,
+                severity,
+                event_timestamp,
+                event_type.as_str(),
+                EventDetails::None,
+            );
+            event.status = status;
+            events.push(event);
+        }
+
+        let s_lite = if presence & HAS_S_LITE != 0 {
+            Some(r.s_lite(timestamp_ms)?)
+        } else {
+            None
+        };
+
+        if r.pos != bytes.len() {
+            return Err(r.invalid("trailing bytes"));
+        }
+
+        Ok(Self {
+            version: SNAPSHOT_VERSION.to_string(),
+            timestamp_ms,
+            baseline: BaselineSummary {
+                profile,
+                state: if presence & HAS_LOCKED != 0 {
+                    "locked"
+                } else {
+                    "building"
+                }
+                .to_string(),
+                sample_count,
+                progress,
+                stats,
+            },
+            deltas,
+            z_scores,
+            s_lite,
+            events,
+            flags,
+            criticality_ranking: None,
+            channels: None,
+        })
+    }
+}
+
+/// Known flags in bit order.
+const KNOWN_FLAGS: [(u8, &str); 6] = [
+    (FLAG_BASELINE_BUILDING, "BASELINE_BUILDING"),
+    (FLAG_BASELINE_LOCKED, "BASELINE_LOCKED"),
+    (FLAG_SMOOTHING_EMA, SMOOTHING_PREFIX),
+    (FLAG_STRUCTURE_BREAK_DETECTED, "STRUCTURE_BREAK_DETECTED"),
+    (FLAG_STRUCTURE_BREAK_SKIPPED, "STRUCTURE_BREAK_SKIPPED"),
+    (FLAG_ANOMALY_DETECTION_ENABLED, "ANOMALY_DETECTION_ENABLED"),
+];
+
+/// Split flags into the known-flag bitfield, the smoothing alpha and the
+/// flags the bitfield can't carry.
+fn split_flags(flags: &[String]) -> (u8, Option<f64>, Vec<&str>) {
+    let mut bits = 0;
+    let mut alpha = None;
+    let mut extra = Vec::new();
+
+    for flag in flags {
+        if let Some(value) = flag.strip_prefix(SMOOTHING_PREFIX) {
+            match value.parse::<f64>() {
+                Ok(parsed)
+                    if alpha.is_none()
+                        && (0.0..=2.55).contains(&parsed)
+                        && format!("{:.2}", parsed) == value =>
+                {
+                    bits |= FLAG_SMOOTHING_EMA;
+                    alpha = Some(parsed);
+                }
+                _ => extra.push(flag.as_str()),
+            }
+            continue;
+        }
+        match KNOWN_FLAGS
+            .iter()
+            .find(|(bit, name)| *bit != FLAG_SMOOTHING_EMA && *name == flag.as_str())
+        {
+            Some((bit, _)) if bits & *bit == 0 => bits |= *bit,
+            _ => extra.push(flag.as_str()),
+        }
+    }
+
+    (bits, alpha, extra)
+}
+
+fn scale(precision: u8) -> f64 {
+    10f64.powi(precision as i32)
+}
+
+fn mask(values: &[Option<f64>]) -> u8 {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_some())
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+fn event_type_code(event_type: EventType) -> u8 {
+    match event_type {
+        EventType::BaselineBuilding => 0,
+        EventType::BaselineLocked => 1,
+        EventType::PayloadEntropySpike => 2,
+        EventType::StructureBreak => 3,
+        EventType::RedundancyDrop => 4,
+        EventType::ComplexitySurge => 5,
+        EventType::CriticalityShift => 6,
+        EventType::ChannelEntropyAnomaly => 7,
+    }
+}
+
+fn event_type_from_code(code: u8) -> Option<EventType> {
+    Some(match code {
+        0 => EventType::BaselineBuilding,
+        1 => EventType::BaselineLocked,
+        2 => EventType::PayloadEntropySpike,
+        3 => EventType::StructureBreak,
+        4 => EventType::RedundancyDrop,
+        5 => EventType::ComplexitySurge,
+        6 => EventType::CriticalityShift,
+        7 => EventType::ChannelEntropyAnomaly,
+        _ => return None,
+    })
+}
+
+fn severity_code(severity: EventSeverity) -> u8 {
+    match severity {
+        EventSeverity::Info => 0,
+        EventSeverity::Warning => 1,
+        EventSeverity::Critical => 2,
+    }
+}
+
+fn severity_from_code(code: u8) -> Option<EventSeverity> {
+    Some(match code {
+        0 => EventSeverity::Info,
+        1 => EventSeverity::Warning,
+        2 => EventSeverity::Critical,
+        _ => return None,
+    })
+}
+
+fn status_code(status: EventStatus) -> u8 {
+    match status {
+        EventStatus::Final => 0,
+        EventStatus::Provisional => 1,
+        EventStatus::Confirmed => 2,
+        EventStatus::Retracted => 3,
+    }
+}
+
+fn status_from_code(code: u8) -> EventStatus {
+    match code {
+        0 => EventStatus::Final,
+        1 => EventStatus::Provisional,
+        2 => EventStatus::Confirmed,
+        _ => EventStatus::Retracted,
+    }
+}
+
+/// Channel ids referenced by an S-lite: entropy channels first, then any
+/// edge endpoint without an entropy.
+fn channel_table(s_lite: &SLite) -> Vec<&str> {
+    let mut table: Vec<&str> = s_lite
+        .channels
+        .iter()
+        .map(|c| c.channel_id.as_str())
+        .collect();
+    for edge in &s_lite.edges {
+        for id in [edge.channel_a.as_str(), edge.channel_b.as_str()] {
+            if !table.contains(&id) {
+                table.push(id);
+            }
+        }
+    }
+    table
+}
+
+/// Whether `edges` is the full upper triangle over `n` channels in
+/// row-major order, as produced by the S-lite extractor.
+fn is_pairwise(edges: &[SLiteEdge], table: &[&str]) -> bool {
+    let n = table.len();
+    if edges.len() != n * n.saturating_sub(1) / 2 {
+        return false;
+    }
+    let mut edges = edges.iter();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            match edges.next() {
+                Some(e) if e.channel_a == table[i] && e.channel_b == table[j] => {}
+                _ => return false,
+            }
+        }
+    }
+    true
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+struct Writer {
+    buf: Vec<u8>,
+    scale: f64,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    /// Signed difference `value - base`.
+    fn delta(&mut self, base: u64, value: u64) {
+        self.varint(zigzag(value.wrapping_sub(base) as i64));
+    }
+
+    fn str(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    /// Value in 0.0..=1.0 as a u8.
+    fn unit(&mut self, value: f64) {
+        let value = if value.is_nan() { 0.0 } else { value };
+        self.u8((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+    }
+
+    /// Saturating i16 fixed-point.
+    fn fixed(&mut self, value: f64) {
+        let value = if value.is_nan() { 0.0 } else { value };
+        let scaled = (value * self.scale)
+            .round()
+            .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+        self.buf.extend_from_slice(&scaled.to_le_bytes());
+    }
+
+    fn optionals(&mut self, values: &[Option<f64>]) {
+        for value in values.iter().flatten() {
+            self.fixed(*value);
+        }
+    }
+
+    /// Mask, h_bytes, then the optional metrics.
+    fn metrics(&mut self, h_bytes: f64, optional: &[Option<f64>]) {
+        self.u8(mask(optional));
+        self.fixed(h_bytes);
+        self.optionals(optional);
+    }
+
+    fn s_lite(&mut self, s_lite: &SLite, timestamp_ms: u64, names: bool) {
+        let table = channel_table(s_lite);
+        let pairwise = is_pairwise(&s_lite.edges, &table);
+
+        let mut bits = 0;
+        if names {
+            bits |= S_LITE_NAMES;
+        }
+        if pairwise {
+            bits |= S_LITE_PAIRWISE;
+        }
+        self.u8(bits);
+        self.delta(timestamp_ms, s_lite.timestamp_ms);
+        self.varint(s_lite.channel_count as u64);
+        self.varint(table.len() as u64);
+        self.varint(s_lite.channels.len() as u64);
+        if names {
+            for id in &table {
+                self.str(id);
+            }
+        }
+        for channel in &s_lite.channels {
+            self.fixed(channel.h);
+        }
+
+        if !pairwise {
+            self.varint(s_lite.edges.len() as u64);
+        }
+        let index = |id: &str| table.iter().position(|t| *t == id).unwrap_or(0);
+        for edge in &s_lite.edges {
+            if !pairwise {
+                let (a, b) = (
+                    index(edge.channel_a.as_str()),
+                    index(edge.channel_b.as_str()),
+                );
+                if table.len() <= 16 {
+                    self.u8((a << 4 | b) as u8);
+                } else {
+                    self.varint(a as u64);
+                    self.varint(b as u64);
+                }
+            }
+            self.unit(edge.weight);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    scale: f64,
+}
+
+impl<'a> Reader<'a> {
+    fn invalid(&self, reason: &'static str) -> SnapshotDecodeError {
+        SnapshotDecodeError::Invalid {
+            offset: self.pos,
+            reason,
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotDecodeError> {
+        if self.bytes.len() - self.pos < n {
+            return Err(SnapshotDecodeError::Truncated {
+                offset: self.bytes.len(),
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotDecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, SnapshotDecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.invalid("varint too long"))
+    }
+
+    /// Element count, bounded by the remaining input at `min_size` bytes
+    /// per element so a corrupted count can't trigger a huge allocation.
+    fn count(&mut self, min_size: usize) -> Result<usize, SnapshotDecodeError> {
+        let count = self.varint()?;
+        let remaining = (self.bytes.len() - self.pos) as u64;
+        if count.saturating_mul(min_size as u64) > remaining {
+            return Err(SnapshotDecodeError::Truncated {
+                offset: self.bytes.len(),
+            });
+        }
+        Ok(count as usize)
+    }
+
+    fn delta(&mut self, base: u64) -> Result<u64, SnapshotDecodeError> {
+        Ok(base.wrapping_add(unzigzag(self.varint()?) as u64))
+    }
+
+    fn str(&mut self) -> Result<String, SnapshotDecodeError> {
+        let len = self.count(1)?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.invalid("invalid UTF-8"))
+    }
+
+    fn unit(&mut self) -> Result<f64, SnapshotDecodeError> {
+        Ok(self.u8()? as f64 / 255.0)
+    }
+
+    fn fixed(&mut self) -> Result<f64, SnapshotDecodeError> {
+        let bytes = self.take(2)?;
+        Ok(i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / self.scale)
+    }
+
+    fn optionals<const N: usize>(
+        &mut self,
+        mask: u8,
+    ) -> Result<[Option<f64>; N], SnapshotDecodeError> {
+        let mut values = [None; N];
+        for (i, value) in values.iter_mut().enumerate() {
+            if mask & (1 << i) != 0 {
+                *value = Some(self.fixed()?);
+            }
+        }
+        Ok(values)
+    }
+
+    fn metrics(&mut self) -> Result<(f64, [Option<f64>; 3]), SnapshotDecodeError> {
+        let mask = self.u8()?;
+        let h_bytes = self.fixed()?;
+        Ok((h_bytes, self.optionals::<3>(mask)?))
+    }
+
+    fn s_lite(&mut self, timestamp_ms: u64) -> Result<SLite, SnapshotDecodeError> {
+        let bits = self.u8()?;
+        if bits & !(S_LITE_NAMES | S_LITE_PAIRWISE) != 0 {
+            return Err(self.invalid("unknown S-lite bits"));
+        }
+        let s_lite_timestamp = self.delta(timestamp_ms)?;
+        let channel_count = self.varint()? as usize;
+        // Every channel is an entropy or an edge endpoint: at least a byte
+        let table_len = self.count(1)?;
+        let entropy_count = self.count(2)?;
+        if entropy_count > table_len {
+            return Err(self.invalid("more entropies than channels"));
+        }
+
+        let table: Vec<String> = if bits & S_LITE_NAMES != 0 {
+            let mut table = Vec::with_capacity(table_len);
+            for _ in 0..table_len {
+                table.push(self.str()?);
+            }
+            table
+        } else {
+            (0..table_len).map(|i| format!("ch{}", i)).collect()
+        };
+
+        let mut channels = Vec::with_capacity(entropy_count);
+        for id in &table[..entropy_count] {
+            channels.push(ChannelEntropy {
+                channel_id: id.clone(),
+                h: self.fixed()?,
+            });
+        }
+
+        let mut edges = Vec::new();
+        if bits & S_LITE_PAIRWISE != 0 {
+            let n = table_len;
+            if n.saturating_mul(n.saturating_sub(1)) / 2 > self.bytes.len() - self.pos {
+                return Err(SnapshotDecodeError::Truncated {
+                    offset: self.bytes.len(),
+                });
+            }
+            for (a, b) in (0..n).flat_map(|i| ((i + 1)..n).map(move |j| (i, j))) {
+                edges.push(SLiteEdge {
+                    channel_a: table[a].clone(),
+                    channel_b: table[b].clone(),
+                    weight: self.unit()?,
+                });
+            }
+        } else {
+            // Each edge is its endpoints followed by its weight
+            let count = self.count(2)?;
+            edges.reserve(count);
+            for _ in 0..count {
+                let (a, b) = if table_len <= 16 {
+                    let packed = self.u8()?;
+                    ((packed >> 4) as usize, (packed & 0x0F) as usize)
+                } else {
+                    (self.varint()? as usize, self.varint()? as usize)
+                };
+                if a >= table_len || b >= table_len {
+                    return Err(self.invalid("edge channel out of range"));
+                }
+                edges.push(SLiteEdge {
+                    channel_a: table[a].clone(),
+                    channel_b: table[b].clone(),
+                    weight: self.unit()?,
+                });
+            }
+        }
+
+        Ok(SLite {
+            edges,
+            channel_count,
+            timestamp_ms: s_lite_timestamp,
+            channels,
+            change: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TS: u64 = 1_741_234_567_000;
+
+    fn channels(n: usize) -> Vec<ChannelEntropy> {
+        (0..n)
+            .map(|i| ChannelEntropy {
+                channel_id: format!("sensor_{}", i),
+                h: 1.0 + i as f64 * 0.731,
+            })
+            .collect()
+    }
+
+    fn pairwise_edges(channels: &[ChannelEntropy]) -> Vec<SLiteEdge> {
+        let mut edges = Vec::new();
+        for i in 0..channels.len() {
+            for j in (i + 1)..channels.len() {
+                edges.push(SLiteEdge {
+                    channel_a: channels[i].channel_id.clone(),
+                    channel_b: channels[j].channel_id.clone(),
+                    weight: 1.0 / (1.0 + (i * 7 + j) as f64 * 0.137),
+                });
+            }
+        }
+        edges
+    }
+
+    /// Locked snapshot with stats, deltas, z-scores, 5 events and an
+    /// S-lite of 5 channels (10 edges).
+    fn typical_snapshot() -> ComplexitySnapshot {
+        let channels = channels(5);
+        let edges = pairwise_edges(&channels);
+        let mut events = vec![
+            ComplexityEvent::new(
+                EventType::PayloadEntropySpike,
+                EventSeverity::Warning,
+                TS,
+                "spike",
+                EventDetails::ZScore {
+                    value: 2.5,
+                    threshold: 2.0,
+                },
+            ),
+            ComplexityEvent::new(
+                EventType::ComplexitySurge,
+                EventSeverity::Critical,
+                TS,
+                "surge",
+                EventDetails::None,
+            ),
+            ComplexityEvent::new(
+                EventType::RedundancyDrop,
+                EventSeverity::Warning,
+                TS - 5_000,
+                "drop",
+                EventDetails::None,
+            ),
+            ComplexityEvent::new(
+                EventType::StructureBreak,
+                EventSeverity::Warning,
+                TS,
+                "break",
+                EventDetails::None,
+            ),
+            ComplexityEvent::new(
+                EventType::CriticalityShift,
+                EventSeverity::Info,
+                TS + 250,
+                "shift",
+                EventDetails::None,
+            ),
+        ];
+        events[0].status = EventStatus::Provisional;
+        events[2].status = EventStatus::Retracted;
+
+        ComplexitySnapshot {
+            version: SNAPSHOT_VERSION.to_string(),
+            timestamp_ms: TS,
+            baseline: BaselineSummary {
+                profile: DEFAULT_PROFILE.to_string(),
+                state: "locked".to_string(),
+                sample_count: 240,
+                progress: 1.0,
+                stats: Some(BaselineStats {
+                    tc_mean: Some(1.234),
+                    tc_std: Some(0.0567),
+                    h_joint_mean: Some(4.321),
+                    h_joint_std: Some(0.2109),
+                    h_bytes_mean: 5.4321,
+                    h_bytes_std: 0.3141,
+                    r_mean: Some(0.4567),
+                    r_std: Some(0.0891),
+                }),
+            },
+            deltas: Some(Deltas {
+                tc: Some(0.123),
+                h_joint: Some(-0.456),
+                h_bytes: 1.789,
+                r: Some(-0.0314),
+            }),
+            z_scores: Some(ZScores {
+                tc: Some(2.171),
+                h_joint: Some(-2.162),
+                h_bytes: 5.696,
+                r: Some(-0.352),
+            }),
+            s_lite: Some(SLite {
+                edges,
+                channel_count: 5,
+                timestamp_ms: TS,
+                channels,
+                change: None,
+            }),
+            events,
+            flags: vec![
+                "BASELINE_LOCKED".to_string(),
+                "SMOOTHING_EMA_0.30".to_string(),
+                "ANOMALY_DETECTION_ENABLED".to_string(),
+            ],
+            criticality_ranking: None,
+            channels: None,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64, tolerance: f64) {
+        assert!((a - b).abs() <= tolerance, "{} vs {}", a, b);
+    }
+
+    fn assert_close_opt(a: Option<f64>, b: Option<f64>, tolerance: f64) {
+        assert_eq!(a.is_some(), b.is_some());
+        if let (Some(a), Some(b)) = (a, b) {
+            assert_close(a, b, tolerance);
+        }
+    }
+
+    #[test]
+    fn test_lossy_roundtrip_within_quantization_error() {
+        let snapshot = typical_snapshot();
+        let decoded = ComplexitySnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+
+        let fixed = 0.5 / scale(DEFAULT_PRECISION) + 1e-9;
+        let unit = 1.0 / 510.0 + 1e-9;
+
+        assert_eq!(decoded.timestamp_ms, snapshot.timestamp_ms);
+        assert!(decoded.is_baseline_locked());
+        assert_eq!(decoded.baseline.profile, snapshot.baseline.profile);
+        assert_eq!(
+            decoded.baseline.sample_count,
+            snapshot.baseline.sample_count
+        );
+        assert_close(decoded.baseline.progress, snapshot.baseline.progress, unit);
+
+        let (a, b) = (
+            decoded.baseline.stats.unwrap(),
+            snapshot.baseline.stats.unwrap(),
+        );
+        assert_close_opt(a.tc_mean, b.tc_mean, fixed);
+        assert_close_opt(a.tc_std, b.tc_std, fixed);
+        assert_close_opt(a.h_joint_mean, b.h_joint_mean, fixed);
+        assert_close_opt(a.h_joint_std, b.h_joint_std, fixed);
+        assert_close(a.h_bytes_mean, b.h_bytes_mean, fixed);
+        assert_close(a.h_bytes_std, b.h_bytes_std, fixed);
+        assert_close_opt(a.r_mean, b.r_mean, fixed);
+        assert_close_opt(a.r_std, b.r_std, fixed);
+
+        let (a, b) = (decoded.deltas.unwrap(), snapshot.deltas.unwrap());
+        assert_close_opt(a.tc, b.tc, fixed);
+        assert_close_opt(a.h_joint, b.h_joint, fixed);
+        assert_close(a.h_bytes, b.h_bytes, fixed);
+        assert_close_opt(a.r, b.r, fixed);
+
+        let (a, b) = (decoded.z_scores.unwrap(), snapshot.z_scores.unwrap());
+        assert_close_opt(a.tc, b.tc, fixed);
+        assert_close_opt(a.h_joint, b.h_joint, fixed);
+        assert_close(a.h_bytes, b.h_bytes, fixed);
+        assert_close_opt(a.r, b.r, fixed);
+
+        assert_eq!(decoded.flags, snapshot.flags);
+
+        assert_eq!(decoded.events.len(), snapshot.events.len());
+        for (a, b) in decoded.events.iter().zip(&snapshot.events) {
+            assert_eq!(a.event_type, b.event_type);
+            assert_eq!(a.severity, b.severity);
+            assert_eq!(a.status, b.status);
+            assert_eq!(a.timestamp_ms, b.timestamp_ms);
+            assert_eq!(a.message, b.event_type.as_str());
+        }
+
+        let (a, b) = (decoded.s_lite.unwrap(), snapshot.s_lite.unwrap());
+        assert_eq!(a.timestamp_ms, b.timestamp_ms);
+        assert_eq!(a.channel_count, b.channel_count);
+        assert_eq!(a.channels.len(), b.channels.len());
+        for (a, b) in a.channels.iter().zip(&b.channels) {
+            assert_close(a.h, b.h, fixed);
+        }
+        assert_eq!(a.edges.len(), b.edges.len());
+        for (a, b) in a.edges.iter().zip(&b.edges) {
+            assert_close(a.weight, b.weight, unit);
+        }
+        assert_eq!(a.edges[0].channel_a, "ch0");
+        assert_eq!(a.edges[0].channel_b, "ch1");
+    }
+
+    #[test]
+    fn test_typical_snapshot_under_100_bytes() {
+        let snapshot = typical_snapshot();
+        assert_eq!(snapshot.events.len(), 5);
+        assert_eq!(snapshot.s_lite.as_ref().unwrap().edges.len(), 10);
+
+        let bytes = snapshot.to_bytes();
+        assert!(bytes.len() < 100, "{} bytes", bytes.len());
+        assert!(bytes.len() * 5 < snapshot.to_json().unwrap().len());
+    }
+
+    #[test]
+    fn test_channel_names_and_sparse_edges() {
+        let mut snapshot = typical_snapshot();
+        let s_lite = snapshot.s_lite.as_mut().unwrap();
+        s_lite.edges.retain(|e| e.weight > 0.5);
+        s_lite.edges.push(SLiteEdge {
+            channel_a: "sensor_4".to_string(),
+            channel_b: "external".to_string(),
+            weight: 0.25,
+        });
+
+        let options = BinaryOptions {
+            precision: 3,
+            channel_names: true,
+        };
+        let decoded = ComplexitySnapshot::from_bytes(&snapshot.to_bytes_with(&options)).unwrap();
+        let (a, b) = (decoded.s_lite.unwrap(), snapshot.s_lite.unwrap());
+        assert_eq!(a.channels.len(), 5);
+        assert_eq!(a.edges.len(), b.edges.len());
+        for (a, b) in a.edges.iter().zip(&b.edges) {
+            assert_eq!(a.channel_a, b.channel_a);
+            assert_eq!(a.channel_b, b.channel_b);
+        }
+        for (a, b) in a.channels.iter().zip(&b.channels) {
+            assert_eq!(a.channel_id, b.channel_id);
+            assert_close(a.h, b.h, 0.0005 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_building_snapshot_and_unknown_flags() {
+        let baseline = crate::baseline::Baseline::new(false);
+        let mut snapshot =
+            ComplexitySnapshot::building(TS, &baseline, Vec::new()).with_profile("night");
+        snapshot.flags.push("CUSTOM_FLAG".to_string());
+
+        let decoded = ComplexitySnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert!(!decoded.is_baseline_locked());
+        assert_eq!(decoded.baseline.profile, "night");
+        assert_eq!(decoded.flags, snapshot.flags);
+        assert!(decoded.deltas.is_none());
+        assert!(decoded.s_lite.is_none());
+    }
+
+    #[test]
+    fn test_values_saturate() {
+        let mut snapshot = typical_snapshot();
+        snapshot.z_scores.as_mut().unwrap().h_bytes = 1.0e6;
+        let decoded = ComplexitySnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert_close(decoded.z_scores.unwrap().h_bytes, 327.67, 1e-9);
+    }
+
+    #[test]
+    fn test_truncated_input_is_rejected() {
+        let bytes = typical_snapshot().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    ComplexitySnapshot::from_bytes(&bytes[..len]),
+                    Err(SnapshotDecodeError::Truncated { .. })
+                ),
+                "prefix of {} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_and_trailing_bytes() {
+        let mut bytes = typical_snapshot().to_bytes();
+        bytes.push(0);
+        assert!(matches!(
+            ComplexitySnapshot::from_bytes(&bytes),
+            Err(SnapshotDecodeError::Invalid { .. })
+        ));
+
+        bytes[0] = 9;
+        assert_eq!(
+            ComplexitySnapshot::from_bytes(&bytes).unwrap_err(),
+            SnapshotDecodeError::UnsupportedVersion(9)
+        );
+    }
+}
@@ -28,6 +28,22 @@ pub struct ComplexityConfig {
 
     /// Output settings.
     pub output: OutputConfig,
+
+    /// Retrospective confirmation of z-score events (off by default).
+    #[serde(default)]
+    pub confirmation: ConfirmationConfig,
+
+    /// Criticality shift detection thresholds.
+    #[serde(default)]
+    pub criticality: CriticalityConfig,
+
+    /// Snapshot retention for history and trends (default: none).
+    #[serde(default)]
+    pub retention: Option<RetentionConfig>,
+
+    /// Per-channel entropy baselines and anomalies (off by default).
+    #[serde(default)]
+    pub channels: ChannelConfig,
 }
 
 /// Baseline learning configuration.
@@ -44,6 +60,12 @@ pub struct BaselineConfig {
 
     /// Rolling window size (if update_mode is Rolling).
     pub rolling_window_snapshots: u32,
+
+    /// Lock the baseline on median and MAD instead of mean and standard
+    /// deviation, limiting the influence of outliers in the build window
+    /// (default: false).
+    #[serde(default)]
+    pub robust: bool,
 }
 
 impl Default for BaselineConfig {
@@ -53,6 +75,7 @@ impl Default for BaselineConfig {
             min_valid_snapshots: 20,
             update_mode: BaselineUpdateMode::Frozen,
             rolling_window_snapshots: 100,
+            robust: false,
         }
     }
 }
@@ -137,6 +160,15 @@ pub struct StructureConfig {
 
     /// Minimum edge weight change to consider a break.
     pub break_threshold: f64,
+
+    /// Minimum channels shared by two consecutive snapshots for break
+    /// detection to run (below this, detection is skipped and flagged).
+    #[serde(default = "default_min_common_channels")]
+    pub min_common_channels: usize,
+}
+
+fn default_min_common_channels() -> usize {
+    2
 }
 
 impl Default for StructureConfig {
@@ -148,6 +180,7 @@ impl Default for StructureConfig {
             sparsify: SparsifyConfig::default(),
             detect_breaks: true,
             break_threshold: 0.3,
+            min_common_channels: default_min_common_channels(),
         }
     }
 }
@@ -217,6 +250,13 @@ pub struct EventTypeConfig {
     pub redundancy_drop: bool,
     pub complexity_surge: bool,
     pub criticality_shift: bool,
+    /// Only raised when [`ChannelConfig::enabled`] is set.
+    #[serde(default = "default_true")]
+    pub channel_entropy_anomaly: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for EventTypeConfig {
@@ -228,6 +268,140 @@ impl Default for EventTypeConfig {
             redundancy_drop: true,
             complexity_surge: true,
             criticality_shift: true,
+            channel_entropy_anomaly: true,
+        }
+    }
+}
+
+/// Per-channel entropy baselines.
+///
+/// When enabled, the baseline also learns the mean and std of each
+/// channel's entropy, for at most [`StructureConfig::max_channels`]
+/// channels: channels first seen once the limit is reached are ignored
+/// and the snapshot is flagged `CHANNEL_LIMIT_REACHED`. Locked snapshots
+/// then carry a z-score per tracked channel, and a channel whose |z|
+/// reaches `z_threshold_warn` raises a `ChannelEntropyAnomaly`, subject to
+/// the anomaly persistence and cooldown kept per channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelConfig {
+    /// Track per-channel statistics (default: false).
+    pub enabled: bool,
+
+    /// |z| threshold for warnings.
+    pub z_threshold_warn: f64,
+
+    /// |z| threshold for critical alerts.
+    pub z_threshold_crit: f64,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            z_threshold_warn: 3.0,
+            z_threshold_crit: 4.0,
+        }
+    }
+}
+
+/// Retrospective confirmation of z-score events.
+///
+/// When enabled, payload entropy spikes, complexity surges and redundancy
+/// drops are emitted as provisional. After `review_after_snapshots` further
+/// snapshots, the engine checks how often the triggering condition held over
+/// the last `window_snapshots` snapshots and emits a confirmed or retracted
+/// follow-up referencing the original event id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfirmationConfig {
+    /// Enable retrospective confirmation (default: false).
+    pub enabled: bool,
+
+    /// Snapshots kept in the review window (K).
+    pub window_snapshots: usize,
+
+    /// Snapshots to wait after an event before reviewing it (R).
+    pub review_after_snapshots: usize,
+
+    /// Fraction of the window (0.0-1.0) in which the condition must hold
+    /// for the event to be confirmed.
+    pub min_held_fraction: f64,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_snapshots: 10,
+            review_after_snapshots: 5,
+            min_held_fraction: 0.5,
+        }
+    }
+}
+
+/// Criticality shift detection.
+///
+/// Channels are ranked by the input's `criticality_ranking`, or by channel
+/// entropy when the input has none. The top `top_k` channels are compared
+/// with the ranking of the last criticality shift event (initially the
+/// first ranking seen), and a shift is raised when either threshold is
+/// exceeded. Shifts are subject to the anomaly persistence and cooldown.
+/// The defaults raise a shift on any change of the top K.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CriticalityConfig {
+    /// Number of top-ranked channels compared.
+    pub top_k: usize,
+
+    /// Channels that may enter the top K without a shift.
+    pub max_set_difference: usize,
+
+    /// Normalized Kendall tau distance (0.0-1.0, the fraction of
+    /// discordant pairs) tolerated between the orderings of the channels
+    /// in both top Ks.
+    pub max_kendall_distance: f64,
+}
+
+impl Default for CriticalityConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 3,
+            max_set_difference: 0,
+            max_kendall_distance: 0.0,
+        }
+    }
+}
+
+/// Snapshot retention in the engine.
+///
+/// Retained snapshots back [`ComplexityEngine::history`],
+/// [`ComplexityEngine::trend`] and [`ComplexityEngine::events_since`].
+/// Memory is bounded by `max_snapshots` whatever the age limit.
+///
+/// [`ComplexityEngine::history`]: crate::ComplexityEngine::history
+/// [`ComplexityEngine::trend`]: crate::ComplexityEngine::trend
+/// [`ComplexityEngine::events_since`]: crate::ComplexityEngine::events_since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Maximum snapshots kept; the oldest is dropped first.
+    pub max_snapshots: usize,
+
+    /// Drop snapshots older than this (ms) before the latest one.
+    pub max_age_ms: Option<u64>,
+
+    /// Keep retained copies without their S-lite, the largest part of a
+    /// snapshot.
+    pub drop_s_lite: bool,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_snapshots: 360,
+            max_age_ms: None,
+            drop_s_lite: false,
         }
     }
 }
@@ -265,6 +439,7 @@ mod tests {
         assert!(!config.enabled);
         assert_eq!(config.baseline.build_time_ms, 300_000);
         assert_eq!(config.baseline.min_valid_snapshots, 20);
+        assert!(!config.confirmation.enabled);
     }
 
     #[test]
@@ -276,6 +451,15 @@ mod tests {
         assert_eq!(config.baseline.build_time_ms, parsed.baseline.build_time_ms);
     }
 
+    #[test]
+    fn test_confirmation_defaults_when_missing() {
+        let mut value = serde_json::to_value(ComplexityConfig::default()).unwrap();
+        value.as_object_mut().unwrap().remove("confirmation");
+        let parsed: ComplexityConfig = serde_json::from_value(value).unwrap();
+        assert!(!parsed.confirmation.enabled);
+        assert_eq!(parsed.confirmation.window_snapshots, 10);
+    }
+
     #[test]
     fn test_baseline_update_modes() {
         let frozen = BaselineUpdateMode::Frozen;
@@ -0,0 +1,194 @@
+// ALEC Complexity - Standalone complexity monitoring
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Retrospective confirmation of z-score events.
+//!
+//! Z-score events are emitted as soon as their condition persists, which
+//! makes a single noisy snapshot enough to raise an alert. The
+//! [`EventConfirmer`] keeps the conditions of the last K snapshots and,
+//! R snapshots after an event, follows it up with a confirmation or a
+//! retraction depending on how much of the window the condition held for.
+
+use crate::config::ConfirmationConfig;
+use crate::event::{ComplexityEvent, EventStatus, EventType};
+use std::collections::VecDeque;
+
+/// Event types subject to review: those driven by a z-score condition.
+fn is_reviewable(event_type: EventType) -> bool {
+    matches!(
+        event_type,
+        EventType::PayloadEntropySpike | EventType::ComplexitySurge | EventType::RedundancyDrop
+    )
+}
+
+/// Provisional event awaiting review.
+#[derive(Debug, Clone)]
+struct PendingReview {
+    event: ComplexityEvent,
+    remaining: usize,
+}
+
+/// Rolling window of held conditions and pending reviews.
+pub struct EventConfirmer {
+    config: ConfirmationConfig,
+    /// Conditions held at each of the last K snapshots, oldest first.
+    window: VecDeque<Vec<EventType>>,
+    /// Provisional events in emission order.
+    pending: Vec<PendingReview>,
+}
+
+impl EventConfirmer {
+    pub fn new(config: ConfirmationConfig) -> Self {
+        Self {
+            config,
+            window: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Whether retrospective confirmation is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Number of provisional events awaiting review.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Record one snapshot and review due events.
+    ///
+    /// `held` lists the conditions holding at this snapshot. Reviewable
+    /// entries of `events` (with ids already assigned) are marked
+    /// provisional and queued; the returned follow-ups need ids.
+    pub fn observe(
+        &mut self,
+        held: Vec<EventType>,
+        events: &mut [ComplexityEvent],
+        timestamp_ms: u64,
+    ) -> Vec<ComplexityEvent> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        self.window.push_back(held);
+        while self.window.len() > self.config.window_snapshots.max(1) {
+            self.window.pop_front();
+        }
+
+        // Review events queued by earlier snapshots; this snapshot
+        // already counts towards their window
+        let mut follow_ups = Vec::new();
+        let window = &self.window;
+        let min_held = self.config.min_held_fraction;
+        self.pending.retain_mut(|review| {
+            review.remaining = review.remaining.saturating_sub(1);
+            if review.remaining > 0 {
+                return true;
+            }
+            let held_fraction = held_fraction(window, review.event.event_type);
+            follow_ups.push(ComplexityEvent::review(
+                &review.event,
+                timestamp_ms,
+                held_fraction >= min_held,
+                held_fraction,
+            ));
+            false
+        });
+
+        for event in events
+            .iter_mut()
+            .filter(|e| is_reviewable(e.event_type) && e.status == EventStatus::Final)
+        {
+            event.status = EventStatus::Provisional;
+            self.pending.push(PendingReview {
+                event: event.clone(),
+                remaining: self.config.review_after_snapshots.max(1),
+            });
+        }
+
+        follow_ups
+    }
+
+    /// Drop the window and all pending reviews.
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.pending.clear();
+    }
+}
+
+/// Fraction of window snapshots in which `event_type`'s condition held.
+fn held_fraction(window: &VecDeque<Vec<EventType>>, event_type: EventType) -> f64 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    let held = window.iter().filter(|h| h.contains(&event_type)).count();
+    held as f64 / window.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventSeverity;
+
+    fn config() -> ConfirmationConfig {
+        ConfirmationConfig {
+            enabled: true,
+            window_snapshots: 4,
+            review_after_snapshots: 3,
+            min_held_fraction: 0.5,
+        }
+    }
+
+    fn spike(id: u64) -> ComplexityEvent {
+        let mut event = ComplexityEvent::payload_entropy_spike(0, EventSeverity::Warning, 2.5, 2.0);
+        event.id = id;
+        event
+    }
+
+    #[test]
+    fn test_disabled_leaves_events_final() {
+        let mut confirmer = EventConfirmer::new(ConfirmationConfig::default());
+        let mut events = vec![spike(1)];
+        let follow_ups = confirmer.observe(vec![EventType::PayloadEntropySpike], &mut events, 0);
+        assert!(follow_ups.is_empty());
+        assert_eq!(events[0].status, EventStatus::Final);
+        assert_eq!(confirmer.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_review_after_r_snapshots() {
+        let mut confirmer = EventConfirmer::new(config());
+        let mut events = vec![spike(1), ComplexityEvent::baseline_locked(0)];
+        confirmer.observe(vec![EventType::PayloadEntropySpike], &mut events, 0);
+        assert_eq!(events[0].status, EventStatus::Provisional);
+        assert_eq!(events[1].status, EventStatus::Final);
+        assert_eq!(confirmer.pending_count(), 1);
+
+        assert!(confirmer.observe(vec![], &mut [], 1).is_empty());
+        assert!(confirmer.observe(vec![], &mut [], 2).is_empty());
+        let follow_ups = confirmer.observe(vec![], &mut [], 3);
+        assert_eq!(follow_ups.len(), 1);
+        assert_eq!(follow_ups[0].status, EventStatus::Retracted);
+        assert_eq!(confirmer.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_held_fraction_over_window() {
+        let mut confirmer = EventConfirmer::new(config());
+        let held = || vec![EventType::PayloadEntropySpike];
+        confirmer.observe(held(), &mut [spike(1)], 0);
+        confirmer.observe(held(), &mut [], 1);
+        confirmer.observe(vec![], &mut [], 2);
+        let follow_ups = confirmer.observe(vec![], &mut [], 3);
+        assert_eq!(follow_ups[0].status, EventStatus::Confirmed);
+        assert!(matches!(
+            follow_ups[0].details,
+            crate::event::EventDetails::Review { original_id: 1, held_fraction }
+                if (held_fraction - 0.5).abs() < 1e-9
+        ));
+    }
+}
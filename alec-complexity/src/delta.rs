@@ -8,6 +8,7 @@
 
 use crate::baseline::Baseline;
 use crate::config::DeltaConfig;
+use crate::input::ChannelEntropy;
 use serde::{Deserialize, Serialize};
 
 /// Delta values (current - baseline mean).
@@ -45,6 +46,39 @@ impl ZScores {
     }
 }
 
+/// Entropy of one channel against its baseline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelZScore {
+    pub channel_id: String,
+    /// Current entropy.
+    pub h: f64,
+    /// Current entropy minus the baseline mean.
+    pub delta: f64,
+    /// Delta over the baseline std.
+    pub z: f64,
+}
+
+/// Z-scores of the channels with valid baseline statistics, in input
+/// order.
+pub fn channel_z_scores(baseline: &Baseline, channels: &[ChannelEntropy]) -> Vec<ChannelZScore> {
+    channels
+        .iter()
+        .filter_map(|channel| {
+            let stats = baseline.channels.get(&channel.channel_id)?;
+            if !stats.is_valid() {
+                return None;
+            }
+            let delta = channel.h - stats.mean;
+            Some(ChannelZScore {
+                channel_id: channel.channel_id.clone(),
+                h: channel.h,
+                delta,
+                z: delta / stats.std,
+            })
+        })
+        .collect()
+}
+
 /// Calculator for deltas and z-scores.
 pub struct DeltaCalculator {
     config: DeltaConfig,
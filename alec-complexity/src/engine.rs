@@ -7,30 +7,49 @@
 //! ComplexityEngine - main orchestration for complexity monitoring.
 
 use crate::anomaly::AnomalyDetector;
-use crate::baseline::BaselineBuilder;
+use crate::baseline::{
+    Baseline, BaselineProfiles, BaselineProfilesState, BaselineSummary, ExclusionWindow,
+};
 use crate::config::ComplexityConfig;
-use crate::delta::DeltaCalculator;
-use crate::event::ComplexityEvent;
+use crate::confirmation::EventConfirmer;
+use crate::delta::{channel_z_scores, DeltaCalculator};
+use crate::event::{ComplexityEvent, EventType};
+use crate::history::{MetricKind, SnapshotHistory, Trend};
 use crate::input::InputSnapshot;
+use crate::sink::EventSink;
 use crate::snapshot::ComplexitySnapshot;
 use crate::structure::SLiteExtractor;
+use std::collections::BTreeSet;
+use std::panic::{self, AssertUnwindSafe};
 
 /// Main complexity engine orchestrating all components.
 pub struct ComplexityEngine {
     config: ComplexityConfig,
-    baseline_builder: BaselineBuilder,
+    baselines: BaselineProfiles,
     delta_calculator: DeltaCalculator,
     structure_extractor: SLiteExtractor,
     anomaly_detector: AnomalyDetector,
+    confirmer: EventConfirmer,
 
-    /// Last top critical channels for shift detection.
+    /// Top critical channels at the last criticality shift (or the first
+    /// ranking seen), the reference new rankings are compared with.
     last_top_critical: Option<Vec<String>>,
     /// Total snapshots processed.
     snapshot_count: u64,
     /// Last snapshot output.
     last_output: Option<ComplexitySnapshot>,
-    /// Whether baseline lock event was emitted.
-    baseline_lock_emitted: bool,
+    /// Retained snapshots, if retention is configured.
+    history: Option<SnapshotHistory>,
+    /// Profiles whose baseline lock event was emitted.
+    baseline_lock_emitted: BTreeSet<String>,
+    /// Registered event sinks.
+    sinks: Vec<Box<dyn EventSink>>,
+    /// Failed sink deliveries (errors and panics).
+    sink_errors: u64,
+    /// Id assigned to the next emitted event.
+    next_event_id: u64,
+    /// Time windows kept out of the baseline.
+    exclusions: Vec<ExclusionWindow>,
 }
 
 impl ComplexityEngine {
@@ -38,18 +57,61 @@ impl ComplexityEngine {
     pub fn new(config: ComplexityConfig) -> Self {
         let track_r = config.deltas.compute_r;
         Self {
-            baseline_builder: BaselineBuilder::new(config.baseline.clone(), track_r),
+            baselines: BaselineProfiles::new(config.baseline.clone(), track_r),
             delta_calculator: DeltaCalculator::new(config.deltas.clone()),
             structure_extractor: SLiteExtractor::new(config.structure.clone()),
             anomaly_detector: AnomalyDetector::new(config.anomaly.clone()),
+            confirmer: EventConfirmer::new(config.confirmation.clone()),
+            history: config.retention.clone().map(SnapshotHistory::new),
             config,
             last_top_critical: None,
             snapshot_count: 0,
             last_output: None,
-            baseline_lock_emitted: false,
+            baseline_lock_emitted: BTreeSet::new(),
+            sinks: Vec::new(),
+            sink_errors: 0,
+            next_event_id: 1,
+            exclusions: Vec::new(),
         }
     }
 
+    /// Create an engine whose active profile is already locked on
+    /// `summary`, with no building phase.
+    ///
+    /// Fails if the configuration computes R and the summary has no R
+    /// statistics. Metrics with a zero std are never scored
+    /// ([`BaselineSummary::zero_std_metrics`]).
+    pub fn with_locked_baseline(
+        config: ComplexityConfig,
+        summary: &BaselineSummary,
+    ) -> Result<Self, String> {
+        let baseline = Baseline::from_summary(summary, config.deltas.compute_r)?;
+        let mut engine = Self::new(config);
+        engine.baselines.active_mut().import(baseline);
+        engine
+            .baseline_lock_emitted
+            .insert(engine.baselines.active_name().to_string());
+        Ok(engine)
+    }
+
+    /// Register a sink receiving events at the end of every `process()`.
+    ///
+    /// Only events at or above the sink's
+    /// [`min_severity`](EventSink::min_severity) are delivered.
+    pub fn add_sink(&mut self, sink: Box<dyn EventSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Number of registered sinks.
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Total failed sink deliveries since creation.
+    pub fn sink_errors(&self) -> u64 {
+        self.sink_errors
+    }
+
     /// Process an input snapshot and return complexity analysis.
     /// Returns None if complexity monitoring is disabled.
     pub fn process(&mut self, input: &InputSnapshot) -> Option<ComplexitySnapshot> {
@@ -61,18 +123,28 @@ impl ComplexityEngine {
 
         let mut events = Vec::new();
 
-        // Process baseline
-        let just_locked = self.baseline_builder.process(
-            input.tc,
-            input.h_joint,
-            input.h_bytes,
-            input.r,
-            input.timestamp_ms,
-        );
+        // Process baseline, unless the input falls in an exclusion window
+        let excluded = self.is_excluded(input.timestamp_ms);
+        let channels_ignored = self.config.channels.enabled
+            && !excluded
+            && self
+                .baselines
+                .active_mut()
+                .process_channels(&input.channel_entropies, self.config.structure.max_channels)
+                > 0;
+        let just_locked = !excluded
+            && self.baselines.active_mut().process(
+                input.tc,
+                input.h_joint,
+                input.h_bytes,
+                input.r,
+                input.timestamp_ms,
+            );
 
         // Check baseline state and emit events (scope limits baseline borrow)
+        let profile = self.baselines.active_name().to_string();
         let baseline_ready = {
-            let baseline = self.baseline_builder.baseline();
+            let baseline = self.baselines.active().baseline();
 
             // Emit baseline events
             if self.config.anomaly.events.baseline_events {
@@ -81,16 +153,25 @@ impl ComplexityEngine {
                         input.timestamp_ms,
                         baseline.build_progress,
                     ));
-                } else if just_locked && !self.baseline_lock_emitted {
+                } else if just_locked && !self.baseline_lock_emitted.contains(&profile) {
                     events.push(ComplexityEvent::baseline_locked(input.timestamp_ms));
-                    self.baseline_lock_emitted = true;
+                    self.baseline_lock_emitted.insert(profile.clone());
                 }
             }
 
             // If baseline is still building, return early snapshot
             if !baseline.is_ready() {
-                let output = ComplexitySnapshot::building(input.timestamp_ms, baseline, events);
-                self.last_output = Some(output.clone());
+                let mut output = ComplexitySnapshot::building(input.timestamp_ms, baseline, events)
+                    .with_profile(&profile);
+                if excluded {
+                    output.flags.push(FLAG_INPUT_EXCLUDED.to_string());
+                }
+                if channels_ignored {
+                    output.flags.push(FLAG_CHANNEL_LIMIT.to_string());
+                }
+                assign_ids(&mut self.next_event_id, &mut output.events);
+                self.dispatch_events(&output.events);
+                self.record(&output);
                 return Some(output);
             }
 
@@ -104,18 +185,26 @@ impl ComplexityEngine {
         // Extract S-lite if we have channel data
         let s_lite = self.structure_extractor.extract(input);
 
-        // Detect structure break
-        let structure_break = if let Some(ref current) = s_lite {
-            self.structure_extractor.detect_break(current)
+        // Detect structure break against the previous extraction
+        let structure_break = if s_lite.is_some() {
+            self.structure_extractor.detect_break_since_previous()
         } else {
             None
         };
-
-        // Detect criticality shift (if we have channel data with signal metrics)
-        let criticality_change = self.detect_criticality_change(input);
+        let structure_skipped = s_lite
+            .as_ref()
+            .and_then(|s| s.change.as_ref())
+            .is_some_and(|c| c.break_detection_skipped);
+
+        // Detect criticality shift (if we have a ranking or channel data)
+        let criticality_ranking = self.criticality_ranking(input);
+        let criticality_change = criticality_ranking
+            .as_deref()
+            .and_then(|ranking| self.detect_criticality_change(ranking));
+        let shifted_top = criticality_change.as_ref().map(|(_, new)| new.clone());
 
         // Now get baseline again for delta computation
-        let baseline = self.baseline_builder.baseline();
+        let baseline = self.baselines.active().baseline();
 
         // Compute deltas and z-scores
         let (deltas, z_scores) = self.delta_calculator.compute(
@@ -133,10 +222,41 @@ impl ComplexityEngine {
             criticality_change,
             input.timestamp_ms,
         );
+        if anomaly_events
+            .iter()
+            .any(|e| e.event_type == EventType::CriticalityShift)
+        {
+            self.last_top_critical = shifted_top;
+        }
         events.extend(anomaly_events);
 
+        // Per-channel z-scores and anomalies
+        let channels = self.config.channels.enabled.then(|| {
+            let channels = channel_z_scores(baseline, &input.channel_entropies);
+            events.extend(self.anomaly_detector.evaluate_channels(
+                &channels,
+                &self.config.channels,
+                input.timestamp_ms,
+            ));
+            channels
+        });
+        assign_ids(&mut self.next_event_id, &mut events);
+
+        // Retrospective review of earlier z-score events
+        if self.confirmer.is_enabled() {
+            let held = self.anomaly_detector.held_conditions(&z_scores);
+            let mut follow_ups = self
+                .confirmer
+                .observe(held, &mut events, input.timestamp_ms);
+            assign_ids(&mut self.next_event_id, &mut follow_ups);
+            events.extend(follow_ups);
+        }
+
         // Build flags
-        let flags = self.build_flags(&structure_break.is_some());
+        let mut flags = self.build_flags(&structure_break.is_some(), structure_skipped, excluded);
+        if channels_ignored {
+            flags.push(FLAG_CHANNEL_LIMIT.to_string());
+        }
 
         // Create output snapshot
         let output = ComplexitySnapshot::new(
@@ -147,20 +267,107 @@ impl ComplexityEngine {
             s_lite,
             events,
             flags,
-        );
+        )
+        .with_profile(&profile)
+        .with_criticality_ranking(criticality_ranking)
+        .with_channels(channels);
 
-        self.last_output = Some(output.clone());
+        self.dispatch_events(&output.events);
+        self.record(&output);
         Some(output)
     }
 
-    /// Get the current baseline.
+    /// Keep `output` as the last output and in the history.
+    fn record(&mut self, output: &ComplexitySnapshot) {
+        if let Some(history) = &mut self.history {
+            history.push(output);
+        }
+        self.last_output = Some(output.clone());
+    }
+
+    /// Get the baseline of the active profile.
     pub fn baseline(&self) -> &crate::baseline::Baseline {
-        self.baseline_builder.baseline()
+        self.baselines.active().baseline()
     }
 
-    /// Check if baseline is locked.
+    /// Check if the active profile's baseline is locked.
     pub fn is_baseline_locked(&self) -> bool {
-        self.baseline_builder.baseline().is_ready()
+        self.baselines.active().baseline().is_ready()
+    }
+
+    /// Switch the baseline profile used for deltas and z-scores.
+    ///
+    /// A profile is created (building) on first use; other profiles
+    /// keep their state. Delta smoothing restarts on a switch so values
+    /// from the previous mode do not bleed into the new one.
+    pub fn set_active_profile(&mut self, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("profile name must not be empty".to_string());
+        }
+        if self.baselines.set_active(name) {
+            self.delta_calculator.reset();
+        }
+        Ok(())
+    }
+
+    /// Name of the active baseline profile.
+    pub fn active_profile(&self) -> &str {
+        self.baselines.active_name()
+    }
+
+    /// Names of all known baseline profiles, sorted.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.baselines.names()
+    }
+
+    /// Get the baseline of a profile by name.
+    pub fn profile_baseline(&self, name: &str) -> Option<&crate::baseline::Baseline> {
+        self.baselines.get(name)
+    }
+
+    /// Lock a profile's baseline immediately with the samples collected
+    /// so far. Returns true if the profile existed and was building.
+    pub fn lock_profile(&mut self, name: &str) -> bool {
+        self.baselines.lock(name)
+    }
+
+    /// Restart building the active profile's baseline.
+    ///
+    /// Other profiles and the exclusion windows are kept.
+    pub fn baseline_reset(&mut self) {
+        self.baselines.reset_active();
+        self.baseline_lock_emitted
+            .remove(self.baselines.active_name());
+        self.delta_calculator.reset();
+    }
+
+    /// Keep inputs timestamped in `start_ms..end_ms` out of the baseline,
+    /// e.g. a period the machine is known to have been faulty.
+    ///
+    /// Excluded inputs neither build nor update any profile's baseline
+    /// and their snapshots carry the `BASELINE_INPUT_EXCLUDED` flag. Once
+    /// the baseline is locked they are still scored against it. Windows
+    /// survive [`Self::baseline_reset`] and [`Self::reset`].
+    pub fn mark_exclusion(&mut self, start_ms: u64, end_ms: u64) -> Result<(), String> {
+        if start_ms >= end_ms {
+            return Err("exclusion window must end after it starts".to_string());
+        }
+        self.exclusions.push(ExclusionWindow { start_ms, end_ms });
+        Ok(())
+    }
+
+    /// Exclusion windows, in the order they were marked.
+    pub fn exclusions(&self) -> &[ExclusionWindow] {
+        &self.exclusions
+    }
+
+    /// Remove all exclusion windows.
+    pub fn clear_exclusions(&mut self) {
+        self.exclusions.clear();
+    }
+
+    fn is_excluded(&self, timestamp_ms: u64) -> bool {
+        self.exclusions.iter().any(|w| w.contains(timestamp_ms))
     }
 
     /// Get the last output snapshot.
@@ -168,6 +375,28 @@ impl ComplexityEngine {
         self.last_output.as_ref()
     }
 
+    /// Retained snapshots, oldest first (none without retention).
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &ComplexitySnapshot> {
+        self.history.iter().flat_map(SnapshotHistory::iter)
+    }
+
+    /// Least-squares trend of a metric's delta over the retained
+    /// snapshots of the last `window_ms`.
+    ///
+    /// `None` without retention or with fewer than two snapshots carrying
+    /// the delta in the window.
+    pub fn trend(&self, metric: MetricKind, window_ms: u64) -> Option<Trend> {
+        self.history.as_ref()?.trend(metric, window_ms)
+    }
+
+    /// Events of the retained snapshots timestamped at or after
+    /// `timestamp_ms`, oldest first.
+    pub fn events_since(&self, timestamp_ms: u64) -> Vec<&ComplexityEvent> {
+        self.history
+            .as_ref()
+            .map_or_else(Vec::new, |h| h.events_since(timestamp_ms))
+    }
+
     /// Get total snapshots processed.
     pub fn snapshot_count(&self) -> u64 {
         self.snapshot_count
@@ -183,85 +412,134 @@ impl ComplexityEngine {
         self.config.enabled
     }
 
-    /// Reset all state.
+    /// Reset all state. Registered sinks and exclusion windows are kept,
+    /// and event ids keep increasing so they stay unique for those sinks.
     pub fn reset(&mut self) {
         let track_r = self.config.deltas.compute_r;
-        self.baseline_builder = BaselineBuilder::new(self.config.baseline.clone(), track_r);
+        self.baselines = BaselineProfiles::new(self.config.baseline.clone(), track_r);
         self.delta_calculator = DeltaCalculator::new(self.config.deltas.clone());
         self.structure_extractor = SLiteExtractor::new(self.config.structure.clone());
         self.anomaly_detector.reset();
+        self.confirmer.reset();
         self.last_top_critical = None;
         self.snapshot_count = 0;
         self.last_output = None;
-        self.baseline_lock_emitted = false;
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.baseline_lock_emitted.clear();
     }
 
-    /// Export baseline state for persistence.
+    /// Export the active profile's baseline for persistence.
     pub fn export_baseline(&self) -> Option<String> {
-        let baseline = self.baseline_builder.export();
+        let baseline = self.baselines.active().export();
         serde_json::to_string(&baseline).ok()
     }
 
-    /// Import baseline state from persistence.
+    /// Import a baseline into the active profile.
     pub fn import_baseline(&mut self, json: &str) -> Result<(), String> {
         let baseline: crate::baseline::Baseline =
             serde_json::from_str(json).map_err(|e| e.to_string())?;
-        self.baseline_builder.import(baseline);
-        if self.baseline_builder.baseline().is_ready() {
-            self.baseline_lock_emitted = true;
+        self.baselines.active_mut().import(baseline);
+        if self.baselines.active().baseline().is_ready() {
+            self.baseline_lock_emitted
+                .insert(self.baselines.active_name().to_string());
         }
         Ok(())
     }
 
-    fn detect_criticality_change(
-        &mut self,
-        input: &InputSnapshot,
-    ) -> Option<(Vec<String>, Vec<String>)> {
+    /// Export every baseline profile and the active profile name.
+    pub fn export_state(&self) -> Option<String> {
+        serde_json::to_string(&self.baselines.export()).ok()
+    }
+
+    /// Import baseline profiles exported with [`Self::export_state`],
+    /// replacing all current profiles.
+    pub fn import_state(&mut self, json: &str) -> Result<(), String> {
+        let state: BaselineProfilesState = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if state.active.is_empty() {
+            return Err("profile name must not be empty".to_string());
+        }
+        self.baselines.import(state);
+        self.delta_calculator.reset();
+        self.baseline_lock_emitted = self
+            .baselines
+            .names()
+            .into_iter()
+            .filter(|name| self.baselines.get(name).is_some_and(|b| b.is_ready()))
+            .collect();
+        Ok(())
+    }
+
+    /// Deliver events to every sink whose severity filter accepts them.
+    /// A failing or panicking sink is counted and skipped.
+    fn dispatch_events(&mut self, events: &[ComplexityEvent]) {
+        for sink in &self.sinks {
+            let min_severity = sink.min_severity();
+            for event in events.iter().filter(|e| e.severity >= min_severity) {
+                let delivered = panic::catch_unwind(AssertUnwindSafe(|| sink.emit(event)));
+                if !matches!(delivered, Ok(Ok(()))) {
+                    self.sink_errors += 1;
+                }
+            }
+        }
+    }
+
+    /// Channels ranked most critical first: the input's criticality
+    /// ranking if any, channel entropy otherwise. `None` when criticality
+    /// shifts are disabled or there is nothing to rank.
+    fn criticality_ranking(&self, input: &InputSnapshot) -> Option<Vec<(String, f64)>> {
         if !self.config.anomaly.events.criticality_shift {
             return None;
         }
 
-        // Need channel data
-        if input.channel_entropies.is_empty() {
+        let mut ranking: Vec<(String, f64)> = match &input.criticality_ranking {
+            Some(ranking) => ranking.clone(),
+            None => input
+                .channel_entropies
+                .iter()
+                .map(|c| (c.channel_id.clone(), c.h))
+                .collect(),
+        };
+        if ranking.is_empty() {
             return None;
         }
 
-        // Sort channels by entropy to find most critical
-        let mut channel_entropies: Vec<_> = input
-            .channel_entropies
-            .iter()
-            .map(|c| (c.channel_id.clone(), c.h))
-            .collect();
-
-        channel_entropies
-            .sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranking.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        Some(ranking)
+    }
 
-        // Take top 3 (or fewer if not enough channels)
-        let top_n = 3.min(channel_entropies.len());
-        let new_top: Vec<String> = channel_entropies[..top_n]
-            .iter()
-            .map(|(id, _)| id.clone())
-            .collect();
+    /// Compare the top K of `ranking` with the reference top K.
+    ///
+    /// Returns `(reference, new top K)` when they differ beyond the
+    /// configured thresholds. The first ranking becomes the reference.
+    fn detect_criticality_change(
+        &mut self,
+        ranking: &[(String, f64)],
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let top_n = self.config.criticality.top_k.min(ranking.len());
+        let new_top: Vec<String> = ranking[..top_n].iter().map(|(id, _)| id.clone()).collect();
 
-        // Compare with previous
-        let result = if let Some(old_top) = &self.last_top_critical {
-            if *old_top != new_top {
-                Some((old_top.clone(), new_top.clone()))
-            } else {
-                None
-            }
-        } else {
-            None
+        let Some(old_top) = &self.last_top_critical else {
+            self.last_top_critical = Some(new_top);
+            return None;
         };
 
-        self.last_top_critical = Some(new_top);
-        result
+        let entered = new_top.iter().filter(|id| !old_top.contains(id)).count();
+        let shifted = entered > self.config.criticality.max_set_difference
+            || kendall_distance(old_top, &new_top) > self.config.criticality.max_kendall_distance;
+        shifted.then(|| (old_top.clone(), new_top))
     }
 
-    fn build_flags(&self, has_structure_break: &bool) -> Vec<String> {
+    fn build_flags(
+        &self,
+        has_structure_break: &bool,
+        structure_skipped: bool,
+        excluded: bool,
+    ) -> Vec<String> {
         let mut flags = Vec::new();
 
-        if self.baseline_builder.baseline().is_ready() {
+        if self.baselines.active().baseline().is_ready() {
             flags.push("BASELINE_LOCKED".to_string());
         }
 
@@ -276,20 +554,67 @@ impl ComplexityEngine {
             flags.push("STRUCTURE_BREAK_DETECTED".to_string());
         }
 
+        if structure_skipped {
+            flags.push("STRUCTURE_BREAK_SKIPPED".to_string());
+        }
+
         if self.config.anomaly.enabled {
             flags.push("ANOMALY_DETECTION_ENABLED".to_string());
         }
 
+        if excluded {
+            flags.push(FLAG_INPUT_EXCLUDED.to_string());
+        }
+
         flags
     }
 }
 
+/// Flag of snapshots whose input was kept out of the baseline.
+const FLAG_INPUT_EXCLUDED: &str = "BASELINE_INPUT_EXCLUDED";
+
+/// Flag for inputs with channels ignored by the baseline because
+/// `structure.max_channels` channels are already tracked.
+const FLAG_CHANNEL_LIMIT: &str = "CHANNEL_LIMIT_REACHED";
+
+/// Give each event the next monotonic id.
+fn assign_ids(next_id: &mut u64, events: &mut [ComplexityEvent]) {
+    for event in events {
+        event.id = *next_id;
+        *next_id += 1;
+    }
+}
+
+/// Normalized Kendall tau distance between the orderings of the channels
+/// present in both rankings: the fraction of their pairs ranked in opposite
+/// order, 0.0 with fewer than two common channels.
+fn kendall_distance(old: &[String], new: &[String]) -> f64 {
+    let positions: Vec<(usize, usize)> = old
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| Some((i, new.iter().position(|n| n == id)?)))
+        .collect();
+    let pairs = positions.len() * positions.len().saturating_sub(1) / 2;
+    if pairs == 0 {
+        return 0.0;
+    }
+
+    let discordant = positions
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| positions[i + 1..].iter().map(move |b| (a, b)))
+        .filter(|(a, b)| (a.0 < b.0) != (a.1 < b.1))
+        .count();
+    discordant as f64 / pairs as f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::{AnomalyConfig, BaselineConfig, EventTypeConfig};
-    use crate::event::EventType;
+    use crate::event::{EventSeverity, EventType};
     use crate::input::ChannelEntropy;
+    use crate::sink::BufferedSink;
 
     fn create_test_config() -> ComplexityConfig {
         ComplexityConfig {
@@ -319,6 +644,7 @@ mod tests {
             h_bytes,
             r: Some(0.5),
             channel_entropies: vec![],
+            criticality_ranking: None,
             source: "test".to_string(),
         }
     }
@@ -570,4 +896,196 @@ mod tests {
             .contains(&"ANOMALY_DETECTION_ENABLED".to_string()));
         assert!(result.flags.contains(&"BASELINE_LOCKED".to_string()));
     }
+
+    fn channels(entries: &[(&str, f64)]) -> Vec<ChannelEntropy> {
+        entries
+            .iter()
+            .map(|(id, h)| ChannelEntropy {
+                channel_id: id.to_string(),
+                h: *h,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_structure_break_ignores_offline_channel() {
+        let mut config = create_test_config();
+        config.structure.break_threshold = 0.1;
+        config.structure.sparsify.enabled = false;
+        config.anomaly.events.criticality_shift = false;
+        let mut engine = ComplexityEngine::new(config);
+
+        engine.process(&create_input(1000, 3.0));
+        engine.process(&create_input(2000, 3.0));
+
+        let mut input = create_input(3000, 3.0);
+        input.channel_entropies = channels(&[("ch1", 2.0), ("ch2", 3.0), ("ch3", 10.0)]);
+        engine.process(&input);
+
+        // ch3 offline for one interval: no break
+        let mut input = create_input(4000, 3.0);
+        input.channel_entropies = channels(&[("ch1", 2.0), ("ch2", 3.0)]);
+        let result = engine.process(&input).unwrap();
+        assert!(!result
+            .events
+            .iter()
+            .any(|e| e.event_type == EventType::StructureBreak));
+
+        // Remaining pair genuinely diverges: break
+        let mut input = create_input(5000, 3.0);
+        input.channel_entropies = channels(&[("ch1", 2.0), ("ch2", 8.0)]);
+        let result = engine.process(&input).unwrap();
+        assert!(result
+            .events
+            .iter()
+            .any(|e| e.event_type == EventType::StructureBreak));
+        assert!(result
+            .flags
+            .contains(&"STRUCTURE_BREAK_DETECTED".to_string()));
+    }
+
+    fn z_h_bytes(result: &ComplexitySnapshot) -> f64 {
+        result.z_scores.as_ref().unwrap().h_bytes
+    }
+
+    #[test]
+    fn test_named_baseline_profiles() {
+        let mut config = create_test_config();
+        config.anomaly.enabled = false;
+        let mut engine = ComplexityEngine::new(config);
+        assert_eq!(engine.active_profile(), "default");
+
+        // Build and lock "idle" around h_bytes = 3.0
+        engine.set_active_profile("idle").unwrap();
+        engine.process(&create_input(1000, 2.9));
+        engine.process(&create_input(2000, 3.1));
+        assert!(engine.is_baseline_locked());
+
+        // Switching to a new profile starts building it
+        engine.set_active_profile("load").unwrap();
+        let result = engine.process(&create_input(3000, 7.5)).unwrap();
+        assert!(result.flags.contains(&"BASELINE_BUILDING".to_string()));
+        assert_eq!(result.baseline.profile, "load");
+        assert!(engine.profile_baseline("idle").unwrap().is_ready());
+        let result = engine.process(&create_input(4000, 8.5)).unwrap();
+        assert!(result.is_baseline_locked());
+
+        // z-scores follow the active profile's statistics
+        let result = engine.process(&create_input(5000, 8.0)).unwrap();
+        assert_eq!(result.baseline.profile, "load");
+        assert!(z_h_bytes(&result).abs() < 1e-9);
+
+        engine.set_active_profile("idle").unwrap();
+        let result = engine.process(&create_input(6000, 3.0)).unwrap();
+        assert_eq!(result.baseline.profile, "idle");
+        assert!(z_h_bytes(&result).abs() < 1e-9);
+        let result = engine.process(&create_input(7000, 8.0)).unwrap();
+        let idle = engine.profile_baseline("idle").unwrap();
+        let expected = (8.0 - idle.h_bytes.mean) / idle.h_bytes.std;
+        assert!((z_h_bytes(&result) - expected).abs() < 1e-9);
+        assert!(z_h_bytes(&result) > 10.0);
+
+        assert_eq!(engine.profile_names(), vec!["default", "idle", "load"]);
+        assert!(engine.set_active_profile("").is_err());
+    }
+
+    #[test]
+    fn test_profiles_export_import_state() {
+        let config = create_test_config();
+        let mut engine = ComplexityEngine::new(config.clone());
+
+        engine.set_active_profile("idle").unwrap();
+        engine.process(&create_input(1000, 2.9));
+        engine.process(&create_input(2000, 3.1));
+        engine.set_active_profile("load").unwrap();
+        engine.process(&create_input(3000, 8.0));
+        assert!(engine.lock_profile("load"));
+
+        let exported = engine.export_state().unwrap();
+        let mut engine2 = ComplexityEngine::new(config);
+        engine2.import_state(&exported).unwrap();
+
+        assert_eq!(engine2.active_profile(), "load");
+        assert!(engine2.profile_baseline("idle").unwrap().is_ready());
+        assert!(engine2.profile_baseline("load").unwrap().is_ready());
+        assert!((engine2.profile_baseline("idle").unwrap().h_bytes.mean - 3.0).abs() < 1e-9);
+    }
+
+    fn severities(events: &[ComplexityEvent]) -> Vec<EventSeverity> {
+        events.iter().map(|e| e.severity).collect()
+    }
+
+    #[test]
+    fn test_sinks_filter_by_severity() {
+        let mut config = create_test_config();
+        config.deltas.smoothing.enabled = false;
+        let mut engine = ComplexityEngine::new(config);
+
+        let all = BufferedSink::new();
+        let critical_only = BufferedSink::new().with_min_severity(EventSeverity::Critical);
+        engine.add_sink(Box::new(all.clone()));
+        engine.add_sink(Box::new(critical_only.clone()));
+        assert_eq!(engine.sink_count(), 2);
+
+        // Info: baseline building and locked
+        engine.process(&create_input(1000, 2.9));
+        engine.process(&create_input(2000, 3.1));
+        let stats = engine.baseline().h_bytes.clone();
+
+        // Warning (z = 2.5), then Critical (z = 5)
+        let warn = engine
+            .process(&create_input(3000, stats.mean + 2.5 * stats.std))
+            .unwrap();
+        assert_eq!(severities(&warn.events), vec![EventSeverity::Warning]);
+        let crit = engine
+            .process(&create_input(4000, stats.mean + 5.0 * stats.std))
+            .unwrap();
+        assert_eq!(severities(&crit.events), vec![EventSeverity::Critical]);
+
+        assert_eq!(
+            severities(&all.drain()),
+            vec![
+                EventSeverity::Info,
+                EventSeverity::Info,
+                EventSeverity::Warning,
+                EventSeverity::Critical,
+            ]
+        );
+        let critical = critical_only.drain();
+        assert_eq!(critical.len(), 1);
+        assert_eq!(critical[0].event_type, EventType::PayloadEntropySpike);
+        assert_eq!(critical[0].timestamp_ms, 4000);
+        assert_eq!(engine.sink_errors(), 0);
+    }
+
+    struct FailingSink {
+        panic: bool,
+    }
+
+    impl EventSink for FailingSink {
+        fn emit(&self, _event: &ComplexityEvent) -> Result<(), String> {
+            if self.panic {
+                panic!("sink exploded");
+            }
+            Err("endpoint unreachable".to_string())
+        }
+    }
+
+    #[test]
+    fn test_failing_sinks_do_not_fail_process() {
+        let config = create_test_config();
+        let mut engine = ComplexityEngine::new(config);
+
+        let buffered = BufferedSink::new();
+        engine.add_sink(Box::new(FailingSink { panic: false }));
+        engine.add_sink(Box::new(FailingSink { panic: true }));
+        engine.add_sink(Box::new(buffered.clone()));
+
+        let result = engine.process(&create_input(1000, 3.0));
+        assert!(result.is_some());
+
+        // One building event, rejected by both failing sinks
+        assert_eq!(engine.sink_errors(), 2);
+        assert_eq!(buffered.len(), 1);
+    }
 }
@@ -26,6 +26,8 @@ pub enum EventType {
     ComplexitySurge,
     /// Criticality ranking changed significantly.
     CriticalityShift,
+    /// One channel's entropy deviates from its baseline.
+    ChannelEntropyAnomaly,
 }
 
 impl EventType {
@@ -38,12 +40,27 @@ impl EventType {
             EventType::RedundancyDrop => "REDUNDANCY_DROP",
             EventType::ComplexitySurge => "COMPLEXITY_SURGE",
             EventType::CriticalityShift => "CRITICALITY_SHIFT",
+            EventType::ChannelEntropyAnomaly => "CHANNEL_ENTROPY_ANOMALY",
+        }
+    }
+
+    /// Stable snake_case name, e.g. for metric labels.
+    pub fn as_snake_case(&self) -> &'static str {
+        match self {
+            EventType::BaselineBuilding => "baseline_building",
+            EventType::BaselineLocked => "baseline_locked",
+            EventType::PayloadEntropySpike => "payload_entropy_spike",
+            EventType::StructureBreak => "structure_break",
+            EventType::RedundancyDrop => "redundancy_drop",
+            EventType::ComplexitySurge => "complexity_surge",
+            EventType::CriticalityShift => "criticality_shift",
+            EventType::ChannelEntropyAnomaly => "channel_entropy_anomaly",
         }
     }
 }
 
-/// Severity level of an event.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Severity level of an event, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum EventSeverity {
     Info,
     Warning,
@@ -58,6 +75,40 @@ impl EventSeverity {
             EventSeverity::Critical => "CRIT",
         }
     }
+
+    /// Stable snake_case name, e.g. for metric labels.
+    pub fn as_snake_case(&self) -> &'static str {
+        match self {
+            EventSeverity::Info => "info",
+            EventSeverity::Warning => "warning",
+            EventSeverity::Critical => "critical",
+        }
+    }
+}
+
+/// Review status of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EventStatus {
+    /// Not subject to retrospective review.
+    #[default]
+    Final,
+    /// Emitted immediately, pending retrospective review.
+    Provisional,
+    /// Follow-up: the condition held over the review window.
+    Confirmed,
+    /// Follow-up: the condition did not hold; the original was a false positive.
+    Retracted,
+}
+
+impl EventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventStatus::Final => "FINAL",
+            EventStatus::Provisional => "PROVISIONAL",
+            EventStatus::Confirmed => "CONFIRMED",
+            EventStatus::Retracted => "RETRACTED",
+        }
+    }
 }
 
 /// Additional details for specific event types.
@@ -74,6 +125,17 @@ pub enum EventDetails {
         old_top: Vec<String>,
         new_top: Vec<String>,
     },
+    /// Channel whose entropy z-score triggered the event.
+    ChannelZScore {
+        channel_id: String,
+        z: f64,
+        threshold: f64,
+    },
+    /// Outcome of the retrospective review of a provisional event.
+    Review {
+        original_id: u64,
+        held_fraction: f64,
+    },
     /// No additional details.
     None,
 }
@@ -81,6 +143,9 @@ pub enum EventDetails {
 /// A complexity event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplexityEvent {
+    /// Monotonic event id, assigned by the engine (0 until assigned).
+    #[serde(default)]
+    pub id: u64,
     /// Event type.
     pub event_type: EventType,
     /// Severity level.
@@ -91,6 +156,9 @@ pub struct ComplexityEvent {
     pub message: String,
     /// Additional details.
     pub details: EventDetails,
+    /// Review status.
+    #[serde(default)]
+    pub status: EventStatus,
 }
 
 impl ComplexityEvent {
@@ -103,14 +171,51 @@ impl ComplexityEvent {
         details: EventDetails,
     ) -> Self {
         Self {
+            id: 0,
             event_type,
             severity,
             timestamp_ms,
             message: message.into(),
             details,
+            status: EventStatus::Final,
         }
     }
 
+    /// Create the follow-up to a provisional event after its review.
+    ///
+    /// A confirmed follow-up keeps the original severity; a retraction
+    /// is informational.
+    pub fn review(
+        original: &ComplexityEvent,
+        timestamp_ms: u64,
+        confirmed: bool,
+        held_fraction: f64,
+    ) -> Self {
+        let (status, severity, verb) = if confirmed {
+            (EventStatus::Confirmed, original.severity, "confirmed")
+        } else {
+            (EventStatus::Retracted, EventSeverity::Info, "retracted")
+        };
+        let mut event = Self::new(
+            original.event_type,
+            severity,
+            timestamp_ms,
+            format!(
+                "{} #{} {}: condition held in {:.0}% of window",
+                original.event_type.as_str(),
+                original.id,
+                verb,
+                held_fraction * 100.0
+            ),
+            EventDetails::Review {
+                original_id: original.id,
+                held_fraction,
+            },
+        );
+        event.status = status;
+        event
+    }
+
     /// Create a baseline building event.
     pub fn baseline_building(timestamp_ms: u64, progress: f64) -> Self {
         Self::new(
@@ -226,6 +331,30 @@ impl ComplexityEvent {
         )
     }
 
+    /// Create a channel entropy anomaly event.
+    pub fn channel_entropy_anomaly(
+        timestamp_ms: u64,
+        severity: EventSeverity,
+        channel_id: &str,
+        z: f64,
+        threshold: f64,
+    ) -> Self {
+        Self::new(
+            EventType::ChannelEntropyAnomaly,
+            severity,
+            timestamp_ms,
+            format!(
+                "Channel {} entropy anomaly: z={:.2} (threshold: {:.2})",
+                channel_id, z, threshold
+            ),
+            EventDetails::ChannelZScore {
+                channel_id: channel_id.to_string(),
+                z,
+                threshold,
+            },
+        )
+    }
+
     /// Serialize to JSON.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -257,6 +386,19 @@ mod tests {
         assert_eq!(EventSeverity::Critical.as_str(), "CRIT");
     }
 
+    #[test]
+    fn test_snake_case_names() {
+        assert_eq!(
+            EventType::PayloadEntropySpike.as_snake_case(),
+            "payload_entropy_spike"
+        );
+        assert_eq!(
+            EventType::CriticalityShift.as_snake_case(),
+            "criticality_shift"
+        );
+        assert_eq!(EventSeverity::Warning.as_snake_case(), "warning");
+    }
+
     #[test]
     fn test_baseline_building_event() {
         let event = ComplexityEvent::baseline_building(1000, 0.5);
@@ -293,6 +435,27 @@ mod tests {
         assert!(json.contains("1000"));
     }
 
+    #[test]
+    fn test_review_event() {
+        let mut original =
+            ComplexityEvent::payload_entropy_spike(1000, EventSeverity::Critical, 3.5, 2.0);
+        original.id = 7;
+        assert_eq!(original.status, EventStatus::Final);
+
+        let confirmed = ComplexityEvent::review(&original, 6000, true, 0.8);
+        assert_eq!(confirmed.status, EventStatus::Confirmed);
+        assert_eq!(confirmed.severity, EventSeverity::Critical);
+        assert!(confirmed.message.contains("#7 confirmed"));
+
+        let retracted = ComplexityEvent::review(&original, 6000, false, 0.2);
+        assert_eq!(retracted.status, EventStatus::Retracted);
+        assert_eq!(retracted.severity, EventSeverity::Info);
+        match retracted.details {
+            EventDetails::Review { original_id, .. } => assert_eq!(original_id, 7),
+            _ => panic!("Wrong details type"),
+        }
+    }
+
     #[test]
     fn test_redundancy_drop_event() {
         let event = ComplexityEvent::redundancy_drop(1000, EventSeverity::Critical, -3.0, -2.0);
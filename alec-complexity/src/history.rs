@@ -0,0 +1,241 @@
+// ALEC Complexity - Standalone complexity monitoring
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Snapshot retention and trends over retained snapshots.
+
+use crate::config::RetentionConfig;
+use crate::event::ComplexityEvent;
+use crate::snapshot::ComplexitySnapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Metric whose trend is computed from the snapshot deltas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricKind {
+    /// Total Correlation.
+    Tc,
+    /// Joint entropy.
+    HJoint,
+    /// Payload entropy.
+    HBytes,
+    /// Resilience index.
+    R,
+}
+
+impl MetricKind {
+    /// Every metric, in baseline field order.
+    pub const ALL: [MetricKind; 4] = [
+        MetricKind::Tc,
+        MetricKind::HJoint,
+        MetricKind::HBytes,
+        MetricKind::R,
+    ];
+
+    /// Stable snake_case name, matching the baseline field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Tc => "tc",
+            MetricKind::HJoint => "h_joint",
+            MetricKind::HBytes => "h_bytes",
+            MetricKind::R => "r",
+        }
+    }
+
+    /// Delta of this metric in a snapshot, if computed.
+    fn delta(self, snapshot: &ComplexitySnapshot) -> Option<f64> {
+        let deltas = snapshot.deltas.as_ref()?;
+        match self {
+            MetricKind::Tc => deltas.tc,
+            MetricKind::HJoint => deltas.h_joint,
+            MetricKind::HBytes => Some(deltas.h_bytes),
+            MetricKind::R => deltas.r,
+        }
+    }
+}
+
+/// Least-squares line fitted over retained snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Trend {
+    /// Change of the metric per second.
+    pub slope: f64,
+    /// Coefficient of determination (0.0-1.0); 1.0 for a constant series.
+    pub r2: f64,
+    /// Snapshots the line was fitted on.
+    pub samples: usize,
+}
+
+/// Bounded history of the snapshots an engine produced.
+#[derive(Debug, Clone)]
+pub struct SnapshotHistory {
+    config: RetentionConfig,
+    snapshots: VecDeque<ComplexitySnapshot>,
+}
+
+impl SnapshotHistory {
+    /// Create an empty history.
+    pub fn new(config: RetentionConfig) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(config.max_snapshots),
+            config,
+        }
+    }
+
+    /// Retain a copy of `snapshot`, dropping snapshots beyond the limits.
+    pub fn push(&mut self, snapshot: &ComplexitySnapshot) {
+        if self.config.max_snapshots == 0 {
+            return;
+        }
+        let mut retained = snapshot.clone();
+        if self.config.drop_s_lite {
+            retained.s_lite = None;
+        }
+        if self.snapshots.len() == self.config.max_snapshots {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(retained);
+
+        if let Some(max_age_ms) = self.config.max_age_ms {
+            let cutoff = snapshot.timestamp_ms.saturating_sub(max_age_ms);
+            while self
+                .snapshots
+                .front()
+                .is_some_and(|s| s.timestamp_ms < cutoff)
+            {
+                self.snapshots.pop_front();
+            }
+        }
+    }
+
+    /// Retained snapshots, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ComplexitySnapshot> + ExactSizeIterator {
+        self.snapshots.iter()
+    }
+
+    /// Number of retained snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Check if no snapshot is retained.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Drop all retained snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Fit a line through the deltas of `metric` in the snapshots of the
+    /// last `window_ms` (up to the latest snapshot).
+    ///
+    /// Snapshots without the delta (baseline building, metric not
+    /// computed) are skipped. `None` with fewer than two usable snapshots
+    /// or when they all share a timestamp.
+    pub fn trend(&self, metric: MetricKind, window_ms: u64) -> Option<Trend> {
+        let latest = self.snapshots.back()?.timestamp_ms;
+        let cutoff = latest.saturating_sub(window_ms);
+        let points: Vec<(f64, f64)> = self
+            .snapshots
+            .iter()
+            .filter(|s| s.timestamp_ms >= cutoff)
+            .filter_map(|s| {
+                let seconds = (s.timestamp_ms - cutoff) as f64 / 1000.0;
+                metric.delta(s).map(|delta| (seconds, delta))
+            })
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for &(x, y) in &points {
+            sxx += (x - mean_x) * (x - mean_x);
+            sxy += (x - mean_x) * (y - mean_y);
+            syy += (y - mean_y) * (y - mean_y);
+        }
+        if sxx == 0.0 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let r2 = if syy == 0.0 {
+            1.0
+        } else {
+            (sxy * sxy / (sxx * syy)).clamp(0.0, 1.0)
+        };
+        Some(Trend {
+            slope,
+            r2,
+            samples: points.len(),
+        })
+    }
+
+    /// Events of the retained snapshots timestamped at or after
+    /// `timestamp_ms`, oldest first.
+    pub fn events_since(&self, timestamp_ms: u64) -> Vec<&ComplexityEvent> {
+        self.snapshots
+            .iter()
+            .flat_map(|s| &s.events)
+            .filter(|e| e.timestamp_ms >= timestamp_ms)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::Baseline;
+    use crate::delta::Deltas;
+
+    fn snapshot(timestamp_ms: u64, h_bytes: f64) -> ComplexitySnapshot {
+        let deltas = Deltas {
+            h_bytes,
+            ..Default::default()
+        };
+        ComplexitySnapshot::new(
+            timestamp_ms,
+            &Baseline::new(false),
+            Some(deltas),
+            None,
+            None,
+            vec![],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_max_age() {
+        let mut history = SnapshotHistory::new(RetentionConfig {
+            max_snapshots: 100,
+            max_age_ms: Some(5_000),
+            drop_s_lite: false,
+        });
+        for i in 0..10 {
+            history.push(&snapshot(i * 1_000, 0.0));
+        }
+        let timestamps: Vec<u64> = history.iter().map(|s| s.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![4_000, 5_000, 6_000, 7_000, 8_000, 9_000]);
+    }
+
+    #[test]
+    fn test_trend_needs_two_points() {
+        let mut history = SnapshotHistory::new(RetentionConfig::default());
+        assert!(history.trend(MetricKind::HBytes, 60_000).is_none());
+        history.push(&snapshot(1_000, 0.5));
+        assert!(history.trend(MetricKind::HBytes, 60_000).is_none());
+        // No TC delta in the snapshots
+        history.push(&snapshot(2_000, 0.5));
+        assert!(history.trend(MetricKind::Tc, 60_000).is_none());
+
+        let flat = history.trend(MetricKind::HBytes, 60_000).unwrap();
+        assert_eq!(flat.slope, 0.0);
+        assert_eq!(flat.r2, 1.0);
+    }
+}
@@ -49,6 +49,20 @@ impl InputAdapter for GatewayInput<'_> {
 
         let r = self.snapshot.resilience.as_ref().and_then(|res| res.r);
 
+        // Channels without enough samples have no impact to rank by
+        let criticality_ranking = self
+            .snapshot
+            .resilience
+            .as_ref()
+            .and_then(|res| res.criticality.as_ref())
+            .map(|crit| {
+                crit.ranking
+                    .iter()
+                    .filter_map(|ch| Some((ch.channel_id.clone(), ch.impact?)))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|ranking| !ranking.is_empty());
+
         InputSnapshot {
             timestamp_ms: self.snapshot.timestamp_ms,
             tc,
@@ -56,6 +70,7 @@ impl InputAdapter for GatewayInput<'_> {
             h_bytes: self.snapshot.payload.h_bytes,
             r,
             channel_entropies,
+            criticality_ranking,
             source: "alec-gateway".to_string(),
         }
     }
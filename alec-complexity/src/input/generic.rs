@@ -6,9 +6,12 @@
 
 //! Generic JSON input adapter.
 //!
-//! Accepts a simple JSON format for integration with any metrics source.
+//! Accepts a simple JSON format for integration with any metrics source,
+//! either one object at a time, as a JSON array, or as CSV rows (see
+//! [`super::GenericInputReader`] for the column layout).
 
-use super::{ChannelEntropy, InputAdapter, InputSnapshot};
+use super::reader::{parse_csv_row, ParseMode};
+use super::{ChannelEntropy, InputAdapter, InputSnapshot, ParseError};
 use serde::{Deserialize, Serialize};
 
 /// Generic JSON input format.
@@ -24,6 +27,10 @@ use serde::{Deserialize, Serialize};
 ///   "channels": [
 ///     {"id": "temp", "h": 3.2},
 ///     {"id": "humid", "h": 2.8}
+///   ],
+///   "criticality": [
+///     {"id": "humid", "score": 0.9},
+///     {"id": "temp", "score": 0.4}
 ///   ]
 /// }
 /// ```
@@ -50,6 +57,10 @@ pub struct GenericInput {
     /// Per-channel entropies (optional).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub channels: Vec<GenericChannelInput>,
+
+    /// Per-channel criticality scores (optional).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criticality: Vec<GenericCriticalityInput>,
 }
 
 /// Per-channel input in generic format.
@@ -59,12 +70,35 @@ pub struct GenericChannelInput {
     pub h: f64,
 }
 
+/// Per-channel criticality score in generic format (higher is more critical).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenericCriticalityInput {
+    pub id: String,
+    pub score: f64,
+}
+
 impl GenericInput {
     /// Parse from JSON string.
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
 
+    /// Parse a JSON array of objects, e.g. a batch of collector snapshots.
+    ///
+    /// Fails on the first invalid element.
+    pub fn from_json_array(json: &str) -> Result<Vec<Self>, ParseError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Parse one CSV row given the header row's column names.
+    ///
+    /// `timestamp_ms` and `h_bytes` are required, `tc`, `h_joint` and `r`
+    /// may be empty, `h_<channel>` columns become channel entropies and
+    /// other columns are ignored.
+    pub fn from_csv_row(headers: &[&str], row: &str) -> Result<Self, ParseError> {
+        parse_csv_row(headers, row, ParseMode::Lenient)
+    }
+
     /// Serialize to JSON string.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -79,6 +113,7 @@ impl GenericInput {
             h_joint: None,
             r: None,
             channels: Vec::new(),
+            criticality: Vec::new(),
         }
     }
 
@@ -109,6 +144,15 @@ impl GenericInput {
         self
     }
 
+    /// Builder: add a channel's criticality score.
+    pub fn with_criticality(mut self, id: &str, score: f64) -> Self {
+        self.criticality.push(GenericCriticalityInput {
+            id: id.to_string(),
+            score,
+        });
+        self
+    }
+
     /// Finalize builder and convert to InputSnapshot.
     pub fn build(self) -> InputSnapshot {
         self.to_input_snapshot()
@@ -136,6 +180,12 @@ impl InputAdapter for GenericInput {
                     h: ch.h,
                 })
                 .collect(),
+            criticality_ranking: (!self.criticality.is_empty()).then(|| {
+                self.criticality
+                    .iter()
+                    .map(|ch| (ch.id.clone(), ch.score))
+                    .collect()
+            }),
             source: "generic-json".to_string(),
         }
     }
@@ -180,9 +230,21 @@ mod tests {
         assert_eq!(snapshot.h_bytes, 5.5);
         assert_eq!(snapshot.tc, Some(2.0));
         assert_eq!(snapshot.channel_entropies.len(), 1);
+        assert_eq!(snapshot.criticality_ranking, None);
         assert_eq!(snapshot.source, "generic-json");
     }
 
+    #[test]
+    fn test_generic_input_criticality() {
+        let json = r#"{"timestamp_ms": 1000, "h_bytes": 5.5,
+            "criticality": [{"id": "humid", "score": 0.9}, {"id": "temp", "score": 0.4}]}"#;
+        let snapshot = GenericInput::from_json(json).unwrap().to_snapshot();
+        assert_eq!(
+            snapshot.criticality_ranking,
+            Some(vec![("humid".to_string(), 0.9), ("temp".to_string(), 0.4)])
+        );
+    }
+
     #[test]
     fn test_json_roundtrip() {
         let input = GenericInput::new(1000, 5.5)
@@ -8,15 +8,19 @@
 //!
 //! Complexity can consume data from multiple sources:
 //! - ALEC Gateway MetricsSnapshot (with `gateway` feature)
-//! - Generic JSON input
+//! - Generic JSON input, single or batched (JSON arrays, JSON Lines, CSV)
 //! - Custom adapters via `InputAdapter` trait
 
 mod generic;
+mod reader;
 
 #[cfg(feature = "gateway")]
 mod gateway;
 
 pub use generic::GenericInput;
+pub use reader::{GenericInputReader, InputFormat, ParseError, ParseErrorKind, ParseMode};
+
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "gateway")]
 pub use gateway::{GatewayInput, MetricsSnapshotExt};
@@ -42,12 +46,17 @@ pub struct InputSnapshot {
     /// Per-channel entropy values for S-lite structure analysis.
     pub channel_entropies: Vec<ChannelEntropy>,
 
+    /// Per-channel criticality scores, e.g. the gateway's leave-one-out
+    /// impact (higher is more critical). Criticality shifts fall back to
+    /// ranking `channel_entropies` when absent.
+    pub criticality_ranking: Option<Vec<(String, f64)>>,
+
     /// Source identifier for debugging/logging.
     pub source: String,
 }
 
 /// Per-channel entropy information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChannelEntropy {
     pub channel_id: String,
     pub h: f64,
@@ -63,6 +72,7 @@ impl InputSnapshot {
             h_bytes,
             r: None,
             channel_entropies: Vec::new(),
+            criticality_ranking: None,
             source: "minimal".to_string(),
         }
     }
@@ -0,0 +1,456 @@
+// ALEC Complexity - Standalone complexity monitoring
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Batch readers for generic input.
+//!
+//! [`GenericInputReader`] reads either JSON Lines (one [`GenericInput`]
+//! object per line) or CSV with a header row, detected from the first
+//! non-empty line. CSV columns:
+//!
+//! ```text
+//! timestamp_ms,h_bytes,tc,h_joint,r,h_temp,h_humid
+//! 1706000000000,6.5,2.3,8.1,0.45,3.2,2.8
+//! ```
+//!
+//! `timestamp_ms` and `h_bytes` are required; `tc`, `h_joint` and `r` are
+//! optional (an empty cell means absent); `h_<channel>` columns become
+//! per-channel entropies. Fields are separated by commas and are not
+//! quoted.
+
+use super::GenericInput;
+use serde_json::Value;
+use std::fmt;
+use std::io::BufRead;
+
+/// Fields understood in JSON objects and CSV headers.
+const KNOWN_FIELDS: [&str; 6] = ["timestamp_ms", "h_bytes", "tc", "h_joint", "r", "channels"];
+
+/// Prefix of CSV columns holding a channel entropy.
+const CHANNEL_PREFIX: &str = "h_";
+
+/// How to treat bad input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Fail on the first bad row, unknown field or missing `h_bytes`.
+    Strict,
+    /// Skip bad rows, record their errors and ignore unknown fields.
+    #[default]
+    Lenient,
+}
+
+/// Input format detected by [`GenericInputReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+/// What went wrong while parsing generic input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// Invalid JSON or JSON not matching the generic format.
+    Json(String),
+    /// A required field or column is missing or empty.
+    MissingField(String),
+    /// A field or column not part of the generic format (strict mode).
+    UnknownField(String),
+    /// A cell that is not a valid number.
+    InvalidValue { field: String, value: String },
+    /// A CSV row with a different number of cells than the header.
+    ColumnCount { expected: usize, found: usize },
+    /// Reading from the underlying source failed.
+    Io(String),
+}
+
+/// Parse error, with the 1-based line number when read line by line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Line of the offending row, if known.
+    pub line: Option<usize>,
+    /// Error details.
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    /// Create an error without a line number.
+    pub fn new(kind: ParseErrorKind) -> Self {
+        Self { line: None, kind }
+    }
+
+    /// Attach a line number.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::new(ParseErrorKind::Json(err.to_string()))
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Json(msg) => write!(f, "invalid JSON: {}", msg),
+            ParseErrorKind::MissingField(field) => write!(f, "missing field '{}'", field),
+            ParseErrorKind::UnknownField(field) => write!(f, "unknown field '{}'", field),
+            ParseErrorKind::InvalidValue { field, value } => {
+                write!(f, "invalid value for '{}': '{}'", field, value)
+            }
+            ParseErrorKind::ColumnCount { expected, found } => {
+                write!(f, "expected {} columns, found {}", expected, found)
+            }
+            ParseErrorKind::Io(msg) => write!(f, "read error: {}", msg),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse one JSON object, rejecting unknown fields in strict mode.
+pub(crate) fn parse_json_object(json: &str, mode: ParseMode) -> Result<GenericInput, ParseError> {
+    let value: Value = serde_json::from_str(json)?;
+    if let Value::Object(map) = &value {
+        if mode == ParseMode::Strict {
+            if let Some(field) = map.keys().find(|k| !KNOWN_FIELDS.contains(&k.as_str())) {
+                return Err(ParseError::new(ParseErrorKind::UnknownField(field.clone())));
+            }
+        }
+        if !map.contains_key("h_bytes") {
+            return Err(ParseError::new(ParseErrorKind::MissingField(
+                "h_bytes".to_string(),
+            )));
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Parse one CSV row against its header, rejecting unknown columns in
+/// strict mode.
+pub(crate) fn parse_csv_row(
+    headers: &[&str],
+    row: &str,
+    mode: ParseMode,
+) -> Result<GenericInput, ParseError> {
+    let cells: Vec<&str> = row.split(',').map(str::trim).collect();
+    if cells.len() != headers.len() {
+        return Err(ParseError::new(ParseErrorKind::ColumnCount {
+            expected: headers.len(),
+            found: cells.len(),
+        }));
+    }
+
+    let mut timestamp_ms = None;
+    let mut h_bytes = None;
+    let mut input = GenericInput::new(0, 0.0);
+    for (&header, &cell) in headers.iter().zip(&cells) {
+        let header = header.trim();
+        match header {
+            "timestamp_ms" => timestamp_ms = parse_cell::<u64>(header, cell)?,
+            "h_bytes" => h_bytes = parse_cell::<f64>(header, cell)?,
+            "tc" => input.tc = parse_cell(header, cell)?,
+            "h_joint" => input.h_joint = parse_cell(header, cell)?,
+            "r" => input.r = parse_cell(header, cell)?,
+            _ => match header.strip_prefix(CHANNEL_PREFIX) {
+                Some(channel) if !channel.is_empty() => {
+                    if let Some(h) = parse_cell(header, cell)? {
+                        input = input.with_channel(channel, h);
+                    }
+                }
+                _ if mode == ParseMode::Strict => {
+                    return Err(ParseError::new(ParseErrorKind::UnknownField(
+                        header.to_string(),
+                    )));
+                }
+                _ => {}
+            },
+        }
+    }
+
+    input.timestamp_ms = timestamp_ms
+        .ok_or_else(|| ParseError::new(ParseErrorKind::MissingField("timestamp_ms".into())))?;
+    input.h_bytes =
+        h_bytes.ok_or_else(|| ParseError::new(ParseErrorKind::MissingField("h_bytes".into())))?;
+    Ok(input)
+}
+
+/// Parse a numeric cell; an empty cell is `None`.
+fn parse_cell<T: std::str::FromStr>(field: &str, cell: &str) -> Result<Option<T>, ParseError> {
+    if cell.is_empty() {
+        return Ok(None);
+    }
+    cell.parse().map(Some).map_err(|_| {
+        ParseError::new(ParseErrorKind::InvalidValue {
+            field: field.to_string(),
+            value: cell.to_string(),
+        })
+    })
+}
+
+/// Streaming reader for JSON Lines or CSV generic input.
+///
+/// Yields one item per data row. In [`ParseMode::Lenient`] bad rows are
+/// skipped and their errors kept in [`GenericInputReader::errors`]; in
+/// [`ParseMode::Strict`] the first error is yielded and reading stops.
+/// Read failures always stop the reader.
+///
+/// # Example
+///
+/// ```
+/// use alec_complexity::input::{GenericInputReader, InputFormat};
+///
+/// let csv = "timestamp_ms,h_bytes,h_temp\n1000,3.0,1.5\n2000,oops,1.6\n";
+/// let mut reader = GenericInputReader::new(csv.as_bytes());
+/// let inputs = reader.read_all().unwrap();
+///
+/// assert_eq!(reader.format(), Some(InputFormat::Csv));
+/// assert_eq!(inputs.len(), 1);
+/// assert_eq!(reader.errors()[0].line, Some(3));
+/// ```
+pub struct GenericInputReader<R> {
+    reader: R,
+    mode: ParseMode,
+    format: Option<InputFormat>,
+    headers: Vec<String>,
+    line: usize,
+    errors: Vec<ParseError>,
+    done: bool,
+}
+
+impl<R: BufRead> GenericInputReader<R> {
+    /// Create a lenient reader.
+    pub fn new(reader: R) -> Self {
+        Self::with_mode(reader, ParseMode::default())
+    }
+
+    /// Create a reader with an explicit parse mode.
+    pub fn with_mode(reader: R, mode: ParseMode) -> Self {
+        Self {
+            reader,
+            mode,
+            format: None,
+            headers: Vec::new(),
+            line: 0,
+            errors: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Parse mode in use.
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// Detected format, once the first non-empty line has been read.
+    pub fn format(&self) -> Option<InputFormat> {
+        self.format
+    }
+
+    /// Errors of rows skipped in lenient mode, in input order.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Read all remaining rows.
+    ///
+    /// Returns the first error in strict mode, or on a read failure.
+    pub fn read_all(&mut self) -> Result<Vec<GenericInput>, ParseError> {
+        self.by_ref().collect()
+    }
+
+    /// Next non-empty line with its number, or `None` at end of input.
+    fn next_line(&mut self) -> Result<Option<(usize, String)>, ParseError> {
+        let mut buf = String::new();
+        loop {
+            buf.clear();
+            let read = self
+                .reader
+                .read_line(&mut buf)
+                .map_err(|e| ParseError::new(ParseErrorKind::Io(e.to_string())))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            let trimmed = buf.trim();
+            if !trimmed.is_empty() {
+                return Ok(Some((self.line, trimmed.to_string())));
+            }
+        }
+    }
+
+    /// Parse one data line in the detected format.
+    fn parse_line(&self, line: &str) -> Result<GenericInput, ParseError> {
+        match self.format {
+            Some(InputFormat::Csv) => {
+                let headers: Vec<&str> = self.headers.iter().map(String::as_str).collect();
+                parse_csv_row(&headers, line, self.mode)
+            }
+            _ => parse_json_object(line, self.mode),
+        }
+    }
+
+    /// Detect the format from the first line, consuming a CSV header.
+    ///
+    /// Returns `true` if the line was a header rather than data.
+    fn detect(&mut self, line: &str) -> Result<bool, ParseError> {
+        if line.starts_with('{') {
+            self.format = Some(InputFormat::JsonLines);
+            return Ok(false);
+        }
+
+        self.format = Some(InputFormat::Csv);
+        self.headers = line.split(',').map(|h| h.trim().to_string()).collect();
+        if self.mode == ParseMode::Strict {
+            for required in ["timestamp_ms", "h_bytes"] {
+                if !self.headers.iter().any(|h| h == required) {
+                    return Err(ParseError::new(ParseErrorKind::MissingField(
+                        required.to_string(),
+                    )));
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for GenericInputReader<R> {
+    type Item = Result<GenericInput, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let (number, line) = match self.next_line() {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            let parsed = if self.format.is_none() {
+                match self.detect(&line) {
+                    Ok(true) => continue,
+                    Ok(false) => self.parse_line(&line),
+                    Err(e) => Err(e),
+                }
+            } else {
+                self.parse_line(&line)
+            };
+
+            match parsed {
+                Ok(input) => return Some(Ok(input)),
+                Err(e) if self.mode == ParseMode::Strict => {
+                    self.done = true;
+                    return Some(Err(e.at_line(number)));
+                }
+                Err(e) => self.errors.push(e.at_line(number)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_row_columns() {
+        let headers = ["timestamp_ms", "h_bytes", "tc", "r", "h_temp", "h_humid"];
+        let input = GenericInput::from_csv_row(&headers, "1000, 5.5, 2.0, , 3.2, 2.8").unwrap();
+        assert_eq!(input.timestamp_ms, 1000);
+        assert_eq!(input.h_bytes, 5.5);
+        assert_eq!(input.tc, Some(2.0));
+        assert_eq!(input.r, None);
+        assert_eq!(input.channels.len(), 2);
+        assert_eq!(input.channels[0].id, "temp");
+        assert_eq!(input.channels[1].h, 2.8);
+    }
+
+    #[test]
+    fn test_csv_row_errors() {
+        let headers = ["timestamp_ms", "h_bytes"];
+        let err = GenericInput::from_csv_row(&headers, "1000").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::ColumnCount {
+                expected: 2,
+                found: 1
+            }
+        );
+
+        let err = GenericInput::from_csv_row(&headers, "1000,").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingField("h_bytes".into()));
+
+        let headers = ["timestamp_ms", "h_bytes", "site"];
+        let err = parse_csv_row(&headers, "1,2,x", ParseMode::Strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnknownField("site".into()));
+        assert!(parse_csv_row(&headers, "1,2,x", ParseMode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn test_json_array() {
+        let json = r#"[{"timestamp_ms": 1000, "h_bytes": 5.5},
+                       {"timestamp_ms": 2000, "h_bytes": 5.6, "tc": 1.0}]"#;
+        let inputs = GenericInput::from_json_array(json).unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[1].tc, Some(1.0));
+
+        assert!(GenericInput::from_json_array(r#"{"timestamp_ms": 1000}"#).is_err());
+    }
+
+    #[test]
+    fn test_jsonl_detection_and_strict_fields() {
+        let jsonl = "{\"timestamp_ms\": 1000, \"h_bytes\": 5.5}\n\n{\"timestamp_ms\": 2000, \"h_bytes\": 5.6, \"site\": \"a\"}\n";
+
+        let mut reader = GenericInputReader::new(jsonl.as_bytes());
+        assert_eq!(reader.read_all().unwrap().len(), 2);
+        assert_eq!(reader.format(), Some(InputFormat::JsonLines));
+
+        let mut reader = GenericInputReader::with_mode(jsonl.as_bytes(), ParseMode::Strict);
+        let err = reader.read_all().unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert_eq!(err.kind, ParseErrorKind::UnknownField("site".into()));
+    }
+
+    #[test]
+    fn test_strict_csv_requires_h_bytes_column() {
+        let csv = "timestamp_ms,h_temp\n1000,3.0\n";
+        let mut reader = GenericInputReader::with_mode(csv.as_bytes(), ParseMode::Strict);
+        let err = reader.read_all().unwrap_err();
+        assert_eq!(err.line, Some(1));
+        assert_eq!(err.kind, ParseErrorKind::MissingField("h_bytes".into()));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = ParseError::new(ParseErrorKind::InvalidValue {
+            field: "h_bytes".into(),
+            value: "abc".into(),
+        })
+        .at_line(3);
+        assert_eq!(
+            err.to_string(),
+            "line 3: invalid value for 'h_bytes': 'abc'"
+        );
+    }
+}
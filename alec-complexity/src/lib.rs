@@ -102,6 +102,11 @@
 //! - **Cooldown**: Same event type cannot repeat within a cooldown window
 //! - **Severity levels**: Warning and Critical based on threshold levels
 //!
+//! Events can be pushed to [`EventSink`]s registered with
+//! [`ComplexityEngine::add_sink`], each with its own minimum severity.
+//! [`BufferedSink`] collects events for forwarding (webhook, MQTT);
+//! `TracingSink` (feature `logging`) writes them to the `log` facade.
+//!
 //! ## Configuration
 //!
 //! The engine is highly configurable via `ComplexityConfig`:
@@ -132,21 +137,35 @@
 // Core modules
 pub mod anomaly;
 pub mod baseline;
+pub mod binary;
 pub mod config;
+pub mod confirmation;
 pub mod delta;
 pub mod engine;
 pub mod event;
+pub mod history;
 pub mod input;
+pub mod sink;
 pub mod snapshot;
 pub mod structure;
 
 // Re-exports for convenience
+pub use baseline::{BaselineSummary, MetricSummary};
+pub use binary::{BinaryOptions, SnapshotDecodeError};
 pub use config::ComplexityConfig;
 pub use engine::ComplexityEngine;
-pub use event::{ComplexityEvent, EventSeverity, EventType};
-pub use input::{ChannelEntropy, GenericInput, InputAdapter, InputSnapshot};
+pub use event::{ComplexityEvent, EventSeverity, EventStatus, EventType};
+pub use history::{MetricKind, Trend};
+pub use input::{
+    ChannelEntropy, GenericInput, GenericInputReader, InputAdapter, InputSnapshot, ParseError,
+    ParseMode,
+};
+pub use sink::{BufferedSink, EventSink};
 pub use snapshot::ComplexitySnapshot;
 
+#[cfg(feature = "logging")]
+pub use sink::TracingSink;
+
 // Gateway-specific re-exports
 #[cfg(feature = "gateway")]
 pub use input::{GatewayInput, MetricsSnapshotExt};
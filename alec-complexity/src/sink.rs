@@ -0,0 +1,240 @@
+// ALEC Complexity - Standalone complexity monitoring
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Event sinks - push complexity events out of the engine.
+//!
+//! Sinks registered with [`ComplexityEngine::add_sink`] receive every event
+//! at or above their minimum severity at the end of each `process()` call.
+//! Delivery failures (errors and panics) are counted by the engine and never
+//! fail processing.
+//!
+//! [`ComplexityEngine::add_sink`]: crate::ComplexityEngine::add_sink
+
+use crate::event::{ComplexityEvent, EventSeverity};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Destination for complexity events.
+pub trait EventSink: Send {
+    /// Deliver one event.
+    fn emit(&self, event: &ComplexityEvent) -> Result<(), String>;
+
+    /// Lowest severity this sink receives. Defaults to all events.
+    fn min_severity(&self) -> EventSeverity {
+        EventSeverity::Info
+    }
+}
+
+/// Sink logging events through the `log` facade.
+///
+/// Info maps to `info!`, Warning to `warn!` and Critical to `error!`.
+/// `tracing` subscribers pick these up through `tracing-log`.
+#[cfg(feature = "logging")]
+#[derive(Debug, Clone)]
+pub struct TracingSink {
+    min_severity: EventSeverity,
+    target: String,
+}
+
+#[cfg(feature = "logging")]
+impl TracingSink {
+    /// Create a sink logging every event under the `alec_complexity` target.
+    pub fn new() -> Self {
+        Self {
+            min_severity: EventSeverity::Info,
+            target: "alec_complexity".to_string(),
+        }
+    }
+
+    /// Only log events at or above `severity`.
+    pub fn with_min_severity(mut self, severity: EventSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Log under a custom target.
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Log level for a severity.
+    pub fn level_for(severity: EventSeverity) -> log::Level {
+        match severity {
+            EventSeverity::Info => log::Level::Info,
+            EventSeverity::Warning => log::Level::Warn,
+            EventSeverity::Critical => log::Level::Error,
+        }
+    }
+}
+
+#[cfg(feature = "logging")]
+impl Default for TracingSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "logging")]
+impl EventSink for TracingSink {
+    fn emit(&self, event: &ComplexityEvent) -> Result<(), String> {
+        log::log!(
+            target: &self.target,
+            Self::level_for(event.severity),
+            "[{}] {} at {}ms: {}",
+            event.severity.as_str(),
+            event.event_type.as_str(),
+            event.timestamp_ms,
+            event.message
+        );
+        Ok(())
+    }
+
+    fn min_severity(&self) -> EventSeverity {
+        self.min_severity
+    }
+}
+
+/// Bounded in-memory sink for forwarding events elsewhere.
+///
+/// Clones share the same buffer: register one clone with the engine and
+/// keep another to [`drain`](Self::drain) from the gateway or exporter
+/// (e.g. to build webhook payloads or MQTT publishes). When the buffer is
+/// full new events are rejected and counted as delivery errors.
+#[derive(Debug, Clone)]
+pub struct BufferedSink {
+    min_severity: EventSeverity,
+    capacity: usize,
+    buffer: Arc<Mutex<VecDeque<ComplexityEvent>>>,
+}
+
+impl BufferedSink {
+    /// Default buffer capacity.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a sink buffering up to [`Self::DEFAULT_CAPACITY`] events.
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Create a sink buffering up to `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            min_severity: EventSeverity::Info,
+            capacity,
+            buffer: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Only buffer events at or above `severity`.
+    pub fn with_min_severity(mut self, severity: EventSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Take all buffered events, oldest first.
+    pub fn drain(&self) -> Vec<ComplexityEvent> {
+        match self.buffer.lock() {
+            Ok(mut buffer) => buffer.drain(..).collect(),
+            Err(poisoned) => poisoned.into_inner().drain(..).collect(),
+        }
+    }
+
+    /// Number of buffered events.
+    pub fn len(&self) -> usize {
+        match self.buffer.lock() {
+            Ok(buffer) => buffer.len(),
+            Err(poisoned) => poisoned.into_inner().len(),
+        }
+    }
+
+    /// Check if no events are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of buffered events.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Default for BufferedSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventSink for BufferedSink {
+    fn emit(&self, event: &ComplexityEvent) -> Result<(), String> {
+        let mut buffer = self.buffer.lock().map_err(|e| e.to_string())?;
+        if buffer.len() >= self.capacity {
+            return Err(format!("event buffer full ({} events)", self.capacity));
+        }
+        buffer.push_back(event.clone());
+        Ok(())
+    }
+
+    fn min_severity(&self) -> EventSeverity {
+        self.min_severity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffered_sink_drain() {
+        let sink = BufferedSink::new();
+        let handle = sink.clone();
+
+        sink.emit(&ComplexityEvent::baseline_building(1000, 0.5))
+            .unwrap();
+        sink.emit(&ComplexityEvent::baseline_locked(2000)).unwrap();
+        assert_eq!(handle.len(), 2);
+
+        let events = handle.drain();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].timestamp_ms, 1000);
+        assert_eq!(events[1].timestamp_ms, 2000);
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_buffered_sink_capacity() {
+        let sink = BufferedSink::with_capacity(1);
+
+        assert!(sink.emit(&ComplexityEvent::baseline_locked(1000)).is_ok());
+        assert!(sink.emit(&ComplexityEvent::baseline_locked(2000)).is_err());
+        assert_eq!(sink.drain().len(), 1);
+        assert!(sink.emit(&ComplexityEvent::baseline_locked(3000)).is_ok());
+    }
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(EventSeverity::Info < EventSeverity::Warning);
+        assert!(EventSeverity::Warning < EventSeverity::Critical);
+        let sink = BufferedSink::new().with_min_severity(EventSeverity::Critical);
+        assert_eq!(sink.min_severity(), EventSeverity::Critical);
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn test_tracing_sink_levels() {
+        assert_eq!(
+            TracingSink::level_for(EventSeverity::Warning),
+            log::Level::Warn
+        );
+        assert_eq!(
+            TracingSink::level_for(EventSeverity::Critical),
+            log::Level::Error
+        );
+        assert!(TracingSink::new()
+            .emit(&ComplexityEvent::baseline_locked(0))
+            .is_ok());
+    }
+}
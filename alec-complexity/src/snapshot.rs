@@ -6,8 +6,8 @@
 
 //! ComplexitySnapshot - output structure with full state.
 
-use crate::baseline::{Baseline, BaselineState};
-use crate::delta::{Deltas, ZScores};
+use crate::baseline::{Baseline, BaselineState, DEFAULT_PROFILE};
+use crate::delta::{ChannelZScore, Deltas, ZScores};
 use crate::event::ComplexityEvent;
 use crate::structure::SLite;
 use serde::{Deserialize, Serialize};
@@ -39,11 +39,20 @@ pub struct ComplexitySnapshot {
     /// Additional flags.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub flags: Vec<String>,
+    /// Channel ranking checked for criticality shifts, most critical first.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub criticality_ranking: Option<Vec<(String, f64)>>,
+    /// Z-scores of the tracked channels, when channel tracking is enabled.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub channels: Option<Vec<ChannelZScore>>,
 }
 
 /// Summary of baseline state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaselineSummary {
+    /// Name of the baseline profile used for this snapshot.
+    #[serde(default = "default_profile")]
+    pub profile: String,
     /// Current state (building or locked).
     pub state: String,
     /// Number of samples collected.
@@ -55,6 +64,10 @@ pub struct BaselineSummary {
     pub stats: Option<BaselineStats>,
 }
 
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
 /// Baseline statistics summary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaselineStats {
@@ -102,6 +115,8 @@ impl ComplexitySnapshot {
             s_lite,
             events,
             flags,
+            criticality_ranking: None,
+            channels: None,
         }
     }
 
@@ -116,9 +131,29 @@ impl ComplexitySnapshot {
             s_lite: None,
             events,
             flags: vec!["BASELINE_BUILDING".to_string()],
+            criticality_ranking: None,
+            channels: None,
         }
     }
 
+    /// Set the baseline profile name reported in the summary.
+    pub fn with_profile(mut self, profile: &str) -> Self {
+        self.baseline.profile = profile.to_string();
+        self
+    }
+
+    /// Set the ranking checked for criticality shifts.
+    pub fn with_criticality_ranking(mut self, ranking: Option<Vec<(String, f64)>>) -> Self {
+        self.criticality_ranking = ranking;
+        self
+    }
+
+    /// Set the per-channel z-scores.
+    pub fn with_channels(mut self, channels: Option<Vec<ChannelZScore>>) -> Self {
+        self.channels = channels;
+        self
+    }
+
     /// Serialize to JSON.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -180,6 +215,7 @@ impl BaselineSummary {
         };
 
         Self {
+            profile: default_profile(),
             state: state.to_string(),
             sample_count,
             progress,
@@ -5,9 +5,15 @@
 // See LICENSE file for details.
 
 //! S-lite structure summary (lightweight pairwise edges).
+//!
+//! Channels are keyed by `channel_id`: the order in which an input lists
+//! its channels never affects edges or break detection. Break detection
+//! between two snapshots only considers the channels present in both, so
+//! a sensor going offline (or a new one appearing) is reported as a
+//! [`StructureChange`] instead of as spurious edge-weight deltas.
 
 use crate::config::StructureConfig;
-use crate::input::InputSnapshot;
+use crate::input::{ChannelEntropy, InputSnapshot};
 use serde::{Deserialize, Serialize};
 
 /// An edge in the S-lite structure.
@@ -28,6 +34,12 @@ pub struct SLite {
     pub channel_count: usize,
     /// Timestamp of extraction.
     pub timestamp_ms: u64,
+    /// Channels included, sorted by `channel_id`.
+    #[serde(default)]
+    pub channels: Vec<ChannelEntropy>,
+    /// Channel set change relative to the previous S-lite (None for the first).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<StructureChange>,
 }
 
 impl SLite {
@@ -37,6 +49,34 @@ impl SLite {
             (e.channel_a == a && e.channel_b == b) || (e.channel_a == b && e.channel_b == a)
         })
     }
+
+    /// Check if a channel is part of this structure.
+    pub fn has_channel(&self, channel_id: &str) -> bool {
+        self.channels
+            .binary_search_by(|c| c.channel_id.as_str().cmp(channel_id))
+            .is_ok()
+    }
+}
+
+/// Channel set differences between two consecutive S-lite structures.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StructureChange {
+    /// Channels present now but not in the previous structure.
+    pub added_channels: Vec<String>,
+    /// Channels present previously but missing now.
+    pub removed_channels: Vec<String>,
+    /// Channels present in both structures (sorted).
+    pub common_channels: Vec<String>,
+    /// Break detection was skipped: fewer than `min_common_channels`
+    /// channels are shared between the two structures.
+    pub break_detection_skipped: bool,
+}
+
+impl StructureChange {
+    /// True if the channel set did not change.
+    pub fn is_unchanged(&self) -> bool {
+        self.added_channels.is_empty() && self.removed_channels.is_empty()
+    }
 }
 
 /// Structure break event details.
@@ -46,6 +86,12 @@ pub struct StructureBreak {
     pub changed_edges: Vec<EdgeChange>,
     /// Total change magnitude.
     pub total_change: f64,
+    /// Channels that appeared since the previous structure.
+    #[serde(default)]
+    pub added_channels: Vec<String>,
+    /// Channels that disappeared since the previous structure.
+    #[serde(default)]
+    pub removed_channels: Vec<String>,
 }
 
 /// Details of an edge change.
@@ -62,6 +108,8 @@ pub struct EdgeChange {
 pub struct SLiteExtractor {
     config: StructureConfig,
     last_s_lite: Option<SLite>,
+    /// S-lite extracted before `last_s_lite` (for engine break detection).
+    previous_s_lite: Option<SLite>,
 }
 
 impl SLiteExtractor {
@@ -69,6 +117,7 @@ impl SLiteExtractor {
         Self {
             config,
             last_s_lite: None,
+            previous_s_lite: None,
         }
     }
 
@@ -82,38 +131,18 @@ impl SLiteExtractor {
             return None;
         }
 
-        let channels = &input.channel_entropies;
-        let n = channels.len().min(self.config.max_channels);
+        // Key by channel_id so input ordering never matters
+        let mut channels = input.channel_entropies.clone();
+        channels.sort_by(|a, b| a.channel_id.cmp(&b.channel_id));
+        channels.dedup_by(|a, b| a.channel_id == b.channel_id);
+        channels.truncate(self.config.max_channels);
 
+        let n = channels.len();
         if n < 2 {
             return None;
         }
 
-        // Compute all pairwise edges
-        let mut edges = Vec::new();
-        let max_h: f64 = channels.iter().map(|c| c.h).fold(0.0, f64::max);
-
-        for i in 0..n {
-            for j in (i + 1)..n {
-                let h_i = channels[i].h;
-                let h_j = channels[j].h;
-
-                // Weight: similarity based on entropy difference
-                // Closer entropies = higher weight (more similar)
-                let diff = (h_i - h_j).abs();
-                let weight = if max_h > 0.0 {
-                    1.0 - (diff / max_h).min(1.0)
-                } else {
-                    1.0
-                };
-
-                edges.push(SLiteEdge {
-                    channel_a: channels[i].channel_id.clone(),
-                    channel_b: channels[j].channel_id.clone(),
-                    weight,
-                });
-            }
-        }
+        let mut edges = pairwise_edges(&channels);
 
         // Sort by weight (descending)
         edges.sort_by(|a, b| {
@@ -128,43 +157,78 @@ impl SLiteExtractor {
             edges.truncate(self.config.sparsify.top_k_edges);
         }
 
+        let change = self
+            .last_s_lite
+            .as_ref()
+            .map(|last| self.channel_change(&last.channels, &channels));
+
         let s_lite = SLite {
             edges,
             channel_count: n,
             timestamp_ms: input.timestamp_ms,
+            channels,
+            change,
         };
 
         // Store for break detection
         let result = s_lite.clone();
-        self.last_s_lite = Some(s_lite);
+        self.previous_s_lite = self.last_s_lite.replace(s_lite);
 
         Some(result)
     }
 
     /// Detect structure break compared to last S-lite.
+    ///
+    /// Use [`detect_break_since_previous`](Self::detect_break_since_previous)
+    /// to compare the two most recent extractions.
     pub fn detect_break(&self, current: &SLite) -> Option<StructureBreak> {
+        let last = self.last_s_lite.as_ref()?;
+        self.compare(last, current)
+    }
+
+    /// Detect structure break between the two most recent extractions.
+    pub fn detect_break_since_previous(&self) -> Option<StructureBreak> {
+        let previous = self.previous_s_lite.as_ref()?;
+        let current = self.last_s_lite.as_ref()?;
+        self.compare(previous, current)
+    }
+
+    /// Compare two structures over the channels they have in common.
+    fn compare(&self, previous: &SLite, current: &SLite) -> Option<StructureBreak> {
         if !self.config.detect_breaks {
             return None;
         }
 
-        let last = self.last_s_lite.as_ref()?;
+        let change = self.channel_change(&previous.channels, &current.channels);
+        if change.break_detection_skipped {
+            return None;
+        }
+
+        // Recompute both sides over the common set only, so a channel
+        // leaving (or joining) does not shift the normalization.
+        let old_edges = pairwise_edges(&common_entropies(
+            &previous.channels,
+            &change.common_channels,
+        ));
+        let new_edges = pairwise_edges(&common_entropies(
+            &current.channels,
+            &change.common_channels,
+        ));
+
         let mut changed_edges = Vec::new();
         let mut total_change = 0.0;
 
-        // Compare edges
-        for curr_edge in &current.edges {
-            if let Some(last_edge) = last.get_edge(&curr_edge.channel_a, &curr_edge.channel_b) {
-                let delta = (curr_edge.weight - last_edge.weight).abs();
-                if delta >= self.config.break_threshold {
-                    changed_edges.push(EdgeChange {
-                        channel_a: curr_edge.channel_a.clone(),
-                        channel_b: curr_edge.channel_b.clone(),
-                        old_weight: last_edge.weight,
-                        new_weight: curr_edge.weight,
-                        delta,
-                    });
-                    total_change += delta;
-                }
+        for (old_edge, new_edge) in old_edges.iter().zip(new_edges.iter()) {
+            let delta = (new_edge.weight - old_edge.weight).abs();
+            if delta >= self.config.break_threshold {
+                changed_edges.push(EdgeChange {
+                    channel_a: new_edge.channel_a.clone(),
+                    channel_b: new_edge.channel_b.clone(),
+                    old_weight: old_edge.weight,
+                    new_weight: new_edge.weight,
+                    delta,
+                });
+                total_change += delta;
             }
         }
 
@@ -174,10 +238,49 @@ impl SLiteExtractor {
             Some(StructureBreak {
                 changed_edges,
                 total_change,
+                added_channels: change.added_channels,
+                removed_channels: change.removed_channels,
             })
         }
     }
 
+    /// Compute channel set differences (both inputs sorted by channel_id).
+    fn channel_change(
+        &self,
+        previous: &[ChannelEntropy],
+        current: &[ChannelEntropy],
+    ) -> StructureChange {
+        let is_in = |set: &[ChannelEntropy], id: &str| {
+            set.binary_search_by(|c| c.channel_id.as_str().cmp(id))
+                .is_ok()
+        };
+
+        let added_channels = current
+            .iter()
+            .filter(|c| !is_in(previous, &c.channel_id))
+            .map(|c| c.channel_id.clone())
+            .collect();
+        let removed_channels = previous
+            .iter()
+            .filter(|c| !is_in(current, &c.channel_id))
+            .map(|c| c.channel_id.clone())
+            .collect();
+        let common_channels: Vec<String> = current
+            .iter()
+            .filter(|c| is_in(previous, &c.channel_id))
+            .map(|c| c.channel_id.clone())
+            .collect();
+
+        // An edge needs two channels, whatever the configured floor.
+        let min_common = self.config.min_common_channels.max(2);
+        StructureChange {
+            break_detection_skipped: common_channels.len() < min_common,
+            added_channels,
+            removed_channels,
+            common_channels,
+        }
+    }
+
     /// Get last extracted S-lite.
     pub fn last_s_lite(&self) -> Option<&SLite> {
         self.last_s_lite.as_ref()
@@ -186,9 +289,52 @@ impl SLiteExtractor {
     /// Reset state.
     pub fn reset(&mut self) {
         self.last_s_lite = None;
+        self.previous_s_lite = None;
     }
 }
 
+/// Compute all pairwise edges over channels sorted by `channel_id`.
+///
+/// Edges are produced in a stable `(a, b)` order with `a < b`.
+fn pairwise_edges(channels: &[ChannelEntropy]) -> Vec<SLiteEdge> {
+    let n = channels.len();
+    let mut edges = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    let max_h: f64 = channels.iter().map(|c| c.h).fold(0.0, f64::max);
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let h_i = channels[i].h;
+            let h_j = channels[j].h;
+
+            // Weight: similarity based on entropy difference
+            // Closer entropies = higher weight (more similar)
+            let diff = (h_i - h_j).abs();
+            let weight = if max_h > 0.0 {
+                1.0 - (diff / max_h).min(1.0)
+            } else {
+                1.0
+            };
+
+            edges.push(SLiteEdge {
+                channel_a: channels[i].channel_id.clone(),
+                channel_b: channels[j].channel_id.clone(),
+                weight,
+            });
+        }
+    }
+
+    edges
+}
+
+/// Restrict sorted channel entropies to the given (sorted) common ids.
+fn common_entropies(channels: &[ChannelEntropy], common: &[String]) -> Vec<ChannelEntropy> {
+    channels
+        .iter()
+        .filter(|c| common.binary_search(&c.channel_id).is_ok())
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,4 +478,118 @@ mod tests {
         let input = create_test_input(&[("ch1", 2.0), ("ch2", 2.5)]);
         assert!(extractor.extract(&input).is_none());
     }
+
+    fn no_sparsify_config(break_threshold: f64) -> StructureConfig {
+        StructureConfig {
+            break_threshold,
+            sparsify: crate::config::SparsifyConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_channel_order_does_not_matter() {
+        let mut extractor = SLiteExtractor::new(no_sparsify_config(0.1));
+
+        extractor.extract(&create_test_input(&[
+            ("ch1", 2.0),
+            ("ch2", 3.0),
+            ("ch3", 4.0),
+        ]));
+        let s_lite = extractor
+            .extract(&create_test_input(&[
+                ("ch3", 4.0),
+                ("ch1", 2.0),
+                ("ch2", 3.0),
+            ]))
+            .unwrap();
+
+        assert_eq!(s_lite.channels[0].channel_id, "ch1");
+        assert!(s_lite.change.as_ref().unwrap().is_unchanged());
+        assert!(extractor.detect_break_since_previous().is_none());
+    }
+
+    #[test]
+    fn test_dropped_channel_no_break() {
+        let mut extractor = SLiteExtractor::new(no_sparsify_config(0.1));
+
+        // ch3 carries the highest entropy: dropping it would shift the
+        // normalization of the remaining pair if it were not recomputed.
+        extractor.extract(&create_test_input(&[
+            ("ch1", 2.0),
+            ("ch2", 3.0),
+            ("ch3", 10.0),
+        ]));
+        let s_lite = extractor
+            .extract(&create_test_input(&[("ch1", 2.0), ("ch2", 3.0)]))
+            .unwrap();
+
+        let change = s_lite.change.unwrap();
+        assert_eq!(change.removed_channels, vec!["ch3".to_string()]);
+        assert!(change.added_channels.is_empty());
+        assert!(!change.break_detection_skipped);
+        assert!(extractor.detect_break_since_previous().is_none());
+
+        // Channel comes back: reported as added, still no break.
+        let s_lite = extractor
+            .extract(&create_test_input(&[
+                ("ch1", 2.0),
+                ("ch2", 3.0),
+                ("ch3", 10.0),
+            ]))
+            .unwrap();
+        assert_eq!(
+            s_lite.change.unwrap().added_channels,
+            vec!["ch3".to_string()]
+        );
+        assert!(extractor.detect_break_since_previous().is_none());
+    }
+
+    #[test]
+    fn test_dropped_channel_remaining_pair_changed() {
+        let mut extractor = SLiteExtractor::new(no_sparsify_config(0.1));
+
+        extractor.extract(&create_test_input(&[
+            ("ch1", 2.0),
+            ("ch2", 2.0),
+            ("ch3", 3.0),
+        ]));
+        extractor.extract(&create_test_input(&[("ch1", 2.0), ("ch2", 5.0)]));
+
+        let brk = extractor.detect_break_since_previous().unwrap();
+        assert_eq!(brk.changed_edges.len(), 1);
+        assert_eq!(brk.changed_edges[0].channel_a, "ch1");
+        assert_eq!(brk.changed_edges[0].channel_b, "ch2");
+        assert_eq!(brk.removed_channels, vec!["ch3".to_string()]);
+    }
+
+    #[test]
+    fn test_min_common_channels_skips_detection() {
+        let config = StructureConfig {
+            min_common_channels: 3,
+            ..no_sparsify_config(0.1)
+        };
+        let mut extractor = SLiteExtractor::new(config);
+
+        extractor.extract(&create_test_input(&[
+            ("ch1", 2.0),
+            ("ch2", 2.0),
+            ("ch3", 3.0),
+        ]));
+        let s_lite = extractor
+            .extract(&create_test_input(&[
+                ("ch1", 2.0),
+                ("ch2", 5.0),
+                ("ch4", 1.0),
+            ]))
+            .unwrap();
+
+        let change = s_lite.change.unwrap();
+        assert!(change.break_detection_skipped);
+        assert_eq!(change.common_channels.len(), 2);
+        assert!(extractor.detect_break_since_previous().is_none());
+    }
 }
@@ -437,6 +437,7 @@ fn test_25_s_lite_sparsification() {
         },
         detect_breaks: true,
         break_threshold: 0.3,
+        min_common_channels: 2,
     };
 
     let mut extractor = SLiteExtractor::new(config);
@@ -467,6 +468,7 @@ fn test_26_s_lite_break_detection() {
         },
         detect_breaks: true,
         break_threshold: 0.1,
+        min_common_channels: 2,
     };
 
     let mut extractor = SLiteExtractor::new(config);
@@ -506,6 +508,7 @@ fn test_27_s_lite_no_break_when_similar() {
         },
         detect_breaks: true,
         break_threshold: 0.5,
+        min_common_channels: 2,
     };
 
     let mut extractor = SLiteExtractor::new(config);
@@ -734,6 +737,8 @@ fn test_36_structure_break_event() {
             delta: 0.4,
         }],
         total_change: 0.4,
+        added_channels: vec![],
+        removed_channels: vec![],
     };
 
     let event = ComplexityEvent::structure_break(1000, break_info);
@@ -915,3 +920,602 @@ fn test_47_full_pipeline_integration() {
     assert!(json.contains("version"));
     assert!(json.contains("baseline"));
 }
+
+// ============================================================================
+// Section 10: Retrospective Confirmation Tests (3 tests)
+// ============================================================================
+
+/// Engine with retrospective confirmation and a baseline around h_bytes = 3.0.
+fn create_confirming_engine() -> ComplexityEngine {
+    let mut config = create_enabled_config();
+    config.anomaly.cooldown_ms = 60_000;
+    config.confirmation = ConfirmationConfig {
+        enabled: true,
+        window_snapshots: 10,
+        review_after_snapshots: 3,
+        min_held_fraction: 0.5,
+    };
+    let mut engine = ComplexityEngine::new(config);
+    for (i, h_bytes) in [2.9, 3.0, 3.1].into_iter().enumerate() {
+        engine.process(&create_input(i as u64 * 1000, h_bytes));
+    }
+    assert!(engine.is_baseline_locked());
+    engine
+}
+
+/// Payload entropy spike events emitted while processing `h_bytes` values.
+fn payload_spike_events(engine: &mut ComplexityEngine, h_bytes: &[f64]) -> Vec<ComplexityEvent> {
+    let mut events = Vec::new();
+    for (i, &h) in h_bytes.iter().enumerate() {
+        let result = engine.process(&create_input(10_000 + i as u64 * 1000, h));
+        events.extend(
+            result
+                .unwrap()
+                .events
+                .into_iter()
+                .filter(|e| e.event_type == EventType::PayloadEntropySpike),
+        );
+    }
+    events
+}
+
+fn review_original_id(event: &ComplexityEvent) -> u64 {
+    match event.details {
+        event::EventDetails::Review { original_id, .. } => original_id,
+        _ => panic!("Wrong details type"),
+    }
+}
+
+#[test]
+fn test_48_single_spike_is_retracted() {
+    let mut engine = create_confirming_engine();
+    let events = payload_spike_events(&mut engine, &[10.0, 3.0, 3.0, 3.0]);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].status, EventStatus::Provisional);
+    assert_eq!(events[1].status, EventStatus::Retracted);
+    assert_eq!(events[1].severity, EventSeverity::Info);
+    assert_eq!(review_original_id(&events[1]), events[0].id);
+    assert!(events[1].id > events[0].id);
+}
+
+#[test]
+fn test_49_sustained_spike_is_confirmed() {
+    let mut engine = create_confirming_engine();
+    let events = payload_spike_events(&mut engine, &[10.0, 10.0, 10.0, 10.0]);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].status, EventStatus::Provisional);
+    assert_eq!(events[1].status, EventStatus::Confirmed);
+    assert_eq!(events[1].severity, events[0].severity);
+    assert_eq!(review_original_id(&events[1]), events[0].id);
+}
+
+#[test]
+fn test_50_confirmation_off_by_default() {
+    let mut config = create_enabled_config();
+    config.anomaly.cooldown_ms = 60_000;
+    let mut engine = ComplexityEngine::new(config);
+    for (i, h_bytes) in [2.9, 3.0, 3.1].into_iter().enumerate() {
+        engine.process(&create_input(i as u64 * 1000, h_bytes));
+    }
+
+    let events = payload_spike_events(&mut engine, &[10.0, 3.0, 3.0, 3.0]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, EventStatus::Final);
+    assert!(events[0].id > 0);
+}
+
+// ============================================================================
+// Section 11: Batch Input Parsing Tests (2 tests)
+// ============================================================================
+
+/// CSV export with one malformed row (line 3) among good ones.
+const MIXED_CSV: &str = "\
+timestamp_ms,h_bytes,tc,h_joint,h_temp,h_humid
+1000,3.0,1.2,1.7,1.5,1.4
+2000,not-a-number,1.2,1.7,1.5,1.4
+3000,3.1,,1.8,1.6,1.3
+4000,3.2,1.1,1.9,1.5,1.5
+";
+
+#[test]
+fn test_51_lenient_csv_skips_bad_rows() {
+    let mut reader = GenericInputReader::with_mode(MIXED_CSV.as_bytes(), ParseMode::Lenient);
+    let inputs = reader.read_all().unwrap();
+
+    assert_eq!(reader.format(), Some(input::InputFormat::Csv));
+    assert_eq!(
+        inputs.iter().map(|i| i.timestamp_ms).collect::<Vec<_>>(),
+        vec![1000, 3000, 4000]
+    );
+    assert_eq!(inputs[1].tc, None);
+    assert_eq!(inputs[2].channels.len(), 2);
+
+    assert_eq!(reader.errors().len(), 1);
+    assert_eq!(reader.errors()[0].line, Some(3));
+
+    // Rows 1000 and 4000 carry tc and h_joint: two valid signals
+    let mut config = create_enabled_config();
+    config.baseline.min_valid_snapshots = 2;
+    let mut engine = ComplexityEngine::new(config);
+    for input in &inputs {
+        engine.process(&input.to_snapshot());
+    }
+    assert!(engine.is_baseline_locked());
+}
+
+#[test]
+fn test_52_strict_csv_fails_fast() {
+    let mut reader = GenericInputReader::with_mode(MIXED_CSV.as_bytes(), ParseMode::Strict);
+
+    assert!(reader.next().unwrap().is_ok());
+    let err = reader.next().unwrap().unwrap_err();
+    assert_eq!(err.line, Some(3));
+    assert!(reader.next().is_none());
+
+    let mut reader = GenericInputReader::with_mode(MIXED_CSV.as_bytes(), ParseMode::Strict);
+    assert!(reader.read_all().is_err());
+    assert!(reader.errors().is_empty());
+}
+
+// ============================================================================
+// Section 12: Baseline Exclusion and Robust Mode Tests (2 tests)
+// ============================================================================
+
+/// Nominal h_bytes with a deterministic wobble.
+fn nominal_h_bytes(i: u64) -> f64 {
+    5.0 + 0.2 * (i as f64 * 1.7).sin()
+}
+
+/// Feed one input per second until the baseline locks. Inputs 10 to 14
+/// are a fault-ridden spike when `spike` is set.
+fn lock_baseline(engine: &mut ComplexityEngine, spike: bool) -> baseline::Baseline {
+    let mut i = 0;
+    while !engine.is_baseline_locked() {
+        let h_bytes = if spike && (10..15).contains(&i) {
+            50.0
+        } else {
+            nominal_h_bytes(i)
+        };
+        engine.process(&create_input(i * 1000, h_bytes));
+        i += 1;
+        assert!(i < 1000, "baseline never locked");
+    }
+    engine.baseline().clone()
+}
+
+fn exclusion_config(robust: bool) -> ComplexityConfig {
+    let mut config = create_enabled_config();
+    config.baseline.min_valid_snapshots = 40;
+    config.baseline.robust = robust;
+    config
+}
+
+#[test]
+fn test_53_spike_in_build_window_excluded_or_robust() {
+    let clean = lock_baseline(&mut ComplexityEngine::new(exclusion_config(false)), false);
+    let naive = lock_baseline(&mut ComplexityEngine::new(exclusion_config(false)), true);
+
+    let mut engine = ComplexityEngine::new(exclusion_config(false));
+    engine.mark_exclusion(10_000, 15_000).unwrap();
+    let excluded = lock_baseline(&mut engine, true);
+
+    let robust_clean = lock_baseline(&mut ComplexityEngine::new(exclusion_config(true)), false);
+    let robust = lock_baseline(&mut ComplexityEngine::new(exclusion_config(true)), true);
+
+    // The spike bakes into the naive baseline
+    assert!(naive.h_bytes.mean - clean.h_bytes.mean > 4.0);
+    assert!(naive.h_bytes.std > 10.0 * clean.h_bytes.std);
+
+    for (built, reference) in [(&excluded, &clean), (&robust, &robust_clean)] {
+        for (a, b) in [
+            (&built.h_bytes, &reference.h_bytes),
+            (&built.tc, &reference.tc),
+            (&built.h_joint, &reference.h_joint),
+        ] {
+            // Within a fraction of the clean spread; the spike is ~300 of them
+            assert!(
+                (a.mean - b.mean).abs() < 0.5 * b.std,
+                "mean {} vs {} (std {})",
+                a.mean,
+                b.mean,
+                b.std
+            );
+            assert!(
+                (a.std - b.std).abs() < 0.5 * b.std,
+                "std {} vs {}",
+                a.std,
+                b.std
+            );
+        }
+    }
+    assert_eq!(excluded.h_bytes.count, 40);
+}
+
+#[test]
+fn test_54_exclusions_survive_baseline_reset() {
+    let mut engine = ComplexityEngine::new(create_enabled_config());
+    assert!(engine.mark_exclusion(5_000, 5_000).is_err());
+    engine.mark_exclusion(5_000, 8_000).unwrap();
+
+    for i in 0..3 {
+        engine.process(&create_input(i * 1000, 3.0 + i as f64 * 0.1));
+    }
+    assert!(engine.is_baseline_locked());
+    let snapshot = engine.process(&create_input(6_000, 9.0)).unwrap();
+    assert!(snapshot
+        .flags
+        .contains(&"BASELINE_INPUT_EXCLUDED".to_string()));
+    assert!(snapshot.z_scores.is_some());
+
+    engine.baseline_reset();
+    assert!(!engine.is_baseline_locked());
+    assert_eq!(engine.exclusions().len(), 1);
+
+    // Still ignored while rebuilding
+    let snapshot = engine.process(&create_input(7_000, 9.0)).unwrap();
+    assert!(snapshot.flags.contains(&"BASELINE_BUILDING".to_string()));
+    assert!(snapshot
+        .flags
+        .contains(&"BASELINE_INPUT_EXCLUDED".to_string()));
+    assert_eq!(engine.baseline().h_bytes.count, 0);
+
+    let snapshot = engine.process(&create_input(8_000, 3.0)).unwrap();
+    assert!(!snapshot
+        .flags
+        .contains(&"BASELINE_INPUT_EXCLUDED".to_string()));
+    assert_eq!(engine.baseline().h_bytes.count, 1);
+
+    engine.clear_exclusions();
+    assert!(engine.exclusions().is_empty());
+}
+
+// ============================================================================
+// Section 13: Criticality Ranking Tests (3 tests)
+// ============================================================================
+
+/// Input at `timestamp_ms` ranking `channels` by the given scores.
+fn ranked_input(timestamp_ms: u64, channels: &[(&str, f64)]) -> input::InputSnapshot {
+    channels
+        .iter()
+        .fold(
+            GenericInput::new(timestamp_ms, 3.0),
+            |input, (id, score)| input.with_criticality(id, *score),
+        )
+        .with_tc(1.0)
+        .with_h_joint(2.0)
+        .with_r(0.5)
+        .build()
+}
+
+/// Engine with a locked baseline, fed `rankings` one second apart.
+/// Returns the criticality shift events of each snapshot.
+fn criticality_shifts(
+    config: ComplexityConfig,
+    rankings: &[&[(&str, f64)]],
+) -> Vec<Vec<ComplexityEvent>> {
+    let mut engine = ComplexityEngine::new(config);
+    for i in 0..3 {
+        engine.process(&create_input(i * 1000, 3.0 + i as f64 * 0.1));
+    }
+    assert!(engine.is_baseline_locked());
+
+    rankings
+        .iter()
+        .enumerate()
+        .map(|(i, ranking)| {
+            let snapshot = engine
+                .process(&ranked_input(10_000 + i as u64 * 1000, ranking))
+                .unwrap();
+            snapshot
+                .events
+                .into_iter()
+                .filter(|e| e.event_type == EventType::CriticalityShift)
+                .collect()
+        })
+        .collect()
+}
+
+const RANKED: &[(&str, f64)] = &[("pump", 0.9), ("valve", 0.5), ("temp", 0.1)];
+const FIRST_AND_THIRD_SWAPPED: &[(&str, f64)] = &[("pump", 0.1), ("valve", 0.5), ("temp", 0.9)];
+
+#[test]
+fn test_55_stable_ranking_no_criticality_shift() {
+    // Scores drift but the order holds
+    let drifted: &[(&str, f64)] = &[("pump", 0.8), ("valve", 0.6), ("temp", 0.2)];
+    let shifts = criticality_shifts(create_enabled_config(), &[RANKED, drifted, RANKED]);
+    assert!(shifts.iter().all(|events| events.is_empty()));
+}
+
+#[test]
+fn test_56_swapped_ranking_emits_one_shift() {
+    let mut engine = ComplexityEngine::new(create_enabled_config());
+    for i in 0..3 {
+        engine.process(&create_input(i * 1000, 3.0 + i as f64 * 0.1));
+    }
+    engine.process(&ranked_input(10_000, RANKED));
+
+    let snapshot = engine
+        .process(&ranked_input(11_000, FIRST_AND_THIRD_SWAPPED))
+        .unwrap();
+    let shifts: Vec<_> = snapshot
+        .events
+        .iter()
+        .filter(|e| e.event_type == EventType::CriticalityShift)
+        .collect();
+    assert_eq!(shifts.len(), 1);
+    match &shifts[0].details {
+        event::EventDetails::CriticalityRanking { old_top, new_top } => {
+            assert_eq!(old_top, &["pump", "valve", "temp"]);
+            assert_eq!(new_top, &["temp", "valve", "pump"]);
+        }
+        other => panic!("unexpected details: {:?}", other),
+    }
+
+    // The snapshot carries the ranking it acted on
+    let json: serde_json::Value = serde_json::from_str(&snapshot.to_json().unwrap()).unwrap();
+    assert_eq!(json["criticality_ranking"][0][0], "temp");
+    assert_eq!(json["criticality_ranking"].as_array().unwrap().len(), 3);
+
+    // The new ranking is the reference now
+    let snapshot = engine
+        .process(&ranked_input(12_000, FIRST_AND_THIRD_SWAPPED))
+        .unwrap();
+    assert!(!snapshot
+        .events
+        .iter()
+        .any(|e| e.event_type == EventType::CriticalityShift));
+}
+
+#[test]
+fn test_57_criticality_thresholds_and_persistence() {
+    let adjacent_swap: &[(&str, f64)] = &[("pump", 0.5), ("valve", 0.9), ("temp", 0.1)];
+
+    // One discordant pair out of three is within a 0.5 distance
+    let mut config = create_enabled_config();
+    config.criticality.max_kendall_distance = 0.5;
+    let shifts = criticality_shifts(
+        config.clone(),
+        &[RANKED, adjacent_swap, FIRST_AND_THIRD_SWAPPED],
+    );
+    assert!(shifts[1].is_empty());
+    assert_eq!(shifts[2].len(), 1);
+
+    // The new order must hold for the persistence time
+    config.anomaly.persistence_ms = 2_000;
+    let shifts = criticality_shifts(
+        config,
+        &[
+            RANKED,
+            FIRST_AND_THIRD_SWAPPED,
+            RANKED,
+            FIRST_AND_THIRD_SWAPPED,
+            FIRST_AND_THIRD_SWAPPED,
+            FIRST_AND_THIRD_SWAPPED,
+        ],
+    );
+    let counts: Vec<usize> = shifts.iter().map(Vec::len).collect();
+    assert_eq!(counts, vec![0, 0, 0, 0, 0, 1]);
+}
+
+// ============================================================================
+// Section 14: Snapshot Retention Tests (2 tests)
+// ============================================================================
+
+fn retaining_config(max_snapshots: usize) -> ComplexityConfig {
+    ComplexityConfig {
+        retention: Some(RetentionConfig {
+            max_snapshots,
+            drop_s_lite: true,
+            ..Default::default()
+        }),
+        ..create_enabled_config()
+    }
+}
+
+#[test]
+fn test_58_retention_keeps_latest_snapshots() {
+    let mut engine = ComplexityEngine::new(retaining_config(10));
+    for i in 0..20u64 {
+        engine.process(&create_input_with_channels(
+            i * 1000,
+            3.0 + (i % 2) as f64 * 0.1,
+        ));
+    }
+
+    let timestamps: Vec<u64> = engine.history().map(|s| s.timestamp_ms).collect();
+    assert_eq!(timestamps, (10..20).map(|i| i * 1000).collect::<Vec<_>>());
+    assert!(engine.history().all(|s| s.s_lite.is_none()));
+    assert!(engine.last_output().unwrap().s_lite.is_some());
+    assert!(engine
+        .events_since(15_000)
+        .iter()
+        .all(|e| e.timestamp_ms >= 15_000));
+
+    // Without retention there is no history
+    let mut engine = ComplexityEngine::new(create_enabled_config());
+    engine.process(&create_input(1000, 3.0));
+    assert_eq!(engine.history().count(), 0);
+    assert!(engine.trend(MetricKind::HBytes, 60_000).is_none());
+}
+
+#[test]
+fn test_59_trend_of_increasing_h_bytes() {
+    let mut engine = ComplexityEngine::new(retaining_config(100));
+    // Baseline around 3.0, then +0.01 per second
+    for i in 0..5u64 {
+        engine.process(&create_input(i * 1000, 3.0 + (i % 2) as f64 * 0.1));
+    }
+    for i in 5..60u64 {
+        engine.process(&create_input(i * 1000, 3.0 + 0.01 * i as f64));
+    }
+
+    let trend = engine.trend(MetricKind::HBytes, 30_000).unwrap();
+    assert_eq!(trend.samples, 31);
+    assert!((trend.slope - 0.01).abs() < 1e-3, "slope {}", trend.slope);
+    assert!(trend.r2 > 0.99, "r2 {}", trend.r2);
+}
+
+// ============================================================================
+// Section 15: Locked Baseline Warm-Start Tests (1 test)
+// ============================================================================
+
+#[test]
+fn test_60_locked_baseline_skips_building() {
+    let means = [("tc", 0.9), ("h_joint", 1.8), ("h_bytes", 3.0), ("r", 0.45)];
+    let stds = [
+        ("tc", 0.03),
+        ("h_joint", 0.06),
+        ("h_bytes", 0.1),
+        ("r", 0.0),
+    ];
+    let to_map = |pairs: &[(&str, f64)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+    let summary = BaselineSummary::from_dataset_stats(&to_map(&means), &to_map(&stds)).unwrap();
+    assert_eq!(summary.zero_std_metrics(), vec![MetricKind::R]);
+
+    let mut engine = ComplexityEngine::with_locked_baseline(create_enabled_config(), &summary)
+        .expect("summary has every metric");
+    assert!(engine.is_baseline_locked());
+
+    // First input is already scored, and no baseline event is emitted
+    let first = engine.process(&create_input(1000, 3.0)).unwrap();
+    assert!(first.is_baseline_locked());
+    assert!(first.z_scores.is_some());
+    assert!(first.events.iter().all(|e| !matches!(
+        e.event_type,
+        EventType::BaselineBuilding | EventType::BaselineLocked
+    )));
+
+    let surge = engine.process(&create_input(2000, 4.0)).unwrap();
+    let types: Vec<EventType> = surge.events.iter().map(|e| e.event_type).collect();
+    assert!(types.contains(&EventType::PayloadEntropySpike));
+    assert!(types.contains(&EventType::ComplexitySurge));
+    // R has no spread, so it never drops
+    assert!(!types.contains(&EventType::RedundancyDrop));
+
+    let mut no_r = summary;
+    no_r.r = None;
+    assert!(ComplexityEngine::with_locked_baseline(create_enabled_config(), &no_r).is_err());
+}
+
+// ============================================================================
+// Section 16: Per-Channel Entropy Tests (2 tests)
+// ============================================================================
+
+fn channel_config(track: bool) -> ComplexityConfig {
+    let mut config = create_enabled_config();
+    config.baseline.min_valid_snapshots = 10;
+    config.anomaly.cooldown_ms = 60_000;
+    config.channels.enabled = track;
+    config
+}
+
+/// Three channels varying slightly; from `spike_from`, "humidity" jumps.
+fn three_channel_input(i: u64, spike_from: u64) -> input::InputSnapshot {
+    let wobble = (i % 3) as f64 * 0.05;
+    let humidity = if i >= spike_from { 3.0 } else { 1.2 + wobble };
+    GenericInput::new(i * 1000, 3.0 + wobble)
+        .with_tc(1.0 + wobble)
+        .with_h_joint(2.0 + wobble)
+        .with_r(0.5 + wobble)
+        .with_channel("temp", 1.0 + wobble)
+        .with_channel("humidity", humidity)
+        .with_channel("pressure", 0.8 + wobble)
+        .build()
+}
+
+#[test]
+fn test_61_channel_spike_names_the_channel() {
+    let run = |track: bool| {
+        let mut engine = ComplexityEngine::new(channel_config(track));
+        (0..13)
+            .filter_map(|i| engine.process(&three_channel_input(i, 10)))
+            .collect::<Vec<_>>()
+    };
+    let tracked = run(true);
+    let untracked = run(false);
+
+    let channel_events: Vec<&ComplexityEvent> = tracked
+        .iter()
+        .flat_map(|s| &s.events)
+        .filter(|e| e.event_type == EventType::ChannelEntropyAnomaly)
+        .collect();
+    // The spike persists, but the cooldown holds it to one event
+    assert_eq!(channel_events.len(), 1);
+    assert_eq!(channel_events[0].timestamp_ms, 10_000);
+    assert_eq!(channel_events[0].severity, EventSeverity::Critical);
+    match &channel_events[0].details {
+        event::EventDetails::ChannelZScore { channel_id, z, .. } => {
+            assert_eq!(channel_id, "humidity");
+            assert!(*z > 4.0);
+        }
+        other => panic!("unexpected details {:?}", other),
+    }
+
+    // The aggregate path is unaffected
+    for (a, b) in tracked.iter().zip(&untracked) {
+        assert_eq!(
+            serde_json::to_value(&a.z_scores).unwrap(),
+            serde_json::to_value(&b.z_scores).unwrap()
+        );
+        let aggregate: Vec<EventType> = a
+            .events
+            .iter()
+            .map(|e| e.event_type)
+            .filter(|t| *t != EventType::ChannelEntropyAnomaly)
+            .collect();
+        let plain: Vec<EventType> = b.events.iter().map(|e| e.event_type).collect();
+        assert_eq!(aggregate, plain);
+        assert!(b.channels.is_none());
+    }
+
+    // The per-channel section is in the JSON, and only with tracking on
+    let spiked = &tracked[10];
+    assert_eq!(spiked.channels.as_ref().unwrap().len(), 3);
+    let json = spiked.to_json().unwrap();
+    let section =
+        |json: &str| serde_json::from_str::<serde_json::Value>(json).unwrap()["channels"].clone();
+    assert!(section(&json).is_array());
+    let back = ComplexitySnapshot::from_json(&json).unwrap();
+    let ids = |s: &ComplexitySnapshot| {
+        s.channels
+            .iter()
+            .flatten()
+            .map(|c| c.channel_id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&back), ["temp", "humidity", "pressure"]);
+    assert!(section(&untracked[10].to_json().unwrap()).is_null());
+}
+
+#[test]
+fn test_62_channel_tracking_capped() {
+    let mut config = channel_config(true);
+    config.structure.max_channels = 2;
+    let mut engine = ComplexityEngine::new(config);
+    let outputs: Vec<ComplexitySnapshot> = (0..12)
+        .filter_map(|i| engine.process(&three_channel_input(i, u64::MAX)))
+        .collect();
+
+    assert!(outputs
+        .iter()
+        .take(10)
+        .all(|s| s.flags.iter().any(|f| f == "CHANNEL_LIMIT_REACHED")));
+    let last = outputs.last().unwrap();
+    assert!(last.is_baseline_locked());
+    let scored: Vec<&str> = last
+        .channels
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|c| c.channel_id.as_str())
+        .collect();
+    assert_eq!(scored, ["temp", "humidity"]);
+    assert_eq!(engine.baseline().channels.len(), 2);
+}
@@ -0,0 +1,58 @@
+// ALEC Complexity - Portable Tests
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Browser dashboard path on the feature set that builds for
+//! `wasm32-unknown-unknown` (`alec` without `std-fs` and `std-time`):
+//! decode messages client-side, then run anomaly detection on metrics
+//! derived from them.
+
+use alec::{Classifier, Context, Decoder, Encoder, RawData};
+use alec_complexity::config::*;
+use alec_complexity::*;
+
+#[test]
+fn test_decode_then_process() {
+    let classifier = Classifier::default();
+    let (mut encoder, mut decoder) = (Encoder::new(), Decoder::new());
+    let (mut emitter, mut receiver) = (Context::new(), Context::new());
+    let mut engine = ComplexityEngine::new(ComplexityConfig {
+        enabled: true,
+        baseline: BaselineConfig {
+            build_time_ms: 0,
+            min_valid_snapshots: 5,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let mut last = None;
+    for i in 0..20u64 {
+        let data = RawData::new(20.0 + (i % 5) as f64 * 0.5, i * 1000);
+        let classification = classifier.classify(&data, &emitter);
+        let message = encoder.encode(&data, &classification, &emitter);
+        emitter.observe_classified(&data, &classification);
+
+        let decoded = decoder.decode(&message, &receiver).unwrap();
+        assert!((decoded.value - data.value).abs() < 0.01);
+        receiver.observe_with_priority(
+            &RawData::new(decoded.value, decoded.timestamp),
+            decoded.priority,
+        );
+
+        // Message size stands in for the payload entropy
+        let input = GenericInput::new(data.timestamp, message.len() as f64)
+            .with_tc(0.5)
+            .with_h_joint(1.0 + decoded.value / 100.0)
+            .with_r(0.2)
+            .build();
+        last = engine.process(&input);
+        assert!(last.is_some());
+    }
+
+    let snapshot = last.unwrap();
+    assert_eq!(snapshot.timestamp_ms, 19_000);
+    assert!(snapshot.baseline.stats.is_some());
+}
@@ -0,0 +1,17 @@
+// ALEC E2E - End-to-end pipeline tests
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! # ALEC E2E - End-to-end pipeline tests
+//!
+//! Tests only, not published: `tests/pipeline.rs` runs a generated
+//! dataset through the whole advertised pipeline, from
+//! [`alec-testdata`] sensors through an `alec-gateway` and a lossy
+//! simulated link to a `FrameDecoder` and `alec-complexity`, with context
+//! sync flowing back.
+//!
+//! Run with `cargo test -p alec-e2e`.
+//!
+//! [`alec-testdata`]: https://github.com/zeekmartin/alec-codec/tree/main/alec-testdata
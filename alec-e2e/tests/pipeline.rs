@@ -0,0 +1,272 @@
+//! The whole pipeline, on a seeded agriculture dataset with one injected
+//! anomaly:
+//! - `alec-testdata` sensors pushed into a gateway with metrics enabled,
+//!   every channel preloaded from a day of training data
+//! - frames over a simulated link losing 5% of them, but corrupting
+//!   none, so channels send no per-message checksums
+//! - a `FrameDecoder` reconstructing values, with context sync flowing
+//!   back over a link with the same loss
+//! - the gateway's metrics snapshots fed to a complexity engine
+//!
+//! Checks value fidelity at 5% loss, context agreement once sync settles,
+//! the expected complexity event within the anomaly's window and the
+//! compression ratio against a recorded floor.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use alec::context::trainer::{Trainer, TrainerConfig};
+use alec::{Context, LinkDirection, LinkModel, RawData, SimulatedLink, SyncConfig};
+use alec_complexity::{ComplexityConfig, ComplexityEngine, ComplexityEvent, MetricsSnapshotExt};
+use alec_gateway::metrics::{MetricsConfig, SignalComputeSchedule, SignalWindow};
+use alec_gateway::{ChannelConfig, FrameDecoder, Gateway, GatewayConfig, SyncFrame};
+use alec_testdata::industries::agriculture::{create_farm_sensors, AgriculturalScenario};
+use alec_testdata::manifest::AnomalyManifest;
+use alec_testdata::{generate_dataset, AnomalyConfig, AnomalyType, Dataset, GeneratorConfig};
+
+const SAMPLE_INTERVAL_MS: u64 = 60_000;
+const LOSS_RATE: f32 = 0.05;
+/// Rows per flush, and per self-describing message: with one in every
+/// frame a lost frame costs its own samples only
+const FLUSH_ROWS: usize = 10;
+/// Decoded values within one quantization step count as faithful
+const TOLERANCE: f64 = 0.01;
+/// Raw sample bytes over frame bytes; observed at 1.16, most of the
+/// overhead being the message headers and the prediction state carried by
+/// every frame
+///
+/// Guarded for the configuration a lossy radio link runs, not for the
+/// channel defaults: the link drops corrupted frames itself (as the
+/// LoRaWAN CRC and MIC do), so channels skip the per-message checksum,
+/// and they need a self-describing message per frame to survive the loss
+/// (without one, only about half the values decode). With the default
+/// checksums on, the same run measures 0.94.
+const COMPRESSION_FLOOR: f64 = 1.1;
+/// Sync rounds allowed for the contexts to agree after the last frame
+const SETTLE_ROUNDS: usize = 20;
+
+fn dataset(seed: u64, hours: f64, anomaly: Option<&AnomalyConfig>) -> Dataset {
+    let mut sensors = create_farm_sensors(AgriculturalScenario::Normal);
+    if let Some(anomaly) = anomaly {
+        for sensor in &mut sensors {
+            if sensor.id == "soil_moisture" {
+                sensor.anomaly = Some(anomaly.clone());
+            }
+        }
+    }
+    let config = GeneratorConfig::new()
+        .with_sample_interval_secs(SAMPLE_INTERVAL_MS / 1000)
+        .with_duration_hours(hours)
+        .with_seed(seed);
+    generate_dataset(&config, &sensors)
+}
+
+/// Train one preload per sensor on `training` (anomaly-free, another
+/// seed), written under `dir`
+fn train_preloads(training: &Dataset, dir: &tempfile::TempDir) -> HashMap<String, PathBuf> {
+    let mut paths = HashMap::new();
+    for id in training.sensor_ids() {
+        let samples: Vec<RawData> = training
+            .rows()
+            .iter()
+            .filter_map(|row| Some(RawData::new(row.get(id)?, row.timestamp_ms)))
+            .collect();
+        let mut trainer = Trainer::new(TrainerConfig::default());
+        trainer.feed(&samples);
+        let path = dir.path().join(format!("{}.alec-context", id));
+        trainer.finish(id).unwrap().save_to_file(&path).unwrap();
+        paths.insert(id.clone(), path);
+    }
+    paths
+}
+
+/// Exchange announces and responses once over `link`
+fn sync_round(
+    gateway: &mut Gateway,
+    decoder: &mut FrameDecoder,
+    link: &mut SimulatedLink<Vec<u8>>,
+) {
+    let announces = gateway.sync_announces();
+    if !announces.is_empty() {
+        link.send(LinkDirection::Uplink, SyncFrame::new(announces).to_bytes());
+    }
+    while let Some(bytes) = link.receive(LinkDirection::Uplink) {
+        let responses = decoder.handle_sync(&bytes).unwrap();
+        if !responses.is_empty() {
+            link.send(
+                LinkDirection::Downlink,
+                SyncFrame::new(responses).to_bytes(),
+            );
+        }
+    }
+    while let Some(bytes) = link.receive(LinkDirection::Downlink) {
+        gateway.handle_sync(&bytes).unwrap();
+    }
+}
+
+fn contexts_agree(gateway: &Gateway, decoder: &FrameDecoder, ids: &[String]) -> bool {
+    ids.iter().all(|id| {
+        decoder.context(id).map(Context::hash)
+            == gateway.channel_context(id).ok().map(Context::hash)
+    })
+}
+
+#[test]
+fn test_pipeline() {
+    let anomaly = AnomalyConfig::new(AnomalyType::decorrelate(20.0, 5.0), 360).with_duration(60);
+    let manifest = AnomalyManifest::from_config("soil_moisture", &anomaly);
+    let preload_dir = tempfile::tempdir().unwrap();
+    let preloads = train_preloads(&dataset(7, 24.0, None), &preload_dir);
+    let dataset = dataset(42, 8.0, Some(&anomaly));
+    let ids = dataset.sensor_ids();
+
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 4096,
+        sync: SyncConfig {
+            announce_interval: 6,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    gateway.enable_metrics(MetricsConfig {
+        enabled: true,
+        signal_compute: SignalComputeSchedule::EveryNFlushes(1),
+        signal_window: SignalWindow::TimeMillis(2 * 3_600_000),
+        ..Default::default()
+    });
+    let mut decoder = FrameDecoder::new();
+    for id in ids {
+        let preload = &preloads[id];
+        gateway
+            .add_channel(
+                id,
+                ChannelConfig {
+                    buffer_size: 2 * FLUSH_ROWS,
+                    self_describing_interval: FLUSH_ROWS as u16,
+                    enable_checksum: false,
+                    preload_path: Some(preload.display().to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        decoder.add_context(id, Context::load_from_file(preload).unwrap());
+        decoder.set_checksum(id, false);
+    }
+    assert!(contexts_agree(&gateway, &decoder, ids));
+
+    let mut frames = SimulatedLink::<Vec<u8>>::new(LinkModel::Ideal).with_loss_rate(LOSS_RATE);
+    let mut sync = SimulatedLink::<Vec<u8>>::new(LinkModel::Ideal).with_loss_rate(LOSS_RATE);
+    let mut complexity = ComplexityEngine::new(ComplexityConfig {
+        enabled: true,
+        ..Default::default()
+    });
+    let mut events: Vec<ComplexityEvent> = Vec::new();
+    let mut last_snapshot = None;
+
+    let rows = dataset.rows();
+    let mut sent = HashMap::new();
+    let (mut delivered, mut faithful, mut raw_bytes, mut frame_bytes) = (0, 0, 0, 0);
+    for (i, row) in rows.iter().enumerate() {
+        for (id, value) in row.iter() {
+            if let Some(value) = value {
+                gateway.push(id, value, row.timestamp_ms).unwrap();
+                sent.insert((id.to_string(), row.timestamp_ms), value);
+                raw_bytes += RawData::new(value, row.timestamp_ms).raw_size();
+            }
+        }
+        if (i + 1) % FLUSH_ROWS != 0 && i + 1 != rows.len() {
+            continue;
+        }
+
+        let now = row.timestamp_ms;
+        frames.advance_to(now);
+        sync.advance_to(now);
+        let report = gateway.flush_with_report_at(now).unwrap();
+        assert!(report.errors.is_empty());
+        let bytes = report.frame.to_bytes();
+        frame_bytes += bytes.len();
+        frames.send(LinkDirection::Uplink, bytes);
+
+        if let Some(snapshot) = gateway.last_metrics() {
+            if last_snapshot != Some(snapshot.timestamp_ms) {
+                last_snapshot = Some(snapshot.timestamp_ms);
+                if let Some(output) = complexity.process(&snapshot.to_complexity_input()) {
+                    events.extend(output.events);
+                }
+            }
+        }
+
+        while let Some(bytes) = frames.receive(LinkDirection::Uplink) {
+            for entry in decoder.decode(&bytes).unwrap() {
+                let data = entry.data.unwrap();
+                for (value, timestamp) in decoder.decode_values(&entry.id, &data).unwrap() {
+                    delivered += 1;
+                    if (value - sent[&(entry.id.clone(), timestamp)]).abs() <= TOLERANCE {
+                        faithful += 1;
+                    }
+                }
+            }
+        }
+        sync_round(&mut gateway, &mut decoder, &mut sync);
+    }
+
+    // (1) Value fidelity for more than 99% of what got through
+    let total = sent.len();
+    assert!(
+        delivered * 100 > total * 90,
+        "{} of {} delivered",
+        delivered,
+        total
+    );
+    assert!(
+        faithful * 100 > delivered * 99,
+        "{} of {} faithful",
+        faithful,
+        delivered
+    );
+
+    // (2) Every channel's contexts agree once sync settles
+    let mut now = rows[rows.len() - 1].timestamp_ms;
+    for _ in 0..SETTLE_ROUNDS {
+        if contexts_agree(&gateway, &decoder, ids) {
+            break;
+        }
+        now += SAMPLE_INTERVAL_MS;
+        sync.advance_to(now);
+        sync_round(&mut gateway, &mut decoder, &mut sync);
+    }
+    for id in ids {
+        assert_eq!(
+            decoder.context(id).map(Context::hash),
+            gateway.channel_context(id).ok().map(Context::hash),
+            "context of {}",
+            id
+        );
+    }
+
+    // (3) The expected event, within the anomaly's window and not before
+    let start_ms = rows[manifest.start_sample].timestamp_ms;
+    let end_ms = rows[manifest.start_sample + manifest.duration_samples.unwrap()].timestamp_ms
+        + FLUSH_ROWS as u64 * SAMPLE_INTERVAL_MS;
+    let expected: Vec<u64> = events
+        .iter()
+        .filter(|event| event.event_type.as_str() == manifest.expected_event)
+        .map(|event| event.timestamp_ms)
+        .collect();
+    assert!(!expected.is_empty(), "no {} event", manifest.expected_event);
+    assert!(
+        expected.iter().all(|&ts| ts >= start_ms),
+        "{} before the anomaly",
+        manifest.expected_event
+    );
+    assert!(
+        expected.iter().any(|&ts| ts <= end_ms),
+        "no {} within the anomaly's window",
+        manifest.expected_event
+    );
+
+    // (4) Compression ratio above the recorded floor
+    let ratio = raw_bytes as f64 / frame_bytes as f64;
+    assert!(ratio > COMPRESSION_FLOOR, "compression ratio {:.3}", ratio);
+}
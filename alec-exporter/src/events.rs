@@ -0,0 +1,301 @@
+// ALEC Exporter - Complexity event store
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! In-memory store of complexity events for incident review.
+//!
+//! Prometheus counters only tell how many anomaly events fired. The
+//! [`EventStore`] keeps the `ComplexityEvent` payloads produced by the
+//! replay engine and by live ingest in a bounded ring buffer, served as
+//! JSON by `GET /events` and pushed live by `GET /events/stream`
+//! (Server-Sent Events).
+//!
+//! Every stored event gets an id from the store's own sequence. Engine ids
+//! restart when the replay loops and differ between gateways; store ids
+//! never repeat, and the SSE `id:` field carries the same value as the
+//! JSON `id`, so both endpoints can be correlated.
+
+use alec_complexity::{ComplexityEvent, EventSink};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Default number of events kept by the store.
+pub const DEFAULT_EVENT_CAPACITY: usize = 1000;
+
+/// Pending events of a slow SSE subscriber before it starts skipping.
+const STREAM_BUFFER: usize = 256;
+
+/// An event as kept by the store.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredEvent {
+    /// Store-wide event id, stable across replay loops.
+    pub id: u64,
+    /// Where the event came from: `replay` or the ingesting `gateway_id`.
+    pub source: String,
+    /// The event as produced by the complexity engine.
+    pub event: ComplexityEvent,
+}
+
+/// Filters of `GET /events`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventQuery {
+    /// Only events with `timestamp_ms >= since_ms`.
+    pub since_ms: Option<u64>,
+    /// Only events of this severity (`Info`/`INFO`, `Warning`/`WARN`,
+    /// `Critical`/`CRIT`, case-insensitive).
+    pub severity: Option<String>,
+    /// Only events of this type (`PayloadEntropySpike` or
+    /// `PAYLOAD_ENTROPY_SPIKE`, case-insensitive).
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    /// Keep at most this many events, the most recent ones.
+    pub limit: Option<usize>,
+}
+
+impl EventQuery {
+    fn matches(&self, stored: &StoredEvent) -> bool {
+        let event = &stored.event;
+        let name_matches = |filter: &Option<String>, debug: String, wire: &str| {
+            filter.as_ref().is_none_or(|filter| {
+                filter.eq_ignore_ascii_case(&debug) || filter.eq_ignore_ascii_case(wire)
+            })
+        };
+        self.since_ms
+            .is_none_or(|since| event.timestamp_ms >= since)
+            && name_matches(
+                &self.severity,
+                format!("{:?}", event.severity),
+                event.severity.as_str(),
+            )
+            && name_matches(
+                &self.event_type,
+                format!("{:?}", event.event_type),
+                event.event_type.as_str(),
+            )
+    }
+}
+
+/// Ring buffer state behind the store lock.
+#[derive(Debug)]
+struct Ring {
+    events: VecDeque<StoredEvent>,
+    next_id: u64,
+}
+
+/// Bounded, thread-safe store of complexity events.
+///
+/// Written from the replay task and the ingest paths, read from the HTTP
+/// handlers. Once `capacity` events are held, each new event evicts the
+/// oldest one.
+#[derive(Debug)]
+pub struct EventStore {
+    capacity: usize,
+    ring: Mutex<Ring>,
+    live: broadcast::Sender<StoredEvent>,
+}
+
+impl EventStore {
+    /// Create a store keeping at most `capacity` events (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (live, _) = broadcast::channel(STREAM_BUFFER);
+        Self {
+            capacity,
+            ring: Mutex::new(Ring {
+                events: VecDeque::with_capacity(capacity),
+                next_id: 1,
+            }),
+            live,
+        }
+    }
+
+    /// Maximum number of events kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of events currently kept.
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.ring.lock().unwrap().events.len()
+    }
+
+    /// Store an event from `source`, returning its store id.
+    pub fn push(&self, source: &str, event: ComplexityEvent) -> u64 {
+        let stored = {
+            let mut ring = self.ring.lock().unwrap();
+            let stored = StoredEvent {
+                id: ring.next_id,
+                source: source.to_string(),
+                event,
+            };
+            ring.next_id += 1;
+            if ring.events.len() == self.capacity {
+                ring.events.pop_front();
+            }
+            ring.events.push_back(stored.clone());
+            stored
+        };
+        let id = stored.id;
+        // No subscriber is not an error
+        let _ = self.live.send(stored);
+        id
+    }
+
+    /// Store several events from the same `source`.
+    pub fn extend<'a>(&self, source: &str, events: impl IntoIterator<Item = &'a ComplexityEvent>) {
+        for event in events {
+            self.push(source, event.clone());
+        }
+    }
+
+    /// Events matching `query`, oldest first.
+    pub fn query(&self, query: &EventQuery) -> Vec<StoredEvent> {
+        let ring = self.ring.lock().unwrap();
+        let mut events: Vec<StoredEvent> = ring
+            .events
+            .iter()
+            .filter(|stored| query.matches(stored))
+            .cloned()
+            .collect();
+        if let Some(limit) = query.limit {
+            let excess = events.len().saturating_sub(limit);
+            events.drain(..excess);
+        }
+        events
+    }
+
+    /// Receive events as they are stored.
+    pub fn subscribe(&self) -> broadcast::Receiver<StoredEvent> {
+        self.live.subscribe()
+    }
+}
+
+impl Default for EventStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+/// Complexity engine sink writing into an [`EventStore`].
+pub struct StoreSink {
+    store: Arc<EventStore>,
+    source: String,
+}
+
+impl StoreSink {
+    /// Sink storing every event under `source`.
+    pub fn new(store: Arc<EventStore>, source: impl Into<String>) -> Self {
+        Self {
+            store,
+            source: source.into(),
+        }
+    }
+}
+
+impl EventSink for StoreSink {
+    fn emit(&self, event: &ComplexityEvent) -> Result<(), String> {
+        self.store.push(&self.source, event.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alec_complexity::event::EventDetails;
+    use alec_complexity::{EventSeverity, EventType};
+
+    fn event(event_type: EventType, severity: EventSeverity, timestamp_ms: u64) -> ComplexityEvent {
+        ComplexityEvent::new(
+            event_type,
+            severity,
+            timestamp_ms,
+            "test event",
+            EventDetails::None,
+        )
+    }
+
+    #[test]
+    fn test_ring_buffer_evicts_oldest() {
+        let store = EventStore::new(3);
+        for i in 0..5 {
+            store.push(
+                "replay",
+                event(EventType::PayloadEntropySpike, EventSeverity::Warning, i),
+            );
+        }
+
+        let events = store.query(&EventQuery::default());
+        assert_eq!(store.len(), 3);
+        assert_eq!(
+            events.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+        assert_eq!(events[0].event.timestamp_ms, 2);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let store = EventStore::default();
+        store.push(
+            "replay",
+            event(EventType::BaselineLocked, EventSeverity::Info, 100),
+        );
+        store.push(
+            "gw-01",
+            event(EventType::RedundancyDrop, EventSeverity::Critical, 200),
+        );
+        store.push(
+            "replay",
+            event(EventType::PayloadEntropySpike, EventSeverity::Warning, 300),
+        );
+        store.push(
+            "replay",
+            event(EventType::RedundancyDrop, EventSeverity::Critical, 400),
+        );
+
+        let query =
+            |query: EventQuery| -> Vec<u64> { store.query(&query).iter().map(|e| e.id).collect() };
+        let severity = |s: &str| EventQuery {
+            severity: Some(s.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query(severity("critical")), vec![2, 4]);
+        assert_eq!(query(severity("WARN")), vec![3]);
+        assert!(query(severity("bogus")).is_empty());
+        assert_eq!(
+            query(EventQuery {
+                event_type: Some("REDUNDANCY_DROP".to_string()),
+                since_ms: Some(300),
+                ..Default::default()
+            }),
+            vec![4]
+        );
+        assert_eq!(
+            query(EventQuery {
+                limit: Some(2),
+                ..Default::default()
+            }),
+            vec![3, 4]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_store_ids() {
+        let store = EventStore::default();
+        let mut live = store.subscribe();
+        let id = store.push(
+            "replay",
+            event(EventType::BaselineLocked, EventSeverity::Info, 1),
+        );
+
+        let received = live.recv().await.unwrap();
+        assert_eq!(received.id, id);
+        assert_eq!(store.query(&EventQuery::default())[0].id, id);
+    }
+}
@@ -0,0 +1,527 @@
+// ALEC Exporter - Live metrics ingest
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Live ingest of gateway metrics over HTTP and UDP.
+//!
+//! Gateways push the `MetricsSnapshot` JSON produced by alec-gateway,
+//! optionally together with the `ComplexitySnapshot` JSON produced by
+//! alec-complexity, wrapped in an envelope carrying their `gateway_id`:
+//!
+//! ```json
+//! {"gateway_id": "gw-01", "metrics": { ... }, "complexity": { ... }}
+//! ```
+//!
+//! Each payload updates the `alec_ingest_*` gauges labeled with that
+//! `gateway_id`. Instances that stop sending for longer than the TTL
+//! have their value series removed and `alec_instance_up` set to 0 (or
+//! removed as well with `forget_stale`), so Prometheus never scrapes
+//! frozen values from a dead gateway.
+//!
+//! The events of ingested complexity snapshots are kept in the
+//! [`EventStore`] given with [`IngestRegistry::with_event_store`], under the
+//! sending `gateway_id`. Their S-lite structures update the [`ViewStore`]
+//! given with [`IngestRegistry::with_view_store`].
+
+use crate::events::EventStore;
+use crate::metrics::{
+    ResilienceZone, INGEST_BASELINE_LOCKED, INGEST_BASELINE_PROGRESS, INGEST_CHANNEL_ENTROPY_BITS,
+    INGEST_JOINT_ENTROPY_BITS, INGEST_LAST_SEEN_SECONDS, INGEST_PAYLOAD_ENTROPY_BITS,
+    INGEST_REQUESTS_TOTAL, INGEST_RESILIENCE_INDEX, INGEST_RESILIENCE_ZONE,
+    INGEST_SUM_ENTROPY_BITS, INGEST_TOTAL_CORRELATION_BITS, INGEST_ZSCORE, INSTANCE_UP,
+};
+use crate::views::ViewStore;
+use alec_complexity::snapshot::SNAPSHOT_VERSION;
+use alec_complexity::ComplexitySnapshot;
+use alec_gateway::MetricsSnapshot;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+use tracing::{debug, info, warn};
+
+/// Maximum length of a `gateway_id` label value.
+pub const MAX_GATEWAY_ID_LEN: usize = 64;
+
+/// Largest UDP datagram accepted by the listener.
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+/// Metric labels used by the z-score gauge.
+const ZSCORE_METRICS: [&str; 4] = ["tc", "h_joint", "h_bytes", "r"];
+
+/// Ingest envelope sent by a gateway.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestPayload {
+    /// Caller-supplied instance identifier (`instance` is accepted too).
+    #[serde(alias = "instance")]
+    pub gateway_id: String,
+    /// Gateway metrics snapshot.
+    pub metrics: MetricsSnapshot,
+    /// Optional complexity snapshot.
+    #[serde(default)]
+    pub complexity: Option<ComplexitySnapshot>,
+}
+
+/// Ingest configuration.
+#[derive(Debug, Clone)]
+pub struct IngestConfig {
+    /// Time without ingest after which an instance is considered stale.
+    pub ttl: Duration,
+    /// Also remove `alec_instance_up` for stale instances instead of
+    /// reporting 0.
+    pub forget_stale: bool,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            forget_stale: false,
+        }
+    }
+}
+
+/// Ingest errors.
+#[derive(Debug, thiserror::Error)]
+pub enum IngestError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid gateway_id: {0}")]
+    InvalidGatewayId(String),
+
+    #[error("Unsupported metrics schema version {found} (expected {expected})")]
+    MetricsVersion { found: u32, expected: u32 },
+
+    #[error("Unsupported complexity snapshot version {found} (expected {expected})")]
+    ComplexityVersion { found: String, expected: String },
+}
+
+/// Per-instance bookkeeping.
+#[derive(Debug)]
+struct InstanceState {
+    last_seen: Instant,
+    channels: BTreeSet<String>,
+    up: bool,
+}
+
+/// Tracks ingesting instances and updates their Prometheus series.
+#[derive(Debug)]
+pub struct IngestRegistry {
+    config: IngestConfig,
+    instances: Mutex<HashMap<String, InstanceState>>,
+    events: Option<Arc<EventStore>>,
+    views: Option<Arc<ViewStore>>,
+}
+
+impl IngestRegistry {
+    /// Create a new registry.
+    pub fn new(config: IngestConfig) -> Self {
+        Self {
+            config,
+            instances: Mutex::new(HashMap::new()),
+            events: None,
+            views: None,
+        }
+    }
+
+    /// Keep the complexity events of ingested snapshots in `store`.
+    pub fn with_event_store(mut self, store: Arc<EventStore>) -> Self {
+        self.events = Some(store);
+        self
+    }
+
+    /// Keep the S-lite structures of ingested snapshots in `store`.
+    pub fn with_view_store(mut self, store: Arc<ViewStore>) -> Self {
+        self.views = Some(store);
+        self
+    }
+
+    /// Parse, validate and apply a raw JSON payload received now.
+    ///
+    /// `transport` is used as a label on `alec_ingest_requests_total`.
+    /// Returns the `gateway_id` on success.
+    pub fn ingest_bytes(&self, bytes: &[u8], transport: &str) -> Result<String, IngestError> {
+        let result = serde_json::from_slice::<IngestPayload>(bytes)
+            .map_err(IngestError::from)
+            .and_then(|payload| {
+                let id = payload.gateway_id.clone();
+                self.ingest_at(&payload, Instant::now()).map(|_| id)
+            });
+        let outcome = if result.is_ok() { "ok" } else { "rejected" };
+        INGEST_REQUESTS_TOTAL
+            .with_label_values(&[transport, outcome])
+            .inc();
+        result
+    }
+
+    /// Validate and apply a payload as if received at `now`.
+    pub fn ingest_at(&self, payload: &IngestPayload, now: Instant) -> Result<(), IngestError> {
+        validate(payload)?;
+        let id = payload.gateway_id.as_str();
+
+        let mut instances = self.instances.lock().unwrap();
+        let state = instances
+            .entry(id.to_string())
+            .or_insert_with(|| InstanceState {
+                last_seen: now,
+                channels: BTreeSet::new(),
+                up: true,
+            });
+        state.last_seen = now;
+        state.up = true;
+
+        apply_metrics(id, &payload.metrics, &mut state.channels);
+        if let Some(ref complexity) = payload.complexity {
+            apply_complexity(id, complexity);
+            if let Some(ref events) = self.events {
+                events.extend(id, &complexity.events);
+            }
+            if let Some(ref views) = self.views {
+                views.record_complexity(id, complexity);
+            }
+        }
+
+        INSTANCE_UP.with_label_values(&[id]).set(1.0);
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        INGEST_LAST_SEEN_SECONDS
+            .with_label_values(&[id])
+            .set(unix_secs);
+
+        debug!("Ingested snapshot from {}", id);
+        Ok(())
+    }
+
+    /// Expire instances not seen within the TTL as of `now`.
+    ///
+    /// Returns the ids that became stale during this call.
+    pub fn expire(&self, now: Instant) -> Vec<String> {
+        let mut instances = self.instances.lock().unwrap();
+        let mut expired = Vec::new();
+
+        for (id, state) in instances.iter_mut() {
+            if state.up && now.saturating_duration_since(state.last_seen) > self.config.ttl {
+                remove_value_series(id, &state.channels);
+                state.channels.clear();
+                state.up = false;
+                if self.config.forget_stale {
+                    let _ = INSTANCE_UP.remove_label_values(&[id.as_str()]);
+                } else {
+                    INSTANCE_UP.with_label_values(&[id.as_str()]).set(0.0);
+                }
+                expired.push(id.clone());
+            }
+        }
+
+        if self.config.forget_stale {
+            instances.retain(|_, state| state.up);
+        }
+        for id in &expired {
+            info!("Instance {} is stale, dropped its series", id);
+        }
+        expired
+    }
+
+    /// Number of instances currently reporting.
+    #[allow(dead_code)]
+    pub fn live_instances(&self) -> usize {
+        self.instances
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|s| s.up)
+            .count()
+    }
+}
+
+/// Listen for ingest payloads on a UDP socket (one JSON per datagram).
+pub async fn run_udp(registry: Arc<IngestRegistry>, addr: SocketAddr) -> std::io::Result<()> {
+    let socket = UdpSocket::bind(addr).await?;
+    info!("UDP ingest listening on {}", addr);
+
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, peer) = socket.recv_from(&mut buf).await?;
+        if let Err(e) = registry.ingest_bytes(&buf[..len], "udp") {
+            warn!("Rejected UDP ingest from {}: {}", peer, e);
+        }
+    }
+}
+
+fn validate(payload: &IngestPayload) -> Result<(), IngestError> {
+    let id = &payload.gateway_id;
+    let valid_chars = id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    if id.is_empty() || id.len() > MAX_GATEWAY_ID_LEN || !valid_chars {
+        return Err(IngestError::InvalidGatewayId(id.clone()));
+    }
+
+    if payload.metrics.version != MetricsSnapshot::VERSION {
+        return Err(IngestError::MetricsVersion {
+            found: payload.metrics.version,
+            expected: MetricsSnapshot::VERSION,
+        });
+    }
+
+    if let Some(ref complexity) = payload.complexity {
+        if complexity.version != SNAPSHOT_VERSION {
+            return Err(IngestError::ComplexityVersion {
+                found: complexity.version.clone(),
+                expected: SNAPSHOT_VERSION.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_metrics(id: &str, snapshot: &MetricsSnapshot, channels: &mut BTreeSet<String>) {
+    let signal = &snapshot.signal;
+    let (tc, h_joint, sum_h) = if signal.valid {
+        (signal.total_corr, signal.h_joint, signal.sum_h)
+    } else {
+        (0.0, 0.0, 0.0)
+    };
+    let (r, zone) = match snapshot.resilience {
+        Some(ref resilience) => (
+            resilience.r.unwrap_or(0.0),
+            resilience.zone.as_deref().unwrap_or("healthy"),
+        ),
+        None => (0.0, "healthy"),
+    };
+
+    INGEST_RESILIENCE_INDEX.with_label_values(&[id]).set(r);
+    INGEST_RESILIENCE_ZONE
+        .with_label_values(&[id])
+        .set(ResilienceZone::from(zone) as i64 as f64);
+    INGEST_TOTAL_CORRELATION_BITS
+        .with_label_values(&[id])
+        .set(tc);
+    INGEST_JOINT_ENTROPY_BITS
+        .with_label_values(&[id])
+        .set(h_joint);
+    INGEST_PAYLOAD_ENTROPY_BITS
+        .with_label_values(&[id])
+        .set(snapshot.payload.h_bytes);
+    INGEST_SUM_ENTROPY_BITS.with_label_values(&[id]).set(sum_h);
+
+    // Replace the channel set so removed channels do not linger
+    let current: BTreeSet<String> = if signal.valid {
+        signal
+            .h_per_channel
+            .iter()
+            .map(|c| c.channel_id.clone())
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+    for gone in channels.difference(&current) {
+        let _ = INGEST_CHANNEL_ENTROPY_BITS.remove_label_values(&[id, gone.as_str()]);
+    }
+    if signal.valid {
+        for channel in &signal.h_per_channel {
+            INGEST_CHANNEL_ENTROPY_BITS
+                .with_label_values(&[id, channel.channel_id.as_str()])
+                .set(channel.h);
+        }
+    }
+    *channels = current;
+}
+
+fn apply_complexity(id: &str, snapshot: &ComplexitySnapshot) {
+    INGEST_BASELINE_PROGRESS
+        .with_label_values(&[id])
+        .set(snapshot.baseline.progress);
+    INGEST_BASELINE_LOCKED
+        .with_label_values(&[id])
+        .set(if snapshot.is_baseline_locked() {
+            1.0
+        } else {
+            0.0
+        });
+
+    if let Some(ref z) = snapshot.z_scores {
+        let values = [z.tc, z.h_joint, Some(z.h_bytes), z.r];
+        for (metric, value) in ZSCORE_METRICS.iter().zip(values) {
+            match value {
+                Some(v) => INGEST_ZSCORE.with_label_values(&[id, *metric]).set(v),
+                None => {
+                    let _ = INGEST_ZSCORE.remove_label_values(&[id, *metric]);
+                }
+            }
+        }
+    }
+}
+
+fn remove_value_series(id: &str, channels: &BTreeSet<String>) {
+    for gauge in [
+        &*INGEST_LAST_SEEN_SECONDS,
+        &*INGEST_RESILIENCE_INDEX,
+        &*INGEST_RESILIENCE_ZONE,
+        &*INGEST_TOTAL_CORRELATION_BITS,
+        &*INGEST_JOINT_ENTROPY_BITS,
+        &*INGEST_PAYLOAD_ENTROPY_BITS,
+        &*INGEST_SUM_ENTROPY_BITS,
+        &*INGEST_BASELINE_PROGRESS,
+        &*INGEST_BASELINE_LOCKED,
+    ] {
+        let _ = gauge.remove_label_values(&[id]);
+    }
+    for channel in channels {
+        let _ = INGEST_CHANNEL_ENTROPY_BITS.remove_label_values(&[id, channel.as_str()]);
+    }
+    for metric in ZSCORE_METRICS {
+        let _ = INGEST_ZSCORE.remove_label_values(&[id, metric]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    fn payload_json(gateway_id: &str, h_bytes: f64) -> String {
+        serde_json::json!({
+            "gateway_id": gateway_id,
+            "metrics": {
+                "version": MetricsSnapshot::VERSION,
+                "timestamp_ms": 1000,
+                "window": {
+                    "kind": "time_ms",
+                    "value": 60000,
+                    "aligned_samples": 10,
+                    "channels_included": 2
+                },
+                "signal": {
+                    "valid": true,
+                    "log_base": "log2",
+                    "h_per_channel": [
+                        {"channel_id": "temp", "h": 1.5},
+                        {"channel_id": "hum", "h": 2.0}
+                    ],
+                    "sum_h": 3.5,
+                    "h_joint": 3.0,
+                    "total_corr": 0.5
+                },
+                "payload": {"frame_size_bytes": 12, "h_bytes": h_bytes},
+                "resilience": {"enabled": true, "r": 0.14, "zone": "healthy"},
+                "flags": []
+            }
+        })
+        .to_string()
+    }
+
+    fn parse(json: &str) -> IngestPayload {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_ingest_two_gateways() {
+        let registry = IngestRegistry::new(IngestConfig::default());
+
+        registry
+            .ingest_bytes(payload_json("test-gw-a", 4.0).as_bytes(), "http")
+            .unwrap();
+        registry
+            .ingest_bytes(payload_json("test-gw-b", 5.0).as_bytes(), "http")
+            .unwrap();
+
+        let output = encode_metrics();
+        assert!(output.contains(r#"alec_ingest_payload_entropy_bits{gateway_id="test-gw-a"} 4"#));
+        assert!(output.contains(r#"alec_ingest_payload_entropy_bits{gateway_id="test-gw-b"} 5"#));
+        assert!(output.contains(r#"alec_instance_up{gateway_id="test-gw-a"} 1"#));
+        assert_eq!(registry.live_instances(), 2);
+    }
+
+    #[test]
+    fn test_ttl_expiry_removes_stale_instance() {
+        let ttl = Duration::from_secs(60);
+        let registry = IngestRegistry::new(IngestConfig {
+            ttl,
+            forget_stale: false,
+        });
+        let t0 = Instant::now();
+
+        registry
+            .ingest_at(&parse(&payload_json("ttl-gw-old", 4.0)), t0)
+            .unwrap();
+        registry
+            .ingest_at(&parse(&payload_json("ttl-gw-new", 5.0)), t0 + ttl)
+            .unwrap();
+
+        let expired = registry.expire(t0 + ttl + Duration::from_secs(1));
+        assert_eq!(expired, vec!["ttl-gw-old".to_string()]);
+        assert_eq!(registry.live_instances(), 1);
+
+        let output = encode_metrics();
+        assert!(!output.contains(r#"alec_ingest_payload_entropy_bits{gateway_id="ttl-gw-old"}"#));
+        assert!(!output.contains(r#"gateway_id="ttl-gw-old",channel="temp""#));
+        assert!(output.contains(r#"alec_instance_up{gateway_id="ttl-gw-old"} 0"#));
+        assert!(output.contains(r#"alec_ingest_payload_entropy_bits{gateway_id="ttl-gw-new"} 5"#));
+
+        // A new ingest brings the instance back
+        registry
+            .ingest_at(
+                &parse(&payload_json("ttl-gw-old", 4.5)),
+                t0 + ttl + Duration::from_secs(2),
+            )
+            .unwrap();
+        assert_eq!(registry.live_instances(), 2);
+    }
+
+    #[test]
+    fn test_forget_stale_removes_up_series() {
+        let ttl = Duration::from_secs(1);
+        let registry = IngestRegistry::new(IngestConfig {
+            ttl,
+            forget_stale: true,
+        });
+        let t0 = Instant::now();
+
+        registry
+            .ingest_at(&parse(&payload_json("forget-gw", 4.0)), t0)
+            .unwrap();
+        assert_eq!(registry.expire(t0 + Duration::from_secs(2)).len(), 1);
+
+        let output = encode_metrics();
+        assert!(!output.contains(r#"gateway_id="forget-gw""#));
+    }
+
+    #[test]
+    fn test_rejects_invalid_payloads() {
+        let registry = IngestRegistry::new(IngestConfig::default());
+
+        let mut bad_version = parse(&payload_json("reject-gw", 4.0));
+        bad_version.metrics.version = MetricsSnapshot::VERSION + 1;
+        assert!(matches!(
+            registry.ingest_at(&bad_version, Instant::now()),
+            Err(IngestError::MetricsVersion { .. })
+        ));
+
+        let bad_id = parse(&payload_json("bad id\"}", 4.0));
+        assert!(matches!(
+            registry.ingest_at(&bad_id, Instant::now()),
+            Err(IngestError::InvalidGatewayId(_))
+        ));
+
+        assert!(matches!(
+            registry.ingest_bytes(b"{not json", "udp"),
+            Err(IngestError::Json(_))
+        ));
+        assert_eq!(registry.live_instances(), 0);
+    }
+
+    #[test]
+    fn test_instance_alias() {
+        let json = payload_json("alias-gw", 4.0).replace("\"gateway_id\"", "\"instance\"");
+        assert_eq!(parse(&json).gateway_id, "alias-gw");
+    }
+}
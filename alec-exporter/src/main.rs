@@ -14,33 +14,62 @@
 //! # Run with a CSV dataset
 //! alec-exporter --csv dataset.csv --speed 10.0
 //!
+//! # Replay as fast as possible (CI), or with reproducible jitter
+//! alec-exporter --csv dataset.csv --immediate
+//! alec-exporter --csv dataset.csv --jitter-ms 200 --jitter-seed 42
+//!
 //! # Run on custom port
 //! alec-exporter --csv dataset.csv --port 9090
+//!
+//! # Accept live snapshots from gateways (HTTP POST /ingest + UDP)
+//! alec-exporter --ingest --udp-port 9101 --ingest-ttl-secs 300
+//!
+//! # Keep the last 5000 complexity events for GET /events
+//! alec-exporter --csv dataset.csv --event-capacity 5000
+//!
+//! # Latest S-lite structure and aligned window for Grafana
+//! curl http://localhost:9100/structure
+//! curl http://localhost:9100/window
 //! ```
 
+mod events;
+mod ingest;
 mod metrics;
+mod views;
 
 #[cfg(feature = "replay")]
 mod replay;
 
 use axum::{
-    extract::State,
+    body::Bytes,
+    extract::{Query, State},
     http::StatusCode,
-    response::{Html, IntoResponse},
-    routing::get,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
+    routing::{get, post},
     Json, Router,
 };
 use clap::Parser;
+use events::{EventQuery, EventStore, StoredEvent, DEFAULT_EVENT_CAPACITY};
+use futures_util::stream::{self, Stream};
+use ingest::{IngestConfig, IngestRegistry};
 use metrics::encode_metrics;
 use serde::Serialize;
+use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{info, Level};
 use tracing_subscriber::EnvFilter;
+use views::ViewStore;
 
 #[cfg(feature = "replay")]
-use replay::{DatasetInfo, ReplayConfig, ReplayEngine, ReplayState};
+use replay::{
+    ColumnMapping, DatasetInfo, Jitter, ReplayConfig, ReplayEngine, ReplayMode, ReplayState,
+};
 
 /// ALEC Prometheus Exporter
 #[derive(Parser, Debug)]
@@ -50,14 +79,30 @@ struct Args {
     #[arg(short, long, default_value = "9100")]
     port: u16,
 
-    /// CSV file to replay
+    /// CSV file to replay (repeat to merge several files on timestamp)
     #[arg(short, long)]
-    csv: Option<String>,
+    csv: Vec<String>,
+
+    /// JSON column mapping for CSVs not in the alec-testdata schema
+    #[arg(short, long)]
+    mapping: Option<String>,
 
     /// Replay speed multiplier (1.0 = real-time)
     #[arg(short, long, default_value = "1.0")]
     speed: f64,
 
+    /// Replay as fast as possible instead of pacing by timestamps
+    #[arg(long)]
+    immediate: bool,
+
+    /// Shift each paced delay by up to this many milliseconds
+    #[arg(long)]
+    jitter_ms: Option<u64>,
+
+    /// Seed of the replay jitter (same seed, same delays)
+    #[arg(long, default_value = "0")]
+    jitter_seed: u64,
+
     /// Loop the replay when it reaches the end
     #[arg(short, long, default_value = "true")]
     loop_replay: bool,
@@ -65,6 +110,27 @@ struct Args {
     /// Log level (trace, debug, info, warn, error)
     #[arg(long, default_value = "info")]
     log_level: String,
+
+    /// Enable the POST /ingest endpoint for live gateway snapshots
+    #[arg(long)]
+    ingest: bool,
+
+    /// Also accept ingest payloads as UDP datagrams on this port
+    #[arg(long)]
+    udp_port: Option<u16>,
+
+    /// Seconds without ingest before an instance is marked stale
+    #[arg(long, default_value = "300")]
+    ingest_ttl_secs: u64,
+
+    /// Remove the alec_instance_up series of stale instances instead of
+    /// reporting 0
+    #[arg(long)]
+    forget_stale: bool,
+
+    /// Complexity events kept for GET /events (oldest evicted first)
+    #[arg(long, default_value_t = DEFAULT_EVENT_CAPACITY)]
+    event_capacity: usize,
 }
 
 /// Application state shared across handlers.
@@ -73,6 +139,9 @@ struct AppState {
     replay_state: Option<Arc<ReplayState>>,
     #[cfg(feature = "replay")]
     dataset_info: Option<DatasetInfo>,
+    ingest: Option<Arc<IngestRegistry>>,
+    events: Arc<EventStore>,
+    views: Arc<ViewStore>,
     #[allow(dead_code)]
     start_time: std::time::Instant,
 }
@@ -98,18 +167,41 @@ async fn main() {
 
     info!("ALEC Exporter v{}", env!("CARGO_PKG_VERSION"));
 
+    let events = Arc::new(EventStore::new(args.event_capacity));
+    info!("Keeping up to {} complexity events", events.capacity());
+    let views = Arc::new(ViewStore::new());
+
     // Initialize replay engine if CSV provided
     #[cfg(feature = "replay")]
-    let (replay_state, dataset_info) = if let Some(csv_path) = args.csv.clone() {
-        let config = ReplayConfig {
-            csv_path,
-            speed: args.speed,
-            loop_replay: args.loop_replay,
-            default_sample_interval_ms: 60_000,
-        };
-
-        match ReplayEngine::from_csv(config) {
+    let (replay_state, dataset_info) = if !args.csv.is_empty() {
+        let engine = args
+            .mapping
+            .as_deref()
+            .map(ColumnMapping::from_json_file)
+            .transpose()
+            .and_then(|mapping| {
+                ReplayEngine::from_csv(ReplayConfig {
+                    csv_paths: args.csv.clone(),
+                    mapping,
+                    mode: if args.immediate {
+                        ReplayMode::Immediate
+                    } else {
+                        ReplayMode::Paced { speed: args.speed }
+                    },
+                    jitter: args.jitter_ms.map(|max_ms| Jitter {
+                        max_ms,
+                        seed: args.jitter_seed,
+                    }),
+                    loop_replay: args.loop_replay,
+                    default_sample_interval_ms: 60_000,
+                })
+            });
+
+        match engine {
             Ok(engine) => {
+                let engine = engine
+                    .with_event_store(events.clone())
+                    .with_view_store(views.clone());
                 let state = engine.state();
                 let info = engine.dataset_info();
 
@@ -117,6 +209,12 @@ async fn main() {
                     "Dataset loaded: {} sensors, {} samples",
                     info.sensor_count, info.sample_count
                 );
+                for file in &info.files {
+                    info!(
+                        "  {}: {} rows ({} skipped)",
+                        file.path, file.row_count, file.skipped_rows
+                    );
+                }
 
                 // Start replay in background
                 tokio::spawn(async move {
@@ -136,16 +234,46 @@ async fn main() {
     };
 
     #[cfg(not(feature = "replay"))]
-    if args.csv.is_some() {
-        tracing::warn!("Replay feature not enabled, ignoring --csv argument");
+    if !args.csv.is_empty() || args.mapping.is_some() {
+        tracing::warn!("Replay feature not enabled, ignoring --csv/--mapping arguments");
     }
 
+    // Initialize live ingest
+    let ingest = if args.ingest || args.udp_port.is_some() {
+        let registry = Arc::new(
+            IngestRegistry::new(IngestConfig {
+                ttl: std::time::Duration::from_secs(args.ingest_ttl_secs),
+                forget_stale: args.forget_stale,
+            })
+            .with_event_store(events.clone())
+            .with_view_store(views.clone()),
+        );
+
+        if let Some(udp_port) = args.udp_port {
+            let udp_registry = registry.clone();
+            let udp_addr = SocketAddr::from(([0, 0, 0, 0], udp_port));
+            tokio::spawn(async move {
+                if let Err(e) = ingest::run_udp(udp_registry, udp_addr).await {
+                    tracing::error!("UDP ingest stopped: {}", e);
+                }
+            });
+        }
+
+        info!("Live ingest enabled (TTL {}s)", args.ingest_ttl_secs);
+        Some(registry)
+    } else {
+        None
+    };
+
     // Create app state
     let state = Arc::new(AppState {
         #[cfg(feature = "replay")]
         replay_state,
         #[cfg(feature = "replay")]
         dataset_info,
+        ingest,
+        events,
+        views,
         start_time: std::time::Instant::now(),
     });
 
@@ -156,6 +284,11 @@ async fn main() {
         .route("/health", get(health_handler))
         .route("/ready", get(ready_handler))
         .route("/status", get(status_handler))
+        .route("/ingest", post(ingest_handler))
+        .route("/events", get(events_handler))
+        .route("/events/stream", get(events_stream_handler))
+        .route("/structure", get(structure_handler))
+        .route("/window", get(window_handler))
         .with_state(state);
 
     // Start server
@@ -194,6 +327,11 @@ async fn root_handler() -> Html<&'static str> {
         <div class="endpoint"><a href="/health">/health</a> - Health check</div>
         <div class="endpoint"><a href="/ready">/ready</a> - Readiness check</div>
         <div class="endpoint"><a href="/status">/status</a> - Status information (JSON)</div>
+        <div class="endpoint"><code>POST /ingest</code> - Live gateway snapshots (with <code>--ingest</code>)</div>
+        <div class="endpoint"><a href="/events">/events</a> - Recent complexity events (JSON, <code>?since_ms=&amp;severity=&amp;type=&amp;limit=</code>)</div>
+        <div class="endpoint"><a href="/events/stream">/events/stream</a> - Live complexity events (Server-Sent Events)</div>
+        <div class="endpoint"><a href="/structure">/structure</a> - Latest S-lite structure (Grafana Node Graph JSON)</div>
+        <div class="endpoint"><a href="/window">/window</a> - Latest aligned sample window (JSON columns)</div>
     </div>
 
     <h2>Metrics</h2>
@@ -206,7 +344,8 @@ async fn root_handler() -> Html<&'static str> {
         <li><code>alec_channel_entropy_bits</code> - Per-channel entropy</li>
         <li><code>alec_baseline_progress</code> - Baseline learning progress</li>
         <li><code>alec_zscore_*</code> - Z-scores for deviation detection</li>
-        <li><code>alec_anomaly_events_total</code> - Anomaly event counter</li>
+        <li><code>alec_anomaly_events_total</code> - Anomaly events by type and severity</li>
+        <li><code>alec_last_event_timestamp_ms</code> - Last anomaly event per type</li>
     </ul>
 
     <p>See <a href="https://github.com/zeekmartin/alec-codec">alec-codec</a> for more information.</p>
@@ -216,7 +355,11 @@ async fn root_handler() -> Html<&'static str> {
 }
 
 /// Metrics handler - returns Prometheus text format.
-async fn metrics_handler() -> impl IntoResponse {
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    // Drop stale ingest instances before serving their series
+    if let Some(ref ingest) = state.ingest {
+        ingest.expire(std::time::Instant::now());
+    }
     let metrics = encode_metrics();
     (
         StatusCode::OK,
@@ -225,6 +368,64 @@ async fn metrics_handler() -> impl IntoResponse {
     )
 }
 
+/// Ingest handler - accepts a gateway snapshot envelope as JSON.
+async fn ingest_handler(State(state): State<Arc<AppState>>, body: Bytes) -> impl IntoResponse {
+    let Some(ref ingest) = state.ingest else {
+        return (StatusCode::NOT_FOUND, "Ingest is disabled".to_string());
+    };
+    match ingest.ingest_bytes(&body, "http") {
+        Ok(_) => (StatusCode::NO_CONTENT, String::new()),
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Events handler - returns the stored complexity events matching the query.
+async fn events_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventQuery>,
+) -> Json<Vec<StoredEvent>> {
+    Json(state.events.query(&query))
+}
+
+/// Event stream handler - pushes complexity events as Server-Sent Events.
+///
+/// Each SSE event carries the store id as `id:` and the JSON of
+/// `GET /events` as data. Subscribers too slow to keep up skip the events
+/// they missed.
+async fn events_stream_handler(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = stream::unfold(state.events.subscribe(), |mut live| async move {
+        loop {
+            match live.recv().await {
+                Ok(stored) => {
+                    let event = Event::default()
+                        .id(stored.id.to_string())
+                        .event("complexity_event")
+                        .json_data(&stored)
+                        .unwrap_or_default();
+                    return Some((Ok(event), live));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event stream subscriber skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Structure handler - returns the latest S-lite as a node graph.
+async fn structure_handler(State(state): State<Arc<AppState>>) -> Response {
+    views::structure_response(&state.views)
+}
+
+/// Window handler - returns the latest aligned sample window as columns.
+async fn window_handler(State(state): State<Arc<AppState>>) -> Response {
+    views::window_response(&state.views)
+}
+
 /// Health check handler.
 async fn health_handler() -> impl IntoResponse {
     (StatusCode::OK, "OK")
@@ -263,6 +464,7 @@ struct ReplayStatus {
     paused: bool,
     position: usize,
     total_samples: usize,
+    logical_time_ms: u64,
     progress_percent: f64,
     sensor_count: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -294,6 +496,9 @@ async fn status_handler(State(state): State<Arc<AppState>>) -> Json<StatusRespon
                 .load(std::sync::atomic::Ordering::SeqCst),
             position,
             total_samples: total,
+            logical_time_ms: replay_state
+                .logical_time_ms
+                .load(std::sync::atomic::Ordering::SeqCst),
             progress_percent: progress,
             sensor_count: state
                 .dataset_info
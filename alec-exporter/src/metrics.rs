@@ -9,10 +9,11 @@
 //! This module defines all Prometheus metrics exposed by the exporter
 //! and provides functions to update them from ALEC snapshots.
 
+use alec_complexity::ComplexityEvent;
 use lazy_static::lazy_static;
 use prometheus::{
     register_counter_vec, register_gauge, register_gauge_vec, CounterVec, Encoder, Gauge, GaugeVec,
-    TextEncoder,
+    Opts, Registry, TextEncoder,
 };
 
 lazy_static! {
@@ -139,14 +140,109 @@ lazy_static! {
     ).unwrap();
 
     // ============================================================
-    // Event Counters
+    // Event Metrics
     // ============================================================
 
-    /// Total anomaly events detected (labeled by event type and severity).
-    pub static ref ANOMALY_EVENTS_TOTAL: CounterVec = register_counter_vec!(
-        "alec_anomaly_events_total",
-        "Total anomaly events detected",
-        &["event_type", "severity"]
+    /// Anomaly event counters and last-event gauges.
+    pub static ref EVENT_METRICS: EventMetrics = {
+        let metrics = EventMetrics::new();
+        metrics.register(prometheus::default_registry()).unwrap();
+        metrics
+    };
+
+    // ============================================================
+    // Ingest Metrics (labeled by gateway_id)
+    // ============================================================
+
+    /// Whether an ingesting instance is live (1) or stale (0).
+    pub static ref INSTANCE_UP: GaugeVec = register_gauge_vec!(
+        "alec_instance_up",
+        "Whether an ingesting gateway is live (1) or stale past the TTL (0)",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Time of the last accepted ingest per instance.
+    pub static ref INGEST_LAST_SEEN_SECONDS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_last_seen_timestamp_seconds",
+        "Unix time of the last accepted ingest",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Resilience Index (R) per instance.
+    pub static ref INGEST_RESILIENCE_INDEX: GaugeVec = register_gauge_vec!(
+        "alec_ingest_resilience_index",
+        "Ingested ALEC Resilience Index (R) (0-1)",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Resilience Zone per instance.
+    pub static ref INGEST_RESILIENCE_ZONE: GaugeVec = register_gauge_vec!(
+        "alec_ingest_resilience_zone",
+        "Ingested ALEC Resilience Zone (0=Healthy, 1=Warning, 2=Critical)",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Total Correlation per instance.
+    pub static ref INGEST_TOTAL_CORRELATION_BITS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_total_correlation_bits",
+        "Ingested ALEC Total Correlation in bits",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Joint Entropy per instance.
+    pub static ref INGEST_JOINT_ENTROPY_BITS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_joint_entropy_bits",
+        "Ingested ALEC Joint Entropy in bits",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Payload Entropy per instance.
+    pub static ref INGEST_PAYLOAD_ENTROPY_BITS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_payload_entropy_bits",
+        "Ingested ALEC Payload Entropy (H_bytes) in bits",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Sum of channel entropies per instance.
+    pub static ref INGEST_SUM_ENTROPY_BITS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_sum_entropy_bits",
+        "Ingested sum of individual channel entropies in bits",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Per-channel entropy per instance.
+    pub static ref INGEST_CHANNEL_ENTROPY_BITS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_channel_entropy_bits",
+        "Ingested per-channel entropy in bits",
+        &["gateway_id", "channel"]
+    ).unwrap();
+
+    /// Baseline learning progress per instance.
+    pub static ref INGEST_BASELINE_PROGRESS: GaugeVec = register_gauge_vec!(
+        "alec_ingest_baseline_progress",
+        "Ingested ALEC baseline learning progress (0-1)",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Baseline locked state per instance.
+    pub static ref INGEST_BASELINE_LOCKED: GaugeVec = register_gauge_vec!(
+        "alec_ingest_baseline_locked",
+        "Ingested ALEC baseline locked state (1=locked, 0=learning)",
+        &["gateway_id"]
+    ).unwrap();
+
+    /// Z-scores per instance (labeled by metric: tc, h_joint, h_bytes, r).
+    pub static ref INGEST_ZSCORE: GaugeVec = register_gauge_vec!(
+        "alec_ingest_zscore",
+        "Ingested z-score for deviation from baseline",
+        &["gateway_id", "metric"]
+    ).unwrap();
+
+    /// Total ingest requests (labeled by transport and result).
+    pub static ref INGEST_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
+        "alec_ingest_requests_total",
+        "Total ingest requests received",
+        &["transport", "result"]
     ).unwrap();
 
     // ============================================================
@@ -174,7 +270,13 @@ lazy_static! {
     /// Replay speed multiplier.
     pub static ref REPLAY_SPEED: Gauge = register_gauge!(
         "alec_exporter_replay_speed",
-        "Replay speed multiplier"
+        "Replay speed multiplier (0 when replaying as fast as possible)"
+    ).unwrap();
+
+    /// Dataset time of the last replayed sample.
+    pub static ref REPLAY_LOGICAL_TIME_MS: Gauge = register_gauge!(
+        "alec_exporter_replay_logical_time_ms",
+        "Dataset time of the last replayed sample (ms since the first sample)"
     ).unwrap();
 }
 
@@ -197,22 +299,61 @@ impl From<&str> for ResilienceZone {
     }
 }
 
-/// Event severity levels.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Severity {
-    Info,
-    Warning,
-    Critical,
+/// Metrics driven by the complexity event stream.
+///
+/// One series per (type, severity) pair, created when the first such event
+/// arrives. Label values are the snake_case names of
+/// [`EventType`](alec_complexity::EventType) and
+/// [`EventSeverity`](alec_complexity::EventSeverity).
+pub struct EventMetrics {
+    /// `alec_anomaly_events_total{type, severity}`
+    events_total: CounterVec,
+    /// `alec_last_event_timestamp_ms{type}`
+    last_timestamp_ms: GaugeVec,
 }
 
-impl Severity {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Severity::Info => "info",
-            Severity::Warning => "warning",
-            Severity::Critical => "critical",
+impl EventMetrics {
+    /// Create unregistered event metrics.
+    pub fn new() -> Self {
+        Self {
+            events_total: CounterVec::new(
+                Opts::new("alec_anomaly_events_total", "Total anomaly events detected"),
+                &["type", "severity"],
+            )
+            .unwrap(),
+            last_timestamp_ms: GaugeVec::new(
+                Opts::new(
+                    "alec_last_event_timestamp_ms",
+                    "Timestamp of the last anomaly event of each type (ms)",
+                ),
+                &["type"],
+            )
+            .unwrap(),
         }
     }
+
+    /// Register the metrics with `registry`.
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.events_total.clone()))?;
+        registry.register(Box::new(self.last_timestamp_ms.clone()))
+    }
+
+    /// Count an event and record its timestamp.
+    pub fn record(&self, event: &ComplexityEvent) {
+        let event_type = event.event_type.as_snake_case();
+        self.events_total
+            .with_label_values(&[event_type, event.severity.as_snake_case()])
+            .inc();
+        self.last_timestamp_ms
+            .with_label_values(&[event_type])
+            .set(event.timestamp_ms as f64);
+    }
+}
+
+impl Default for EventMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Update core metrics from gateway MetricsSnapshot values.
@@ -261,18 +402,17 @@ pub fn update_delta_metrics(d_tc: f64, d_h_joint: f64, d_h_bytes: f64, d_r: f64)
     DELTA_R.set(d_r);
 }
 
-/// Increment anomaly event counter.
-pub fn record_anomaly_event(event_type: &str, severity: Severity) {
-    ANOMALY_EVENTS_TOTAL
-        .with_label_values(&[event_type, severity.as_str()])
-        .inc();
+/// Record an anomaly event in the event metrics.
+pub fn record_anomaly_event(event: &ComplexityEvent) {
+    EVENT_METRICS.record(event);
 }
 
 /// Update replay position metrics.
-pub fn update_replay_metrics(position: usize, total: usize, speed: f64) {
+pub fn update_replay_metrics(position: usize, total: usize, speed: f64, logical_time_ms: u64) {
     REPLAY_POSITION.set(position as f64);
     REPLAY_TOTAL_SAMPLES.set(total as f64);
     REPLAY_SPEED.set(speed);
+    REPLAY_LOGICAL_TIME_MS.set(logical_time_ms as f64);
 }
 
 /// Increment samples processed counter.
@@ -304,10 +444,58 @@ mod tests {
     }
 
     #[test]
-    fn test_severity_as_str() {
-        assert_eq!(Severity::Info.as_str(), "info");
-        assert_eq!(Severity::Warning.as_str(), "warning");
-        assert_eq!(Severity::Critical.as_str(), "critical");
+    fn test_event_metrics_labels() {
+        use alec_complexity::event::EventDetails;
+        use alec_complexity::{EventSeverity, EventType};
+
+        let registry = Registry::new();
+        let metrics = EventMetrics::new();
+        metrics.register(&registry).unwrap();
+        let event = |event_type, severity, timestamp_ms| {
+            ComplexityEvent::new(
+                event_type,
+                severity,
+                timestamp_ms,
+                "test event",
+                EventDetails::None,
+            )
+        };
+        for e in [
+            event(
+                EventType::PayloadEntropySpike,
+                EventSeverity::Warning,
+                1_000,
+            ),
+            event(EventType::RedundancyDrop, EventSeverity::Critical, 2_000),
+            event(
+                EventType::PayloadEntropySpike,
+                EventSeverity::Warning,
+                3_000,
+            ),
+            event(
+                EventType::PayloadEntropySpike,
+                EventSeverity::Critical,
+                4_000,
+            ),
+        ] {
+            metrics.record(&e);
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.gather(), &mut buffer)
+            .unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        for line in [
+            r#"alec_anomaly_events_total{severity="warning",type="payload_entropy_spike"} 2"#,
+            r#"alec_anomaly_events_total{severity="critical",type="payload_entropy_spike"} 1"#,
+            r#"alec_anomaly_events_total{severity="critical",type="redundancy_drop"} 1"#,
+            r#"alec_last_event_timestamp_ms{type="payload_entropy_spike"} 4000"#,
+            r#"alec_last_event_timestamp_ms{type="redundancy_drop"} 2000"#,
+        ] {
+            assert!(output.contains(line), "missing {} in\n{}", line, output);
+        }
+        assert_eq!(output.matches("alec_anomaly_events_total{").count(), 3);
     }
 
     #[test]
@@ -8,33 +8,150 @@
 //!
 //! This module provides functionality to replay pre-generated datasets
 //! through the ALEC metrics pipeline, updating Prometheus metrics in real-time.
+//!
+//! By default a CSV is expected in the alec-testdata schema: a leading
+//! `timestamp_ms` column followed by one column per sensor. Exports from
+//! other tools can be replayed with a [`ColumnMapping`], and several files
+//! are merged into a single stream ordered by timestamp.
+//!
+//! Samples are either paced against the wall clock ([`ReplayMode::Paced`])
+//! or pushed as fast as possible ([`ReplayMode::Immediate`], for CI). In
+//! both modes [`ReplayState::logical_time_ms`] follows the dataset
+//! timestamps, and paced delays can be perturbed by a seeded [`Jitter`] so
+//! demos look realistic yet replay identically.
 
+use crate::events::{EventStore, StoreSink};
 use crate::metrics::{
     increment_samples_processed, record_anomaly_event, update_baseline_metrics,
     update_channel_criticality, update_channel_entropy, update_core_metrics, update_delta_metrics,
-    update_replay_metrics, update_zscore_metrics, Severity,
+    update_replay_metrics, update_zscore_metrics,
 };
+use crate::views::ViewStore;
 use alec_complexity::{
     ChannelEntropy as ComplexityChannelEntropy, ComplexityConfig, ComplexityEngine,
-    ComplexitySnapshot, EventSeverity, InputSnapshot,
+    ComplexitySnapshot, InputSnapshot,
 };
 use alec_gateway::{ChannelConfig, Gateway, GatewayConfig, MetricsConfig, MetricsEngine};
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
+/// How timestamps are written in a CSV column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// Milliseconds since the Unix epoch.
+    UnixMs,
+    /// Seconds since the Unix epoch (fractional seconds allowed).
+    UnixS,
+    /// RFC 3339 date-time, e.g. `2025-03-01T12:00:00Z`.
+    Rfc3339,
+}
+
+impl TimestampFormat {
+    /// Parse a timestamp into milliseconds since the Unix epoch.
+    ///
+    /// Returns `None` for unparsable or pre-epoch values.
+    pub fn parse_ms(&self, raw: &str) -> Option<u64> {
+        let raw = raw.trim();
+        match self {
+            TimestampFormat::UnixMs => raw.parse().ok(),
+            TimestampFormat::UnixS => match raw.parse::<u64>() {
+                Ok(secs) => secs.checked_mul(1000),
+                Err(_) => {
+                    let secs: f64 = raw.parse().ok()?;
+                    if secs.is_finite() && secs >= 0.0 {
+                        Some((secs * 1000.0).round() as u64)
+                    } else {
+                        None
+                    }
+                }
+            },
+            TimestampFormat::Rfc3339 => {
+                let parsed = chrono::DateTime::parse_from_rfc3339(raw).ok()?;
+                u64::try_from(parsed.timestamp_millis()).ok()
+            }
+        }
+    }
+}
+
+/// Maps the columns of a non-testdata CSV onto replay channels.
+///
+/// Columns not listed in `value_columns` are ignored.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ColumnMapping {
+    /// Name of the column holding the row timestamp.
+    pub timestamp_column: String,
+    /// CSV column name -> channel name.
+    pub value_columns: HashMap<String, String>,
+    /// Format of the timestamp column.
+    pub timestamp_format: TimestampFormat,
+}
+
+impl ColumnMapping {
+    /// Load a mapping from a JSON file.
+    pub fn from_json_file(path: &str) -> Result<Self, ReplayError> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| ReplayError::InvalidFormat(e.to_string()))
+    }
+}
+
+/// How samples are paced during replay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayMode {
+    /// Wait the dataset interval between samples, divided by `speed`
+    /// (1.0 = real-time, 10.0 = 10x faster).
+    Paced { speed: f64 },
+    /// Push samples as fast as possible, without waiting.
+    Immediate,
+}
+
+impl ReplayMode {
+    /// Speed multiplier, `None` when immediate.
+    pub fn speed(&self) -> Option<f64> {
+        match self {
+            ReplayMode::Paced { speed } => Some(*speed),
+            ReplayMode::Immediate => None,
+        }
+    }
+}
+
+impl Default for ReplayMode {
+    fn default() -> Self {
+        ReplayMode::Paced { speed: 1.0 }
+    }
+}
+
+/// Seeded perturbation of the delays between paced samples.
+///
+/// Each delay moves by up to `max_ms` either way (never below zero). The
+/// same seed gives the same delays on every run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jitter {
+    /// Largest shift of a delay, in milliseconds.
+    pub max_ms: u64,
+    /// Seed of the jitter generator.
+    pub seed: u64,
+}
+
 /// Configuration for dataset replay.
 #[derive(Debug, Clone)]
 pub struct ReplayConfig {
-    /// Path to CSV dataset file.
-    pub csv_path: String,
-    /// Replay speed multiplier (1.0 = real-time, 10.0 = 10x faster).
-    pub speed: f64,
+    /// Paths to CSV dataset files, merged on timestamp.
+    pub csv_paths: Vec<String>,
+    /// Column mapping applied to every file. `None` expects the
+    /// alec-testdata schema.
+    pub mapping: Option<ColumnMapping>,
+    /// Pacing of the samples.
+    pub mode: ReplayMode,
+    /// Jitter applied to paced delays; ignored when immediate.
+    pub jitter: Option<Jitter>,
     /// Whether to loop the dataset.
     pub loop_replay: bool,
     /// Sample interval in milliseconds (used if dataset doesn't specify).
@@ -44,8 +161,10 @@ pub struct ReplayConfig {
 impl Default for ReplayConfig {
     fn default() -> Self {
         Self {
-            csv_path: String::new(),
-            speed: 1.0,
+            csv_paths: Vec::new(),
+            mapping: None,
+            mode: ReplayMode::default(),
+            jitter: None,
             loop_replay: true,
             default_sample_interval_ms: 60_000, // 1 minute
         }
@@ -63,6 +182,9 @@ pub struct ReplayState {
     pub running: AtomicBool,
     /// Whether replay is paused.
     pub paused: AtomicBool,
+    /// Dataset time of the last replayed sample, in milliseconds since the
+    /// first sample of the dataset.
+    pub logical_time_ms: AtomicU64,
 }
 
 impl Default for ReplayState {
@@ -72,8 +194,52 @@ impl Default for ReplayState {
             total_samples: AtomicUsize::new(0),
             running: AtomicBool::new(false),
             paused: AtomicBool::new(false),
+            logical_time_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Wall-clock delays between replayed samples.
+struct Pacer {
+    mode: ReplayMode,
+    jitter: Option<Jitter>,
+    /// State of the jitter generator
+    jitter_state: u64,
+}
+
+impl Pacer {
+    fn new(config: &ReplayConfig) -> Self {
+        Self {
+            mode: config.mode,
+            jitter: config.jitter,
+            jitter_state: config.jitter.map_or(0, |jitter| jitter.seed),
         }
     }
+
+    /// Delay before a sample `interval_ms` of dataset time after the last one.
+    fn delay_ms(&mut self, interval_ms: u64) -> u64 {
+        let Some(speed) = self.mode.speed() else {
+            return 0;
+        };
+        let delay = (interval_ms as f64 / speed) as u64;
+        match self.jitter {
+            Some(jitter) if jitter.max_ms > 0 => {
+                // Uniform in [delay - max_ms, delay + max_ms]
+                let offset = (self.next_jitter() as u128 % (2 * jitter.max_ms as u128 + 1)) as u64;
+                delay.saturating_add(offset).saturating_sub(jitter.max_ms)
+            }
+            _ => delay,
+        }
+    }
+
+    /// Next value of the jitter generator (SplitMix64)
+    fn next_jitter(&mut self) -> u64 {
+        self.jitter_state = self.jitter_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.jitter_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 }
 
 /// Dataset row for replay.
@@ -83,27 +249,73 @@ struct DataRow {
     values: HashMap<String, Option<f64>>,
 }
 
+/// Rows read from a single CSV file.
+#[derive(Debug)]
+struct ParsedCsv {
+    /// Channels found in the file, in column order.
+    channels: Vec<String>,
+    rows: Vec<DataRow>,
+    /// Rows dropped because of an unparsable timestamp.
+    skipped_rows: usize,
+}
+
 /// Replay engine that feeds datasets through ALEC and updates Prometheus metrics.
 pub struct ReplayEngine {
     config: ReplayConfig,
     state: Arc<ReplayState>,
     gateway: Arc<RwLock<Gateway>>,
     metrics_engine: Arc<RwLock<MetricsEngine>>,
-    complexity_engine: Arc<RwLock<ComplexityEngine>>,
+    complexity_engine: Arc<Mutex<ComplexityEngine>>,
+    /// Store receiving the complexity events, if any.
+    events: Option<Arc<EventStore>>,
+    /// Store receiving the latest S-lite and aligned window, if any.
+    views: Option<Arc<ViewStore>>,
     sensor_ids: Vec<String>,
     rows: Vec<DataRow>,
+    files: Vec<FileInfo>,
 }
 
 impl ReplayEngine {
-    /// Create a new replay engine from a CSV file.
+    /// Create a new replay engine from one or more CSV files.
     pub fn from_csv(config: ReplayConfig) -> Result<Self, ReplayError> {
-        let path = Path::new(&config.csv_path);
-        if !path.exists() {
-            return Err(ReplayError::FileNotFound(config.csv_path.clone()));
+        if config.csv_paths.is_empty() {
+            return Err(ReplayError::EmptyDataset);
+        }
+        for csv_path in &config.csv_paths {
+            if !Path::new(csv_path).exists() {
+                return Err(ReplayError::FileNotFound(csv_path.clone()));
+            }
+        }
+
+        if let Some(ref mapping) = config.mapping {
+            Self::validate_mapping(&config.csv_paths, mapping)?;
         }
 
-        // Parse CSV
-        let (sensor_ids, rows) = Self::parse_csv(path)?;
+        // Parse every file, then merge on timestamp
+        let mut sensor_ids: Vec<String> = Vec::new();
+        let mut parsed = Vec::with_capacity(config.csv_paths.len());
+        let mut files = Vec::with_capacity(config.csv_paths.len());
+        for csv_path in &config.csv_paths {
+            let csv = Self::parse_csv(Path::new(csv_path), config.mapping.as_ref())?;
+            if csv.skipped_rows > 0 {
+                warn!(
+                    "{}: skipped {} rows with unparsable timestamps",
+                    csv_path, csv.skipped_rows
+                );
+            }
+            for channel in &csv.channels {
+                if !sensor_ids.contains(channel) {
+                    sensor_ids.push(channel.clone());
+                }
+            }
+            files.push(FileInfo {
+                path: csv_path.clone(),
+                row_count: csv.rows.len(),
+                skipped_rows: csv.skipped_rows,
+            });
+            parsed.push(csv.rows);
+        }
+        let rows = Self::merge_rows(parsed);
 
         if rows.is_empty() {
             return Err(ReplayError::EmptyDataset);
@@ -121,7 +333,7 @@ impl ReplayEngine {
             max_frame_size: 242,
             ..Default::default()
         };
-        let mut gateway = Gateway::with_config(gateway_config);
+        let mut gateway = Gateway::with_config(gateway_config).expect("valid gateway config");
 
         for sensor_id in &sensor_ids {
             let channel_config = ChannelConfig::default();
@@ -136,11 +348,7 @@ impl ReplayEngine {
         let metrics_engine = MetricsEngine::new(metrics_config);
 
         // Create complexity engine
-        let complexity_config = ComplexityConfig {
-            enabled: true,
-            ..Default::default()
-        };
-        let complexity_engine = ComplexityEngine::new(complexity_config);
+        let complexity_engine = Self::complexity_engine(None);
 
         let state = Arc::new(ReplayState::default());
         state.total_samples.store(rows.len(), Ordering::SeqCst);
@@ -157,30 +365,132 @@ impl ReplayEngine {
             state,
             gateway: Arc::new(RwLock::new(gateway)),
             metrics_engine: Arc::new(RwLock::new(metrics_engine)),
-            complexity_engine: Arc::new(RwLock::new(complexity_engine)),
+            complexity_engine: Arc::new(Mutex::new(complexity_engine)),
+            events: None,
+            views: None,
             sensor_ids,
             rows,
+            files,
         })
     }
 
-    /// Parse a CSV file into sensor IDs and data rows.
-    fn parse_csv(path: &Path) -> Result<(Vec<String>, Vec<DataRow>), ReplayError> {
+    /// Collect the complexity events of the replay into `store`.
+    ///
+    /// Events are stored under the `replay` source, across loops.
+    pub fn with_event_store(mut self, store: Arc<EventStore>) -> Self {
+        self.complexity_engine = Arc::new(Mutex::new(Self::complexity_engine(Some(&store))));
+        self.events = Some(store);
+        self
+    }
+
+    /// Keep the latest S-lite and aligned window of the replay in `store`.
+    pub fn with_view_store(mut self, store: Arc<ViewStore>) -> Self {
+        self.views = Some(store);
+        self
+    }
+
+    /// Complexity engine of the replay, feeding `events` if given.
+    fn complexity_engine(events: Option<&Arc<EventStore>>) -> ComplexityEngine {
+        let mut engine = ComplexityEngine::new(ComplexityConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        if let Some(store) = events {
+            engine.add_sink(Box::new(StoreSink::new(Arc::clone(store), "replay")));
+        }
+        engine
+    }
+
+    /// Check that every mapped column exists before any row is read.
+    ///
+    /// The timestamp column must be present in every file; a value column
+    /// must be present in at least one of them.
+    fn validate_mapping(paths: &[String], mapping: &ColumnMapping) -> Result<(), ReplayError> {
+        let mut unknown = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+
+        for csv_path in paths {
+            let mut reader = csv::Reader::from_path(csv_path)?;
+            let headers = reader.headers()?;
+            let mut has_timestamp = false;
+            for header in headers.iter() {
+                let header = header.trim();
+                if header == mapping.timestamp_column {
+                    has_timestamp = true;
+                }
+                seen.insert(header.to_string());
+            }
+            if !has_timestamp {
+                unknown.push(format!("{} ({})", mapping.timestamp_column, csv_path));
+            }
+        }
+
+        let mut missing: Vec<String> = mapping
+            .value_columns
+            .keys()
+            .filter(|column| !seen.contains(column.as_str()))
+            .cloned()
+            .collect();
+        missing.sort();
+        unknown.extend(missing);
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(ReplayError::UnknownColumns(unknown))
+        }
+    }
+
+    /// Parse a CSV file into channels and data rows.
+    ///
+    /// Rows whose timestamp cannot be parsed are counted and skipped.
+    fn parse_csv(path: &Path, mapping: Option<&ColumnMapping>) -> Result<ParsedCsv, ReplayError> {
         let mut reader = csv::Reader::from_path(path)?;
 
-        // Parse headers
+        // Resolve the timestamp column and (column index, channel) pairs
         let headers = reader.headers()?.clone();
-        let header_strs: Vec<&str> = headers.iter().collect();
+        let header_strs: Vec<&str> = headers.iter().map(str::trim).collect();
+
+        let (timestamp_idx, format, columns) = match mapping {
+            Some(mapping) => {
+                let timestamp_idx = header_strs
+                    .iter()
+                    .position(|h| *h == mapping.timestamp_column)
+                    .ok_or_else(|| {
+                        ReplayError::UnknownColumns(vec![mapping.timestamp_column.clone()])
+                    })?;
+                let columns: Vec<(usize, String)> = header_strs
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, h)| mapping.value_columns.get(*h).map(|c| (i, c.clone())))
+                    .collect();
+                (timestamp_idx, mapping.timestamp_format, columns)
+            }
+            None => {
+                if header_strs.is_empty() || header_strs[0] != "timestamp_ms" {
+                    return Err(ReplayError::InvalidFormat(
+                        "First column must be 'timestamp_ms'".to_string(),
+                    ));
+                }
+                let columns = header_strs[1..]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, h)| (i + 1, h.to_string()))
+                    .collect();
+                (0, TimestampFormat::UnixMs, columns)
+            }
+        };
 
-        if header_strs.is_empty() || header_strs[0] != "timestamp_ms" {
-            return Err(ReplayError::InvalidFormat(
-                "First column must be 'timestamp_ms'".to_string(),
-            ));
+        let mut channels: Vec<String> = Vec::new();
+        for (_, channel) in &columns {
+            if !channels.contains(channel) {
+                channels.push(channel.clone());
+            }
         }
 
-        let sensor_ids: Vec<String> = header_strs[1..].iter().map(|s| s.to_string()).collect();
-
         // Parse rows
         let mut rows = Vec::new();
+        let mut skipped_rows = 0;
         for result in reader.records() {
             let record = result?;
             let values: Vec<&str> = record.iter().collect();
@@ -189,23 +499,25 @@ impl ReplayEngine {
                 continue;
             }
 
-            let timestamp_ms: u64 = values[0]
-                .parse()
-                .map_err(|_| ReplayError::InvalidFormat("Invalid timestamp".to_string()))?;
+            let timestamp_ms = match values.get(timestamp_idx).and_then(|s| format.parse_ms(s)) {
+                Some(ts) => ts,
+                None => {
+                    skipped_rows += 1;
+                    continue;
+                }
+            };
 
             let mut row_values = HashMap::new();
-            for (i, sensor_id) in sensor_ids.iter().enumerate() {
-                let value = if i + 1 < values.len() {
-                    let s = values[i + 1].trim();
+            for (i, channel) in &columns {
+                let value = values.get(*i).and_then(|s| {
+                    let s = s.trim();
                     if s.is_empty() {
                         None
                     } else {
                         s.parse().ok()
                     }
-                } else {
-                    None
-                };
-                row_values.insert(sensor_id.clone(), value);
+                });
+                row_values.insert(channel.clone(), value);
             }
 
             rows.push(DataRow {
@@ -214,7 +526,36 @@ impl ReplayEngine {
             });
         }
 
-        Ok((sensor_ids, rows))
+        Ok(ParsedCsv {
+            channels,
+            rows,
+            skipped_rows,
+        })
+    }
+
+    /// Merge rows from several files into one stream ordered by timestamp.
+    ///
+    /// Rows sharing a timestamp are combined; a present value is never
+    /// overwritten by a missing one.
+    fn merge_rows(files: Vec<Vec<DataRow>>) -> Vec<DataRow> {
+        let mut all: Vec<DataRow> = files.into_iter().flatten().collect();
+        all.sort_by_key(|row| row.timestamp_ms);
+
+        let mut merged: Vec<DataRow> = Vec::with_capacity(all.len());
+        for row in all {
+            match merged.last_mut() {
+                Some(last) if last.timestamp_ms == row.timestamp_ms => {
+                    for (channel, value) in row.values {
+                        let entry = last.values.entry(channel).or_insert(None);
+                        if value.is_some() {
+                            *entry = value;
+                        }
+                    }
+                }
+                _ => merged.push(row),
+            }
+        }
+        merged
     }
 
     /// Get the replay state.
@@ -226,9 +567,11 @@ impl ReplayEngine {
     pub async fn run(&self) {
         self.state.running.store(true, Ordering::SeqCst);
         info!(
-            "Starting replay: speed={}, loop={}",
-            self.config.speed, self.config.loop_replay
+            "Starting replay: mode={:?}, jitter={:?}, loop={}",
+            self.config.mode, self.config.jitter, self.config.loop_replay
         );
+        let mut pacer = Pacer::new(&self.config);
+        let start_ms = self.rows.first().map_or(0, |row| row.timestamp_ms);
 
         loop {
             // Check if we should stop
@@ -250,6 +593,7 @@ impl ReplayEngine {
                 if self.config.loop_replay {
                     info!("Dataset complete, looping...");
                     self.state.position.store(0, Ordering::SeqCst);
+                    self.state.logical_time_ms.store(0, Ordering::SeqCst);
                     // Reset gateway and engines for clean loop
                     self.reset_engines().await;
                     continue;
@@ -264,11 +608,20 @@ impl ReplayEngine {
             let row = &self.rows[position];
             self.process_row(row, position).await;
 
-            // Update position
+            // Update position and dataset time
             self.state.position.fetch_add(1, Ordering::SeqCst);
+            let logical_time_ms = row.timestamp_ms - start_ms;
+            self.state
+                .logical_time_ms
+                .store(logical_time_ms, Ordering::SeqCst);
 
             // Update replay metrics
-            update_replay_metrics(position + 1, self.rows.len(), self.config.speed);
+            update_replay_metrics(
+                position + 1,
+                self.rows.len(),
+                self.config.mode.speed().unwrap_or(0.0),
+                logical_time_ms,
+            );
 
             // Calculate sleep duration
             let base_interval_ms = if position + 1 < self.rows.len() {
@@ -277,9 +630,12 @@ impl ReplayEngine {
                 self.config.default_sample_interval_ms
             };
 
-            let sleep_ms = (base_interval_ms as f64 / self.config.speed) as u64;
+            let sleep_ms = pacer.delay_ms(base_interval_ms);
             if sleep_ms > 0 {
                 sleep(Duration::from_millis(sleep_ms)).await;
+            } else {
+                // Let the HTTP handlers run between immediate samples
+                tokio::task::yield_now().await;
             }
         }
     }
@@ -324,7 +680,14 @@ impl ReplayEngine {
         // Get metrics snapshot
         let metrics_snapshot = {
             let mut metrics_engine = self.metrics_engine.write().await;
-            metrics_engine.observe_frame(&frame_bytes, row.timestamp_ms)
+            let snapshot = metrics_engine.observe_frame(&frame_bytes, row.timestamp_ms);
+            // The aligned window belongs to this flush only
+            if let (Some(views), Some(_)) = (&self.views, &snapshot) {
+                if let Some(window) = metrics_engine.aligned_window() {
+                    views.record_window("replay", row.timestamp_ms, window);
+                }
+            }
+            snapshot
         };
 
         // Update Prometheus metrics from snapshot
@@ -368,7 +731,8 @@ impl ReplayEngine {
             // Update criticality rankings
             if let Some(ref resilience) = snapshot.resilience {
                 if let Some(ref criticality) = resilience.criticality {
-                    for (rank, item) in criticality.ranking.iter().enumerate() {
+                    let ranked = criticality.ranking.iter().filter(|c| !c.insufficient_data);
+                    for (rank, item) in ranked.enumerate() {
                         update_channel_criticality(&item.channel_id, rank as u32 + 1);
                     }
                 }
@@ -379,12 +743,15 @@ impl ReplayEngine {
         if let Some(ref metrics) = metrics_snapshot {
             let input = self.create_input_snapshot(metrics, row.timestamp_ms);
             let complexity_snapshot = {
-                let mut complexity_engine = self.complexity_engine.write().await;
+                let mut complexity_engine = self.complexity_engine.lock().await;
                 complexity_engine.process(&input)
             };
 
             if let Some(ref snapshot) = complexity_snapshot {
                 self.update_prometheus_from_complexity(snapshot);
+                if let Some(ref views) = self.views {
+                    views.record_complexity("replay", snapshot);
+                }
             }
         }
 
@@ -421,12 +788,7 @@ impl ReplayEngine {
 
         // Record anomaly events
         for event in &snapshot.events {
-            let severity = match event.severity {
-                EventSeverity::Info => Severity::Info,
-                EventSeverity::Warning => Severity::Warning,
-                EventSeverity::Critical => Severity::Critical,
-            };
-            record_anomaly_event(event.event_type.as_str(), severity);
+            record_anomaly_event(event);
         }
     }
 
@@ -453,6 +815,12 @@ impl ReplayEngine {
 
         if let Some(ref resilience) = metrics.resilience {
             input.r = resilience.r;
+            input.criticality_ranking = resilience.criticality.as_ref().map(|crit| {
+                crit.ranking
+                    .iter()
+                    .filter_map(|ch| Some((ch.channel_id.clone(), ch.impact?)))
+                    .collect()
+            });
         }
 
         input
@@ -466,7 +834,8 @@ impl ReplayEngine {
             *gateway = Gateway::with_config(GatewayConfig {
                 max_frame_size: 242,
                 ..Default::default()
-            });
+            })
+            .expect("valid gateway config");
 
             for sensor_id in &self.sensor_ids {
                 let channel_config = ChannelConfig::default();
@@ -485,11 +854,8 @@ impl ReplayEngine {
 
         // Reset complexity engine
         {
-            let mut complexity_engine = self.complexity_engine.write().await;
-            *complexity_engine = ComplexityEngine::new(ComplexityConfig {
-                enabled: true,
-                ..Default::default()
-            });
+            let mut complexity_engine = self.complexity_engine.lock().await;
+            *complexity_engine = Self::complexity_engine(self.events.as_ref());
         }
     }
 
@@ -525,6 +891,7 @@ impl ReplayEngine {
             sample_count: self.rows.len(),
             duration_ms,
             sensor_ids: self.sensor_ids.clone(),
+            files: self.files.clone(),
         }
     }
 }
@@ -537,6 +904,19 @@ pub struct DatasetInfo {
     pub sample_count: usize,
     pub duration_ms: u64,
     pub sensor_ids: Vec<String>,
+    /// Per-file row counts, in load order.
+    pub files: Vec<FileInfo>,
+}
+
+/// Row counts for one source file.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct FileInfo {
+    pub path: String,
+    /// Rows loaded, before merging with other files.
+    pub row_count: usize,
+    /// Rows skipped because of an unparsable timestamp.
+    pub skipped_rows: usize,
 }
 
 /// Replay errors.
@@ -551,6 +931,9 @@ pub enum ReplayError {
     #[error("Invalid format: {0}")]
     InvalidFormat(String),
 
+    #[error("Unknown columns in mapping: {}", .0.join(", "))]
+    UnknownColumns(Vec<String>),
+
     #[error("Empty dataset")]
     EmptyDataset,
 
@@ -561,6 +944,11 @@ pub enum ReplayError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::{EventQuery, DEFAULT_EVENT_CAPACITY};
+    use crate::views::tests::{assert_structure_schema, assert_window_schema, body_of};
+    use crate::views::{structure_response, window_response};
+    use alec_complexity::EventSeverity;
+    use axum::http::StatusCode;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -574,23 +962,135 @@ mod tests {
         file
     }
 
+    fn write_csv(lines: &[&str]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file.flush().unwrap();
+        file
+    }
+
+    fn path_of(file: &NamedTempFile) -> String {
+        file.path().to_string_lossy().to_string()
+    }
+
+    fn historian_mapping(columns: &[(&str, &str)]) -> ColumnMapping {
+        ColumnMapping {
+            timestamp_column: "time".to_string(),
+            value_columns: columns
+                .iter()
+                .map(|(col, channel)| (col.to_string(), channel.to_string()))
+                .collect(),
+            timestamp_format: TimestampFormat::Rfc3339,
+        }
+    }
+
     #[test]
     fn test_parse_csv() {
         let file = create_test_csv();
-        let (sensor_ids, rows) = ReplayEngine::parse_csv(file.path()).expect("Failed to parse CSV");
+        let csv = ReplayEngine::parse_csv(file.path(), None).expect("Failed to parse CSV");
+
+        assert_eq!(csv.channels, vec!["temp", "humidity"]);
+        assert_eq!(csv.rows.len(), 3);
+        assert_eq!(csv.rows[0].timestamp_ms, 1000);
+        assert_eq!(csv.rows[0].values.get("temp"), Some(&Some(25.0)));
+    }
+
+    #[test]
+    fn test_timestamp_formats() {
+        assert_eq!(TimestampFormat::UnixMs.parse_ms("1500"), Some(1500));
+        assert_eq!(TimestampFormat::UnixS.parse_ms("2"), Some(2000));
+        assert_eq!(TimestampFormat::UnixS.parse_ms("2.5"), Some(2500));
+        assert_eq!(
+            TimestampFormat::Rfc3339.parse_ms("1970-01-01T00:00:01.250Z"),
+            Some(1250)
+        );
+        assert_eq!(
+            TimestampFormat::Rfc3339.parse_ms("1970-01-01T01:00:01+01:00"),
+            Some(1000)
+        );
+        assert_eq!(TimestampFormat::Rfc3339.parse_ms("yesterday"), None);
+        assert_eq!(TimestampFormat::UnixMs.parse_ms("-5"), None);
+    }
+
+    #[test]
+    fn test_multiple_csvs_merge_in_order() {
+        let boiler = write_csv(&[
+            "time,BoilerTemp_degC",
+            "2025-03-01T12:00:00Z,71.5",
+            "2025-03-01T12:00:20Z,72.0",
+            "2025-03-01T12:00:40Z,72.5",
+        ]);
+        let pump = write_csv(&[
+            "time,Pump Flow (l/min)",
+            "2025-03-01T12:00:10Z,14.0",
+            "2025-03-01T12:00:20Z,14.5",
+            "not a timestamp,99.0",
+            "2025-03-01T12:00:30Z,15.0",
+        ]);
+
+        let config = ReplayConfig {
+            csv_paths: vec![path_of(&boiler), path_of(&pump)],
+            mapping: Some(historian_mapping(&[
+                ("BoilerTemp_degC", "boiler_temp"),
+                ("Pump Flow (l/min)", "pump_flow"),
+            ])),
+            loop_replay: false,
+            ..Default::default()
+        };
+
+        let engine = ReplayEngine::from_csv(config).unwrap();
+        let base = 1_740_830_400_000u64;
+        let timestamps: Vec<u64> = engine.rows.iter().map(|r| r.timestamp_ms - base).collect();
+        assert_eq!(timestamps, vec![0, 10_000, 20_000, 30_000, 40_000]);
+
+        // Shared timestamp carries both channels
+        assert_eq!(engine.rows[2].values.get("boiler_temp"), Some(&Some(72.0)));
+        assert_eq!(engine.rows[2].values.get("pump_flow"), Some(&Some(14.5)));
+        assert_eq!(engine.rows[1].values.get("pump_flow"), Some(&Some(14.0)));
+        assert_eq!(engine.rows[1].values.get("boiler_temp"), None);
 
-        assert_eq!(sensor_ids, vec!["temp", "humidity"]);
-        assert_eq!(rows.len(), 3);
-        assert_eq!(rows[0].timestamp_ms, 1000);
-        assert_eq!(rows[0].values.get("temp"), Some(&Some(25.0)));
+        let info = engine.dataset_info();
+        assert_eq!(info.sensor_ids, vec!["boiler_temp", "pump_flow"]);
+        assert_eq!(info.sample_count, 5);
+        assert_eq!(info.duration_ms, 40_000);
+        assert_eq!(info.files.len(), 2);
+        assert_eq!(info.files[0].row_count, 3);
+        assert_eq!(info.files[0].skipped_rows, 0);
+        assert_eq!(info.files[1].row_count, 3);
+        assert_eq!(info.files[1].skipped_rows, 1);
+    }
+
+    #[test]
+    fn test_unknown_mapping_columns_rejected() {
+        let file = write_csv(&["time,temp", "2025-03-01T12:00:00Z,20.0"]);
+        let config = ReplayConfig {
+            csv_paths: vec![path_of(&file)],
+            mapping: Some(historian_mapping(&[
+                ("temp", "temp"),
+                ("pressure", "pressure"),
+                ("flow", "flow"),
+            ])),
+            ..Default::default()
+        };
+
+        match ReplayEngine::from_csv(config) {
+            Err(ReplayError::UnknownColumns(columns)) => {
+                assert_eq!(columns, vec!["flow", "pressure"]);
+            }
+            other => panic!("expected UnknownColumns, got {:?}", other.err()),
+        }
     }
 
     #[test]
     fn test_replay_engine_creation() {
         let file = create_test_csv();
         let config = ReplayConfig {
-            csv_path: file.path().to_string_lossy().to_string(),
-            speed: 1.0,
+            csv_paths: vec![path_of(&file)],
+            mapping: None,
+            mode: ReplayMode::Paced { speed: 1.0 },
+            jitter: None,
             loop_replay: false,
             default_sample_interval_ms: 1000,
         };
@@ -604,7 +1104,7 @@ mod tests {
     fn test_dataset_info() {
         let file = create_test_csv();
         let config = ReplayConfig {
-            csv_path: file.path().to_string_lossy().to_string(),
+            csv_paths: vec![path_of(&file)],
             ..Default::default()
         };
 
@@ -615,4 +1115,174 @@ mod tests {
         assert_eq!(info.sample_count, 3);
         assert_eq!(info.duration_ms, 2000);
     }
+
+    #[tokio::test]
+    async fn test_immediate_replay_advances_logical_time() {
+        let mut lines = vec!["timestamp_ms,temp,humidity".to_string()];
+        for i in 0..1000u64 {
+            lines.push(format!(
+                "{},{},{}",
+                1_000 + i * 1_000,
+                20.0 + (i % 7) as f64,
+                60.0
+            ));
+        }
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let file = write_csv(&lines);
+        let engine = ReplayEngine::from_csv(ReplayConfig {
+            csv_paths: vec![path_of(&file)],
+            mode: ReplayMode::Immediate,
+            loop_replay: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        engine.run().await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let state = engine.state();
+        assert_eq!(state.position.load(Ordering::SeqCst), 1000);
+        assert_eq!(
+            state.logical_time_ms.load(Ordering::SeqCst),
+            engine.dataset_info().duration_ms
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_collects_events() {
+        let mut lines = vec!["timestamp_ms,temp,humidity".to_string()];
+        for i in 0..1000u64 {
+            // A burst of erratic values after the baseline locks
+            let temp = if (600..650).contains(&i) {
+                ((i * 7919) % 97) as f64
+            } else {
+                20.0 + (i % 7) as f64
+            };
+            lines.push(format!("{},{},{}", 1_000 + i * 1_000, temp, 60.0));
+        }
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let file = write_csv(&lines);
+        let store = Arc::new(EventStore::new(DEFAULT_EVENT_CAPACITY));
+        let engine = ReplayEngine::from_csv(ReplayConfig {
+            csv_paths: vec![path_of(&file)],
+            mode: ReplayMode::Immediate,
+            loop_replay: false,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_event_store(Arc::clone(&store));
+
+        engine.run().await;
+
+        let all = store.query(&EventQuery::default());
+        assert!(!all.is_empty());
+        assert!(all.iter().all(|stored| stored.source == "replay"));
+        assert!(all.windows(2).all(|pair| pair[0].id < pair[1].id));
+
+        for severity in [
+            EventSeverity::Info,
+            EventSeverity::Warning,
+            EventSeverity::Critical,
+        ] {
+            let filtered = store.query(&EventQuery {
+                severity: Some(severity.as_str().to_string()),
+                ..Default::default()
+            });
+            let expected: Vec<u64> = all
+                .iter()
+                .filter(|stored| stored.event.severity == severity)
+                .map(|stored| stored.id)
+                .collect();
+            assert_eq!(
+                filtered.iter().map(|stored| stored.id).collect::<Vec<_>>(),
+                expected
+            );
+        }
+        assert!(all
+            .iter()
+            .any(|stored| stored.event.severity == EventSeverity::Info));
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_structure_and_window() {
+        let mut lines = vec!["timestamp_ms,temp,humidity,pressure".to_string()];
+        for i in 0..1000u64 {
+            lines.push(format!(
+                "{},{},{},{}",
+                1_000 + i * 1_000,
+                20.0 + (i % 7) as f64,
+                60.0 + (i % 5) as f64,
+                1013.0 + (i % 3) as f64
+            ));
+        }
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let file = write_csv(&lines);
+        let views = Arc::new(ViewStore::new());
+        let engine = ReplayEngine::from_csv(ReplayConfig {
+            csv_paths: vec![path_of(&file)],
+            mode: ReplayMode::Immediate,
+            loop_replay: false,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_view_store(Arc::clone(&views));
+
+        assert_eq!(structure_response(&views).status(), StatusCode::NO_CONTENT);
+        engine.run().await;
+
+        let (status, structure) = body_of(structure_response(&views)).await;
+        assert_eq!(status, StatusCode::OK);
+        let structure = structure.unwrap();
+        assert_structure_schema(&structure);
+        assert_eq!(structure["source"], "replay");
+        assert_eq!(structure["nodes"].as_array().unwrap().len(), 3);
+        assert!(!structure["edges"].as_array().unwrap().is_empty());
+
+        let (status, window) = body_of(window_response(&views)).await;
+        assert_eq!(status, StatusCode::OK);
+        let window = window.unwrap();
+        assert_window_schema(&window);
+        // The window reflects the last row
+        assert_eq!(window["timestamp_ms"], 1_000_000);
+        let names: Vec<&str> = window["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["timestamp_ms", "humidity", "pressure", "temp"]);
+    }
+
+    #[test]
+    fn test_jitter_schedule_is_reproducible() {
+        let config = |seed| ReplayConfig {
+            mode: ReplayMode::Paced { speed: 10.0 },
+            jitter: Some(Jitter { max_ms: 50, seed }),
+            ..Default::default()
+        };
+        let intervals = [1_000, 1_000, 250, 3_000, 1_000, 1_000, 40, 1_000];
+        let schedule = |seed| {
+            let mut pacer = Pacer::new(&config(seed));
+            intervals
+                .iter()
+                .map(|&interval| pacer.delay_ms(interval))
+                .collect::<Vec<_>>()
+        };
+
+        let first = schedule(7);
+        assert_eq!(first, schedule(7));
+        assert_ne!(first, schedule(8));
+        for (delay, interval) in first.iter().zip(intervals) {
+            let paced = interval / 10;
+            assert!(*delay >= paced.saturating_sub(50) && *delay <= paced + 50);
+        }
+
+        // Jitter does not apply to immediate replay
+        let mut pacer = Pacer::new(&ReplayConfig {
+            mode: ReplayMode::Immediate,
+            ..config(7)
+        });
+        assert_eq!(pacer.delay_ms(1_000), 0);
+    }
 }
@@ -0,0 +1,461 @@
+// ALEC Exporter - Structure and window views
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! JSON views of the latest S-lite structure and aligned sample window.
+//!
+//! Prometheus series cannot carry a graph or a matrix. The [`ViewStore`]
+//! keeps the latest S-lite and [`StructureBreak`] of the complexity
+//! snapshots, and the latest [`AlignedWindow`] of the metrics engine, as
+//! written by the replay task and by live ingest. They are served by:
+//!
+//! - `GET /structure`: the S-lite in the Grafana Node Graph shape, channels
+//!   as nodes (`mainStat` is their entropy in bits) and edges carrying
+//!   their weight as `mainStat`, plus the last structure break, if any:
+//!
+//!   ```json
+//!   {"timestamp_ms": 1700000060000, "source": "replay",
+//!    "nodes": [{"id": "temp", "title": "temp", "mainStat": 2.1}],
+//!    "edges": [{"id": "humidity--temp", "source": "humidity", "target": "temp", "mainStat": 0.8}],
+//!    "structure_break": {"timestamp_ms": 1700000050000, "source": "replay", "total_change": 0.4,
+//!                        "changed_edges": [ ... ], "added_channels": [], "removed_channels": []}}
+//!   ```
+//!
+//! - `GET /window`: the aligned matrix as columns, the reference timestamps
+//!   first, then one `number` column per channel (missing values are
+//!   `null`):
+//!
+//!   ```json
+//!   {"timestamp_ms": 1700000060000, "source": "replay", "rows": 2,
+//!    "columns": [{"name": "timestamp_ms", "type": "time", "values": [1700000059000, 1700000060000]},
+//!                {"name": "temp", "type": "number", "values": [21.5, null]}]}
+//!   ```
+//!
+//! `timestamp_ms` is the timestamp of the snapshot the view reflects. Both
+//! endpoints answer `204 No Content` until a snapshot has been seen.
+//! Ingested metrics snapshots carry no sample matrix, so `/window` only
+//! reflects the replay.
+
+use alec_complexity::event::EventDetails;
+use alec_complexity::structure::{EdgeChange, SLite, StructureBreak};
+use alec_complexity::ComplexitySnapshot;
+use alec_gateway::metrics::AlignedWindow;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// A value and where and when it was observed.
+#[derive(Debug, Clone)]
+struct Latest<T> {
+    timestamp_ms: u64,
+    source: String,
+    value: T,
+}
+
+impl<T> Latest<T> {
+    fn new(source: &str, timestamp_ms: u64, value: T) -> Self {
+        Self {
+            timestamp_ms,
+            source: source.to_string(),
+            value,
+        }
+    }
+}
+
+/// Latest S-lite, structure break and aligned window, shared between the
+/// replay and ingest writers and the HTTP handlers.
+#[derive(Debug, Default)]
+pub struct ViewStore {
+    structure: Mutex<Option<Latest<SLite>>>,
+    last_break: Mutex<Option<Latest<StructureBreak>>>,
+    window: Mutex<Option<Latest<AlignedWindow>>>,
+}
+
+impl ViewStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the S-lite and structure break of a complexity snapshot from
+    /// `source`.
+    ///
+    /// Snapshots without an S-lite (baseline still building) leave the
+    /// previous structure in place.
+    pub fn record_complexity(&self, source: &str, snapshot: &ComplexitySnapshot) {
+        if let Some(ref s_lite) = snapshot.s_lite {
+            *self.structure.lock().unwrap() =
+                Some(Latest::new(source, snapshot.timestamp_ms, s_lite.clone()));
+        }
+        let last_break = snapshot
+            .events
+            .iter()
+            .rev()
+            .find_map(|event| match event.details {
+                EventDetails::Structure(ref structure_break) => Some(Latest::new(
+                    source,
+                    event.timestamp_ms,
+                    structure_break.clone(),
+                )),
+                _ => None,
+            });
+        if last_break.is_some() {
+            *self.last_break.lock().unwrap() = last_break;
+        }
+    }
+
+    /// Keep the aligned window of the metrics snapshot taken at
+    /// `timestamp_ms` by `source`.
+    #[cfg_attr(not(feature = "replay"), allow(dead_code))]
+    pub fn record_window(&self, source: &str, timestamp_ms: u64, window: &AlignedWindow) {
+        *self.window.lock().unwrap() = Some(Latest::new(source, timestamp_ms, window.clone()));
+    }
+
+    /// Latest structure as a node graph, `None` before the first S-lite.
+    pub fn structure(&self) -> Option<StructureGraph> {
+        let structure = self.structure.lock().unwrap().clone()?;
+        let structure_break = self.last_break.lock().unwrap().clone().map(BreakView::from);
+        Some(StructureGraph::new(structure, structure_break))
+    }
+
+    /// Latest aligned window as columns, `None` before the first one.
+    pub fn window(&self) -> Option<WindowTable> {
+        let window = self.window.lock().unwrap().clone()?;
+        Some(WindowTable::from(window))
+    }
+}
+
+/// A channel of the structure graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    /// Channel entropy, in bits.
+    #[serde(rename = "mainStat")]
+    pub main_stat: f64,
+}
+
+/// An S-lite edge of the structure graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    /// `<source>--<target>`.
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    /// Edge weight.
+    #[serde(rename = "mainStat")]
+    pub main_stat: f64,
+}
+
+/// The last structure break and where it was observed.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakView {
+    pub timestamp_ms: u64,
+    pub source: String,
+    pub total_change: f64,
+    pub changed_edges: Vec<EdgeChange>,
+    pub added_channels: Vec<String>,
+    pub removed_channels: Vec<String>,
+}
+
+impl From<Latest<StructureBreak>> for BreakView {
+    fn from(latest: Latest<StructureBreak>) -> Self {
+        Self {
+            timestamp_ms: latest.timestamp_ms,
+            source: latest.source,
+            total_change: latest.value.total_change,
+            changed_edges: latest.value.changed_edges,
+            added_channels: latest.value.added_channels,
+            removed_channels: latest.value.removed_channels,
+        }
+    }
+}
+
+/// Body of `GET /structure`.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureGraph {
+    /// Timestamp of the S-lite.
+    pub timestamp_ms: u64,
+    /// `replay` or the ingesting `gateway_id`.
+    pub source: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structure_break: Option<BreakView>,
+}
+
+impl StructureGraph {
+    fn new(structure: Latest<SLite>, structure_break: Option<BreakView>) -> Self {
+        let nodes = structure
+            .value
+            .channels
+            .iter()
+            .map(|channel| GraphNode {
+                id: channel.channel_id.clone(),
+                title: channel.channel_id.clone(),
+                main_stat: channel.h,
+            })
+            .collect();
+        let edges = structure
+            .value
+            .edges
+            .iter()
+            .map(|edge| GraphEdge {
+                id: format!("{}--{}", edge.channel_a, edge.channel_b),
+                source: edge.channel_a.clone(),
+                target: edge.channel_b.clone(),
+                main_stat: edge.weight,
+            })
+            .collect();
+        Self {
+            timestamp_ms: structure.timestamp_ms,
+            source: structure.source,
+            nodes,
+            edges,
+            structure_break,
+        }
+    }
+}
+
+/// Values of a window column, tagged with their Grafana field type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "values", rename_all = "lowercase")]
+pub enum ColumnValues {
+    Time(Vec<u64>),
+    Number(Vec<Option<f64>>),
+}
+
+/// A column of the aligned window.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowColumn {
+    pub name: String,
+    #[serde(flatten)]
+    pub values: ColumnValues,
+}
+
+/// Body of `GET /window`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowTable {
+    /// Timestamp of the metrics snapshot the window was aligned for.
+    pub timestamp_ms: u64,
+    /// `replay` or the ingesting `gateway_id`.
+    pub source: String,
+    /// Number of aligned rows.
+    pub rows: usize,
+    /// `timestamp_ms`, then the channels sorted by ID.
+    pub columns: Vec<WindowColumn>,
+}
+
+impl From<Latest<AlignedWindow>> for WindowTable {
+    fn from(latest: Latest<AlignedWindow>) -> Self {
+        let window = latest.value;
+        let mut columns = Vec::with_capacity(window.channels.len() + 1);
+        columns.push(WindowColumn {
+            name: "timestamp_ms".to_string(),
+            values: ColumnValues::Time(window.timestamps.clone()),
+        });
+        for (j, channel) in window.channels.iter().enumerate() {
+            let values = window
+                .values
+                .iter()
+                .map(|row| Some(row[j]).filter(|v| v.is_finite()))
+                .collect();
+            columns.push(WindowColumn {
+                name: channel.clone(),
+                values: ColumnValues::Number(values),
+            });
+        }
+        Self {
+            timestamp_ms: latest.timestamp_ms,
+            source: latest.source,
+            rows: window.len(),
+            columns,
+        }
+    }
+}
+
+/// `200` with `view` as JSON, or `204` when there is nothing to show yet.
+fn json_or_no_content<T: Serialize>(view: Option<T>) -> Response {
+    match view {
+        Some(view) => Json(view).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Response of `GET /structure`.
+pub fn structure_response(views: &ViewStore) -> Response {
+    json_or_no_content(views.structure())
+}
+
+/// Response of `GET /window`.
+pub fn window_response(views: &ViewStore) -> Response {
+    json_or_no_content(views.window())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alec_complexity::baseline::Baseline;
+    use alec_complexity::structure::SLiteEdge;
+    use alec_complexity::{ChannelEntropy, ComplexityEvent};
+    use serde_json::Value;
+
+    /// JSON body and status of a handler response.
+    pub(crate) async fn body_of(response: Response) -> (StatusCode, Option<Value>) {
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = (!bytes.is_empty()).then(|| serde_json::from_slice(&bytes).unwrap());
+        (status, body)
+    }
+
+    /// Check `body` against the documented `GET /structure` schema.
+    pub(crate) fn assert_structure_schema(body: &Value) {
+        assert!(body["timestamp_ms"].is_u64());
+        assert!(body["source"].is_string());
+        for node in body["nodes"].as_array().unwrap() {
+            assert!(node["id"].is_string());
+            assert_eq!(node["title"], node["id"]);
+            assert!(node["mainStat"].is_f64());
+        }
+        let ids: Vec<&Value> = body["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| &n["id"])
+            .collect();
+        for edge in body["edges"].as_array().unwrap() {
+            assert!(edge["id"].is_string());
+            assert!(ids.contains(&&edge["source"]));
+            assert!(ids.contains(&&edge["target"]));
+            assert!(edge["mainStat"].is_f64());
+        }
+        if let Some(structure_break) = body.get("structure_break") {
+            assert!(structure_break["timestamp_ms"].is_u64());
+            assert!(structure_break["total_change"].is_f64());
+            assert!(structure_break["changed_edges"].is_array());
+        }
+    }
+
+    /// Check `body` against the documented `GET /window` schema.
+    pub(crate) fn assert_window_schema(body: &Value) {
+        assert!(body["timestamp_ms"].is_u64());
+        assert!(body["source"].is_string());
+        let rows = body["rows"].as_u64().unwrap() as usize;
+        let columns = body["columns"].as_array().unwrap();
+        assert_eq!(columns[0]["name"], "timestamp_ms");
+        assert_eq!(columns[0]["type"], "time");
+        for (i, column) in columns.iter().enumerate() {
+            let values = column["values"].as_array().unwrap();
+            assert_eq!(values.len(), rows);
+            if i > 0 {
+                assert!(column["name"].is_string());
+                assert_eq!(column["type"], "number");
+                assert!(values.iter().all(|v| v.is_f64() || v.is_null()));
+            } else {
+                assert!(values.iter().all(Value::is_u64));
+            }
+        }
+    }
+
+    fn s_lite(timestamp_ms: u64) -> SLite {
+        SLite {
+            edges: vec![SLiteEdge {
+                channel_a: "humidity".to_string(),
+                channel_b: "temp".to_string(),
+                weight: 0.75,
+            }],
+            channel_count: 2,
+            timestamp_ms,
+            channels: vec![
+                ChannelEntropy {
+                    channel_id: "humidity".to_string(),
+                    h: 1.5,
+                },
+                ChannelEntropy {
+                    channel_id: "temp".to_string(),
+                    h: 2.0,
+                },
+            ],
+            change: None,
+        }
+    }
+
+    fn snapshot(timestamp_ms: u64, events: Vec<ComplexityEvent>) -> ComplexitySnapshot {
+        ComplexitySnapshot::new(
+            timestamp_ms,
+            &Baseline::new(false),
+            None,
+            None,
+            Some(s_lite(timestamp_ms)),
+            events,
+            Vec::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_no_content_before_first_snapshot() {
+        let views = ViewStore::new();
+        let (status, body) = body_of(structure_response(&views)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(body.is_none());
+        let (status, _) = body_of(window_response(&views)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_structure_keeps_last_break() {
+        let views = ViewStore::new();
+        let structure_break = ComplexityEvent::structure_break(
+            1_000,
+            StructureBreak {
+                changed_edges: Vec::new(),
+                total_change: 0.5,
+                added_channels: vec!["temp".to_string()],
+                removed_channels: Vec::new(),
+            },
+        );
+        views.record_complexity("gw-01", &snapshot(1_000, vec![structure_break]));
+        // A later snapshot without a break keeps the previous one
+        views.record_complexity("gw-01", &snapshot(2_000, Vec::new()));
+
+        let (status, body) = body_of(structure_response(&views)).await;
+        assert_eq!(status, StatusCode::OK);
+        let body = body.unwrap();
+        assert_structure_schema(&body);
+        assert_eq!(body["timestamp_ms"], 2_000);
+        assert_eq!(body["source"], "gw-01");
+        assert_eq!(body["edges"][0]["id"], "humidity--temp");
+        assert_eq!(body["edges"][0]["mainStat"], 0.75);
+        assert_eq!(body["structure_break"]["timestamp_ms"], 1_000);
+        assert_eq!(body["structure_break"]["added_channels"][0], "temp");
+    }
+
+    #[tokio::test]
+    async fn test_window_missing_values_are_null() {
+        let views = ViewStore::new();
+        views.record_window(
+            "replay",
+            2_000,
+            &AlignedWindow {
+                timestamps: vec![1_000, 2_000],
+                channels: vec!["humidity".to_string(), "temp".to_string()],
+                values: vec![vec![60.0, 21.5], vec![f64::NAN, 22.0]],
+            },
+        );
+
+        let (status, body) = body_of(window_response(&views)).await;
+        assert_eq!(status, StatusCode::OK);
+        let body = body.unwrap();
+        assert_window_schema(&body);
+        assert_eq!(body["rows"], 2);
+        assert_eq!(body["columns"][1]["name"], "humidity");
+        assert!(body["columns"][1]["values"][1].is_null());
+        assert_eq!(body["columns"][2]["values"][1], 22.0);
+    }
+}
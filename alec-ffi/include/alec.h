@@ -58,6 +58,14 @@ typedef struct AlecEncoder AlecEncoder;
  */
 typedef struct AlecDecoder AlecDecoder;
 
+/**
+ * Opaque gateway handle (multi-channel priority packing).
+ *
+ * Created with alec_gateway_new(), freed with alec_gateway_free().
+ * Only available when the library is built with the `gateway` feature.
+ */
+typedef struct AlecGateway AlecGateway;
+
 /**
  * Result codes for ALEC functions.
  */
@@ -184,6 +192,18 @@ AlecEncoder* alec_encoder_new(void);
  */
 AlecEncoder* alec_encoder_new_with_checksum(void);
 
+/**
+ * Create a new encoder that appends a checksum only to important messages.
+ *
+ * Messages classified at threshold_priority or more important carry a
+ * trailing 4-byte checksum. Pair with alec_decoder_new_with_policy()
+ * using the same threshold.
+ *
+ * @param threshold_priority Least important priority (1-5) still checksummed
+ * @return Pointer to new encoder, or NULL if threshold_priority is not 1-5.
+ */
+AlecEncoder* alec_encoder_new_with_policy(uint8_t threshold_priority);
+
 /**
  * Create a new ALEC encoder with a custom configuration.
  *
@@ -201,6 +221,25 @@ AlecEncoder* alec_encoder_new_with_checksum(void);
  */
 AlecEncoder* alec_encoder_new_with_config(const AlecEncoderConfig* config);
 
+/**
+ * Create a new encoder that can be shared between threads.
+ *
+ * Same as alec_encoder_new(), but every call on the handle takes an
+ * internal lock (a mutex on hosted builds, a spin lock on no_std
+ * builds), so several threads or RTOS tasks may use it without a mutex
+ * of their own. Handles from the other constructors stay lock-free.
+ *
+ * The lock does not protect the handle's lifetime: alec_encoder_free()
+ * waits for the current lock holder and refuses a second free call made
+ * while the first one runs, but once the handle is dropped a later free
+ * call, or a thread still waiting for the lock, reads freed memory.
+ *
+ * @return Pointer to new encoder, or NULL on allocation failure.
+ *         Must be freed with alec_encoder_free(), once, after every
+ *         other thread is done with it.
+ */
+AlecEncoder* alec_encoder_new_threadsafe(void);
+
 /**
  * Force the next encode call to emit a keyframe (Raw32 for all channels).
  *
@@ -259,7 +298,12 @@ AlecResult alec_downlink_handler(
  *
  * @param encoder Encoder to free. May be NULL (no-op).
  *
- * @warning Do not use the encoder after calling this function.
+ * A thread-safe encoder is freed once the thread holding its lock
+ * releases it. A second free call is refused only while it races the
+ * first one; after that it reads freed memory.
+ *
+ * @warning Do not use the encoder after calling this function, nor
+ *          start a call on it from another thread while it is freed.
  */
 void alec_encoder_free(AlecEncoder* encoder);
 
@@ -286,6 +330,35 @@ AlecResult alec_encode_value(
     size_t* output_len
 );
 
+/**
+ * Encode a single value with a priority chosen by the application.
+ *
+ * Bypasses the classifier: the message carries the given priority and the
+ * operator-override header flag.
+ *
+ * @param encoder        Encoder handle (must not be NULL).
+ * @param value          The value to encode.
+ * @param timestamp      Timestamp for the value (can be 0 if not used).
+ * @param priority       Priority to send (1-5, 1 = P1 critical).
+ * @param source_id      Source identifier string (null-terminated, can be NULL).
+ * @param output         Output buffer for encoded data.
+ * @param output_capacity Size of output buffer in bytes.
+ * @param output_len     Pointer to store actual encoded length.
+ *
+ * @return ALEC_OK on success, ALEC_ERROR_INVALID_INPUT if priority is
+ *         outside 1-5, error code otherwise.
+ */
+AlecResult alec_encode_value_with_priority(
+    AlecEncoder* encoder,
+    double value,
+    uint64_t timestamp,
+    uint8_t priority,
+    const char* source_id,
+    uint8_t* output,
+    size_t output_capacity,
+    size_t* output_len
+);
+
 /**
  * Encode multiple values with adaptive per-channel compression.
  *
@@ -298,7 +371,7 @@ AlecResult alec_encode_value(
  *
  * @param encoder         Encoder handle.
  * @param values          Array of f64 values (one per channel).
- * @param value_count     Number of channels.
+ * @param value_count     Number of channels (1-255).
  * @param timestamps      Per-channel timestamps (array of uint64_t),
  *                        or NULL to use 0 for all channels.
  * @param source_ids      Per-channel source identifier strings (array of
@@ -361,9 +434,10 @@ AlecResult alec_encoder_load_context(
 /**
  * Get the current context version.
  *
- * The context version changes when the encoder learns new patterns.
- * Encoder and decoder must have matching versions for correct
- * decompression.
+ * The context version is the dictionary version: it changes only when
+ * the encoder learns, prunes or renumbers patterns, not on every
+ * encoded value. Encoder and decoder must have matching versions for
+ * correct decompression.
  *
  * @param encoder Encoder handle.
  *
@@ -477,6 +551,18 @@ AlecDecoder* alec_decoder_new(void);
  */
 AlecDecoder* alec_decoder_new_with_checksum(void);
 
+/**
+ * Create a new decoder that verifies checksums only on important messages.
+ *
+ * Counterpart of alec_encoder_new_with_policy(): messages whose header
+ * priority is at threshold_priority or more important must carry a valid
+ * checksum.
+ *
+ * @param threshold_priority Least important priority (1-5) still checksummed
+ * @return Pointer to new decoder, or NULL if threshold_priority is not 1-5.
+ */
+AlecDecoder* alec_decoder_new_with_policy(uint8_t threshold_priority);
+
 /**
  * Create a new ALEC decoder with a custom configuration.
  *
@@ -497,6 +583,17 @@ AlecDecoder* alec_decoder_new_with_checksum(void);
  */
 AlecDecoder* alec_decoder_new_with_config(const AlecEncoderConfig* config);
 
+/**
+ * Create a new decoder that can be shared between threads.
+ *
+ * Decoder counterpart of alec_encoder_new_threadsafe(), with the same
+ * limits on freeing.
+ *
+ * @return Pointer to new decoder, or NULL on allocation failure.
+ *         Must be freed with alec_decoder_free(), once.
+ */
+AlecDecoder* alec_decoder_new_threadsafe(void);
+
 /**
  * Reset a decoder to its initial state.
  *
@@ -518,6 +615,8 @@ void alec_decoder_reset(AlecDecoder* dec);
  * Free a decoder.
  *
  * @param decoder Decoder to free. May be NULL (no-op).
+ *
+ * Thread-safe decoders are freed as in alec_encoder_free().
  */
 void alec_decoder_free(AlecDecoder* decoder);
 
@@ -561,6 +660,40 @@ AlecResult alec_decode_multi(
     size_t* values_count
 );
 
+/** Timestamp written by alec_decode_multi_ex() for compressed header timestamps. */
+#define ALEC_TIMESTAMP_UNKNOWN UINT64_MAX
+
+/**
+ * Decode compressed data to multiple values with their channel ids and
+ * timestamps.
+ *
+ * Entry i of values, name_ids and timestamps describes the same decoded
+ * value. Timestamps are the frame timestamp in seconds, or
+ * ALEC_TIMESTAMP_UNKNOWN. A frame with zero values succeeds with
+ * *values_count == 0.
+ *
+ * @param decoder      Decoder handle.
+ * @param input        Compressed input data.
+ * @param input_len    Length of input data.
+ * @param values       Output buffer for decoded values.
+ * @param name_ids     Output buffer for channel name ids.
+ * @param timestamps   Output buffer for timestamps.
+ * @param capacity     Number of entries each output buffer can hold.
+ * @param values_count Pointer to store actual number of decoded values.
+ *
+ * @return ALEC_OK on success, error code otherwise.
+ */
+AlecResult alec_decode_multi_ex(
+    AlecDecoder* decoder,
+    const uint8_t* input,
+    size_t input_len,
+    double* values,
+    uint16_t* name_ids,
+    uint64_t* timestamps,
+    size_t capacity,
+    size_t* values_count
+);
+
 /**
  * Load decoder context from a preload file.
  *
@@ -577,6 +710,8 @@ AlecResult alec_decoder_load_context(
 /**
  * Get the current decoder context version.
  *
+ * Like alec_encoder_context_version(), this is the dictionary version.
+ *
  * @param decoder Decoder handle.
  *
  * @return Context version number, or 0 if decoder is NULL.
@@ -899,6 +1034,100 @@ AlecResult alec_decoder_context_load(
     size_t buf_len
 );
 
+/* ============================================================================
+ * Gateway (multi-channel frames)
+ *
+ * Priority packing of many named channels into one size-limited frame,
+ * on top of the alec-gateway crate. Only available when the library is
+ * built with the `gateway` Cargo feature (on by default for hosted
+ * builds, off for `--no-default-features` MCU builds).
+ * ============================================================================ */
+
+/**
+ * Create a new gateway.
+ *
+ * @param max_frame_size Maximum flushed frame size in bytes. 0 selects
+ *                       the default (242 bytes, LoRaWAN DR4/DR5).
+ *
+ * @return Gateway handle, or NULL on allocation failure.
+ */
+AlecGateway* alec_gateway_new(size_t max_frame_size);
+
+/**
+ * Free a gateway. NULL is a no-op.
+ */
+void alec_gateway_free(AlecGateway* gateway);
+
+/**
+ * Register a sensor channel.
+ *
+ * @param gateway  Gateway handle.
+ * @param name     Null-terminated UTF-8 channel name (1..=255 bytes).
+ * @param priority Packing priority, 0 = highest.
+ *
+ * @return ALEC_OK on success; ALEC_ERROR_NULL_POINTER for a NULL
+ *         pointer; ALEC_ERROR_INVALID_UTF8 if name is not valid UTF-8;
+ *         ALEC_ERROR_INVALID_INPUT for an empty/oversized name, a
+ *         duplicate channel or when the channel limit is reached.
+ */
+AlecResult alec_gateway_add_channel(
+    AlecGateway* gateway,
+    const char* name,
+    uint8_t priority
+);
+
+/**
+ * Push a sensor value to a channel.
+ *
+ * @return ALEC_OK on success; ALEC_ERROR_NULL_POINTER for a NULL
+ *         pointer; ALEC_ERROR_INVALID_UTF8 if name is not valid UTF-8;
+ *         ALEC_ERROR_INVALID_INPUT for an unknown channel;
+ *         ALEC_ERROR_BUFFER_TOO_SMALL if the channel buffer is full
+ *         (flush first).
+ */
+AlecResult alec_gateway_push(
+    AlecGateway* gateway,
+    const char* name,
+    double value,
+    uint64_t timestamp
+);
+
+/**
+ * Flush all channels into a single priority-packed frame.
+ *
+ * On ALEC_ERROR_BUFFER_TOO_SMALL `*out_len` reports the required size,
+ * `out_buf` is unchanged and the encoded frame is retained: the next
+ * call returns it, so no samples are lost.
+ *
+ * @param gateway Gateway handle.
+ * @param out_buf Destination buffer.
+ * @param cap     Size of `out_buf` in bytes.
+ * @param out_len Out: bytes written (on success) or required size
+ *                (on ALEC_ERROR_BUFFER_TOO_SMALL).
+ *
+ * @return ALEC_OK on success; ALEC_ERROR_BUFFER_TOO_SMALL if `cap` is
+ *         too small; ALEC_ERROR_ENCODING_FAILED if a channel failed to
+ *         encode; ALEC_ERROR_NULL_POINTER for a NULL pointer.
+ */
+AlecResult alec_gateway_flush(
+    AlecGateway* gateway,
+    uint8_t* out_buf,
+    size_t cap,
+    size_t* out_len
+);
+
+/**
+ * Report the number of channels and samples included in the most
+ * recent successful flush. Either out pointer may be NULL.
+ *
+ * @return ALEC_OK on success; ALEC_ERROR_NULL_POINTER if gateway is NULL.
+ */
+AlecResult alec_gateway_last_flush_stats(
+    const AlecGateway* gateway,
+    size_t* out_channels,
+    size_t* out_samples
+);
+
 /* ============================================================================
  * Bare-metal / RTOS heap initialization
  *
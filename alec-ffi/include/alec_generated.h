@@ -37,6 +37,17 @@
  */
 #define ALEC_DEFAULT_NUM_CHANNELS 0
 
+/**
+ * Timestamp written by `alec_decode_multi_ex` when the frame header only
+ * carries a compressed timestamp.
+ */
+#define ALEC_TIMESTAMP_UNKNOWN UINT64_MAX
+
+/**
+ * Default maximum frame size used by `alec_gateway_new(0)` (LoRaWAN DR4+).
+ */
+#define ALEC_DEFAULT_GATEWAY_FRAME_SIZE 242
+
 /**
  * Result codes for ALEC FFI functions
  */
@@ -87,8 +98,8 @@ typedef enum AlecResult {
 /**
  * Opaque decoder handle
  *
- * Created with `alec_decoder_new()`, freed with `alec_decoder_free()`.
- * Do not access internal fields directly.
+ * Created with `alec_decoder_new()` (or `alec_decoder_new_threadsafe()`),
+ * freed with `alec_decoder_free()`. Do not access internal fields directly.
  *
  * Decoder FFI is only available when the `decoder` Cargo feature is
  * enabled (default on hosted/server builds, off on `zephyr`/MCU builds).
@@ -98,11 +109,19 @@ typedef struct AlecDecoder AlecDecoder;
 /**
  * Opaque encoder handle
  *
- * Created with `alec_encoder_new()`, freed with `alec_encoder_free()`.
- * Do not access internal fields directly.
+ * Created with `alec_encoder_new()` (or `alec_encoder_new_threadsafe()`),
+ * freed with `alec_encoder_free()`. Do not access internal fields directly.
  */
 typedef struct AlecEncoder AlecEncoder;
 
+/**
+ * Opaque gateway handle
+ *
+ * Created with `alec_gateway_new()`, freed with `alec_gateway_free()`.
+ * Do not access internal fields directly.
+ */
+typedef struct AlecGateway AlecGateway;
+
 /**
  * Runtime configuration for a new ALEC encoder.
  *
@@ -223,6 +242,26 @@ struct AlecEncoder *alec_encoder_new(void);
  */
 struct AlecEncoder *alec_encoder_new_with_checksum(void);
 
+/**
+ * Create a new encoder that appends a checksum only to important messages
+ *
+ * Messages classified at `threshold_priority` or more important carry a
+ * trailing 4-byte checksum; less important ones are sent without it.
+ * The matching decoder must be created with
+ * `alec_decoder_new_with_policy()` and the same threshold.
+ *
+ * # Arguments
+ *
+ * * `threshold_priority` - Least important priority (1–5) that still
+ *   carries a checksum. 2 covers P1 and P2.
+ *
+ * # Returns
+ *
+ * A pointer to a new encoder, or NULL if `threshold_priority` is not
+ * in 1–5.
+ */
+struct AlecEncoder *alec_encoder_new_with_policy(uint8_t threshold_priority);
+
 /**
  * Create a new ALEC encoder with a custom configuration.
  *
@@ -253,6 +292,28 @@ struct AlecEncoder *alec_encoder_new_with_checksum(void);
  */
 struct AlecEncoder *alec_encoder_new_with_config(const struct AlecEncoderConfig *config);
 
+/**
+ * Create a new encoder that can be shared between threads
+ *
+ * Same as `alec_encoder_new()`, but every call on the handle takes an
+ * internal lock, so several threads (or RTOS tasks) may use it without
+ * a mutex of their own. Calls block while another thread holds the
+ * lock. Handles from the other constructors stay lock-free.
+ *
+ * The lock does not protect the handle's lifetime. `alec_encoder_free()`
+ * waits for the current lock holder, and refuses a second free call
+ * made while the first one runs. Once the handle is dropped, its memory
+ * is gone: a later free call, or a thread still waiting for the lock,
+ * reads freed memory. Free the handle once, after every other thread
+ * is done with it.
+ *
+ * # Returns
+ *
+ * A pointer to a new encoder, or NULL on allocation failure.
+ * The encoder must be freed with `alec_encoder_free()`, once.
+ */
+struct AlecEncoder *alec_encoder_new_threadsafe(void);
+
 /**
  * Force the next encode call to emit a keyframe (Raw32 for all channels).
  *
@@ -323,7 +384,11 @@ enum AlecResult alec_downlink_handler(struct AlecEncoder *encoder,
  *
  * # Safety
  *
- * The encoder must not be used after calling this function.
+ * The encoder must not be used after calling this function. A
+ * thread-safe encoder is freed once the thread holding its lock
+ * releases it. A second free call is refused only while it races the
+ * first one; after that it reads freed memory, as does any thread
+ * still waiting for the lock (see `alec_encoder_new_threadsafe()`).
  */
 void alec_encoder_free(struct AlecEncoder *encoder);
 
@@ -352,6 +417,38 @@ enum AlecResult alec_encode_value(struct AlecEncoder *encoder,
                                   uintptr_t output_capacity,
                                   uintptr_t *output_len);
 
+/**
+ * Encode a single value with a priority chosen by the application
+ *
+ * Same as `alec_encode_value`, but the classifier is bypassed: the
+ * message carries `priority` and the operator-override header flag so
+ * the receiver can tell it from a classifier-assigned priority.
+ *
+ * # Arguments
+ *
+ * * `encoder` - Encoder handle (must not be NULL)
+ * * `value` - The value to encode
+ * * `timestamp` - Timestamp for the value (can be 0 if not used)
+ * * `priority` - Priority to send (1–5, 1 = P1 critical)
+ * * `source_id` - Source identifier string (null-terminated, can be NULL)
+ * * `output` - Output buffer for encoded data
+ * * `output_capacity` - Size of output buffer in bytes
+ * * `output_len` - Pointer to store actual encoded length
+ *
+ * # Returns
+ *
+ * `ALEC_OK` on success, `ALEC_ERROR_INVALID_INPUT` if `priority` is
+ * outside 1–5, other error codes as for `alec_encode_value`.
+ */
+enum AlecResult alec_encode_value_with_priority(struct AlecEncoder *encoder,
+                                                double value,
+                                                uint64_t timestamp,
+                                                uint8_t priority,
+                                                const char *source_id,
+                                                uint8_t *output,
+                                                uintptr_t output_capacity,
+                                                uintptr_t *output_len);
+
 /**
  * Encode multiple values with adaptive per-channel compression.
  *
@@ -363,7 +460,7 @@ enum AlecResult alec_encode_value(struct AlecEncoder *encoder,
  *
  * * `encoder` - Encoder handle
  * * `values` - Array of f64 values to encode (one per channel)
- * * `value_count` - Number of channels
+ * * `value_count` - Number of channels, 1 to 255
  * * `timestamps` - Per-channel timestamps (array of uint64_t), or NULL to
  *   use 0 for all channels
  * * `source_ids` - Per-channel source identifier strings (array of
@@ -422,6 +519,9 @@ enum AlecResult alec_encoder_load_context(struct AlecEncoder *encoder, const cha
 /**
  * Get the current context version
  *
+ * This is the dictionary version: it changes only when patterns are
+ * added, removed or renumbered, not on every encoded value.
+ *
  * # Arguments
  *
  * * `encoder` - Encoder handle
@@ -555,6 +655,20 @@ struct AlecDecoder *alec_decoder_new(void);
  */
 struct AlecDecoder *alec_decoder_new_with_checksum(void);
 
+/**
+ * Create a new decoder that verifies checksums only on important messages
+ *
+ * Counterpart of `alec_encoder_new_with_policy()`: messages whose header
+ * priority is at `threshold_priority` or more important must end with a
+ * valid checksum; others are decoded without one.
+ *
+ * # Returns
+ *
+ * A pointer to a new decoder, or NULL if `threshold_priority` is not
+ * in 1–5.
+ */
+struct AlecDecoder *alec_decoder_new_with_policy(uint8_t threshold_priority);
+
 /**
  * Create a new ALEC decoder with a custom configuration.
  *
@@ -578,6 +692,19 @@ struct AlecDecoder *alec_decoder_new_with_checksum(void);
  */
 struct AlecDecoder *alec_decoder_new_with_config(const struct AlecEncoderConfig *config);
 
+/**
+ * Create a new decoder that can be shared between threads
+ *
+ * Decoder counterpart of `alec_encoder_new_threadsafe()`, with the
+ * same limits on freeing.
+ *
+ * # Returns
+ *
+ * A pointer to a new decoder, or NULL on allocation failure.
+ * The decoder must be freed with `alec_decoder_free()`, once.
+ */
+struct AlecDecoder *alec_decoder_new_threadsafe(void);
+
 /**
  * Reset a decoder to its initial state.
  *
@@ -622,6 +749,12 @@ bool alec_decoder_gap_detected(const struct AlecDecoder *decoder, uint8_t *out_g
  * # Arguments
  *
  * * `decoder` - Decoder to free. May be NULL (no-op).
+ *
+ * # Safety
+ *
+ * As for `alec_encoder_free()`: a thread-safe decoder is freed once its
+ * lock is released, and a second free call is refused only while it
+ * races the first one.
  */
 void alec_decoder_free(struct AlecDecoder *decoder);
 
@@ -669,6 +802,39 @@ enum AlecResult alec_decode_multi(struct AlecDecoder *decoder,
                                   uintptr_t values_capacity,
                                   uintptr_t *values_count);
 
+/**
+ * Decode compressed data to multiple values with their channel ids and
+ * timestamps
+ *
+ * Fills three parallel arrays: entry `i` of `values`, `name_ids` and
+ * `timestamps` describes the same decoded value. Timestamps are the frame
+ * timestamp in seconds, or `ALEC_TIMESTAMP_UNKNOWN`. A frame with zero
+ * values succeeds with `*values_count == 0`.
+ *
+ * # Arguments
+ *
+ * * `decoder` - Decoder handle
+ * * `input` - Compressed input data
+ * * `input_len` - Length of input data
+ * * `values` - Output buffer for decoded values
+ * * `name_ids` - Output buffer for channel name ids
+ * * `timestamps` - Output buffer for timestamps
+ * * `capacity` - Number of entries each output buffer can hold
+ * * `values_count` - Pointer to store actual number of decoded values
+ *
+ * # Returns
+ *
+ * `ALEC_OK` on success, error code otherwise.
+ */
+enum AlecResult alec_decode_multi_ex(struct AlecDecoder *decoder,
+                                     const uint8_t *input,
+                                     uintptr_t input_len,
+                                     double *values,
+                                     uint16_t *name_ids,
+                                     uint64_t *timestamps,
+                                     uintptr_t capacity,
+                                     uintptr_t *values_count);
+
 /**
  * Load decoder context from a preload file
  *
@@ -686,6 +852,9 @@ enum AlecResult alec_decoder_load_context(struct AlecDecoder *decoder, const cha
 /**
  * Get the current decoder context version
  *
+ * This is the dictionary version: it changes only when patterns are
+ * added, removed or renumbered, not on every decoded value.
+ *
  * # Arguments
  *
  * * `decoder` - Decoder handle
@@ -1000,6 +1169,147 @@ enum AlecResult alec_decoder_context_load(struct AlecDecoder *dec,
                                           const uint8_t *buf,
                                           uintptr_t buf_len);
 
+/**
+ * Create a new ALEC gateway
+ *
+ * # Arguments
+ *
+ * * `max_frame_size` - Maximum size of a flushed frame in bytes. `0`
+ *   selects the default (242 bytes, LoRaWAN DR4/DR5).
+ *
+ * # Returns
+ *
+ * A pointer to a new gateway, or NULL on allocation failure.
+ * The gateway must be freed with `alec_gateway_free()` when no longer needed.
+ *
+ * # Example (C)
+ *
+ * ```c
+ * AlecGateway* gw = alec_gateway_new(242);
+ * alec_gateway_add_channel(gw, "temperature", 1);
+ * alec_gateway_push(gw, "temperature", 22.5, 1000);
+ *
+ * uint8_t frame[242];
+ * size_t frame_len;
+ * if (alec_gateway_flush(gw, frame, sizeof(frame), &frame_len) == ALEC_OK) {
+ *     // Send frame[0..frame_len]
+ * }
+ * alec_gateway_free(gw);
+ * ```
+ */
+struct AlecGateway *alec_gateway_new(uintptr_t max_frame_size);
+
+/**
+ * Free a gateway
+ *
+ * # Arguments
+ *
+ * * `gateway` - Gateway to free. May be NULL (no-op).
+ *
+ * # Safety
+ *
+ * The gateway must not be used after calling this function.
+ */
+void alec_gateway_free(struct AlecGateway *gateway);
+
+/**
+ * Register a sensor channel on the gateway
+ *
+ * # Arguments
+ *
+ * * `gateway` - Gateway handle
+ * * `name` - Channel name (null-terminated UTF-8, ≤ 255 bytes)
+ * * `priority` - Packing priority, 0 = highest. Higher-priority channels
+ *   are placed first in the frame and survive frame-size truncation.
+ *
+ * # Returns
+ *
+ * * `ALEC_OK` on success.
+ * * `ALEC_ERROR_NULL_POINTER` if `gateway` or `name` is NULL.
+ * * `ALEC_ERROR_INVALID_UTF8` if `name` is not valid UTF-8.
+ * * `ALEC_ERROR_INVALID_INPUT` if the name is empty or longer than
+ *   255 bytes, the channel already exists, or the channel limit is
+ *   reached.
+ */
+enum AlecResult alec_gateway_add_channel(struct AlecGateway *gateway,
+                                         const char *name,
+                                         uint8_t priority);
+
+/**
+ * Push a sensor value to a gateway channel
+ *
+ * # Arguments
+ *
+ * * `gateway` - Gateway handle
+ * * `name` - Channel name (null-terminated UTF-8)
+ * * `value` - Sensor value
+ * * `timestamp` - Timestamp of the measurement (can be 0 if not used)
+ *
+ * # Returns
+ *
+ * * `ALEC_OK` on success.
+ * * `ALEC_ERROR_NULL_POINTER` if `gateway` or `name` is NULL.
+ * * `ALEC_ERROR_INVALID_UTF8` if `name` is not valid UTF-8.
+ * * `ALEC_ERROR_INVALID_INPUT` if the channel does not exist.
+ * * `ALEC_ERROR_BUFFER_TOO_SMALL` if the channel's pending buffer is
+ *   full — call `alec_gateway_flush` first.
+ */
+enum AlecResult alec_gateway_push(struct AlecGateway *gateway,
+                                  const char *name,
+                                  double value,
+                                  uint64_t timestamp);
+
+/**
+ * Flush all channels into a single priority-packed frame
+ *
+ * Channels are packed in priority order up to the gateway's
+ * `max_frame_size`. An empty frame (2 header bytes) is written when no
+ * channel has pending data.
+ *
+ * If the frame does not fit in `out_buf`, `ALEC_ERROR_BUFFER_TOO_SMALL`
+ * is returned, `*out_len` reports the required size and `out_buf` is NOT
+ * modified. The already-encoded frame is retained by the gateway and
+ * returned by the next call to `alec_gateway_flush`, so retrying with a
+ * larger buffer loses no samples.
+ *
+ * # Arguments
+ *
+ * * `gateway` - Gateway handle
+ * * `out_buf` - Output buffer for the serialized frame
+ * * `cap` - Size of `out_buf` in bytes
+ * * `out_len` - Out: bytes written (on success) or required size
+ *   (on `ALEC_ERROR_BUFFER_TOO_SMALL`)
+ *
+ * # Returns
+ *
+ * * `ALEC_OK` on success.
+ * * `ALEC_ERROR_NULL_POINTER` for a NULL required pointer.
+ * * `ALEC_ERROR_BUFFER_TOO_SMALL` if `cap` is too small.
+ * * `ALEC_ERROR_ENCODING_FAILED` if a channel failed to encode.
+ */
+enum AlecResult alec_gateway_flush(struct AlecGateway *gateway,
+                                   uint8_t *out_buf,
+                                   uintptr_t cap,
+                                   uintptr_t *out_len);
+
+/**
+ * Report what the most recent successful flush contained
+ *
+ * # Arguments
+ *
+ * * `gateway` - Gateway handle
+ * * `out_channels` - Out: number of channels in the last frame (may be NULL)
+ * * `out_samples` - Out: number of samples in the last frame (may be NULL)
+ *
+ * # Returns
+ *
+ * `ALEC_OK` on success, `ALEC_ERROR_NULL_POINTER` if `gateway` is NULL.
+ * Both counts are 0 before the first successful flush.
+ */
+enum AlecResult alec_gateway_last_flush_stats(const struct AlecGateway *gateway,
+                                              uintptr_t *out_channels,
+                                              uintptr_t *out_samples);
+
 extern uint8_t *k_aligned_alloc(uintptr_t align, uintptr_t size);
 
 extern void k_free(uint8_t *ptr);
@@ -16,7 +16,8 @@
 //! - Pointers are valid and non-null (unless documented otherwise)
 //! - Buffer sizes are accurate
 //! - Handles are not used after being freed
-//! - Thread safety is managed by the caller
+//! - Handles are not shared between threads, except those created by
+//!   `alec_encoder_new_threadsafe()` / `alec_decoder_new_threadsafe()`
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -127,17 +128,22 @@ mod bare_metal_support {
 #[cfg(not(feature = "std"))]
 use alloc::{boxed::Box, vec::Vec};
 
+use core::cell::UnsafeCell;
 use core::ffi::{c_char, CStr};
+use core::ops::{Deref, DerefMut};
 use core::slice;
+use core::sync::atomic::{AtomicBool, Ordering};
 #[cfg(feature = "std")]
 use std::path::Path;
 
 use alec::classifier::Classifier;
 use alec::context::{Context, ContextConfig};
-use alec::protocol::{ChannelInput, RawData};
+use alec::protocol::{ChannelInput, ChecksumPolicy, Priority, RawData};
 #[cfg(feature = "decoder")]
 use alec::Decoder;
-use alec::Encoder;
+use alec::{Encoder, EncoderConfig};
+#[cfg(feature = "gateway")]
+use alec_gateway::{ChannelConfig, Gateway, GatewayConfig, GatewayError};
 
 /// Key used when `observe()`-ing channel `i` in a fixed-channel frame.
 /// Must match `Encoder::fixed_channel_source_id` — kept here because
@@ -312,11 +318,150 @@ pub enum AlecResult {
     ErrorCorruptData = 9,
 }
 
+// ============================================================================
+// Handles
+// ============================================================================
+
+/// Lock of a thread-safe handle
+///
+/// A mutex on hosted builds; a spin lock on `no_std` builds, where the
+/// holder must be able to run while others wait (beware of priority
+/// inversion between RTOS tasks).
+struct HandleLock {
+    #[cfg(feature = "std")]
+    mutex: std::sync::Mutex<()>,
+    #[cfg(not(feature = "std"))]
+    locked: AtomicBool,
+    /// Set by the first free call
+    freed: AtomicBool,
+}
+
+#[cfg(feature = "std")]
+type LockGuard<'a> = std::sync::MutexGuard<'a, ()>;
+
+#[cfg(not(feature = "std"))]
+struct LockGuard<'a>(&'a AtomicBool);
+
+#[cfg(not(feature = "std"))]
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl HandleLock {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            mutex: std::sync::Mutex::new(()),
+            #[cfg(not(feature = "std"))]
+            locked: AtomicBool::new(false),
+            freed: AtomicBool::new(false),
+        }
+    }
+
+    /// Wait for the lock
+    fn acquire(&self) -> LockGuard<'_> {
+        #[cfg(feature = "std")]
+        {
+            // A panicking holder leaves the state as usable as any
+            // unlocked handle
+            self.mutex
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            LockGuard(&self.locked)
+        }
+    }
+}
+
+/// State behind an FFI handle, locked on each access if the handle is
+/// thread-safe
+struct Handle<T> {
+    lock: Option<HandleLock>,
+    state: UnsafeCell<T>,
+}
+
+/// Access to the state of a handle, holding its lock if it has one
+struct HandleGuard<'a, T> {
+    state: &'a mut T,
+    _lock: Option<LockGuard<'a>>,
+}
+
+impl<T> Deref for HandleGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.state
+    }
+}
+
+impl<T> DerefMut for HandleGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.state
+    }
+}
+
+impl<T> Handle<T> {
+    fn new(state: T) -> Self {
+        Self {
+            lock: None,
+            state: UnsafeCell::new(state),
+        }
+    }
+
+    /// Exclusive access to the state
+    ///
+    /// Waits for the lock of a thread-safe handle. Other handles rely on
+    /// the caller not sharing them between threads.
+    fn state(&self) -> HandleGuard<'_, T> {
+        let lock = self.lock.as_ref().map(HandleLock::acquire);
+        HandleGuard {
+            // SAFETY: the lock, or the caller for unlocked handles,
+            // guarantees this is the only access
+            state: unsafe { &mut *self.state.get() },
+            _lock: lock,
+        }
+    }
+
+    /// Whether this free call is the one to drop the handle
+    ///
+    /// On a thread-safe handle, waits for the current lock holder and
+    /// refuses free calls racing the first. The flag lives in the handle:
+    /// calls made after it is dropped cannot be detected.
+    fn release(&self) -> bool {
+        match &self.lock {
+            None => true,
+            Some(lock) => {
+                if lock.freed.swap(true, Ordering::AcqRel) {
+                    return false;
+                }
+                drop(lock.acquire());
+                true
+            }
+        }
+    }
+}
+
 /// Opaque encoder handle
 ///
-/// Created with `alec_encoder_new()`, freed with `alec_encoder_free()`.
-/// Do not access internal fields directly.
+/// Created with `alec_encoder_new()` (or `alec_encoder_new_threadsafe()`),
+/// freed with `alec_encoder_free()`. Do not access internal fields directly.
 pub struct AlecEncoder {
+    handle: Handle<EncoderState>,
+}
+
+/// Encoder state behind an [`AlecEncoder`] handle
+struct EncoderState {
     encoder: Encoder,
     classifier: Classifier,
     context: Context,
@@ -338,13 +483,19 @@ pub struct AlecEncoder {
 
 /// Opaque decoder handle
 ///
-/// Created with `alec_decoder_new()`, freed with `alec_decoder_free()`.
-/// Do not access internal fields directly.
+/// Created with `alec_decoder_new()` (or `alec_decoder_new_threadsafe()`),
+/// freed with `alec_decoder_free()`. Do not access internal fields directly.
 ///
 /// Decoder FFI is only available when the `decoder` Cargo feature is
 /// enabled (default on hosted/server builds, off on `zephyr`/MCU builds).
 #[cfg(feature = "decoder")]
 pub struct AlecDecoder {
+    handle: Handle<DecoderState>,
+}
+
+/// Decoder state behind an [`AlecDecoder`] handle
+#[cfg(feature = "decoder")]
+struct DecoderState {
     decoder: Decoder,
     context: Context,
     /// Header sequence number observed on the most recent multi-frame
@@ -355,6 +506,31 @@ pub struct AlecDecoder {
     last_gap_size: u8,
 }
 
+impl AlecEncoder {
+    fn boxed(state: EncoderState) -> Box<Self> {
+        Box::new(Self {
+            handle: Handle::new(state),
+        })
+    }
+
+    fn state(&self) -> HandleGuard<'_, EncoderState> {
+        self.handle.state()
+    }
+}
+
+#[cfg(feature = "decoder")]
+impl AlecDecoder {
+    fn boxed(state: DecoderState) -> Box<Self> {
+        Box::new(Self {
+            handle: Handle::new(state),
+        })
+    }
+
+    fn state(&self) -> HandleGuard<'_, DecoderState> {
+        self.handle.state()
+    }
+}
+
 // ============================================================================
 // Version and Utility Functions
 // ============================================================================
@@ -429,7 +605,7 @@ pub extern "C" fn alec_result_to_string(result: AlecResult) -> *const c_char {
 #[no_mangle]
 pub extern "C" fn alec_encoder_new() -> *mut AlecEncoder {
     let defaults = AlecEncoderConfig::defaults();
-    let encoder = Box::new(AlecEncoder {
+    let encoder = AlecEncoder::boxed(EncoderState {
         encoder: Encoder::new(),
         classifier: Classifier::default(),
         context: Context::new(),
@@ -449,7 +625,7 @@ pub extern "C" fn alec_encoder_new() -> *mut AlecEncoder {
 #[no_mangle]
 pub extern "C" fn alec_encoder_new_with_checksum() -> *mut AlecEncoder {
     let defaults = AlecEncoderConfig::defaults();
-    let encoder = Box::new(AlecEncoder {
+    let encoder = AlecEncoder::boxed(EncoderState {
         encoder: Encoder::with_checksum(),
         classifier: Classifier::default(),
         context: Context::new(),
@@ -461,6 +637,52 @@ pub extern "C" fn alec_encoder_new_with_checksum() -> *mut AlecEncoder {
     Box::into_raw(encoder)
 }
 
+/// Map an FFI priority threshold (1–5, 1 = P1 critical) to a checksum
+/// policy covering that priority and every more important one.
+fn checksum_policy_from_threshold(threshold_priority: u8) -> Option<ChecksumPolicy> {
+    if threshold_priority == 0 {
+        return None;
+    }
+    Priority::from_u8(threshold_priority - 1).map(ChecksumPolicy::ForPriorityAtOrAbove)
+}
+
+/// Create a new encoder that appends a checksum only to important messages
+///
+/// Messages classified at `threshold_priority` or more important carry a
+/// trailing 4-byte checksum; less important ones are sent without it.
+/// The matching decoder must be created with
+/// `alec_decoder_new_with_policy()` and the same threshold.
+///
+/// # Arguments
+///
+/// * `threshold_priority` - Least important priority (1–5) that still
+///   carries a checksum. 2 covers P1 and P2.
+///
+/// # Returns
+///
+/// A pointer to a new encoder, or NULL if `threshold_priority` is not
+/// in 1–5.
+#[no_mangle]
+pub extern "C" fn alec_encoder_new_with_policy(threshold_priority: u8) -> *mut AlecEncoder {
+    let Some(checksum_policy) = checksum_policy_from_threshold(threshold_priority) else {
+        return core::ptr::null_mut();
+    };
+    let defaults = AlecEncoderConfig::defaults();
+    let encoder = AlecEncoder::boxed(EncoderState {
+        encoder: Encoder::with_config(EncoderConfig {
+            checksum_policy,
+            ..Default::default()
+        }),
+        classifier: Classifier::default(),
+        context: Context::new(),
+        force_keyframe_pending: false,
+        messages_since_keyframe: 0,
+        keyframe_interval: defaults.keyframe_interval,
+        smart_resync: defaults.smart_resync,
+    });
+    Box::into_raw(encoder)
+}
+
 /// Create a new ALEC encoder with a custom configuration.
 ///
 /// Mirrors the Milesight integration requirements: the caller specifies
@@ -508,7 +730,7 @@ pub extern "C" fn alec_encoder_new_with_config(
         context.ensure_source_stats(i + 1);
     }
 
-    let encoder = Box::new(AlecEncoder {
+    let encoder = AlecEncoder::boxed(EncoderState {
         encoder: Encoder::new(),
         classifier: Classifier::default(),
         context,
@@ -520,6 +742,31 @@ pub extern "C" fn alec_encoder_new_with_config(
     Box::into_raw(encoder)
 }
 
+/// Create a new encoder that can be shared between threads
+///
+/// Same as `alec_encoder_new()`, but every call on the handle takes an
+/// internal lock, so several threads (or RTOS tasks) may use it without
+/// a mutex of their own. Calls block while another thread holds the
+/// lock. Handles from the other constructors stay lock-free.
+///
+/// The lock does not protect the handle's lifetime. `alec_encoder_free()`
+/// waits for the current lock holder, and refuses a second free call
+/// made while the first one runs. Once the handle is dropped, its memory
+/// is gone: a later free call, or a thread still waiting for the lock,
+/// reads freed memory. Free the handle once, after every other thread
+/// is done with it.
+///
+/// # Returns
+///
+/// A pointer to a new encoder, or NULL on allocation failure.
+/// The encoder must be freed with `alec_encoder_free()`, once.
+#[no_mangle]
+pub extern "C" fn alec_encoder_new_threadsafe() -> *mut AlecEncoder {
+    let mut encoder = unsafe { Box::from_raw(alec_encoder_new()) };
+    encoder.handle.lock = Some(HandleLock::new());
+    Box::into_raw(encoder)
+}
+
 /// Force the next encode call to emit a keyframe (Raw32 for all channels).
 ///
 /// Intended to be called from a LoRaWAN downlink handler receiving the
@@ -541,7 +788,8 @@ pub extern "C" fn alec_force_keyframe(encoder: *mut AlecEncoder) {
     if encoder.is_null() {
         return;
     }
-    let enc = unsafe { &mut *encoder };
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
     if !enc.smart_resync {
         return;
     }
@@ -621,10 +869,14 @@ pub extern "C" fn alec_downlink_handler(
 ///
 /// # Safety
 ///
-/// The encoder must not be used after calling this function.
+/// The encoder must not be used after calling this function. A
+/// thread-safe encoder is freed once the thread holding its lock
+/// releases it. A second free call is refused only while it races the
+/// first one; after that it reads freed memory, as does any thread
+/// still waiting for the lock (see `alec_encoder_new_threadsafe()`).
 #[no_mangle]
 pub extern "C" fn alec_encoder_free(encoder: *mut AlecEncoder) {
-    if !encoder.is_null() {
+    if !encoder.is_null() && unsafe { &*encoder }.handle.release() {
         unsafe {
             drop(Box::from_raw(encoder));
         }
@@ -661,7 +913,8 @@ pub extern "C" fn alec_encode_value(
         return AlecResult::ErrorNullPointer;
     }
 
-    let enc = unsafe { &mut *encoder };
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
     let sid = hash_source_id(source_id);
 
     // Create RawData with hashed source_id for per-channel context isolation
@@ -673,8 +926,8 @@ pub extern "C" fn alec_encode_value(
     // Encode the message
     let message = enc.encoder.encode(&raw_data, &classification, &enc.context);
 
-    // Convert to bytes
-    let encoded = message.to_bytes();
+    // Convert to bytes (with checksum if the encoder's policy covers it)
+    let encoded = enc.encoder.message_to_bytes(&message);
     if encoded.len() > output_capacity {
         return AlecResult::ErrorBufferTooSmall;
     }
@@ -691,6 +944,69 @@ pub extern "C" fn alec_encode_value(
     AlecResult::Ok
 }
 
+/// Encode a single value with a priority chosen by the application
+///
+/// Same as `alec_encode_value`, but the classifier is bypassed: the
+/// message carries `priority` and the operator-override header flag so
+/// the receiver can tell it from a classifier-assigned priority.
+///
+/// # Arguments
+///
+/// * `encoder` - Encoder handle (must not be NULL)
+/// * `value` - The value to encode
+/// * `timestamp` - Timestamp for the value (can be 0 if not used)
+/// * `priority` - Priority to send (1–5, 1 = P1 critical)
+/// * `source_id` - Source identifier string (null-terminated, can be NULL)
+/// * `output` - Output buffer for encoded data
+/// * `output_capacity` - Size of output buffer in bytes
+/// * `output_len` - Pointer to store actual encoded length
+///
+/// # Returns
+///
+/// `ALEC_OK` on success, `ALEC_ERROR_INVALID_INPUT` if `priority` is
+/// outside 1–5, other error codes as for `alec_encode_value`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn alec_encode_value_with_priority(
+    encoder: *mut AlecEncoder,
+    value: f64,
+    timestamp: u64,
+    priority: u8,
+    source_id: *const c_char,
+    output: *mut u8,
+    output_capacity: usize,
+    output_len: *mut usize,
+) -> AlecResult {
+    if encoder.is_null() || output.is_null() || output_len.is_null() {
+        return AlecResult::ErrorNullPointer;
+    }
+    let Some(priority) = priority.checked_sub(1).and_then(Priority::from_u8) else {
+        return AlecResult::ErrorInvalidInput;
+    };
+
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
+    let raw_data = RawData::with_source(hash_source_id(source_id), value, timestamp);
+    let message = enc
+        .encoder
+        .encode_with_priority(&raw_data, priority, &enc.context);
+
+    let encoded = enc.encoder.message_to_bytes(&message);
+    if encoded.len() > output_capacity {
+        return AlecResult::ErrorBufferTooSmall;
+    }
+
+    let output_slice = unsafe { slice::from_raw_parts_mut(output, output_capacity) };
+    output_slice[..encoded.len()].copy_from_slice(&encoded);
+    unsafe {
+        *output_len = encoded.len();
+    }
+
+    enc.context.observe(&raw_data);
+
+    AlecResult::Ok
+}
+
 /// Encode multiple values with adaptive per-channel compression.
 ///
 /// Each channel is independently classified (P1–P5) and encoded using the
@@ -701,7 +1017,7 @@ pub extern "C" fn alec_encode_value(
 ///
 /// * `encoder` - Encoder handle
 /// * `values` - Array of f64 values to encode (one per channel)
-/// * `value_count` - Number of channels
+/// * `value_count` - Number of channels, 1 to 255
 /// * `timestamps` - Per-channel timestamps (array of uint64_t), or NULL to
 ///   use 0 for all channels
 /// * `source_ids` - Per-channel source identifier strings (array of
@@ -732,11 +1048,13 @@ pub extern "C" fn alec_encode_multi(
         return AlecResult::ErrorNullPointer;
     }
 
-    if value_count == 0 {
+    // Channel name ids are a single byte on the wire
+    if value_count == 0 || value_count > alec::MAX_MULTI_VALUES {
         return AlecResult::ErrorInvalidInput;
     }
 
-    let enc = unsafe { &mut *encoder };
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
     let values_slice = unsafe { slice::from_raw_parts(values, value_count) };
 
     // Build ChannelInput array
@@ -770,8 +1088,16 @@ pub extern "C" fn alec_encode_multi(
     // Otherwise let the classifier decide naturally.
     // For now, we use the classifier and override priorities post-classification.
     let (message, classifications) =
-        enc.encoder
-            .encode_multi_adaptive(&channels, timestamp, &enc.context, &enc.classifier);
+        match enc
+            .encoder
+            .encode_multi_adaptive(&channels, timestamp, &enc.context, &enc.classifier)
+        {
+            Ok(encoded) => encoded,
+            Err(alec::error::AlecError::Encode(alec::error::EncodeError::PayloadTooLarge {
+                ..
+            })) => return AlecResult::ErrorInvalidInput,
+            Err(_) => return AlecResult::ErrorEncodingFailed,
+        };
 
     // If explicit priorities were provided, we need to re-encode with those.
     // However, the cleaner approach is to let the classifier work and use the
@@ -826,7 +1152,7 @@ pub extern "C" fn alec_encoder_save_context(
         return AlecResult::ErrorNullPointer;
     }
 
-    let enc = unsafe { &*encoder };
+    let enc = unsafe { &*encoder }.state();
 
     let path_str = match unsafe { CStr::from_ptr(path) }.to_str() {
         Ok(s) => s,
@@ -867,7 +1193,8 @@ pub extern "C" fn alec_encoder_load_context(
         return AlecResult::ErrorNullPointer;
     }
 
-    let enc = unsafe { &mut *encoder };
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
 
     let path_str = match unsafe { CStr::from_ptr(path) }.to_str() {
         Ok(s) => s,
@@ -885,6 +1212,9 @@ pub extern "C" fn alec_encoder_load_context(
 
 /// Get the current context version
 ///
+/// This is the dictionary version: it changes only when patterns are
+/// added, removed or renumbered, not on every encoded value.
+///
 /// # Arguments
 ///
 /// * `encoder` - Encoder handle
@@ -897,7 +1227,7 @@ pub extern "C" fn alec_encoder_context_version(encoder: *const AlecEncoder) -> u
     if encoder.is_null() {
         return 0;
     }
-    let enc = unsafe { &*encoder };
+    let enc = unsafe { &*encoder }.state();
     enc.context.context_version()
 }
 
@@ -992,7 +1322,7 @@ pub extern "C" fn alec_encoder_context_save(
     if enc.is_null() || buf.is_null() || written.is_null() {
         return AlecResult::ErrorNullPointer;
     }
-    let e = unsafe { &*enc };
+    let e = unsafe { &*enc }.state();
 
     // v1.3.9: zero-heap streaming write directly into the caller's
     // buffer. We avoid the ~1.5 KB temporary `Vec<u8>` that the
@@ -1153,7 +1483,8 @@ pub extern "C" fn alec_encoder_context_load(
     let sequence = u16::from_be_bytes([data[6], data[7]]);
     let msk = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
 
-    let e = unsafe { &mut *enc };
+    let mut guard = unsafe { &*enc }.state();
+    let e = &mut *guard;
     // `Context::new()` is stack-only (no allocations until a source
     // is inserted). Assigning it drops the old Context here,
     // returning its BTreeMap nodes / Vec<f64> history / Pattern data
@@ -1203,7 +1534,7 @@ pub extern "C" fn alec_encoder_context_load(
 #[cfg(feature = "decoder")]
 #[no_mangle]
 pub extern "C" fn alec_decoder_new() -> *mut AlecDecoder {
-    let decoder = Box::new(AlecDecoder {
+    let decoder = AlecDecoder::boxed(DecoderState {
         decoder: Decoder::new(),
         context: Context::new(),
         last_header_sequence: None,
@@ -1220,7 +1551,7 @@ pub extern "C" fn alec_decoder_new() -> *mut AlecDecoder {
 #[cfg(feature = "decoder")]
 #[no_mangle]
 pub extern "C" fn alec_decoder_new_with_checksum() -> *mut AlecDecoder {
-    let decoder = Box::new(AlecDecoder {
+    let decoder = AlecDecoder::boxed(DecoderState {
         decoder: Decoder::with_checksum_verification(),
         context: Context::new(),
         last_header_sequence: None,
@@ -1229,6 +1560,31 @@ pub extern "C" fn alec_decoder_new_with_checksum() -> *mut AlecDecoder {
     Box::into_raw(decoder)
 }
 
+/// Create a new decoder that verifies checksums only on important messages
+///
+/// Counterpart of `alec_encoder_new_with_policy()`: messages whose header
+/// priority is at `threshold_priority` or more important must end with a
+/// valid checksum; others are decoded without one.
+///
+/// # Returns
+///
+/// A pointer to a new decoder, or NULL if `threshold_priority` is not
+/// in 1–5.
+#[cfg(feature = "decoder")]
+#[no_mangle]
+pub extern "C" fn alec_decoder_new_with_policy(threshold_priority: u8) -> *mut AlecDecoder {
+    let Some(policy) = checksum_policy_from_threshold(threshold_priority) else {
+        return core::ptr::null_mut();
+    };
+    let decoder = AlecDecoder::boxed(DecoderState {
+        decoder: Decoder::with_checksum_policy(policy),
+        context: Context::new(),
+        last_header_sequence: None,
+        last_gap_size: 0,
+    });
+    Box::into_raw(decoder)
+}
+
 /// Create a new ALEC decoder with a custom configuration.
 ///
 /// Mirrors `alec_encoder_new_with_config` for the decoder side. The
@@ -1260,7 +1616,7 @@ pub extern "C" fn alec_decoder_new_with_config(
     };
 
     let context = Context::with_config(cfg.to_context_config());
-    let decoder = Box::new(AlecDecoder {
+    let decoder = AlecDecoder::boxed(DecoderState {
         decoder: Decoder::new(),
         context,
         last_header_sequence: None,
@@ -1269,6 +1625,23 @@ pub extern "C" fn alec_decoder_new_with_config(
     Box::into_raw(decoder)
 }
 
+/// Create a new decoder that can be shared between threads
+///
+/// Decoder counterpart of `alec_encoder_new_threadsafe()`, with the
+/// same limits on freeing.
+///
+/// # Returns
+///
+/// A pointer to a new decoder, or NULL on allocation failure.
+/// The decoder must be freed with `alec_decoder_free()`, once.
+#[cfg(feature = "decoder")]
+#[no_mangle]
+pub extern "C" fn alec_decoder_new_threadsafe() -> *mut AlecDecoder {
+    let mut decoder = unsafe { Box::from_raw(alec_decoder_new()) };
+    decoder.handle.lock = Some(HandleLock::new());
+    Box::into_raw(decoder)
+}
+
 /// Reset a decoder to its initial state.
 ///
 /// Wipes all per-channel prediction state (the per-source EMA, last
@@ -1288,7 +1661,8 @@ pub extern "C" fn alec_decoder_reset(dec: *mut AlecDecoder) {
     if dec.is_null() {
         return;
     }
-    let d = unsafe { &mut *dec };
+    let mut guard = unsafe { &*dec }.state();
+    let d = &mut *guard;
     d.decoder.reset();
     d.context.reset_to_baseline();
     d.last_header_sequence = None;
@@ -1324,7 +1698,7 @@ pub extern "C" fn alec_decoder_gap_detected(
         }
         return false;
     }
-    let dec = unsafe { &*decoder };
+    let dec = unsafe { &*decoder }.state();
     if !out_gap_size.is_null() {
         unsafe { *out_gap_size = dec.last_gap_size };
     }
@@ -1336,10 +1710,16 @@ pub extern "C" fn alec_decoder_gap_detected(
 /// # Arguments
 ///
 /// * `decoder` - Decoder to free. May be NULL (no-op).
+///
+/// # Safety
+///
+/// As for `alec_encoder_free()`: a thread-safe decoder is freed once its
+/// lock is released, and a second free call is refused only while it
+/// races the first one.
 #[cfg(feature = "decoder")]
 #[no_mangle]
 pub extern "C" fn alec_decoder_free(decoder: *mut AlecDecoder) {
-    if !decoder.is_null() {
+    if !decoder.is_null() && unsafe { &*decoder }.handle.release() {
         unsafe {
             drop(Box::from_raw(decoder));
         }
@@ -1372,7 +1752,8 @@ pub extern "C" fn alec_decode_value(
         return AlecResult::ErrorNullPointer;
     }
 
-    let dec = unsafe { &mut *decoder };
+    let mut guard = unsafe { &*decoder }.state();
+    let dec = &mut *guard;
     let input_slice = unsafe { slice::from_raw_parts(input, input_len) };
 
     match dec.decoder.decode_bytes(input_slice, &dec.context) {
@@ -1417,7 +1798,8 @@ pub extern "C" fn alec_decode_multi(
         return AlecResult::ErrorNullPointer;
     }
 
-    let dec = unsafe { &mut *decoder };
+    let mut guard = unsafe { &*decoder }.state();
+    let dec = &mut *guard;
     let input_slice = unsafe { slice::from_raw_parts(input, input_len) };
 
     // First parse the message from bytes
@@ -1425,12 +1807,35 @@ pub extern "C" fn alec_decode_multi(
         Some(msg) => msg,
         None => return AlecResult::ErrorDecodingFailed,
     };
+    track_header_sequence(dec, message.header.sequence);
+
+    match dec.decoder.decode_multi(&message, &dec.context) {
+        Ok(value_pairs) => {
+            if value_pairs.len() > values_capacity {
+                return AlecResult::ErrorBufferTooSmall;
+            }
 
-    // Gap detection: compute the number of missing frames between the
-    // previous header sequence and this one. `decode_multi` does not
-    // touch `Decoder::last_sequence`, so we track it here at the FFI
-    // layer. Clipped to 255 to fit `last_gap_size: u8`.
-    let cur_seq = message.header.sequence;
+            let values_slice = unsafe { slice::from_raw_parts_mut(values, values_capacity) };
+            for (i, (_, val)) in value_pairs.iter().enumerate() {
+                values_slice[i] = *val;
+            }
+            unsafe {
+                *values_count = value_pairs.len();
+            }
+            AlecResult::Ok
+        }
+        Err(_) => AlecResult::ErrorDecodingFailed,
+    }
+}
+
+/// Gap detection for the multi-value decode paths
+///
+/// Computes the number of missing frames between the previous header
+/// sequence and this one. `decode_multi` does not touch
+/// `Decoder::last_sequence`, so we track it here at the FFI layer.
+/// Clipped to 255 to fit `last_gap_size: u8`.
+#[cfg(feature = "decoder")]
+fn track_header_sequence(dec: &mut DecoderState, cur_seq: u16) {
     dec.last_gap_size = match dec.last_header_sequence {
         Some(prev) => {
             let diff = cur_seq.wrapping_sub(prev);
@@ -1444,19 +1849,83 @@ pub extern "C" fn alec_decode_multi(
         None => 0,
     };
     dec.last_header_sequence = Some(cur_seq);
+}
 
-    match dec.decoder.decode_multi(&message, &dec.context) {
-        Ok(value_pairs) => {
-            if value_pairs.len() > values_capacity {
+/// Timestamp written by `alec_decode_multi_ex` when the frame header only
+/// carries a compressed timestamp.
+pub const ALEC_TIMESTAMP_UNKNOWN: u64 = u64::MAX;
+
+/// Decode compressed data to multiple values with their channel ids and
+/// timestamps
+///
+/// Fills three parallel arrays: entry `i` of `values`, `name_ids` and
+/// `timestamps` describes the same decoded value. Timestamps are the frame
+/// timestamp in seconds, or `ALEC_TIMESTAMP_UNKNOWN`. A frame with zero
+/// values succeeds with `*values_count == 0`.
+///
+/// # Arguments
+///
+/// * `decoder` - Decoder handle
+/// * `input` - Compressed input data
+/// * `input_len` - Length of input data
+/// * `values` - Output buffer for decoded values
+/// * `name_ids` - Output buffer for channel name ids
+/// * `timestamps` - Output buffer for timestamps
+/// * `capacity` - Number of entries each output buffer can hold
+/// * `values_count` - Pointer to store actual number of decoded values
+///
+/// # Returns
+///
+/// `ALEC_OK` on success, error code otherwise.
+#[cfg(feature = "decoder")]
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn alec_decode_multi_ex(
+    decoder: *mut AlecDecoder,
+    input: *const u8,
+    input_len: usize,
+    values: *mut f64,
+    name_ids: *mut u16,
+    timestamps: *mut u64,
+    capacity: usize,
+    values_count: *mut usize,
+) -> AlecResult {
+    if decoder.is_null()
+        || input.is_null()
+        || values.is_null()
+        || name_ids.is_null()
+        || timestamps.is_null()
+        || values_count.is_null()
+    {
+        return AlecResult::ErrorNullPointer;
+    }
+
+    let mut guard = unsafe { &*decoder }.state();
+    let dec = &mut *guard;
+    let input_slice = unsafe { slice::from_raw_parts(input, input_len) };
+
+    let message = match alec::protocol::EncodedMessage::from_bytes(input_slice) {
+        Some(msg) => msg,
+        None => return AlecResult::ErrorDecodingFailed,
+    };
+    track_header_sequence(dec, message.header.sequence);
+
+    match dec.decoder.decode_multi_ex(&message, &dec.context) {
+        Ok(decoded) => {
+            if decoded.len() > capacity {
                 return AlecResult::ErrorBufferTooSmall;
             }
 
-            let values_slice = unsafe { slice::from_raw_parts_mut(values, values_capacity) };
-            for (i, (_, val)) in value_pairs.iter().enumerate() {
-                values_slice[i] = *val;
+            let values_slice = unsafe { slice::from_raw_parts_mut(values, capacity) };
+            let ids_slice = unsafe { slice::from_raw_parts_mut(name_ids, capacity) };
+            let ts_slice = unsafe { slice::from_raw_parts_mut(timestamps, capacity) };
+            for (i, value) in decoded.iter().enumerate() {
+                values_slice[i] = value.value;
+                ids_slice[i] = value.name_id;
+                ts_slice[i] = value.timestamp.unwrap_or(ALEC_TIMESTAMP_UNKNOWN);
             }
             unsafe {
-                *values_count = value_pairs.len();
+                *values_count = decoded.len();
             }
             AlecResult::Ok
         }
@@ -1484,7 +1953,8 @@ pub extern "C" fn alec_decoder_load_context(
         return AlecResult::ErrorNullPointer;
     }
 
-    let dec = unsafe { &mut *decoder };
+    let mut guard = unsafe { &*decoder }.state();
+    let dec = &mut *guard;
 
     let path_str = match unsafe { CStr::from_ptr(path) }.to_str() {
         Ok(s) => s,
@@ -1502,6 +1972,9 @@ pub extern "C" fn alec_decoder_load_context(
 
 /// Get the current decoder context version
 ///
+/// This is the dictionary version: it changes only when patterns are
+/// added, removed or renumbered, not on every decoded value.
+///
 /// # Arguments
 ///
 /// * `decoder` - Decoder handle
@@ -1515,7 +1988,7 @@ pub extern "C" fn alec_decoder_context_version(decoder: *const AlecDecoder) -> u
     if decoder.is_null() {
         return 0;
     }
-    let dec = unsafe { &*decoder };
+    let dec = unsafe { &*decoder }.state();
     dec.context.context_version()
 }
 
@@ -1573,7 +2046,8 @@ pub extern "C" fn alec_encode_multi_fixed(
         return AlecResult::ErrorInvalidInput;
     }
 
-    let enc = unsafe { &mut *encoder };
+    let mut guard = unsafe { &*encoder }.state();
+    let enc = &mut *guard;
     let values_slice = unsafe { slice::from_raw_parts(values, channel_count) };
     let output_slice = unsafe { slice::from_raw_parts_mut(output, output_capacity) };
 
@@ -1696,7 +2170,8 @@ pub extern "C" fn alec_decode_multi_fixed(
         return AlecResult::ErrorInvalidInput;
     }
 
-    let d = unsafe { &mut *dec };
+    let mut guard = unsafe { &*dec }.state();
+    let d = &mut *guard;
     let input_slice = unsafe { slice::from_raw_parts(frame_data, frame_len) };
     let output_slice = unsafe { slice::from_raw_parts_mut(values_out, max_channels) };
     let channel_count = max_channels;
@@ -1730,13 +2205,6 @@ pub extern "C" fn alec_decode_multi_fixed(
                     );
                     d.context.reset_to_baseline();
                 } else if info.context_mismatch {
-                    // Also surface a u32-level check via the core
-                    // Context::check_version API. We truncate our
-                    // tracked u32 version to the low 16 bits so the
-                    // comparison is wire-format-accurate; the full
-                    // u32 version is informational only here.
-                    let wire_ver = info.context_version as u32;
-                    let _result = d.context.check_version(wire_ver);
                     log::warn!(
                         "ALEC ctx_ver mismatch on fixed-channel decode: \
                          wire={}, context reset to baseline",
@@ -1848,7 +2316,8 @@ pub extern "C" fn alec_decoder_feed_values(
         return AlecResult::ErrorInvalidInput;
     }
 
-    let d = unsafe { &mut *dec };
+    let mut guard = unsafe { &*dec }.state();
+    let d = &mut *guard;
     let values_slice = unsafe { slice::from_raw_parts(values, num_values) };
 
     // Step 1: capture the wire-equivalent ctx_ver BEFORE observing.
@@ -1856,7 +2325,7 @@ pub extern "C" fn alec_decoder_feed_values(
     // CompactHeader BEFORE its post-encode observe loop; we mirror
     // that exactly so a future real frame's ctx_ver-mismatch check
     // sees the same value the encoder will write.
-    let wire_ctx_ver = (d.context.context_version() & 0xFFFF) as u16;
+    let wire_ctx_ver = (d.context.version() & 0xFFFF) as u16;
 
     // Step 2: compute the wire-equivalent sequence. The encoder
     // increments its `Encoder::sequence` once per encode call; the
@@ -1929,7 +2398,7 @@ pub extern "C" fn alec_decoder_export_state_size(
     if decoder.is_null() || sensor_type.is_null() || out_size.is_null() {
         return AlecResult::ErrorNullPointer;
     }
-    let dec = unsafe { &*decoder };
+    let dec = unsafe { &*decoder }.state();
     let sens_str = match unsafe { CStr::from_ptr(sensor_type) }.to_str() {
         Ok(s) => s,
         Err(_) => return AlecResult::ErrorInvalidUtf8,
@@ -1984,7 +2453,7 @@ pub extern "C" fn alec_decoder_export_state(
     if decoder.is_null() || sensor_type.is_null() || out_buf.is_null() || out_len.is_null() {
         return AlecResult::ErrorNullPointer;
     }
-    let dec = unsafe { &*decoder };
+    let dec = unsafe { &*decoder }.state();
     let sens_str = match unsafe { CStr::from_ptr(sensor_type) }.to_str() {
         Ok(s) => s,
         Err(_) => return AlecResult::ErrorInvalidUtf8,
@@ -2043,7 +2512,8 @@ pub extern "C" fn alec_decoder_import_state(
     let data_slice = unsafe { slice::from_raw_parts(data, data_len) };
     match Context::from_preload_bytes(data_slice) {
         Ok(ctx) => {
-            let dec = unsafe { &mut *decoder };
+            let mut guard = unsafe { &*decoder }.state();
+            let dec = &mut *guard;
             // Replace context only — session state (last_header_sequence,
             // last_gap_size) is intentionally preserved.
             dec.context = ctx;
@@ -2111,7 +2581,7 @@ pub extern "C" fn alec_decoder_context_save(
     if dec.is_null() || buf.is_null() || written.is_null() {
         return AlecResult::ErrorNullPointer;
     }
-    let d = unsafe { &*dec };
+    let d = unsafe { &*dec }.state();
     let bytes = match d.context.to_preload_bytes(FFI_CONTEXT_TAG) {
         Ok(b) => b,
         Err(_) => return AlecResult::ErrorEncodingFailed,
@@ -2158,7 +2628,8 @@ pub extern "C" fn alec_decoder_context_load(
     let data_slice = unsafe { slice::from_raw_parts(buf, buf_len) };
     match Context::from_preload_bytes(data_slice) {
         Ok(ctx) => {
-            let d = unsafe { &mut *dec };
+            let mut guard = unsafe { &*dec }.state();
+            let d = &mut *guard;
             d.context = ctx;
             AlecResult::Ok
         }
@@ -2166,6 +2637,348 @@ pub extern "C" fn alec_decoder_context_load(
     }
 }
 
+// ============================================================================
+// Gateway Functions
+//
+// Multi-channel orchestration (priority packing, frame size limits) on top
+// of the `alec-gateway` crate. Gated behind the `gateway` Cargo feature,
+// which pulls in `std` — gateways are hosted Linux/RTOS-with-libc targets,
+// never the encoder-only MCU firmware build.
+// ============================================================================
+
+/// Default maximum frame size used by `alec_gateway_new(0)` (LoRaWAN DR4+).
+#[cfg(feature = "gateway")]
+pub const ALEC_DEFAULT_GATEWAY_FRAME_SIZE: usize = 242;
+
+/// Opaque gateway handle
+///
+/// Created with `alec_gateway_new()`, freed with `alec_gateway_free()`.
+/// Do not access internal fields directly.
+#[cfg(feature = "gateway")]
+pub struct AlecGateway {
+    gateway: Gateway,
+    /// Serialized frame that did not fit in the caller's buffer on the
+    /// previous flush. Returned as-is by the next `alec_gateway_flush`
+    /// so no already-encoded samples are lost.
+    pending_frame: Option<Vec<u8>>,
+    /// Number of channels included in the most recent successful flush.
+    last_flush_channels: usize,
+    /// Number of samples included in the most recent successful flush.
+    last_flush_samples: usize,
+    /// Channel/sample counts belonging to `pending_frame`.
+    pending_counts: (usize, usize),
+}
+
+/// Map a gateway-level error onto the FFI result codes.
+#[cfg(feature = "gateway")]
+fn gateway_error_to_result(err: &GatewayError) -> AlecResult {
+    match err {
+        GatewayError::EncodingError(_) | GatewayError::NonceExhausted(_) => {
+            AlecResult::ErrorEncodingFailed
+        }
+        GatewayError::AuthenticationFailed(_)
+        | GatewayError::InvalidTextData { .. }
+        | GatewayError::InvalidValueData { .. }
+        | GatewayError::InvalidCompositeData(_) => AlecResult::ErrorDecodingFailed,
+        GatewayError::InvalidSnapshot(_) => AlecResult::ErrorCorruptData,
+        GatewayError::Io(_) => AlecResult::ErrorFileIo,
+        GatewayError::FrameTooLarge { .. } | GatewayError::BufferFull(_) => {
+            AlecResult::ErrorBufferTooSmall
+        }
+        GatewayError::ChannelNotFound(_)
+        | GatewayError::ChannelAlreadyExists(_)
+        | GatewayError::InvalidConfig(_)
+        | GatewayError::MaxChannelsReached { .. }
+        | GatewayError::InvalidTimestamp { .. }
+        | GatewayError::ValueKindMismatch { .. }
+        | GatewayError::TextTooLong { .. } => AlecResult::ErrorInvalidInput,
+    }
+}
+
+/// Borrow a null-terminated UTF-8 channel name.
+#[cfg(feature = "gateway")]
+fn channel_name<'a>(name: *const c_char) -> Result<&'a str, AlecResult> {
+    if name.is_null() {
+        return Err(AlecResult::ErrorNullPointer);
+    }
+    unsafe { CStr::from_ptr(name) }
+        .to_str()
+        .map_err(|_| AlecResult::ErrorInvalidUtf8)
+}
+
+/// Create a new ALEC gateway
+///
+/// # Arguments
+///
+/// * `max_frame_size` - Maximum size of a flushed frame in bytes. `0`
+///   selects the default (242 bytes, LoRaWAN DR4/DR5).
+///
+/// # Returns
+///
+/// A pointer to a new gateway, or NULL on allocation failure.
+/// The gateway must be freed with `alec_gateway_free()` when no longer needed.
+///
+/// # Example (C)
+///
+/// ```c
+/// AlecGateway* gw = alec_gateway_new(242);
+/// alec_gateway_add_channel(gw, "temperature", 1);
+/// alec_gateway_push(gw, "temperature", 22.5, 1000);
+///
+/// uint8_t frame[242];
+/// size_t frame_len;
+/// if (alec_gateway_flush(gw, frame, sizeof(frame), &frame_len) == ALEC_OK) {
+///     // Send frame[0..frame_len]
+/// }
+/// alec_gateway_free(gw);
+/// ```
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_new(max_frame_size: usize) -> *mut AlecGateway {
+    let max_frame_size = if max_frame_size == 0 {
+        ALEC_DEFAULT_GATEWAY_FRAME_SIZE
+    } else {
+        max_frame_size
+    };
+    let Ok(gateway) = Gateway::with_config(GatewayConfig::with_max_frame_size(max_frame_size))
+    else {
+        return core::ptr::null_mut();
+    };
+    let gateway = Box::new(AlecGateway {
+        gateway,
+        pending_frame: None,
+        last_flush_channels: 0,
+        last_flush_samples: 0,
+        pending_counts: (0, 0),
+    });
+    Box::into_raw(gateway)
+}
+
+/// Free a gateway
+///
+/// # Arguments
+///
+/// * `gateway` - Gateway to free. May be NULL (no-op).
+///
+/// # Safety
+///
+/// The gateway must not be used after calling this function.
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_free(gateway: *mut AlecGateway) {
+    if !gateway.is_null() {
+        unsafe {
+            drop(Box::from_raw(gateway));
+        }
+    }
+}
+
+/// Register a sensor channel on the gateway
+///
+/// # Arguments
+///
+/// * `gateway` - Gateway handle
+/// * `name` - Channel name (null-terminated UTF-8, ≤ 255 bytes)
+/// * `priority` - Packing priority, 0 = highest. Higher-priority channels
+///   are placed first in the frame and survive frame-size truncation.
+///
+/// # Returns
+///
+/// * `ALEC_OK` on success.
+/// * `ALEC_ERROR_NULL_POINTER` if `gateway` or `name` is NULL.
+/// * `ALEC_ERROR_INVALID_UTF8` if `name` is not valid UTF-8.
+/// * `ALEC_ERROR_INVALID_INPUT` if the name is empty or longer than
+///   255 bytes, the channel already exists, or the channel limit is
+///   reached.
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_add_channel(
+    gateway: *mut AlecGateway,
+    name: *const c_char,
+    priority: u8,
+) -> AlecResult {
+    if gateway.is_null() {
+        return AlecResult::ErrorNullPointer;
+    }
+    let name = match channel_name(name) {
+        Ok(n) => n,
+        Err(r) => return r,
+    };
+    // The frame format stores the channel id length in a single byte.
+    if name.is_empty() || name.len() > u8::MAX as usize {
+        return AlecResult::ErrorInvalidInput;
+    }
+
+    let gw = unsafe { &mut *gateway };
+    match gw
+        .gateway
+        .add_channel(name, ChannelConfig::with_priority(priority))
+    {
+        Ok(()) => AlecResult::Ok,
+        Err(e) => gateway_error_to_result(&e),
+    }
+}
+
+/// Push a sensor value to a gateway channel
+///
+/// # Arguments
+///
+/// * `gateway` - Gateway handle
+/// * `name` - Channel name (null-terminated UTF-8)
+/// * `value` - Sensor value
+/// * `timestamp` - Timestamp of the measurement (can be 0 if not used)
+///
+/// # Returns
+///
+/// * `ALEC_OK` on success.
+/// * `ALEC_ERROR_NULL_POINTER` if `gateway` or `name` is NULL.
+/// * `ALEC_ERROR_INVALID_UTF8` if `name` is not valid UTF-8.
+/// * `ALEC_ERROR_INVALID_INPUT` if the channel does not exist.
+/// * `ALEC_ERROR_BUFFER_TOO_SMALL` if the channel's pending buffer is
+///   full — call `alec_gateway_flush` first.
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_push(
+    gateway: *mut AlecGateway,
+    name: *const c_char,
+    value: f64,
+    timestamp: u64,
+) -> AlecResult {
+    if gateway.is_null() {
+        return AlecResult::ErrorNullPointer;
+    }
+    let name = match channel_name(name) {
+        Ok(n) => n,
+        Err(r) => return r,
+    };
+
+    let gw = unsafe { &mut *gateway };
+    match gw.gateway.push(name, value, timestamp) {
+        Ok(()) => AlecResult::Ok,
+        Err(e) => gateway_error_to_result(&e),
+    }
+}
+
+/// Flush all channels into a single priority-packed frame
+///
+/// Channels are packed in priority order up to the gateway's
+/// `max_frame_size`. An empty frame (2 header bytes) is written when no
+/// channel has pending data.
+///
+/// If the frame does not fit in `out_buf`, `ALEC_ERROR_BUFFER_TOO_SMALL`
+/// is returned, `*out_len` reports the required size and `out_buf` is NOT
+/// modified. The already-encoded frame is retained by the gateway and
+/// returned by the next call to `alec_gateway_flush`, so retrying with a
+/// larger buffer loses no samples.
+///
+/// # Arguments
+///
+/// * `gateway` - Gateway handle
+/// * `out_buf` - Output buffer for the serialized frame
+/// * `cap` - Size of `out_buf` in bytes
+/// * `out_len` - Out: bytes written (on success) or required size
+///   (on `ALEC_ERROR_BUFFER_TOO_SMALL`)
+///
+/// # Returns
+///
+/// * `ALEC_OK` on success.
+/// * `ALEC_ERROR_NULL_POINTER` for a NULL required pointer.
+/// * `ALEC_ERROR_BUFFER_TOO_SMALL` if `cap` is too small.
+/// * `ALEC_ERROR_ENCODING_FAILED` if a channel failed to encode.
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_flush(
+    gateway: *mut AlecGateway,
+    out_buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> AlecResult {
+    if gateway.is_null() || out_buf.is_null() || out_len.is_null() {
+        return AlecResult::ErrorNullPointer;
+    }
+    let gw = unsafe { &mut *gateway };
+
+    let (bytes, counts) = match gw.pending_frame.take() {
+        Some(bytes) => (bytes, gw.pending_counts),
+        None => {
+            // Snapshot pending counts so the samples that made it into
+            // the frame can be reported after the flush drains them.
+            let pending: Vec<(String, usize)> = gw
+                .gateway
+                .channels()
+                .into_iter()
+                .map(|id| {
+                    let n = gw.gateway.pending(&id).unwrap_or(0);
+                    (id, n)
+                })
+                .collect();
+
+            let frame = match gw.gateway.flush() {
+                Ok(f) => f,
+                Err(e) => return gateway_error_to_result(&e),
+            };
+            let samples = frame
+                .channels
+                .iter()
+                .map(|ch| {
+                    pending
+                        .iter()
+                        .find(|(id, _)| *id == ch.id)
+                        .map(|(_, n)| *n)
+                        .unwrap_or(0)
+                })
+                .sum();
+            (frame.to_bytes(), (frame.channel_count(), samples))
+        }
+    };
+
+    if bytes.len() > cap {
+        unsafe { *out_len = bytes.len() };
+        gw.pending_frame = Some(bytes);
+        gw.pending_counts = counts;
+        return AlecResult::ErrorBufferTooSmall;
+    }
+
+    let out_slice = unsafe { slice::from_raw_parts_mut(out_buf, cap) };
+    out_slice[..bytes.len()].copy_from_slice(&bytes);
+    unsafe { *out_len = bytes.len() };
+    gw.last_flush_channels = counts.0;
+    gw.last_flush_samples = counts.1;
+    AlecResult::Ok
+}
+
+/// Report what the most recent successful flush contained
+///
+/// # Arguments
+///
+/// * `gateway` - Gateway handle
+/// * `out_channels` - Out: number of channels in the last frame (may be NULL)
+/// * `out_samples` - Out: number of samples in the last frame (may be NULL)
+///
+/// # Returns
+///
+/// `ALEC_OK` on success, `ALEC_ERROR_NULL_POINTER` if `gateway` is NULL.
+/// Both counts are 0 before the first successful flush.
+#[cfg(feature = "gateway")]
+#[no_mangle]
+pub extern "C" fn alec_gateway_last_flush_stats(
+    gateway: *const AlecGateway,
+    out_channels: *mut usize,
+    out_samples: *mut usize,
+) -> AlecResult {
+    if gateway.is_null() {
+        return AlecResult::ErrorNullPointer;
+    }
+    let gw = unsafe { &*gateway };
+    if !out_channels.is_null() {
+        unsafe { *out_channels = gw.last_flush_channels };
+    }
+    if !out_samples.is_null() {
+        unsafe { *out_samples = gw.last_flush_samples };
+    }
+    AlecResult::Ok
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -2284,7 +3097,7 @@ mod tests {
         let version = alec_encoder_context_version(enc);
         assert_eq!(version, 0); // Initial version
 
-        // Encode some data to increment version
+        // Encode some data
         let mut output = [0u8; 256];
         let mut output_len: usize = 0;
         alec_encode_value(
@@ -2297,8 +3110,16 @@ mod tests {
             &mut output_len,
         );
 
-        let new_version = alec_encoder_context_version(enc);
-        assert!(new_version > version);
+        // Observations don't change the dictionary
+        assert_eq!(alec_encoder_context_version(enc), version);
+
+        // Learning a pattern does
+        unsafe { &*enc }
+            .state()
+            .context
+            .register_pattern(alec::context::Pattern::new(vec![1, 2, 3]))
+            .unwrap();
+        assert!(alec_encoder_context_version(enc) > version);
 
         alec_encoder_free(enc);
     }
@@ -2325,6 +3146,44 @@ mod tests {
         alec_encoder_free(enc);
     }
 
+    #[test]
+    fn test_encode_value_with_priority() {
+        let enc = alec_encoder_new();
+        let mut output = [0u8; 256];
+        let mut output_len: usize = 0;
+
+        let result = alec_encode_value_with_priority(
+            enc,
+            22.5,
+            1234567890,
+            1,
+            ptr::null(),
+            output.as_mut_ptr(),
+            output.len(),
+            &mut output_len,
+        );
+        assert_eq!(result, AlecResult::Ok);
+        let header = alec::MessageHeader::from_bytes(&output[..output_len]).unwrap();
+        assert_eq!(header.priority, Priority::P1Critical);
+        assert!(header.is_operator_override());
+
+        for priority in [0, 6] {
+            let result = alec_encode_value_with_priority(
+                enc,
+                22.5,
+                1234567891,
+                priority,
+                ptr::null(),
+                output.as_mut_ptr(),
+                output.len(),
+                &mut output_len,
+            );
+            assert_eq!(result, AlecResult::ErrorInvalidInput);
+        }
+
+        alec_encoder_free(enc);
+    }
+
     #[test]
     fn test_encode_value_with_source_id() {
         let enc = alec_encoder_new();
@@ -2371,12 +3230,12 @@ mod tests {
         let ha = hash_source_id(a.as_ptr() as *const c_char);
         let hb = hash_source_id(b.as_ptr() as *const c_char);
         assert!(
-            ha >= 1 && ha <= 127,
+            (1..=127).contains(&ha),
             "hash out of 1-byte varint range: {}",
             ha
         );
         assert!(
-            hb >= 1 && hb <= 127,
+            (1..=127).contains(&hb),
             "hash out of 1-byte varint range: {}",
             hb
         );
@@ -2425,6 +3284,63 @@ mod tests {
         alec_decoder_free(dec);
     }
 
+    #[test]
+    fn test_encoder_with_policy() {
+        assert!(alec_encoder_new_with_policy(0).is_null());
+        assert!(alec_encoder_new_with_policy(6).is_null());
+        assert!(alec_decoder_new_with_policy(0).is_null());
+
+        let plain = alec_encoder_new();
+        let enc = alec_encoder_new_with_policy(5);
+        let dec = alec_decoder_new_with_policy(5);
+        assert!(!enc.is_null());
+        assert!(!dec.is_null());
+
+        let mut plain_out = [0u8; 256];
+        let mut plain_len: usize = 0;
+        let result = alec_encode_value(
+            plain,
+            22.5,
+            12345,
+            ptr::null(),
+            plain_out.as_mut_ptr(),
+            plain_out.len(),
+            &mut plain_len,
+        );
+        assert_eq!(result, AlecResult::Ok);
+
+        let mut encoded = [0u8; 256];
+        let mut encoded_len: usize = 0;
+        let result = alec_encode_value(
+            enc,
+            22.5,
+            12345,
+            ptr::null(),
+            encoded.as_mut_ptr(),
+            encoded.len(),
+            &mut encoded_len,
+        );
+        assert_eq!(result, AlecResult::Ok);
+        // Threshold 5 covers every priority: checksum always appended
+        assert_eq!(encoded_len, plain_len + alec::protocol::CHECKSUM_SIZE);
+
+        let mut decoded_value: f64 = 0.0;
+        let mut decoded_timestamp: u64 = 0;
+        let result = alec_decode_value(
+            dec,
+            encoded.as_ptr(),
+            encoded_len,
+            &mut decoded_value,
+            &mut decoded_timestamp,
+        );
+        assert_eq!(result, AlecResult::Ok);
+        assert!((decoded_value - 22.5).abs() < 0.01);
+
+        alec_encoder_free(plain);
+        alec_encoder_free(enc);
+        alec_decoder_free(dec);
+    }
+
     // ============================================================================
     // Bloc A1: Config FFI + keyframe + gap detection
     // ============================================================================
@@ -2436,7 +3352,7 @@ mod tests {
         assert_eq!(ALEC_DEFAULT_MAX_PATTERNS, 256);
         assert_eq!(ALEC_DEFAULT_MAX_MEMORY_BYTES, 2048);
         assert_eq!(ALEC_DEFAULT_KEYFRAME_INTERVAL, 50);
-        assert!(ALEC_DEFAULT_SMART_RESYNC);
+        const _: () = assert!(ALEC_DEFAULT_SMART_RESYNC);
 
         let d = AlecEncoderConfig::defaults();
         assert_eq!(d.history_size, ALEC_DEFAULT_HISTORY_SIZE);
@@ -2450,7 +3366,7 @@ mod tests {
     fn test_encoder_new_with_config_null_uses_defaults() {
         let enc = alec_encoder_new_with_config(ptr::null());
         assert!(!enc.is_null());
-        let e = unsafe { &*enc };
+        let e = unsafe { &*enc }.state();
         assert_eq!(e.keyframe_interval, ALEC_DEFAULT_KEYFRAME_INTERVAL);
         assert!(e.smart_resync);
         assert!(!e.force_keyframe_pending);
@@ -2470,7 +3386,7 @@ mod tests {
         };
         let enc = alec_encoder_new_with_config(&cfg);
         assert!(!enc.is_null());
-        let e = unsafe { &*enc };
+        let e = unsafe { &*enc }.state();
         assert_eq!(e.keyframe_interval, 25);
         assert!(!e.smart_resync);
         alec_encoder_free(enc);
@@ -2490,7 +3406,7 @@ mod tests {
         };
         let enc = alec_encoder_new_with_config(&cfg);
         assert!(!enc.is_null());
-        let e = unsafe { &*enc };
+        let e = unsafe { &*enc }.state();
         // keyframe_interval=0 must be preserved verbatim (disabled).
         assert_eq!(e.keyframe_interval, 0);
         // Encoding should still succeed — history/patterns/memory got defaults.
@@ -2513,9 +3429,9 @@ mod tests {
     fn test_force_keyframe_sets_flag() {
         let enc = alec_encoder_new();
         assert!(!enc.is_null());
-        assert!(!unsafe { &*enc }.force_keyframe_pending);
+        assert!(!unsafe { &*enc }.state().force_keyframe_pending);
         alec_force_keyframe(enc);
-        assert!(unsafe { &*enc }.force_keyframe_pending);
+        assert!(unsafe { &*enc }.state().force_keyframe_pending);
         alec_encoder_free(enc);
     }
 
@@ -2538,7 +3454,7 @@ mod tests {
         let enc = alec_encoder_new_with_config(&cfg);
         alec_force_keyframe(enc);
         // smart_resync=false → force_keyframe is a no-op.
-        assert!(!unsafe { &*enc }.force_keyframe_pending);
+        assert!(!unsafe { &*enc }.state().force_keyframe_pending);
         alec_encoder_free(enc);
     }
 
@@ -2640,6 +3556,117 @@ mod tests {
         alec_decoder_free(dec);
     }
 
+    /// Serialize a multi frame built with the Rust encoder
+    fn multi_frame(values: &[(u8, f64)], timestamp_ms: u64) -> Vec<u8> {
+        let context = alec::Context::new();
+        alec::Encoder::new()
+            .encode_multi(values, 0, timestamp_ms, alec::Priority::P3Normal, &context)
+            .unwrap()
+            .to_bytes()
+    }
+
+    #[test]
+    fn test_decode_multi_ex_parallel_arrays() {
+        let dec = alec_decoder_new();
+        let mut vals = [0f64; 4];
+        let mut ids = [0u16; 4];
+        let mut ts = [0u64; 4];
+        let mut count: usize = 0;
+
+        for (i, timestamp_ms) in [1_000_000u64, 1_060_000].iter().enumerate() {
+            let frame = multi_frame(&[(1, 22.5), (2, 65.0), (3, 1013.25)], *timestamp_ms);
+            let res = alec_decode_multi_ex(
+                dec,
+                frame.as_ptr(),
+                frame.len(),
+                vals.as_mut_ptr(),
+                ids.as_mut_ptr(),
+                ts.as_mut_ptr(),
+                vals.len(),
+                &mut count,
+            );
+            assert_eq!(res, AlecResult::Ok, "frame {}", i);
+            assert_eq!(count, 3);
+            assert_eq!(&ids[..3], &[1, 2, 3]);
+            assert!((vals[2] - 1013.25).abs() < 0.01);
+            assert_eq!(&ts[..3], &[timestamp_ms / 1000; 3]);
+        }
+
+        // Compressed header timestamp → ALEC_TIMESTAMP_UNKNOWN
+        let mut message = alec::protocol::EncodedMessage::from_bytes(&multi_frame(
+            &[(1, 22.5), (2, 65.0), (3, 1013.25)],
+            1_120_000,
+        ))
+        .unwrap();
+        message.header.layout = alec::HeaderLayout::Plain(alec::protocol::TimestampFormat::Delta);
+        let frame = message.to_bytes();
+        let res = alec_decode_multi_ex(
+            dec,
+            frame.as_ptr(),
+            frame.len(),
+            vals.as_mut_ptr(),
+            ids.as_mut_ptr(),
+            ts.as_mut_ptr(),
+            vals.len(),
+            &mut count,
+        );
+        assert_eq!(res, AlecResult::Ok);
+        assert_eq!(count, 3);
+        assert_eq!(&ts[..3], &[ALEC_TIMESTAMP_UNKNOWN; 3]);
+
+        alec_decoder_free(dec);
+    }
+
+    #[test]
+    fn test_decode_multi_ex_empty_and_too_small() {
+        let dec = alec_decoder_new();
+        let mut vals = [0f64; 2];
+        let mut ids = [0u16; 2];
+        let mut ts = [0u64; 2];
+        let mut count: usize = 99;
+
+        let empty = multi_frame(&[], 1_000_000);
+        let res = alec_decode_multi_ex(
+            dec,
+            empty.as_ptr(),
+            empty.len(),
+            vals.as_mut_ptr(),
+            ids.as_mut_ptr(),
+            ts.as_mut_ptr(),
+            vals.len(),
+            &mut count,
+        );
+        assert_eq!(res, AlecResult::Ok);
+        assert_eq!(count, 0);
+
+        let frame = multi_frame(&[(1, 1.0), (2, 2.0), (3, 3.0)], 1_000_000);
+        let res = alec_decode_multi_ex(
+            dec,
+            frame.as_ptr(),
+            frame.len(),
+            vals.as_mut_ptr(),
+            ids.as_mut_ptr(),
+            ts.as_mut_ptr(),
+            vals.len(),
+            &mut count,
+        );
+        assert_eq!(res, AlecResult::ErrorBufferTooSmall);
+
+        let res = alec_decode_multi_ex(
+            dec,
+            frame.as_ptr(),
+            frame.len(),
+            vals.as_mut_ptr(),
+            ptr::null_mut(),
+            ts.as_mut_ptr(),
+            vals.len(),
+            &mut count,
+        );
+        assert_eq!(res, AlecResult::ErrorNullPointer);
+
+        alec_decoder_free(dec);
+    }
+
     /// cbindgen should surface the new symbols. This test is a compile-time
     /// guarantee that the FFI entry points exist with the expected signatures.
     #[test]
@@ -2892,8 +3919,8 @@ mod tests {
         let dec = alec_decoder_new();
 
         unsafe {
-            (*enc).context.set_version(65530);
-            (*dec).context.set_version(65530);
+            (*enc).state().context.set_version(65530);
+            (*dec).state().context.set_version(65530);
         }
 
         let row = [3.6, 22.5, 45.0, 420.0, 1013.25];
@@ -3281,17 +4308,17 @@ mod tests {
             do_encode(enc, &row, &mut out);
         }
         // Context should now hold non-trivial state.
-        let e = unsafe { &*enc };
+        let e = unsafe { &*enc }.state();
         assert!(e.context.last_value(1).is_some());
         assert!(e.context.last_value(5).is_some());
         let pre_version = e.context.version();
 
         // Reset.
-        unsafe { &mut *enc }.context.reset_to_baseline();
+        unsafe { &*enc }.state().context.reset_to_baseline();
 
         // All per-channel last_value are cleared — but the version
         // counter is preserved (see C1 contract).
-        let e = unsafe { &*enc };
+        let e = unsafe { &*enc }.state();
         assert!(e.context.last_value(1).is_none());
         assert!(e.context.last_value(5).is_none());
         assert_eq!(e.context.version(), pre_version);
@@ -3492,7 +4519,7 @@ mod tests {
         let r = alec_downlink_handler(enc, cmd.as_ptr(), cmd.len());
         assert_eq!(r, AlecResult::Ok);
         assert!(
-            unsafe { &*enc }.force_keyframe_pending,
+            unsafe { &*enc }.state().force_keyframe_pending,
             "downlink 0xFF must arm the force-keyframe flag"
         );
 
@@ -3530,13 +4557,13 @@ mod tests {
     #[test]
     fn test_downlink_handler_invalid_command() {
         let enc = alec_encoder_new();
-        assert!(!unsafe { &*enc }.force_keyframe_pending);
+        assert!(!unsafe { &*enc }.state().force_keyframe_pending);
 
         let cmd = [0x00_u8];
         let r = alec_downlink_handler(enc, cmd.as_ptr(), cmd.len());
         assert_eq!(r, AlecResult::ErrorInvalidInput);
         assert!(
-            !unsafe { &*enc }.force_keyframe_pending,
+            !unsafe { &*enc }.state().force_keyframe_pending,
             "unknown downlink must NOT arm the force-keyframe flag"
         );
 
@@ -3544,7 +4571,7 @@ mod tests {
         let cmd = [0x7E_u8, 0xFF, 0xAA];
         let r = alec_downlink_handler(enc, cmd.as_ptr(), cmd.len());
         assert_eq!(r, AlecResult::ErrorInvalidInput);
-        assert!(!unsafe { &*enc }.force_keyframe_pending);
+        assert!(!unsafe { &*enc }.state().force_keyframe_pending);
 
         // NULL / empty inputs are defensively handled.
         assert_eq!(
@@ -3590,7 +4617,7 @@ mod tests {
         // currently 9*5=45 post-observation; we pretend the decoder
         // last saw a version ~30k ahead so the next non-keyframe
         // trips the mismatch detector.
-        unsafe { &mut *dec }.decoder.reset();
+        unsafe { &*dec }.state().decoder.reset();
         // reset() clears last_fixed_sequence too, so replay a frame
         // to re-arm sequence tracking:
         do_decode(dec, &frames[8]);
@@ -3616,7 +4643,12 @@ mod tests {
         // readings for channel 1, so its per-source observation count
         // is ≥ 3 and `predict()` reports the `MovingAverage` model.
         use alec::context::PredictionModel;
-        let model_before = unsafe { &*dec }.context.predict(1).unwrap().model_type;
+        let model_before = unsafe { &*dec }
+            .state()
+            .context
+            .predict(1)
+            .unwrap()
+            .model_type;
         assert_eq!(model_before, PredictionModel::MovingAverage);
 
         // Decoding the tampered frame must surface a mismatch and
@@ -3644,7 +4676,12 @@ mod tests {
         // branch in SourceStats::predict). If the reset had NOT
         // fired, count would be 10 and the model would still be
         // MovingAverage.
-        let model_after = unsafe { &*dec }.context.predict(1).unwrap().model_type;
+        let model_after = unsafe { &*dec }
+            .state()
+            .context
+            .predict(1)
+            .unwrap()
+            .model_type;
         assert_eq!(
             model_after,
             PredictionModel::LastValue,
@@ -3971,8 +5008,8 @@ mod tests {
 
         // Overwrite the session-state fields with a known tuple.
         unsafe {
-            (*dec).last_header_sequence = Some(42);
-            (*dec).last_gap_size = 2;
+            (*dec).state().last_header_sequence = Some(42);
+            (*dec).state().last_gap_size = 2;
         }
 
         // Export and re-import — session state should survive.
@@ -3993,7 +5030,7 @@ mod tests {
             AlecResult::Ok
         );
 
-        let dec_ref = unsafe { &*dec };
+        let dec_ref = unsafe { &*dec }.state();
         assert_eq!(dec_ref.last_header_sequence, Some(42));
         assert_eq!(dec_ref.last_gap_size, 2);
 
@@ -4017,13 +5054,14 @@ mod tests {
         // Register one pattern directly on the context so we can
         // check it survives the reset.
         use alec::context::Pattern;
-        let code = unsafe { &mut *dec }
+        let code = unsafe { &*dec }
+            .state()
             .context
             .register_pattern(Pattern::new(vec![0x11, 0x22, 0x33]))
             .unwrap();
 
         // Reset → export → import → verify.
-        unsafe { &mut *dec }.context.reset_to_baseline();
+        unsafe { &*dec }.state().context.reset_to_baseline();
         let mut buf = vec![0u8; 4096];
         let mut n: usize = 0;
         assert_eq!(
@@ -4044,7 +5082,7 @@ mod tests {
         );
 
         // source_stats is empty (predict returns None for any sid).
-        let d = unsafe { &*dec_fresh };
+        let d = unsafe { &*dec_fresh }.state();
         assert!(d.context.predict(1).is_none());
         assert!(d.context.predict(5).is_none());
         // Pattern registered pre-reset must still be in the dictionary.
@@ -4109,4 +5147,83 @@ mod tests {
 
         alec_decoder_free(dec);
     }
+
+    // ========================================================================
+    // Thread-safe handles
+    // ========================================================================
+
+    #[test]
+    fn test_threadsafe_encoder_shared_between_threads() {
+        const THREADS: u64 = 8;
+        const ITERATIONS: u64 = 10_000;
+        let enc = alec_encoder_new_threadsafe();
+        assert!(!enc.is_null());
+        // Raw pointers are not Send: share the address
+        let address = enc as usize;
+
+        let workers: Vec<_> = (0..THREADS)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    let enc = address as *mut AlecEncoder;
+                    let source = std::ffi::CString::new(format!("sensor-{}", t)).unwrap();
+                    let mut output = [0u8; 64];
+                    for i in 0..ITERATIONS {
+                        let mut output_len = 0;
+                        let result = alec_encode_value(
+                            enc,
+                            20.0 + (i % 10) as f64 * 0.1,
+                            i,
+                            source.as_ptr(),
+                            output.as_mut_ptr(),
+                            output.len(),
+                            &mut output_len,
+                        );
+                        assert_eq!(result, AlecResult::Ok);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // Every observation counted: no update lost to a race
+        let total = THREADS * ITERATIONS;
+        {
+            let e = unsafe { &*enc }.state();
+            assert_eq!(e.context.observation_count(), total);
+            assert_eq!(e.context.source_count(), THREADS as usize);
+        }
+        alec_encoder_free(enc);
+    }
+
+    #[test]
+    fn test_threadsafe_free_waits_for_lock() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::{mpsc, Arc};
+
+        let dec = alec_decoder_new_threadsafe();
+        let address = dec as usize;
+        let released = Arc::new(AtomicBool::new(false));
+        let (locked_tx, locked_rx) = mpsc::channel();
+
+        let holder = {
+            let released = Arc::clone(&released);
+            std::thread::spawn(move || {
+                let dec = address as *mut AlecDecoder;
+                let _guard = unsafe { &*dec }.state();
+                locked_tx.send(()).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                released.store(true, Ordering::SeqCst);
+            })
+        };
+        locked_rx.recv().unwrap();
+        let freeing = unsafe { &*dec }.handle.release();
+        assert!(released.load(Ordering::SeqCst));
+        assert!(freeing);
+        // A second free of a handle being freed is refused
+        assert!(!unsafe { &*dec }.handle.release());
+        unsafe { drop(Box::from_raw(dec)) };
+        holder.join().unwrap();
+    }
 }
@@ -137,15 +137,14 @@ fn feed_values_matches_encoder_state() {
         assert_eq!(r, AlecResult::Ok, "feed_values failed at frame {}", i);
     }
 
-    // (a) Context versions must agree. Both incremented by 5
-    //     (channel_count) per frame × 100 frames = 500 — but we
-    //     don't hard-code that, just check parity.
+    // (a) Dictionary versions must agree. Observations don't change
+    //     them; the observation-driven wire ctx_ver is not exposed
+    //     over FFI.
     let enc_v = alec_encoder_context_version(enc);
     let dec_v = alec_decoder_context_version(dec_fed);
     assert_eq!(
         enc_v, dec_v,
-        "context.version mismatch: enc={enc_v}, dec_fed={dec_v} \
-         — feed_values' observe loop is not in step with the encoder's"
+        "context version mismatch: enc={enc_v}, dec_fed={dec_v}"
     );
 
     // (b) Probe round-trip. With dec_fed's prediction state == enc's,
@@ -246,8 +245,8 @@ fn mixed_alec_and_tlv_round_trip() {
     assert!(alec_count > 0, "expected some ALEC frames");
     assert!(tlv_count > 0, "expected some TLV-fallback frames");
 
-    // Encoder + decoder context.version stay in step throughout
-    // (both advance by 5/frame regardless of ALEC vs TLV).
+    // Encoder + decoder dictionary versions stay in step throughout
+    // (observations, ALEC or TLV, don't change them).
     assert_eq!(
         alec_encoder_context_version(enc),
         alec_decoder_context_version(dec),
@@ -0,0 +1,242 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! `alec_gateway_*` integration tests.
+//!
+//! Cover the gateway FFI surface used by C integrators who want the
+//! multi-channel behaviour (priority packing, frame size limits) rather
+//! than single-value encode: handle lifecycle, push/flush round-trip into
+//! a LoRaWAN-sized 242-byte buffer, and the buffer-too-small contract
+//! (required size reported, frame retained for the retry).
+
+#![cfg(feature = "gateway")]
+
+use std::ffi::CString;
+use std::ptr;
+
+use alec_ffi::{
+    alec_gateway_add_channel, alec_gateway_flush, alec_gateway_free, alec_gateway_last_flush_stats,
+    alec_gateway_new, alec_gateway_push, AlecResult,
+};
+use alec_gateway::Frame;
+
+fn cstr(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+#[test]
+fn gateway_lifecycle() {
+    let gw = alec_gateway_new(242);
+    assert!(!gw.is_null());
+    alec_gateway_free(gw);
+
+    // Default frame size.
+    let gw = alec_gateway_new(0);
+    assert!(!gw.is_null());
+    alec_gateway_free(gw);
+
+    // NULL is a no-op.
+    alec_gateway_free(ptr::null_mut());
+}
+
+#[test]
+fn gateway_null_pointers() {
+    let temp = cstr("temp");
+    let mut buf = [0u8; 16];
+    let mut len = 0usize;
+
+    assert_eq!(
+        alec_gateway_add_channel(ptr::null_mut(), temp.as_ptr(), 1),
+        AlecResult::ErrorNullPointer
+    );
+    assert_eq!(
+        alec_gateway_push(ptr::null_mut(), temp.as_ptr(), 1.0, 0),
+        AlecResult::ErrorNullPointer
+    );
+    assert_eq!(
+        alec_gateway_flush(ptr::null_mut(), buf.as_mut_ptr(), buf.len(), &mut len),
+        AlecResult::ErrorNullPointer
+    );
+    assert_eq!(
+        alec_gateway_last_flush_stats(ptr::null(), ptr::null_mut(), ptr::null_mut()),
+        AlecResult::ErrorNullPointer
+    );
+
+    let gw = alec_gateway_new(242);
+    assert_eq!(
+        alec_gateway_add_channel(gw, ptr::null(), 1),
+        AlecResult::ErrorNullPointer
+    );
+    assert_eq!(
+        alec_gateway_flush(gw, ptr::null_mut(), 0, &mut len),
+        AlecResult::ErrorNullPointer
+    );
+    alec_gateway_free(gw);
+}
+
+#[test]
+fn gateway_invalid_utf8_channel_name() {
+    let gw = alec_gateway_new(242);
+    let bad: [u8; 3] = [0xC3, 0x28, 0x00];
+    assert_eq!(
+        alec_gateway_add_channel(gw, bad.as_ptr() as *const _, 1),
+        AlecResult::ErrorInvalidUtf8
+    );
+    assert_eq!(
+        alec_gateway_push(gw, bad.as_ptr() as *const _, 1.0, 0),
+        AlecResult::ErrorInvalidUtf8
+    );
+    alec_gateway_free(gw);
+}
+
+#[test]
+fn gateway_channel_errors() {
+    let gw = alec_gateway_new(242);
+    let temp = cstr("temp");
+    let empty = cstr("");
+
+    assert_eq!(
+        alec_gateway_add_channel(gw, temp.as_ptr(), 1),
+        AlecResult::Ok
+    );
+    assert_eq!(
+        alec_gateway_add_channel(gw, temp.as_ptr(), 1),
+        AlecResult::ErrorInvalidInput
+    );
+    assert_eq!(
+        alec_gateway_add_channel(gw, empty.as_ptr(), 1),
+        AlecResult::ErrorInvalidInput
+    );
+
+    let unknown = cstr("unknown");
+    assert_eq!(
+        alec_gateway_push(gw, unknown.as_ptr(), 1.0, 0),
+        AlecResult::ErrorInvalidInput
+    );
+    alec_gateway_free(gw);
+}
+
+#[test]
+fn gateway_push_flush_roundtrip_242() {
+    let gw = alec_gateway_new(242);
+    let temp = cstr("temperature");
+    let humid = cstr("humidity");
+    let co2 = cstr("co2");
+
+    assert_eq!(
+        alec_gateway_add_channel(gw, co2.as_ptr(), 5),
+        AlecResult::Ok
+    );
+    assert_eq!(
+        alec_gateway_add_channel(gw, temp.as_ptr(), 1),
+        AlecResult::Ok
+    );
+    assert_eq!(
+        alec_gateway_add_channel(gw, humid.as_ptr(), 2),
+        AlecResult::Ok
+    );
+
+    for i in 0..3u64 {
+        let t = 1_000 + i * 1_000;
+        assert_eq!(
+            alec_gateway_push(gw, temp.as_ptr(), 22.5 + i as f64 * 0.1, t),
+            AlecResult::Ok
+        );
+        assert_eq!(
+            alec_gateway_push(gw, humid.as_ptr(), 65.0, t),
+            AlecResult::Ok
+        );
+    }
+    assert_eq!(
+        alec_gateway_push(gw, co2.as_ptr(), 420.0, 1_000),
+        AlecResult::Ok
+    );
+
+    let mut buf = [0u8; 242];
+    let mut len = 0usize;
+    assert_eq!(
+        alec_gateway_flush(gw, buf.as_mut_ptr(), buf.len(), &mut len),
+        AlecResult::Ok
+    );
+    assert!(len > 2 && len <= 242);
+
+    let frame = Frame::from_bytes(&buf[..len]).expect("valid gateway frame");
+    assert_eq!(frame.channel_count(), 3);
+    // Priority order: temperature (1), humidity (2), co2 (5).
+    assert_eq!(frame.channels[0].id, "temperature");
+    assert_eq!(frame.channels[1].id, "humidity");
+    assert_eq!(frame.channels[2].id, "co2");
+
+    let mut channels = 0usize;
+    let mut samples = 0usize;
+    assert_eq!(
+        alec_gateway_last_flush_stats(gw, &mut channels, &mut samples),
+        AlecResult::Ok
+    );
+    assert_eq!(channels, 3);
+    assert_eq!(samples, 7);
+
+    // Nothing pending: the next flush is an empty frame.
+    assert_eq!(
+        alec_gateway_flush(gw, buf.as_mut_ptr(), buf.len(), &mut len),
+        AlecResult::Ok
+    );
+    assert_eq!(len, 2);
+    assert_eq!(
+        alec_gateway_last_flush_stats(gw, &mut channels, &mut samples),
+        AlecResult::Ok
+    );
+    assert_eq!(channels, 0);
+    assert_eq!(samples, 0);
+
+    alec_gateway_free(gw);
+}
+
+#[test]
+fn gateway_flush_buffer_too_small() {
+    let gw = alec_gateway_new(242);
+    let temp = cstr("temp");
+    assert_eq!(
+        alec_gateway_add_channel(gw, temp.as_ptr(), 1),
+        AlecResult::Ok
+    );
+    for i in 0..4u64 {
+        assert_eq!(
+            alec_gateway_push(gw, temp.as_ptr(), 20.0 + i as f64, i * 1_000),
+            AlecResult::Ok
+        );
+    }
+
+    let mut small = [0xAAu8; 4];
+    let mut required = 0usize;
+    assert_eq!(
+        alec_gateway_flush(gw, small.as_mut_ptr(), small.len(), &mut required),
+        AlecResult::ErrorBufferTooSmall
+    );
+    assert!(required > small.len());
+    // No partial write.
+    assert_eq!(small, [0xAA; 4]);
+
+    // Retrying with a big enough buffer returns the retained frame.
+    let mut buf = vec![0u8; required];
+    let mut len = 0usize;
+    assert_eq!(
+        alec_gateway_flush(gw, buf.as_mut_ptr(), buf.len(), &mut len),
+        AlecResult::Ok
+    );
+    assert_eq!(len, required);
+    let frame = Frame::from_bytes(&buf[..len]).unwrap();
+    assert_eq!(frame.channel_count(), 1);
+
+    let mut samples = 0usize;
+    assert_eq!(
+        alec_gateway_last_flush_stats(gw, ptr::null_mut(), &mut samples),
+        AlecResult::Ok
+    );
+    assert_eq!(samples, 4);
+
+    alec_gateway_free(gw);
+}
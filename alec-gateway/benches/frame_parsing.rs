@@ -0,0 +1,108 @@
+//! Benchmarks for owned vs borrowed frame parsing
+//!
+//! Parses a 242-byte frame with 10 channel entries, each holding an ALEC
+//! message, and reports the heap allocations of each path.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use alec::{Context, EncodedMessage, EncodedMessageRef, Encoder, Priority, RawData};
+use alec_gateway::{Frame, FrameRef};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+/// System allocator counting allocations
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const FRAME_SIZE: usize = 242;
+const CHANNELS: usize = 10;
+
+/// Frame of 10 entries of 24 bytes: a 3-byte id and an 18-byte message
+fn build_frame() -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    let context = Context::new();
+    let messages: Vec<Vec<u8>> = (0..CHANNELS)
+        .map(|i| {
+            let data = RawData::new(20.0 + i as f64 * 0.5, i as u64 * 1000);
+            encoder
+                .encode_with_priority(&data, Priority::P3Normal, &context)
+                .to_bytes()
+        })
+        .collect();
+
+    let mut frame = Frame::new();
+    for (i, message) in messages.into_iter().enumerate() {
+        frame.add_channel(format!("ch{i}"), message);
+    }
+    let bytes = frame.to_bytes();
+    assert_eq!(bytes.len(), FRAME_SIZE);
+    bytes
+}
+
+/// Parse the frame and every entry's message, copying them
+fn parse_owned(bytes: &[u8]) -> usize {
+    let frame = Frame::from_bytes(bytes).unwrap();
+    frame
+        .channels
+        .iter()
+        .map(|ch| EncodedMessage::from_bytes(&ch.data).unwrap().len())
+        .sum()
+}
+
+/// Parse the frame and every entry's message in place
+fn parse_borrowed(bytes: &[u8]) -> usize {
+    let frame = FrameRef::from_bytes(bytes).unwrap();
+    frame
+        .channels()
+        .map(|ch| EncodedMessageRef::from_bytes(ch.data).unwrap().len())
+        .sum()
+}
+
+/// Allocations made by one call of `parse`
+fn count_allocations(parse: fn(&[u8]) -> usize, bytes: &[u8]) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(parse(black_box(bytes)));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_frame_parsing(c: &mut Criterion) {
+    let bytes = build_frame();
+    assert_eq!(parse_owned(&bytes), parse_borrowed(&bytes));
+
+    let owned = count_allocations(parse_owned, &bytes);
+    let borrowed = count_allocations(parse_borrowed, &bytes);
+    println!("allocations per {FRAME_SIZE}-byte frame: owned {owned}, borrowed {borrowed}");
+    assert!(owned > 0);
+    assert_eq!(borrowed, 0);
+
+    let mut group = c.benchmark_group("frame_parsing");
+    group.throughput(Throughput::Bytes(FRAME_SIZE as u64));
+
+    group.bench_function("owned", |b| b.iter(|| parse_owned(black_box(&bytes))));
+    group.bench_function("borrowed", |b| b.iter(|| parse_borrowed(black_box(&bytes))));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_frame_parsing);
+criterion_main!(benches);
@@ -15,7 +15,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.max_frame_size
     );
 
-    let mut gateway = Gateway::with_config(config);
+    let mut gateway = Gateway::with_config(config).unwrap();
 
     // Add sensor channels with different priorities
     gateway.add_channel(
@@ -8,53 +8,212 @@
 //!
 //! The [`Aggregator`] handles the logic of collecting encoded data from
 //! multiple channels and packing them into frames that respect size constraints.
+//!
+//! Every aggregated frame takes the next frame sequence number. With the
+//! `crypto` feature, channels configured with an encryption key are sealed
+//! under a nonce derived from it, so the sealed size (data plus
+//! [`crate::crypto::OVERHEAD`]) is what counts against the frame budget.
+//!
+//! With [`GatewayConfig::structural_headers`], frames are built as
+//! [`Frame::VERSION_STRUCTURAL`] and every entry records how many samples
+//! its channel flushed.
+//!
+//! [`Aggregator::estimate`] predicts the frame the next
+//! [`aggregate`](Aggregator::aggregate) would build, without flushing.
+//!
+//! A channel that fails to encode does not stop the others: its error is
+//! collected in the [`FlushReport`] (unless [`GatewayConfig::fail_fast`]),
+//! and after [`GatewayConfig::fault_threshold`] failed flushes in a row
+//! the channel is faulted and skipped.
+//!
+//! With [`GatewayConfig::starvation`], channels the frame budget keeps
+//! leaving out are boosted towards the front of the flush order; the
+//! [`FlushReport`] lists configured and effective priorities.
+//!
+//! With [`GatewayConfig::composite`], the eligible numeric channels go
+//! first, in one composite entry: each [`CompositeData`] message carries
+//! one sample of every channel with a sample in the same second, under a
+//! single header. Channels the entry cannot fit are left out of the frame.
+//!
+//! Channels sharing a [`ChannelConfig::group`] are packed as one unit: the
+//! pending data of every channel of the group goes in the frame, or all of
+//! it is deferred to a later flush. A group takes the most urgent effective
+//! priority among its channels, and is never part of a composite entry. A
+//! group whose data could not fit even an empty frame fails the flush with
+//! [`GatewayError::InvalidConfig`] naming it.
+//!
+//! [`ChannelConfig::group`]: crate::ChannelConfig::group
 
-use crate::channel_manager::ChannelManager;
+use crate::channel_manager::{Channel, ChannelManager};
 use crate::config::GatewayConfig;
-use crate::error::Result;
-use crate::frame::{Frame, FrameBuilder};
+use crate::error::{GatewayError, Result};
+use crate::frame::{ChannelData, CompositeData, Frame, FrameBuilder};
+use alec::{CompositeEntry, Context, Encoder, RawData};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Predicted outcome of the next flush (see [`Aggregator::estimate`])
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FlushEstimate {
+    /// Size of the frame if every pending channel were included
+    pub total_bytes: usize,
+    /// `(channel id, entry payload bytes, samples)` for every channel with
+    /// pending samples, in flush order; sealed channels include the
+    /// encryption overhead. In composite mode the channels of the
+    /// composite entry are counted once, first, under an empty id
+    pub per_channel: Vec<(String, usize, usize)>,
+    /// Whether `total_bytes` fits the maximum frame size, i.e. whether the
+    /// flush would carry every pending channel
+    pub fits_in_frame: bool,
+}
+
+/// Outcome of a flush that isolates channel failures
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    /// Frame carrying every channel that encoded
+    pub frame: Frame,
+    /// `(channel id, error)` for channels that failed to encode; their
+    /// samples stay buffered
+    pub errors: Vec<(String, GatewayError)>,
+    /// Channels that reached the fault threshold during this flush
+    pub tripped: Vec<String>,
+    /// Faulted channels skipped without encoding; channels in `tripped`
+    /// are skipped from the next flush on
+    pub faulted: Vec<String>,
+    /// `(channel id, configured priority, effective priority)` of every
+    /// channel considered, in flush order; they differ for channels boosted
+    /// by [`GatewayConfig::starvation`]
+    pub priorities: Vec<(String, u8, u8)>,
+    /// `(group, included)` for every channel group with pending data, in
+    /// flush order; the channels of a group left out stay buffered
+    pub groups: Vec<(String, bool)>,
+}
+
+impl FlushReport {
+    /// Whether every channel flushed without error
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty() && self.faulted.is_empty()
+    }
+}
 
 /// Aggregates data from multiple channels into frames
 pub struct Aggregator {
     /// Configuration for the aggregator
     config: GatewayConfig,
+    /// Sequence number of the next frame
+    frame_sequence: u64,
+    /// Encoder of the composite messages
+    composite_encoder: Encoder,
+}
+
+/// Composite entry built on copies of its channels' contexts
+struct CompositeBuild {
+    data: CompositeData,
+    /// `(samples carried, context after them)` per channel of the table
+    channels: Vec<(usize, Context)>,
+    /// Encoder after the messages
+    encoder: Encoder,
+}
+
+/// Channel group packed as one unit
+struct GroupPlan {
+    name: String,
+    /// Channels of the group that are not faulted
+    members: Vec<String>,
+    /// `(id length, data length, sealed)` of the entries they would add
+    entries: Vec<(usize, usize, bool)>,
+}
+
+impl CompositeBuild {
+    fn samples(&self) -> usize {
+        self.channels.iter().map(|(samples, _)| samples).sum()
+    }
 }
 
 impl Aggregator {
     /// Create a new aggregator with the given configuration
     pub fn new(config: GatewayConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            frame_sequence: 0,
+            composite_encoder: Encoder::new(),
+        }
     }
 
     /// Flush all channels and aggregate into a single frame
     ///
     /// Channels are processed in priority order (lower priority value = higher priority).
-    /// If the frame reaches its maximum size, lower-priority channels may be skipped.
-    pub fn aggregate(&self, manager: &mut ChannelManager) -> Result<Frame> {
-        let mut builder = FrameBuilder::new(self.config.max_frame_size);
-
-        // Collect channel IDs sorted by priority
-        let mut channel_ids: Vec<_> = manager.list().cloned().collect();
-        channel_ids.sort_by_key(|id| {
-            manager
-                .get(id)
-                .map(|c| c.config.priority)
-                .unwrap_or(u8::MAX)
-        });
+    /// If the frame reaches its maximum size, lower-priority channels may be skipped,
+    /// and with [`GatewayConfig::starvation`] they are boosted in later flushes.
+    /// Channel encode errors are dropped; see
+    /// [`aggregate_with_report`](Self::aggregate_with_report).
+    pub fn aggregate(&mut self, manager: &mut ChannelManager) -> Result<Frame> {
+        Ok(self.aggregate_with_report(manager)?.frame)
+    }
 
-        // Process channels in priority order
-        for id in channel_ids {
-            let channel = manager.get_mut(&id)?;
-            let data = channel.flush()?;
+    /// Flush all channels and report per-channel failures
+    ///
+    /// Like [`aggregate`](Self::aggregate), but a channel that fails to
+    /// encode is listed in the report and the remaining channels still
+    /// make the frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first channel encode error with
+    /// [`GatewayConfig::fail_fast`], and sealing errors.
+    pub fn aggregate_with_report(&mut self, manager: &mut ChannelManager) -> Result<FlushReport> {
+        let order = self.flush_order(manager);
+        self.collect(manager, order)
+    }
 
-            if !data.is_empty() && !builder.try_add(id.clone(), data) {
-                // Frame is full - in future, could return multiple frames
-                // For now, we just stop adding channels
-                break;
+    /// `(channel id, configured priority, effective priority)` of every
+    /// channel, in the order a full flush processes them
+    ///
+    /// The channels of a group are kept together, at the most urgent
+    /// effective priority among them.
+    fn flush_order(&self, manager: &ChannelManager) -> Vec<(String, u8, u8)> {
+        let starvation = self.config.starvation.as_ref();
+        let mut order: Vec<_> = manager
+            .iter()
+            .map(|(id, channel)| {
+                let priority = channel.config.priority;
+                let (skipped, fill) = (channel.skipped_flushes(), channel.fill_ratio());
+                let effective = starvation.map_or(priority, |starvation| {
+                    starvation.effective_priority(priority, skipped, fill)
+                });
+                let group = channel.config.group.clone();
+                (id.clone(), priority, effective, skipped, fill, group)
+            })
+            .collect();
+
+        let mut groups: HashMap<String, (u8, u32, f64)> = HashMap::new();
+        for (_, _, effective, skipped, fill, group) in &order {
+            if let Some(group) = group {
+                let key = groups
+                    .entry(group.clone())
+                    .or_insert((*effective, *skipped, *fill));
+                *key = (key.0.min(*effective), key.1.max(*skipped), key.2.max(*fill));
+            }
+        }
+        for (_, _, effective, skipped, fill, group) in &mut order {
+            if let Some(&key) = group.as_ref().and_then(|group| groups.get(group)) {
+                (*effective, *skipped, *fill) = key;
             }
         }
 
-        Ok(builder.build())
+        order.sort_by(|a, b| {
+            a.2.cmp(&b.2)
+                .then_with(|| match starvation {
+                    // Among equals, longest skipped then fullest first
+                    Some(_) => b.3.cmp(&a.3).then(b.4.total_cmp(&a.4)),
+                    None => Ordering::Equal,
+                })
+                .then_with(|| a.5.cmp(&b.5))
+        });
+        order
+            .into_iter()
+            .map(|(id, priority, effective, _, _, _)| (id, priority, effective))
+            .collect()
     }
 
     /// Flush specific channels and aggregate into a frame
@@ -62,22 +221,528 @@ impl Aggregator {
     /// Only the specified channels will be flushed. Channels are processed
     /// in the order provided.
     pub fn aggregate_channels(
-        &self,
+        &mut self,
         manager: &mut ChannelManager,
         channel_ids: &[&str],
     ) -> Result<Frame> {
-        let mut builder = FrameBuilder::new(self.config.max_frame_size);
+        Ok(self
+            .aggregate_channels_with_report(manager, channel_ids)?
+            .frame)
+    }
 
+    /// Flush specific channels and report per-channel failures
+    ///
+    /// A channel of a group brings the rest of its group along, right
+    /// after it. See [`aggregate_with_report`](Self::aggregate_with_report).
+    pub fn aggregate_channels_with_report(
+        &mut self,
+        manager: &mut ChannelManager,
+        channel_ids: &[&str],
+    ) -> Result<FlushReport> {
+        let mut ids: Vec<String> = Vec::new();
         for id in channel_ids {
-            if let Ok(channel) = manager.get_mut(id) {
-                let data = channel.flush()?;
-                if !data.is_empty() && !builder.try_add(id.to_string(), data) {
+            let Ok(channel) = manager.get(id) else {
+                continue;
+            };
+            if ids.contains(&channel.id) {
+                continue;
+            }
+            ids.push(channel.id.clone());
+            if let Some(group) = &channel.config.group {
+                let mut members: Vec<_> = manager
+                    .iter()
+                    .filter(|(id, other)| {
+                        other.config.group.as_ref() == Some(group) && !ids.contains(id)
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                members.sort();
+                ids.extend(members);
+            }
+        }
+
+        let mut order = Vec::with_capacity(ids.len());
+        for id in ids {
+            let priority = manager.get(&id)?.config.priority;
+            order.push((id, priority, priority));
+        }
+        self.collect(manager, order)
+    }
+
+    /// Flush the channels in `order` into the next frame
+    ///
+    /// Once the frame is full, the remaining channels with pending samples
+    /// are only counted as skipped. A group the frame cannot fit fills it
+    /// the same way.
+    fn collect(
+        &mut self,
+        manager: &mut ChannelManager,
+        order: Vec<(String, u8, u8)>,
+    ) -> Result<FlushReport> {
+        let mut report = FlushReport::default();
+        let groups = self.plan_groups(manager, &order, &mut report)?;
+        let mut builder = self.builder();
+        let sequence = self.next_frame_sequence();
+        let (composite, left_out) = if self.config.composite {
+            self.pack_composite(&mut builder, manager, &order)?
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let mut full = !left_out.is_empty();
+
+        for (id, _, _) in &order {
+            let channel = manager.get_mut(id)?;
+            if composite.contains(id) || left_out.contains(id) {
+                channel.set_skipped(left_out.contains(id));
+                continue;
+            }
+            if let Some(group) = groups.iter().find(|group| group.members.contains(id)) {
+                let included = match report.groups.iter().find(|(name, _)| *name == group.name) {
+                    Some(&(_, included)) => included,
+                    None => {
+                        let included = !full
+                            && builder.size_with(&group.entries) <= self.config.max_frame_size;
+                        full |= !included;
+                        report.groups.push((group.name.clone(), included));
+                        included
+                    }
+                };
+                if !included {
+                    let pending = channel.pending() > 0;
+                    channel.set_skipped(pending);
+                    continue;
+                }
+            }
+            if full {
+                let pending = channel.pending() > 0;
+                channel.set_skipped(pending);
+                continue;
+            }
+            if channel.is_faulted() {
+                report.faulted.push(id.clone());
+                continue;
+            }
+
+            // Buckets of grouped channels were closed by `plan_groups`
+            if channel.config.group.is_none() {
+                self.close_bucket(id, channel, &mut report)?;
+            }
+
+            let pending = channel.pending() > 0;
+            let remaining = builder.remaining();
+            for stream in channel.streams_mut() {
+                if !self.flush_stream(&mut builder, id, stream, sequence, &mut report)? {
+                    // Frame is full - in future, could return multiple frames
+                    // For now, we just stop adding channels
+                    full = true;
                     break;
                 }
             }
+            // Left out by the budget, not by an encode error
+            let included = builder.remaining() < remaining;
+            channel.set_skipped(full && pending && !included);
+        }
+
+        report.frame = builder.build();
+        report.priorities = order;
+        Ok(report)
+    }
+
+    /// Close the open bucket of a channel not carrying partial buckets
+    fn close_bucket(
+        &self,
+        id: &str,
+        channel: &mut Channel,
+        report: &mut FlushReport,
+    ) -> Result<()> {
+        let carry_partial = channel
+            .config
+            .downsample
+            .as_ref()
+            .map_or(true, |d| d.carry_partial);
+        if !carry_partial {
+            match channel.close_bucket() {
+                Ok(()) => {}
+                Err(e) if self.config.fail_fast => return Err(e),
+                Err(e) => report.errors.push((id.to_string(), e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Packing units of the channel groups in `order` with pending data,
+    /// in flush order
+    ///
+    /// Closes the buckets of the groups' channels first, so the projected
+    /// entries are those the flush adds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidConfig`] for a group whose entries
+    /// exceed the maximum frame size on their own.
+    fn plan_groups(
+        &self,
+        manager: &mut ChannelManager,
+        order: &[(String, u8, u8)],
+        report: &mut FlushReport,
+    ) -> Result<Vec<GroupPlan>> {
+        let mut groups: Vec<GroupPlan> = Vec::new();
+        for (id, _, _) in order {
+            let channel = manager.get_mut(id)?;
+            let Some(name) = channel.config.group.clone() else {
+                continue;
+            };
+            if channel.is_faulted() {
+                continue;
+            }
+            self.close_bucket(id, channel, report)?;
+
+            let index = match groups.iter().position(|group| group.name == name) {
+                Some(index) => index,
+                None => {
+                    groups.push(GroupPlan {
+                        name,
+                        members: Vec::new(),
+                        entries: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.members.push(id.clone());
+            group.entries.extend(
+                Self::projected_entries(channel)
+                    .into_iter()
+                    .map(|(id, bytes, _, sealed)| (id.len(), bytes, sealed)),
+            );
+        }
+
+        groups.retain(|group| !group.entries.is_empty());
+        for group in &groups {
+            let size = self.builder().size_with(&group.entries);
+            if size > self.config.max_frame_size {
+                return Err(GatewayError::InvalidConfig(format!(
+                    "Channel group '{}' needs a {} byte frame, more than the maximum frame size of {}",
+                    group.name, size, self.config.max_frame_size
+                )));
+            }
+        }
+        Ok(groups)
+    }
+
+    /// `(entry id, payload bytes, samples, sealed)` of every entry a flush
+    /// of `channel` would add, as [`Channel::projected_size`] predicts it
+    #[cfg_attr(not(feature = "crypto"), allow(unused_mut))]
+    fn projected_entries(channel: &mut Channel) -> Vec<(String, usize, usize, bool)> {
+        let mut entries = Vec::new();
+        for stream in channel.streams_mut() {
+            let samples = stream.pending();
+            let mut bytes = stream.projected_size();
+            if bytes == 0 {
+                continue;
+            }
+            let mut sealed = false;
+            #[cfg(feature = "crypto")]
+            if stream.config.encryption.is_some() {
+                bytes += crate::crypto::OVERHEAD;
+                sealed = true;
+            }
+            entries.push((stream.id.clone(), bytes, samples, sealed));
+        }
+        entries
+    }
+
+    /// Channels of `order` eligible for the composite entry
+    ///
+    /// Grouped channels are packed with their group instead.
+    fn composite_candidates(manager: &ChannelManager, order: &[(String, u8, u8)]) -> Vec<String> {
+        order
+            .iter()
+            .filter(|(id, _, _)| {
+                manager.get(id).is_ok_and(|channel| {
+                    channel.config.group.is_none() && channel.composite_eligible()
+                })
+            })
+            .map(|(id, _, _)| id.clone())
+            .take(u8::MAX as usize)
+            .collect()
+    }
+
+    /// Add the composite entry of the eligible channels to the frame
+    ///
+    /// Channels are dropped from the end of the flush order until the
+    /// entry fits. Returns the channels carried and the channels dropped.
+    fn pack_composite(
+        &mut self,
+        builder: &mut FrameBuilder,
+        manager: &mut ChannelManager,
+        order: &[(String, u8, u8)],
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let mut carried = Self::composite_candidates(manager, order);
+        let mut left_out = Vec::new();
+
+        while !carried.is_empty() {
+            let build = self.build_composite(manager, &carried)?;
+            let entry = ChannelData {
+                id: String::new(),
+                data: build.data.to_bytes(),
+                encrypted: false,
+                composite: true,
+                sample_count: self
+                    .config
+                    .structural_headers
+                    .then(|| build.samples().min(u16::MAX as usize) as u16),
+            };
+            if builder.try_push(entry) {
+                for (id, (samples, context)) in carried.iter().zip(build.channels) {
+                    manager.get_mut(id)?.commit_composite(samples, context);
+                }
+                self.composite_encoder = build.encoder;
+                break;
+            }
+            left_out.extend(carried.pop());
+        }
+
+        left_out.reverse();
+        Ok((carried, left_out))
+    }
+
+    /// Encode the buffers of `ids` as composite messages, leaving the
+    /// channels untouched
+    ///
+    /// Each message takes the next sample of every channel whose next
+    /// sample falls in the earliest second pending, classified as the
+    /// channel's own flush would; its priority is the most urgent among
+    /// them. A channel whose context stops predicting a finite value is
+    /// not carried further.
+    fn build_composite(&self, manager: &ChannelManager, ids: &[String]) -> Result<CompositeBuild> {
+        let channels = ids
+            .iter()
+            .map(|id| manager.get(id))
+            .collect::<Result<Vec<_>>>()?;
+        let mut states: Vec<(usize, Context)> = channels
+            .iter()
+            .map(|channel| (0, channel.context().clone()))
+            .collect();
+        let mut encoder = self.composite_encoder.clone();
+        let mut messages = Vec::new();
+        let mut stopped = vec![false; channels.len()];
+
+        while messages.len() < u8::MAX as usize {
+            let next: Vec<Option<(f64, u64)>> = (0..channels.len())
+                .map(|i| {
+                    if stopped[i] {
+                        None
+                    } else {
+                        channels[i].buffered().get(states[i].0).copied()
+                    }
+                })
+                .collect();
+            let Some(second) = next.iter().flatten().map(|(_, ts)| ts / 1000).min() else {
+                break;
+            };
+
+            let mut round = Vec::new();
+            for (i, next) in next.into_iter().enumerate() {
+                let Some((value, timestamp)) = next.filter(|(_, ts)| ts / 1000 == second) else {
+                    continue;
+                };
+                let context = &states[i].1;
+                if context.predict(0).is_some_and(|p| !p.value.is_finite()) {
+                    stopped[i] = true;
+                    continue;
+                }
+                let data = RawData::new(value, timestamp);
+                let classification = channels[i].classifier().classify(&data, context);
+                round.push((i, data, classification));
+            }
+            if round.is_empty() {
+                continue;
+            }
+
+            let timestamp = round.iter().map(|(_, data, _)| data.timestamp).min();
+            let priority = round.iter().map(|(_, _, c)| c.priority).min();
+            let entries: Vec<_> = round
+                .iter()
+                .map(|(i, data, _)| CompositeEntry {
+                    channel_id: *i as u32,
+                    value: data.value,
+                    context: &states[*i].1,
+                })
+                .collect();
+            let message = encoder.encode_composite(
+                0,
+                timestamp.unwrap_or_default(),
+                priority.unwrap_or_default(),
+                &entries,
+            )?;
+            messages.push(message.to_bytes_with_checksum());
+
+            for (i, data, classification) in &round {
+                let (sent, context) = &mut states[*i];
+                context.observe_classified(data, classification);
+                *sent += 1;
+            }
+        }
+
+        Ok(CompositeBuild {
+            data: CompositeData {
+                channels: ids.to_vec(),
+                messages,
+            },
+            channels: states,
+            encoder,
+        })
+    }
+
+    /// Flush one stream of channel `id` into the frame
+    ///
+    /// Failures are recorded in `report` against `id`, tripping the
+    /// stream once it reaches the fault threshold. Returns `false` when
+    /// the frame is full.
+    fn flush_stream(
+        &self,
+        builder: &mut FrameBuilder,
+        id: &str,
+        stream: &mut Channel,
+        sequence: u64,
+        report: &mut FlushReport,
+    ) -> Result<bool> {
+        let samples = stream.pending();
+        let data = match stream.flush() {
+            Ok(data) => data,
+            Err(e) if self.config.fail_fast => return Err(e),
+            Err(e) => {
+                let threshold = self.config.fault_threshold;
+                if threshold > 0 && stream.consecutive_failures() >= threshold {
+                    stream.set_faulted();
+                    report.tripped.push(id.to_string());
+                }
+                report.errors.push((id.to_string(), e));
+                return Ok(true);
+            }
+        };
+        // A partial flush leaves the failing samples buffered
+        let samples = samples - stream.pending();
+
+        Ok(data.is_empty() || self.pack(builder, stream, data, samples, sequence)?)
+    }
+
+    /// Predict what [`aggregate`](Self::aggregate) would produce now
+    ///
+    /// Channels are projected with [`Channel::projected_size`], which
+    /// leaves buffers and contexts untouched and is incremental between
+    /// flushes. The estimate is exact as long as the channels are not
+    /// modified before the flush. Faulted channels are left out, and so
+    /// are open downsampling buckets.
+    pub fn estimate(&self, manager: &mut ChannelManager) -> FlushEstimate {
+        let mut per_channel = Vec::new();
+        let mut encrypted = false;
+        let order = self.flush_order(manager);
+        let mut composite = Vec::new();
+        if self.config.composite {
+            composite = Self::composite_candidates(manager, &order);
+            match self.build_composite(manager, &composite) {
+                Ok(build) if !composite.is_empty() => {
+                    per_channel.push((String::new(), build.data.size(), build.samples()));
+                }
+                _ => composite.clear(),
+            }
+        }
+        for (id, _, _) in order {
+            let Ok(channel) = manager.get_mut(&id) else {
+                continue;
+            };
+            if channel.is_faulted() || composite.contains(&id) {
+                continue;
+            }
+            for (id, bytes, samples, sealed) in Self::projected_entries(channel) {
+                encrypted |= sealed;
+                per_channel.push((id, bytes, samples));
+            }
         }
 
-        Ok(builder.build())
+        let version = if self.config.structural_headers {
+            Frame::VERSION_STRUCTURAL
+        } else if encrypted || !composite.is_empty() {
+            Frame::VERSION_FLAGS
+        } else {
+            Frame::VERSION
+        };
+        let total_bytes = Frame::new().size()
+            + per_channel
+                .iter()
+                .map(|(id, bytes, _)| Frame::entry_size(id.len(), *bytes, version))
+                .sum::<usize>();
+
+        FlushEstimate {
+            total_bytes,
+            per_channel,
+            fits_in_frame: total_bytes <= self.config.max_frame_size,
+        }
+    }
+
+    /// Frame builder for the configured frame format
+    fn builder(&self) -> FrameBuilder {
+        if self.config.structural_headers {
+            FrameBuilder::with_structural_headers(self.config.max_frame_size)
+        } else {
+            FrameBuilder::new(self.config.max_frame_size)
+        }
+    }
+
+    /// Add a channel's flushed data to the frame, sealing it if configured
+    ///
+    /// Returns `false` when the entry does not fit.
+    #[cfg_attr(not(feature = "crypto"), allow(unused_variables, unused_mut))]
+    fn pack(
+        &self,
+        builder: &mut FrameBuilder,
+        channel: &Channel,
+        data: Vec<u8>,
+        samples: usize,
+        sequence: u64,
+    ) -> Result<bool> {
+        let mut entry = ChannelData {
+            id: channel.id.clone(),
+            data,
+            encrypted: false,
+            composite: false,
+            sample_count: self
+                .config
+                .structural_headers
+                .then(|| samples.min(u16::MAX as usize) as u16),
+        };
+
+        #[cfg(feature = "crypto")]
+        if let Some(encryption) = &channel.config.encryption {
+            let sequence = u32::try_from(sequence)
+                .map_err(|_| GatewayError::NonceExhausted(channel.id.clone()))?;
+            entry.data = encryption.seal(&channel.id, sequence, &entry.data)?;
+            entry.encrypted = true;
+        }
+
+        Ok(builder.try_push(entry))
+    }
+
+    /// Take the sequence number for the frame being built
+    fn next_frame_sequence(&mut self) -> u64 {
+        let sequence = self.frame_sequence;
+        self.frame_sequence = self.frame_sequence.saturating_add(1);
+        sequence
+    }
+
+    /// Sequence number the next frame will use
+    pub fn frame_sequence(&self) -> u64 {
+        self.frame_sequence
+    }
+
+    /// Set the sequence number of the next frame
+    ///
+    /// Encrypted channels derive their nonce from it: restore the persisted
+    /// value after a restart, never a lower one.
+    pub fn set_frame_sequence(&mut self, sequence: u64) {
+        self.frame_sequence = sequence;
     }
 
     /// Get the maximum frame size
@@ -99,7 +764,7 @@ mod tests {
     #[test]
     fn test_aggregator_empty() {
         let config = GatewayConfig::default();
-        let aggregator = Aggregator::new(config);
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         let frame = aggregator.aggregate(&mut manager).unwrap();
@@ -109,7 +774,7 @@ mod tests {
     #[test]
     fn test_aggregator_single_channel() {
         let config = GatewayConfig::default();
-        let aggregator = Aggregator::new(config);
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         manager.add("temp", ChannelConfig::default()).unwrap();
@@ -123,7 +788,7 @@ mod tests {
     #[test]
     fn test_aggregator_multiple_channels() {
         let config = GatewayConfig::default();
-        let aggregator = Aggregator::new(config);
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         manager.add("temp", ChannelConfig::default()).unwrap();
@@ -139,7 +804,7 @@ mod tests {
     #[test]
     fn test_aggregator_priority_order() {
         let config = GatewayConfig::default();
-        let aggregator = Aggregator::new(config);
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         // Add channels with different priorities
@@ -170,9 +835,11 @@ mod tests {
 
     #[test]
     fn test_aggregator_max_size() {
-        let mut config = GatewayConfig::default();
-        config.max_frame_size = 50; // Very small frame
-        let aggregator = Aggregator::new(config);
+        let config = GatewayConfig {
+            max_frame_size: 50, // Very small frame
+            ..Default::default()
+        };
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         // Add many channels - not all will fit
@@ -197,7 +864,7 @@ mod tests {
     #[test]
     fn test_aggregator_specific_channels() {
         let config = GatewayConfig::default();
-        let aggregator = Aggregator::new(config);
+        let mut aggregator = Aggregator::new(config);
         let mut manager = ChannelManager::new(10);
 
         manager.add("temp", ChannelConfig::default()).unwrap();
@@ -225,4 +892,37 @@ mod tests {
         // Humid should still have pending data
         assert_eq!(manager.get("humid").unwrap().pending(), 1);
     }
+
+    #[test]
+    fn test_aggregator_structural_headers() {
+        let mut manager = ChannelManager::new(10);
+        manager.add("temp", ChannelConfig::default()).unwrap();
+        manager.add("humid", ChannelConfig::default()).unwrap();
+        for i in 0..3 {
+            manager
+                .get_mut("temp")
+                .unwrap()
+                .push(22.5, 1000 + i)
+                .unwrap();
+        }
+        manager.get_mut("humid").unwrap().push(65.0, 1000).unwrap();
+
+        let mut plain = Aggregator::new(GatewayConfig::default());
+        let mut structural = Aggregator::new(GatewayConfig {
+            structural_headers: true,
+            ..Default::default()
+        });
+
+        let frame = structural.aggregate(&mut manager).unwrap();
+        assert_eq!(frame.version, Frame::VERSION_STRUCTURAL);
+        let summary = Frame::inspect(&frame.to_bytes()).unwrap();
+        assert_eq!(summary.total_samples(), Some(4));
+        assert_eq!(frame.get_channel("temp").unwrap().sample_count, Some(3));
+
+        // Off by default: same entries, no per-entry overhead
+        manager.get_mut("temp").unwrap().push(22.5, 2000).unwrap();
+        let frame = plain.aggregate(&mut manager).unwrap();
+        assert_eq!(frame.version, Frame::VERSION);
+        assert_eq!(frame.get_channel("temp").unwrap().sample_count, None);
+    }
 }
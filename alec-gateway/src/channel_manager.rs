@@ -9,17 +9,97 @@
 //! This module provides the [`ChannelManager`] and [`Channel`] types for
 //! managing multiple sensor channels, each with its own ALEC encoder and context.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 
-use alec::{Classifier, Context, Encoder, RawData};
+use alec::error::EncodeError;
+use alec::{
+    AlecError, ChecksumPolicy, Classification, Classifier, Context, Encoder, EncoderConfig, RawData,
+};
 
-use crate::config::ChannelConfig;
-use crate::error::{GatewayError, Result};
+use crate::config::{
+    Aggregate, BackwardsPolicy, ChannelConfig, Downsample, OverflowPolicy, TimestampPolicy,
+    ValueKind,
+};
+use crate::error::{GatewayError, Result, TimestampRule};
+use crate::text::{TextEncoder, MAX_TEXT_LEN};
 
 /// Unique identifier for a channel
 pub type ChannelId = String;
 
+/// Dry run of a channel's next flush, advanced as samples arrive
+struct FlushProjection {
+    /// Encoder as the next flush will find it, advanced by the dry run
+    encoder: Encoder,
+    /// Context as the next flush will find it, advanced by the dry run
+    context: Context,
+    /// Text entry state, advanced by the dry run
+    text: TextEncoder,
+    /// Buffered samples already encoded
+    samples: usize,
+    /// Encoded size of those samples
+    bytes: usize,
+}
+
+/// Running statistics of an open downsampling bucket
+struct Bucket {
+    /// Bucket start timestamp
+    start: u64,
+    count: u64,
+    min: f64,
+    max: f64,
+    last: f64,
+    /// Welford running mean and sum of squared deviations
+    mean: f64,
+    m2: f64,
+}
+
+impl Bucket {
+    fn new(start: u64, value: f64) -> Self {
+        Self {
+            start,
+            count: 1,
+            min: value,
+            max: value,
+            last: value,
+            mean: value,
+            m2: 0.0,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.last = value;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn value(&self, aggregate: Aggregate) -> f64 {
+        match aggregate {
+            Aggregate::Min => self.min,
+            Aggregate::Max => self.max,
+            Aggregate::Mean => self.mean,
+            Aggregate::Last => self.last,
+            Aggregate::Count => self.count as f64,
+            Aggregate::StdDev => (self.m2 / self.count as f64).sqrt(),
+        }
+    }
+}
+
+/// Downsampling state of a channel with [`ChannelConfig::downsample`]
+struct Downsampler {
+    config: Downsample,
+    /// Bucket receiving samples, if any sample arrived since the last close
+    bucket: Option<Bucket>,
+    /// One channel per configured aggregate, in configuration order
+    derived: Vec<Channel>,
+    /// Recent raw samples, oldest first
+    raw: VecDeque<(f64, u64)>,
+}
+
 /// A single sensor channel with its own encoder and context
 pub struct Channel {
     /// Channel identifier
@@ -34,77 +114,681 @@ pub struct Channel {
     context: Context,
     /// Buffer of pending values: (value, timestamp)
     buffer: Vec<(f64, u64)>,
+    /// Buffer of pending strings of a text channel: (value, timestamp)
+    text_buffer: Vec<(String, u64)>,
+    /// Last timestamp accepted by the timestamp policy
+    last_timestamp: Option<u64>,
+    /// Samples dropped by the overflow policy since creation
+    dropped: u64,
+    /// Dry run backing [`Channel::projected_size`], if still valid
+    projection: Option<FlushProjection>,
+    /// Flushes in a row that failed to encode
+    consecutive_failures: u32,
+    /// Set once the failures reach the gateway's fault threshold
+    faulted: bool,
+    /// Flushes in a row the frame budget left this channel out of
+    skipped_flushes: u32,
+    /// Bucket accumulator and derived channels, when downsampling
+    downsampler: Option<Downsampler>,
 }
 
 impl Channel {
     /// Create a new channel with the given ID and configuration
+    ///
+    /// A downsampled channel gets one derived channel per aggregate, with
+    /// the same configuration except for the preload and timestamp policy,
+    /// which only apply to the raw samples. Text channels cannot be
+    /// downsampled.
     pub fn new(id: impl Into<String>, config: ChannelConfig) -> Result<Self> {
-        let encoder = if config.enable_checksum {
-            Encoder::with_checksum()
-        } else {
-            Encoder::new()
-        };
-
-        let context = if let Some(ref path) = config.preload_path {
-            Context::load_from_file(Path::new(path)).map_err(|e| {
-                GatewayError::InvalidConfig(format!("Failed to load preload '{}': {}", path, e))
-            })?
-        } else {
-            Context::new()
-        };
+        let id = id.into();
+        if config.value_kind == ValueKind::Text && config.downsample.is_some() {
+            return Err(GatewayError::InvalidConfig(format!(
+                "Text channel '{}' cannot be downsampled",
+                id
+            )));
+        }
+        let encoder = Self::new_encoder(&config);
+        let context = Self::load_context(&config)?;
+        let downsampler = config
+            .downsample
+            .as_ref()
+            .map(|downsample| Self::new_downsampler(&id, &config, downsample))
+            .transpose()?;
 
         Ok(Self {
-            id: id.into(),
+            id,
             config,
             encoder,
             classifier: Classifier::default(),
             context,
             buffer: Vec::new(),
+            text_buffer: Vec::new(),
+            last_timestamp: None,
+            dropped: 0,
+            projection: None,
+            consecutive_failures: 0,
+            faulted: false,
+            skipped_flushes: 0,
+            downsampler,
         })
     }
 
+    /// Channel whose contexts the caller restores: the preload is not
+    /// loaded, but stays in the configuration for a later reset
+    pub(crate) fn without_preload(id: impl Into<String>, config: ChannelConfig) -> Result<Self> {
+        let preload_path = config.preload_path.clone();
+        let mut channel = Self::new(
+            id,
+            ChannelConfig {
+                preload_path: None,
+                ..config
+            },
+        )?;
+        channel.config.preload_path = preload_path;
+        Ok(channel)
+    }
+
+    /// Derived channels of a downsampled channel
+    fn new_downsampler(
+        id: &str,
+        config: &ChannelConfig,
+        downsample: &Downsample,
+    ) -> Result<Downsampler> {
+        if downsample.bucket_ms == 0 || downsample.aggregates.is_empty() {
+            return Err(GatewayError::InvalidConfig(format!(
+                "Downsampling of '{}' needs a bucket width and at least one aggregate",
+                id
+            )));
+        }
+
+        let mut derived: Vec<Channel> = Vec::with_capacity(downsample.aggregates.len());
+        for aggregate in &downsample.aggregates {
+            let derived_id = format!("{}.{}", id, aggregate.suffix());
+            if derived.iter().any(|ch| ch.id == derived_id) {
+                return Err(GatewayError::InvalidConfig(format!(
+                    "Duplicate aggregate {:?} on '{}'",
+                    aggregate, id
+                )));
+            }
+            let derived_config = ChannelConfig {
+                preload_path: None,
+                timestamp_policy: TimestampPolicy::default(),
+                downsample: None,
+                ..config.clone()
+            };
+            derived.push(Channel::new(derived_id, derived_config)?);
+        }
+
+        Ok(Downsampler {
+            config: downsample.clone(),
+            bucket: None,
+            derived,
+            raw: VecDeque::new(),
+        })
+    }
+
+    /// Fresh encoder for the configuration
+    fn new_encoder(config: &ChannelConfig) -> Encoder {
+        Encoder::with_config(EncoderConfig {
+            checksum_policy: if config.enable_checksum {
+                ChecksumPolicy::Always
+            } else {
+                ChecksumPolicy::Never
+            },
+            self_describing_interval: config.self_describing_interval,
+            ..EncoderConfig::default()
+        })
+    }
+
+    /// Context from the configured preload, or an empty one
+    fn load_context(config: &ChannelConfig) -> Result<Context> {
+        match config.preload_path {
+            Some(ref path) => Context::load_from_file(Path::new(path)).map_err(|e| {
+                GatewayError::InvalidConfig(format!("Failed to load preload '{}': {}", path, e))
+            }),
+            None => Ok(Context::new()),
+        }
+    }
+
     /// Push a value to the channel buffer
+    ///
+    /// The timestamp is validated against the channel's timestamp policy
+    /// and may be clamped to the last accepted one. A full buffer is
+    /// handled by the channel's [`OverflowPolicy`] (see
+    /// [`ChannelConfig::overflow_policy`]); a dropped sample still counts
+    /// as accepted for timestamp validation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::ValueKindMismatch`] on a text channel.
     pub fn push(&mut self, value: f64, timestamp: u64) -> Result<()> {
-        if self.buffer.len() >= self.config.buffer_size {
+        self.check_value_kind(ValueKind::Numeric)?;
+        let timestamp = self.check_timestamp(timestamp)?;
+        if self.downsampler.is_some() {
+            return self.push_downsampled(value, timestamp);
+        }
+        if self.make_room()? {
+            self.buffer.push((value, timestamp));
+        }
+        self.last_timestamp = Some(timestamp);
+        Ok(())
+    }
+
+    /// Push a string to a text channel's buffer
+    ///
+    /// Timestamps and a full buffer are handled as by
+    /// [`push`](Self::push).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::ValueKindMismatch`] on a numeric channel and
+    /// [`GatewayError::TextTooLong`] for strings over [`MAX_TEXT_LEN`]
+    /// bytes.
+    pub fn push_str(&mut self, value: &str, timestamp: u64) -> Result<()> {
+        self.check_value_kind(ValueKind::Text)?;
+        if value.len() > MAX_TEXT_LEN {
+            return Err(GatewayError::TextTooLong {
+                channel: self.id.clone(),
+                len: value.len(),
+                max: MAX_TEXT_LEN,
+            });
+        }
+        let timestamp = self.check_timestamp(timestamp)?;
+        if self.make_room()? {
+            self.text_buffer.push((value.to_string(), timestamp));
+        }
+        self.last_timestamp = Some(timestamp);
+        Ok(())
+    }
+
+    /// Reject values of another kind than the channel's
+    pub(crate) fn check_value_kind(&self, kind: ValueKind) -> Result<()> {
+        if self.config.value_kind != kind {
+            return Err(GatewayError::ValueKindMismatch {
+                channel: self.id.clone(),
+                expected: self.config.value_kind,
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply the overflow policy before buffering a sample
+    ///
+    /// Returns whether the sample is to be buffered.
+    fn make_room(&mut self) -> Result<bool> {
+        if self.buffered_len() < self.config.buffer_size {
+            return Ok(true);
+        }
+        match self.config.overflow_policy(OverflowPolicy::default()) {
+            OverflowPolicy::Error => Err(GatewayError::BufferFull(self.id.clone())),
+            OverflowPolicy::DropNewest => {
+                self.dropped += 1;
+                Ok(false)
+            }
+            OverflowPolicy::DropOldest => {
+                self.dropped += 1;
+                if self.buffered_len() == 0 {
+                    // Zero-sized buffer: nothing to evict
+                    return Ok(false);
+                }
+                if self.text_buffer.is_empty() {
+                    self.buffer.remove(0);
+                } else {
+                    self.text_buffer.remove(0);
+                }
+                // Later samples were encoded after the evicted one
+                self.projection = None;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Samples in the channel's own buffer
+    fn buffered_len(&self) -> usize {
+        self.buffer.len() + self.text_buffer.len()
+    }
+
+    /// Fold a raw sample into the open bucket, closing it first if the
+    /// sample belongs to a later one
+    fn push_downsampled(&mut self, value: f64, timestamp: u64) -> Result<()> {
+        let Some(downsampler) = self.downsampler.as_mut() else {
+            return Ok(());
+        };
+        let start = timestamp - timestamp % downsampler.config.bucket_ms;
+
+        match &mut downsampler.bucket {
+            Some(bucket) if start <= bucket.start => bucket.add(value),
+            _ => {
+                self.close_bucket()?;
+                if let Some(downsampler) = self.downsampler.as_mut() {
+                    downsampler.bucket = Some(Bucket::new(start, value));
+                }
+            }
+        }
+
+        if let Some(downsampler) = self.downsampler.as_mut() {
+            let window = downsampler.config.raw_window_ms;
+            if window > 0 {
+                downsampler.raw.push_back((value, timestamp));
+                while downsampler
+                    .raw
+                    .front()
+                    .is_some_and(|&(_, ts)| ts.saturating_add(window) < timestamp)
+                {
+                    downsampler.raw.pop_front();
+                }
+            }
+        }
+        self.last_timestamp = Some(timestamp);
+        Ok(())
+    }
+
+    /// Close the open downsampling bucket, pushing its aggregates to the
+    /// derived channels
+    ///
+    /// Does nothing on channels without downsampling or without an open
+    /// bucket. A flush calls this unless
+    /// [`Downsample::carry_partial`] is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::BufferFull`] if a derived channel is full
+    /// and rejects samples; the bucket stays open.
+    pub fn close_bucket(&mut self) -> Result<()> {
+        let Some(downsampler) = self.downsampler.as_mut() else {
+            return Ok(());
+        };
+        if downsampler.bucket.is_none() {
+            return Ok(());
+        }
+        if downsampler.derived.iter().any(Channel::rejects_push) {
             return Err(GatewayError::BufferFull(self.id.clone()));
         }
-        self.buffer.push((value, timestamp));
+
+        if let Some(bucket) = downsampler.bucket.take() {
+            for (aggregate, derived) in downsampler
+                .config
+                .aggregates
+                .iter()
+                .zip(&mut downsampler.derived)
+            {
+                derived.push(bucket.value(*aggregate), bucket.start)?;
+            }
+        }
         Ok(())
     }
 
+    /// Whether a push would fail with [`GatewayError::BufferFull`]
+    fn rejects_push(&self) -> bool {
+        self.buffered_len() >= self.config.buffer_size
+            && self.config.overflow_policy(OverflowPolicy::default()) == OverflowPolicy::Error
+    }
+
+    /// Raw samples kept by a downsampled channel, oldest first
+    ///
+    /// Covers [`Downsample::raw_window_ms`] before the newest sample; empty
+    /// for channels without downsampling.
+    pub fn raw_window(&self) -> Vec<(f64, u64)> {
+        self.downsampler
+            .as_ref()
+            .map(|d| d.raw.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Channels whose buffers a flush encodes: the derived channels of a
+    /// downsampled channel, the channel itself otherwise
+    pub fn streams(&self) -> Vec<&Channel> {
+        match &self.downsampler {
+            Some(downsampler) => downsampler.derived.iter().collect(),
+            None => vec![self],
+        }
+    }
+
+    /// Mutable [`streams`](Self::streams)
+    pub(crate) fn streams_mut(&mut self) -> Vec<&mut Channel> {
+        if self.downsampler.is_none() {
+            return vec![self];
+        }
+        self.downsampler
+            .as_mut()
+            .map(|d| d.derived.iter_mut().collect())
+            .unwrap_or_default()
+    }
+
+    /// Samples dropped by the overflow policy since the channel was created
+    ///
+    /// Includes samples dropped by derived channels.
+    pub fn dropped(&self) -> u64 {
+        let derived: u64 = self
+            .downsampler
+            .as_ref()
+            .map(|d| d.derived.iter().map(Channel::dropped).sum())
+            .unwrap_or(0);
+        self.dropped + derived
+    }
+
+    /// Apply the timestamp policy without pushing
+    ///
+    /// Returns the timestamp the sample would be stored with.
+    pub fn check_timestamp(&self, timestamp: u64) -> Result<u64> {
+        let policy = &self.config.timestamp_policy;
+        let reject = |rule| GatewayError::InvalidTimestamp {
+            channel: self.id.clone(),
+            timestamp,
+            rule,
+        };
+
+        if let Some(min) = policy.reject_before {
+            if timestamp < min {
+                return Err(reject(TimestampRule::TooEarly { min }));
+            }
+        }
+
+        let Some(last) = self.last_timestamp else {
+            return Ok(timestamp);
+        };
+
+        if let Some(max_skew_ms) = policy.reject_after_skew_ms {
+            if timestamp > last.saturating_add(max_skew_ms) {
+                return Err(reject(TimestampRule::TooFarAhead { last, max_skew_ms }));
+            }
+        }
+
+        if timestamp < last {
+            return match policy.on_backwards {
+                BackwardsPolicy::Reject => Err(reject(TimestampRule::Backwards { last })),
+                BackwardsPolicy::ClampToLast => Ok(last),
+                BackwardsPolicy::Accept => Ok(timestamp),
+            };
+        }
+
+        Ok(timestamp)
+    }
+
+    /// Last timestamp accepted on this channel
+    pub fn last_timestamp(&self) -> Option<u64> {
+        self.last_timestamp
+    }
+
+    /// Buffered (value, timestamp) pairs, oldest first
+    pub fn buffered(&self) -> &[(f64, u64)] {
+        &self.buffer
+    }
+
+    /// Buffered (value, timestamp) pairs of a text channel, oldest first
+    pub fn buffered_text(&self) -> &[(String, u64)] {
+        &self.text_buffer
+    }
+
     /// Encode all buffered values and clear buffer
     ///
-    /// Returns the encoded bytes for all values in the buffer.
+    /// Returns the encoded bytes for all values in the buffer. Encoding
+    /// stops at the first value that cannot be encoded (e.g. the context
+    /// predicts a non-finite value): the values before it are returned and
+    /// drained, the rest stay buffered, and the next flush returns the
+    /// error. Every error counts towards
+    /// [`consecutive_failures`](Self::consecutive_failures); a successful
+    /// flush resets the count.
+    ///
+    /// Text channels encode their strings against the context dictionary
+    /// (see [`ValueKind::Text`]), which cannot fail.
     pub fn flush(&mut self) -> Result<Vec<u8>> {
+        if !self.text_buffer.is_empty() {
+            self.projection = None;
+            let mut encoder = TextEncoder::default();
+            let mut encoded = Vec::new();
+            for (value, timestamp) in self.text_buffer.drain(..) {
+                encoder.encode(&mut self.context, &value, timestamp, &mut encoded);
+            }
+            self.consecutive_failures = 0;
+            return Ok(encoded);
+        }
         if self.buffer.is_empty() {
             return Ok(Vec::new());
         }
 
+        self.projection = None;
         let mut encoded = Vec::new();
+        let mut flushed = 0;
 
-        for (value, timestamp) in self.buffer.drain(..) {
+        for &(value, timestamp) in &self.buffer {
             let data = RawData::new(value, timestamp);
-            let classification = self.classifier.classify(&data, &self.context);
-            let bytes = self
-                .encoder
-                .encode_to_bytes(&data, &classification, &self.context);
-            encoded.extend_from_slice(&bytes);
+            let classification = match Self::encode_sample(
+                &mut self.encoder,
+                &self.classifier,
+                &self.context,
+                &data,
+            ) {
+                Ok((bytes, classification)) => {
+                    encoded.extend_from_slice(&bytes);
+                    classification
+                }
+                Err(e) if flushed == 0 => {
+                    self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                    return Err(e);
+                }
+                Err(_) => break,
+            };
 
             // Update context after encoding
-            self.context.observe(&data);
+            self.context.observe_classified(&data, &classification);
+            flushed += 1;
         }
 
+        self.buffer.drain(..flushed);
+        self.consecutive_failures = 0;
         Ok(encoded)
     }
 
+    /// Encode one value, refusing to predict from a corrupted context
+    fn encode_sample(
+        encoder: &mut Encoder,
+        classifier: &Classifier,
+        context: &Context,
+        data: &RawData,
+    ) -> Result<(Vec<u8>, Classification)> {
+        if let Some(prediction) = context.predict(data.source_id) {
+            if !prediction.value.is_finite() {
+                return Err(AlecError::from(EncodeError::InvalidValue(format!(
+                    "context predicts {} for source {}",
+                    prediction.value, data.source_id
+                )))
+                .into());
+            }
+        }
+        let classification = classifier.classify(data, context);
+        let bytes = encoder.encode_to_bytes(data, &classification, context);
+        Ok((bytes, classification))
+    }
+
+    /// Whether the aggregator may carry this channel's buffer in a
+    /// composite entry ([`GatewayConfig::composite`])
+    ///
+    /// Only plain numeric channels qualify: not text, downsampled,
+    /// encrypted or faulted, and with a context whose prediction is
+    /// finite.
+    ///
+    /// [`GatewayConfig::composite`]: crate::GatewayConfig::composite
+    pub(crate) fn composite_eligible(&self) -> bool {
+        #[cfg(feature = "crypto")]
+        if self.config.encryption.is_some() {
+            return false;
+        }
+        self.config.value_kind == ValueKind::Numeric
+            && self.downsampler.is_none()
+            && !self.faulted
+            && !self.buffer.is_empty()
+            && self
+                .context
+                .predict(0)
+                .map_or(true, |prediction| prediction.value.is_finite())
+    }
+
+    /// Classifier used when encoding the channel's samples
+    pub(crate) fn classifier(&self) -> &Classifier {
+        &self.classifier
+    }
+
+    /// Record that the first `samples` buffered values went out in a
+    /// composite entry, encoded against `context`, which the channel now
+    /// continues from
+    pub(crate) fn commit_composite(&mut self, samples: usize, context: Context) {
+        self.buffer.drain(..samples);
+        self.context = context;
+        self.projection = None;
+        self.consecutive_failures = 0;
+    }
+
+    /// Size in bytes [`flush`](Self::flush) would return right now
+    ///
+    /// The first call after a flush encodes the buffer against a copy of
+    /// the encoder and context; later calls only encode the samples pushed
+    /// since, so polling is cheap. The channel's own encoder, context and
+    /// buffer are left untouched and the result matches the next flush
+    /// exactly. Evicting a sample ([`OverflowPolicy::DropOldest`]),
+    /// clearing the buffer, resetting the sequence or borrowing the
+    /// context mutably discards the copy, and the next call starts over.
+    /// Like the flush, the projection stops at the first value that fails
+    /// to encode.
+    pub fn projected_size(&mut self) -> usize {
+        if self.buffered_len() == 0 {
+            return 0;
+        }
+
+        let projection = self.projection.get_or_insert_with(|| FlushProjection {
+            encoder: self.encoder.clone(),
+            context: self.context.clone(),
+            text: TextEncoder::default(),
+            samples: 0,
+            bytes: 0,
+        });
+        if !self.text_buffer.is_empty() {
+            let mut encoded = Vec::new();
+            for (value, timestamp) in &self.text_buffer[projection.samples..] {
+                projection
+                    .text
+                    .encode(&mut projection.context, value, *timestamp, &mut encoded);
+            }
+            projection.samples = self.text_buffer.len();
+            projection.bytes += encoded.len();
+            return projection.bytes;
+        }
+        for &(value, timestamp) in &self.buffer[projection.samples..] {
+            let data = RawData::new(value, timestamp);
+            let Ok((bytes, classification)) = Self::encode_sample(
+                &mut projection.encoder,
+                &self.classifier,
+                &projection.context,
+                &data,
+            ) else {
+                // Retried on the next call, like the flush would
+                return projection.bytes;
+            };
+            projection.bytes += bytes.len();
+            projection
+                .context
+                .observe_classified(&data, &classification);
+            projection.samples += 1;
+        }
+        projection.bytes
+    }
+
+    /// Flushes in a row that returned an error
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Whether the channel is faulted and skipped by flushes
+    ///
+    /// A downsampled channel is faulted when any derived channel is. See
+    /// [`GatewayConfig::fault_threshold`](crate::GatewayConfig::fault_threshold).
+    pub fn is_faulted(&self) -> bool {
+        self.faulted
+            || self
+                .downsampler
+                .as_ref()
+                .is_some_and(|d| d.derived.iter().any(Channel::is_faulted))
+    }
+
+    /// Mark the channel faulted
+    pub(crate) fn set_faulted(&mut self) {
+        self.faulted = true;
+    }
+
+    /// Flushes in a row the frame budget left this channel out of, with
+    /// samples pending
+    ///
+    /// Back to 0 once the channel makes a frame or has nothing pending.
+    /// See [`StarvationConfig`](crate::StarvationConfig).
+    pub fn skipped_flushes(&self) -> u32 {
+        self.skipped_flushes
+    }
+
+    /// Record whether the last flush left the channel out
+    pub(crate) fn set_skipped(&mut self, skipped: bool) {
+        self.skipped_flushes = if skipped {
+            self.skipped_flushes.saturating_add(1)
+        } else {
+            0
+        };
+    }
+
+    /// Fill ratio of the fullest buffer among the channel's streams
+    pub fn fill_ratio(&self) -> f64 {
+        self.streams()
+            .iter()
+            .map(|stream| match stream.config.buffer_size {
+                0 => 1.0,
+                size => stream.buffered_len() as f64 / size as f64,
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Clear a fault with a fresh encoder and context
+    ///
+    /// The context is reloaded from the configured preload when
+    /// `reload_preload` is set, and starts empty otherwise. Buffered
+    /// samples and the timestamp state are kept. Derived channels of a
+    /// downsampled channel are reset too, with empty contexts. The
+    /// receiving decoder must reset its contexts the same way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the preload cannot be loaded; the channel is
+    /// left unchanged.
+    pub fn reset(&mut self, reload_preload: bool) -> Result<()> {
+        self.context = if reload_preload {
+            Self::load_context(&self.config)?
+        } else {
+            Context::new()
+        };
+        self.encoder = Self::new_encoder(&self.config);
+        self.classifier = Classifier::default();
+        self.projection = None;
+        self.consecutive_failures = 0;
+        self.faulted = false;
+        if let Some(downsampler) = self.downsampler.as_mut() {
+            for derived in &mut downsampler.derived {
+                derived.reset(false)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Number of pending values in the buffer
+    ///
+    /// For a downsampled channel, the aggregates pending in its derived
+    /// channels; the open bucket is not counted.
     pub fn pending(&self) -> usize {
-        self.buffer.len()
+        match &self.downsampler {
+            Some(downsampler) => downsampler.derived.iter().map(Channel::pending).sum(),
+            None => self.buffered_len(),
+        }
     }
 
     /// Check if the buffer is empty
     pub fn is_empty(&self) -> bool {
-        self.buffer.is_empty()
+        self.pending() == 0
     }
 
     /// Get the channel's context version
@@ -119,17 +803,55 @@ impl Channel {
 
     /// Get a mutable reference to the channel's context
     pub fn context_mut(&mut self) -> &mut Context {
+        self.projection = None;
         &mut self.context
     }
 
     /// Reset the channel's encoder sequence
     pub fn reset_sequence(&mut self) {
+        self.projection = None;
         self.encoder.reset_sequence();
     }
 
+    /// Sequence number of the channel's next encoded message
+    pub(crate) fn encoder_sequence(&self) -> u16 {
+        self.encoder.sequence()
+    }
+
+    /// Restore the last accepted timestamp of a saved channel
+    pub(crate) fn restore_last_timestamp(&mut self, timestamp: Option<u64>) {
+        self.last_timestamp = timestamp;
+    }
+
+    /// Restore the encoding state and buffers of a saved stream
+    pub(crate) fn restore_state(
+        &mut self,
+        context: Context,
+        sequence: u16,
+        buffer: Vec<(f64, u64)>,
+        text_buffer: Vec<(String, u64)>,
+    ) {
+        self.projection = None;
+        self.context = context;
+        self.encoder.restore_sequence(sequence);
+        self.buffer = buffer;
+        self.text_buffer = text_buffer;
+    }
+
     /// Clear the buffer without encoding
+    ///
+    /// Also discards the open bucket and the derived channels' buffers of
+    /// a downsampled channel.
     pub fn clear_buffer(&mut self) {
+        self.projection = None;
         self.buffer.clear();
+        self.text_buffer.clear();
+        if let Some(downsampler) = self.downsampler.as_mut() {
+            downsampler.bucket = None;
+            for derived in &mut downsampler.derived {
+                derived.clear_buffer();
+            }
+        }
     }
 }
 
@@ -138,7 +860,8 @@ impl std::fmt::Debug for Channel {
         f.debug_struct("Channel")
             .field("id", &self.id)
             .field("config", &self.config)
-            .field("pending", &self.buffer.len())
+            .field("pending", &self.buffered_len())
+            .field("faulted", &self.faulted)
             .finish()
     }
 }
@@ -179,6 +902,20 @@ impl ChannelManager {
         Ok(())
     }
 
+    /// Add a channel built elsewhere, e.g. restored from a snapshot
+    pub(crate) fn insert(&mut self, channel: Channel) -> Result<()> {
+        if self.channels.contains_key(&channel.id) {
+            return Err(GatewayError::ChannelAlreadyExists(channel.id));
+        }
+        if self.channels.len() >= self.max_channels {
+            return Err(GatewayError::MaxChannelsReached {
+                max: self.max_channels,
+            });
+        }
+        self.channels.insert(channel.id.clone(), channel);
+        Ok(())
+    }
+
     /// Remove a channel
     pub fn remove(&mut self, id: &str) -> Result<Channel> {
         self.channels
@@ -297,6 +1034,29 @@ mod tests {
         assert!(encoded.is_empty());
     }
 
+    #[test]
+    fn test_channel_flush_corrupt_context() {
+        let mut channel = Channel::new("test", ChannelConfig::default()).unwrap();
+        channel.push(22.5, 1000).unwrap();
+        channel.flush().unwrap();
+
+        // Corrupt the predictor with samples buffered
+        channel.push(22.6, 2000).unwrap();
+        channel.push(22.7, 3000).unwrap();
+        channel
+            .context_mut()
+            .observe(&RawData::new(f64::INFINITY, 1500));
+        channel.push(22.8, 4000).unwrap();
+        assert!(channel.flush().is_err());
+        assert_eq!(channel.pending(), 3);
+        assert_eq!(channel.consecutive_failures(), 1);
+
+        channel.reset(false).unwrap();
+        assert!(!channel.flush().unwrap().is_empty());
+        assert_eq!(channel.pending(), 0);
+        assert_eq!(channel.consecutive_failures(), 0);
+    }
+
     #[test]
     fn test_channel_manager_add() {
         let mut manager = ChannelManager::new(10);
@@ -6,6 +6,13 @@
 
 //! Configuration types for ALEC Gateway
 
+use alec::sync::SyncConfig;
+
+use crate::error::{GatewayError, Result};
+
+#[cfg(feature = "crypto")]
+use crate::crypto::EncryptionConfig;
+
 /// Gateway-level configuration
 #[derive(Debug, Clone)]
 pub struct GatewayConfig {
@@ -17,6 +24,76 @@ pub struct GatewayConfig {
 
     /// Enable checksums on all channels by default
     pub enable_checksums: bool,
+
+    /// Overflow policy for channels that do not set one
+    ///
+    /// Critical channels (priority [`ChannelConfig::CRITICAL_PRIORITY`])
+    /// without an explicit policy always use [`OverflowPolicy::Error`].
+    pub overflow: OverflowPolicy,
+
+    /// Emit version 3 frames carrying each entry's sample count
+    ///
+    /// Costs 3 bytes per entry (flags and sample count) and lets the
+    /// receiver summarize frames with [`Frame::inspect`] without any
+    /// channel context. Off by default, keeping today's frame sizes.
+    ///
+    /// [`Frame::inspect`]: crate::Frame::inspect
+    pub structural_headers: bool,
+
+    /// Pack numeric channels into composite ALEC messages sharing one
+    /// header per timestamp, instead of one standalone message per sample
+    ///
+    /// Cuts the per-channel header and checksum out of flushes of many
+    /// channels with few samples each. Channels that are encrypted,
+    /// downsampled or text keep their own entries. Receivers decode the
+    /// composite entry with [`FrameDecoder::decode_composite`]. Off by
+    /// default.
+    ///
+    /// [`FrameDecoder::decode_composite`]: crate::FrameDecoder::decode_composite
+    pub composite: bool,
+
+    /// Return the first channel encode error from a flush instead of
+    /// reporting it and carrying on with the other channels
+    ///
+    /// Off by default; see [`Gateway::flush_with_report`].
+    ///
+    /// [`Gateway::flush_with_report`]: crate::Gateway::flush_with_report
+    pub fail_fast: bool,
+
+    /// Failed flushes in a row after which a channel is faulted (default: 3)
+    ///
+    /// Faulted channels are skipped until
+    /// [`Gateway::reset_channel`](crate::Gateway::reset_channel). 0 never
+    /// faults a channel.
+    pub fault_threshold: u32,
+
+    /// Context synchronization of every channel with the receiver
+    ///
+    /// Each channel gets its own synchronizer; `announce_interval` counts
+    /// the frame entries the channel flushed. See
+    /// [`Gateway::sync_announces`](crate::Gateway::sync_announces).
+    pub sync: SyncConfig,
+
+    /// Maximum size of the sync frame built from one
+    /// [`Gateway::sync_announces`](crate::Gateway::sync_announces) call,
+    /// in bytes (0 = no cap)
+    ///
+    /// Lower-priority channels that do not fit are deferred to the next
+    /// call.
+    pub max_sync_bytes: usize,
+
+    /// Transport the frames are sent over, if known
+    ///
+    /// Set by [`GatewayConfig::for_transport`]; [`GatewayConfig::validate`]
+    /// then checks the frame and sync sizes against what the transport
+    /// carries in one transmission.
+    pub transport: Option<TransportPreset>,
+
+    /// Priority boost for channels left out of flushes by the frame budget
+    ///
+    /// `None` (default) flushes in configured priority order only. See
+    /// [`StarvationConfig`].
+    pub starvation: Option<StarvationConfig>,
 }
 
 impl Default for GatewayConfig {
@@ -25,6 +102,15 @@ impl Default for GatewayConfig {
             max_frame_size: 242, // LoRaWAN DR0
             max_channels: 32,
             enable_checksums: true,
+            overflow: OverflowPolicy::default(),
+            structural_headers: false,
+            composite: false,
+            fail_fast: false,
+            fault_threshold: 3,
+            sync: SyncConfig::default(),
+            max_sync_bytes: 0,
+            transport: None,
+            starvation: None,
         }
     }
 }
@@ -54,14 +140,246 @@ impl GatewayConfig {
             ..Default::default()
         }
     }
+
+    /// Create a configuration sized for a transport
+    ///
+    /// Frames and sync frames fit one transmission of the transport.
+    /// Constrained radio links drop the oldest buffered samples when a
+    /// channel overflows, keeping the freshest readings; MQTT keeps the
+    /// [`OverflowPolicy::Error`] default. Transport parameters the preset
+    /// does not support leave `max_frame_size` at 0, which
+    /// [`GatewayConfig::validate`] rejects.
+    pub fn for_transport(transport: TransportPreset) -> Self {
+        let max_frame_size = transport.max_payload().unwrap_or(0);
+        let overflow = match transport {
+            TransportPreset::Mqtt { .. } => OverflowPolicy::Error,
+            _ => OverflowPolicy::DropOldest,
+        };
+        Self {
+            max_frame_size,
+            overflow,
+            max_sync_bytes: max_frame_size,
+            transport: Some(transport),
+            ..Default::default()
+        }
+    }
+
+    /// Check the configuration for contradictory settings
+    ///
+    /// Rejects a zero frame size or channel cap, and with a
+    /// [`transport`](Self::transport) set, unsupported transport parameters
+    /// and frame or sync sizes the transport cannot carry.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_frame_size == 0 {
+            return Err(GatewayError::InvalidConfig(
+                "max_frame_size must be at least 1 byte".to_string(),
+            ));
+        }
+        if self.max_channels == 0 {
+            return Err(GatewayError::InvalidConfig(
+                "max_channels must be at least 1".to_string(),
+            ));
+        }
+
+        if let Some(starvation) = &self.starvation {
+            if starvation.flushes_per_level == 0 {
+                return Err(GatewayError::InvalidConfig(
+                    "starvation.flushes_per_level must be at least 1".to_string(),
+                ));
+            }
+            if !(starvation.near_full > 0.0 && starvation.near_full <= 1.0) {
+                return Err(GatewayError::InvalidConfig(format!(
+                    "starvation.near_full {} is not a fill ratio in (0, 1]",
+                    starvation.near_full
+                )));
+            }
+        }
+
+        let Some(transport) = self.transport else {
+            return Ok(());
+        };
+        let max_payload = transport.max_payload().ok_or_else(|| {
+            GatewayError::InvalidConfig(format!("Unsupported transport {:?}", transport))
+        })?;
+        if self.max_frame_size > max_payload {
+            return Err(GatewayError::InvalidConfig(format!(
+                "max_frame_size {} exceeds the {} bytes {:?} carries",
+                self.max_frame_size, max_payload, transport
+            )));
+        }
+        if self.max_sync_bytes > max_payload {
+            return Err(GatewayError::InvalidConfig(format!(
+                "max_sync_bytes {} exceeds the {} bytes {:?} carries",
+                self.max_sync_bytes, max_payload, transport
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Starvation protection of low-priority channels
+///
+/// With a tight frame budget, a channel behind busier higher-priority
+/// channels can be left out of every flush. Each flush the budget leaves a
+/// channel with pending samples out of counts as skipped, and every
+/// `flushes_per_level` skipped flushes raise its effective priority by
+/// `level_step`, up to `max_boost`. A skipped channel whose buffer is at
+/// least `near_full` full gains one more level, before overflow drops its
+/// samples. The boost ends as soon as the channel makes a frame. Among
+/// channels of equal effective priority, the longest skipped goes first,
+/// then the one with the fullest buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarvationConfig {
+    /// Skipped flushes per boost level (default: 4)
+    pub flushes_per_level: u32,
+
+    /// Priority units gained per level (default: 32)
+    pub level_step: u8,
+
+    /// Highest effective priority a boost reaches (default: 1)
+    ///
+    /// Keeping it above [`ChannelConfig::CRITICAL_PRIORITY`] leaves
+    /// critical channels ahead of any boosted one. Channels configured at
+    /// or above it are never boosted.
+    pub max_boost: u8,
+
+    /// Buffer fill ratio from which a skipped channel gains an extra level
+    /// (default: 0.8)
+    pub near_full: f64,
+}
+
+impl Default for StarvationConfig {
+    fn default() -> Self {
+        Self {
+            flushes_per_level: 4,
+            level_step: 32,
+            max_boost: 1,
+            near_full: 0.8,
+        }
+    }
+}
+
+impl StarvationConfig {
+    /// Effective priority of a channel configured at `priority`, after
+    /// `skipped` skipped flushes with its buffer `fill` full (0.0 to 1.0)
+    pub fn effective_priority(&self, priority: u8, skipped: u32, fill: f64) -> u8 {
+        if priority <= self.max_boost || skipped == 0 {
+            return priority;
+        }
+        let mut levels = skipped / self.flushes_per_level.max(1);
+        if fill >= self.near_full {
+            levels += 1;
+        }
+        let boost = levels.saturating_mul(self.level_step as u32);
+        let boosted = (priority as u32).saturating_sub(boost) as u8;
+        boosted.max(self.max_boost)
+    }
+}
+
+/// Transport a gateway sends its frames over
+///
+/// Each preset knows the largest application payload the transport
+/// carries in one transmission, see [`TransportPreset::max_payload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportPreset {
+    /// LoRaWAN uplink at data rate DR0–DR5
+    Lorawan {
+        /// Regional parameters
+        region: LoRaRegion,
+        /// Uplink data rate
+        data_rate: u8,
+    },
+    /// NB-IoT over UDP
+    NbIot {
+        /// IP MTU of the PDN connection (1280 is a common, safe value)
+        mtu: usize,
+    },
+    /// BLE GATT notifications
+    Ble {
+        /// Negotiated ATT MTU (23 without an exchange, the BLE 4.0/4.1
+        /// default; up to 517 with BLE 4.2 and later)
+        att_mtu: usize,
+    },
+    /// MQTT publish
+    Mqtt {
+        /// Maximum packet size accepted by the broker
+        max_packet: usize,
+    },
+}
+
+impl TransportPreset {
+    /// IPv6 (40) and UDP (8) headers, covering the smaller IPv4 case
+    pub const UDP_OVERHEAD: usize = 48;
+
+    /// ATT opcode and attribute handle of a notification
+    pub const ATT_OVERHEAD: usize = 3;
+
+    /// Smallest ATT MTU allowed by the Bluetooth specification
+    pub const ATT_MIN_MTU: usize = 23;
+
+    /// Longest attribute value allowed by the Bluetooth specification
+    pub const ATT_MAX_VALUE: usize = 512;
+
+    /// Room left for the MQTT fixed header, topic name and properties
+    pub const MQTT_OVERHEAD: usize = 128;
+
+    /// Largest frame carried in one transmission, `None` if the transport
+    /// parameters are invalid or unsupported
+    pub fn max_payload(&self) -> Option<usize> {
+        let payload = match *self {
+            TransportPreset::Lorawan { region, data_rate } => region.max_payload(data_rate)?,
+            TransportPreset::NbIot { mtu } => mtu.checked_sub(Self::UDP_OVERHEAD)?,
+            TransportPreset::Ble { att_mtu } if att_mtu < Self::ATT_MIN_MTU => return None,
+            TransportPreset::Ble { att_mtu } => {
+                (att_mtu - Self::ATT_OVERHEAD).min(Self::ATT_MAX_VALUE)
+            }
+            TransportPreset::Mqtt { max_packet } => max_packet.checked_sub(Self::MQTT_OVERHEAD)?,
+        };
+        (payload > 0).then_some(payload)
+    }
+}
+
+/// LoRaWAN regional parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoRaRegion {
+    /// EU863-870
+    Eu868,
+    /// US902-928
+    Us915,
+    /// AS923, without uplink dwell time limit
+    As923,
+}
+
+impl LoRaRegion {
+    /// Maximum application payload at an uplink data rate, without MAC
+    /// commands in FOpts (`None`: no such uplink data rate in DR0–DR5)
+    pub fn max_payload(&self, data_rate: u8) -> Option<usize> {
+        match (self, data_rate) {
+            (LoRaRegion::Eu868, 0..=2) => Some(51),
+            (LoRaRegion::Eu868, 3) => Some(115),
+            (LoRaRegion::Eu868, 4 | 5) => Some(222),
+            (LoRaRegion::Us915, 0) => Some(11),
+            (LoRaRegion::Us915, 1) => Some(53),
+            (LoRaRegion::Us915, 2) => Some(125),
+            (LoRaRegion::Us915, 3 | 4) => Some(242),
+            (LoRaRegion::As923, 0 | 1) => Some(51),
+            (LoRaRegion::As923, 2 | 3) => Some(115),
+            (LoRaRegion::As923, 4 | 5) => Some(242),
+            _ => None,
+        }
+    }
 }
 
 /// Per-channel configuration
 #[derive(Debug, Clone)]
 pub struct ChannelConfig {
-    /// Buffer size for pending values
+    /// Maximum number of buffered samples awaiting a flush
     pub buffer_size: usize,
 
+    /// What to do when a sample arrives at a full buffer (`None`: the
+    /// gateway default, or [`OverflowPolicy::Error`] for critical channels)
+    pub overflow: Option<OverflowPolicy>,
+
     /// Preload file path (optional)
     pub preload_path: Option<String>,
 
@@ -70,20 +388,64 @@ pub struct ChannelConfig {
 
     /// Enable checksum for this channel
     pub enable_checksum: bool,
+
+    /// Validation applied to sample timestamps on push
+    pub timestamp_policy: TimestampPolicy,
+
+    /// Transmit per-bucket statistics instead of raw samples
+    pub downsample: Option<Downsample>,
+
+    /// Kind of values the channel accepts
+    pub value_kind: ValueKind,
+
+    /// Channel group: the aggregator puts the pending data of all of a
+    /// group's channels in the same frame, or defers all of it
+    pub group: Option<String>,
+
+    /// Send a self-describing value on the first message of the channel
+    /// and then every this many messages (0: never)
+    ///
+    /// Lets a receiver that lost frames resynchronize without a context
+    /// sync. See [`alec::EncoderConfig::self_describing_interval`].
+    pub self_describing_interval: u16,
+
+    /// Seal this channel's encoded bytes with a pre-shared key
+    #[cfg(feature = "crypto")]
+    pub encryption: Option<EncryptionConfig>,
 }
 
 impl Default for ChannelConfig {
     fn default() -> Self {
         Self {
             buffer_size: 64,
+            overflow: None,
             preload_path: None,
             priority: 128,
             enable_checksum: true,
+            timestamp_policy: TimestampPolicy::default(),
+            downsample: None,
+            value_kind: ValueKind::Numeric,
+            group: None,
+            self_describing_interval: 0,
+            #[cfg(feature = "crypto")]
+            encryption: None,
         }
     }
 }
 
 impl ChannelConfig {
+    /// Priority of critical channels, which never drop samples by default
+    pub const CRITICAL_PRIORITY: u8 = 0;
+
+    /// Overflow policy in effect, given the gateway-level default
+    pub fn overflow_policy(&self, default: OverflowPolicy) -> OverflowPolicy {
+        match self.overflow {
+            Some(policy) => policy,
+            None if self.priority == Self::CRITICAL_PRIORITY => OverflowPolicy::Error,
+            None => default,
+        }
+    }
+
     /// Create a configuration with specific priority
     pub fn with_priority(priority: u8) -> Self {
         Self {
@@ -107,6 +469,212 @@ impl ChannelConfig {
             ..Default::default()
         }
     }
+
+    /// Create a configuration with a buffer bound and overflow policy
+    pub fn with_overflow(buffer_size: usize, overflow: OverflowPolicy) -> Self {
+        Self {
+            buffer_size,
+            overflow: Some(overflow),
+            ..Default::default()
+        }
+    }
+
+    /// Create a configuration with a timestamp policy
+    pub fn with_timestamp_policy(timestamp_policy: TimestampPolicy) -> Self {
+        Self {
+            timestamp_policy,
+            ..Default::default()
+        }
+    }
+
+    /// Create a configuration downsampling the channel
+    pub fn with_downsample(downsample: Downsample) -> Self {
+        Self {
+            downsample: Some(downsample),
+            ..Default::default()
+        }
+    }
+
+    /// Create a configuration for values of `value_kind`
+    pub fn with_value_kind(value_kind: ValueKind) -> Self {
+        Self {
+            value_kind,
+            ..Default::default()
+        }
+    }
+
+    /// Create a configuration in channel group `group`
+    pub fn with_group(group: impl Into<String>) -> Self {
+        Self {
+            group: Some(group.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a configuration encrypting the channel's data
+    #[cfg(feature = "crypto")]
+    pub fn with_encryption(encryption: EncryptionConfig) -> Self {
+        Self {
+            encryption: Some(encryption),
+            ..Default::default()
+        }
+    }
+}
+
+/// Kind of values a channel accepts
+///
+/// Numeric channels take [`Gateway::push`](crate::Gateway::push) and are
+/// ALEC-encoded. Text channels take
+/// [`Gateway::push_str`](crate::Gateway::push_str): each string is sent
+/// once in full, registered as a pattern of the channel's context, and
+/// then as a reference to it (see
+/// [`FrameDecoder::decode_text`](crate::FrameDecoder::decode_text)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueKind {
+    /// `f64` sensor readings
+    #[default]
+    Numeric,
+    /// Status strings, enums and short codes of up to
+    /// [`MAX_TEXT_LEN`](crate::MAX_TEXT_LEN) bytes
+    Text,
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueKind::Numeric => write!(f, "numeric"),
+            ValueKind::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// What to do with a sample pushed to a full channel buffer
+///
+/// Dropped samples are counted per channel (see
+/// [`Channel::dropped`](crate::Channel::dropped)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Evict the oldest buffered sample to make room
+    DropOldest,
+    /// Discard the incoming sample
+    DropNewest,
+    /// Reject the sample with [`GatewayError::BufferFull`]
+    ///
+    /// [`GatewayError::BufferFull`]: crate::GatewayError::BufferFull
+    #[default]
+    Error,
+}
+
+/// What to do with a sample older than the last accepted one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackwardsPolicy {
+    /// Reject the sample with [`GatewayError::InvalidTimestamp`]
+    ///
+    /// [`GatewayError::InvalidTimestamp`]: crate::GatewayError::InvalidTimestamp
+    Reject,
+    /// Keep the value but use the last accepted timestamp
+    ClampToLast,
+    /// Keep the sample as is
+    #[default]
+    Accept,
+}
+
+/// Per-channel sample timestamp validation
+///
+/// The default accepts every timestamp, as before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimestampPolicy {
+    /// Reject timestamps strictly before this value (e.g. a bad RTC at epoch)
+    pub reject_before: Option<u64>,
+
+    /// Reject timestamps more than this many ms after the last accepted one
+    pub reject_after_skew_ms: Option<u64>,
+
+    /// Handling of timestamps going backwards (e.g. after an NTP step)
+    pub on_backwards: BackwardsPolicy,
+}
+
+impl TimestampPolicy {
+    /// Policy keeping each channel's timestamps monotone and bounded
+    pub fn strict(max_skew_ms: u64) -> Self {
+        Self {
+            reject_before: None,
+            reject_after_skew_ms: Some(max_skew_ms),
+            on_backwards: BackwardsPolicy::Reject,
+        }
+    }
+}
+
+/// Time-bucketed downsampling for high-rate channels
+///
+/// Raw samples are folded into buckets of `bucket_ms`, aligned on
+/// multiples of it and assigned by sample timestamp. When a sample lands
+/// in a later bucket, the open one closes and each aggregate is pushed,
+/// stamped with the bucket start, to a derived channel named
+/// `<channel>.<suffix>` (e.g. `vib.min`), which is encoded and framed like
+/// any other channel. Samples going backwards join the open bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Downsample {
+    /// Bucket width in milliseconds
+    pub bucket_ms: u64,
+
+    /// Statistics transmitted per bucket, one derived channel each
+    pub aggregates: Vec<Aggregate>,
+
+    /// Keep a partially filled bucket open across flushes (default) rather
+    /// than emitting it with the flush
+    ///
+    /// An emitted partial bucket is closed: later samples in the same time
+    /// span start a new bucket with the same start timestamp.
+    pub carry_partial: bool,
+
+    /// Raw samples kept locally, in ms before the newest one (0: none)
+    ///
+    /// See [`Channel::raw_window`](crate::Channel::raw_window).
+    pub raw_window_ms: u64,
+}
+
+impl Downsample {
+    /// Downsampling into `bucket_ms` buckets, carrying partial buckets
+    pub fn new(bucket_ms: u64, aggregates: impl Into<Vec<Aggregate>>) -> Self {
+        Self {
+            bucket_ms,
+            aggregates: aggregates.into(),
+            carry_partial: true,
+            raw_window_ms: 0,
+        }
+    }
+}
+
+/// Statistic computed over a downsampling bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aggregate {
+    /// Smallest value
+    Min,
+    /// Largest value
+    Max,
+    /// Arithmetic mean
+    Mean,
+    /// Value with the latest arrival
+    Last,
+    /// Number of samples
+    Count,
+    /// Population standard deviation
+    StdDev,
+}
+
+impl Aggregate {
+    /// Suffix of the derived channel id
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Mean => "mean",
+            Aggregate::Last => "last",
+            Aggregate::Count => "count",
+            Aggregate::StdDev => "stddev",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -119,6 +687,34 @@ mod tests {
         assert_eq!(config.max_frame_size, 242);
         assert_eq!(config.max_channels, 32);
         assert!(config.enable_checksums);
+        assert_eq!(config.overflow, OverflowPolicy::Error);
+        assert!(!config.structural_headers);
+        assert!(!config.composite);
+    }
+
+    #[test]
+    fn test_overflow_policy_resolution() {
+        let config = ChannelConfig::default();
+        assert_eq!(
+            config.overflow_policy(OverflowPolicy::DropOldest),
+            OverflowPolicy::DropOldest
+        );
+
+        let critical = ChannelConfig::with_priority(ChannelConfig::CRITICAL_PRIORITY);
+        assert_eq!(
+            critical.overflow_policy(OverflowPolicy::DropOldest),
+            OverflowPolicy::Error
+        );
+
+        let explicit = ChannelConfig {
+            priority: ChannelConfig::CRITICAL_PRIORITY,
+            overflow: Some(OverflowPolicy::DropNewest),
+            ..Default::default()
+        };
+        assert_eq!(
+            explicit.overflow_policy(OverflowPolicy::Error),
+            OverflowPolicy::DropNewest
+        );
     }
 
     #[test]
@@ -130,6 +726,80 @@ mod tests {
         assert_eq!(dr4.max_frame_size, 242);
     }
 
+    #[test]
+    fn test_transport_presets() {
+        let frame_size = |transport| GatewayConfig::for_transport(transport).max_frame_size;
+        let eu868 = |data_rate| TransportPreset::Lorawan {
+            region: LoRaRegion::Eu868,
+            data_rate,
+        };
+        assert_eq!(frame_size(eu868(0)), 51);
+        assert_eq!(frame_size(eu868(3)), 115);
+        assert_eq!(frame_size(eu868(5)), 222);
+        let us915 = |data_rate| TransportPreset::Lorawan {
+            region: LoRaRegion::Us915,
+            data_rate,
+        };
+        assert_eq!(frame_size(us915(0)), 11);
+        assert_eq!(frame_size(us915(4)), 242);
+        assert_eq!(
+            frame_size(TransportPreset::Lorawan {
+                region: LoRaRegion::As923,
+                data_rate: 2,
+            }),
+            115
+        );
+        assert_eq!(frame_size(TransportPreset::NbIot { mtu: 1280 }), 1232);
+        assert_eq!(frame_size(TransportPreset::Ble { att_mtu: 23 }), 20);
+        assert_eq!(frame_size(TransportPreset::Ble { att_mtu: 247 }), 244);
+        assert_eq!(frame_size(TransportPreset::Ble { att_mtu: 517 }), 512);
+        assert_eq!(frame_size(TransportPreset::Mqtt { max_packet: 4096 }), 3968);
+
+        let config = GatewayConfig::for_transport(eu868(0));
+        assert_eq!(config.max_sync_bytes, 51);
+        assert_eq!(config.overflow, OverflowPolicy::DropOldest);
+        assert!(config.validate().is_ok());
+        let mqtt = GatewayConfig::for_transport(TransportPreset::Mqtt { max_packet: 4096 });
+        assert_eq!(mqtt.overflow, OverflowPolicy::Error);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(GatewayConfig::default().validate().is_ok());
+
+        let mut config = GatewayConfig::for_transport(TransportPreset::Lorawan {
+            region: LoRaRegion::Eu868,
+            data_rate: 0,
+        });
+        config.max_frame_size = 500;
+        assert!(matches!(
+            config.validate(),
+            Err(GatewayError::InvalidConfig(_))
+        ));
+
+        let invalid = [
+            GatewayConfig::with_max_frame_size(0),
+            GatewayConfig {
+                max_channels: 0,
+                ..Default::default()
+            },
+            GatewayConfig {
+                max_sync_bytes: 300,
+                ..GatewayConfig::for_transport(TransportPreset::Ble { att_mtu: 247 })
+            },
+            // No DR5 uplink in US915
+            GatewayConfig::for_transport(TransportPreset::Lorawan {
+                region: LoRaRegion::Us915,
+                data_rate: 5,
+            }),
+            GatewayConfig::for_transport(TransportPreset::Ble { att_mtu: 20 }),
+            GatewayConfig::for_transport(TransportPreset::NbIot { mtu: 48 }),
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?} accepted", config);
+        }
+    }
+
     #[test]
     fn test_channel_config_default() {
         let config = ChannelConfig::default();
@@ -139,6 +809,14 @@ mod tests {
         assert!(config.enable_checksum);
     }
 
+    #[test]
+    fn test_timestamp_policy_default_accepts_all() {
+        let policy = ChannelConfig::default().timestamp_policy;
+        assert_eq!(policy.reject_before, None);
+        assert_eq!(policy.reject_after_skew_ms, None);
+        assert_eq!(policy.on_backwards, BackwardsPolicy::Accept);
+    }
+
     #[test]
     fn test_channel_config_with_priority() {
         let config = ChannelConfig::with_priority(1);
@@ -150,4 +828,17 @@ mod tests {
         let config = ChannelConfig::with_preload("test.alec-context");
         assert_eq!(config.preload_path, Some("test.alec-context".to_string()));
     }
+
+    #[test]
+    fn test_starvation_boost_capped() {
+        let starvation = StarvationConfig::default();
+        assert_eq!(starvation.effective_priority(200, 0, 1.0), 200);
+        assert_eq!(starvation.effective_priority(200, 3, 0.0), 200);
+        assert_eq!(starvation.effective_priority(200, 4, 0.0), 168);
+        // Near overflow: one extra level
+        assert_eq!(starvation.effective_priority(200, 4, 0.8), 136);
+        // Never past max_boost, and critical channels stay as they are
+        assert_eq!(starvation.effective_priority(200, 1000, 1.0), 1);
+        assert_eq!(starvation.effective_priority(0, 1000, 1.0), 0);
+    }
 }
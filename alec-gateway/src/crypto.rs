@@ -0,0 +1,179 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Application-layer encryption of channel data (feature `crypto`)
+//!
+//! Channels with an [`EncryptionConfig`] have their encoded bytes sealed
+//! with an AEAD before frame packing. A sealed entry is:
+//!
+//! ```text
+//! [frame_sequence: 4 LE] [ciphertext: M] [tag: 16]
+//! ```
+//!
+//! The 12-byte nonce is `frame_sequence (4 LE) || FNV-1a-64(channel_id) (8 LE)`
+//! and the channel id is bound as associated data. The gateway increments
+//! the frame sequence on every flush, so a nonce is never reused for a
+//! given key and channel as long as the sequence is not rewound; restore
+//! it with [`Gateway::set_frame_sequence`] after a restart.
+//!
+//! [`Gateway::set_frame_sequence`]: crate::Gateway::set_frame_sequence
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::error::{GatewayError, Result};
+
+/// Authentication tag size in bytes
+pub const TAG_SIZE: usize = 16;
+
+/// Nonce material (frame sequence) carried with each sealed entry
+pub const SEQUENCE_SIZE: usize = 4;
+
+/// Bytes added to a channel's data by encryption
+pub const OVERHEAD: usize = SEQUENCE_SIZE + TAG_SIZE;
+
+/// AEAD algorithm used for a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncryptionAlgo {
+    /// ChaCha20-Poly1305 (RFC 8439)
+    #[default]
+    ChaCha20Poly1305,
+}
+
+/// Pre-shared key configuration for one channel
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionConfig {
+    /// 256-bit pre-shared key
+    pub key: [u8; 32],
+    /// AEAD algorithm
+    pub algo: EncryptionAlgo,
+}
+
+impl EncryptionConfig {
+    /// ChaCha20-Poly1305 with the given key
+    pub fn chacha20poly1305(key: [u8; 32]) -> Self {
+        Self {
+            key,
+            algo: EncryptionAlgo::ChaCha20Poly1305,
+        }
+    }
+
+    /// Seal `plaintext` for `channel_id` in the frame with `sequence`
+    pub fn seal(&self, channel_id: &str, sequence: u32, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce(channel_id, sequence);
+        let ciphertext = match self.algo {
+            EncryptionAlgo::ChaCha20Poly1305 => ChaCha20Poly1305::new(Key::from_slice(&self.key))
+                .encrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: plaintext,
+                        aad: channel_id.as_bytes(),
+                    },
+                ),
+        }
+        .map_err(|_| GatewayError::FrameTooLarge {
+            size: plaintext.len(),
+            max: u16::MAX as usize - OVERHEAD,
+        })?;
+
+        let mut sealed = Vec::with_capacity(SEQUENCE_SIZE + ciphertext.len());
+        sealed.extend_from_slice(&sequence.to_le_bytes());
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Open an entry sealed by [`EncryptionConfig::seal`]
+    ///
+    /// Fails with [`GatewayError::AuthenticationFailed`] when the entry was
+    /// tampered with, truncated, or sealed with another key or channel id.
+    pub fn open(&self, channel_id: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        let auth_failed = || GatewayError::AuthenticationFailed(channel_id.to_string());
+        if sealed.len() < OVERHEAD {
+            return Err(auth_failed());
+        }
+        let sequence = u32::from_le_bytes([sealed[0], sealed[1], sealed[2], sealed[3]]);
+        let nonce = nonce(channel_id, sequence);
+        let plaintext = match self.algo {
+            EncryptionAlgo::ChaCha20Poly1305 => ChaCha20Poly1305::new(Key::from_slice(&self.key))
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &sealed[SEQUENCE_SIZE..],
+                        aad: channel_id.as_bytes(),
+                    },
+                ),
+        };
+        plaintext.map_err(|_| auth_failed())
+    }
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field("key", &"<redacted>")
+            .field("algo", &self.algo)
+            .finish()
+    }
+}
+
+/// Nonce for a channel in the frame with `sequence`
+fn nonce(channel_id: &str, sequence: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..4].copy_from_slice(&sequence.to_le_bytes());
+    nonce[4..].copy_from_slice(&fnv1a64(channel_id.as_bytes()).to_le_bytes());
+    nonce
+}
+
+/// FNV-1a 64-bit hash
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let config = EncryptionConfig::chacha20poly1305([7; 32]);
+        let sealed = config.seal("temp", 42, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(sealed.len(), 4 + OVERHEAD);
+        assert_eq!(&sealed[..4], &42u32.to_le_bytes());
+        assert_eq!(config.open("temp", &sealed).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_open_rejects_other_channel_id() {
+        let config = EncryptionConfig::chacha20poly1305([7; 32]);
+        let sealed = config.seal("temp", 1, &[1, 2, 3]).unwrap();
+        assert!(matches!(
+            config.open("humid", &sealed),
+            Err(GatewayError::AuthenticationFailed(id)) if id == "humid"
+        ));
+    }
+
+    #[test]
+    fn test_open_rejects_truncated() {
+        let config = EncryptionConfig::chacha20poly1305([7; 32]);
+        assert!(config.open("temp", &[0; OVERHEAD - 1]).is_err());
+    }
+
+    #[test]
+    fn test_nonce_differs_per_sequence_and_channel() {
+        assert_ne!(nonce("temp", 1), nonce("temp", 2));
+        assert_ne!(nonce("temp", 1), nonce("humid", 1));
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let config = EncryptionConfig::chacha20poly1305([0xAB; 32]);
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("redacted"));
+        assert!(!debug.contains("171"));
+    }
+}
@@ -0,0 +1,394 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Receiver-side frame unpacking
+//!
+//! The [`FrameDecoder`] splits a frame back into per-channel ALEC bytes,
+//! opening encrypted entries with the pre-shared keys registered through
+//! [`FrameDecoder::add_key`] (feature `crypto`). Each entry is reported on
+//! its own, so one entry failing authentication does not discard the
+//! others in the same frame.
+//!
+//! The decoder also keeps a copy of every channel's context, kept in step
+//! with the gateway's through [`FrameDecoder::handle_sync`], which
+//! [`FrameDecoder::decode_text`] reads the strings of text channels from
+//! and [`FrameDecoder::decode_composite`] the values of composite entries.
+//! [`FrameDecoder::decode_values`] decodes the entries of numeric channels
+//! with a decoder kept per channel, so self-describing messages (see
+//! [`ChannelConfig::self_describing_interval`](crate::ChannelConfig::self_describing_interval))
+//! resynchronize a channel that lost frames.
+
+use std::collections::HashMap;
+
+use alec::sync::{SyncConfig, SyncDiff, SyncMessage, SyncState, Synchronizer};
+use alec::{ChecksumPolicy, Context, Decoder, EncodedMessageRef, RawData};
+
+use crate::channel_manager::ChannelId;
+#[cfg(feature = "crypto")]
+use crate::crypto::EncryptionConfig;
+use crate::error::{GatewayError, Result};
+use crate::frame::{CompositeData, Frame, FrameParseError, FrameRef};
+use crate::sync::SyncFrame;
+use crate::text;
+
+/// One channel entry of a decoded frame
+#[derive(Debug)]
+pub struct DecodedChannel {
+    /// Channel identifier
+    pub id: String,
+    /// Whether the entry was encrypted on the wire
+    pub encrypted: bool,
+    /// Whether the entry is composite, see
+    /// [`FrameDecoder::decode_composite`]
+    pub composite: bool,
+    /// ALEC-encoded bytes, or why they could not be recovered
+    pub data: Result<Vec<u8>>,
+}
+
+/// Receiver-side context of one channel
+#[derive(Debug, Clone)]
+struct RemoteChannel {
+    context: Context,
+    synchronizer: Synchronizer,
+    /// Decoder of the channel's numeric entries
+    decoder: Decoder,
+}
+
+impl RemoteChannel {
+    fn new(context: Context, sync: &SyncConfig) -> Self {
+        Self {
+            context,
+            synchronizer: Synchronizer::with_config(sync.clone()),
+            decoder: Decoder::with_checksum_verification(),
+        }
+    }
+}
+
+/// Unpacks frames produced by a [`crate::Gateway`]
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    /// Pre-shared keys by channel id
+    #[cfg(feature = "crypto")]
+    keys: HashMap<String, EncryptionConfig>,
+    /// Contexts by channel id, kept in sync with the gateway
+    channels: HashMap<ChannelId, RemoteChannel>,
+    /// Configuration of the per-channel synchronizers
+    sync: SyncConfig,
+}
+
+impl FrameDecoder {
+    /// Create a decoder with no keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a decoder whose channels synchronize with `config`
+    ///
+    /// `max_version_gap` decides when a channel asks for a full resync
+    /// instead of an incremental one.
+    pub fn with_sync_config(config: SyncConfig) -> Self {
+        Self {
+            sync: config,
+            ..Self::default()
+        }
+    }
+
+    /// Register the starting context of a channel, e.g. its preload
+    ///
+    /// Channels first seen in a sync frame start from an empty context.
+    pub fn add_context(&mut self, channel_id: impl Into<String>, context: Context) {
+        self.channels
+            .insert(channel_id.into(), RemoteChannel::new(context, &self.sync));
+    }
+
+    /// Set whether a channel's numeric entries end with a checksum
+    ///
+    /// Must match the channel's
+    /// [`ChannelConfig::enable_checksum`](crate::ChannelConfig::enable_checksum);
+    /// channels expect one by default, as the configuration does. Only
+    /// [`decode_values`](Self::decode_values) reads the checksums, since
+    /// entries are otherwise passed on undecoded. Resets the channel's
+    /// decoder, so set it before decoding the channel.
+    pub fn set_checksum(&mut self, channel_id: &str, enabled: bool) {
+        let policy = if enabled {
+            ChecksumPolicy::Always
+        } else {
+            ChecksumPolicy::Never
+        };
+        self.remote(channel_id.to_string()).decoder = Decoder::with_checksum_policy(policy);
+    }
+
+    /// Context held for a channel
+    pub fn context(&self, channel_id: &str) -> Option<&Context> {
+        self.channels.get(channel_id).map(|ch| &ch.context)
+    }
+
+    /// Sync state of a channel
+    pub fn sync_state(&self, channel_id: &str) -> Option<&SyncState> {
+        self.channels
+            .get(channel_id)
+            .map(|ch| ch.synchronizer.state())
+    }
+
+    /// Handle a [`SyncFrame`] from the gateway, returning the responses
+    /// for the downlink
+    ///
+    /// Each entry goes to its channel's [`Synchronizer`]: announces that
+    /// reveal a drift produce a sync request, diffs are applied to the
+    /// channel's context. Diffs from [`Synchronizer::handle_request`]
+    /// carry the whole dictionary, so patterns missing from them are
+    /// dropped before applying. A diff that fails its hash check leaves
+    /// the channel [`SyncState::Diverged`] and the next announce asks for
+    /// a full resync. Other message types are ignored.
+    ///
+    /// Send the responses as `SyncFrame::new(responses).to_bytes()` to
+    /// [`Gateway::handle_sync`](crate::Gateway::handle_sync).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid sync frame.
+    pub fn handle_sync(
+        &mut self,
+        bytes: &[u8],
+    ) -> std::result::Result<Vec<(ChannelId, SyncMessage)>, FrameParseError> {
+        let frame = SyncFrame::from_bytes(bytes)?;
+        let mut responses = Vec::new();
+
+        for (id, message) in frame.messages {
+            let channel = self.remote(id.clone());
+
+            match message {
+                SyncMessage::Announce(announce) => {
+                    if let Some(response) = channel.synchronizer.check_sync_needed(
+                        announce.version,
+                        announce.hash,
+                        &channel.context,
+                    ) {
+                        responses.push((id, response));
+                    }
+                }
+                SyncMessage::Diff(diff) => {
+                    let removed = channel
+                        .context
+                        .pattern_ids()
+                        .filter(|code| !diff.added.iter().any(|(added, _)| added == code))
+                        .collect();
+                    let diff = SyncDiff { removed, ..diff };
+                    // Failure is recorded in the sync state
+                    let _ = channel
+                        .synchronizer
+                        .handle_diff(&diff, &mut channel.context);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(responses)
+    }
+
+    /// Context of a channel, starting empty if first seen
+    fn remote(&mut self, channel_id: String) -> &mut RemoteChannel {
+        let sync = &self.sync;
+        self.channels
+            .entry(channel_id)
+            .or_insert_with(|| RemoteChannel::new(Context::new(), sync))
+    }
+
+    /// Decode the data of a numeric channel entry into `(value, timestamp)`
+    /// pairs, timestamps in milliseconds
+    ///
+    /// `data` is the entry's [`DecodedChannel::data`]. The channel's
+    /// context observes every value as the gateway's did, so every entry of
+    /// a channel must be decoded once, in the order it was flushed. After a
+    /// lost entry the values drift from the gateway's until the next
+    /// self-describing message or context sync. Channels not registered
+    /// with [`add_context`](Self::add_context) start from an empty context.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidValueData`] if a message is malformed,
+    /// fails its checksum or cannot be decoded; the values before it stay
+    /// observed.
+    pub fn decode_values(&mut self, channel_id: &str, data: &[u8]) -> Result<Vec<(f64, u64)>> {
+        let channel = self.remote(channel_id.to_string());
+        let decoded = channel
+            .decoder
+            .decode_buffer_into(data, &mut channel.context)
+            .map_err(|e| GatewayError::InvalidValueData {
+                channel: channel_id.to_string(),
+                reason: e.to_string(),
+            })?;
+        Ok(decoded
+            .into_iter()
+            .map(|d| (d.value, (d.timestamp_micros() / 1000) as u64))
+            .collect())
+    }
+
+    /// Decode the data of a text channel entry into `(value, timestamp)`
+    /// pairs
+    ///
+    /// `data` is the entry's [`DecodedChannel::data`]. Strings sent in
+    /// full are registered in the channel's context as the gateway
+    /// registered them, so every entry of a channel must be decoded once,
+    /// in the order it was flushed. Channels not registered with
+    /// [`add_context`](Self::add_context) start from an empty context.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidTextData`] if the data is malformed
+    /// or references a pattern missing from the context.
+    pub fn decode_text(&mut self, channel_id: &str, data: &[u8]) -> Result<Vec<(String, u64)>> {
+        let channel = self.remote(channel_id.to_string());
+        text::decode(&mut channel.context, data).map_err(|reason| GatewayError::InvalidTextData {
+            channel: channel_id.to_string(),
+            reason,
+        })
+    }
+
+    /// Decode the data of a composite entry into
+    /// `(channel id, value, timestamp)` samples, oldest first
+    ///
+    /// `data` is the entry's [`DecodedChannel::data`]. Values are decoded
+    /// against the contexts held for their channels, which then observe
+    /// them as the gateway's contexts did, so every composite entry must
+    /// be decoded once, in the order it was flushed. Channels not
+    /// registered with [`add_context`](Self::add_context) start from an
+    /// empty context.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidCompositeData`] if the data is
+    /// malformed, a message fails its checksum or a value cannot be
+    /// decoded.
+    pub fn decode_composite(&mut self, data: &[u8]) -> Result<Vec<(ChannelId, f64, u64)>> {
+        let invalid = GatewayError::InvalidCompositeData;
+        let composite = CompositeData::from_bytes(data)
+            .ok_or_else(|| invalid("truncated composite data".to_string()))?;
+        for id in &composite.channels {
+            self.remote(id.clone());
+        }
+
+        let mut decoder = Decoder::new();
+        let mut samples = Vec::new();
+        for bytes in &composite.messages {
+            let message = EncodedMessageRef::from_bytes_with_checksum(bytes)
+                .map_err(|e| invalid(e.to_string()))?;
+            let decoded = decoder
+                .decode_composite_ref(&message, |channel| {
+                    let id = composite.channels.get(channel as usize)?;
+                    self.channels.get(id).map(|ch| &ch.context)
+                })
+                .map_err(|e| invalid(e.to_string()))?;
+
+            let timestamp = decoded.timestamp * 1000;
+            for entry in decoded.values {
+                // Decoded, so the channel is in the table
+                let id = &composite.channels[entry.channel_id as usize];
+                let value = entry
+                    .value
+                    .map_err(|e| invalid(format!("channel {}: {}", id, e)))?;
+                let channel = self.remote(id.clone());
+                channel.context.observe(&RawData::new(value, timestamp));
+                samples.push((id.clone(), value, timestamp));
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// Register the key used to open a channel's entries
+    #[cfg(feature = "crypto")]
+    pub fn add_key(&mut self, channel_id: impl Into<String>, config: EncryptionConfig) {
+        self.keys.insert(channel_id.into(), config);
+    }
+
+    /// Parse frame bytes and decode every entry
+    pub fn decode(
+        &self,
+        bytes: &[u8],
+    ) -> std::result::Result<Vec<DecodedChannel>, FrameParseError> {
+        let frame = FrameRef::from_bytes(bytes)?;
+        Ok(self.decode_frame_ref(&frame))
+    }
+
+    /// Decode every entry of a parsed frame
+    pub fn decode_frame(&self, frame: &Frame) -> Vec<DecodedChannel> {
+        frame
+            .channels
+            .iter()
+            .map(|ch| DecodedChannel {
+                id: ch.id.clone(),
+                encrypted: ch.encrypted,
+                composite: ch.composite,
+                data: self.decode_entry(&ch.id, &ch.data, ch.encrypted),
+            })
+            .collect()
+    }
+
+    /// Decode every entry of a frame parsed in place
+    ///
+    /// Same result as [`FrameDecoder::decode_frame`] on the owned frame,
+    /// with each entry's data copied once.
+    pub fn decode_frame_ref(&self, frame: &FrameRef<'_>) -> Vec<DecodedChannel> {
+        frame
+            .channels()
+            .map(|ch| DecodedChannel {
+                data: self.decode_entry(&ch.id, ch.data, ch.encrypted),
+                id: ch.id.into_owned(),
+                encrypted: ch.encrypted,
+                composite: ch.composite,
+            })
+            .collect()
+    }
+
+    /// Recover the ALEC bytes of one entry
+    fn decode_entry(&self, id: &str, data: &[u8], encrypted: bool) -> Result<Vec<u8>> {
+        if !encrypted {
+            return Ok(data.to_vec());
+        }
+
+        #[cfg(feature = "crypto")]
+        if let Some(config) = self.keys.get(id) {
+            return config.open(id, data);
+        }
+
+        Err(GatewayError::InvalidConfig(format!(
+            "no key for encrypted channel: {}",
+            id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plaintext_passthrough() {
+        let mut frame = Frame::new();
+        frame.add_channel("temp".to_string(), vec![1, 2, 3]);
+
+        let decoded = FrameDecoder::new().decode(&frame.to_bytes()).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].id, "temp");
+        assert!(!decoded[0].encrypted);
+        assert_eq!(decoded[0].data.as_ref().unwrap(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_encrypted_without_key() {
+        let mut frame = Frame::new();
+        frame.add_encrypted_channel("temp".to_string(), vec![0; 24]);
+        frame.add_channel("humid".to_string(), vec![4, 5]);
+
+        let decoded = FrameDecoder::new().decode_frame(&frame);
+        assert!(matches!(
+            decoded[0].data,
+            Err(GatewayError::InvalidConfig(_))
+        ));
+        assert_eq!(decoded[1].data.as_ref().unwrap(), &vec![4, 5]);
+    }
+}
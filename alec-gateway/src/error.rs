@@ -8,6 +8,8 @@
 
 use thiserror::Error;
 
+use crate::config::ValueKind;
+
 /// Main error type for Gateway operations
 #[derive(Error, Debug)]
 pub enum GatewayError {
@@ -38,6 +40,84 @@ pub enum GatewayError {
     /// Maximum channels reached
     #[error("Maximum channels ({max}) reached")]
     MaxChannelsReached { max: usize },
+
+    /// Sample timestamp rejected by the channel's timestamp policy
+    #[error("Invalid timestamp {timestamp} on channel {channel}: {rule}")]
+    InvalidTimestamp {
+        channel: String,
+        timestamp: u64,
+        rule: TimestampRule,
+    },
+
+    /// Encrypted channel data failed authentication (tampered or wrong key)
+    #[error("Authentication failed for channel: {0}")]
+    AuthenticationFailed(String),
+
+    /// Frame sequence space exhausted; encrypting more would reuse a nonce
+    #[error("Nonce space exhausted for channel: {0}")]
+    NonceExhausted(String),
+
+    /// Sample of another kind than the channel's `value_kind`
+    #[error("Channel {channel} only accepts {expected} values")]
+    ValueKindMismatch {
+        channel: String,
+        expected: ValueKind,
+    },
+
+    /// String sample longer than a text channel carries
+    #[error("Text too long for channel {channel}: {len} bytes (max: {max})")]
+    TextTooLong {
+        channel: String,
+        len: usize,
+        max: usize,
+    },
+
+    /// Text channel entry that cannot be decoded
+    #[error("Invalid text data for channel {channel}: {reason}")]
+    InvalidTextData { channel: String, reason: String },
+
+    /// Numeric channel entry that cannot be decoded
+    #[error("Invalid value data for channel {channel}: {reason}")]
+    InvalidValueData { channel: String, reason: String },
+
+    /// Composite entry that cannot be decoded
+    #[error("Invalid composite data: {0}")]
+    InvalidCompositeData(String),
+
+    /// Gateway snapshot that cannot be loaded
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    /// I/O error while saving or loading a snapshot
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Timestamp policy rule violated by a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampRule {
+    /// Before `reject_before`
+    TooEarly { min: u64 },
+    /// More than `reject_after_skew_ms` after the last accepted timestamp
+    TooFarAhead { last: u64, max_skew_ms: u64 },
+    /// Before the last accepted timestamp with `BackwardsPolicy::Reject`
+    Backwards { last: u64 },
+}
+
+impl std::fmt::Display for TimestampRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimestampRule::TooEarly { min } => write!(f, "before minimum {}", min),
+            TimestampRule::TooFarAhead { last, max_skew_ms } => write!(
+                f,
+                "more than {}ms after last accepted {}",
+                max_skew_ms, last
+            ),
+            TimestampRule::Backwards { last } => {
+                write!(f, "before last accepted {}", last)
+            }
+        }
+    }
 }
 
 /// Result type alias for Gateway operations
@@ -17,6 +17,36 @@
 //! channel_data:
 //! [id_len: 1] [id: N] [data_len: 2 LE] [data: M]
 //! ```
+//!
+//! Version 2 frames, used as soon as one entry is encrypted, add a flags
+//! byte to every entry:
+//!
+//! ```text
+//! channel_data:
+//! [id_len: 1] [id: N] [flags: 1] [data_len: 2 LE] [data: M]
+//! ```
+//!
+//! Flag `0x01` marks data sealed with the channel's pre-shared key
+//! (feature `crypto`). Flag `0x02` marks the composite entry
+//! ([`GatewayConfig::composite`]), whose empty id is followed by
+//! [`CompositeData`].
+//!
+//! Version 3 frames ([`GatewayConfig::structural_headers`]) also carry the
+//! number of samples encoded in each entry, so [`Frame::inspect`] can
+//! summarize a frame without decoding it:
+//!
+//! ```text
+//! channel_data:
+//! [id_len: 1] [id: N] [flags: 1] [sample_count: 2 LE] [data_len: 2 LE] [data: M]
+//! ```
+//!
+//! [`FrameRef`] parses a frame in place, borrowing the entry data from
+//! the frame bytes instead of copying it.
+//!
+//! [`GatewayConfig::structural_headers`]: crate::GatewayConfig::structural_headers
+//! [`GatewayConfig::composite`]: crate::GatewayConfig::composite
+
+use std::borrow::Cow;
 
 /// Aggregated frame containing data from multiple channels
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,14 +62,178 @@ pub struct Frame {
 pub struct ChannelData {
     /// Channel identifier
     pub id: String,
-    /// Encoded data bytes
+    /// Encoded data bytes, sealed when `encrypted` is set
     pub data: Vec<u8>,
+    /// Whether `data` is encrypted
+    pub encrypted: bool,
+    /// Whether `data` is a [`CompositeData`] covering several channels
+    pub composite: bool,
+    /// Number of samples encoded in `data`
+    ///
+    /// Only carried on the wire by [`Frame::VERSION_STRUCTURAL`] frames,
+    /// where a missing count is written as 0.
+    pub sample_count: Option<u16>,
+}
+
+impl ChannelData {
+    /// Flags bit marking encrypted data
+    pub const FLAG_ENCRYPTED: u8 = 0x01;
+
+    /// Flags bit marking a composite entry
+    pub const FLAG_COMPOSITE: u8 = 0x02;
+
+    /// Flags byte for this entry
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.encrypted {
+            flags |= Self::FLAG_ENCRYPTED;
+        }
+        if self.composite {
+            flags |= Self::FLAG_COMPOSITE;
+        }
+        flags
+    }
+
+    /// Ids of the channels the entry carries: its own, or those of the
+    /// composite table
+    pub fn channel_ids(&self) -> Vec<String> {
+        if !self.composite {
+            return vec![self.id.clone()];
+        }
+        CompositeData::from_bytes(&self.data)
+            .map(|data| data.channels)
+            .unwrap_or_default()
+    }
+
+    /// Whether the entry can only be written with a flags byte
+    fn needs_flags(&self) -> bool {
+        self.encrypted || self.composite
+    }
+}
+
+/// Data of a composite entry: composite ALEC messages and the channels
+/// their entries refer to
+///
+/// ```text
+/// [channel_count: 1] [channel...] [message_count: 1] [message...]
+///
+/// channel: [id_len: 1] [id: N]
+/// message: [len: 2 LE] [bytes: M]
+/// ```
+///
+/// Channel `i` of the table is channel id `i` in the messages, which are
+/// encoded with [`alec::Encoder::encode_composite`] and carry a checksum.
+/// Each message holds one sample of each channel it covers, all within the
+/// same second.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompositeData {
+    /// Channel ids, indexed by the messages' channel ids
+    pub channels: Vec<String>,
+    /// Composite messages, with checksum, oldest first
+    pub messages: Vec<Vec<u8>>,
+}
+
+impl CompositeData {
+    /// Serialize the entry data
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.size());
+        buf.push(self.channels.len() as u8);
+        for id in &self.channels {
+            buf.push(id.len() as u8);
+            buf.extend_from_slice(id.as_bytes());
+        }
+        buf.push(self.messages.len() as u8);
+        for message in &self.messages {
+            buf.extend_from_slice(&(message.len() as u16).to_le_bytes());
+            buf.extend_from_slice(message);
+        }
+        buf
+    }
+
+    /// Parse entry data, `None` if it is truncated or has trailing bytes
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut pos = 0;
+        let mut take = |len: usize| {
+            let bytes = data.get(pos..pos + len)?;
+            pos += len;
+            Some(bytes)
+        };
+
+        let channel_count = take(1)?[0];
+        let mut channels = Vec::with_capacity(channel_count as usize);
+        for _ in 0..channel_count {
+            let id_len = take(1)?[0] as usize;
+            channels.push(String::from_utf8_lossy(take(id_len)?).to_string());
+        }
+        let message_count = take(1)?[0];
+        let mut messages = Vec::with_capacity(message_count as usize);
+        for _ in 0..message_count {
+            let len = take(2)?;
+            let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+            messages.push(take(len)?.to_vec());
+        }
+
+        (pos == data.len()).then_some(Self { channels, messages })
+    }
+
+    /// Serialized size in bytes
+    pub fn size(&self) -> usize {
+        2 + self.channels.iter().map(|id| 1 + id.len()).sum::<usize>()
+            + self.messages.iter().map(|m| 2 + m.len()).sum::<usize>()
+    }
+}
+
+/// Structural summary of a frame, see [`Frame::inspect`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameSummary {
+    /// Frame format version
+    pub version: u8,
+    /// Size of the frame in bytes
+    pub total_size: usize,
+    /// One summary per channel entry, in frame order
+    pub channels: Vec<ChannelSummary>,
+}
+
+impl FrameSummary {
+    /// Number of channel entries
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Total samples across entries, if the frame carries sample counts
+    pub fn total_samples(&self) -> Option<u64> {
+        self.channels
+            .iter()
+            .map(|ch| ch.sample_count.map(u64::from))
+            .sum()
+    }
+}
+
+/// Structural summary of one channel entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSummary {
+    /// Channel identifier
+    pub id: String,
+    /// Samples in the entry ([`Frame::VERSION_STRUCTURAL`] frames only)
+    pub sample_count: Option<u16>,
+    /// Length of the entry's data in bytes
+    pub payload_len: usize,
+    /// Whether the data is encrypted
+    pub encrypted: bool,
+    /// Whether the entry is composite
+    pub composite: bool,
 }
 
 impl Frame {
     /// Current frame format version
     pub const VERSION: u8 = 1;
 
+    /// Frame format version with a flags byte per entry
+    pub const VERSION_FLAGS: u8 = 2;
+
+    /// Frame format version with a flags byte and a sample count per entry
+    pub const VERSION_STRUCTURAL: u8 = 3;
+
     /// Create a new empty frame
     pub fn new() -> Self {
         Self {
@@ -48,9 +242,73 @@ impl Frame {
         }
     }
 
+    /// Create a new empty frame carrying per-entry sample counts
+    pub fn with_structural_headers() -> Self {
+        Self {
+            version: Self::VERSION_STRUCTURAL,
+            channels: Vec::new(),
+        }
+    }
+
     /// Add channel data to the frame
     pub fn add_channel(&mut self, id: String, data: Vec<u8>) {
-        self.channels.push(ChannelData { id, data });
+        self.push(ChannelData {
+            id,
+            data,
+            encrypted: false,
+            composite: false,
+            sample_count: None,
+        });
+    }
+
+    /// Add encrypted channel data, switching to [`Frame::VERSION_FLAGS`]
+    pub fn add_encrypted_channel(&mut self, id: String, data: Vec<u8>) {
+        self.push(ChannelData {
+            id,
+            data,
+            encrypted: true,
+            composite: false,
+            sample_count: None,
+        });
+    }
+
+    /// Add an entry, switching to [`Frame::VERSION_FLAGS`] if it is
+    /// encrypted or composite and the frame has no flags byte yet
+    pub fn push(&mut self, entry: ChannelData) {
+        if entry.needs_flags() && !self.has_flags() {
+            self.version = Self::VERSION_FLAGS;
+        }
+        self.channels.push(entry);
+    }
+
+    /// Check if any entry is encrypted
+    pub fn has_encrypted(&self) -> bool {
+        self.channels.iter().any(|ch| ch.encrypted)
+    }
+
+    /// The composite entry, if any
+    pub fn composite(&self) -> Option<&ChannelData> {
+        self.channels.iter().find(|ch| ch.composite)
+    }
+
+    /// Check if entries carry a flags byte
+    fn has_flags(&self) -> bool {
+        self.version >= Self::VERSION_FLAGS
+    }
+
+    /// Check if entries carry a sample count
+    pub fn has_structural_headers(&self) -> bool {
+        self.version >= Self::VERSION_STRUCTURAL
+    }
+
+    /// Serialized size of one entry in a frame of `version`
+    pub(crate) fn entry_size(id_len: usize, data_len: usize, version: u8) -> usize {
+        let header = match version {
+            Self::VERSION => 0,
+            Self::VERSION_FLAGS => 1,
+            _ => 3,
+        };
+        1 + id_len + header + 2 + data_len
     }
 
     /// Check if the frame is empty
@@ -79,6 +337,14 @@ impl Frame {
             buf.push(ch.id.len() as u8);
             // ID
             buf.extend_from_slice(ch.id.as_bytes());
+            // Flags
+            if self.has_flags() {
+                buf.push(ch.flags());
+            }
+            // Sample count (little-endian u16)
+            if self.has_structural_headers() {
+                buf.extend_from_slice(&ch.sample_count.unwrap_or(0).to_le_bytes());
+            }
             // Data length (little-endian u16)
             buf.extend_from_slice(&(ch.data.len() as u16).to_le_bytes());
             // Data
@@ -89,84 +355,295 @@ impl Frame {
     }
 
     /// Parse a frame from bytes
+    ///
+    /// Copies every entry; see [`FrameRef`] to parse in place.
     pub fn from_bytes(data: &[u8]) -> Result<Self, FrameParseError> {
+        FrameRef::from_bytes(data).map(|frame| frame.to_owned())
+    }
+
+    /// Summarize a frame without copying or decoding its data
+    ///
+    /// Needs no channel context: sample counts come from the entry
+    /// headers of [`Frame::VERSION_STRUCTURAL`] frames and are `None`
+    /// for older versions.
+    pub fn inspect(data: &[u8]) -> Result<FrameSummary, FrameParseError> {
+        let frame = FrameRef::from_bytes(data)?;
+        let channels = frame
+            .channels()
+            .map(|entry| ChannelSummary {
+                id: entry.id.into_owned(),
+                sample_count: entry.sample_count,
+                payload_len: entry.data.len(),
+                encrypted: entry.encrypted,
+                composite: entry.composite,
+            })
+            .collect();
+
+        Ok(FrameSummary {
+            version: frame.version,
+            total_size: data.len(),
+            channels,
+        })
+    }
+
+    /// Calculate the total size of the frame in bytes
+    pub fn size(&self) -> usize {
+        let mut size = 2; // version + channel_count
+        for ch in &self.channels {
+            // id_len + id + [flags] + [sample_count] + data_len + data
+            size += Self::entry_size(ch.id.len(), ch.data.len(), self.version);
+        }
+        size
+    }
+
+    /// Get channel data by ID
+    pub fn get_channel(&self, id: &str) -> Option<&ChannelData> {
+        self.channels.iter().find(|ch| ch.id == id)
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Frame parsed in place, borrowing its entries from the frame bytes
+///
+/// [`FrameRef::from_bytes`] validates the whole frame without allocating;
+/// [`FrameRef::channels`] then walks the entries, each of which borrows its
+/// data. A receiver handling a stream of frames can reuse one buffer for
+/// all of them: parse the buffer, decode every entry, drop the `FrameRef`,
+/// and read the next frame into the same buffer.
+///
+/// ```
+/// use alec_gateway::{Frame, FrameRef};
+///
+/// let mut frame = Frame::new();
+/// frame.add_channel("temp".to_string(), vec![1, 2, 3]);
+/// frame.add_channel("hum".to_string(), vec![4, 5]);
+///
+/// let mut buffer = Vec::with_capacity(242);
+/// for _ in 0..3 {
+///     buffer.clear();
+///     buffer.extend_from_slice(&frame.to_bytes());
+///
+///     let parsed = FrameRef::from_bytes(&buffer).unwrap();
+///     let total: usize = parsed.channels().map(|ch| ch.data.len()).sum();
+///     assert_eq!(total, 5);
+///     // `parsed` is dropped here, releasing `buffer`
+/// }
+/// assert_eq!(FrameRef::from_bytes(&buffer).unwrap().to_owned(), frame);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRef<'a> {
+    /// Frame format version
+    pub version: u8,
+    /// Bytes of the channel entries
+    entries: &'a [u8],
+    /// Number of channel entries
+    count: usize,
+}
+
+/// Data from a single channel within a [`FrameRef`], borrowed from the
+/// frame bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelDataRef<'a> {
+    /// Channel identifier, only copied if it is not valid UTF-8
+    pub id: Cow<'a, str>,
+    /// Encoded data bytes, sealed when `encrypted` is set
+    pub data: &'a [u8],
+    /// Whether `data` is encrypted
+    pub encrypted: bool,
+    /// Whether `data` is a [`CompositeData`] covering several channels
+    pub composite: bool,
+    /// Number of samples encoded in `data`
+    /// ([`Frame::VERSION_STRUCTURAL`] frames only)
+    pub sample_count: Option<u16>,
+}
+
+impl ChannelDataRef<'_> {
+    /// Copy the entry into a [`ChannelData`]
+    pub fn to_owned(&self) -> ChannelData {
+        ChannelData {
+            id: self.id.to_string(),
+            data: self.data.to_vec(),
+            encrypted: self.encrypted,
+            composite: self.composite,
+            sample_count: self.sample_count,
+        }
+    }
+}
+
+impl<'a> FrameRef<'a> {
+    /// Parse and validate a frame without copying its entries
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, FrameParseError> {
         if data.len() < 2 {
             return Err(FrameParseError::TooShort);
         }
 
         let version = data[0];
-        if version != Self::VERSION {
+        if !(Frame::VERSION..=Frame::VERSION_STRUCTURAL).contains(&version) {
             return Err(FrameParseError::UnsupportedVersion(version));
         }
 
-        let channel_count = data[1] as usize;
-        let mut pos = 2;
-        let mut channels = Vec::with_capacity(channel_count);
+        let frame = Self {
+            version,
+            entries: &data[2..],
+            count: data[1] as usize,
+        };
+        for entry in frame.entries() {
+            entry?;
+        }
+        Ok(frame)
+    }
 
-        for _ in 0..channel_count {
-            if pos >= data.len() {
-                return Err(FrameParseError::Truncated);
-            }
+    /// Iterate over the channel entries, in frame order
+    pub fn channels(&self) -> impl Iterator<Item = ChannelDataRef<'a>> {
+        // Validated by `from_bytes`
+        self.entries().map_while(|entry| entry.ok())
+    }
 
-            // ID length
-            let id_len = data[pos] as usize;
-            pos += 1;
+    /// Check if the frame is empty
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
 
-            // ID
-            if pos + id_len > data.len() {
-                return Err(FrameParseError::TruncatedChannelId);
+    /// Get the number of channels in the frame
+    pub fn channel_count(&self) -> usize {
+        self.count
+    }
+
+    /// Check if entries carry a sample count
+    pub fn has_structural_headers(&self) -> bool {
+        self.version >= Frame::VERSION_STRUCTURAL
+    }
+
+    /// Get channel data by ID
+    pub fn get_channel(&self, id: &str) -> Option<ChannelDataRef<'a>> {
+        self.channels().find(|ch| ch.id == id)
+    }
+
+    /// The composite entry, if any
+    pub fn composite(&self) -> Option<ChannelDataRef<'a>> {
+        self.channels().find(|ch| ch.composite)
+    }
+
+    /// Copy the frame into an owned [`Frame`]
+    pub fn to_owned(&self) -> Frame {
+        Frame {
+            version: self.version,
+            channels: self.channels().map(|ch| ch.to_owned()).collect(),
+        }
+    }
+
+    /// Walk the entry bytes, stopping at the first malformed entry
+    fn entries(&self) -> RawEntries<'a> {
+        RawEntries {
+            version: self.version,
+            data: self.entries,
+            pos: 0,
+            channel: 0,
+            count: self.count,
+        }
+    }
+}
+
+/// Iterator splitting entry bytes into entries
+struct RawEntries<'a> {
+    version: u8,
+    data: &'a [u8],
+    pos: usize,
+    channel: usize,
+    count: usize,
+}
+
+impl<'a> RawEntries<'a> {
+    /// Parse the entry at the current position
+    fn parse(&mut self) -> Result<ChannelDataRef<'a>, FrameParseError> {
+        let data = self.data;
+        let channel = self.channel;
+        let mut pos = self.pos;
+        if pos >= data.len() {
+            return Err(FrameParseError::Truncated { channel });
+        }
+
+        // ID length
+        let id_len = data[pos] as usize;
+        pos += 1;
+
+        // ID
+        if pos + id_len > data.len() {
+            return Err(FrameParseError::TruncatedChannelId { channel });
+        }
+        let id = &data[pos..pos + id_len];
+        pos += id_len;
+
+        // Flags
+        let mut flags = 0;
+        if self.version >= Frame::VERSION_FLAGS {
+            if pos >= data.len() {
+                return Err(FrameParseError::Truncated { channel });
             }
-            let id = String::from_utf8_lossy(&data[pos..pos + id_len]).to_string();
-            pos += id_len;
+            flags = data[pos];
+            pos += 1;
+        }
 
-            // Data length
+        // Sample count
+        let mut sample_count = None;
+        if self.version >= Frame::VERSION_STRUCTURAL {
             if pos + 2 > data.len() {
-                return Err(FrameParseError::TruncatedDataLength);
+                return Err(FrameParseError::TruncatedSampleCount { channel });
             }
-            let data_len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+            sample_count = Some(u16::from_le_bytes([data[pos], data[pos + 1]]));
             pos += 2;
-
-            // Data
-            if pos + data_len > data.len() {
-                return Err(FrameParseError::TruncatedChannelData);
-            }
-            let channel_data = data[pos..pos + data_len].to_vec();
-            pos += data_len;
-
-            channels.push(ChannelData {
-                id,
-                data: channel_data,
-            });
         }
 
-        Ok(Self { version, channels })
-    }
+        // Data length
+        if pos + 2 > data.len() {
+            return Err(FrameParseError::TruncatedDataLength { channel });
+        }
+        let data_len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos += 2;
 
-    /// Calculate the total size of the frame in bytes
-    pub fn size(&self) -> usize {
-        let mut size = 2; // version + channel_count
-        for ch in &self.channels {
-            size += 1; // id_len
-            size += ch.id.len(); // id
-            size += 2; // data_len
-            size += ch.data.len(); // data
+        // Data
+        if pos + data_len > data.len() {
+            return Err(FrameParseError::TruncatedChannelData { channel });
         }
-        size
-    }
+        self.pos = pos + data_len;
 
-    /// Get channel data by ID
-    pub fn get_channel(&self, id: &str) -> Option<&ChannelData> {
-        self.channels.iter().find(|ch| ch.id == id)
+        Ok(ChannelDataRef {
+            id: String::from_utf8_lossy(id),
+            data: &data[pos..pos + data_len],
+            encrypted: flags & ChannelData::FLAG_ENCRYPTED != 0,
+            composite: flags & ChannelData::FLAG_COMPOSITE != 0,
+            sample_count,
+        })
     }
 }
 
-impl Default for Frame {
-    fn default() -> Self {
-        Self::new()
+impl<'a> Iterator for RawEntries<'a> {
+    type Item = Result<ChannelDataRef<'a>, FrameParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel >= self.count {
+            return None;
+        }
+        let entry = self.parse();
+        // Nothing follows a malformed entry
+        self.channel = if entry.is_ok() {
+            self.channel + 1
+        } else {
+            self.count
+        };
+        Some(entry)
     }
 }
 
 /// Errors that can occur when parsing a frame
+///
+/// Errors within an entry carry the index of the offending channel
+/// entry (0-based, in frame order).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FrameParseError {
     /// Frame data is too short
@@ -174,13 +651,32 @@ pub enum FrameParseError {
     /// Unsupported frame version
     UnsupportedVersion(u8),
     /// Frame data is truncated
-    Truncated,
+    Truncated { channel: usize },
     /// Channel ID is truncated
-    TruncatedChannelId,
+    TruncatedChannelId { channel: usize },
+    /// Sample count field is truncated
+    TruncatedSampleCount { channel: usize },
     /// Data length field is truncated
-    TruncatedDataLength,
+    TruncatedDataLength { channel: usize },
     /// Channel data is truncated
-    TruncatedChannelData,
+    TruncatedChannelData { channel: usize },
+    /// Sync frame entry does not hold a valid sync message
+    InvalidSyncMessage { channel: usize },
+}
+
+impl FrameParseError {
+    /// Index of the offending channel entry, if the error is within one
+    pub fn channel(&self) -> Option<usize> {
+        match self {
+            Self::TooShort | Self::UnsupportedVersion(_) => None,
+            Self::Truncated { channel }
+            | Self::TruncatedChannelId { channel }
+            | Self::TruncatedSampleCount { channel }
+            | Self::TruncatedDataLength { channel }
+            | Self::TruncatedChannelData { channel }
+            | Self::InvalidSyncMessage { channel } => Some(*channel),
+        }
+    }
 }
 
 impl std::fmt::Display for FrameParseError {
@@ -188,10 +684,22 @@ impl std::fmt::Display for FrameParseError {
         match self {
             Self::TooShort => write!(f, "Frame too short"),
             Self::UnsupportedVersion(v) => write!(f, "Unsupported frame version: {}", v),
-            Self::Truncated => write!(f, "Frame truncated"),
-            Self::TruncatedChannelId => write!(f, "Truncated channel ID"),
-            Self::TruncatedDataLength => write!(f, "Truncated data length"),
-            Self::TruncatedChannelData => write!(f, "Truncated channel data"),
+            Self::Truncated { channel } => write!(f, "Frame truncated in channel {}", channel),
+            Self::TruncatedChannelId { channel } => {
+                write!(f, "Truncated channel ID in channel {}", channel)
+            }
+            Self::TruncatedSampleCount { channel } => {
+                write!(f, "Truncated sample count in channel {}", channel)
+            }
+            Self::TruncatedDataLength { channel } => {
+                write!(f, "Truncated data length in channel {}", channel)
+            }
+            Self::TruncatedChannelData { channel } => {
+                write!(f, "Truncated channel data in channel {}", channel)
+            }
+            Self::InvalidSyncMessage { channel } => {
+                write!(f, "Invalid sync message in channel {}", channel)
+            }
         }
     }
 }
@@ -213,19 +721,81 @@ impl FrameBuilder {
         }
     }
 
+    /// Create a frame builder for [`Frame::VERSION_STRUCTURAL`] frames
+    pub fn with_structural_headers(max_size: usize) -> Self {
+        Self {
+            frame: Frame::with_structural_headers(),
+            max_size,
+        }
+    }
+
     /// Try to add channel data, returns false if frame would exceed max size
     pub fn try_add(&mut self, id: String, data: Vec<u8>) -> bool {
-        // Calculate additional size needed
-        let additional_size = 1 + id.len() + 2 + data.len();
+        self.try_push(ChannelData {
+            id,
+            data,
+            encrypted: false,
+            composite: false,
+            sample_count: None,
+        })
+    }
+
+    /// Try to add sealed channel data, returns false if frame would exceed max size
+    ///
+    /// The first encrypted entry switches the frame to
+    /// [`Frame::VERSION_FLAGS`], which costs one flags byte per entry
+    /// already in the frame as well.
+    pub fn try_add_encrypted(&mut self, id: String, data: Vec<u8>) -> bool {
+        self.try_push(ChannelData {
+            id,
+            data,
+            encrypted: true,
+            composite: false,
+            sample_count: None,
+        })
+    }
+
+    /// Try to add an entry, returns false if frame would exceed max size
+    pub fn try_push(&mut self, entry: ChannelData) -> bool {
+        let version = if entry.needs_flags() && !self.frame.has_flags() {
+            Frame::VERSION_FLAGS
+        } else {
+            self.frame.version
+        };
+        // Entries already in the frame gain a flags byte on upgrade
+        let upgrade = if version != self.frame.version {
+            self.frame.channel_count()
+        } else {
+            0
+        };
+        let additional_size =
+            upgrade + Frame::entry_size(entry.id.len(), entry.data.len(), version);
 
         if self.frame.size() + additional_size > self.max_size {
             return false;
         }
 
-        self.frame.add_channel(id, data);
+        self.frame.push(entry);
         true
     }
 
+    /// Size of the frame once `entries`, as `(id length, data length,
+    /// sealed)`, are added to it
+    pub(crate) fn size_with(&self, entries: &[(usize, usize, bool)]) -> usize {
+        let sealed = entries.iter().any(|&(_, _, sealed)| sealed);
+        let (version, upgrade) = if sealed && !self.frame.has_flags() {
+            (Frame::VERSION_FLAGS, self.frame.channel_count())
+        } else {
+            (self.frame.version, 0)
+        };
+        self.frame.size()
+            + upgrade
+            + entries
+                .iter()
+                .map(|&(id_len, data_len, _)| Frame::entry_size(id_len, data_len, version))
+                .sum::<usize>()
+    }
+
     /// Get the remaining space in bytes
     pub fn remaining(&self) -> usize {
         self.max_size.saturating_sub(self.frame.size())
@@ -319,7 +889,95 @@ mod tests {
     #[test]
     fn test_frame_parse_truncated() {
         let result = Frame::from_bytes(&[1, 1]); // Says 1 channel but no data
-        assert!(matches!(result, Err(FrameParseError::Truncated)));
+        assert!(matches!(
+            result,
+            Err(FrameParseError::Truncated { channel: 0 })
+        ));
+    }
+
+    /// Structural frame with 3 channels of 4, 1 and 12 samples
+    fn structural_frame() -> Frame {
+        let mut frame = Frame::with_structural_headers();
+        for (id, samples, len) in [("temp", 4u16, 9usize), ("humid", 1, 3), ("accel", 12, 40)] {
+            frame.push(ChannelData {
+                id: id.to_string(),
+                data: vec![0xA5; len],
+                encrypted: false,
+                composite: false,
+                sample_count: Some(samples),
+            });
+        }
+        frame
+    }
+
+    #[test]
+    fn test_frame_inspect_structural() {
+        let frame = structural_frame();
+        let bytes = frame.to_bytes();
+        assert_eq!(bytes[0], Frame::VERSION_STRUCTURAL);
+        assert_eq!(bytes.len(), frame.size());
+
+        let summary = Frame::inspect(&bytes).unwrap();
+        assert_eq!(summary.version, Frame::VERSION_STRUCTURAL);
+        assert_eq!(summary.total_size, bytes.len());
+        assert_eq!(summary.channel_count(), 3);
+        assert_eq!(summary.total_samples(), Some(17));
+        let channels: Vec<_> = summary
+            .channels
+            .iter()
+            .map(|ch| (ch.id.as_str(), ch.sample_count, ch.payload_len))
+            .collect();
+        assert_eq!(
+            channels,
+            [
+                ("temp", Some(4), 9),
+                ("humid", Some(1), 3),
+                ("accel", Some(12), 40)
+            ]
+        );
+
+        assert_eq!(Frame::from_bytes(&bytes).unwrap(), frame);
+    }
+
+    #[test]
+    fn test_frame_inspect_names_bad_channel() {
+        let bytes = structural_frame().to_bytes();
+        // Entry 1 ("humid") data_len follows 2 + (1 + 4 + 1 + 2 + 2 + 9)
+        // + (1 + 5 + 1 + 2) bytes; flip its high byte
+        let mut corrupted = bytes.clone();
+        let data_len_pos = 2 + 19 + 9;
+        assert_eq!(corrupted[data_len_pos], 3);
+        corrupted[data_len_pos + 1] ^= 0x01;
+
+        let err = Frame::inspect(&corrupted).unwrap_err();
+        assert_eq!(err, FrameParseError::TruncatedChannelData { channel: 1 });
+        assert_eq!(err.channel(), Some(1));
+        assert!(err.to_string().contains("channel 1"));
+        assert_eq!(Frame::from_bytes(&corrupted).unwrap_err(), err);
+    }
+
+    #[test]
+    fn test_frame_inspect_legacy_has_no_counts() {
+        let mut frame = Frame::new();
+        frame.add_channel("temp".to_string(), vec![1, 2, 3]);
+
+        let summary = Frame::inspect(&frame.to_bytes()).unwrap();
+        assert_eq!(summary.version, Frame::VERSION);
+        assert_eq!(summary.channels[0].sample_count, None);
+        assert_eq!(summary.channels[0].payload_len, 3);
+        assert_eq!(summary.total_samples(), None);
+    }
+
+    #[test]
+    fn test_frame_builder_structural_size() {
+        let mut builder = FrameBuilder::with_structural_headers(100);
+        assert!(builder.try_add("t".to_string(), vec![1, 2]));
+        // 2 (header) + 1 (id_len) + 1 (id) + 1 (flags) + 2 (samples) + 2 (data_len) + 2 = 11
+        assert_eq!(builder.current_size(), 11);
+        assert!(builder.try_add_encrypted("s".to_string(), vec![3]));
+        let frame = builder.build();
+        assert_eq!(frame.version, Frame::VERSION_STRUCTURAL);
+        assert_eq!(frame.to_bytes().len(), frame.size());
     }
 
     #[test]
@@ -342,6 +1000,79 @@ mod tests {
         assert!(!builder.try_add("b".to_string(), vec![3, 4]));
     }
 
+    #[test]
+    fn test_frame_flags_roundtrip() {
+        let mut frame = Frame::new();
+        frame.add_channel("temp".to_string(), vec![1, 2, 3]);
+        frame.add_encrypted_channel("secret".to_string(), vec![4, 5, 6, 7]);
+        assert_eq!(frame.version, Frame::VERSION_FLAGS);
+
+        let bytes = frame.to_bytes();
+        assert_eq!(bytes.len(), frame.size());
+        let restored = Frame::from_bytes(&bytes).unwrap();
+        assert_eq!(frame, restored);
+        assert!(!restored.channels[0].encrypted);
+        assert!(restored.channels[1].encrypted);
+    }
+
+    #[test]
+    fn test_frame_plain_stays_version_1() {
+        let mut frame = Frame::new();
+        frame.add_channel("temp".to_string(), vec![1]);
+        assert_eq!(frame.to_bytes()[0], Frame::VERSION);
+        assert!(!frame.has_encrypted());
+    }
+
+    #[test]
+    fn test_frame_builder_encrypted_counts_flags() {
+        let mut builder = FrameBuilder::new(100);
+        assert!(builder.try_add("a".to_string(), vec![1, 2]));
+        assert_eq!(builder.current_size(), 8);
+        // Upgrade adds a flags byte to "a": 8 + 1 + (1 + 1 + 1 + 2 + 2) = 16
+        assert!(builder.try_add_encrypted("b".to_string(), vec![3, 4]));
+        assert_eq!(builder.current_size(), 16);
+        assert_eq!(builder.build().to_bytes().len(), 16);
+    }
+
+    #[test]
+    fn test_frame_builder_encrypted_max_size() {
+        let mut builder = FrameBuilder::new(15);
+        assert!(builder.try_add("a".to_string(), vec![1, 2]));
+        // Would need 16 bytes once the flags byte is counted
+        assert!(!builder.try_add_encrypted("b".to_string(), vec![3, 4]));
+        assert_eq!(builder.build().version, Frame::VERSION);
+    }
+
+    #[test]
+    fn test_composite_entry_roundtrip() {
+        let data = CompositeData {
+            channels: vec!["temp".to_string(), "humid".to_string()],
+            messages: vec![vec![1, 2, 3], vec![4]],
+        };
+        let bytes = data.to_bytes();
+        assert_eq!(bytes.len(), data.size());
+        assert_eq!(CompositeData::from_bytes(&bytes).unwrap(), data);
+        assert_eq!(CompositeData::from_bytes(&bytes[..bytes.len() - 1]), None);
+
+        let mut builder = FrameBuilder::new(100);
+        assert!(builder.try_add("a".to_string(), vec![1, 2]));
+        assert!(builder.try_push(ChannelData {
+            id: String::new(),
+            data: bytes,
+            encrypted: false,
+            composite: true,
+            sample_count: None,
+        }));
+        let frame = builder.build();
+        assert_eq!(frame.version, Frame::VERSION_FLAGS);
+        assert_eq!(frame.to_bytes().len(), frame.size());
+
+        let restored = Frame::from_bytes(&frame.to_bytes()).unwrap();
+        let entry = restored.composite().unwrap();
+        assert_eq!(entry.channel_ids(), ["temp", "humid"]);
+        assert_eq!(restored.channels[0].channel_ids(), ["a"]);
+    }
+
     #[test]
     fn test_frame_builder_remaining() {
         let mut builder = FrameBuilder::new(100);
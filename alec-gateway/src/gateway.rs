@@ -19,7 +19,7 @@
 //!     ..Default::default()
 //! };
 //!
-//! let mut gateway = Gateway::with_config(config);
+//! let mut gateway = Gateway::with_config(config).unwrap();
 //!
 //! gateway.add_channel("temp", ChannelConfig::default()).unwrap();
 //! gateway.push("temp", 22.5, 1000).unwrap();
@@ -28,11 +28,21 @@
 //! // Send frame.to_bytes() over LoRaWAN, MQTT, etc.
 //! ```
 
-use crate::aggregator::Aggregator;
-use crate::channel_manager::ChannelManager;
-use crate::config::{ChannelConfig, GatewayConfig};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use alec::health::{HealthCheck, HealthCheckable, HealthConfig};
+use alec::security::{AuditEvent, AuditEventType, SecurityContext, Severity};
+use alec::sync::{SyncMessage, Synchronizer};
+use alec::Context;
+
+use crate::aggregator::{Aggregator, FlushEstimate, FlushReport};
+use crate::channel_manager::{Channel, ChannelId, ChannelManager};
+use crate::config::{ChannelConfig, GatewayConfig, ValueKind};
 use crate::error::Result;
-use crate::frame::Frame;
+use crate::frame::{ChannelData, Frame, FrameParseError};
+use crate::snapshot::{self, SnapshotOptions, SnapshotReport};
+use crate::sync::{ChannelSync, SyncFrame};
 
 #[cfg(feature = "metrics")]
 use crate::metrics::{MetricsConfig, MetricsEngine, MetricsSnapshot};
@@ -45,6 +55,12 @@ pub struct Gateway {
     aggregator: Aggregator,
     /// Gateway configuration
     config: GatewayConfig,
+    /// Security context receiving audit events (optional)
+    security: Option<SecurityContext>,
+    /// Sync state by stream id, created on the stream's first flush
+    syncs: HashMap<ChannelId, ChannelSync>,
+    /// Outcome of the snapshot load that created the gateway, if any
+    snapshot_report: Option<SnapshotReport>,
     /// Metrics engine (feature-gated)
     #[cfg(feature = "metrics")]
     metrics_engine: Option<MetricsEngine>,
@@ -56,15 +72,29 @@ pub struct Gateway {
 impl Gateway {
     /// Create a new gateway with default configuration
     pub fn new() -> Self {
-        Self::with_config(GatewayConfig::default())
+        Self::with_config_unchecked(GatewayConfig::default())
     }
 
     /// Create a new gateway with custom configuration
-    pub fn with_config(config: GatewayConfig) -> Self {
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidConfig`](crate::GatewayError::InvalidConfig)
+    /// if [`GatewayConfig::validate`] rejects the configuration.
+    pub fn with_config(config: GatewayConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self::with_config_unchecked(config))
+    }
+
+    /// Create a new gateway without validating the configuration
+    pub fn with_config_unchecked(config: GatewayConfig) -> Self {
         Self {
             manager: ChannelManager::new(config.max_channels),
             aggregator: Aggregator::new(config.clone()),
             config,
+            security: None,
+            syncs: HashMap::new(),
+            snapshot_report: None,
             #[cfg(feature = "metrics")]
             metrics_engine: None,
             #[cfg(feature = "metrics")]
@@ -85,13 +115,21 @@ impl Gateway {
     /// - A channel with the same ID already exists
     /// - The maximum number of channels has been reached
     /// - The preload file (if specified) cannot be loaded
-    pub fn add_channel(&mut self, id: impl Into<String>, config: ChannelConfig) -> Result<()> {
+    ///
+    /// A channel without an overflow policy takes the gateway default
+    /// ([`GatewayConfig::overflow`]), except critical channels which keep
+    /// [`OverflowPolicy::Error`](crate::OverflowPolicy::Error).
+    pub fn add_channel(&mut self, id: impl Into<String>, mut config: ChannelConfig) -> Result<()> {
         let id_string = id.into();
+        config.overflow = Some(config.overflow_policy(self.config.overflow));
 
-        // Register with metrics engine if enabled
+        // Register with metrics engine if enabled; text channels have no
+        // signal to measure
         #[cfg(feature = "metrics")]
         if let Some(ref mut engine) = self.metrics_engine {
-            engine.register_channel(&id_string);
+            if config.value_kind == ValueKind::Numeric {
+                engine.register_channel(&id_string);
+            }
         }
 
         self.manager.add(id_string, config)
@@ -107,7 +145,10 @@ impl Gateway {
     ///
     /// Returns an error if the channel does not exist.
     pub fn remove_channel(&mut self, id: &str) -> Result<()> {
-        self.manager.remove(id)?;
+        let channel = self.manager.remove(id)?;
+        for stream in channel.streams() {
+            self.syncs.remove(&stream.id);
+        }
         Ok(())
     }
 
@@ -123,15 +164,56 @@ impl Gateway {
     ///
     /// Returns an error if:
     /// - The channel does not exist
-    /// - The channel's buffer is full
+    /// - The channel is a text channel
+    /// - The timestamp violates the channel's timestamp policy
+    /// - The channel's buffer is full and its overflow policy is
+    ///   [`OverflowPolicy::Error`](crate::OverflowPolicy::Error)
     pub fn push(&mut self, channel_id: &str, value: f64, timestamp: u64) -> Result<()> {
+        // Rejected samples never reach the metrics window
+        let channel = self.manager.get(channel_id)?;
+        channel.check_value_kind(ValueKind::Numeric)?;
+        let timestamp = channel.check_timestamp(timestamp)?;
+
         // Observe sample for metrics (if enabled)
         #[cfg(feature = "metrics")]
         if let Some(ref mut engine) = self.metrics_engine {
             engine.observe_sample(channel_id, value, timestamp);
         }
 
-        self.manager.get_mut(channel_id)?.push(value, timestamp)
+        let channel = self.manager.get_mut(channel_id)?;
+        let dropped_before = channel.dropped();
+        let result = channel.push(value, timestamp);
+        self.audit_first_drop(channel_id, dropped_before);
+        result
+    }
+
+    /// Push a string to a text channel
+    ///
+    /// The first occurrence of a string is sent in full and registered in
+    /// the channel's context; later ones cost a few bytes. See
+    /// [`ValueKind::Text`].
+    ///
+    /// # Arguments
+    ///
+    /// * `channel_id` - ID of the target channel
+    /// * `value` - Status or code, at most [`MAX_TEXT_LEN`](crate::MAX_TEXT_LEN) bytes
+    /// * `timestamp` - Timestamp of the measurement
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The channel does not exist
+    /// - The channel is not a text channel
+    /// - The string is longer than [`MAX_TEXT_LEN`](crate::MAX_TEXT_LEN) bytes
+    /// - The timestamp violates the channel's timestamp policy
+    /// - The channel's buffer is full and its overflow policy is
+    ///   [`OverflowPolicy::Error`](crate::OverflowPolicy::Error)
+    pub fn push_str(&mut self, channel_id: &str, value: &str, timestamp: u64) -> Result<()> {
+        let channel = self.manager.get_mut(channel_id)?;
+        let dropped_before = channel.dropped();
+        let result = channel.push_str(value, timestamp);
+        self.audit_first_drop(channel_id, dropped_before);
+        result
     }
 
     /// Push multiple values to a channel
@@ -145,61 +227,233 @@ impl Gateway {
     ///
     /// Returns an error if:
     /// - The channel does not exist
-    /// - The channel's buffer becomes full
+    /// - A timestamp violates the channel's timestamp policy
+    /// - The channel's buffer becomes full and its overflow policy is
+    ///   [`OverflowPolicy::Error`](crate::OverflowPolicy::Error)
     pub fn push_multi(&mut self, channel_id: &str, values: &[(f64, u64)]) -> Result<()> {
         let channel = self.manager.get_mut(channel_id)?;
-        for (value, timestamp) in values {
-            channel.push(*value, *timestamp)?;
+        let dropped_before = channel.dropped();
+        let result = values
+            .iter()
+            .try_for_each(|(value, timestamp)| channel.push(*value, *timestamp));
+        self.audit_first_drop(channel_id, dropped_before);
+        result
+    }
+
+    /// Audit a channel that just started dropping samples
+    fn audit_first_drop(&self, channel_id: &str, dropped_before: u64) {
+        let (Some(security), Ok(channel)) = (&self.security, self.manager.get(channel_id)) else {
+            return;
+        };
+        if dropped_before == 0 && channel.dropped() > 0 {
+            security.audit(
+                AuditEvent::new(
+                    AuditEventType::BufferOverflow,
+                    format!(
+                        "Channel '{}' buffer full ({} samples), dropping with {:?}",
+                        channel_id,
+                        channel.config.buffer_size,
+                        channel.config.overflow_policy(self.config.overflow)
+                    ),
+                )
+                .with_severity(Severity::Medium)
+                .with_field("channel", channel_id)
+                .with_field("buffer_size", channel.config.buffer_size)
+                .with_field(
+                    "policy",
+                    format!("{:?}", channel.config.overflow_policy(self.config.overflow)),
+                ),
+            );
         }
-        Ok(())
+    }
+
+    /// Samples dropped by a channel's overflow policy
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel does not exist.
+    pub fn dropped(&self, channel_id: &str) -> Result<u64> {
+        Ok(self.manager.get(channel_id)?.dropped())
+    }
+
+    /// Samples dropped across all channels
+    pub fn total_dropped(&self) -> u64 {
+        self.manager.iter().map(|(_, ch)| ch.dropped()).sum()
+    }
+
+    /// Flushes in a row the frame budget left a channel out of
+    ///
+    /// See [`GatewayConfig::starvation`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel does not exist.
+    pub fn skipped_flushes(&self, channel_id: &str) -> Result<u32> {
+        Ok(self.manager.get(channel_id)?.skipped_flushes())
+    }
+
+    /// Attach a security context receiving audit events, such as a
+    /// channel starting to drop samples
+    pub fn set_security_context(&mut self, security: SecurityContext) {
+        self.security = Some(security);
+    }
+
+    /// Attached security context, if any
+    pub fn security_context(&self) -> Option<&SecurityContext> {
+        self.security.as_ref()
     }
 
     /// Flush all channels and return aggregated frame
     ///
     /// Channels are processed in priority order. The frame respects the
-    /// configured maximum size.
+    /// configured maximum size, and carries the channels of a
+    /// [group](ChannelConfig::group) together or not at all. Channels that
+    /// fail to encode are left out;
+    /// use [`flush_with_report`](Self::flush_with_report) to see why.
     pub fn flush(&mut self) -> Result<Frame> {
-        let frame = self.aggregator.aggregate(&mut self.manager)?;
+        Ok(self.flush_with_report()?.frame)
+    }
 
-        // Compute and store metrics (if enabled)
-        #[cfg(feature = "metrics")]
-        if let Some(ref mut engine) = self.metrics_engine {
-            let payload = frame.to_bytes();
-            // Use current time in milliseconds (or 0 if unavailable)
-            let current_time_ms = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as u64)
-                .unwrap_or(0);
-            if let Some(snapshot) = engine.observe_frame(&payload, current_time_ms) {
-                self.last_metrics_snapshot = Some(snapshot);
-            }
-        }
+    /// Flush all channels, isolating channels that fail to encode
+    ///
+    /// Healthy channels make the frame while the errors of failing ones
+    /// are returned in the report, their samples kept buffered. After
+    /// [`GatewayConfig::fault_threshold`] failed flushes in a row a channel
+    /// is faulted: it is audited (when a security context is attached),
+    /// reported degraded by the health check and skipped until
+    /// [`reset_channel`](Self::reset_channel).
+    ///
+    /// # Errors
+    ///
+    /// With [`GatewayConfig::fail_fast`], the first channel encode error
+    /// fails the whole flush instead. A channel group whose data exceeds
+    /// the maximum frame size on its own fails it with
+    /// [`GatewayError::InvalidConfig`](crate::GatewayError::InvalidConfig).
+    pub fn flush_with_report(&mut self) -> Result<FlushReport> {
+        self.flush_with_report_at(wall_clock_ms())
+    }
 
-        Ok(frame)
+    /// Flush all channels at an explicit time, isolating channels that
+    /// fail to encode
+    ///
+    /// As [`flush_with_report`](Self::flush_with_report), with the frame's
+    /// metrics (feature `metrics`) stamped `now_ms` instead of the wall
+    /// clock, for replays and simulations: metrics windows and the
+    /// snapshots fed to complexity analysis follow the data's time.
+    pub fn flush_with_report_at(&mut self, now_ms: u64) -> Result<FlushReport> {
+        let report = self.aggregator.aggregate_with_report(&mut self.manager)?;
+        self.finish_flush(&report, now_ms);
+        Ok(report)
+    }
+
+    /// Predict the frame [`flush`](Self::flush) would build, without flushing
+    ///
+    /// Buffers, contexts and the frame sequence are left untouched. Each
+    /// channel keeps a dry run of its pending samples that only encodes
+    /// what was pushed since the previous call, so polling before every
+    /// scheduling decision stays cheap. The estimate matches the next flush
+    /// byte for byte unless channels change in between.
+    pub fn estimate_flush(&mut self) -> FlushEstimate {
+        self.aggregator.estimate(&mut self.manager)
     }
 
     /// Flush specific channels and return aggregated frame
     ///
     /// Only the specified channels will be flushed.
     pub fn flush_channels(&mut self, channel_ids: &[&str]) -> Result<Frame> {
-        let frame = self
+        Ok(self.flush_channels_with_report(channel_ids)?.frame)
+    }
+
+    /// Flush specific channels, isolating channels that fail to encode
+    ///
+    /// See [`flush_with_report`](Self::flush_with_report).
+    pub fn flush_channels_with_report(&mut self, channel_ids: &[&str]) -> Result<FlushReport> {
+        let report = self
             .aggregator
-            .aggregate_channels(&mut self.manager, channel_ids)?;
+            .aggregate_channels_with_report(&mut self.manager, channel_ids)?;
+        self.finish_flush(&report, wall_clock_ms());
+        Ok(report)
+    }
+
+    /// Audit newly faulted channels, advance announce schedules and
+    /// record frame metrics at `now_ms`
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn finish_flush(&mut self, report: &FlushReport, now_ms: u64) {
+        for id in report
+            .frame
+            .channels
+            .iter()
+            .flat_map(ChannelData::channel_ids)
+        {
+            let sync = self
+                .syncs
+                .entry(id)
+                .or_insert_with(|| ChannelSync::new(&self.config.sync));
+            if sync.synchronizer.should_announce() {
+                sync.announce_due = true;
+            }
+        }
+
+        if let Some(security) = &self.security {
+            for id in &report.tripped {
+                let error = report
+                    .errors
+                    .iter()
+                    .find(|(failed, _)| failed == id)
+                    .map(|(_, e)| e.to_string())
+                    .unwrap_or_default();
+                security.audit(
+                    AuditEvent::new(
+                        AuditEventType::Error,
+                        format!(
+                            "Channel '{}' faulted after {} failed flushes: {}",
+                            id, self.config.fault_threshold, error
+                        ),
+                    )
+                    .with_severity(Severity::High)
+                    .with_field("channel", id.as_str())
+                    .with_field("failures", self.config.fault_threshold)
+                    .with_field("error", error),
+                );
+            }
+        }
 
         // Compute and store metrics (if enabled)
         #[cfg(feature = "metrics")]
         if let Some(ref mut engine) = self.metrics_engine {
-            let payload = frame.to_bytes();
-            let current_time_ms = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as u64)
-                .unwrap_or(0);
-            if let Some(snapshot) = engine.observe_frame(&payload, current_time_ms) {
+            let payload = report.frame.to_bytes();
+            if let Some(snapshot) = engine.observe_frame(&payload, now_ms) {
                 self.last_metrics_snapshot = Some(snapshot);
             }
         }
+    }
+
+    /// Clear a channel's fault after operator intervention
+    ///
+    /// The channel gets a fresh encoder and a context reloaded from its
+    /// preload (`reload_preload`) or empty; buffered samples are kept and
+    /// go out with the next flush. The receiving decoder must reset the
+    /// channel's context the same way. Also works on channels that are
+    /// failing but not yet faulted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel does not exist or its preload
+    /// cannot be loaded.
+    pub fn reset_channel(&mut self, channel_id: &str, reload_preload: bool) -> Result<()> {
+        self.manager.get_mut(channel_id)?.reset(reload_preload)
+    }
 
-        Ok(frame)
+    /// IDs of faulted channels, sorted
+    pub fn faulted_channels(&self) -> Vec<&str> {
+        let mut faulted: Vec<&str> = self
+            .manager
+            .iter()
+            .filter(|(_, ch)| ch.is_faulted())
+            .map(|(id, _)| id.as_str())
+            .collect();
+        faulted.sort_unstable();
+        faulted
     }
 
     /// Get list of channel IDs
@@ -252,6 +506,19 @@ impl Gateway {
         self.aggregator.set_max_frame_size(size);
     }
 
+    /// Sequence number the next flushed frame will use
+    pub fn frame_sequence(&self) -> u64 {
+        self.aggregator.frame_sequence()
+    }
+
+    /// Set the sequence number of the next flushed frame
+    ///
+    /// Encrypted channels derive their nonce from the frame sequence, so
+    /// persist it and restore it here after a restart.
+    pub fn set_frame_sequence(&mut self, sequence: u64) {
+        self.aggregator.set_frame_sequence(sequence);
+    }
+
     /// Get a channel's context version
     ///
     /// # Errors
@@ -261,11 +528,203 @@ impl Gateway {
         Ok(self.manager.get(channel_id)?.context_version())
     }
 
+    /// Get a channel's context
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel does not exist.
+    pub fn channel_context(&self, channel_id: &str) -> Result<&Context> {
+        Ok(self.manager.get(channel_id)?.context())
+    }
+
     /// Check if the gateway has any pending data
     pub fn has_pending_data(&self) -> bool {
         self.manager.total_pending() > 0
     }
 
+    // =====================================================================
+    // Snapshot API
+    // =====================================================================
+
+    /// Save the gateway's channels, configurations and contexts
+    ///
+    /// Buffered samples are left out; see
+    /// [`save_snapshot_with`](Self::save_snapshot_with). Metrics state is
+    /// not saved, only whether metrics were enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Io`](crate::GatewayError::Io) if writing
+    /// fails.
+    pub fn save_snapshot(&self, writer: impl Write) -> Result<()> {
+        self.save_snapshot_with(writer, &SnapshotOptions::default())
+    }
+
+    /// Save the gateway as [`save_snapshot`](Self::save_snapshot) does,
+    /// with options
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::Io`](crate::GatewayError::Io) if writing
+    /// fails.
+    pub fn save_snapshot_with(
+        &self,
+        mut writer: impl Write,
+        options: &SnapshotOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let metrics_enabled = self.metrics_enabled();
+        #[cfg(not(feature = "metrics"))]
+        let metrics_enabled = false;
+
+        let bytes = snapshot::write(
+            &self.config,
+            self.frame_sequence(),
+            metrics_enabled,
+            options,
+            self.manager.iter().map(|(_, channel)| channel),
+        )?;
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Rebuild a gateway from a snapshot
+    ///
+    /// Channels come back with their configuration, contexts, encoder and
+    /// frame sequences, and buffered samples if they were saved, so
+    /// compression resumes warm and the receiver's contexts stay in step.
+    /// A channel that fails to restore (e.g. a corrupted context, or an
+    /// encrypted channel, whose key is not saved) is skipped and listed in
+    /// [`snapshot_report`](Self::snapshot_report). Metrics are not
+    /// re-enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::InvalidSnapshot`](crate::GatewayError::InvalidSnapshot)
+    /// if the header or gateway configuration is unreadable or of another
+    /// version, or [`GatewayError::Io`](crate::GatewayError::Io) if
+    /// reading fails.
+    pub fn load_snapshot(mut reader: impl Read) -> Result<Gateway> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let snapshot = snapshot::read(&data)?;
+
+        let mut gateway = Self::with_config_unchecked(snapshot.config);
+        gateway.set_frame_sequence(snapshot.frame_sequence);
+        let mut report = snapshot.report;
+        for channel in snapshot.channels {
+            let id = channel.id.clone();
+            match gateway.manager.insert(channel) {
+                Ok(()) => report.restored += 1,
+                Err(e) => report.skipped.push(snapshot::SkippedChannel {
+                    id,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        gateway.snapshot_report = Some(report);
+        Ok(gateway)
+    }
+
+    /// Outcome of the [`load_snapshot`](Self::load_snapshot) that created
+    /// the gateway, `None` for a gateway created otherwise
+    pub fn snapshot_report(&self) -> Option<&SnapshotReport> {
+        self.snapshot_report.as_ref()
+    }
+
+    // =====================================================================
+    // Context synchronization API
+    // =====================================================================
+
+    /// Take the sync messages due for the receiving
+    /// [`FrameDecoder`](crate::FrameDecoder), highest priority first
+    ///
+    /// A channel's announce falls due on the schedule of
+    /// [`GatewayConfig::sync`], counted in the frame entries it flushed;
+    /// a diff falls due when [`handle_sync`](Self::handle_sync) receives
+    /// the decoder's request, and replaces the announce. Messages are
+    /// built from the contexts as they are now. With
+    /// [`GatewayConfig::max_sync_bytes`] set, channels that would push the
+    /// [`SyncFrame`] past the cap are deferred to the next call; the first
+    /// message always goes, so an oversized diff is sent alone.
+    ///
+    /// Send the result as `SyncFrame::new(messages).to_bytes()`.
+    pub fn sync_announces(&mut self) -> Vec<(ChannelId, SyncMessage)> {
+        let mut streams: Vec<&Channel> = self
+            .manager
+            .iter()
+            .flat_map(|(_, ch)| ch.streams())
+            .collect();
+        streams.sort_by(|a, b| {
+            a.config
+                .priority
+                .cmp(&b.config.priority)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let budget = self.config.max_sync_bytes;
+        let mut size = SyncFrame::default().size();
+        let mut messages = Vec::new();
+        for stream in streams {
+            if messages.len() >= SyncFrame::MAX_ENTRIES {
+                break;
+            }
+            let Some(sync) = self.syncs.get_mut(&stream.id) else {
+                continue;
+            };
+            if !sync.is_due() {
+                continue;
+            }
+
+            let message = sync
+                .request
+                .as_ref()
+                .and_then(|request| sync.synchronizer.handle_request(request, stream.context()))
+                .unwrap_or_else(|| Synchronizer::create_announce(stream.context()));
+            let entry_size = SyncFrame::entry_size(stream.id.len(), message.to_bytes().len());
+            if budget > 0 && size + entry_size > budget && !messages.is_empty() {
+                continue;
+            }
+
+            size += entry_size;
+            sync.announce_due = false;
+            sync.request = None;
+            messages.push((stream.id.clone(), message));
+        }
+        messages
+    }
+
+    /// Handle a [`SyncFrame`] sent back by the receiving decoder
+    ///
+    /// Sync requests queue a diff for the next
+    /// [`sync_announces`](Self::sync_announces); a newer request for the
+    /// same channel replaces the queued one. Messages for unknown
+    /// channels and other message types are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid sync frame.
+    pub fn handle_sync(&mut self, bytes: &[u8]) -> std::result::Result<(), FrameParseError> {
+        let frame = SyncFrame::from_bytes(bytes)?;
+        for (id, message) in frame.messages {
+            let SyncMessage::Request(request) = message else {
+                continue;
+            };
+            let known = self
+                .manager
+                .iter()
+                .any(|(_, ch)| ch.streams().iter().any(|stream| stream.id == id));
+            if !known {
+                continue;
+            }
+            self.syncs
+                .entry(id)
+                .or_insert_with(|| ChannelSync::new(&self.config.sync))
+                .request = Some(request);
+        }
+        Ok(())
+    }
+
     // =====================================================================
     // Metrics API (feature-gated)
     // =====================================================================
@@ -340,10 +799,104 @@ impl Default for Gateway {
     }
 }
 
+impl HealthCheckable for Gateway {
+    fn health_check(&self) -> HealthCheck {
+        self.health_check_with(&HealthConfig::default())
+    }
+
+    /// Channels are stale when their last sample lags the newest sample
+    /// across all channels by more than `stale_channel_ms`. Faulted
+    /// channels degrade the gateway until reset. Buffer fill is measured
+    /// on the derived channels of downsampled channels.
+    fn health_check_with(&self, config: &HealthConfig) -> HealthCheck {
+        let streams: Vec<&Channel> = self
+            .manager
+            .iter()
+            .flat_map(|(_, ch)| ch.streams())
+            .collect();
+        let capacity: usize = streams.iter().map(|ch| ch.config.buffer_size).sum();
+        let fullest = streams
+            .iter()
+            .filter(|ch| ch.config.buffer_size > 0)
+            .map(|ch| {
+                (
+                    ch.id.as_str(),
+                    ch.pending() as f64 / ch.config.buffer_size as f64,
+                )
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let newest = self
+            .manager
+            .iter()
+            .filter_map(|(_, ch)| ch.last_timestamp())
+            .max();
+        let mut stale: Vec<&str> = self
+            .manager
+            .iter()
+            .filter(|(_, ch)| {
+                matches!((newest, ch.last_timestamp()), (Some(newest), Some(last))
+                    if newest - last > config.stale_channel_ms)
+            })
+            .map(|(id, _)| id.as_str())
+            .collect();
+        stale.sort_unstable();
+
+        let faulted = self.faulted_channels();
+
+        let details = format!(
+            "Buffered: {}/{}, Stale channels: {}, Faulted channels: {}, Dropped: {}",
+            self.manager.total_pending(),
+            capacity,
+            stale.len(),
+            faulted.len(),
+            self.total_dropped()
+        );
+        let fill_reason = |id: &str, fill: f64, threshold: f64| {
+            format!(
+                "Buffer fill {:.0}% on '{}' at or above {:.0}% ({})",
+                fill * 100.0,
+                id,
+                threshold * 100.0,
+                details
+            )
+        };
+
+        match fullest {
+            Some((id, fill)) if fill >= config.unhealthy_buffer_fill => HealthCheck::unhealthy(
+                "Gateway",
+                fill_reason(id, fill, config.unhealthy_buffer_fill),
+            ),
+            Some((id, fill)) if fill >= config.degraded_buffer_fill => HealthCheck::degraded(
+                "Gateway",
+                fill_reason(id, fill, config.degraded_buffer_fill),
+            ),
+            _ if !faulted.is_empty() => HealthCheck::degraded(
+                "Gateway",
+                format!("Faulted channels: {} ({})", faulted.join(", "), details),
+            ),
+            _ if !stale.is_empty() => HealthCheck::degraded(
+                "Gateway",
+                format!("Stale channels: {} ({})", stale.join(", "), details),
+            ),
+            _ => HealthCheck::healthy("Gateway").with_message(details),
+        }
+    }
+}
+
+/// Current time in milliseconds (or 0 if unavailable)
+fn wall_clock_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::GatewayError;
+    use alec::health::HealthStatus;
 
     #[test]
     fn test_gateway_new() {
@@ -358,8 +911,9 @@ mod tests {
             max_frame_size: 100,
             max_channels: 5,
             enable_checksums: false,
+            ..Default::default()
         };
-        let gateway = Gateway::with_config(config);
+        let gateway = Gateway::with_config(config).unwrap();
         assert_eq!(gateway.max_frame_size(), 100);
     }
 
@@ -490,4 +1044,63 @@ mod tests {
         gateway.set_max_frame_size(100);
         assert_eq!(gateway.max_frame_size(), 100);
     }
+
+    #[test]
+    fn test_health_check_buffer_fill() {
+        let mut gateway = Gateway::new();
+        gateway
+            .add_channel("temp", ChannelConfig::with_buffer_size(10))
+            .unwrap();
+        assert_eq!(gateway.health_check().status, HealthStatus::Healthy);
+
+        for i in 0..8 {
+            gateway.push("temp", 20.0, 1000 + i * 1000).unwrap();
+        }
+        let check = gateway.health_check();
+        assert_eq!(check.component, "Gateway");
+        assert_eq!(check.status, HealthStatus::Degraded);
+        assert!(check.message.contains("Buffer fill 80% on 'temp'"));
+        assert!(check.message.contains("Buffered: 8/10"));
+
+        for i in 8..10 {
+            gateway.push("temp", 20.0, 1000 + i * 1000).unwrap();
+        }
+        assert_eq!(gateway.health_check().status, HealthStatus::Unhealthy);
+
+        gateway.flush().unwrap();
+        assert_eq!(gateway.health_check().status, HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_health_check_stale_channel() {
+        let mut gateway = Gateway::new();
+        gateway
+            .add_channel("temp", ChannelConfig::default())
+            .unwrap();
+        gateway
+            .add_channel("humid", ChannelConfig::default())
+            .unwrap();
+        gateway.push("temp", 20.0, 1_000).unwrap();
+        gateway.push("humid", 50.0, 1_000).unwrap();
+        gateway.flush().unwrap();
+
+        gateway.push("temp", 20.0, 61_000).unwrap();
+        assert_eq!(gateway.health_check().status, HealthStatus::Healthy);
+
+        gateway.push("temp", 20.0, 61_001).unwrap();
+        let check = gateway.health_check();
+        assert_eq!(check.status, HealthStatus::Degraded);
+        assert!(
+            check.message.contains("Stale channels: humid"),
+            "{}",
+            check.message
+        );
+
+        // Thresholds come from the health configuration
+        let check = gateway.health_check_with(&HealthConfig {
+            stale_channel_ms: 120_000,
+            ..Default::default()
+        });
+        assert_eq!(check.status, HealthStatus::Healthy);
+    }
 }
@@ -20,18 +20,23 @@
 //! - **Priority-based aggregation**: Critical sensors get bandwidth first
 //! - **Frame packing**: Optimize for LoRaWAN/MQTT payload limits
 //! - **Preload support**: Load pre-trained contexts per channel
+//! - **Context sync**: Keep a remote decoder's per-channel contexts in step
+//! - **Text channels**: Status strings sent once, then as dictionary references
+//! - **Composite messages**: One shared header for the samples of many channels
+//! - **Snapshots**: Warm restart with the channels and contexts of a saved gateway
+//! - **Encryption** (feature `crypto`): Per-channel AEAD with a pre-shared key
 //!
 //! ## Quick Start
 //!
 //! ```rust
-//! use alec_gateway::{Gateway, ChannelConfig, GatewayConfig};
+//! use alec_gateway::{Gateway, ChannelConfig, GatewayConfig, LoRaRegion, TransportPreset};
 //!
-//! // Create gateway with LoRaWAN frame limit
-//! let config = GatewayConfig {
-//!     max_frame_size: 242,
-//!     ..Default::default()
-//! };
-//! let mut gateway = Gateway::with_config(config);
+//! // Create gateway with the LoRaWAN EU868 DR4 frame limit
+//! let config = GatewayConfig::for_transport(TransportPreset::Lorawan {
+//!     region: LoRaRegion::Eu868,
+//!     data_rate: 4,
+//! });
+//! let mut gateway = Gateway::with_config(config).unwrap();
 //!
 //! // Add sensor channels
 //! gateway.add_channel("temperature", ChannelConfig {
@@ -86,21 +91,43 @@
 mod aggregator;
 mod channel_manager;
 mod config;
+mod decoder;
 mod error;
 mod frame;
 mod gateway;
+mod snapshot;
+mod sync;
+mod text;
 
 // Metrics module (feature-gated)
 #[cfg(feature = "metrics")]
 pub mod metrics;
 
+// Crypto module (feature-gated)
+#[cfg(feature = "crypto")]
+pub mod crypto;
+
 // Public API
-pub use aggregator::Aggregator;
+pub use aggregator::{Aggregator, FlushEstimate, FlushReport};
 pub use channel_manager::{Channel, ChannelId, ChannelManager};
-pub use config::{ChannelConfig, GatewayConfig};
-pub use error::{GatewayError, Result};
-pub use frame::{ChannelData, Frame, FrameBuilder, FrameParseError};
+pub use config::{
+    Aggregate, BackwardsPolicy, ChannelConfig, Downsample, GatewayConfig, LoRaRegion,
+    OverflowPolicy, StarvationConfig, TimestampPolicy, TransportPreset, ValueKind,
+};
+pub use decoder::{DecodedChannel, FrameDecoder};
+pub use error::{GatewayError, Result, TimestampRule};
+pub use frame::{
+    ChannelData, ChannelDataRef, ChannelSummary, CompositeData, Frame, FrameBuilder,
+    FrameParseError, FrameRef, FrameSummary,
+};
 pub use gateway::Gateway;
+pub use snapshot::{SkippedChannel, SnapshotOptions, SnapshotReport, SNAPSHOT_VERSION};
+pub use sync::SyncFrame;
+pub use text::MAX_TEXT_LEN;
+
+// Crypto re-exports (feature-gated)
+#[cfg(feature = "crypto")]
+pub use crypto::{EncryptionAlgo, EncryptionConfig};
 
 // Metrics re-exports (feature-gated)
 #[cfg(feature = "metrics")]
@@ -15,14 +15,90 @@ use std::collections::VecDeque;
 pub struct AlignedSnapshot {
     /// Values indexed by channel order.
     pub values: Vec<f64>,
-    /// Channel IDs in order (for debugging and future extensions).
-    #[allow(dead_code)]
+    /// Channel IDs in order.
     pub channel_ids: Vec<String>,
-    /// Reference timestamp (for debugging and future extensions).
-    #[allow(dead_code)]
+    /// Reference timestamp.
     pub timestamp_ms: u64,
 }
 
+/// Aligned multi-channel sample matrix, as used by the signal estimator.
+///
+/// `values[i][j]` is channel `channels[j]` at `timestamps[i]`. Channels are
+/// sorted by ID so exports have a stable column order; a channel
+/// missing from a snapshot (under [`MissingDataPolicy::AllowPartial`]) is
+/// `NaN`. The data is a copy, independent of the sliding window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignedWindow {
+    /// Reference timestamps, one per row.
+    pub timestamps: Vec<u64>,
+    /// Channel IDs, one per column.
+    pub channels: Vec<String>,
+    /// Aligned values, one row per timestamp.
+    pub values: Vec<Vec<f64>>,
+}
+
+impl AlignedWindow {
+    pub(crate) fn from_snapshots(channels: &[String], snapshots: &[AlignedSnapshot]) -> Self {
+        let mut channels = channels.to_vec();
+        channels.sort();
+
+        let values = snapshots
+            .iter()
+            .map(|snapshot| {
+                channels
+                    .iter()
+                    .map(|id| {
+                        snapshot
+                            .channel_ids
+                            .iter()
+                            .position(|c| c == id)
+                            .map_or(f64::NAN, |k| snapshot.values[k])
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            timestamps: snapshots.iter().map(|s| s.timestamp_ms).collect(),
+            channels,
+            values,
+        }
+    }
+
+    /// Number of aligned rows.
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Check if the matrix has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// Write the matrix as CSV: a `timestamp_ms,<channels>` header, then
+    /// one line per row. Missing values are empty fields.
+    pub fn to_csv<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(writer, "timestamp_ms")?;
+        for channel in &self.channels {
+            write!(writer, ",{}", channel)?;
+        }
+        writeln!(writer)?;
+
+        for (timestamp, row) in self.timestamps.iter().zip(&self.values) {
+            write!(writer, "{}", timestamp)?;
+            for value in row {
+                if value.is_nan() {
+                    write!(writer, ",")?;
+                } else {
+                    write!(writer, ",{}", value)?;
+                }
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
 /// Aligner for creating multi-channel snapshots.
 pub struct Aligner {
     strategy: AlignmentStrategy,
@@ -128,8 +204,9 @@ impl Aligner {
                     }
                 }
 
+                // Only interpolate across a positive interval
                 match (before, after) {
-                    (Some(b), Some(a)) if b.timestamp_ms != a.timestamp_ms => {
+                    (Some(b), Some(a)) if a.timestamp_ms > b.timestamp_ms => {
                         let t_range = (a.timestamp_ms - b.timestamp_ms) as f64;
                         let t_offset = (t_ref - b.timestamp_ms) as f64;
                         let alpha = t_offset / t_range;
@@ -110,11 +110,18 @@ pub enum MissingDataPolicy {
 }
 
 /// Normalization configuration.
+///
+/// Parameters are learned online per channel; see
+/// [`MetricsEngine::normalization_state`](super::MetricsEngine::normalization_state).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalizationConfig {
     pub enabled: bool,
     pub method: NormalizationMethod,
+    /// Samples a channel needs before its values are normalized.
     pub min_samples: usize,
+    /// Include the learned parameters in every snapshot (for debugging).
+    #[serde(default)]
+    pub include_in_snapshot: bool,
 }
 
 impl Default for NormalizationConfig {
@@ -123,11 +130,12 @@ impl Default for NormalizationConfig {
             enabled: true,
             method: NormalizationMethod::ZScore,
             min_samples: 10,
+            include_in_snapshot: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NormalizationMethod {
     /// Standardize using running mean/std.
     ZScore,
@@ -183,6 +191,9 @@ pub struct ResilienceConfig {
     pub enabled: bool,
     /// Per-channel criticality via leave-one-out ΔR.
     pub criticality: CriticalityConfig,
+    /// When to run the leave-one-out criticality ranking.
+    #[serde(default)]
+    pub criticality_schedule: CriticalitySchedule,
     /// Zone thresholds for R interpretation.
     pub thresholds: ResilienceThresholds,
     /// Minimum total univariate entropy to consider R valid.
@@ -194,6 +205,7 @@ impl Default for ResilienceConfig {
         Self {
             enabled: false, // Separate opt-in
             criticality: CriticalityConfig::default(),
+            criticality_schedule: CriticalitySchedule::default(),
             thresholds: ResilienceThresholds::default(),
             min_sum_h: 0.1,
         }
@@ -206,8 +218,6 @@ pub struct CriticalityConfig {
     pub enabled: bool,
     /// Maximum channels for criticality computation.
     pub max_channels: usize,
-    /// Compute criticality every N signal computations.
-    pub every_n_signal_computes: u32,
 }
 
 impl Default for CriticalityConfig {
@@ -215,11 +225,31 @@ impl Default for CriticalityConfig {
         Self {
             enabled: true,
             max_channels: 16,
-            every_n_signal_computes: 1,
         }
     }
 }
 
+/// When to run the leave-one-out criticality ranking.
+///
+/// Leave-one-out recomputes the joint entropy once per channel, so it is
+/// scheduled independently from the signal metrics. A ranking that falls
+/// due is computed on the next flush that also computes R.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CriticalitySchedule {
+    /// Compute at most once every N flushes.
+    EveryNFlushes(u32),
+    /// Compute at most once every T milliseconds.
+    EveryMillis(u64),
+    /// Never compute the ranking.
+    Disabled,
+}
+
+impl Default for CriticalitySchedule {
+    fn default() -> Self {
+        CriticalitySchedule::EveryNFlushes(1)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResilienceThresholds {
     /// R >= this is "healthy".
@@ -288,6 +318,19 @@ mod tests {
         assert!(thresholds.healthy_min > thresholds.attention_min);
     }
 
+    #[test]
+    fn test_criticality_schedule_defaults_when_missing() {
+        let config = ResilienceConfig::default();
+        let mut json: serde_json::Value = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("criticality_schedule");
+
+        let parsed: ResilienceConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            parsed.criticality_schedule,
+            CriticalitySchedule::EveryNFlushes(1)
+        );
+    }
+
     #[test]
     fn test_numerics_config() {
         let config = NumericsConfig::default();
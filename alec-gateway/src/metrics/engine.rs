@@ -6,13 +6,18 @@
 
 //! MetricsEngine - main orchestration for metrics computation.
 
-use super::alignment::Aligner;
-use super::config::{LogBase, MetricsConfig, SignalComputeSchedule, SignalWindow};
+use super::alignment::{AlignedWindow, Aligner};
+use super::config::{
+    CriticalitySchedule, LogBase, MetricsConfig, NormalizationMethod, SignalComputeSchedule,
+    SignalWindow,
+};
+use super::normalization::NormParams;
 use super::payload::PayloadEntropyCalculator;
-use super::resilience::ResilienceCalculator;
+use super::resilience::{ResilienceCalculator, ResilienceMetrics};
 use super::signal::GaussianEntropyEstimator;
 use super::snapshot::MetricsSnapshot;
 use super::window::{SlidingWindow, WindowConfig};
+use std::collections::HashMap;
 
 /// Main metrics engine for the gateway.
 pub struct MetricsEngine {
@@ -22,12 +27,18 @@ pub struct MetricsEngine {
     signal_estimator: GaussianEntropyEstimator,
     payload_calculator: PayloadEntropyCalculator,
     resilience_calculator: ResilienceCalculator,
+    /// Learned normalization parameters per channel.
+    normalization: HashMap<String, NormParams>,
 
     // Scheduling state
     flush_count: u64,
     last_signal_compute_ms: u64,
     signal_compute_count: u64,
+    last_criticality_flush: u64,
+    last_criticality_ms: Option<u64>,
     last_snapshot: Option<MetricsSnapshot>,
+    last_resilience: Option<ResilienceMetrics>,
+    last_aligned: Option<AlignedWindow>,
 }
 
 impl MetricsEngine {
@@ -46,12 +57,18 @@ impl MetricsEngine {
             aligner: Aligner::new(config.alignment.clone(), config.missing_data.clone()),
             signal_estimator: GaussianEntropyEstimator::new(log_base, config.numerics.clone()),
             payload_calculator: PayloadEntropyCalculator::new(config.payload.clone()),
-            resilience_calculator: ResilienceCalculator::new(config.resilience.clone()),
+            resilience_calculator: ResilienceCalculator::new(config.resilience.clone())
+                .with_log_base(log_base),
+            normalization: HashMap::new(),
             config,
             flush_count: 0,
             last_signal_compute_ms: 0,
             signal_compute_count: 0,
+            last_criticality_flush: 0,
+            last_criticality_ms: None,
             last_snapshot: None,
+            last_resilience: None,
+            last_aligned: None,
         }
     }
 
@@ -62,6 +79,55 @@ impl MetricsEngine {
             return;
         }
         self.window.push(channel_id, value, timestamp_ms);
+        if let Some(method) = self.normalization_method() {
+            self.normalization
+                .entry(channel_id.to_string())
+                .or_insert_with(|| NormParams::new(method))
+                .update(value);
+        }
+    }
+
+    /// Normalize `value` with the parameters learned for `channel_id`.
+    ///
+    /// `None` until the channel has seen
+    /// [`min_samples`](super::NormalizationConfig::min_samples), or when
+    /// normalization is disabled. The scale is floored at
+    /// [`covariance_epsilon`](super::NumericsConfig::covariance_epsilon).
+    pub fn normalize(&self, channel_id: &str, value: f64) -> Option<f64> {
+        let params = self.normalization.get(channel_id)?;
+        if params.n < self.config.normalization.min_samples as u64 {
+            return None;
+        }
+        Some(params.normalize(value, self.config.numerics.covariance_epsilon))
+    }
+
+    /// Learned normalization parameters per channel.
+    ///
+    /// Export them before a restart and pass them to
+    /// [`import_normalization_state`](Self::import_normalization_state) so
+    /// normalized values stay continuous instead of re-learning.
+    pub fn normalization_state(&self) -> HashMap<String, NormParams> {
+        self.normalization.clone()
+    }
+
+    /// Restore parameters exported by
+    /// [`normalization_state`](Self::normalization_state), replacing
+    /// those learned for the same channels.
+    ///
+    /// Parameters learned with another method than the configured one
+    /// are skipped. Returns how many channels were restored.
+    pub fn import_normalization_state(&mut self, state: HashMap<String, NormParams>) -> usize {
+        let Some(method) = self.normalization_method() else {
+            return 0;
+        };
+        let mut imported = 0;
+        for (channel_id, params) in state {
+            if params.method == method {
+                self.normalization.insert(channel_id, params);
+                imported += 1;
+            }
+        }
+        imported
     }
 
     /// Observe a flushed frame (called from Gateway::flush).
@@ -93,30 +159,45 @@ impl MetricsEngine {
 
             // Align samples
             let snapshots = self.aligner.align(&self.window, &reference_times);
+            self.last_aligned = (!channel_ids.is_empty()
+                && channel_ids.len() <= self.config.numerics.max_channels_for_joint)
+                .then(|| AlignedWindow::from_snapshots(&channel_ids, &snapshots));
 
             // Compute signal metrics
             let signal = self.signal_estimator.compute(&snapshots, &channel_ids);
 
             // Compute resilience if signal is valid
-            let resilience = signal.as_ref().and_then(|s| {
-                let r = self.resilience_calculator.compute(s)?;
-
-                // Compute criticality if enabled and on schedule
-                let should_compute_crit = self.config.resilience.criticality.enabled
-                    && (self.signal_compute_count
-                        % self.config.resilience.criticality.every_n_signal_computes as u64
-                        == 0);
-
-                if should_compute_crit {
-                    let criticality = self.resilience_calculator.compute_criticality(s, r.r);
-                    Some(super::resilience::ResilienceMetrics { criticality, ..r })
-                } else {
-                    Some(r)
+            let mut resilience = signal
+                .as_ref()
+                .and_then(|s| self.resilience_calculator.compute(s));
+
+            // Compute criticality if enabled and on schedule
+            if let (Some(s), Some(r)) = (signal.as_ref(), resilience.as_mut()) {
+                if self.should_compute_criticality(current_time_ms) {
+                    self.last_criticality_flush = self.flush_count;
+                    self.last_criticality_ms = Some(current_time_ms);
+
+                    // Leave-one-out only for channels with enough samples
+                    // of their own in the window
+                    let min_samples = self.config.numerics.min_aligned_samples;
+                    let window = &self.window;
+                    let estimator = &self.signal_estimator;
+                    r.criticality = self.resilience_calculator.compute_criticality(s, r.r, |k| {
+                        let samples = window.get_samples(&channel_ids[k]).map_or(0, |v| v.len());
+                        if samples < min_samples {
+                            return None;
+                        }
+                        estimator.compute_without(&snapshots, &channel_ids, k)
+                    });
                 }
-            });
+            }
+            if resilience.is_some() {
+                self.last_resilience = resilience.clone();
+            }
 
             (signal, resilience)
         } else {
+            self.last_aligned = None;
             (None, None)
         };
 
@@ -135,7 +216,7 @@ impl MetricsEngine {
 
         let flags = self.build_flags();
 
-        let snapshot = MetricsSnapshot::new(
+        let mut snapshot = MetricsSnapshot::new(
             current_time_ms,
             window_kind,
             window_value,
@@ -146,6 +227,15 @@ impl MetricsEngine {
             flags,
         );
 
+        if self.config.normalization.include_in_snapshot {
+            snapshot.normalization = Some(
+                self.normalization
+                    .iter()
+                    .map(|(id, params)| (id.clone(), *params))
+                    .collect(),
+            );
+        }
+
         self.last_snapshot = Some(snapshot.clone());
         Some(snapshot)
     }
@@ -155,6 +245,23 @@ impl MetricsEngine {
         self.last_snapshot.as_ref()
     }
 
+    /// Get the last computed resilience metrics.
+    ///
+    /// Use [`ResilienceMetrics::criticality_ranking`] for the channels
+    /// whose loss would hurt the most.
+    pub fn last_resilience(&self) -> Option<&ResilienceMetrics> {
+        self.last_resilience.as_ref()
+    }
+
+    /// Get the aligned sample matrix of the last flush.
+    ///
+    /// Only populated when signal metrics were computed on the last flush,
+    /// and `None` when the channel count exceeds
+    /// [`NumericsConfig::max_channels_for_joint`](super::NumericsConfig::max_channels_for_joint).
+    pub fn aligned_window(&self) -> Option<&AlignedWindow> {
+        self.last_aligned.as_ref()
+    }
+
     /// Check if metrics are enabled.
     pub fn is_enabled(&self) -> bool {
         self.config.enabled
@@ -175,13 +282,25 @@ impl MetricsEngine {
         self.signal_compute_count
     }
 
+    /// Get the number of samples currently held in the sliding window.
+    pub fn window_sample_count(&self) -> usize {
+        self.window.total_samples()
+    }
+
     /// Clear all accumulated data and reset counters.
+    ///
+    /// Learned normalization parameters are cleared too.
     pub fn reset(&mut self) {
         self.window.clear();
+        self.normalization.clear();
         self.flush_count = 0;
         self.last_signal_compute_ms = 0;
         self.signal_compute_count = 0;
+        self.last_criticality_flush = 0;
+        self.last_criticality_ms = None;
         self.last_snapshot = None;
+        self.last_resilience = None;
+        self.last_aligned = None;
     }
 
     /// Pre-register a channel in the metrics engine.
@@ -190,6 +309,13 @@ impl MetricsEngine {
         self.window.register_channel(channel_id);
     }
 
+    /// Method channels learn with, if normalization is on.
+    fn normalization_method(&self) -> Option<NormalizationMethod> {
+        let normalization = &self.config.normalization;
+        (normalization.enabled && normalization.method != NormalizationMethod::None)
+            .then_some(normalization.method)
+    }
+
     fn should_compute_signal(&self, current_time_ms: u64) -> bool {
         match &self.config.signal_compute {
             SignalComputeSchedule::EveryNFlushes(n) => self.flush_count % (*n as u64) == 0,
@@ -203,6 +329,21 @@ impl MetricsEngine {
         }
     }
 
+    fn should_compute_criticality(&self, current_time_ms: u64) -> bool {
+        if !self.config.resilience.criticality.enabled {
+            return false;
+        }
+        match &self.config.resilience.criticality_schedule {
+            CriticalitySchedule::EveryNFlushes(n) => {
+                self.flush_count - self.last_criticality_flush >= (*n).max(1) as u64
+            }
+            CriticalitySchedule::EveryMillis(ms) => self
+                .last_criticality_ms
+                .map_or(true, |last| current_time_ms >= last + ms),
+            CriticalitySchedule::Disabled => false,
+        }
+    }
+
     fn generate_reference_times(&self, current_time_ms: u64) -> Vec<u64> {
         // Generate evenly spaced reference times within the window
         let window_ms = match self.config.signal_window {
@@ -417,6 +558,30 @@ mod tests {
         assert!(flags.iter().any(|f| f == "RESILIENCE_ENABLED"));
     }
 
+    #[test]
+    fn test_criticality_schedule() {
+        let mut config = create_enabled_config();
+        config.resilience.criticality_schedule = CriticalitySchedule::EveryNFlushes(3);
+        let mut engine = MetricsEngine::new(config);
+
+        engine.flush_count = 2;
+        assert!(!engine.should_compute_criticality(1000));
+        engine.flush_count = 3;
+        assert!(engine.should_compute_criticality(1000));
+        engine.last_criticality_flush = 3;
+        engine.flush_count = 5;
+        assert!(!engine.should_compute_criticality(1000));
+
+        engine.config.resilience.criticality_schedule = CriticalitySchedule::EveryMillis(5000);
+        assert!(engine.should_compute_criticality(1000));
+        engine.last_criticality_ms = Some(1000);
+        assert!(!engine.should_compute_criticality(4000));
+        assert!(engine.should_compute_criticality(6000));
+
+        engine.config.resilience.criticality_schedule = CriticalitySchedule::Disabled;
+        assert!(!engine.should_compute_criticality(100_000));
+    }
+
     #[test]
     fn test_generate_reference_times() {
         let config = MetricsConfig {
@@ -62,6 +62,7 @@
 mod alignment;
 mod config;
 mod engine;
+mod normalization;
 mod payload;
 mod resilience;
 mod signal;
@@ -69,8 +70,10 @@ mod snapshot;
 mod window;
 
 // Re-export public API
+pub use alignment::AlignedWindow;
 pub use config::*;
 pub use engine::MetricsEngine;
+pub use normalization::NormParams;
 pub use payload::{ChannelPayloadMetrics, PayloadMetrics};
 pub use resilience::{ChannelCriticality, ResilienceMetrics, ResilienceZone};
 pub use signal::{ChannelEntropy, SignalMetrics};
@@ -0,0 +1,157 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Online per-channel normalization.
+//!
+//! Each channel learns a location and a scale from every sample it
+//! observes, without keeping the samples:
+//!
+//! - **ZScore**: running mean and sample standard deviation (Welford).
+//! - **RobustMad**: a clipped running mean approximating the median, and
+//!   a clipped running mean absolute deviation approximating the MAD,
+//!   rescaled to standard-deviation units. Deviations beyond
+//!   [`ROBUST_CLIP`] scales count as that many scales, so a single
+//!   outlier moves neither estimate by more than `ROBUST_CLIP * scale / n`.
+//!
+//! Both are deterministic: the same samples in the same order always give
+//! the same parameters, and the parameters alone ([`NormParams`]) are
+//! enough to resume learning, so exported state imported into a fresh
+//! engine normalizes exactly as before.
+
+use super::config::NormalizationMethod;
+use serde::{Deserialize, Serialize};
+
+/// Deviations, in scales, beyond which RobustMad clips a sample.
+pub const ROBUST_CLIP: f64 = 3.0;
+
+/// Mean absolute deviation to standard deviation for Gaussian data (√(π/2)).
+const MEAN_ABS_DEV_TO_STD: f64 = 1.253_314_137_315_500_3;
+
+/// Learned normalization parameters of one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NormParams {
+    /// Method the parameters were learned with.
+    pub method: NormalizationMethod,
+    /// Mean (ZScore) or approximate median (RobustMad).
+    pub location: f64,
+    /// Standard deviation, or its robust estimate; 0.0 until learned.
+    pub scale: f64,
+    /// Samples learned from.
+    pub n: u64,
+}
+
+impl NormParams {
+    /// Parameters before any sample.
+    pub fn new(method: NormalizationMethod) -> Self {
+        Self {
+            method,
+            location: 0.0,
+            scale: 0.0,
+            n: 0,
+        }
+    }
+
+    /// Learn from one sample.
+    ///
+    /// Non-finite samples are ignored.
+    pub fn update(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.n += 1;
+        let n = self.n as f64;
+        match self.method {
+            NormalizationMethod::ZScore => {
+                // Welford, with M2 recovered from the sample std
+                let mut m2 = self.scale * self.scale * (n - 2.0).max(0.0);
+                let delta = value - self.location;
+                self.location += delta / n;
+                m2 += delta * (value - self.location);
+                self.scale = if self.n > 1 {
+                    (m2 / (n - 1.0)).max(0.0).sqrt()
+                } else {
+                    0.0
+                };
+            }
+            NormalizationMethod::RobustMad => {
+                if self.n == 1 {
+                    self.location = value;
+                    self.scale = 0.0;
+                    return;
+                }
+                let deviation = value - self.location;
+                // No clipping until a scale is learned
+                let clipped = if self.scale > 0.0 {
+                    let bound = ROBUST_CLIP * self.scale;
+                    deviation.clamp(-bound, bound)
+                } else {
+                    deviation
+                };
+                self.location += clipped / n;
+                let spread = clipped.abs() * MEAN_ABS_DEV_TO_STD;
+                // The first deviation is measured from a single sample
+                self.scale += (spread - self.scale) / (n - 1.0);
+            }
+            NormalizationMethod::None => {}
+        }
+    }
+
+    /// Normalize `value`, with the scale floored at `min_scale` so
+    /// constant channels don't blow up.
+    pub fn normalize(&self, value: f64, min_scale: f64) -> f64 {
+        match self.method {
+            NormalizationMethod::None => value,
+            _ => (value - self.location) / self.scale.max(min_scale),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn learn(method: NormalizationMethod, values: &[f64]) -> NormParams {
+        let mut params = NormParams::new(method);
+        for &v in values {
+            params.update(v);
+        }
+        params
+    }
+
+    #[test]
+    fn test_zscore_matches_batch() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let params = learn(NormalizationMethod::ZScore, &values);
+        assert_eq!(params.n, 8);
+        assert!((params.location - 5.0).abs() < 1e-12);
+        // Sample std: sqrt(32 / 7)
+        assert!((params.scale - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_robust_mad_resists_outlier() {
+        let mut values: Vec<f64> = (0..200).map(|i| 20.0 + (i % 5) as f64 * 0.5).collect();
+        let clean = learn(NormalizationMethod::RobustMad, &values);
+        values[150] = 1.0e6;
+        let spiked = learn(NormalizationMethod::RobustMad, &values);
+
+        assert!((clean.location - 21.0).abs() < 0.5);
+        assert!((spiked.location - clean.location).abs() < 0.1);
+        assert!((spiked.scale - clean.scale).abs() < 0.1);
+        // Deterministic
+        assert_eq!(spiked, learn(NormalizationMethod::RobustMad, &values));
+    }
+
+    #[test]
+    fn test_constant_channel_floor() {
+        let params = learn(NormalizationMethod::ZScore, &[3.0; 50]);
+        assert_eq!(params.scale, 0.0);
+        assert_eq!(params.normalize(3.0, 1e-8), 0.0);
+        let z = params.normalize(3.5, 1e-8);
+        assert!(z.is_finite());
+        assert_eq!(z, 0.5 / 1e-8);
+    }
+}
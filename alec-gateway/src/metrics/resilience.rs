@@ -6,7 +6,7 @@
 
 //! Resilience index (R) and criticality computation.
 
-use super::config::ResilienceConfig;
+use super::config::{LogBase, ResilienceConfig};
 use super::signal::SignalMetrics;
 
 /// Resilience zone classification.
@@ -38,21 +38,58 @@ pub struct ResilienceMetrics {
     pub criticality: Option<Vec<ChannelCriticality>>,
 }
 
+impl ResilienceMetrics {
+    /// Channels ranked by the impact of losing them, most critical first.
+    ///
+    /// Empty when criticality was not computed for this result. Channels
+    /// with insufficient data come last.
+    pub fn criticality_ranking(&self) -> Vec<ChannelCriticality> {
+        self.criticality.clone().unwrap_or_default()
+    }
+}
+
+/// Leave-one-out result for one channel.
 #[derive(Debug, Clone)]
 pub struct ChannelCriticality {
     pub channel_id: String,
-    /// ΔR = R_all - R_without_this_channel
-    pub delta_r: f64,
+    /// ΔR = R_all - R_without_this_channel.
+    ///
+    /// `None` when the channel had too few samples in the window.
+    pub delta_r: Option<f64>,
+    /// Fraction of the channel's variance the other channels cannot
+    /// explain, in [0, 1]: 1 for an independent channel, 0 for an exact
+    /// duplicate. Ranking key: losing a high-impact channel loses
+    /// information no other channel carries.
+    ///
+    /// `None` when the channel had too few samples in the window.
+    pub impact: Option<f64>,
+}
+
+impl ChannelCriticality {
+    /// Check if the channel was skipped for lack of samples.
+    pub fn insufficient_data(&self) -> bool {
+        self.impact.is_none()
+    }
 }
 
 /// Resilience calculator.
 pub struct ResilienceCalculator {
     config: ResilienceConfig,
+    log_base: LogBase,
 }
 
 impl ResilienceCalculator {
     pub fn new(config: ResilienceConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            log_base: LogBase::Two,
+        }
+    }
+
+    /// Set the log base of the entropies passed in (defaults to bits).
+    pub fn with_log_base(mut self, log_base: LogBase) -> Self {
+        self.log_base = log_base;
+        self
     }
 
     /// Compute resilience metrics from signal metrics.
@@ -67,11 +104,7 @@ impl ResilienceCalculator {
         }
 
         // R = TC / Σ H_i = 1 - H_joint / Σ H_i
-        let r = if signal.sum_h > 0.0 {
-            (signal.total_correlation / signal.sum_h).clamp(0.0, 1.0)
-        } else {
-            0.0
-        };
+        let r = self.r_index(signal);
 
         let zone = self.classify_zone(r);
 
@@ -87,11 +120,19 @@ impl ResilienceCalculator {
 
     /// Compute criticality ranking via leave-one-out analysis.
     /// This is expensive and should be rate-limited.
-    pub fn compute_criticality(
+    ///
+    /// `without(k)` returns the signal metrics recomputed without channel
+    /// `k`, or `None` if channel `k` has too few samples, in which case it
+    /// is reported as insufficient data.
+    pub fn compute_criticality<F>(
         &self,
         signal: &SignalMetrics,
         r_all: f64,
-    ) -> Option<Vec<ChannelCriticality>> {
+        mut without: F,
+    ) -> Option<Vec<ChannelCriticality>>
+    where
+        F: FnMut(usize) -> Option<SignalMetrics>,
+    {
         if !self.config.criticality.enabled {
             return None;
         }
@@ -101,36 +142,60 @@ impl ResilienceCalculator {
             return None;
         }
 
-        let mut criticality = Vec::with_capacity(n);
-
-        for channel in &signal.h_per_channel {
-            // Compute R without this channel
-            // R_without_k = (TC - contribution_k) / (sum_h - h_k)
-            // Approximation: assume TC scales proportionally
-            let sum_h_without = signal.sum_h - channel.h;
-            if sum_h_without > self.config.min_sum_h {
-                // Simplified: assume TC contribution is proportional
-                // More accurate would require recomputing joint entropy
-                let tc_without_approx = signal.total_correlation * (sum_h_without / signal.sum_h);
-                let r_without = tc_without_approx / sum_h_without;
-                let delta_r = r_all - r_without;
-
-                criticality.push(ChannelCriticality {
+        let mut ranked = Vec::with_capacity(n);
+        let mut insufficient = Vec::new();
+
+        for (k, channel) in signal.h_per_channel.iter().enumerate() {
+            let Some(rest) = without(k) else {
+                insufficient.push(ChannelCriticality {
                     channel_id: channel.channel_id.clone(),
-                    delta_r,
+                    delta_r: None,
+                    impact: None,
                 });
-            }
+                continue;
+            };
+
+            let r_without = self.r_index(&rest);
+
+            // I(X_k; X_rest) = H(X_k) + H(X_rest) - H(X_all); for Gaussians
+            // the unexplained variance fraction is exp(-2 I) with I in nats
+            let mutual_info = channel.h + rest.h_joint - signal.h_joint;
+            let impact = (-2.0 * self.to_nats(mutual_info)).exp().clamp(0.0, 1.0);
+
+            ranked.push(ChannelCriticality {
+                channel_id: channel.channel_id.clone(),
+                delta_r: Some(r_all - r_without),
+                impact: Some(impact),
+            });
         }
 
-        // Sort by absolute delta_r descending (most critical first)
-        criticality.sort_by(|a, b| {
-            b.delta_r
-                .abs()
-                .partial_cmp(&a.delta_r.abs())
+        // Most critical first; |ΔR| breaks ties
+        ranked.sort_by(|a, b| {
+            let key =
+                |c: &ChannelCriticality| (c.impact.unwrap_or(0.0), c.delta_r.unwrap_or(0.0).abs());
+            key(b)
+                .partial_cmp(&key(a))
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        ranked.extend(insufficient);
+
+        Some(ranked)
+    }
+
+    /// R = TC / Σ H_i, clamped to [0, 1].
+    fn r_index(&self, signal: &SignalMetrics) -> f64 {
+        if signal.sum_h > 0.0 {
+            (signal.total_correlation / signal.sum_h).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
 
-        Some(criticality)
+    fn to_nats(&self, value: f64) -> f64 {
+        match self.log_base {
+            LogBase::E => value,
+            LogBase::Two => value * std::f64::consts::LN_2,
+        }
     }
 
     fn classify_zone(&self, r: f64) -> ResilienceZone {
@@ -321,6 +386,22 @@ mod tests {
         assert!(result.r >= 0.0 && result.r <= 1.0);
     }
 
+    /// Signal metrics of `signal` without channel `k`, with the given
+    /// joint entropy.
+    fn without_channel(signal: &SignalMetrics, k: usize, h_joint: f64) -> SignalMetrics {
+        let mut h_per_channel = signal.h_per_channel.clone();
+        let removed = h_per_channel.remove(k);
+        let sum_h = signal.sum_h - removed.h;
+        SignalMetrics {
+            h_per_channel,
+            sum_h,
+            h_joint,
+            total_correlation: (sum_h - h_joint).max(0.0),
+            aligned_samples: signal.aligned_samples,
+            channels_included: signal.channels_included - 1,
+        }
+    }
+
     #[test]
     fn test_criticality_computation() {
         let config = ResilienceConfig {
@@ -328,7 +409,6 @@ mod tests {
             criticality: super::super::config::CriticalityConfig {
                 enabled: true,
                 max_channels: 16,
-                every_n_signal_computes: 1,
             },
             ..Default::default()
         };
@@ -336,9 +416,16 @@ mod tests {
         let signal = create_test_signal();
 
         let r = signal.total_correlation / signal.sum_h;
-        let criticality = calculator.compute_criticality(&signal, r).unwrap();
+        let criticality = calculator
+            .compute_criticality(&signal, r, |k| Some(without_channel(&signal, k, 3.0)))
+            .unwrap();
 
         assert_eq!(criticality.len(), 3);
+        // Dropping any channel leaves TC = 4 - 3 = 1 over ΣH = 4
+        let delta_r = criticality[0].delta_r.unwrap();
+        assert!((delta_r - (2.0 / 6.0 - 1.0 / 4.0)).abs() < 1e-9);
+        // I = 2 + 3 - 4 = 1 bit, so a quarter of the variance is unexplained
+        assert!((criticality[0].impact.unwrap() - 0.25).abs() < 1e-9);
     }
 
     #[test]
@@ -354,18 +441,17 @@ mod tests {
         let calculator = ResilienceCalculator::new(config);
         let signal = create_test_signal();
 
-        let criticality = calculator.compute_criticality(&signal, 0.33);
+        let criticality = calculator.compute_criticality(&signal, 0.33, |_| None);
         assert!(criticality.is_none());
     }
 
     #[test]
-    fn test_criticality_sorted_by_delta_r() {
+    fn test_criticality_sorted_by_impact() {
         let config = ResilienceConfig {
             enabled: true,
             criticality: super::super::config::CriticalityConfig {
                 enabled: true,
                 max_channels: 16,
-                every_n_signal_computes: 1,
             },
             min_sum_h: 0.1,
             ..Default::default()
@@ -394,15 +480,45 @@ mod tests {
             channels_included: 3,
         };
 
+        // Mutual information with the rest: ch1 0.9, ch2 0.2, ch3 0.5 bits
+        let joints = [3.9, 1.2, 2.5];
         let r = signal.total_correlation / signal.sum_h;
-        let criticality = calculator.compute_criticality(&signal, r).unwrap();
+        let criticality = calculator
+            .compute_criticality(&signal, r, |k| Some(without_channel(&signal, k, joints[k])))
+            .unwrap();
 
-        // Should be sorted by absolute delta_r descending
+        let order: Vec<&str> = criticality.iter().map(|c| c.channel_id.as_str()).collect();
+        assert_eq!(order, ["ch2", "ch3", "ch1"]);
         for i in 1..criticality.len() {
-            assert!(criticality[i - 1].delta_r.abs() >= criticality[i].delta_r.abs());
+            assert!(criticality[i - 1].impact >= criticality[i].impact);
         }
     }
 
+    #[test]
+    fn test_criticality_insufficient_data_ranked_last() {
+        let config = ResilienceConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let calculator = ResilienceCalculator::new(config);
+        let signal = create_test_signal();
+
+        let result = ResilienceMetrics {
+            r: 0.33,
+            zone: ResilienceZone::Attention,
+            criticality: calculator.compute_criticality(&signal, 0.33, |k| {
+                (k != 0).then(|| without_channel(&signal, k, 3.5))
+            }),
+        };
+
+        let ranking = result.criticality_ranking();
+        assert_eq!(ranking.len(), 3);
+        assert_eq!(ranking[2].channel_id, "ch1");
+        assert!(ranking[2].insufficient_data());
+        assert!(ranking[2].delta_r.is_none());
+        assert!(!ranking[0].insufficient_data());
+    }
+
     #[test]
     fn test_zone_as_str() {
         assert_eq!(ResilienceZone::Healthy.as_str(), "healthy");
@@ -109,6 +109,39 @@ impl GaussianEntropyEstimator {
         })
     }
 
+    /// Compute signal metrics with one channel left out.
+    ///
+    /// Used by the leave-one-out criticality ranking.
+    pub fn compute_without(
+        &self,
+        snapshots: &[AlignedSnapshot],
+        channel_ids: &[String],
+        excluded: usize,
+    ) -> Option<SignalMetrics> {
+        let reduced: Vec<AlignedSnapshot> = snapshots
+            .iter()
+            .map(|snapshot| AlignedSnapshot {
+                values: snapshot
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != excluded)
+                    .map(|(_, v)| *v)
+                    .collect(),
+                channel_ids: Vec::new(),
+                timestamp_ms: snapshot.timestamp_ms,
+            })
+            .collect();
+        let ids: Vec<String> = channel_ids
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != excluded)
+            .map(|(_, id)| id.clone())
+            .collect();
+
+        self.compute(&reduced, &ids)
+    }
+
     /// Gaussian entropy for 1D: H(X) = 0.5 * log(2πeσ²)
     fn gaussian_entropy_1d(&self, variance: f64) -> f64 {
         if variance <= 0.0 {
@@ -251,6 +284,25 @@ mod tests {
         assert!(result.total_correlation >= 0.0);
     }
 
+    #[test]
+    fn test_compute_without_drops_channel() {
+        let numerics = NumericsConfig {
+            min_aligned_samples: 10,
+            ..Default::default()
+        };
+        let estimator = GaussianEntropyEstimator::new(LogBase::Two, numerics);
+
+        let snapshots = create_test_snapshots(50, 3);
+        let channel_ids = vec!["ch0".to_string(), "ch1".to_string(), "ch2".to_string()];
+
+        let result = estimator
+            .compute_without(&snapshots, &channel_ids, 1)
+            .unwrap();
+        assert_eq!(result.channels_included, 2);
+        assert_eq!(result.h_per_channel[0].channel_id, "ch0");
+        assert_eq!(result.h_per_channel[1].channel_id, "ch2");
+    }
+
     #[test]
     fn test_variance_computation() {
         let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
@@ -6,10 +6,12 @@
 
 //! MetricsSnapshot serialization for export.
 
+use super::normalization::NormParams;
 use super::payload::PayloadMetrics;
 use super::resilience::ResilienceMetrics;
 use super::signal::SignalMetrics;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Complete metrics snapshot for export.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +31,10 @@ pub struct MetricsSnapshot {
     pub resilience: Option<ResilienceSnapshot>,
     /// Computation flags.
     pub flags: Vec<String>,
+    /// Learned normalization parameters per channel, when
+    /// `NormalizationConfig::include_in_snapshot` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<BTreeMap<String, NormParams>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,7 +102,13 @@ pub struct CriticalitySnapshot {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CriticalityRankingJson {
     pub channel_id: String,
-    pub delta_r: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_r: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<f64>,
+    /// Channel skipped: fewer samples than `min_aligned_samples`.
+    #[serde(default)]
+    pub insufficient_data: bool,
 }
 
 impl MetricsSnapshot {
@@ -179,9 +191,13 @@ impl MetricsSnapshot {
                     .map(|c| CriticalityRankingJson {
                         channel_id: c.channel_id.clone(),
                         delta_r: c.delta_r,
+                        impact: c.impact,
+                        insufficient_data: c.insufficient_data(),
                     })
                     .collect(),
-                note: "delta_r = R_all - R_without_channel (leave-one-out)".to_string(),
+                note: "ranked by impact = unexplained variance fraction; \
+                       delta_r = R_all - R_without_channel (leave-one-out)"
+                    .to_string(),
             }),
         });
 
@@ -198,6 +214,7 @@ impl MetricsSnapshot {
             payload: payload_snapshot,
             resilience: resilience_snapshot,
             flags,
+            normalization: None,
         }
     }
 
@@ -320,6 +337,51 @@ mod tests {
         assert_eq!(res.zone, Some("attention".to_string()));
     }
 
+    #[test]
+    fn test_snapshot_criticality_ranking_json() {
+        use crate::metrics::resilience::ChannelCriticality;
+
+        let payload = create_test_payload();
+        let signal = create_test_signal();
+        let resilience = ResilienceMetrics {
+            r: 0.33,
+            zone: ResilienceZone::Attention,
+            criticality: Some(vec![
+                ChannelCriticality {
+                    channel_id: "ch2".to_string(),
+                    delta_r: Some(0.1),
+                    impact: Some(0.9),
+                },
+                ChannelCriticality {
+                    channel_id: "ch1".to_string(),
+                    delta_r: None,
+                    impact: None,
+                },
+            ]),
+        };
+
+        let snapshot = MetricsSnapshot::new(
+            1234567890,
+            "time_ms",
+            60_000,
+            Some(&signal),
+            &payload,
+            Some(&resilience),
+            "log2",
+            vec![],
+        );
+
+        let json = snapshot.to_json_compact().unwrap();
+        assert!(json.contains("\"impact\":0.9"));
+        assert!(json.contains("\"insufficient_data\":true"));
+
+        let parsed = MetricsSnapshot::from_json(&json).unwrap();
+        let ranking = parsed.resilience.unwrap().criticality.unwrap().ranking;
+        assert_eq!(ranking[0].channel_id, "ch2");
+        assert!(!ranking[0].insufficient_data);
+        assert_eq!(ranking[1].delta_r, None);
+    }
+
     #[test]
     fn test_json_serialization() {
         let payload = create_test_payload();
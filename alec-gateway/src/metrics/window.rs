@@ -39,8 +39,17 @@ impl SlidingWindow {
     }
 
     /// Add a sample to a channel's window.
-    pub fn push(&mut self, channel_id: &str, value: f64, timestamp_ms: u64) {
+    ///
+    /// Samples older than the channel's latest one are skipped so the
+    /// window stays ordered. Returns false if the sample was skipped.
+    pub fn push(&mut self, channel_id: &str, value: f64, timestamp_ms: u64) -> bool {
         let samples = self.channels.entry(channel_id.to_string()).or_default();
+        if samples
+            .back()
+            .is_some_and(|last| timestamp_ms < last.timestamp_ms)
+        {
+            return false;
+        }
 
         samples.push_back(Sample {
             value,
@@ -49,6 +58,7 @@ impl SlidingWindow {
 
         // Prune based on config
         self.prune_channel(channel_id, timestamp_ms);
+        true
     }
 
     /// Get all samples for a channel within the window.
@@ -68,7 +78,6 @@ impl SlidingWindow {
     }
 
     /// Get total sample count across all channels.
-    #[allow(dead_code)]
     pub fn total_samples(&self) -> usize {
         self.channels.values().map(|s| s.len()).sum()
     }
@@ -204,6 +213,23 @@ mod tests {
         assert_eq!(max, 5000);
     }
 
+    #[test]
+    fn test_window_skips_backwards_samples() {
+        let mut window = SlidingWindow::new(WindowConfig::TimeMillis(60_000));
+        assert!(window.push("ch1", 1.0, 2000));
+        assert!(!window.push("ch1", 2.0, 1000));
+        assert!(window.push("ch1", 3.0, 2000));
+        assert!(window.push("ch2", 4.0, 1000));
+
+        let timestamps: Vec<u64> = window
+            .get_samples("ch1")
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp_ms)
+            .collect();
+        assert_eq!(timestamps, vec![2000, 2000]);
+    }
+
     #[test]
     fn test_window_clear() {
         let mut window = SlidingWindow::new(WindowConfig::TimeMillis(60_000));
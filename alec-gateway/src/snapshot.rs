@@ -0,0 +1,690 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Whole-gateway snapshots
+//!
+//! [`Gateway::save_snapshot`](crate::Gateway::save_snapshot) writes the
+//! gateway configuration, every channel with its configuration, each
+//! stream's context and encoder sequence, the frame sequence and,
+//! optionally, the buffered samples. [`Gateway::load_snapshot`] rebuilds a
+//! gateway from it that keeps compressing where the saved one left off.
+//!
+//! # Snapshot Format
+//!
+//! ```text
+//! [magic: "ALGS"] [version: 1] [flags: 1] [frame_sequence: 8 LE]
+//! [gateway_config] [channel_count: 2 LE] [channel...]
+//!
+//! channel:
+//! [record_len: 4 LE] [id_len: 2 LE] [id: N] [channel_config]
+//! [last_timestamp: opt u64] [stream_count: 1] [stream...]
+//!
+//! stream:
+//! [id_len: 2 LE] [id: N] [sequence: 2 LE] [context_len: 4 LE] [context: M]
+//! [buffer_len: 4 LE] [(value: f64 LE, timestamp: 8 LE)...]      flag 0x02
+//! [text_len: 4 LE] [(len: 1, bytes, timestamp: 8 LE)...]         flag 0x02
+//! ```
+//!
+//! Contexts use the preload format
+//! ([`Context::to_preload_bytes`](alec::Context::to_preload_bytes)), whose
+//! checksum validates them on load. Flag `0x01` records that metrics were
+//! enabled; their state is not saved. Each channel record is
+//! length-prefixed, so a channel that fails to restore is skipped and
+//! reported in [`SnapshotReport::skipped`] without affecting the others.
+//!
+//! The open bucket and raw window of a downsampled channel, sync state,
+//! drop and fault counters are not saved. Neither are encryption keys: an
+//! encrypted channel is skipped on load and must be added again with its
+//! key.
+//!
+//! [`Gateway::load_snapshot`]: crate::Gateway::load_snapshot
+
+use alec::sync::SyncConfig;
+use alec::Context;
+
+use crate::channel_manager::Channel;
+use crate::config::{
+    Aggregate, BackwardsPolicy, ChannelConfig, Downsample, GatewayConfig, LoRaRegion,
+    OverflowPolicy, StarvationConfig, TimestampPolicy, TransportPreset, ValueKind,
+};
+use crate::error::{GatewayError, Result};
+
+/// Start of every snapshot
+const MAGIC: &[u8; 4] = b"ALGS";
+
+/// Snapshot format version
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// Metrics were enabled on the saved gateway
+const FLAG_METRICS: u8 = 0x01;
+
+/// Streams carry their buffered samples
+const FLAG_BUFFERS: u8 = 0x02;
+
+/// What [`Gateway::save_snapshot_with`](crate::Gateway::save_snapshot_with)
+/// writes beyond the configuration and contexts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// Save samples buffered but not yet flushed (default: off, as they
+    /// can be large)
+    pub include_buffers: bool,
+}
+
+/// Outcome of [`Gateway::load_snapshot`](crate::Gateway::load_snapshot)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotReport {
+    /// Format version of the snapshot
+    pub version: u8,
+    /// Metrics were enabled on the saved gateway
+    ///
+    /// They are not re-enabled: call `enable_metrics` (feature `metrics`)
+    /// with the wanted configuration.
+    pub metrics_enabled: bool,
+    /// The snapshot carried buffered samples
+    pub buffers_restored: bool,
+    /// Channels restored
+    pub restored: usize,
+    /// Channels left out, with the reason
+    pub skipped: Vec<SkippedChannel>,
+}
+
+/// Channel of a snapshot that could not be restored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedChannel {
+    /// Channel identifier
+    pub id: String,
+    /// Why it was skipped
+    pub reason: String,
+}
+
+/// Parsed snapshot, before its channels join a gateway
+pub(crate) struct Snapshot {
+    pub(crate) config: GatewayConfig,
+    pub(crate) frame_sequence: u64,
+    pub(crate) report: SnapshotReport,
+    pub(crate) channels: Vec<Channel>,
+}
+
+/// Serialize a gateway's state
+pub(crate) fn write<'a>(
+    config: &GatewayConfig,
+    frame_sequence: u64,
+    metrics_enabled: bool,
+    options: &SnapshotOptions,
+    channels: impl IntoIterator<Item = &'a Channel>,
+) -> Result<Vec<u8>> {
+    let mut flags = 0;
+    if metrics_enabled {
+        flags |= FLAG_METRICS;
+    }
+    if options.include_buffers {
+        flags |= FLAG_BUFFERS;
+    }
+
+    let mut out = MAGIC.to_vec();
+    out.push(SNAPSHOT_VERSION);
+    out.push(flags);
+    out.extend_from_slice(&frame_sequence.to_le_bytes());
+    write_gateway_config(&mut out, config);
+
+    let mut channels: Vec<&Channel> = channels.into_iter().collect();
+    channels.sort_by(|a, b| a.id.cmp(&b.id));
+    let count = u16::try_from(channels.len())
+        .map_err(|_| GatewayError::InvalidSnapshot("more than 65535 channels".to_string()))?;
+    out.extend_from_slice(&count.to_le_bytes());
+
+    for channel in channels {
+        let record = write_channel(channel, options.include_buffers)?;
+        let len = u32::try_from(record.len()).map_err(|_| {
+            GatewayError::InvalidSnapshot(format!("channel '{}' too large", channel.id))
+        })?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&record);
+    }
+    Ok(out)
+}
+
+/// Parse a snapshot, skipping the channels that fail to restore
+pub(crate) fn read(data: &[u8]) -> Result<Snapshot> {
+    let invalid = GatewayError::InvalidSnapshot;
+    let mut input = Input::new(data);
+    if input.take(4).map_err(invalid)? != MAGIC {
+        return Err(invalid("not a gateway snapshot".to_string()));
+    }
+    let version = input.u8().map_err(invalid)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(invalid(format!(
+            "unsupported snapshot version {} (expected {})",
+            version, SNAPSHOT_VERSION
+        )));
+    }
+    let flags = input.u8().map_err(invalid)?;
+    let frame_sequence = input.u64().map_err(invalid)?;
+    let config = read_gateway_config(&mut input).map_err(invalid)?;
+    let count = input.u16().map_err(invalid)?;
+
+    let buffers = flags & FLAG_BUFFERS != 0;
+    let mut report = SnapshotReport {
+        version,
+        metrics_enabled: flags & FLAG_METRICS != 0,
+        buffers_restored: buffers,
+        ..Default::default()
+    };
+    let mut channels = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let len = input.u32().map_err(invalid)? as usize;
+        let record = input.take(len).map_err(invalid)?;
+        match read_channel(record, buffers) {
+            Ok(channel) => channels.push(channel),
+            Err(skipped) => report.skipped.push(skipped),
+        }
+    }
+    if !input.is_empty() {
+        return Err(invalid(format!("{} trailing bytes", input.remaining())));
+    }
+
+    Ok(Snapshot {
+        config,
+        frame_sequence,
+        report,
+        channels,
+    })
+}
+
+fn write_channel(channel: &Channel, buffers: bool) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    put_str(&mut out, &channel.id);
+    write_channel_config(&mut out, &channel.config);
+    put_opt_u64(&mut out, channel.last_timestamp());
+
+    let streams = channel.streams();
+    out.push(streams.len() as u8);
+    for stream in streams {
+        put_str(&mut out, &stream.id);
+        out.extend_from_slice(&stream.encoder_sequence().to_le_bytes());
+        let context = stream.context().to_preload_bytes("")?;
+        out.extend_from_slice(&(context.len() as u32).to_le_bytes());
+        out.extend_from_slice(&context);
+        if buffers {
+            let buffered = stream.buffered();
+            out.extend_from_slice(&(buffered.len() as u32).to_le_bytes());
+            for (value, timestamp) in buffered {
+                out.extend_from_slice(&value.to_le_bytes());
+                out.extend_from_slice(&timestamp.to_le_bytes());
+            }
+            let text = stream.buffered_text();
+            out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            for (value, timestamp) in text {
+                out.push(value.len() as u8);
+                out.extend_from_slice(value.as_bytes());
+                out.extend_from_slice(&timestamp.to_le_bytes());
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn read_channel(record: &[u8], buffers: bool) -> std::result::Result<Channel, SkippedChannel> {
+    let mut input = Input::new(record);
+    let id = input.str().map_err(|reason| SkippedChannel {
+        id: String::new(),
+        reason,
+    })?;
+    let skipped = |reason: String| SkippedChannel {
+        id: id.clone(),
+        reason,
+    };
+
+    let (config, encrypted) = read_channel_config(&mut input).map_err(skipped)?;
+    if encrypted {
+        return Err(skipped(
+            "encryption keys are not stored in snapshots".to_string(),
+        ));
+    }
+    let last_timestamp = input.opt_u64().map_err(skipped)?;
+    let mut channel =
+        Channel::without_preload(id.clone(), config).map_err(|e| skipped(e.to_string()))?;
+    channel.restore_last_timestamp(last_timestamp);
+
+    let stream_count = input.u8().map_err(skipped)? as usize;
+    let mut streams = channel.streams_mut();
+    if stream_count != streams.len() {
+        return Err(skipped(format!(
+            "{} streams saved, configuration has {}",
+            stream_count,
+            streams.len()
+        )));
+    }
+    for stream in streams.iter_mut() {
+        let stream_id = input.str().map_err(skipped)?;
+        if stream_id != stream.id {
+            return Err(skipped(format!("unexpected stream '{}'", stream_id)));
+        }
+        let sequence = input.u16().map_err(skipped)?;
+        let len = input.u32().map_err(skipped)? as usize;
+        let context = Context::from_preload_bytes(input.take(len).map_err(skipped)?)
+            .map_err(|e| skipped(format!("context of '{}': {}", stream_id, e)))?;
+
+        let mut buffer = Vec::new();
+        let mut text_buffer = Vec::new();
+        if buffers {
+            for _ in 0..input.u32().map_err(skipped)? {
+                buffer.push((input.f64().map_err(skipped)?, input.u64().map_err(skipped)?));
+            }
+            for _ in 0..input.u32().map_err(skipped)? {
+                let len = input.u8().map_err(skipped)? as usize;
+                let value = String::from_utf8(input.take(len).map_err(skipped)?.to_vec())
+                    .map_err(|_| skipped("buffered text is not UTF-8".to_string()))?;
+                text_buffer.push((value, input.u64().map_err(skipped)?));
+            }
+        }
+        stream.restore_state(context, sequence, buffer, text_buffer);
+    }
+    if !input.is_empty() {
+        return Err(skipped(format!("{} trailing bytes", input.remaining())));
+    }
+    Ok(channel)
+}
+
+fn write_gateway_config(out: &mut Vec<u8>, config: &GatewayConfig) {
+    out.extend_from_slice(&(config.max_frame_size as u64).to_le_bytes());
+    out.extend_from_slice(&(config.max_channels as u64).to_le_bytes());
+    out.push(config.enable_checksums as u8);
+    out.push(overflow_code(config.overflow));
+    out.push(config.structural_headers as u8);
+    out.push(config.composite as u8);
+    out.push(config.fail_fast as u8);
+    out.extend_from_slice(&config.fault_threshold.to_le_bytes());
+    write_sync_config(out, &config.sync);
+    out.extend_from_slice(&(config.max_sync_bytes as u64).to_le_bytes());
+
+    match config.transport {
+        None => out.push(0),
+        Some(TransportPreset::Lorawan { region, data_rate }) => {
+            out.push(1);
+            out.push(match region {
+                LoRaRegion::Eu868 => 0,
+                LoRaRegion::Us915 => 1,
+                LoRaRegion::As923 => 2,
+            });
+            out.push(data_rate);
+        }
+        Some(TransportPreset::NbIot { mtu }) => {
+            out.push(2);
+            out.extend_from_slice(&(mtu as u64).to_le_bytes());
+        }
+        Some(TransportPreset::Ble { att_mtu }) => {
+            out.push(3);
+            out.extend_from_slice(&(att_mtu as u64).to_le_bytes());
+        }
+        Some(TransportPreset::Mqtt { max_packet }) => {
+            out.push(4);
+            out.extend_from_slice(&(max_packet as u64).to_le_bytes());
+        }
+    }
+
+    match &config.starvation {
+        None => out.push(0),
+        Some(starvation) => {
+            out.push(1);
+            out.extend_from_slice(&starvation.flushes_per_level.to_le_bytes());
+            out.push(starvation.level_step);
+            out.push(starvation.max_boost);
+            out.extend_from_slice(&starvation.near_full.to_le_bytes());
+        }
+    }
+}
+
+fn read_gateway_config(input: &mut Input) -> std::result::Result<GatewayConfig, String> {
+    let max_frame_size = input.usize()?;
+    let max_channels = input.usize()?;
+    let enable_checksums = input.bool()?;
+    let overflow = overflow_from_code(input.u8()?)?;
+    let structural_headers = input.bool()?;
+    let composite = input.bool()?;
+    let fail_fast = input.bool()?;
+    let fault_threshold = input.u32()?;
+    let sync = read_sync_config(input)?;
+    let max_sync_bytes = input.usize()?;
+
+    let transport = match input.u8()? {
+        0 => None,
+        1 => {
+            let region = match input.u8()? {
+                0 => LoRaRegion::Eu868,
+                1 => LoRaRegion::Us915,
+                2 => LoRaRegion::As923,
+                code => return Err(format!("unknown LoRaWAN region {}", code)),
+            };
+            Some(TransportPreset::Lorawan {
+                region,
+                data_rate: input.u8()?,
+            })
+        }
+        2 => Some(TransportPreset::NbIot {
+            mtu: input.usize()?,
+        }),
+        3 => Some(TransportPreset::Ble {
+            att_mtu: input.usize()?,
+        }),
+        4 => Some(TransportPreset::Mqtt {
+            max_packet: input.usize()?,
+        }),
+        code => return Err(format!("unknown transport {}", code)),
+    };
+
+    let starvation = if input.bool()? {
+        Some(StarvationConfig {
+            flushes_per_level: input.u32()?,
+            level_step: input.u8()?,
+            max_boost: input.u8()?,
+            near_full: input.f64()?,
+        })
+    } else {
+        None
+    };
+
+    Ok(GatewayConfig {
+        max_frame_size,
+        max_channels,
+        enable_checksums,
+        overflow,
+        structural_headers,
+        composite,
+        fail_fast,
+        fault_threshold,
+        sync,
+        max_sync_bytes,
+        transport,
+        starvation,
+    })
+}
+
+fn write_sync_config(out: &mut Vec<u8>, sync: &SyncConfig) {
+    out.extend_from_slice(&sync.announce_interval.to_le_bytes());
+    put_opt_u64(out, sync.announce_interval_ms);
+    out.push(sync.announce_jitter_percent);
+    out.extend_from_slice(&sync.jitter_seed.to_le_bytes());
+    out.extend_from_slice(&sync.max_version_gap.to_le_bytes());
+    out.extend_from_slice(&sync.sync_timeout.to_le_bytes());
+    out.extend_from_slice(&sync.max_sync_retries.to_le_bytes());
+    out.push(sync.auto_sync as u8);
+    out.extend_from_slice(&sync.max_detail_responses.to_le_bytes());
+}
+
+fn read_sync_config(input: &mut Input) -> std::result::Result<SyncConfig, String> {
+    Ok(SyncConfig {
+        announce_interval: input.u32()?,
+        announce_interval_ms: input.opt_u64()?,
+        announce_jitter_percent: input.u8()?,
+        jitter_seed: input.u64()?,
+        max_version_gap: input.u32()?,
+        sync_timeout: input.u64()?,
+        max_sync_retries: input.u32()?,
+        auto_sync: input.bool()?,
+        max_detail_responses: input.u32()?,
+    })
+}
+
+fn write_channel_config(out: &mut Vec<u8>, config: &ChannelConfig) {
+    out.extend_from_slice(&(config.buffer_size as u64).to_le_bytes());
+    out.push(
+        config
+            .overflow
+            .map_or(0, |policy| 1 + overflow_code(policy)),
+    );
+    match &config.preload_path {
+        None => out.push(0),
+        Some(path) => {
+            out.push(1);
+            put_str(out, path);
+        }
+    }
+    out.push(config.priority);
+    out.push(config.enable_checksum as u8);
+
+    let policy = &config.timestamp_policy;
+    put_opt_u64(out, policy.reject_before);
+    put_opt_u64(out, policy.reject_after_skew_ms);
+    out.push(match policy.on_backwards {
+        BackwardsPolicy::Reject => 0,
+        BackwardsPolicy::ClampToLast => 1,
+        BackwardsPolicy::Accept => 2,
+    });
+
+    match &config.downsample {
+        None => out.push(0),
+        Some(downsample) => {
+            out.push(1);
+            out.extend_from_slice(&downsample.bucket_ms.to_le_bytes());
+            out.push(downsample.aggregates.len() as u8);
+            for aggregate in &downsample.aggregates {
+                out.push(match aggregate {
+                    Aggregate::Min => 0,
+                    Aggregate::Max => 1,
+                    Aggregate::Mean => 2,
+                    Aggregate::Last => 3,
+                    Aggregate::Count => 4,
+                    Aggregate::StdDev => 5,
+                });
+            }
+            out.push(downsample.carry_partial as u8);
+            out.extend_from_slice(&downsample.raw_window_ms.to_le_bytes());
+        }
+    }
+
+    out.push(match config.value_kind {
+        ValueKind::Numeric => 0,
+        ValueKind::Text => 1,
+    });
+
+    #[cfg(feature = "crypto")]
+    let encrypted = config.encryption.is_some();
+    #[cfg(not(feature = "crypto"))]
+    let encrypted = false;
+    out.push(encrypted as u8);
+
+    match &config.group {
+        None => out.push(0),
+        Some(group) => {
+            out.push(1);
+            put_str(out, group);
+        }
+    }
+    out.extend_from_slice(&config.self_describing_interval.to_le_bytes());
+}
+
+/// Channel configuration and whether the channel was encrypted
+fn read_channel_config(input: &mut Input) -> std::result::Result<(ChannelConfig, bool), String> {
+    let buffer_size = input.usize()?;
+    let overflow = match input.u8()? {
+        0 => None,
+        code => Some(overflow_from_code(code - 1)?),
+    };
+    let preload_path = if input.bool()? {
+        Some(input.str()?)
+    } else {
+        None
+    };
+    let priority = input.u8()?;
+    let enable_checksum = input.bool()?;
+
+    let timestamp_policy = TimestampPolicy {
+        reject_before: input.opt_u64()?,
+        reject_after_skew_ms: input.opt_u64()?,
+        on_backwards: match input.u8()? {
+            0 => BackwardsPolicy::Reject,
+            1 => BackwardsPolicy::ClampToLast,
+            2 => BackwardsPolicy::Accept,
+            code => return Err(format!("unknown backwards policy {}", code)),
+        },
+    };
+
+    let downsample = if input.bool()? {
+        let bucket_ms = input.u64()?;
+        let count = input.u8()?;
+        let mut aggregates = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            aggregates.push(match input.u8()? {
+                0 => Aggregate::Min,
+                1 => Aggregate::Max,
+                2 => Aggregate::Mean,
+                3 => Aggregate::Last,
+                4 => Aggregate::Count,
+                5 => Aggregate::StdDev,
+                code => return Err(format!("unknown aggregate {}", code)),
+            });
+        }
+        Some(Downsample {
+            bucket_ms,
+            aggregates,
+            carry_partial: input.bool()?,
+            raw_window_ms: input.u64()?,
+        })
+    } else {
+        None
+    };
+
+    let value_kind = match input.u8()? {
+        0 => ValueKind::Numeric,
+        1 => ValueKind::Text,
+        code => return Err(format!("unknown value kind {}", code)),
+    };
+    let encrypted = input.bool()?;
+    let group = if input.bool()? {
+        Some(input.str()?)
+    } else {
+        None
+    };
+    let self_describing_interval = input.u16()?;
+
+    let config = ChannelConfig {
+        buffer_size,
+        overflow,
+        preload_path,
+        priority,
+        enable_checksum,
+        timestamp_policy,
+        downsample,
+        value_kind,
+        group,
+        self_describing_interval,
+        #[cfg(feature = "crypto")]
+        encryption: None,
+    };
+    Ok((config, encrypted))
+}
+
+fn overflow_code(policy: OverflowPolicy) -> u8 {
+    match policy {
+        OverflowPolicy::DropOldest => 0,
+        OverflowPolicy::DropNewest => 1,
+        OverflowPolicy::Error => 2,
+    }
+}
+
+fn overflow_from_code(code: u8) -> std::result::Result<OverflowPolicy, String> {
+    match code {
+        0 => Ok(OverflowPolicy::DropOldest),
+        1 => Ok(OverflowPolicy::DropNewest),
+        2 => Ok(OverflowPolicy::Error),
+        code => Err(format!("unknown overflow policy {}", code)),
+    }
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn put_opt_u64(out: &mut Vec<u8>, value: Option<u64>) {
+    match value {
+        None => out.push(0),
+        Some(value) => {
+            out.push(1);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Cursor over snapshot bytes, failing with the offset of a truncation
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Input<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> std::result::Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| format!("truncated at byte {}", self.pos))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> std::result::Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("N bytes taken"))
+    }
+
+    fn u8(&mut self) -> std::result::Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> std::result::Result<bool, String> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("invalid flag {} at byte {}", value, self.pos - 1)),
+        }
+    }
+
+    fn u16(&mut self) -> std::result::Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> std::result::Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> std::result::Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn usize(&mut self) -> std::result::Result<usize, String> {
+        let value = self.u64()?;
+        usize::try_from(value).map_err(|_| format!("{} does not fit this platform", value))
+    }
+
+    fn f64(&mut self) -> std::result::Result<f64, String> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn opt_u64(&mut self) -> std::result::Result<Option<u64>, String> {
+        if self.bool()? {
+            self.u64().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn str(&mut self) -> std::result::Result<String, String> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "id is not UTF-8".to_string())
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+}
@@ -0,0 +1,234 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Per-channel context synchronization
+//!
+//! Evolution keeps reshaping each channel's dictionary on the gateway, so
+//! the contexts held by a [`crate::FrameDecoder`] drift unless they are
+//! synchronized. The core [`Synchronizer`] works on a single context; this
+//! module multiplexes its messages over channels.
+//!
+//! [`Gateway::sync_announces`](crate::Gateway::sync_announces) produces the
+//! per-channel messages due, [`SyncFrame`] carries them in a dedicated
+//! control frame, and
+//! [`FrameDecoder::handle_sync`](crate::FrameDecoder::handle_sync) answers
+//! with the requests going back down the link, handed to
+//! [`Gateway::handle_sync`](crate::Gateway::handle_sync).
+//!
+//! # Sync Frame Format
+//!
+//! ```text
+//! [version: 1 = 0x80] [entry_count: 1] [entry...]
+//!
+//! entry:
+//! [id_len: 1] [id: N] [message_len: 2 LE] [message: M]
+//! ```
+//!
+//! The message is a [`SyncMessage::to_bytes`] encoding. The version byte
+//! has its high bit set, so sync frames never parse as data frames sharing
+//! the same link.
+
+use alec::sync::{SyncConfig, SyncMessage, SyncRequest, Synchronizer};
+
+use crate::channel_manager::ChannelId;
+use crate::frame::FrameParseError;
+
+/// Control frame multiplexing sync messages of several channels
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SyncFrame {
+    /// `(channel id, message)` entries, in frame order
+    pub messages: Vec<(ChannelId, SyncMessage)>,
+}
+
+impl SyncFrame {
+    /// Sync frame format version
+    pub const VERSION: u8 = 0x80;
+
+    /// Maximum number of entries in one frame
+    pub const MAX_ENTRIES: usize = u8::MAX as usize;
+
+    /// Create a sync frame from `(channel id, message)` entries
+    pub fn new(messages: Vec<(ChannelId, SyncMessage)>) -> Self {
+        Self { messages }
+    }
+
+    /// Check if bytes start like a sync frame rather than a data frame
+    pub fn is_sync_frame(bytes: &[u8]) -> bool {
+        bytes.first() == Some(&Self::VERSION)
+    }
+
+    /// Serialized size of one entry
+    pub(crate) fn entry_size(id_len: usize, message_len: usize) -> usize {
+        1 + id_len + 2 + message_len
+    }
+
+    /// Check if the frame has no entries
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Calculate the total size of the frame in bytes
+    pub fn size(&self) -> usize {
+        2 + self
+            .messages
+            .iter()
+            .map(|(id, message)| Self::entry_size(id.len(), message.to_bytes().len()))
+            .sum::<usize>()
+    }
+
+    /// Serialize the frame to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![Self::VERSION, self.messages.len() as u8];
+        for (id, message) in &self.messages {
+            let message = message.to_bytes();
+            buf.push(id.len() as u8);
+            buf.extend_from_slice(id.as_bytes());
+            buf.extend_from_slice(&(message.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&message);
+        }
+        buf
+    }
+
+    /// Parse a sync frame from bytes
+    ///
+    /// Entry errors carry the index of the offending entry, like data
+    /// frames.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, FrameParseError> {
+        if data.len() < 2 {
+            return Err(FrameParseError::TooShort);
+        }
+        if data[0] != Self::VERSION {
+            return Err(FrameParseError::UnsupportedVersion(data[0]));
+        }
+
+        let count = data[1] as usize;
+        let mut pos = 2;
+        let mut messages = Vec::with_capacity(count);
+
+        for channel in 0..count {
+            if pos >= data.len() {
+                return Err(FrameParseError::Truncated { channel });
+            }
+            let id_len = data[pos] as usize;
+            pos += 1;
+
+            if pos + id_len > data.len() {
+                return Err(FrameParseError::TruncatedChannelId { channel });
+            }
+            let id = String::from_utf8_lossy(&data[pos..pos + id_len]).to_string();
+            pos += id_len;
+
+            if pos + 2 > data.len() {
+                return Err(FrameParseError::TruncatedDataLength { channel });
+            }
+            let len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+            pos += 2;
+
+            if pos + len > data.len() {
+                return Err(FrameParseError::TruncatedChannelData { channel });
+            }
+            let message = SyncMessage::from_bytes(&data[pos..pos + len])
+                .ok_or(FrameParseError::InvalidSyncMessage { channel })?;
+            pos += len;
+
+            messages.push((id, message));
+        }
+
+        Ok(Self { messages })
+    }
+}
+
+impl From<Vec<(ChannelId, SyncMessage)>> for SyncFrame {
+    fn from(messages: Vec<(ChannelId, SyncMessage)>) -> Self {
+        Self::new(messages)
+    }
+}
+
+/// Gateway-side sync state of one stream
+#[derive(Debug)]
+pub(crate) struct ChannelSync {
+    /// Announce schedule, counted in flushed entries
+    pub(crate) synchronizer: Synchronizer,
+    /// Announce owed to the decoder, deferred until it fits the budget
+    pub(crate) announce_due: bool,
+    /// Request of the decoder still waiting for its diff
+    pub(crate) request: Option<SyncRequest>,
+}
+
+impl ChannelSync {
+    pub(crate) fn new(config: &SyncConfig) -> Self {
+        Self {
+            synchronizer: Synchronizer::with_config(config.clone()),
+            announce_due: false,
+            request: None,
+        }
+    }
+
+    /// Whether a message is owed to the decoder
+    pub(crate) fn is_due(&self) -> bool {
+        self.announce_due || self.request.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alec::sync::SyncAnnounce;
+
+    fn announce(version: u32) -> SyncMessage {
+        SyncMessage::Announce(SyncAnnounce {
+            version,
+            hash: 0xDEAD_BEEF,
+            pattern_count: 3,
+        })
+    }
+
+    #[test]
+    fn test_sync_frame_roundtrip() {
+        let frame = SyncFrame::new(vec![
+            ("temp".to_string(), announce(4)),
+            (
+                "humid".to_string(),
+                Synchronizer::create_request(2, Some(5)),
+            ),
+        ]);
+
+        let bytes = frame.to_bytes();
+        assert!(SyncFrame::is_sync_frame(&bytes));
+        assert_eq!(bytes.len(), frame.size());
+        assert_eq!(SyncFrame::from_bytes(&bytes).unwrap(), frame);
+    }
+
+    #[test]
+    fn test_sync_frame_parse_errors() {
+        assert_eq!(
+            SyncFrame::from_bytes(&[0x80]),
+            Err(FrameParseError::TooShort)
+        );
+        assert_eq!(
+            SyncFrame::from_bytes(&[1, 0]),
+            Err(FrameParseError::UnsupportedVersion(1))
+        );
+
+        let bytes = SyncFrame::new(vec![("temp".to_string(), announce(1))]).to_bytes();
+        assert_eq!(
+            SyncFrame::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FrameParseError::TruncatedChannelData { channel: 0 })
+        );
+
+        // Unknown message type
+        let bytes = [0x80, 1, 1, b't', 1, 0, 0xFF];
+        assert_eq!(
+            SyncFrame::from_bytes(&bytes),
+            Err(FrameParseError::InvalidSyncMessage { channel: 0 })
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// ALEC Gateway - Multi-sensor orchestration layer
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Dictionary-backed encoding of text channels
+//!
+//! A text channel ([`ValueKind::Text`](crate::ValueKind::Text)) sends each
+//! distinct string once in full and registers it as a pattern of the
+//! channel's context; later occurrences reference the pattern code. The
+//! receiver registers the same literals in the same order, so both
+//! dictionaries assign the same codes.
+//!
+//! # Entry Format
+//!
+//! ```text
+//! [sample...]
+//!
+//! sample:
+//! [tag: 1] [timestamp_delta: varint] [code: varint]          tag 0x00, reference
+//! [tag: 1] [timestamp_delta: varint] [len: 1] [bytes: len]   tag 0x01, literal
+//! ```
+//!
+//! Timestamp deltas are zigzag-encoded, relative to the previous sample of
+//! the entry (0 for the first one). Once known, a status string costs 3
+//! to 5 bytes per sample.
+
+use alec::context::Pattern;
+use alec::protocol::{read_varint_u64, write_varint};
+use alec::Context;
+
+/// Longest string a text channel carries, in bytes
+pub const MAX_TEXT_LEN: usize = u8::MAX as usize;
+
+/// Tag of a sample referencing a dictionary pattern
+const TAG_REFERENCE: u8 = 0x00;
+
+/// Tag of a sample carrying its bytes
+const TAG_LITERAL: u8 = 0x01;
+
+/// Encoding state of one entry: the previous sample's timestamp
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TextEncoder {
+    previous: u64,
+}
+
+impl TextEncoder {
+    /// Append one sample, registering unseen strings in `context`
+    ///
+    /// `value` is at most [`MAX_TEXT_LEN`] bytes long. When the dictionary
+    /// is full the string is sent as a literal without being registered,
+    /// as the receiver does.
+    pub(crate) fn encode(
+        &mut self,
+        context: &mut Context,
+        value: &str,
+        timestamp: u64,
+        out: &mut Vec<u8>,
+    ) {
+        let bytes = value.as_bytes();
+        let delta = zigzag(timestamp.wrapping_sub(self.previous) as i64);
+        self.previous = timestamp;
+
+        match find(context, bytes) {
+            Some(code) => {
+                out.push(TAG_REFERENCE);
+                write_varint(delta, out);
+                write_varint(u64::from(code), out);
+            }
+            None => {
+                out.push(TAG_LITERAL);
+                write_varint(delta, out);
+                out.push(bytes.len() as u8);
+                out.extend_from_slice(bytes);
+                register(context, bytes);
+            }
+        }
+    }
+}
+
+/// Decode an entry into `(value, timestamp)` samples, registering its
+/// literals in `context`
+pub(crate) fn decode(
+    context: &mut Context,
+    data: &[u8],
+) -> std::result::Result<Vec<(String, u64)>, String> {
+    let mut samples = Vec::new();
+    let mut previous = 0u64;
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let tag = data[pos];
+        pos += 1;
+        let delta = unzigzag(read_varint(data, &mut pos)?);
+        let timestamp = previous.wrapping_add(delta as u64);
+        previous = timestamp;
+
+        let bytes = match tag {
+            TAG_REFERENCE => {
+                let code = u32::try_from(read_varint(data, &mut pos)?)
+                    .map_err(|_| "pattern code out of range".to_string())?;
+                context
+                    .get_pattern(code)
+                    .map(|pattern| pattern.data.to_vec())
+                    .ok_or_else(|| format!("unknown pattern code {}", code))?
+            }
+            TAG_LITERAL => {
+                let len = *data.get(pos).ok_or("truncated literal length")? as usize;
+                pos += 1;
+                let bytes = data
+                    .get(pos..pos + len)
+                    .ok_or("truncated literal")?
+                    .to_vec();
+                pos += len;
+                register(context, &bytes);
+                bytes
+            }
+            tag => return Err(format!("unknown sample tag 0x{:02x}", tag)),
+        };
+        let value = String::from_utf8(bytes).map_err(|_| "invalid UTF-8".to_string())?;
+        samples.push((value, timestamp));
+    }
+
+    Ok(samples)
+}
+
+/// Code of the pattern holding exactly `bytes`
+///
+/// [`Context::find_pattern`] also matches 8-byte strings read as legacy
+/// numeric patterns, hence the check.
+fn find(context: &Context, bytes: &[u8]) -> Option<u32> {
+    let code = context.find_pattern(bytes)?;
+    let pattern = context.get_pattern(code)?;
+    (&pattern.data[..] == bytes).then_some(code)
+}
+
+/// Register a literal, on both peers alike
+///
+/// Empty strings are not registered, and a full dictionary leaves the
+/// string unregistered.
+fn register(context: &mut Context, bytes: &[u8]) {
+    if bytes.is_empty() || find(context, bytes).is_some() {
+        return;
+    }
+    let _ = context.register_pattern(Pattern::from_slice(bytes));
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> std::result::Result<u64, String> {
+    let (value, len) = data
+        .get(*pos..)
+        .and_then(read_varint_u64)
+        .ok_or("truncated or invalid varint")?;
+    *pos += len;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_with_backwards_timestamp() {
+        let samples = [
+            ("OK", 5_000u64),
+            ("FAULT", 4_000),
+            ("OK", 9_000),
+            ("", 9_000),
+        ];
+        let mut sender = Context::new();
+        let mut encoder = TextEncoder::default();
+        let mut data = Vec::new();
+        for (value, timestamp) in samples {
+            encoder.encode(&mut sender, value, timestamp, &mut data);
+        }
+
+        let mut receiver = Context::new();
+        let decoded = decode(&mut receiver, &data).unwrap();
+        let expected: Vec<(String, u64)> =
+            samples.iter().map(|(v, ts)| (v.to_string(), *ts)).collect();
+        assert_eq!(decoded, expected);
+        assert_eq!(receiver.hash(), sender.hash());
+        assert_eq!(receiver.pattern_count(), 2);
+    }
+
+    #[test]
+    fn test_eight_byte_string_is_not_numeric() {
+        // Reads as a normal f64 in the legacy numeric form
+        let mut context = Context::new();
+        context.register_pattern(Pattern::numeric(1.0)).unwrap();
+        assert_eq!(find(&context, &1.0f64.to_be_bytes()), None);
+    }
+}
@@ -0,0 +1,187 @@
+// ALEC Gateway - Crypto Tests
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Tests for per-channel encryption (feature `crypto`).
+//!
+//! These tests verify:
+//! - Encrypted entries open to the same bytes as unencrypted ones
+//! - A wrong key or a tampered entry fails authentication
+//! - Plaintext entries in a mixed frame are unaffected
+//! - The frame budget accounts for the encryption overhead
+//! - Flush estimates include the overhead and the flags byte
+
+#![cfg(feature = "crypto")]
+
+use alec_gateway::crypto::OVERHEAD;
+use alec_gateway::{
+    ChannelConfig, EncryptionConfig, Frame, FrameDecoder, Gateway, GatewayConfig, GatewayError,
+};
+
+const KEY: [u8; 32] = [0x42; 32];
+
+fn encrypted_config() -> ChannelConfig {
+    ChannelConfig::with_encryption(EncryptionConfig::chacha20poly1305(KEY))
+}
+
+fn push_samples(gateway: &mut Gateway, channel: &str) {
+    for i in 0..5u64 {
+        gateway
+            .push(channel, 20.0 + i as f64 * 0.1, 1000 + i * 1000)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_encrypted_roundtrip_matches_plaintext() {
+    let mut plain = Gateway::new();
+    plain.add_channel("temp", ChannelConfig::default()).unwrap();
+    push_samples(&mut plain, "temp");
+    let expected = plain.flush().unwrap().channels[0].data.clone();
+
+    let mut gateway = Gateway::new();
+    gateway.add_channel("temp", encrypted_config()).unwrap();
+    push_samples(&mut gateway, "temp");
+    let frame = gateway.flush().unwrap();
+
+    assert_eq!(frame.version, Frame::VERSION_FLAGS);
+    assert!(frame.channels[0].encrypted);
+    assert_eq!(frame.channels[0].data.len(), expected.len() + OVERHEAD);
+    assert_ne!(frame.channels[0].data[4..4 + expected.len()], expected[..]);
+
+    let mut decoder = FrameDecoder::new();
+    decoder.add_key("temp", EncryptionConfig::chacha20poly1305(KEY));
+    let decoded = decoder.decode(&frame.to_bytes()).unwrap();
+    assert_eq!(decoded.len(), 1);
+    assert!(decoded[0].encrypted);
+    assert_eq!(decoded[0].data.as_ref().unwrap(), &expected);
+}
+
+#[test]
+fn test_wrong_key_fails_authentication() {
+    let mut gateway = Gateway::new();
+    gateway.add_channel("temp", encrypted_config()).unwrap();
+    push_samples(&mut gateway, "temp");
+    let bytes = gateway.flush().unwrap().to_bytes();
+
+    let mut decoder = FrameDecoder::new();
+    decoder.add_key("temp", EncryptionConfig::chacha20poly1305([0x24; 32]));
+    let decoded = decoder.decode(&bytes).unwrap();
+    assert!(matches!(
+        &decoded[0].data,
+        Err(GatewayError::AuthenticationFailed(id)) if id == "temp"
+    ));
+}
+
+#[test]
+fn test_tampered_entry_does_not_affect_plaintext_entry() {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel(
+            "secret",
+            ChannelConfig {
+                priority: 1,
+                ..ChannelConfig::with_encryption(EncryptionConfig::chacha20poly1305(KEY))
+            },
+        )
+        .unwrap();
+    gateway
+        .add_channel("public", ChannelConfig::with_priority(2))
+        .unwrap();
+    push_samples(&mut gateway, "secret");
+    push_samples(&mut gateway, "public");
+
+    let mut frame = gateway.flush().unwrap();
+    let public = frame.get_channel("public").unwrap().data.clone();
+    let last = frame.channels[0].data.len() - 1;
+    frame.channels[0].data[last] ^= 0x01;
+
+    let mut decoder = FrameDecoder::new();
+    decoder.add_key("secret", EncryptionConfig::chacha20poly1305(KEY));
+    let decoded = decoder.decode(&frame.to_bytes()).unwrap();
+
+    assert_eq!(decoded[0].id, "secret");
+    assert!(matches!(
+        decoded[0].data,
+        Err(GatewayError::AuthenticationFailed(_))
+    ));
+    assert_eq!(decoded[1].id, "public");
+    assert!(!decoded[1].encrypted);
+    assert_eq!(decoded[1].data.as_ref().unwrap(), &public);
+}
+
+#[test]
+fn test_frame_sequence_changes_ciphertext() {
+    let mut gateway = Gateway::new();
+    gateway.add_channel("temp", encrypted_config()).unwrap();
+
+    gateway.push("temp", 20.0, 1000).unwrap();
+    let first = gateway.flush().unwrap();
+    assert_eq!(gateway.frame_sequence(), 1);
+    gateway.push("temp", 20.0, 2000).unwrap();
+    gateway.set_frame_sequence(7);
+    let second = gateway.flush().unwrap();
+
+    assert_eq!(&first.channels[0].data[..4], &0u32.to_le_bytes());
+    assert_eq!(&second.channels[0].data[..4], &7u32.to_le_bytes());
+    assert_eq!(gateway.frame_sequence(), 8);
+}
+
+#[test]
+fn test_nonce_exhausted() {
+    let mut gateway = Gateway::new();
+    gateway.add_channel("temp", encrypted_config()).unwrap();
+    gateway.set_frame_sequence(u32::MAX as u64 + 1);
+    gateway.push("temp", 20.0, 1000).unwrap();
+
+    assert!(matches!(
+        gateway.flush(),
+        Err(GatewayError::NonceExhausted(id)) if id == "temp"
+    ));
+}
+
+#[test]
+fn test_frame_budget_includes_overhead() {
+    let mut plain = Gateway::new();
+    plain.add_channel("temp", ChannelConfig::default()).unwrap();
+    push_samples(&mut plain, "temp");
+    let plain_size = plain.flush().unwrap().size();
+
+    // Room for the plaintext entry and its flags byte, but not the tag
+    let encrypted_frame = |max_frame_size| {
+        let mut gateway = Gateway::with_config(GatewayConfig {
+            max_frame_size,
+            ..Default::default()
+        })
+        .unwrap();
+        gateway.add_channel("temp", encrypted_config()).unwrap();
+        push_samples(&mut gateway, "temp");
+        gateway.flush().unwrap()
+    };
+
+    assert!(encrypted_frame(plain_size + OVERHEAD).is_empty());
+    let frame = encrypted_frame(plain_size + OVERHEAD + 1);
+    assert_eq!(frame.channel_count(), 1);
+    assert_eq!(frame.size(), plain_size + OVERHEAD + 1);
+}
+
+#[test]
+fn test_estimate_flush_includes_overhead() {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel("temp", ChannelConfig::default())
+        .unwrap();
+    gateway.add_channel("secret", encrypted_config()).unwrap();
+    push_samples(&mut gateway, "temp");
+    push_samples(&mut gateway, "secret");
+
+    let estimate = gateway.estimate_flush();
+    let frame = gateway.flush().unwrap();
+
+    assert_eq!(estimate.total_bytes, frame.size());
+    for (id, bytes, _) in &estimate.per_channel {
+        assert_eq!(*bytes, frame.get_channel(id).unwrap().data.len());
+    }
+}
@@ -6,10 +6,25 @@
 // 2. Push/Flush (10 tests)
 // 3. Frame (10 tests)
 // 4. Gateway (10+ tests)
-
+// 5. Flush Estimates
+// 6. Starvation Protection
+// 7. Text Channels
+// 8. Composite Messages
+// 9. Snapshots
+// 10. Channel Groups
+// 11. Zero-Copy Parsing
+
+use std::sync::{Arc, Mutex};
+
+use alec::health::{HealthCheckable, HealthStatus};
+use alec::security::{
+    AuditEvent, AuditEventType, AuditFilter, AuditLogger, SecurityConfig, SecurityContext,
+};
 use alec_gateway::{
-    Aggregator, Channel, ChannelConfig, ChannelData, ChannelManager, Frame, FrameBuilder,
-    FrameParseError, Gateway, GatewayConfig, GatewayError,
+    Aggregate, Aggregator, BackwardsPolicy, Channel, ChannelConfig, ChannelData, ChannelManager,
+    CompositeData, Downsample, FlushReport, Frame, FrameBuilder, FrameDecoder, FrameParseError,
+    FrameRef, Gateway, GatewayConfig, GatewayError, LoRaRegion, OverflowPolicy, SnapshotOptions,
+    StarvationConfig, TimestampPolicy, TimestampRule, TransportPreset, ValueKind, MAX_TEXT_LEN,
 };
 
 // ============================================================================
@@ -361,11 +376,46 @@ fn test_gateway_with_custom_config() {
         max_frame_size: 100,
         max_channels: 5,
         enable_checksums: false,
+        ..Default::default()
     };
-    let gateway = Gateway::with_config(config);
+    let gateway = Gateway::with_config(config).unwrap();
     assert_eq!(gateway.max_frame_size(), 100);
 }
 
+#[test]
+fn test_gateway_rejects_invalid_config() {
+    let dr0 = GatewayConfig::for_transport(TransportPreset::Lorawan {
+        region: LoRaRegion::Eu868,
+        data_rate: 0,
+    });
+    let oversized = GatewayConfig {
+        max_frame_size: 500,
+        ..dr0.clone()
+    };
+    assert!(matches!(
+        Gateway::with_config(oversized.clone()),
+        Err(GatewayError::InvalidConfig(_))
+    ));
+    assert_eq!(
+        Gateway::with_config_unchecked(oversized).max_frame_size(),
+        500
+    );
+
+    // Frames flushed under a preset fit one transmission
+    let mut gateway = Gateway::with_config(dr0).unwrap();
+    for i in 0..8 {
+        gateway
+            .add_channel(format!("sensor{}", i), ChannelConfig::default())
+            .unwrap();
+        for t in 0..10 {
+            gateway
+                .push(&format!("sensor{}", i), 20.0 + t as f64, t * 1000)
+                .unwrap();
+        }
+    }
+    assert!(gateway.flush().unwrap().to_bytes().len() <= 51);
+}
+
 #[test]
 fn test_gateway_full_workflow() {
     let mut gateway = Gateway::new();
@@ -558,7 +608,7 @@ fn test_gateway_remove_channel() {
 #[test]
 fn test_aggregator_empty_manager() {
     let config = GatewayConfig::default();
-    let aggregator = Aggregator::new(config);
+    let mut aggregator = Aggregator::new(config);
     let mut manager = ChannelManager::new(10);
 
     let frame = aggregator.aggregate(&mut manager).unwrap();
@@ -567,9 +617,11 @@ fn test_aggregator_empty_manager() {
 
 #[test]
 fn test_aggregator_respects_max_size() {
-    let mut config = GatewayConfig::default();
-    config.max_frame_size = 50;
-    let aggregator = Aggregator::new(config);
+    let config = GatewayConfig {
+        max_frame_size: 50,
+        ..Default::default()
+    };
+    let mut aggregator = Aggregator::new(config);
 
     let mut manager = ChannelManager::new(20);
 
@@ -692,6 +744,9 @@ fn test_channel_data_struct() {
     let data = ChannelData {
         id: "test".to_string(),
         data: vec![1, 2, 3],
+        encrypted: false,
+        composite: false,
+        sample_count: None,
     };
     assert_eq!(data.id, "test");
     assert_eq!(data.data, vec![1, 2, 3]);
@@ -713,3 +768,1318 @@ fn test_frame_builder_is_empty() {
     builder.try_add("test".to_string(), vec![1, 2, 3]);
     assert!(!builder.is_empty());
 }
+
+// ============================================================================
+// Timestamp Policy Tests
+// ============================================================================
+
+#[test]
+fn test_timestamp_backwards_clamp_to_last() {
+    let policy = TimestampPolicy {
+        on_backwards: BackwardsPolicy::ClampToLast,
+        ..Default::default()
+    };
+    let mut channel = Channel::new("temp", ChannelConfig::with_timestamp_policy(policy)).unwrap();
+
+    // NTP step backwards after 3000
+    for (value, ts) in [
+        (1.0, 1000),
+        (2.0, 2000),
+        (3.0, 3000),
+        (4.0, 1500),
+        (5.0, 3500),
+    ] {
+        channel.push(value, ts).unwrap();
+    }
+
+    let timestamps: Vec<u64> = channel.buffered().iter().map(|(_, ts)| *ts).collect();
+    assert_eq!(timestamps, vec![1000, 2000, 3000, 3000, 3500]);
+    assert!(timestamps.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(channel.buffered()[3].0, 4.0);
+}
+
+#[test]
+fn test_timestamp_backwards_reject() {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel(
+            "temp",
+            ChannelConfig::with_timestamp_policy(TimestampPolicy {
+                on_backwards: BackwardsPolicy::Reject,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+    gateway.push("temp", 1.0, 2000).unwrap();
+    let err = gateway.push("temp", 2.0, 1000).unwrap_err();
+    assert!(matches!(
+        err,
+        GatewayError::InvalidTimestamp {
+            timestamp: 1000,
+            rule: TimestampRule::Backwards { last: 2000 },
+            ..
+        }
+    ));
+    assert_eq!(gateway.pending("temp").unwrap(), 1);
+}
+
+#[test]
+fn test_timestamp_reject_before_and_default_accepts() {
+    let policy = TimestampPolicy {
+        reject_before: Some(1_600_000_000_000),
+        ..Default::default()
+    };
+    let mut channel = Channel::new("temp", ChannelConfig::with_timestamp_policy(policy)).unwrap();
+    let err = channel.push(1.0, 0).unwrap_err();
+    assert!(matches!(
+        err,
+        GatewayError::InvalidTimestamp {
+            rule: TimestampRule::TooEarly { .. },
+            ..
+        }
+    ));
+    assert!(err.to_string().contains("temp"));
+    assert!(channel.is_empty());
+
+    // Default policy keeps accepting anything
+    let mut channel = Channel::new("temp", ChannelConfig::default()).unwrap();
+    channel.push(1.0, 5000).unwrap();
+    channel.push(2.0, 0).unwrap();
+    assert_eq!(channel.pending(), 2);
+}
+
+// ============================================================================
+// Overflow Policy Tests
+// ============================================================================
+
+/// Channel with a 10-sample buffer holding values 0..10
+fn full_channel(policy: OverflowPolicy) -> Channel {
+    let mut channel = Channel::new("temp", ChannelConfig::with_overflow(10, policy)).unwrap();
+    for i in 0..10 {
+        channel.push(i as f64, 1000 + i * 1000).unwrap();
+    }
+    assert_eq!(channel.dropped(), 0);
+    channel
+}
+
+fn buffered_values(channel: &Channel) -> Vec<f64> {
+    channel.buffered().iter().map(|(v, _)| *v).collect()
+}
+
+#[test]
+fn test_overflow_drop_oldest() {
+    let mut channel = full_channel(OverflowPolicy::DropOldest);
+    channel.push(10.0, 11_000).unwrap();
+
+    assert_eq!(channel.pending(), 10);
+    assert_eq!(
+        buffered_values(&channel),
+        (1..=10).map(f64::from).collect::<Vec<_>>()
+    );
+    assert_eq!(channel.dropped(), 1);
+}
+
+#[test]
+fn test_overflow_drop_newest() {
+    let mut channel = full_channel(OverflowPolicy::DropNewest);
+    channel.push(10.0, 11_000).unwrap();
+
+    assert_eq!(channel.pending(), 10);
+    assert_eq!(
+        buffered_values(&channel),
+        (0..10).map(f64::from).collect::<Vec<_>>()
+    );
+    assert_eq!(channel.dropped(), 1);
+    assert_eq!(channel.last_timestamp(), Some(11_000));
+}
+
+#[test]
+fn test_overflow_error() {
+    let mut channel = full_channel(OverflowPolicy::Error);
+    let result = channel.push(10.0, 11_000);
+
+    assert!(matches!(result, Err(GatewayError::BufferFull(_))));
+    assert_eq!(
+        buffered_values(&channel),
+        (0..10).map(f64::from).collect::<Vec<_>>()
+    );
+    assert_eq!(channel.dropped(), 0);
+}
+
+#[test]
+fn test_overflow_gateway_default_spares_critical_channels() {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        overflow: OverflowPolicy::DropOldest,
+        ..Default::default()
+    })
+    .unwrap();
+    gateway
+        .add_channel("temp", ChannelConfig::with_buffer_size(2))
+        .unwrap();
+    gateway
+        .add_channel(
+            "alarm",
+            ChannelConfig {
+                buffer_size: 2,
+                priority: ChannelConfig::CRITICAL_PRIORITY,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    gateway
+        .push_multi("temp", &[(1.0, 1000), (2.0, 2000), (3.0, 3000)])
+        .unwrap();
+    assert_eq!(gateway.dropped("temp").unwrap(), 1);
+
+    gateway
+        .push_multi("alarm", &[(1.0, 1000), (2.0, 2000)])
+        .unwrap();
+    let result = gateway.push("alarm", 3.0, 3000);
+    assert!(matches!(result, Err(GatewayError::BufferFull(_))));
+    assert_eq!(gateway.dropped("alarm").unwrap(), 0);
+    assert_eq!(gateway.total_dropped(), 1);
+
+    // Counters survive a flush
+    gateway.flush().unwrap();
+    assert_eq!(gateway.dropped("temp").unwrap(), 1);
+}
+
+/// Audit logger sharing its events with the test
+struct SharedLog(Arc<Mutex<Vec<AuditEvent>>>);
+
+impl AuditLogger for SharedLog {
+    fn log(&self, event: AuditEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn test_overflow_audits_first_drop_only() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut gateway = Gateway::new();
+    gateway.set_security_context(
+        SecurityContext::new(SecurityConfig::with_audit())
+            .with_audit_logger(Box::new(SharedLog(events.clone()))),
+    );
+    gateway
+        .add_channel(
+            "temp",
+            ChannelConfig::with_overflow(1, OverflowPolicy::DropNewest),
+        )
+        .unwrap();
+
+    for i in 0..4 {
+        gateway.push("temp", i as f64, 1000 + i * 1000).unwrap();
+    }
+
+    assert_eq!(gateway.dropped("temp").unwrap(), 3);
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, AuditEventType::BufferOverflow);
+    assert!(events[0].details.contains("'temp'"));
+    assert!(AuditFilter::default()
+        .with_field("channel", "temp")
+        .with_field("buffer_size", 1)
+        .with_field("policy", "DropNewest")
+        .matches(&events[0]));
+}
+
+// ============================================================================
+// Flush Estimate Tests
+// ============================================================================
+
+fn sensor_value(channel: usize, i: u64) -> f64 {
+    20.0 + channel as f64 + (i as f64 * 0.3).sin() * (1.0 + channel as f64 * 0.5)
+}
+
+#[test]
+fn test_estimate_flush_matches_flush() {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 32 * 1024,
+        max_channels: 50,
+        ..Default::default()
+    })
+    .unwrap();
+    for ch in 0..50 {
+        gateway
+            .add_channel(
+                format!("ch{:02}", ch),
+                ChannelConfig::with_priority(ch as u8),
+            )
+            .unwrap();
+    }
+
+    // A first flush gives the contexts some history
+    for round in 0..2u64 {
+        for i in 0..20u64 {
+            for ch in 0..50 {
+                // Odd channels skip samples so buffers differ in length
+                if ch % 2 == 1 && i % 3 == 0 {
+                    continue;
+                }
+                let t = round * 20 + i;
+                gateway
+                    .push(
+                        &format!("ch{:02}", ch),
+                        sensor_value(ch, t),
+                        1000 + t * 1000,
+                    )
+                    .unwrap();
+            }
+            // Poll mid-way: later polls only encode the new samples
+            if i == 10 {
+                gateway.estimate_flush();
+            }
+        }
+        if round == 0 {
+            gateway.flush().unwrap();
+        }
+    }
+
+    let pending = gateway.total_pending();
+    let version = gateway.channel_context_version("ch07").unwrap();
+    let sequence = gateway.frame_sequence();
+    let estimate = gateway.estimate_flush();
+
+    // Nothing was consumed
+    assert_eq!(gateway.total_pending(), pending);
+    assert_eq!(gateway.channel_context_version("ch07").unwrap(), version);
+    assert_eq!(gateway.frame_sequence(), sequence);
+    assert_eq!(gateway.estimate_flush(), estimate);
+
+    let frame = gateway.flush().unwrap();
+    assert!(estimate.fits_in_frame);
+    assert_eq!(estimate.total_bytes, frame.to_bytes().len());
+    assert_eq!(estimate.per_channel.len(), frame.channel_count());
+    for ((id, bytes, samples), entry) in estimate.per_channel.iter().zip(&frame.channels) {
+        assert_eq!(id, &entry.id);
+        assert_eq!(*bytes, entry.data.len());
+        let ch: usize = id[2..].parse().unwrap();
+        assert_eq!(*samples, if ch % 2 == 1 { 13 } else { 20 });
+    }
+}
+
+#[test]
+fn test_estimate_flush_reports_oversize() {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 40,
+        structural_headers: true,
+        ..Default::default()
+    })
+    .unwrap();
+    gateway.add_channel("a", ChannelConfig::default()).unwrap();
+    gateway.add_channel("b", ChannelConfig::default()).unwrap();
+    for i in 0..10u64 {
+        gateway
+            .push("a", sensor_value(0, i), 1000 + i * 1000)
+            .unwrap();
+        gateway
+            .push("b", sensor_value(1, i), 1000 + i * 1000)
+            .unwrap();
+    }
+
+    let estimate = gateway.estimate_flush();
+    assert!(!estimate.fits_in_frame);
+    assert!(estimate.total_bytes > 40);
+
+    // The flush leaves out what does not fit
+    let frame = gateway.flush().unwrap();
+    assert!(frame.size() < estimate.total_bytes);
+}
+
+#[test]
+fn test_estimate_flush_after_eviction() {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel(
+            "temp",
+            ChannelConfig::with_overflow(8, OverflowPolicy::DropOldest),
+        )
+        .unwrap();
+
+    for i in 0..5u64 {
+        gateway
+            .push("temp", sensor_value(0, i), 1000 + i * 1000)
+            .unwrap();
+    }
+    gateway.estimate_flush();
+    for i in 5..12u64 {
+        gateway
+            .push("temp", sensor_value(0, i), 1000 + i * 1000)
+            .unwrap();
+    }
+
+    let estimate = gateway.estimate_flush();
+    assert_eq!(estimate.per_channel[0].2, 8);
+    assert_eq!(estimate.total_bytes, gateway.flush().unwrap().size());
+
+    let empty = gateway.estimate_flush();
+    assert!(empty.per_channel.is_empty());
+    assert_eq!(empty.total_bytes, Frame::new().size());
+}
+
+// ============================================================================
+// Channel Fault Tests
+// ============================================================================
+
+/// Gateway with a channel poisoned by a NaN sample and two healthy ones
+fn gateway_with_poisoned_channel(config: GatewayConfig) -> Gateway {
+    let mut gateway = Gateway::with_config(config).unwrap();
+    for id in ["bad", "temp", "humid"] {
+        gateway.add_channel(id, ChannelConfig::default()).unwrap();
+        gateway
+            .push_multi(id, &[(20.0, 1000), (20.5, 2000), (21.0, 3000)])
+            .unwrap();
+    }
+    // Encodes raw, then leaves a NaN prediction in the context
+    gateway.push("bad", f64::NAN, 4000).unwrap();
+    assert!(gateway.flush_with_report().unwrap().is_clean());
+    gateway
+}
+
+#[test]
+fn test_faulted_channel_isolated_and_reset() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut gateway = gateway_with_poisoned_channel(GatewayConfig {
+        max_frame_size: 1024,
+        ..Default::default()
+    });
+    gateway.set_security_context(
+        SecurityContext::new(SecurityConfig::with_audit())
+            .with_audit_logger(Box::new(SharedLog(events.clone()))),
+    );
+
+    for cycle in 0..4u64 {
+        let timestamp = 5000 + cycle * 1000;
+        for id in ["bad", "temp", "humid"] {
+            gateway.push(id, 21.0, timestamp).unwrap();
+        }
+        let report = gateway.flush_with_report().unwrap();
+
+        // Healthy channels keep flowing
+        assert!(report.frame.get_channel("temp").is_some());
+        assert!(report.frame.get_channel("humid").is_some());
+        assert!(report.frame.get_channel("bad").is_none());
+
+        if cycle < 3 {
+            assert_eq!(report.errors.len(), 1);
+            assert_eq!(report.errors[0].0, "bad");
+            assert!(matches!(report.errors[0].1, GatewayError::EncodingError(_)));
+        } else {
+            // Skipped without another attempt
+            assert!(report.errors.is_empty());
+            assert_eq!(report.faulted, vec!["bad".to_string()]);
+        }
+        assert_eq!(report.tripped.is_empty(), cycle != 2);
+    }
+
+    assert_eq!(gateway.faulted_channels(), vec!["bad"]);
+    assert_eq!(gateway.pending("bad").unwrap(), 4);
+    let check = gateway.health_check();
+    assert_eq!(check.status, HealthStatus::Degraded);
+    assert!(check.message.contains("Faulted channels: bad"));
+    {
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AuditEventType::Error);
+        assert!(events[0].details.contains("'bad'"));
+    }
+
+    // Buffered samples go out with the fresh context
+    gateway.reset_channel("bad", false).unwrap();
+    assert!(gateway.faulted_channels().is_empty());
+    let report = gateway.flush_with_report().unwrap();
+    assert!(report.is_clean());
+    assert!(report.frame.get_channel("bad").is_some());
+    assert_eq!(gateway.pending("bad").unwrap(), 0);
+    assert_eq!(gateway.health_check().status, HealthStatus::Healthy);
+}
+
+#[test]
+fn test_fail_fast_flush() {
+    let mut gateway = gateway_with_poisoned_channel(GatewayConfig {
+        fail_fast: true,
+        ..Default::default()
+    });
+    gateway.push("bad", 21.0, 5000).unwrap();
+    gateway.push("temp", 21.0, 5000).unwrap();
+
+    assert!(matches!(
+        gateway.flush(),
+        Err(GatewayError::EncodingError(_))
+    ));
+    assert_eq!(gateway.pending("bad").unwrap(), 1);
+    assert!(gateway.faulted_channels().is_empty());
+}
+
+// ============================================================================
+// Downsampling Tests
+// ============================================================================
+
+/// 100 samples/s for 3 s; sample `i` has value `i`
+fn push_vibration(gateway: &mut Gateway) {
+    for i in 0..300u64 {
+        gateway.push("vib", i as f64, i * 10).unwrap();
+    }
+}
+
+fn downsampled_gateway(carry_partial: bool) -> Gateway {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 1024,
+        structural_headers: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let mut downsample =
+        Downsample::new(1000, vec![Aggregate::Min, Aggregate::Max, Aggregate::Mean]);
+    downsample.carry_partial = carry_partial;
+    gateway
+        .add_channel("vib", ChannelConfig::with_downsample(downsample))
+        .unwrap();
+    gateway
+}
+
+#[test]
+fn test_downsample_bucket_aggregates() {
+    let downsample = Downsample {
+        raw_window_ms: 50,
+        ..Downsample::new(
+            1000,
+            vec![
+                Aggregate::Min,
+                Aggregate::Max,
+                Aggregate::Mean,
+                Aggregate::Count,
+                Aggregate::StdDev,
+            ],
+        )
+    };
+    let mut channel = Channel::new("vib", ChannelConfig::with_downsample(downsample)).unwrap();
+    for i in 0..300u64 {
+        channel.push(i as f64, i * 10).unwrap();
+    }
+
+    // The third bucket is still open
+    let streams = channel.streams();
+    let ids: Vec<&str> = streams.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        ["vib.min", "vib.max", "vib.mean", "vib.count", "vib.stddev"]
+    );
+    assert_eq!(streams[0].buffered(), &[(0.0, 0), (100.0, 1000)]);
+    assert_eq!(streams[1].buffered(), &[(99.0, 0), (199.0, 1000)]);
+    assert_eq!(streams[2].buffered(), &[(49.5, 0), (149.5, 1000)]);
+    assert_eq!(streams[3].buffered(), &[(100.0, 0), (100.0, 1000)]);
+    // Population standard deviation of 100 consecutive integers
+    let stddev = streams[4].buffered()[0].0;
+    assert!((stddev - (9999.0f64 / 12.0).sqrt()).abs() < 1e-9);
+    assert_eq!(channel.pending(), 10);
+
+    channel.close_bucket().unwrap();
+    assert_eq!(channel.streams()[2].buffered()[2], (249.5, 2000));
+
+    // Raw samples within 50 ms of the newest
+    let raw = channel.raw_window();
+    assert_eq!(raw.first(), Some(&(294.0, 2940)));
+    assert_eq!(raw.last(), Some(&(299.0, 2990)));
+}
+
+#[test]
+fn test_downsample_carries_partial_bucket() {
+    let mut gateway = downsampled_gateway(true);
+    push_vibration(&mut gateway);
+
+    let frame = gateway.flush().unwrap();
+    assert_eq!(frame.channel_count(), 3);
+    for id in ["vib.min", "vib.max", "vib.mean"] {
+        assert_eq!(frame.get_channel(id).unwrap().sample_count, Some(2));
+    }
+    assert!(frame.get_channel("vib").is_none());
+    assert_eq!(gateway.pending("vib").unwrap(), 0);
+
+    // The carried bucket closes with the next sample
+    gateway.push("vib", 300.0, 3000).unwrap();
+    let frame = gateway.flush().unwrap();
+    assert_eq!(frame.get_channel("vib.mean").unwrap().sample_count, Some(1));
+}
+
+#[test]
+fn test_downsample_emits_partial_bucket() {
+    let mut gateway = downsampled_gateway(false);
+    push_vibration(&mut gateway);
+
+    let estimate = gateway.estimate_flush();
+    assert!(estimate
+        .per_channel
+        .iter()
+        .all(|(_, _, samples)| *samples == 2));
+
+    let frame = gateway.flush().unwrap();
+    for id in ["vib.min", "vib.max", "vib.mean"] {
+        assert_eq!(frame.get_channel(id).unwrap().sample_count, Some(3));
+    }
+
+    // A late sample opens the emitted bucket again
+    gateway.push("vib", 1.0, 2995).unwrap();
+    let frame = gateway.flush().unwrap();
+    assert_eq!(frame.get_channel("vib.min").unwrap().sample_count, Some(1));
+}
+
+#[test]
+fn test_downsample_rejects_invalid_config() {
+    for downsample in [
+        Downsample::new(0, vec![Aggregate::Mean]),
+        Downsample::new(1000, vec![]),
+        Downsample::new(1000, vec![Aggregate::Mean, Aggregate::Mean]),
+    ] {
+        let config = ChannelConfig::with_downsample(downsample);
+        assert!(matches!(
+            Channel::new("vib", config),
+            Err(GatewayError::InvalidConfig(_))
+        ));
+    }
+}
+
+// ============================================================================
+// Starvation Protection
+// ============================================================================
+
+/// Frame budget fitting two single-sample entries, not three
+const TWO_ENTRY_FRAME: usize = 55;
+
+/// Gateway with `higher` priority-3 channels ahead of channel `p4`
+fn starved_gateway(
+    higher: usize,
+    p4: ChannelConfig,
+    starvation: Option<StarvationConfig>,
+) -> Gateway {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: TWO_ENTRY_FRAME,
+        starvation,
+        ..Default::default()
+    })
+    .unwrap();
+    for i in 0..higher {
+        gateway
+            .add_channel(format!("p3{}", i), ChannelConfig::with_priority(3))
+            .unwrap();
+    }
+    gateway
+        .add_channel("p4", ChannelConfig { priority: 4, ..p4 })
+        .unwrap();
+    gateway
+}
+
+/// Push one sample to every channel, flush, and report whether `p4` made it
+fn flush_round(gateway: &mut Gateway, round: u64) -> (bool, FlushReport) {
+    for id in gateway.channels() {
+        gateway
+            .push(&id, 20.0 + (round % 3) as f64, round * 1000)
+            .unwrap();
+    }
+    let report = gateway.flush_with_report().unwrap();
+    (report.frame.get_channel("p4").is_some(), report)
+}
+
+#[test]
+fn test_starved_channel_boosted_every_n_plus_one_flushes() {
+    let starvation = StarvationConfig {
+        flushes_per_level: 2,
+        level_step: 1,
+        ..Default::default()
+    };
+    let mut gateway = starved_gateway(2, ChannelConfig::default(), Some(starvation));
+
+    let mut since_included = 0;
+    for round in 0..30 {
+        let (included, report) = flush_round(&mut gateway, round);
+        assert_eq!(report.frame.channel_count(), 2);
+        if included {
+            since_included = 0;
+            assert!(report.priorities.contains(&("p4".to_string(), 4, 3)));
+        } else {
+            since_included += 1;
+            assert!(since_included <= 2, "p4 left out {} times", since_included);
+        }
+    }
+
+    // Off: the priority-3 channels take every frame
+    let mut gateway = starved_gateway(2, ChannelConfig::default(), None);
+    for round in 0..30 {
+        let (included, report) = flush_round(&mut gateway, round);
+        assert!(!included);
+        assert!(report
+            .priorities
+            .iter()
+            .all(|(_, configured, effective)| configured == effective));
+    }
+    assert_eq!(gateway.skipped_flushes("p4").unwrap(), 30);
+}
+
+#[test]
+fn test_near_full_buffer_boosts_earlier() {
+    let starvation = StarvationConfig {
+        flushes_per_level: 100,
+        level_step: 1,
+        near_full: 1.0,
+        ..Default::default()
+    };
+    // Two buffered samples still fit the frame
+    let p4 = ChannelConfig::with_overflow(2, OverflowPolicy::DropOldest);
+    let mut gateway = starved_gateway(3, p4, Some(starvation));
+
+    let rounds: Vec<bool> = (0..3)
+        .map(|round| flush_round(&mut gateway, round).0)
+        .collect();
+    // Boosted once the buffer is full, before overflow drops a sample
+    assert_eq!(rounds, vec![false, true, false]);
+    assert_eq!(gateway.dropped("p4").unwrap(), 0);
+    assert_eq!(gateway.skipped_flushes("p4").unwrap(), 1);
+}
+
+#[test]
+fn test_starvation_config_validated() {
+    for starvation in [
+        StarvationConfig {
+            flushes_per_level: 0,
+            ..Default::default()
+        },
+        StarvationConfig {
+            near_full: 1.5,
+            ..Default::default()
+        },
+    ] {
+        let config = GatewayConfig {
+            starvation: Some(starvation),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(GatewayError::InvalidConfig(_))
+        ));
+    }
+    assert!(GatewayConfig {
+        starvation: Some(StarvationConfig::default()),
+        ..Default::default()
+    }
+    .validate()
+    .is_ok());
+}
+
+// ============================================================================
+// Text Channels
+// ============================================================================
+
+fn text_gateway() -> Gateway {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel("status", ChannelConfig::with_value_kind(ValueKind::Text))
+        .unwrap();
+    gateway
+}
+
+/// Flush the gateway and decode its single text entry
+fn flush_text(gateway: &mut Gateway, decoder: &mut FrameDecoder) -> (usize, Vec<(String, u64)>) {
+    let frame = gateway.flush().unwrap();
+    let data = &frame.get_channel("status").unwrap().data;
+    (data.len(), decoder.decode_text("status", data).unwrap())
+}
+
+#[test]
+fn test_text_channel_roundtrip() {
+    let mut gateway = text_gateway();
+    let mut decoder = FrameDecoder::new();
+
+    let first = [("OK", 1_000), ("DEGRADED", 2_000), ("OK", 3_000)];
+    for (value, timestamp) in first {
+        gateway.push_str("status", value, timestamp).unwrap();
+    }
+    let (_, decoded) = flush_text(&mut gateway, &mut decoder);
+    assert_eq!(
+        decoded,
+        first.map(|(value, timestamp)| (value.to_string(), timestamp))
+    );
+
+    // Repeats cost a few bytes each, a novel string travels in full
+    gateway.push_str("status", "DEGRADED", 4_000).unwrap();
+    let (size, decoded) = flush_text(&mut gateway, &mut decoder);
+    assert!(size <= 5, "{} bytes", size);
+    assert_eq!(decoded, vec![("DEGRADED".to_string(), 4_000)]);
+
+    let fault = "FAULT E-1042: pump over-current";
+    gateway.push_str("status", fault, 5_000).unwrap();
+    gateway.push_str("status", "OK", 6_000).unwrap();
+    gateway.push_str("status", fault, 7_000).unwrap();
+    let estimate = gateway.estimate_flush();
+    let (size, decoded) = flush_text(&mut gateway, &mut decoder);
+    assert!(size < 2 * fault.len(), "{} bytes", size);
+    assert_eq!(estimate.per_channel, vec![("status".to_string(), size, 3)]);
+    assert_eq!(
+        decoded,
+        vec![
+            (fault.to_string(), 5_000),
+            ("OK".to_string(), 6_000),
+            (fault.to_string(), 7_000)
+        ]
+    );
+
+    assert_eq!(
+        decoder.context("status").unwrap().hash(),
+        gateway.channel_context("status").unwrap().hash()
+    );
+}
+
+#[test]
+fn test_text_channel_rejects_mixed_kinds_and_long_strings() {
+    let mut gateway = text_gateway();
+    gateway
+        .add_channel("temp", ChannelConfig::default())
+        .unwrap();
+
+    assert!(matches!(
+        gateway.push("status", 1.0, 1_000),
+        Err(GatewayError::ValueKindMismatch {
+            expected: ValueKind::Text,
+            ..
+        })
+    ));
+    let err = gateway.push_str("temp", "OK", 1_000).unwrap_err();
+    assert_eq!(err.to_string(), "Channel temp only accepts numeric values");
+
+    let longest = "x".repeat(MAX_TEXT_LEN);
+    gateway.push_str("status", &longest, 1_000).unwrap();
+    let too_long = "x".repeat(MAX_TEXT_LEN + 1);
+    assert!(matches!(
+        gateway.push_str("status", &too_long, 2_000),
+        Err(GatewayError::TextTooLong { len, max, .. }) if len == MAX_TEXT_LEN + 1 && max == MAX_TEXT_LEN
+    ));
+    assert_eq!(gateway.pending("status").unwrap(), 1);
+
+    // Text channels cannot be downsampled
+    let config = ChannelConfig {
+        downsample: Some(Downsample::new(1_000, [Aggregate::Last])),
+        ..ChannelConfig::with_value_kind(ValueKind::Text)
+    };
+    assert!(matches!(
+        gateway.add_channel("mode", config),
+        Err(GatewayError::InvalidConfig(_))
+    ));
+}
+
+// ============================================================================
+// Composite Messages
+// ============================================================================
+
+/// Gateway with 20 numeric channels and room for all of them
+fn twenty_channel_gateway(composite: bool) -> Gateway {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 1024,
+        composite,
+        ..Default::default()
+    })
+    .unwrap();
+    for i in 0..20 {
+        gateway
+            .add_channel(format!("s{:02}", i), ChannelConfig::default())
+            .unwrap();
+    }
+    gateway
+}
+
+/// One sample per channel, all in the same second
+fn push_round(gateway: &mut Gateway, round: u64) {
+    for i in 0..20 {
+        let value = 20.0 + i as f64 * 0.5 + round as f64 * 0.25;
+        gateway
+            .push(&format!("s{:02}", i), value, 60_000 * (round + 1) + i)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_composite_flush_is_smaller() {
+    let mut plain = twenty_channel_gateway(false);
+    let mut composite = twenty_channel_gateway(true);
+
+    for round in 0..3 {
+        push_round(&mut plain, round);
+        push_round(&mut composite, round);
+        let estimate = composite.estimate_flush();
+        let plain_size = plain.flush().unwrap().to_bytes().len();
+        let frame = composite.flush().unwrap();
+        let composite_size = frame.to_bytes().len();
+
+        assert_eq!(frame.channel_count(), 1);
+        assert_eq!(frame.version, Frame::VERSION_FLAGS);
+        assert_eq!(estimate.total_bytes, composite_size);
+        assert_eq!(estimate.per_channel[0].2, 20);
+        assert!(
+            composite_size * 4 <= plain_size * 3,
+            "round {}: {} bytes composite, {} bytes plain",
+            round,
+            composite_size,
+            plain_size
+        );
+        assert_eq!(composite.total_pending(), 0);
+    }
+}
+
+#[test]
+fn test_composite_roundtrip() {
+    let mut gateway = twenty_channel_gateway(true);
+    // Text channels keep their own entry
+    gateway
+        .add_channel("status", ChannelConfig::with_value_kind(ValueKind::Text))
+        .unwrap();
+    let mut decoder = FrameDecoder::new();
+
+    for round in 0..3 {
+        push_round(&mut gateway, round);
+        // A second sample for one channel goes in a second message
+        gateway
+            .push("s03", 99.5, 60_000 * (round + 1) + 1_500)
+            .unwrap();
+        gateway
+            .push_str("status", "OK", 60_000 * (round + 1))
+            .unwrap();
+
+        let bytes = gateway.flush().unwrap().to_bytes();
+        let entries = decoder.decode(&bytes).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].composite);
+        assert!(!entries[1].composite);
+
+        let data = entries[0].data.as_ref().unwrap();
+        let table = CompositeData::from_bytes(data).unwrap();
+        assert_eq!(table.channels.len(), 20);
+        assert_eq!(table.messages.len(), 2);
+
+        let samples = decoder.decode_composite(data).unwrap();
+        assert_eq!(samples.len(), 21);
+        for (id, value, timestamp) in &samples[..20] {
+            let i: u64 = id[1..].parse().unwrap();
+            let expected = 20.0 + i as f64 * 0.5 + round as f64 * 0.25;
+            assert!((value - expected).abs() < 0.01, "{}: {}", id, value);
+            assert_eq!(*timestamp, 60_000 * (round + 1));
+        }
+        assert_eq!(samples[20].0, "s03");
+        assert!((samples[20].1 - 99.5).abs() < 0.01);
+    }
+
+    for i in 0..20 {
+        let id = format!("s{:02}", i);
+        assert_eq!(
+            decoder.context(&id).unwrap().hash(),
+            gateway.channel_context(&id).unwrap().hash(),
+            "{}",
+            id
+        );
+    }
+}
+
+#[test]
+fn test_composite_leaves_out_what_does_not_fit() {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 60,
+        composite: true,
+        ..Default::default()
+    })
+    .unwrap();
+    for i in 0..20 {
+        let id = format!("s{:02}", i);
+        gateway
+            .add_channel(&id, ChannelConfig::with_priority(i as u8))
+            .unwrap();
+        gateway.push(&id, i as f64, 1_000).unwrap();
+    }
+
+    let frame = gateway.flush().unwrap();
+    assert!(frame.size() <= 60);
+    let carried = frame.channels[0].channel_ids();
+    assert!(!carried.is_empty() && carried.len() < 20);
+    // Highest priorities first, the rest stays buffered
+    assert_eq!(carried[0], "s00");
+    assert_eq!(gateway.pending("s00").unwrap(), 0);
+    assert_eq!(gateway.pending("s19").unwrap(), 1);
+}
+
+// ============================================================================
+// Snapshots
+// ============================================================================
+
+const SNAPSHOT_CHANNELS: [&str; 5] = ["temp", "humidity", "pressure", "co2", "light"];
+
+/// Reading `i` of snapshot channel `c`
+fn snapshot_reading(c: usize, i: u64) -> f64 {
+    20.0 + c as f64 * 10.0 + (i % 4) as f64 * 0.5
+}
+
+fn snapshot_gateway() -> Gateway {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 1024,
+        ..Default::default()
+    })
+    .unwrap();
+    for (c, id) in SNAPSHOT_CHANNELS.iter().enumerate() {
+        gateway
+            .add_channel(*id, ChannelConfig::with_priority(c as u8 + 1))
+            .unwrap();
+    }
+    gateway
+}
+
+/// Push readings `from..to` of every channel, a second apart
+fn push_snapshot_readings(gateway: &mut Gateway, from: u64, to: u64) {
+    for i in from..to {
+        for (c, id) in SNAPSHOT_CHANNELS.iter().enumerate() {
+            gateway
+                .push(id, snapshot_reading(c, i), 1_000 + i * 1000)
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_snapshot_warm_start() {
+    let mut gateway = snapshot_gateway();
+    for round in 0..10 {
+        push_snapshot_readings(&mut gateway, round * 10, round * 10 + 10);
+        gateway.flush().unwrap();
+    }
+    // Mid-operation: samples waiting for the next flush
+    push_snapshot_readings(&mut gateway, 100, 103);
+
+    let mut bytes = Vec::new();
+    gateway
+        .save_snapshot_with(
+            &mut bytes,
+            &SnapshotOptions {
+                include_buffers: true,
+            },
+        )
+        .unwrap();
+    let mut restored = Gateway::load_snapshot(bytes.as_slice()).unwrap();
+
+    let report = restored.snapshot_report().unwrap();
+    assert_eq!(report.restored, 5);
+    assert!(report.skipped.is_empty());
+    assert!(report.buffers_restored);
+    assert!(!report.metrics_enabled);
+    assert_eq!(restored.frame_sequence(), gateway.frame_sequence());
+    assert_eq!(restored.config().max_frame_size, 1024);
+    assert_eq!(restored.total_pending(), 15);
+    for id in SNAPSHOT_CHANNELS {
+        assert_eq!(
+            restored.channel_context(id).unwrap().hash(),
+            gateway.channel_context(id).unwrap().hash()
+        );
+    }
+
+    // The restored gateway picks up exactly where the saved one was
+    push_snapshot_readings(&mut gateway, 103, 110);
+    push_snapshot_readings(&mut restored, 103, 110);
+    assert_eq!(restored.flush().unwrap(), gateway.flush().unwrap());
+
+    // ...and compresses better than a gateway starting cold
+    let mut cold = snapshot_gateway();
+    push_snapshot_readings(&mut restored, 110, 113);
+    push_snapshot_readings(&mut cold, 110, 113);
+    let warm_frame = restored.flush().unwrap();
+    let cold_frame = cold.flush().unwrap();
+    assert!(
+        warm_frame.size() < cold_frame.size(),
+        "warm {} bytes, cold {} bytes",
+        warm_frame.size(),
+        cold_frame.size()
+    );
+}
+
+#[test]
+fn test_snapshot_without_buffers() {
+    let mut gateway = snapshot_gateway();
+    push_snapshot_readings(&mut gateway, 0, 5);
+    gateway.flush().unwrap();
+    push_snapshot_readings(&mut gateway, 5, 7);
+
+    let mut bytes = Vec::new();
+    gateway.save_snapshot(&mut bytes).unwrap();
+    let restored = Gateway::load_snapshot(bytes.as_slice()).unwrap();
+    assert!(!restored.snapshot_report().unwrap().buffers_restored);
+    assert_eq!(restored.total_pending(), 0);
+    assert_eq!(restored.channel_count(), 5);
+    assert!(Gateway::new().snapshot_report().is_none());
+}
+
+#[test]
+fn test_snapshot_skips_corrupt_context() {
+    let mut gateway = snapshot_gateway();
+    push_snapshot_readings(&mut gateway, 0, 10);
+    gateway.flush().unwrap();
+
+    let mut bytes = Vec::new();
+    gateway.save_snapshot(&mut bytes).unwrap();
+    // Channels are saved in id order: the first context is co2's
+    let context = bytes.windows(4).position(|w| w == b"ALCS").unwrap();
+    bytes[context + 20] ^= 0xFF;
+
+    let restored = Gateway::load_snapshot(bytes.as_slice()).unwrap();
+    let report = restored.snapshot_report().unwrap();
+    assert_eq!(report.restored, 4);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].id, "co2");
+    assert!(!restored.has_channel("co2"));
+    assert!(restored.has_channel("temp"));
+}
+
+#[test]
+fn test_snapshot_rejects_bad_header() {
+    let mut bytes = Vec::new();
+    snapshot_gateway().save_snapshot(&mut bytes).unwrap();
+
+    let mut other_version = bytes.clone();
+    other_version[4] += 1;
+    assert!(matches!(
+        Gateway::load_snapshot(other_version.as_slice()),
+        Err(GatewayError::InvalidSnapshot(_))
+    ));
+    assert!(matches!(
+        Gateway::load_snapshot(&bytes[..10]),
+        Err(GatewayError::InvalidSnapshot(_))
+    ));
+    assert!(matches!(
+        Gateway::load_snapshot(&b"not a snapshot"[..]),
+        Err(GatewayError::InvalidSnapshot(_))
+    ));
+}
+
+// ============================================================================
+// Channel Groups
+// ============================================================================
+
+const METER: [&str; 3] = ["meter.voltage", "meter.current", "meter.pf"];
+
+/// Gateway with the three meter channels grouped behind a noisier,
+/// more urgent load channel
+fn grouped_gateway(max_frame_size: usize) -> Gateway {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size,
+        starvation: Some(StarvationConfig {
+            flushes_per_level: 1,
+            level_step: 1,
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+    .unwrap();
+    gateway
+        .add_channel("load", ChannelConfig::with_priority(3))
+        .unwrap();
+    for (i, id) in METER.iter().enumerate() {
+        let config = ChannelConfig {
+            priority: 4 + i as u8,
+            ..ChannelConfig::with_group("meter")
+        };
+        gateway.add_channel(*id, config).unwrap();
+    }
+    gateway
+}
+
+#[test]
+fn test_group_co_located_or_co_deferred() {
+    // Fits the group next to short load bursts, not next to long ones
+    let mut gateway = grouped_gateway(150);
+    let (mut included, mut deferred) = (0, 0);
+
+    for round in 0..20u64 {
+        for i in 0..1 + round % 4 {
+            let timestamp = round * 10_000 + i * 1000;
+            gateway
+                .push(
+                    "load",
+                    50.0 + ((round * 7 + i) % 13) as f64 * 3.7,
+                    timestamp,
+                )
+                .unwrap();
+        }
+        for (i, id) in METER.iter().enumerate() {
+            let value = [230.0, 5.0, 0.9][i] + (round % 5) as f64 * 0.3;
+            gateway.push(id, value, round * 10_000).unwrap();
+        }
+
+        let report = gateway.flush_with_report().unwrap();
+        let carried: Vec<bool> = METER
+            .iter()
+            .map(|id| report.frame.get_channel(id).is_some())
+            .collect();
+        assert!(
+            carried.iter().all(|&c| c == carried[0]),
+            "round {}: {:?}",
+            round,
+            carried
+        );
+        assert_eq!(report.groups, vec![("meter".to_string(), carried[0])]);
+
+        let skipped: Vec<u32> = METER
+            .iter()
+            .map(|id| gateway.skipped_flushes(id).unwrap())
+            .collect();
+        assert!(skipped.iter().all(|&s| s == skipped[0]));
+        if carried[0] {
+            included += 1;
+            assert!(METER.iter().all(|id| gateway.pending(id).unwrap() == 0));
+        } else {
+            deferred += 1;
+            assert!(skipped[0] > 0);
+            assert!(METER.iter().all(|id| gateway.pending(id).unwrap() > 0));
+        }
+
+        // The group moves at the priority of its most urgent channel
+        let effective: Vec<u8> = report
+            .priorities
+            .iter()
+            .filter(|(id, _, _)| id.starts_with("meter."))
+            .map(|&(_, _, effective)| effective)
+            .collect();
+        assert_eq!(effective.len(), 3);
+        assert!(effective.iter().all(|&e| e == effective[0] && e <= 4));
+    }
+    assert!(included > 0 && deferred > 0, "{} {}", included, deferred);
+}
+
+#[test]
+fn test_flush_channels_brings_whole_group() {
+    let mut gateway = grouped_gateway(242);
+    for id in gateway.channels() {
+        gateway.push(&id, 1.0, 1000).unwrap();
+    }
+
+    let report = gateway.flush_channels_with_report(&["meter.pf"]).unwrap();
+    assert_eq!(report.frame.channel_count(), 3);
+    assert_eq!(report.priorities[0].0, "meter.pf");
+    assert!(METER
+        .iter()
+        .all(|id| report.frame.get_channel(id).is_some()));
+    assert_eq!(gateway.pending("load").unwrap(), 1);
+
+    // Groups survive a snapshot
+    let mut bytes = Vec::new();
+    gateway.save_snapshot(&mut bytes).unwrap();
+    let mut restored = Gateway::load_snapshot(bytes.as_slice()).unwrap();
+    for id in METER {
+        restored.push(id, 2.0, 2000).unwrap();
+    }
+    let report = restored
+        .flush_channels_with_report(&["meter.current"])
+        .unwrap();
+    assert_eq!(report.frame.channel_count(), 3);
+    assert_eq!(report.groups, vec![("meter".to_string(), true)]);
+}
+
+#[test]
+fn test_group_larger_than_frame_is_config_error() {
+    let mut gateway = grouped_gateway(40);
+    for (i, id) in METER.iter().enumerate() {
+        gateway.push(id, i as f64, 1000).unwrap();
+    }
+    gateway.push("load", 1.0, 1000).unwrap();
+
+    match gateway.flush_with_report() {
+        Err(GatewayError::InvalidConfig(message)) => assert!(message.contains("'meter'")),
+        other => panic!("expected a configuration error, got {:?}", other),
+    }
+    // Nothing was flushed
+    assert!(METER.iter().all(|id| gateway.pending(id).unwrap() == 1));
+    assert_eq!(gateway.pending("load").unwrap(), 1);
+}
+
+// ============================================================================
+// Zero-Copy Parsing
+// ============================================================================
+
+/// Flushed frames of a plain, a structural and a composite gateway
+fn frames_of_every_kind() -> Vec<Vec<u8>> {
+    let configs = [
+        GatewayConfig::default(),
+        GatewayConfig {
+            structural_headers: true,
+            ..Default::default()
+        },
+        GatewayConfig {
+            composite: true,
+            ..Default::default()
+        },
+    ];
+    let mut frames = Vec::new();
+    for config in configs {
+        let mut gateway = Gateway::with_config(config).unwrap();
+        for id in ["temp", "humidity", "co2"] {
+            gateway.add_channel(id, ChannelConfig::default()).unwrap();
+        }
+        for round in 0..3u64 {
+            for (i, id) in ["temp", "humidity", "co2"].iter().enumerate() {
+                let value = 20.0 + i as f64 * 5.0 + round as f64 * 0.5;
+                gateway.push(id, value, 1_000 * (round + 1)).unwrap();
+            }
+            frames.push(gateway.flush().unwrap().to_bytes());
+        }
+    }
+    frames
+}
+
+#[test]
+fn test_frame_ref_matches_frame() {
+    for bytes in frames_of_every_kind() {
+        let frame = Frame::from_bytes(&bytes).unwrap();
+        let parsed = FrameRef::from_bytes(&bytes).unwrap();
+
+        assert_eq!(parsed.to_owned(), frame);
+        assert_eq!(parsed.version, frame.version);
+        assert_eq!(parsed.channel_count(), frame.channel_count());
+        assert_eq!(
+            parsed.has_structural_headers(),
+            frame.has_structural_headers()
+        );
+        let channels: Vec<ChannelData> = parsed.channels().map(|ch| ch.to_owned()).collect();
+        assert_eq!(channels, frame.channels);
+        assert_eq!(
+            parsed.composite().map(|ch| ch.to_owned()).as_ref(),
+            frame.composite()
+        );
+        for ch in &frame.channels {
+            assert_eq!(parsed.get_channel(&ch.id).unwrap().data, &ch.data[..]);
+        }
+    }
+}
+
+#[test]
+fn test_frame_ref_rejects_what_frame_rejects() {
+    for bytes in frames_of_every_kind() {
+        for len in 0..bytes.len() {
+            let expected = Frame::from_bytes(&bytes[..len]).unwrap_err();
+            assert_eq!(FrameRef::from_bytes(&bytes[..len]), Err(expected));
+        }
+    }
+    assert_eq!(
+        FrameRef::from_bytes(&[9, 0]),
+        Err(FrameParseError::UnsupportedVersion(9))
+    );
+}
+
+#[test]
+fn test_decode_frame_ref_matches_decode_frame() {
+    let decoder = FrameDecoder::new();
+    for bytes in frames_of_every_kind() {
+        let owned = decoder.decode_frame(&Frame::from_bytes(&bytes).unwrap());
+        let borrowed = decoder.decode_frame_ref(&FrameRef::from_bytes(&bytes).unwrap());
+        assert_eq!(borrowed.len(), owned.len());
+        for (b, o) in borrowed.iter().zip(&owned) {
+            assert_eq!(b.id, o.id);
+            assert_eq!(b.composite, o.composite);
+            assert_eq!(b.data.as_ref().unwrap(), o.data.as_ref().unwrap());
+        }
+    }
+}
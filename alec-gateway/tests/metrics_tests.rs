@@ -12,16 +12,19 @@
 //! - Resilience index calculation
 //! - MetricsEngine orchestration
 //! - Gateway integration
+//! - Normalization state export and import
 
 #![cfg(feature = "metrics")]
 
 use alec_gateway::metrics::{
-    AlignmentStrategy, LogBase, MetricsConfig, MetricsEngine, MetricsSnapshot, MissingDataPolicy,
-    NormalizationConfig, NormalizationMethod, NumericsConfig, PayloadMetricsConfig,
-    ResilienceConfig, ResilienceThresholds, ResilienceZone, SignalComputeSchedule, SignalEstimator,
-    SignalWindow,
+    AlignedWindow, AlignmentStrategy, CriticalitySchedule, LogBase, MetricsConfig, MetricsEngine,
+    MetricsSnapshot, MissingDataPolicy, NormalizationConfig, NormalizationMethod, NumericsConfig,
+    PayloadMetricsConfig, ResilienceConfig, ResilienceThresholds, ResilienceZone,
+    SignalComputeSchedule, SignalEstimator, SignalWindow,
+};
+use alec_gateway::{
+    ChannelConfig, Gateway, GatewayConfig, GatewayError, TimestampPolicy, TimestampRule,
 };
-use alec_gateway::{ChannelConfig, Gateway, GatewayConfig};
 
 // ===========================================================================
 // Configuration Tests
@@ -193,6 +196,7 @@ fn test_config_normalization() {
             enabled: true,
             method: NormalizationMethod::ZScore,
             min_samples: 20,
+            include_in_snapshot: false,
         },
         ..Default::default()
     };
@@ -212,6 +216,7 @@ fn test_config_normalization_robust_mad() {
             enabled: true,
             method: NormalizationMethod::RobustMad,
             min_samples: 10,
+            include_in_snapshot: false,
         },
         ..Default::default()
     };
@@ -899,7 +904,7 @@ fn test_rapid_consecutive_flushes() {
 #[test]
 fn test_metrics_with_lorawan_config() {
     let gateway_config = GatewayConfig::lorawan(4);
-    let mut gateway = Gateway::with_config(gateway_config);
+    let mut gateway = Gateway::with_config(gateway_config).unwrap();
 
     gateway.enable_metrics(create_enabled_config());
 
@@ -991,3 +996,360 @@ fn test_multiple_flush_metrics_update() {
     assert!(first_size > 0);
     assert!(second_size > 0);
 }
+
+#[test]
+fn test_future_timestamp_does_not_pollute_window() {
+    let mut gateway = Gateway::new();
+    gateway.enable_metrics(create_enabled_config());
+    gateway
+        .add_channel(
+            "temp",
+            ChannelConfig::with_timestamp_policy(TimestampPolicy {
+                reject_after_skew_ms: Some(60_000),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+    let now = 1_700_000_000_000u64;
+    gateway.push("temp", 22.5, now).unwrap();
+    gateway.push("temp", 22.6, now + 1000).unwrap();
+    let samples = gateway.metrics_engine_mut().unwrap().window_sample_count();
+
+    // Bad RTC: ten years ahead
+    let future = now + 10 * 365 * 24 * 3600 * 1000;
+    let err = gateway.push("temp", 22.7, future).unwrap_err();
+    match err {
+        GatewayError::InvalidTimestamp {
+            channel,
+            timestamp,
+            rule,
+        } => {
+            assert_eq!(channel, "temp");
+            assert_eq!(timestamp, future);
+            assert_eq!(
+                rule,
+                TimestampRule::TooFarAhead {
+                    last: now + 1000,
+                    max_skew_ms: 60_000
+                }
+            );
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    assert_eq!(
+        gateway.metrics_engine_mut().unwrap().window_sample_count(),
+        samples
+    );
+    assert_eq!(gateway.pending("temp").unwrap(), 2);
+
+    // Subsequent in-range samples are still accepted
+    gateway.push("temp", 22.8, now + 2000).unwrap();
+    assert_eq!(gateway.pending("temp").unwrap(), 3);
+}
+
+// ===========================================================================
+// Criticality Ranking Tests
+// ===========================================================================
+
+fn criticality_config(schedule: CriticalitySchedule) -> MetricsConfig {
+    MetricsConfig {
+        enabled: true,
+        signal_compute: SignalComputeSchedule::EveryNFlushes(1),
+        resilience: ResilienceConfig {
+            enabled: true,
+            criticality_schedule: schedule,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Two identical channels and one independent channel, 60 s at 2 Hz
+fn feed_duplicates_and_independent(engine: &mut MetricsEngine) {
+    let mut lcg: u64 = 12345;
+    for i in 0..=120u64 {
+        let ts = i * 500;
+        let shared = 10.0 * (i as f64 * 0.7).sin() + (i % 7) as f64 * 0.3;
+        lcg = lcg
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let noise = (lcg >> 33) as f64 / (1u64 << 31) as f64 * 10.0 - 5.0;
+
+        engine.observe_sample("a", shared, ts);
+        engine.observe_sample("a_copy", shared, ts);
+        engine.observe_sample("independent", noise, ts);
+    }
+}
+
+#[test]
+fn test_criticality_ranks_independent_channel_first() {
+    let mut engine = MetricsEngine::new(criticality_config(CriticalitySchedule::default()));
+    feed_duplicates_and_independent(&mut engine);
+
+    let snapshot = engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+
+    let ranking = engine.last_resilience().unwrap().criticality_ranking();
+    assert_eq!(ranking.len(), 3);
+    assert_eq!(ranking[0].channel_id, "independent");
+    assert!(ranking[0].impact.unwrap() > 0.5);
+    for duplicate in &ranking[1..] {
+        assert!(duplicate.impact.unwrap() < 0.01);
+        assert!(duplicate.delta_r.is_some());
+    }
+
+    // The JSON snapshot carries the same ranking
+    let json_ranking = snapshot.resilience.unwrap().criticality.unwrap().ranking;
+    assert_eq!(json_ranking[0].channel_id, "independent");
+    assert!(json_ranking.iter().all(|c| !c.insufficient_data));
+}
+
+#[test]
+fn test_criticality_flags_sparse_channel() {
+    let mut engine = MetricsEngine::new(criticality_config(CriticalitySchedule::default()));
+    feed_duplicates_and_independent(&mut engine);
+    // A handful of samples: aligned by sample-and-hold, but too few to rank
+    for i in 0..5u64 {
+        engine.observe_sample("sparse", i as f64, i * 500);
+    }
+
+    engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+
+    let ranking = engine.last_resilience().unwrap().criticality_ranking();
+    assert_eq!(ranking.len(), 4);
+    let sparse = ranking.last().unwrap();
+    assert_eq!(sparse.channel_id, "sparse");
+    assert!(sparse.insufficient_data());
+    assert!(sparse.delta_r.is_none() && sparse.impact.is_none());
+    assert!(ranking[..3].iter().all(|c| !c.insufficient_data()));
+}
+
+#[test]
+fn test_criticality_schedule_independent_of_signal() {
+    let mut engine = MetricsEngine::new(criticality_config(CriticalitySchedule::EveryNFlushes(2)));
+    feed_duplicates_and_independent(&mut engine);
+
+    let first = engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    let first = first.resilience.unwrap();
+    assert!(first.r.is_some());
+    assert!(first.criticality.is_none());
+
+    let second = engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    assert!(second.resilience.unwrap().criticality.is_some());
+
+    let mut disabled = MetricsEngine::new(criticality_config(CriticalitySchedule::Disabled));
+    feed_duplicates_and_independent(&mut disabled);
+    disabled.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    assert!(disabled
+        .last_resilience()
+        .unwrap()
+        .criticality_ranking()
+        .is_empty());
+}
+
+// ===========================================================================
+// Aligned Window Tests
+// ===========================================================================
+
+fn aligned_config(max_channels_for_joint: usize) -> MetricsConfig {
+    MetricsConfig {
+        enabled: true,
+        signal_window: SignalWindow::LastNSamples(100),
+        signal_compute: SignalComputeSchedule::EveryNFlushes(1),
+        alignment: AlignmentStrategy::LinearInterpolation,
+        numerics: NumericsConfig {
+            min_aligned_samples: 5,
+            covariance_epsilon: 1e-5,
+            max_channels_for_joint,
+        },
+        ..Default::default()
+    }
+}
+
+/// Three channels with different periods and offsets; reference times are
+/// 0, 15 s, 30 s, 45 s and 60 s
+fn feed_offset_channels(engine: &mut MetricsEngine) {
+    // a: every 10 s, value = t in seconds
+    for k in 0..=6u64 {
+        engine.observe_sample("a", (k * 10) as f64, k * 10_000);
+    }
+    // b: every 5 s from 2.5 s, value = k²
+    for k in 0..=12u64 {
+        engine.observe_sample("b", (k * k) as f64, 2_500 + k * 5_000);
+    }
+    // c: every 7 s, alternating 0 and 7
+    for k in 0..=9u64 {
+        engine.observe_sample("c", (k % 2 * 7) as f64, k * 7_000);
+    }
+}
+
+#[test]
+fn test_aligned_window_linear_interpolation() {
+    let mut engine = MetricsEngine::new(aligned_config(16));
+    feed_offset_channels(&mut engine);
+    assert!(engine.aligned_window().is_none());
+
+    engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+
+    let aligned = engine.aligned_window().unwrap();
+    assert_eq!(aligned.timestamps, [0, 15_000, 30_000, 45_000, 60_000]);
+    assert_eq!(aligned.channels, ["a", "b", "c"]);
+    assert_eq!(
+        aligned.values,
+        [
+            [0.0, 0.0, 0.0],
+            [15.0, 6.5, 1.0],
+            [30.0, 30.5, 2.0],
+            [45.0, 72.5, 3.0],
+            [60.0, 132.5, 4.0],
+        ]
+    );
+}
+
+#[test]
+fn test_aligned_window_csv() {
+    let window = AlignedWindow {
+        timestamps: vec![1000, 2000],
+        channels: vec!["a".to_string(), "b".to_string()],
+        values: vec![vec![1.5, f64::NAN], vec![2.0, -3.25]],
+    };
+
+    let mut csv = Vec::new();
+    window.to_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "timestamp_ms,a,b\n1000,1.5,\n2000,2,-3.25\n"
+    );
+}
+
+#[test]
+fn test_aligned_window_only_after_signal_compute() {
+    let mut config = aligned_config(16);
+    config.signal_compute = SignalComputeSchedule::EveryNFlushes(2);
+    let mut engine = MetricsEngine::new(config);
+    feed_offset_channels(&mut engine);
+
+    engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    assert!(engine.aligned_window().is_none());
+    engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    assert_eq!(engine.aligned_window().unwrap().len(), 5);
+
+    engine.reset();
+    assert!(engine.aligned_window().is_none());
+}
+
+#[test]
+fn test_aligned_window_respects_max_channels() {
+    let mut engine = MetricsEngine::new(aligned_config(2));
+    feed_offset_channels(&mut engine);
+
+    engine.observe_frame(&[1, 2, 3], 60_000).unwrap();
+    assert!(engine.aligned_window().is_none());
+}
+
+// ===========================================================================
+// Normalization State Tests
+// ===========================================================================
+
+fn normalization_config(method: NormalizationMethod) -> MetricsConfig {
+    MetricsConfig {
+        enabled: true,
+        signal_compute: SignalComputeSchedule::EveryNFlushes(1),
+        normalization: NormalizationConfig {
+            enabled: true,
+            method,
+            min_samples: 10,
+            include_in_snapshot: true,
+        },
+        ..Default::default()
+    }
+}
+
+/// Sawtooth on "temp" with a spike, constant "door"
+fn feed_normalization(engine: &mut MetricsEngine, from: u64, to: u64) {
+    for i in from..to {
+        let temp = if i == 25 {
+            400.0
+        } else {
+            20.0 + (i % 7) as f64 * 0.5
+        };
+        engine.observe_sample("temp", temp, i * 1000);
+        engine.observe_sample("door", 1.0, i * 1000);
+    }
+}
+
+#[test]
+fn test_normalization_continuous_across_restart() {
+    for method in [NormalizationMethod::ZScore, NormalizationMethod::RobustMad] {
+        let mut engine = MetricsEngine::new(normalization_config(method));
+        feed_normalization(&mut engine, 0, 40);
+        let before = engine.normalize("temp", 23.0).unwrap();
+        let state = engine.normalization_state();
+        assert_eq!(state["temp"].n, 40);
+        assert_eq!(state["temp"].method, method);
+
+        let mut fresh = MetricsEngine::new(normalization_config(method));
+        assert_eq!(fresh.normalize("temp", 23.0), None);
+        assert_eq!(fresh.import_normalization_state(state), 2);
+        assert_eq!(fresh.normalize("temp", 23.0), Some(before));
+
+        // Learning resumes identically
+        feed_normalization(&mut engine, 40, 60);
+        feed_normalization(&mut fresh, 40, 60);
+        assert_eq!(fresh.normalization_state(), engine.normalization_state());
+    }
+}
+
+#[test]
+fn test_normalization_import_skips_other_method() {
+    let mut engine = MetricsEngine::new(normalization_config(NormalizationMethod::ZScore));
+    feed_normalization(&mut engine, 0, 20);
+
+    let mut robust = MetricsEngine::new(normalization_config(NormalizationMethod::RobustMad));
+    assert_eq!(
+        robust.import_normalization_state(engine.normalization_state()),
+        0
+    );
+    assert!(robust.normalization_state().is_empty());
+}
+
+#[test]
+fn test_normalization_needs_min_samples() {
+    let mut engine = MetricsEngine::new(normalization_config(NormalizationMethod::ZScore));
+    feed_normalization(&mut engine, 0, 9);
+    assert_eq!(engine.normalize("temp", 21.0), None);
+    feed_normalization(&mut engine, 9, 10);
+    assert!(engine.normalize("temp", 21.0).is_some());
+    assert_eq!(engine.normalize("unknown", 21.0), None);
+}
+
+#[test]
+fn test_normalization_constant_channel_stays_finite() {
+    let mut engine = MetricsEngine::new(normalization_config(NormalizationMethod::RobustMad));
+    feed_normalization(&mut engine, 0, 40);
+    assert_eq!(engine.normalization_state()["door"].scale, 0.0);
+    assert_eq!(engine.normalize("door", 1.0), Some(0.0));
+    assert!(engine.normalize("door", 2.0).unwrap().is_finite());
+}
+
+#[test]
+fn test_normalization_in_snapshot() {
+    let mut engine = MetricsEngine::new(normalization_config(NormalizationMethod::ZScore));
+    feed_normalization(&mut engine, 0, 20);
+    let snapshot = engine.observe_frame(&[1, 2, 3], 20_000).unwrap();
+    let params = snapshot.normalization.as_ref().unwrap();
+    assert_eq!(params["temp"].n, 20);
+
+    let parsed = MetricsSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+    assert_eq!(parsed.normalization.unwrap()["temp"].n, 20);
+
+    // Off by default
+    let mut config = normalization_config(NormalizationMethod::ZScore);
+    config.normalization.include_in_snapshot = false;
+    let mut engine = MetricsEngine::new(config);
+    feed_normalization(&mut engine, 0, 20);
+    let snapshot = engine.observe_frame(&[1, 2, 3], 20_000).unwrap();
+    assert!(snapshot.normalization.is_none());
+    assert!(!snapshot.to_json().unwrap().contains("normalization"));
+}
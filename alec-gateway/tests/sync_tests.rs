@@ -0,0 +1,170 @@
+// ALEC Gateway - Sync Tests
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Tests for per-channel context synchronization.
+//!
+//! These tests verify:
+//! - A decoder's channel contexts follow the gateway's through evolution
+//! - The sync byte cap defers lower-priority channels
+//! - Requests for unknown channels are ignored
+
+use std::path::Path;
+
+use alec::context::Pattern;
+use alec::{Context, SyncConfig, SyncMessage, SyncState, Synchronizer};
+use alec_gateway::{ChannelConfig, FrameDecoder, Gateway, GatewayConfig, SyncFrame};
+
+/// Preload whose dictionary evolution prunes and reorders
+fn write_preload(path: &Path) {
+    let mut ctx = Context::new();
+    // Used once: pruned by the first evolution
+    ctx.register_pattern(Pattern::numeric(1.5)).unwrap();
+    // Rare before frequent: reordered by the first evolution
+    for (value, uses) in [(20.0, 3), (21.0, 8)] {
+        for _ in 0..uses {
+            ctx.register_pattern(Pattern::numeric(value)).unwrap();
+        }
+    }
+    ctx.save_to_file(path, "temperature").unwrap();
+}
+
+fn sync_config() -> SyncConfig {
+    SyncConfig {
+        announce_interval: 5,
+        ..Default::default()
+    }
+}
+
+/// Exchange one round of sync frames, returning the gateway's messages
+fn sync_round(gateway: &mut Gateway, decoder: &mut FrameDecoder) -> Vec<(String, SyncMessage)> {
+    let announces = gateway.sync_announces();
+    if !announces.is_empty() {
+        let uplink = SyncFrame::new(announces.clone()).to_bytes();
+        let responses = decoder.handle_sync(&uplink).unwrap();
+        if !responses.is_empty() {
+            let downlink = SyncFrame::new(responses).to_bytes();
+            gateway.handle_sync(&downlink).unwrap();
+        }
+    }
+    announces
+}
+
+#[test]
+fn test_decoder_contexts_follow_evolution() {
+    let dir = tempfile::tempdir().unwrap();
+    let preload = dir.path().join("temperature.alec-context");
+    write_preload(&preload);
+    let preload_path = preload.to_str().unwrap().to_string();
+
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        max_frame_size: 1024,
+        sync: sync_config(),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut decoder = FrameDecoder::with_sync_config(sync_config());
+    let channels = ["temp_a", "temp_b", "temp_c"];
+    for (priority, id) in channels.iter().enumerate() {
+        gateway
+            .add_channel(
+                *id,
+                ChannelConfig {
+                    preload_path: Some(preload_path.clone()),
+                    priority: priority as u8,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    // The last channel starts from an empty context on the decoder
+    let initial = Context::load_from_file(&preload).unwrap();
+    decoder.add_context("temp_a", initial.clone());
+    decoder.add_context("temp_b", initial.clone());
+
+    for i in 0..1000u64 {
+        for (offset, id) in channels.iter().enumerate() {
+            let value = 20.0 + offset as f64 + (i % 7) as f64 * 0.1;
+            gateway.push(id, value, 1000 + i * 1000).unwrap();
+        }
+        if i % 10 == 9 {
+            let frame = gateway.flush().unwrap();
+            assert_eq!(decoder.decode_frame(&frame).len(), channels.len());
+            sync_round(&mut gateway, &mut decoder);
+        }
+    }
+
+    for id in channels {
+        let local = gateway.channel_context(id).unwrap();
+        let remote = decoder.context(id).unwrap();
+        assert_ne!(local.hash(), initial.hash(), "{} did not evolve", id);
+        assert_eq!(remote.hash(), local.hash(), "{} out of sync", id);
+        assert_eq!(remote.dictionary_version(), local.dictionary_version());
+        assert_eq!(decoder.sync_state(id), Some(&SyncState::Synchronized));
+    }
+}
+
+#[test]
+fn test_sync_budget_defers_lower_priority() {
+    let mut gateway = Gateway::with_config(GatewayConfig {
+        sync: SyncConfig {
+            announce_interval: 1,
+            ..Default::default()
+        },
+        // Header and one announce entry
+        max_sync_bytes: SyncFrame::new(vec![(
+            "high".to_string(),
+            Synchronizer::create_announce(&Context::new()),
+        )])
+        .size(),
+        ..Default::default()
+    })
+    .unwrap();
+    for (id, priority) in [("low", 5), ("high", 1)] {
+        gateway
+            .add_channel(
+                id,
+                ChannelConfig {
+                    priority,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        gateway.push(id, 20.0, 1000).unwrap();
+    }
+    gateway.flush().unwrap();
+
+    let first = gateway.sync_announces();
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].0, "high");
+
+    let second = gateway.sync_announces();
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].0, "low");
+
+    assert!(gateway.sync_announces().is_empty());
+}
+
+#[test]
+fn test_sync_request_answered_with_diff() {
+    let mut gateway = Gateway::new();
+    gateway
+        .add_channel("temp", ChannelConfig::default())
+        .unwrap();
+
+    let request = SyncFrame::new(vec![
+        ("temp".to_string(), Synchronizer::create_request(0, None)),
+        ("gone".to_string(), Synchronizer::create_request(0, None)),
+    ]);
+    gateway.handle_sync(&request.to_bytes()).unwrap();
+
+    let messages = gateway.sync_announces();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0, "temp");
+    assert!(matches!(messages[0].1, SyncMessage::Diff(_)));
+    assert!(gateway.sync_announces().is_empty());
+
+    assert!(gateway.handle_sync(&[1, 0]).is_err());
+}
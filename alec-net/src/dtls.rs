@@ -0,0 +1,542 @@
+// ALEC Net - Network transports for ALEC channels
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! UDP channel secured by DTLS 1.2
+//!
+//! [`DtlsChannel`] implements [`Channel`] over a UDP socket, one peer per
+//! channel. The handshake is driven by a [`DtlsConfig`]:
+//! - **PSK** (`DtlsConfig::psk` set): `TLS_PSK_WITH_AES_128_CCM_8`, the
+//!   suite of the IoT DTLS profile (RFC 7925). The server only answers the
+//!   configured identity.
+//! - **Certificates** (`tls.allow_self_signed`): each side presents a
+//!   self-signed certificate generated by [`DtlsChannel::new`] and the peer
+//!   is authenticated by its fingerprint through an attached
+//!   [`SecurityContext`] (`ECDHE_ECDSA_WITH_AES_128_CCM_8`).
+//!
+//! Handshake outcomes are audited on the security context
+//! (`ConnectionEstablished` / `AuthSuccess`, or `AuthFailure`), and the
+//! [`TlsState`] is exposed for health checks. DTLS does not fragment
+//! application data: messages whose record would exceed the configured
+//! MTU are still sent, with a warning, and counted.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alec::channel::{Channel, ChannelMetrics};
+use alec::error::{ChannelError, Result};
+use alec::health::{HealthCheck, HealthCheckable};
+use alec::security::{AuditEvent, AuditEventType, CertValidation, SecurityContext, Severity};
+use alec::tls::HandshakeResult;
+use alec::{AlecError, DtlsConfig, EncodedMessage, TlsState};
+use sha2::{Digest, Sha256};
+use tokio::net::UdpSocket;
+use tokio::runtime::Runtime;
+use webrtc_dtls::cipher_suite::CipherSuiteId;
+use webrtc_dtls::config::{ClientAuthType, Config, ExtendedMasterSecretType};
+use webrtc_dtls::conn::DTLSConn;
+use webrtc_dtls::crypto::Certificate;
+use webrtc_util::Conn;
+
+/// MTU used when [`DtlsConfig::mtu`] is not set
+pub const DEFAULT_MTU: u16 = 1200;
+
+/// DTLS record overhead with a CCM_8 suite: 13-byte record header,
+/// 8-byte explicit nonce and 8-byte tag
+pub const RECORD_OVERHEAD: usize = 29;
+
+/// Handshake timeout used unless [`DtlsChannel::with_handshake_timeout`]
+/// is called
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest datagram accepted from the peer
+const MAX_DATAGRAM: usize = 8192;
+
+const PROTOCOL_VERSION: &str = "DTLS 1.2";
+
+/// Which side of the handshake this channel plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Client,
+    Server,
+}
+
+/// [`Channel`] over UDP secured by DTLS
+///
+/// Created in [`TlsState::Disconnected`]; [`connect`](Self::connect) (client)
+/// or [`bind`](Self::bind) then [`accept`](Self::accept) (server) run the
+/// handshake. The channel owns a small Tokio runtime, so it is used from
+/// plain threads like any other [`Channel`].
+pub struct DtlsChannel {
+    config: DtlsConfig,
+    runtime: Runtime,
+    socket: Option<Arc<UdpSocket>>,
+    conn: Option<Arc<DTLSConn>>,
+    state: TlsState,
+    certificate: Option<Certificate>,
+    security: Option<Arc<SecurityContext>>,
+    handshake_timeout: Duration,
+    handshake: Option<HandshakeResult>,
+    metrics: ChannelMetrics,
+    oversized: u64,
+    errors: u64,
+}
+
+impl std::fmt::Debug for DtlsChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DtlsChannel")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .field("local_addr", &self.local_addr())
+            .field("peer_addr", &self.peer_addr())
+            .field("handshake", &self.handshake)
+            .finish()
+    }
+}
+
+impl DtlsChannel {
+    /// Create a channel from its DTLS configuration
+    ///
+    /// Fails if the configuration has neither a PSK nor
+    /// `tls.allow_self_signed`: certificate files are not supported yet.
+    pub fn new(config: DtlsConfig) -> Result<Self> {
+        let certificate = if config.psk.is_some() {
+            None
+        } else if config.tls.allow_self_signed {
+            let name = config
+                .tls
+                .server_name
+                .clone()
+                .unwrap_or_else(|| "alec".to_string());
+            Some(Certificate::generate_self_signed(vec![name]).map_err(dtls_error)?)
+        } else {
+            return Err(disconnected(
+                "DTLS needs a PSK or allow_self_signed; certificate files are not supported",
+            ));
+        };
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| disconnected(format!("Failed to start DTLS runtime: {}", e)))?;
+
+        Ok(Self {
+            config,
+            runtime,
+            socket: None,
+            conn: None,
+            state: TlsState::Disconnected,
+            certificate,
+            security: None,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            handshake: None,
+            metrics: ChannelMetrics::default(),
+            oversized: 0,
+            errors: 0,
+        })
+    }
+
+    /// Validate peer fingerprints and audit handshakes on `security`
+    pub fn with_security(mut self, security: Arc<SecurityContext>) -> Self {
+        self.security = Some(security);
+        self
+    }
+
+    /// Give up a handshake (or waiting for a client) after `timeout`
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Current connection state
+    pub fn state(&self) -> TlsState {
+        self.state
+    }
+
+    /// Outcome of the last successful handshake
+    pub fn handshake(&self) -> Option<&HandshakeResult> {
+        self.handshake.as_ref()
+    }
+
+    /// SHA-256 fingerprint of this side's certificate (certificate mode)
+    ///
+    /// Register it with the peer's
+    /// [`SecurityConfig::allow_fingerprint`](alec::security::SecurityConfig::allow_fingerprint).
+    pub fn local_fingerprint(&self) -> Option<String> {
+        self.certificate
+            .as_ref()
+            .and_then(|cert| cert.certificate.first())
+            .map(|der| fingerprint(&der.0))
+    }
+
+    /// Address of the local socket, once bound
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.socket.as_ref().and_then(|s| s.local_addr().ok())
+    }
+
+    /// Address of the peer, once connected
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.socket.as_ref().and_then(|s| s.peer_addr().ok())
+    }
+
+    /// Largest message sent without exceeding the MTU
+    pub fn max_message_size(&self) -> usize {
+        (self.config.mtu.unwrap_or(DEFAULT_MTU) as usize).saturating_sub(RECORD_OVERHEAD)
+    }
+
+    /// Messages sent although their record exceeded the MTU
+    pub fn oversized_messages(&self) -> u64 {
+        self.oversized
+    }
+
+    /// Bind the local UDP socket, returning its address
+    pub fn bind(&mut self, addr: SocketAddr) -> Result<SocketAddr> {
+        let socket = self
+            .runtime
+            .block_on(UdpSocket::bind(addr))
+            .map_err(|e| disconnected(format!("Failed to bind {}: {}", addr, e)))?;
+        let local = socket
+            .local_addr()
+            .map_err(|e| disconnected(format!("Failed to bind {}: {}", addr, e)))?;
+        self.socket = Some(Arc::new(socket));
+        Ok(local)
+    }
+
+    /// Run the handshake as a client with the server at `remote`
+    ///
+    /// Binds an ephemeral port first unless [`bind`](Self::bind) was called.
+    pub fn connect(&mut self, remote: SocketAddr) -> Result<&HandshakeResult> {
+        if self.socket.is_none() {
+            self.bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))?;
+        }
+        let socket = Arc::clone(self.socket.as_ref().expect("bound above"));
+        self.runtime
+            .block_on(socket.connect(remote))
+            .map_err(|e| disconnected(format!("Failed to connect to {}: {}", remote, e)))?;
+        self.run_handshake(Role::Client, socket, remote)
+    }
+
+    /// Wait for a client on the bound socket and run the handshake as a
+    /// server
+    ///
+    /// The first datagram received selects the peer; the channel then only
+    /// talks to it.
+    pub fn accept(&mut self) -> Result<&HandshakeResult> {
+        let socket = Arc::clone(
+            self.socket
+                .as_ref()
+                .ok_or_else(|| disconnected("DTLS server socket is not bound"))?,
+        );
+        let timeout = self.handshake_timeout;
+        let peer = self.runtime.block_on(async {
+            let mut probe = [0u8; 1];
+            tokio::time::timeout(timeout, socket.peek_from(&mut probe)).await
+        });
+        let peer = match peer {
+            Ok(Ok((_, peer))) => peer,
+            Ok(Err(e)) => return Err(disconnected(format!("Failed to receive: {}", e))),
+            Err(_) => {
+                return Err(ChannelError::Timeout {
+                    timeout_ms: timeout.as_millis() as u64,
+                }
+                .into())
+            }
+        };
+        self.runtime
+            .block_on(socket.connect(peer))
+            .map_err(|e| disconnected(format!("Failed to connect to {}: {}", peer, e)))?;
+        self.run_handshake(Role::Server, socket, peer)
+    }
+
+    /// Send raw bytes (e.g. a gateway frame) in one DTLS record
+    pub fn send_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let conn = self.connected()?;
+        if bytes.len() > self.max_message_size() {
+            self.oversized += 1;
+            log::warn!(
+                "DTLS record of {} bytes exceeds the {}-byte MTU and will be fragmented by IP",
+                bytes.len() + RECORD_OVERHEAD,
+                self.config.mtu.unwrap_or(DEFAULT_MTU)
+            );
+        }
+
+        let result = self.runtime.block_on(conn.write(bytes, None));
+        match result {
+            Ok(_) => {
+                self.metrics.bytes_sent += bytes.len() as u64;
+                self.metrics.messages_sent += 1;
+                Ok(())
+            }
+            Err(e) => {
+                self.record_error();
+                Err(self.fail(e))
+            }
+        }
+    }
+
+    /// Receive the bytes of one DTLS record
+    pub fn receive_bytes(&mut self, timeout: Duration) -> Result<Vec<u8>> {
+        let conn = self.connected()?;
+        let mut buffer = vec![0u8; MAX_DATAGRAM];
+        let result = self.runtime.block_on(conn.read(&mut buffer, Some(timeout)));
+        match result {
+            Ok(n) => {
+                buffer.truncate(n);
+                self.metrics.bytes_received += n as u64;
+                self.metrics.messages_received += 1;
+                Ok(buffer)
+            }
+            Err(webrtc_dtls::Error::ErrDeadlineExceeded) => Err(ChannelError::Timeout {
+                timeout_ms: timeout.as_millis() as u64,
+            }
+            .into()),
+            Err(e) => {
+                self.record_error();
+                Err(self.fail(e))
+            }
+        }
+    }
+
+    fn run_handshake(
+        &mut self,
+        role: Role,
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+    ) -> Result<&HandshakeResult> {
+        self.state = TlsState::Handshaking;
+        let config = self.dtls_config(role);
+        let timeout = self.handshake_timeout;
+        let conn: Arc<dyn Conn + Send + Sync> = socket;
+        let outcome = self.runtime.block_on(async {
+            tokio::time::timeout(
+                timeout,
+                DTLSConn::new(conn, config, role == Role::Client, None),
+            )
+            .await
+        });
+        let conn = match outcome {
+            Ok(Ok(conn)) => Arc::new(conn),
+            Ok(Err(e)) => return Err(self.reject(peer, format!("handshake failed: {}", e))),
+            Err(_) => {
+                return Err(self.reject(
+                    peer,
+                    format!("handshake timed out after {}ms", timeout.as_millis()),
+                ))
+            }
+        };
+
+        // Certificate mode: authenticate the peer by its fingerprint
+        let state = self.runtime.block_on(conn.connection_state());
+        let peer_fingerprint = state.peer_certificates.first().map(|der| fingerprint(der));
+        if let (Some(fp), Some(security)) = (&peer_fingerprint, &self.security) {
+            let validation = security.validate_cert(fp);
+            if validation != CertValidation::Valid {
+                let _ = self.runtime.block_on(conn.close());
+                return Err(self.reject(peer, format!("peer certificate {}: {}", fp, validation)));
+            }
+        }
+
+        self.conn = Some(conn);
+        self.state = TlsState::Connected;
+        self.audit(
+            AuditEvent::new(
+                AuditEventType::ConnectionEstablished,
+                format!("DTLS session with {}", peer),
+            )
+            .with_field("peer", peer.to_string())
+            .with_field("cipher_suite", self.cipher_suite().to_string()),
+        );
+        self.audit(
+            AuditEvent::new(
+                AuditEventType::AuthSuccess,
+                format!("DTLS peer {} authenticated", peer),
+            )
+            .with_field("peer", peer.to_string()),
+        );
+        Ok(self.handshake.insert(HandshakeResult {
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            cipher_suite: self.cipher_suite().to_string(),
+            peer_fingerprint,
+            alpn_protocol: None,
+        }))
+    }
+
+    fn dtls_config(&self, role: Role) -> Config {
+        let mut config = Config {
+            cipher_suites: vec![self.cipher_suite()],
+            extended_master_secret: ExtendedMasterSecretType::Require,
+            mtu: self.config.mtu.unwrap_or(DEFAULT_MTU) as usize,
+            flight_interval: Duration::from_millis(
+                self.config.retransmit_timeout_ms.unwrap_or(0) as u64
+            ),
+            server_name: self.config.tls.server_name.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        if let Some(psk) = &self.config.psk {
+            let identity = psk.identity.clone().into_bytes();
+            let key = psk.key.clone();
+            if role == Role::Client {
+                config.psk_identity_hint = Some(identity);
+                config.psk = Some(Arc::new(move |_hint: &[u8]| Ok(key.clone())));
+            } else {
+                config.psk = Some(Arc::new(move |peer_identity: &[u8]| {
+                    if peer_identity == identity {
+                        Ok(key.clone())
+                    } else {
+                        Err(webrtc_dtls::Error::ErrIdentityNoPsk)
+                    }
+                }));
+            }
+        } else if let Some(certificate) = &self.certificate {
+            config.certificates = vec![certificate.clone()];
+            // Self-signed peers: trust comes from fingerprint validation
+            config.insecure_skip_verify = true;
+            if role == Role::Server {
+                config.client_auth = ClientAuthType::RequireAnyClientCert;
+            }
+        }
+        config
+    }
+
+    fn cipher_suite(&self) -> CipherSuiteId {
+        if self.config.psk.is_some() {
+            CipherSuiteId::Tls_Psk_With_Aes_128_Ccm_8
+        } else {
+            CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_128_Ccm_8
+        }
+    }
+
+    fn connected(&self) -> Result<Arc<DTLSConn>> {
+        match (&self.conn, self.state) {
+            (Some(conn), TlsState::Connected) => Ok(Arc::clone(conn)),
+            _ => Err(disconnected(format!(
+                "DTLS channel is not connected ({:?})",
+                self.state
+            ))),
+        }
+    }
+
+    /// Record a failed handshake and build its error
+    fn reject(&mut self, peer: SocketAddr, reason: String) -> AlecError {
+        self.state = TlsState::Error;
+        self.conn = None;
+        self.audit(
+            AuditEvent::new(
+                AuditEventType::AuthFailure,
+                format!("DTLS peer {}: {}", peer, reason),
+            )
+            .with_severity(Severity::High)
+            .with_field("peer", peer.to_string())
+            .with_field("reason", reason.as_str()),
+        );
+        disconnected(format!("DTLS {}", reason))
+    }
+
+    /// Record a failed established session and build its error
+    fn fail(&mut self, error: webrtc_dtls::Error) -> AlecError {
+        if matches!(
+            error,
+            webrtc_dtls::Error::ErrConnClosed | webrtc_dtls::Error::ErrAlertFatalOrClose
+        ) {
+            self.state = TlsState::Closed;
+        }
+        dtls_error(error)
+    }
+
+    fn record_error(&mut self) {
+        self.errors += 1;
+        let attempts = self.metrics.messages_sent + self.metrics.messages_received + self.errors;
+        self.metrics.error_rate = self.errors as f32 / attempts as f32;
+    }
+
+    fn audit(&self, event: AuditEvent) {
+        if let Some(security) = &self.security {
+            security.audit(event);
+        }
+    }
+}
+
+impl Channel for DtlsChannel {
+    fn send(&mut self, message: EncodedMessage) -> Result<()> {
+        self.send_bytes(&message.to_bytes())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<EncodedMessage> {
+        let bytes = self.receive_bytes(timeout)?;
+        EncodedMessage::from_bytes(&bytes)
+            .ok_or_else(|| disconnected("Received a datagram that is not an ALEC message"))
+    }
+
+    fn is_available(&self) -> bool {
+        self.state == TlsState::Connected
+    }
+
+    fn metrics(&self) -> ChannelMetrics {
+        self.metrics.clone()
+    }
+
+    fn close(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let _ = self.runtime.block_on(conn.close());
+            if let Some(peer) = self.peer_addr() {
+                self.audit(
+                    AuditEvent::new(
+                        AuditEventType::ConnectionClosed,
+                        format!("DTLS session with {}", peer),
+                    )
+                    .with_field("peer", peer.to_string()),
+                );
+            }
+        }
+        if self.state != TlsState::Error {
+            self.state = TlsState::Closed;
+        }
+    }
+}
+
+impl HealthCheckable for DtlsChannel {
+    fn health_check(&self) -> HealthCheck {
+        let start = Instant::now();
+        let check = match self.state {
+            TlsState::Connected => HealthCheck::healthy("dtls"),
+            TlsState::Disconnected | TlsState::Handshaking => {
+                HealthCheck::degraded("dtls", format!("{:?}", self.state))
+            }
+            TlsState::Closed => HealthCheck::unhealthy("dtls", "Closed"),
+            TlsState::Error => HealthCheck::unhealthy("dtls", "Handshake failed"),
+        };
+        check.with_latency(start.elapsed())
+    }
+}
+
+impl Drop for DtlsChannel {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let _ = self.runtime.block_on(conn.close());
+        }
+    }
+}
+
+/// Lowercase hex SHA-256 of a DER certificate
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn disconnected(reason: impl Into<String>) -> AlecError {
+    ChannelError::Disconnected {
+        reason: reason.into(),
+    }
+    .into()
+}
+
+fn dtls_error(error: webrtc_dtls::Error) -> AlecError {
+    disconnected(format!("DTLS error: {}", error))
+}
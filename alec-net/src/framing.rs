@@ -0,0 +1,282 @@
+// ALEC Net - Network transports for ALEC channels
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Length-prefixed framing over byte streams (TCP)
+//!
+//! [`AlecFrameCodec`] implements the `tokio_util` codec traits: each frame
+//! is a `u32` little-endian length followed by that many bytes, an
+//! [`EncodedMessage`] (`to_bytes`) or any serialized frame such as a
+//! gateway `Frame`. Use it with `tokio_util::codec::Framed` on a
+//! `TcpStream`; frames split across reads are reassembled.
+//!
+//! # Malformed lengths
+//!
+//! A zero length, or a length above [`max_frame_len`](AlecFrameCodec::with_max_frame_len),
+//! is reported as an `Err(FrameError)` item and the stream goes on:
+//! - a zero-length frame is consumed (its 4 prefix bytes)
+//! - an oversized frame is skipped: its declared length is trusted and
+//!   that many bytes are discarded, so the next frame is read from the
+//!   right offset
+//!
+//! Skipping only makes sense while the length is plausible. Beyond
+//! [`max_skip_len`](AlecFrameCodec::with_max_skip_len) the prefix is taken
+//! as corruption: there is no marker to resynchronize on, so the decoder
+//! fails with an `InvalidData` I/O error and the connection should be
+//! dropped and re-established (see [`FrameConnector`]).
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use alec::protocol::{HeaderExtension, MessageHeader, CHECKSUM_SIZE};
+use alec::recovery::RetryStrategy;
+use alec::{EncodedMessage, MAX_PAYLOAD_SIZE};
+use bytes::{Buf, BufMut, BytesMut};
+use tokio::net::TcpStream;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+/// Size of the length prefix
+pub const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Largest serialized [`EncodedMessage`]: extended header, a
+/// [`MAX_PAYLOAD_SIZE`] payload and a checksum
+pub const MAX_MESSAGE_LEN: usize =
+    MessageHeader::SIZE + HeaderExtension::MAX_SIZE + MAX_PAYLOAD_SIZE + CHECKSUM_SIZE;
+
+/// Declared length up to which oversized frames are skipped by default
+pub const DEFAULT_MAX_SKIP_LEN: usize = 1 << 20;
+
+/// A frame refused by [`AlecFrameCodec`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// Length prefix of zero
+    Empty,
+    /// Length above the codec's maximum
+    TooLong {
+        /// Declared or actual frame length
+        length: usize,
+        /// Codec's maximum frame length
+        max: usize,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Empty => write!(f, "Empty frame"),
+            FrameError::TooLong { length, max } => {
+                write!(f, "Frame of {} bytes exceeds maximum {}", length, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// `u32` LE length-prefixed frame codec
+///
+/// Decodes to `Result<BytesMut, FrameError>` items: recoverable framing
+/// errors are items, fatal ones (I/O, implausible length) are stream
+/// errors. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct AlecFrameCodec {
+    max_frame_len: usize,
+    max_skip_len: usize,
+    /// Bytes of an oversized frame still to discard
+    skipping: usize,
+}
+
+impl AlecFrameCodec {
+    /// Codec for frames up to [`MAX_MESSAGE_LEN`] bytes
+    pub fn new() -> Self {
+        Self {
+            max_frame_len: MAX_MESSAGE_LEN,
+            max_skip_len: DEFAULT_MAX_SKIP_LEN,
+            skipping: 0,
+        }
+    }
+
+    /// Accept frames up to `max_frame_len` bytes (capped at `u32::MAX`)
+    ///
+    /// For streams carrying frames larger than one message, such as
+    /// gateway frames of several channels.
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len.min(u32::MAX as usize);
+        self
+    }
+
+    /// Skip oversized frames declaring up to `max_skip_len` bytes; longer
+    /// declarations fail the stream
+    pub fn with_max_skip_len(mut self, max_skip_len: usize) -> Self {
+        self.max_skip_len = max_skip_len;
+        self
+    }
+
+    /// Largest frame accepted, in bytes
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Check a frame about to be written
+    fn check_outgoing(&self, length: usize) -> io::Result<()> {
+        let error = match length {
+            0 => FrameError::Empty,
+            length if length > self.max_frame_len => FrameError::TooLong {
+                length,
+                max: self.max_frame_len,
+            },
+            _ => return Ok(()),
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidInput, error))
+    }
+}
+
+impl Default for AlecFrameCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for AlecFrameCodec {
+    type Item = Result<BytesMut, FrameError>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        if self.skipping > 0 {
+            let skipped = self.skipping.min(src.len());
+            src.advance(skipped);
+            self.skipping -= skipped;
+            if self.skipping > 0 {
+                return Ok(None);
+            }
+        }
+
+        if src.len() < LENGTH_PREFIX_SIZE {
+            src.reserve(LENGTH_PREFIX_SIZE - src.len());
+            return Ok(None);
+        }
+        let mut prefix = [0u8; LENGTH_PREFIX_SIZE];
+        prefix.copy_from_slice(&src[..LENGTH_PREFIX_SIZE]);
+        let length = u32::from_le_bytes(prefix) as usize;
+
+        if length == 0 {
+            src.advance(LENGTH_PREFIX_SIZE);
+            return Ok(Some(Err(FrameError::Empty)));
+        }
+        if length > self.max_frame_len {
+            let error = FrameError::TooLong {
+                length,
+                max: self.max_frame_len,
+            };
+            if length > self.max_skip_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+            }
+            src.advance(LENGTH_PREFIX_SIZE);
+            self.skipping = length;
+            return Ok(Some(Err(error)));
+        }
+
+        let needed = LENGTH_PREFIX_SIZE + length;
+        if src.len() < needed {
+            src.reserve(needed - src.len());
+            return Ok(None);
+        }
+        src.advance(LENGTH_PREFIX_SIZE);
+        Ok(Some(Ok(src.split_to(length))))
+    }
+}
+
+impl Encoder<&[u8]> for AlecFrameCodec {
+    type Error = io::Error;
+
+    /// Write one frame; empty or oversized frames are refused with an
+    /// `InvalidInput` error and nothing is written
+    fn encode(&mut self, frame: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        self.check_outgoing(frame.len())?;
+        dst.reserve(LENGTH_PREFIX_SIZE + frame.len());
+        dst.put_u32_le(frame.len() as u32);
+        dst.extend_from_slice(frame);
+        Ok(())
+    }
+}
+
+impl Encoder<Vec<u8>> for AlecFrameCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: Vec<u8>, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(frame.as_slice(), dst)
+    }
+}
+
+impl Encoder<&EncodedMessage> for AlecFrameCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, message: &EncodedMessage, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(message.to_bytes().as_slice(), dst)
+    }
+}
+
+/// Opens framed TCP connections, retrying per a [`RetryStrategy`]
+///
+/// Call [`connect`](Self::connect) again to reconnect after the stream
+/// ends or fails.
+#[derive(Debug, Clone)]
+pub struct FrameConnector {
+    addr: SocketAddr,
+    strategy: RetryStrategy,
+    codec: AlecFrameCodec,
+}
+
+impl FrameConnector {
+    /// Connector to `addr` with the default codec
+    pub fn new(addr: SocketAddr, strategy: RetryStrategy) -> Self {
+        Self {
+            addr,
+            strategy,
+            codec: AlecFrameCodec::new(),
+        }
+    }
+
+    /// Use `codec` for the connections
+    pub fn with_codec(mut self, codec: AlecFrameCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Address connected to
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Connect, retrying failed attempts after the strategy's delays
+    ///
+    /// Returns the last connection error once the retries are exhausted.
+    pub async fn connect(&self) -> io::Result<Framed<TcpStream, AlecFrameCodec>> {
+        let mut attempt = 0;
+        loop {
+            match TcpStream::connect(self.addr).await {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    return Ok(Framed::new(stream, self.codec.clone()));
+                }
+                Err(e) => match self.strategy.delay_for_attempt(attempt) {
+                    Some(delay) => {
+                        log::debug!(
+                            "connect to {} failed ({}), retry {} in {:?}",
+                            self.addr,
+                            e,
+                            attempt + 1,
+                            delay
+                        );
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+}
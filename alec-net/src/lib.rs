@@ -0,0 +1,49 @@
+// ALEC Net - Network transports for ALEC channels
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! # ALEC Net - Network transports for ALEC channels
+//!
+//! Concrete implementations of [`alec::channel::Channel`] over real
+//! networks, kept out of the core crate so that it stays free of async
+//! runtimes and cryptographic libraries.
+//!
+//! ## Features
+//!
+//! - **DTLS over UDP** (feature `dtls`, default): [`DtlsChannel`], driven
+//!   by [`alec::DtlsConfig`], with PSK or self-signed certificates pinned
+//!   through [`alec::security::SecurityContext`]
+//! - **Framed TCP** (feature `tokio`): `AlecFrameCodec`, a `tokio_util`
+//!   codec length-prefixing messages or frames over byte streams, and
+//!   `FrameConnector` reconnecting per an
+//!   [`alec::recovery::RetryStrategy`]
+//!
+//! ## Quick Start
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use alec::channel::Channel;
+//! use alec::DtlsConfig;
+//! use alec_net::DtlsChannel;
+//!
+//! let config = DtlsConfig::new().with_psk("sensor-7", b"shared secret");
+//! let mut channel = DtlsChannel::new(config).unwrap();
+//! channel.connect("192.0.2.10:5684".parse().unwrap()).unwrap();
+//!
+//! let message = channel.receive(Duration::from_secs(5)).unwrap();
+//! println!("{} bytes", message.len());
+//! ```
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "dtls")]
+pub mod dtls;
+#[cfg(feature = "tokio")]
+pub mod framing;
+
+#[cfg(feature = "dtls")]
+pub use dtls::DtlsChannel;
+#[cfg(feature = "tokio")]
+pub use framing::{AlecFrameCodec, FrameConnector, FrameError};
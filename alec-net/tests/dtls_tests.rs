@@ -0,0 +1,207 @@
+//! DTLS channel over localhost UDP:
+//! - PSK handshake, then ALEC messages and raw frames round-trip
+//! - A wrong PSK is rejected on both sides and audited
+//! - Self-signed certificates are accepted only with a pinned fingerprint
+//! - Records above the MTU are still delivered, and counted
+
+#![cfg(feature = "dtls")]
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use alec::channel::Channel;
+use alec::health::{HealthCheckable, HealthStatus};
+use alec::security::{AuditEvent, AuditEventType, AuditLogger, SecurityConfig, SecurityContext};
+use alec::{Classifier, Context, Decoder, DtlsConfig, Encoder, RawData, TlsConfig, TlsState};
+use alec_net::DtlsChannel;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Audit logger shared with the test
+#[derive(Clone, Default)]
+struct SharedLog(Arc<Mutex<Vec<AuditEvent>>>);
+
+impl SharedLog {
+    fn count(&self, event_type: AuditEventType) -> usize {
+        let events = self.0.lock().unwrap();
+        events.iter().filter(|e| e.event_type == event_type).count()
+    }
+}
+
+impl AuditLogger for SharedLog {
+    fn log(&self, event: AuditEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn flush(&self) {}
+}
+
+fn security(config: SecurityConfig, log: &SharedLog) -> Arc<SecurityContext> {
+    let config = SecurityConfig {
+        audit_enabled: true,
+        ..config
+    };
+    Arc::new(SecurityContext::new(config).with_audit_logger(Box::new(log.clone())))
+}
+
+fn channel(config: DtlsConfig) -> DtlsChannel {
+    DtlsChannel::new(config)
+        .unwrap()
+        .with_handshake_timeout(TIMEOUT)
+}
+
+/// Bind `server` on localhost and accept a client in a thread running
+/// `session` once connected
+fn serve<T: Send + 'static>(
+    mut server: DtlsChannel,
+    session: impl FnOnce(&mut DtlsChannel) -> T + Send + 'static,
+) -> (
+    std::net::SocketAddr,
+    thread::JoinHandle<(DtlsChannel, Option<T>)>,
+) {
+    let addr = server.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let handle = thread::spawn(move || {
+        let outcome = server.accept().is_ok().then(|| session(&mut server));
+        (server, outcome)
+    });
+    (addr, handle)
+}
+
+#[test]
+fn test_psk_roundtrip() {
+    let config = DtlsConfig::new().with_psk("sensor-7", b"0123456789abcdef");
+    let log = SharedLog::default();
+    let server = channel(config.clone()).with_security(security(SecurityConfig::new(), &log));
+    assert_eq!(server.state(), TlsState::Disconnected);
+
+    let (addr, handle) = serve(server, |server| {
+        let context = Context::new();
+        let mut decoder = Decoder::new();
+        let values: Vec<f64> = (0..3)
+            .map(|_| {
+                let message = server.receive(TIMEOUT).unwrap();
+                decoder.decode(&message, &context).unwrap().value
+            })
+            .collect();
+        let frame = server.receive_bytes(TIMEOUT).unwrap();
+        server.send_bytes(b"ack").unwrap();
+        (values, frame)
+    });
+
+    let mut client = channel(config);
+    let handshake = client.connect(addr).unwrap().clone();
+    assert_eq!(handshake.cipher_suite, "TLS_PSK_WITH_AES_128_CCM_8");
+    assert_eq!(handshake.peer_fingerprint, None);
+    assert_eq!(client.state(), TlsState::Connected);
+    assert!(client.is_available());
+    assert_eq!(client.health_check().status, HealthStatus::Healthy);
+
+    let context = Context::new();
+    let mut encoder = Encoder::new();
+    for i in 0..3 {
+        let data = RawData::new(20.0 + i as f64, 1_000 * i);
+        let classification = Classifier::default().classify(&data, &context);
+        client
+            .send(encoder.encode(&data, &classification, &context))
+            .unwrap();
+    }
+    client.send_bytes(&[0xA1, 0xEC, 0x00, 0x01]).unwrap();
+    assert_eq!(client.receive_bytes(TIMEOUT).unwrap(), b"ack");
+
+    let (server, outcome) = handle.join().unwrap();
+    let (values, frame) = outcome.unwrap();
+    assert_eq!(values, [20.0, 21.0, 22.0]);
+    assert_eq!(frame, [0xA1, 0xEC, 0x00, 0x01]);
+    assert_eq!(server.state(), TlsState::Connected);
+    assert_eq!(server.metrics().messages_received, 4);
+    assert_eq!(log.count(AuditEventType::AuthSuccess), 1);
+    assert_eq!(log.count(AuditEventType::ConnectionEstablished), 1);
+
+    client.close();
+    assert_eq!(client.state(), TlsState::Closed);
+    assert!(client.send_bytes(b"late").is_err());
+}
+
+#[test]
+fn test_wrong_psk_rejected() {
+    let log = SharedLog::default();
+    let server = DtlsChannel::new(DtlsConfig::new().with_psk("sensor-7", b"0123456789abcdef"))
+        .unwrap()
+        .with_handshake_timeout(Duration::from_secs(2))
+        .with_security(security(SecurityConfig::new(), &log));
+    let (addr, handle) = serve(server, |_| ());
+
+    let mut client = DtlsChannel::new(DtlsConfig::new().with_psk("sensor-7", b"fedcba9876543210"))
+        .unwrap()
+        .with_handshake_timeout(Duration::from_secs(2));
+    assert!(client.connect(addr).is_err());
+    assert_eq!(client.state(), TlsState::Error);
+    assert!(!client.is_available());
+    assert_eq!(client.health_check().status, HealthStatus::Unhealthy);
+
+    let (server, outcome) = handle.join().unwrap();
+    assert!(outcome.is_none());
+    assert_eq!(server.state(), TlsState::Error);
+    assert_eq!(log.count(AuditEventType::AuthFailure), 1);
+    assert_eq!(log.count(AuditEventType::AuthSuccess), 0);
+}
+
+#[test]
+fn test_certificate_fingerprint_pinning() {
+    let config = DtlsConfig::from_tls(TlsConfig::new().allow_self_signed());
+    let client = channel(config.clone());
+    let stranger = channel(config.clone());
+    let pinned = client.local_fingerprint().unwrap();
+    assert_eq!(pinned.len(), 64);
+    assert_ne!(stranger.local_fingerprint(), Some(pinned.clone()));
+
+    for (mut client, accepted) in [(client, true), (stranger, false)] {
+        let log = SharedLog::default();
+        let security = security(SecurityConfig::secure().allow_fingerprint(&pinned), &log);
+        let server = channel(config.clone()).with_security(security);
+        let (addr, handle) = serve(server, |server| server.receive_bytes(TIMEOUT).unwrap());
+
+        let connected = client.connect(addr).is_ok();
+        if connected {
+            client.send_bytes(b"hello").unwrap();
+        }
+        let (server, outcome) = handle.join().unwrap();
+        assert_eq!(outcome.is_some(), accepted);
+        if accepted {
+            assert_eq!(outcome.unwrap(), b"hello");
+            assert_eq!(
+                server.handshake().unwrap().peer_fingerprint.as_deref(),
+                Some(pinned.as_str())
+            );
+        } else {
+            assert_eq!(server.state(), TlsState::Error);
+            assert_eq!(log.count(AuditEventType::AuthFailure), 1);
+        }
+    }
+}
+
+#[test]
+fn test_oversized_record_counted() {
+    let config = DtlsConfig::new()
+        .with_psk("sensor-7", b"0123456789abcdef")
+        .with_mtu(200);
+    let (addr, handle) = serve(channel(config.clone()), |server| {
+        let mut sizes = Vec::new();
+        for _ in 0..2 {
+            sizes.push(server.receive_bytes(TIMEOUT).unwrap().len());
+        }
+        sizes
+    });
+
+    let mut client = channel(config);
+    client.connect(addr).unwrap();
+    assert_eq!(client.max_message_size(), 171);
+    client.send_bytes(&[7; 171]).unwrap();
+    assert_eq!(client.oversized_messages(), 0);
+    client.send_bytes(&[7; 300]).unwrap();
+    assert_eq!(client.oversized_messages(), 1);
+
+    let (_, outcome) = handle.join().unwrap();
+    assert_eq!(outcome.unwrap(), [171, 300]);
+}
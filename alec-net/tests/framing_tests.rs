@@ -0,0 +1,200 @@
+//! Length-prefixed framing over byte streams:
+//! - 1000 messages of mixed sizes cross a tokio duplex stream intact and
+//!   in order, including one written a few bytes at a time
+//! - Empty and oversized frames are reported and skipped without losing
+//!   the frames around them; an implausible length fails the stream
+//! - Frames the codec would refuse are not written
+//! - The connector retries until a late listener comes up
+
+#![cfg(feature = "tokio")]
+
+use std::io;
+use std::time::Duration;
+
+use alec::protocol::{EncodedMessage, HeaderLayout, MessageHeader, MessageType, Priority};
+use alec::recovery::RetryStrategy;
+use alec_net::framing::{LENGTH_PREFIX_SIZE, MAX_MESSAGE_LEN};
+use alec_net::{AlecFrameCodec, FrameConnector, FrameError};
+use bytes::BytesMut;
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+const MESSAGES: usize = 1000;
+/// Index of the message written in tiny chunks
+const CHUNKED: usize = 500;
+
+/// Message `i`, with payload sizes cycling from 1 byte to 60 kB
+fn message(i: usize) -> EncodedMessage {
+    let size = match i % 10 {
+        0 => 60_000,
+        1 => 1,
+        n => n * 97,
+    };
+    EncodedMessage::new(
+        MessageHeader {
+            layout: HeaderLayout::default(),
+            message_type: MessageType::Data,
+            priority: Priority::P3Normal,
+            sequence: i as u16,
+            timestamp: i as u32,
+            context_version: 0,
+        },
+        (0..size).map(|b| (b + i) as u8).collect(),
+    )
+}
+
+/// Length-prefixed bytes of `frame`
+fn framed(frame: &[u8]) -> Vec<u8> {
+    let mut bytes = (frame.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(frame);
+    bytes
+}
+
+#[tokio::test]
+async fn test_duplex_mixed_sizes_in_order() {
+    let (client, server) = tokio::io::duplex(4096);
+
+    let writer = tokio::spawn(async move {
+        let mut sink = FramedWrite::new(client, AlecFrameCodec::new());
+        for i in 0..MESSAGES {
+            let message = message(i);
+            if i == CHUNKED {
+                // Bypass the codec: 3 bytes per write
+                SinkExt::<&EncodedMessage>::flush(&mut sink).await.unwrap();
+                let stream = sink.get_mut();
+                for chunk in framed(&message.to_bytes()).chunks(3) {
+                    stream.write_all(chunk).await.unwrap();
+                    stream.flush().await.unwrap();
+                    tokio::task::yield_now().await;
+                }
+            } else {
+                sink.send(&message).await.unwrap();
+            }
+        }
+        SinkExt::<&EncodedMessage>::close(&mut sink).await.unwrap();
+    });
+
+    let mut frames = FramedRead::new(server, AlecFrameCodec::new());
+    let mut received = 0;
+    while let Some(frame) = frames.next().await {
+        let frame = frame.unwrap().unwrap();
+        let expected = message(received);
+        assert_eq!(&frame[..], &expected.to_bytes()[..], "message {}", received);
+        assert_eq!(EncodedMessage::from_bytes(&frame).unwrap(), expected);
+        received += 1;
+    }
+    writer.await.unwrap();
+    assert_eq!(received, MESSAGES);
+}
+
+#[test]
+fn test_malformed_lengths_skipped() {
+    let mut codec = AlecFrameCodec::new().with_max_frame_len(16);
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&framed(b"first"));
+    src.extend_from_slice(&0u32.to_le_bytes());
+    src.extend_from_slice(&framed(&[0xEE; 40]));
+    src.extend_from_slice(&framed(b"second"));
+
+    assert_eq!(
+        &codec.decode(&mut src).unwrap().unwrap().unwrap()[..],
+        b"first"
+    );
+    assert_eq!(
+        codec.decode(&mut src).unwrap().unwrap(),
+        Err(FrameError::Empty)
+    );
+    assert_eq!(
+        codec.decode(&mut src).unwrap().unwrap(),
+        Err(FrameError::TooLong {
+            length: 40,
+            max: 16
+        })
+    );
+    assert_eq!(
+        &codec.decode(&mut src).unwrap().unwrap().unwrap()[..],
+        b"second"
+    );
+    assert!(codec.decode(&mut src).unwrap().is_none());
+}
+
+#[test]
+fn test_oversized_skip_across_reads() {
+    let mut codec = AlecFrameCodec::new().with_max_frame_len(16);
+    let mut bytes = framed(&[0xEE; 40]);
+    bytes.extend_from_slice(&framed(b"after"));
+
+    // Delivered one byte at a time
+    let mut src = BytesMut::new();
+    let mut items = Vec::new();
+    for &byte in &bytes {
+        src.extend_from_slice(&[byte]);
+        while let Some(item) = codec.decode(&mut src).unwrap() {
+            items.push(item.map(|frame| frame.to_vec()));
+        }
+    }
+    assert_eq!(
+        items,
+        [
+            Err(FrameError::TooLong {
+                length: 40,
+                max: 16
+            }),
+            Ok(b"after".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn test_implausible_length_fails_stream() {
+    let mut codec = AlecFrameCodec::new().with_max_skip_len(1 << 20);
+    let mut src = BytesMut::from(&u32::MAX.to_le_bytes()[..]);
+    let error = codec.decode(&mut src).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_refused_frames_not_written() {
+    let mut codec = AlecFrameCodec::new();
+    let mut dst = BytesMut::new();
+
+    let too_long = vec![0u8; MAX_MESSAGE_LEN + 1];
+    let error = codec.encode(too_long, &mut dst).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    let error = codec.encode(&[][..], &mut dst).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert!(dst.is_empty());
+
+    codec.encode(&message(0), &mut dst).unwrap();
+    assert_eq!(dst.len(), LENGTH_PREFIX_SIZE + message(0).len());
+}
+
+#[tokio::test]
+async fn test_connector_retries_until_listening() {
+    // Reserve a free port, then leave it closed for a while
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let server = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut frames = FramedRead::new(stream, AlecFrameCodec::new());
+        frames.next().await.unwrap().unwrap().unwrap()
+    });
+
+    let connector = FrameConnector::new(addr, RetryStrategy::fixed(50, Duration::from_millis(20)));
+    let mut framed = connector.connect().await.unwrap();
+    framed.send(&message(7)).await.unwrap();
+    let frame = server.await.unwrap();
+    assert_eq!(&frame[..], &message(7).to_bytes()[..]);
+
+    // Without retries the first failure is returned
+    drop(framed);
+    let closed = FrameConnector::new(addr, RetryStrategy::None);
+    assert!(closed.connect().await.is_err());
+}
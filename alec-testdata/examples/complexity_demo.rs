@@ -175,25 +175,16 @@ fn run_complexity_analysis(
     let mut engine = ComplexityEngine::new(config);
     let mut all_events = Vec::new();
 
-    // Process each row
-    for row in dataset.rows() {
-        // Create input from dataset row
-        // We'll compute a simple "entropy" approximation based on value spread
-        let values: Vec<f64> = row.iter().filter_map(|(_, v)| v).collect();
-
-        if values.is_empty() {
+    // Process each row through the row metrics approximation
+    for i in 0..dataset.len() {
+        let Some(metrics) = dataset.row_metrics(i) else {
             continue;
-        }
-
-        // Simple h_bytes approximation
-        let mean: f64 = values.iter().sum::<f64>() / values.len() as f64;
-        let variance: f64 =
-            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
-        let h_bytes = (variance.sqrt() + 1.0).log2() * 2.0; // Rough approximation
+        };
 
-        let input = GenericInput::new(row.timestamp_ms, h_bytes)
-            .with_tc(variance.sqrt() / 10.0) // Rough TC approximation
-            .with_r(0.5) // Placeholder
+        let input = GenericInput::new(dataset.rows()[i].timestamp_ms, metrics.h_bytes)
+            .with_tc(metrics.tc)
+            .with_h_joint(metrics.h_joint)
+            .with_r(metrics.r)
             .build();
 
         if let Some(snapshot) = engine.process(&input) {
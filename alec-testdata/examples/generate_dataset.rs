@@ -76,7 +76,7 @@ fn generate_agriculture_datasets() {
         }
 
         // Create and save manifest
-        let manifest = create_manifest(&dataset, &sensors, name, "agriculture");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "agriculture");
         let manifest_path = format!("datasets/agriculture/{}.manifest.json", name);
         if let Err(e) = manifest.to_json_file(&manifest_path) {
             eprintln!("  Warning: Could not save manifest: {}", e);
@@ -124,7 +124,7 @@ fn generate_satellite_datasets() {
             println!("  Created {}", csv_path);
         }
 
-        let manifest = create_manifest(&dataset, &sensors, name, "satellite");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "satellite");
         let _ = manifest.to_json_file(format!("datasets/satellite/{}.manifest.json", name));
     }
 }
@@ -169,7 +169,7 @@ fn generate_manufacturing_datasets() {
             println!("  Created {}", csv_path);
         }
 
-        let manifest = create_manifest(&dataset, &sensors, name, "manufacturing");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "manufacturing");
         let _ = manifest.to_json_file(format!("datasets/manufacturing/{}.manifest.json", name));
     }
 }
@@ -202,7 +202,7 @@ fn generate_smart_city_datasets() {
             println!("  Created {}", csv_path);
         }
 
-        let manifest = create_manifest(&dataset, &sensors, name, "smart_city");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "smart_city");
         let _ = manifest.to_json_file(format!("datasets/smart_city/{}.manifest.json", name));
     }
 }
@@ -239,7 +239,7 @@ fn generate_logistics_datasets() {
             println!("  Created {}", csv_path);
         }
 
-        let manifest = create_manifest(&dataset, &sensors, name, "logistics");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "logistics");
         let _ = manifest.to_json_file(format!("datasets/logistics/{}.manifest.json", name));
     }
 }
@@ -272,12 +272,13 @@ fn generate_energy_datasets() {
             println!("  Created {}", csv_path);
         }
 
-        let manifest = create_manifest(&dataset, &sensors, name, "energy");
+        let manifest = create_manifest(&config, &dataset, &sensors, name, "energy");
         let _ = manifest.to_json_file(format!("datasets/energy/{}.manifest.json", name));
     }
 }
 
 fn create_manifest(
+    config: &GeneratorConfig,
     dataset: &Dataset,
     sensors: &[alec_testdata::SensorConfig],
     name: &str,
@@ -290,7 +291,9 @@ fn create_manifest(
             dataset.len(),
             dataset.metadata.sample_interval_ms.unwrap_or(60_000),
         )
-        .with_seed(42);
+        .with_timestamp_unit(dataset.timestamp_unit())
+        .with_seed(42)
+        .with_nominal_stats(config.nominal_stats(sensors, dataset));
 
     for sensor in sensors {
         if let Some(stats) = dataset.stats(&sensor.id) {
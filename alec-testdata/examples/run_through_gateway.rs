@@ -51,7 +51,7 @@ fn run_through_gateway(dataset: &alec_testdata::Dataset) {
         ..Default::default()
     };
 
-    let mut gateway = Gateway::with_config(gateway_config);
+    let mut gateway = Gateway::with_config(gateway_config).unwrap();
 
     // Enable metrics
     gateway.enable_metrics(MetricsConfig {
@@ -57,6 +57,86 @@ impl AnomalyConfig {
     }
 }
 
+/// Correlated anomalies cascading from one sensor to others.
+///
+/// Real incidents propagate: a pump failure shows up as a pressure drop,
+/// then a temperature rise, then a flow stop. The trigger anomaly starts
+/// on `trigger_sensor`; each effect starts `delay_samples` later on its
+/// own sensor and lasts as long as the trigger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CascadeConfig {
+    /// Cascade name, used to group its events in the manifest.
+    pub name: String,
+    /// Sensor the incident starts on.
+    pub trigger_sensor: String,
+    /// Anomaly on the trigger sensor.
+    pub trigger: AnomalyConfig,
+    /// Time-shifted anomalies on other sensors.
+    pub effects: Vec<CascadeEffect>,
+}
+
+/// One downstream effect of a [`CascadeConfig`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CascadeEffect {
+    /// Sensor affected.
+    pub sensor_id: String,
+    /// Type of anomaly on this sensor.
+    pub anomaly_type: AnomalyType,
+    /// Samples between the trigger start and this effect.
+    pub delay_samples: usize,
+    /// Factor applied to the anomaly magnitude ([`AnomalyType::scaled`]).
+    pub magnitude_scale: f64,
+}
+
+impl CascadeConfig {
+    /// Create a cascade triggered by `trigger` on `trigger_sensor`.
+    pub fn new(name: &str, trigger_sensor: &str, trigger: AnomalyConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            trigger_sensor: trigger_sensor.to_string(),
+            trigger,
+            effects: Vec::new(),
+        }
+    }
+
+    /// Add an effect on `sensor_id`, `delay_samples` after the trigger.
+    pub fn with_effect(
+        mut self,
+        sensor_id: &str,
+        anomaly_type: AnomalyType,
+        delay_samples: usize,
+        magnitude_scale: f64,
+    ) -> Self {
+        self.effects.push(CascadeEffect {
+            sensor_id: sensor_id.to_string(),
+            anomaly_type,
+            delay_samples,
+            magnitude_scale,
+        });
+        self
+    }
+
+    /// Anomaly injected on the sensor of an effect.
+    pub fn effect_anomaly(&self, effect: &CascadeEffect) -> AnomalyConfig {
+        AnomalyConfig {
+            anomaly_type: effect.anomaly_type.scaled(effect.magnitude_scale),
+            start_sample: self.trigger.start_sample + effect.delay_samples,
+            duration_samples: self.trigger.duration_samples,
+        }
+    }
+
+    /// Every anomaly of the cascade with its sensor, trigger first.
+    pub fn anomalies(&self) -> Vec<(&str, AnomalyConfig)> {
+        std::iter::once((self.trigger_sensor.as_str(), self.trigger.clone()))
+            .chain(
+                self.effects
+                    .iter()
+                    .map(|effect| (effect.sensor_id.as_str(), self.effect_anomaly(effect))),
+            )
+            .collect()
+    }
+}
+
 /// Type of anomaly to inject.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AnomalyType {
@@ -162,6 +242,41 @@ impl AnomalyType {
         AnomalyType::Decorrelate { noise_std, center }
     }
 
+    /// Scale the magnitude of the anomaly by `factor`.
+    ///
+    /// Scales spike magnitude, drift rate, decorrelation noise,
+    /// oscillation amplitude, bias offset and the noise increase beyond
+    /// 1; stuck, dropout, clipping and intermittent anomalies have no
+    /// magnitude and are returned unchanged.
+    pub fn scaled(&self, factor: f64) -> Self {
+        match self.clone() {
+            AnomalyType::Spike { magnitude } => AnomalyType::Spike {
+                magnitude: magnitude * factor,
+            },
+            AnomalyType::Drift { rate_per_sample } => AnomalyType::Drift {
+                rate_per_sample: rate_per_sample * factor,
+            },
+            AnomalyType::Decorrelate { noise_std, center } => AnomalyType::Decorrelate {
+                noise_std: noise_std * factor,
+                center,
+            },
+            AnomalyType::Oscillation {
+                amplitude,
+                frequency_hz,
+            } => AnomalyType::Oscillation {
+                amplitude: amplitude * factor,
+                frequency_hz,
+            },
+            AnomalyType::BiasShift { offset } => AnomalyType::BiasShift {
+                offset: offset * factor,
+            },
+            AnomalyType::NoiseIncrease { factor: noise } => AnomalyType::NoiseIncrease {
+                factor: 1.0 + (noise - 1.0) * factor,
+            },
+            other => other,
+        }
+    }
+
     /// Get expected detection event type.
     pub fn expected_event(&self) -> &'static str {
         match self {
@@ -466,6 +581,19 @@ mod tests {
         assert_eq!(config.duration_samples, Some(200));
     }
 
+    #[test]
+    fn test_scaled_anomaly() {
+        assert!(matches!(
+            AnomalyType::spike(10.0).scaled(0.5),
+            AnomalyType::Spike { magnitude } if magnitude == 5.0
+        ));
+        assert!(matches!(
+            AnomalyType::NoiseIncrease { factor: 3.0 }.scaled(0.5),
+            AnomalyType::NoiseIncrease { factor } if factor == 2.0
+        ));
+        assert!(matches!(AnomalyType::Stuck.scaled(2.0), AnomalyType::Stuck));
+    }
+
     #[test]
     fn test_expected_event() {
         assert_eq!(AnomalyType::Stuck.expected_event(), "STRUCTURE_BREAK");
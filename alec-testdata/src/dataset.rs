@@ -34,10 +34,74 @@ pub enum DatasetError {
     Empty,
 }
 
+/// Unit of the row timestamps of a dataset.
+///
+/// Serialized as `"s"`, `"ms"` or `"us"`; datasets written before the
+/// unit was recorded are in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TimestampUnit {
+    #[serde(rename = "s")]
+    Seconds,
+    #[default]
+    #[serde(rename = "ms")]
+    Millis,
+    #[serde(rename = "us")]
+    Micros,
+}
+
+impl TimestampUnit {
+    /// Microseconds in one unit.
+    pub fn micros_per_unit(self) -> i64 {
+        match self {
+            TimestampUnit::Seconds => 1_000_000,
+            TimestampUnit::Millis => 1_000,
+            TimestampUnit::Micros => 1,
+        }
+    }
+
+    /// Express a timestamp in this unit as microseconds (saturating).
+    pub fn to_micros(self, value: u64) -> i64 {
+        i64::try_from(value)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(self.micros_per_unit())
+    }
+
+    /// Express a millisecond timestamp in this unit (truncating to whole
+    /// seconds, saturating at `u64::MAX`).
+    pub fn from_millis(self, timestamp_ms: u64) -> u64 {
+        match self {
+            TimestampUnit::Seconds => timestamp_ms / 1_000,
+            TimestampUnit::Millis => timestamp_ms,
+            TimestampUnit::Micros => timestamp_ms.saturating_mul(1_000),
+        }
+    }
+
+    /// Name of the CSV timestamp column.
+    pub fn column_name(self) -> &'static str {
+        match self {
+            TimestampUnit::Seconds => "timestamp_s",
+            TimestampUnit::Millis => "timestamp_ms",
+            TimestampUnit::Micros => "timestamp_us",
+        }
+    }
+
+    /// Unit of a CSV timestamp column.
+    pub fn from_column_name(name: &str) -> Option<Self> {
+        [
+            TimestampUnit::Seconds,
+            TimestampUnit::Millis,
+            TimestampUnit::Micros,
+        ]
+        .into_iter()
+        .find(|unit| unit.column_name() == name)
+    }
+}
+
 /// A single row of dataset values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatasetRow {
-    /// Timestamp in milliseconds.
+    /// Timestamp, in the dataset's [`DatasetMetadata::timestamp_unit`]
+    /// (milliseconds unless set otherwise).
     pub timestamp_ms: u64,
     /// Sensor values keyed by sensor ID.
     pub values: HashMap<String, Option<f64>>,
@@ -63,6 +127,11 @@ impl DatasetRow {
         self.values.get(sensor_id).copied().flatten()
     }
 
+    /// Timestamp in microseconds, given the dataset's unit.
+    pub fn timestamp_micros(&self, unit: TimestampUnit) -> i64 {
+        unit.to_micros(self.timestamp_ms)
+    }
+
     /// Iterate over sensor values.
     pub fn iter(&self) -> impl Iterator<Item = (&str, Option<f64>)> {
         self.values.iter().map(|(k, v)| (k.as_str(), *v))
@@ -99,6 +168,9 @@ pub struct DatasetMetadata {
     /// Sample interval in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sample_interval_ms: Option<u64>,
+    /// Unit of the row timestamps.
+    #[serde(default)]
+    pub timestamp_unit: TimestampUnit,
 }
 
 impl Dataset {
@@ -160,7 +232,9 @@ impl Dataset {
         if self.rows.len() < 2 {
             return 0;
         }
-        self.rows.last().unwrap().timestamp_ms - self.rows.first().unwrap().timestamp_ms
+        let elapsed =
+            self.rows.last().unwrap().timestamp_ms - self.rows.first().unwrap().timestamp_ms;
+        (self.metadata.timestamp_unit.to_micros(elapsed) / 1_000) as u64
     }
 
     /// Get a column as a vector of values.
@@ -168,11 +242,22 @@ impl Dataset {
         self.rows.iter().map(|r| r.get(sensor_id)).collect()
     }
 
-    /// Get timestamps as a vector.
+    /// Get timestamps as a vector, in the dataset's unit.
     pub fn timestamps(&self) -> Vec<u64> {
         self.rows.iter().map(|r| r.timestamp_ms).collect()
     }
 
+    /// Get timestamps in microseconds, whatever the dataset's unit.
+    pub fn timestamps_micros(&self) -> Vec<i64> {
+        let unit = self.metadata.timestamp_unit;
+        self.rows.iter().map(|r| r.timestamp_micros(unit)).collect()
+    }
+
+    /// Unit of the row timestamps.
+    pub fn timestamp_unit(&self) -> TimestampUnit {
+        self.metadata.timestamp_unit
+    }
+
     /// Set metadata.
     pub fn with_metadata(mut self, metadata: DatasetMetadata) -> Self {
         self.metadata = metadata;
@@ -199,29 +284,12 @@ impl Dataset {
 
     /// Export to CSV file.
     pub fn to_csv(&self, path: impl AsRef<Path>) -> Result<(), DatasetError> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-
-        // Header
-        write!(writer, "timestamp_ms")?;
-        for sensor_id in &self.sensor_ids {
-            write!(writer, ",{}", sensor_id)?;
-        }
-        writeln!(writer)?;
-
-        // Data rows
+        let mut writer =
+            DatasetWriter::create_in(path, &self.sensor_ids, self.metadata.timestamp_unit)?;
         for row in &self.rows {
-            write!(writer, "{}", row.timestamp_ms)?;
-            for sensor_id in &self.sensor_ids {
-                match row.get(sensor_id) {
-                    Some(v) => write!(writer, ",{:.6}", v)?,
-                    None => write!(writer, ",")?,
-                }
-            }
-            writeln!(writer)?;
+            writer.write_row(row)?;
         }
-
-        writer.flush()?;
+        writer.finish()?;
         Ok(())
     }
 
@@ -235,12 +303,14 @@ impl Dataset {
         let header = lines.next().ok_or(DatasetError::Empty)??;
         let columns: Vec<&str> = header.split(',').collect();
 
-        if columns.is_empty() || columns[0] != "timestamp_ms" {
-            return Err(DatasetError::MissingColumn("timestamp_ms".to_string()));
-        }
+        let unit = columns
+            .first()
+            .and_then(|column| TimestampUnit::from_column_name(column))
+            .ok_or_else(|| DatasetError::MissingColumn("timestamp_ms".to_string()))?;
 
         let sensor_ids: Vec<String> = columns[1..].iter().map(|s| s.to_string()).collect();
         let mut dataset = Dataset::new(sensor_ids.clone());
+        dataset.metadata.timestamp_unit = unit;
 
         // Parse data rows
         for (line_num, line_result) in lines.enumerate() {
@@ -323,6 +393,105 @@ impl Dataset {
             max,
         })
     }
+
+    /// Complexity metrics of the row at `index`, `None` when out of range
+    /// or when no sensor has a value.
+    pub fn row_metrics(&self, index: usize) -> Option<RowMetrics> {
+        RowMetrics::from_row(self.rows.get(index)?, &self.sensor_ids)
+    }
+}
+
+/// Incremental CSV writer.
+///
+/// Writes the header on creation and one line per row, so datasets can be
+/// exported straight from [`crate::generator::GeneratorConfig::stream`]
+/// without holding them in memory. Produces the same format as
+/// [`Dataset::to_csv`].
+pub struct DatasetWriter<W: Write> {
+    writer: W,
+    sensor_ids: Vec<String>,
+    rows_written: usize,
+}
+
+impl DatasetWriter<BufWriter<File>> {
+    /// Create a CSV file and write its header.
+    pub fn create(path: impl AsRef<Path>, sensor_ids: &[String]) -> Result<Self, DatasetError> {
+        Self::create_in(path, sensor_ids, TimestampUnit::Millis)
+    }
+
+    /// Create a CSV file whose timestamp column is named for `unit`.
+    pub fn create_in(
+        path: impl AsRef<Path>,
+        sensor_ids: &[String],
+        unit: TimestampUnit,
+    ) -> Result<Self, DatasetError> {
+        let file = File::create(path)?;
+        Self::new_in(BufWriter::new(file), sensor_ids, unit)
+    }
+}
+
+impl<W: Write> DatasetWriter<W> {
+    /// Wrap a writer and write the CSV header.
+    pub fn new(writer: W, sensor_ids: &[String]) -> Result<Self, DatasetError> {
+        Self::new_in(writer, sensor_ids, TimestampUnit::Millis)
+    }
+
+    /// Wrap a writer and write the CSV header, naming the timestamp
+    /// column for `unit` (`timestamp_s`, `timestamp_ms` or `timestamp_us`).
+    pub fn new_in(
+        mut writer: W,
+        sensor_ids: &[String],
+        unit: TimestampUnit,
+    ) -> Result<Self, DatasetError> {
+        write!(writer, "{}", unit.column_name())?;
+        for sensor_id in sensor_ids {
+            write!(writer, ",{}", sensor_id)?;
+        }
+        writeln!(writer)?;
+
+        Ok(Self {
+            writer,
+            sensor_ids: sensor_ids.to_vec(),
+            rows_written: 0,
+        })
+    }
+
+    /// Write one row. Sensors missing from the row are left empty.
+    pub fn write_row(&mut self, row: &DatasetRow) -> Result<(), DatasetError> {
+        write!(self.writer, "{}", row.timestamp_ms)?;
+        for sensor_id in &self.sensor_ids {
+            match row.get(sensor_id) {
+                Some(v) => write!(self.writer, ",{:.6}", v)?,
+                None => write!(self.writer, ",")?,
+            }
+        }
+        writeln!(self.writer)?;
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    /// Write every row from an iterator, returning how many were written.
+    pub fn write_rows(
+        &mut self,
+        rows: impl IntoIterator<Item = DatasetRow>,
+    ) -> Result<usize, DatasetError> {
+        let before = self.rows_written;
+        for row in rows {
+            self.write_row(&row)?;
+        }
+        Ok(self.rows_written - before)
+    }
+
+    /// Number of data rows written so far.
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W, DatasetError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
 }
 
 /// Basic statistics for a sensor column.
@@ -335,6 +504,58 @@ pub struct SensorStats {
     pub max: f64,
 }
 
+/// Complexity metrics approximated from the values of one row.
+///
+/// A cheap, deterministic stand-in for gateway metrics, so that the
+/// nominal statistics in a manifest and a replay of the dataset agree.
+/// All are derived from the spread of the row's values across sensors,
+/// except `r`, the fraction of sensors with a value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RowMetrics {
+    /// Spread across sensors, scaled down.
+    pub tc: f64,
+    /// Log of the value range across sensors.
+    pub h_joint: f64,
+    /// Log of the spread across sensors.
+    pub h_bytes: f64,
+    /// Fraction of sensors with a value.
+    pub r: f64,
+}
+
+impl RowMetrics {
+    /// Metrics of `row`, visiting sensors in `sensor_ids` order. `None`
+    /// when no sensor has a value.
+    pub fn from_row(row: &DatasetRow, sensor_ids: &[String]) -> Option<Self> {
+        let values: Vec<f64> = sensor_ids.iter().filter_map(|id| row.get(id)).collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let spread = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        Some(Self {
+            tc: spread / 10.0,
+            h_joint: (max - min + 1.0).log2(),
+            h_bytes: (spread + 1.0).log2() * 2.0,
+            r: n / sensor_ids.len() as f64,
+        })
+    }
+
+    /// Metrics keyed by their complexity baseline name.
+    pub fn named(&self) -> [(&'static str, f64); 4] {
+        [
+            ("tc", self.tc),
+            ("h_joint", self.h_joint),
+            ("h_bytes", self.h_bytes),
+            ("r", self.r),
+        ]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +628,43 @@ mod tests {
         assert_eq!(loaded.rows[0].get("temp"), Some(25.5));
     }
 
+    #[test]
+    fn test_timestamp_units_agree() {
+        use crate::generator::{generate_dataset, GeneratorConfig, SensorConfig};
+        use crate::patterns::SignalPattern;
+
+        let sensors = [SensorConfig::new(
+            "temp",
+            "C",
+            0.0,
+            40.0,
+            SignalPattern::Constant { value: 20.0 },
+        )];
+        let config = GeneratorConfig::new().with_num_samples(5).with_seed(7);
+        let secs = generate_dataset(
+            &config.clone().with_timestamp_unit(TimestampUnit::Seconds),
+            &sensors,
+        );
+        let micros = generate_dataset(&config.with_timestamp_unit(TimestampUnit::Micros), &sensors);
+
+        assert_eq!(secs.rows[0].timestamp_ms, 1_706_745_600);
+        assert_eq!(micros.rows[0].timestamp_ms, 1_706_745_600_000_000);
+        assert_eq!(secs.timestamps_micros(), micros.timestamps_micros());
+        assert_eq!(secs.duration_ms(), micros.duration_ms());
+
+        // The unit survives CSV and JSON
+        let temp_file = NamedTempFile::new().unwrap();
+        micros.to_csv(temp_file.path()).unwrap();
+        let loaded = Dataset::from_csv(temp_file.path()).unwrap();
+        assert_eq!(loaded.timestamp_unit(), TimestampUnit::Micros);
+        assert_eq!(loaded.timestamps_micros(), secs.timestamps_micros());
+
+        let json = serde_json::to_string(&secs).unwrap();
+        assert!(json.contains("\"timestamp_unit\":\"s\""));
+        let loaded: Dataset = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.timestamp_unit(), TimestampUnit::Seconds);
+    }
+
     #[test]
     fn test_csv_with_missing_values() {
         let mut dataset = Dataset::new(vec!["temp".to_string()]);
@@ -423,6 +681,26 @@ mod tests {
         assert_eq!(loaded.rows[0].get("temp"), None);
     }
 
+    #[test]
+    fn test_writer_matches_to_csv() {
+        let mut dataset = Dataset::new(vec!["temp".to_string(), "humidity".to_string()]);
+        dataset.add_row_vec(vec![1000.0, 25.5, 60.0]);
+        dataset.add_row(DatasetRow::new(2000).with_value("temp", Some(26.0)));
+
+        let temp_file = NamedTempFile::new().unwrap();
+        dataset.to_csv(temp_file.path()).unwrap();
+        let expected = std::fs::read_to_string(temp_file.path()).unwrap();
+
+        let mut writer = DatasetWriter::new(Vec::new(), dataset.sensor_ids()).unwrap();
+        let written = writer.write_rows(dataset.rows().iter().cloned()).unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(writer.rows_written(), 2);
+        let bytes = writer.finish().unwrap();
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+        assert!(expected.ends_with("2000,26.000000,\n"));
+    }
+
     #[test]
     fn test_json_roundtrip() {
         let mut dataset = Dataset::new(vec!["temp".to_string()])
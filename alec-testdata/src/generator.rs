@@ -9,14 +9,15 @@
 //! This module provides the main generation API for creating
 //! realistic sensor datasets.
 
-use crate::anomalies::{AnomalyConfig, AnomalyState};
-use crate::dataset::{Dataset, DatasetMetadata, DatasetRow};
+use crate::anomalies::{AnomalyConfig, AnomalyState, CascadeConfig};
+use crate::dataset::{Dataset, DatasetMetadata, DatasetRow, TimestampUnit};
+use crate::manifest::NominalStats;
 use crate::patterns::{PatternState, SignalPattern};
 use rand::prelude::*;
 use rand::rngs::StdRng;
 use rand_distr::Normal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
 /// Generator configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +31,14 @@ pub struct GeneratorConfig {
     /// Random seed for reproducibility.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
+    /// Correlated anomalies spanning several sensors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cascades: Vec<CascadeConfig>,
+    /// Unit of the emitted row timestamps (default: milliseconds).
+    ///
+    /// `start_time_ms` and `sample_interval_ms` stay in milliseconds.
+    #[serde(default)]
+    pub timestamp_unit: TimestampUnit,
 }
 
 impl Default for GeneratorConfig {
@@ -39,6 +48,8 @@ impl Default for GeneratorConfig {
             sample_interval_ms: 60_000,   // 1 minute
             num_samples: 60,              // 1 hour
             seed: None,
+            cascades: Vec::new(),
+            timestamp_unit: TimestampUnit::Millis,
         }
     }
 }
@@ -67,6 +78,12 @@ impl GeneratorConfig {
         self
     }
 
+    /// Set the unit of the emitted row timestamps.
+    pub fn with_timestamp_unit(mut self, unit: TimestampUnit) -> Self {
+        self.timestamp_unit = unit;
+        self
+    }
+
     /// Set number of samples.
     pub fn with_num_samples(mut self, n: usize) -> Self {
         self.num_samples = n;
@@ -93,6 +110,15 @@ impl GeneratorConfig {
         self
     }
 
+    /// Add a cascade of correlated anomalies.
+    ///
+    /// Cascade anomalies apply on top of each sensor's own anomaly;
+    /// effects on sensors missing from the dataset are ignored.
+    pub fn with_cascade(mut self, cascade: CascadeConfig) -> Self {
+        self.cascades.push(cascade);
+        self
+    }
+
     /// Get total duration in milliseconds.
     pub fn duration_ms(&self) -> u64 {
         self.sample_interval_ms * (self.num_samples.saturating_sub(1)) as u64
@@ -192,12 +218,10 @@ impl SensorConfig {
 }
 
 /// Generate a dataset from configuration.
+///
+/// Materializes every row in memory. For long runs prefer
+/// [`GeneratorConfig::stream`], which yields the same rows one at a time.
 pub fn generate_dataset(config: &GeneratorConfig, sensors: &[SensorConfig]) -> Dataset {
-    let mut rng: Box<dyn RngCore> = match config.seed {
-        Some(s) => Box::new(StdRng::seed_from_u64(s)),
-        None => Box::new(StdRng::from_entropy()),
-    };
-
     let sensor_ids: Vec<String> = sensors.iter().map(|s| s.id.clone()).collect();
     let mut dataset = Dataset::new(sensor_ids);
 
@@ -207,80 +231,188 @@ pub fn generate_dataset(config: &GeneratorConfig, sensors: &[SensorConfig]) -> D
         description: None,
         seed: config.seed,
         sample_interval_ms: Some(config.sample_interval_ms),
+        timestamp_unit: config.timestamp_unit,
     };
 
-    // Initialize pattern states
-    let mut pattern_states: HashMap<String, PatternState> = sensors
-        .iter()
-        .map(|s| (s.id.clone(), PatternState::for_pattern(&s.pattern)))
-        .collect();
-
-    // Initialize anomaly states
-    let mut anomaly_states: HashMap<String, AnomalyState> = sensors
-        .iter()
-        .map(|s| (s.id.clone(), AnomalyState::default()))
-        .collect();
-
-    // History for correlations
-    let mut history: HashMap<String, Vec<f64>> =
-        sensors.iter().map(|s| (s.id.clone(), Vec::new())).collect();
-
-    // Generate samples
-    for i in 0..config.num_samples {
-        let timestamp = config.start_time_ms + (i as u64 * config.sample_interval_ms);
+    dataset.rows = config.stream(sensors).collect();
+    dataset
+}
+
+impl GeneratorConfig {
+    /// Generate rows lazily, one sample at a time.
+    ///
+    /// Yields exactly the rows [`generate_dataset`] would produce for the
+    /// same configuration and seed, but only keeps per-sensor pattern and
+    /// anomaly state plus the few past values needed by lagged
+    /// correlations. Pair with [`crate::dataset::DatasetWriter`] to write
+    /// arbitrarily long datasets with bounded memory.
+    pub fn stream<'a>(&self, sensors: &'a [SensorConfig]) -> DatasetStream<'a> {
+        DatasetStream::new(self.clone(), sensors)
+    }
+
+    /// Whether any anomaly of `sensors` or of a cascade is active at
+    /// sample `index`.
+    pub fn is_anomalous(&self, sensors: &[SensorConfig], index: usize) -> bool {
+        sensors
+            .iter()
+            .filter_map(|sensor| sensor.anomaly.as_ref())
+            .any(|anomaly| anomaly.is_active(index))
+            || self
+                .cascades
+                .iter()
+                .flat_map(|cascade| cascade.anomalies())
+                .any(|(_, anomaly)| anomaly.is_active(index))
+    }
+
+    /// Nominal statistics of `dataset`, generated from this configuration
+    /// and `sensors`, over the rows no anomaly touches.
+    pub fn nominal_stats(&self, sensors: &[SensorConfig], dataset: &Dataset) -> NominalStats {
+        NominalStats::from_metrics(
+            (0..dataset.len())
+                .filter(|&i| !self.is_anomalous(sensors, i))
+                .filter_map(|i| dataset.row_metrics(i)),
+        )
+    }
+}
+
+/// Streaming row generator returned by [`GeneratorConfig::stream`].
+pub struct DatasetStream<'a> {
+    config: GeneratorConfig,
+    sensors: &'a [SensorConfig],
+    rng: Box<dyn RngCore>,
+    pattern_states: Vec<PatternState>,
+    anomaly_states: Vec<AnomalyState>,
+    /// Cascade anomalies per sensor, with their state.
+    cascade_anomalies: Vec<Vec<(AnomalyConfig, AnomalyState)>>,
+    /// Index of each sensor's correlation source, if it exists.
+    correlation_sources: Vec<Option<usize>>,
+    /// Most recent non-missing values per sensor, for lagged correlations.
+    history: Vec<VecDeque<f64>>,
+    /// Per-sensor history bound (largest lag + 1).
+    history_capacity: usize,
+    next_sample: usize,
+}
+
+impl<'a> DatasetStream<'a> {
+    fn new(config: GeneratorConfig, sensors: &'a [SensorConfig]) -> Self {
+        let rng: Box<dyn RngCore> = match config.seed {
+            Some(s) => Box::new(StdRng::seed_from_u64(s)),
+            None => Box::new(StdRng::from_entropy()),
+        };
+
+        let correlation_sources = sensors
+            .iter()
+            .map(|s| {
+                s.correlation
+                    .as_ref()
+                    .and_then(|c| sensors.iter().position(|o| o.id == c.source_id))
+            })
+            .collect();
+
+        let history_capacity = sensors
+            .iter()
+            .filter_map(|s| s.correlation.as_ref())
+            .map(|c| c.lag_samples + 1)
+            .max()
+            .unwrap_or(1);
+
+        let mut cascade_anomalies = vec![Vec::new(); sensors.len()];
+        for cascade in &config.cascades {
+            for (sensor_id, anomaly) in cascade.anomalies() {
+                if let Some(idx) = sensors.iter().position(|s| s.id == sensor_id) {
+                    cascade_anomalies[idx].push((anomaly, AnomalyState::default()));
+                }
+            }
+        }
+
+        Self {
+            config,
+            sensors,
+            rng,
+            pattern_states: sensors
+                .iter()
+                .map(|s| PatternState::for_pattern(&s.pattern))
+                .collect(),
+            anomaly_states: vec![AnomalyState::default(); sensors.len()],
+            cascade_anomalies,
+            correlation_sources,
+            history: vec![VecDeque::with_capacity(history_capacity); sensors.len()],
+            history_capacity,
+            next_sample: 0,
+        }
+    }
+
+    /// Index of the next sample to be generated.
+    pub fn position(&self) -> usize {
+        self.next_sample
+    }
+
+    /// Number of past values currently retained for correlations.
+    ///
+    /// Never exceeds `sensors.len() * max_buffered_per_sensor()`,
+    /// regardless of how many rows have been generated.
+    pub fn buffered_values(&self) -> usize {
+        self.history.iter().map(VecDeque::len).sum()
+    }
+
+    /// Upper bound on retained past values per sensor.
+    pub fn max_buffered_per_sensor(&self) -> usize {
+        self.history_capacity
+    }
+
+    fn generate_row(&mut self, i: usize) -> DatasetRow {
+        let timestamp = self.config.start_time_ms + (i as u64 * self.config.sample_interval_ms);
         // Use relative time for pattern evaluation (time since start)
-        let relative_time = i as u64 * config.sample_interval_ms;
-        let mut row = DatasetRow::new(timestamp);
+        let relative_time = i as u64 * self.config.sample_interval_ms;
+        let mut row = DatasetRow::new(self.config.timestamp_unit.from_millis(timestamp));
+        let sensors = self.sensors;
+        let rng = &mut *self.rng;
 
         // First pass: generate base values for non-correlated sensors
-        let mut base_values: HashMap<String, f64> = HashMap::new();
+        let mut base_values: Vec<Option<f64>> = vec![None; sensors.len()];
 
-        for sensor in sensors {
+        for (idx, sensor) in sensors.iter().enumerate() {
             if sensor.correlation.is_none() {
-                let state = pattern_states.get_mut(&sensor.id).unwrap();
-                let base = state.evaluate(&sensor.pattern, relative_time, &mut *rng);
-                base_values.insert(sensor.id.clone(), base);
+                let state = &mut self.pattern_states[idx];
+                base_values[idx] = Some(state.evaluate(&sensor.pattern, relative_time, rng));
             }
         }
 
         // Second pass: generate correlated values
-        for sensor in sensors {
+        for (idx, sensor) in sensors.iter().enumerate() {
             if let Some(ref corr) = sensor.correlation {
-                let source_value = match (corr.lag_samples > 0, history.get(&corr.source_id)) {
+                let source = self.correlation_sources[idx];
+                let hist = source.map(|src| &self.history[src]);
+                let source_value = match (corr.lag_samples > 0, hist) {
                     (true, Some(hist)) if hist.len() > corr.lag_samples => {
                         hist[hist.len() - corr.lag_samples - 1]
                     }
-                    _ => *base_values.get(&corr.source_id).unwrap_or(&0.0),
+                    _ => source.and_then(|src| base_values[src]).unwrap_or(0.0),
                 };
 
                 // Apply correlation
-                let base = apply_correlation(
-                    source_value,
-                    corr.coefficient,
-                    sensor.min,
-                    sensor.max,
-                    &mut *rng,
-                );
-                base_values.insert(sensor.id.clone(), base);
+                let base =
+                    apply_correlation(source_value, corr.coefficient, sensor.min, sensor.max, rng);
+                base_values[idx] = Some(base);
             }
         }
 
         // Third pass: apply noise, anomalies, and clamp
-        for sensor in sensors {
-            let mut value = *base_values.get(&sensor.id).unwrap_or(&0.0);
+        for (idx, sensor) in sensors.iter().enumerate() {
+            let mut value = base_values[idx].unwrap_or(0.0);
 
             // Add noise
             if sensor.noise_std > 0.0 {
                 let noise_dist = Normal::new(0.0, sensor.noise_std).unwrap();
-                value += noise_dist.sample(&mut *rng);
+                value += noise_dist.sample(rng);
             }
 
-            // Apply anomaly
+            // Apply anomaly (state machine keyed by sample index)
             let final_value = if let Some(ref anomaly) = sensor.anomaly {
                 if anomaly.is_active(i) {
-                    let state = anomaly_states.get_mut(&sensor.id).unwrap();
+                    let state = &mut self.anomaly_states[idx];
                     let samples_since = anomaly.samples_since_start(i);
-                    state.apply(&anomaly.anomaly_type, value, samples_since, &mut *rng)
+                    state.apply(&anomaly.anomaly_type, value, samples_since, rng)
                 } else {
                     Some(value)
                 }
@@ -288,22 +420,53 @@ pub fn generate_dataset(config: &GeneratorConfig, sensors: &[SensorConfig]) -> D
                 Some(value)
             };
 
+            // Apply cascade anomalies on top
+            let mut final_value = final_value;
+            for (anomaly, state) in &mut self.cascade_anomalies[idx] {
+                if let (Some(v), true) = (final_value, anomaly.is_active(i)) {
+                    let samples_since = anomaly.samples_since_start(i);
+                    final_value = state.apply(&anomaly.anomaly_type, v, samples_since, rng);
+                }
+            }
+
             // Clamp to valid range and store
             let final_value = final_value.map(|v| v.clamp(sensor.min, sensor.max));
             row.values.insert(sensor.id.clone(), final_value);
 
-            // Update history for correlations
+            // Update bounded history for correlations
             if let Some(v) = final_value {
-                history.get_mut(&sensor.id).unwrap().push(v);
+                let hist = &mut self.history[idx];
+                if hist.len() == self.history_capacity {
+                    hist.pop_front();
+                }
+                hist.push_back(v);
             }
         }
 
-        dataset.add_row(row);
+        row
     }
+}
 
-    dataset
+impl Iterator for DatasetStream<'_> {
+    type Item = DatasetRow;
+
+    fn next(&mut self) -> Option<DatasetRow> {
+        if self.next_sample >= self.config.num_samples {
+            return None;
+        }
+        let i = self.next_sample;
+        self.next_sample += 1;
+        Some(self.generate_row(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.config.num_samples.saturating_sub(self.next_sample);
+        (remaining, Some(remaining))
+    }
 }
 
+impl ExactSizeIterator for DatasetStream<'_> {}
+
 /// Apply correlation transformation.
 fn apply_correlation(
     source_value: f64,
@@ -578,4 +741,139 @@ mod tests {
             assert_eq!(r1.get("temp"), r2.get("temp"));
         }
     }
+
+    fn streaming_sensors() -> Vec<SensorConfig> {
+        vec![
+            SensorConfig::new(
+                "temp",
+                "°C",
+                0.0,
+                100.0,
+                SignalPattern::Constant { value: 50.0 },
+            )
+            .with_noise(2.0)
+            .with_anomaly(AnomalyConfig::new(AnomalyType::Stuck, 500).with_duration(100)),
+            SensorConfig::new(
+                "humidity",
+                "%",
+                0.0,
+                100.0,
+                SignalPattern::Constant { value: 60.0 },
+            )
+            .with_lagged_correlation("temp", -0.8, 3),
+        ]
+    }
+
+    #[test]
+    fn test_stream_matches_generate_dataset() {
+        let config = GeneratorConfig::new().with_num_samples(2_000).with_seed(7);
+        let sensors = streaming_sensors();
+
+        let dataset = generate_dataset(&config, &sensors);
+        let streamed: Vec<DatasetRow> = config.stream(&sensors).collect();
+
+        assert_eq!(streamed.len(), dataset.len());
+        for (a, b) in streamed.iter().zip(dataset.rows()) {
+            assert_eq!(a.timestamp_ms, b.timestamp_ms);
+            assert_eq!(a.get("temp"), b.get("temp"));
+            assert_eq!(a.get("humidity"), b.get("humidity"));
+        }
+    }
+
+    #[test]
+    fn test_stream_ten_million_rows_bounded() {
+        const TOTAL: usize = 10_000_000;
+        let sensors = streaming_sensors();
+        let config = GeneratorConfig::new()
+            .with_sample_interval_secs(1)
+            .with_num_samples(TOTAL)
+            .with_seed(7);
+        let reference = generate_dataset(&config.clone().with_num_samples(2_000), &sensors);
+
+        let mut stream = config.stream(&sensors);
+        assert_eq!(stream.len(), TOTAL);
+
+        // Only the lagged-correlation window is retained, never the rows
+        let bound = sensors.len() * stream.max_buffered_per_sensor();
+        assert_eq!(bound, 2 * 4);
+
+        let mut count = 0;
+        let mut last_timestamp = 0;
+        while let Some(row) = stream.next() {
+            if let Some(expected) = reference.rows().get(count) {
+                assert_eq!(row.timestamp_ms, expected.timestamp_ms);
+                assert_eq!(row.get("temp"), expected.get("temp"));
+                assert_eq!(row.get("humidity"), expected.get("humidity"));
+            }
+            assert!(stream.buffered_values() <= bound);
+            last_timestamp = row.timestamp_ms;
+            count += 1;
+        }
+
+        assert_eq!(count, TOTAL);
+        assert_eq!(last_timestamp, config.end_time_ms());
+        assert_eq!(stream.position(), TOTAL);
+    }
+
+    /// Pump failure: pressure drops, then temperature rises, then flow
+    /// falls by half the configured magnitude.
+    fn pump_failure() -> CascadeConfig {
+        CascadeConfig::new(
+            "pump_failure",
+            "pressure",
+            AnomalyConfig::new(AnomalyType::BiasShift { offset: -10.0 }, 100).with_duration(50),
+        )
+        .with_effect("temp", AnomalyType::BiasShift { offset: 5.0 }, 10, 1.0)
+        .with_effect("flow", AnomalyType::BiasShift { offset: -8.0 }, 30, 0.5)
+    }
+
+    fn constant_sensor(id: &str, value: f64) -> SensorConfig {
+        SensorConfig::new(id, "u", 0.0, 100.0, SignalPattern::Constant { value })
+    }
+
+    #[test]
+    fn test_cascade_offsets() {
+        let config = GeneratorConfig::new()
+            .with_num_samples(200)
+            .with_seed(42)
+            .with_cascade(pump_failure());
+        let sensors = vec![
+            constant_sensor("pressure", 50.0),
+            constant_sensor("temp", 20.0),
+            constant_sensor("flow", 10.0),
+        ];
+
+        let dataset = generate_dataset(&config, &sensors);
+
+        // (sensor, baseline, deviation, trigger start + delay)
+        for (id, base, deviation, start) in [
+            ("pressure", 50.0, -10.0, 100),
+            ("temp", 20.0, 5.0, 110),
+            ("flow", 10.0, -4.0, 130),
+        ] {
+            let column: Vec<f64> = dataset.column(id).into_iter().flatten().collect();
+            let first_deviation = column.iter().position(|v| (v - base).abs() > 1e-9);
+            assert_eq!(first_deviation, Some(start), "{}", id);
+            assert!((column[start] - base - deviation).abs() < 1e-9, "{}", id);
+            // Effects last as long as the trigger
+            assert!(
+                (column[start + 49] - base - deviation).abs() < 1e-9,
+                "{}",
+                id
+            );
+            assert!((column[start + 50] - base).abs() < 1e-9, "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_cascade_without_sensor_is_ignored() {
+        let config = GeneratorConfig::new()
+            .with_num_samples(200)
+            .with_seed(42)
+            .with_cascade(pump_failure());
+        let sensors = vec![constant_sensor("temp", 20.0)];
+
+        let dataset = generate_dataset(&config, &sensors);
+        assert_eq!(dataset.rows()[110].get("temp"), Some(25.0));
+    }
 }
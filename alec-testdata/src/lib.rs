@@ -34,6 +34,27 @@
 //! dataset.to_csv("farm_data.csv").unwrap();
 //! ```
 //!
+//! ## Streaming
+//!
+//! Long datasets can be generated and written row by row with bounded
+//! memory:
+//!
+//! ```rust,no_run
+//! use alec_testdata::{DatasetWriter, GeneratorConfig};
+//! use alec_testdata::industries::agriculture::{AgriculturalScenario, create_farm_sensors};
+//!
+//! let config = GeneratorConfig::new()
+//!     .with_sample_interval_secs(1)
+//!     .with_duration_hours(90.0 * 24.0)
+//!     .with_seed(42);
+//! let sensors = create_farm_sensors(AgriculturalScenario::Normal);
+//! let ids: Vec<String> = sensors.iter().map(|s| s.id.clone()).collect();
+//!
+//! let mut writer = DatasetWriter::create("farm_90d.csv", &ids).unwrap();
+//! writer.write_rows(config.stream(&sensors)).unwrap();
+//! writer.finish().unwrap();
+//! ```
+//!
 //! ## Industry Generators
 //!
 //! Each industry module provides pre-configured sensor sets:
@@ -73,12 +94,12 @@ pub mod patterns;
 pub mod scenario;
 
 // Re-exports for convenience
-pub use anomalies::{AnomalyConfig, AnomalyType};
-pub use dataset::{Dataset, DatasetRow};
-pub use generator::{generate_dataset, GeneratorConfig, SensorConfig};
-pub use manifest::{DatasetManifest, SensorManifest};
+pub use anomalies::{AnomalyConfig, AnomalyType, CascadeConfig, CascadeEffect};
+pub use dataset::{Dataset, DatasetRow, DatasetWriter, RowMetrics, TimestampUnit};
+pub use generator::{generate_dataset, DatasetStream, GeneratorConfig, SensorConfig};
+pub use manifest::{CascadeManifest, DatasetManifest, NominalStats, SensorManifest};
 pub use patterns::SignalPattern;
-pub use scenario::{AnomalyScenario, ExpectedEvent, ScenarioValidation};
+pub use scenario::{AnomalyScenario, CascadeValidation, ExpectedEvent, ScenarioValidation};
 
 /// Library version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -9,9 +9,11 @@
 //! Manifests provide metadata about datasets, including expected
 //! metrics ranges and anomaly information.
 
-use crate::anomalies::AnomalyConfig;
+use crate::anomalies::{AnomalyConfig, CascadeConfig};
+use crate::dataset::{RowMetrics, TimestampUnit};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Dataset manifest describing a pre-generated dataset.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,14 +30,23 @@ pub struct DatasetManifest {
     pub sample_count: usize,
     /// Sample interval in milliseconds.
     pub sample_interval_ms: u64,
+    /// Unit of the dataset's row timestamps.
+    #[serde(default)]
+    pub timestamp_unit: TimestampUnit,
     /// Sensor definitions.
     pub sensors: Vec<SensorManifest>,
     /// Anomalies present in the dataset.
     #[serde(default)]
     pub anomalies: Vec<AnomalyManifest>,
+    /// Cascades of correlated anomalies present in the dataset.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cascades: Vec<CascadeManifest>,
     /// Expected metrics ranges.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expected_metrics: Option<ExpectedMetrics>,
+    /// Statistics of the row metrics over the non-anomalous samples.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nominal_stats: Option<NominalStats>,
     /// Generation timestamp.
     pub generated_at: DateTime<Utc>,
     /// Random seed used.
@@ -77,6 +88,42 @@ pub struct AnomalyManifest {
     pub expected_event: String,
 }
 
+/// Cascade information in manifest.
+///
+/// Each event carries its own sensor and window, so detectors can be
+/// scored per effect as well as for the cascade as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CascadeManifest {
+    /// Cascade name.
+    pub name: String,
+    /// Anomaly on the trigger sensor.
+    pub trigger: AnomalyManifest,
+    /// Time-shifted anomalies on the affected sensors.
+    pub effects: Vec<AnomalyManifest>,
+}
+
+impl CascadeManifest {
+    /// Create from cascade config.
+    pub fn from_config(config: &CascadeConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            trigger: AnomalyManifest::from_config(&config.trigger_sensor, &config.trigger),
+            effects: config
+                .effects
+                .iter()
+                .map(|effect| {
+                    AnomalyManifest::from_config(&effect.sensor_id, &config.effect_anomaly(effect))
+                })
+                .collect(),
+        }
+    }
+
+    /// All expected events, trigger first.
+    pub fn events(&self) -> impl Iterator<Item = &AnomalyManifest> {
+        std::iter::once(&self.trigger).chain(&self.effects)
+    }
+}
+
 /// Expected metrics ranges for validation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExpectedMetrics {
@@ -94,6 +141,53 @@ pub struct ExpectedMetrics {
     pub h_bytes_range: Option<(f64, f64)>,
 }
 
+/// Nominal statistics of the row metrics ([`RowMetrics`]).
+///
+/// Means and standard deviations are keyed by metric name (`tc`,
+/// `h_joint`, `h_bytes`, `r`), the format the complexity baseline
+/// summary is built from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NominalStats {
+    /// Mean of each metric.
+    pub means: BTreeMap<String, f64>,
+    /// Sample standard deviation of each metric.
+    pub stds: BTreeMap<String, f64>,
+    /// Rows the statistics were computed over.
+    pub sample_count: usize,
+}
+
+impl NominalStats {
+    /// Statistics of a series of row metrics. Standard deviations are 0.0
+    /// below two rows.
+    pub fn from_metrics(metrics: impl IntoIterator<Item = RowMetrics>) -> Self {
+        let metrics: Vec<RowMetrics> = metrics.into_iter().collect();
+        let mut stats = Self {
+            sample_count: metrics.len(),
+            ..Default::default()
+        };
+        if metrics.is_empty() {
+            return stats;
+        }
+
+        let n = metrics.len() as f64;
+        for (i, (name, _)) in metrics[0].named().iter().enumerate() {
+            let mean = metrics.iter().map(|m| m.named()[i].1).sum::<f64>() / n;
+            let std = if metrics.len() > 1 {
+                let m2: f64 = metrics
+                    .iter()
+                    .map(|m| (m.named()[i].1 - mean).powi(2))
+                    .sum();
+                (m2 / (n - 1.0)).sqrt()
+            } else {
+                0.0
+            };
+            stats.means.insert(name.to_string(), mean);
+            stats.stds.insert(name.to_string(), std);
+        }
+        stats
+    }
+}
+
 impl DatasetManifest {
     /// Create a new manifest.
     pub fn new(name: &str, industry: &str) -> Self {
@@ -104,9 +198,12 @@ impl DatasetManifest {
             duration_ms: 0,
             sample_count: 0,
             sample_interval_ms: 60_000,
+            timestamp_unit: TimestampUnit::Millis,
             sensors: Vec::new(),
             anomalies: Vec::new(),
+            cascades: Vec::new(),
             expected_metrics: None,
+            nominal_stats: None,
             generated_at: Utc::now(),
             seed: None,
         }
@@ -126,6 +223,12 @@ impl DatasetManifest {
         self
     }
 
+    /// Set the unit of the dataset's row timestamps.
+    pub fn with_timestamp_unit(mut self, unit: TimestampUnit) -> Self {
+        self.timestamp_unit = unit;
+        self
+    }
+
     /// Add a sensor.
     pub fn add_sensor(mut self, sensor: SensorManifest) -> Self {
         self.sensors.push(sensor);
@@ -138,12 +241,24 @@ impl DatasetManifest {
         self
     }
 
+    /// Add a cascade of correlated anomalies.
+    pub fn add_cascade(mut self, cascade: &CascadeConfig) -> Self {
+        self.cascades.push(CascadeManifest::from_config(cascade));
+        self
+    }
+
     /// Set expected metrics.
     pub fn with_expected_metrics(mut self, metrics: ExpectedMetrics) -> Self {
         self.expected_metrics = Some(metrics);
         self
     }
 
+    /// Set nominal statistics.
+    pub fn with_nominal_stats(mut self, stats: NominalStats) -> Self {
+        self.nominal_stats = Some(stats);
+        self
+    }
+
     /// Set seed.
     pub fn with_seed(mut self, seed: u64) -> Self {
         self.seed = Some(seed);
@@ -241,4 +356,165 @@ mod tests {
         assert_eq!(sensor.id, "temp");
         assert_eq!(sensor.expected_entropy_range, Some((2.5, 3.5)));
     }
+
+    /// Two sensors with a bias shift on `temp` from sample 200 to 250
+    fn shifted_dataset() -> (
+        crate::GeneratorConfig,
+        Vec<crate::SensorConfig>,
+        crate::Dataset,
+    ) {
+        use crate::anomalies::AnomalyType;
+        use crate::{generate_dataset, GeneratorConfig, SensorConfig, SignalPattern};
+
+        let config = GeneratorConfig::new().with_num_samples(300).with_seed(7);
+        let sensors = vec![
+            SensorConfig::new(
+                "temp",
+                "°C",
+                -100.0,
+                100.0,
+                SignalPattern::Sine {
+                    amplitude: 2.0,
+                    period_ms: 3_600_000,
+                    phase: 0.0,
+                    offset: 20.0,
+                },
+            )
+            .with_noise(0.2)
+            .with_anomaly(
+                AnomalyConfig::new(AnomalyType::BiasShift { offset: -40.0 }, 200).with_duration(50),
+            ),
+            SensorConfig::new(
+                "humidity",
+                "%",
+                0.0,
+                100.0,
+                SignalPattern::Constant { value: 60.0 },
+            )
+            .with_noise(1.0),
+        ];
+        let dataset = generate_dataset(&config, &sensors);
+        (config, sensors, dataset)
+    }
+
+    #[test]
+    fn test_nominal_stats_skip_anomalies() {
+        let (config, sensors, dataset) = shifted_dataset();
+        assert!(config.is_anomalous(&sensors, 200));
+        assert!(!config.is_anomalous(&sensors, 250));
+
+        let stats = config.nominal_stats(&sensors, &dataset);
+        assert_eq!(stats.sample_count, 250);
+        let everything =
+            NominalStats::from_metrics((0..dataset.len()).filter_map(|i| dataset.row_metrics(i)));
+        assert!(stats.means["tc"] < everything.means["tc"]);
+        assert!(stats.stds["tc"] > 0.0);
+        // Every sensor always reports
+        assert_eq!(stats.means["r"], 1.0);
+        assert_eq!(stats.stds["r"], 0.0);
+
+        let manifest = DatasetManifest::new("shifted", "testing").with_nominal_stats(stats.clone());
+        let restored: DatasetManifest = serde_json::from_str(&manifest.to_json().unwrap()).unwrap();
+        let restored = restored.nominal_stats.unwrap();
+        assert_eq!(restored.sample_count, stats.sample_count);
+        for (name, std) in &stats.stds {
+            assert!((restored.stds[name] - std).abs() < 1e-12);
+        }
+    }
+
+    #[cfg(feature = "complexity")]
+    #[test]
+    fn test_locked_baseline_from_manifest() {
+        use alec_complexity::config::AnomalyConfig as DetectorConfig;
+        use alec_complexity::{
+            BaselineSummary, ComplexityConfig, ComplexityEngine, GenericInput, MetricKind,
+        };
+
+        let (config, sensors, dataset) = shifted_dataset();
+        let manifest = DatasetManifest::new("shifted", "testing")
+            .with_nominal_stats(config.nominal_stats(&sensors, &dataset))
+            .add_anomaly(AnomalyManifest::from_config(
+                "temp",
+                sensors[0].anomaly.as_ref().unwrap(),
+            ));
+
+        let stats = manifest.nominal_stats.as_ref().unwrap();
+        let summary = BaselineSummary::from_dataset_stats(&stats.means, &stats.stds)
+            .unwrap()
+            .with_sample_count(stats.sample_count as u64);
+        assert_eq!(summary.zero_std_metrics(), vec![MetricKind::R]);
+
+        let engine_config = ComplexityConfig {
+            enabled: true,
+            anomaly: DetectorConfig {
+                persistence_ms: config.sample_interval_ms,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = ComplexityEngine::with_locked_baseline(engine_config, &summary).unwrap();
+
+        let anomaly = &manifest.anomalies[0];
+        let end = anomaly.start_sample + anomaly.duration_samples.unwrap();
+        let mut events = Vec::new();
+        for i in anomaly.start_sample..end {
+            let metrics = dataset.row_metrics(i).unwrap();
+            let input = GenericInput::new(dataset.rows()[i].timestamp_ms, metrics.h_bytes)
+                .with_tc(metrics.tc)
+                .with_h_joint(metrics.h_joint)
+                .with_r(metrics.r)
+                .build();
+            let snapshot = engine.process(&input).unwrap();
+            assert!(snapshot.is_baseline_locked());
+            events.extend(snapshot.events);
+        }
+
+        assert!(events
+            .iter()
+            .all(|e| e.event_type.as_str() != "BASELINE_BUILDING"));
+        let surge = events
+            .iter()
+            .find(|e| e.event_type.as_str() == anomaly.expected_event)
+            .expect("expected event fires");
+        // Persistence is one sample
+        assert!(surge.timestamp_ms <= dataset.rows()[anomaly.start_sample + 1].timestamp_ms);
+    }
+
+    #[test]
+    fn test_manifest_cascade_events() {
+        use crate::anomalies::AnomalyType;
+
+        let cascade = CascadeConfig::new(
+            "pump_failure",
+            "pressure",
+            AnomalyConfig::new(AnomalyType::BiasShift { offset: -10.0 }, 100).with_duration(50),
+        )
+        .with_effect("temp", AnomalyType::drift(0.1), 10, 1.0)
+        .with_effect("flow", AnomalyType::Stuck, 30, 1.0);
+
+        let manifest = DatasetManifest::new("plant", "manufacturing").add_cascade(&cascade);
+
+        let events: Vec<_> = manifest.cascades[0]
+            .events()
+            .map(|e| {
+                (
+                    e.sensor_id.as_str(),
+                    e.start_sample,
+                    e.duration_samples,
+                    e.expected_event.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("pressure", 100, Some(50), "COMPLEXITY_SURGE"),
+                ("temp", 110, Some(50), "COMPLEXITY_SURGE"),
+                ("flow", 130, Some(50), "STRUCTURE_BREAK"),
+            ]
+        );
+
+        let json = manifest.to_json().unwrap();
+        assert!(json.contains("\"cascades\""));
+    }
 }
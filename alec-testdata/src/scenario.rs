@@ -10,6 +10,7 @@
 //! detection outcomes.
 
 use crate::anomalies::AnomalyType;
+use crate::manifest::{AnomalyManifest, CascadeManifest};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -103,6 +104,37 @@ pub struct ScenarioValidation {
     /// Minimum z-score expected.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_z_score: Option<f64>,
+    /// Group detection criterion for cascades.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cascade: Option<CascadeValidation>,
+}
+
+/// Detection criterion for a cascade of correlated anomalies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CascadeValidation {
+    /// Effects that must be flagged for the cascade to count as detected.
+    pub min_effects_detected: usize,
+}
+
+impl CascadeValidation {
+    /// Require at least `k` flagged effects.
+    pub fn at_least(k: usize) -> Self {
+        Self {
+            min_effects_detected: k,
+        }
+    }
+
+    /// Whether the cascade was detected as a group.
+    ///
+    /// `flagged` tells whether the detector flagged one effect, typically
+    /// by looking for an event on its sensor within its window.
+    pub fn is_detected(
+        &self,
+        cascade: &CascadeManifest,
+        mut flagged: impl FnMut(&AnomalyManifest) -> bool,
+    ) -> bool {
+        cascade.effects.iter().filter(|e| flagged(e)).count() >= self.min_effects_detected
+    }
 }
 
 impl AnomalyScenario {
@@ -347,4 +379,24 @@ mod tests {
         assert!(names.contains(&"gradual_drift"));
         assert!(names.contains(&"sudden_spike"));
     }
+
+    #[test]
+    fn test_cascade_group_detection() {
+        use crate::anomalies::{AnomalyConfig, CascadeConfig};
+
+        let cascade = CascadeConfig::new(
+            "pump_failure",
+            "pressure",
+            AnomalyConfig::new(AnomalyType::Stuck, 100),
+        )
+        .with_effect("temp", AnomalyType::drift(0.1), 10, 1.0)
+        .with_effect("flow", AnomalyType::Stuck, 30, 1.0)
+        .with_effect("vibration", AnomalyType::spike(5.0), 5, 1.0);
+        let manifest = CascadeManifest::from_config(&cascade);
+
+        // Detector flagged temp and flow, missed vibration
+        let flagged = |e: &AnomalyManifest| e.sensor_id != "vibration";
+        assert!(CascadeValidation::at_least(2).is_detected(&manifest, flagged));
+        assert!(!CascadeValidation::at_least(3).is_detected(&manifest, flagged));
+    }
 }
@@ -0,0 +1,131 @@
+//! Benchmarks for context evolution and sync diff application
+
+use alec::context::{EvolutionConfig, Pattern};
+use alec::{Context, RawData, SyncDiff, Synchronizer};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use std::time::{Duration, Instant};
+
+/// Numeric pattern with a frequency scattered over codes, so evolution
+/// renumbers most of the dictionary
+fn pattern(i: u32) -> Pattern {
+    Pattern {
+        frequency: (i as u64 * 7919) % 97 + 1,
+        ..Pattern::numeric(i as f64 * 0.01)
+    }
+}
+
+/// Context holding `count` patterns, none of them pruned by evolution
+fn dictionary(count: u32) -> Context {
+    evolving_dictionary(count, EvolutionConfig::default())
+}
+
+/// [`dictionary`] evolving with `evolution`
+fn evolving_dictionary(count: u32, evolution: EvolutionConfig) -> Context {
+    let mut ctx = Context::with_evolution(EvolutionConfig {
+        min_frequency: 1,
+        max_age: u64::MAX,
+        ..evolution
+    });
+    for i in 0..count {
+        ctx.set_pattern(i, pattern(i)).unwrap();
+    }
+    ctx
+}
+
+fn bench_evolve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("context");
+    let ctx = dictionary(10_000);
+
+    group.bench_function("evolve_10k_patterns", |b| {
+        b.iter_batched(
+            || ctx.clone(),
+            |mut ctx| {
+                ctx.evolve();
+                black_box(ctx)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+/// Slowest single observation over an evolution, from the triggering one
+/// until no cycle is pending
+fn bench_observe_worst_case(c: &mut Criterion) {
+    let mut group = c.benchmark_group("context");
+    const INTERVAL: u64 = 1_000;
+
+    for (name, step_patterns) in [
+        ("observe_worst_case_50k_patterns_at_once", 0),
+        ("observe_worst_case_50k_patterns_incremental", 256),
+    ] {
+        let mut ctx = evolving_dictionary(
+            50_000,
+            EvolutionConfig {
+                evolution_interval: INTERVAL,
+                step_patterns,
+                ..Default::default()
+            },
+        );
+        // Next observation triggers the evolution
+        for i in 1..INTERVAL {
+            ctx.observe(&RawData::new(20.0, i));
+        }
+
+        group.bench_function(name, |b| {
+            b.iter_custom(|iters| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    let mut ctx = ctx.clone();
+                    let mut worst = Duration::ZERO;
+                    loop {
+                        let start = Instant::now();
+                        ctx.observe(black_box(&RawData::new(20.0, INTERVAL)));
+                        worst = worst.max(start.elapsed());
+                        if !ctx.evolution_pending() {
+                            break;
+                        }
+                    }
+                    total += worst;
+                }
+                total
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_apply_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync");
+    let base = dictionary(1_000);
+    let mut target = base.clone();
+    for i in 1_000..2_000 {
+        target.set_pattern(i, pattern(i)).unwrap();
+    }
+    target.set_dictionary_version(base.dictionary_version() + 1);
+    let diff: SyncDiff = Synchronizer::generate_diff(&base, &target);
+    assert_eq!(diff.added.len(), 1_000);
+
+    group.bench_function("apply_diff_1k_additions", |b| {
+        b.iter_batched(
+            || base.clone(),
+            |mut ctx| {
+                Synchronizer::apply_diff(&mut ctx, &diff).unwrap();
+                black_box(ctx)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_evolve,
+    bench_observe_worst_case,
+    bench_apply_diff
+);
+criterion_main!(benches);
@@ -317,7 +317,7 @@ fn build_preload(ctx: &Context, config: &PreloadConfig) -> PreloadFile {
     for (&code, pattern) in ctx.patterns_iter() {
         if code <= u16::MAX as u32 {
             dictionary.push(PreloadDictEntry {
-                pattern: pattern.data.clone(),
+                pattern: pattern.data.to_vec(),
                 code: code as u16,
                 frequency: pattern.frequency as u32,
             });
@@ -345,7 +345,7 @@ fn build_preload(ctx: &Context, config: &PreloadConfig) -> PreloadFile {
 
     PreloadFile {
         format_version: 1,
-        context_version: ctx.version(),
+        context_version: ctx.dictionary_version(),
         sensor_type: config.sensor_type.to_string(),
         created_timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
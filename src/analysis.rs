@@ -0,0 +1,509 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Capture analysis
+//!
+//! Walks a binary capture of concatenated ALEC messages and summarizes
+//! what it contains: message types, encodings, priorities, sizes, sources
+//! and timestamps. With the receiver's context, the messages are also
+//! decoded to measure the decode success rate and compression ratio.
+//!
+//! Messages carry no length prefix, so each one is delimited by parsing
+//! its header and payload. Only `Data` messages and confirmations can be
+//! delimited this way. Unreadable bytes are skipped until the next
+//! readable message and reported once per corrupted segment in
+//! [`CaptureReport::errors`].
+//!
+//! ```
+//! use alec::analysis::analyze_capture;
+//! use alec::{Classifier, Context, Encoder, RawData};
+//!
+//! let mut encoder = Encoder::new();
+//! let mut context = Context::new();
+//! let mut capture = Vec::new();
+//! for i in 0..10 {
+//!     let data = RawData::new(20.0 + i as f64 * 0.1, 1_000_000 + i * 1_000);
+//!     let classification = Classifier::default().classify(&data, &context);
+//!     capture.extend(encoder.encode_to_bytes(&data, &classification, &context));
+//!     context.observe(&data);
+//! }
+//!
+//! let report = analyze_capture(&capture, Some(&Context::new()));
+//! assert_eq!(report.messages, 10);
+//! assert_eq!(report.decode.as_ref().unwrap().succeeded, 10);
+//! println!("{}", report);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::Context;
+use crate::decoder::Decoder;
+use crate::protocol::{
+    payload_len, read_varint, ChecksumPolicy, EncodedMessage, EncodingType, MessageHeader,
+    MessageType, RawData, TimestampFormat, CHECKSUM_SIZE,
+};
+
+/// Upper bounds (inclusive) of the message size buckets, in bytes
+const SIZE_BUCKETS: [usize; 4] = [8, 16, 32, 64];
+
+/// Unreadable segment of a capture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureError {
+    /// Offset of the first unreadable byte
+    pub offset: usize,
+    /// Number of bytes skipped before the next readable message
+    pub skipped: usize,
+    /// Why the first message of the segment could not be read
+    pub reason: String,
+}
+
+/// Number of messages within a size range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBucket {
+    /// Range label, e.g. "9-16"
+    pub label: String,
+    /// Messages in the range
+    pub count: u64,
+}
+
+/// Message size distribution, checksums included
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeStats {
+    /// Smallest message (bytes)
+    pub min: usize,
+    /// Largest message (bytes)
+    pub max: usize,
+    /// Mean message size (bytes)
+    pub mean: f64,
+    /// Messages per size range
+    pub buckets: Vec<SizeBucket>,
+}
+
+impl Default for SizeStats {
+    fn default() -> Self {
+        let mut lower = 0;
+        let mut buckets: Vec<SizeBucket> = SIZE_BUCKETS
+            .iter()
+            .map(|&upper| {
+                let label = format!("{}-{}", lower, upper);
+                lower = upper + 1;
+                SizeBucket { label, count: 0 }
+            })
+            .collect();
+        buckets.push(SizeBucket {
+            label: format!("{}+", lower),
+            count: 0,
+        });
+
+        Self {
+            min: 0,
+            max: 0,
+            mean: 0.0,
+            buckets,
+        }
+    }
+}
+
+/// Decode results, present when the capture was analyzed with a context
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecodeStats {
+    /// Messages handed to the decoder
+    pub attempted: u64,
+    /// Messages decoded successfully
+    pub succeeded: u64,
+    /// Values decoded (one per single message, several per multi message,
+    /// none per confirmation)
+    pub values: u64,
+    /// Raw size of the decoded values (bytes)
+    pub raw_bytes: u64,
+    /// Size of the successfully decoded value messages (bytes)
+    pub encoded_bytes: u64,
+}
+
+impl DecodeStats {
+    /// Share of messages decoded successfully (0.0-1.0)
+    pub fn success_rate(&self) -> f64 {
+        if self.attempted == 0 {
+            return 0.0;
+        }
+        self.succeeded as f64 / self.attempted as f64
+    }
+
+    /// Compression ratio of the decoded messages versus raw (raw / encoded)
+    pub fn compression_ratio(&self) -> f64 {
+        if self.encoded_bytes == 0 {
+            return 1.0;
+        }
+        self.raw_bytes as f64 / self.encoded_bytes as f64
+    }
+}
+
+/// Summary of a capture of raw ALEC messages
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureReport {
+    /// Capture size (bytes)
+    pub total_bytes: usize,
+    /// Messages read
+    pub messages: u64,
+    /// Messages per message type
+    pub message_types: BTreeMap<String, u64>,
+    /// Messages per payload encoding
+    pub encodings: BTreeMap<String, u64>,
+    /// Messages per priority
+    pub priorities: BTreeMap<String, u64>,
+    /// Message size distribution
+    pub sizes: SizeStats,
+    /// Source ids seen
+    pub sources: BTreeSet<u32>,
+    /// Earliest full header timestamp (seconds)
+    pub first_timestamp: Option<u64>,
+    /// Latest full header timestamp (seconds)
+    pub last_timestamp: Option<u64>,
+    /// Messages whose checksum matched
+    pub checksums_verified: u64,
+    /// Messages whose checksum did not match
+    pub checksum_failures: u64,
+    /// Bytes that could not be read as messages
+    pub skipped_bytes: usize,
+    /// Unreadable segments
+    pub errors: Vec<CaptureError>,
+    /// Decode results, when analyzed with a context
+    pub decode: Option<DecodeStats>,
+}
+
+impl CaptureReport {
+    /// Serialize to pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Serialize to compact JSON
+    pub fn to_json_compact(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Record one message of `size` bytes
+    fn record(&mut self, message: &EncodedMessage, size: usize) {
+        let header = &message.header;
+        *self
+            .message_types
+            .entry(format!("{:?}", header.message_type))
+            .or_insert(0) += 1;
+        *self
+            .priorities
+            .entry(format!("{:?}", header.priority))
+            .or_insert(0) += 1;
+        if let Some(encoding) = message.encoding_type() {
+            *self.encodings.entry(format!("{:?}", encoding)).or_insert(0) += 1;
+        }
+        if let Some((source_id, _)) = read_varint(&message.payload) {
+            self.sources.insert(source_id);
+        }
+        if header.timestamp_format() == TimestampFormat::Full {
+            let timestamp = header.timestamp as u64;
+            self.first_timestamp =
+                Some(self.first_timestamp.map_or(timestamp, |t| t.min(timestamp)));
+            self.last_timestamp = Some(self.last_timestamp.map_or(timestamp, |t| t.max(timestamp)));
+        }
+
+        let sizes = &mut self.sizes;
+        if self.messages == 0 {
+            sizes.min = size;
+            sizes.max = size;
+        } else {
+            sizes.min = sizes.min.min(size);
+            sizes.max = sizes.max.max(size);
+        }
+        sizes.mean += (size as f64 - sizes.mean) / (self.messages + 1) as f64;
+        let bucket = SIZE_BUCKETS
+            .iter()
+            .position(|&upper| size <= upper)
+            .unwrap_or(SIZE_BUCKETS.len());
+        sizes.buckets[bucket].count += 1;
+
+        self.messages += 1;
+    }
+}
+
+impl fmt::Display for CaptureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "ALEC capture report")?;
+        writeln!(f, "  {:<20} {:>10}", "Bytes", self.total_bytes)?;
+        writeln!(f, "  {:<20} {:>10}", "Messages", self.messages)?;
+        writeln!(f, "  {:<20} {:>10}", "Sources", self.sources.len())?;
+        if let (Some(first), Some(last)) = (self.first_timestamp, self.last_timestamp) {
+            writeln!(f, "  {:<20} {:>10}", "First timestamp", first)?;
+            writeln!(f, "  {:<20} {:>10}", "Last timestamp", last)?;
+        }
+        writeln!(
+            f,
+            "  {:<20} {:>10}",
+            "Checksums verified", self.checksums_verified
+        )?;
+        writeln!(
+            f,
+            "  {:<20} {:>10}",
+            "Checksum failures", self.checksum_failures
+        )?;
+        writeln!(f, "  {:<20} {:>10}", "Skipped bytes", self.skipped_bytes)?;
+
+        for (title, counts) in [
+            ("Message types", &self.message_types),
+            ("Encodings", &self.encodings),
+            ("Priorities", &self.priorities),
+        ] {
+            writeln!(f, "{}", title)?;
+            for (name, count) in counts {
+                writeln!(f, "  {:<20} {:>10}", name, count)?;
+            }
+        }
+
+        writeln!(
+            f,
+            "Sizes (min {}, max {}, mean {:.1})",
+            self.sizes.min, self.sizes.max, self.sizes.mean
+        )?;
+        for bucket in &self.sizes.buckets {
+            writeln!(f, "  {:<20} {:>10}", bucket.label, bucket.count)?;
+        }
+
+        if let Some(decode) = &self.decode {
+            writeln!(f, "Decoding")?;
+            writeln!(
+                f,
+                "  {:<20} {:>10}",
+                "Succeeded",
+                format!("{}/{}", decode.succeeded, decode.attempted)
+            )?;
+            writeln!(
+                f,
+                "  {:<20} {:>9.1}%",
+                "Success rate",
+                decode.success_rate() * 100.0
+            )?;
+            writeln!(
+                f,
+                "  {:<20} {:>9.2}x",
+                "Compression ratio",
+                decode.compression_ratio()
+            )?;
+        }
+
+        if !self.errors.is_empty() {
+            writeln!(f, "Errors")?;
+            for error in &self.errors {
+                writeln!(
+                    f,
+                    "  @{:<8} {:>5} bytes  {}",
+                    error.offset, error.skipped, error.reason
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a message could not be read at some offset
+enum ReadError {
+    /// Bytes do not form a message
+    Malformed(String),
+    /// Message parsed but its checksum did not match
+    Checksum,
+}
+
+/// Message read from a capture
+struct Frame {
+    message: EncodedMessage,
+    /// Bytes used, checksum included
+    len: usize,
+    checksum_verified: bool,
+}
+
+/// Analyze a capture of concatenated messages
+///
+/// Checksums are detected: a message followed by its own checksum is
+/// counted as verified. Use [`analyze_capture_with_policy`] when the
+/// encoder's checksum policy is known, so mismatches are reported.
+///
+/// When `context` is given, messages are decoded against a copy of it,
+/// updated with every decoded value as a receiver would.
+pub fn analyze_capture(bytes: &[u8], context: Option<&Context>) -> CaptureReport {
+    analyze(bytes, context, None)
+}
+
+/// Analyze a capture written with a known checksum policy
+pub fn analyze_capture_with_policy(
+    bytes: &[u8],
+    context: Option<&Context>,
+    policy: ChecksumPolicy,
+) -> CaptureReport {
+    analyze(bytes, context, Some(policy))
+}
+
+fn analyze(
+    bytes: &[u8],
+    context: Option<&Context>,
+    policy: Option<ChecksumPolicy>,
+) -> CaptureReport {
+    let mut report = CaptureReport {
+        total_bytes: bytes.len(),
+        ..Default::default()
+    };
+    let mut receiver = context.map(|context| (Decoder::new(), context.clone()));
+    report.decode = receiver.as_ref().map(|_| DecodeStats::default());
+
+    // Start of the unreadable segment being skipped, if any
+    let mut segment: Option<usize> = None;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        match read_frame(&bytes[offset..], policy) {
+            Ok(frame) => {
+                if let Some(start) = segment.take() {
+                    close_segment(&mut report, start, offset);
+                }
+                report.record(&frame.message, frame.len);
+                if frame.checksum_verified {
+                    report.checksums_verified += 1;
+                }
+                if let (Some((decoder, context)), Some(stats)) =
+                    (receiver.as_mut(), report.decode.as_mut())
+                {
+                    decode_frame(decoder, context, &frame, stats);
+                }
+                offset += frame.len;
+            }
+            Err(error) => {
+                if segment.is_none() {
+                    let reason = match error {
+                        ReadError::Malformed(reason) => reason,
+                        ReadError::Checksum => {
+                            report.checksum_failures += 1;
+                            "checksum mismatch".to_string()
+                        }
+                    };
+                    report.errors.push(CaptureError {
+                        offset,
+                        skipped: 0,
+                        reason,
+                    });
+                    segment = Some(offset);
+                }
+                offset += 1;
+            }
+        }
+    }
+    if let Some(start) = segment {
+        close_segment(&mut report, start, bytes.len());
+    }
+
+    report
+}
+
+/// Record the size of the unreadable segment `start..end`
+fn close_segment(report: &mut CaptureReport, start: usize, end: usize) {
+    report.skipped_bytes += end - start;
+    if let Some(error) = report.errors.last_mut() {
+        error.skipped = end - start;
+    }
+}
+
+/// Decode one frame and update the receiver context
+fn decode_frame(
+    decoder: &mut Decoder,
+    context: &mut Context,
+    frame: &Frame,
+    stats: &mut DecodeStats,
+) {
+    stats.attempted += 1;
+    if frame.message.is_confirmation() || frame.message.is_plan() {
+        if decoder.decode_item(&frame.message, context).is_ok() {
+            stats.succeeded += 1;
+        }
+        return;
+    }
+
+    let decoded: Vec<RawData> = if frame.message.encoding_type() == Some(EncodingType::Multi) {
+        match decoder.decode_multi_ex(&frame.message, context) {
+            Ok(values) => values
+                .iter()
+                .map(|v| {
+                    RawData::with_source(v.name_id as u32, v.value, v.timestamp.unwrap_or_default())
+                })
+                .collect(),
+            Err(_) => return,
+        }
+    } else {
+        match decoder.decode(&frame.message, context) {
+            Ok(d) => vec![RawData::with_source(d.source_id, d.value, d.timestamp)],
+            Err(_) => return,
+        }
+    };
+
+    stats.succeeded += 1;
+    stats.values += decoded.len() as u64;
+    stats.encoded_bytes += frame.len as u64;
+    for data in &decoded {
+        stats.raw_bytes += data.raw_size() as u64;
+        context.observe(data);
+    }
+}
+
+/// Read the message at the start of `bytes`
+fn read_frame(bytes: &[u8], policy: Option<ChecksumPolicy>) -> Result<Frame, ReadError> {
+    let malformed = |reason: &str| ReadError::Malformed(reason.to_string());
+
+    let (header, header_len) =
+        MessageHeader::parse(bytes).ok_or_else(|| malformed("invalid header"))?;
+    if let Some(extension) = header.extension() {
+        extension
+            .check()
+            .map_err(|e| ReadError::Malformed(e.to_string()))?;
+    }
+
+    let payload = &bytes[header_len..];
+    let payload_len = match header.message_type {
+        MessageType::Data => payload_len(payload, header.is_self_describing()),
+        // Confirmations carry only the source id
+        MessageType::Heartbeat => read_varint(payload).map(|(_, len)| len),
+        other => {
+            return Err(ReadError::Malformed(format!(
+                "cannot delimit {:?} message",
+                other
+            )))
+        }
+    }
+    .ok_or_else(|| malformed("truncated or invalid payload"))?;
+    let len = header_len + payload_len;
+    let checksum = bytes
+        .get(len..len + CHECKSUM_SIZE)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let matches = checksum == Some(header.checksum_algorithm().checksum(&bytes[..len]));
+
+    let checksum_verified = match policy {
+        Some(policy) if policy.applies_to(header.priority) => {
+            if !matches {
+                return Err(ReadError::Checksum);
+            }
+            true
+        }
+        Some(_) => false,
+        None => matches,
+    };
+
+    let message = EncodedMessage::new(header, bytes[header_len..len].to_vec());
+    Ok(Frame {
+        message,
+        len: if checksum_verified {
+            len + CHECKSUM_SIZE
+        } else {
+            len
+        },
+        checksum_verified,
+    })
+}
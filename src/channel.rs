@@ -8,11 +8,34 @@
 //!
 //! This module provides traits and implementations for communication channels
 //! between emitters and receivers.
-
-use crate::error::{ChannelError, Result};
-use crate::protocol::EncodedMessage;
+//!
+//! [`Channel`] is a single object owned by one thread. To run the sending
+//! and receiving sides separately (different threads, or a poll loop),
+//! split a [`MemoryChannel`] into a [`MemorySender`] / [`MemoryReceiver`]
+//! pair implementing [`ChannelTx`] / [`ChannelRx`].
+//!
+//! # Thread safety
+//!
+//! Both halves are `Send` and internally synchronized (`Sync` as well), so
+//! each can be moved to its own thread. Non-blocking `try_send` /
+//! `try_recv` return [`ChannelError::WouldBlock`] instead of waiting.
+//!
+//! # Transmit queue
+//!
+//! [`PriorityQueue`] holds messages or frames between encoding and the
+//! radio. It is bounded in bytes and, when over budget, evicts the
+//! lowest priorities first; P1 items are only dropped past an absolute
+//! cap. An attached [`CircuitBreaker`] pauses dequeuing while the link is
+//! down.
+
+use crate::error::{AlecError, ChannelError, Result};
+use crate::health::{HealthCheck, HealthCheckable, HealthConfig};
+use crate::latency::{LatencyLog, LatencyReport};
+use crate::protocol::{EncodedMessage, Priority};
+use crate::recovery::{CircuitBreaker, CircuitState};
 use std::collections::VecDeque;
-use std::time::Duration;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 /// Statistics about channel usage
 #[derive(Debug, Clone, Default)]
@@ -115,6 +138,39 @@ impl MemoryChannel {
             other.rx_buffer.push_back(msg);
         }
     }
+
+    /// Maximum number of buffered messages per direction
+    pub fn capacity(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    /// Split into a sending and a receiving half
+    ///
+    /// Messages sent on the [`MemorySender`] are delivered directly to the
+    /// [`MemoryReceiver`], bounded by the buffer size. Pending incoming
+    /// messages are received first, followed by pending outgoing ones.
+    /// A closed channel yields closed halves.
+    pub fn split(self) -> (MemorySender, MemoryReceiver) {
+        let mut queue = self.rx_buffer;
+        queue.extend(self.tx_buffer);
+        let shared = Arc::new(SharedQueue {
+            state: Mutex::new(QueueState {
+                queue,
+                capacity: self.max_buffer_size,
+                sender_open: self.is_open,
+                receiver_open: self.is_open,
+                metrics: self.metrics,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        });
+        (
+            MemorySender {
+                shared: Arc::clone(&shared),
+            },
+            MemoryReceiver { shared },
+        )
+    }
 }
 
 impl Default for MemoryChannel {
@@ -176,6 +232,297 @@ impl Channel for MemoryChannel {
     }
 }
 
+/// Sending half of a split channel
+pub trait ChannelTx {
+    /// Send a message, waiting for buffer space
+    fn send(&mut self, message: EncodedMessage) -> Result<()>;
+
+    /// Send a message without waiting
+    ///
+    /// On failure the message is handed back inside the error.
+    fn try_send(&mut self, message: EncodedMessage) -> std::result::Result<(), TrySendError>;
+
+    /// Maximum number of messages in flight
+    fn capacity(&self) -> usize;
+
+    /// Number of messages sent but not yet received
+    fn len(&self) -> usize;
+
+    /// Check if no messages are in flight
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if the receiving half is still open
+    fn is_available(&self) -> bool;
+
+    /// Close the sending half; the receiver drains what is left
+    fn close(&mut self);
+}
+
+/// Receiving half of a split channel
+pub trait ChannelRx {
+    /// Receive a message, waiting up to `timeout`
+    fn recv(&mut self, timeout: Duration) -> Result<EncodedMessage>;
+
+    /// Receive a message without waiting
+    ///
+    /// Returns [`ChannelError::WouldBlock`] when no message is ready.
+    fn try_recv(&mut self) -> Result<EncodedMessage>;
+
+    /// Maximum number of messages in flight
+    fn capacity(&self) -> usize;
+
+    /// Number of messages ready to receive
+    fn len(&self) -> usize;
+
+    /// Check if no messages are ready
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if messages can still arrive
+    fn is_available(&self) -> bool;
+
+    /// Close the receiving half; further sends fail
+    fn close(&mut self);
+}
+
+/// Error from [`ChannelTx::try_send`], carrying the unsent message
+#[derive(Debug, Clone)]
+pub enum TrySendError {
+    /// The buffer is full
+    WouldBlock(EncodedMessage),
+    /// The receiving half is closed
+    Disconnected(EncodedMessage),
+}
+
+impl TrySendError {
+    /// Take back the unsent message
+    pub fn into_inner(self) -> EncodedMessage {
+        match self {
+            TrySendError::WouldBlock(message) | TrySendError::Disconnected(message) => message,
+        }
+    }
+
+    /// Check if the send failed only because the buffer is full
+    pub fn is_would_block(&self) -> bool {
+        matches!(self, TrySendError::WouldBlock(_))
+    }
+}
+
+impl From<TrySendError> for AlecError {
+    fn from(e: TrySendError) -> Self {
+        match e {
+            TrySendError::WouldBlock(_) => ChannelError::WouldBlock.into(),
+            TrySendError::Disconnected(_) => disconnected("Receiver is closed").into(),
+        }
+    }
+}
+
+fn disconnected(reason: &str) -> ChannelError {
+    ChannelError::Disconnected {
+        reason: reason.to_string(),
+    }
+}
+
+/// State shared by the halves of a split [`MemoryChannel`]
+#[derive(Debug)]
+struct SharedQueue {
+    state: Mutex<QueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    queue: VecDeque<EncodedMessage>,
+    capacity: usize,
+    sender_open: bool,
+    receiver_open: bool,
+    metrics: ChannelMetrics,
+}
+
+impl SharedQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        // No invariant spans a panic: recover the state
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sending half of a split [`MemoryChannel`]
+///
+/// `Send` and `Sync`; dropping it closes the sending side.
+#[derive(Debug)]
+pub struct MemorySender {
+    shared: Arc<SharedQueue>,
+}
+
+impl MemorySender {
+    /// Get channel metrics (shared with the receiver)
+    pub fn metrics(&self) -> ChannelMetrics {
+        self.shared.lock().metrics.clone()
+    }
+
+    fn push(state: &mut QueueState, message: EncodedMessage) {
+        state.metrics.bytes_sent += message.len() as u64;
+        state.metrics.messages_sent += 1;
+        state.queue.push_back(message);
+    }
+}
+
+impl ChannelTx for MemorySender {
+    fn send(&mut self, message: EncodedMessage) -> Result<()> {
+        let mut state = self.shared.lock();
+        loop {
+            if !state.sender_open {
+                return Err(disconnected("Channel is closed").into());
+            }
+            if !state.receiver_open {
+                return Err(disconnected("Receiver is closed").into());
+            }
+            if state.queue.len() < state.capacity {
+                break;
+            }
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        Self::push(&mut state, message);
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn try_send(&mut self, message: EncodedMessage) -> std::result::Result<(), TrySendError> {
+        let mut state = self.shared.lock();
+        if !state.sender_open || !state.receiver_open {
+            return Err(TrySendError::Disconnected(message));
+        }
+        if state.queue.len() >= state.capacity {
+            return Err(TrySendError::WouldBlock(message));
+        }
+        Self::push(&mut state, message);
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.shared.lock().capacity
+    }
+
+    fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    fn is_available(&self) -> bool {
+        let state = self.shared.lock();
+        state.sender_open && state.receiver_open
+    }
+
+    fn close(&mut self) {
+        self.shared.lock().sender_open = false;
+        self.shared.not_empty.notify_all();
+    }
+}
+
+impl Drop for MemorySender {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Receiving half of a split [`MemoryChannel`]
+///
+/// `Send` and `Sync`; dropping it closes the receiving side.
+#[derive(Debug)]
+pub struct MemoryReceiver {
+    shared: Arc<SharedQueue>,
+}
+
+impl MemoryReceiver {
+    /// Get channel metrics (shared with the sender)
+    pub fn metrics(&self) -> ChannelMetrics {
+        self.shared.lock().metrics.clone()
+    }
+
+    fn pop(&self, state: &mut QueueState) -> Option<EncodedMessage> {
+        let message = state.queue.pop_front()?;
+        state.metrics.bytes_received += message.len() as u64;
+        state.metrics.messages_received += 1;
+        self.shared.not_full.notify_one();
+        Some(message)
+    }
+}
+
+impl ChannelRx for MemoryReceiver {
+    fn recv(&mut self, timeout: Duration) -> Result<EncodedMessage> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            if !state.receiver_open {
+                return Err(disconnected("Channel is closed").into());
+            }
+            if let Some(message) = self.pop(&mut state) {
+                return Ok(message);
+            }
+            if !state.sender_open {
+                return Err(disconnected("Sender is closed").into());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(ChannelError::Timeout {
+                    timeout_ms: timeout.as_millis() as u64,
+                }
+                .into());
+            }
+            state = self
+                .shared
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<EncodedMessage> {
+        let mut state = self.shared.lock();
+        if !state.receiver_open {
+            return Err(disconnected("Channel is closed").into());
+        }
+        match self.pop(&mut state) {
+            Some(message) => Ok(message),
+            None if !state.sender_open => Err(disconnected("Sender is closed").into()),
+            None => Err(ChannelError::WouldBlock.into()),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.shared.lock().capacity
+    }
+
+    fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    fn is_available(&self) -> bool {
+        let state = self.shared.lock();
+        state.receiver_open && (state.sender_open || !state.queue.is_empty())
+    }
+
+    fn close(&mut self) {
+        self.shared.lock().receiver_open = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+impl Drop for MemoryReceiver {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
 /// A channel pair for bidirectional communication
 #[derive(Debug)]
 pub struct ChannelPair {
@@ -234,11 +581,16 @@ impl LossyChannel {
 
     /// Simple PRNG for deterministic testing
     fn next_random(&mut self) -> f32 {
-        self.rng_state = self.rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-        ((self.rng_state >> 16) & 0x7fff) as f32 / 32767.0
+        next_random(&mut self.rng_state)
     }
 }
 
+/// Deterministic LCG in `[0, 1]` shared by the simulated channels
+fn next_random(state: &mut u64) -> f32 {
+    *state = state.wrapping_mul(1103515245).wrapping_add(12345);
+    ((*state >> 16) & 0x7fff) as f32 / 32767.0
+}
+
 impl Channel for LossyChannel {
     fn send(&mut self, message: EncodedMessage) -> Result<()> {
         // Simulate packet loss
@@ -269,15 +621,681 @@ impl Channel for LossyChannel {
     }
 }
 
+/// Direction of a [`SimulatedLink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkDirection {
+    /// Device to network
+    Uplink,
+    /// Network to device
+    Downlink,
+}
+
+impl LinkDirection {
+    fn index(self) -> usize {
+        match self {
+            LinkDirection::Uplink => 0,
+            LinkDirection::Downlink => 1,
+        }
+    }
+}
+
+/// When a [`SimulatedLink`] can deliver what is sent on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkModel {
+    /// Everything is delivered as soon as it is sent
+    Ideal,
+    /// Up for `on_ms`, then asleep for `off_ms`, repeating from time 0
+    ///
+    /// Messages sent while the link sleeps (NB-IoT PSM) are held until
+    /// it wakes up. A zero `on_ms` never wakes: everything is dropped.
+    DutyCycled {
+        /// Time awake per cycle, in ms
+        on_ms: u64,
+        /// Time asleep per cycle, in ms
+        off_ms: u64,
+    },
+    /// Uplinks go through at once, downlinks only in the receive windows
+    /// that follow an uplink (LoRaWAN Class A)
+    ///
+    /// Each uplink opens `count` windows of `window_ms`, back to back, the
+    /// first one `window_ms` after the uplink. A window carries one
+    /// downlink, delivered when the window opens (or when sent, if it is
+    /// already open). Downlinks finding no window left wait for the next
+    /// uplink.
+    ReceiveWindowsAfterSend {
+        /// Length of each window, and delay before the first one, in ms
+        window_ms: u64,
+        /// Windows opened per uplink
+        count: u32,
+    },
+    /// Each direction transmits one message at a time at its own bit rate
+    ///
+    /// A message is delivered once it is fully transmitted, after the
+    /// messages sent before it in the same direction. A zero rate drops
+    /// everything sent that way.
+    Asymmetric {
+        /// Uplink bit rate
+        uplink_bps: u32,
+        /// Downlink bit rate
+        downlink_bps: u32,
+    },
+}
+
+/// Delivery statistics of one direction of a [`SimulatedLink`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Messages sent
+    pub sent: u64,
+    /// Messages received
+    pub delivered: u64,
+    /// Messages lost, refused by the model or over capacity
+    pub dropped: u64,
+    /// Delivered messages that arrived later than they were sent
+    pub delayed: u64,
+    /// Longest time between sending and delivery, in ms
+    pub max_delay_ms: u64,
+}
+
+/// One direction of a [`SimulatedLink`]
+#[derive(Debug)]
+struct LinkQueue<T> {
+    /// Scheduled messages, in delivery order: (sent at, deliver at, item)
+    in_flight: VecDeque<(u64, u64, T)>,
+    /// Messages waiting for the model to schedule them: (sent at, item)
+    waiting: VecDeque<(u64, T)>,
+    /// End of the transmission in progress (`Asymmetric`)
+    busy_until: u64,
+    stats: LinkStats,
+}
+
+impl<T> LinkQueue<T> {
+    fn new() -> Self {
+        Self {
+            in_flight: VecDeque::new(),
+            waiting: VecDeque::new(),
+            busy_until: 0,
+            stats: LinkStats::default(),
+        }
+    }
+
+    fn held(&self) -> usize {
+        self.in_flight.len() + self.waiting.len()
+    }
+}
+
+/// Two-way link shaped by a [`LinkModel`] on a logical clock
+///
+/// Nothing happens on its own: the test moves the clock with
+/// [`advance_to`](Self::advance_to), and [`receive`](Self::receive) only
+/// returns messages whose delivery time has come. Optional random loss
+/// uses the same deterministic generator as [`LossyChannel`]. Each
+/// direction holds at most [`capacity`](Self::with_capacity) undelivered
+/// messages; more are dropped.
+#[derive(Debug)]
+pub struct SimulatedLink<T: QueueItem = EncodedMessage> {
+    model: LinkModel,
+    now_ms: u64,
+    loss_rate: f32,
+    rng_state: u64,
+    capacity: usize,
+    /// Uplink, downlink
+    directions: [LinkQueue<T>; 2],
+    /// Time of the last uplink, opening receive windows
+    last_uplink_ms: Option<u64>,
+    /// First receive window of the last uplink not used yet
+    next_window: u32,
+}
+
+impl<T: QueueItem> SimulatedLink<T> {
+    /// Create a lossless link at time 0
+    pub fn new(model: LinkModel) -> Self {
+        Self {
+            model,
+            now_ms: 0,
+            loss_rate: 0.0,
+            rng_state: 12345,
+            capacity: 1000,
+            directions: [LinkQueue::new(), LinkQueue::new()],
+            last_uplink_ms: None,
+            next_window: 0,
+        }
+    }
+
+    /// Also lose messages at random, at the given rate (0.0-1.0)
+    pub fn with_loss_rate(mut self, loss_rate: f32) -> Self {
+        self.loss_rate = loss_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Maximum undelivered messages held per direction
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// The link's model
+    pub fn model(&self) -> &LinkModel {
+        &self.model
+    }
+
+    /// Current time of the logical clock, in ms
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    /// Move the clock forward to `now_ms` (never backwards)
+    pub fn advance_to(&mut self, now_ms: u64) {
+        self.now_ms = self.now_ms.max(now_ms);
+    }
+
+    /// Move the clock forward by `ms`
+    pub fn advance(&mut self, ms: u64) {
+        self.now_ms = self.now_ms.saturating_add(ms);
+    }
+
+    /// Whether the link is awake at `time_ms`
+    ///
+    /// Always true except for a sleeping [`LinkModel::DutyCycled`] link.
+    pub fn is_awake_at(&self, time_ms: u64) -> bool {
+        match self.model {
+            LinkModel::DutyCycled { on_ms, off_ms } => {
+                let period = on_ms.saturating_add(off_ms);
+                off_ms == 0 || (on_ms != 0 && time_ms % period < on_ms)
+            }
+            _ => true,
+        }
+    }
+
+    /// Send `item` at the current time
+    ///
+    /// Returns `false` if it was dropped: lost, refused by the model or
+    /// over capacity. Accepted messages are delivered in the order sent.
+    pub fn send(&mut self, direction: LinkDirection, item: T) -> bool {
+        let now = self.now_ms;
+        let lost = self.loss_rate > 0.0 && next_random(&mut self.rng_state) < self.loss_rate;
+        let queue = &mut self.directions[direction.index()];
+        queue.stats.sent += 1;
+        if lost || queue.held() >= self.capacity {
+            queue.stats.dropped += 1;
+            return false;
+        }
+
+        let deliver_at = match (self.model, direction) {
+            (LinkModel::Ideal, _) => Some(now),
+            (LinkModel::DutyCycled { on_ms, off_ms }, _) => {
+                if self.is_awake_at(now) {
+                    Some(now)
+                } else if on_ms == 0 {
+                    None
+                } else {
+                    // Start of the next cycle
+                    let period = on_ms + off_ms;
+                    Some(now - now % period + period)
+                }
+            }
+            (LinkModel::ReceiveWindowsAfterSend { .. }, LinkDirection::Uplink) => {
+                self.last_uplink_ms = Some(now);
+                self.next_window = 0;
+                self.schedule_waiting_downlinks();
+                Some(now)
+            }
+            (LinkModel::ReceiveWindowsAfterSend { .. }, LinkDirection::Downlink) => {
+                let queue = &mut self.directions[LinkDirection::Downlink.index()];
+                queue.waiting.push_back((now, item));
+                self.schedule_waiting_downlinks();
+                return true;
+            }
+            (
+                LinkModel::Asymmetric {
+                    uplink_bps,
+                    downlink_bps,
+                },
+                _,
+            ) => {
+                let bps = match direction {
+                    LinkDirection::Uplink => uplink_bps,
+                    LinkDirection::Downlink => downlink_bps,
+                } as u64;
+                (bps != 0).then(|| {
+                    let bits = item.queued_size() as u64 * 8;
+                    let transmit_ms = (bits * 1000 + bps - 1) / bps;
+                    let queue = &mut self.directions[direction.index()];
+                    queue.busy_until = queue.busy_until.max(now) + transmit_ms;
+                    queue.busy_until
+                })
+            }
+        };
+
+        let queue = &mut self.directions[direction.index()];
+        match deliver_at {
+            Some(deliver_at) => {
+                queue.in_flight.push_back((now, deliver_at, item));
+                true
+            }
+            None => {
+                queue.stats.dropped += 1;
+                false
+            }
+        }
+    }
+
+    /// Put waiting downlinks in the receive windows still open
+    fn schedule_waiting_downlinks(&mut self) {
+        let LinkModel::ReceiveWindowsAfterSend { window_ms, count } = self.model else {
+            return;
+        };
+        let Some(uplink_ms) = self.last_uplink_ms else {
+            return;
+        };
+        let queue = &mut self.directions[LinkDirection::Downlink.index()];
+        while !queue.waiting.is_empty() && self.next_window < count {
+            let opens = uplink_ms + window_ms * (self.next_window as u64 + 1);
+            self.next_window += 1;
+            if opens + window_ms <= self.now_ms {
+                // Window already closed
+                continue;
+            }
+            if let Some((sent_at, item)) = queue.waiting.pop_front() {
+                queue
+                    .in_flight
+                    .push_back((sent_at, opens.max(self.now_ms), item));
+            }
+        }
+    }
+
+    /// Next message delivered by now in `direction`, if any
+    pub fn receive(&mut self, direction: LinkDirection) -> Option<T> {
+        let queue = &mut self.directions[direction.index()];
+        match queue.in_flight.front() {
+            Some((_, deliver_at, _)) if *deliver_at <= self.now_ms => {}
+            _ => return None,
+        }
+        let (sent_at, deliver_at, item) = queue.in_flight.pop_front()?;
+        let delay = deliver_at - sent_at;
+        queue.stats.delivered += 1;
+        if delay > 0 {
+            queue.stats.delayed += 1;
+        }
+        queue.stats.max_delay_ms = queue.stats.max_delay_ms.max(delay);
+        Some(item)
+    }
+
+    /// Time the next scheduled message in `direction` is delivered
+    ///
+    /// `None` when nothing is scheduled, including downlinks still waiting
+    /// for an uplink to open receive windows.
+    pub fn next_delivery_ms(&self, direction: LinkDirection) -> Option<u64> {
+        self.directions[direction.index()]
+            .in_flight
+            .front()
+            .map(|(_, deliver_at, _)| *deliver_at)
+    }
+
+    /// Messages sent in `direction` and not received yet
+    pub fn pending(&self, direction: LinkDirection) -> usize {
+        self.directions[direction.index()].held()
+    }
+
+    /// Delivery statistics of `direction`
+    pub fn stats(&self, direction: LinkDirection) -> &LinkStats {
+        &self.directions[direction.index()].stats
+    }
+}
+
+/// Something a [`PriorityQueue`] can hold, with its size in bytes
+pub trait QueueItem {
+    /// Bytes the item counts against the queue budget
+    fn queued_size(&self) -> usize;
+
+    /// Sequence number the queue's latency log keys the item by, if any
+    fn sequence(&self) -> Option<u16> {
+        None
+    }
+}
+
+impl QueueItem for EncodedMessage {
+    fn queued_size(&self) -> usize {
+        self.len()
+    }
+
+    fn sequence(&self) -> Option<u16> {
+        Some(self.header.sequence)
+    }
+}
+
+/// Serialized frames (e.g. a gateway `Frame::to_bytes`)
+impl QueueItem for Vec<u8> {
+    fn queued_size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Configuration for a [`PriorityQueue`]
+#[derive(Debug, Clone)]
+pub struct PriorityQueueConfig {
+    /// Total bytes queued before items are evicted, lowest priority first
+    pub budget_bytes: usize,
+    /// Absolute cap on total bytes, only reached by P1 items
+    ///
+    /// P1 items are never evicted to honor the budget; they may push the
+    /// queue past it up to this cap (taken as at least `budget_bytes`),
+    /// beyond which the oldest P1 items go.
+    pub critical_cap_bytes: usize,
+}
+
+impl Default for PriorityQueueConfig {
+    fn default() -> Self {
+        Self {
+            budget_bytes: 64 * 1024,
+            critical_cap_bytes: 128 * 1024,
+        }
+    }
+}
+
+/// Priorities in queue order, indexed by their `u8` value
+const PRIORITIES: [Priority; 5] = [
+    Priority::P1Critical,
+    Priority::P2Important,
+    Priority::P3Normal,
+    Priority::P4Deferred,
+    Priority::P5Disposable,
+];
+
+/// Number of priority levels
+const PRIORITY_LEVELS: usize = PRIORITIES.len();
+
+/// Bounded transmit queue with one FIFO per [`Priority`]
+///
+/// [`enqueue`](Self::enqueue) evicts the oldest items of the lowest
+/// non-empty priority (P5, then P4, ... then P2) while the queue is over
+/// its byte budget, counting each drop per priority.
+/// [`dequeue_highest`](Self::dequeue_highest) returns the oldest item of
+/// the highest priority, so order is preserved within a priority.
+///
+/// With a [`CircuitBreaker`] attached, dequeuing pauses while the circuit
+/// is open; report transmission outcomes through
+/// [`circuit_breaker_mut`](Self::circuit_breaker_mut).
+///
+/// With latency tracking enabled, [`enqueue_at`](Self::enqueue_at) and
+/// [`dequeue_highest_at`](Self::dequeue_highest_at) record how long each
+/// item waited (see [`crate::latency`]).
+#[derive(Debug)]
+pub struct PriorityQueue<T: QueueItem = EncodedMessage> {
+    /// One FIFO per priority, P1 first: (item, size)
+    queues: [VecDeque<(T, usize)>; PRIORITY_LEVELS],
+    /// Bytes queued per priority
+    bytes: [usize; PRIORITY_LEVELS],
+    /// Items evicted per priority
+    dropped: [u64; PRIORITY_LEVELS],
+    /// Circuit breaker gating dequeue (optional)
+    breaker: Option<CircuitBreaker>,
+    /// Configuration
+    config: PriorityQueueConfig,
+    /// Queue times of recent items, if latency tracking is enabled
+    latency: Option<LatencyLog>,
+}
+
+impl<T: QueueItem> PriorityQueue<T> {
+    /// Create a queue with the given byte budget and no extra room for P1
+    pub fn new(budget_bytes: usize) -> Self {
+        Self::with_config(PriorityQueueConfig {
+            budget_bytes,
+            critical_cap_bytes: budget_bytes,
+        })
+    }
+
+    /// Create a queue with custom configuration
+    pub fn with_config(config: PriorityQueueConfig) -> Self {
+        Self {
+            queues: Default::default(),
+            bytes: [0; PRIORITY_LEVELS],
+            dropped: [0; PRIORITY_LEVELS],
+            breaker: None,
+            config,
+            latency: None,
+        }
+    }
+
+    /// Pause dequeuing whenever `breaker` is open
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Record the queue times of the last `capacity` items queued with
+    /// [`enqueue_at`](Self::enqueue_at)
+    ///
+    /// Only items with a [`QueueItem::sequence`] are tracked.
+    pub fn with_latency_tracking(mut self, capacity: usize) -> Self {
+        self.latency = Some(LatencyLog::new(capacity));
+        self
+    }
+
+    /// Queue times of recent items, if latency tracking is enabled
+    pub fn latency_log(&self) -> Option<&LatencyLog> {
+        self.latency.as_ref()
+    }
+
+    /// Queue latencies per priority, if latency tracking is enabled
+    pub fn latency_report(&self) -> Option<&LatencyReport> {
+        self.latency.as_ref().map(LatencyLog::report)
+    }
+
+    /// Attached circuit breaker, if any
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    /// Attached circuit breaker, to record transmission outcomes
+    pub fn circuit_breaker_mut(&mut self) -> Option<&mut CircuitBreaker> {
+        self.breaker.as_mut()
+    }
+
+    /// Queue an item, evicting lower-priority items if over budget
+    ///
+    /// Returns `false` if the item itself was evicted (it was among the
+    /// oldest of the lowest priority, or too large to fit).
+    pub fn enqueue(&mut self, item: T, priority: Priority) -> bool {
+        let level = priority as usize;
+        let size = item.queued_size();
+        let ahead = self.queues[level].len();
+        self.queues[level].push_back((item, size));
+        self.bytes[level] += size;
+        self.enforce_budget(level) <= ahead
+    }
+
+    /// [`enqueue`](Self::enqueue), recording `now_ms` as the item's
+    /// enqueue time if latency tracking is enabled
+    pub fn enqueue_at(&mut self, item: T, priority: Priority, now_ms: u64) -> bool {
+        if let (Some(log), Some(sequence)) = (&mut self.latency, item.sequence()) {
+            log.record_enqueued(sequence, priority, now_ms);
+        }
+        self.enqueue(item, priority)
+    }
+
+    /// Put back an item that failed to transmit, ahead of its priority
+    ///
+    /// Evicts like [`enqueue`](Self::enqueue); as the oldest item of its
+    /// priority, the requeued item is the first of it to go.
+    pub fn requeue(&mut self, item: T, priority: Priority) -> bool {
+        let level = priority as usize;
+        let size = item.queued_size();
+        self.queues[level].push_front((item, size));
+        self.bytes[level] += size;
+        self.enforce_budget(level) == 0
+    }
+
+    /// Remove the oldest item of the highest non-empty priority
+    ///
+    /// Returns `None` when the queue is empty or the circuit breaker is
+    /// open.
+    pub fn dequeue_highest(&mut self) -> Option<(Priority, T)> {
+        if self.is_empty() {
+            return None;
+        }
+        if let Some(breaker) = &mut self.breaker {
+            if !breaker.should_allow() {
+                return None;
+            }
+        }
+        let level = self.queues.iter().position(|q| !q.is_empty())?;
+        let (item, size) = self.queues[level].pop_front()?;
+        self.bytes[level] -= size;
+        Some((PRIORITIES[level], item))
+    }
+
+    /// [`dequeue_highest`](Self::dequeue_highest), recording `now_ms` as
+    /// the item's dequeue time if latency tracking is enabled
+    pub fn dequeue_highest_at(&mut self, now_ms: u64) -> Option<(Priority, T)> {
+        let (priority, item) = self.dequeue_highest()?;
+        if let (Some(log), Some(sequence)) = (&mut self.latency, item.sequence()) {
+            log.record_dequeued(sequence, priority, now_ms);
+        }
+        Some((priority, item))
+    }
+
+    /// Priority of the item [`dequeue_highest`](Self::dequeue_highest)
+    /// would return
+    pub fn peek_priority(&self) -> Option<Priority> {
+        let level = self.queues.iter().position(|q| !q.is_empty())?;
+        Some(PRIORITIES[level])
+    }
+
+    /// Whether dequeuing is paused by an open circuit breaker
+    pub fn is_paused(&self) -> bool {
+        self.breaker
+            .as_ref()
+            .is_some_and(|b| b.state() == CircuitState::Open)
+    }
+
+    /// Total queued items
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// Check if nothing is queued
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Queued items of one priority
+    pub fn len_at(&self, priority: Priority) -> usize {
+        self.queues[priority as usize].len()
+    }
+
+    /// Total queued bytes
+    pub fn bytes(&self) -> usize {
+        self.bytes.iter().sum()
+    }
+
+    /// Queued bytes of one priority
+    pub fn bytes_at(&self, priority: Priority) -> usize {
+        self.bytes[priority as usize]
+    }
+
+    /// Queued bytes as a share of the budget (above 1.0 with P1 overflow)
+    pub fn occupancy(&self) -> f64 {
+        if self.config.budget_bytes == 0 {
+            return if self.is_empty() { 0.0 } else { f64::INFINITY };
+        }
+        self.bytes() as f64 / self.config.budget_bytes as f64
+    }
+
+    /// Items of one priority evicted since creation
+    pub fn dropped(&self, priority: Priority) -> u64 {
+        self.dropped[priority as usize]
+    }
+
+    /// Items evicted since creation, all priorities
+    pub fn total_dropped(&self) -> u64 {
+        self.dropped.iter().sum()
+    }
+
+    /// Queue configuration
+    pub fn config(&self) -> &PriorityQueueConfig {
+        &self.config
+    }
+
+    /// Evict down to the budget (P1 down to the cap), returning how many
+    /// items of `level` went
+    fn enforce_budget(&mut self, level: usize) -> usize {
+        let mut evicted = 0;
+        while self.bytes() > self.config.budget_bytes {
+            let Some(victim) = (1..PRIORITY_LEVELS)
+                .rev()
+                .find(|&l| !self.queues[l].is_empty())
+            else {
+                break;
+            };
+            self.evict(victim);
+            if victim == level {
+                evicted += 1;
+            }
+        }
+
+        let cap = self.config.critical_cap_bytes.max(self.config.budget_bytes);
+        while self.bytes() > cap && !self.queues[0].is_empty() {
+            self.evict(0);
+            if level == 0 {
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    /// Drop the oldest item of `level`, counting it
+    fn evict(&mut self, level: usize) {
+        if let Some((_, size)) = self.queues[level].pop_front() {
+            self.bytes[level] -= size;
+            self.dropped[level] += 1;
+        }
+    }
+}
+
+impl<T: QueueItem> HealthCheckable for PriorityQueue<T> {
+    fn health_check(&self) -> HealthCheck {
+        self.health_check_with(&HealthConfig::default())
+    }
+
+    /// Byte occupancy is judged against the buffer-fill thresholds and
+    /// item count against the queue-depth ones; an open circuit breaker
+    /// degrades the queue.
+    fn health_check_with(&self, config: &HealthConfig) -> HealthCheck {
+        let fill = self.occupancy();
+        let depth = self.len();
+        let details = format!(
+            "Queued: {} items, {}/{} bytes, Dropped: {}",
+            depth,
+            self.bytes(),
+            self.config.budget_bytes,
+            self.total_dropped()
+        );
+
+        if fill >= config.unhealthy_buffer_fill || depth >= config.unhealthy_queue_depth {
+            HealthCheck::unhealthy("PriorityQueue", format!("Queue full ({})", details))
+        } else if fill >= config.degraded_buffer_fill || depth >= config.degraded_queue_depth {
+            HealthCheck::degraded("PriorityQueue", format!("Queue filling ({})", details))
+        } else if self.is_paused() {
+            HealthCheck::degraded(
+                "PriorityQueue",
+                format!("Circuit open, dequeue paused ({})", details),
+            )
+        } else {
+            HealthCheck::healthy("PriorityQueue").with_message(details)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::protocol::{MessageHeader, MessageType, Priority};
+    use crate::health::HealthStatus;
+    use crate::protocol::{HeaderLayout, MessageHeader, MessageType, Priority};
 
     fn make_test_message(seq: u16) -> EncodedMessage {
         EncodedMessage::new(
             MessageHeader {
-                version: 1,
+                layout: HeaderLayout::default(),
                 message_type: MessageType::Data,
                 priority: Priority::P3Normal,
                 sequence: seq,
@@ -373,6 +1391,78 @@ mod tests {
         assert_eq!(metrics.messages_sent, 1);
     }
 
+    #[test]
+    fn test_split_delivers_pending_then_new() {
+        let mut channel = MemoryChannel::new();
+        channel.push_incoming(make_test_message(1));
+        channel.send(make_test_message(2)).unwrap();
+
+        let (mut tx, mut rx) = channel.split();
+        tx.send(make_test_message(3)).unwrap();
+        assert_eq!(rx.len(), 3);
+
+        for seq in 1..=3 {
+            assert_eq!(rx.try_recv().unwrap().header.sequence, seq);
+        }
+        assert!(matches!(
+            rx.try_recv(),
+            Err(AlecError::Channel(ChannelError::WouldBlock))
+        ));
+    }
+
+    #[test]
+    fn test_split_try_send_would_block() {
+        let (mut tx, mut rx) = MemoryChannel::with_buffer_size(2).split();
+        assert_eq!(tx.capacity(), 2);
+
+        tx.try_send(make_test_message(1)).unwrap();
+        tx.try_send(make_test_message(2)).unwrap();
+        let err = tx.try_send(make_test_message(3)).unwrap_err();
+        assert!(err.is_would_block());
+        assert_eq!(err.into_inner().header.sequence, 3);
+        assert_eq!(tx.len(), 2);
+
+        // Draining one frees one slot
+        rx.try_recv().unwrap();
+        tx.try_send(make_test_message(3)).unwrap();
+        assert_eq!(rx.len(), 2);
+    }
+
+    #[test]
+    fn test_split_close_semantics() {
+        let (mut tx, mut rx) = MemoryChannel::new().split();
+        tx.send(make_test_message(1)).unwrap();
+        drop(tx);
+
+        // Remaining messages are still delivered
+        assert!(rx.is_available());
+        assert_eq!(rx.recv(Duration::from_secs(1)).unwrap().header.sequence, 1);
+        assert!(!rx.is_available());
+        assert!(matches!(
+            rx.try_recv(),
+            Err(AlecError::Channel(ChannelError::Disconnected { .. }))
+        ));
+
+        let (mut tx, rx) = MemoryChannel::new().split();
+        drop(rx);
+        assert!(!tx.is_available());
+        assert!(tx.send(make_test_message(1)).is_err());
+        assert!(matches!(
+            tx.try_send(make_test_message(2)),
+            Err(TrySendError::Disconnected(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_recv_timeout() {
+        let (_tx, mut rx) = MemoryChannel::new().split();
+        let result = rx.recv(Duration::from_millis(10));
+        assert!(matches!(
+            result,
+            Err(AlecError::Channel(ChannelError::Timeout { timeout_ms: 10 }))
+        ));
+    }
+
     #[test]
     fn test_lossy_channel() {
         let mut channel = LossyChannel::new(0.5); // 50% loss rate
@@ -397,4 +1487,143 @@ mod tests {
         // But not all
         assert!(received > 0);
     }
+
+    fn frame(tag: u8, len: usize) -> Vec<u8> {
+        vec![tag; len]
+    }
+
+    #[test]
+    fn test_priority_queue_evicts_lowest_first() {
+        let mut queue = PriorityQueue::new(100);
+        let mixed = [
+            (1, Priority::P5Disposable),
+            (2, Priority::P1Critical),
+            (3, Priority::P4Deferred),
+            (4, Priority::P3Normal),
+            (5, Priority::P5Disposable),
+            (6, Priority::P2Important),
+            (7, Priority::P4Deferred),
+            (8, Priority::P1Critical),
+            (9, Priority::P3Normal),
+            (10, Priority::P5Disposable),
+            (11, Priority::P4Deferred),
+            (12, Priority::P2Important),
+        ];
+        // 12 frames of 10 bytes into a 100-byte budget
+        for (tag, priority) in mixed {
+            queue.enqueue(frame(tag, 10), priority);
+        }
+
+        assert_eq!(queue.bytes(), 100);
+        assert_eq!(queue.dropped(Priority::P5Disposable), 2);
+        assert_eq!(queue.dropped(Priority::P4Deferred), 0);
+        assert_eq!(queue.total_dropped(), 2);
+
+        // Two more push out the last P5 and the oldest P4
+        queue.enqueue(frame(13, 10), Priority::P3Normal);
+        queue.enqueue(frame(14, 10), Priority::P1Critical);
+        assert_eq!(queue.dropped(Priority::P5Disposable), 3);
+        assert_eq!(queue.dropped(Priority::P4Deferred), 1);
+        for priority in [
+            Priority::P1Critical,
+            Priority::P2Important,
+            Priority::P3Normal,
+        ] {
+            assert_eq!(queue.dropped(priority), 0);
+        }
+        assert_eq!(queue.len() as u64 + queue.total_dropped(), 14);
+
+        // Highest priority first, FIFO within a priority
+        let order: Vec<(Priority, u8)> =
+            core::iter::from_fn(|| queue.dequeue_highest().map(|(p, f)| (p, f[0]))).collect();
+        assert_eq!(
+            order,
+            vec![
+                (Priority::P1Critical, 2),
+                (Priority::P1Critical, 8),
+                (Priority::P1Critical, 14),
+                (Priority::P2Important, 6),
+                (Priority::P2Important, 12),
+                (Priority::P3Normal, 4),
+                (Priority::P3Normal, 9),
+                (Priority::P3Normal, 13),
+                (Priority::P4Deferred, 7),
+                (Priority::P4Deferred, 11),
+            ]
+        );
+        assert_eq!(queue.bytes(), 0);
+    }
+
+    #[test]
+    fn test_priority_queue_critical_cap() {
+        let mut queue = PriorityQueue::with_config(PriorityQueueConfig {
+            budget_bytes: 30,
+            critical_cap_bytes: 50,
+        });
+        assert!(queue.enqueue(frame(1, 10), Priority::P3Normal));
+        for tag in 2..=5 {
+            assert!(queue.enqueue(frame(tag, 10), Priority::P1Critical));
+        }
+        // P3 went to honor the budget; P1 overflows up to the cap
+        assert_eq!(queue.dropped(Priority::P3Normal), 1);
+        assert_eq!(queue.bytes(), 40);
+        assert!(queue.occupancy() > 1.0);
+
+        // A lower priority cannot displace P1 and is dropped itself
+        assert!(!queue.enqueue(frame(6, 10), Priority::P2Important));
+        assert_eq!(queue.dropped(Priority::P2Important), 1);
+
+        // Past the cap, the oldest P1 goes
+        assert!(queue.enqueue(frame(7, 10), Priority::P1Critical));
+        assert!(queue.enqueue(frame(8, 10), Priority::P1Critical));
+        assert_eq!(queue.dropped(Priority::P1Critical), 1);
+        assert_eq!(queue.bytes(), 50);
+        assert_eq!(queue.dequeue_highest().unwrap().1[0], 3);
+    }
+
+    #[test]
+    fn test_priority_queue_requeue_keeps_order() {
+        let mut queue = PriorityQueue::new(100);
+        queue.enqueue(frame(1, 10), Priority::P3Normal);
+        queue.enqueue(frame(2, 10), Priority::P3Normal);
+
+        let (priority, first) = queue.dequeue_highest().unwrap();
+        assert!(queue.requeue(first, priority));
+        assert_eq!(queue.dequeue_highest().unwrap().1[0], 1);
+        assert_eq!(queue.dequeue_highest().unwrap().1[0], 2);
+    }
+
+    #[test]
+    fn test_priority_queue_pauses_on_open_circuit() {
+        let mut queue = PriorityQueue::new(100).with_circuit_breaker(CircuitBreaker::new());
+        queue.enqueue(make_test_message(1), Priority::P1Critical);
+        assert_eq!(queue.bytes(), make_test_message(1).len());
+
+        queue.circuit_breaker_mut().unwrap().force_open();
+        assert!(queue.is_paused());
+        assert!(queue.dequeue_highest().is_none());
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.health_check().status, HealthStatus::Degraded);
+
+        queue.circuit_breaker_mut().unwrap().force_closed();
+        let (priority, message) = queue.dequeue_highest().unwrap();
+        assert_eq!(priority, Priority::P1Critical);
+        assert_eq!(message.header.sequence, 1);
+    }
+
+    #[test]
+    fn test_priority_queue_health() {
+        let mut queue = PriorityQueue::new(100);
+        assert_eq!(queue.health_check().status, HealthStatus::Healthy);
+
+        for tag in 0..9 {
+            queue.enqueue(frame(tag, 10), Priority::P3Normal);
+        }
+        assert_eq!(queue.health_check().status, HealthStatus::Degraded);
+
+        queue.enqueue(frame(9, 10), Priority::P3Normal);
+        let check = queue.health_check();
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+        assert!(check.message.contains("100/100 bytes"));
+    }
 }
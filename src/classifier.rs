@@ -8,11 +8,25 @@
 //!
 //! This module determines the priority level (P1-P5) of each data point
 //! based on its deviation from predictions and configured thresholds.
+//!
+//! Classification runs in two stages:
+//! - an [`AnomalyScorer`] tells what is unusual about a value, as
+//!   [`AnomalyLabel`]s
+//! - a [`PriorityMapper`] turns the labels into a [`Priority`], from a
+//!   table of [`PriorityRule`]s
+//!
+//! [`Classifier`] composes the two; its default mapping is the historical
+//! one. The [`Classification`] carries the labels as well as the priority,
+//! so consumers can see why a value got its priority.
 
 use crate::context::Context;
 use crate::protocol::{Priority, RawData};
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 #[cfg(feature = "std")]
 use std::collections::HashMap;
 
@@ -27,6 +41,10 @@ pub struct Classification {
     pub delta: f64,
     /// Confidence in the prediction (0.0-1.0)
     pub confidence: f32,
+    /// Everything the scorer found, the label behind `reason` included
+    ///
+    /// Empty when the priority did not come from a [`PriorityMapper`].
+    pub labels: Vec<AnomalyLabel>,
 }
 
 impl Classification {
@@ -42,17 +60,43 @@ impl Classification {
             reason,
             delta,
             confidence,
+            labels: Vec::new(),
         }
     }
 
     /// Create classification for when no prediction is available
     pub fn no_prediction() -> Self {
-        Self {
-            priority: Priority::P3Normal,
-            reason: ClassificationReason::NoPrediction,
-            delta: 0.0,
-            confidence: 0.0,
-        }
+        Self::new(
+            Priority::P3Normal,
+            ClassificationReason::NoPrediction,
+            0.0,
+            0.0,
+        )
+    }
+
+    /// Priority set by the application instead of the classifier
+    ///
+    /// Messages encoded with it carry the operator-override header flag,
+    /// so the receiver can tell them from algorithmic priorities.
+    ///
+    /// ```
+    /// use alec::{Classification, Priority};
+    ///
+    /// let classification = Classification::manual(Priority::P1Critical, "manual sampling");
+    /// assert!(classification.is_override());
+    /// ```
+    pub fn manual(priority: Priority, note: impl Into<String>) -> Self {
+        Self::new(
+            priority,
+            ClassificationReason::OperatorOverride { note: note.into() },
+            0.0,
+            1.0,
+        )
+    }
+
+    /// Check if the priority was set by the application
+    pub fn is_override(&self) -> bool {
+        matches!(self.reason, ClassificationReason::OperatorOverride { .. })
     }
 }
 
@@ -73,6 +117,16 @@ pub enum ClassificationReason {
     NoPrediction,
     /// Explicitly requested by user
     UserRequested,
+    /// Priority overridden by the application
+    OperatorOverride { note: String },
+    /// Value trending fast, or towards a critical threshold
+    TrendDetected {
+        /// Fitted slope, in value units per minute
+        slope: f64,
+        /// Seconds until a critical threshold is crossed at this slope
+        /// (`None`: moving away from the thresholds, or none set)
+        projected_crossing_secs: Option<f64>,
+    },
 }
 
 /// Types of detected anomalies
@@ -88,6 +142,183 @@ pub enum AnomalyType {
     Drift,
     /// Value outside expected range
     OutOfRange,
+    /// Value not changing at all
+    Stuck,
+}
+
+/// Something an [`AnomalyScorer`] found about a value
+///
+/// A value always gets one of `InRange`, `Negligible`, `Scheduled` or
+/// `PredictionDeviation`, which describe its deviation from the
+/// prediction, plus any of the others.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnomalyLabel {
+    /// Deviation from the prediction between the minimum delta and the
+    /// anomaly threshold
+    InRange,
+    /// Deviation from the prediction below the minimum delta
+    Negligible,
+    /// In range, on a source with a transmission schedule
+    /// ([`ClassifierConfig::scheduled_interval`])
+    Scheduled,
+    /// Outside the source's critical thresholds
+    ThresholdExceeded {
+        /// Threshold crossed
+        threshold: f64,
+        /// Value
+        actual: f64,
+    },
+    /// At least [`ClassifierConfig::outlier_sigma`] standard deviations
+    /// from the mean of the source's history
+    Outlier {
+        /// Distance from the mean, in standard deviations
+        sigma: f64,
+    },
+    /// The source's [`TrendRule`] triggered
+    RateOfChange {
+        /// Fitted slope, in value units per minute
+        slope: f64,
+        /// Seconds until a critical threshold is crossed at this slope
+        projected_crossing_secs: Option<f64>,
+        /// Whether the crossing is within the rule's horizon (otherwise
+        /// the slope is steeper than the rule allows)
+        crossing_imminent: bool,
+    },
+    /// Deviation from the prediction above the anomaly threshold
+    PredictionDeviation {
+        /// Relative deviation
+        relative: f64,
+        /// Whether it is above the critical anomaly threshold too
+        critical: bool,
+    },
+    /// Same value for too long: at least
+    /// [`ClassifierConfig::stuck_samples`] observations in a row,
+    /// covering at least [`ClassifierConfig::stuck_min_span_ms`]
+    ///
+    /// See [`ConstantRun`]. A frozen sensor compresses well and looks like
+    /// a quiet one; this tells them apart.
+    ///
+    /// [`ConstantRun`]: crate::context::ConstantRun
+    Stuck {
+        /// Observations in the run, this one included
+        count: u64,
+        /// Time covered by the run, in ms
+        span_ms: u64,
+    },
+}
+
+impl AnomalyLabel {
+    /// Kind of the label, as matched by [`PriorityRule`]s
+    pub fn kind(&self) -> LabelKind {
+        match self {
+            AnomalyLabel::InRange => LabelKind::InRange,
+            AnomalyLabel::Negligible => LabelKind::Negligible,
+            AnomalyLabel::Scheduled => LabelKind::Scheduled,
+            AnomalyLabel::ThresholdExceeded { .. } => LabelKind::ThresholdExceeded,
+            AnomalyLabel::Outlier { .. } => LabelKind::Outlier,
+            AnomalyLabel::RateOfChange {
+                crossing_imminent: true,
+                ..
+            } => LabelKind::ImminentCrossing,
+            AnomalyLabel::RateOfChange { .. } => LabelKind::RateOfChange,
+            AnomalyLabel::PredictionDeviation { critical: true, .. } => {
+                LabelKind::CriticalDeviation
+            }
+            AnomalyLabel::PredictionDeviation { .. } => LabelKind::PredictionDeviation,
+            AnomalyLabel::Stuck { .. } => LabelKind::Stuck,
+        }
+    }
+
+    /// Severity of the label, compared to [`PriorityRule::min_score`]
+    ///
+    /// Distance beyond the threshold, sigma, absolute slope per minute,
+    /// relative deviation or observations in a row; 0.0 for the labels without
+    /// a measure.
+    pub fn score(&self) -> f64 {
+        match self {
+            AnomalyLabel::InRange | AnomalyLabel::Negligible | AnomalyLabel::Scheduled => 0.0,
+            AnomalyLabel::ThresholdExceeded { threshold, actual } => (actual - threshold).abs(),
+            AnomalyLabel::Outlier { sigma } => *sigma,
+            AnomalyLabel::RateOfChange { slope, .. } => slope.abs(),
+            AnomalyLabel::PredictionDeviation { relative, .. } => *relative,
+            AnomalyLabel::Stuck { count, .. } => *count as f64,
+        }
+    }
+
+    /// Classification reason of a priority decided by this label
+    fn reason(&self) -> ClassificationReason {
+        match *self {
+            AnomalyLabel::InRange => ClassificationReason::NormalValue,
+            AnomalyLabel::Negligible => ClassificationReason::BelowMinimumDelta,
+            AnomalyLabel::Scheduled => ClassificationReason::ScheduledTransmission,
+            AnomalyLabel::ThresholdExceeded { threshold, actual } => {
+                ClassificationReason::ThresholdExceeded { threshold, actual }
+            }
+            AnomalyLabel::Outlier { .. } => ClassificationReason::AnomalyDetected {
+                anomaly_type: AnomalyType::OutOfRange,
+            },
+            AnomalyLabel::RateOfChange {
+                slope,
+                projected_crossing_secs,
+                ..
+            } => ClassificationReason::TrendDetected {
+                slope,
+                projected_crossing_secs,
+            },
+            AnomalyLabel::PredictionDeviation { critical, .. } => {
+                ClassificationReason::AnomalyDetected {
+                    anomaly_type: if critical {
+                        AnomalyType::ExtremeDeviation
+                    } else {
+                        AnomalyType::SignificantDeviation
+                    },
+                }
+            }
+            AnomalyLabel::Stuck { .. } => ClassificationReason::AnomalyDetected {
+                anomaly_type: AnomalyType::Stuck,
+            },
+        }
+    }
+}
+
+/// Kind of [`AnomalyLabel`] a [`PriorityRule`] applies to
+///
+/// `RateOfChange` and `PredictionDeviation` labels have two kinds each,
+/// by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LabelKind {
+    /// [`AnomalyLabel::InRange`]
+    InRange,
+    /// [`AnomalyLabel::Negligible`]
+    Negligible,
+    /// [`AnomalyLabel::Scheduled`]
+    Scheduled,
+    /// [`AnomalyLabel::ThresholdExceeded`]
+    ThresholdExceeded,
+    /// [`AnomalyLabel::Outlier`]
+    Outlier,
+    /// [`AnomalyLabel::RateOfChange`] for a steep slope
+    RateOfChange,
+    /// [`AnomalyLabel::RateOfChange`] for a threshold crossing within the
+    /// horizon
+    ImminentCrossing,
+    /// [`AnomalyLabel::PredictionDeviation`] below the critical threshold
+    PredictionDeviation,
+    /// [`AnomalyLabel::PredictionDeviation`] above the critical threshold
+    CriticalDeviation,
+    /// [`AnomalyLabel::Stuck`]
+    Stuck,
+}
+
+/// What an [`AnomalyScorer`] found about a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyScore {
+    /// Findings, empty when the source cannot be predicted yet
+    pub labels: Vec<AnomalyLabel>,
+    /// Relative deviation from the prediction
+    pub delta: f64,
+    /// Confidence in the prediction (0.0-1.0)
+    pub confidence: f32,
 }
 
 /// Critical thresholds for a source
@@ -106,6 +337,41 @@ impl CriticalThresholds {
     }
 }
 
+/// Rate-of-change rules for a source
+///
+/// The slope is a least-squares fit over the value being classified and
+/// the source's most recent timestamped observations (see
+/// [`Context::samples`]), so irregular sampling is accounted for.
+/// Timestamps are taken as milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendRule {
+    /// Points the slope is fitted over, the classified value included
+    /// (default: 8, at least 2)
+    ///
+    /// No trend is reported until that many are available; the context
+    /// keeps [`ContextConfig::trend_samples`] observations per source.
+    ///
+    /// [`ContextConfig::trend_samples`]: crate::context::ContextConfig::trend_samples
+    pub window: usize,
+    /// Escalate to P2 when the absolute slope exceeds this, in value units
+    /// per minute (`None`: disabled)
+    pub max_slope_per_min: Option<f64>,
+    /// Escalate to P1 when, at the current slope, the value would cross
+    /// one of the source's critical thresholds within this many seconds
+    /// (`None`: disabled)
+    pub crossing_horizon_secs: Option<f64>,
+}
+
+impl Default for TrendRule {
+    fn default() -> Self {
+        Self {
+            window: 8,
+            max_slope_per_min: None,
+            crossing_horizon_secs: None,
+        }
+    }
+}
+
 /// Configuration for the classifier
 #[derive(Debug, Clone)]
 pub struct ClassifierConfig {
@@ -119,6 +385,20 @@ pub struct ClassifierConfig {
     pub critical_thresholds: HashMap<u32, CriticalThresholds>,
     /// Scheduled transmission interval in seconds (0 = disabled)
     pub scheduled_interval: u64,
+    /// Rate-of-change rules by source_id
+    pub trend_rules: HashMap<u32, TrendRule>,
+    /// Standard deviations from the history's mean from which a value is
+    /// labeled an outlier (default: 3.0, 0.0 = disabled)
+    pub outlier_sigma: f64,
+    /// Identical observations in a row from which a source can be labeled
+    /// stuck (default: 100, 0 = disabled)
+    ///
+    /// "Identical" allows [`ContextConfig::stuck_epsilon`].
+    ///
+    /// [`ContextConfig::stuck_epsilon`]: crate::context::ContextConfig::stuck_epsilon
+    pub stuck_samples: usize,
+    /// Time those observations must cover, in ms (default: one hour)
+    pub stuck_min_span_ms: u64,
 }
 
 impl Default for ClassifierConfig {
@@ -129,66 +409,70 @@ impl Default for ClassifierConfig {
             minimum_delta_threshold: 0.01,
             critical_thresholds: HashMap::new(),
             scheduled_interval: 0,
+            trend_rules: HashMap::new(),
+            outlier_sigma: 3.0,
+            stuck_samples: 100,
+            stuck_min_span_ms: 3_600_000,
         }
     }
 }
 
-/// Data classifier that assigns priority levels
-#[derive(Debug, Clone)]
-pub struct Classifier {
+/// First classification stage: labels what is unusual about a value
+///
+/// Labels are independent findings; which one decides the priority is up
+/// to the [`PriorityMapper`].
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyScorer {
     config: ClassifierConfig,
-    _last_scheduled: HashMap<u32, u64>,
 }
 
-impl Classifier {
-    /// Create a new classifier with default configuration
+impl AnomalyScorer {
+    /// Create a scorer with default configuration
     pub fn new() -> Self {
-        Self {
-            config: ClassifierConfig::default(),
-            _last_scheduled: HashMap::new(),
-        }
+        Self::default()
     }
 
-    /// Create a classifier with custom configuration
+    /// Create a scorer with custom configuration
     pub fn with_config(config: ClassifierConfig) -> Self {
-        Self {
-            config,
-            _last_scheduled: HashMap::new(),
-        }
+        Self { config }
     }
 
-    /// Set critical thresholds for a source
-    pub fn set_critical_thresholds(&mut self, source_id: u32, min: f64, max: f64) {
-        self.config
-            .critical_thresholds
-            .insert(source_id, CriticalThresholds::new(min, max));
+    /// Get current configuration
+    pub fn config(&self) -> &ClassifierConfig {
+        &self.config
     }
 
-    /// Classify a data point
-    pub fn classify(&self, data: &RawData, context: &Context) -> Classification {
-        // Try to get prediction
-        let prediction = match context.predict(data.source_id) {
-            Some(p) => p,
-            None => return Classification::no_prediction(),
-        };
-
-        // Calculate delta
-        let delta_info = self.calculate_delta(data.value, prediction.value);
-
-        // Check critical thresholds first (highest priority)
-        if let Some(classification) =
-            self.check_critical_thresholds(data.value, data.source_id, &delta_info)
-        {
-            return classification;
-        }
+    /// Update configuration
+    pub fn set_config(&mut self, config: ClassifierConfig) {
+        self.config = config;
+    }
 
-        // Check for anomalies
-        if let Some(classification) = self.check_anomaly(&delta_info, prediction.confidence) {
-            return classification;
+    /// Label a data point
+    ///
+    /// Without a prediction for the source there is nothing to compare
+    /// against: no labels.
+    pub fn score(&self, data: &RawData, context: &Context) -> AnomalyScore {
+        let Some(prediction) = context.predict(data.source_id) else {
+            return AnomalyScore {
+                labels: Vec::new(),
+                delta: 0.0,
+                confidence: 0.0,
+            };
+        };
+        let delta = self.calculate_delta(data.value, prediction.value);
+
+        let mut labels = Vec::new();
+        labels.extend(self.check_critical_thresholds(data.value, data.source_id));
+        labels.extend(self.check_trend(data, context));
+        labels.push(self.check_deviation(&delta));
+        labels.extend(self.check_outlier(data, context));
+        labels.extend(self.check_stuck(data, context));
+
+        AnomalyScore {
+            labels,
+            delta: delta.relative,
+            confidence: prediction.confidence,
         }
-
-        // Normal classification
-        self.classify_normal(data.timestamp, &delta_info, prediction.confidence)
     }
 
     /// Calculate absolute and relative delta
@@ -203,12 +487,7 @@ impl Classifier {
     }
 
     /// Check if value exceeds critical thresholds
-    fn check_critical_thresholds(
-        &self,
-        value: f64,
-        source_id: u32,
-        delta: &DeltaInfo,
-    ) -> Option<Classification> {
+    fn check_critical_thresholds(&self, value: f64, source_id: u32) -> Option<AnomalyLabel> {
         let thresholds = self.config.critical_thresholds.get(&source_id)?;
 
         let violated = if value < thresholds.min {
@@ -219,82 +498,360 @@ impl Classifier {
             None
         }?;
 
-        Some(Classification::new(
-            Priority::P1Critical,
-            ClassificationReason::ThresholdExceeded {
-                threshold: violated,
-                actual: value,
-            },
-            delta.relative,
-            1.0,
-        ))
+        Some(AnomalyLabel::ThresholdExceeded {
+            threshold: violated,
+            actual: value,
+        })
     }
 
-    /// Check for statistical anomalies
-    fn check_anomaly(&self, delta: &DeltaInfo, confidence: f32) -> Option<Classification> {
-        if delta.relative <= self.config.anomaly_threshold {
+    /// Check the source's rate of change against its trend rule
+    fn check_trend(&self, data: &RawData, context: &Context) -> Option<AnomalyLabel> {
+        let rule = self.config.trend_rules.get(&data.source_id)?;
+        let window = rule.window.max(2);
+        let samples = context.samples(data.source_id);
+        if samples.len() + 1 < window {
             return None;
         }
+        let recent = &samples[samples.len() + 1 - window..];
+        let slope_per_sec = fit_slope(recent, data.timestamp, data.value)?;
+        let slope = slope_per_sec * 60.0;
 
-        let (priority, anomaly_type) = if delta.relative > self.config.critical_anomaly_threshold {
-            (Priority::P1Critical, AnomalyType::ExtremeDeviation)
+        let projected_crossing_secs = self
+            .config
+            .critical_thresholds
+            .get(&data.source_id)
+            .and_then(|thresholds| {
+                if slope_per_sec > 0.0 {
+                    Some((thresholds.max - data.value) / slope_per_sec)
+                } else if slope_per_sec < 0.0 {
+                    Some((data.value - thresholds.min) / -slope_per_sec)
+                } else {
+                    None
+                }
+            })
+            .filter(|secs| *secs >= 0.0);
+
+        let crossing_imminent = rule
+            .crossing_horizon_secs
+            .zip(projected_crossing_secs)
+            .is_some_and(|(horizon, secs)| secs <= horizon);
+        if !crossing_imminent && !rule.max_slope_per_min.is_some_and(|max| slope.abs() > max) {
+            return None;
+        }
+
+        Some(AnomalyLabel::RateOfChange {
+            slope,
+            projected_crossing_secs,
+            crossing_imminent,
+        })
+    }
+
+    /// Label the deviation from the prediction
+    fn check_deviation(&self, delta: &DeltaInfo) -> AnomalyLabel {
+        if delta.relative > self.config.anomaly_threshold {
+            AnomalyLabel::PredictionDeviation {
+                relative: delta.relative,
+                critical: delta.relative > self.config.critical_anomaly_threshold,
+            }
+        } else if delta.relative < self.config.minimum_delta_threshold {
+            AnomalyLabel::Negligible
+        } else if self.config.scheduled_interval > 0 {
+            // Simplified: would need timestamp checking in real impl
+            AnomalyLabel::Scheduled
         } else {
-            (Priority::P2Important, AnomalyType::SignificantDeviation)
-        };
+            AnomalyLabel::InRange
+        }
+    }
+
+    /// Check the value against the spread of the source's history
+    fn check_outlier(&self, data: &RawData, context: &Context) -> Option<AnomalyLabel> {
+        if self.config.outlier_sigma <= 0.0 {
+            return None;
+        }
+        let (history, _) = context.history(data.source_id)?;
+        let std_dev = history.std_dev().filter(|s| *s > 0.0)?;
+        let sigma = (data.value - history.mean()?).abs() / std_dev;
+        (sigma >= self.config.outlier_sigma).then_some(AnomalyLabel::Outlier { sigma })
+    }
+
+    /// Check the run of identical values this one extends
+    fn check_stuck(&self, data: &RawData, context: &Context) -> Option<AnomalyLabel> {
+        if self.config.stuck_samples == 0 {
+            return None;
+        }
+        let run = context.constant_run_with(data);
+        (run.count >= self.config.stuck_samples as u64
+            && run.span_ms() >= self.config.stuck_min_span_ms)
+            .then_some(AnomalyLabel::Stuck {
+                count: run.count,
+                span_ms: run.span_ms(),
+            })
+    }
+}
 
-        Some(Classification::new(
+/// One entry of a [`PriorityMapper`] table
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorityRule {
+    /// Kind of label the rule applies to
+    pub label: LabelKind,
+    /// Source the rule is limited to (`None`: every source)
+    pub source: Option<u32>,
+    /// Lowest [`AnomalyLabel::score`] the rule applies to
+    pub min_score: f64,
+    /// Priority given by the rule
+    pub priority: Priority,
+}
+
+impl PriorityRule {
+    /// Rule giving `priority` to every label of kind `label`
+    pub fn new(label: LabelKind, priority: Priority) -> Self {
+        Self {
+            label,
+            source: None,
+            min_score: 0.0,
             priority,
-            ClassificationReason::AnomalyDetected { anomaly_type },
-            delta.relative,
+        }
+    }
+
+    /// Limit the rule to one source
+    pub fn for_source(mut self, source_id: u32) -> Self {
+        self.source = Some(source_id);
+        self
+    }
+
+    /// Only apply the rule to labels scoring at least `min_score`
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Whether the rule applies to `label` of `source_id`
+    pub fn matches(&self, source_id: u32, label: &AnomalyLabel) -> bool {
+        label.kind() == self.label
+            && self.source.map_or(true, |source| source == source_id)
+            && label.score() >= self.min_score
+    }
+}
+
+/// Second classification stage: maps labels to a priority
+///
+/// Rules are tried in order; the first one matching any of the value's
+/// labels gives the priority, and that label the classification's reason.
+/// A value with no labels (no prediction yet), or none matched, gets the
+/// fallback priority.
+///
+/// The default table reproduces the historical classification, except
+/// that stuck sources are escalated to P2 so they are reported promptly
+/// (they previously kept the priority of their other labels):
+///
+/// | Label | Priority |
+/// |-------|----------|
+/// | `ThresholdExceeded` | P1 |
+/// | `ImminentCrossing` | P1 |
+/// | `CriticalDeviation` | P1 |
+/// | `PredictionDeviation` | P2 |
+/// | `RateOfChange` | P2 |
+/// | `Stuck` | P2 |
+/// | `Negligible` | P5 |
+/// | `Scheduled` | P3 |
+/// | `InRange` | P4 |
+///
+/// `Outlier` labels are reported but do not change the priority unless a
+/// rule is added for them. The fallback is P3.
+///
+/// ```
+/// use alec::classifier::{LabelKind, PriorityRule};
+/// use alec::{Classifier, ClassifierConfig, Context, PriorityMapper, Priority, RawData};
+///
+/// // Significant deviations of source 7 are only P3 for us
+/// let mapper = PriorityMapper::default()
+///     .with_rule(PriorityRule::new(LabelKind::PredictionDeviation, Priority::P3Normal).for_source(7));
+/// let classifier = Classifier::with_stages(ClassifierConfig::default(), mapper);
+///
+/// let mut context = Context::new();
+/// for i in 0..10 {
+///     context.observe(&RawData::with_source(7, 20.0, i * 1000));
+/// }
+/// let classification = classifier.classify(&RawData::with_source(7, 24.0, 10_000), &context);
+/// assert_eq!(classification.priority, Priority::P3Normal);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorityMapper {
+    rules: Vec<PriorityRule>,
+    fallback: Priority,
+}
+
+impl PriorityMapper {
+    /// Create a mapper with the default table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a mapper without rules: everything gets `fallback`
+    pub fn empty(fallback: Priority) -> Self {
+        Self {
+            rules: Vec::new(),
+            fallback,
+        }
+    }
+
+    /// Add a rule ahead of the existing ones
+    pub fn with_rule(mut self, rule: PriorityRule) -> Self {
+        self.rules.insert(0, rule);
+        self
+    }
+
+    /// Add a rule after the existing ones
+    pub fn push_rule(&mut self, rule: PriorityRule) {
+        self.rules.push(rule);
+    }
+
+    /// Rules, in the order they are tried
+    pub fn rules(&self) -> &[PriorityRule] {
+        &self.rules
+    }
+
+    /// Mutable access to the rules, e.g. to change a default priority
+    pub fn rules_mut(&mut self) -> &mut Vec<PriorityRule> {
+        &mut self.rules
+    }
+
+    /// Priority of values with no label, or none matched
+    pub fn fallback(&self) -> Priority {
+        self.fallback
+    }
+
+    /// Map a scored value of `source_id` to its classification
+    pub fn map(&self, source_id: u32, score: AnomalyScore) -> Classification {
+        let decided = self.rules.iter().find_map(|rule| {
+            score
+                .labels
+                .iter()
+                .find(|label| rule.matches(source_id, label))
+                .map(|label| (rule.priority, label))
+        });
+        let (priority, reason, confidence) = match decided {
+            Some((priority, label @ AnomalyLabel::ThresholdExceeded { .. })) => {
+                (priority, label.reason(), 1.0)
+            }
+            Some((priority, label)) => (priority, label.reason(), score.confidence),
+            None if score.labels.is_empty() => {
+                (self.fallback, ClassificationReason::NoPrediction, 0.0)
+            }
+            None => (
+                self.fallback,
+                ClassificationReason::NormalValue,
+                score.confidence,
+            ),
+        };
+        Classification {
+            priority,
+            reason,
+            delta: score.delta,
             confidence,
-        ))
+            labels: score.labels,
+        }
     }
+}
 
-    /// Classify normal (non-anomalous) values
-    fn classify_normal(
-        &self,
-        _timestamp: u64,
-        delta: &DeltaInfo,
-        confidence: f32,
-    ) -> Classification {
-        // Check if delta is too small to bother sending
-        if delta.relative < self.config.minimum_delta_threshold {
-            return Classification::new(
-                Priority::P5Disposable,
-                ClassificationReason::BelowMinimumDelta,
-                delta.relative,
-                confidence,
-            );
+impl Default for PriorityMapper {
+    fn default() -> Self {
+        let rules = [
+            (LabelKind::ThresholdExceeded, Priority::P1Critical),
+            (LabelKind::ImminentCrossing, Priority::P1Critical),
+            (LabelKind::CriticalDeviation, Priority::P1Critical),
+            (LabelKind::PredictionDeviation, Priority::P2Important),
+            (LabelKind::RateOfChange, Priority::P2Important),
+            (LabelKind::Stuck, Priority::P2Important),
+            (LabelKind::Negligible, Priority::P5Disposable),
+            (LabelKind::Scheduled, Priority::P3Normal),
+            (LabelKind::InRange, Priority::P4Deferred),
+        ];
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|(label, priority)| PriorityRule::new(label, priority))
+                .collect(),
+            fallback: Priority::P3Normal,
         }
+    }
+}
+
+/// Data classifier that assigns priority levels
+///
+/// An [`AnomalyScorer`] followed by a [`PriorityMapper`].
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    scorer: AnomalyScorer,
+    mapper: PriorityMapper,
+    _last_scheduled: HashMap<u32, u64>,
+}
 
-        // Check if this is a scheduled transmission time
-        // (simplified: would need timestamp checking in real impl)
-        if self.config.scheduled_interval > 0 {
-            return Classification::new(
-                Priority::P3Normal,
-                ClassificationReason::ScheduledTransmission,
-                delta.relative,
-                confidence,
-            );
+impl Classifier {
+    /// Create a new classifier with default configuration
+    pub fn new() -> Self {
+        Self::with_config(ClassifierConfig::default())
+    }
+
+    /// Create a classifier with custom configuration
+    pub fn with_config(config: ClassifierConfig) -> Self {
+        Self::with_stages(config, PriorityMapper::default())
+    }
+
+    /// Create a classifier with custom configuration and priority mapping
+    pub fn with_stages(config: ClassifierConfig, mapper: PriorityMapper) -> Self {
+        Self {
+            scorer: AnomalyScorer::with_config(config),
+            mapper,
+            _last_scheduled: HashMap::new(),
         }
+    }
 
-        // Default: deferred
-        Classification::new(
-            Priority::P4Deferred,
-            ClassificationReason::NormalValue,
-            delta.relative,
-            confidence,
-        )
+    /// Set critical thresholds for a source
+    pub fn set_critical_thresholds(&mut self, source_id: u32, min: f64, max: f64) {
+        self.scorer
+            .config
+            .critical_thresholds
+            .insert(source_id, CriticalThresholds::new(min, max));
+    }
+
+    /// Set the rate-of-change rules of a source
+    ///
+    /// Projected crossings use the thresholds set with
+    /// [`set_critical_thresholds`](Self::set_critical_thresholds).
+    pub fn set_trend_rule(&mut self, source_id: u32, rule: TrendRule) {
+        self.scorer.config.trend_rules.insert(source_id, rule);
+    }
+
+    /// Classify a data point
+    pub fn classify(&self, data: &RawData, context: &Context) -> Classification {
+        self.mapper
+            .map(data.source_id, self.scorer.score(data, context))
     }
 
     /// Get current configuration
     pub fn config(&self) -> &ClassifierConfig {
-        &self.config
+        self.scorer.config()
     }
 
     /// Update configuration
     pub fn set_config(&mut self, config: ClassifierConfig) {
-        self.config = config;
+        self.scorer.set_config(config);
+    }
+
+    /// The scoring stage
+    pub fn scorer(&self) -> &AnomalyScorer {
+        &self.scorer
+    }
+
+    /// The priority mapping stage
+    pub fn mapper(&self) -> &PriorityMapper {
+        &self.mapper
+    }
+
+    /// Replace the priority mapping
+    pub fn set_mapper(&mut self, mapper: PriorityMapper) {
+        self.mapper = mapper;
     }
 }
 
@@ -304,6 +861,32 @@ impl Default for Classifier {
     }
 }
 
+/// Least-squares slope, in value units per second, of `samples` followed
+/// by the point `(timestamp, value)`; timestamps in milliseconds
+///
+/// `None` if all the timestamps are equal.
+fn fit_slope(samples: &[(u64, f64)], timestamp: u64, value: f64) -> Option<f64> {
+    // Seconds relative to the newest point, keeping the sums small
+    let points = samples
+        .iter()
+        .copied()
+        .chain(core::iter::once((timestamp, value)))
+        .map(|(t, v)| ((t as f64 - timestamp as f64) / 1000.0, v));
+    let n = (samples.len() + 1) as f64;
+    let (mut sum_t, mut sum_v) = (0.0, 0.0);
+    for (t, v) in points.clone() {
+        sum_t += t;
+        sum_v += v;
+    }
+    let (mean_t, mean_v) = (sum_t / n, sum_v / n);
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (t, v) in points {
+        covariance += (t - mean_t) * (v - mean_v);
+        variance += (t - mean_t) * (t - mean_t);
+    }
+    (variance > 0.0).then(|| covariance / variance)
+}
+
 /// Internal struct for delta calculations
 struct DeltaInfo {
     #[allow(dead_code)]
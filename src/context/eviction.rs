@@ -0,0 +1,143 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Pattern scoring and eviction policies
+//!
+//! An [`EvictionPolicy`] decides which dictionary patterns survive
+//! [`Context::evolve`](super::Context::evolve): [`retain`](EvictionPolicy::retain)
+//! prunes, and [`score`](EvictionPolicy::score) orders the survivors (best
+//! patterns get the lowest codes) and picks victims when the dictionary
+//! must shrink. Equal scores are broken by the current code, lowest first,
+//! so two peers evolving the same dictionary agree on the result.
+//!
+//! Provided policies:
+//! - [`FrequencyRecency`] (default): log-frequency with a recency decay
+//! - [`Lfu`]: usage count only
+//! - [`Lru`]: last use only
+//! - [`WindowedFrequency`]: usage count, undecayed within a window; suits
+//!   bursty sources whose patterns sit idle between bursts
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use super::{EvolutionConfig, Pattern};
+
+/// Strategy ranking dictionary patterns for pruning and eviction
+///
+/// Configured through [`ContextConfig::eviction`](super::ContextConfig::eviction).
+/// Implementations must be deterministic: peers running the same policy
+/// over the same dictionary must keep the same patterns.
+pub trait EvictionPolicy: EvictionPolicyClone + core::fmt::Debug + Send + Sync {
+    /// Value of keeping `pattern` at observation count `now` (higher is better)
+    fn score(&self, pattern: &Pattern, now: u64) -> f64;
+
+    /// Whether `pattern` survives pruning at observation count `now`
+    ///
+    /// Defaults to the evolution thresholds: at least `min_frequency` uses
+    /// and last used within `max_age` observations.
+    fn retain(&self, pattern: &Pattern, now: u64, evolution: &EvolutionConfig) -> bool {
+        pattern.frequency >= evolution.min_frequency
+            && now.saturating_sub(pattern.last_used) <= evolution.max_age
+    }
+}
+
+/// Boxed cloning for [`EvictionPolicy`] trait objects
+///
+/// Implemented for every `Clone` policy; there is no need to implement it
+/// by hand.
+pub trait EvictionPolicyClone {
+    /// Clone into a new box
+    fn clone_box(&self) -> Box<dyn EvictionPolicy>;
+}
+
+impl<T: EvictionPolicy + Clone + 'static> EvictionPolicyClone for T {
+    fn clone_box(&self) -> Box<dyn EvictionPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn EvictionPolicy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl Default for Box<dyn EvictionPolicy> {
+    fn default() -> Self {
+        Box::new(FrequencyRecency)
+    }
+}
+
+/// Log-frequency times a recency decay over 1000 observations
+///
+/// The historical behavior, see [`Pattern::score`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrequencyRecency;
+
+impl EvictionPolicy for FrequencyRecency {
+    fn score(&self, pattern: &Pattern, now: u64) -> f64 {
+        pattern.score(now)
+    }
+}
+
+/// Least frequently used: ranks by usage count alone
+///
+/// Prunes on `min_frequency` only; idle patterns are kept however old.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lfu;
+
+impl EvictionPolicy for Lfu {
+    fn score(&self, pattern: &Pattern, _now: u64) -> f64 {
+        pattern.frequency as f64
+    }
+
+    fn retain(&self, pattern: &Pattern, _now: u64, evolution: &EvolutionConfig) -> bool {
+        pattern.frequency >= evolution.min_frequency
+    }
+}
+
+/// Least recently used: ranks by last use alone
+///
+/// Prunes on `max_age` only; rarely used patterns are kept while recent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lru;
+
+impl EvictionPolicy for Lru {
+    fn score(&self, pattern: &Pattern, _now: u64) -> f64 {
+        pattern.last_used as f64
+    }
+
+    fn retain(&self, pattern: &Pattern, now: u64, evolution: &EvolutionConfig) -> bool {
+        now.saturating_sub(pattern.last_used) <= evolution.max_age
+    }
+}
+
+/// Usage count with no decay while used within the last `window` observations
+///
+/// Patterns idle for longer than `window` score 0 and are pruned; `window`
+/// takes the place of `max_age`. Set it above the longest idle gap of the
+/// source (e.g. a day of observations for nightly uploads) so burst
+/// patterns are still there when the next burst starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowedFrequency {
+    /// Observations a pattern keeps its full score after its last use
+    pub window: u64,
+}
+
+impl EvictionPolicy for WindowedFrequency {
+    fn score(&self, pattern: &Pattern, now: u64) -> f64 {
+        if now.saturating_sub(pattern.last_used) <= self.window {
+            pattern.frequency as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn retain(&self, pattern: &Pattern, now: u64, evolution: &EvolutionConfig) -> bool {
+        pattern.frequency >= evolution.min_frequency
+            && now.saturating_sub(pattern.last_used) <= self.window
+    }
+}
@@ -0,0 +1,219 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Evolution spread over observations
+//!
+//! [`Context::evolve`] scores, prunes and renumbers the whole dictionary at
+//! once, which on large dictionaries stalls the observation that triggers
+//! it. With [`EvolutionConfig::step_patterns`](super::EvolutionConfig::step_patterns)
+//! set, a scheduled evolution becomes a cycle advanced by every
+//! observation:
+//! 1. Scan: `step_patterns` codes per step are scored at the observation
+//!    count the cycle started at, pruned patterns are left out and each
+//!    step's survivors are sorted into a run
+//! 2. Rebuild: the runs are merged best first, `step_patterns` patterns
+//!    per step, into a renumbered copy of the dictionary, cut to
+//!    `max_patterns`
+//! 3. Swap: the copy replaces the dictionary
+//!
+//! Until the swap the dictionary is the one the cycle started from, on
+//! both peers alike, since both observe in lockstep. The outcome is the
+//! dictionary [`Context::evolve`] would have produced when the cycle
+//! started. To keep it so, every call changing the dictionary
+//! (registering, removing or setting patterns, merges, imports, another
+//! evolution) first completes the pending cycle, as does
+//! [`Context::finish_evolution`].
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use core::cmp::Ordering;
+
+use xxhash_rust::xxh64::xxh64;
+
+use super::{Context, Map, Pattern};
+
+/// Ranking order of `(score, code)` pairs: best score first, lowest code
+/// first among equals, as [`Context::evolve`] ranks
+fn rank(a: &(f64, u32), b: &(f64, u32)) -> Ordering {
+    b.0.partial_cmp(&a.0)
+        .unwrap_or(Ordering::Equal)
+        .then(a.1.cmp(&b.1))
+}
+
+/// State of an evolution cycle in progress
+#[derive(Debug, Clone)]
+pub(super) struct EvolutionCycle {
+    /// Observation count the patterns are scored at
+    now: u64,
+    /// Next code to scan
+    cursor: u32,
+    /// Codes below this one are scanned
+    scan_end: u32,
+    /// Patterns scanned so far
+    scanned: usize,
+    /// Sorted `(score, code)` runs, one per scan step
+    runs: Vec<Vec<(f64, u32)>>,
+    /// Position of the next pair to merge in each run
+    heads: Vec<usize>,
+    /// Renumbered dictionary being built
+    dictionary: Map<u32, Pattern>,
+    /// Index of the renumbered dictionary
+    pattern_index: Map<u64, u32>,
+    /// Whether any pattern was dropped or moved
+    changed: bool,
+}
+
+impl EvolutionCycle {
+    fn new(now: u64, scan_end: u32) -> Self {
+        Self {
+            now,
+            cursor: 0,
+            scan_end,
+            scanned: 0,
+            runs: Vec::new(),
+            heads: Vec::new(),
+            dictionary: Map::new(),
+            pattern_index: Map::new(),
+            changed: false,
+        }
+    }
+
+    /// Best unmerged pair across the runs, and the run it heads
+    fn next_ranked(&self) -> Option<(usize, (f64, u32))> {
+        let mut best: Option<(usize, (f64, u32))> = None;
+        for (run, (pairs, &head)) in self.runs.iter().zip(&self.heads).enumerate() {
+            let Some(&pair) = pairs.get(head) else {
+                continue;
+            };
+            let better = match best {
+                Some((_, current)) => rank(&pair, &current) == Ordering::Less,
+                None => true,
+            };
+            if better {
+                best = Some((run, pair));
+            }
+        }
+        best
+    }
+}
+
+impl Context {
+    /// Whether an evolution cycle is in progress
+    ///
+    /// See [`EvolutionConfig::step_patterns`](super::EvolutionConfig::step_patterns).
+    pub fn evolution_pending(&self) -> bool {
+        self.evolution.is_some()
+    }
+
+    /// Complete the evolution cycle in progress, if any
+    ///
+    /// The dictionary becomes the one [`evolve`](Self::evolve) would have
+    /// produced when the cycle started. Call it on both peers before
+    /// persisting or exporting a context mid-cycle.
+    pub fn finish_evolution(&mut self) {
+        while self.evolution.is_some() {
+            self.evolution_step(usize::MAX);
+        }
+    }
+
+    /// Start an evolution cycle at the current observation count
+    pub(super) fn start_evolution(&mut self) {
+        self.finish_evolution();
+        self.evolution = Some(Box::new(EvolutionCycle::new(
+            self.observation_count,
+            self.next_code,
+        )));
+    }
+
+    /// Advance the cycle in progress by up to `budget` patterns
+    pub(super) fn evolution_step(&mut self, budget: usize) {
+        let Some(mut cycle) = self.evolution.take() else {
+            return;
+        };
+        let budget = budget.max(1);
+
+        // 1. Scan
+        if cycle.cursor < cycle.scan_end {
+            let end = cycle.scan_end.min(
+                cycle
+                    .cursor
+                    .saturating_add(budget.min(u32::MAX as usize) as u32),
+            );
+            let policy = &self.config.eviction;
+            let evolution = &self.config.evolution;
+            let mut run = Vec::new();
+            for code in cycle.cursor..end {
+                let Some(pattern) = self.dictionary.get(&code) else {
+                    continue;
+                };
+                cycle.scanned += 1;
+                if evolution.protects(pattern) || policy.retain(pattern, cycle.now, evolution) {
+                    run.push((policy.score(pattern, cycle.now), code));
+                } else {
+                    cycle.changed = true;
+                }
+            }
+            run.sort_by(rank);
+            if !run.is_empty() {
+                cycle.runs.push(run);
+                cycle.heads.push(0);
+            }
+            cycle.cursor = end;
+            self.evolution = Some(cycle);
+            return;
+        }
+        if cycle.scanned != self.dictionary.len() {
+            // Codes at or past `next_code`: not covered by the scan
+            let now = cycle.now;
+            self.evolve_at(now);
+            return;
+        }
+        #[cfg(feature = "std")]
+        if cycle.dictionary.is_empty() {
+            // Sized once: growing would rehash in a single step
+            let survivors: usize = cycle.runs.iter().map(Vec::len).sum();
+            let survivors = survivors.min(self.config.max_patterns);
+            cycle.dictionary.reserve(survivors);
+            cycle.pattern_index.reserve(survivors);
+        }
+
+        // 2. Rebuild, best first
+        for _ in 0..budget {
+            let Some((run, (_, code))) = cycle.next_ranked() else {
+                break;
+            };
+            cycle.heads[run] += 1;
+            let new_code = cycle.dictionary.len() as u32;
+            if cycle.dictionary.len() >= self.config.max_patterns {
+                cycle.changed = true;
+                continue;
+            }
+            if let Some(pattern) = self.dictionary.get(&code) {
+                cycle.changed |= code != new_code;
+                cycle
+                    .pattern_index
+                    .insert(xxh64(&pattern.data, 0), new_code);
+                cycle.dictionary.insert(new_code, pattern.clone());
+            }
+        }
+        if cycle.next_ranked().is_some() {
+            self.evolution = Some(cycle);
+            return;
+        }
+
+        // 3. Swap
+        if !cycle.dictionary.is_empty() {
+            self.next_code = cycle.dictionary.len() as u32;
+        }
+        self.dictionary = core::mem::take(&mut cycle.dictionary);
+        self.pattern_index = core::mem::take(&mut cycle.pattern_index);
+        self.version += 1;
+        if cycle.changed {
+            self.dictionary_version += 1;
+        }
+    }
+}
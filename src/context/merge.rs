@@ -0,0 +1,201 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Merging two trained contexts
+//!
+//! [`Context::merge`] folds another context into this one, e.g. when two
+//! gateways are consolidated and neither training should be thrown away:
+//! - Patterns with the same data are kept once, with their frequencies
+//!   summed; other incoming patterns keep their code when it is free and
+//!   get a new one otherwise
+//! - Source statistics are combined: mean and variance as if every
+//!   observation had been seen by one context, histories concatenated and
+//!   truncated to the most recent values
+//! - A [`MergeStrategy`] picks which side wins where values cannot be
+//!   combined (last value, EMA, pattern timestamps)
+
+use xxhash_rust::xxh64::xxh64;
+
+use super::{for_each_sorted_u32, Context, Pattern, SourceStats};
+use crate::stats::{Ema, WindowedStats};
+
+/// Which side wins when a merge cannot combine two values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep this context's values
+    #[default]
+    PreferSelf,
+    /// Take the merged-in context's values
+    PreferOther,
+    /// Take the values of the side with the higher pattern frequency or
+    /// source observation count (this context on ties)
+    HighestFrequency,
+}
+
+impl MergeStrategy {
+    /// Whether the merged-in side wins, given both sides' usage counts
+    fn prefers_other(self, own: u64, other: u64) -> bool {
+        match self {
+            Self::PreferSelf => false,
+            Self::PreferOther => true,
+            Self::HighestFrequency => other > own,
+        }
+    }
+}
+
+/// Outcome of a [`Context::merge`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Incoming patterns added to the dictionary
+    pub patterns_added: usize,
+    /// Incoming patterns already in the dictionary, frequencies summed
+    pub patterns_deduped: usize,
+    /// Patterns evicted to stay within `max_patterns` and `max_memory`
+    pub patterns_evicted: usize,
+    /// Sources of the merged-in context, new or shared, folded into this one
+    pub sources_merged: usize,
+}
+
+impl Context {
+    /// Merge `other` into this context
+    ///
+    /// Dictionaries are united: identical pattern data is kept once with
+    /// the summed frequency, and an incoming pattern whose code is taken
+    /// gets the next free code. Source statistics are combined with a
+    /// parallel Welford update, and histories are concatenated (winning
+    /// side last) and truncated to this context's history size. `strategy`
+    /// decides the last value and EMA of shared sources, and the value and
+    /// timestamps of shared patterns.
+    /// Source aliases of `other` are added where this context has none.
+    ///
+    /// If the result exceeds `max_patterns` or `max_memory`, the lowest
+    /// scoring patterns under [`ContextConfig::eviction`](super::ContextConfig::eviction)
+    /// are evicted. The configuration and observation count of this
+    /// context are kept. The version is bumped once, and the dictionary
+    /// version too if the dictionary changed.
+    pub fn merge(&mut self, other: &Context, strategy: MergeStrategy) -> MergeReport {
+        self.finish_evolution();
+        let mut report = MergeReport::default();
+        let dictionary_hash = self.hash();
+
+        // Patterns, in ascending incoming code order so peers merging the
+        // same contexts assign the same codes
+        for_each_sorted_u32(&other.dictionary, |code, incoming| {
+            let hash = xxh64(&incoming.data, 0);
+            if let Some(existing) = self
+                .pattern_index
+                .get(&hash)
+                .and_then(|code| self.dictionary.get_mut(code))
+            {
+                merge_pattern(existing, incoming, strategy);
+                report.patterns_deduped += 1;
+                return;
+            }
+
+            let code = if self.dictionary.contains_key(&code) {
+                self.next_code
+            } else {
+                code
+            };
+            self.pattern_index.insert(hash, code);
+            self.dictionary.insert(code, incoming.clone());
+            self.next_code = self.next_code.max(code + 1);
+            report.patterns_added += 1;
+        });
+
+        // Source statistics
+        for_each_sorted_u32(&other.source_stats, |source_id, incoming| {
+            let history_size = self.config.history_size;
+            let ema_alpha = self.config.ema_alpha;
+            let trend_samples = self.config.trend_samples;
+            let stats = self
+                .source_stats
+                .entry(source_id)
+                .or_insert_with(|| SourceStats::new(history_size, ema_alpha, trend_samples));
+            stats.merge(incoming, strategy, trend_samples);
+            report.sources_merged += 1;
+        });
+        for_each_sorted_u32(&other.source_aliases, |source_id, &native_id| {
+            self.source_aliases.entry(source_id).or_insert(native_id);
+        });
+
+        report.patterns_evicted = self.shrink_to_limits();
+
+        self.version += 1;
+        if self.hash() != dictionary_hash {
+            self.dictionary_version += 1;
+        }
+        report
+    }
+
+    /// Evict lowest-scoring patterns until both dictionary limits hold
+    fn shrink_to_limits(&mut self) -> usize {
+        let now = self.observation_count;
+        let mut count = self.dictionary.len();
+        let mut memory = self.memory_usage();
+        let mut evicted = 0;
+        for code in self.eviction_order(now) {
+            if count <= self.config.max_patterns && memory <= self.config.max_memory {
+                break;
+            }
+            let size = self.dictionary.get(&code).map_or(0, |p| p.data.len());
+            count -= 1;
+            memory -= size + super::PATTERN_OVERHEAD;
+            evicted += 1;
+        }
+        self.remove_lowest(evicted, now)
+    }
+}
+
+/// Fold `incoming` into `existing`, two patterns with the same data
+fn merge_pattern(existing: &mut Pattern, incoming: &Pattern, strategy: MergeStrategy) {
+    let frequency = existing.frequency.saturating_add(incoming.frequency);
+    if strategy.prefers_other(existing.frequency, incoming.frequency) {
+        existing.value = incoming.value;
+        existing.last_used = incoming.last_used;
+        existing.created_at = incoming.created_at;
+    }
+    existing.frequency = frequency;
+    existing.raise_priority(incoming.priority);
+}
+
+impl SourceStats {
+    /// Combine with the statistics of the same source seen elsewhere
+    pub(super) fn merge(
+        &mut self,
+        other: &SourceStats,
+        strategy: MergeStrategy,
+        max_samples: usize,
+    ) {
+        if other.running.count() == 0 {
+            return;
+        }
+        let other_wins = self.running.count() == 0
+            || strategy.prefers_other(self.running.count(), other.running.count());
+
+        // Parallel Welford (Chan et al.) update of mean and variance
+        self.running.merge(&other.running);
+
+        // Winning side's values are the most recent
+        let mut history = WindowedStats::new(self.history.capacity());
+        if other_wins {
+            history.extend(self.history.iter());
+            history.extend(other.history.iter());
+            self.last_value = other.last_value;
+            self.ema = Ema::with_value(self.ema.alpha(), other.ema.value());
+        } else {
+            history.extend(other.history.iter());
+            history.extend(self.history.iter());
+        }
+        self.history = history;
+
+        // Timestamped samples interleave by time
+        self.samples.extend_from_slice(&other.samples);
+        self.samples.sort_by_key(|(timestamp, _)| *timestamp);
+        let excess = self.samples.len().saturating_sub(max_samples);
+        self.samples.drain(..excess);
+    }
+}
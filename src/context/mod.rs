@@ -11,17 +11,37 @@
 //! - Predictive model for delta encoding
 //! - Synchronization mechanisms
 //! - Preload file support for instant optimal compression
-
+//! - A [`trainer`] building preloads from historical data (std only)
+//! - Pluggable [`EvictionPolicy`] deciding which patterns evolution keeps
+//! - [`Context::merge`] uniting two trained contexts
+//! - Canonical numeric patterns ([`canonicalize`]) independent of float paths
+
+mod eviction;
+mod incremental;
+mod merge;
+mod numeric;
 mod preload;
+mod remap;
+#[cfg(feature = "std")]
+pub mod trainer;
 
+pub use eviction::*;
+pub use merge::*;
+pub use numeric::*;
 pub use preload::*;
+pub use remap::*;
 
 #[cfg(not(feature = "std"))]
-use alloc::{string::ToString, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeSet, string::ToString, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeSet;
 
-use crate::error::{ContextError, Result};
-use crate::protocol::RawData;
-use xxhash_rust::xxh64::xxh64;
+use crate::classifier::Classification;
+use crate::error::{BulkError, ContextError, Result};
+use crate::protocol::{Priority, RawData};
+use crate::stats::{Ema, RunningStats, WindowedStats};
+use smallvec::SmallVec;
+use xxhash_rust::xxh64::{xxh64, Xxh64};
 
 #[cfg(feature = "std")]
 type Map<K, V> = std::collections::HashMap<K, V>;
@@ -92,23 +112,23 @@ fn for_each_sorted_u32<V, F: FnMut(u32, &V)>(map: &Map<u32, V>, mut f: F) {
 fn write_source_stats_into(out: &mut [u8], w: &mut usize, sid: u32, s: &SourceStats) {
     out[*w..*w + 4].copy_from_slice(&sid.to_le_bytes());
     *w += 4;
-    out[*w..*w + 8].copy_from_slice(&s.count.to_le_bytes());
+    out[*w..*w + 8].copy_from_slice(&s.running.count().to_le_bytes());
     *w += 8;
     out[*w..*w + 8].copy_from_slice(&s.last_value.to_le_bytes());
     *w += 8;
-    out[*w..*w + 8].copy_from_slice(&s.ema.to_le_bytes());
+    out[*w..*w + 8].copy_from_slice(&s.ema.value().unwrap_or(0.0).to_le_bytes());
     *w += 8;
-    out[*w..*w + 8].copy_from_slice(&s.ema_alpha.to_le_bytes());
+    out[*w..*w + 8].copy_from_slice(&s.ema.alpha().to_le_bytes());
     *w += 8;
-    out[*w..*w + 8].copy_from_slice(&s.sum_sq_diff.to_le_bytes());
+    out[*w..*w + 8].copy_from_slice(&s.running.m2().to_le_bytes());
     *w += 8;
-    out[*w..*w + 8].copy_from_slice(&s.mean.to_le_bytes());
+    out[*w..*w + 8].copy_from_slice(&s.running.mean().to_le_bytes());
     *w += 8;
-    out[*w..*w + 4].copy_from_slice(&(s.max_history as u32).to_le_bytes());
+    out[*w..*w + 4].copy_from_slice(&(s.history.capacity() as u32).to_le_bytes());
     *w += 4;
     out[*w..*w + 4].copy_from_slice(&(s.history.len() as u32).to_le_bytes());
     *w += 4;
-    for v in &s.history {
+    for v in s.history.iter() {
         out[*w..*w + 8].copy_from_slice(&v.to_le_bytes());
         *w += 8;
     }
@@ -142,6 +162,9 @@ pub const MAX_PATTERN_SIZE: usize = 255;
 /// Default memory limit for context (64 KB)
 pub const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024;
 
+/// Estimated bookkeeping bytes per dictionary entry, on top of its data
+const PATTERN_OVERHEAD: usize = 32;
+
 /// A prediction for a source
 #[derive(Debug, Clone, PartialEq)]
 pub struct Prediction {
@@ -173,70 +196,49 @@ struct SourceStats {
     /// Last observed value
     last_value: f64,
     /// Exponential moving average
-    ema: f64,
-    /// EMA alpha (smoothing factor, 0-1)
-    ema_alpha: f64,
-    /// Number of observations
-    count: u64,
-    /// Sum of squared differences (for variance)
-    sum_sq_diff: f64,
-    /// Running mean
-    mean: f64,
+    ema: Ema,
+    /// Count, mean and variance of every observation
+    running: RunningStats,
     /// History buffer for advanced predictions
-    history: Vec<f64>,
-    /// Maximum history size
-    max_history: usize,
+    history: WindowedStats,
+    /// Recent `(timestamp, value)` observations, oldest first
+    ///
+    /// Not part of the preload format: a loaded context starts without.
+    samples: Vec<(u64, f64)>,
 }
 
 impl SourceStats {
-    fn new(max_history: usize, ema_alpha: f64) -> Self {
+    fn new(max_history: usize, ema_alpha: f64, max_samples: usize) -> Self {
         Self {
             last_value: 0.0,
-            ema: 0.0,
-            ema_alpha,
-            count: 0,
-            sum_sq_diff: 0.0,
-            mean: 0.0,
-            history: Vec::with_capacity(max_history),
-            max_history,
+            ema: Ema::new(ema_alpha),
+            running: RunningStats::new(),
+            history: WindowedStats::new(max_history),
+            samples: Vec::with_capacity(max_samples),
         }
     }
 
     fn observe(&mut self, value: f64) {
-        self.count += 1;
-        self.last_value = value;
-
-        // Update EMA
-        if self.count == 1 {
-            self.ema = value;
-        } else {
-            self.ema = self.ema_alpha * value + (1.0 - self.ema_alpha) * self.ema;
-        }
-
-        // Update running statistics (Welford's algorithm)
-        let delta = value - self.mean;
-        self.mean += delta / self.count as f64;
-        let delta2 = value - self.mean;
-        self.sum_sq_diff += delta * delta2;
+        self.observe_weighted(value, 1.0);
+    }
 
-        // Update history
-        if self.history.len() >= self.max_history {
-            self.history.remove(0);
-        }
+    /// Fold a value in with the EMA alpha and Welford steps scaled by
+    /// `weight` (0-1); a weight of 1.0 is a plain observation
+    fn observe_weighted(&mut self, value: f64, weight: f64) {
+        self.last_value = value;
+        self.ema.push_weighted(value, weight);
+        self.running.push_weighted(value, weight);
         self.history.push(value);
     }
 
     fn predict(&self) -> Option<Prediction> {
-        if self.count == 0 {
+        let count = self.running.count();
+        if count == 0 {
             return None;
         }
 
         // Calculate variance for confidence
-        let variance = if self.count > 1 {
-            self.sum_sq_diff / (self.count - 1) as f64
-        } else {
-            0.0
-        };
+        let variance = self.running.variance();
 
         // Lower variance = higher confidence
         let confidence = if variance < 0.001 {
@@ -250,10 +252,9 @@ impl SourceStats {
         };
 
         // Use EMA for prediction after enough observations
-        let (predicted_value, model_type) = if self.count < 3 {
-            (self.last_value, PredictionModel::LastValue)
-        } else {
-            (self.ema, PredictionModel::MovingAverage)
+        let (predicted_value, model_type) = match self.ema.value() {
+            Some(ema) if count >= 3 => (ema, PredictionModel::MovingAverage),
+            _ => (self.last_value, PredictionModel::LastValue),
         };
 
         Some(Prediction {
@@ -263,22 +264,99 @@ impl SourceStats {
         })
     }
 
+    /// Keep a timestamped observation, at most `max_samples` of them
+    fn record_sample(&mut self, timestamp: u64, value: f64, max_samples: usize) {
+        if max_samples == 0 {
+            return;
+        }
+        if self.samples.len() >= max_samples {
+            let excess = self.samples.len() + 1 - max_samples;
+            self.samples.drain(..excess);
+        }
+        self.samples.push((timestamp, value));
+    }
+
     fn moving_average(&self, window: usize) -> Option<f64> {
         if self.history.is_empty() {
             return None;
         }
         let window = window.min(self.history.len());
         let start = self.history.len() - window;
-        let sum: f64 = self.history[start..].iter().sum();
+        let sum: f64 = self.history.iter().skip(start).sum();
         Some(sum / window as f64)
     }
 }
 
+/// Consecutive observations of a source at the same value
+///
+/// Each observation within [`ContextConfig::stuck_epsilon`] of the
+/// previous one extends the run; any other starts a new one. Tracked for
+/// every observation, whatever its priority weight, so a source whose
+/// readings are not sent (dead-zone, report by exception) is still seen
+/// as frozen. See [`Context::constant_run`].
+///
+/// The default run is empty (`count` 0): the next observation starts a
+/// new run.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConstantRun {
+    /// Last value of the run
+    pub value: f64,
+    /// Observations in the run
+    pub count: u64,
+    /// Timestamp of the first observation, in ms
+    pub start_ms: u64,
+    /// Timestamp of the last observation, in ms
+    pub end_ms: u64,
+}
+
+impl ConstantRun {
+    /// A run of one observation
+    pub fn new(value: f64, timestamp_ms: u64) -> Self {
+        Self {
+            value,
+            count: 1,
+            start_ms: timestamp_ms,
+            end_ms: timestamp_ms,
+        }
+    }
+
+    /// Time covered by the run, in ms
+    pub fn span_ms(&self) -> u64 {
+        self.end_ms.saturating_sub(self.start_ms)
+    }
+
+    /// The run once `value` is observed at `timestamp_ms`: extended if
+    /// within `epsilon` of the last value, a new run otherwise
+    pub fn next(&self, value: f64, timestamp_ms: u64, epsilon: f64) -> Self {
+        if self.count > 0 && (value - self.value).abs() <= epsilon {
+            Self {
+                value,
+                count: self.count.saturating_add(1),
+                start_ms: self.start_ms,
+                end_ms: timestamp_ms.max(self.end_ms),
+            }
+        } else {
+            Self::new(value, timestamp_ms)
+        }
+    }
+}
+
+/// Bytes of a [`Pattern`] kept inline, without a heap allocation
+///
+/// Covers canonical numeric patterns ([`NUMERIC_PATTERN_SIZE`] bytes).
+pub const PATTERN_INLINE_SIZE: usize = 16;
+
+/// Pattern bytes: inline up to [`PATTERN_INLINE_SIZE`] bytes, on the heap
+/// beyond
+///
+/// Dereferences to `[u8]` and converts from `Vec<u8>` and `&[u8]`.
+pub type PatternData = SmallVec<[u8; PATTERN_INLINE_SIZE]>;
+
 /// A pattern in the dictionary with usage statistics
 #[derive(Debug, Clone, PartialEq)]
 pub struct Pattern {
     /// Raw bytes of the pattern
-    pub data: Vec<u8>,
+    pub data: PatternData,
     /// Associated value (if numeric pattern)
     pub value: Option<f64>,
     /// Usage frequency counter
@@ -287,59 +365,132 @@ pub struct Pattern {
     pub last_used: u64,
     /// When the pattern was created (observation count)
     pub created_at: u64,
+    /// Highest priority of the encodes that used the pattern, if reported
+    ///
+    /// Set by [`touch_with_priority`](Self::touch_with_priority); local to
+    /// this context, neither serialized nor synchronized. See
+    /// [`EvolutionConfig::protect_priority_at_or_above`].
+    pub priority: Option<Priority>,
 }
 
 impl Pattern {
     /// Create a new pattern
+    ///
+    /// Bytes that fit inline are copied out of `data`, which is freed;
+    /// [`from_slice`](Self::from_slice) avoids the allocation.
     pub fn new(data: Vec<u8>) -> Self {
         Self {
-            data,
+            data: PatternData::from_vec(data),
             value: None,
             frequency: 1,
             last_used: 0,
             created_at: 0,
+            priority: None,
+        }
+    }
+
+    /// Create a new pattern from borrowed bytes
+    pub fn from_slice(data: &[u8]) -> Self {
+        Self {
+            data: PatternData::from_slice(data),
+            value: None,
+            frequency: 1,
+            last_used: 0,
+            created_at: 0,
+            priority: None,
         }
     }
 
     /// Create a new pattern with timestamp
     pub fn with_timestamp(data: Vec<u8>, timestamp: u64) -> Self {
         Self {
-            data,
+            data: PatternData::from_vec(data),
             value: None,
             frequency: 1,
             last_used: timestamp,
             created_at: timestamp,
+            priority: None,
         }
     }
 
-    /// Create a numeric pattern
+    /// Create a numeric pattern at the default scale factor
+    ///
+    /// The value is quantized with [`canonicalize`] and stored in the
+    /// canonical form (see [`numeric_pattern_bytes`]); `value` holds the
+    /// quantized value.
     pub fn numeric(value: f64) -> Self {
+        Self::numeric_scaled(value, crate::DEFAULT_SCALE_FACTOR)
+    }
+
+    /// Create a numeric pattern quantized at `scale`
+    pub fn numeric_scaled(value: f64, scale: u32) -> Self {
+        let data = numeric_pattern_data(value, scale);
         Self {
-            data: value.to_be_bytes().to_vec(),
-            value: Some(value),
+            value: numeric_pattern_value(&data),
+            data,
             frequency: 1,
             last_used: 0,
             created_at: 0,
+            priority: None,
         }
     }
 
     /// Create a numeric pattern with timestamp
     pub fn numeric_with_timestamp(value: f64, timestamp: u64) -> Self {
         Self {
-            data: value.to_be_bytes().to_vec(),
-            value: Some(value),
-            frequency: 1,
             last_used: timestamp,
             created_at: timestamp,
+            ..Self::numeric(value)
         }
     }
 
+    /// Create a numeric pattern in the legacy form (`f64::to_be_bytes`)
+    ///
+    /// Only for exchanging dictionaries with peers that predate canonical
+    /// numeric patterns; see [`Context::migrate_numeric_patterns`].
+    pub fn numeric_legacy(value: f64) -> Self {
+        Self {
+            data: PatternData::from_slice(&value.to_be_bytes()),
+            value: Some(value),
+            frequency: 1,
+            last_used: 0,
+            created_at: 0,
+            priority: None,
+        }
+    }
+
+    /// Numeric value readable from the pattern bytes alone
+    ///
+    /// Canonical numeric patterns always have one; legacy 8-byte patterns
+    /// are recognized by [`legacy_numeric_value`]. Import paths use it to
+    /// restore `value`, which is not serialized.
+    pub fn numeric_value_from_data(&self) -> Option<f64> {
+        numeric_pattern_value(&self.data).or_else(|| legacy_numeric_value(&self.data))
+    }
+
     /// Update usage statistics
     pub fn touch(&mut self, timestamp: u64) {
         self.frequency = self.frequency.saturating_add(1);
         self.last_used = timestamp;
     }
 
+    /// Update usage statistics for a use by an encode at `priority`
+    ///
+    /// Also raises [`priority`](Self::priority) to `priority` if that is
+    /// higher (P1 is the highest). `None` is the same as [`touch`](Self::touch).
+    pub fn touch_with_priority(&mut self, timestamp: u64, priority: Option<Priority>) {
+        self.touch(timestamp);
+        self.raise_priority(priority);
+    }
+
+    /// Keep the higher of the current and given priorities
+    pub(crate) fn raise_priority(&mut self, priority: Option<Priority>) {
+        self.priority = match (self.priority, priority) {
+            (Some(current), Some(new)) => Some(current.min(new)),
+            (current, new) => current.or(new),
+        };
+    }
+
     /// Calculate a score for this pattern (higher = more valuable)
     /// Score combines frequency and recency
     pub fn score(&self, current_time: u64) -> f64 {
@@ -373,6 +524,34 @@ pub struct EvolutionConfig {
     pub promotion_threshold: u64,
     /// Whether evolution is enabled
     pub enabled: bool,
+    /// Patterns processed per observation by a scheduled evolution
+    ///
+    /// 0 runs it at once in the triggering observation. Otherwise it is
+    /// spread over the following observations, `step_patterns` patterns
+    /// at a time, and ends with the dictionary an immediate evolution
+    /// would have produced. A cycle still pending at the next trigger is
+    /// completed at once, so keep `evolution_interval` above about twice
+    /// the dictionary size divided by `step_patterns`.
+    pub step_patterns: usize,
+    /// Patterns used by encodes at this priority or higher are never
+    /// pruned (default: `None`, no protection)
+    ///
+    /// Protects patterns of rare but critical sources from the frequency
+    /// and age thresholds of [`ContextConfig::eviction`]. They still count
+    /// against `max_patterns` and `max_memory`: evicting to honor those,
+    /// protected patterns go last, and each one that goes anyway is
+    /// counted by [`Context::protection_overrides`].
+    pub protect_priority_at_or_above: Option<Priority>,
+}
+
+impl EvolutionConfig {
+    /// Whether `pattern` is exempt from pruning
+    pub fn protects(&self, pattern: &Pattern) -> bool {
+        match (self.protect_priority_at_or_above, pattern.priority) {
+            (Some(threshold), Some(priority)) => priority <= threshold,
+            _ => false,
+        }
+    }
 }
 
 impl Default for EvolutionConfig {
@@ -383,10 +562,50 @@ impl Default for EvolutionConfig {
             evolution_interval: 100,
             promotion_threshold: 10,
             enabled: true,
+            step_patterns: 0,
+            protect_priority_at_or_above: None,
         }
     }
 }
 
+/// Hash of dictionary entries given in ascending code order
+///
+/// Shared by [`Context::hash`] and sync diff previews, which hash a
+/// dictionary without building it.
+pub(crate) fn dictionary_hash<'a>(entries: impl Iterator<Item = (u32, &'a [u8])>) -> u64 {
+    let mut hasher = Xxh64::new(0);
+    for (code, data) in entries {
+        hasher.update(&code.to_be_bytes());
+        hasher.update(&(data.len() as u16).to_be_bytes());
+        hasher.update(data);
+    }
+    hasher.digest()
+}
+
+/// [`dictionary_hash`] of a dictionary map
+fn map_hash(dictionary: &Map<u32, Pattern>) -> u64 {
+    // Codes in ascending order for a deterministic hash: the no_std
+    // `BTreeMap` already iterates that way
+    #[cfg(not(feature = "std"))]
+    {
+        dictionary_hash(
+            dictionary
+                .iter()
+                .map(|(code, pattern)| (*code, &pattern.data[..])),
+        )
+    }
+    #[cfg(feature = "std")]
+    {
+        let mut codes: Vec<u32> = dictionary.keys().copied().collect();
+        codes.sort_unstable();
+        dictionary_hash(codes.into_iter().filter_map(|code| {
+            dictionary
+                .get(&code)
+                .map(|pattern| (code, &pattern.data[..]))
+        }))
+    }
+}
+
 /// Configuration for the context
 #[derive(Debug, Clone)]
 pub struct ContextConfig {
@@ -396,10 +615,36 @@ pub struct ContextConfig {
     pub max_memory: usize,
     /// History size per source for predictions
     pub history_size: usize,
+    /// Timestamped observations kept per source for trend detection
+    /// (default: 16, 0 = none)
+    ///
+    /// See [`Context::samples`].
+    pub trend_samples: usize,
     /// EMA alpha (smoothing factor for predictions)
     pub ema_alpha: f64,
     /// Evolution configuration
     pub evolution: EvolutionConfig,
+    /// Policy ranking patterns for pruning and eviction
+    /// (default: [`FrequencyRecency`])
+    pub eviction: Box<dyn EvictionPolicy>,
+    /// Statistics weight per priority, indexed by `Priority as usize`,
+    /// applied by [`Context::observe_classified`] (default: all 1.0)
+    ///
+    /// Scales the EMA alpha and the running mean/variance steps; 0.0
+    /// leaves the statistics untouched. Both ends must use the same
+    /// weights for their predictions to agree.
+    pub priority_weights: [f64; 5],
+    /// Largest change between consecutive observations still counted as
+    /// the same value by [`Context::constant_run`] (default: 0.0,
+    /// identical values only)
+    pub stuck_epsilon: f64,
+}
+
+impl ContextConfig {
+    /// Statistics weight of a priority, clamped to 0-1
+    pub fn priority_weight(&self, priority: Priority) -> f64 {
+        self.priority_weights[priority as usize].clamp(0.0, 1.0)
+    }
 }
 
 impl Default for ContextConfig {
@@ -408,8 +653,12 @@ impl Default for ContextConfig {
             max_patterns: MAX_PATTERNS,
             max_memory: DEFAULT_MEMORY_LIMIT,
             history_size: 100,
+            trend_samples: 16,
             ema_alpha: 0.3,
             evolution: EvolutionConfig::default(),
+            eviction: Box::default(),
+            priority_weights: [1.0; 5],
+            stuck_epsilon: 0.0,
         }
     }
 }
@@ -417,8 +666,11 @@ impl Default for ContextConfig {
 /// The shared context between emitter and receiver
 #[derive(Debug, Clone)]
 pub struct Context {
-    /// Current version number
+    /// Current version number, bumped by observations and dictionary changes
     version: u32,
+    /// Dictionary version, bumped only when patterns are added, removed
+    /// or renumbered
+    dictionary_version: u32,
     /// Total observation count (used for timestamps)
     observation_count: u64,
     /// Dictionary: code -> pattern
@@ -429,10 +681,18 @@ pub struct Context {
     next_code: u32,
     /// Per-source statistics for prediction
     source_stats: Map<u32, SourceStats>,
+    /// Per-source run of identical observations
+    constant_runs: Map<u32, ConstantRun>,
+    /// Stable (device-native) ids of local sources, local id -> native id
+    source_aliases: Map<u32, u32>,
     /// Configuration
     config: ContextConfig,
     /// Scale factor for delta encoding
     scale_factor: u32,
+    /// Evolution cycle in progress
+    evolution: Option<Box<incremental::EvolutionCycle>>,
+    /// Protected patterns evicted anyway to honor the dictionary limits
+    protection_overrides: u64,
 }
 
 impl Context {
@@ -440,13 +700,18 @@ impl Context {
     pub fn new() -> Self {
         Self {
             version: 0,
+            dictionary_version: 0,
             observation_count: 0,
             dictionary: Map::new(),
             pattern_index: Map::new(),
             next_code: 0,
             source_stats: Map::new(),
+            constant_runs: Map::new(),
+            source_aliases: Map::new(),
             config: ContextConfig::default(),
             scale_factor: crate::DEFAULT_SCALE_FACTOR,
+            evolution: None,
+            protection_overrides: 0,
         }
     }
 
@@ -454,13 +719,18 @@ impl Context {
     pub fn with_config(config: ContextConfig) -> Self {
         Self {
             version: 0,
+            dictionary_version: 0,
             observation_count: 0,
             dictionary: Map::new(),
             pattern_index: Map::new(),
             next_code: 0,
             source_stats: Map::new(),
+            constant_runs: Map::new(),
+            source_aliases: Map::new(),
             config,
             scale_factor: crate::DEFAULT_SCALE_FACTOR,
+            evolution: None,
+            protection_overrides: 0,
         }
     }
 
@@ -479,32 +749,47 @@ impl Context {
     }
 
     /// Get current version
+    ///
+    /// Bumped on every observation as well as on dictionary changes, so
+    /// two peers that observed the same values agree on it. This is the
+    /// version carried in message headers; use
+    /// [`dictionary_version`](Self::dictionary_version) to compare
+    /// dictionaries.
     pub fn version(&self) -> u32 {
         self.version
     }
 
+    /// Get the dictionary version
+    ///
+    /// Only changes when patterns are added, removed or renumbered,
+    /// i.e. when [`hash`](Self::hash) may change. This is the version
+    /// context synchronization compares.
+    pub fn dictionary_version(&self) -> u32 {
+        self.dictionary_version
+    }
+
     /// Get scale factor
     pub fn scale_factor(&self) -> u32 {
         self.scale_factor
     }
 
+    /// Set the scale factor of delta encodings and numeric patterns
+    ///
+    /// Both ends must use the same scale factor; 0 is taken as 1. Set it
+    /// before numeric patterns are registered, as patterns already in the
+    /// dictionary keep the bytes of the previous scale. See
+    /// [`Encoder::precision_advice`](crate::Encoder::precision_advice) for
+    /// a per-source recommendation.
+    pub fn set_scale_factor(&mut self, scale_factor: u32) {
+        self.scale_factor = scale_factor.max(1);
+    }
+
     /// Calculate hash of the entire context for sync verification
+    ///
+    /// Streams the dictionary into the hasher in code order, without
+    /// copying pattern bytes.
     pub fn hash(&self) -> u64 {
-        let mut data = Vec::new();
-
-        // Sort codes for deterministic hash
-        let mut codes: Vec<_> = self.dictionary.keys().collect();
-        codes.sort();
-
-        for code in codes {
-            if let Some(pattern) = self.dictionary.get(code) {
-                data.extend_from_slice(&code.to_be_bytes());
-                data.extend_from_slice(&(pattern.data.len() as u16).to_be_bytes());
-                data.extend_from_slice(&pattern.data);
-            }
-        }
-
-        xxh64(&data, 0)
+        map_hash(&self.dictionary)
     }
 
     /// Get number of patterns in dictionary
@@ -522,7 +807,7 @@ impl Context {
         let dict_size: usize = self
             .dictionary
             .values()
-            .map(|p| p.data.len() + 32) // pattern data + overhead
+            .map(|p| p.data.len() + PATTERN_OVERHEAD)
             .sum();
         let stats_size = self.source_stats.len() * 200; // approximate
         dict_size + stats_size + 256 // base overhead
@@ -534,81 +819,207 @@ impl Context {
     }
 
     /// Run context evolution (pruning + reordering)
+    ///
+    /// Both steps follow [`ContextConfig::eviction`]. A dictionary holding
+    /// more than `max_patterns` is cut down to it, lowest scores first
+    /// (every insertion path already refuses to go beyond it). An
+    /// evolution cycle in progress is completed first.
     pub fn evolve(&mut self) {
-        let current_time = self.observation_count;
+        self.finish_evolution();
+        self.evolve_at(self.observation_count);
+    }
+
+    /// Evolve with patterns scored at observation count `current_time`
+    fn evolve_at(&mut self, current_time: u64) {
+        let dictionary_hash = self.hash();
 
         // 1. Prune old/unused patterns
         self.prune_patterns(current_time);
+        let excess = self
+            .dictionary
+            .len()
+            .saturating_sub(self.config.max_patterns);
+        self.remove_lowest(excess, current_time);
 
         // 2. Reorder by score (frequent patterns get lower IDs)
         self.reorder_patterns(current_time);
 
-        // 3. Increment version
+        // 3. Increment versions
         self.version += 1;
+        if self.hash() != dictionary_hash {
+            self.dictionary_version += 1;
+        }
     }
 
-    /// Prune patterns that are old or rarely used
-    fn prune_patterns(&mut self, current_time: u64) {
-        let config = &self.config.evolution;
-        let min_freq = config.min_frequency;
-        let max_age = config.max_age;
+    /// Evict the `count` lowest-scoring patterns
+    ///
+    /// Scores come from [`ContextConfig::eviction`]; among equal scores
+    /// the highest code goes first. Remaining codes are left as they are
+    /// until the next [`evolve`](Self::evolve). Returns how many patterns
+    /// were evicted.
+    pub fn evict_lowest(&mut self, count: usize) -> usize {
+        self.finish_evolution();
+        let evicted = self.remove_lowest(count, self.observation_count);
+        if evicted > 0 {
+            self.version += 1;
+            self.dictionary_version += 1;
+        }
+        evicted
+    }
+
+    /// Remove up to `count` patterns, in [`eviction_order`](Self::eviction_order)
+    fn remove_lowest(&mut self, count: usize, current_time: u64) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let ranked = self.eviction_order(current_time);
+        let evicted = count.min(ranked.len());
+        for &code in &ranked[..evicted] {
+            if let Some(pattern) = self.dictionary.remove(&code) {
+                self.pattern_index.remove(&xxh64(&pattern.data, 0));
+                if self.config.evolution.protects(&pattern) {
+                    self.protection_overrides += 1;
+                }
+            }
+        }
+        evicted
+    }
 
-        // Collect patterns to remove
-        let to_remove: Vec<u32> = self
+    /// Codes in the order they are evicted: unprotected patterns first,
+    /// then protected ones, each lowest policy score first and, among
+    /// equal scores, highest code first
+    fn eviction_order(&self, current_time: u64) -> Vec<u32> {
+        let policy = &self.config.eviction;
+        let evolution = &self.config.evolution;
+        let mut ranked: Vec<(bool, f64, u32)> = self
             .dictionary
             .iter()
-            .filter(|(_, pattern)| {
-                let age = current_time.saturating_sub(pattern.last_used);
-                pattern.frequency < min_freq || age > max_age
+            .map(|(code, pattern)| {
+                (
+                    evolution.protects(pattern),
+                    policy.score(pattern, current_time),
+                    *code,
+                )
             })
-            .map(|(code, _)| *code)
             .collect();
+        ranked.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(core::cmp::Ordering::Equal))
+                .then(b.2.cmp(&a.2))
+        });
+        ranked.into_iter().map(|(_, _, code)| code).collect()
+    }
 
-        // Remove patterns and their index entries
-        for code in to_remove {
-            if let Some(pattern) = self.dictionary.remove(&code) {
-                let hash = xxh64(&pattern.data, 0);
-                self.pattern_index.remove(&hash);
+    /// Protected patterns evicted anyway to honor `max_patterns` or
+    /// `max_memory`
+    ///
+    /// See [`EvolutionConfig::protect_priority_at_or_above`]. A non-zero
+    /// count means the limits are too tight for the protected patterns.
+    pub fn protection_overrides(&self) -> u64 {
+        self.protection_overrides
+    }
+
+    /// Prune patterns the eviction policy does not retain, except
+    /// protected ones
+    fn prune_patterns(&mut self, current_time: u64) {
+        let policy = &self.config.eviction;
+        let evolution = &self.config.evolution;
+        let index = &mut self.pattern_index;
+
+        // Drop patterns and their index entries in place
+        self.dictionary.retain(|_, pattern| {
+            let keep =
+                evolution.protects(pattern) || policy.retain(pattern, current_time, evolution);
+            if !keep {
+                index.remove(&xxh64(&pattern.data, 0));
             }
-        }
+            keep
+        });
     }
 
     /// Reorder patterns by score (best patterns get lowest IDs)
+    ///
+    /// Patterns are moved to their new codes in place, following the
+    /// cycles of the renumbering, and index entries are rewritten rather
+    /// than rehashed: no pattern is cloned or reallocated. A dictionary
+    /// already in order is left untouched.
     fn reorder_patterns(&mut self, current_time: u64) {
         if self.dictionary.is_empty() {
             return;
         }
 
-        // Collect and sort by score (descending)
-        let keys: Vec<_> = self.dictionary.keys().copied().collect();
-        let mut entries: Vec<_> = keys
-            .into_iter()
-            .filter_map(|k| self.dictionary.remove(&k).map(|v| (k, v)))
+        // Rank codes by score (descending), ties by current code
+        let policy = &self.config.eviction;
+        let mut ranked: Vec<(f64, u32)> = self
+            .dictionary
+            .iter()
+            .map(|(code, pattern)| (policy.score(pattern, current_time), *code))
             .collect();
-        entries.sort_by(|a, b| {
-            b.1.score(current_time)
-                .partial_cmp(&a.1.score(current_time))
+        ranked.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
                 .unwrap_or(core::cmp::Ordering::Equal)
+                .then(a.1.cmp(&b.1))
         });
+        self.next_code = ranked.len() as u32;
+        if ranked
+            .iter()
+            .enumerate()
+            .all(|(new_code, &(_, code))| code == new_code as u32)
+        {
+            return;
+        }
 
-        // Clear pattern index
-        self.pattern_index.clear();
+        // (old code, new code), sorted by old code for lookups
+        let mut renumber: Vec<(u32, u32)> = ranked
+            .iter()
+            .enumerate()
+            .map(|(new_code, &(_, code))| (code, new_code as u32))
+            .collect();
+        renumber.sort_unstable_by_key(|&(code, _)| code);
+        let new_code_of = |code: u32| {
+            renumber
+                .binary_search_by_key(&code, |&(old, _)| old)
+                .ok()
+                .map(|i| (i, renumber[i].1))
+        };
 
-        // Reassign IDs (best patterns get lowest IDs)
-        self.next_code = 0;
-        for (_, pattern) in entries {
-            let new_id = self.next_code;
-            let hash = xxh64(&pattern.data, 0);
-            self.pattern_index.insert(hash, new_id);
-            self.dictionary.insert(new_id, pattern);
-            self.next_code += 1;
+        // Each chain lifts one pattern out, then every insert hands back
+        // the pattern it displaces until a free code ends the chain
+        let mut moved: Vec<bool> = core::iter::repeat(false).take(renumber.len()).collect();
+        for start in 0..renumber.len() {
+            let (code, target) = renumber[start];
+            if moved[start] || code == target {
+                continue;
+            }
+            moved[start] = true;
+            let Some(mut carried) = self.dictionary.remove(&code) else {
+                continue;
+            };
+            let mut target = target;
+            while let Some(displaced) = self.dictionary.insert(target, carried) {
+                match new_code_of(target) {
+                    Some((i, next)) if !moved[i] => {
+                        moved[i] = true;
+                        carried = displaced;
+                        target = next;
+                    }
+                    // Unreachable: a displaced pattern always has a pending move
+                    _ => break,
+                }
+            }
+        }
+
+        for code in self.pattern_index.values_mut() {
+            if let Some((_, new_code)) = new_code_of(*code) {
+                *code = new_code;
+            }
         }
     }
 
     /// Observe a new data point (update statistics and trigger evolution)
-    /// Pre-allocate a `SourceStats` entry for the given `source_id`
-    /// without recording any observation. No-op if an entry already
-    /// exists.
+    /// Pre-allocate a `SourceStats` entry and an empty [`ConstantRun`]
+    /// for the given `source_id` without recording any observation.
+    /// No-op if the entries already exist.
     ///
     /// Intended to be called at encoder-creation time for targets with
     /// tight heap budgets (Cortex-M MCUs): moving the per-channel
@@ -618,40 +1029,99 @@ impl Context {
     ///
     /// Allocates:
     /// * one `BTreeMap`-node (on `no_std`) / `HashMap`-bucket (on
-    ///   `std`) entry if the key is new.
-    /// * one `Vec<f64>` with capacity `config.history_size` (length 0).
+    ///   `std`) entry per map if the key is new.
+    /// * one history ring buffer ([`WindowedStats`]) of
+    ///   `config.history_size` values (empty).
+    /// * one `Vec<(u64, f64)>` with capacity `config.trend_samples`
+    ///   (length 0), unless it is 0.
     ///
     /// Does NOT update `version` or `observation_count` — this is a
     /// pure warm-up and does not count as an observation.
     pub fn ensure_source_stats(&mut self, source_id: u32) {
         let ema_alpha = self.config.ema_alpha;
         let history_size = self.config.history_size;
+        let trend_samples = self.config.trend_samples;
         self.source_stats
             .entry(source_id)
-            .or_insert_with(|| SourceStats::new(history_size, ema_alpha));
+            .or_insert_with(|| SourceStats::new(history_size, ema_alpha, trend_samples));
+        self.constant_runs.entry(source_id).or_default();
+    }
+
+    /// Seed a source's prediction statistics with past values
+    ///
+    /// The values are folded in order, as if observed, but `version` and
+    /// `observation_count` are left untouched. Apply the same values on
+    /// both ends (e.g. a preload's `statistics.recent_values`) before the
+    /// first message so predictions are available from the start.
+    pub fn warm_source(&mut self, source_id: u32, values: &[f64]) {
+        self.ensure_source_stats(source_id);
+        if let Some(stats) = self.source_stats.get_mut(&source_id) {
+            for &value in values {
+                stats.observe(value);
+            }
+        }
     }
 
     pub fn observe(&mut self, data: &RawData) {
+        self.observe_weighted(data, 1.0);
+    }
+
+    /// Observe a classified data point, weighting its statistics by priority
+    ///
+    /// The weight comes from [`ContextConfig::priority_weights`], so a burst
+    /// of low-priority readings can be kept from dragging the predictions.
+    /// A weight of 0.0 skips the source statistics entirely; the
+    /// observation still counts towards evolution scheduling. With the
+    /// default weights this is the same as [`observe`](Self::observe).
+    pub fn observe_classified(&mut self, data: &RawData, classification: &Classification) {
+        self.observe_with_priority(data, classification.priority);
+    }
+
+    /// Observe a data point of a known priority
+    ///
+    /// Receiver-side counterpart of
+    /// [`observe_classified`](Self::observe_classified), fed with the
+    /// priority carried by the message header.
+    pub fn observe_with_priority(&mut self, data: &RawData, priority: Priority) {
+        let weight = self.config.priority_weight(priority);
+        self.observe_weighted(data, weight);
+    }
+
+    fn observe_weighted(&mut self, data: &RawData, weight: f64) {
         self.observation_count += 1;
+        let run = self.constant_run_with(data);
+        self.constant_runs.insert(data.source_id, run);
 
         // Update source statistics
-        let ema_alpha = self.config.ema_alpha;
-        let history_size = self.config.history_size;
-        let stats = self
-            .source_stats
-            .entry(data.source_id)
-            .or_insert_with(|| SourceStats::new(history_size, ema_alpha));
-
-        stats.observe(data.value);
+        if weight > 0.0 {
+            let ema_alpha = self.config.ema_alpha;
+            let history_size = self.config.history_size;
+            let trend_samples = self.config.trend_samples;
+            let stats = self
+                .source_stats
+                .entry(data.source_id)
+                .or_insert_with(|| SourceStats::new(history_size, ema_alpha, trend_samples));
+
+            stats.observe_weighted(data.value, weight);
+            stats.record_sample(data.timestamp, data.value, trend_samples);
+        }
         self.version += 1;
 
-        // Check if evolution is needed
+        // Advance or start evolution
         let evolution = &self.config.evolution;
-        if evolution.enabled
+        let step_patterns = evolution.step_patterns;
+        let due = evolution.enabled
             && evolution.evolution_interval > 0
-            && self.observation_count % evolution.evolution_interval == 0
-        {
-            self.evolve();
+            && self.observation_count % evolution.evolution_interval == 0;
+        if self.evolution_pending() {
+            self.evolution_step(step_patterns);
+        }
+        if due {
+            if step_patterns == 0 {
+                self.evolve();
+            } else {
+                self.start_evolution();
+            }
         }
     }
 
@@ -665,13 +1135,60 @@ impl Context {
         self.source_stats.get(&source_id).map(|s| s.last_value)
     }
 
+    /// Past values of a source and its EMA alpha
+    pub(crate) fn history(&self, source_id: u32) -> Option<(&WindowedStats, f64)> {
+        self.source_stats
+            .get(&source_id)
+            .map(|stats| (&stats.history, stats.ema.alpha()))
+    }
+
+    /// Observation count, EMA and EMA alpha behind a source's prediction
+    ///
+    /// An unknown source reports no observation and the configured alpha.
+    pub(crate) fn predictor_state(&self, source_id: u32) -> (u64, Option<f64>, f64) {
+        match self.source_stats.get(&source_id) {
+            Some(stats) => (stats.running.count(), stats.ema.value(), stats.ema.alpha()),
+            None => (0, None, self.config.ema_alpha),
+        }
+    }
+
     /// Get moving average for a source
     pub fn moving_average(&self, source_id: u32, window: usize) -> Option<f64> {
         self.source_stats.get(&source_id)?.moving_average(window)
     }
 
+    /// Current run of identical observations of a source
+    ///
+    /// `None` before the source's first observation.
+    pub fn constant_run(&self, source_id: u32) -> Option<ConstantRun> {
+        self.constant_runs
+            .get(&source_id)
+            .filter(|run| run.count > 0)
+            .copied()
+    }
+
+    /// Run of `data`'s source once `data` is observed
+    pub fn constant_run_with(&self, data: &RawData) -> ConstantRun {
+        match self.constant_runs.get(&data.source_id) {
+            Some(run) => run.next(data.value, data.timestamp, self.config.stuck_epsilon),
+            None => ConstantRun::new(data.value, data.timestamp),
+        }
+    }
+
+    /// Recent `(timestamp, value)` observations of a source, oldest first
+    ///
+    /// At most [`ContextConfig::trend_samples`] are kept. Values seeded
+    /// with [`warm_source`](Self::warm_source) or loaded from a preload
+    /// carry no timestamp and are not included.
+    pub fn samples(&self, source_id: u32) -> &[(u64, f64)] {
+        self.source_stats
+            .get(&source_id)
+            .map_or(&[], |stats| stats.samples.as_slice())
+    }
+
     /// Register a new pattern in the dictionary
     pub fn register_pattern(&mut self, pattern: Pattern) -> Result<u32> {
+        self.finish_evolution();
         // Check limits
         if self.dictionary.len() >= self.config.max_patterns {
             return Err(ContextError::DictionaryFull {
@@ -704,25 +1221,235 @@ impl Context {
         self.pattern_index.insert(pattern_hash, code);
         self.dictionary.insert(code, pattern);
         self.version += 1;
+        self.dictionary_version += 1;
 
         Ok(code)
     }
 
+    /// Register many patterns at once
+    ///
+    /// Meant for byte sequences known up front (model strings, unit
+    /// codes, firmware banners) so they compress from the first message
+    /// instead of waiting for evolution to discover them. Every pattern
+    /// is checked against the size, `max_patterns` and `max_memory`
+    /// limits before the dictionary is touched: either all of them are
+    /// registered or none is, and the error names the first offending
+    /// index. On success the dictionary version is bumped once.
+    ///
+    /// Returns one code per input pattern. Patterns already in the
+    /// dictionary (or repeated in `patterns`) keep a single code and
+    /// have their frequency incremented, like
+    /// [`register_pattern`](Self::register_pattern). New codes are
+    /// assigned in input order, so the same import on peers with the
+    /// same dictionary yields the same codes and hash.
+    pub fn register_patterns_bulk(
+        &mut self,
+        patterns: &[&[u8]],
+    ) -> core::result::Result<Vec<u32>, BulkError> {
+        let now = self.observation_count;
+        self.register_bulk(
+            patterns
+                .iter()
+                .map(|data| Pattern::with_timestamp(data.to_vec(), now))
+                .collect(),
+        )
+    }
+
+    /// Register numeric constants as patterns
+    ///
+    /// Numeric counterpart of
+    /// [`register_patterns_bulk`](Self::register_patterns_bulk), with the
+    /// same all-or-nothing checks and code assignment.
+    pub fn seed_from_values(
+        &mut self,
+        values: &[f64],
+    ) -> core::result::Result<Vec<u32>, BulkError> {
+        let now = self.observation_count;
+        let scale = self.scale_factor;
+        self.register_bulk(
+            values
+                .iter()
+                .map(|&value| Pattern {
+                    last_used: now,
+                    created_at: now,
+                    ..Pattern::numeric_scaled(value, scale)
+                })
+                .collect(),
+        )
+    }
+
+    fn register_bulk(
+        &mut self,
+        patterns: Vec<Pattern>,
+    ) -> core::result::Result<Vec<u32>, BulkError> {
+        self.finish_evolution();
+
+        // Validation pass: resolve codes without mutating anything
+        let mut codes = Vec::with_capacity(patterns.len());
+        let mut pending: Map<u64, u32> = Map::new();
+        let mut hashes = Vec::with_capacity(patterns.len());
+        let mut next_code = self.next_code;
+        let mut count = self.dictionary.len();
+        let mut memory = self.memory_usage();
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            let reject = |error| BulkError { index, error };
+            if pattern.data.len() > MAX_PATTERN_SIZE {
+                return Err(reject(ContextError::PatternTooLarge {
+                    size: pattern.data.len(),
+                    max: MAX_PATTERN_SIZE,
+                }));
+            }
+
+            let hash = xxh64(&pattern.data, 0);
+            hashes.push(hash);
+            if let Some(&code) = self.pattern_index.get(&hash).or_else(|| pending.get(&hash)) {
+                codes.push(code);
+                continue;
+            }
+
+            if count >= self.config.max_patterns {
+                return Err(reject(ContextError::DictionaryFull {
+                    max: self.config.max_patterns,
+                }));
+            }
+            memory += pattern.data.len() + PATTERN_OVERHEAD;
+            if memory > self.config.max_memory {
+                return Err(reject(ContextError::MemoryLimitExceeded {
+                    used: memory,
+                    limit: self.config.max_memory,
+                }));
+            }
+
+            pending.insert(hash, next_code);
+            codes.push(next_code);
+            next_code += 1;
+            count += 1;
+        }
+
+        // Commit pass
+        for ((pattern, hash), &code) in patterns.into_iter().zip(hashes).zip(&codes) {
+            if let Some(existing) = self.dictionary.get_mut(&code) {
+                existing.frequency += 1;
+            } else {
+                self.pattern_index.insert(hash, code);
+                self.dictionary.insert(code, pattern);
+            }
+        }
+
+        if next_code != self.next_code {
+            self.next_code = next_code;
+            self.version += 1;
+            self.dictionary_version += 1;
+        }
+
+        Ok(codes)
+    }
+
     /// Get pattern by code
     pub fn get_pattern(&self, code: u32) -> Option<&Pattern> {
         self.dictionary.get(&code)
     }
 
+    /// Record a use of pattern `code` by an encode at `priority`
+    ///
+    /// Touches the pattern at the current observation count (see
+    /// [`Pattern::touch_with_priority`]). Returns `false` if there is no
+    /// such pattern.
+    pub fn touch_pattern(&mut self, code: u32, priority: Option<Priority>) -> bool {
+        let now = self.observation_count;
+        match self.dictionary.get_mut(&code) {
+            Some(pattern) => {
+                pattern.touch_with_priority(now, priority);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Find pattern code by data
+    ///
+    /// Legacy numeric pattern bytes (`f64::to_be_bytes`) not found as such
+    /// are looked up in their canonical form, so callers still building
+    /// them find migrated patterns.
     pub fn find_pattern(&self, data: &[u8]) -> Option<u32> {
         let hash = xxh64(data, 0);
-        self.pattern_index.get(&hash).copied()
+        self.pattern_index.get(&hash).copied().or_else(|| {
+            let value = legacy_numeric_value(data)?;
+            let canonical = numeric_pattern_bytes(value, self.scale_factor);
+            self.pattern_index.get(&xxh64(&canonical, 0)).copied()
+        })
+    }
+
+    /// Find the numeric pattern of `value`
+    ///
+    /// Looks up the canonical form at the context's scale factor, then
+    /// the legacy form of a dictionary not migrated yet.
+    pub fn find_numeric_pattern(&self, value: f64) -> Option<u32> {
+        let canonical = numeric_pattern_bytes(value, self.scale_factor);
+        self.pattern_index
+            .get(&xxh64(&canonical, 0))
+            .or_else(|| self.pattern_index.get(&xxh64(&value.to_be_bytes(), 0)))
+            .copied()
+    }
+
+    /// Rewrite legacy numeric patterns in the canonical form
+    ///
+    /// A pattern is legacy when its 8 bytes are `f64::to_be_bytes` of its
+    /// `value` (built by [`Pattern::numeric_legacy`] or restored on import,
+    /// see [`legacy_numeric_value`]). It is requantized at the context's
+    /// scale factor and keeps its code; legacy patterns that land on the
+    /// same canonical value are merged into the lowest code, frequencies
+    /// summed. Run it on both peers: the hash then only depends on the
+    /// quantized values. Bumps the version and the dictionary version once
+    /// if anything changed, and returns the number of patterns rewritten.
+    pub fn migrate_numeric_patterns(&mut self) -> usize {
+        self.finish_evolution();
+        let mut legacy: Vec<(u32, f64)> = Vec::new();
+        for_each_sorted_u32(&self.dictionary, |code, pattern| {
+            if let Some(value) = pattern.value {
+                if pattern.data.len() == LEGACY_NUMERIC_PATTERN_SIZE
+                    && pattern.data[..] == value.to_be_bytes()
+                {
+                    legacy.push((code, value));
+                }
+            }
+        });
+
+        for &(code, value) in &legacy {
+            let Some(mut pattern) = self.dictionary.remove(&code) else {
+                continue;
+            };
+            self.pattern_index.remove(&xxh64(&pattern.data, 0));
+            let canonical = Pattern::numeric_scaled(value, self.scale_factor);
+            let hash = xxh64(&canonical.data, 0);
+            if let Some(existing) = self
+                .pattern_index
+                .get(&hash)
+                .and_then(|code| self.dictionary.get_mut(code))
+            {
+                existing.frequency = existing.frequency.saturating_add(pattern.frequency);
+                existing.last_used = existing.last_used.max(pattern.last_used);
+                continue;
+            }
+            pattern.data = canonical.data;
+            pattern.value = canonical.value;
+            self.pattern_index.insert(hash, code);
+            self.dictionary.insert(code, pattern);
+        }
+
+        if !legacy.is_empty() {
+            self.version += 1;
+            self.dictionary_version += 1;
+        }
+        legacy.len()
     }
 
     // === Synchronization helper methods ===
 
     /// Remove a pattern by ID
     pub fn remove_pattern(&mut self, id: u32) {
+        self.finish_evolution();
         if let Some(pattern) = self.dictionary.remove(&id) {
             let hash = xxh64(&pattern.data, 0);
             self.pattern_index.remove(&hash);
@@ -730,13 +1457,72 @@ impl Context {
     }
 
     /// Set a pattern at a specific ID (for sync)
-    pub fn set_pattern(&mut self, id: u32, pattern: Pattern) {
+    ///
+    /// A pattern without `value` gets the one its bytes encode, if any
+    /// (see [`Pattern::numeric_value_from_data`]).
+    ///
+    /// # Errors
+    ///
+    /// Checked like [`register_pattern`](Self::register_pattern), leaving
+    /// the dictionary untouched: [`ContextError::PatternTooLarge`] beyond
+    /// [`MAX_PATTERN_SIZE`] bytes, [`ContextError::DictionaryFull`] for a
+    /// new ID once `max_patterns` patterns are held. Replacing the pattern
+    /// at an existing ID is always within the limit.
+    pub fn set_pattern(&mut self, id: u32, mut pattern: Pattern) -> Result<()> {
+        self.finish_evolution();
+        self.check_pattern_changes(&[], core::iter::once((id, &pattern)))?;
+        if pattern.value.is_none() {
+            pattern.value = pattern.numeric_value_from_data();
+        }
         let hash = xxh64(&pattern.data, 0);
         self.pattern_index.insert(hash, id);
         self.dictionary.insert(id, pattern);
         if id >= self.next_code {
             self.next_code = id + 1;
         }
+        Ok(())
+    }
+
+    /// Check that removing the patterns at `removed`, then setting `added`
+    /// with [`set_pattern`](Self::set_pattern), stays within the limits
+    ///
+    /// Lets a sync diff be refused as a whole before any of it is applied.
+    /// Fails with [`ContextError::PatternTooLarge`] for the first pattern
+    /// beyond [`MAX_PATTERN_SIZE`] bytes, or with
+    /// [`ContextError::DictionaryFull`] when the dictionary would end up
+    /// with more than `max_patterns` patterns.
+    pub fn check_pattern_changes<'a>(
+        &self,
+        removed: &[u32],
+        added: impl IntoIterator<Item = (u32, &'a Pattern)>,
+    ) -> Result<()> {
+        let removed: BTreeSet<u32> = removed
+            .iter()
+            .copied()
+            .filter(|id| self.dictionary.contains_key(id))
+            .collect();
+        let mut new_ids = BTreeSet::new();
+        for (id, pattern) in added {
+            if pattern.data.len() > MAX_PATTERN_SIZE {
+                return Err(ContextError::PatternTooLarge {
+                    size: pattern.data.len(),
+                    max: MAX_PATTERN_SIZE,
+                }
+                .into());
+            }
+            if !self.dictionary.contains_key(&id) || removed.contains(&id) {
+                new_ids.insert(id);
+            }
+        }
+
+        let count = self.dictionary.len() - removed.len() + new_ids.len();
+        if count > self.config.max_patterns {
+            return Err(ContextError::DictionaryFull {
+                max: self.config.max_patterns,
+            }
+            .into());
+        }
+        Ok(())
     }
 
     /// Check if a pattern exists by ID
@@ -754,11 +1540,20 @@ impl Context {
         self.dictionary.keys().copied()
     }
 
-    /// Set version directly (for sync)
+    /// Set version directly
     pub fn set_version(&mut self, version: u32) {
         self.version = version;
     }
 
+    /// Set dictionary version directly (for sync)
+    ///
+    /// [`remove_pattern`](Self::remove_pattern) and
+    /// [`set_pattern`](Self::set_pattern) leave the dictionary version
+    /// alone; the sync path sets the peer's version after applying them.
+    pub fn set_dictionary_version(&mut self, version: u32) {
+        self.dictionary_version = version;
+    }
+
     /// Get iterator over pattern hashes (for fleet sync)
     pub fn pattern_hashes(&self) -> impl Iterator<Item = u64> + '_ {
         self.pattern_index.keys().copied()
@@ -768,8 +1563,8 @@ impl Context {
     pub fn export_full(&self) -> Vec<u8> {
         let mut data = Vec::new();
 
-        // Version
-        data.extend_from_slice(&self.version.to_be_bytes());
+        // Dictionary version
+        data.extend_from_slice(&self.dictionary_version.to_be_bytes());
 
         // Hash
         data.extend_from_slice(&self.hash().to_be_bytes());
@@ -795,6 +1590,11 @@ impl Context {
     }
 
     /// Import full context
+    ///
+    /// The dictionary is replaced only once the whole export has been
+    /// read and checked: truncated data, more than `max_patterns`
+    /// patterns ([`ContextError::DictionaryFull`]) or a hash mismatch
+    /// leave the current dictionary as it was.
     pub fn import_full(&mut self, data: &[u8]) -> Result<()> {
         if data.len() < 14 {
             return Err(ContextError::SyncFailed {
@@ -808,13 +1608,16 @@ impl Context {
             data[4], data[5], data[6], data[7], data[8], data[9], data[10], data[11],
         ]);
         let count = u16::from_be_bytes([data[12], data[13]]) as usize;
-
-        // Clear current dictionary
-        self.dictionary.clear();
-        self.pattern_index.clear();
-        self.next_code = 0;
+        if count > self.config.max_patterns {
+            return Err(ContextError::DictionaryFull {
+                max: self.config.max_patterns,
+            }
+            .into());
+        }
 
         // Read patterns
+        let mut dictionary: Map<u32, Pattern> = Map::new();
+        let mut pattern_index: Map<u64, u32> = Map::new();
         let mut offset = 14;
         for _ in 0..count {
             if offset + 5 > data.len() {
@@ -844,18 +1647,14 @@ impl Context {
             offset += len;
 
             let pattern_hash = xxh64(&pattern_data, 0);
-            self.dictionary.insert(code, Pattern::new(pattern_data));
-            self.pattern_index.insert(pattern_hash, code);
-
-            if code >= self.next_code {
-                self.next_code = code + 1;
-            }
+            let mut pattern = Pattern::new(pattern_data);
+            pattern.value = pattern.numeric_value_from_data();
+            dictionary.insert(code, pattern);
+            pattern_index.insert(pattern_hash, code);
         }
 
-        self.version = version;
-
         // Verify hash
-        let computed_hash = self.hash();
+        let computed_hash = map_hash(&dictionary);
         if computed_hash != hash {
             return Err(ContextError::HashMismatch {
                 expected: hash,
@@ -864,16 +1663,27 @@ impl Context {
             .into());
         }
 
+        // Replace the current dictionary
+        self.finish_evolution();
+        self.pattern_index = pattern_index;
+        self.next_code = dictionary.keys().max().map_or(0, |&code| code + 1);
+        self.dictionary = dictionary;
+        self.dictionary_version = version;
+
         Ok(())
     }
 
     /// Reset context to initial state
     pub fn reset(&mut self) {
+        self.evolution = None;
         self.dictionary.clear();
         self.pattern_index.clear();
         self.source_stats.clear();
+        self.constant_runs.clear();
+        self.source_aliases.clear();
         self.next_code = 0;
         self.version = 0;
+        self.dictionary_version = 0;
         self.observation_count = 0;
     }
 
@@ -894,6 +1704,7 @@ impl Context {
     ///   and variance state. This is the core of the recovery — the
     ///   decoder must not apply stale predictions to new Delta8 /
     ///   Delta16 bytes after a gap.
+    /// - `constant_runs`: the per-source runs of identical values.
     ///
     /// What this preserves:
     /// - `dictionary` and `pattern_index`: any preloaded patterns
@@ -915,6 +1726,7 @@ impl Context {
     /// `register_pattern()` does not create a duplicate entry.
     pub fn reset_to_baseline(&mut self) {
         self.source_stats.clear();
+        self.constant_runs.clear();
     }
 
     /// Verify hash matches
@@ -955,7 +1767,7 @@ impl Context {
     /// // ... train the context with data ...
     /// ctx.save_to_file(Path::new("temperature.alec-context"), "temperature").unwrap();
     /// ```
-    #[cfg(feature = "std")]
+    #[cfg(feature = "std-fs")]
     pub fn save_to_file(&self, path: &std::path::Path, sensor_type: &str) -> Result<()> {
         let preload = PreloadFile::from_context(self, sensor_type);
         preload.save_to_file(path)
@@ -983,29 +1795,49 @@ impl Context {
     /// let ctx = Context::load_from_file(Path::new("temperature.alec-context")).unwrap();
     /// assert!(ctx.pattern_count() > 0);
     /// ```
-    #[cfg(feature = "std")]
+    #[cfg(feature = "std-fs")]
     pub fn load_from_file(path: &std::path::Path) -> Result<Self> {
         let preload = PreloadFile::load_from_file(path)?;
         Self::from_preload(&preload)
     }
 
     /// Create a context from a preload file
+    ///
+    /// Restores the versions and dictionary. Source statistics are not
+    /// tied to a source id in the file; seed them with
+    /// [`Context::warm_source`].
     #[cfg(feature = "std")]
-    fn from_preload(preload: &PreloadFile) -> Result<Self> {
+    pub fn from_preload(preload: &PreloadFile) -> Result<Self> {
         let mut ctx = Self::new();
+        if preload.dictionary.len() > ctx.config.max_patterns {
+            return Err(ContextError::DictionaryFull {
+                max: ctx.config.max_patterns,
+            }
+            .into());
+        }
 
-        // Restore version
+        // Restore versions
         ctx.version = preload.context_version;
+        ctx.dictionary_version = preload.context_version;
 
         // Restore dictionary
         for entry in &preload.dictionary {
-            let pattern = Pattern {
-                data: entry.pattern.clone(),
+            if entry.pattern.len() > MAX_PATTERN_SIZE {
+                return Err(ContextError::PatternTooLarge {
+                    size: entry.pattern.len(),
+                    max: MAX_PATTERN_SIZE,
+                }
+                .into());
+            }
+            let mut pattern = Pattern {
+                data: PatternData::from_slice(&entry.pattern),
                 value: None,
                 frequency: entry.frequency as u64,
                 last_used: 0,
                 created_at: 0,
+                priority: None,
             };
+            pattern.value = pattern.numeric_value_from_data();
             let code = entry.code as u32;
             let hash = xxh64(&pattern.data, 0);
             ctx.pattern_index.insert(hash, code);
@@ -1020,22 +1852,23 @@ impl Context {
 
     /// Get context version for sync checking
     ///
-    /// This version should be included in message headers to allow
-    /// the decoder to verify it has the correct context.
+    /// Same as [`dictionary_version`](Self::dictionary_version):
+    /// observations don't change it, so two peers with the same
+    /// dictionary report the same version however long they've run.
     pub fn context_version(&self) -> u32 {
-        self.version
+        self.dictionary_version
     }
 
-    /// Check if context version matches expected version
+    /// Check if the dictionary version matches expected version
     ///
     /// Returns a `VersionCheckResult` indicating whether versions match
     /// or providing details about the mismatch.
     pub fn check_version(&self, message_version: u32) -> VersionCheckResult {
-        if self.version == message_version {
+        if self.dictionary_version == message_version {
             VersionCheckResult::Match
         } else {
             VersionCheckResult::Mismatch {
-                expected: self.version,
+                expected: self.dictionary_version,
                 actual: message_version,
             }
         }
@@ -1053,8 +1886,13 @@ impl Context {
     // Wire format (ALCS = "ALec Context State"):
     //
     //     magic      [4]  b"ALCS"
-    //     version    [4]  u32 LE format version (currently 1)
+    //     version    [4]  u32 LE format version: 2 if the context has
+    //                     source aliases, 1 otherwise so alias-free state
+    //                     stays readable by older releases
     //     ctx_ver    [4]  u32 LE Context::version() (full u32, not u16-truncated)
+    //                     Context::dictionary_version() is not stored: a
+    //                     restored context reports 0 until its next
+    //                     dictionary change or sync.
     //     scale      [4]  u32 LE Context::scale_factor()
     //     obs_count  [8]  u64 LE Context::observation_count()
     //     next_code  [4]  u32 LE dictionary's next code assignment
@@ -1080,6 +1918,10 @@ impl Context {
     //         frequency  [8] u64 LE
     //         last_used  [8] u64 LE
     //         created_at [8] u64 LE
+    //     alias_count [4] u32 LE number of source aliases (version 2 only)
+    //     for each alias (sorted by source_id):
+    //         source_id [4] u32 LE
+    //         native_id [4] u32 LE
     //     checksum   [4]  CRC32 (CRC_32_ISO_HDLC) over the whole buffer
     //                     up to this point, written last
     //
@@ -1092,6 +1934,15 @@ impl Context {
     // field the encoder / decoder rely on.
     // ========================================================================
 
+    /// ALCS version written for this context: the alias table needs 2
+    fn alcs_format_version(&self) -> u32 {
+        if self.source_aliases.is_empty() {
+            1
+        } else {
+            ALCS_FORMAT_VERSION
+        }
+    }
+
     /// Serialize this context to a self-contained byte buffer.
     ///
     /// Intended for per-DevEUI sidecar state persistence (Redis etc.).
@@ -1164,6 +2015,10 @@ impl Context {
             //      + last_used(8) + created_at(8) = 30 B + data.
             total += 30 + data_len;
         }
+        if !self.source_aliases.is_empty() {
+            // alias_count + (source_id + native_id) per alias
+            total += 4 + self.source_aliases.len() * 8;
+        }
         total += 4; // trailing CRC32
         Ok(total)
     }
@@ -1221,7 +2076,7 @@ impl Context {
         // Magic + format version.
         out[w..w + 4].copy_from_slice(ALCS_MAGIC);
         w += 4;
-        out[w..w + 4].copy_from_slice(&ALCS_FORMAT_VERSION.to_le_bytes());
+        out[w..w + 4].copy_from_slice(&self.alcs_format_version().to_le_bytes());
         w += 4;
         // Core context scalars.
         out[w..w + 4].copy_from_slice(&self.version.to_le_bytes());
@@ -1267,6 +2122,17 @@ impl Context {
             write_pattern_into(out, &mut w, code, p);
         });
 
+        // === Source aliases (version 2) ===
+        if !self.source_aliases.is_empty() {
+            out[w..w + 4].copy_from_slice(&(self.source_aliases.len() as u32).to_le_bytes());
+            w += 4;
+            for_each_sorted_u32(&self.source_aliases, |sid, native| {
+                out[w..w + 4].copy_from_slice(&sid.to_le_bytes());
+                out[w + 4..w + 8].copy_from_slice(&native.to_le_bytes());
+                w += 8;
+            });
+        }
+
         // === Trailing CRC32 ===
         // The `crc` crate's Crc<u32> holds a 1 KB lookup table; the
         // `const` hoists it into rodata so no stack copy is made on
@@ -1308,7 +2174,7 @@ impl Context {
             return false;
         }
         let format_version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-        if format_version != ALCS_FORMAT_VERSION {
+        if !(1..=ALCS_FORMAT_VERSION).contains(&format_version) {
             return false;
         }
 
@@ -1336,6 +2202,9 @@ impl Context {
     /// * `DecodeError::BufferTooShort` / `InvalidHeader` / `MalformedMessage`
     ///   for structural problems.
     /// * `DecodeError::InvalidChecksum` if the CRC32 does not match.
+    /// * `ContextError::DictionaryFull` / `PatternTooLarge` for more than
+    ///   [`MAX_PATTERNS`] patterns or a pattern beyond [`MAX_PATTERN_SIZE`]
+    ///   bytes.
     pub fn from_preload_bytes(data: &[u8]) -> Result<Self> {
         use crc::{Crc, CRC_32_ISO_HDLC};
         const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
@@ -1367,7 +2236,7 @@ impl Context {
         }
 
         let format_version = u32::from_le_bytes(data[4..8].try_into().unwrap());
-        if format_version != ALCS_FORMAT_VERSION {
+        if !(1..=ALCS_FORMAT_VERSION).contains(&format_version) {
             return Err(crate::error::DecodeError::MalformedMessage {
                 offset: 4,
                 reason: {
@@ -1458,26 +2327,20 @@ impl Context {
                 }
                 .into());
             }
-            let mut history: Vec<f64> = Vec::with_capacity(hist_len);
-            for i in 0..hist_len {
-                let hv = f64::from_le_bytes(
-                    data[offset + i * 8..offset + i * 8 + 8].try_into().unwrap(),
-                );
-                history.push(hv);
-            }
+            let mut history = WindowedStats::new(max_history);
+            history.extend((0..hist_len).map(|i| {
+                f64::from_le_bytes(data[offset + i * 8..offset + i * 8 + 8].try_into().unwrap())
+            }));
             offset += hist_bytes;
 
             source_stats.insert(
                 source_id,
                 SourceStats {
                     last_value,
-                    ema,
-                    ema_alpha,
-                    count,
-                    sum_sq_diff,
-                    mean,
+                    ema: Ema::with_value(ema_alpha, (count > 0).then_some(ema)),
+                    running: RunningStats::from_parts(count, mean, sum_sq_diff),
                     history,
-                    max_history,
+                    samples: Vec::new(),
                 },
             );
         }
@@ -1492,6 +2355,9 @@ impl Context {
         }
         let dict_count = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
         offset += 4;
+        if dict_count > MAX_PATTERNS {
+            return Err(ContextError::DictionaryFull { max: MAX_PATTERNS }.into());
+        }
 
         let mut dictionary: Map<u32, Pattern> = Map::new();
         let mut pattern_index: Map<u64, u32> = Map::new();
@@ -1508,6 +2374,13 @@ impl Context {
             let data_len =
                 u16::from_le_bytes(data[offset + 4..offset + 6].try_into().unwrap()) as usize;
             offset += 6;
+            if data_len > MAX_PATTERN_SIZE {
+                return Err(ContextError::PatternTooLarge {
+                    size: data_len,
+                    max: MAX_PATTERN_SIZE,
+                }
+                .into());
+            }
 
             // Fixed post-data part: 24 bytes (frequency 8 + last_used 8 + created_at 8).
             if offset + data_len + 24 > crc_offset {
@@ -1517,7 +2390,7 @@ impl Context {
                 }
                 .into());
             }
-            let pattern_bytes = data[offset..offset + data_len].to_vec();
+            let pattern_bytes = PatternData::from_slice(&data[offset..offset + data_len]);
             offset += data_len;
 
             let frequency = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
@@ -1526,17 +2399,47 @@ impl Context {
             offset += 24;
 
             let hash = xxh64(&pattern_bytes, 0);
+            let mut pattern = Pattern {
+                data: pattern_bytes,
+                value: None,
+                frequency,
+                last_used,
+                created_at,
+                priority: None,
+            };
+            pattern.value = pattern.numeric_value_from_data();
             pattern_index.insert(hash, code);
-            dictionary.insert(
-                code,
-                Pattern {
-                    data: pattern_bytes,
-                    value: None,
-                    frequency,
-                    last_used,
-                    created_at,
-                },
-            );
+            dictionary.insert(code, pattern);
+        }
+
+        // === Source aliases (version 2) ===
+        let mut source_aliases: Map<u32, u32> = Map::new();
+        if format_version >= 2 {
+            if offset + 4 > crc_offset {
+                return Err(crate::error::DecodeError::BufferTooShort {
+                    needed: offset + 4,
+                    available: data.len(),
+                }
+                .into());
+            }
+            let alias_count =
+                u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+            let alias_bytes = alias_count.saturating_mul(8);
+            if offset + alias_bytes > crc_offset {
+                return Err(crate::error::DecodeError::BufferTooShort {
+                    needed: offset + alias_bytes,
+                    available: data.len(),
+                }
+                .into());
+            }
+            for _ in 0..alias_count {
+                let source_id = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+                let native_id =
+                    u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap());
+                source_aliases.insert(source_id, native_id);
+                offset += 8;
+            }
         }
 
         // If `offset` != crc_offset here, the buffer has trailing bytes
@@ -1564,13 +2467,18 @@ impl Context {
 
         Ok(Self {
             version,
+            dictionary_version: 0,
             observation_count,
             dictionary,
             pattern_index,
             next_code,
             source_stats,
+            constant_runs: Map::new(),
+            source_aliases,
             config: ContextConfig::default(),
             scale_factor,
+            evolution: None,
+            protection_overrides: 0,
         })
     }
 }
@@ -1581,7 +2489,10 @@ impl Context {
 pub const ALCS_MAGIC: &[u8; 4] = b"ALCS";
 
 /// Current ALCS format version. Increment on any wire-level change.
-pub const ALCS_FORMAT_VERSION: u32 = 1;
+///
+/// Version 2 adds the source alias table. Contexts without aliases are
+/// still written as version 1, and both versions are read.
+pub const ALCS_FORMAT_VERSION: u32 = 2;
 
 impl Default for Context {
     fn default() -> Self {
@@ -1590,7 +2501,7 @@ impl Default for Context {
 }
 
 // HealthCheckable implementation for Context
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 impl crate::health::HealthCheckable for Context {
     fn health_check(&self) -> crate::health::HealthCheck {
         use crate::health::{HealthCheck, HealthStatus};
@@ -1795,6 +2706,8 @@ mod tests {
                 evolution_interval: 10,
                 promotion_threshold: 5,
                 enabled: false, // Manual control
+                step_patterns: 0,
+                protect_priority_at_or_above: None,
             },
             ..ContextConfig::default()
         };
@@ -1826,6 +2739,8 @@ mod tests {
                 evolution_interval: 10,
                 promotion_threshold: 5,
                 enabled: false,
+                step_patterns: 0,
+                protect_priority_at_or_above: None,
             },
             ..ContextConfig::default()
         };
@@ -1909,6 +2824,8 @@ mod tests {
                 evolution_interval: 5, // Evolve every 5 observations
                 promotion_threshold: 5,
                 enabled: true,
+                step_patterns: 0,
+                protect_priority_at_or_above: None,
             },
             ..ContextConfig::default()
         };
@@ -1930,6 +2847,39 @@ mod tests {
         assert_eq!(ctx.observation_count(), 5);
     }
 
+    #[test]
+    fn test_dictionary_version_tracks_dictionary_only() {
+        let mut ctx = Context::new();
+
+        // Observations (and the evolutions they trigger) leave an empty
+        // dictionary untouched
+        for i in 0..1000 {
+            ctx.observe(&RawData::new(20.0 + (i % 7) as f64, i));
+        }
+        assert_eq!(ctx.version(), 1000 + 10);
+        assert_eq!(ctx.dictionary_version(), 0);
+
+        // A new pattern bumps it, a known one doesn't
+        ctx.register_pattern(Pattern::new(vec![1, 2, 3])).unwrap();
+        ctx.register_pattern(Pattern::new(vec![1, 2, 3])).unwrap();
+        assert_eq!(ctx.dictionary_version(), 1);
+
+        // Evolution that keeps the dictionary as is doesn't
+        ctx.evolve();
+        assert_eq!(ctx.dictionary_version(), 1);
+
+        // Evolution that prunes a pattern does
+        ctx.register_pattern(Pattern::new(vec![4, 5, 6])).unwrap();
+        assert_eq!(ctx.dictionary_version(), 2);
+        ctx.evolve();
+        assert_eq!(ctx.pattern_count(), 1);
+        assert_eq!(ctx.dictionary_version(), 3);
+        assert_eq!(ctx.context_version(), ctx.dictionary_version());
+
+        ctx.reset();
+        assert_eq!(ctx.dictionary_version(), 0);
+    }
+
     #[test]
     fn test_pattern_score() {
         let mut pattern = Pattern::new(vec![1, 2, 3]);
@@ -1991,13 +2941,13 @@ mod tests {
         for sid in [1u32, 2] {
             let a = ctx.source_stats.get(&sid).unwrap();
             let b = restored.source_stats.get(&sid).unwrap();
-            assert_eq!(a.count, b.count, "sid {} count", sid);
+            assert_eq!(a.running.count(), b.running.count(), "sid {} count", sid);
             assert!(a.last_value.to_bits() == b.last_value.to_bits());
-            assert!(a.ema.to_bits() == b.ema.to_bits());
-            assert!(a.ema_alpha.to_bits() == b.ema_alpha.to_bits());
-            assert!(a.sum_sq_diff.to_bits() == b.sum_sq_diff.to_bits());
-            assert!(a.mean.to_bits() == b.mean.to_bits());
-            assert_eq!(a.max_history, b.max_history);
+            assert!(a.ema.value().unwrap().to_bits() == b.ema.value().unwrap().to_bits());
+            assert!(a.ema.alpha().to_bits() == b.ema.alpha().to_bits());
+            assert!(a.running.m2().to_bits() == b.running.m2().to_bits());
+            assert!(a.running.mean().to_bits() == b.running.mean().to_bits());
+            assert_eq!(a.history.capacity(), b.history.capacity());
             assert_eq!(a.history.len(), b.history.len());
             for (x, y) in a.history.iter().zip(b.history.iter()) {
                 assert_eq!(x.to_bits(), y.to_bits(), "sid {} history", sid);
@@ -0,0 +1,165 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Canonical numeric patterns
+//!
+//! A numeric pattern used to store `f64::to_be_bytes` of its value, so two
+//! peers computing "the same" value through different float paths (scale
+//! then round on one side, truncate on the other) stored different bytes
+//! and disagreed on [`Context::hash`](super::Context::hash). Numeric
+//! patterns are now quantized with [`canonicalize`] and stored as
+//! [`NUMERIC_PATTERN_SIZE`] bytes, all big-endian:
+//!
+//! ```text
+//! [NUMERIC_PATTERN_TAG] [scale: u32] [quantized: i64]
+//! ```
+//!
+//! Patterns in the legacy 8-byte form are recognized on import
+//! ([`legacy_numeric_value`]) and rewritten by
+//! [`Context::migrate_numeric_patterns`](super::Context::migrate_numeric_patterns).
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::PatternData;
+
+/// First byte of a canonical numeric pattern
+pub const NUMERIC_PATTERN_TAG: u8 = 0xC5;
+
+/// Size of a canonical numeric pattern: tag, scale and quantized value
+pub const NUMERIC_PATTERN_SIZE: usize = 13;
+
+/// Size of a legacy numeric pattern (`f64::to_be_bytes`)
+pub const LEGACY_NUMERIC_PATTERN_SIZE: usize = 8;
+
+/// Quantize `value` to an integer number of `1 / scale` steps
+///
+/// The product `value * scale` is computed once in `f64` (exact IEEE 754
+/// rounding, identical on every platform) and rounded half away from
+/// zero, so `0.125` at scale 100 gives 13. Decimal values with no exact
+/// binary form round by the product: `1.005` is stored as
+/// `1.00499999999999989…` and gives 100. `-0.0` gives 0, infinities and
+/// magnitudes beyond `i64` saturate, and `NaN` gives 0.
+pub fn canonicalize(value: f64, scale: u32) -> i64 {
+    let product = value * scale as f64;
+    // `as` saturates and maps NaN to 0
+    let truncated = product as i64;
+    let fraction = product - truncated as f64;
+    if fraction >= 0.5 {
+        truncated.saturating_add(1)
+    } else if fraction <= -0.5 {
+        truncated.saturating_sub(1)
+    } else {
+        truncated
+    }
+}
+
+/// Canonical pattern bytes of `value` at `scale`
+pub fn numeric_pattern_bytes(value: f64, scale: u32) -> Vec<u8> {
+    numeric_pattern_data(value, scale).to_vec()
+}
+
+/// [`numeric_pattern_bytes`] stored inline, without a heap allocation
+pub(crate) fn numeric_pattern_data(value: f64, scale: u32) -> PatternData {
+    let mut data = PatternData::new();
+    data.push(NUMERIC_PATTERN_TAG);
+    data.extend_from_slice(&scale.to_be_bytes());
+    data.extend_from_slice(&canonicalize(value, scale).to_be_bytes());
+    data
+}
+
+/// Value of a canonical numeric pattern, `None` for any other bytes
+pub fn numeric_pattern_value(data: &[u8]) -> Option<f64> {
+    if data.len() != NUMERIC_PATTERN_SIZE || data[0] != NUMERIC_PATTERN_TAG {
+        return None;
+    }
+    let scale = u32::from_be_bytes(data[1..5].try_into().ok()?);
+    let quantized = i64::from_be_bytes(data[5..].try_into().ok()?);
+    if scale == 0 {
+        return None;
+    }
+    Some(quantized as f64 / scale as f64)
+}
+
+/// Value of a legacy numeric pattern (`f64::to_be_bytes`)
+///
+/// Only zero and normal finite values are recognized: byte patterns such
+/// as big-endian small integers would read as subnormals and are left
+/// alone. Any other 8-byte pattern may still be mistaken for a number.
+pub fn legacy_numeric_value(data: &[u8]) -> Option<f64> {
+    let bytes: [u8; LEGACY_NUMERIC_PATTERN_SIZE] = data.try_into().ok()?;
+    let value = f64::from_be_bytes(bytes);
+    (value == 0.0 || value.is_normal()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_signed_zero() {
+        assert_eq!(canonicalize(-0.0, 100), 0);
+        assert_eq!(
+            numeric_pattern_bytes(-0.0, 100),
+            numeric_pattern_bytes(0.0, 100)
+        );
+        assert_ne!((-0.0f64).to_be_bytes(), 0.0f64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_canonicalize_rounding_boundaries() {
+        // Exact binary ties round away from zero
+        assert_eq!(canonicalize(0.125, 100), 13);
+        assert_eq!(canonicalize(-0.125, 100), -13);
+        assert_eq!(canonicalize(0.5, 1), 1);
+        assert_eq!(canonicalize(-2.5, 1), -3);
+        // Decimal .005 values round by their scaled binary value
+        assert_eq!(canonicalize(1.005, 100), 100);
+        assert_eq!(canonicalize(1.015, 100), 101);
+        // 2.6749999… * 100 rounds to exactly 267.5
+        assert_eq!(canonicalize(2.675, 100), 268);
+        // Values a float path apart agree
+        assert_eq!(canonicalize(0.1 + 0.2, 100), canonicalize(0.3, 100));
+        assert_eq!(canonicalize(21.49999, 100), 2150);
+        assert_eq!(canonicalize(-21.004, 100), -2100);
+    }
+
+    #[test]
+    fn test_canonicalize_large_magnitudes() {
+        assert_eq!(canonicalize(1e15, 100), 100_000_000_000_000_000);
+        assert_eq!(canonicalize(1e300, 100), i64::MAX);
+        assert_eq!(canonicalize(-1e300, 100), i64::MIN);
+        assert_eq!(canonicalize(f64::INFINITY, 100), i64::MAX);
+        assert_eq!(canonicalize(f64::NEG_INFINITY, 100), i64::MIN);
+        assert_eq!(canonicalize(f64::NAN, 100), 0);
+        // 2^53 and beyond are integers: no fractional step
+        assert_eq!(
+            canonicalize(9_007_199_254_740_993.0, 1),
+            9_007_199_254_740_992
+        );
+    }
+
+    #[test]
+    fn test_numeric_pattern_roundtrip() {
+        let data = numeric_pattern_bytes(-40.256, 100);
+        assert_eq!(data.len(), NUMERIC_PATTERN_SIZE);
+        assert_eq!(data[0], NUMERIC_PATTERN_TAG);
+        assert_eq!(&data[1..5], &100u32.to_be_bytes());
+        assert_eq!(numeric_pattern_value(&data), Some(-40.26));
+        assert_eq!(numeric_pattern_value(&data[..12]), None);
+        assert_eq!(numeric_pattern_value(&(21.5f64).to_be_bytes()), None);
+    }
+
+    #[test]
+    fn test_legacy_numeric_value() {
+        assert_eq!(legacy_numeric_value(&21.5f64.to_be_bytes()), Some(21.5));
+        assert_eq!(legacy_numeric_value(&(-0.0f64).to_be_bytes()), Some(-0.0));
+        // Small big-endian integers read as subnormals
+        assert_eq!(legacy_numeric_value(&7u64.to_be_bytes()), None);
+        assert_eq!(legacy_numeric_value(&f64::NAN.to_be_bytes()), None);
+        assert_eq!(legacy_numeric_value(&[1, 2, 3]), None);
+    }
+}
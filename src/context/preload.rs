@@ -31,12 +31,12 @@ use alloc::{
     vec::Vec,
 };
 
-#[cfg(feature = "std")]
+#[cfg(feature = "std-fs")]
 use crate::error::ContextError;
 use crate::error::{AlecError, DecodeError};
-#[cfg(feature = "std")]
+#[cfg(feature = "std-fs")]
 use std::io::{Read, Write};
-#[cfg(feature = "std")]
+#[cfg(feature = "std-fs")]
 use std::path::Path;
 
 /// Magic bytes for ALEC preload files
@@ -384,7 +384,7 @@ impl PreloadFile {
         for (&code, pattern) in ctx.patterns_iter() {
             if code <= u16::MAX as u32 {
                 dictionary.push(PreloadDictEntry {
-                    pattern: pattern.data.clone(),
+                    pattern: pattern.data.to_vec(),
                     code: code as u16,
                     frequency: pattern.frequency as u32,
                 });
@@ -407,17 +407,17 @@ impl PreloadFile {
 
         Self {
             format_version: PRELOAD_FORMAT_VERSION,
-            context_version: ctx.version(),
+            context_version: ctx.dictionary_version(),
             sensor_type: sensor_type.to_string(),
             created_timestamp: {
-                #[cfg(feature = "std")]
+                #[cfg(feature = "std-time")]
                 {
                     std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
                         .map(|d| d.as_secs())
                         .unwrap_or(0)
                 }
-                #[cfg(not(feature = "std"))]
+                #[cfg(not(feature = "std-time"))]
                 {
                     0
                 }
@@ -585,7 +585,7 @@ impl PreloadFile {
     }
 
     /// Save preload to a file
-    #[cfg(feature = "std")]
+    #[cfg(feature = "std-fs")]
     pub fn save_to_file(&self, path: &Path) -> Result<(), AlecError> {
         let bytes = self.to_bytes();
         let mut file = std::fs::File::create(path).map_err(|e| ContextError::SyncFailed {
@@ -599,7 +599,7 @@ impl PreloadFile {
     }
 
     /// Load preload from a file
-    #[cfg(feature = "std")]
+    #[cfg(feature = "std-fs")]
     pub fn load_from_file(path: &Path) -> Result<Self, AlecError> {
         let mut file = std::fs::File::open(path).map_err(|e| ContextError::SyncFailed {
             reason: format!("Failed to open preload file: {}", e),
@@ -0,0 +1,169 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Renumbering the sources of a context
+//!
+//! A gateway keys its source statistics by compact ids it assigns itself,
+//! to keep headers short, while devices and backends know the sources by
+//! their native 32-bit ids. When a context moves between them the keys
+//! must be translated:
+//! - [`Context::remap_sources`] rewrites the keys with an explicit map,
+//!   refusing collisions or merging the collided statistics
+//! - Source aliases record the native id of each local source; they are
+//!   written by [`Context::to_preload_bytes`] (ALCS version 2), so the
+//!   importer can call [`Context::translate_sources`] with its own
+//!   native-to-local mapping
+//!
+//! Encoders and decoders never see either: only the context keys change.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::{for_each_sorted_u32, ConstantRun, Context, Map, MergeStrategy, SourceStats};
+use crate::error::{ContextError, Result};
+
+/// Outcome of a [`Context::remap_sources`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemapReport {
+    /// Sources whose id changed
+    pub sources_remapped: usize,
+    /// Sources folded into another source that landed on the same id
+    pub sources_merged: usize,
+    /// Map entries naming a source this context does not track
+    pub unknown_sources: usize,
+}
+
+impl Context {
+    /// Rewrite source ids with `map` (old id -> new id)
+    ///
+    /// Sources missing from `map` keep their id. Aliases move with their
+    /// source, the first one in ascending id order winning on collisions. When several sources end up on the same id the remap is
+    /// refused with [`ContextError::SourceCollision`] and the context is
+    /// left untouched, unless `merge` is given: the collided statistics
+    /// are then combined as in [`Context::merge`], folded in ascending
+    /// original id order, with `merge` deciding between the statistics
+    /// already on the id and the incoming ones.
+    ///
+    /// Swaps such as `{1 -> 2, 2 -> 1}` are not collisions. The version
+    /// is bumped if any source or alias changed id.
+    pub fn remap_sources(
+        &mut self,
+        map: &Map<u32, u32>,
+        merge: Option<MergeStrategy>,
+    ) -> Result<RemapReport> {
+        let mut report = RemapReport {
+            unknown_sources: map
+                .keys()
+                .filter(|id| !self.source_stats.contains_key(id))
+                .count(),
+            ..Default::default()
+        };
+
+        // (old id, new id), in ascending old id order
+        let mut moves: Vec<(u32, u32)> = Vec::with_capacity(self.source_stats.len());
+        for_each_sorted_u32(&self.source_stats, |id, _| {
+            moves.push((id, map.get(&id).copied().unwrap_or(id)));
+        });
+        report.sources_remapped = moves.iter().filter(|(old, new)| old != new).count();
+        let aliases_moved = self
+            .source_aliases
+            .keys()
+            .any(|id| map.get(id).is_some_and(|new| new != id));
+        if report.sources_remapped == 0 && !aliases_moved {
+            return Ok(report);
+        }
+
+        let strategy = match merge {
+            Some(strategy) => strategy,
+            None => {
+                let mut targets: Vec<u32> = moves.iter().map(|&(_, new)| new).collect();
+                targets.sort_unstable();
+                if let Some(pair) = targets.windows(2).find(|pair| pair[0] == pair[1]) {
+                    let source_id = pair[0];
+                    return Err(ContextError::SourceCollision {
+                        source_id,
+                        sources: targets.iter().filter(|&&id| id == source_id).count(),
+                    }
+                    .into());
+                }
+                MergeStrategy::default()
+            }
+        };
+
+        let trend_samples = self.config.trend_samples;
+        let mut source_stats: Map<u32, SourceStats> = Map::new();
+        for &(old, new) in &moves {
+            let Some(stats) = self.source_stats.remove(&old) else {
+                continue;
+            };
+            match source_stats.get_mut(&new) {
+                Some(existing) => {
+                    existing.merge(&stats, strategy, trend_samples);
+                    report.sources_merged += 1;
+                }
+                None => {
+                    source_stats.insert(new, stats);
+                }
+            }
+        }
+        self.source_stats = source_stats;
+
+        // Runs of merged sources cannot be combined: they start over
+        let mut constant_runs: Map<u32, ConstantRun> = Map::new();
+        let mut merged = Vec::new();
+        for_each_sorted_u32(&self.constant_runs, |old, run| {
+            let new = map.get(&old).copied().unwrap_or(old);
+            if constant_runs.insert(new, *run).is_some() {
+                merged.push(new);
+            }
+        });
+        for id in merged {
+            constant_runs.remove(&id);
+        }
+        self.constant_runs = constant_runs;
+
+        let mut source_aliases = Map::new();
+        for_each_sorted_u32(&self.source_aliases, |old, &native_id| {
+            let new = map.get(&old).copied().unwrap_or(old);
+            source_aliases.entry(new).or_insert(native_id);
+        });
+        self.source_aliases = source_aliases;
+
+        self.version += 1;
+        Ok(report)
+    }
+
+    /// Translate source ids through their aliases and the importer's
+    /// `native_to_local` mapping
+    ///
+    /// Each aliased source whose native id is in `native_to_local` moves
+    /// to the local id it maps to, keeping its alias; other sources keep
+    /// their id. Collisions are handled as in
+    /// [`remap_sources`](Self::remap_sources).
+    pub fn translate_sources(
+        &mut self,
+        native_to_local: &Map<u32, u32>,
+        merge: Option<MergeStrategy>,
+    ) -> Result<RemapReport> {
+        let mut map = Map::new();
+        for_each_sorted_u32(&self.source_aliases, |id, native_id| {
+            if let Some(&local) = native_to_local.get(native_id) {
+                map.insert(id, local);
+            }
+        });
+        self.remap_sources(&map, merge)
+    }
+
+    /// Record `native_id` as the stable id of source `source_id`
+    pub fn set_source_alias(&mut self, source_id: u32, native_id: u32) {
+        self.source_aliases.insert(source_id, native_id);
+    }
+
+    /// Stable id recorded for source `source_id`
+    pub fn source_alias(&self, source_id: u32) -> Option<u32> {
+        self.source_aliases.get(&source_id).copied()
+    }
+}
@@ -0,0 +1,450 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Preload training from historical data
+//!
+//! The [`Trainer`] collects samples (from [`RawData`] slices or a CSV in
+//! the `alec-testdata` layout) and builds a [`PreloadFile`] from them:
+//!
+//! 1. With two passes, a first pass learns the value distribution and
+//!    picks the dictionary quantization step.
+//! 2. The next pass runs the samples through a context (prediction
+//!    model) and fills the dictionary with the most frequent quantized
+//!    values.
+//!
+//! The last `holdout_fraction` of the samples is kept out of training and
+//! re-encoded with a context loaded from the preload, giving the expected
+//! compression ratio in the [`TrainingReport`].
+//!
+//! # Example
+//!
+//! ```
+//! use alec::context::trainer::{Trainer, TrainerConfig};
+//! use alec::context::Context;
+//! use alec::RawData;
+//!
+//! let samples: Vec<RawData> = (0..1000)
+//!     .map(|i| RawData::with_source(1, 20.0 + (i as f64 * 0.05).sin(), i * 1000))
+//!     .collect();
+//!
+//! let mut trainer = Trainer::new(TrainerConfig::default());
+//! trainer.feed(&samples);
+//! let preload = trainer.finish("temperature").unwrap();
+//! assert!(trainer.report().unwrap().expected_compression_ratio > 1.0);
+//!
+//! let mut context = Context::from_preload(&preload).unwrap();
+//! context.warm_source(1, &preload.statistics.recent_values);
+//! ```
+
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use super::{Context, ContextConfig, EvolutionConfig, Pattern, PreloadFile};
+use crate::classifier::Classifier;
+use crate::encoder::Encoder;
+use crate::error::{ContextError, DecodeError, Result};
+use crate::protocol::RawData;
+
+/// Maximum warm-up values stored in a preload (length is a `u8`)
+const MAX_RECENT_VALUES: usize = u8::MAX as usize;
+
+/// Configuration for a [`Trainer`]
+#[derive(Debug, Clone)]
+pub struct TrainerConfig {
+    /// Number of dictionary patterns to keep
+    pub target_patterns: usize,
+    /// Evolution settings of the training context
+    pub evolution: EvolutionConfig,
+    /// Finest value resolution of dictionary patterns (steps per unit)
+    pub scale_factor: u32,
+    /// Training passes: 1 quantizes at `1 / scale_factor`, 2 first learns
+    /// the value distribution to spread patterns over the observed range
+    pub passes: u8,
+    /// Fraction of the samples, taken from the end, held out for evaluation
+    pub holdout_fraction: f64,
+}
+
+impl Default for TrainerConfig {
+    fn default() -> Self {
+        Self {
+            target_patterns: 64,
+            evolution: EvolutionConfig::default(),
+            scale_factor: crate::DEFAULT_SCALE_FACTOR,
+            passes: 2,
+            holdout_fraction: 0.1,
+        }
+    }
+}
+
+/// Metrics of the last [`Trainer::finish`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingReport {
+    /// Samples used for training
+    pub training_samples: usize,
+    /// Samples held out for evaluation
+    pub holdout_samples: usize,
+    /// Passes run over the training samples
+    pub passes: u8,
+    /// Quantization step of dictionary patterns
+    pub quantization_step: f64,
+    /// Patterns written to the dictionary
+    pub patterns: usize,
+    /// Mean of the training values
+    pub mean: f64,
+    /// Standard deviation of the training values
+    pub std_dev: f64,
+    /// Raw size of the holdout samples in bytes
+    pub holdout_raw_bytes: usize,
+    /// Encoded size of the holdout samples in bytes
+    pub holdout_encoded_bytes: usize,
+    /// Expected compression ratio (raw / encoded) on the holdout slice
+    pub expected_compression_ratio: f64,
+}
+
+/// Maps CSV columns to source ids
+///
+/// Matches the `alec-testdata` CSV layout by default: a `timestamp_ms`
+/// column followed by one column per sensor, empty cells for missing
+/// values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMapping {
+    /// Name of the timestamp column (milliseconds)
+    pub timestamp_column: String,
+    /// Value columns and the source id their samples get
+    pub columns: Vec<(String, u32)>,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            timestamp_column: "timestamp_ms".to_string(),
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// Create a mapping with the `timestamp_ms` timestamp column
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a value column to a source id
+    pub fn column(mut self, name: impl Into<String>, source_id: u32) -> Self {
+        self.columns.push((name.into(), source_id));
+        self
+    }
+}
+
+/// Builds preload files from historical samples
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    /// Configuration
+    config: TrainerConfig,
+    /// Samples fed so far, in order
+    samples: Vec<RawData>,
+    /// Metrics of the last finish
+    report: Option<TrainingReport>,
+}
+
+impl Trainer {
+    /// Create a trainer
+    pub fn new(config: TrainerConfig) -> Self {
+        Self {
+            config,
+            samples: Vec::new(),
+            report: None,
+        }
+    }
+
+    /// Add samples, in time order
+    pub fn feed(&mut self, samples: &[RawData]) {
+        self.samples.extend_from_slice(samples);
+    }
+
+    /// Add samples from CSV, returning how many were read
+    ///
+    /// Rows are read in order; each non-empty mapped cell becomes one
+    /// sample. Parse errors report the 1-based line number as offset.
+    pub fn feed_csv<R: BufRead>(&mut self, reader: R, mapping: &ColumnMapping) -> Result<usize> {
+        let malformed = |line: usize, reason: String| DecodeError::MalformedMessage {
+            offset: line,
+            reason,
+        };
+        let mut lines = reader.lines();
+
+        let header = match lines.next() {
+            Some(line) => line.map_err(|e| ContextError::SyncFailed {
+                reason: format!("Failed to read CSV: {}", e),
+            })?,
+            None => return Err(malformed(1, "missing CSV header".to_string()).into()),
+        };
+        let names: Vec<&str> = header.split(',').map(str::trim).collect();
+        let position = |name: &str| {
+            names
+                .iter()
+                .position(|n| *n == name)
+                .ok_or_else(|| malformed(1, format!("missing column: {}", name)))
+        };
+        let timestamp_index = position(&mapping.timestamp_column)?;
+        let columns = mapping
+            .columns
+            .iter()
+            .map(|(name, source_id)| Ok((position(name)?, *source_id)))
+            .collect::<core::result::Result<Vec<_>, DecodeError>>()?;
+
+        let before = self.samples.len();
+        for (index, line) in lines.enumerate() {
+            let line_number = index + 2;
+            let line = line.map_err(|e| ContextError::SyncFailed {
+                reason: format!("Failed to read CSV: {}", e),
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            let timestamp: u64 = cells
+                .get(timestamp_index)
+                .and_then(|cell| cell.parse().ok())
+                .ok_or_else(|| malformed(line_number, "invalid timestamp".to_string()))?;
+
+            for &(column, source_id) in &columns {
+                let cell = cells.get(column).copied().unwrap_or("");
+                if cell.is_empty() {
+                    continue;
+                }
+                let value: f64 = cell
+                    .parse()
+                    .map_err(|_| malformed(line_number, format!("invalid value: {}", cell)))?;
+                self.samples
+                    .push(RawData::with_source(source_id, value, timestamp));
+            }
+        }
+        Ok(self.samples.len() - before)
+    }
+
+    /// Number of samples fed so far
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Metrics of the last [`Trainer::finish`]
+    pub fn report(&self) -> Option<&TrainingReport> {
+        self.report.as_ref()
+    }
+
+    /// Train on the samples fed so far and build a preload
+    ///
+    /// The warm-up values (`statistics.recent_values`) are the training
+    /// tail of the last source fed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::InvalidValue` when there are no finite
+    /// training samples.
+    pub fn finish(&mut self, sensor_type: &str) -> Result<PreloadFile> {
+        let holdout = ((self.samples.len() as f64 * self.config.holdout_fraction.clamp(0.0, 1.0))
+            as usize)
+            .min(self.samples.len().saturating_sub(1));
+        let split = self.samples.len() - holdout;
+        let (training, holdout) = self.samples.split_at(split);
+        let values: Vec<f64> = training
+            .iter()
+            .map(|s| s.value)
+            .filter(|v| v.is_finite())
+            .collect();
+        if values.is_empty() {
+            return Err(crate::error::EncodeError::InvalidValue(
+                "no finite training samples".to_string(),
+            )
+            .into());
+        }
+
+        // Pass 1: value distribution and quantization step
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let resolution = 1.0 / self.config.scale_factor.max(1) as f64;
+        let passes = self.config.passes.clamp(1, 2);
+        let step = if passes >= 2 && self.config.target_patterns > 0 {
+            ((max - min) / self.config.target_patterns as f64).max(resolution)
+        } else {
+            resolution
+        };
+
+        // Pass 2: prediction model and dictionary
+        let mut context = Context::with_config(ContextConfig {
+            max_patterns: self.config.target_patterns.max(1),
+            evolution: self.config.evolution.clone(),
+            ..Default::default()
+        });
+        let mut counts: HashMap<i64, u64> = HashMap::new();
+        for sample in training {
+            context.observe(sample);
+            if sample.value.is_finite() {
+                *counts
+                    .entry((sample.value / step).round() as i64)
+                    .or_default() += 1;
+            }
+        }
+        let mut buckets: Vec<(i64, u64)> = counts.into_iter().collect();
+        buckets.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        buckets.truncate(self.config.target_patterns);
+        for (bucket, frequency) in &buckets {
+            let mut pattern = Pattern::numeric(*bucket as f64 * step);
+            pattern.frequency = *frequency;
+            context.register_pattern(pattern)?;
+        }
+
+        let mut preload = PreloadFile::from_context(&context, sensor_type);
+        preload.training_samples = training.len() as u64;
+        preload.prediction.model_type = context
+            .predict(training[training.len() - 1].source_id)
+            .map_or(preload.prediction.model_type, |prediction| {
+                prediction.model_type.into()
+            });
+        let std_dev = variance.sqrt();
+        preload.statistics.mean = mean;
+        preload.statistics.variance = variance;
+        preload.statistics.min_observed = min;
+        preload.statistics.max_observed = max;
+        preload.statistics.min_expected = mean - 4.0 * std_dev;
+        preload.statistics.max_expected = mean + 4.0 * std_dev;
+        preload.statistics.recent_values = Self::recent_values(training);
+
+        // Evaluation: re-encode the holdout from the preload
+        let (holdout_raw_bytes, holdout_encoded_bytes) = Self::evaluate(&preload, holdout)?;
+
+        self.report = Some(TrainingReport {
+            training_samples: training.len(),
+            holdout_samples: holdout.len(),
+            passes,
+            quantization_step: step,
+            patterns: buckets.len(),
+            mean,
+            std_dev,
+            holdout_raw_bytes,
+            holdout_encoded_bytes,
+            expected_compression_ratio: if holdout_encoded_bytes == 0 {
+                1.0
+            } else {
+                holdout_raw_bytes as f64 / holdout_encoded_bytes as f64
+            },
+        });
+        Ok(preload)
+    }
+
+    /// Training tail of the last source fed
+    fn recent_values(training: &[RawData]) -> Vec<f64> {
+        let source_id = training[training.len() - 1].source_id;
+        let history = ContextConfig::default().history_size.min(MAX_RECENT_VALUES);
+        let mut recent: Vec<f64> = training
+            .iter()
+            .rev()
+            .filter(|s| s.source_id == source_id && s.value.is_finite())
+            .take(history)
+            .map(|s| s.value)
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Encode `holdout` with a context loaded from `preload`
+    ///
+    /// Returns the raw and encoded sizes in bytes.
+    fn evaluate(preload: &PreloadFile, holdout: &[RawData]) -> Result<(usize, usize)> {
+        let mut context = Context::from_preload(preload)?;
+        let mut warmed: Vec<u32> = Vec::new();
+        let mut encoder = Encoder::new();
+        let classifier = Classifier::default();
+        let (mut raw, mut encoded) = (0, 0);
+        for sample in holdout {
+            if !warmed.contains(&sample.source_id) {
+                context.warm_source(sample.source_id, &preload.statistics.recent_values);
+                warmed.push(sample.source_id);
+            }
+            let classification = classifier.classify(sample, &context);
+            raw += sample.raw_size();
+            encoded += encoder
+                .encode_to_bytes(sample, &classification, &context)
+                .len();
+            context.observe_classified(sample, &classification);
+        }
+        Ok((raw, encoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "timestamp_ms,temp,humidity\n\
+                       1000,21.500000,60.000000\n\
+                       2000,,61.000000\n\
+                       3000,21.700000,\n";
+
+    #[test]
+    fn test_feed_csv_testdata_layout() {
+        let mut trainer = Trainer::new(TrainerConfig::default());
+        let mapping = ColumnMapping::new().column("temp", 1).column("humidity", 2);
+        let read = trainer.feed_csv(CSV.as_bytes(), &mapping).unwrap();
+
+        assert_eq!(read, 4);
+        assert_eq!(trainer.samples[0], RawData::with_source(1, 21.5, 1000));
+        assert_eq!(trainer.samples[2], RawData::with_source(2, 61.0, 2000));
+        assert_eq!(trainer.samples[3], RawData::with_source(1, 21.7, 3000));
+    }
+
+    #[test]
+    fn test_feed_csv_missing_column() {
+        let mut trainer = Trainer::new(TrainerConfig::default());
+        let mapping = ColumnMapping::new().column("pressure", 1);
+        assert!(trainer.feed_csv(CSV.as_bytes(), &mapping).is_err());
+        assert_eq!(trainer.sample_count(), 0);
+    }
+
+    #[test]
+    fn test_feed_csv_reports_line() {
+        let mut trainer = Trainer::new(TrainerConfig::default());
+        let mapping = ColumnMapping::new().column("temp", 1);
+        let err = trainer
+            .feed_csv("timestamp_ms,temp\n1000,abc\n".as_bytes(), &mapping)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::AlecError::Decode(DecodeError::MalformedMessage { offset: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_finish_without_samples() {
+        let mut trainer = Trainer::new(TrainerConfig::default());
+        assert!(trainer.finish("temperature").is_err());
+        assert!(trainer.report().is_none());
+    }
+
+    #[test]
+    fn test_dictionary_respects_target() {
+        let samples: Vec<RawData> = (0..2000)
+            .map(|i| RawData::with_source(1, (i % 500) as f64, i * 1000))
+            .collect();
+        let mut trainer = Trainer::new(TrainerConfig {
+            target_patterns: 16,
+            ..Default::default()
+        });
+        trainer.feed(&samples);
+        let preload = trainer.finish("counter").unwrap();
+
+        let report = trainer.report().unwrap();
+        assert_eq!(preload.dictionary.len(), 16);
+        assert_eq!(report.patterns, 16);
+        assert!(report.quantization_step > 1.0);
+        assert_eq!(report.holdout_samples, 200);
+        assert_eq!(preload.training_samples, 1800);
+    }
+}
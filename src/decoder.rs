@@ -10,15 +10,29 @@
 //! using the shared context for decompression.
 
 #[cfg(not(feature = "std"))]
-use alloc::{string::ToString, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, format, string::ToString, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
 
 use crate::context::Context;
 use crate::encoder::{fixed_bitmap_bytes, FixedEncoding};
-use crate::error::{DecodeError, Result};
+use crate::error::{AlecError, DecodeError, ErrorContext, ResourceLimit, Result};
+use crate::latency::{LatencyLog, LatencyReport};
+use crate::metrics::OutcomeWindow;
+use crate::observer::{self, DecodeInfo, DecodeObserver, IntegrityStatus, Stopwatch};
 use crate::protocol::{
-    classify_compact_marker, ctx_version_compatible, CompactHeader, DecodedData, EncodedMessage,
-    EncodingType,
+    classify_compact_marker, ctx_version_compatible, payload_len, read_varint, value_len,
+    ChecksumPolicy, CompactHeader, CompositeValue, Decoded, DecodedComposite, DecodedData,
+    DecodedValue, EncodedMessage, EncodedMessageRef, EncodingType, MessageHeader, MessageType,
+    Priority, TimestampFormat, TimestampUnit, TransmissionPlan, WireDialect, CHECKSUM_SIZE,
+    PLAN_RELATIVE_MARKER,
 };
+use crate::sequence::{SequenceStats, SequenceTracker, SequenceWindow};
+use crate::session::SessionInput;
+#[cfg(feature = "std")]
+use crate::session::SessionRecorder;
+use crate::stats::Ema;
+use crate::sync::{SyncMessage, Synchronizer};
 
 /// Maximum forward jump of the u16 context_version tolerated by the
 /// fixed-channel decoder before flagging a version mismatch. The
@@ -27,6 +41,257 @@ use crate::protocol::{
 /// while still catching large skips.
 const FIXED_CTX_MAX_JUMP: u16 = 256;
 
+/// Decoded points kept per source for gap filling
+const GAP_FILL_HISTORY: usize = 16;
+
+/// How [`Decoder::estimate_at`] fills a gap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapFillMethod {
+    /// Repeat the last decoded value
+    Hold,
+    /// Interpolate between the decoded values around the gap, or
+    /// extrapolate the last trend past the latest one
+    #[default]
+    Linear,
+    /// Use the context's prediction past the latest decoded value;
+    /// gaps closed by a later value are interpolated
+    Predict,
+}
+
+/// Gap-filling configuration (see [`Decoder::enable_gap_fill`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapFillConfig {
+    /// Longest gap, in ms, that is filled; beyond it no estimate is made
+    pub max_gap_ms: u64,
+    /// Fill method
+    pub method: GapFillMethod,
+}
+
+impl Default for GapFillConfig {
+    fn default() -> Self {
+        Self {
+            max_gap_ms: 300_000,
+            method: GapFillMethod::default(),
+        }
+    }
+}
+
+/// Value estimated for a timestamp with no decoded message
+///
+/// Never produced by the decode methods: estimates only come from
+/// [`Decoder::estimate_at`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimatedValue {
+    /// Estimated value
+    pub value: f64,
+    /// Method actually used (Linear falls back to Hold with a single point)
+    pub method: GapFillMethod,
+    /// Confidence (0.0-1.0), decreasing with the time since the last
+    /// decoded value
+    pub confidence: f32,
+}
+
+/// What [`Decoder::decode_with_now`] does with a stale message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaleAction {
+    /// Decode it with [`DecodedData::stale`] set
+    #[default]
+    Flag,
+    /// Fail with [`DecodeError::Stale`]
+    Reject,
+}
+
+/// Age beyond which a message of some priority is stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleThreshold {
+    /// Largest age, in ms, of a current message
+    pub max_age_ms: u64,
+    /// What to do with older messages
+    pub action: StaleAction,
+}
+
+/// Sender's prediction state for a source, seeded by a self-describing
+/// message and advanced by every value decoded for the source since
+#[derive(Debug, Clone, Copy)]
+struct SeededSource {
+    last_value: f64,
+    ema: Ema,
+    count: u64,
+    /// Scale factor of delta encodings
+    scale: f64,
+}
+
+impl SeededSource {
+    /// Fold in a decoded value, as the sender's context observes it
+    fn observe(&mut self, value: f64) {
+        self.last_value = value;
+        self.ema.push(value);
+        self.count += 1;
+    }
+
+    /// Prediction the sender encodes against, chosen as by
+    /// [`Context::predict`]
+    fn prediction(&self) -> f64 {
+        match self.ema.value() {
+            Some(ema) if self.count >= 3 => ema,
+            _ => self.last_value,
+        }
+    }
+}
+
+/// Upper bounds, in ms, of the [`AgeHistogram`] buckets; a last bucket
+/// counts older messages
+pub const AGE_BUCKETS_MS: [u64; 4] = [60_000, 300_000, 3_600_000, 86_400_000];
+
+/// Ages of the messages decoded with [`Decoder::decode_with_now`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AgeHistogram {
+    /// Messages per bucket: up to 1 min, 5 min, 1 h, 24 h, then older
+    pub counts: [u64; AGE_BUCKETS_MS.len() + 1],
+}
+
+impl AgeHistogram {
+    /// Count a message of `age_ms`
+    pub fn record(&mut self, age_ms: u64) {
+        let bucket = AGE_BUCKETS_MS.partition_point(|&bound| bound < age_ms);
+        self.counts[bucket] += 1;
+    }
+
+    /// Messages counted
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Decoder statistics (see [`Decoder::stats`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecoderStats {
+    /// Ages of the messages decoded with a reference time, stale ones
+    /// included
+    pub age_histogram: AgeHistogram,
+    /// Stale messages decoded with [`DecodedData::stale`] set
+    pub stale_flagged: u64,
+    /// Stale messages rejected with [`DecodeError::Stale`]
+    pub stale_rejected: u64,
+}
+
+/// Decoder configuration
+///
+/// The resource limits bound the work a single message can cause, so a
+/// corrupted or malicious message cannot exhaust a shared receiver. A
+/// message over a limit fails with
+/// [`DecodeError::ResourceLimitExceeded`]. The defaults are far above what
+/// the wire format produces for legitimate traffic (a multi-value message
+/// holds at most 255 values, a fixed-channel frame 64).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderConfig {
+    /// Which incoming messages must carry a checksum
+    /// (default: [`ChecksumPolicy::Never`])
+    pub checksum_policy: ChecksumPolicy,
+    /// Most values a single message may decode into (default: 1024)
+    ///
+    /// Checked against the count a multi-value message claims, before
+    /// anything is allocated for it.
+    pub max_values_per_message: usize,
+    /// Most bytes of decoded output a single message may expand into
+    /// (default: 256 KiB)
+    pub max_expansion_bytes: usize,
+    /// Most time, in microseconds, spent decoding a single message
+    /// (default: 100 000)
+    ///
+    /// Best effort: checked between values, and only with the `std-time`
+    /// feature.
+    pub max_decode_micros: u64,
+    /// Staleness threshold per priority, indexed by `Priority as usize`
+    /// (default: none)
+    ///
+    /// Only applied by [`Decoder::decode_with_now`].
+    pub stale_thresholds: [Option<StaleThreshold>; 5],
+    /// Header layout of incoming messages (default:
+    /// [`WireDialect::Standard`])
+    ///
+    /// Applies to the byte-level entry points ([`Decoder::decode_bytes`],
+    /// [`Decoder::decode_bytes_item`], [`Decoder::decode_buffer`]); parse
+    /// messages for the other methods with
+    /// [`EncodedMessageRef::from_bytes_in`].
+    pub dialect: WireDialect,
+    /// How far ahead of the last sequence number a message counts as
+    /// newer (default: [`SequenceWindow::new`] at 16 bits)
+    ///
+    /// Only affects [`Decoder::sequence_stats`].
+    pub sequence_window: SequenceWindow,
+    /// Timestamp unit the receiving application expects (default: none)
+    ///
+    /// Messages recording another unit
+    /// ([`MessageHeader::timestamp_unit`]) fail with
+    /// [`DecodeError::TimestampUnitMismatch`]; messages recording none
+    /// (older senders, or encoders without
+    /// [`crate::EncoderConfig::timestamp_unit`]) are accepted. Decoded
+    /// values report timestamps in the recorded unit
+    /// ([`DecodedData::timestamp`]).
+    pub timestamp_unit: Option<TimestampUnit>,
+    /// Inclusive range of plausible full header timestamps (default: none)
+    ///
+    /// Messages whose header carries a full timestamp outside it fail with
+    /// [`DecodeError::MalformedMessage`]. Without checksums, this is what
+    /// tells a header written in another [`WireDialect`] apart: its
+    /// timestamp is read from misplaced or byte-swapped bytes.
+    pub timestamp_range: Option<(u32, u32)>,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        Self {
+            checksum_policy: ChecksumPolicy::Never,
+            max_values_per_message: 1024,
+            max_expansion_bytes: 256 * 1024,
+            max_decode_micros: 100_000,
+            stale_thresholds: [None; 5],
+            dialect: WireDialect::Standard,
+            sequence_window: SequenceWindow::default(),
+            timestamp_unit: None,
+            timestamp_range: None,
+        }
+    }
+}
+
+impl DecoderConfig {
+    /// Set the staleness threshold of `priority`
+    pub fn with_stale_threshold(
+        mut self,
+        priority: Priority,
+        max_age_ms: u64,
+        action: StaleAction,
+    ) -> Self {
+        self.stale_thresholds[priority as usize] = Some(StaleThreshold { max_age_ms, action });
+        self
+    }
+
+    /// Staleness threshold of `priority`, if any
+    pub fn stale_threshold(&self, priority: Priority) -> Option<StaleThreshold> {
+        self.stale_thresholds[priority as usize]
+    }
+}
+
+/// Fail with [`DecodeError::ResourceLimitExceeded`] if `attempted` is over
+/// `limit`
+fn check_limit(which: ResourceLimit, limit: u64, attempted: u64) -> Result<()> {
+    if attempted > limit {
+        return Err(DecodeError::ResourceLimitExceeded {
+            which,
+            limit,
+            attempted,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Decoded timestamp in milliseconds, whatever unit the message records
+fn decoded_millis(decoded: &DecodedData) -> u64 {
+    u64::try_from(decoded.timestamp_micros() / 1000).unwrap_or(0)
+}
+
 /// Outcome of a successful `decode_multi_fixed` call.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixedFrameInfo {
@@ -45,138 +310,1222 @@ pub struct FixedFrameInfo {
     pub context_mismatch: bool,
 }
 
-/// Decoder for ALEC messages
-#[derive(Debug, Clone)]
-pub struct Decoder {
-    /// Whether to verify checksum on incoming messages
-    verify_checksum: bool,
-    /// Last decoded sequence number (for gap detection)
-    last_sequence: Option<u16>,
-    /// Sequence observed on the most recent fixed-channel frame.
-    last_fixed_sequence: Option<u16>,
-    /// Context version observed on the most recent fixed-channel frame.
-    last_fixed_ctx_version: Option<u16>,
-}
+/// Decoder for ALEC messages
+///
+/// An optional [`DecodeObserver`] can be installed with
+/// [`Decoder::set_observer`] to receive one callback per decode call.
+/// Cloning a decoder does not clone its observer, nor its session
+/// recorder (see [`crate::session`]).
+pub struct Decoder {
+    /// Checksum policy and resource limits
+    config: DecoderConfig,
+    /// Last decoded sequence number
+    last_sequence: Option<u16>,
+    /// Gap, duplicate and wrap detection over the decoded sequence numbers
+    sequence: SequenceTracker,
+    /// Sequence observed on the most recent fixed-channel frame.
+    last_fixed_sequence: Option<u16>,
+    /// Context version observed on the most recent fixed-channel frame.
+    last_fixed_ctx_version: Option<u16>,
+    /// Last absolute timestamp (seconds) per source, for compressed
+    /// header timestamps
+    last_timestamps: BTreeMap<u32, u32>,
+    /// Sequence of the last header whose timestamp was resolved, to
+    /// detect messages lost between compressed timestamps
+    timestamp_sequence: Option<u16>,
+    /// Optional per-message observer
+    observer: Option<Box<dyn DecodeObserver>>,
+    /// Gap-filling configuration, if enabled
+    gap_fill: Option<GapFillConfig>,
+    /// Recent decoded `(timestamp_ms, value)` per source, oldest first
+    decoded_points: BTreeMap<u32, Vec<(u64, f64)>>,
+    /// Failures among the most recent single-value decodes
+    decode_errors: OutcomeWindow,
+    /// Checksum mismatches among the most recent single-value decodes
+    checksum_failures: OutcomeWindow,
+    /// Active transmission plans by plan id, with the announcement priority
+    plans: BTreeMap<u8, (TransmissionPlan, Priority)>,
+    /// Message ages and staleness counts
+    stats: DecoderStats,
+    /// Sources decoded from the sender's prediction state, seeded by
+    /// self-describing messages
+    seeded: BTreeMap<u32, SeededSource>,
+    /// Decode times of recent messages, if latency tracking is enabled
+    latency: Option<LatencyLog>,
+    /// Session log receiving every input, if recording
+    #[cfg(feature = "std")]
+    recorder: Option<SessionRecorder>,
+}
+
+impl core::fmt::Debug for Decoder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Decoder")
+            .field("config", &self.config)
+            .field("last_sequence", &self.last_sequence)
+            .field("sequence", &self.sequence)
+            .field("last_fixed_sequence", &self.last_fixed_sequence)
+            .field("last_fixed_ctx_version", &self.last_fixed_ctx_version)
+            .field("last_timestamps", &self.last_timestamps.len())
+            .field("timestamp_sequence", &self.timestamp_sequence)
+            .field("observer", &self.observer.is_some())
+            .field("gap_fill", &self.gap_fill)
+            .field("decoded_points", &self.decoded_points.len())
+            .field("plans", &self.plans.len())
+            .field("stats", &self.stats)
+            .field("seeded", &self.seeded.len())
+            .field("latency_tracking", &self.latency.is_some())
+            .field("recording", &self.is_recording())
+            .finish()
+    }
+}
+
+impl Clone for Decoder {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config,
+            last_sequence: self.last_sequence,
+            sequence: self.sequence.clone(),
+            last_fixed_sequence: self.last_fixed_sequence,
+            last_fixed_ctx_version: self.last_fixed_ctx_version,
+            last_timestamps: self.last_timestamps.clone(),
+            timestamp_sequence: self.timestamp_sequence,
+            observer: None,
+            gap_fill: self.gap_fill,
+            decoded_points: self.decoded_points.clone(),
+            decode_errors: self.decode_errors,
+            checksum_failures: self.checksum_failures,
+            plans: self.plans.clone(),
+            stats: self.stats,
+            seeded: self.seeded.clone(),
+            latency: self.latency.clone(),
+            #[cfg(feature = "std")]
+            recorder: None,
+        }
+    }
+}
+
+impl Decoder {
+    /// Create a new decoder
+    pub fn new() -> Self {
+        Self::with_checksum_policy(ChecksumPolicy::Never)
+    }
+
+    /// Create decoder with checksum verification enabled
+    pub fn with_checksum_verification() -> Self {
+        Self::with_checksum_policy(ChecksumPolicy::Always)
+    }
+
+    /// Create decoder that verifies checksums according to `policy`
+    ///
+    /// Must match the encoder's policy: messages whose header priority is
+    /// covered are expected to end with a checksum, all others are parsed
+    /// without one.
+    pub fn with_checksum_policy(policy: ChecksumPolicy) -> Self {
+        Self::with_config(DecoderConfig {
+            checksum_policy: policy,
+            ..Default::default()
+        })
+    }
+
+    /// Create decoder with a checksum policy and resource limits
+    pub fn with_config(config: DecoderConfig) -> Self {
+        Self {
+            config,
+            last_sequence: None,
+            sequence: SequenceTracker::new(config.sequence_window),
+            last_fixed_sequence: None,
+            last_fixed_ctx_version: None,
+            last_timestamps: BTreeMap::new(),
+            timestamp_sequence: None,
+            observer: None,
+            gap_fill: None,
+            decoded_points: BTreeMap::new(),
+            decode_errors: OutcomeWindow::default(),
+            checksum_failures: OutcomeWindow::default(),
+            plans: BTreeMap::new(),
+            stats: DecoderStats::default(),
+            seeded: BTreeMap::new(),
+            latency: None,
+            #[cfg(feature = "std")]
+            recorder: None,
+        }
+    }
+
+    /// Check if checksum verification is enabled for at least some messages
+    pub fn checksum_verification_enabled(&self) -> bool {
+        self.config.checksum_policy != ChecksumPolicy::Never
+    }
+
+    /// Get the checksum policy
+    pub fn checksum_policy(&self) -> ChecksumPolicy {
+        self.config.checksum_policy
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &DecoderConfig {
+        &self.config
+    }
+
+    /// Replace the checksum policy, resource limits and staleness
+    /// thresholds
+    pub fn set_config(&mut self, config: DecoderConfig) {
+        #[cfg(feature = "std")]
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.log(
+                SessionInput::Config,
+                &[],
+                &crate::session::config_to_bytes(&config),
+            );
+        }
+        self.sequence.set_window(config.sequence_window);
+        self.config = config;
+    }
+
+    /// Fail if decoding started at `stopwatch` is over `max_decode_micros`
+    fn check_decode_time(&self, stopwatch: &Stopwatch) -> Result<()> {
+        match stopwatch.elapsed() {
+            Some(elapsed) => check_limit(
+                ResourceLimit::DecodeMicros,
+                self.config.max_decode_micros,
+                elapsed.as_micros() as u64,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Check if the policy requires a checksum on this raw message
+    fn expects_checksum(&self, bytes: &[u8]) -> bool {
+        bytes
+            .first()
+            .and_then(|&byte| MessageHeader::decode_header_byte(byte).2)
+            .is_some_and(|priority| self.config.checksum_policy.applies_to(priority))
+    }
+
+    /// Install an observer notified after every decode call.
+    ///
+    /// Replaces any previously installed observer. See
+    /// [`crate::observer`] for the callback contract.
+    pub fn set_observer(&mut self, observer: Box<dyn DecodeObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Remove the installed observer, if any.
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
+
+    /// Check if an observer is installed.
+    pub fn has_observer(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    /// Start logging every input of the session to `writer`
+    ///
+    /// Writes the configuration and `context`, the receiver's starting
+    /// context, then appends each recorded input (see [`crate::session`])
+    /// until [`Decoder::stop_recording`]. Replaces any recording in
+    /// progress, which is dropped unflushed. Start on a fresh decoder, as
+    /// a [`SessionReplayer`](crate::session::SessionReplayer) starts from
+    /// one.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`AlecError::Protocol`] if the header cannot be written;
+    /// the decoder is then not recording.
+    #[cfg(feature = "std")]
+    pub fn start_recording(
+        &mut self,
+        writer: impl std::io::Write + Send + Sync + 'static,
+        context: &Context,
+    ) -> Result<()> {
+        self.recorder = None;
+        self.recorder = Some(SessionRecorder::start(
+            Box::new(writer),
+            &self.config,
+            context,
+        )?);
+        Ok(())
+    }
+
+    /// Stop recording and flush the session log
+    ///
+    /// A write failure during the session stops the logging without
+    /// failing the decodes; it is returned here. Does nothing if not
+    /// recording.
+    #[cfg(feature = "std")]
+    pub fn stop_recording(&mut self) -> std::io::Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Check if the session is being recorded
+    pub fn is_recording(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.recorder.is_some();
+        #[cfg(not(feature = "std"))]
+        false
+    }
+
+    /// Log one input, then run `apply` with the recorder set aside so the
+    /// entry points it calls in turn are not logged again
+    fn recorded<T>(
+        &mut self,
+        input: SessionInput,
+        prefix: &[u8],
+        bytes: &[u8],
+        apply: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        #[cfg(feature = "std")]
+        {
+            let Some(mut recorder) = self.recorder.take() else {
+                return apply(self);
+            };
+            recorder.log(input, prefix, bytes);
+            let result = apply(self);
+            self.recorder = Some(recorder);
+            result
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = (input, prefix, bytes);
+            apply(self)
+        }
+    }
+
+    /// Decode a message
+    pub fn decode(&mut self, message: &EncodedMessage, context: &Context) -> Result<DecodedData> {
+        self.decode_ref(&message.as_borrowed(), context)
+    }
+
+    /// Decode a message parsed in place (see [`EncodedMessageRef`])
+    ///
+    /// Same result as [`Decoder::decode`] on the owned message.
+    pub fn decode_ref(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        context: &Context,
+    ) -> Result<DecodedData> {
+        let stopwatch = self.observer.as_ref().map(|_| Stopwatch::start());
+        let result = self.decode_message(message, context);
+        self.record_outcome(&result);
+        if let Some(stopwatch) = stopwatch {
+            self.notify_single(
+                &stopwatch,
+                Some(message),
+                message.len(),
+                IntegrityStatus::NotChecked,
+                &result,
+            );
+        }
+        result
+    }
+
+    /// Count a single-value decode in the recent error windows
+    fn record_outcome(&mut self, result: &Result<DecodedData>) {
+        self.decode_errors.record(result.is_err());
+        self.checksum_failures.record(matches!(
+            result,
+            Err(AlecError::Decode(e)) if e.checksum_algorithm().is_some()
+        ));
+    }
+
+    /// Share of failed decodes among the last 64 single-value decodes,
+    /// 0.0 before the first one
+    pub fn decode_error_rate(&self) -> f64 {
+        self.decode_errors.rate()
+    }
+
+    /// Checksum mismatches among the last 64 single-value decodes
+    pub fn recent_checksum_failures(&self) -> usize {
+        self.checksum_failures.hits()
+    }
+
+    /// Report a single-value decode to the observer
+    fn notify_single(
+        &self,
+        stopwatch: &Stopwatch,
+        message: Option<&EncodedMessageRef<'_>>,
+        encoded_size: usize,
+        integrity: IntegrityStatus,
+        result: &Result<DecodedData>,
+    ) {
+        let source_id = result.as_ref().ok().map(|data| data.source_id);
+        let error = result.as_ref().err();
+        self.notify(
+            stopwatch,
+            source_id,
+            message,
+            encoded_size,
+            integrity,
+            error,
+        );
+    }
+
+    /// Report a decode attempt to the observer
+    fn notify(
+        &self,
+        stopwatch: &Stopwatch,
+        source_id: Option<u32>,
+        message: Option<&EncodedMessageRef<'_>>,
+        encoded_size: usize,
+        integrity: IntegrityStatus,
+        error: Option<&AlecError>,
+    ) {
+        if let Some(obs) = &self.observer {
+            let info = DecodeInfo {
+                source_id,
+                encoding: message.and_then(|m| m.encoding_type()),
+                encoded_size,
+                duration: stopwatch.elapsed(),
+                integrity,
+                error,
+            };
+            observer::notify(|| obs.on_decode(&info));
+        }
+    }
+
+    /// Reject newer major versions, unknown critical flags, timestamps in
+    /// another unit than expected and implausible full timestamps
+    fn check_header(&self, header: &MessageHeader) -> Result<()> {
+        if let Some(extension) = header.extension() {
+            extension.check()?;
+        }
+        if let (Some(expected), Some(actual)) =
+            (self.config.timestamp_unit, header.timestamp_unit())
+        {
+            if expected != actual {
+                return Err(DecodeError::TimestampUnitMismatch { expected, actual }.into());
+            }
+        }
+        if let Some((min, max)) = self.config.timestamp_range {
+            if header.timestamp_format() == TimestampFormat::Full
+                && !(min..=max).contains(&header.timestamp)
+            {
+                return Err(DecodeError::MalformedMessage {
+                    offset: 0,
+                    reason: format!("implausible timestamp {}", header.timestamp),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a message without notifying the observer
+    fn decode_message(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        context: &Context,
+    ) -> Result<DecodedData> {
+        self.check_header(&message.header)?;
+        if message.is_confirmation() {
+            return Err(DecodeError::MalformedMessage {
+                offset: 0,
+                reason: "Confirmation carries no value".to_string(),
+            }
+            .into());
+        }
+
+        self.track_sequence(message.header.sequence);
+
+        // Parse payload
+        let payload = message.payload;
+        if payload.is_empty() {
+            return Err(DecodeError::BufferTooShort {
+                needed: 1,
+                available: 0,
+            }
+            .into());
+        }
+
+        // Decode source ID (varint)
+        let (source_id, offset) = self.decode_varint(payload)?;
+
+        if offset >= payload.len() {
+            return Err(DecodeError::BufferTooShort {
+                needed: offset + 1,
+                available: payload.len(),
+            }
+            .into());
+        }
+
+        // Decode encoding type
+        let encoding_byte = payload[offset];
+        let encoding_type = EncodingType::from_u8(encoding_byte)
+            .ok_or(DecodeError::UnknownEncodingType(encoding_byte))?;
+        if encoding_type == EncodingType::Plan {
+            return Err(DecodeError::MalformedMessage {
+                offset,
+                reason: "Plan carries no value".to_string(),
+            }
+            .into());
+        }
+        if encoding_type == EncodingType::Composite {
+            return Err(DecodeError::MalformedMessage {
+                offset,
+                reason: "Composite encoding should use decode_composite".to_string(),
+            }
+            .into());
+        }
+        let self_describing = message.header.is_self_describing();
+        if self_describing && encoding_type != EncodingType::Raw64 {
+            return Err(DecodeError::MalformedMessage {
+                offset,
+                reason: "Self-describing message must be Raw64".to_string(),
+            }
+            .into());
+        }
+
+        let timestamp = self.resolve_timestamp(source_id, &message.header, false)?;
+        check_limit(
+            ResourceLimit::ExpansionBytes,
+            self.config.max_expansion_bytes as u64,
+            core::mem::size_of::<DecodedData>() as u64,
+        )?;
+
+        // Decode value based on encoding type, against the sender's state
+        // for seeded sources
+        let data = &payload[offset + 1..];
+        let value = if self_describing {
+            self.decode_self_describing(data, source_id, context)?
+        } else if let Some(seed) = self.seeded.get(&source_id) {
+            self.decode_seeded(seed, encoding_type, data, source_id, context)?
+        } else {
+            self.decode_value(encoding_type, data, source_id, context)?
+        };
+        if let Some(seed) = self.seeded.get_mut(&source_id) {
+            seed.observe(value);
+        }
+
+        self.last_timestamps.insert(source_id, timestamp);
+        let timestamp = message
+            .header
+            .timestamp_in_unit()
+            .unwrap_or(timestamp as u64);
+        let mut decoded = DecodedData::new(source_id, timestamp, value, message.header.priority);
+        decoded.operator_override = message.header.is_operator_override();
+        decoded.timestamp_unit = message.header.timestamp_unit();
+        decoded.suspect = message.header.is_suspect();
+        if self.gap_fill.is_some() {
+            self.record_point(source_id, decoded_millis(&decoded), value);
+        }
+        Ok(decoded)
+    }
+
+    /// Decode a message that carries a value, a confirmation or a plan
+    ///
+    /// Values are decoded as by [`Decoder::decode`]. Confirmations (see
+    /// [`crate::Encoder::encode_confirmation`]) leave the sequence tracking
+    /// untouched, since their header sequence refers to an earlier message.
+    /// Plans (see [`crate::Encoder::announce_plan`]) are recorded so the
+    /// plan-relative frames that follow can be decoded; [`Decoder::decode`]
+    /// rejects them.
+    pub fn decode_item(&mut self, message: &EncodedMessage, context: &Context) -> Result<Decoded> {
+        self.decode_item_ref(&message.as_borrowed(), context)
+    }
+
+    /// Decode a message parsed in place that carries a value, a
+    /// confirmation or a plan, as by [`Decoder::decode_item`]
+    pub fn decode_item_ref(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        context: &Context,
+    ) -> Result<Decoded> {
+        if message.is_confirmation() {
+            return self.decode_confirmation(message);
+        }
+        if message.is_plan() {
+            return self.decode_plan(message);
+        }
+        self.decode_ref(message, context).map(Decoded::Value)
+    }
+
+    /// Decode raw bytes carrying a value, a confirmation or a plan
+    ///
+    /// Checksums are handled as by [`Decoder::decode_bytes`], which also
+    /// decodes plan-relative frames.
+    pub fn decode_bytes_item(&mut self, bytes: &[u8], context: &Context) -> Result<Decoded> {
+        self.recorded(SessionInput::DecodeItem, &[], bytes, |decoder| {
+            decoder.decode_bytes_item_unrecorded(bytes, context)
+        })
+    }
+
+    fn decode_bytes_item_unrecorded(&mut self, bytes: &[u8], context: &Context) -> Result<Decoded> {
+        if bytes.first() == Some(&PLAN_RELATIVE_MARKER) {
+            return self.decode_bytes(bytes, context).map(Decoded::Value);
+        }
+        let dialect = &self.config.dialect;
+        let message = if self.expects_checksum(bytes) {
+            EncodedMessageRef::from_bytes_with_checksum_in(bytes, dialect)?
+        } else {
+            EncodedMessageRef::from_bytes_in(bytes, dialect).ok_or(DecodeError::InvalidHeader)?
+        };
+        if message.is_confirmation() {
+            return self.decode_confirmation(&message);
+        }
+        if message.is_plan() {
+            return self.decode_plan(&message);
+        }
+        self.decode_bytes(bytes, context).map(Decoded::Value)
+    }
+
+    /// Decode a buffer of concatenated messages
+    ///
+    /// Messages carry no length prefix, so each one is delimited by parsing
+    /// its header and payload: the buffer may hold single-value `Data`
+    /// messages, plans and confirmations, each followed by a checksum when
+    /// the checksum policy covers its priority. Items are decoded in order
+    /// as by [`Decoder::decode_bytes_item`]; `context` is not updated
+    /// between them.
+    ///
+    /// Decoding stops at the first failure. The error carries an
+    /// [`ErrorContext`] with the offset of the failing message in `bytes`
+    /// and, when its header could be read, its sequence number and source.
+    pub fn decode_buffer(&mut self, bytes: &[u8], context: &Context) -> Result<Vec<Decoded>> {
+        self.recorded(SessionInput::DecodeBuffer, &[], bytes, |decoder| {
+            decoder.decode_buffer_unrecorded(bytes, context)
+        })
+    }
+
+    fn decode_buffer_unrecorded(
+        &mut self,
+        bytes: &[u8],
+        context: &Context,
+    ) -> Result<Vec<Decoded>> {
+        let mut items = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            let location = self.locate(rest).with_offset(offset);
+            let item = self
+                .message_len(rest)
+                .and_then(|len| {
+                    let item = self.decode_bytes_item(&rest[..len], context)?;
+                    Ok((item, len))
+                })
+                .map_err(|e| e.with_context(location));
+            let (item, len) = item?;
+            items.push(item);
+            offset += len;
+        }
+        Ok(items)
+    }
+
+    /// Decode a buffer of concatenated values, observing each one into
+    /// `context`
+    ///
+    /// The receiving side of an encoder that observes every value it
+    /// sends, e.g. a gateway channel flushing several samples at once:
+    /// each single-value `Data` message is delimited as by
+    /// [`Decoder::decode_buffer`] and decoded as by
+    /// [`Decoder::decode_into`], so the next one is decoded against the
+    /// context the sender had. Decoding stops at the first failure, with
+    /// the offset of the failing message in `bytes`; the values before it
+    /// stay observed.
+    ///
+    /// [`Decoder::decode_buffer`] holds `context` fixed, so it misdecodes
+    /// every predicted value after the first, and messages carry no length
+    /// prefix to split the buffer with beforehand.
+    pub fn decode_buffer_into(
+        &mut self,
+        bytes: &[u8],
+        context: &mut Context,
+    ) -> Result<Vec<DecodedData>> {
+        let mut values = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let rest = &bytes[offset..];
+            let location = self.locate(rest).with_offset(offset);
+            let len = self
+                .message_len(rest)
+                .map_err(|e| e.with_context(location))?;
+            let decoded = self
+                .decode_into(&rest[..len], context)
+                .map_err(|e| e.with_context(location))?;
+            values.push(decoded);
+            offset += len;
+        }
+        Ok(values)
+    }
+
+    /// Source and sequence of the message at the start of `bytes`, as far
+    /// as they can be read
+    fn locate(&self, bytes: &[u8]) -> ErrorContext {
+        let Some((header, header_len)) = MessageHeader::parse_in(bytes, &self.config.dialect)
+        else {
+            return ErrorContext::new();
+        };
+        let location = ErrorContext::new().with_sequence(header.sequence as u32);
+        match read_varint(&bytes[header_len..]) {
+            Some((source_id, _)) => location.with_source_id(source_id),
+            None => location,
+        }
+    }
+
+    /// Length of the message at the start of `bytes`, checksum included
+    fn message_len(&self, bytes: &[u8]) -> Result<usize> {
+        let (header, header_len) = MessageHeader::parse_in(bytes, &self.config.dialect)
+            .ok_or(DecodeError::InvalidHeader)?;
+        let payload = &bytes[header_len..];
+        let payload_len = match header.message_type {
+            MessageType::Data => payload_len(payload, header.is_self_describing()),
+            // Confirmations carry only the source id
+            MessageType::Heartbeat => read_varint(payload).map(|(_, len)| len),
+            other => {
+                return Err(DecodeError::MalformedMessage {
+                    offset: 0,
+                    reason: format!("cannot delimit {:?} message", other),
+                }
+                .into())
+            }
+        }
+        .ok_or(DecodeError::MalformedMessage {
+            offset: header_len,
+            reason: "truncated or invalid payload".to_string(),
+        })?;
+
+        let len = header_len + payload_len;
+        if !self.expects_checksum(bytes) {
+            return Ok(len);
+        }
+        if bytes.len() < len + CHECKSUM_SIZE {
+            return Err(DecodeError::BufferTooShort {
+                needed: len + CHECKSUM_SIZE,
+                available: bytes.len(),
+            }
+            .into());
+        }
+        Ok(len + CHECKSUM_SIZE)
+    }
+
+    /// Record a plan announcement
+    ///
+    /// A new plan replaces any earlier plan of the same source.
+    fn decode_plan(&mut self, message: &EncodedMessageRef<'_>) -> Result<Decoded> {
+        self.check_header(&message.header)?;
+
+        let (source_id, offset) = self.decode_varint(message.payload)?;
+        let timestamp = self.resolve_timestamp(source_id, &message.header, false)?;
+        let body = message.payload.get(offset + 1..).unwrap_or(&[]);
+        let (plan, _) =
+            TransmissionPlan::read_body(body, source_id, timestamp).ok_or_else(|| {
+                DecodeError::MalformedMessage {
+                    offset: offset + 1,
+                    reason: "Invalid plan".to_string(),
+                }
+            })?;
+
+        self.track_sequence(message.header.sequence);
+        self.last_timestamps.insert(source_id, timestamp);
+        self.plans
+            .retain(|_, (active, _)| active.source_id != source_id);
+        let decoded = Decoded::Plan {
+            source_id,
+            plan_id: plan.plan_id,
+            messages: plan.messages,
+        };
+        self.plans
+            .insert(plan.plan_id, (plan, message.header.priority));
+        Ok(decoded)
+    }
+
+    /// Decode a plan-relative frame
+    ///
+    /// Frames past the plan's message count or timeout, or for a plan that
+    /// was never received, fail with [`DecodeError::PlanMissing`]. Their
+    /// timestamps are not used as compressed-timestamp references, since
+    /// the encoder does not track them either.
+    fn decode_plan_relative(&mut self, bytes: &[u8]) -> Result<DecodedData> {
+        let prefix = TransmissionPlan::FRAME_PREFIX;
+        if bytes.len() < prefix {
+            return Err(DecodeError::BufferTooShort {
+                needed: prefix,
+                available: bytes.len(),
+            }
+            .into());
+        }
+        let plan_id = bytes[1];
+        let index = bytes[2];
+        let missing = DecodeError::PlanMissing { plan_id };
+        let Some((plan, priority)) = self.plans.get(&plan_id).cloned() else {
+            return Err(missing.into());
+        };
+        if index >= plan.messages {
+            self.plans.remove(&plan_id);
+            return Err(missing.into());
+        }
+
+        let (offset, len) = self.decode_varint(&bytes[prefix..])?;
+        if offset > plan.timeout_s {
+            self.plans.remove(&plan_id);
+            return Err(missing.into());
+        }
+        let value =
+            plan.decode_delta(&bytes[prefix + len..])
+                .ok_or(DecodeError::BufferTooShort {
+                    needed: prefix + len + plan.encoding.typical_size(),
+                    available: bytes.len(),
+                })?;
+        let timestamp = plan
+            .timestamp
+            .checked_add(offset)
+            .ok_or(DecodeError::InvalidHeader)?;
+
+        if index + 1 == plan.messages {
+            self.plans.remove(&plan_id);
+        }
+        if self.gap_fill.is_some() {
+            self.record_point(plan.source_id, timestamp as u64 * 1000, value);
+        }
+        Ok(DecodedData::new(
+            plan.source_id,
+            timestamp as u64,
+            value,
+            priority,
+        ))
+    }
+
+    /// Decode a confirmation message
+    fn decode_confirmation(&mut self, message: &EncodedMessageRef<'_>) -> Result<Decoded> {
+        self.check_header(&message.header)?;
+
+        let (source_id, _) = self.decode_varint(message.payload)?;
+        let timestamp = self.resolve_timestamp(source_id, &message.header, true)?;
+        self.last_timestamps.insert(source_id, timestamp);
+
+        Ok(Decoded::Confirmation {
+            source_id,
+            timestamp: timestamp as u64,
+            ref_seq: message.header.sequence,
+        })
+    }
+
+    /// Start recording decoded values for [`Decoder::estimate_at`]
+    pub fn enable_gap_fill(&mut self, config: GapFillConfig) {
+        self.gap_fill = Some(config);
+    }
+
+    /// Stop gap filling and forget the recorded values
+    pub fn disable_gap_fill(&mut self) {
+        self.gap_fill = None;
+        self.decoded_points.clear();
+    }
+
+    /// Gap-filling configuration, if enabled
+    pub fn gap_fill(&self) -> Option<GapFillConfig> {
+        self.gap_fill
+    }
+
+    /// Record a decoded value, keeping the history sorted by timestamp
+    fn record_point(&mut self, source_id: u32, timestamp_ms: u64, value: f64) {
+        let points = self.decoded_points.entry(source_id).or_default();
+        match points.binary_search_by(|(t, _)| t.cmp(&timestamp_ms)) {
+            Ok(index) => points[index].1 = value,
+            Err(index) => points.insert(index, (timestamp_ms, value)),
+        }
+        if points.len() > GAP_FILL_HISTORY {
+            points.remove(0);
+        }
+    }
+
+    /// Estimate a source's value at a timestamp with no decoded message
+    ///
+    /// `timestamp_ms` is on the decoded timestamps' scale in milliseconds
+    /// (`DecodedData::timestamp_micros / 1000`), and `context` is the one
+    /// decoded values are observed into. Returns `None` when gap filling is
+    /// off, when a value was decoded at exactly that time, before the
+    /// recorded history, or when the gap exceeds `max_gap_ms`: a gap closed
+    /// by a later value counts its full length, an open gap the time since
+    /// the last decoded value.
+    pub fn estimate_at(
+        &self,
+        source_id: u32,
+        timestamp_ms: u64,
+        context: &Context,
+    ) -> Option<EstimatedValue> {
+        let config = self.gap_fill?;
+        let points = self.decoded_points.get(&source_id)?;
+        let after = points.partition_point(|(t, _)| *t <= timestamp_ms);
+        let (before_t, before_v) = *points.get(after.checked_sub(1)?)?;
+        if before_t == timestamp_ms {
+            return None;
+        }
+
+        let elapsed = timestamp_ms - before_t;
+        let (value, method, confidence) = match points.get(after) {
+            Some(&(next_t, next_v)) => {
+                if next_t - before_t > config.max_gap_ms {
+                    return None;
+                }
+                match config.method {
+                    GapFillMethod::Hold => (before_v, GapFillMethod::Hold, 1.0),
+                    GapFillMethod::Linear | GapFillMethod::Predict => {
+                        let fraction = elapsed as f64 / (next_t - before_t) as f64;
+                        let value = before_v + (next_v - before_v) * fraction;
+                        (value, GapFillMethod::Linear, 1.0)
+                    }
+                }
+            }
+            None => {
+                if elapsed > config.max_gap_ms {
+                    return None;
+                }
+                let previous = after.checked_sub(2).and_then(|i| points.get(i));
+                let prediction = context.predict(source_id);
+                match (config.method, previous, prediction) {
+                    (GapFillMethod::Linear, Some(&(prev_t, prev_v)), _) => {
+                        let slope = (before_v - prev_v) / (before_t - prev_t) as f64;
+                        (
+                            before_v + slope * elapsed as f64,
+                            GapFillMethod::Linear,
+                            1.0,
+                        )
+                    }
+                    (GapFillMethod::Predict, _, Some(prediction)) => (
+                        prediction.value,
+                        GapFillMethod::Predict,
+                        prediction.confidence,
+                    ),
+                    _ => (before_v, GapFillMethod::Hold, 1.0),
+                }
+            }
+        };
+
+        // Confidence fades linearly to zero at `max_gap_ms`
+        let fade = if config.max_gap_ms == 0 {
+            0.0
+        } else {
+            1.0 - elapsed as f64 / config.max_gap_ms as f64
+        };
+        Some(EstimatedValue {
+            value,
+            method,
+            confidence: (confidence as f64 * fade) as f32,
+        })
+    }
+
+    /// Reconstruct the absolute header timestamp for a source
+    ///
+    /// Compressed timestamps need a previous timestamp from the same
+    /// source; without one the caller must obtain an anchor (see
+    /// [`crate::Encoder::force_timestamp_anchor`]). A header whose
+    /// sequence does not follow the previous one means messages were
+    /// lost, possibly carrying timestamps the next compressed ones are
+    /// relative to: every source then needs a new anchor. Confirmations
+    /// carry the sequence of the value they confirm (`repeats_sequence`),
+    /// which may be the previous one.
+    fn resolve_timestamp(
+        &mut self,
+        source_id: u32,
+        header: &MessageHeader,
+        repeats_sequence: bool,
+    ) -> Result<u32> {
+        let follows = self.timestamp_sequence.map_or(true, |last| {
+            header.sequence == last.wrapping_add(1) || (repeats_sequence && header.sequence == last)
+        });
+        if !follows {
+            self.last_timestamps.clear();
+        }
+        self.timestamp_sequence = Some(header.sequence);
+
+        let format = header.timestamp_format();
+        if format == TimestampFormat::Full {
+            return Ok(header.timestamp);
+        }
+
+        let last = *self
+            .last_timestamps
+            .get(&source_id)
+            .ok_or(DecodeError::MissingTimestampAnchor { source_id })?;
+        let regression = DecodeError::TimestampRegression { source_id, last };
+
+        let forward = match format {
+            TimestampFormat::Delta => {
+                let delta = TimestampFormat::unpack_delta(header.timestamp);
+                if delta < 0 {
+                    return Err(regression.into());
+                }
+                delta as u64
+            }
+            _ => {
+                let (low, bits) = TimestampFormat::unpack_truncated(header.timestamp);
+                if bits == 0 || bits > crate::protocol::MAX_TRUNCATED_TIMESTAMP_BITS {
+                    return Err(DecodeError::InvalidHeader.into());
+                }
+                let mask = (1u32 << bits) - 1;
+                let forward = low.wrapping_sub(last) & mask;
+                // A jump of half the range or more means the low bits went
+                // backwards
+                if forward >= 1u32 << (bits - 1) {
+                    return Err(regression.into());
+                }
+                forward as u64
+            }
+        };
+
+        u32::try_from(last as u64 + forward).map_err(|_| DecodeError::InvalidHeader.into())
+    }
+
+    /// Forget the last timestamp of every source
+    ///
+    /// The next compressed timestamp of each source is rejected with
+    /// [`DecodeError::MissingTimestampAnchor`] until an anchor arrives.
+    pub fn clear_timestamps(&mut self) {
+        self.last_timestamps.clear();
+        self.timestamp_sequence = None;
+    }
+
+    /// Whether values of `source_id` are decoded from the sender's
+    /// prediction state
+    ///
+    /// A self-describing message seeds that state for a source the
+    /// context cannot predict, e.g. on a receiver that joined mid-stream
+    /// (see [`crate::EncoderConfig::self_describing_interval`]). The
+    /// decoder then follows it with every value it decodes for the
+    /// source, assuming the sender observes each value it sends with
+    /// [`Context::observe`]; later self-describing messages correct any
+    /// drift.
+    pub fn is_seeded(&self, source_id: u32) -> bool {
+        self.seeded.contains_key(&source_id)
+    }
+
+    /// Forget the prediction state seeded by self-describing messages
+    ///
+    /// Call this once the context is synchronized with the sender's.
+    pub fn clear_seeds(&mut self) {
+        self.seeded.clear();
+    }
 
-impl Decoder {
-    /// Create a new decoder
-    pub fn new() -> Self {
-        Self {
-            verify_checksum: false,
-            last_sequence: None,
-            last_fixed_sequence: None,
-            last_fixed_ctx_version: None,
-        }
+    /// Decode from raw bytes (with optional checksum verification)
+    ///
+    /// A checksum is expected only when the checksum policy covers the
+    /// priority in the message header. Plan-relative frames (see
+    /// [`TransmissionPlan`]) never carry one.
+    pub fn decode_bytes(&mut self, bytes: &[u8], context: &Context) -> Result<DecodedData> {
+        self.recorded(SessionInput::Decode, &[], bytes, |decoder| {
+            let result = decoder.decode_bytes_observed(bytes, context);
+            decoder.record_outcome(&result);
+            result
+        })
     }
 
-    /// Create decoder with checksum verification enabled
-    pub fn with_checksum_verification() -> Self {
-        Self {
-            verify_checksum: true,
-            last_sequence: None,
-            last_fixed_sequence: None,
-            last_fixed_ctx_version: None,
-        }
+    /// Decode raw bytes and observe the decoded value into `context`
+    ///
+    /// The receiving side of a context kept in step with the sender's: as
+    /// [`Decoder::decode_bytes`], then `context` observes the value at the
+    /// decoded source and timestamp. A failed decode leaves `context`
+    /// unchanged.
+    pub fn decode_into(&mut self, bytes: &[u8], context: &mut Context) -> Result<DecodedData> {
+        self.recorded(SessionInput::DecodeInto, &[], bytes, |decoder| {
+            let decoded = decoder.decode_bytes(bytes, context)?;
+            context.observe(&crate::protocol::RawData::with_source(
+                decoded.source_id,
+                decoded.value,
+                decoded_millis(&decoded),
+            ));
+            Ok(decoded)
+        })
     }
 
-    /// Check if checksum verification is enabled
-    pub fn checksum_verification_enabled(&self) -> bool {
-        self.verify_checksum
+    /// Apply a serialized [`SyncMessage::Diff`] to `context`
+    ///
+    /// As [`Synchronizer::apply_diff`], from the bytes received.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`DecodeError::MalformedMessage`] if `bytes` is not a
+    /// diff, or with the error of [`Synchronizer::apply_diff`].
+    pub fn apply_sync_diff(&mut self, bytes: &[u8], context: &mut Context) -> Result<()> {
+        self.recorded(
+            SessionInput::SyncDiff,
+            &[],
+            bytes,
+            |_| match SyncMessage::from_bytes(bytes) {
+                Some(SyncMessage::Diff(diff)) => Synchronizer::apply_diff(context, &diff),
+                _ => Err(DecodeError::MalformedMessage {
+                    offset: 0,
+                    reason: "not a sync diff".to_string(),
+                }
+                .into()),
+            },
+        )
     }
 
-    /// Decode a message
-    pub fn decode(&mut self, message: &EncodedMessage, context: &Context) -> Result<DecodedData> {
-        // Check for sequence gaps
-        if let Some(last_seq) = self.last_sequence {
-            let expected = last_seq.wrapping_add(1);
-            if message.header.sequence != expected {
-                // Sequence gap detected (could log warning)
-                // For now, just continue
-            }
-        }
-        self.last_sequence = Some(message.header.sequence);
+    /// Replace `context`'s dictionary with a
+    /// [`Context::export_full`] export
+    ///
+    /// As [`Context::import_full`].
+    pub fn import_context(&mut self, bytes: &[u8], context: &mut Context) -> Result<()> {
+        self.recorded(SessionInput::Import, &[], bytes, |_| {
+            context.import_full(bytes)
+        })
+    }
 
-        // Parse payload
-        let payload = &message.payload;
-        if payload.is_empty() {
-            return Err(DecodeError::BufferTooShort {
-                needed: 1,
-                available: 0,
+    /// Decode raw bytes received at `now_ms`, checking the message age
+    ///
+    /// As [`Decoder::decode_bytes`], then sets [`DecodedData::age_ms`] to
+    /// the time elapsed since the decoded timestamp (0 for a timestamp
+    /// ahead of `now_ms`) and counts it in [`Decoder::stats`]. A message
+    /// older than the [`StaleThreshold`] of its priority is flagged with
+    /// [`DecodedData::stale`] or rejected with [`DecodeError::Stale`].
+    ///
+    /// `now_ms` is on the decoded timestamps' scale in milliseconds
+    /// (`DecodedData::timestamp_micros / 1000`), whatever unit the message
+    /// records. A rejected message still updates
+    /// the decoder state, like any decoded message; a receiver keeping its
+    /// context in step with the sender's must then observe the message
+    /// anyway, or flag instead of rejecting.
+    pub fn decode_with_now(
+        &mut self,
+        bytes: &[u8],
+        context: &Context,
+        now_ms: u64,
+    ) -> Result<DecodedData> {
+        self.recorded(
+            SessionInput::DecodeAt,
+            &now_ms.to_le_bytes(),
+            bytes,
+            |decoder| decoder.decode_with_now_unrecorded(bytes, context, now_ms),
+        )
+    }
+
+    fn decode_with_now_unrecorded(
+        &mut self,
+        bytes: &[u8],
+        context: &Context,
+        now_ms: u64,
+    ) -> Result<DecodedData> {
+        let mut decoded = self.decode_bytes(bytes, context)?;
+        let measured_ms = decoded_millis(&decoded);
+        let age_ms = now_ms.saturating_sub(measured_ms);
+        decoded.age_ms = Some(age_ms);
+        self.stats.age_histogram.record(age_ms);
+        if let Some(log) = &mut self.latency {
+            // Plan-relative frames have no sequence to correlate by
+            if let Some((header, _)) = MessageHeader::parse_in(bytes, &self.config.dialect) {
+                log.record_decoded(header.sequence, decoded.priority, measured_ms, now_ms);
             }
-            .into());
         }
 
-        // Decode source ID (varint)
-        let (source_id, offset) = self.decode_varint(payload)?;
-
-        if offset >= payload.len() {
-            return Err(DecodeError::BufferTooShort {
-                needed: offset + 1,
-                available: payload.len(),
+        let Some(threshold) = self.config.stale_threshold(decoded.priority) else {
+            return Ok(decoded);
+        };
+        if age_ms <= threshold.max_age_ms {
+            return Ok(decoded);
+        }
+        match threshold.action {
+            StaleAction::Flag => {
+                self.stats.stale_flagged += 1;
+                decoded.stale = true;
+                Ok(decoded)
+            }
+            StaleAction::Reject => {
+                self.stats.stale_rejected += 1;
+                Err(DecodeError::Stale {
+                    source_id: decoded.source_id,
+                    priority: decoded.priority,
+                    age_ms,
+                    max_age_ms: threshold.max_age_ms,
+                }
+                .into())
             }
-            .into());
         }
+    }
 
-        // Decode encoding type
-        let encoding_byte = payload[offset];
-        let encoding_type = EncodingType::from_u8(encoding_byte)
-            .ok_or(DecodeError::UnknownEncodingType(encoding_byte))?;
+    /// Message ages and staleness counts
+    pub fn stats(&self) -> &DecoderStats {
+        &self.stats
+    }
 
-        // Decode value based on encoding type
-        let value = self.decode_value(encoding_type, &payload[offset + 1..], source_id, context)?;
+    /// Record the decode time of the last `capacity` messages decoded
+    /// with [`Decoder::decode_with_now`], for [`crate::latency`]
+    ///
+    /// Their end-to-end latency runs from the header timestamp, so it has
+    /// the resolution of the recorded timestamp unit (one second when none
+    /// is recorded); merge the sender's log for finer stages.
+    /// Replaces any log kept so far.
+    pub fn enable_latency_tracking(&mut self, capacity: usize) {
+        self.latency = Some(LatencyLog::new(capacity));
+    }
 
-        Ok(DecodedData::new(
-            source_id,
-            message.header.timestamp as u64,
-            value,
-            message.header.priority,
-        ))
+    /// Stop recording decode times, dropping the log
+    pub fn disable_latency_tracking(&mut self) {
+        self.latency = None;
     }
 
-    /// Decode from raw bytes (with optional checksum verification)
-    pub fn decode_bytes(&mut self, bytes: &[u8], context: &Context) -> Result<DecodedData> {
-        let message = if self.verify_checksum {
-            EncodedMessage::from_bytes_with_checksum(bytes)?
-        } else {
-            EncodedMessage::from_bytes(bytes).ok_or(DecodeError::InvalidHeader)?
-        };
-        self.decode(&message, context)
+    /// Decode times of recent messages, if latency tracking is enabled
+    pub fn latency_log(&self) -> Option<&LatencyLog> {
+        self.latency.as_ref()
     }
 
-    /// Decode a varint from the buffer
-    fn decode_varint(&self, buffer: &[u8]) -> Result<(u32, usize)> {
-        let mut result: u32 = 0;
-        let mut shift = 0;
-        let mut offset = 0;
+    /// End-to-end latencies per priority, if latency tracking is enabled
+    pub fn latency_report(&self) -> Option<&LatencyReport> {
+        self.latency.as_ref().map(LatencyLog::report)
+    }
 
-        loop {
-            if offset >= buffer.len() {
-                return Err(DecodeError::BufferTooShort {
-                    needed: offset + 1,
-                    available: buffer.len(),
-                }
-                .into());
+    /// Decode raw bytes and notify the observer
+    fn decode_bytes_observed(&mut self, bytes: &[u8], context: &Context) -> Result<DecodedData> {
+        let stopwatch = self.observer.as_ref().map(|_| Stopwatch::start());
+        if bytes.first() == Some(&PLAN_RELATIVE_MARKER) {
+            let result = self.decode_plan_relative(bytes);
+            if let Some(stopwatch) = stopwatch {
+                let integrity = IntegrityStatus::NotChecked;
+                self.notify_single(&stopwatch, None, bytes.len(), integrity, &result);
             }
+            return result;
+        }
+        let verify_checksum = self.expects_checksum(bytes);
+        let dialect = &self.config.dialect;
+        let parsed = if verify_checksum {
+            EncodedMessageRef::from_bytes_with_checksum_in(bytes, dialect).map_err(AlecError::from)
+        } else {
+            EncodedMessageRef::from_bytes_in(bytes, dialect)
+                .ok_or_else(|| DecodeError::InvalidHeader.into())
+        };
 
-            let byte = buffer[offset];
-            result |= ((byte & 0x7F) as u32) << shift;
-            offset += 1;
+        let Some(stopwatch) = stopwatch else {
+            return self.decode_message(&parsed?, context);
+        };
 
-            if byte & 0x80 == 0 {
-                break;
+        let integrity = match (&parsed, verify_checksum) {
+            (_, false) => IntegrityStatus::NotChecked,
+            (Err(AlecError::Decode(e)), true) if e.checksum_algorithm().is_some() => {
+                IntegrityStatus::Failed
             }
-
-            shift += 7;
-            if shift >= 32 {
-                return Err(DecodeError::MalformedMessage {
-                    offset,
-                    reason: "Varint too long".to_string(),
-                }
-                .into());
+            (Err(_), true) => IntegrityStatus::NotChecked,
+            (Ok(_), true) => IntegrityStatus::Verified,
+        };
+        match parsed {
+            Ok(message) => {
+                let result = self.decode_message(&message, context);
+                self.notify_single(&stopwatch, Some(&message), bytes.len(), integrity, &result);
+                result
+            }
+            Err(e) => {
+                self.notify(&stopwatch, None, None, bytes.len(), integrity, Some(&e));
+                Err(e)
             }
         }
+    }
 
-        Ok((result, offset))
+    /// Decode a varint from the buffer
+    fn decode_varint(&self, buffer: &[u8]) -> Result<(u32, usize)> {
+        match read_varint(buffer) {
+            Some(varint) => Ok(varint),
+            // Only the fifth byte can overflow
+            None if buffer.len() < 5 => Err(DecodeError::BufferTooShort {
+                needed: buffer.len() + 1,
+                available: buffer.len(),
+            }
+            .into()),
+            None => Err(DecodeError::MalformedMessage {
+                offset: 4,
+                reason: "Varint overflows u32".to_string(),
+            }
+            .into()),
+        }
     }
 
     /// Decode value based on encoding type
@@ -202,7 +1551,90 @@ impl Decoder {
                 reason: "Multi encoding should use decode_multi".to_string(),
             }
             .into()),
+            EncodingType::Plan => Err(DecodeError::MalformedMessage {
+                offset: 0,
+                reason: "Plan carries no value".to_string(),
+            }
+            .into()),
+            EncodingType::Composite => Err(DecodeError::MalformedMessage {
+                offset: 0,
+                reason: "Composite encoding should use decode_composite".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Decode a self-describing value, seeding the sender's prediction
+    /// state if the context cannot predict the source
+    ///
+    /// The raw value is followed by the sender's scale factor and
+    /// observation count (varints), EMA and EMA alpha (f64 BE).
+    fn decode_self_describing(
+        &mut self,
+        data: &[u8],
+        source_id: u32,
+        context: &Context,
+    ) -> Result<f64> {
+        let value = self.decode_raw64(data)?;
+        let state = &data[8..];
+        let (scale, offset) = self.decode_varint(state)?;
+        let (count, len) = self.decode_varint(&state[offset..])?;
+        let offset = offset + len;
+        if state.len() < offset + 16 {
+            return Err(DecodeError::BufferTooShort {
+                needed: offset + 16,
+                available: state.len(),
+            }
+            .into());
+        }
+        let ema = f64::from_be_bytes(state[offset..offset + 8].try_into().unwrap());
+        let alpha = f64::from_be_bytes(state[offset + 8..offset + 16].try_into().unwrap());
+
+        if context.predict(source_id).is_none() || self.seeded.contains_key(&source_id) {
+            self.seeded.insert(
+                source_id,
+                SeededSource {
+                    last_value: value,
+                    ema: Ema::with_value(alpha, (count > 0).then_some(ema)),
+                    count: count as u64,
+                    scale: scale.max(1) as f64,
+                },
+            );
+        }
+        Ok(value)
+    }
+
+    /// Decode a value of a seeded source, predicted from the seed rather
+    /// than the context
+    fn decode_seeded(
+        &self,
+        seed: &SeededSource,
+        encoding_type: EncodingType,
+        data: &[u8],
+        source_id: u32,
+        context: &Context,
+    ) -> Result<f64> {
+        let width = match encoding_type {
+            EncodingType::Delta8 => 1,
+            EncodingType::Delta16 => 2,
+            EncodingType::Delta32 => 4,
+            EncodingType::Repeated => return Ok(seed.last_value),
+            EncodingType::Interpolated => return Ok(seed.prediction()),
+            _ => return self.decode_value(encoding_type, data, source_id, context),
+        };
+        if data.len() < width {
+            return Err(DecodeError::BufferTooShort {
+                needed: width,
+                available: data.len(),
+            }
+            .into());
         }
+        let delta = match width {
+            1 => data[0] as i8 as i32,
+            2 => i16::from_be_bytes([data[0], data[1]]) as i32,
+            _ => i32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+        };
+        Ok(seed.prediction() + delta as f64 / seed.scale)
     }
 
     /// Decode raw f64
@@ -378,12 +1810,229 @@ impl Decoder {
     /// Handles all per-channel encoding types (Raw64, Raw32, Delta8, Delta16,
     /// Delta32, Repeated, Interpolated). The `name_id` of each channel is used
     /// as its `source_id` (cast to `u32`) for context-dependent decodings.
+    ///
+    /// Thin wrapper over [`Decoder::decode_multi_ex`] keeping only the
+    /// `(name_id, value)` pairs.
     pub fn decode_multi(
         &mut self,
         message: &EncodedMessage,
         context: &Context,
     ) -> Result<Vec<(u8, f64)>> {
-        let payload = &message.payload;
+        let values = self.decode_multi_ex(message, context)?;
+        Ok(values
+            .into_iter()
+            .map(|v| (v.name_id as u8, v.value))
+            .collect())
+    }
+
+    /// Decode multi-value message into structured values.
+    ///
+    /// Every value carries the frame timestamp when the header holds a full
+    /// timestamp. A message with zero values decodes to an empty vec.
+    ///
+    /// ```
+    /// use alec::{Context, Decoder, Encoder, Priority};
+    ///
+    /// let context = Context::new();
+    /// let message = Encoder::new().encode_multi(
+    ///     &[(1, 21.5), (2, 48.0)],
+    ///     0,
+    ///     60_000,
+    ///     Priority::P3Normal,
+    ///     &context,
+    /// ).unwrap();
+    ///
+    /// let values = Decoder::new().decode_multi_ex(&message, &context).unwrap();
+    /// assert_eq!(values.len(), 2);
+    /// assert_eq!(values[1].name_id, 2);
+    /// assert_eq!(values[1].timestamp, Some(60));
+    /// ```
+    pub fn decode_multi_ex(
+        &mut self,
+        message: &EncodedMessage,
+        context: &Context,
+    ) -> Result<Vec<DecodedValue>> {
+        let message = message.as_borrowed();
+        let stopwatch = self.observer.as_ref().map(|_| Stopwatch::start());
+        let result = self.decode_multi_message(&message, context);
+        if let Some(stopwatch) = stopwatch {
+            self.notify(
+                &stopwatch,
+                None,
+                Some(&message),
+                message.len(),
+                IntegrityStatus::NotChecked,
+                result.as_ref().err(),
+            );
+        }
+        result
+    }
+
+    /// Decode a composite message (see [`crate::Encoder::encode_composite`])
+    ///
+    /// `context_of` returns the context of each channel id, or `None` for
+    /// a channel the receiver does not know. Values are decoded against
+    /// source id 0 of their channel's context, which is not updated:
+    /// observe each value in its channel's context before decoding the
+    /// next message. An entry that fails (unknown channel, pattern or
+    /// encoding) only fails its own [`CompositeValue`]; the other entries
+    /// still decode.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the message is not a composite, is malformed, or exceeds
+    /// the decoder's resource limits.
+    pub fn decode_composite<'c>(
+        &mut self,
+        message: &EncodedMessage,
+        context_of: impl FnMut(u32) -> Option<&'c Context>,
+    ) -> Result<DecodedComposite> {
+        self.decode_composite_ref(&message.as_borrowed(), context_of)
+    }
+
+    /// Decode a composite message parsed in place, as by
+    /// [`Decoder::decode_composite`]
+    pub fn decode_composite_ref<'c>(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        context_of: impl FnMut(u32) -> Option<&'c Context>,
+    ) -> Result<DecodedComposite> {
+        let stopwatch = self.observer.as_ref().map(|_| Stopwatch::start());
+        let result = self.decode_composite_message(message, context_of);
+        if let Some(stopwatch) = stopwatch {
+            self.notify(
+                &stopwatch,
+                result.as_ref().ok().map(|decoded| decoded.source_id),
+                Some(message),
+                message.len(),
+                IntegrityStatus::NotChecked,
+                result.as_ref().err(),
+            );
+        }
+        result
+    }
+
+    /// Decode a composite message without notifying the observer
+    fn decode_composite_message<'c>(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        mut context_of: impl FnMut(u32) -> Option<&'c Context>,
+    ) -> Result<DecodedComposite> {
+        self.check_header(&message.header)?;
+        if !message.is_composite() {
+            return Err(DecodeError::MalformedMessage {
+                offset: 0,
+                reason: "Expected Composite encoding".to_string(),
+            }
+            .into());
+        }
+
+        let payload = message.payload;
+        let (source_id, mut offset) = self.decode_varint(payload)?;
+        // Encoding byte, checked above
+        offset += 1;
+        let (count, len) = self.decode_varint(&payload[offset..])?;
+        offset += len;
+
+        // Bound the work before allocating for the claimed count
+        let count = count as usize;
+        check_limit(
+            ResourceLimit::ValuesPerMessage,
+            self.config.max_values_per_message as u64,
+            count as u64,
+        )?;
+        check_limit(
+            ResourceLimit::ExpansionBytes,
+            self.config.max_expansion_bytes as u64,
+            (count * core::mem::size_of::<CompositeValue>()) as u64,
+        )?;
+
+        let timestamp = self.resolve_timestamp(source_id, &message.header, false)?;
+        // Each entry takes at least its channel id, encoding and length
+        let mut values = Vec::with_capacity(count.min((payload.len() - offset) / 3));
+        let stopwatch = Stopwatch::start();
+
+        for _ in 0..count {
+            self.check_decode_time(&stopwatch)?;
+
+            let (channel_id, len) = self.decode_varint(&payload[offset..])?;
+            offset += len;
+            let encoding_byte = *payload.get(offset).ok_or(DecodeError::BufferTooShort {
+                needed: offset + 1,
+                available: payload.len(),
+            })?;
+            offset += 1;
+            let (value_size, len) = self.decode_varint(&payload[offset..])?;
+            offset += len;
+            let end = offset + value_size as usize;
+            let data = payload
+                .get(offset..end)
+                .ok_or(DecodeError::BufferTooShort {
+                    needed: end,
+                    available: payload.len(),
+                })?;
+
+            let value = self
+                .decode_composite_value(encoding_byte, data, offset, channel_id, &mut context_of)
+                .map_err(|e| match e {
+                    AlecError::Decode(e) => e,
+                    other => DecodeError::MalformedMessage {
+                        offset,
+                        reason: other.to_string(),
+                    },
+                });
+            values.push(CompositeValue { channel_id, value });
+            offset = end;
+        }
+
+        self.track_sequence(message.header.sequence);
+        self.last_timestamps.insert(source_id, timestamp);
+        Ok(DecodedComposite {
+            source_id,
+            timestamp: timestamp as u64,
+            priority: message.header.priority,
+            values,
+        })
+    }
+
+    /// Decode the value of one composite entry, `data` starting at `offset`
+    fn decode_composite_value<'c>(
+        &self,
+        encoding_byte: u8,
+        data: &[u8],
+        offset: usize,
+        channel_id: u32,
+        context_of: &mut impl FnMut(u32) -> Option<&'c Context>,
+    ) -> Result<f64> {
+        let encoding = EncodingType::from_u8(encoding_byte)
+            .ok_or(DecodeError::UnknownEncodingType(encoding_byte))?;
+        if value_len(encoding, data) != Some(data.len())
+            || matches!(encoding, EncodingType::Plan | EncodingType::Multi)
+        {
+            return Err(DecodeError::MalformedMessage {
+                offset,
+                reason: format!("Invalid {:?} composite entry", encoding),
+            }
+            .into());
+        }
+        let context = context_of(channel_id).ok_or(DecodeError::UnknownChannel { channel_id })?;
+        self.decode_value(encoding, data, 0, context)
+    }
+
+    /// Decode a multi-value message without notifying the observer
+    fn decode_multi_message(
+        &mut self,
+        message: &EncodedMessageRef<'_>,
+        context: &Context,
+    ) -> Result<Vec<DecodedValue>> {
+        self.check_header(&message.header)?;
+
+        let timestamp = match message.header.timestamp_format() {
+            TimestampFormat::Full => Some(message.header.timestamp as u64),
+            _ => None,
+        };
+
+        let payload = message.payload;
 
         // Source ID (frame-level, ignored for per-channel decode)
         let (_source_id, mut offset) = self.decode_varint(payload)?;
@@ -420,9 +2069,25 @@ impl Decoder {
         let count = payload[offset] as usize;
         offset += 1;
 
-        let mut values = Vec::with_capacity(count);
+        // Bound the work before allocating for the claimed count
+        check_limit(
+            ResourceLimit::ValuesPerMessage,
+            self.config.max_values_per_message as u64,
+            count as u64,
+        )?;
+        check_limit(
+            ResourceLimit::ExpansionBytes,
+            self.config.max_expansion_bytes as u64,
+            (count * core::mem::size_of::<DecodedValue>()) as u64,
+        )?;
+
+        // Each value takes at least its name id and encoding bytes
+        let mut values = Vec::with_capacity(count.min((payload.len() - offset) / 2));
+        let stopwatch = Stopwatch::start();
 
         for _ in 0..count {
+            self.check_decode_time(&stopwatch)?;
+
             // Name ID (1 byte)
             if offset >= payload.len() {
                 return Err(DecodeError::BufferTooShort {
@@ -494,7 +2159,12 @@ impl Decoder {
                 }
             };
 
-            values.push((name_id, value));
+            values.push(DecodedValue {
+                name_id: name_id as u16,
+                value,
+                timestamp,
+                quality: None,
+            });
         }
 
         Ok(values)
@@ -503,8 +2173,13 @@ impl Decoder {
     /// Reset decoder state
     pub fn reset(&mut self) {
         self.last_sequence = None;
+        self.sequence.restart();
         self.last_fixed_sequence = None;
         self.last_fixed_ctx_version = None;
+        self.last_timestamps.clear();
+        self.timestamp_sequence = None;
+        self.decoded_points.clear();
+        self.plans.clear();
     }
 
     /// Get last decoded sequence number
@@ -512,6 +2187,21 @@ impl Decoder {
         self.last_sequence
     }
 
+    /// Gaps, duplicates, stale numbers and wraps among the decoded
+    /// sequence numbers
+    ///
+    /// The decoder follows a single stream: an encoder numbers all its
+    /// sources with one counter. Kept across [`Decoder::reset`].
+    pub fn sequence_stats(&self) -> &SequenceStats {
+        self.sequence.stats()
+    }
+
+    /// Record the sequence number of a decoded message
+    fn track_sequence(&mut self, sequence: u16) {
+        self.last_sequence = Some(sequence);
+        self.sequence.observe(sequence as u32);
+    }
+
     /// Last sequence decoded via `decode_multi_fixed` (separate tracker
     /// from the legacy multi-frame path so the two can coexist in the
     /// same `Decoder`).
@@ -574,6 +2264,14 @@ impl Decoder {
             }
             .into());
         }
+        // Bounded at 64 channels to mirror the encoder's upper limit.
+        if channel_count > 64 {
+            return Err(DecodeError::MalformedMessage {
+                offset: 0,
+                reason: "channel_count > 64 not supported by fixed wire format".to_string(),
+            }
+            .into());
+        }
         if output.len() < channel_count {
             return Err(DecodeError::BufferTooShort {
                 needed: channel_count,
@@ -642,15 +2340,7 @@ impl Decoder {
         let data_start = bitmap_start + bitmap_bytes;
 
         // Parse each channel's encoding from the bitmap, 2 bits LSB-first per byte.
-        // Use a stack-allocated scratch array (bounded at 64 channels
-        // to mirror the encoder's upper limit).
-        if channel_count > 64 {
-            return Err(DecodeError::MalformedMessage {
-                offset: 0,
-                reason: "channel_count > 64 not supported by fixed wire format".to_string(),
-            }
-            .into());
-        }
+        // Use a stack-allocated scratch array (channel_count <= 64).
         let mut encodings: [FixedEncoding; 64] = [FixedEncoding::Repeated; 64];
         for i in 0..channel_count {
             let bits = (input[bitmap_start + i / 4] >> ((i % 4) * 2)) & 0b11;
@@ -760,6 +2450,54 @@ impl Default for Decoder {
     }
 }
 
+// HealthCheckable implementation for Decoder
+#[cfg(feature = "std-time")]
+impl crate::health::HealthCheckable for Decoder {
+    fn health_check(&self) -> crate::health::HealthCheck {
+        self.health_check_with(&crate::health::HealthConfig::default())
+    }
+
+    fn health_check_with(
+        &self,
+        config: &crate::health::HealthConfig,
+    ) -> crate::health::HealthCheck {
+        use crate::health::HealthCheck;
+
+        let error_rate = self.decode_error_rate();
+        let checksum_failures = self.recent_checksum_failures();
+        let details = format!(
+            "Recent decodes: {}, Error rate: {:.0}%, Checksum failures: {}",
+            self.decode_errors.count(),
+            error_rate * 100.0,
+            checksum_failures
+        );
+
+        if checksum_failures > config.unhealthy_checksum_failures {
+            HealthCheck::unhealthy(
+                "Decoder",
+                format!(
+                    "Checksum failures {} above {} ({})",
+                    checksum_failures, config.unhealthy_checksum_failures, details
+                ),
+            )
+        } else if self.decode_errors.count() >= config.min_window_messages
+            && error_rate > config.unhealthy_decode_error_rate
+        {
+            HealthCheck::unhealthy(
+                "Decoder",
+                format!(
+                    "Error rate {:.0}% above {:.0}% ({})",
+                    error_rate * 100.0,
+                    config.unhealthy_decode_error_rate * 100.0,
+                    details
+                ),
+            )
+        } else {
+            HealthCheck::healthy("Decoder").with_message(details)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -845,13 +2583,15 @@ mod tests {
 
         let values: Vec<(u8, f64)> = vec![(1, 22.5), (2, 65.0), (3, 1013.25)];
 
-        let message = encoder.encode_multi(
-            &values,
-            42,
-            12345,
-            crate::protocol::Priority::P3Normal,
-            &context,
-        );
+        let message = encoder
+            .encode_multi(
+                &values,
+                42,
+                12345,
+                crate::protocol::Priority::P3Normal,
+                &context,
+            )
+            .unwrap();
 
         let decoded = decoder.decode_multi(&message, &context).unwrap();
 
@@ -871,15 +2611,8 @@ mod tests {
 
         for value in test_values {
             let mut buffer = Vec::new();
-            // Encode
-            let mut v = value;
-            while v >= 0x80 {
-                buffer.push((v as u8 & 0x7F) | 0x80);
-                v >>= 7;
-            }
-            buffer.push(v as u8);
+            crate::protocol::write_varint(u64::from(value), &mut buffer);
 
-            // Decode
             let (decoded, _) = decoder.decode_varint(&buffer).unwrap();
             assert_eq!(decoded, value);
         }
@@ -976,4 +2709,43 @@ mod tests {
         let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
         assert!((decoded.value - data.value).abs() < 0.001);
     }
+
+    #[test]
+    fn test_checksum_policy_by_priority() {
+        use crate::classifier::{Classification, ClassificationReason};
+        use crate::encoder::EncoderConfig;
+        use crate::protocol::{ChecksumPolicy, Priority, CHECKSUM_SIZE};
+
+        let policy = ChecksumPolicy::ForPriorityAtOrAbove(Priority::P2Important);
+        let mut encoder = Encoder::with_config(EncoderConfig {
+            checksum_policy: policy,
+            ..Default::default()
+        });
+        let mut decoder = Decoder::with_checksum_policy(policy);
+        let context = Context::new();
+        let data = RawData::new(42.5, 12345);
+        let classify =
+            |priority| Classification::new(priority, ClassificationReason::UserRequested, 0.0, 1.0);
+
+        let critical = encoder.encode_to_bytes(&data, &classify(Priority::P1Critical), &context);
+        let deferred = encoder.encode_to_bytes(&data, &classify(Priority::P4Deferred), &context);
+        assert_eq!(critical.len(), deferred.len() + CHECKSUM_SIZE);
+
+        let decoded = decoder.decode_bytes(&critical, &context).unwrap();
+        assert_eq!(decoded.priority, Priority::P1Critical);
+        let decoded = decoder.decode_bytes(&deferred, &context).unwrap();
+        assert_eq!(decoded.priority, Priority::P4Deferred);
+
+        // Covered priority: corruption is detected
+        let mut corrupted = critical.clone();
+        corrupted[5] ^= 0xFF;
+        assert!(matches!(
+            decoder.decode_bytes(&corrupted, &context),
+            Err(AlecError::Decode(DecodeError::InvalidChecksum { .. }))
+        ));
+
+        // Covered priority without its checksum is rejected
+        let truncated = &critical[..critical.len() - CHECKSUM_SIZE];
+        assert!(decoder.decode_bytes(truncated, &context).is_err());
+    }
 }
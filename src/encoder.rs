@@ -34,16 +34,248 @@
 //! ```
 
 #[cfg(not(feature = "std"))]
-use alloc::{vec, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::classifier::{Classification, Classifier};
+use crate::classifier::{AnomalyLabel, Classification, Classifier};
 use crate::context::Context;
 use crate::error::{EncodeError, Result};
-use crate::metrics::CompressionMetrics;
+use crate::latency::{LatencyLog, LatencyReport};
+use crate::metrics::{CompressionMetrics, OutcomeWindow};
+use crate::observer::{self, EncodeInfo, EncodeObserver, Stopwatch};
 use crate::protocol::{
-    ChannelInput, CompactHeader, EncodedMessage, EncodingType, MessageHeader, MessageType,
-    Priority, RawData, COMPACT_MARKER_DATA, COMPACT_MARKER_KEYFRAME,
+    ChannelInput, ChecksumAlgorithm, ChecksumPolicy, CompactHeader, CompositeEntry, EncodedMessage,
+    EncodingType, HeaderExtension, HeaderLayout, MessageHeader, MessageType, PredictionState,
+    Priority, RawData, TimestampFormat, TimestampMode, TimestampUnit, TransmissionPlan,
+    WireDialect, WireVersion, CHECKSUM_SIZE, COMPACT_MARKER_DATA, COMPACT_MARKER_KEYFRAME,
+    FLAG_CRC32C, FLAG_OPERATOR_OVERRIDE, FLAG_SELF_DESCRIBING, FLAG_SUSPECT,
+    MAX_TRUNCATED_TIMESTAMP_BITS, PLAN_RELATIVE_MARKER,
 };
+use crate::sequence::{SequencePersistence, SequenceStore};
+use crate::stats::RunningStats;
+use crate::sync::DetailCache;
+use crate::{MAX_MULTI_VALUES, MAX_PAYLOAD_SIZE};
+
+/// Static configuration for an [`Encoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncoderConfig {
+    /// Which messages get a trailing checksum when serialized
+    pub checksum_policy: ChecksumPolicy,
+    /// Which checksummed messages use CRC-32C instead of xxHash32
+    ///
+    /// Applies to messages covered by both policies; they carry the
+    /// [`FLAG_CRC32C`](crate::protocol::FLAG_CRC32C) header flag, which
+    /// extends their header by [`HeaderExtension::SIZE`] bytes.
+    pub crc32c_policy: ChecksumPolicy,
+    /// How single-value message headers carry their timestamp
+    pub timestamp_mode: TimestampMode,
+    /// Learn a per-source encoding preference and try it first
+    ///
+    /// Only affects single-value messages. See
+    /// [`Encoder::preferred_encoding`].
+    pub adaptive_selection: bool,
+    /// When the sequence number is saved, once a store is attached with
+    /// [`Encoder::set_sequence_store`]
+    pub sequence_persistence: SequencePersistence,
+    /// Send a self-describing value on a source's first message and then
+    /// every this many messages of the source (0: only on demand)
+    ///
+    /// A self-describing message carries the absolute value with the
+    /// sender's prediction state, so a decoder that joins mid-stream can
+    /// decode the following deltas without a context sync. See
+    /// [`Encoder::force_self_describing`].
+    pub self_describing_interval: u16,
+    /// Header layout of serialized messages (default:
+    /// [`WireDialect::Standard`])
+    ///
+    /// Applies to [`Encoder::message_to_bytes`] and
+    /// [`Encoder::encode_to_bytes`].
+    pub dialect: WireDialect,
+    /// Unit of the timestamps given to the encoder (default: none,
+    /// meaning milliseconds, not recorded)
+    ///
+    /// Applies to the bare `timestamp` arguments (e.g. of
+    /// [`Encoder::encode_multi`]); [`RawData`] carries its own unit. When
+    /// set, every header is extended to record the unit and, for
+    /// milliseconds and microseconds, the sub-second part of the
+    /// timestamp in that unit, so a decoder expecting another one fails
+    /// with
+    /// [`DecodeError::TimestampUnitMismatch`](crate::error::DecodeError::TimestampUnitMismatch).
+    pub timestamp_unit: Option<TimestampUnit>,
+}
+
+/// Last timestamp sent for one source
+#[derive(Debug, Clone, Copy)]
+struct TimestampAnchor {
+    /// Last full timestamp (seconds) sent for the source
+    last: u32,
+    /// Compressed messages sent since the last anchor
+    since_anchor: u16,
+}
+
+/// Priority of plan announcements; only values classified at this
+/// priority or below travel plan-relative
+const PLAN_PRIORITY: Priority = Priority::P3Normal;
+
+/// Default plan lifetime in seconds (see [`Encoder::set_plan_timeout`])
+const DEFAULT_PLAN_TIMEOUT_SECS: u32 = 900;
+
+/// Plan announced for one source and the frames sent under it
+#[derive(Debug, Clone)]
+struct ActivePlan {
+    plan: TransmissionPlan,
+    /// Plan-relative frames sent so far
+    sent: u8,
+}
+
+/// Encoding types tracked by adaptive selection, in greedy order
+const SELECTION_TYPES: [EncodingType; 6] = [
+    EncodingType::Repeated,
+    EncodingType::Delta8,
+    EncodingType::Delta16,
+    EncodingType::Delta32,
+    EncodingType::Raw32,
+    EncodingType::Raw64,
+];
+
+/// Weight kept by the previous statistics on each update
+const SELECTION_DECAY: f64 = 0.95;
+
+/// Every this many samples a source runs the full greedy chain so that
+/// non-preferred paths keep being scored
+const SELECTION_EXPLORE_INTERVAL: u32 = 32;
+
+/// Decayed encoding statistics for one source
+#[derive(Debug, Clone, Copy, Default)]
+struct SelectionStats {
+    /// Decayed win score per type, indexed like [`SELECTION_TYPES`]
+    scores: [f64; SELECTION_TYPES.len()],
+    /// Decayed mean encoded size (bytes) per type
+    sizes: [f64; SELECTION_TYPES.len()],
+    /// Samples encoded for the source
+    samples: u32,
+}
+
+impl SelectionStats {
+    /// Encoding type with the best score, ties going to the smaller size
+    fn preferred(&self) -> Option<EncodingType> {
+        let mut best: Option<usize> = None;
+        for i in 0..SELECTION_TYPES.len() {
+            if self.scores[i] <= 0.0 {
+                continue;
+            }
+            best = match best {
+                Some(b)
+                    if self.scores[b] > self.scores[i]
+                        || (self.scores[b] == self.scores[i] && self.sizes[b] <= self.sizes[i]) =>
+                {
+                    Some(b)
+                }
+                _ => Some(i),
+            };
+        }
+        best.map(|i| SELECTION_TYPES[i])
+    }
+
+    /// Record the encoding actually used for one sample
+    fn record(&mut self, encoding: EncodingType, size: usize) {
+        for (i, ty) in SELECTION_TYPES.iter().enumerate() {
+            self.scores[i] *= SELECTION_DECAY;
+            if *ty == encoding {
+                self.scores[i] += 1.0 - SELECTION_DECAY;
+                self.sizes[i] = if self.sizes[i] == 0.0 {
+                    size as f64
+                } else {
+                    self.sizes[i] * SELECTION_DECAY + size as f64 * (1.0 - SELECTION_DECAY)
+                };
+            }
+        }
+        self.samples = self.samples.wrapping_add(1);
+    }
+}
+
+/// Scale factors weighed by [`Encoder::precision_advice`], coarsest first
+pub const PRECISION_SCALE_FACTORS: [u32; 7] = [1, 10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+/// Prediction errors a source needs before [`Encoder::precision_advice`]
+/// gives an estimate
+pub const PRECISION_ADVICE_MIN_SAMPLES: usize = 16;
+
+/// Extra bytes per sample still considered free when recommending a scale
+const FREE_PRECISION_BYTES: f32 = 0.25;
+
+/// What the precision of a source costs on the wire
+///
+/// Returned by [`Encoder::precision_advice`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecisionAdvice {
+    /// Finest scale factor costing at most a quarter byte per sample more
+    /// than the cheapest one
+    pub recommended_scale_factor: u32,
+    /// Estimated value bytes per sample at each of
+    /// [`PRECISION_SCALE_FACTORS`], coarsest first
+    ///
+    /// Counts the encoded value only: header, source id and encoding byte
+    /// do not depend on the scale.
+    pub est_bytes_per_sample_at: Vec<(u32, f32)>,
+    /// Prediction errors the estimates are based on
+    pub samples: usize,
+}
+
+/// Width of a source's quantization dead-zone
+///
+/// Set per source with [`Encoder::set_dead_zone`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadZone {
+    /// Fixed width, in the source's units
+    Fixed(f64),
+    /// This multiple of the source's recent noise
+    ///
+    /// The noise is estimated from the source's history in the context as
+    /// the standard deviation of successive differences over √2, which a
+    /// slow trend barely affects. Without at least three values in the
+    /// history, nothing is suppressed.
+    NoiseMultiple(f64),
+}
+
+impl DeadZone {
+    /// Width of the dead-zone for `source_id`, if it can be told yet
+    fn width(self, source_id: u32, context: &Context) -> Option<f64> {
+        match self {
+            DeadZone::Fixed(width) => Some(width),
+            DeadZone::NoiseMultiple(k) => {
+                let (history, _) = context.history(source_id)?;
+                if history.len() < 3 {
+                    return None;
+                }
+                let mut differences = RunningStats::new();
+                let mut values = history.iter();
+                let mut last = values.next()?;
+                for value in values {
+                    differences.push(value - last);
+                    last = value;
+                }
+                Some(k * differences.std_dev() / core::f64::consts::SQRT_2)
+            }
+        }
+    }
+}
+
+/// Dead-zone of one source and the value its decoder holds
+#[derive(Debug, Clone, Copy)]
+struct DeadZoneState {
+    dead_zone: DeadZone,
+    /// Last value sent for the source (NaN before the first one)
+    held: f64,
+    /// Whether values were suppressed since `held` was sent
+    suppressed: bool,
+}
 
 /// Encoder for ALEC messages.
 ///
@@ -55,12 +287,122 @@ use crate::protocol::{
 /// `Encoder` is not thread-safe. Each thread should have its own instance.
 /// For multi-threaded scenarios, consider using separate encoders per thread
 /// or wrapping in a `Mutex`.
-#[derive(Debug, Clone)]
+///
+/// # Observers
+///
+/// An optional [`EncodeObserver`] can be installed with
+/// [`Encoder::set_observer`] to receive one callback per encoded message.
+/// Cloning an encoder does not clone its observer.
 pub struct Encoder {
     /// Next sequence number
     sequence: u16,
-    /// Whether to include checksum in encoded bytes
-    include_checksum: bool,
+    /// Which messages include a checksum in encoded bytes
+    checksum_policy: ChecksumPolicy,
+    /// Which checksummed messages use CRC-32C
+    crc32c_policy: ChecksumPolicy,
+    /// How single-value headers carry their timestamp
+    timestamp_mode: TimestampMode,
+    /// Per-source timestamp reference for compressed timestamps
+    timestamp_anchors: BTreeMap<u32, TimestampAnchor>,
+    /// Whether single-value encoding learns a per-source preference
+    adaptive_selection: bool,
+    /// Per-source statistics for adaptive selection
+    selection: BTreeMap<u32, SelectionStats>,
+    /// Optional per-message observer
+    observer: Option<Box<dyn EncodeObserver>>,
+    /// Single-value messages encoded since creation
+    messages_encoded: u64,
+    /// Raw fallbacks among the most recent single-value messages
+    fallbacks: OutcomeWindow,
+    /// Duration of the most recent `encode` call (std only)
+    last_encode_latency: Option<core::time::Duration>,
+    /// Wire-format version written into headers
+    wire_version: WireVersion,
+    /// Sequence number and timestamp (seconds) of the last value message
+    /// per source
+    value_sequences: BTreeMap<u32, (u16, u32)>,
+    /// Active transmission plan per source
+    plans: BTreeMap<u32, ActivePlan>,
+    /// Identifier given to the next plan
+    next_plan_id: u8,
+    /// Lifetime of new plans in seconds
+    plan_timeout_secs: u32,
+    /// Sources whose plan ended; their next value is sent raw
+    plan_resync: BTreeSet<u32>,
+    /// Optional store persisting the sequence number
+    sequence_store: Option<Box<dyn SequenceStore>>,
+    /// When the sequence number is saved
+    sequence_persistence: SequencePersistence,
+    /// Sequence numbers issued since the last successful save
+    unsaved_sequences: u16,
+    /// Messages per source between self-describing ones (0: on demand)
+    self_describing_interval: u16,
+    /// Messages sent per source since its last self-describing one
+    since_self_describing: BTreeMap<u32, u16>,
+    /// Sources whose next value is sent self-describing on demand
+    self_describing_pending: BTreeSet<u32>,
+    /// Header layout of serialized messages
+    dialect: WireDialect,
+    /// Encode times of recent messages, if latency tracking is enabled
+    latency: Option<LatencyLog>,
+    /// Unit of bare timestamps, recorded in headers when set
+    timestamp_unit: Option<TimestampUnit>,
+    /// Per-source quantization dead-zones
+    dead_zones: BTreeMap<u32, DeadZoneState>,
+}
+
+impl core::fmt::Debug for Encoder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Encoder")
+            .field("sequence", &self.sequence)
+            .field("checksum_policy", &self.checksum_policy)
+            .field("crc32c_policy", &self.crc32c_policy)
+            .field("timestamp_mode", &self.timestamp_mode)
+            .field("adaptive_selection", &self.adaptive_selection)
+            .field("observer", &self.observer.is_some())
+            .field("messages_encoded", &self.messages_encoded)
+            .field("wire_version", &self.wire_version)
+            .field("plans", &self.plans.len())
+            .field("sequence_store", &self.sequence_store.is_some())
+            .field("self_describing_interval", &self.self_describing_interval)
+            .field("timestamp_unit", &self.timestamp_unit)
+            .field("dead_zones", &self.dead_zones.len())
+            .finish()
+    }
+}
+
+impl Clone for Encoder {
+    fn clone(&self) -> Self {
+        Self {
+            sequence: self.sequence,
+            checksum_policy: self.checksum_policy,
+            crc32c_policy: self.crc32c_policy,
+            timestamp_mode: self.timestamp_mode,
+            timestamp_anchors: self.timestamp_anchors.clone(),
+            adaptive_selection: self.adaptive_selection,
+            selection: self.selection.clone(),
+            observer: None,
+            messages_encoded: self.messages_encoded,
+            fallbacks: self.fallbacks,
+            last_encode_latency: self.last_encode_latency,
+            wire_version: self.wire_version,
+            value_sequences: self.value_sequences.clone(),
+            plans: self.plans.clone(),
+            next_plan_id: self.next_plan_id,
+            plan_timeout_secs: self.plan_timeout_secs,
+            plan_resync: self.plan_resync.clone(),
+            sequence_store: None,
+            sequence_persistence: self.sequence_persistence,
+            unsaved_sequences: 0,
+            self_describing_interval: self.self_describing_interval,
+            since_self_describing: self.since_self_describing.clone(),
+            self_describing_pending: self.self_describing_pending.clone(),
+            dialect: self.dialect,
+            latency: self.latency.clone(),
+            timestamp_unit: self.timestamp_unit,
+            dead_zones: self.dead_zones.clone(),
+        }
+    }
 }
 
 impl Encoder {
@@ -76,9 +418,51 @@ impl Encoder {
     /// assert!(!encoder.checksum_enabled());
     /// ```
     pub fn new() -> Self {
+        Self::with_config(EncoderConfig::default())
+    }
+
+    /// Create an encoder from an explicit configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::{ChecksumPolicy, Encoder, EncoderConfig, Priority};
+    ///
+    /// let encoder = Encoder::with_config(EncoderConfig {
+    ///     checksum_policy: ChecksumPolicy::ForPriorityAtOrAbove(Priority::P2Important),
+    ///     ..Default::default()
+    /// });
+    /// assert!(encoder.checksum_enabled());
+    /// ```
+    pub fn with_config(config: EncoderConfig) -> Self {
         Self {
             sequence: 0,
-            include_checksum: false,
+            checksum_policy: config.checksum_policy,
+            crc32c_policy: config.crc32c_policy,
+            timestamp_mode: config.timestamp_mode,
+            timestamp_anchors: BTreeMap::new(),
+            adaptive_selection: config.adaptive_selection,
+            selection: BTreeMap::new(),
+            observer: None,
+            messages_encoded: 0,
+            fallbacks: OutcomeWindow::default(),
+            last_encode_latency: None,
+            wire_version: WireVersion::LEGACY,
+            value_sequences: BTreeMap::new(),
+            plans: BTreeMap::new(),
+            next_plan_id: 0,
+            plan_timeout_secs: DEFAULT_PLAN_TIMEOUT_SECS,
+            plan_resync: BTreeSet::new(),
+            sequence_store: None,
+            sequence_persistence: config.sequence_persistence,
+            unsaved_sequences: 0,
+            self_describing_interval: config.self_describing_interval,
+            since_self_describing: BTreeMap::new(),
+            self_describing_pending: BTreeSet::new(),
+            dialect: config.dialect,
+            latency: None,
+            timestamp_unit: config.timestamp_unit,
+            dead_zones: BTreeMap::new(),
         }
     }
 
@@ -96,15 +480,470 @@ impl Encoder {
     /// assert!(encoder.checksum_enabled());
     /// ```
     pub fn with_checksum() -> Self {
-        Self {
-            sequence: 0,
-            include_checksum: true,
-        }
+        Self::with_config(EncoderConfig {
+            checksum_policy: ChecksumPolicy::Always,
+            ..Default::default()
+        })
     }
 
-    /// Check if checksum is enabled for this encoder.
+    /// Check if checksum is enabled for at least some messages.
     pub fn checksum_enabled(&self) -> bool {
-        self.include_checksum
+        self.checksum_policy != ChecksumPolicy::Never
+    }
+
+    /// Get the checksum policy.
+    pub fn checksum_policy(&self) -> ChecksumPolicy {
+        self.checksum_policy
+    }
+
+    /// Checksum algorithm of a message with this priority, if it carries
+    /// a checksum.
+    pub fn checksum_algorithm(&self, priority: Priority) -> Option<ChecksumAlgorithm> {
+        if !self.checksum_policy.applies_to(priority) {
+            None
+        } else if self.crc32c_policy.applies_to(priority) {
+            Some(ChecksumAlgorithm::Crc32c)
+        } else {
+            Some(ChecksumAlgorithm::Xxh32)
+        }
+    }
+
+    /// Get the timestamp mode.
+    pub fn timestamp_mode(&self) -> TimestampMode {
+        self.timestamp_mode
+    }
+
+    /// Check if adaptive encoding selection is enabled.
+    pub fn adaptive_selection(&self) -> bool {
+        self.adaptive_selection
+    }
+
+    /// Encoding type adaptive selection currently prefers for a source.
+    ///
+    /// Returns `None` when adaptive selection is disabled or the source has
+    /// not been encoded yet. Intended for debugging and tests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::{Classifier, Context, Encoder, EncoderConfig, EncodingType, RawData};
+    ///
+    /// let mut encoder = Encoder::with_config(EncoderConfig {
+    ///     adaptive_selection: true,
+    ///     ..Default::default()
+    /// });
+    /// let classifier = Classifier::default();
+    /// let mut context = Context::new();
+    ///
+    /// for i in 0..20 {
+    ///     let data = RawData::with_source(7, 20.0, i);
+    ///     let classification = classifier.classify(&data, &context);
+    ///     encoder.encode(&data, &classification, &context);
+    ///     context.observe_classified(&data, &classification);
+    /// }
+    /// assert_eq!(encoder.preferred_encoding(7), Some(EncodingType::Repeated));
+    /// ```
+    pub fn preferred_encoding(&self, source_id: u32) -> Option<EncodingType> {
+        self.selection
+            .get(&source_id)
+            .and_then(SelectionStats::preferred)
+    }
+
+    /// Estimate what each scale factor would cost a source
+    ///
+    /// Replays the prediction over the source's history in `context` and
+    /// quantizes each prediction error at every candidate scale factor, as
+    /// the delta encodings do: a finer scale keeps more decimals but needs
+    /// wider deltas once the errors stop rounding to small steps. On a
+    /// stable signal extra decimals are often free. The recommendation is
+    /// the finest scale whose estimate stays within a quarter byte of the
+    /// cheapest; apply it on both ends with [`Context::set_scale_factor`].
+    ///
+    /// Returns `None` while the source has fewer than
+    /// [`PRECISION_ADVICE_MIN_SAMPLES`] prediction errors in its history.
+    ///
+    /// ```
+    /// use alec::{Context, Encoder, RawData};
+    ///
+    /// let mut context = Context::new();
+    /// assert!(Encoder::new().precision_advice(1, &context).is_none());
+    ///
+    /// for i in 0..50 {
+    ///     let value = 21.0 + (i % 3) as f64 * 0.001;
+    ///     context.observe(&RawData::with_source(1, value, i * 1000));
+    /// }
+    /// let advice = Encoder::new().precision_advice(1, &context).unwrap();
+    /// assert!(advice.recommended_scale_factor >= 10_000);
+    /// ```
+    pub fn precision_advice(&self, source_id: u32, context: &Context) -> Option<PrecisionAdvice> {
+        let (history, ema_alpha) = context.history(source_id)?;
+        let samples = history.len().saturating_sub(1);
+        if samples < PRECISION_ADVICE_MIN_SAMPLES {
+            return None;
+        }
+
+        let mut totals = [0usize; PRECISION_SCALE_FACTORS.len()];
+        let mut values = history.iter();
+        let mut last = values.next()?;
+        let mut prediction = last;
+        for value in values {
+            for (total, &scale) in totals.iter_mut().zip(&PRECISION_SCALE_FACTORS) {
+                *total += Self::value_size(value, last, prediction, scale);
+            }
+            prediction = ema_alpha * value + (1.0 - ema_alpha) * prediction;
+            last = value;
+        }
+
+        let est_bytes_per_sample_at: Vec<(u32, f32)> = PRECISION_SCALE_FACTORS
+            .iter()
+            .zip(totals)
+            .map(|(&scale, total)| (scale, total as f32 / samples as f32))
+            .collect();
+        let cheapest = est_bytes_per_sample_at
+            .iter()
+            .map(|&(_, bytes)| bytes)
+            .fold(f32::INFINITY, f32::min);
+        let recommended_scale_factor = est_bytes_per_sample_at
+            .iter()
+            .rev()
+            .find(|&&(_, bytes)| bytes <= cheapest + FREE_PRECISION_BYTES)
+            .map_or(crate::DEFAULT_SCALE_FACTOR, |&(scale, _)| scale);
+
+        Some(PrecisionAdvice {
+            recommended_scale_factor,
+            est_bytes_per_sample_at,
+            samples,
+        })
+    }
+
+    /// Value bytes the greedy chain would spend on `value` at `scale`
+    fn value_size(value: f64, last: f64, prediction: f64, scale: u32) -> usize {
+        if (value - last).abs() < f64::EPSILON {
+            return EncodingType::Repeated.typical_size();
+        }
+        Self::delta_type(value - prediction, scale as f64)
+            .unwrap_or_else(|| Self::raw_encoding(&RawData::new(value, 0)).0)
+            .typical_size()
+    }
+
+    /// Wire-format version written into message headers.
+    pub fn wire_version(&self) -> WireVersion {
+        self.wire_version
+    }
+
+    /// Select the wire-format version written into message headers.
+    ///
+    /// [`WireVersion::LEGACY`] (the default) keeps the compact header;
+    /// later versions add a [`HeaderExtension`] and always send full
+    /// timestamps. Use the version settled by
+    /// [`crate::sync::VersionNegotiation`] on bidirectional links.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::{Encoder, WireVersion};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.set_wire_version(WireVersion::CURRENT);
+    /// assert_eq!(encoder.wire_version(), WireVersion::CURRENT);
+    /// ```
+    pub fn set_wire_version(&mut self, version: WireVersion) {
+        self.wire_version = version;
+    }
+
+    /// Context version as a header carries it, so the message round-trips
+    /// through its bytes
+    fn header_context_version(context: &Context) -> u32 {
+        context.version() & MessageHeader::MAX_CONTEXT_VERSION
+    }
+
+    /// Header layout for a message with this priority, timestamped
+    /// `micros`
+    fn header_layout(&self, priority: Priority, micros: i64) -> HeaderLayout {
+        self.header_extension(priority, micros)
+            .map_or(HeaderLayout::default(), HeaderLayout::Extended)
+    }
+
+    /// Header extension for a message with this priority, timestamped
+    /// `micros`: the selected wire version, or the current one when the
+    /// message needs a flag
+    fn header_extension(&self, priority: Priority, micros: i64) -> Option<HeaderExtension> {
+        let crc32c = self.checksum_algorithm(priority) == Some(ChecksumAlgorithm::Crc32c);
+        let mut extension = if crc32c || self.timestamp_unit.is_some() {
+            HeaderExtension::new(self.wire_version.max(WireVersion::CURRENT))
+        } else if self.wire_version > WireVersion::LEGACY {
+            HeaderExtension::new(self.wire_version)
+        } else {
+            return None;
+        };
+        if crc32c {
+            extension.flags |= FLAG_CRC32C;
+        }
+        if let Some(unit) = self.timestamp_unit {
+            extension.flags |= unit.to_flags();
+            extension.subsec = unit
+                .from_micros(micros.rem_euclid(TimestampUnit::Seconds.micros_per_unit()))
+                as u32;
+        }
+        Some(extension)
+    }
+
+    /// Unit of bare timestamps given to the encoder
+    pub fn timestamp_unit(&self) -> Option<TimestampUnit> {
+        self.timestamp_unit
+    }
+
+    /// Microseconds of a bare timestamp, in the encoder's unit
+    fn bare_micros(&self, timestamp: u64) -> i64 {
+        RawData::new(0.0, 0)
+            .with_timestamp_in(self.timestamp_unit.unwrap_or_default(), timestamp)
+            .timestamp_micros()
+    }
+
+    /// Header timestamp (whole seconds) of a timestamp in microseconds
+    fn header_timestamp(micros: i64) -> u32 {
+        TimestampUnit::Seconds.from_micros(micros) as u32
+    }
+
+    /// Send a full timestamp on the next message of every source.
+    ///
+    /// Call this when the decoder reports
+    /// [`crate::error::DecodeError::MissingTimestampAnchor`], e.g. after
+    /// it restarted and lost its per-source timestamps.
+    pub fn force_timestamp_anchor(&mut self) {
+        self.timestamp_anchors.clear();
+    }
+
+    /// Send the next value of `source_id` as a self-describing message.
+    ///
+    /// Call this when a receiver joins or reports it cannot predict the
+    /// source, e.g. after a restart; see
+    /// [`EncoderConfig::self_describing_interval`] for periodic ones.
+    pub fn force_self_describing(&mut self, source_id: u32) {
+        self.self_describing_pending.insert(source_id);
+    }
+
+    /// Give `source_id` a quantization dead-zone
+    ///
+    /// Single values of the source whose change is below the dead-zone are
+    /// sent as [`EncodingType::Repeated`], which costs no value bytes; the
+    /// decoder holds the previous value. This absorbs a sensor's noise
+    /// floor without coarsening the scale factor for real changes.
+    ///
+    /// # Anti-drift guarantee
+    ///
+    /// Suppressed changes are not forgotten: the encoder accumulates them
+    /// as the distance between the value and the last one sent, and a
+    /// value is only suppressed while that distance is below the dead-zone
+    /// too. A slow drift is therefore sent at the latest once it has moved
+    /// the value by the dead-zone, and a decoder that observes what it
+    /// decodes never lags the true value by more than the dead-zone, plus
+    /// the rounding of the values actually sent. The
+    /// first value sent after suppressed ones goes raw, since the
+    /// decoder's prediction was built on the held value.
+    ///
+    /// ```
+    /// use alec::{Classifier, Context, DeadZone, Encoder, EncodingType, RawData};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.set_dead_zone(1, DeadZone::Fixed(0.05));
+    /// let classifier = Classifier::default();
+    /// let mut context = Context::new();
+    /// let mut encodings = Vec::new();
+    /// for (i, value) in [20.0, 20.02, 19.99, 20.03, 20.06].into_iter().enumerate() {
+    ///     let data = RawData::with_source(1, value, i as u64 * 1000);
+    ///     let classification = classifier.classify(&data, &context);
+    ///     let message = encoder.encode(&data, &classification, &context);
+    ///     encodings.push(message.encoding_type());
+    ///     context.observe(&data);
+    /// }
+    /// // 20.06 moved by 0.03 only, but is 0.06 away from the 20.0 held
+    /// // by the decoder
+    /// assert_eq!(encodings[1..4], [Some(EncodingType::Repeated); 3]);
+    /// assert_eq!(encodings[4], Some(EncodingType::Raw32));
+    /// ```
+    pub fn set_dead_zone(&mut self, source_id: u32, dead_zone: DeadZone) {
+        self.dead_zones
+            .entry(source_id)
+            .and_modify(|state| state.dead_zone = dead_zone)
+            .or_insert(DeadZoneState {
+                dead_zone,
+                held: f64::NAN,
+                suppressed: false,
+            });
+    }
+
+    /// Remove the dead-zone of `source_id`
+    pub fn clear_dead_zone(&mut self, source_id: u32) {
+        self.dead_zones.remove(&source_id);
+    }
+
+    /// Dead-zone set for `source_id`, if any
+    pub fn dead_zone(&self, source_id: u32) -> Option<DeadZone> {
+        self.dead_zones.get(&source_id).map(|state| state.dead_zone)
+    }
+
+    /// Current width of the dead-zone of `source_id`
+    ///
+    /// `None` without a dead-zone, or while a
+    /// [`DeadZone::NoiseMultiple`] has too little history.
+    pub fn dead_zone_width(&self, source_id: u32, context: &Context) -> Option<f64> {
+        self.dead_zones
+            .get(&source_id)?
+            .dead_zone
+            .width(source_id, context)
+    }
+
+    /// Whether the source's dead-zone suppresses this finite value
+    fn suppressed_by_dead_zone(&mut self, data: &RawData, context: &Context) -> bool {
+        if self.plan_resync.contains(&data.source_id) {
+            return false;
+        }
+        let Some(state) = self.dead_zones.get(&data.source_id) else {
+            return false;
+        };
+        let (Some(width), Some(last)) = (
+            state.dead_zone.width(data.source_id, context),
+            context.last_value(data.source_id),
+        ) else {
+            return false;
+        };
+        // NaN `held` (nothing sent yet) never suppresses
+        let suppress = (data.value - last).abs() < width && (data.value - state.held).abs() < width;
+        if suppress {
+            if let Some(state) = self.dead_zones.get_mut(&data.source_id) {
+                state.suppressed = true;
+            }
+        }
+        suppress
+    }
+
+    /// Record a value sent for the source; returns whether values were
+    /// suppressed since the previous one
+    fn dead_zone_sent(&mut self, source_id: u32, value: f64) -> bool {
+        match self.dead_zones.get_mut(&source_id) {
+            Some(state) => {
+                state.held = value;
+                core::mem::take(&mut state.suppressed)
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the value about to be encoded for `source_id` is sent
+    /// self-describing, counting it towards the interval
+    fn self_describing_due(&mut self, source_id: u32) -> bool {
+        let forced = self.self_describing_pending.remove(&source_id);
+        let interval = self.self_describing_interval;
+        if interval == 0 {
+            return forced;
+        }
+        let since = self.since_self_describing.get(&source_id).copied();
+        let due = forced || since.map_or(true, |since| since.saturating_add(1) >= interval);
+        let since = if due { 0 } else { since.unwrap_or(0) + 1 };
+        self.since_self_describing.insert(source_id, since);
+        due
+    }
+
+    /// Serialize a message, appending a checksum if the policy covers
+    /// its priority.
+    pub fn message_to_bytes(&self, message: &EncodedMessage) -> Vec<u8> {
+        let dialect = &self.dialect;
+        if self.checksum_policy.applies_to(message.header.priority) {
+            message.to_bytes_with_checksum_in(dialect)
+        } else {
+            message.to_bytes_in(dialect)
+        }
+    }
+
+    /// Install an observer notified after every encoded message.
+    ///
+    /// Replaces any previously installed observer. See
+    /// [`crate::observer`] for the callback contract.
+    pub fn set_observer(&mut self, observer: Box<dyn EncodeObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Remove the installed observer, if any.
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
+
+    /// Check if an observer is installed.
+    pub fn has_observer(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    /// Keep the encode times of the last `capacity` messages passed to
+    /// [`Encoder::record_encoded`], for [`crate::latency`].
+    ///
+    /// Replaces any log kept so far.
+    pub fn enable_latency_tracking(&mut self, capacity: usize) {
+        self.latency = Some(LatencyLog::new(capacity));
+    }
+
+    /// Stop recording encode times, dropping the log.
+    pub fn disable_latency_tracking(&mut self) {
+        self.latency = None;
+    }
+
+    /// Record that `message`, carrying a value measured at `measured_ms`,
+    /// was encoded at `now_ms` (both on the caller's clock).
+    ///
+    /// Does nothing unless latency tracking is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::latency::LatencyStage;
+    /// use alec::{Context, Encoder, Priority, RawData};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.enable_latency_tracking(256);
+    /// let data = RawData::new(21.5, 10_000);
+    /// let message = encoder.encode_with_priority(&data, Priority::P1Critical, &Context::new());
+    /// encoder.record_encoded(&message, data.timestamp, 10_040);
+    ///
+    /// let report = encoder.latency_report().unwrap();
+    /// let encode = report.histogram(Priority::P1Critical, LatencyStage::Encode);
+    /// assert_eq!(encode.counts[0], 1);
+    /// ```
+    pub fn record_encoded(&mut self, message: &EncodedMessage, measured_ms: u64, now_ms: u64) {
+        if let Some(log) = &mut self.latency {
+            let header = &message.header;
+            log.record_encoded(header.sequence, header.priority, measured_ms, now_ms);
+        }
+    }
+
+    /// Encode times of recent messages, if latency tracking is enabled.
+    pub fn latency_log(&self) -> Option<&LatencyLog> {
+        self.latency.as_ref()
+    }
+
+    /// Encode latencies per priority, if latency tracking is enabled.
+    pub fn latency_report(&self) -> Option<&LatencyReport> {
+        self.latency.as_ref().map(LatencyLog::report)
+    }
+
+    /// Number of single-value messages encoded since creation.
+    pub fn messages_encoded(&self) -> u64 {
+        self.messages_encoded
+    }
+
+    /// Share of raw (fallback) encodings among the last 64 single-value
+    /// messages, 0.0 before the first one.
+    ///
+    /// A high rate means predictions rarely land close enough for delta
+    /// or repeated encoding.
+    pub fn fallback_rate(&self) -> f64 {
+        self.fallbacks.rate()
+    }
+
+    /// Duration of the most recent [`Encoder::encode`] call.
+    ///
+    /// Always `None` without the `std` feature.
+    pub fn last_encode_latency(&self) -> Option<core::time::Duration> {
+        self.last_encode_latency
     }
 
     /// Get the current sequence number.
@@ -132,10 +971,73 @@ impl Encoder {
         self.sequence = seq;
     }
 
+    /// Persist the sequence number across restarts.
+    ///
+    /// If `store` holds a sequence number for
+    /// [`SequencePersistence::source_id`], numbering resumes
+    /// [`SequencePersistence::effective_skip`] past it, so the messages
+    /// sent after the last save before a restart are not numbered again.
+    /// The starting point is saved right away, then every
+    /// [`SequencePersistence::save_interval`] messages. Cloning the
+    /// encoder does not clone the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if the initial save fails. The store is
+    /// attached anyway and the save is retried with the next message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::{Encoder, InMemorySequenceStore, SequenceStore};
+    ///
+    /// // Saved before the restart
+    /// let mut store = InMemorySequenceStore::new();
+    /// store.save(0, 40).unwrap();
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.set_sequence_store(Box::new(store)).unwrap();
+    /// assert_eq!(encoder.sequence(), 1040);
+    /// ```
+    pub fn set_sequence_store(&mut self, mut store: Box<dyn SequenceStore>) -> Result<()> {
+        let persistence = self.sequence_persistence;
+        if let Some(saved) = store.load(persistence.source_id) {
+            self.sequence = (saved as u16).wrapping_add(persistence.effective_skip());
+        }
+        self.sequence_store = Some(store);
+        self.unsaved_sequences = 0;
+        self.save_sequence()
+    }
+
+    /// Detach the sequence store, if any.
+    ///
+    /// Call [`Encoder::save_sequence`] first to keep the latest number.
+    pub fn take_sequence_store(&mut self) -> Option<Box<dyn SequenceStore>> {
+        self.sequence_store.take()
+    }
+
+    /// Save the next sequence number now, e.g. before a clean shutdown.
+    ///
+    /// Does nothing without a sequence store.
+    pub fn save_sequence(&mut self) -> Result<()> {
+        let Some(store) = self.sequence_store.as_mut() else {
+            return Ok(());
+        };
+        store.save(self.sequence_persistence.source_id, self.sequence as u32)?;
+        self.unsaved_sequences = 0;
+        Ok(())
+    }
+
     /// Encode data and return raw bytes.
     ///
     /// This is a convenience method that combines encoding and serialization.
-    /// If checksum is enabled, the returned bytes include the checksum.
+    /// The returned bytes include a checksum when the checksum policy
+    /// covers the message's classified priority.
+    ///
+    /// While a plan announced with [`Encoder::announce_plan`] covers the
+    /// source, values that fit the plan are returned as plan-relative
+    /// frames instead (see [`TransmissionPlan`]), unless the classification
+    /// is an operator override.
     ///
     /// # Arguments
     ///
@@ -152,13 +1054,13 @@ impl Encoder {
         classification: &Classification,
         context: &Context,
     ) -> Vec<u8> {
-        let message = self.encode(data, classification, context);
-
-        if self.include_checksum {
-            message.to_bytes_with_checksum()
-        } else {
-            message.to_bytes()
+        if !classification.is_override() {
+            if let Some(frame) = self.encode_plan_relative(data, classification.priority) {
+                return frame;
+            }
         }
+        let message = self.encode(data, classification, context);
+        self.message_to_bytes(&message)
     }
 
     /// Encode data while collecting compression metrics.
@@ -187,7 +1089,27 @@ impl Encoder {
         if let Some(encoding) = message.encoding_type() {
             metrics.record_encode(data.raw_size(), message.len(), encoding);
         }
+        if message.header.is_operator_override() {
+            metrics.record_override();
+        }
+
+        message
+    }
 
+    /// Encode data and keep its full-resolution copy in a detail cache.
+    ///
+    /// The sample is stored under the message's sequence number so a
+    /// later `ReqDetail` can be answered with
+    /// [`crate::sync::Synchronizer::handle_detail_request`].
+    pub fn encode_with_detail_cache(
+        &mut self,
+        data: &RawData,
+        classification: &Classification,
+        context: &Context,
+        cache: &mut DetailCache,
+    ) -> EncodedMessage {
+        let message = self.encode(data, classification, context);
+        cache.insert(message.header.sequence as u32, data.clone());
         message
     }
 
@@ -232,14 +1154,392 @@ impl Encoder {
         classification: &Classification,
         context: &Context,
     ) -> EncodedMessage {
+        let stopwatch = Stopwatch::start();
+        let mut message = self.encode_single(data, classification, context);
+        if classification.is_override() {
+            Self::mark(&mut message.header, FLAG_OPERATOR_OVERRIDE);
+        }
+        if classification
+            .labels
+            .iter()
+            .any(|label| matches!(label, AnomalyLabel::Stuck { .. }))
+        {
+            Self::mark(&mut message.header, FLAG_SUSPECT);
+        }
+        self.compress_timestamp(&mut message.header, data.source_id);
+        self.value_sequences.insert(
+            data.source_id,
+            (message.header.sequence, data.timestamp_secs() as u32),
+        );
+        self.messages_encoded += 1;
+        self.fallbacks.record(matches!(
+            message.encoding_type(),
+            Some(EncodingType::Raw32 | EncodingType::Raw64)
+        ));
+        self.last_encode_latency = stopwatch.elapsed();
+        if let Some(obs) = &self.observer {
+            let residual = if data.value.is_finite() {
+                context
+                    .predict(data.source_id)
+                    .map(|prediction| data.value - prediction.value)
+            } else {
+                None
+            };
+            Self::notify(
+                obs.as_ref(),
+                &message,
+                data.source_id,
+                data.raw_size(),
+                residual,
+            );
+        }
+        message
+    }
+
+    /// Encode data with a priority chosen by the application
+    ///
+    /// Shorthand for [`Encoder::encode`] with [`Classification::manual`]:
+    /// the header carries the operator-override flag, which needs an
+    /// extended header. On a [`WireVersion::LEGACY`] link the message is
+    /// extended with [`WireVersion::CURRENT`] anyway.
+    ///
+    /// ```
+    /// use alec::{Context, Decoder, Encoder, Priority, RawData};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let context = Context::new();
+    /// let message = encoder.encode_with_priority(&RawData::new(21.5, 0), Priority::P1Critical, &context);
+    /// assert!(message.header.is_operator_override());
+    ///
+    /// let decoded = Decoder::new().decode(&message, &context).unwrap();
+    /// assert_eq!(decoded.priority, Priority::P1Critical);
+    /// assert!(decoded.operator_override);
+    /// ```
+    pub fn encode_with_priority(
+        &mut self,
+        data: &RawData,
+        priority: Priority,
+        context: &Context,
+    ) -> EncodedMessage {
+        self.encode(data, &Classification::manual(priority, ""), context)
+    }
+
+    /// Set a header extension flag, extending the header if needed
+    fn mark(header: &mut MessageHeader, flag: u8) {
+        let mut extension = header
+            .extension()
+            .unwrap_or_else(|| HeaderExtension::new(WireVersion::CURRENT));
+        extension.flags |= flag;
+        header.layout = HeaderLayout::Extended(extension);
+    }
+
+    /// Confirm a reading whose value did not change since the last message
+    ///
+    /// For report-by-exception layers that suppress repeated values but
+    /// must still prove a reading was taken. The message carries only the
+    /// source id, the timestamp (compressed per the timestamp mode) and,
+    /// in the header sequence, the sequence number of the last value
+    /// message for the source; it does not consume a sequence number.
+    ///
+    /// Returns `None` if no value was encoded for the source yet.
+    ///
+    /// ```
+    /// use alec::{Classifier, Context, Decoded, Decoder, Encoder, Priority, RawData};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let context = Context::new();
+    /// let data = RawData::with_source(7, 21.5, 60_000);
+    /// let classification = Classifier::default().classify(&data, &context);
+    /// let value = encoder.encode(&data, &classification, &context);
+    ///
+    /// let confirmation = encoder
+    ///     .encode_confirmation(7, 120_000, Priority::P3Normal, &context)
+    ///     .unwrap();
+    /// assert!(confirmation.len() < value.len());
+    ///
+    /// let mut decoder = Decoder::new();
+    /// let decoded = decoder.decode_item(&confirmation, &context).unwrap();
+    /// assert_eq!(
+    ///     decoded,
+    ///     Decoded::Confirmation { source_id: 7, timestamp: 120, ref_seq: value.header.sequence }
+    /// );
+    /// ```
+    pub fn encode_confirmation(
+        &mut self,
+        source_id: u32,
+        timestamp: u64,
+        priority: Priority,
+        context: &Context,
+    ) -> Option<EncodedMessage> {
+        let (ref_seq, _) = *self.value_sequences.get(&source_id)?;
+
+        let mut payload = Vec::new();
+        self.encode_varint(source_id, &mut payload);
+
+        let micros = self.bare_micros(timestamp);
+        let mut header = MessageHeader {
+            layout: self.header_layout(priority, micros),
+            message_type: MessageType::Heartbeat,
+            priority,
+            sequence: ref_seq,
+            timestamp: Self::header_timestamp(micros),
+            context_version: Self::header_context_version(context),
+        };
+        self.compress_timestamp(&mut header, source_id);
+
+        Some(EncodedMessage::new(header, payload))
+    }
+
+    /// Announce how the next values of a source will be encoded
+    ///
+    /// The returned message carries a [`TransmissionPlan`]: the next
+    /// `n_messages` values of `source_id` are sent by
+    /// [`Encoder::encode_to_bytes`] as plan-relative frames holding only a
+    /// plan id, an index, a time offset and a delta against the source's
+    /// last value, and decode with the plan's `P3Normal` priority. Values
+    /// the plan cannot represent, values classified above `P3Normal` and
+    /// values covered by the checksum policy still go out as regular
+    /// messages.
+    ///
+    /// The plan ends after `n_messages` frames or once a value is more than
+    /// [`Encoder::plan_timeout`] seconds past the last value message, and
+    /// the first value after it is sent raw. A decoder that missed the plan
+    /// rejects its frames with
+    /// [`crate::error::DecodeError::PlanMissing`] and picks up again there.
+    ///
+    /// Returns `None` if `n_messages` is 0 or no value of the source has
+    /// been encoded and observed yet.
+    ///
+    /// ```
+    /// use alec::{Classifier, Context, Decoded, Decoder, Encoder, RawData};
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let mut decoder = Decoder::new();
+    /// let classifier = Classifier::default();
+    /// let mut context = Context::new();
+    ///
+    /// let first = RawData::with_source(3, 21.0, 60_000);
+    /// let bytes = encoder.encode_to_bytes(&first, &classifier.classify(&first, &context), &context);
+    /// decoder.decode_bytes(&bytes, &context).unwrap();
+    /// context.observe(&first);
+    ///
+    /// let plan = encoder.announce_plan(3, 10, &context).unwrap();
+    /// assert!(matches!(decoder.decode_item(&plan, &context), Ok(Decoded::Plan { .. })));
+    ///
+    /// let next = RawData::with_source(3, 21.25, 120_000);
+    /// let frame = encoder.encode_to_bytes(&next, &classifier.classify(&next, &context), &context);
+    /// assert!(frame.len() < bytes.len());
+    /// assert_eq!(decoder.decode_bytes(&frame, &context).unwrap().value, 21.25);
+    /// ```
+    pub fn announce_plan(
+        &mut self,
+        source_id: u32,
+        n_messages: u8,
+        context: &Context,
+    ) -> Option<EncodedMessage> {
+        let scale = context.scale_factor();
+        if n_messages == 0 || scale == 0 {
+            return None;
+        }
+        let (_, timestamp) = *self.value_sequences.get(&source_id)?;
+        let base = context.last_value(source_id)?;
+
+        let encoding = match self.preferred_encoding(source_id) {
+            Some(EncodingType::Repeated | EncodingType::Delta8) => EncodingType::Delta8,
+            Some(EncodingType::Delta32) => EncodingType::Delta32,
+            _ => EncodingType::Delta16,
+        };
+        let plan = TransmissionPlan {
+            plan_id: self.next_plan_id,
+            source_id,
+            messages: n_messages,
+            encoding,
+            scale,
+            base,
+            timestamp,
+            timeout_s: self.plan_timeout_secs,
+        };
+        self.next_plan_id = self.next_plan_id.wrapping_add(1);
+
+        let mut payload = Vec::new();
+        self.encode_varint(source_id, &mut payload);
+        payload.push(EncodingType::Plan as u8);
+        plan.write_body(&mut payload);
+
+        let mut header = MessageHeader {
+            layout: self.header_layout(
+                PLAN_PRIORITY,
+                TimestampUnit::Seconds.to_micros(i64::from(timestamp)),
+            ),
+            message_type: MessageType::Data,
+            priority: PLAN_PRIORITY,
+            sequence: self.next_sequence(),
+            timestamp,
+            context_version: Self::header_context_version(context),
+        };
+        self.compress_timestamp(&mut header, source_id);
+
+        self.plans.insert(source_id, ActivePlan { plan, sent: 0 });
+        Some(EncodedMessage::new(header, payload))
+    }
+
+    /// Plan currently covering a source, if any
+    pub fn active_plan(&self, source_id: u32) -> Option<&TransmissionPlan> {
+        self.plans.get(&source_id).map(|active| &active.plan)
+    }
+
+    /// Lifetime of new plans in seconds.
+    pub fn plan_timeout(&self) -> u32 {
+        self.plan_timeout_secs
+    }
+
+    /// Set the lifetime of plans announced from now on (default 900 s).
+    pub fn set_plan_timeout(&mut self, secs: u32) {
+        self.plan_timeout_secs = secs;
+    }
+
+    /// Encode a value as a plan-relative frame, if a plan covers it
+    fn encode_plan_relative(&mut self, data: &RawData, priority: Priority) -> Option<Vec<u8>> {
+        if priority < PLAN_PRIORITY || self.checksum_policy.applies_to(priority) {
+            return None;
+        }
+        let active = self.plans.get_mut(&data.source_id)?;
+        let offset = (data.timestamp_secs() as u32).checked_sub(active.plan.timestamp)?;
+        if offset > active.plan.timeout_s {
+            self.end_plan(data.source_id);
+            return None;
+        }
+        let delta = active.plan.encode_delta(data.value)?;
+
+        let plan_id = active.plan.plan_id;
+        let index = active.sent;
+        active.sent += 1;
+        if active.sent >= active.plan.messages {
+            self.end_plan(data.source_id);
+        }
+
+        let mut frame = Vec::with_capacity(TransmissionPlan::FRAME_PREFIX + 1 + delta.len());
+        frame.push(PLAN_RELATIVE_MARKER);
+        frame.push(plan_id);
+        frame.push(index);
+        self.encode_varint(offset, &mut frame);
+        frame.extend(delta);
+        self.messages_encoded += 1;
+        Some(frame)
+    }
+
+    /// Drop a source's plan; its next value is sent raw
+    fn end_plan(&mut self, source_id: u32) {
+        if self.plans.remove(&source_id).is_some() {
+            self.plan_resync.insert(source_id);
+        }
+    }
+
+    /// Rewrite a full-timestamp header according to the timestamp mode
+    ///
+    /// Falls back to a full anchor on the first message of a source, every
+    /// `anchor_interval` messages, and whenever the compressed form could
+    /// not be reconstructed (clock going backwards, gap too large).
+    fn compress_timestamp(&mut self, header: &mut MessageHeader, source_id: u32) {
+        if self.timestamp_mode == TimestampMode::Full {
+            return;
+        }
+        // Extended headers always carry a full timestamp, which anchors
+        // the following compressed ones
+        if header.extension().is_some() {
+            self.timestamp_anchors.insert(
+                source_id,
+                TimestampAnchor {
+                    last: header.timestamp,
+                    since_anchor: 0,
+                },
+            );
+            return;
+        }
+
+        let timestamp = header.timestamp;
+        let previous = self.timestamp_anchors.get(&source_id).copied();
+        let compressed = previous.and_then(|prev| {
+            let delta = timestamp.checked_sub(prev.last)?;
+            match self.timestamp_mode {
+                TimestampMode::Full => None,
+                TimestampMode::DeltaVarint { anchor_interval } => {
+                    if anchor_interval > 0 && prev.since_anchor >= anchor_interval {
+                        return None;
+                    }
+                    Some((
+                        TimestampFormat::Delta,
+                        TimestampFormat::pack_delta(delta as i64),
+                    ))
+                }
+                TimestampMode::TruncatedEpoch { bits } => {
+                    let bits = bits.clamp(1, MAX_TRUNCATED_TIMESTAMP_BITS);
+                    // The decoder treats forward jumps of half the range or
+                    // more as regressions
+                    if delta >= 1u32 << (bits - 1) {
+                        return None;
+                    }
+                    Some((
+                        TimestampFormat::Truncated,
+                        TimestampFormat::pack_truncated(timestamp, bits),
+                    ))
+                }
+            }
+        });
+
+        let since_anchor = match compressed {
+            Some((format, field)) => {
+                header.layout = HeaderLayout::Plain(format);
+                header.timestamp = field;
+                previous.map_or(1, |prev| prev.since_anchor.saturating_add(1))
+            }
+            None => 0,
+        };
+        self.timestamp_anchors.insert(
+            source_id,
+            TimestampAnchor {
+                last: timestamp,
+                since_anchor,
+            },
+        );
+    }
+
+    /// Encode a single value without notifying the observer
+    fn encode_single(
+        &mut self,
+        data: &RawData,
+        classification: &Classification,
+        context: &Context,
+    ) -> EncodedMessage {
+        // A self-describing value also resyncs a decoder after a plan
+        if self.self_describing_due(data.source_id) {
+            self.plan_resync.remove(&data.source_id);
+            self.dead_zone_sent(data.source_id, data.value);
+            return self.encode_self_describing(data, classification.priority, context);
+        }
+
         // Check for invalid values
         if data.value.is_nan() || data.value.is_infinite() {
             // Fall back to raw encoding for invalid values
+            self.dead_zone_sent(data.source_id, f64::NAN);
             return self.encode_raw(data, classification.priority, context);
         }
 
-        // Choose encoding based on context
-        let (encoding_type, encoded_value) = self.choose_encoding(data, context);
+        // Changes within the dead-zone go as "no change". Otherwise choose
+        // encoding based on context; after a plan, or after values held by
+        // a dead-zone, the decoder's prediction no longer matches ours, so
+        // the first value is sent in full
+        let (encoding_type, encoded_value) = if self.suppressed_by_dead_zone(data, context) {
+            (EncodingType::Repeated, Vec::new())
+        } else if self.plan_resync.remove(&data.source_id)
+            | self.dead_zone_sent(data.source_id, data.value)
+        {
+            Self::raw_encoding(data)
+        } else if self.adaptive_selection {
+            self.choose_encoding_adaptive(data, context)
+        } else {
+            self.choose_encoding(data, context)
+        };
 
         // Build payload
         let mut payload = Vec::new();
@@ -255,17 +1555,38 @@ impl Encoder {
 
         // Build header
         let header = MessageHeader {
-            version: crate::PROTOCOL_VERSION,
+            layout: self.header_layout(classification.priority, data.timestamp_micros()),
             message_type: MessageType::Data,
             priority: classification.priority,
             sequence: self.next_sequence(),
-            timestamp: (data.timestamp / 1000) as u32,
-            context_version: context.version(),
+            timestamp: data.timestamp_secs() as u32,
+            context_version: Self::header_context_version(context),
         };
 
         EncodedMessage::new(header, payload)
     }
 
+    /// Report an encoded message to the observer
+    fn notify(
+        obs: &dyn EncodeObserver,
+        message: &EncodedMessage,
+        source_id: u32,
+        raw_size: usize,
+        residual: Option<f64>,
+    ) {
+        let info = EncodeInfo {
+            source_id,
+            priority: message.header.priority,
+            encoding: message.encoding_type().unwrap_or_default(),
+            raw_size,
+            encoded_size: message.len(),
+            residual,
+            context_version: message.header.context_version,
+            operator_override: message.header.is_operator_override(),
+        };
+        observer::notify(|| obs.on_encode(&info));
+    }
+
     /// Encode as raw (fallback)
     fn encode_raw(
         &mut self,
@@ -285,53 +1606,159 @@ impl Encoder {
         payload.extend_from_slice(&data.value.to_be_bytes());
 
         let header = MessageHeader {
-            version: crate::PROTOCOL_VERSION,
+            layout: self.header_layout(priority, data.timestamp_micros()),
             message_type: MessageType::Data,
             priority,
             sequence: self.next_sequence(),
-            timestamp: (data.timestamp / 1000) as u32,
-            context_version: context.version(),
+            timestamp: data.timestamp_secs() as u32,
+            context_version: Self::header_context_version(context),
         };
 
         EncodedMessage::new(header, payload)
     }
 
+    /// Encode as raw, followed by the sender's prediction state for the
+    /// source (see [`PredictionState`])
+    ///
+    /// The state is the one the value was encoded against; the decoder
+    /// assumes the sender then observes the value.
+    fn encode_self_describing(
+        &mut self,
+        data: &RawData,
+        priority: Priority,
+        context: &Context,
+    ) -> EncodedMessage {
+        let mut message = self.encode_raw(data, priority, context);
+        let (count, ema, alpha) = context.predictor_state(data.source_id);
+        PredictionState {
+            scale: context.scale_factor(),
+            count: count.min(u32::MAX as u64) as u32,
+            ema: ema.unwrap_or(0.0),
+            alpha,
+        }
+        .write(&mut message.payload);
+
+        let mut extension = message
+            .header
+            .extension()
+            .unwrap_or_else(|| HeaderExtension::new(WireVersion::CURRENT));
+        extension.flags |= FLAG_SELF_DESCRIBING;
+        message.header.layout = HeaderLayout::Extended(extension);
+        message
+    }
+
     /// Choose the best encoding for this value
     fn choose_encoding(&self, data: &RawData, context: &Context) -> (EncodingType, Vec<u8>) {
-        // Check if value matches last value exactly (repeated) — MOST COMPACT
-        if let Some(last) = context.last_value(data.source_id) {
-            if (data.value - last).abs() < f64::EPSILON {
-                return (EncodingType::Repeated, vec![]);
+        // Repeated is the most compact, then the narrowest delta, then raw
+        Self::repeated_encoding(data, context)
+            .or_else(|| Self::delta_encoding(data, context))
+            .unwrap_or_else(|| Self::raw_encoding(data))
+    }
+
+    /// Choose an encoding, trying the source's learned preference first
+    ///
+    /// Repeated is always checked first since it is free on the wire and
+    /// costs a single lookup. The preferred path is then tried on its own;
+    /// if it does not apply, or would exceed the raw 32-bit size, the full
+    /// greedy chain runs instead. Every [`SELECTION_EXPLORE_INTERVAL`]
+    /// samples the greedy chain runs regardless, so a source whose
+    /// statistics change can move to another preference.
+    ///
+    /// Only outcomes the greedy chain would also have produced are scored:
+    /// samples without a prediction yet (always raw) and raw fast-path hits
+    /// (which never checked whether a delta fits) are counted but not
+    /// scored, otherwise a source's first raw sample would lock it on raw.
+    fn choose_encoding_adaptive(
+        &mut self,
+        data: &RawData,
+        context: &Context,
+    ) -> (EncodingType, Vec<u8>) {
+        let stats = self.selection.get(&data.source_id).copied();
+        let preferred = stats
+            .filter(|s| s.samples % SELECTION_EXPLORE_INTERVAL != 0)
+            .and_then(|s| s.preferred());
+
+        let mut verified = true;
+        let fast = preferred.and_then(|preferred| {
+            Self::repeated_encoding(data, context).or_else(|| match preferred {
+                EncodingType::Delta8 | EncodingType::Delta16 | EncodingType::Delta32 => {
+                    Self::delta_encoding(data, context)
+                        .filter(|(_, bytes)| bytes.len() <= core::mem::size_of::<f32>())
+                }
+                EncodingType::Raw32 | EncodingType::Raw64 => {
+                    let (ty, bytes) = Self::raw_encoding(data);
+                    verified = false;
+                    (ty == preferred).then_some((ty, bytes))
+                }
+                _ => None,
+            })
+        });
+        let (encoding_type, encoded_value) = match fast {
+            Some(choice) => choice,
+            None => {
+                verified = context.predict(data.source_id).is_some();
+                self.choose_encoding(data, context)
             }
+        };
+
+        let stats = self.selection.entry(data.source_id).or_default();
+        if verified {
+            stats.record(encoding_type, encoded_value.len());
+        } else if stats.samples > 0 {
+            stats.samples = stats.samples.wrapping_add(1);
         }
+        (encoding_type, encoded_value)
+    }
 
-        // Try to get prediction for delta encoding
-        if let Some(prediction) = context.predict(data.source_id) {
-            let delta = data.value - prediction.value;
-            let scale = context.scale_factor() as f64;
-            let raw = delta * scale;
-            let scaled_delta = if raw >= 0.0 { raw + 0.5 } else { raw - 0.5 };
-            let scaled_delta = scaled_delta as i64 as f64;
-
-            // Check if delta fits in i8
-            if scaled_delta >= i8::MIN as f64 && scaled_delta <= i8::MAX as f64 {
-                let delta_i8 = scaled_delta as i8;
-                return (EncodingType::Delta8, vec![delta_i8 as u8]);
-            }
+    /// Repeated encoding, if the value matches the source's last value
+    fn repeated_encoding(data: &RawData, context: &Context) -> Option<(EncodingType, Vec<u8>)> {
+        let last = context.last_value(data.source_id)?;
+        ((data.value - last).abs() < f64::EPSILON).then(|| (EncodingType::Repeated, vec![]))
+    }
 
-            // Check if delta fits in i16
-            if scaled_delta >= i16::MIN as f64 && scaled_delta <= i16::MAX as f64 {
-                let delta_i16 = scaled_delta as i16;
-                return (EncodingType::Delta16, delta_i16.to_be_bytes().to_vec());
-            }
+    /// Narrowest delta encoding against the source's prediction
+    fn delta_encoding(data: &RawData, context: &Context) -> Option<(EncodingType, Vec<u8>)> {
+        let prediction = context.predict(data.source_id)?;
+        let delta = data.value - prediction.value;
+        let scale = context.scale_factor() as f64;
+        let scaled_delta = Self::scaled_delta(delta, scale);
+
+        match Self::delta_type(delta, scale)? {
+            EncodingType::Delta8 => Some((EncodingType::Delta8, vec![scaled_delta as i8 as u8])),
+            EncodingType::Delta16 => Some((
+                EncodingType::Delta16,
+                (scaled_delta as i16).to_be_bytes().to_vec(),
+            )),
+            _ => Some((
+                EncodingType::Delta32,
+                (scaled_delta as i32).to_be_bytes().to_vec(),
+            )),
+        }
+    }
 
-            // Check if delta fits in i32
-            if scaled_delta >= i32::MIN as f64 && scaled_delta <= i32::MAX as f64 {
-                let delta_i32 = scaled_delta as i32;
-                return (EncodingType::Delta32, delta_i32.to_be_bytes().to_vec());
-            }
+    /// `delta` in steps of `1 / scale`, rounded half away from zero
+    fn scaled_delta(delta: f64, scale: f64) -> f64 {
+        let raw = delta * scale;
+        let scaled_delta = if raw >= 0.0 { raw + 0.5 } else { raw - 0.5 };
+        scaled_delta as i64 as f64
+    }
+
+    /// Narrowest delta type holding `delta` at `scale`, if any
+    fn delta_type(delta: f64, scale: f64) -> Option<EncodingType> {
+        let scaled_delta = Self::scaled_delta(delta, scale);
+        if scaled_delta >= i8::MIN as f64 && scaled_delta <= i8::MAX as f64 {
+            Some(EncodingType::Delta8)
+        } else if scaled_delta >= i16::MIN as f64 && scaled_delta <= i16::MAX as f64 {
+            Some(EncodingType::Delta16)
+        } else if scaled_delta >= i32::MIN as f64 && scaled_delta <= i32::MAX as f64 {
+            Some(EncodingType::Delta32)
+        } else {
+            None
         }
+    }
 
+    /// Raw encoding, 32-bit when the value survives the narrowing
+    fn raw_encoding(data: &RawData) -> (EncodingType, Vec<u8>) {
         // Check if value can fit in f32 without significant loss
         let as_f32 = data.value as f32;
         if (as_f32 as f64 - data.value).abs() < 0.0001 {
@@ -344,22 +1771,33 @@ impl Encoder {
 
     /// Encode a varint (variable-length integer)
     fn encode_varint(&self, value: u32, output: &mut Vec<u8>) {
-        let mut v = value;
-        while v >= 0x80 {
-            output.push((v as u8 & 0x7F) | 0x80);
-            v >>= 7;
-        }
-        output.push(v as u8);
+        crate::protocol::write_varint(u64::from(value), output);
     }
 
     /// Get next sequence number
+    ///
+    /// Saves the counter once a save interval has been issued; a failed
+    /// save is retried with the next message.
     fn next_sequence(&mut self) -> u16 {
         let seq = self.sequence;
         self.sequence = self.sequence.wrapping_add(1);
+        if self.sequence_store.is_some() {
+            self.unsaved_sequences = self.unsaved_sequences.saturating_add(1);
+            if self.unsaved_sequences >= self.sequence_persistence.save_interval {
+                let _ = self.save_sequence();
+            }
+        }
         seq
     }
 
     /// Encode multiple values in one message
+    ///
+    /// # Errors
+    ///
+    /// `EncodeError::PayloadTooLarge`, with `size` and `max` counted in
+    /// values, when `values` holds more than
+    /// [`MAX_MULTI_VALUES`] entries: split them
+    /// over several messages. No sequence number is used up.
     pub fn encode_multi(
         &mut self,
         values: &[(u8, f64)], // (name_id, value) pairs
@@ -367,7 +1805,8 @@ impl Encoder {
         timestamp: u64,
         priority: Priority,
         context: &Context,
-    ) -> EncodedMessage {
+    ) -> Result<EncodedMessage> {
+        check_multi_count(values.len())?;
         let mut payload = Vec::new();
 
         // Source ID
@@ -389,18 +1828,110 @@ impl Encoder {
             payload.extend_from_slice(&(*value as f32).to_be_bytes());
         }
 
+        let micros = self.bare_micros(timestamp);
         let header = MessageHeader {
-            version: crate::PROTOCOL_VERSION,
+            layout: self.header_layout(priority, micros),
             message_type: MessageType::Data,
             priority,
             sequence: self.next_sequence(),
-            timestamp: (timestamp / 1000) as u32,
-            context_version: context.version(),
+            timestamp: Self::header_timestamp(micros),
+            context_version: Self::header_context_version(context),
         };
 
-        EncodedMessage::new(header, payload)
+        let message = EncodedMessage::new(header, payload);
+        if let Some(obs) = &self.observer {
+            let raw_size = values.len() * Self::RAW_SAMPLE_SIZE;
+            Self::notify(obs.as_ref(), &message, source_id, raw_size, None);
+        }
+        Ok(message)
+    }
+
+    /// Encode the values of several channels under one header
+    ///
+    /// Each entry is encoded against its own context as a standalone
+    /// message would be (repeated, delta or raw; non-finite values raw),
+    /// and carries its channel id and length, so a multi-channel flush
+    /// pays for a single header. Every entry takes `timestamp`,
+    /// `priority` and the message's single sequence number; `source_id`
+    /// identifies the emitter. The header's context version is 0, since
+    /// each entry has its own context. Contexts are not updated: observe
+    /// each value in its channel's context afterwards.
+    ///
+    /// Decode with [`crate::Decoder::decode_composite`]. A payload beyond
+    /// [`MAX_PAYLOAD_SIZE`] bytes is refused with
+    /// `EncodeError::PayloadTooLarge` before a sequence number is used.
+    ///
+    /// ```
+    /// use alec::{CompositeEntry, Context, Decoder, Encoder, Priority};
+    ///
+    /// let (temperature, humidity) = (Context::new(), Context::new());
+    /// let entries = [
+    ///     CompositeEntry { channel_id: 0, value: 21.5, context: &temperature },
+    ///     CompositeEntry { channel_id: 1, value: 48.0, context: &humidity },
+    /// ];
+    /// let message = Encoder::new()
+    ///     .encode_composite(9, 60_000, Priority::P3Normal, &entries)
+    ///     .unwrap();
+    /// assert!(message.is_composite());
+    ///
+    /// let decoded = Decoder::new()
+    ///     .decode_composite(&message, |channel| Some([&temperature, &humidity][channel as usize]))
+    ///     .unwrap();
+    /// assert_eq!(decoded.values[1].value, Ok(48.0));
+    /// ```
+    pub fn encode_composite(
+        &mut self,
+        source_id: u32,
+        timestamp: u64,
+        priority: Priority,
+        entries: &[CompositeEntry<'_>],
+    ) -> Result<EncodedMessage> {
+        let mut payload = Vec::new();
+        self.encode_varint(source_id, &mut payload);
+        payload.push(EncodingType::Composite as u8);
+        self.encode_varint(entries.len() as u32, &mut payload);
+
+        for entry in entries {
+            let data = RawData::new(entry.value, timestamp);
+            let (encoding_type, encoded_value) = if data.value.is_finite() {
+                self.choose_encoding(&data, entry.context)
+            } else {
+                (EncodingType::Raw64, data.value.to_be_bytes().to_vec())
+            };
+            self.encode_varint(entry.channel_id, &mut payload);
+            payload.push(encoding_type as u8);
+            self.encode_varint(encoded_value.len() as u32, &mut payload);
+            payload.extend(encoded_value);
+        }
+        if payload.len() > MAX_PAYLOAD_SIZE {
+            return Err(EncodeError::PayloadTooLarge {
+                size: payload.len(),
+                max: MAX_PAYLOAD_SIZE,
+            }
+            .into());
+        }
+
+        let micros = self.bare_micros(timestamp);
+        let header = MessageHeader {
+            layout: self.header_layout(priority, micros),
+            message_type: MessageType::Data,
+            priority,
+            sequence: self.next_sequence(),
+            timestamp: Self::header_timestamp(micros),
+            context_version: 0,
+        };
+
+        let message = EncodedMessage::new(header, payload);
+        if let Some(obs) = &self.observer {
+            let raw_size = entries.len() * Self::RAW_SAMPLE_SIZE;
+            Self::notify(obs.as_ref(), &message, source_id, raw_size, None);
+        }
+        Ok(message)
     }
 
+    /// Raw size of one sample, matching `RawData::raw_size`
+    const RAW_SAMPLE_SIZE: usize = 20;
+
     /// Maximum frame size for P4 inclusion (BLE ATT_MTU)
     const MULTI_FRAME_CAP: usize = 127;
 
@@ -413,13 +1944,19 @@ impl Encoder {
     ///
     /// Returns the encoded message and a list of classifications (one per input
     /// channel, in the same order) so the caller can observe P5 channels.
+    ///
+    /// # Errors
+    ///
+    /// `EncodeError::PayloadTooLarge`, counted in values, when more than
+    /// [`MAX_MULTI_VALUES`] channels classify
+    /// P1–P3 and must all be included.
     pub fn encode_multi_adaptive(
         &mut self,
         channels: &[ChannelInput],
         timestamp: u64,
         context: &Context,
         classifier: &Classifier,
-    ) -> (EncodedMessage, Vec<Classification>) {
+    ) -> Result<(EncodedMessage, Vec<Classification>)> {
         // Classify every channel using name_id as context key
         // (matches decoder convention: name_id → source_id for multi frames)
         let classified: Vec<(&ChannelInput, Classification)> = channels
@@ -448,6 +1985,8 @@ impl Encoder {
             }
         }
 
+        check_multi_count(must_include.len())?;
+
         // Sort must_include by priority (P1 first)
         must_include.sort_by_key(|(_, cls)| cls.priority);
 
@@ -472,9 +2011,24 @@ impl Encoder {
             included_count += 1;
         }
 
+        let priority = must_include
+            .first()
+            .map(|(_, cls)| cls.priority)
+            .unwrap_or(Priority::P3Normal);
+
         // Try to fit P4 (deferred) channels
-        let header_overhead = MessageHeader::SIZE;
+        let micros = self.bare_micros(timestamp);
+        let mut header_overhead = MessageHeader::SIZE;
+        if let Some(extension) = self.header_extension(priority, micros) {
+            header_overhead += extension.encoded_len();
+        }
+        if self.checksum_policy.applies_to(priority) {
+            header_overhead += CHECKSUM_SIZE;
+        }
         for (ch, _cls) in &deferred {
+            if included_count as usize == MAX_MULTI_VALUES {
+                break;
+            }
             // Speculatively encode into a temp buffer to check size
             let mut tmp = Vec::new();
             self.write_channel_entry(ch, context, &mut tmp);
@@ -488,19 +2042,22 @@ impl Encoder {
         payload[count_pos] = included_count;
 
         let header = MessageHeader {
-            version: crate::PROTOCOL_VERSION,
+            layout: self.header_layout(priority, micros),
             message_type: MessageType::Data,
-            priority: must_include
-                .first()
-                .map(|(_, cls)| cls.priority)
-                .unwrap_or(Priority::P3Normal),
+            priority,
             sequence: self.next_sequence(),
-            timestamp: (timestamp / 1000) as u32,
-            context_version: context.version(),
+            timestamp: Self::header_timestamp(micros),
+            context_version: Self::header_context_version(context),
         };
 
+        let message = EncodedMessage::new(header, payload);
+        if let Some(obs) = &self.observer {
+            let raw_size = channels.len() * Self::RAW_SAMPLE_SIZE;
+            Self::notify(obs.as_ref(), &message, 0, raw_size, None);
+        }
+
         let classifications = classified.into_iter().map(|(_, cls)| cls).collect();
-        (EncodedMessage::new(header, payload), classifications)
+        Ok((message, classifications))
     }
 
     /// Write one channel entry into the multi payload.
@@ -859,6 +2416,59 @@ impl Default for Encoder {
     }
 }
 
+// HealthCheckable implementation for Encoder
+#[cfg(feature = "std-time")]
+impl crate::health::HealthCheckable for Encoder {
+    fn health_check(&self) -> crate::health::HealthCheck {
+        self.health_check_with(&crate::health::HealthConfig::default())
+    }
+
+    fn health_check_with(
+        &self,
+        config: &crate::health::HealthConfig,
+    ) -> crate::health::HealthCheck {
+        use crate::health::HealthCheck;
+
+        let latency = self.last_encode_latency.unwrap_or_default();
+        let fallback_rate = self.fallback_rate();
+        let details = format!(
+            "Messages: {}, Last latency: {}us, Fallback rate: {:.0}%",
+            self.messages_encoded,
+            latency.as_micros(),
+            fallback_rate * 100.0
+        );
+
+        let check = if self.fallbacks.count() >= config.min_window_messages
+            && fallback_rate > config.degraded_fallback_rate
+        {
+            HealthCheck::degraded(
+                "Encoder",
+                format!(
+                    "Fallback rate {:.0}% above {:.0}% ({})",
+                    fallback_rate * 100.0,
+                    config.degraded_fallback_rate * 100.0,
+                    details
+                ),
+            )
+        } else {
+            HealthCheck::healthy("Encoder").with_message(details)
+        };
+        check.with_latency(latency)
+    }
+}
+
+/// Refuse a multi-value message of more than [`MAX_MULTI_VALUES`] values
+fn check_multi_count(count: usize) -> Result<()> {
+    if count > MAX_MULTI_VALUES {
+        return Err(EncodeError::PayloadTooLarge {
+            size: count,
+            max: MAX_MULTI_VALUES,
+        }
+        .into());
+    }
+    Ok(())
+}
+
 /// Builder for creating encoded messages manually
 pub struct MessageBuilder {
     header: MessageHeader,
@@ -898,9 +2508,9 @@ impl MessageBuilder {
         self
     }
 
-    /// Set context version
+    /// Set context version, kept to the 24 bits a header carries
     pub fn context_version(mut self, version: u32) -> Self {
-        self.header.context_version = version;
+        self.header.context_version = version & MessageHeader::MAX_CONTEXT_VERSION;
         self
     }
 
@@ -1026,11 +2636,31 @@ mod tests {
             (3, 1013.25), // pressure
         ];
 
-        let message = encoder.encode_multi(&values, 42, 12345, Priority::P3Normal, &context);
+        let message = encoder
+            .encode_multi(&values, 42, 12345, Priority::P3Normal, &context)
+            .unwrap();
 
         assert_eq!(message.encoding_type(), Some(EncodingType::Multi));
     }
 
+    #[test]
+    fn test_encode_with_detail_cache() {
+        let mut encoder = Encoder::new();
+        let classifier = Classifier::default();
+        let context = Context::new();
+        let mut cache = DetailCache::new(8);
+
+        for i in 0..3 {
+            let data = RawData::new(20.0 + i as f64, i * 1000);
+            let classification = classifier.classify(&data, &context);
+            let message =
+                encoder.encode_with_detail_cache(&data, &classification, &context, &mut cache);
+            let entry = cache.get(message.header.sequence as u32).unwrap();
+            assert_eq!(entry.data, data);
+        }
+        assert_eq!(cache.len(), 3);
+    }
+
     #[test]
     fn test_varint_encoding() {
         let encoder = Encoder::new();
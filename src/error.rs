@@ -7,9 +7,13 @@
 //! Error types for ALEC
 //!
 //! This module defines all error types used throughout the library.
+//!
+//! Errors raised where the emitter, sequence number or position in a
+//! buffer is known carry an [`ErrorContext`]; use [`AlecError::inner`] to
+//! match on the underlying error whether or not it was annotated.
 
 #[cfg(not(feature = "std"))]
-use alloc::string::String;
+use alloc::{boxed::Box, string::String};
 
 #[cfg(feature = "std")]
 use thiserror::Error;
@@ -40,6 +44,72 @@ pub enum AlecError {
     /// Protocol error
     #[cfg_attr(feature = "std", error("Protocol error: {0}"))]
     Protocol(String),
+
+    /// Error annotated with where it occurred
+    ///
+    /// Built by [`AlecError::with_context`]; never nests.
+    #[cfg_attr(feature = "std", error("{error} ({context})"))]
+    WithContext {
+        /// The underlying error
+        error: Box<AlecError>,
+        /// Where it occurred
+        context: ErrorContext,
+    },
+}
+
+impl AlecError {
+    /// Annotate the error with where it occurred
+    ///
+    /// Fields already set on an annotated error are kept; the others are
+    /// filled from `context`.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            AlecError::WithContext {
+                error,
+                context: existing,
+            } => AlecError::WithContext {
+                error,
+                context: existing.or(context),
+            },
+            error if context.is_empty() => error,
+            error => AlecError::WithContext {
+                error: Box::new(error),
+                context,
+            },
+        }
+    }
+
+    /// Where the error occurred, if known
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            AlecError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its context, for matching
+    ///
+    /// ```
+    /// use alec::error::{AlecError, DecodeError, ErrorContext};
+    ///
+    /// let err = AlecError::from(DecodeError::InvalidHeader)
+    ///     .with_context(ErrorContext::new().with_offset(12));
+    /// assert!(matches!(err.inner(), AlecError::Decode(DecodeError::InvalidHeader)));
+    /// ```
+    pub fn inner(&self) -> &AlecError {
+        match self {
+            AlecError::WithContext { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Consume the error, dropping its context
+    pub fn into_inner(self) -> AlecError {
+        match self {
+            AlecError::WithContext { error, .. } => *error,
+            error => error,
+        }
+    }
 }
 
 #[cfg(not(feature = "std"))]
@@ -51,10 +121,85 @@ impl core::fmt::Display for AlecError {
             AlecError::Context(e) => write!(f, "Context error: {}", e),
             AlecError::Channel(e) => write!(f, "Channel error: {}", e),
             AlecError::Protocol(s) => write!(f, "Protocol error: {}", s),
+            AlecError::WithContext { error, context } => write!(f, "{} ({})", error, context),
+        }
+    }
+}
+
+/// Where an error occurred
+///
+/// Every field is optional: decoders fill in what they know.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Emitter of the message
+    pub source_id: Option<u32>,
+    /// Sequence number of the message
+    pub sequence: Option<u32>,
+    /// Byte offset of the message in the buffer being decoded
+    pub offset: Option<usize>,
+}
+
+impl ErrorContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the source
+    pub fn with_source_id(mut self, source_id: u32) -> Self {
+        self.source_id = Some(source_id);
+        self
+    }
+
+    /// Set the sequence number
+    pub fn with_sequence(mut self, sequence: u32) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// Set the byte offset
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Whether no field is set
+    pub fn is_empty(&self) -> bool {
+        self.source_id.is_none() && self.sequence.is_none() && self.offset.is_none()
+    }
+
+    /// Fields of `self`, completed by those of `other`
+    fn or(self, other: Self) -> Self {
+        Self {
+            source_id: self.source_id.or(other.source_id),
+            sequence: self.sequence.or(other.sequence),
+            offset: self.offset.or(other.offset),
         }
     }
 }
 
+impl core::fmt::Display for ErrorContext {
+    /// Set fields only, e.g. `source 3, sequence 7, offset 42`
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let fields = [
+            ("source", self.source_id.map(|v| v as usize)),
+            ("sequence", self.sequence.map(|v| v as usize)),
+            ("offset", self.offset),
+        ];
+        let mut first = true;
+        for (name, value) in fields {
+            if let Some(value) = value {
+                if !first {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{} {}", name, value)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(not(feature = "std"))]
 impl From<EncodeError> for AlecError {
     fn from(e: EncodeError) -> Self {
@@ -143,6 +288,34 @@ impl core::fmt::Display for EncodeError {
     }
 }
 
+/// Decode resource limit of a [`crate::decoder::DecoderConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceLimit {
+    /// `max_values_per_message`
+    ValuesPerMessage,
+    /// `max_expansion_bytes`
+    ExpansionBytes,
+    /// `max_decode_micros`
+    DecodeMicros,
+}
+
+impl ResourceLimit {
+    /// Name of the configuration field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceLimit::ValuesPerMessage => "max_values_per_message",
+            ResourceLimit::ExpansionBytes => "max_expansion_bytes",
+            ResourceLimit::DecodeMicros => "max_decode_micros",
+        }
+    }
+}
+
+impl core::fmt::Display for ResourceLimit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Errors during decoding
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Error))]
@@ -154,6 +327,13 @@ pub enum DecodeError {
     )]
     InvalidChecksum { expected: u32, actual: u32 },
 
+    /// Invalid CRC-32C checksum ([`crate::protocol::FLAG_CRC32C`])
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid CRC-32C: expected {expected:08x}, got {actual:08x}")
+    )]
+    InvalidCrc32c { expected: u32, actual: u32 },
+
     /// Context mismatch (can't decode without correct context)
     #[cfg_attr(
         feature = "std",
@@ -190,6 +370,91 @@ pub enum DecodeError {
     /// Invalid header
     #[cfg_attr(feature = "std", error("Invalid header"))]
     InvalidHeader,
+
+    /// Compressed timestamp received before any anchor from this source,
+    /// or after lost messages
+    #[cfg_attr(
+        feature = "std",
+        error("No timestamp anchor for source {source_id}: full timestamp required")
+    )]
+    MissingTimestampAnchor { source_id: u32 },
+
+    /// Compressed timestamp would move backwards in time
+    #[cfg_attr(
+        feature = "std",
+        error("Timestamp regression for source {source_id}: last seen {last}")
+    )]
+    TimestampRegression { source_id: u32, last: u32 },
+
+    /// Message written with a newer major wire-format version
+    #[cfg_attr(
+        feature = "std",
+        error("Unsupported wire version: major {theirs}, supported up to {ours}")
+    )]
+    UnsupportedVersion { ours: u8, theirs: u8 },
+
+    /// Message sets critical header flags this decoder does not understand
+    #[cfg_attr(
+        feature = "std",
+        error("Unsupported critical header flags: {flags:#04x}")
+    )]
+    UnsupportedFlags { flags: u8 },
+
+    /// Plan-relative frame for a plan that was lost or has expired
+    #[cfg_attr(
+        feature = "std",
+        error("No active plan {plan_id}: plan message lost or expired")
+    )]
+    PlanMissing { plan_id: u8 },
+
+    /// Message would take more decode work than the decoder allows
+    #[cfg_attr(
+        feature = "std",
+        error("Decode resource limit {which} exceeded: {attempted} > {limit}")
+    )]
+    ResourceLimitExceeded {
+        which: ResourceLimit,
+        limit: u64,
+        attempted: u64,
+    },
+
+    /// Message older than the staleness threshold of its priority
+    #[cfg_attr(
+        feature = "std",
+        error("Stale {priority:?} message from source {source_id}: {age_ms} ms old, at most {max_age_ms} ms allowed")
+    )]
+    Stale {
+        source_id: u32,
+        priority: crate::protocol::Priority,
+        age_ms: u64,
+        max_age_ms: u64,
+    },
+
+    /// Composite entry for a channel the receiver has no context for
+    #[cfg_attr(feature = "std", error("Unknown composite channel {channel_id}"))]
+    UnknownChannel { channel_id: u32 },
+
+    /// Sender records timestamps in another unit than the decoder expects
+    /// ([`crate::DecoderConfig::timestamp_unit`])
+    #[cfg_attr(
+        feature = "std",
+        error("Timestamp unit mismatch: expected {expected}, message has {actual}")
+    )]
+    TimestampUnitMismatch {
+        expected: crate::protocol::TimestampUnit,
+        actual: crate::protocol::TimestampUnit,
+    },
+}
+
+impl DecodeError {
+    /// Algorithm of the checksum that did not match, for checksum errors
+    pub fn checksum_algorithm(&self) -> Option<crate::protocol::ChecksumAlgorithm> {
+        match self {
+            DecodeError::InvalidChecksum { .. } => Some(crate::protocol::ChecksumAlgorithm::Xxh32),
+            DecodeError::InvalidCrc32c { .. } => Some(crate::protocol::ChecksumAlgorithm::Crc32c),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(not(feature = "std"))]
@@ -203,6 +468,13 @@ impl core::fmt::Display for DecodeError {
                     expected, actual
                 )
             }
+            DecodeError::InvalidCrc32c { expected, actual } => {
+                write!(
+                    f,
+                    "Invalid CRC-32C: expected {:08x}, got {:08x}",
+                    expected, actual
+                )
+            }
             DecodeError::ContextMismatch { expected, actual } => {
                 write!(
                     f,
@@ -230,6 +502,70 @@ impl core::fmt::Display for DecodeError {
                 )
             }
             DecodeError::InvalidHeader => write!(f, "Invalid header"),
+            DecodeError::MissingTimestampAnchor { source_id } => {
+                write!(
+                    f,
+                    "No timestamp anchor for source {}: full timestamp required",
+                    source_id
+                )
+            }
+            DecodeError::TimestampRegression { source_id, last } => {
+                write!(
+                    f,
+                    "Timestamp regression for source {}: last seen {}",
+                    source_id, last
+                )
+            }
+            DecodeError::UnsupportedVersion { ours, theirs } => {
+                write!(
+                    f,
+                    "Unsupported wire version: major {}, supported up to {}",
+                    theirs, ours
+                )
+            }
+            DecodeError::UnsupportedFlags { flags } => {
+                write!(f, "Unsupported critical header flags: {:#04x}", flags)
+            }
+            DecodeError::PlanMissing { plan_id } => {
+                write!(
+                    f,
+                    "No active plan {}: plan message lost or expired",
+                    plan_id
+                )
+            }
+            DecodeError::ResourceLimitExceeded {
+                which,
+                limit,
+                attempted,
+            } => {
+                write!(
+                    f,
+                    "Decode resource limit {} exceeded: {} > {}",
+                    which, attempted, limit
+                )
+            }
+            DecodeError::Stale {
+                source_id,
+                priority,
+                age_ms,
+                max_age_ms,
+            } => {
+                write!(
+                    f,
+                    "Stale {:?} message from source {}: {} ms old, at most {} ms allowed",
+                    priority, source_id, age_ms, max_age_ms
+                )
+            }
+            DecodeError::UnknownChannel { channel_id } => {
+                write!(f, "Unknown composite channel {}", channel_id)
+            }
+            DecodeError::TimestampUnitMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Timestamp unit mismatch: expected {}, message has {}",
+                    expected, actual
+                )
+            }
         }
     }
 }
@@ -273,6 +609,20 @@ pub enum ContextError {
         error("Memory limit exceeded: {used} bytes exceeds {limit}")
     )]
     MemoryLimitExceeded { used: usize, limit: usize },
+
+    /// Sync diff would overwrite different patterns
+    #[cfg_attr(
+        feature = "std",
+        error("Sync diff conflicts: {conflicts} patterns would be overwritten")
+    )]
+    DiffConflict { conflicts: usize },
+
+    /// Source remapping would send several sources to the same id
+    #[cfg_attr(
+        feature = "std",
+        error("Source id collision: {sources} sources would be remapped to {source_id}")
+    )]
+    SourceCollision { source_id: u32, sources: usize },
 }
 
 #[cfg(not(feature = "std"))]
@@ -305,10 +655,45 @@ impl core::fmt::Display for ContextError {
             ContextError::MemoryLimitExceeded { used, limit } => {
                 write!(f, "Memory limit exceeded: {} bytes exceeds {}", used, limit)
             }
+            ContextError::DiffConflict { conflicts } => {
+                write!(
+                    f,
+                    "Sync diff conflicts: {} patterns would be overwritten",
+                    conflicts
+                )
+            }
+            ContextError::SourceCollision { source_id, sources } => {
+                write!(
+                    f,
+                    "Source id collision: {} sources would be remapped to {}",
+                    sources, source_id
+                )
+            }
         }
     }
 }
 
+/// A bulk dictionary import rejected before anything was inserted
+///
+/// Returned by [`Context::register_patterns_bulk`](crate::Context::register_patterns_bulk)
+/// and [`Context::seed_from_values`](crate::Context::seed_from_values).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[cfg_attr(feature = "std", error("Pattern {index} rejected: {error}"))]
+pub struct BulkError {
+    /// Index in the input of the first pattern that violated a limit
+    pub index: usize,
+    /// The limit it violated
+    pub error: ContextError,
+}
+
+#[cfg(not(feature = "std"))]
+impl core::fmt::Display for BulkError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Pattern {} rejected: {}", self.index, self.error)
+    }
+}
+
 /// Errors related to the communication channel
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Error))]
@@ -332,6 +717,10 @@ pub enum ChannelError {
     /// Rate limited
     #[cfg_attr(feature = "std", error("Rate limited: retry after {retry_after_ms}ms"))]
     RateLimited { retry_after_ms: u64 },
+
+    /// Non-blocking operation could not complete now
+    #[cfg_attr(feature = "std", error("Operation would block"))]
+    WouldBlock,
 }
 
 #[cfg(not(feature = "std"))]
@@ -351,6 +740,7 @@ impl core::fmt::Display for ChannelError {
             ChannelError::RateLimited { retry_after_ms } => {
                 write!(f, "Rate limited: retry after {}ms", retry_after_ms)
             }
+            ChannelError::WouldBlock => write!(f, "Operation would block"),
         }
     }
 }
@@ -376,4 +766,34 @@ mod tests {
         let alec_err: AlecError = encode_err.into();
         assert!(matches!(alec_err, AlecError::Encode(_)));
     }
+
+    #[test]
+    fn test_error_context() {
+        let err = AlecError::from(DecodeError::InvalidChecksum {
+            expected: 1,
+            actual: 2,
+        });
+        assert_eq!(err.context(), None);
+        assert_eq!(err.clone().with_context(ErrorContext::new()), err);
+
+        let err = err
+            .with_context(ErrorContext::new().with_offset(42))
+            .with_context(ErrorContext::new().with_source_id(3).with_offset(7));
+        assert_eq!(
+            err.context(),
+            Some(&ErrorContext {
+                source_id: Some(3),
+                sequence: None,
+                offset: Some(42),
+            })
+        );
+        assert!(matches!(
+            err.inner(),
+            AlecError::Decode(DecodeError::InvalidChecksum { .. })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Decoding error: Invalid checksum: expected 00000001, got 00000002 (source 3, offset 42)"
+        );
+    }
 }
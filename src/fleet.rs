@@ -8,23 +8,92 @@
 //!
 //! Manages multiple contexts and provides cross-fleet analytics.
 //! Supports:
-//! - Individual contexts per emitter
+//! - Individual contexts per emitter, or contexts shared by the whole
+//!   fleet or by groups of emitters (see [`ContextMode`])
 //! - Shared fleet-wide context for common patterns
 //! - Cross-fleet anomaly detection
 //! - Fleet-wide statistics
+//! - Tenants whose emitter ids may collide, each with its own emitter
+//!   state, contexts and statistics
+//! - Routing processed messages to subscribers, by callback
+//!   ([`FleetManager::subscribe`]) or queue
+//!   ([`FleetManager::drain_matching`]), filtered by priority, emitter
+//!   and message type
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 use crate::classifier::Classifier;
 use crate::context::{Context, Pattern};
-use crate::decoder::Decoder;
-use crate::error::{ChannelError, Result};
-use crate::protocol::{Priority, RawData};
+use crate::decoder::{Decoder, DecoderConfig};
+use crate::error::{AlecError, ChannelError, DecodeError, Result};
+use crate::health::{HealthCheck, HealthCheckable, HealthConfig};
+use crate::protocol::{MessageType, Priority, RawData};
 use crate::security::{AuditEvent, AuditEventType, SecurityContext, Severity};
+use crate::sequence::{SequenceStats, SequenceStep, SequenceTracker, SequenceWindow};
+use crate::sync::{SyncMessage, Synchronizer};
 
 /// Unique identifier for an emitter
 pub type EmitterId = u32;
 
+/// Identifier for a group of emitters sharing one context
+pub type GroupId = u32;
+
+/// Identifier of a [`FleetManager::subscribe`] subscription
+pub type SubscriptionId = u32;
+
+/// Callback receiving the processed messages of a subscription
+pub type MessageHandler = Box<dyn Fn(&ProcessedMessage) + Send>;
+
+/// Identifier of a tenant (customer or site) owning a set of emitters
+pub type TenantId = u32;
+
+/// Group used by [`ContextMode::Shared`]
+pub const SHARED_GROUP: GroupId = 0;
+
+/// How decoding contexts are allocated to emitters
+///
+/// Selected at construction time through [`FleetConfig::context_mode`].
+#[derive(Debug, Clone, Default)]
+pub enum ContextMode {
+    /// Each emitter has its own context (strict isolation, for
+    /// heterogeneous fleets)
+    #[default]
+    PerEmitter,
+    /// All emitters feed and decode against a single context (for
+    /// homogeneous fleets)
+    Shared,
+    /// Emitters mapped to the same group share a context
+    SharedByGroup(fn(EmitterId) -> GroupId),
+    /// Emitters listed in the map share their group's context; unlisted
+    /// emitters keep their own context
+    SharedByGroupMap(HashMap<EmitterId, GroupId>),
+}
+
+impl ContextMode {
+    /// Key of the context used for an emitter
+    pub fn key_for(&self, emitter_id: EmitterId) -> ContextKey {
+        match self {
+            ContextMode::PerEmitter => ContextKey::Emitter(emitter_id),
+            ContextMode::Shared => ContextKey::Group(SHARED_GROUP),
+            ContextMode::SharedByGroup(group_of) => ContextKey::Group(group_of(emitter_id)),
+            ContextMode::SharedByGroupMap(groups) => match groups.get(&emitter_id) {
+                Some(group) => ContextKey::Group(*group),
+                None => ContextKey::Emitter(emitter_id),
+            },
+        }
+    }
+}
+
+/// Identifies the owner of a context in the fleet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ContextKey {
+    /// Context private to one emitter
+    Emitter(EmitterId),
+    /// Context shared by a group of emitters
+    Group(GroupId),
+}
+
 /// Fleet-wide statistics
 #[derive(Debug, Clone, Default)]
 pub struct FleetStats {
@@ -38,12 +107,19 @@ pub struct FleetStats {
     pub anomaly_count: u64,
     /// Cross-fleet anomalies (emitter behaving differently)
     pub cross_fleet_anomalies: u64,
+    /// Messages rejected for exceeding a decode resource limit
+    pub resource_limit_violations: u64,
+    /// Sequence gaps, duplicates, stale numbers and wraps over all emitters
+    pub sequence: SequenceStats,
 }
 
 /// State for a single emitter
 #[derive(Debug)]
 pub struct EmitterState {
     /// Emitter's context
+    ///
+    /// Stays empty when the emitter uses a shared group context; use
+    /// [`FleetManager::context_for`] to get the context actually in use.
     pub context: Context,
     /// Last seen timestamp
     pub last_seen: u64,
@@ -55,6 +131,10 @@ pub struct EmitterState {
     max_recent: usize,
     /// Is this emitter behaving anomalously?
     pub is_anomalous: bool,
+    /// Messages rejected for exceeding a decode resource limit
+    pub resource_limit_violations: u64,
+    /// Sequence numbers of the emitter's decoded messages
+    pub sequence: SequenceTracker,
 }
 
 impl EmitterState {
@@ -67,6 +147,8 @@ impl EmitterState {
             recent_values: Vec::with_capacity(100),
             max_recent: 100,
             is_anomalous: false,
+            resource_limit_violations: 0,
+            sequence: SequenceTracker::default(),
         }
     }
 
@@ -79,6 +161,8 @@ impl EmitterState {
             recent_values: Vec::with_capacity(max_recent),
             max_recent,
             is_anomalous: false,
+            resource_limit_violations: 0,
+            sequence: SequenceTracker::default(),
         }
     }
 
@@ -146,6 +230,21 @@ pub struct FleetConfig {
     pub fleet_sync_interval: u64,
     /// Maximum recent values to track per emitter
     pub max_recent_values: usize,
+    /// How decoding contexts are allocated to emitters
+    pub context_mode: ContextMode,
+    /// Tenant used by the methods without a tenant argument
+    pub default_tenant: TenantId,
+    /// Checksum policy and decode resource limits of the tenants' decoders
+    pub decoder: DecoderConfig,
+    /// Resource limit violations after which an emitter is flagged
+    /// anomalous (quarantined); 0 never flags
+    pub max_resource_violations: u64,
+    /// Messages kept per [`FleetManager::drain_matching`] queue; the
+    /// oldest are dropped beyond it
+    pub subscription_queue_capacity: usize,
+    /// How far ahead of an emitter's last sequence number a message
+    /// counts as newer
+    pub sequence_window: SequenceWindow,
 }
 
 impl Default for FleetConfig {
@@ -157,6 +256,12 @@ impl Default for FleetConfig {
             min_emitters_for_comparison: 3,
             fleet_sync_interval: 1000,
             max_recent_values: 100,
+            context_mode: ContextMode::PerEmitter,
+            default_tenant: 0,
+            decoder: DecoderConfig::default(),
+            max_resource_violations: 3,
+            subscription_queue_capacity: 1024,
+            sequence_window: SequenceWindow::default(),
         }
     }
 }
@@ -170,79 +275,169 @@ pub struct ProcessedMessage {
     pub value: f64,
     /// Assigned priority
     pub priority: Priority,
+    /// Type of the decoded message
+    pub message_type: MessageType,
     /// Whether this triggered a cross-fleet anomaly
     pub is_cross_fleet_anomaly: bool,
+    /// Sequence number relative to the emitter's previous message
+    pub sequence_step: SequenceStep,
 }
 
-/// Manages a fleet of emitters
+/// Messages a subscription receives
+///
+/// Each non-empty list keeps the messages whose field it contains; an
+/// empty list matches anything, so the default filter matches every
+/// message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionFilter {
+    /// Priorities received (empty: all)
+    pub priorities: Vec<Priority>,
+    /// Emitters received from (empty: all)
+    pub emitters: Vec<EmitterId>,
+    /// Message types received (empty: all)
+    pub message_types: Vec<MessageType>,
+}
+
+impl SubscriptionFilter {
+    /// Filter matching every message
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only receive these priorities
+    pub fn with_priorities(mut self, priorities: impl IntoIterator<Item = Priority>) -> Self {
+        self.priorities = priorities.into_iter().collect();
+        self
+    }
+
+    /// Only receive from these emitters
+    pub fn with_emitters(mut self, emitters: impl IntoIterator<Item = EmitterId>) -> Self {
+        self.emitters = emitters.into_iter().collect();
+        self
+    }
+
+    /// Only receive these message types
+    pub fn with_message_types(
+        mut self,
+        message_types: impl IntoIterator<Item = MessageType>,
+    ) -> Self {
+        self.message_types = message_types.into_iter().collect();
+        self
+    }
+
+    /// Check whether a processed message passes the filter
+    pub fn matches(&self, message: &ProcessedMessage) -> bool {
+        (self.priorities.is_empty() || self.priorities.contains(&message.priority))
+            && (self.emitters.is_empty() || self.emitters.contains(&message.emitter_id))
+            && (self.message_types.is_empty() || self.message_types.contains(&message.message_type))
+    }
+}
+
+/// Callback subscription
+struct Subscriber {
+    id: SubscriptionId,
+    filter: SubscriptionFilter,
+    handler: MessageHandler,
+}
+
+impl std::fmt::Debug for Subscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("id", &self.id)
+            .field("filter", &self.filter)
+            .finish()
+    }
+}
+
+/// Bounded queue of the messages matching a filter, drained by
+/// [`FleetManager::drain_matching`]
 #[derive(Debug)]
-pub struct FleetManager {
+struct MessageQueue {
+    filter: SubscriptionFilter,
+    messages: VecDeque<ProcessedMessage>,
+}
+
+/// Emitters, contexts and statistics of one tenant
+///
+/// Emitter ids are only unique within a tenant: each tenant has its own
+/// emitter states, decoding contexts, decoder, fleet context and
+/// statistics, and nothing is shared across tenants.
+#[derive(Debug)]
+struct TenantFleet {
     /// Individual contexts per emitter
     emitter_contexts: HashMap<EmitterId, EmitterState>,
+    /// Contexts shared by groups of emitters (non-`PerEmitter` modes)
+    group_contexts: HashMap<GroupId, Context>,
     /// Shared fleet-wide context (common patterns)
     fleet_context: Context,
-    /// Classifier for fleet-wide analysis
-    #[allow(dead_code)]
-    classifier: Classifier,
     /// Decoder
     decoder: Decoder,
-    /// Configuration
-    config: FleetConfig,
     /// Statistics
     stats: FleetStats,
     /// Message counter for sync interval
     message_counter: u64,
 }
 
-impl FleetManager {
-    /// Create a new fleet manager
-    pub fn new() -> Self {
-        Self {
-            emitter_contexts: HashMap::new(),
-            fleet_context: Context::new(),
-            classifier: Classifier::default(),
-            decoder: Decoder::new(),
-            config: FleetConfig::default(),
-            stats: FleetStats::default(),
-            message_counter: 0,
-        }
-    }
-
-    /// Create with custom configuration
-    pub fn with_config(config: FleetConfig) -> Self {
+impl TenantFleet {
+    fn new(config: &FleetConfig) -> Self {
         Self {
             emitter_contexts: HashMap::new(),
+            group_contexts: HashMap::new(),
             fleet_context: Context::new(),
-            classifier: Classifier::default(),
-            decoder: Decoder::new(),
-            config,
+            decoder: Decoder::with_config(config.decoder),
             stats: FleetStats::default(),
             message_counter: 0,
         }
     }
 
-    /// Process a message from an emitter
-    pub fn process_message(
+    /// Process a message from one of this tenant's emitters
+    fn process_message(
         &mut self,
+        config: &FleetConfig,
         emitter_id: EmitterId,
         message: &crate::protocol::EncodedMessage,
         timestamp: u64,
     ) -> Result<ProcessedMessage> {
         // Get or create emitter state
-        let max_recent = self.config.max_recent_values;
-        let emitter = self
-            .emitter_contexts
-            .entry(emitter_id)
-            .or_insert_with(|| EmitterState::with_capacity(max_recent));
-
-        // Decode message
-        let decoded = self.decoder.decode(message, &emitter.context)?;
+        let max_recent = config.max_recent_values;
+        let emitter = self.emitter_contexts.entry(emitter_id).or_insert_with(|| {
+            let mut state = EmitterState::with_capacity(max_recent);
+            state.sequence = SequenceTracker::new(config.sequence_window);
+            state
+        });
+        let context = match config.context_mode.key_for(emitter_id) {
+            ContextKey::Emitter(_) => &mut emitter.context,
+            ContextKey::Group(group) => self.group_contexts.entry(group).or_default(),
+        };
+
+        // Decode message; repeated resource limit violations quarantine
+        // the emitter
+        let decoded = match self.decoder.decode(message, context) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                if matches!(
+                    e.inner(),
+                    AlecError::Decode(DecodeError::ResourceLimitExceeded { .. })
+                ) {
+                    self.stats.resource_limit_violations += 1;
+                    emitter.resource_limit_violations += 1;
+                    if config.max_resource_violations > 0
+                        && emitter.resource_limit_violations >= config.max_resource_violations
+                    {
+                        emitter.is_anomalous = true;
+                    }
+                }
+                return Err(e);
+            }
+        };
 
         // Update emitter state
+        context.observe_with_priority(&RawData::new(decoded.value, timestamp), decoded.priority);
         emitter.record_value(decoded.value, timestamp);
-        emitter
-            .context
-            .observe(&RawData::new(decoded.value, timestamp));
+        let (sequence_step, wrapped) = emitter
+            .sequence
+            .observe_wrapped(message.header.sequence as u32);
+        self.stats.sequence.count(sequence_step, wrapped);
 
         // Update stats
         self.stats.total_messages += 1;
@@ -254,7 +449,7 @@ impl FleetManager {
             .or_insert(0) += 1;
 
         // Check for cross-fleet anomaly
-        let cross_fleet_anomaly = self.check_cross_fleet_anomaly(emitter_id, decoded.value);
+        let cross_fleet_anomaly = self.check_cross_fleet_anomaly(config, emitter_id, decoded.value);
         if cross_fleet_anomaly {
             self.stats.cross_fleet_anomalies += 1;
             if let Some(e) = self.emitter_contexts.get_mut(&emitter_id) {
@@ -269,8 +464,8 @@ impl FleetManager {
 
         // Periodic fleet sync
         self.message_counter += 1;
-        if self.message_counter >= self.config.fleet_sync_interval {
-            self.sync_fleet_patterns();
+        if self.message_counter >= config.fleet_sync_interval {
+            self.sync_fleet_patterns(&config.context_mode);
             self.message_counter = 0;
         }
 
@@ -278,13 +473,20 @@ impl FleetManager {
             emitter_id,
             value: decoded.value,
             priority: decoded.priority,
+            message_type: message.header.message_type,
             is_cross_fleet_anomaly: cross_fleet_anomaly,
+            sequence_step,
         })
     }
 
-    /// Check if this value is anomalous compared to fleet
-    fn check_cross_fleet_anomaly(&self, emitter_id: EmitterId, value: f64) -> bool {
-        if self.emitter_contexts.len() < self.config.min_emitters_for_comparison {
+    /// Check if this value is anomalous compared to the tenant's fleet
+    fn check_cross_fleet_anomaly(
+        &self,
+        config: &FleetConfig,
+        emitter_id: EmitterId,
+        value: f64,
+    ) -> bool {
+        if self.emitter_contexts.len() < config.min_emitters_for_comparison {
             return false;
         }
 
@@ -296,7 +498,7 @@ impl FleetManager {
             .filter_map(|(_, state)| state.mean())
             .collect();
 
-        if other_means.len() < self.config.min_emitters_for_comparison - 1 {
+        if other_means.len() < config.min_emitters_for_comparison - 1 {
             return false;
         }
 
@@ -315,20 +517,45 @@ impl FleetManager {
 
         // Check if this value is outside threshold
         let z_score = (value - fleet_mean).abs() / fleet_std;
-        z_score > self.config.cross_fleet_threshold
+        z_score > config.cross_fleet_threshold
     }
 
-    /// Get list of active emitters
-    pub fn active_emitters(&self, current_time: u64) -> Vec<EmitterId> {
+    /// Get the context used to decode messages from an emitter
+    fn context_for(&self, mode: &ContextMode, emitter_id: EmitterId) -> Option<&Context> {
+        match mode.key_for(emitter_id) {
+            ContextKey::Emitter(id) => self.emitter_contexts.get(&id).map(|e| &e.context),
+            ContextKey::Group(group) => self.group_contexts.get(&group),
+        }
+    }
+
+    /// Iterate over the distinct contexts in use, each exactly once
+    fn contexts<'a>(
+        &'a self,
+        mode: &'a ContextMode,
+    ) -> impl Iterator<Item = (ContextKey, &'a Context)> {
+        let private = self
+            .emitter_contexts
+            .iter()
+            .filter(move |(id, _)| matches!(mode.key_for(**id), ContextKey::Emitter(_)))
+            .map(|(id, state)| (ContextKey::Emitter(*id), &state.context));
+        let groups = self
+            .group_contexts
+            .iter()
+            .map(|(group, context)| (ContextKey::Group(*group), context));
+        private.chain(groups)
+    }
+
+    /// Emitters not seen within `timeout` seconds
+    fn stale_emitters(&self, timeout: u64, current_time: u64) -> Vec<EmitterId> {
         self.emitter_contexts
             .iter()
-            .filter(|(_, state)| current_time - state.last_seen < self.config.emitter_timeout)
+            .filter(|(_, state)| current_time.saturating_sub(state.last_seen) >= timeout)
             .map(|(id, _)| *id)
             .collect()
     }
 
-    /// Get list of anomalous emitters
-    pub fn anomalous_emitters(&self) -> Vec<EmitterId> {
+    /// Emitters flagged anomalous
+    fn anomalous_emitters(&self) -> Vec<EmitterId> {
         self.emitter_contexts
             .iter()
             .filter(|(_, state)| state.is_anomalous)
@@ -336,86 +563,451 @@ impl FleetManager {
             .collect()
     }
 
+    /// Promote patterns common to most of the tenant's contexts
+    fn sync_fleet_patterns(&mut self, mode: &ContextMode) {
+        let context_count = self.contexts(mode).count();
+        if context_count < 2 {
+            return;
+        }
+
+        // Find patterns that appear in multiple contexts
+        let mut pattern_counts: HashMap<u64, (u32, Option<Pattern>)> = HashMap::new();
+
+        for (_key, context) in self.contexts(mode) {
+            for (_id, pattern) in context.patterns_iter() {
+                let hash = xxhash_rust::xxh64::xxh64(&pattern.data, 0);
+                let entry = pattern_counts.entry(hash).or_insert((0, None));
+                entry.0 += 1;
+                if entry.1.is_none() {
+                    entry.1 = Some(pattern.clone());
+                }
+            }
+        }
+
+        // Promote patterns found in >50% of contexts
+        let threshold = context_count / 2;
+        for (_, (count, pattern_opt)) in pattern_counts {
+            if count as usize > threshold {
+                if let Some(pattern) = pattern_opt {
+                    // Add to fleet context if not already present
+                    if self.fleet_context.find_pattern(&pattern.data).is_none() {
+                        let _ = self.fleet_context.register_pattern(pattern);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove emitters not seen for twice the emitter timeout
+    fn cleanup_stale_emitters(&mut self, config: &FleetConfig, current_time: u64) {
+        let timeout = config.emitter_timeout * 2;
+        self.emitter_contexts
+            .retain(|_, state| current_time - state.last_seen < timeout);
+        self.stats.emitter_count = self.emitter_contexts.len();
+
+        // Drop group contexts no remaining emitter uses
+        let mode = &config.context_mode;
+        let emitters = &self.emitter_contexts;
+        self.group_contexts.retain(|group, _| {
+            emitters
+                .keys()
+                .any(|id| mode.key_for(*id) == ContextKey::Group(*group))
+        });
+    }
+}
+
+/// Manages a fleet of emitters
+///
+/// Emitters belong to tenants (customers or sites) whose emitter ids may
+/// collide; [`FleetManager::process_message_for`] keeps each tenant's
+/// emitter state, contexts, rate limits and statistics apart. The methods
+/// without a tenant argument work on [`FleetConfig::default_tenant`], so
+/// single-tenant deployments never see the tenant dimension.
+#[derive(Debug)]
+pub struct FleetManager {
+    /// Per-tenant fleets; the default tenant always exists
+    tenants: HashMap<TenantId, TenantFleet>,
+    /// Classifier for fleet-wide analysis
+    #[allow(dead_code)]
+    classifier: Classifier,
+    /// Configuration
+    config: FleetConfig,
+    /// Callback subscriptions, in subscription order
+    subscribers: Vec<Subscriber>,
+    /// Identifier given to the next subscription
+    next_subscription: SubscriptionId,
+    /// Queues created by `drain_matching`, one per filter
+    queues: Vec<MessageQueue>,
+    /// Subscription handlers that panicked
+    handler_panics: u64,
+    /// Messages dropped from full queues
+    dropped_queued: u64,
+}
+
+impl FleetManager {
+    /// Create a new fleet manager
+    pub fn new() -> Self {
+        Self::with_config(FleetConfig::default())
+    }
+
+    /// Create with custom configuration
+    pub fn with_config(config: FleetConfig) -> Self {
+        let mut tenants = HashMap::new();
+        tenants.insert(config.default_tenant, TenantFleet::new(&config));
+        Self {
+            tenants,
+            classifier: Classifier::default(),
+            config,
+            subscribers: Vec::new(),
+            next_subscription: 0,
+            queues: Vec::new(),
+            handler_panics: 0,
+            dropped_queued: 0,
+        }
+    }
+
+    /// Fleet of the default tenant
+    fn default_fleet(&self) -> &TenantFleet {
+        &self.tenants[&self.config.default_tenant]
+    }
+
+    /// Mutable fleet of the default tenant
+    fn default_fleet_mut(&mut self) -> &mut TenantFleet {
+        self.tenants
+            .get_mut(&self.config.default_tenant)
+            .expect("default tenant is created with the manager")
+    }
+
+    /// Process a message from an emitter of the default tenant
+    pub fn process_message(
+        &mut self,
+        emitter_id: EmitterId,
+        message: &crate::protocol::EncodedMessage,
+        timestamp: u64,
+    ) -> Result<ProcessedMessage> {
+        self.process_message_for(self.config.default_tenant, emitter_id, message, timestamp)
+    }
+
+    /// Process a message from an emitter of the given tenant
+    ///
+    /// The message is decoded against, and updates, only that tenant's
+    /// state: emitter 7 of one tenant and emitter 7 of another are
+    /// unrelated. A processed message is then routed to the matching
+    /// subscriptions, whatever its tenant.
+    pub fn process_message_for(
+        &mut self,
+        tenant: TenantId,
+        emitter_id: EmitterId,
+        message: &crate::protocol::EncodedMessage,
+        timestamp: u64,
+    ) -> Result<ProcessedMessage> {
+        let processed = self
+            .tenants
+            .entry(tenant)
+            .or_insert_with(|| TenantFleet::new(&self.config))
+            .process_message(&self.config, emitter_id, message, timestamp)?;
+        self.dispatch(&processed);
+        Ok(processed)
+    }
+
+    /// Call `handler` with every processed message matching `filter`
+    ///
+    /// Handlers run synchronously once a message is processed, in
+    /// subscription order, before the processing call returns; failed
+    /// messages are not routed. A panicking handler is caught and counted
+    /// ([`FleetManager::handler_panics`]) and the remaining handlers still
+    /// run.
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use alec::fleet::{FleetManager, SubscriptionFilter};
+    /// use alec::{Context, Encoder, Priority, RawData};
+    ///
+    /// let mut fleet = FleetManager::new();
+    /// let alerts = Arc::new(AtomicU64::new(0));
+    /// let counter = alerts.clone();
+    /// fleet.subscribe(
+    ///     SubscriptionFilter::all().with_priorities([Priority::P1Critical]),
+    ///     Box::new(move |_| {
+    ///         counter.fetch_add(1, Ordering::Relaxed);
+    ///     }),
+    /// );
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let context = Context::new();
+    /// for priority in [Priority::P1Critical, Priority::P3Normal] {
+    ///     let message = encoder.encode_with_priority(&RawData::new(21.5, 0), priority, &context);
+    ///     fleet.process_message(1, &message, 0).unwrap();
+    /// }
+    /// assert_eq!(alerts.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn subscribe(
+        &mut self,
+        filter: SubscriptionFilter,
+        handler: MessageHandler,
+    ) -> SubscriptionId {
+        let id = self.next_subscription;
+        self.next_subscription = self.next_subscription.wrapping_add(1);
+        self.subscribers.push(Subscriber {
+            id,
+            filter,
+            handler,
+        });
+        id
+    }
+
+    /// Remove a subscription; returns whether it existed
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|subscriber| subscriber.id != id);
+        self.subscribers.len() != before
+    }
+
+    /// Take the processed messages matching `filter`, oldest first
+    ///
+    /// Pull alternative to [`FleetManager::subscribe`]: the first call
+    /// with a filter creates its queue and returns nothing; each later
+    /// call with an equal filter returns the matching messages processed
+    /// since the previous one. A queue keeps at most
+    /// [`FleetConfig::subscription_queue_capacity`] messages, dropping the
+    /// oldest ([`FleetManager::dropped_queued_messages`]).
+    pub fn drain_matching(&mut self, filter: &SubscriptionFilter) -> Vec<ProcessedMessage> {
+        match self.queues.iter_mut().find(|queue| queue.filter == *filter) {
+            Some(queue) => queue.messages.drain(..).collect(),
+            None => {
+                self.queues.push(MessageQueue {
+                    filter: filter.clone(),
+                    messages: VecDeque::new(),
+                });
+                Vec::new()
+            }
+        }
+    }
+
+    /// Subscription handlers that panicked since creation
+    pub fn handler_panics(&self) -> u64 {
+        self.handler_panics
+    }
+
+    /// Messages dropped from full [`FleetManager::drain_matching`] queues
+    /// since creation
+    pub fn dropped_queued_messages(&self) -> u64 {
+        self.dropped_queued
+    }
+
+    /// Route a processed message to the matching handlers, then queues
+    fn dispatch(&mut self, message: &ProcessedMessage) {
+        for subscriber in &self.subscribers {
+            if !subscriber.filter.matches(message) {
+                continue;
+            }
+            let handler = &subscriber.handler;
+            if catch_unwind(AssertUnwindSafe(|| handler(message))).is_err() {
+                self.handler_panics += 1;
+            }
+        }
+
+        let capacity = self.config.subscription_queue_capacity;
+        for queue in &mut self.queues {
+            if !queue.filter.matches(message) {
+                continue;
+            }
+            if queue.messages.len() >= capacity {
+                queue.messages.pop_front();
+                self.dropped_queued += 1;
+            }
+            if capacity > 0 {
+                queue.messages.push_back(message.clone());
+            }
+        }
+    }
+
+    /// Get list of active emitters
+    pub fn active_emitters(&self, current_time: u64) -> Vec<EmitterId> {
+        self.default_fleet()
+            .emitter_contexts
+            .iter()
+            .filter(|(_, state)| current_time - state.last_seen < self.config.emitter_timeout)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Get list of emitters not seen within the emitter timeout
+    pub fn stale_emitters(&self, current_time: u64) -> Vec<EmitterId> {
+        self.default_fleet()
+            .stale_emitters(self.config.emitter_timeout, current_time)
+    }
+
+    /// Get list of anomalous emitters
+    pub fn anomalous_emitters(&self) -> Vec<EmitterId> {
+        self.default_fleet().anomalous_emitters()
+    }
+
     /// Get emitter state
     pub fn get_emitter(&self, id: EmitterId) -> Option<&EmitterState> {
-        self.emitter_contexts.get(&id)
+        self.get_tenant_emitter(self.config.default_tenant, id)
+    }
+
+    /// Get the state of an emitter of the given tenant
+    pub fn get_tenant_emitter(&self, tenant: TenantId, id: EmitterId) -> Option<&EmitterState> {
+        self.tenants.get(&tenant)?.emitter_contexts.get(&id)
     }
 
     /// Get mutable emitter state
     pub fn get_emitter_mut(&mut self, id: EmitterId) -> Option<&mut EmitterState> {
-        self.emitter_contexts.get_mut(&id)
+        self.default_fleet_mut().emitter_contexts.get_mut(&id)
+    }
+
+    /// Get the context mode
+    pub fn context_mode(&self) -> &ContextMode {
+        &self.config.context_mode
     }
 
-    /// Get fleet statistics
+    /// Get the context used to decode messages from an emitter
+    ///
+    /// Returns `None` until the emitter (or, in shared modes, its group)
+    /// has been seen.
+    pub fn context_for(&self, emitter_id: EmitterId) -> Option<&Context> {
+        self.tenant_context_for(self.config.default_tenant, emitter_id)
+    }
+
+    /// Get the context used to decode messages from an emitter of the
+    /// given tenant
+    pub fn tenant_context_for(&self, tenant: TenantId, emitter_id: EmitterId) -> Option<&Context> {
+        self.tenants
+            .get(&tenant)?
+            .context_for(&self.config.context_mode, emitter_id)
+    }
+
+    /// Iterate over the distinct contexts in use, each exactly once
+    pub fn contexts(&self) -> impl Iterator<Item = (ContextKey, &Context)> {
+        self.default_fleet().contexts(&self.config.context_mode)
+    }
+
+    /// Get number of distinct contexts in use
+    pub fn context_count(&self) -> usize {
+        self.contexts().count()
+    }
+
+    /// Emitters whose messages are decoded against the given context
+    pub fn context_members(&self, key: ContextKey) -> Vec<EmitterId> {
+        self.default_fleet()
+            .emitter_contexts
+            .keys()
+            .filter(|id| self.config.context_mode.key_for(**id) == key)
+            .copied()
+            .collect()
+    }
+
+    /// Build one sync announce per distinct context
+    ///
+    /// Emitters sharing a context get a single announce for their group
+    /// instead of one each.
+    pub fn sync_announces(&self) -> Vec<(ContextKey, SyncMessage)> {
+        let mut announces: Vec<(ContextKey, SyncMessage)> = self
+            .contexts()
+            .map(|(key, context)| (key, Synchronizer::create_announce(context)))
+            .collect();
+        announces.sort_by_key(|(key, _)| *key);
+        announces
+    }
+
+    /// Estimated memory held by decoding contexts of all tenants, in bytes
+    ///
+    /// Shared contexts are counted once, however many emitters use them.
+    pub fn estimated_memory(&self) -> usize {
+        self.tenants
+            .values()
+            .flat_map(|fleet| fleet.contexts(&self.config.context_mode))
+            .map(|(_, context)| context.estimated_memory())
+            .sum()
+    }
+
+    /// Get fleet statistics of the default tenant
     pub fn stats(&self) -> &FleetStats {
-        &self.stats
+        &self.default_fleet().stats
+    }
+
+    /// Get fleet statistics of the given tenant
+    pub fn tenant_stats(&self, tenant: TenantId) -> Option<&FleetStats> {
+        self.tenants.get(&tenant).map(|fleet| &fleet.stats)
+    }
+
+    /// Fleet statistics summed over all tenants
+    pub fn aggregate_stats(&self) -> FleetStats {
+        let mut total = FleetStats::default();
+        for fleet in self.tenants.values() {
+            let stats = &fleet.stats;
+            total.emitter_count += stats.emitter_count;
+            total.total_messages += stats.total_messages;
+            total.anomaly_count += stats.anomaly_count;
+            total.cross_fleet_anomalies += stats.cross_fleet_anomalies;
+            total.sequence.merge(&stats.sequence);
+            for (priority, count) in &stats.priority_distribution {
+                *total.priority_distribution.entry(*priority).or_insert(0) += count;
+            }
+        }
+        total
+    }
+
+    /// Tenants known to the manager, in ascending order
+    ///
+    /// Always includes the default tenant.
+    pub fn tenants(&self) -> Vec<TenantId> {
+        let mut tenants: Vec<TenantId> = self.tenants.keys().copied().collect();
+        tenants.sort_unstable();
+        tenants
+    }
+
+    /// Tenant used by the methods without a tenant argument
+    pub fn default_tenant(&self) -> TenantId {
+        self.config.default_tenant
     }
 
     /// Get fleet-wide context
     pub fn fleet_context(&self) -> &Context {
-        &self.fleet_context
+        &self.default_fleet().fleet_context
     }
 
     /// Get mutable fleet context
     pub fn fleet_context_mut(&mut self) -> &mut Context {
-        &mut self.fleet_context
+        &mut self.default_fleet_mut().fleet_context
+    }
+
+    /// Get the fleet-wide context of the given tenant
+    pub fn tenant_fleet_context(&self, tenant: TenantId) -> Option<&Context> {
+        self.tenants.get(&tenant).map(|fleet| &fleet.fleet_context)
     }
 
     /// Get number of tracked emitters
     pub fn emitter_count(&self) -> usize {
-        self.emitter_contexts.len()
+        self.default_fleet().emitter_contexts.len()
     }
 
     /// Iterate over all emitters
     pub fn emitters(&self) -> impl Iterator<Item = (&EmitterId, &EmitterState)> {
-        self.emitter_contexts.iter()
+        self.default_fleet().emitter_contexts.iter()
     }
 
-    /// Promote common patterns to fleet context
+    /// Promote common patterns to each tenant's fleet context
     pub fn sync_fleet_patterns(&mut self) {
-        if self.emitter_contexts.len() < 2 {
-            return;
-        }
-
-        // Find patterns that appear in multiple emitters
-        let mut pattern_counts: HashMap<u64, (u32, Option<Pattern>)> = HashMap::new();
-
-        for state in self.emitter_contexts.values() {
-            for (_id, pattern) in state.context.patterns_iter() {
-                let hash = xxhash_rust::xxh64::xxh64(&pattern.data, 0);
-                let entry = pattern_counts.entry(hash).or_insert((0, None));
-                entry.0 += 1;
-                if entry.1.is_none() {
-                    entry.1 = Some(pattern.clone());
-                }
-            }
-        }
-
-        // Promote patterns found in >50% of emitters
-        let threshold = self.emitter_contexts.len() / 2;
-        for (_, (count, pattern_opt)) in pattern_counts {
-            if count as usize > threshold {
-                if let Some(pattern) = pattern_opt {
-                    // Add to fleet context if not already present
-                    if self.fleet_context.find_pattern(&pattern.data).is_none() {
-                        let _ = self.fleet_context.register_pattern(pattern);
-                    }
-                }
-            }
+        let mode = &self.config.context_mode;
+        for fleet in self.tenants.values_mut() {
+            fleet.sync_fleet_patterns(mode);
         }
     }
 
-    /// Remove stale emitters
+    /// Remove stale emitters of every tenant
     pub fn cleanup_stale_emitters(&mut self, current_time: u64) {
-        let timeout = self.config.emitter_timeout * 2;
-        self.emitter_contexts
-            .retain(|_, state| current_time - state.last_seen < timeout);
-        self.stats.emitter_count = self.emitter_contexts.len();
+        for fleet in self.tenants.values_mut() {
+            fleet.cleanup_stale_emitters(&self.config, current_time);
+        }
     }
 
     /// Reset an emitter's anomaly flag
     pub fn clear_anomaly(&mut self, emitter_id: EmitterId) {
-        if let Some(emitter) = self.emitter_contexts.get_mut(&emitter_id) {
+        if let Some(emitter) = self.get_emitter_mut(emitter_id) {
             emitter.is_anomalous = false;
         }
     }
@@ -423,6 +1015,7 @@ impl FleetManager {
     /// Get fleet-wide mean across all emitters
     pub fn fleet_mean(&self) -> Option<f64> {
         let means: Vec<f64> = self
+            .default_fleet()
             .emitter_contexts
             .values()
             .filter_map(|s| s.mean())
@@ -439,6 +1032,7 @@ impl FleetManager {
     pub fn fleet_std_dev(&self) -> Option<f64> {
         let fleet_mean = self.fleet_mean()?;
         let means: Vec<f64> = self
+            .default_fleet()
             .emitter_contexts
             .values()
             .filter_map(|s| s.mean())
@@ -465,16 +1059,47 @@ impl FleetManager {
         message: &crate::protocol::EncodedMessage,
         timestamp: u64,
         security: &mut SecurityContext,
+    ) -> Result<ProcessedMessage> {
+        self.process_message_secure_for(
+            self.config.default_tenant,
+            emitter_id,
+            message,
+            timestamp,
+            security,
+        )
+    }
+
+    /// Process a message from a tenant's emitter with security checks
+    ///
+    /// Like [`FleetManager::process_message_secure`], with rate limits
+    /// tracked per tenant (the default tenant uses
+    /// [`SecurityContext::check_rate_limit`]) and the tenant recorded in
+    /// every audit event.
+    pub fn process_message_secure_for(
+        &mut self,
+        tenant: TenantId,
+        emitter_id: EmitterId,
+        message: &crate::protocol::EncodedMessage,
+        timestamp: u64,
+        security: &mut SecurityContext,
     ) -> Result<ProcessedMessage> {
         // Rate limiting check
-        if !security.check_rate_limit(emitter_id, timestamp) {
+        let allowed = if tenant == self.config.default_tenant {
+            security.check_rate_limit(emitter_id, timestamp)
+        } else {
+            security.check_tenant_rate_limit(tenant, emitter_id, timestamp)
+        };
+        if !allowed {
             security.audit(
                 AuditEvent::new(
                     AuditEventType::RateLimitExceeded,
                     format!("Emitter {} exceeded rate limit", emitter_id),
                 )
                 .with_emitter(emitter_id)
-                .with_severity(Severity::Medium),
+                .with_tenant(tenant)
+                .with_severity(Severity::Medium)
+                .with_field("retry_after_ms", 1000)
+                .with_field("sequence", message.header.sequence),
             );
             return Err(crate::error::AlecError::Channel(
                 ChannelError::RateLimited {
@@ -489,11 +1114,15 @@ impl FleetManager {
                 AuditEventType::MessageReceived,
                 format!("Message from emitter {}", emitter_id),
             )
-            .with_emitter(emitter_id),
+            .with_emitter(emitter_id)
+            .with_tenant(tenant)
+            .with_field("bytes", message.len())
+            .with_field("sequence", message.header.sequence)
+            .with_field("context_version", message.header.context_version),
         );
 
         // Process the message normally
-        let result = self.process_message(emitter_id, message, timestamp)?;
+        let result = self.process_message_for(tenant, emitter_id, message, timestamp)?;
 
         // Audit anomalies
         if result.is_cross_fleet_anomaly {
@@ -506,7 +1135,9 @@ impl FleetManager {
                     ),
                 )
                 .with_emitter(emitter_id)
-                .with_severity(Severity::High),
+                .with_tenant(tenant)
+                .with_severity(Severity::High)
+                .with_field("value", result.value),
             );
         }
 
@@ -520,9 +1151,71 @@ impl Default for FleetManager {
     }
 }
 
+impl HealthCheckable for FleetManager {
+    fn health_check(&self) -> HealthCheck {
+        self.health_check_with(&HealthConfig::default())
+    }
+
+    /// Covers the emitters of every tenant. Stale emitters are judged
+    /// against the most recent `last_seen` in their tenant; quarantined
+    /// emitters are those flagged anomalous and not yet cleared with
+    /// [`FleetManager::clear_anomaly`].
+    fn health_check_with(&self, config: &HealthConfig) -> HealthCheck {
+        let mut total = 0;
+        let mut stale = 0;
+        let mut quarantined = 0;
+        for fleet in self.tenants.values() {
+            let latest = fleet
+                .emitter_contexts
+                .values()
+                .map(|state| state.last_seen)
+                .max()
+                .unwrap_or(0);
+            total += fleet.emitter_contexts.len();
+            stale += fleet
+                .stale_emitters(self.config.emitter_timeout, latest)
+                .len();
+            quarantined += fleet.anomalous_emitters().len();
+        }
+        let details = format!(
+            "Emitters: {}, Stale: {}, Quarantined: {}",
+            total, stale, quarantined
+        );
+        if total == 0 {
+            return HealthCheck::healthy("FleetManager").with_message(details);
+        }
+
+        let fraction = |count: usize| count as f64 / total as f64;
+        let (metric, count) = if fraction(stale) >= fraction(quarantined) {
+            ("Stale emitters", stale)
+        } else {
+            ("Quarantined emitters", quarantined)
+        };
+        let reason = |threshold: f64| {
+            format!(
+                "{} {:.0}% above {:.0}% ({})",
+                metric,
+                fraction(count) * 100.0,
+                threshold * 100.0,
+                details
+            )
+        };
+
+        if fraction(count) > config.unhealthy_emitter_fraction {
+            HealthCheck::unhealthy("FleetManager", reason(config.unhealthy_emitter_fraction))
+        } else if fraction(count) > config.degraded_emitter_fraction {
+            HealthCheck::degraded("FleetManager", reason(config.degraded_emitter_fraction))
+        } else {
+            HealthCheck::healthy("FleetManager").with_message(details)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encoder::Encoder;
+    use crate::health::HealthStatus;
 
     #[test]
     fn test_fleet_manager_creation() {
@@ -577,15 +1270,19 @@ mod tests {
             for j in 0..10 {
                 state.record_value(20.0 + (j as f64 * 0.1), j);
             }
-            fleet.emitter_contexts.insert(i, state);
+            fleet.default_fleet_mut().emitter_contexts.insert(i, state);
         }
 
         // Check that a very different value is detected as anomaly
-        let is_anomaly = fleet.check_cross_fleet_anomaly(99, 100.0);
+        let is_anomaly = fleet
+            .default_fleet()
+            .check_cross_fleet_anomaly(&fleet.config, 99, 100.0);
         assert!(is_anomaly);
 
         // Check that a similar value is not anomaly
-        let is_normal = fleet.check_cross_fleet_anomaly(99, 20.5);
+        let is_normal = fleet
+            .default_fleet()
+            .check_cross_fleet_anomaly(&fleet.config, 99, 20.5);
         assert!(!is_normal);
     }
 
@@ -599,15 +1296,15 @@ mod tests {
         // Add emitters with different timestamps
         let mut state1 = EmitterState::new();
         state1.last_seen = 100;
-        fleet.emitter_contexts.insert(1, state1);
+        fleet.default_fleet_mut().emitter_contexts.insert(1, state1);
 
         let mut state2 = EmitterState::new();
         state2.last_seen = 150;
-        fleet.emitter_contexts.insert(2, state2);
+        fleet.default_fleet_mut().emitter_contexts.insert(2, state2);
 
         let mut state3 = EmitterState::new();
         state3.last_seen = 10; // Old
-        fleet.emitter_contexts.insert(3, state3);
+        fleet.default_fleet_mut().emitter_contexts.insert(3, state3);
 
         // At time 160, with timeout 100:
         // state1: 160-100=60 < 100, active
@@ -637,11 +1334,11 @@ mod tests {
 
         let mut state1 = EmitterState::new();
         state1.last_seen = 150; // Recent
-        fleet.emitter_contexts.insert(1, state1);
+        fleet.default_fleet_mut().emitter_contexts.insert(1, state1);
 
         let mut state2 = EmitterState::new();
         state2.last_seen = 10; // Old
-        fleet.emitter_contexts.insert(2, state2);
+        fleet.default_fleet_mut().emitter_contexts.insert(2, state2);
 
         // Cleanup at time 250 (timeout*2 = 200)
         // state1: 250-150=100 < 200, kept
@@ -653,6 +1350,55 @@ mod tests {
         assert!(fleet.get_emitter(2).is_none());
     }
 
+    #[test]
+    fn test_health_check_stale_and_quarantined() {
+        let mut fleet = FleetManager::with_config(FleetConfig {
+            emitter_timeout: 100,
+            ..Default::default()
+        });
+        assert_eq!(fleet.health_check().status, HealthStatus::Healthy);
+
+        for id in 0..10 {
+            let mut state = EmitterState::new();
+            state.last_seen = 1_000;
+            fleet.default_fleet_mut().emitter_contexts.insert(id, state);
+        }
+        let check = fleet.health_check();
+        assert_eq!(check.component, "FleetManager");
+        assert_eq!(check.status, HealthStatus::Healthy);
+        assert!(check.message.contains("Emitters: 10"));
+
+        // 2 of 10 emitters silent for the timeout
+        for id in 0..2 {
+            fleet.get_emitter_mut(id).unwrap().last_seen = 900;
+        }
+        assert_eq!(fleet.stale_emitters(1_000).len(), 2);
+        let check = fleet.health_check();
+        assert_eq!(check.status, HealthStatus::Degraded);
+        assert!(
+            check.message.contains("Stale emitters 20%"),
+            "{}",
+            check.message
+        );
+
+        // 6 of 10 quarantined
+        for id in 2..8 {
+            fleet.get_emitter_mut(id).unwrap().is_anomalous = true;
+        }
+        let check = fleet.health_check();
+        assert_eq!(check.status, HealthStatus::Unhealthy);
+        assert!(
+            check.message.contains("Quarantined emitters 60%"),
+            "{}",
+            check.message
+        );
+
+        for id in 2..8 {
+            fleet.clear_anomaly(id);
+        }
+        assert_eq!(fleet.health_check().status, HealthStatus::Degraded);
+    }
+
     #[test]
     fn test_fleet_mean() {
         let mut fleet = FleetManager::new();
@@ -661,17 +1407,17 @@ mod tests {
         let mut state1 = EmitterState::new();
         state1.record_value(10.0, 0);
         state1.record_value(10.0, 1);
-        fleet.emitter_contexts.insert(1, state1);
+        fleet.default_fleet_mut().emitter_contexts.insert(1, state1);
 
         let mut state2 = EmitterState::new();
         state2.record_value(20.0, 0);
         state2.record_value(20.0, 1);
-        fleet.emitter_contexts.insert(2, state2);
+        fleet.default_fleet_mut().emitter_contexts.insert(2, state2);
 
         let mut state3 = EmitterState::new();
         state3.record_value(30.0, 0);
         state3.record_value(30.0, 1);
-        fleet.emitter_contexts.insert(3, state3);
+        fleet.default_fleet_mut().emitter_contexts.insert(3, state3);
 
         // Fleet mean should be (10+20+30)/3 = 20
         assert_eq!(fleet.fleet_mean(), Some(20.0));
@@ -683,17 +1429,119 @@ mod tests {
 
         let mut state1 = EmitterState::new();
         state1.is_anomalous = true;
-        fleet.emitter_contexts.insert(1, state1);
+        fleet.default_fleet_mut().emitter_contexts.insert(1, state1);
 
         let mut state2 = EmitterState::new();
         state2.is_anomalous = false;
-        fleet.emitter_contexts.insert(2, state2);
+        fleet.default_fleet_mut().emitter_contexts.insert(2, state2);
 
         let anomalous = fleet.anomalous_emitters();
         assert_eq!(anomalous.len(), 1);
         assert!(anomalous.contains(&1));
     }
 
+    /// Encode `rounds` values from each of `emitters` emitters sharing one
+    /// emitter-side context and feed them to the fleet
+    fn feed_shared_fleet(fleet: &mut FleetManager, emitters: u32, rounds: u64) {
+        let classifier = Classifier::default();
+        let mut tx_context = Context::new();
+        let mut encoders: Vec<Encoder> = (0..emitters).map(|_| Encoder::new()).collect();
+
+        for round in 0..rounds {
+            for (id, encoder) in encoders.iter_mut().enumerate() {
+                let value = 20.0 + (id % 5) as f64 * 0.1 + round as f64 * 0.01;
+                let data = RawData::new(value, round);
+                let classification = classifier.classify(&data, &tx_context);
+                let message = encoder.encode(&data, &classification, &tx_context);
+                tx_context.observe_classified(&data, &classification);
+
+                let result = fleet.process_message(id as u32, &message, round).unwrap();
+                assert!((result.value - value).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_context_mode_keys() {
+        assert_eq!(ContextMode::PerEmitter.key_for(7), ContextKey::Emitter(7));
+        assert_eq!(
+            ContextMode::Shared.key_for(7),
+            ContextKey::Group(SHARED_GROUP)
+        );
+        assert_eq!(
+            ContextMode::SharedByGroup(|id| id / 10).key_for(42),
+            ContextKey::Group(4)
+        );
+
+        let map = ContextMode::SharedByGroupMap(HashMap::from([(1, 9)]));
+        assert_eq!(map.key_for(1), ContextKey::Group(9));
+        assert_eq!(map.key_for(2), ContextKey::Emitter(2));
+    }
+
+    #[test]
+    fn test_shared_context_memory_and_decoding() {
+        let mut shared = FleetManager::with_config(FleetConfig {
+            context_mode: ContextMode::Shared,
+            ..Default::default()
+        });
+        feed_shared_fleet(&mut shared, 100, 5);
+
+        assert_eq!(shared.emitter_count(), 100);
+        assert_eq!(shared.context_count(), 1);
+        let context = shared.context_for(42).unwrap();
+        assert!(std::ptr::eq(context, shared.context_for(7).unwrap()));
+        assert_eq!(context.observation_count(), 500);
+        assert_eq!(shared.estimated_memory(), context.estimated_memory());
+
+        let mut isolated = FleetManager::new();
+        for id in 0..100 {
+            let data = RawData::new(20.0, 0);
+            let message = Encoder::new().encode(
+                &data,
+                &Classifier::default().classify(&data, &Context::new()),
+                &Context::new(),
+            );
+            isolated.process_message(id, &message, 0).unwrap();
+        }
+        assert_eq!(isolated.context_count(), 100);
+        assert!(isolated.estimated_memory() > 50 * shared.estimated_memory());
+
+        // One announce for the whole fleet
+        let announces = shared.sync_announces();
+        assert_eq!(announces.len(), 1);
+        assert_eq!(announces[0].0, ContextKey::Group(SHARED_GROUP));
+        assert_eq!(shared.context_members(announces[0].0).len(), 100);
+    }
+
+    #[test]
+    fn test_grouped_contexts() {
+        let mut fleet = FleetManager::with_config(FleetConfig {
+            context_mode: ContextMode::SharedByGroup(|id| id % 2),
+            emitter_timeout: 100,
+            ..Default::default()
+        });
+
+        let data = RawData::new(20.0, 0);
+        let message = Encoder::new().encode(
+            &data,
+            &Classifier::default().classify(&data, &Context::new()),
+            &Context::new(),
+        );
+        for id in 0..10 {
+            fleet.process_message(id, &message, 0).unwrap();
+        }
+
+        assert_eq!(fleet.context_count(), 2);
+        assert_eq!(fleet.context_members(ContextKey::Group(1)).len(), 5);
+        assert_eq!(fleet.context_for(3).unwrap().observation_count(), 5);
+        assert_eq!(fleet.sync_announces().len(), 2);
+
+        // Group contexts are dropped once all their members go stale
+        fleet.cleanup_stale_emitters(1_000);
+        assert_eq!(fleet.emitter_count(), 0);
+        assert_eq!(fleet.context_count(), 0);
+    }
+
     #[test]
     fn test_recent_values_capacity() {
         let mut state = EmitterState::with_capacity(5);
@@ -707,4 +1555,160 @@ mod tests {
         assert_eq!(state.recent_values.len(), 5);
         assert_eq!(state.recent_values, vec![5.0, 6.0, 7.0, 8.0, 9.0]);
     }
+
+    /// Emitter side of emitter 7 of one tenant
+    struct TenantEmitter {
+        tenant: TenantId,
+        encoder: Encoder,
+        context: Context,
+        sent: u64,
+    }
+
+    impl TenantEmitter {
+        fn new(tenant: TenantId) -> Self {
+            Self {
+                tenant,
+                encoder: Encoder::new(),
+                context: Context::new(),
+                sent: 0,
+            }
+        }
+
+        /// Encode `value`, process it in `fleet` and return the decoded value
+        fn send(&mut self, fleet: &mut FleetManager, value: f64) -> f64 {
+            let data = RawData::new(value, self.sent * 1_000);
+            let classification = Classifier::default().classify(&data, &self.context);
+            let message = self.encoder.encode(&data, &classification, &self.context);
+            self.context.observe_classified(&data, &classification);
+            self.sent += 1;
+            fleet
+                .process_message_for(self.tenant, 7, &message, data.timestamp)
+                .unwrap()
+                .value
+        }
+    }
+
+    #[test]
+    fn test_tenants_with_colliding_emitter_ids_stay_isolated() {
+        let mut fleet = FleetManager::new();
+        let mut cold = TenantEmitter::new(1);
+        let mut hot = TenantEmitter::new(2);
+
+        // Interleave the two tenants' emitter 7 message by message; each
+        // decodes against its own tenant's context
+        for i in 0..40 {
+            let value = 4.0 + (i % 4) as f64 * 0.5;
+            assert!((cold.send(&mut fleet, value) - value).abs() < 0.05);
+            if i < 25 {
+                let value = 80.0 + i as f64 * 0.25;
+                assert!((hot.send(&mut fleet, value) - value).abs() < 0.05);
+            }
+        }
+
+        let cold_ctx = fleet.tenant_context_for(1, 7).unwrap();
+        let hot_ctx = fleet.tenant_context_for(2, 7).unwrap();
+        assert!(!std::ptr::eq(cold_ctx, hot_ctx));
+        assert_eq!(cold_ctx.observation_count(), 40);
+        assert_eq!(hot_ctx.observation_count(), 25);
+        assert!(cold_ctx.predict(0).unwrap().value < 10.0);
+        assert!(hot_ctx.predict(0).unwrap().value > 80.0);
+        assert_eq!(fleet.get_tenant_emitter(1, 7).unwrap().message_count, 40);
+        let last = fleet
+            .get_tenant_emitter(2, 7)
+            .unwrap()
+            .last_value()
+            .unwrap();
+        assert!((last - 86.0).abs() < 0.05);
+
+        // Per-tenant and aggregated stats; the default tenant saw nothing
+        assert_eq!(fleet.tenant_stats(1).unwrap().total_messages, 40);
+        assert_eq!(fleet.tenant_stats(2).unwrap().total_messages, 25);
+        assert_eq!(fleet.stats().total_messages, 0);
+        assert!(fleet.get_emitter(7).is_none());
+        assert!(fleet.tenant_stats(3).is_none());
+        let total = fleet.aggregate_stats();
+        assert_eq!(total.total_messages, 65);
+        assert_eq!(total.emitter_count, 2);
+        assert_eq!(
+            total.priority_distribution.values().sum::<u64>(),
+            total.total_messages
+        );
+        assert_eq!(fleet.tenants(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_default_tenant_is_configurable() {
+        let mut fleet = FleetManager::with_config(FleetConfig {
+            default_tenant: 5,
+            ..Default::default()
+        });
+        let mut emitter = TenantEmitter::new(5);
+        for value in [20.0, 20.5, 21.0] {
+            emitter.send(&mut fleet, value);
+        }
+
+        assert_eq!(fleet.default_tenant(), 5);
+        assert_eq!(fleet.tenants(), vec![5]);
+        assert_eq!(fleet.stats().total_messages, 3);
+        assert_eq!(fleet.get_emitter(7).unwrap().message_count, 3);
+        assert!(std::ptr::eq(
+            fleet.context_for(7).unwrap(),
+            fleet.tenant_context_for(5, 7).unwrap()
+        ));
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedAudit(std::sync::Arc<std::sync::Mutex<Vec<AuditEvent>>>);
+
+    impl crate::security::AuditLogger for SharedAudit {
+        fn log(&self, event: AuditEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_secure_processing_is_per_tenant() {
+        use crate::security::SecurityConfig;
+
+        let audit = SharedAudit::default();
+        let mut security = SecurityContext::new(SecurityConfig {
+            audit_enabled: true,
+            ..SecurityConfig::with_rate_limit(1, 2)
+        })
+        .with_audit_logger(Box::new(audit.clone()));
+        let mut fleet = FleetManager::new();
+
+        let data = RawData::new(20.0, 0);
+        let message = Encoder::new().encode(
+            &data,
+            &Classifier::default().classify(&data, &Context::new()),
+            &Context::new(),
+        );
+
+        // Emitter 7 of tenant 1 uses up its burst...
+        for _ in 0..2 {
+            fleet
+                .process_message_secure_for(1, 7, &message, 0, &mut security)
+                .unwrap();
+        }
+        assert!(fleet
+            .process_message_secure_for(1, 7, &message, 0, &mut security)
+            .is_err());
+        // ...without throttling emitter 7 of tenant 2 or of the default tenant
+        fleet
+            .process_message_secure_for(2, 7, &message, 0, &mut security)
+            .unwrap();
+        fleet
+            .process_message_secure(7, &message, 0, &mut security)
+            .unwrap();
+
+        let events = audit.0.lock().unwrap();
+        assert!(events.iter().all(|e| e.emitter_id == Some(7)));
+        let tenants: Vec<Option<u32>> = events.iter().map(|e| e.tenant_id).collect();
+        assert_eq!(tenants, vec![Some(1), Some(1), Some(1), Some(2), Some(0)]);
+        assert_eq!(events[2].event_type, AuditEventType::RateLimitExceeded);
+        assert!(events[3].to_log_line().contains("tenant=2 emitter=7"));
+    }
 }
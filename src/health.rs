@@ -115,6 +115,24 @@ pub struct HealthConfig {
     pub unhealthy_memory_bytes: usize,
     /// Check interval
     pub check_interval: Duration,
+    /// Min recent messages before encoder and decoder rates are judged
+    pub min_window_messages: usize,
+    /// Max share of raw-fallback encodings before degraded (0.0-1.0)
+    pub degraded_fallback_rate: f64,
+    /// Max share of failed decodes before unhealthy (0.0-1.0)
+    pub unhealthy_decode_error_rate: f64,
+    /// Max recent checksum failures before unhealthy
+    pub unhealthy_checksum_failures: usize,
+    /// Channel buffer fill at which a gateway is degraded (0.0-1.0)
+    pub degraded_buffer_fill: f64,
+    /// Channel buffer fill at which a gateway is unhealthy (0.0-1.0)
+    pub unhealthy_buffer_fill: f64,
+    /// Max lag behind the newest channel before a channel is stale (ms)
+    pub stale_channel_ms: u64,
+    /// Max share of stale or quarantined emitters before degraded (0.0-1.0)
+    pub degraded_emitter_fraction: f64,
+    /// Max share of stale or quarantined emitters before unhealthy (0.0-1.0)
+    pub unhealthy_emitter_fraction: f64,
 }
 
 impl Default for HealthConfig {
@@ -127,6 +145,15 @@ impl Default for HealthConfig {
             degraded_memory_bytes: 10_000_000,
             unhealthy_memory_bytes: 100_000_000,
             check_interval: Duration::from_secs(10),
+            min_window_messages: 16,
+            degraded_fallback_rate: 0.5,
+            unhealthy_decode_error_rate: 0.1,
+            unhealthy_checksum_failures: 3,
+            degraded_buffer_fill: 0.8,
+            unhealthy_buffer_fill: 1.0,
+            stale_channel_ms: 60_000,
+            degraded_emitter_fraction: 0.1,
+            unhealthy_emitter_fraction: 0.5,
         }
     }
 }
@@ -160,6 +187,15 @@ impl HealthMonitor {
         }
     }
 
+    /// Run a component's health check against this monitor's thresholds
+    /// and record the result
+    pub fn check(&mut self, component: &dyn HealthCheckable) -> HealthStatus {
+        let check = component.health_check_with(&self.config);
+        let status = check.status;
+        self.add_check(check);
+        status
+    }
+
     /// Add a health check result
     pub fn add_check(&mut self, check: HealthCheck) {
         // Remove old check for same component
@@ -271,6 +307,14 @@ impl HealthMonitor {
 pub trait HealthCheckable {
     /// Perform health check
     fn health_check(&self) -> HealthCheck;
+
+    /// Perform health check against the given thresholds
+    ///
+    /// Components without configurable thresholds ignore `config`.
+    fn health_check_with(&self, config: &HealthConfig) -> HealthCheck {
+        let _ = config;
+        self.health_check()
+    }
 }
 
 #[cfg(test)]
@@ -437,5 +481,35 @@ mod tests {
         assert_eq!(config.degraded_latency_ms, 100);
         assert_eq!(config.unhealthy_latency_ms, 1000);
         assert_eq!(config.check_interval, Duration::from_secs(10));
+        assert!(config.degraded_buffer_fill < config.unhealthy_buffer_fill);
+        assert!(config.degraded_emitter_fraction < config.unhealthy_emitter_fraction);
+    }
+
+    #[test]
+    fn test_health_monitor_check_uses_config() {
+        struct Fixed;
+        impl HealthCheckable for Fixed {
+            fn health_check(&self) -> HealthCheck {
+                HealthCheck::healthy("fixed")
+            }
+
+            fn health_check_with(&self, config: &HealthConfig) -> HealthCheck {
+                if config.min_window_messages == 0 {
+                    HealthCheck::degraded("fixed", "min_window_messages is 0")
+                } else {
+                    self.health_check()
+                }
+            }
+        }
+
+        let mut monitor = HealthMonitor::new();
+        assert_eq!(monitor.check(&Fixed), HealthStatus::Healthy);
+
+        let mut monitor = HealthMonitor::with_config(HealthConfig {
+            min_window_messages: 0,
+            ..Default::default()
+        });
+        assert_eq!(monitor.check(&Fixed), HealthStatus::Degraded);
+        assert_eq!(monitor.status(), HealthStatus::Degraded);
     }
 }
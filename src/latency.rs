@@ -0,0 +1,413 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! End-to-end latency per priority, split by stage
+//!
+//! A message goes through four stages between measurement and decoding:
+//!
+//! - [`LatencyStage::Encode`]: measurement to encoding, recorded by an
+//!   [`Encoder`](crate::Encoder) with [`Encoder::record_encoded`]
+//! - [`LatencyStage::Queue`]: time spent in a
+//!   [`PriorityQueue`](crate::channel::PriorityQueue), from
+//!   `enqueue_at` to `dequeue_highest_at`
+//! - [`LatencyStage::Transit`]: leaving the sender to being decoded
+//! - [`LatencyStage::EndToEnd`]: measurement to decoding, recorded by a
+//!   [`Decoder`](crate::Decoder) in `decode_with_now`
+//!
+//! Tracking is opt-in on each side (`enable_latency_tracking`) and costs
+//! nothing while off. No clock is read: every call takes the caller's
+//! `now_ms`, so any monotonic or wall clock works as long as both sides
+//! agree on it. Each side keeps a [`LatencyLog`] of the stage times of
+//! its recent messages, keyed by sequence number, and a [`LatencyReport`]
+//! of the stages it saw itself. Merging the logs of the sender, the queue
+//! and the receiver correlates them by sequence and attributes each
+//! message's delay to a stage ([`LatencyLog::merged_report`]).
+//!
+//! [`Encoder::record_encoded`]: crate::Encoder::record_encoded
+//!
+//! ```
+//! use alec::latency::{LatencyLog, LatencyStage};
+//! use alec::Priority;
+//!
+//! let mut sender = LatencyLog::new(64);
+//! let mut receiver = LatencyLog::new(64);
+//! sender.record_encoded(7, Priority::P1Critical, 1_000, 1_200);
+//! receiver.record_decoded(7, Priority::P1Critical, 1_000, 3_500);
+//!
+//! let mut merged = sender.clone();
+//! merged.merge(&receiver);
+//! let breakdown = merged.breakdown(7).unwrap();
+//! assert_eq!(breakdown.transit_ms, Some(2_300));
+//! assert_eq!(breakdown.slowest_stage(), Some(LatencyStage::Transit));
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::protocol::Priority;
+
+/// Upper bounds, in ms, of the [`LatencyHistogram`] buckets; a last
+/// bucket counts slower messages
+pub const LATENCY_BUCKETS_MS: [u64; 4] = [1_000, 5_000, 30_000, 300_000];
+
+/// Latencies of one stage and priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyHistogram {
+    /// Messages per bucket: under 1 s, 5 s, 30 s, 5 min, then 5 min or more
+    pub counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl LatencyHistogram {
+    /// Count a message of `latency_ms`
+    pub fn record(&mut self, latency_ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS.partition_point(|&bound| bound <= latency_ms);
+        self.counts[bucket] += 1;
+    }
+
+    /// Messages counted
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Messages of `bound_ms` or more, `bound_ms` being a bucket bound
+    /// (e.g. 30 000 for the messages over a 30 s budget)
+    pub fn at_or_above(&self, bound_ms: u64) -> u64 {
+        let bucket = LATENCY_BUCKETS_MS.partition_point(|&bound| bound < bound_ms);
+        self.counts[bucket..].iter().sum()
+    }
+
+    /// Add the counts of `other`
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+}
+
+/// Stage of a message's way from measurement to decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LatencyStage {
+    /// Measurement to encoding
+    Encode = 0,
+    /// Enqueued to dequeued in the transmit queue
+    Queue = 1,
+    /// Leaving the sender (dequeued, or encoded without a queue) to decoded
+    Transit = 2,
+    /// Measurement to decoding
+    EndToEnd = 3,
+}
+
+impl LatencyStage {
+    /// Every stage, in message order
+    pub const ALL: [LatencyStage; 4] = [
+        LatencyStage::Encode,
+        LatencyStage::Queue,
+        LatencyStage::Transit,
+        LatencyStage::EndToEnd,
+    ];
+}
+
+/// Latency histograms per priority and stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyReport {
+    /// Indexed by `Priority as usize`, then `LatencyStage as usize`
+    histograms: [[LatencyHistogram; 4]; 5],
+}
+
+impl LatencyReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a message of `priority` that spent `latency_ms` in `stage`
+    pub fn record(&mut self, priority: Priority, stage: LatencyStage, latency_ms: u64) {
+        self.histograms[priority as usize][stage as usize].record(latency_ms);
+    }
+
+    /// Histogram of one priority and stage
+    pub fn histogram(&self, priority: Priority, stage: LatencyStage) -> &LatencyHistogram {
+        &self.histograms[priority as usize][stage as usize]
+    }
+
+    /// Add the counts of `other`
+    pub fn merge(&mut self, other: &LatencyReport) {
+        for (mine, theirs) in self.histograms.iter_mut().zip(&other.histograms) {
+            for (mine, theirs) in mine.iter_mut().zip(theirs) {
+                mine.merge(theirs);
+            }
+        }
+    }
+}
+
+/// Times, in the caller's ms, at which one message passed each stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageTimes {
+    /// Priority of the message
+    pub priority: Priority,
+    /// When the value was measured
+    pub measured_ms: Option<u64>,
+    /// When the message was encoded
+    pub encoded_ms: Option<u64>,
+    /// When the message entered the transmit queue
+    pub enqueued_ms: Option<u64>,
+    /// When the message left the transmit queue
+    pub dequeued_ms: Option<u64>,
+    /// When the message was decoded
+    pub decoded_ms: Option<u64>,
+}
+
+impl StageTimes {
+    fn new(priority: Priority) -> Self {
+        Self {
+            priority,
+            measured_ms: None,
+            encoded_ms: None,
+            enqueued_ms: None,
+            dequeued_ms: None,
+            decoded_ms: None,
+        }
+    }
+
+    /// Latency of each stage whose ends are both known
+    pub fn breakdown(&self) -> LatencyBreakdown {
+        let span = |from: Option<u64>, to: Option<u64>| Some(to?.saturating_sub(from?));
+        LatencyBreakdown {
+            priority: self.priority,
+            encode_ms: span(self.measured_ms, self.encoded_ms),
+            queue_ms: span(self.enqueued_ms, self.dequeued_ms),
+            transit_ms: span(self.dequeued_ms.or(self.encoded_ms), self.decoded_ms),
+            end_to_end_ms: span(self.measured_ms, self.decoded_ms),
+        }
+    }
+
+    /// Keep the times already set, fill the others from `other`
+    fn fill_from(&mut self, other: &StageTimes) {
+        self.measured_ms = self.measured_ms.or(other.measured_ms);
+        self.encoded_ms = self.encoded_ms.or(other.encoded_ms);
+        self.enqueued_ms = self.enqueued_ms.or(other.enqueued_ms);
+        self.dequeued_ms = self.dequeued_ms.or(other.dequeued_ms);
+        self.decoded_ms = self.decoded_ms.or(other.decoded_ms);
+    }
+}
+
+/// Per-stage latency of one message, see [`StageTimes::breakdown`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyBreakdown {
+    /// Priority of the message
+    pub priority: Priority,
+    /// Measurement to encoding
+    pub encode_ms: Option<u64>,
+    /// Time in the transmit queue
+    pub queue_ms: Option<u64>,
+    /// Leaving the sender to decoding
+    pub transit_ms: Option<u64>,
+    /// Measurement to decoding
+    pub end_to_end_ms: Option<u64>,
+}
+
+impl LatencyBreakdown {
+    /// Latency of one stage, if known
+    pub fn stage(&self, stage: LatencyStage) -> Option<u64> {
+        match stage {
+            LatencyStage::Encode => self.encode_ms,
+            LatencyStage::Queue => self.queue_ms,
+            LatencyStage::Transit => self.transit_ms,
+            LatencyStage::EndToEnd => self.end_to_end_ms,
+        }
+    }
+
+    /// Stage the message spent the most time in (end-to-end excluded);
+    /// the earliest on a tie
+    pub fn slowest_stage(&self) -> Option<LatencyStage> {
+        let stages = [
+            LatencyStage::Encode,
+            LatencyStage::Queue,
+            LatencyStage::Transit,
+        ];
+        stages
+            .into_iter()
+            .filter_map(|stage| Some((stage, self.stage(stage)?)))
+            .fold(
+                None,
+                |slowest: Option<(LatencyStage, u64)>, (stage, ms)| match slowest {
+                    Some((_, longest)) if longest >= ms => slowest,
+                    _ => Some((stage, ms)),
+                },
+            )
+            .map(|(stage, _)| stage)
+    }
+}
+
+/// Stage times of the most recent messages, keyed by sequence number
+///
+/// Holds up to `capacity` messages and drops the oldest beyond. Sequence
+/// numbers wrap every 65 536 messages, so correlate logs well within a
+/// wrap. The [`report`](Self::report) counts the stages completed on this
+/// side as they are recorded.
+#[derive(Debug, Clone)]
+pub struct LatencyLog {
+    capacity: usize,
+    entries: BTreeMap<u16, StageTimes>,
+    /// Sequence numbers, oldest first
+    order: VecDeque<u16>,
+    report: LatencyReport,
+}
+
+impl LatencyLog {
+    /// Create a log keeping up to `capacity` messages
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+            order: VecDeque::with_capacity(capacity.min(1024)),
+            report: LatencyReport::new(),
+        }
+    }
+
+    /// Stages completed on this side
+    pub fn report(&self) -> &LatencyReport {
+        &self.report
+    }
+
+    /// Stage times of message `sequence`, if kept
+    pub fn get(&self, sequence: u16) -> Option<&StageTimes> {
+        self.entries.get(&sequence)
+    }
+
+    /// Per-stage latency of message `sequence`, if kept
+    pub fn breakdown(&self, sequence: u16) -> Option<LatencyBreakdown> {
+        self.get(sequence).map(StageTimes::breakdown)
+    }
+
+    /// Messages kept, by sequence number
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &StageTimes)> {
+        self.entries
+            .iter()
+            .map(|(&sequence, times)| (sequence, times))
+    }
+
+    /// Number of messages kept
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no message is kept
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record that message `sequence`, measured at `measured_ms`, was
+    /// encoded at `now_ms`
+    pub fn record_encoded(
+        &mut self,
+        sequence: u16,
+        priority: Priority,
+        measured_ms: u64,
+        now_ms: u64,
+    ) {
+        let times = self.entry(sequence, priority);
+        times.measured_ms = Some(measured_ms);
+        times.encoded_ms = Some(now_ms);
+        self.report.record(
+            priority,
+            LatencyStage::Encode,
+            now_ms.saturating_sub(measured_ms),
+        );
+    }
+
+    /// Record that message `sequence` entered the transmit queue at
+    /// `now_ms`
+    pub fn record_enqueued(&mut self, sequence: u16, priority: Priority, now_ms: u64) {
+        self.entry(sequence, priority).enqueued_ms = Some(now_ms);
+    }
+
+    /// Record that message `sequence` left the transmit queue at `now_ms`
+    ///
+    /// Counts its queue time if it was recorded entering.
+    pub fn record_dequeued(&mut self, sequence: u16, priority: Priority, now_ms: u64) {
+        let times = self.entry(sequence, priority);
+        times.dequeued_ms = Some(now_ms);
+        if let Some(enqueued_ms) = times.enqueued_ms {
+            self.report.record(
+                priority,
+                LatencyStage::Queue,
+                now_ms.saturating_sub(enqueued_ms),
+            );
+        }
+    }
+
+    /// Record that message `sequence`, measured at `measured_ms`, was
+    /// decoded at `now_ms`
+    pub fn record_decoded(
+        &mut self,
+        sequence: u16,
+        priority: Priority,
+        measured_ms: u64,
+        now_ms: u64,
+    ) {
+        let times = self.entry(sequence, priority);
+        times.measured_ms = Some(measured_ms);
+        times.decoded_ms = Some(now_ms);
+        self.report.record(
+            priority,
+            LatencyStage::EndToEnd,
+            now_ms.saturating_sub(measured_ms),
+        );
+    }
+
+    /// Fold in the times of another side's log, correlating by sequence
+    ///
+    /// Times already set here are kept, so merge the sender's log first:
+    /// its measurement times have ms resolution, the receiver's only the
+    /// header's seconds. The report is not changed; see
+    /// [`merged_report`](Self::merged_report).
+    pub fn merge(&mut self, other: &LatencyLog) {
+        for (&sequence, theirs) in &other.entries {
+            match self.entries.get_mut(&sequence) {
+                Some(mine) => mine.fill_from(theirs),
+                None => {
+                    self.entries.insert(sequence, *theirs);
+                    self.order.push_back(sequence);
+                }
+            }
+        }
+    }
+
+    /// Report of every stage known for the messages kept, e.g. after
+    /// merging the logs of both sides
+    pub fn merged_report(&self) -> LatencyReport {
+        let mut report = LatencyReport::new();
+        for times in self.entries.values() {
+            let breakdown = times.breakdown();
+            for stage in LatencyStage::ALL {
+                if let Some(latency_ms) = breakdown.stage(stage) {
+                    report.record(times.priority, stage, latency_ms);
+                }
+            }
+        }
+        report
+    }
+
+    /// Entry of `sequence`, created (dropping the oldest beyond capacity)
+    /// if new
+    fn entry(&mut self, sequence: u16, priority: Priority) -> &mut StageTimes {
+        if !self.entries.contains_key(&sequence) {
+            while self.entries.len() >= self.capacity.max(1) {
+                let Some(oldest) = self.order.pop_front() else {
+                    break;
+                };
+                self.entries.remove(&oldest);
+            }
+            self.order.push_back(sequence);
+        }
+        self.entries
+            .entry(sequence)
+            .or_insert_with(|| StageTimes::new(priority))
+    }
+}
@@ -30,7 +30,7 @@
 //! assert!((decoded.value - data.value).abs() < 0.01);
 //!
 //! // Update context
-//! context.observe(&data);
+//! context.observe_classified(&data, &classification);
 //! ```
 //!
 //! ## Modules
@@ -40,8 +40,20 @@
 //! - [`decoder`]: Message decoding
 //! - [`classifier`]: Priority classification
 //! - [`context`]: Shared context (dictionary + prediction model)
-//! - [`channel`]: Communication channel abstraction (std only)
+//! - [`channel`]: Communication channel abstraction (`std-time`)
 //! - [`metrics`]: Compression statistics and analysis
+//! - [`observer`]: Per-message encode/decode observer hooks
+//! - [`sequence`]: Sequence number persistence across restarts
+//! - [`stats`]: Streaming mean/variance, EMA and window statistics
+//! - [`simulator`]: End-to-end emitter/receiver simulation (`std-time`)
+//! - `analysis`: Reports on captures of raw messages (`analysis` feature)
+//!
+//! ## WebAssembly
+//!
+//! `wasm32-unknown-unknown` has no file system and no clock. Without the
+//! `std-fs` and `std-time` features (`default-features = false,
+//! features = ["std"]`) the codec builds for it: protocol, encoder,
+//! decoder, classifier, context and sync.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -54,55 +66,88 @@ pub mod context;
 pub mod decoder;
 pub mod encoder;
 pub mod error;
+pub mod latency;
 pub mod metrics;
+pub mod observer;
 pub mod protocol;
+pub mod sequence;
+pub mod session;
+pub mod stats;
 pub mod sync;
 pub mod tls;
 
-// Std-only modules
-#[cfg(feature = "std")]
+// Std-only modules (`std-time`: need a wall clock)
+#[cfg(feature = "analysis")]
+pub mod analysis;
+#[cfg(feature = "std-time")]
 pub mod channel;
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub mod fleet;
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub mod health;
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub mod recovery;
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub mod security;
+#[cfg(feature = "std-time")]
+pub mod simulator;
 
 // Re-exports for convenient access (always available)
-pub use classifier::{Classification, ClassificationReason, Classifier};
+pub use classifier::{
+    AnomalyLabel, AnomalyScorer, Classification, ClassificationReason, Classifier,
+    ClassifierConfig, PriorityMapper,
+};
 pub use context::Context;
-pub use decoder::Decoder;
-pub use encoder::Encoder;
+pub use decoder::{
+    AgeHistogram, Decoder, DecoderConfig, DecoderStats, EstimatedValue, GapFillConfig,
+    GapFillMethod, StaleAction, StaleThreshold,
+};
+pub use encoder::{DeadZone, Encoder, EncoderConfig, PrecisionAdvice};
 pub use error::{AlecError, Result};
 pub use metrics::{CompressionMetrics, ContextMetrics};
+pub use observer::{DecodeInfo, DecodeObserver, EncodeInfo, EncodeObserver, IntegrityStatus};
 pub use protocol::{
-    ChannelInput, EncodedMessage, EncodingType, MessageHeader, MessageType, Priority, RawData,
+    ChannelInput, ChecksumAlgorithm, ChecksumPolicy, CompositeEntry, CompositeValue, Decoded,
+    DecodedComposite, DecodedValue, EncodedMessage, EncodedMessageRef, EncodingType,
+    HeaderExtension, HeaderLayout, MessageHeader, MessageType, Priority, Quality, RawData,
+    TimestampMode, TimestampUnit, TransmissionPlan, WireDialect, WireVersion,
 };
+pub use sequence::{
+    InMemorySequenceStore, SequencePersistence, SequenceStep, SequenceStore, SequenceTracker,
+    SequenceWindow,
+};
+pub use stats::{Ema, RunningStats, WindowedStats};
 pub use sync::{
-    SyncAnnounce, SyncConfig, SyncDiff, SyncMessage, SyncRequest, SyncState, Synchronizer,
+    DetailCache, DiffPreview, SyncAnnounce, SyncConfig, SyncDiff, SyncMessage, SyncRequest,
+    SyncState, Synchronizer, VersionNegotiation,
 };
-pub use tls::{DtlsConfig, TlsConfig, TlsState};
+pub use tls::{DtlsConfig, PskConfig, TlsConfig, TlsState};
 
 // Std-only re-exports
-#[cfg(feature = "std")]
-pub use channel::Channel;
-#[cfg(feature = "std")]
-pub use fleet::{EmitterId, EmitterState, FleetConfig, FleetManager, FleetStats, ProcessedMessage};
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
+pub use channel::{
+    Channel, ChannelRx, ChannelTx, LinkDirection, LinkModel, LinkStats, MemoryReceiver,
+    MemorySender, PriorityQueue, PriorityQueueConfig, QueueItem, SimulatedLink, TrySendError,
+};
+#[cfg(feature = "std-time")]
+pub use fleet::{
+    ContextKey, ContextMode, EmitterId, EmitterState, FleetConfig, FleetManager, FleetStats,
+    GroupId, MessageHandler, ProcessedMessage, SubscriptionFilter, SubscriptionId, TenantId,
+};
+#[cfg(feature = "std-time")]
 pub use health::{HealthCheck, HealthCheckable, HealthConfig, HealthMonitor, HealthStatus};
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub use recovery::{
     with_retry, with_retry_metrics, CircuitBreaker, CircuitConfig, CircuitState, DegradationLevel,
     RetryResult, RetryStrategy,
 };
-#[cfg(feature = "std")]
+#[cfg(feature = "std-time")]
 pub use security::{
-    AuditEvent, AuditEventType, AuditFilter, AuditLogger, CertValidation, MemoryAuditLogger,
-    RateLimiter, SecurityConfig, SecurityContext, Severity,
+    AuditEvent, AuditEventType, AuditFilter, AuditLogger, CertValidation, FieldValue,
+    MemoryAuditLogger, RateLimiter, SecurityConfig, SecurityContext, Severity,
 };
+#[cfg(feature = "std-fs")]
+pub use sequence::FileSequenceStore;
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -113,6 +158,10 @@ pub const PROTOCOL_VERSION: u8 = 1;
 /// Maximum payload size in bytes
 pub const MAX_PAYLOAD_SIZE: usize = 65535;
 
+/// Maximum values in one multi-value message (the count is a single byte
+/// on the wire)
+pub const MAX_MULTI_VALUES: usize = u8::MAX as usize;
+
 /// Default scale factor for delta encoding (100 = 2 decimal places)
 pub const DEFAULT_SCALE_FACTOR: u32 = 100;
 
@@ -139,6 +188,6 @@ mod tests {
 
         assert!((decoded.value - data.value).abs() < 0.001);
 
-        context.observe(&data);
+        context.observe_classified(&data, &classification);
     }
 }
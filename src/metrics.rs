@@ -33,6 +33,8 @@ pub struct CompressionMetrics {
     pub prediction_hits: u64,
     /// Prediction misses
     pub prediction_misses: u64,
+    /// Messages whose priority was set by the application
+    pub overridden_messages: u64,
 }
 
 impl CompressionMetrics {
@@ -58,6 +60,11 @@ impl CompressionMetrics {
         }
     }
 
+    /// Record a message carrying the operator-override flag
+    pub fn record_override(&mut self) {
+        self.overridden_messages += 1;
+    }
+
     /// Calculate compression ratio (higher = better)
     /// Returns raw_size / encoded_size
     pub fn compression_ratio(&self) -> f64 {
@@ -150,6 +157,13 @@ impl CompressionMetrics {
             ));
         }
 
+        if self.overridden_messages > 0 {
+            report.push_str(&format!(
+                "Operator overrides: {}\n",
+                self.overridden_messages
+            ));
+        }
+
         report
     }
 }
@@ -196,6 +210,51 @@ impl ContextMetrics {
     }
 }
 
+/// Yes/no outcomes of the last 64 events, one bit each
+///
+/// Cheap enough to update on every encode or decode; backs the rates
+/// reported by the health checks.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct OutcomeWindow {
+    /// Most recent outcome in the lowest bit
+    bits: u64,
+    /// Outcomes recorded, up to [`Self::CAPACITY`]
+    count: u8,
+}
+
+impl OutcomeWindow {
+    /// Number of outcomes kept
+    pub(crate) const CAPACITY: usize = 64;
+
+    /// Record one outcome, dropping the oldest once full
+    pub(crate) fn record(&mut self, hit: bool) {
+        self.bits = (self.bits << 1) | u64::from(hit);
+        if (self.count as usize) < Self::CAPACITY {
+            self.count += 1;
+        }
+    }
+
+    /// Outcomes in the window
+    #[cfg_attr(not(feature = "std-time"), allow(dead_code))]
+    pub(crate) fn count(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Positive outcomes in the window
+    pub(crate) fn hits(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Share of positive outcomes, 0.0 when empty
+    pub(crate) fn rate(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.hits() as f64 / self.count as f64
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,4 +321,21 @@ mod tests {
         assert_eq!(metrics.raw_bytes, 0);
         assert_eq!(metrics.prediction_hits, 0);
     }
+
+    #[test]
+    fn test_outcome_window_drops_oldest() {
+        let mut window = OutcomeWindow::default();
+        assert_eq!(window.rate(), 0.0);
+
+        window.record(true);
+        window.record(false);
+        assert_eq!((window.count(), window.hits()), (2, 1));
+        assert!((window.rate() - 0.5).abs() < 1e-12);
+
+        for _ in 0..OutcomeWindow::CAPACITY - 1 {
+            window.record(false);
+        }
+        assert_eq!(window.count(), OutcomeWindow::CAPACITY);
+        assert_eq!(window.hits(), 0);
+    }
 }
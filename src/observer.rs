@@ -0,0 +1,425 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Per-message observer hooks for the encoder and decoder
+//!
+//! Observers let an application ship per-message telemetry (encoding
+//! chosen, sizes, prediction residual, decode latency, integrity status)
+//! to its observability stack without wrapping every codec call.
+//!
+//! # Contract
+//!
+//! - Observers are **optional**. When none is installed the codec only
+//!   pays for an `Option` check.
+//! - Observers are **infallible** from the codec's point of view: they
+//!   return nothing and cannot influence encoding or decoding. With the
+//!   `std` feature a panicking observer is caught and ignored; on
+//!   `no_std` builds observers must not panic.
+//! - Exactly one callback is made per encode/decode call.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//! use alec::observer::MetricsObserver;
+//! use alec::{Classifier, Context, Encoder, RawData};
+//!
+//! let metrics = Arc::new(MetricsObserver::new());
+//! let mut encoder = Encoder::new();
+//! encoder.set_observer(Box::new(metrics.clone()));
+//!
+//! let context = Context::new();
+//! let data = RawData::new(22.5, 0);
+//! let classification = Classifier::default().classify(&data, &context);
+//! encoder.encode(&data, &classification, &context);
+//!
+//! assert_eq!(metrics.metrics().message_count, 1);
+//! ```
+
+use core::time::Duration;
+
+use crate::error::AlecError;
+use crate::protocol::{EncodingType, Priority};
+
+#[cfg(feature = "std")]
+use crate::metrics::CompressionMetrics;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+/// Information about one encoded message
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodeInfo {
+    /// Source identifier (0 for frame-level multi messages)
+    pub source_id: u32,
+    /// Priority written in the header
+    pub priority: Priority,
+    /// Encoding type chosen (`Multi` for multi-value messages)
+    pub encoding: EncodingType,
+    /// Size of the raw input in bytes
+    pub raw_size: usize,
+    /// Size of the encoded message in bytes (header + payload)
+    pub encoded_size: usize,
+    /// Value minus context prediction, if a prediction was available
+    pub residual: Option<f64>,
+    /// Context version written in the header
+    pub context_version: u32,
+    /// Whether the priority was set by the application
+    pub operator_override: bool,
+}
+
+/// Integrity check outcome for a decoded message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityStatus {
+    /// No checksum was verified (decoder not configured for it, or the
+    /// message was already parsed)
+    NotChecked,
+    /// Checksum verified
+    Verified,
+    /// Checksum present but did not match
+    Failed,
+}
+
+/// Information about one decode attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeInfo<'a> {
+    /// Source identifier, if the payload got far enough to read it
+    pub source_id: Option<u32>,
+    /// Encoding type, if the payload got far enough to read it
+    pub encoding: Option<EncodingType>,
+    /// Size of the input in bytes
+    pub encoded_size: usize,
+    /// Wall-clock decode duration (`None` on `no_std` builds)
+    pub duration: Option<Duration>,
+    /// Integrity check outcome
+    pub integrity: IntegrityStatus,
+    /// Error returned to the caller, if the decode failed
+    pub error: Option<&'a AlecError>,
+}
+
+impl DecodeInfo<'_> {
+    /// Check if the decode succeeded
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Observer notified after every encode
+///
+/// Implementations must not panic on `no_std` builds (see module docs).
+pub trait EncodeObserver: Send + Sync {
+    /// Called once per encoded message
+    fn on_encode(&self, info: &EncodeInfo);
+}
+
+/// Observer notified after every decode attempt, successful or not
+///
+/// Implementations must not panic on `no_std` builds (see module docs).
+pub trait DecodeObserver: Send + Sync {
+    /// Called once per decode call
+    fn on_decode(&self, info: &DecodeInfo<'_>);
+}
+
+#[cfg(feature = "std")]
+impl<T: EncodeObserver + ?Sized> EncodeObserver for Arc<T> {
+    fn on_encode(&self, info: &EncodeInfo) {
+        (**self).on_encode(info)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: DecodeObserver + ?Sized> DecodeObserver for Arc<T> {
+    fn on_decode(&self, info: &DecodeInfo<'_>) {
+        (**self).on_decode(info)
+    }
+}
+
+/// Invoke an observer callback, swallowing panics where possible
+#[inline]
+pub(crate) fn notify<F: FnOnce()>(f: F) {
+    #[cfg(feature = "std")]
+    {
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        f();
+    }
+}
+
+/// Measures decode latency when a clock is available
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std-time")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "std-time")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        #[cfg(feature = "std-time")]
+        {
+            Some(self.start.elapsed())
+        }
+        #[cfg(not(feature = "std-time"))]
+        {
+            None
+        }
+    }
+}
+
+/// Bundled observer feeding [`CompressionMetrics`]
+///
+/// Share it with the codec through an `Arc` and read the accumulated
+/// metrics with [`MetricsObserver::metrics`]. Encodes are recorded with
+/// [`CompressionMetrics::record_encode`]; messages that had a prediction
+/// also count as a prediction hit when the compact path (`Repeated`,
+/// `Interpolated` or `Delta8`) was taken, and as a miss otherwise.
+/// Successful decodes increment `decode_count`, failures `decode_errors`.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct MetricsObserver {
+    inner: Mutex<MetricsObserverState>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct MetricsObserverState {
+    metrics: CompressionMetrics,
+    decode_count: u64,
+    decode_errors: u64,
+}
+
+#[cfg(feature = "std")]
+impl MetricsObserver {
+    /// Create an observer with empty metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of the encode-side metrics
+    pub fn metrics(&self) -> CompressionMetrics {
+        self.lock().metrics.clone()
+    }
+
+    /// Number of successful decodes observed
+    pub fn decode_count(&self) -> u64 {
+        self.lock().decode_count
+    }
+
+    /// Number of failed decodes observed
+    pub fn decode_errors(&self) -> u64 {
+        self.lock().decode_errors
+    }
+
+    /// Reset all accumulated metrics
+    pub fn reset(&self) {
+        *self.lock() = MetricsObserverState::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetricsObserverState> {
+        // A poisoned lock only means another observer call panicked;
+        // the counters themselves are still consistent.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl EncodeObserver for MetricsObserver {
+    fn on_encode(&self, info: &EncodeInfo) {
+        let mut state = self.lock();
+        state
+            .metrics
+            .record_encode(info.raw_size, info.encoded_size, info.encoding);
+        if info.residual.is_some() {
+            let hit = matches!(
+                info.encoding,
+                EncodingType::Repeated | EncodingType::Interpolated | EncodingType::Delta8
+            );
+            state.metrics.record_prediction(hit);
+        }
+        if info.operator_override {
+            state.metrics.record_override();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl DecodeObserver for MetricsObserver {
+    fn on_decode(&self, info: &DecodeInfo<'_>) {
+        let mut state = self.lock();
+        if info.is_ok() {
+            state.decode_count += 1;
+        } else {
+            state.decode_errors += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::Classifier;
+    use crate::context::Context;
+    use crate::decoder::Decoder;
+    use crate::encoder::Encoder;
+    use crate::protocol::RawData;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Recorder {
+        encodes: Mutex<Vec<EncodeInfo>>,
+        decodes: Mutex<Vec<(usize, IntegrityStatus, bool)>>,
+    }
+
+    impl EncodeObserver for Recorder {
+        fn on_encode(&self, info: &EncodeInfo) {
+            self.encodes.lock().unwrap().push(info.clone());
+        }
+    }
+
+    impl DecodeObserver for Recorder {
+        fn on_decode(&self, info: &DecodeInfo<'_>) {
+            self.decodes
+                .lock()
+                .unwrap()
+                .push((info.encoded_size, info.integrity, info.is_ok()));
+        }
+    }
+
+    #[test]
+    fn test_encode_observer_one_call_per_encode() {
+        let recorder = Arc::new(Recorder::default());
+        let mut encoder = Encoder::new();
+        encoder.set_observer(Box::new(recorder.clone()));
+
+        let classifier = Classifier::default();
+        let mut context = Context::new();
+        let mut sizes = Vec::new();
+        for i in 0..5 {
+            let data = RawData::new(20.0 + i as f64 * 0.1, i * 1000);
+            let classification = classifier.classify(&data, &context);
+            let message = encoder.encode(&data, &classification, &context);
+            sizes.push(message.to_bytes().len());
+            context.observe(&data);
+        }
+
+        let encodes = recorder.encodes.lock().unwrap();
+        assert_eq!(encodes.len(), 5);
+        for (info, size) in encodes.iter().zip(&sizes) {
+            assert_eq!(info.encoded_size, *size);
+            assert_eq!(info.raw_size, 20);
+        }
+        // First message has no prediction, later ones do.
+        assert!(encodes[0].residual.is_none());
+        assert!(encodes[4].residual.is_some());
+    }
+
+    #[test]
+    fn test_decode_observer_one_call_per_decode() {
+        let recorder = Arc::new(Recorder::default());
+        let mut encoder = Encoder::with_checksum();
+        let mut decoder = Decoder::with_checksum_verification();
+        decoder.set_observer(Box::new(recorder.clone()));
+
+        let context = Context::new();
+        let data = RawData::new(42.0, 0);
+        let classification = Classifier::default().classify(&data, &context);
+        let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+
+        decoder.decode_bytes(&bytes, &context).unwrap();
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        assert!(decoder.decode_bytes(&corrupted, &context).is_err());
+
+        let decodes = recorder.decodes.lock().unwrap();
+        assert_eq!(decodes.len(), 2);
+        assert_eq!(decodes[0], (bytes.len(), IntegrityStatus::Verified, true));
+        assert_eq!(decodes[1], (bytes.len(), IntegrityStatus::Failed, false));
+    }
+
+    #[test]
+    fn test_panicking_observer_is_ignored() {
+        struct Panicky(AtomicUsize);
+        impl EncodeObserver for Panicky {
+            fn on_encode(&self, _info: &EncodeInfo) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                panic!("observer bug");
+            }
+        }
+
+        let observer = Arc::new(Panicky(AtomicUsize::new(0)));
+        let mut encoder = Encoder::new();
+        encoder.set_observer(Box::new(observer.clone()));
+
+        let context = Context::new();
+        let data = RawData::new(1.0, 0);
+        let classification = Classifier::default().classify(&data, &context);
+        let message = encoder.encode(&data, &classification, &context);
+
+        assert!(!message.is_empty());
+        assert_eq!(observer.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_metrics_observer() {
+        let metrics = Arc::new(MetricsObserver::new());
+        let mut encoder = Encoder::new();
+        let mut decoder = Decoder::new();
+        encoder.set_observer(Box::new(metrics.clone()));
+        decoder.set_observer(Box::new(metrics.clone()));
+
+        let classifier = Classifier::default();
+        let mut context = Context::new();
+        let mut direct = CompressionMetrics::new();
+        for i in 0..10 {
+            let data = RawData::new(20.0, i * 1000);
+            let classification = classifier.classify(&data, &context);
+            let message = encoder.encode(&data, &classification, &context);
+            direct.record_encode(
+                data.raw_size(),
+                message.len(),
+                message.encoding_type().unwrap(),
+            );
+            decoder.decode(&message, &context).unwrap();
+            context.observe(&data);
+        }
+
+        let observed = metrics.metrics();
+        assert_eq!(observed.message_count, 10);
+        assert_eq!(observed.raw_bytes, direct.raw_bytes);
+        assert_eq!(observed.encoded_bytes, direct.encoded_bytes);
+        assert_eq!(metrics.decode_count(), 10);
+        assert_eq!(metrics.decode_errors(), 0);
+
+        metrics.reset();
+        assert_eq!(metrics.metrics().message_count, 0);
+    }
+
+    #[test]
+    fn test_clear_observer() {
+        let recorder = Arc::new(Recorder::default());
+        let mut encoder = Encoder::new();
+        encoder.set_observer(Box::new(recorder.clone()));
+        assert!(encoder.has_observer());
+        encoder.clear_observer();
+        assert!(!encoder.has_observer());
+
+        let context = Context::new();
+        let data = RawData::new(1.0, 0);
+        let classification = Classifier::default().classify(&data, &context);
+        encoder.encode(&data, &classification, &context);
+        assert!(recorder.encodes.lock().unwrap().is_empty());
+    }
+}
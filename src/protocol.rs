@@ -15,10 +15,11 @@
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+use crate::context::Context;
 use crate::error::DecodeError;
 use core::fmt;
 
-/// Checksum size in bytes (xxHash32)
+/// Checksum size in bytes (xxHash32 or CRC-32C)
 pub const CHECKSUM_SIZE: usize = 4;
 
 /// Raw data from a sensor or source
@@ -26,10 +27,12 @@ pub const CHECKSUM_SIZE: usize = 4;
 pub struct RawData {
     /// Unique identifier for the data source
     pub source_id: u32,
-    /// Timestamp (relative or absolute)
+    /// Timestamp (relative or absolute), in `timestamp_unit`
     pub timestamp: u64,
     /// The measured value
     pub value: f64,
+    /// Unit of `timestamp` (default: [`TimestampUnit::Millis`])
+    pub timestamp_unit: TimestampUnit,
 }
 
 impl RawData {
@@ -39,6 +42,7 @@ impl RawData {
             source_id: 0,
             timestamp,
             value,
+            timestamp_unit: TimestampUnit::Millis,
         }
     }
 
@@ -48,9 +52,39 @@ impl RawData {
             source_id,
             timestamp,
             value,
+            timestamp_unit: TimestampUnit::Millis,
         }
     }
 
+    /// Set the timestamp, expressed in `unit`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use alec::{RawData, TimestampUnit};
+    ///
+    /// let secs = RawData::new(21.5, 0).with_timestamp_in(TimestampUnit::Seconds, 1_700_000_000);
+    /// let micros =
+    ///     RawData::new(21.5, 0).with_timestamp_in(TimestampUnit::Micros, 1_700_000_000_000_000);
+    /// assert_eq!(secs.timestamp_micros(), micros.timestamp_micros());
+    /// ```
+    pub fn with_timestamp_in(mut self, unit: TimestampUnit, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self.timestamp_unit = unit;
+        self
+    }
+
+    /// Timestamp in microseconds, whatever its unit (saturating)
+    pub fn timestamp_micros(&self) -> i64 {
+        let timestamp = i64::try_from(self.timestamp).unwrap_or(i64::MAX);
+        self.timestamp_unit.to_micros(timestamp)
+    }
+
+    /// Timestamp in whole seconds, as message headers carry it
+    pub fn timestamp_secs(&self) -> u64 {
+        TimestampUnit::Seconds.from_micros(self.timestamp_micros()) as u64
+    }
+
     /// Size of raw data in bytes (for comparison)
     pub fn raw_size(&self) -> usize {
         // source_id (4) + timestamp (8) + value (8) = 20 bytes
@@ -113,6 +147,240 @@ impl Priority {
     }
 }
 
+/// Which messages carry a trailing checksum
+///
+/// The header byte has no spare bit to flag checksum presence, so the
+/// encoder and decoder must agree on the same policy. Both sides derive
+/// the decision from the priority stored in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChecksumPolicy {
+    /// Never append a checksum
+    #[default]
+    Never,
+    /// Append a checksum to every message
+    Always,
+    /// Append a checksum to messages at least as important as the given
+    /// priority (`ForPriorityAtOrAbove(P2Important)` covers P1 and P2)
+    ForPriorityAtOrAbove(Priority),
+}
+
+impl ChecksumPolicy {
+    /// Check if a message with this priority carries a checksum
+    pub fn applies_to(&self, priority: Priority) -> bool {
+        match self {
+            ChecksumPolicy::Never => false,
+            ChecksumPolicy::Always => true,
+            ChecksumPolicy::ForPriorityAtOrAbove(threshold) => priority <= *threshold,
+        }
+    }
+}
+
+/// Algorithm of a message's trailing checksum
+///
+/// Selected per message by the [`FLAG_CRC32C`] header flag, so the
+/// decoder needs no configuration to verify either. Both are
+/// [`CHECKSUM_SIZE`] bytes, but [`ChecksumAlgorithm::Crc32c`] needs an
+/// extended header ([`HeaderExtension::SIZE`] more bytes, or
+/// [`HeaderExtension::MAX_SIZE`] with a sub-second timestamp).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChecksumAlgorithm {
+    /// xxHash32 with seed 0
+    #[default]
+    Xxh32,
+    /// CRC-32C (Castagnoli), detects every error of up to 2 bits; matches
+    /// the CRC computed in hardware by many radio transceivers
+    Crc32c,
+}
+
+impl ChecksumAlgorithm {
+    /// Checksum of `bytes`
+    pub fn checksum(self, bytes: &[u8]) -> u32 {
+        match self {
+            ChecksumAlgorithm::Xxh32 => xxhash_rust::xxh32::xxh32(bytes, 0),
+            ChecksumAlgorithm::Crc32c => {
+                use crc::{Crc, CRC_32_ISCSI};
+                const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+                CRC32C.checksum(bytes)
+            }
+        }
+    }
+
+    /// Error reported when this checksum does not match
+    pub fn mismatch(self, expected: u32, actual: u32) -> DecodeError {
+        match self {
+            ChecksumAlgorithm::Xxh32 => DecodeError::InvalidChecksum { expected, actual },
+            ChecksumAlgorithm::Crc32c => DecodeError::InvalidCrc32c { expected, actual },
+        }
+    }
+}
+
+/// How the encoder writes header timestamps
+///
+/// Compressed timestamps are relative to the previous message from the
+/// same source, so the decoder needs a full-timestamp *anchor* per
+/// source first. Encoders emit one on the first message of each source,
+/// periodically, and after [`crate::Encoder::force_timestamp_anchor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimestampMode {
+    /// Full 32-bit timestamp in every header
+    #[default]
+    Full,
+    /// Varint seconds since the previous message from the same source,
+    /// with a full anchor every `anchor_interval` messages (0 = only when
+    /// required)
+    DeltaVarint { anchor_interval: u16 },
+    /// Low `bits` bits of the timestamp (1..=24); the decoder restores
+    /// the high bits from the last timestamp seen for the source
+    TruncatedEpoch { bits: u8 },
+}
+
+/// Unit of a [`RawData`] timestamp
+///
+/// Unspecified timestamps are milliseconds, which is what the encoder
+/// has always assumed. A unit set in
+/// [`EncoderConfig::timestamp_unit`](crate::EncoderConfig::timestamp_unit)
+/// is recorded in the header extension ([`FLAG_TIMESTAMP_UNIT_MASK`]),
+/// along with the sub-second part of the timestamp in that unit
+/// ([`HeaderExtension::subsec`]), so a decoder configured for another unit
+/// rejects the message instead of reporting ages off by a factor of 1000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimestampUnit {
+    /// Seconds
+    Seconds,
+    /// Milliseconds
+    #[default]
+    Millis,
+    /// Microseconds
+    Micros,
+}
+
+impl TimestampUnit {
+    /// Microseconds in one unit
+    pub const fn micros_per_unit(self) -> i64 {
+        match self {
+            TimestampUnit::Seconds => 1_000_000,
+            TimestampUnit::Millis => 1_000,
+            TimestampUnit::Micros => 1,
+        }
+    }
+
+    /// Express `value`, in this unit, in microseconds (saturating)
+    pub fn to_micros(self, value: i64) -> i64 {
+        value.saturating_mul(self.micros_per_unit())
+    }
+
+    /// Express `micros` in this unit, rounding towards negative infinity
+    pub fn from_micros(self, micros: i64) -> i64 {
+        micros.div_euclid(self.micros_per_unit())
+    }
+
+    /// Units in one second
+    pub const fn per_second(self) -> u32 {
+        (1_000_000 / self.micros_per_unit()) as u32
+    }
+
+    /// Bytes of the sub-second part an extended header recording this
+    /// unit carries ([`HeaderExtension::subsec`])
+    pub const fn subsec_len(self) -> usize {
+        match self {
+            TimestampUnit::Seconds => 0,
+            TimestampUnit::Millis => 2,
+            TimestampUnit::Micros => 3,
+        }
+    }
+
+    /// Header extension flag bits recording this unit
+    pub fn to_flags(self) -> u8 {
+        let code = match self {
+            TimestampUnit::Seconds => 1,
+            TimestampUnit::Millis => 2,
+            TimestampUnit::Micros => 3,
+        };
+        code << FLAG_TIMESTAMP_UNIT_MASK.trailing_zeros()
+    }
+
+    /// Unit recorded in header extension flags, if any
+    pub fn from_flags(flags: u8) -> Option<Self> {
+        match (flags & FLAG_TIMESTAMP_UNIT_MASK) >> FLAG_TIMESTAMP_UNIT_MASK.trailing_zeros() {
+            1 => Some(TimestampUnit::Seconds),
+            2 => Some(TimestampUnit::Millis),
+            3 => Some(TimestampUnit::Micros),
+            _ => None,
+        }
+    }
+
+    /// Short name (`s`, `ms` or `us`)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimestampUnit::Seconds => "s",
+            TimestampUnit::Millis => "ms",
+            TimestampUnit::Micros => "us",
+        }
+    }
+}
+
+impl fmt::Display for TimestampUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Largest `bits` value honoured by [`TimestampMode::TruncatedEpoch`]
+pub const MAX_TRUNCATED_TIMESTAMP_BITS: u8 = 24;
+
+/// Timestamp layout of a serialized header
+///
+/// There are no spare bits in the header byte, so the two version bits
+/// carry the layout: version 1 is a full header, 2 and 3 mark compressed
+/// timestamps and 0 marks an extended header (see [`HeaderExtension`]),
+/// which always carries a full timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimestampFormat {
+    /// 4-byte big-endian timestamp (anchor)
+    Full,
+    /// Varint of the zigzag-encoded delta to the previous timestamp
+    Delta,
+    /// Varint of `(low_bits << 5) | bits`
+    Truncated,
+}
+
+impl TimestampFormat {
+    /// Header version value used for a delta layout
+    pub const DELTA_VERSION: u8 = 2;
+    /// Header version value used for a truncated layout
+    pub const TRUNCATED_VERSION: u8 = 3;
+
+    /// Timestamp layout selected by a header version value
+    pub fn from_version(version: u8) -> Self {
+        match version & 0x03 {
+            Self::DELTA_VERSION => TimestampFormat::Delta,
+            Self::TRUNCATED_VERSION => TimestampFormat::Truncated,
+            _ => TimestampFormat::Full,
+        }
+    }
+
+    /// Pack a signed delta into the header timestamp field
+    pub fn pack_delta(delta: i64) -> u32 {
+        ((delta << 1) ^ (delta >> 63)) as u32
+    }
+
+    /// Unpack the signed delta from a delta header timestamp field
+    pub fn unpack_delta(field: u32) -> i64 {
+        ((field >> 1) as i64) ^ -((field & 1) as i64)
+    }
+
+    /// Pack the low `bits` bits of `timestamp` into the header timestamp field
+    pub fn pack_truncated(timestamp: u32, bits: u8) -> u32 {
+        let bits = bits.clamp(1, MAX_TRUNCATED_TIMESTAMP_BITS);
+        ((timestamp & ((1u32 << bits) - 1)) << 5) | bits as u32
+    }
+
+    /// Unpack `(low_bits, bits)` from a truncated header timestamp field
+    pub fn unpack_truncated(field: u32) -> (u32, u8) {
+        (field >> 5, (field & 0x1F) as u8)
+    }
+}
+
 impl fmt::Display for Priority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -330,6 +598,11 @@ pub enum EncodingType {
     Interpolated = 0x31,
     /// Multiple values in one message
     Multi = 0x40,
+    /// Transmission plan announcement (see [`TransmissionPlan`])
+    Plan = 0x50,
+    /// Values of several channels, each against its own context
+    /// (see [`crate::Encoder::encode_composite`])
+    Composite = 0x60,
 }
 
 impl EncodingType {
@@ -346,6 +619,8 @@ impl EncodingType {
             0x30 => Some(EncodingType::Repeated),
             0x31 => Some(EncodingType::Interpolated),
             0x40 => Some(EncodingType::Multi),
+            0x50 => Some(EncodingType::Plan),
+            0x60 => Some(EncodingType::Composite),
             _ => None,
         }
     }
@@ -362,25 +637,403 @@ impl EncodingType {
             EncodingType::PatternDelta => 3, // varint + 1 byte
             EncodingType::Repeated => 0,
             EncodingType::Interpolated => 0,
-            EncodingType::Multi => 0, // variable
+            EncodingType::Multi => 0,     // variable
+            EncodingType::Plan => 13,     // fixed fields + 1-byte varints
+            EncodingType::Composite => 0, // variable
         }
     }
 }
 
-/// Message header (10 bytes total)
+// ============================================================================
+// Composite messages (one header for a whole multi-channel flush)
+//
+// Values of several channels, each encoded against its own context, share
+// the header of a single data message whose encoding byte is
+// `EncodingType::Composite`:
+//
+//     source_id (varint) | 0x60 | count (varint) | entry...
+//
+//     entry: channel_id (varint) | encoding | len (varint) | value (len bytes)
+//
+// `source_id` identifies the emitter, and every entry takes the header
+// timestamp and priority. `encoding` is a single-value encoding; `len`
+// lets a receiver skip the entries it has no context for. The 3-bit type
+// field has no free code, so, like multi-value messages, composites are
+// regular data messages: decoders predating them fail with
+// `DecodeError::UnknownEncodingType`.
+// ============================================================================
+
+/// Value of one channel in a composite message
+/// (see [`crate::Encoder::encode_composite`])
+#[derive(Debug, Clone, Copy)]
+pub struct CompositeEntry<'a> {
+    /// Channel identifier carried on the wire
+    pub channel_id: u32,
+    /// Value to encode
+    pub value: f64,
+    /// Context of the channel, holding the value's predictions under
+    /// source id 0 as single-source channels observe them
+    /// (`RawData::new`)
+    pub context: &'a Context,
+}
+
+// ============================================================================
+// Transmission plans ("lazy" decisions sent ahead of the data)
+//
+// A plan is announced in a regular data message whose encoding byte is
+// `EncodingType::Plan`:
+//
+//     source_id (varint) | 0x50 | plan_id | messages | encoding
+//                        | scale (varint) | base (f64 BE) | timeout_s (varint)
+//
+// While the plan is active, values of that source travel in
+// plan-relative frames that do NOT use `MessageHeader`:
+//
+//     byte 0   : PLAN_RELATIVE_MARKER (0xA5)
+//     byte 1   : plan_id
+//     byte 2   : index of the message within the plan (0-based)
+//     byte 3.. : seconds since the plan timestamp (varint)
+//     then     : delta against `base` at `scale`, width set by `encoding`
+//
+// The source, priority, encoding, scale, base and context version all
+// come from the plan. The marker's priority bits (0b101) are not a valid
+// priority, so a plan-relative frame never parses as a regular header.
+// ============================================================================
+
+/// First byte of a plan-relative frame.
+pub const PLAN_RELATIVE_MARKER: u8 = 0xA5;
+
+/// Per-source decision announced ahead of the data it covers
+///
+/// The next `messages` values of `source_id` are sent as deltas against
+/// `base` at `scale`, each `encoding` wide. The plan expires after
+/// `messages` frames or `timeout_s` seconds past `timestamp`, whichever
+/// comes first; the encoder then returns to regular messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransmissionPlan {
+    /// Identifier carried by every plan-relative frame
+    pub plan_id: u8,
+    /// Source the plan covers
+    pub source_id: u32,
+    /// Number of plan-relative frames covered
+    pub messages: u8,
+    /// Delta width (`Delta8`, `Delta16` or `Delta32`)
+    pub encoding: EncodingType,
+    /// Scale applied to deltas
+    pub scale: u32,
+    /// Value deltas are taken against
+    pub base: f64,
+    /// Reference timestamp (seconds) for frame time offsets
+    pub timestamp: u32,
+    /// Seconds after `timestamp` past which the plan is void
+    pub timeout_s: u32,
+}
+
+impl TransmissionPlan {
+    /// Size of a plan-relative frame before the time offset
+    pub const FRAME_PREFIX: usize = 3;
+
+    /// Encode `value` as a delta at this plan's width
+    ///
+    /// Returns `None` if the delta does not fit.
+    pub fn encode_delta(&self, value: f64) -> Option<Vec<u8>> {
+        if !value.is_finite() {
+            return None;
+        }
+        let raw = (value - self.base) * self.scale as f64;
+        let rounded = if raw >= 0.0 { raw + 0.5 } else { raw - 0.5 };
+        let scaled = rounded as i64;
+        match self.encoding {
+            EncodingType::Delta8 => i8::try_from(scaled).ok().map(|d| d.to_be_bytes().to_vec()),
+            EncodingType::Delta16 => i16::try_from(scaled).ok().map(|d| d.to_be_bytes().to_vec()),
+            EncodingType::Delta32 => i32::try_from(scaled).ok().map(|d| d.to_be_bytes().to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Decode a delta at this plan's width from the start of `bytes`
+    pub fn decode_delta(&self, bytes: &[u8]) -> Option<f64> {
+        let scaled = match self.encoding {
+            EncodingType::Delta8 => *bytes.first()? as i8 as i64,
+            EncodingType::Delta16 => i16::from_be_bytes(bytes.get(..2)?.try_into().ok()?) as i64,
+            EncodingType::Delta32 => i32::from_be_bytes(bytes.get(..4)?.try_into().ok()?) as i64,
+            _ => return None,
+        };
+        Some(self.base + scaled as f64 / self.scale as f64)
+    }
+
+    /// Append the plan fields that follow the encoding byte
+    pub fn write_body(&self, out: &mut Vec<u8>) {
+        out.push(self.plan_id);
+        out.push(self.messages);
+        out.push(self.encoding as u8);
+        write_varint(u64::from(self.scale), out);
+        out.extend_from_slice(&self.base.to_be_bytes());
+        write_varint(u64::from(self.timeout_s), out);
+    }
+
+    /// Parse the plan fields that follow the encoding byte
+    ///
+    /// `source_id` and `timestamp` come from the enclosing message.
+    /// Returns the plan and the number of bytes read.
+    pub fn read_body(bytes: &[u8], source_id: u32, timestamp: u32) -> Option<(Self, usize)> {
+        let plan_id = *bytes.first()?;
+        let messages = *bytes.get(1)?;
+        let encoding = EncodingType::from_u8(*bytes.get(2)?)?;
+        if !matches!(
+            encoding,
+            EncodingType::Delta8 | EncodingType::Delta16 | EncodingType::Delta32
+        ) {
+            return None;
+        }
+        let mut pos = 3;
+        let (scale, len) = read_varint(bytes.get(pos..)?)?;
+        if scale == 0 {
+            return None;
+        }
+        pos += len;
+        let base = f64::from_be_bytes(bytes.get(pos..pos + 8)?.try_into().ok()?);
+        pos += 8;
+        let (timeout_s, len) = read_varint(bytes.get(pos..)?)?;
+        pos += len;
+
+        Some((
+            Self {
+                plan_id,
+                source_id,
+                messages,
+                encoding,
+                scale,
+                base,
+                timestamp,
+                timeout_s,
+            },
+            pos,
+        ))
+    }
+}
+
+/// Wire-format version carried by an extended header
+///
+/// Peers reject messages with a higher major version and decode messages
+/// with a higher minor version, skipping the flag bits they do not know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WireVersion {
+    /// Incompatible layout changes
+    pub major: u8,
+    /// Backward-compatible additions, signalled through header flags
+    pub minor: u8,
+}
+
+impl WireVersion {
+    /// Version spoken by headers without an extension
+    pub const LEGACY: WireVersion = WireVersion::new(1, 0);
+    /// Highest version this build understands
+    pub const CURRENT: WireVersion = WireVersion::new(1, 6);
+
+    /// Create a version (each component is kept to 4 bits)
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self {
+            major: major & 0x0F,
+            minor: minor & 0x0F,
+        }
+    }
+
+    /// Pack into one byte (`major << 4 | minor`)
+    pub fn to_byte(self) -> u8 {
+        (self.major << 4) | self.minor
+    }
+
+    /// Unpack from one byte
+    pub fn from_byte(byte: u8) -> Self {
+        Self::new(byte >> 4, byte & 0x0F)
+    }
+}
+
+impl Default for WireVersion {
+    fn default() -> Self {
+        Self::LEGACY
+    }
+}
+
+impl fmt::Display for WireVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Extension flag bits a decoder must understand to read the message
+///
+/// A set bit in this mask that the decoder does not know makes the message
+/// undecodable; unknown bits outside it are ignored.
+pub const CRITICAL_FLAGS_MASK: u8 = 0xF0;
+
+/// Extension flag: the priority was set by the application
+/// ([`crate::Classification::manual`]) rather than the classifier (1.2+)
+pub const FLAG_OPERATOR_OVERRIDE: u8 = 0x01;
+
+/// Extension flag: the trailing checksum, if any, is CRC-32C rather than
+/// xxHash32 ([`ChecksumAlgorithm`]); critical (1.3+)
+pub const FLAG_CRC32C: u8 = 0x10;
+
+/// Extension flag: the message can be decoded without context and seeds
+/// the decoder's prediction state for its source
+/// ([`crate::EncoderConfig::self_describing_interval`]); critical (1.4+)
+pub const FLAG_SELF_DESCRIBING: u8 = 0x20;
+
+/// Extension flag bits: the sender's [`TimestampUnit`] (0: not recorded,
+/// 1: seconds, 2: milliseconds, 3: microseconds); milliseconds and
+/// microseconds add the sub-second part ([`HeaderExtension::subsec`])
+/// (1.5+)
+pub const FLAG_TIMESTAMP_UNIT_MASK: u8 = 0x06;
+
+/// Extension flag: the sender found the source stuck
+/// ([`crate::classifier::AnomalyLabel::Stuck`]), so a flat value is suspect
+/// rather than merely quiet (1.6+)
+pub const FLAG_SUSPECT: u8 = 0x08;
+
+/// Extension flag bits defined by [`WireVersion::CURRENT`]
+pub const KNOWN_FLAGS: u8 = FLAG_OPERATOR_OVERRIDE
+    | FLAG_CRC32C
+    | FLAG_SELF_DESCRIBING
+    | FLAG_TIMESTAMP_UNIT_MASK
+    | FLAG_SUSPECT;
+
+/// Version and flags carried by an extended header
+///
+/// Serialized as two bytes right after the header byte when the version
+/// bits equal [`MessageHeader::EXTENDED_VERSION`], followed by the
+/// sub-second part of the timestamp when the flags record milliseconds or
+/// microseconds ([`TimestampUnit::subsec_len`] bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct HeaderExtension {
+    /// Wire-format version of the sender
+    pub version: WireVersion,
+    /// Feature flags (see [`CRITICAL_FLAGS_MASK`])
+    pub flags: u8,
+    /// Sub-second part of the header timestamp, in the unit recorded in
+    /// `flags` ([`FLAG_TIMESTAMP_UNIT_MASK`]); only sent for milliseconds
+    /// and microseconds
+    pub subsec: u32,
+}
+
+impl HeaderExtension {
+    /// Serialized size in bytes of the version and flags
+    pub const SIZE: usize = 2;
+
+    /// Largest serialized size in bytes, with a sub-second part in
+    /// microseconds
+    pub const MAX_SIZE: usize = Self::SIZE + 3;
+
+    /// Create an extension without flags
+    pub fn new(version: WireVersion) -> Self {
+        Self {
+            version,
+            flags: 0,
+            subsec: 0,
+        }
+    }
+
+    /// Bytes of the sub-second part, per the recorded unit
+    pub fn subsec_len(&self) -> usize {
+        TimestampUnit::from_flags(self.flags).map_or(0, TimestampUnit::subsec_len)
+    }
+
+    /// Serialized size in bytes
+    pub fn encoded_len(&self) -> usize {
+        Self::SIZE + self.subsec_len()
+    }
+
+    /// Check that this build can decode a message carrying this extension
+    pub fn check(&self) -> Result<(), DecodeError> {
+        if self.version.major > WireVersion::CURRENT.major {
+            return Err(DecodeError::UnsupportedVersion {
+                ours: WireVersion::CURRENT.major,
+                theirs: self.version.major,
+            });
+        }
+        let unknown_critical = self.flags & CRITICAL_FLAGS_MASK & !KNOWN_FLAGS;
+        if unknown_critical != 0 {
+            return Err(DecodeError::UnsupportedFlags {
+                flags: unknown_critical,
+            });
+        }
+        if let Some(unit) = TimestampUnit::from_flags(self.flags) {
+            if self.subsec >= unit.per_second() {
+                return Err(DecodeError::InvalidHeader);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Layout selected by the two version bits of a header
+///
+/// A plain header carries its timestamp in any [`TimestampFormat`]; an
+/// extended header carries a [`HeaderExtension`] and always a full
+/// timestamp. Holding both in one value leaves no combination that the
+/// version bits cannot express.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderLayout {
+    /// Version bits 1-3, timestamp in this format
+    Plain(TimestampFormat),
+    /// Version bits [`MessageHeader::EXTENDED_VERSION`], followed by the
+    /// extension bytes
+    Extended(HeaderExtension),
+}
+
+impl HeaderLayout {
+    /// Value of the version bits
+    pub fn version(self) -> u8 {
+        match self {
+            HeaderLayout::Plain(TimestampFormat::Full) => crate::PROTOCOL_VERSION,
+            HeaderLayout::Plain(TimestampFormat::Delta) => TimestampFormat::DELTA_VERSION,
+            HeaderLayout::Plain(TimestampFormat::Truncated) => TimestampFormat::TRUNCATED_VERSION,
+            HeaderLayout::Extended(_) => MessageHeader::EXTENDED_VERSION,
+        }
+    }
+
+    /// Timestamp layout: extended headers always carry a full timestamp
+    pub fn timestamp_format(self) -> TimestampFormat {
+        match self {
+            HeaderLayout::Plain(format) => format,
+            HeaderLayout::Extended(_) => TimestampFormat::Full,
+        }
+    }
+
+    /// Extension carried by an extended header
+    pub fn extension(self) -> Option<HeaderExtension> {
+        match self {
+            HeaderLayout::Plain(_) => None,
+            HeaderLayout::Extended(extension) => Some(extension),
+        }
+    }
+}
+
+impl Default for HeaderLayout {
+    fn default() -> Self {
+        HeaderLayout::Plain(TimestampFormat::Full)
+    }
+}
+
+/// Message header (10 bytes with a full timestamp, 7-11 bytes compressed,
+/// plus [`HeaderExtension::encoded_len`] when extended)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MessageHeader {
-    /// Protocol version (2 bits in header byte)
-    pub version: u8,
+    /// Version bits (2 bits in header byte) and, for an extended header,
+    /// the wire version and flags
+    pub layout: HeaderLayout,
     /// Message type (3 bits in header byte)
     pub message_type: MessageType,
     /// Priority level (3 bits in header byte)
     pub priority: Priority,
     /// Sequence number (u16, wraps every 65 536 frames)
     pub sequence: u16,
-    /// Timestamp (Unix seconds)
+    /// Timestamp (Unix seconds), or its compressed form when the layout
+    /// selects a compressed [`TimestampFormat`]
     pub timestamp: u32,
-    /// Context version used for encoding (serialized as u24, max 16 777 215)
+    /// Context version used for encoding (serialized as u24, max
+    /// [`Self::MAX_CONTEXT_VERSION`]; higher bits are not sent)
     pub context_version: u32,
 }
 
@@ -388,7 +1041,7 @@ impl MessageHeader {
     /// Create a new header with default values
     pub fn new(message_type: MessageType, priority: Priority) -> Self {
         Self {
-            version: crate::PROTOCOL_VERSION,
+            layout: HeaderLayout::default(),
             message_type,
             priority,
             sequence: 0,
@@ -397,12 +1050,97 @@ impl MessageHeader {
         }
     }
 
-    /// Header size in bytes
+    /// Version bits value marking an extended header
+    pub const EXTENDED_VERSION: u8 = 0;
+
+    /// Largest context version a header carries (24 bits)
+    pub const MAX_CONTEXT_VERSION: u32 = 0x00FF_FFFF;
+
+    /// Header size in bytes with a full timestamp
     pub const SIZE: usize = 10;
 
+    /// Smallest possible header size (1-byte compressed timestamp)
+    pub const MIN_SIZE: usize = 7;
+
+    /// Value of the version bits
+    pub fn version(&self) -> u8 {
+        self.layout.version()
+    }
+
+    /// Wire version and flags of an extended header
+    pub fn extension(&self) -> Option<HeaderExtension> {
+        self.layout.extension()
+    }
+
+    /// Timestamp layout selected by the version bits
+    pub fn timestamp_format(&self) -> TimestampFormat {
+        self.layout.timestamp_format()
+    }
+
+    /// Timestamp unit recorded by the sender, if any
+    pub fn timestamp_unit(&self) -> Option<TimestampUnit> {
+        self.extension()
+            .and_then(|ext| TimestampUnit::from_flags(ext.flags))
+    }
+
+    /// Timestamp in the unit recorded by the sender, sub-second part
+    /// included, if a unit is recorded
+    pub fn timestamp_in_unit(&self) -> Option<u64> {
+        let extension = self.extension()?;
+        let unit = TimestampUnit::from_flags(extension.flags)?;
+        Some(u64::from(self.timestamp) * u64::from(unit.per_second()) + u64::from(extension.subsec))
+    }
+
+    /// Check if the operator-override flag is set
+    pub fn is_operator_override(&self) -> bool {
+        self.extension()
+            .is_some_and(|ext| ext.flags & FLAG_OPERATOR_OVERRIDE != 0)
+    }
+
+    /// Check if the suspect flag is set
+    pub fn is_suspect(&self) -> bool {
+        self.extension()
+            .is_some_and(|ext| ext.flags & FLAG_SUSPECT != 0)
+    }
+
+    /// Check if the self-describing flag is set
+    pub fn is_self_describing(&self) -> bool {
+        self.extension()
+            .is_some_and(|ext| ext.flags & FLAG_SELF_DESCRIBING != 0)
+    }
+
+    /// Algorithm of the trailing checksum, if the message carries one
+    pub fn checksum_algorithm(&self) -> ChecksumAlgorithm {
+        if self
+            .extension()
+            .is_some_and(|ext| ext.flags & FLAG_CRC32C != 0)
+        {
+            ChecksumAlgorithm::Crc32c
+        } else {
+            ChecksumAlgorithm::Xxh32
+        }
+    }
+
+    /// Wire-format version this header was written with
+    pub fn wire_version(&self) -> WireVersion {
+        self.extension()
+            .map(|ext| ext.version)
+            .unwrap_or(WireVersion::LEGACY)
+    }
+
+    /// Serialized size of this header in bytes
+    pub fn encoded_len(&self) -> usize {
+        let extension_len = self.extension().map_or(0, |ext| ext.encoded_len());
+        extension_len
+            + match self.timestamp_format() {
+                TimestampFormat::Full => Self::SIZE,
+                _ => Self::MIN_SIZE - 1 + varint_len(self.timestamp),
+            }
+    }
+
     /// Encode the header byte (version + type + priority)
     pub fn encode_header_byte(&self) -> u8 {
-        let version_bits = (self.version & 0x03) << 6;
+        let version_bits = self.version() << 6;
         let type_bits = (self.message_type as u8 & 0x07) << 3;
         let priority_bits = self.priority as u8 & 0x07;
         version_bits | type_bits | priority_bits
@@ -417,19 +1155,54 @@ impl MessageHeader {
     }
 
     /// Serialize header to bytes
-    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
-        let mut bytes = [0u8; Self::SIZE];
-        bytes[0] = self.encode_header_byte();
-        bytes[1..3].copy_from_slice(&self.sequence.to_be_bytes());
-        bytes[3..7].copy_from_slice(&self.timestamp.to_be_bytes());
-        let cv = self.context_version & 0x00FFFFFF;
-        bytes[7..10].copy_from_slice(&cv.to_be_bytes()[1..]);
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_in(&WireDialect::Standard)
+    }
+
+    /// Serialize header to bytes in the layout of `dialect`
+    pub fn to_bytes_in(&self, dialect: &WireDialect) -> Vec<u8> {
+        let profile = dialect.profile();
+        let order = profile.byte_order;
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.push(self.encode_header_byte());
+        if let Some(ext) = self.extension() {
+            bytes.push(ext.version.to_byte());
+            bytes.push(ext.flags);
+            order.write(ext.subsec, ext.subsec_len(), &mut bytes);
+        }
+        for field in profile.field_order {
+            match field {
+                HeaderField::Sequence => order.write(self.sequence as u32, 2, &mut bytes),
+                HeaderField::Timestamp => match self.timestamp_format() {
+                    TimestampFormat::Full => order.write(self.timestamp, 4, &mut bytes),
+                    _ => write_varint(u64::from(self.timestamp), &mut bytes),
+                },
+                HeaderField::ContextVersion => order.write(
+                    self.context_version & Self::MAX_CONTEXT_VERSION,
+                    3,
+                    &mut bytes,
+                ),
+            }
+        }
         bytes
     }
 
     /// Deserialize header from bytes
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < Self::SIZE {
+        Self::parse(bytes).map(|(header, _)| header)
+    }
+
+    /// Deserialize header from the start of `bytes`
+    ///
+    /// Returns the header and the number of bytes it occupied.
+    pub fn parse(bytes: &[u8]) -> Option<(Self, usize)> {
+        Self::parse_in(bytes, &WireDialect::Standard)
+    }
+
+    /// Deserialize header from the start of `bytes`, in the layout of
+    /// `dialect`
+    pub fn parse_in(bytes: &[u8], dialect: &WireDialect) -> Option<(Self, usize)> {
+        if bytes.len() < Self::MIN_SIZE {
             return None;
         }
 
@@ -437,19 +1210,380 @@ impl MessageHeader {
         let msg_type = msg_type?;
         let priority = priority?;
 
-        let sequence = u16::from_be_bytes([bytes[1], bytes[2]]);
-        let timestamp = u32::from_be_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]);
-        let context_version = u32::from_be_bytes([0, bytes[7], bytes[8], bytes[9]]);
+        let (layout, start) = if version == Self::EXTENDED_VERSION {
+            let ext = bytes.get(1..1 + HeaderExtension::SIZE)?;
+            let mut extension = HeaderExtension {
+                version: WireVersion::from_byte(ext[0]),
+                flags: ext[1],
+                subsec: 0,
+            };
+            let subsec_len = extension.subsec_len();
+            if subsec_len > 0 {
+                let rest = bytes.get(1 + HeaderExtension::SIZE..)?;
+                extension.subsec = dialect.profile().byte_order.read(rest, subsec_len)?;
+            }
+            (
+                HeaderLayout::Extended(extension),
+                1 + extension.encoded_len(),
+            )
+        } else {
+            (
+                HeaderLayout::Plain(TimestampFormat::from_version(version)),
+                1,
+            )
+        };
 
-        Some(Self {
-            version,
-            message_type: msg_type,
-            priority,
-            sequence,
-            timestamp,
-            context_version,
+        let profile = dialect.profile();
+        let order = profile.byte_order;
+        let (mut sequence, mut timestamp, mut context_version) = (0, 0, 0);
+        let mut offset = start;
+        for field in profile.field_order {
+            let rest = bytes.get(offset..)?;
+            let len = match field {
+                HeaderField::Sequence => {
+                    sequence = order.read(rest, 2)? as u16;
+                    2
+                }
+                HeaderField::Timestamp => {
+                    let (value, len) = match layout.timestamp_format() {
+                        TimestampFormat::Full => (order.read(rest, 4)?, 4),
+                        _ => read_varint(rest)?,
+                    };
+                    timestamp = value;
+                    len
+                }
+                HeaderField::ContextVersion => {
+                    context_version = order.read(rest, 3)?;
+                    3
+                }
+            };
+            offset += len;
+        }
+
+        Some((
+            Self {
+                layout,
+                message_type: msg_type,
+                priority,
+                sequence,
+                timestamp,
+                context_version,
+            },
+            offset,
+        ))
+    }
+}
+
+/// Byte order of the multi-byte fields of a message's framing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Most significant byte first (the standard layout)
+    BigEndian,
+    /// Least significant byte first
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// Append the low `len` bytes of `value`
+    fn write(self, value: u32, len: usize, out: &mut Vec<u8>) {
+        match self {
+            ByteOrder::BigEndian => out.extend_from_slice(&value.to_be_bytes()[4 - len..]),
+            ByteOrder::LittleEndian => out.extend_from_slice(&value.to_le_bytes()[..len]),
+        }
+    }
+
+    /// Read a `len`-byte value from the start of `bytes`
+    fn read(self, bytes: &[u8], len: usize) -> Option<u32> {
+        let field = bytes.get(..len)?;
+        let mut buf = [0u8; 4];
+        Some(match self {
+            ByteOrder::BigEndian => {
+                buf[4 - len..].copy_from_slice(field);
+                u32::from_be_bytes(buf)
+            }
+            ByteOrder::LittleEndian => {
+                buf[..len].copy_from_slice(field);
+                u32::from_le_bytes(buf)
+            }
+        })
+    }
+}
+
+/// Multi-byte field of a [`MessageHeader`], see
+/// [`DialectProfile::field_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderField {
+    /// Sequence number (2 bytes)
+    Sequence,
+    /// Timestamp (4 bytes, or a varint when compressed)
+    Timestamp,
+    /// Context version (3 bytes)
+    ContextVersion,
+}
+
+/// Header layout of a non-standard [`WireDialect`]
+///
+/// The header byte and the extension bytes of an extended header always
+/// come first. The three multi-byte fields follow in
+/// [`field_order`](Self::field_order); they and the trailing checksum are
+/// written in [`byte_order`](Self::byte_order). A compressed timestamp
+/// stays a varint, which has no byte order. Payloads are not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DialectProfile {
+    byte_order: ByteOrder,
+    field_order: [HeaderField; 3],
+}
+
+impl DialectProfile {
+    /// Field order of the standard layout
+    pub const STANDARD_FIELD_ORDER: [HeaderField; 3] = [
+        HeaderField::Sequence,
+        HeaderField::Timestamp,
+        HeaderField::ContextVersion,
+    ];
+
+    /// The standard layout
+    const STANDARD: Self = Self {
+        byte_order: ByteOrder::BigEndian,
+        field_order: Self::STANDARD_FIELD_ORDER,
+    };
+
+    /// Create a profile, `None` unless `field_order` holds every field
+    /// exactly once
+    pub fn new(byte_order: ByteOrder, field_order: [HeaderField; 3]) -> Option<Self> {
+        let complete = Self::STANDARD_FIELD_ORDER
+            .iter()
+            .all(|field| field_order.contains(field));
+        complete.then_some(Self {
+            byte_order,
+            field_order,
         })
     }
+
+    /// Little-endian fields in the standard order
+    pub fn little_endian() -> Self {
+        Self {
+            byte_order: ByteOrder::LittleEndian,
+            field_order: Self::STANDARD_FIELD_ORDER,
+        }
+    }
+
+    /// Byte order of the multi-byte fields and the checksum
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Order of the multi-byte header fields
+    pub fn field_order(&self) -> [HeaderField; 3] {
+        self.field_order
+    }
+}
+
+/// Header layout messages are read and written in
+///
+/// Lets a [`Decoder`](crate::Decoder) accept traffic from an
+/// implementation whose framing differs from ALEC's only in byte order and
+/// header field order, e.g. during a migration. The dialect is fixed per
+/// decoder ([`DecoderConfig::dialect`](crate::DecoderConfig::dialect)) and
+/// encoder ([`EncoderConfig::dialect`](crate::EncoderConfig::dialect));
+/// it is never detected from the traffic.
+///
+/// A standard decoder misreads the header fields of legacy messages; with
+/// checksums, the byte-swapped checksum rejects them, and without, a
+/// [`DecoderConfig::timestamp_range`](crate::DecoderConfig::timestamp_range)
+/// rejects their misread timestamps as malformed. Compact and
+/// fixed-channel frames have their own headers and always use the
+/// standard layout.
+///
+/// ```
+/// use alec::protocol::{ByteOrder, DialectProfile, HeaderField, WireDialect};
+/// use alec::{MessageHeader, MessageType, Priority};
+///
+/// let profile = DialectProfile::new(
+///     ByteOrder::LittleEndian,
+///     [HeaderField::Timestamp, HeaderField::Sequence, HeaderField::ContextVersion],
+/// )
+/// .unwrap();
+/// let dialect = WireDialect::Legacy(profile);
+///
+/// let mut header = MessageHeader::new(MessageType::Data, Priority::P3Normal);
+/// header.sequence = 0x0102;
+/// header.timestamp = 0x0A0B_0C0D;
+/// let bytes = header.to_bytes_in(&dialect);
+/// assert_eq!(bytes[1..7], [0x0D, 0x0C, 0x0B, 0x0A, 0x02, 0x01]);
+/// assert_eq!(MessageHeader::parse_in(&bytes, &dialect), Some((header, bytes.len())));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WireDialect {
+    /// ALEC's own layout: big-endian fields, sequence first
+    #[default]
+    Standard,
+    /// Legacy layout described by a profile
+    Legacy(DialectProfile),
+}
+
+impl WireDialect {
+    /// Layout of the dialect
+    pub fn profile(&self) -> DialectProfile {
+        match self {
+            WireDialect::Standard => DialectProfile::STANDARD,
+            WireDialect::Legacy(profile) => *profile,
+        }
+    }
+
+    /// Append a checksum in the dialect's byte order
+    pub(crate) fn write_checksum(&self, checksum: u32, out: &mut Vec<u8>) {
+        self.profile()
+            .byte_order
+            .write(checksum, CHECKSUM_SIZE, out);
+    }
+
+    /// Read the checksum at the start of `bytes`
+    pub(crate) fn read_checksum(&self, bytes: &[u8]) -> Option<u32> {
+        self.profile().byte_order.read(bytes, CHECKSUM_SIZE)
+    }
+}
+
+/// Number of bytes `value` takes as a varint
+fn varint_len(value: u32) -> usize {
+    let mut len = 1;
+    let mut v = value >> 7;
+    while v != 0 {
+        len += 1;
+        v >>= 7;
+    }
+    len
+}
+
+/// Append `value` as a LEB128 varint
+///
+/// The varint layout of every ALEC wire and file format, shared with
+/// `alec-gateway`.
+pub fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read a LEB128 varint, returning the value and its length
+///
+/// Rejects varints longer than 10 bytes or overflowing `u64`.
+pub fn read_varint_u64(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+    for (i, &byte) in bytes.iter().take(10).enumerate() {
+        if i == 9 && byte > 0x01 {
+            return None;
+        }
+        value |= u64::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Read a LEB128 varint, returning the value and its length
+///
+/// Rejects varints longer than 5 bytes or overflowing `u32`.
+pub(crate) fn read_varint(bytes: &[u8]) -> Option<(u32, usize)> {
+    let (value, len) = read_varint_u64(bytes.get(..5).unwrap_or(bytes))?;
+    Some((u32::try_from(value).ok()?, len))
+}
+
+/// Length of the Data payload at the start of `payload`
+///
+/// The payload of a self-describing message (see
+/// [`MessageHeader::is_self_describing`]) also carries the sender's
+/// prediction state after the value.
+pub(crate) fn payload_len(payload: &[u8], self_describing: bool) -> Option<usize> {
+    let (_, mut pos) = read_varint(payload)?;
+    let encoding = EncodingType::from_u8(*payload.get(pos)?)?;
+    pos += 1;
+
+    if encoding == EncodingType::Multi {
+        let count = *payload.get(pos)?;
+        pos += 1;
+        for _ in 0..count {
+            // name_id, then the channel encoding
+            let encoding = EncodingType::from_u8(*payload.get(pos + 1)?)?;
+            pos += 2;
+            pos += value_len(encoding, payload.get(pos..)?)?;
+        }
+    } else if encoding == EncodingType::Composite {
+        let (count, len) = read_varint(payload.get(pos..)?)?;
+        pos += len;
+        for _ in 0..count {
+            // channel_id, encoding, then the length-prefixed value
+            pos += read_varint(payload.get(pos..)?)?.1 + 1;
+            let (value_len, len) = read_varint(payload.get(pos..)?)?;
+            pos += len + value_len as usize;
+        }
+    } else {
+        pos += value_len(encoding, &payload[pos..])?;
+        if self_describing {
+            pos += PredictionState::read(payload.get(pos..)?)?.1;
+        }
+    }
+
+    (pos <= payload.len()).then_some(pos)
+}
+
+/// Sender's prediction state, carried by a self-describing message after
+/// its raw value (see [`MessageHeader::is_self_describing`])
+///
+/// Layout: scale factor (varint), observations of the source (varint),
+/// EMA (f64 BE, 0.0 without observations) and EMA alpha (f64 BE).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PredictionState {
+    pub scale: u32,
+    pub count: u32,
+    pub ema: f64,
+    pub alpha: f64,
+}
+
+impl PredictionState {
+    /// Append the state
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_varint(u64::from(self.scale), out);
+        write_varint(u64::from(self.count), out);
+        out.extend_from_slice(&self.ema.to_be_bytes());
+        out.extend_from_slice(&self.alpha.to_be_bytes());
+    }
+
+    /// Parse the state at the start of `bytes`, returning it and its
+    /// length
+    pub fn read(bytes: &[u8]) -> Option<(Self, usize)> {
+        let (scale, scale_len) = read_varint(bytes)?;
+        let (count, count_len) = read_varint(bytes.get(scale_len..)?)?;
+        let pos = scale_len + count_len;
+        let ema = f64::from_be_bytes(bytes.get(pos..pos + 8)?.try_into().ok()?);
+        let alpha = f64::from_be_bytes(bytes.get(pos + 8..pos + 16)?.try_into().ok()?);
+        let state = Self {
+            scale,
+            count,
+            ema,
+            alpha,
+        };
+        Some((state, pos + 16))
+    }
+}
+
+/// Length of a value encoded with `encoding` at the start of `data`
+pub(crate) fn value_len(encoding: EncodingType, data: &[u8]) -> Option<usize> {
+    let len = match encoding {
+        EncodingType::Raw64 => 8,
+        EncodingType::Raw32 | EncodingType::Delta32 => 4,
+        EncodingType::Delta16 => 2,
+        EncodingType::Delta8 => 1,
+        EncodingType::Repeated | EncodingType::Interpolated => 0,
+        EncodingType::Pattern => read_varint(data)?.1,
+        EncodingType::PatternDelta => read_varint(data)?.1 + 1,
+        EncodingType::Plan => TransmissionPlan::read_body(data, 0, 0)?.1,
+        EncodingType::Multi | EncodingType::Composite => return None,
+    };
+    (len <= data.len()).then_some(len)
 }
 
 impl Default for MessageHeader {
@@ -473,9 +1607,14 @@ impl EncodedMessage {
         Self { header, payload }
     }
 
+    /// Borrow the message as an [`EncodedMessageRef`]
+    pub fn as_borrowed(&self) -> EncodedMessageRef<'_> {
+        EncodedMessageRef::new(self.header.clone(), &self.payload)
+    }
+
     /// Total size of the message in bytes
     pub fn len(&self) -> usize {
-        MessageHeader::SIZE + self.payload.len()
+        self.as_borrowed().len()
     }
 
     /// Check if the message is empty (no payload)
@@ -483,87 +1622,243 @@ impl EncodedMessage {
         self.payload.is_empty()
     }
 
+    /// Check if this is a confirmation of an unchanged value
+    ///
+    /// The 3-bit type field has no free code, so confirmations travel as
+    /// `Heartbeat` messages whose payload is the source id (a keep-alive
+    /// heartbeat has no payload). The header sequence holds the sequence
+    /// number of the message that carried the value.
+    pub fn is_confirmation(&self) -> bool {
+        self.as_borrowed().is_confirmation()
+    }
+
+    /// Check if this message announces a [`TransmissionPlan`]
+    pub fn is_plan(&self) -> bool {
+        self.as_borrowed().is_plan()
+    }
+
+    /// Check if this message carries a composite of channel values
+    /// (see [`crate::Encoder::encode_composite`])
+    pub fn is_composite(&self) -> bool {
+        self.as_borrowed().is_composite()
+    }
+
     /// Get the encoding type from the payload (first byte after source_id varint)
     pub fn encoding_type(&self) -> Option<EncodingType> {
-        // Payload format: source_id (varint) + encoding_type (1 byte) + value
-        // Decode the varint to find where the encoding byte starts.
-        let mut pos = 0;
-        while pos < self.payload.len() {
-            let byte = self.payload[pos];
-            pos += 1;
-            if byte & 0x80 == 0 {
-                // End of varint — next byte is the encoding type
-                return self
-                    .payload
-                    .get(pos)
-                    .and_then(|&b| EncodingType::from_u8(b));
-            }
-        }
-        None
+        self.as_borrowed().encoding_type()
     }
 
     /// Serialize the entire message to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(self.len());
-        bytes.extend_from_slice(&self.header.to_bytes());
-        bytes.extend_from_slice(&self.payload);
-        bytes
+        self.as_borrowed().to_bytes()
     }
 
     /// Deserialize message from bytes
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() < MessageHeader::SIZE {
-            return None;
-        }
-
-        let header = MessageHeader::from_bytes(&bytes[..MessageHeader::SIZE])?;
-        let payload = bytes[MessageHeader::SIZE..].to_vec();
-
-        Some(Self { header, payload })
+        EncodedMessageRef::from_bytes(bytes).map(|message| message.to_owned())
     }
 
-    /// Compute checksum of the message (header + payload)
+    /// Compute checksum of the message (header + payload) with the
+    /// algorithm selected by its header
     pub fn compute_checksum(&self) -> u32 {
-        use xxhash_rust::xxh32::xxh32;
-
-        let mut data = Vec::with_capacity(MessageHeader::SIZE + self.payload.len());
-        data.extend_from_slice(&self.header.to_bytes());
-        data.extend_from_slice(&self.payload);
-
-        xxh32(&data, 0) // seed = 0
+        self.header.checksum_algorithm().checksum(&self.to_bytes())
     }
 
     /// Serialize message with checksum appended
     pub fn to_bytes_with_checksum(&self) -> Vec<u8> {
-        let mut bytes = self.to_bytes();
-        let checksum = self.compute_checksum();
-        bytes.extend_from_slice(&checksum.to_be_bytes());
+        self.to_bytes_with_checksum_in(&WireDialect::Standard)
+    }
+
+    /// Serialize message in the layout of `dialect`
+    pub fn to_bytes_in(&self, dialect: &WireDialect) -> Vec<u8> {
+        self.as_borrowed().to_bytes_in(dialect)
+    }
+
+    /// Serialize message in the layout of `dialect`, with checksum
+    /// appended
+    pub fn to_bytes_with_checksum_in(&self, dialect: &WireDialect) -> Vec<u8> {
+        let mut bytes = self.to_bytes_in(dialect);
+        let checksum = self.header.checksum_algorithm().checksum(&bytes);
+        dialect.write_checksum(checksum, &mut bytes);
         bytes
     }
 
     /// Deserialize message from bytes with checksum verification
     pub fn from_bytes_with_checksum(bytes: &[u8]) -> Result<Self, DecodeError> {
-        if bytes.len() < MessageHeader::SIZE + CHECKSUM_SIZE {
+        EncodedMessageRef::from_bytes_with_checksum(bytes).map(|message| message.to_owned())
+    }
+}
+
+/// An encoded message parsed in place: the header is decoded, the payload
+/// borrowed from the received bytes
+///
+/// The zero-copy counterpart of [`EncodedMessage`]: parsing allocates
+/// nothing, so a receiver handling many frames does not copy (or, on
+/// embedded targets, fragment the heap with) every payload. Call
+/// [`to_owned`](Self::to_owned) to keep a message past its buffer.
+///
+/// The message cannot outlive the bytes it was parsed from. The usual
+/// loop parses the receive buffer, decodes every message with
+/// [`Decoder::decode_ref`](crate::Decoder::decode_ref), then drops the
+/// buffer; decoded values own their data, so only they are kept:
+///
+/// ```
+/// use alec::{Context, Decoder, EncodedMessageRef, Encoder, Priority, RawData};
+///
+/// let context = Context::new();
+/// let mut encoder = Encoder::new();
+/// let frames: Vec<Vec<u8>> = (0..3)
+///     .map(|i| {
+///         let data = RawData::new(20.0 + i as f64, i * 1000);
+///         encoder
+///             .encode_with_priority(&data, Priority::P3Normal, &context)
+///             .to_bytes()
+///     })
+///     .collect();
+///
+/// let mut decoder = Decoder::new();
+/// let mut values = Vec::new();
+/// for buffer in frames {
+///     let message = EncodedMessageRef::from_bytes(&buffer).unwrap();
+///     values.push(decoder.decode_ref(&message, &context).unwrap().value);
+///     // `message` ends here, `buffer` can be dropped
+/// }
+/// assert_eq!(values, [20.0, 21.0, 22.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedMessageRef<'a> {
+    /// Message header
+    pub header: MessageHeader,
+    /// Encoded payload, borrowed from the message bytes
+    pub payload: &'a [u8],
+}
+
+impl<'a> EncodedMessageRef<'a> {
+    /// Create a message from a header and a borrowed payload
+    pub fn new(header: MessageHeader, payload: &'a [u8]) -> Self {
+        Self { header, payload }
+    }
+
+    /// Parse a message without copying its payload
+    pub fn from_bytes(bytes: &'a [u8]) -> Option<Self> {
+        Self::from_bytes_in(bytes, &WireDialect::Standard)
+    }
+
+    /// Parse a message in the layout of `dialect`
+    pub fn from_bytes_in(bytes: &'a [u8], dialect: &WireDialect) -> Option<Self> {
+        let (header, header_len) = MessageHeader::parse_in(bytes, dialect)?;
+        Some(Self::new(header, &bytes[header_len..]))
+    }
+
+    /// Parse a message followed by its checksum, verifying it, without
+    /// copying the payload
+    pub fn from_bytes_with_checksum(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        Self::from_bytes_with_checksum_in(bytes, &WireDialect::Standard)
+    }
+
+    /// Parse a message followed by its checksum in the layout of
+    /// `dialect`, verifying it
+    pub fn from_bytes_with_checksum_in(
+        bytes: &'a [u8],
+        dialect: &WireDialect,
+    ) -> Result<Self, DecodeError> {
+        if bytes.len() < MessageHeader::MIN_SIZE + CHECKSUM_SIZE {
             return Err(DecodeError::BufferTooShort {
-                needed: MessageHeader::SIZE + CHECKSUM_SIZE,
+                needed: MessageHeader::MIN_SIZE + CHECKSUM_SIZE,
                 available: bytes.len(),
             });
         }
 
         let checksum_offset = bytes.len() - CHECKSUM_SIZE;
-        let expected = u32::from_be_bytes(bytes[checksum_offset..].try_into().unwrap());
+        let expected = dialect
+            .read_checksum(&bytes[checksum_offset..])
+            .ok_or(DecodeError::InvalidHeader)?;
 
-        let message =
-            Self::from_bytes(&bytes[..checksum_offset]).ok_or(DecodeError::InvalidHeader)?;
+        let message = Self::from_bytes_in(&bytes[..checksum_offset], dialect)
+            .ok_or(DecodeError::InvalidHeader)?;
 
-        let actual = message.compute_checksum();
+        // Hash the received bytes: a compressed timestamp varint may be
+        // encoded non-minimally and would not survive re-serialization
+        let algorithm = message.header.checksum_algorithm();
+        let actual = algorithm.checksum(&bytes[..checksum_offset]);
 
         if actual != expected {
-            return Err(DecodeError::InvalidChecksum { expected, actual });
+            return Err(algorithm.mismatch(expected, actual));
         }
 
         Ok(message)
     }
+
+    /// Copy the payload into an owned [`EncodedMessage`]
+    pub fn to_owned(&self) -> EncodedMessage {
+        EncodedMessage::new(self.header.clone(), self.payload.to_vec())
+    }
+
+    /// Total size of the message in bytes
+    pub fn len(&self) -> usize {
+        self.header.encoded_len() + self.payload.len()
+    }
+
+    /// Check if the message is empty (no payload)
+    pub fn is_empty(&self) -> bool {
+        self.payload.is_empty()
+    }
+
+    /// Check if this is a confirmation of an unchanged value
+    /// (see [`EncodedMessage::is_confirmation`])
+    pub fn is_confirmation(&self) -> bool {
+        self.header.message_type == MessageType::Heartbeat && !self.payload.is_empty()
+    }
+
+    /// Check if this message announces a [`TransmissionPlan`]
+    pub fn is_plan(&self) -> bool {
+        self.header.message_type == MessageType::Data
+            && self.encoding_type() == Some(EncodingType::Plan)
+    }
+
+    /// Check if this message carries a composite of channel values
+    pub fn is_composite(&self) -> bool {
+        self.header.message_type == MessageType::Data
+            && self.encoding_type() == Some(EncodingType::Composite)
+    }
+
+    /// Get the encoding type from the payload (first byte after source_id varint)
+    pub fn encoding_type(&self) -> Option<EncodingType> {
+        // Payload format: source_id (varint) + encoding_type (1 byte) + value
+        // Decode the varint to find where the encoding byte starts.
+        let mut pos = 0;
+        while pos < self.payload.len() {
+            let byte = self.payload[pos];
+            pos += 1;
+            if byte & 0x80 == 0 {
+                // End of varint — next byte is the encoding type
+                return self
+                    .payload
+                    .get(pos)
+                    .and_then(|&b| EncodingType::from_u8(b));
+            }
+        }
+        None
+    }
+
+    /// Serialize the entire message to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_in(&WireDialect::Standard)
+    }
+
+    /// Serialize message in the layout of `dialect`
+    pub fn to_bytes_in(&self, dialect: &WireDialect) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&self.header.to_bytes_in(dialect));
+        bytes.extend_from_slice(self.payload);
+        bytes
+    }
+}
+
+impl<'a> From<&'a EncodedMessage> for EncodedMessageRef<'a> {
+    fn from(message: &'a EncodedMessage) -> Self {
+        message.as_borrowed()
+    }
 }
 
 /// Decoded data result
@@ -571,7 +1866,9 @@ impl EncodedMessage {
 pub struct DecodedData {
     /// Source identifier
     pub source_id: u32,
-    /// Timestamp from header
+    /// Timestamp from header: in `timestamp_unit` when the sender
+    /// recorded one ([`MessageHeader::timestamp_in_unit`]), otherwise in
+    /// seconds
     pub timestamp: u64,
     /// Decoded value
     pub value: f64,
@@ -579,6 +1876,21 @@ pub struct DecodedData {
     pub priority: Priority,
     /// Whether deferred data is available
     pub deferred_available: bool,
+    /// Whether the priority was set by the application
+    /// ([`MessageHeader::is_operator_override`])
+    pub operator_override: bool,
+    /// Time elapsed since `timestamp` when decoded, in ms; only set by
+    /// [`Decoder::decode_with_now`](crate::Decoder::decode_with_now)
+    pub age_ms: Option<u64>,
+    /// Whether the message is older than its priority's staleness
+    /// threshold ([`crate::decoder::StaleThreshold`])
+    pub stale: bool,
+    /// Timestamp unit recorded by the sender
+    /// ([`MessageHeader::timestamp_unit`])
+    pub timestamp_unit: Option<TimestampUnit>,
+    /// Whether the sender found the source stuck
+    /// ([`MessageHeader::is_suspect`])
+    pub suspect: bool,
 }
 
 impl DecodedData {
@@ -590,6 +1902,113 @@ impl DecodedData {
             value,
             priority,
             deferred_available: false,
+            operator_override: false,
+            age_ms: None,
+            stale: false,
+            timestamp_unit: None,
+            suspect: false,
+        }
+    }
+
+    /// Timestamp in microseconds, whatever its unit (saturating)
+    pub fn timestamp_micros(&self) -> i64 {
+        let timestamp = i64::try_from(self.timestamp).unwrap_or(i64::MAX);
+        self.timestamp_unit
+            .unwrap_or(TimestampUnit::Seconds)
+            .to_micros(timestamp)
+    }
+}
+
+/// One channel of a decoded composite message
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeValue {
+    /// Channel identifier
+    pub channel_id: u32,
+    /// Decoded value, or why this entry could not be decoded
+    pub value: core::result::Result<f64, DecodeError>,
+}
+
+/// Decoded composite message (see [`crate::Decoder::decode_composite`])
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedComposite {
+    /// Emitter identifier
+    pub source_id: u32,
+    /// Timestamp shared by every value
+    pub timestamp: u64,
+    /// Priority shared by every value
+    pub priority: Priority,
+    /// One value per entry, in message order
+    pub values: Vec<CompositeValue>,
+}
+
+/// Item decoded from a single-source message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decoded {
+    /// A measurement
+    Value(DecodedData),
+    /// A transmission plan was recorded
+    ///
+    /// Carries no value: the following plan-relative frames of the source
+    /// decode against it.
+    Plan {
+        /// Source identifier
+        source_id: u32,
+        /// Plan identifier
+        plan_id: u8,
+        /// Number of plan-relative frames covered
+        messages: u8,
+    },
+    /// Receipt of a reading whose value did not change
+    ///
+    /// Carries no value: the reading equals the one sent in message
+    /// `ref_seq`, so storage can record the receipt without duplicating
+    /// the measurement.
+    Confirmation {
+        /// Source identifier
+        source_id: u32,
+        /// Timestamp of the confirmed reading (seconds)
+        timestamp: u64,
+        /// Sequence number of the message carrying the value
+        ref_seq: u16,
+    },
+}
+
+/// Quality indicator for a decoded value
+///
+/// The wire format does not carry quality yet, so the decoders currently
+/// report `None`; the type exists so callers can be written against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quality {
+    /// Value is trustworthy
+    Good,
+    /// Value is usable but may be inaccurate
+    Uncertain,
+    /// Value should not be used
+    Bad,
+}
+
+/// One value of a decoded multi-value message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodedValue {
+    /// Channel name identifier
+    pub name_id: u16,
+    /// Decoded value
+    pub value: f64,
+    /// Timestamp from the frame header (seconds), `None` when the header
+    /// only carries a compressed timestamp
+    pub timestamp: Option<u64>,
+    /// Value quality, `None` when unknown
+    pub quality: Option<Quality>,
+}
+
+impl DecodedValue {
+    /// Create a decoded value with no timestamp or quality
+    pub fn new(name_id: u16, value: f64) -> Self {
+        Self {
+            name_id,
+            value,
+            timestamp: None,
+            quality: None,
         }
     }
 }
@@ -615,10 +2034,22 @@ mod tests {
         assert!(!Priority::P5Disposable.should_transmit());
     }
 
+    #[test]
+    fn test_checksum_policy_applies_to() {
+        assert!(!ChecksumPolicy::Never.applies_to(Priority::P1Critical));
+        assert!(ChecksumPolicy::Always.applies_to(Priority::P5Disposable));
+
+        let policy = ChecksumPolicy::ForPriorityAtOrAbove(Priority::P2Important);
+        assert!(policy.applies_to(Priority::P1Critical));
+        assert!(policy.applies_to(Priority::P2Important));
+        assert!(!policy.applies_to(Priority::P3Normal));
+        assert!(!policy.applies_to(Priority::P4Deferred));
+    }
+
     #[test]
     fn test_header_byte_roundtrip() {
         let header = MessageHeader {
-            version: 1,
+            layout: HeaderLayout::default(),
             message_type: MessageType::Data,
             priority: Priority::P2Important,
             sequence: 0,
@@ -637,7 +2068,7 @@ mod tests {
     #[test]
     fn test_header_serialization() {
         let header = MessageHeader {
-            version: 1,
+            layout: HeaderLayout::default(),
             message_type: MessageType::Sync,
             priority: Priority::P1Critical,
             sequence: 12345,
@@ -648,7 +2079,7 @@ mod tests {
         let bytes = header.to_bytes();
         let restored = MessageHeader::from_bytes(&bytes).unwrap();
 
-        assert_eq!(header.version, restored.version);
+        assert_eq!(header.layout, restored.layout);
         assert_eq!(header.message_type, restored.message_type);
         assert_eq!(header.priority, restored.priority);
         assert_eq!(header.sequence, restored.sequence);
@@ -705,7 +2136,7 @@ mod tests {
     fn test_checksum_roundtrip() {
         let message = EncodedMessage {
             header: MessageHeader {
-                version: 1,
+                layout: HeaderLayout::default(),
                 message_type: MessageType::Data,
                 priority: Priority::P2Important,
                 sequence: 42,
@@ -739,6 +2170,107 @@ mod tests {
         assert!(matches!(result, Err(DecodeError::InvalidChecksum { .. })));
     }
 
+    #[test]
+    fn test_compressed_timestamp_header_roundtrip() {
+        let mut header = MessageHeader {
+            layout: HeaderLayout::Plain(TimestampFormat::Delta),
+            message_type: MessageType::Data,
+            priority: Priority::P3Normal,
+            sequence: 7,
+            timestamp: TimestampFormat::pack_delta(30),
+            context_version: 0x00ABCDEF,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), MessageHeader::MIN_SIZE);
+        assert_eq!(header.encoded_len(), bytes.len());
+        let (restored, len) = MessageHeader::parse(&bytes).unwrap();
+        assert_eq!(restored, header);
+        assert_eq!(len, bytes.len());
+        assert_eq!(TimestampFormat::unpack_delta(restored.timestamp), 30);
+        assert_eq!(
+            TimestampFormat::unpack_delta(TimestampFormat::pack_delta(-3)),
+            -3
+        );
+
+        header.layout = HeaderLayout::Plain(TimestampFormat::Truncated);
+        header.timestamp = TimestampFormat::pack_truncated(0x1234_5678, 16);
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), MessageHeader::SIZE - 1);
+        let restored = MessageHeader::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            TimestampFormat::unpack_truncated(restored.timestamp),
+            (0x5678, 16)
+        );
+    }
+
+    #[test]
+    fn test_sub_second_extension() {
+        let mut extension = HeaderExtension::new(WireVersion::CURRENT);
+        extension.flags = TimestampUnit::Millis.to_flags();
+        extension.subsec = 999;
+        let mut header = MessageHeader::new(MessageType::Data, Priority::P3Normal);
+        header.layout = HeaderLayout::Extended(extension);
+        header.timestamp = 1_700_000_000;
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), MessageHeader::SIZE + HeaderExtension::SIZE + 2);
+        assert_eq!(&bytes[3..5], &999u16.to_be_bytes());
+        assert_eq!(
+            MessageHeader::parse(&bytes),
+            Some((header.clone(), bytes.len()))
+        );
+        assert_eq!(header.timestamp_in_unit(), Some(1_700_000_000_999));
+        assert!(extension.check().is_ok());
+
+        extension.subsec = 1_000;
+        assert_eq!(extension.check(), Err(DecodeError::InvalidHeader));
+    }
+
+    #[test]
+    fn test_extended_header_roundtrip() {
+        let header = MessageHeader {
+            layout: HeaderLayout::Extended(HeaderExtension {
+                version: WireVersion::new(1, 4),
+                flags: 0x01,
+                subsec: 0,
+            }),
+            message_type: MessageType::Data,
+            priority: Priority::P2Important,
+            sequence: 300,
+            timestamp: 1_700_000_000,
+            context_version: 9,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), MessageHeader::SIZE + HeaderExtension::SIZE);
+        assert_eq!(header.encoded_len(), bytes.len());
+        assert_eq!(bytes[0] >> 6, MessageHeader::EXTENDED_VERSION);
+        assert_eq!(&bytes[1..3], &[0x14, 0x01]);
+        let (restored, len) = MessageHeader::parse(&bytes).unwrap();
+        assert_eq!(restored, header);
+        assert_eq!(len, bytes.len());
+        assert_eq!(restored.wire_version(), WireVersion::new(1, 4));
+        assert!(MessageHeader::parse(&bytes[..len - 1]).is_none());
+    }
+
+    #[test]
+    fn test_header_extension_check() {
+        let mut ext = HeaderExtension::new(WireVersion::new(1, 9));
+        ext.flags = !CRITICAL_FLAGS_MASK;
+        assert!(ext.check().is_ok());
+
+        ext.flags = 0x80;
+        assert_eq!(
+            ext.check(),
+            Err(DecodeError::UnsupportedFlags { flags: 0x80 })
+        );
+
+        let ext = HeaderExtension::new(WireVersion::new(2, 0));
+        assert_eq!(
+            ext.check(),
+            Err(DecodeError::UnsupportedVersion { ours: 1, theirs: 2 })
+        );
+        assert_eq!(MessageHeader::default().wire_version(), WireVersion::LEGACY);
+    }
+
     #[test]
     fn test_checksum_buffer_too_short() {
         let short_bytes = vec![0u8; MessageHeader::SIZE]; // No checksum
@@ -776,6 +2308,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_varint_limits() {
+        for value in [0, 127, 128, u64::from(u32::MAX), u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(value, &mut bytes);
+            assert_eq!(read_varint_u64(&bytes), Some((value, bytes.len())));
+            assert_eq!(
+                read_varint(&bytes),
+                u32::try_from(value).ok().map(|v| (v, bytes.len()))
+            );
+        }
+        // Overflowing u64, longer than 10 bytes, truncated
+        let mut overflow = vec![0xFF; 9];
+        overflow.push(0x02);
+        assert_eq!(read_varint_u64(&overflow), None);
+        assert_eq!(read_varint_u64(&[0x80; 11]), None);
+        assert_eq!(read_varint_u64(&[0x80, 0x80]), None);
+        // Non-canonical but within 5 bytes
+        assert_eq!(read_varint(&[0x81, 0x80, 0x00]), Some((1, 3)));
+    }
+
     #[test]
     fn test_classify_compact_marker() {
         assert_eq!(classify_compact_marker(0xA1), Some(false));
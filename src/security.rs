@@ -8,7 +8,8 @@
 //!
 //! Provides authentication, encryption, and audit capabilities:
 //! - Security configuration
-//! - Audit logging with configurable backends
+//! - Audit logging with configurable backends, events carrying structured
+//!   key-value fields
 //! - Rate limiting using token bucket algorithm
 //! - Certificate validation helpers
 
@@ -77,6 +78,11 @@ impl SecurityConfig {
 
 /// Audit event types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "SCREAMING_SNAKE_CASE")
+)]
 pub enum AuditEventType {
     /// New connection established
     ConnectionEstablished,
@@ -104,6 +110,8 @@ pub enum AuditEventType {
     EmitterRegistered,
     /// Emitter removed
     EmitterRemoved,
+    /// A buffer started dropping data
+    BufferOverflow,
 }
 
 impl std::fmt::Display for AuditEventType {
@@ -122,12 +130,14 @@ impl std::fmt::Display for AuditEventType {
             AuditEventType::ConfigChanged => write!(f, "CONFIG_CHANGED"),
             AuditEventType::EmitterRegistered => write!(f, "EMITTER_REGISTERED"),
             AuditEventType::EmitterRemoved => write!(f, "EMITTER_REMOVED"),
+            AuditEventType::BufferOverflow => write!(f, "BUFFER_OVERFLOW"),
         }
     }
 }
 
 /// Severity levels for audit events
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Severity {
     /// Informational event
     Info = 1,
@@ -154,8 +164,99 @@ impl From<u8> for Severity {
     }
 }
 
+/// Value of a structured audit event field
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(untagged))]
+pub enum FieldValue {
+    /// Text
+    Str(String),
+    /// Integer; unsigned values above `i64::MAX` saturate
+    Int(i64),
+    /// Floating-point number
+    Float(f64),
+    /// Flag
+    Bool(bool),
+}
+
+impl std::fmt::Display for FieldValue {
+    /// Formats the value for a `key=value` log pair, quoting text that
+    /// is empty or contains whitespace, `=` or `"`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Str(s)
+                if s.is_empty()
+                    || s.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') =>
+            {
+                write!(f, "{:?}", s)
+            }
+            FieldValue::Str(s) => write!(f, "{}", s),
+            FieldValue::Int(v) => write!(f, "{}", v),
+            FieldValue::Float(v) => write!(f, "{}", v),
+            FieldValue::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<f32> for FieldValue {
+    fn from(value: f32) -> Self {
+        FieldValue::Float(value as f64)
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        FieldValue::Float(value)
+    }
+}
+
+macro_rules! field_value_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for FieldValue {
+                fn from(value: $int) -> Self {
+                    FieldValue::Int(i64::try_from(value).unwrap_or(i64::MAX))
+                }
+            }
+        )*
+    };
+}
+
+field_value_from_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+/// Serialize `(key, value)` pairs as a JSON-style map
+#[cfg(feature = "serde")]
+fn serialize_fields<S: serde::Serializer>(
+    fields: &[(String, FieldValue)],
+    serializer: S,
+) -> core::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(fields.iter().map(|(key, value)| (key, value)))
+}
+
 /// Audit event
+///
+/// `details` is the human-readable summary; `fields` carry the same facts
+/// (channel, sizes, versions...) as typed key-value pairs that filters and
+/// log pipelines can match without parsing the text.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AuditEvent {
     /// Timestamp (Unix epoch seconds)
     pub timestamp: u64,
@@ -163,10 +264,15 @@ pub struct AuditEvent {
     pub event_type: AuditEventType,
     /// Emitter ID (if applicable)
     pub emitter_id: Option<u32>,
+    /// Tenant owning the emitter (if applicable)
+    pub tenant_id: Option<u32>,
     /// Additional details
     pub details: String,
     /// Severity level
     pub severity: Severity,
+    /// Structured fields, in insertion order with unique keys
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_fields"))]
+    pub fields: Vec<(String, FieldValue)>,
 }
 
 impl AuditEvent {
@@ -179,8 +285,10 @@ impl AuditEvent {
                 .unwrap_or(0),
             event_type,
             emitter_id: None,
+            tenant_id: None,
             details: details.into(),
             severity: Severity::Info,
+            fields: Vec::new(),
         }
     }
 
@@ -194,8 +302,10 @@ impl AuditEvent {
             timestamp,
             event_type,
             emitter_id: None,
+            tenant_id: None,
             details: details.into(),
             severity: Severity::Info,
+            fields: Vec::new(),
         }
     }
 
@@ -205,6 +315,12 @@ impl AuditEvent {
         self
     }
 
+    /// Set the tenant ID
+    pub fn with_tenant(mut self, tenant_id: u32) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
     /// Set the severity level
     pub fn with_severity(mut self, severity: Severity) -> Self {
         self.severity = severity;
@@ -217,15 +333,52 @@ impl AuditEvent {
         self
     }
 
+    /// Set a structured field, replacing any previous value of `key`
+    ///
+    /// ```
+    /// use alec::security::{AuditEvent, AuditEventType, FieldValue};
+    ///
+    /// let event = AuditEvent::new(AuditEventType::MessageReceived, "Frame received")
+    ///     .with_field("channel", "temp")
+    ///     .with_field("bytes", 242);
+    /// assert_eq!(event.field("bytes"), Some(&FieldValue::Int(242)));
+    /// ```
+    pub fn with_field(mut self, key: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        let key = key.into();
+        let value = value.into();
+        match self.fields.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.fields.push((key, value)),
+        }
+        self
+    }
+
+    /// Value of a structured field
+    pub fn field(&self, key: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
     /// Format as a log line
+    ///
+    /// Fields follow the tenant and emitter as `key=value` pairs, before
+    /// the details.
     pub fn to_log_line(&self) -> String {
         let emitter = self
             .emitter_id
             .map(|id| format!(" emitter={}", id))
             .unwrap_or_default();
+        let tenant = self
+            .tenant_id
+            .map(|id| format!(" tenant={}", id))
+            .unwrap_or_default();
+        let fields: String = self
+            .fields
+            .iter()
+            .map(|(key, value)| format!(" {}={}", key, value))
+            .collect();
         format!(
-            "[{}] {:?} {}{} - {}",
-            self.timestamp, self.severity, self.event_type, emitter, self.details
+            "[{}] {:?} {}{}{}{} - {}",
+            self.timestamp, self.severity, self.event_type, tenant, emitter, fields, self.details
         )
     }
 }
@@ -251,15 +404,25 @@ pub struct AuditFilter {
     pub event_type: Option<AuditEventType>,
     /// Filter by emitter ID
     pub emitter_id: Option<u32>,
+    /// Filter by tenant ID
+    pub tenant_id: Option<u32>,
     /// Filter by minimum severity
     pub min_severity: Option<Severity>,
     /// Filter by time range (start)
     pub from_timestamp: Option<u64>,
     /// Filter by time range (end)
     pub to_timestamp: Option<u64>,
+    /// Filter by field values: the event must hold every pair
+    pub fields: Vec<(String, FieldValue)>,
 }
 
 impl AuditFilter {
+    /// Also require field `key` to equal `value`
+    pub fn with_field(mut self, key: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        self.fields.push((key.into(), value.into()));
+        self
+    }
+
     /// Check if an event matches this filter
     pub fn matches(&self, event: &AuditEvent) -> bool {
         if let Some(et) = self.event_type {
@@ -272,6 +435,11 @@ impl AuditFilter {
                 return false;
             }
         }
+        if let Some(tid) = self.tenant_id {
+            if event.tenant_id != Some(tid) {
+                return false;
+            }
+        }
         if let Some(min_sev) = self.min_severity {
             if event.severity < min_sev {
                 return false;
@@ -287,7 +455,9 @@ impl AuditFilter {
                 return false;
             }
         }
-        true
+        self.fields
+            .iter()
+            .all(|(key, value)| event.field(key) == Some(value))
     }
 }
 
@@ -381,6 +551,18 @@ impl AuditLogger for MemoryAuditLogger {
 }
 
 /// Rate limiter using token bucket algorithm
+///
+/// Emitters not updated for `idle_timeout_secs` are evicted, either by
+/// [`RateLimiter::evict_idle`] or opportunistically by
+/// [`RateLimiter::check`] (at most once per timeout period, and whenever
+/// more than `max_tracked` emitters are tracked). An evicted emitter that
+/// returns starts again with a full burst, which is what it would have
+/// refilled to anyway as long as the timeout is at least `burst / rate`
+/// seconds.
+///
+/// If the cap is still exceeded once idle emitters are gone (many emitters
+/// active at once), the least recently updated ones are evicted too; they
+/// also get a fresh burst when they return.
 #[derive(Debug)]
 pub struct RateLimiter {
     /// Tokens per second
@@ -391,9 +573,20 @@ pub struct RateLimiter {
     tokens: HashMap<u32, f64>,
     /// Last update time per emitter (in seconds)
     last_update: HashMap<u32, u64>,
+    /// Tracked emitters above which `check` evicts
+    max_tracked: usize,
+    /// Time without update after which an emitter is idle (seconds)
+    idle_timeout_secs: u64,
+    /// Time of the last opportunistic eviction (seconds)
+    last_eviction: u64,
 }
 
 impl RateLimiter {
+    /// Default cap on tracked emitters
+    pub const DEFAULT_MAX_TRACKED: usize = 10_000;
+    /// Default idle timeout (seconds)
+    pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 300;
+
     /// Create a new rate limiter
     ///
     /// # Arguments
@@ -405,13 +598,40 @@ impl RateLimiter {
             burst: burst as f64,
             tokens: HashMap::new(),
             last_update: HashMap::new(),
+            max_tracked: Self::DEFAULT_MAX_TRACKED,
+            idle_timeout_secs: Self::DEFAULT_IDLE_TIMEOUT_SECS,
+            last_eviction: 0,
         }
     }
 
+    /// Set the number of tracked emitters above which `check` evicts
+    pub fn with_max_tracked(mut self, max_tracked: usize) -> Self {
+        self.max_tracked = max_tracked.max(1);
+        self
+    }
+
+    /// Set the time without update after which an emitter is idle
+    pub fn with_idle_timeout(mut self, idle_timeout_secs: u64) -> Self {
+        self.idle_timeout_secs = idle_timeout_secs.max(1);
+        self
+    }
+
+    /// Cap on tracked emitters
+    pub fn max_tracked(&self) -> usize {
+        self.max_tracked
+    }
+
+    /// Idle timeout (seconds)
+    pub fn idle_timeout_secs(&self) -> u64 {
+        self.idle_timeout_secs
+    }
+
     /// Check if a request is allowed for an emitter
     ///
     /// Returns true if allowed (consumes a token), false if rate limited
     pub fn check(&mut self, emitter_id: u32, now_secs: u64) -> bool {
+        self.maybe_evict(emitter_id, now_secs);
+
         let tokens = self.tokens.entry(emitter_id).or_insert(self.burst);
         let last = self.last_update.entry(emitter_id).or_insert(now_secs);
 
@@ -463,10 +683,66 @@ impl RateLimiter {
         self.last_update.clear();
     }
 
+    /// Limiter with the same settings and no tracked emitters
+    fn empty_copy(&self) -> Self {
+        Self {
+            rate: self.rate,
+            burst: self.burst,
+            tokens: HashMap::new(),
+            last_update: HashMap::new(),
+            max_tracked: self.max_tracked,
+            idle_timeout_secs: self.idle_timeout_secs,
+            last_eviction: 0,
+        }
+    }
+
     /// Get number of tracked emitters
     pub fn tracked_count(&self) -> usize {
         self.tokens.len()
     }
+
+    /// Evict emitters not updated within the idle timeout
+    ///
+    /// Returns the number of emitters evicted.
+    pub fn evict_idle(&mut self, now_secs: u64) -> usize {
+        let before = self.tokens.len();
+        let timeout = self.idle_timeout_secs;
+        self.last_update
+            .retain(|_, last| now_secs.saturating_sub(*last) < timeout);
+        self.tokens
+            .retain(|id, _| self.last_update.contains_key(id));
+        self.last_eviction = now_secs;
+        before - self.tokens.len()
+    }
+
+    /// Evict before tracking `emitter_id` if a sweep is due
+    fn maybe_evict(&mut self, emitter_id: u32, now_secs: u64) {
+        let new_emitter = !self.tokens.contains_key(&emitter_id);
+        let over_cap = new_emitter && self.tokens.len() >= self.max_tracked;
+        let sweep_due = now_secs.saturating_sub(self.last_eviction) >= self.idle_timeout_secs;
+        if !over_cap && !sweep_due {
+            return;
+        }
+
+        self.evict_idle(now_secs);
+        if !new_emitter || self.tokens.len() < self.max_tracked {
+            return;
+        }
+
+        // Every tracked emitter is active: drop the least recently updated
+        // tenth so the next sweeps are not triggered on every call
+        let mut by_age: Vec<(u64, u32)> = self
+            .last_update
+            .iter()
+            .map(|(&id, &last)| (last, id))
+            .collect();
+        by_age.sort_unstable();
+        let excess = self.tokens.len() + 1 - self.max_tracked;
+        let count = excess.max(self.max_tracked / 10).min(by_age.len());
+        for &(_, id) in &by_age[..count] {
+            self.reset(id);
+        }
+    }
 }
 
 /// Certificate validation result
@@ -531,6 +807,9 @@ pub struct SecurityContext {
     pub config: SecurityConfig,
     /// Rate limiter (if enabled)
     pub rate_limiter: Option<RateLimiter>,
+    /// Rate limiters of tenants other than the default one, created from
+    /// `rate_limiter`'s settings on first use
+    tenant_rate_limiters: HashMap<u32, RateLimiter>,
     /// Audit logger (if enabled)
     audit_logger: Option<Box<dyn AuditLogger>>,
 }
@@ -540,6 +819,7 @@ impl std::fmt::Debug for SecurityContext {
         f.debug_struct("SecurityContext")
             .field("config", &self.config)
             .field("rate_limiter", &self.rate_limiter)
+            .field("tenant_rate_limiters", &self.tenant_rate_limiters.len())
             .field("audit_logger", &self.audit_logger.is_some())
             .finish()
     }
@@ -555,6 +835,7 @@ impl SecurityContext {
         Self {
             config,
             rate_limiter,
+            tenant_rate_limiters: HashMap::new(),
             audit_logger: None,
         }
     }
@@ -588,6 +869,27 @@ impl SecurityContext {
         }
     }
 
+    /// Check rate limit for an emitter of a tenant
+    ///
+    /// Each tenant gets its own token buckets with the settings of
+    /// [`SecurityContext::rate_limiter`], so emitters sharing an id across
+    /// tenants do not throttle each other. `check_rate_limit` holds the
+    /// buckets of the fleet's default tenant.
+    pub fn check_tenant_rate_limit(
+        &mut self,
+        tenant_id: u32,
+        emitter_id: u32,
+        now_secs: u64,
+    ) -> bool {
+        let Some(ref base) = self.rate_limiter else {
+            return true; // No rate limiting configured
+        };
+        self.tenant_rate_limiters
+            .entry(tenant_id)
+            .or_insert_with(|| base.empty_copy())
+            .check(emitter_id, now_secs)
+    }
+
     /// Validate a certificate fingerprint
     pub fn validate_cert(&self, fingerprint: &str) -> CertValidation {
         if !self.config.mtls_required {
@@ -673,6 +975,60 @@ mod tests {
         assert!(limiter.check(2, 0));
     }
 
+    #[test]
+    fn test_rate_limiter_evict_idle() {
+        let mut limiter = RateLimiter::new(10, 5).with_idle_timeout(60);
+
+        for id in 0..10_000 {
+            assert!(limiter.check(id, 0));
+        }
+        assert_eq!(limiter.tracked_count(), 10_000);
+
+        assert_eq!(limiter.evict_idle(59), 0);
+        assert_eq!(limiter.evict_idle(60), 10_000);
+        assert_eq!(limiter.tracked_count(), 0);
+
+        // A returning emitter starts with a full burst
+        assert_eq!(limiter.remaining(0), 5.0);
+    }
+
+    #[test]
+    fn test_rate_limiter_active_emitter_not_evicted() {
+        let mut limiter = RateLimiter::new(1, 5)
+            .with_idle_timeout(60)
+            .with_max_tracked(100);
+
+        for now in 0..1_000u64 {
+            limiter.check(u32::MAX, now);
+            // A stream of short-lived emitters
+            limiter.check(now as u32, now);
+            assert!(limiter.tracked_count() <= 100);
+        }
+        limiter.evict_idle(1_000);
+
+        assert!(limiter.last_update.contains_key(&u32::MAX));
+        assert!(limiter.tracked_count() <= 60);
+    }
+
+    #[test]
+    fn test_rate_limiter_cap_evicts_oldest() {
+        let mut limiter = RateLimiter::new(10, 5)
+            .with_idle_timeout(3_600)
+            .with_max_tracked(100);
+
+        for id in 0..100 {
+            limiter.check(id, id as u64);
+        }
+        assert_eq!(limiter.tracked_count(), 100);
+
+        // No emitter is idle, so the oldest tenth makes room
+        limiter.check(1_000, 100);
+        assert_eq!(limiter.tracked_count(), 91);
+        assert!(!limiter.last_update.contains_key(&0));
+        assert!(limiter.last_update.contains_key(&99));
+        assert!(limiter.last_update.contains_key(&1_000));
+    }
+
     #[test]
     fn test_audit_logger() {
         let logger = MemoryAuditLogger::new(100);
@@ -798,6 +1154,97 @@ mod tests {
         assert!(line.contains("Invalid credentials"));
     }
 
+    #[test]
+    fn test_audit_event_fields() {
+        let event = AuditEvent::with_timestamp(AuditEventType::ContextSync, "Sync sent", 1_000)
+            .with_tenant(2)
+            .with_emitter(7)
+            .with_field("channel", "temp")
+            .with_field("bytes", 242usize)
+            .with_field("ratio", 0.5)
+            .with_field("full", false)
+            .with_field("version", 3u32)
+            .with_field("version", 4u32);
+
+        // Replacing a field keeps its position
+        let keys: Vec<&str> = event.fields.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["channel", "bytes", "ratio", "full", "version"]);
+        assert_eq!(event.field("version"), Some(&FieldValue::Int(4)));
+        assert_eq!(event.field("missing"), None);
+        assert_eq!(FieldValue::from(u64::MAX), FieldValue::Int(i64::MAX));
+
+        assert_eq!(
+            event.to_log_line(),
+            "[1000] Info CONTEXT_SYNC tenant=2 emitter=7 channel=temp bytes=242 ratio=0.5 \
+             full=false version=4 - Sync sent"
+        );
+        let quoted = AuditEvent::new(AuditEventType::Error, "x").with_field("error", "a b=\"c\"");
+        assert!(quoted.to_log_line().contains(r#" error="a b=\"c\"" "#));
+    }
+
+    #[test]
+    fn test_audit_filter_by_field() {
+        let logger = MemoryAuditLogger::new(100);
+        for (channel, bytes) in [("temp", 10), ("humid", 20), ("temp", 30)] {
+            logger.log(
+                AuditEvent::new(AuditEventType::BufferOverflow, "Buffer full")
+                    .with_field("channel", channel)
+                    .with_field("bytes", bytes),
+            );
+        }
+        logger.log(AuditEvent::new(AuditEventType::BufferOverflow, "No fields"));
+
+        let filter = AuditFilter::default().with_field("channel", "temp");
+        assert_eq!(logger.query(&filter).len(), 2);
+
+        let filter = AuditFilter {
+            event_type: Some(AuditEventType::BufferOverflow),
+            ..Default::default()
+        }
+        .with_field("channel", "temp")
+        .with_field("bytes", 30);
+        let results = logger.query(&filter);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].field("bytes"), Some(&FieldValue::Int(30)));
+
+        // Same number, other type
+        let filter = AuditFilter::default().with_field("bytes", 30.0);
+        assert!(logger.query(&filter).is_empty());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_audit_event_json() {
+        let event = AuditEvent::with_timestamp(AuditEventType::RateLimitExceeded, "Limited", 5)
+            .with_emitter(7)
+            .with_severity(Severity::Medium)
+            .with_field("channel", "temp")
+            .with_field("retry_after_ms", 1000)
+            .with_field("ratio", 0.25)
+            .with_field("burst", true);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestamp": 5,
+                "event_type": "RATE_LIMIT_EXCEEDED",
+                "emitter_id": 7,
+                "tenant_id": null,
+                "details": "Limited",
+                "severity": "Medium",
+                "fields": {
+                    "channel": "temp",
+                    "retry_after_ms": 1000,
+                    "ratio": 0.25,
+                    "burst": true
+                }
+            })
+        );
+        // One line per event for JSONL
+        assert!(!serde_json::to_string(&event).unwrap().contains('\n'));
+    }
+
     #[test]
     fn test_severity_ordering() {
         assert!(Severity::Info < Severity::Low);
@@ -0,0 +1,484 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Sequence number persistence
+//!
+//! An [`Encoder`](crate::Encoder) numbers its messages from zero, so a
+//! restarted emitter would reuse sequence numbers the receiver has
+//! already seen. With a [`SequenceStore`] attached through
+//! [`Encoder::set_sequence_store`](crate::Encoder::set_sequence_store),
+//! the encoder saves its next sequence number every
+//! [`SequencePersistence::save_interval`] messages and, on restart, resumes
+//! [`SequencePersistence::skip_ahead`] past the saved value so the
+//! numbers emitted after the last save are never reused.
+//!
+//! Provided stores:
+//! - [`InMemorySequenceStore`]: a map, for tests and simulations
+//! - [`FileSequenceStore`] (std): a small binary file rewritten on save
+//!
+//! On the receiving side, a [`SequenceTracker`] compares sequence numbers
+//! with serial-number arithmetic (RFC 1982 style): numbers wrap, and
+//! within a [`SequenceWindow`] a number ahead of the last one is newer,
+//! anything else an old or repeated message. A wrap from 65535 to 0 is
+//! then an ordinary next message, and a sequence far behind the last one
+//! is stale rather than a huge gap.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::error::Result;
+
+/// Durable storage for encoder sequence numbers
+///
+/// Values are the next sequence number to use, keyed by
+/// [`SequencePersistence::source_id`].
+pub trait SequenceStore: Send + Sync {
+    /// Last saved sequence number for `source_id`
+    fn load(&mut self, source_id: u32) -> Option<u32>;
+
+    /// Save `sequence` for `source_id`
+    ///
+    /// A failed save is retried with the next message.
+    fn save(&mut self, source_id: u32, sequence: u32) -> Result<()>;
+}
+
+/// When an encoder saves its sequence number and how far it skips on
+/// restart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequencePersistence {
+    /// Key of the encoder's counter in the store
+    ///
+    /// An encoder numbers the messages of all its sources with a single
+    /// counter, saved under this identifier of the emitter.
+    pub source_id: u32,
+    /// Messages between saves (0 saves after every message)
+    ///
+    /// Larger intervals spare flash wear at the cost of a larger
+    /// `skip_ahead`.
+    pub save_interval: u16,
+    /// Sequence numbers skipped on restart
+    ///
+    /// Never less than `save_interval`, so the numbers sent since the last
+    /// save are not reused. Keep it below 32768: sequence numbers wrap, and
+    /// a larger skip would look like a step back to the receiver.
+    pub skip_ahead: u16,
+}
+
+impl SequencePersistence {
+    /// Skip applied on restart, at least one save interval
+    pub fn effective_skip(&self) -> u16 {
+        self.skip_ahead.max(self.save_interval)
+    }
+}
+
+impl Default for SequencePersistence {
+    fn default() -> Self {
+        Self {
+            source_id: 0,
+            save_interval: 100,
+            skip_ahead: 1000,
+        }
+    }
+}
+
+/// Sequence store kept in memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InMemorySequenceStore {
+    sequences: BTreeMap<u32, u32>,
+    saves: u64,
+}
+
+impl InMemorySequenceStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Saved sequence number for `source_id`
+    pub fn get(&self, source_id: u32) -> Option<u32> {
+        self.sequences.get(&source_id).copied()
+    }
+
+    /// Number of saves since creation
+    pub fn saves(&self) -> u64 {
+        self.saves
+    }
+}
+
+impl SequenceStore for InMemorySequenceStore {
+    fn load(&mut self, source_id: u32) -> Option<u32> {
+        self.get(source_id)
+    }
+
+    fn save(&mut self, source_id: u32, sequence: u32) -> Result<()> {
+        self.sequences.insert(source_id, sequence);
+        self.saves += 1;
+        Ok(())
+    }
+}
+
+/// Width of the sequence numbers being compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SequenceWidth {
+    /// 16-bit numbers, as in the message header
+    U16,
+    /// 32-bit numbers
+    U32,
+}
+
+impl SequenceWidth {
+    /// Largest sequence number
+    pub fn max(self) -> u32 {
+        match self {
+            SequenceWidth::U16 => u16::MAX as u32,
+            SequenceWidth::U32 => u32::MAX,
+        }
+    }
+
+    /// Distance from `from` forward to `to`, modulo the width
+    pub fn distance(self, from: u32, to: u32) -> u32 {
+        to.wrapping_sub(from) & self.max()
+    }
+}
+
+/// How far ahead of the last sequence number a number counts as newer
+///
+/// Numbers up to `ahead` past the last one are newer (the gap between
+/// them counts as lost messages); the rest of the number space is behind
+/// it, so those messages are stale. The default keeps the last
+/// [`SequenceWindow::DEFAULT_BEHIND`] numbers behind and treats the rest
+/// as ahead, which tolerates long outages and the encoder's restart skip
+/// (see [`SequencePersistence::skip_ahead`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceWindow {
+    /// Width of the compared numbers
+    pub width: SequenceWidth,
+    /// Largest forward distance counted as newer (at least 1)
+    pub ahead: u32,
+}
+
+impl SequenceWindow {
+    /// Numbers behind the last one that [`SequenceWindow::new`] counts as
+    /// stale
+    pub const DEFAULT_BEHIND: u32 = 1024;
+
+    /// Window keeping [`Self::DEFAULT_BEHIND`] numbers behind
+    pub fn new(width: SequenceWidth) -> Self {
+        Self {
+            width,
+            ahead: width.max() - Self::DEFAULT_BEHIND,
+        }
+    }
+
+    /// RFC 1982 window: newer when less than half the number space ahead
+    pub fn half(width: SequenceWidth) -> Self {
+        Self {
+            width,
+            ahead: width.max() / 2,
+        }
+    }
+
+    /// Classify `sequence` against the last accepted number
+    pub fn classify(&self, last: u32, sequence: u32) -> SequenceStep {
+        let distance = self.width.distance(last, sequence);
+        if distance == 0 {
+            SequenceStep::Duplicate
+        } else if distance <= self.ahead.max(1) {
+            if distance == 1 {
+                SequenceStep::Next
+            } else {
+                SequenceStep::Gap {
+                    missing: distance - 1,
+                }
+            }
+        } else {
+            SequenceStep::Stale {
+                behind: self.width.max() - distance + 1,
+            }
+        }
+    }
+}
+
+impl Default for SequenceWindow {
+    fn default() -> Self {
+        Self::new(SequenceWidth::U16)
+    }
+}
+
+/// Where a sequence number falls relative to the last one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceStep {
+    /// First number seen
+    First,
+    /// The number right after the last one
+    Next,
+    /// Newer, with `missing` numbers skipped
+    Gap {
+        /// Numbers between the last one and this one
+        missing: u32,
+    },
+    /// The last number again
+    Duplicate,
+    /// Behind the last number by `behind` (a late or replayed message)
+    Stale {
+        /// Distance back from the last number
+        behind: u32,
+    },
+}
+
+impl SequenceStep {
+    /// Whether the number is the first or ahead of the last one
+    pub fn is_newer(&self) -> bool {
+        matches!(
+            self,
+            SequenceStep::First | SequenceStep::Next | SequenceStep::Gap { .. }
+        )
+    }
+}
+
+/// Counters of a [`SequenceTracker`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// Sequence numbers observed
+    pub observed: u64,
+    /// Newer numbers that skipped some
+    pub gaps: u64,
+    /// Numbers skipped by those gaps
+    pub missing: u64,
+    /// Repeats of the last number
+    pub duplicates: u64,
+    /// Numbers behind the last one
+    pub stale: u64,
+    /// Times the accepted numbers wrapped past the largest value
+    pub wraps: u64,
+}
+
+impl SequenceStats {
+    /// Count one observed number, `wrapped` if it was accepted past the
+    /// largest value
+    pub fn count(&mut self, step: SequenceStep, wrapped: bool) {
+        self.observed += 1;
+        match step {
+            SequenceStep::First | SequenceStep::Next => {}
+            SequenceStep::Gap { missing } => {
+                self.gaps += 1;
+                self.missing += missing as u64;
+            }
+            SequenceStep::Duplicate => self.duplicates += 1,
+            SequenceStep::Stale { .. } => self.stale += 1,
+        }
+        if wrapped {
+            self.wraps += 1;
+        }
+    }
+
+    /// Add the counters of `other`
+    pub fn merge(&mut self, other: &SequenceStats) {
+        self.observed += other.observed;
+        self.gaps += other.gaps;
+        self.missing += other.missing;
+        self.duplicates += other.duplicates;
+        self.stale += other.stale;
+        self.wraps += other.wraps;
+    }
+}
+
+/// Last sequence number of a stream, with gap, duplicate and wrap counts
+///
+/// Only newer numbers advance the tracker; duplicates and stale numbers
+/// are counted and otherwise ignored.
+///
+/// ```
+/// use alec::sequence::{SequenceStep, SequenceTracker, SequenceWidth, SequenceWindow};
+///
+/// let mut tracker = SequenceTracker::new(SequenceWindow::new(SequenceWidth::U16));
+/// for sequence in [65534, 65535, 0, 1] {
+///     tracker.observe(sequence);
+/// }
+/// assert_eq!(tracker.stats().gaps, 0);
+/// assert_eq!(tracker.stats().wraps, 1);
+/// assert_eq!(tracker.observe(0), SequenceStep::Stale { behind: 1 });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequenceTracker {
+    window: SequenceWindow,
+    last: Option<u32>,
+    stats: SequenceStats,
+}
+
+impl SequenceTracker {
+    /// Create a tracker that has seen nothing
+    pub fn new(window: SequenceWindow) -> Self {
+        Self {
+            window,
+            last: None,
+            stats: SequenceStats::default(),
+        }
+    }
+
+    /// Classify `sequence` and count it
+    pub fn observe(&mut self, sequence: u32) -> SequenceStep {
+        self.observe_wrapped(sequence).0
+    }
+
+    /// [`observe`](Self::observe), also telling whether the accepted
+    /// number wrapped past the largest value
+    pub fn observe_wrapped(&mut self, sequence: u32) -> (SequenceStep, bool) {
+        let sequence = sequence & self.window.width.max();
+        let (step, wrapped) = match self.last {
+            None => (SequenceStep::First, false),
+            Some(last) => {
+                let step = self.window.classify(last, sequence);
+                (step, sequence < last && step.is_newer())
+            }
+        };
+        if step.is_newer() {
+            self.last = Some(sequence);
+        }
+        self.stats.count(step, wrapped);
+        (step, wrapped)
+    }
+
+    /// Last accepted sequence number
+    pub fn last(&self) -> Option<u32> {
+        self.last
+    }
+
+    /// Counters since creation
+    pub fn stats(&self) -> &SequenceStats {
+        &self.stats
+    }
+
+    /// Comparison window
+    pub fn window(&self) -> SequenceWindow {
+        self.window
+    }
+
+    /// Compare the next numbers within `window`
+    pub fn set_window(&mut self, window: SequenceWindow) {
+        self.window = window;
+    }
+
+    /// Forget the last number, so the next one starts afresh; counters
+    /// are kept
+    pub fn restart(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(feature = "std-fs")]
+pub use file::FileSequenceStore;
+
+#[cfg(feature = "std-fs")]
+mod file {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::SequenceStore;
+    use crate::error::{AlecError, ContextError, Result};
+
+    /// Magic bytes of a sequence file
+    const MAGIC: [u8; 4] = *b"ASEQ";
+
+    /// Current sequence file format version
+    const FORMAT_VERSION: u8 = 1;
+
+    /// Sequence store backed by a file
+    ///
+    /// The file holds the magic `ASEQ`, a format version byte, then one
+    /// little-endian `(source_id: u32, sequence: u32)` pair per source. It
+    /// is rewritten through a temporary file and a rename on every save, so
+    /// a crash leaves either the old or the new map.
+    #[derive(Debug, Clone)]
+    pub struct FileSequenceStore {
+        path: PathBuf,
+        sequences: BTreeMap<u32, u32>,
+    }
+
+    impl FileSequenceStore {
+        /// Open the store at `path`, empty if the file does not exist
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let path = path.as_ref().to_path_buf();
+            let sequences = match fs::read(&path) {
+                Ok(bytes) => Self::parse(&bytes)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => {
+                    return Err(ContextError::SyncFailed {
+                        reason: format!("Failed to read sequence file: {}", e),
+                    }
+                    .into())
+                }
+            };
+            Ok(Self { path, sequences })
+        }
+
+        /// Path of the backing file
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn parse(bytes: &[u8]) -> Result<BTreeMap<u32, u32>> {
+            let invalid = |reason: &str| -> AlecError {
+                ContextError::SyncFailed {
+                    reason: format!("Invalid sequence file: {}", reason),
+                }
+                .into()
+            };
+            if bytes.len() < MAGIC.len() + 1 || bytes[..MAGIC.len()] != MAGIC {
+                return Err(invalid("bad magic"));
+            }
+            if bytes[MAGIC.len()] != FORMAT_VERSION {
+                return Err(invalid("unsupported version"));
+            }
+            let entries = &bytes[MAGIC.len() + 1..];
+            if entries.len() % 8 != 0 {
+                return Err(invalid("truncated entry"));
+            }
+            Ok(entries
+                .chunks_exact(8)
+                .map(|entry| {
+                    let source_id = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                    let sequence = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+                    (source_id, sequence)
+                })
+                .collect())
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + self.sequences.len() * 8);
+            bytes.extend_from_slice(&MAGIC);
+            bytes.push(FORMAT_VERSION);
+            for (source_id, sequence) in &self.sequences {
+                bytes.extend_from_slice(&source_id.to_le_bytes());
+                bytes.extend_from_slice(&sequence.to_le_bytes());
+            }
+            bytes
+        }
+    }
+
+    impl SequenceStore for FileSequenceStore {
+        fn load(&mut self, source_id: u32) -> Option<u32> {
+            self.sequences.get(&source_id).copied()
+        }
+
+        fn save(&mut self, source_id: u32, sequence: u32) -> Result<()> {
+            self.sequences.insert(source_id, sequence);
+            let mut tmp = self.path.clone().into_os_string();
+            tmp.push(".tmp");
+            fs::write(&tmp, self.to_bytes())
+                .and_then(|()| fs::rename(&tmp, &self.path))
+                .map_err(|e| {
+                    ContextError::SyncFailed {
+                        reason: format!("Failed to write sequence file: {}", e),
+                    }
+                    .into()
+                })
+        }
+    }
+}
@@ -0,0 +1,600 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Decoder session recording and replay
+//!
+//! A [`Decoder`] recording its session (see [`Decoder::start_recording`])
+//! appends every input it receives to a writer: message bytes, sync diffs
+//! and context imports applied through it, and configuration changes. A
+//! [`SessionReplayer`] rebuilds the decoder and its context from the log,
+//! one input at a time, reporting the context hash after each one so the
+//! input where two runs diverge can be found by bisection.
+//!
+//! Inputs are logged as received, framed but never re-serialized. Only
+//! the byte-level entry points are recorded: [`Decoder::decode_bytes`],
+//! [`Decoder::decode_with_now`], [`Decoder::decode_bytes_item`],
+//! [`Decoder::decode_buffer`], [`Decoder::decode_into`],
+//! [`Decoder::apply_sync_diff`], [`Decoder::import_context`] and
+//! [`Decoder::set_config`]. Start recording on a fresh decoder, as the
+//! replay starts from one.
+//!
+//! # Log Format
+//!
+//! ```text
+//! [magic: "ALSR"] [version: 1] [config_len: varint] [config]
+//! [context_len: varint] [context: preload format] [record...]
+//!
+//! record:
+//! [index: varint] [input: 1] [len: varint] [payload: len]
+//! ```
+//!
+//! Varints are LEB128. Indexes count from 0, one per input. The payload
+//! of a [`SessionInput::DecodeAt`] record is the receive time (8 bytes LE)
+//! followed by the message; the other inputs carry their bytes as given.
+//!
+//! # Example
+//!
+//! ```
+//! use alec::session::SessionReplayer;
+//! use alec::{Classifier, Context, Decoder, Encoder, RawData};
+//!
+//! let path = std::env::temp_dir().join("alec-session-example.log");
+//! let mut encoder = Encoder::new();
+//! let mut sender = Context::new();
+//!
+//! let mut receiver = Context::new();
+//! let mut decoder = Decoder::new();
+//! let log = std::fs::File::create(&path).unwrap();
+//! decoder.start_recording(log, &receiver).unwrap();
+//! for i in 0..10 {
+//!     let data = RawData::new(20.0 + i as f64, i * 1000);
+//!     let classification = Classifier::default().classify(&data, &sender);
+//!     let bytes = encoder.encode_to_bytes(&data, &classification, &sender);
+//!     sender.observe(&data);
+//!     decoder.decode_into(&bytes, &mut receiver).unwrap();
+//! }
+//! decoder.stop_recording().unwrap();
+//!
+//! let log = std::fs::File::open(&path).unwrap();
+//! let mut replayer = SessionReplayer::new(std::io::BufReader::new(log)).unwrap();
+//! assert_eq!(replayer.replay_to(9).unwrap(), receiver.hash());
+//! ```
+//!
+//! [`Decoder`]: crate::Decoder
+//! [`Decoder::start_recording`]: crate::Decoder::start_recording
+//! [`Decoder::decode_bytes`]: crate::Decoder::decode_bytes
+//! [`Decoder::decode_with_now`]: crate::Decoder::decode_with_now
+//! [`Decoder::decode_bytes_item`]: crate::Decoder::decode_bytes_item
+//! [`Decoder::decode_buffer`]: crate::Decoder::decode_buffer
+//! [`Decoder::decode_into`]: crate::Decoder::decode_into
+//! [`Decoder::apply_sync_diff`]: crate::Decoder::apply_sync_diff
+//! [`Decoder::import_context`]: crate::Decoder::import_context
+//! [`Decoder::set_config`]: crate::Decoder::set_config
+
+#[cfg(feature = "std")]
+pub use self::replay::{ReplayStep, SessionReplayer};
+
+#[cfg(feature = "std")]
+pub(crate) use self::record::SessionRecorder;
+
+/// Input recorded in a decoder session log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SessionInput {
+    /// Message given to [`Decoder::decode_bytes`](crate::Decoder::decode_bytes)
+    Decode = 0x01,
+    /// Message given to
+    /// [`Decoder::decode_with_now`](crate::Decoder::decode_with_now), with
+    /// its receive time
+    DecodeAt = 0x02,
+    /// Message given to
+    /// [`Decoder::decode_bytes_item`](crate::Decoder::decode_bytes_item)
+    DecodeItem = 0x03,
+    /// Buffer given to [`Decoder::decode_buffer`](crate::Decoder::decode_buffer)
+    DecodeBuffer = 0x04,
+    /// Message given to [`Decoder::decode_into`](crate::Decoder::decode_into)
+    DecodeInto = 0x05,
+    /// Sync diff given to
+    /// [`Decoder::apply_sync_diff`](crate::Decoder::apply_sync_diff)
+    SyncDiff = 0x10,
+    /// Context export given to
+    /// [`Decoder::import_context`](crate::Decoder::import_context)
+    Import = 0x11,
+    /// Configuration given to [`Decoder::set_config`](crate::Decoder::set_config)
+    Config = 0x20,
+}
+
+impl SessionInput {
+    /// Convert from the record byte
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(SessionInput::Decode),
+            0x02 => Some(SessionInput::DecodeAt),
+            0x03 => Some(SessionInput::DecodeItem),
+            0x04 => Some(SessionInput::DecodeBuffer),
+            0x05 => Some(SessionInput::DecodeInto),
+            0x10 => Some(SessionInput::SyncDiff),
+            0x11 => Some(SessionInput::Import),
+            0x20 => Some(SessionInput::Config),
+            _ => None,
+        }
+    }
+}
+
+/// Start of every session log
+#[cfg(feature = "std")]
+const MAGIC: &[u8; 4] = b"ALSR";
+
+/// Session log format version
+#[cfg(feature = "std")]
+const FORMAT_VERSION: u8 = 1;
+
+/// Serialize a decoder configuration
+#[cfg(feature = "std")]
+pub(crate) fn config_to_bytes(config: &crate::DecoderConfig) -> Vec<u8> {
+    use crate::protocol::{ByteOrder, ChecksumPolicy, HeaderField, TimestampUnit, WireDialect};
+
+    let mut out = Vec::with_capacity(64);
+    match config.checksum_policy {
+        ChecksumPolicy::Never => out.push(0),
+        ChecksumPolicy::Always => out.push(1),
+        ChecksumPolicy::ForPriorityAtOrAbove(priority) => {
+            out.push(2);
+            out.push(priority as u8);
+        }
+    }
+    out.extend_from_slice(&(config.max_values_per_message as u64).to_le_bytes());
+    out.extend_from_slice(&(config.max_expansion_bytes as u64).to_le_bytes());
+    out.extend_from_slice(&config.max_decode_micros.to_le_bytes());
+    for threshold in &config.stale_thresholds {
+        match threshold {
+            None => out.push(0),
+            Some(threshold) => {
+                out.push(match threshold.action {
+                    crate::StaleAction::Flag => 1,
+                    crate::StaleAction::Reject => 2,
+                });
+                out.extend_from_slice(&threshold.max_age_ms.to_le_bytes());
+            }
+        }
+    }
+    // Standard dialect: nothing, as in logs written before dialects
+    if let WireDialect::Legacy(profile) = config.dialect {
+        out.push(1);
+        out.push(match profile.byte_order() {
+            ByteOrder::BigEndian => 0,
+            ByteOrder::LittleEndian => 1,
+        });
+        out.extend(profile.field_order().iter().map(|field| match field {
+            HeaderField::Sequence => 0,
+            HeaderField::Timestamp => 1,
+            HeaderField::ContextVersion => 2,
+        }));
+    }
+    // No timestamp unit: nothing, as in logs written before units
+    if let Some(unit) = config.timestamp_unit {
+        out.push(2);
+        out.push(match unit {
+            TimestampUnit::Seconds => 0,
+            TimestampUnit::Millis => 1,
+            TimestampUnit::Micros => 2,
+        });
+    }
+    // No timestamp range: nothing, as in logs written before ranges
+    if let Some((min, max)) = config.timestamp_range {
+        out.push(3);
+        out.extend_from_slice(&min.to_le_bytes());
+        out.extend_from_slice(&max.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+mod record {
+    use std::io::{self, Write};
+
+    use super::{config_to_bytes, SessionInput, FORMAT_VERSION, MAGIC};
+    use crate::context::Context;
+    use crate::decoder::DecoderConfig;
+    use crate::error::Result;
+    use crate::protocol::write_varint;
+
+    /// Writer side of a session log, held by a recording decoder
+    pub(crate) struct SessionRecorder {
+        writer: Box<dyn Write + Send + Sync>,
+        next_index: u64,
+        /// Record header scratch space, reused across records
+        frame: Vec<u8>,
+        /// First write failure; nothing is written after it
+        error: Option<io::Error>,
+    }
+
+    impl SessionRecorder {
+        /// Write the log header: the starting configuration and context
+        pub(crate) fn start(
+            mut writer: Box<dyn Write + Send + Sync>,
+            config: &DecoderConfig,
+            context: &Context,
+        ) -> Result<Self> {
+            let config = config_to_bytes(config);
+            let context = context.to_preload_bytes("")?;
+            let mut header = MAGIC.to_vec();
+            header.push(FORMAT_VERSION);
+            write_varint(config.len() as u64, &mut header);
+            header.extend_from_slice(&config);
+            write_varint(context.len() as u64, &mut header);
+            writer
+                .write_all(&header)
+                .and_then(|()| writer.write_all(&context))
+                .map_err(|e| crate::AlecError::Protocol(format!("Session log: {}", e)))?;
+            Ok(Self {
+                writer,
+                next_index: 0,
+                frame: Vec::with_capacity(24),
+                error: None,
+            })
+        }
+
+        /// Append one input, its payload being `prefix` then `bytes`
+        pub(crate) fn log(&mut self, input: SessionInput, prefix: &[u8], bytes: &[u8]) {
+            if self.error.is_some() {
+                return;
+            }
+            self.frame.clear();
+            write_varint(self.next_index, &mut self.frame);
+            self.frame.push(input as u8);
+            write_varint((prefix.len() + bytes.len()) as u64, &mut self.frame);
+            self.frame.extend_from_slice(prefix);
+            let written = self
+                .writer
+                .write_all(&self.frame)
+                .and_then(|()| self.writer.write_all(bytes));
+            match written {
+                Ok(()) => self.next_index += 1,
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        /// Flush the log, returning the first write failure if any
+        pub(crate) fn finish(mut self) -> io::Result<()> {
+            match self.error.take() {
+                Some(e) => Err(e),
+                None => self.writer.flush(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod replay {
+    use std::io::Read;
+
+    use super::{SessionInput, FORMAT_VERSION, MAGIC};
+    use crate::context::Context;
+    use crate::decoder::{Decoder, DecoderConfig, StaleAction, StaleThreshold};
+    use crate::error::{AlecError, Result};
+    use crate::protocol::{
+        read_varint_u64, ByteOrder, ChecksumPolicy, DialectProfile, HeaderField, Priority,
+        TimestampUnit, WireDialect,
+    };
+    use crate::sequence::SequenceWindow;
+
+    fn invalid(reason: impl core::fmt::Display) -> AlecError {
+        AlecError::Protocol(format!("Invalid session log: {}", reason))
+    }
+
+    /// One input replayed by [`SessionReplayer::step`]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ReplayStep {
+        /// Index of the input in the session
+        pub index: u64,
+        /// What the input was
+        pub input: SessionInput,
+        /// Error the decoder returned for it, as during the session
+        pub error: Option<AlecError>,
+        /// Context hash once the input is applied
+        pub context_hash: u64,
+    }
+
+    /// Rebuilds a recorded decoder session, input by input
+    ///
+    /// Starts from a decoder with the recorded configuration and the
+    /// recorded starting context, then applies each logged input through
+    /// the same decoder method as during the session.
+    pub struct SessionReplayer<R: Read> {
+        reader: R,
+        decoder: Decoder,
+        context: Context,
+        next_index: u64,
+    }
+
+    impl<R: Read> SessionReplayer<R> {
+        /// Read the log header
+        ///
+        /// # Errors
+        ///
+        /// Fails with [`AlecError::Protocol`] if the log is unreadable or
+        /// of another format version, or with the preload error if the
+        /// starting context is invalid.
+        pub fn new(mut reader: R) -> Result<Self> {
+            let mut magic = [0u8; 5];
+            read_exact(&mut reader, &mut magic)?;
+            if &magic[..4] != MAGIC {
+                return Err(invalid("bad magic"));
+            }
+            if magic[4] != FORMAT_VERSION {
+                return Err(invalid(format!("unsupported version {}", magic[4])));
+            }
+            let config = read_block(&mut reader)?;
+            let config = config_from_bytes(&config)?;
+            let context = Context::from_preload_bytes(&read_block(&mut reader)?)?;
+            Ok(Self {
+                reader,
+                decoder: Decoder::with_config(config),
+                context,
+                next_index: 0,
+            })
+        }
+
+        /// Replay the next input, `None` at the end of the log
+        ///
+        /// # Errors
+        ///
+        /// Fails with [`AlecError::Protocol`] on a truncated or corrupted
+        /// record. Errors the decoder returned during the session are
+        /// reported in [`ReplayStep::error`] instead.
+        pub fn step(&mut self) -> Result<Option<ReplayStep>> {
+            let Some(index) = read_varint(&mut self.reader, true)? else {
+                return Ok(None);
+            };
+            if index != self.next_index {
+                return Err(invalid(format!(
+                    "input {} where {} was expected",
+                    index, self.next_index
+                )));
+            }
+            let mut byte = [0u8; 1];
+            read_exact(&mut self.reader, &mut byte)?;
+            let input = SessionInput::from_u8(byte[0])
+                .ok_or_else(|| invalid(format!("unknown input 0x{:02X}", byte[0])))?;
+            let payload = read_block(&mut self.reader)?;
+
+            let error = self.apply(input, &payload)?.err();
+            self.next_index += 1;
+            Ok(Some(ReplayStep {
+                index,
+                input,
+                error,
+                context_hash: self.context.hash(),
+            }))
+        }
+
+        /// Replay up to and including input `index`, returning the context
+        /// hash at that point
+        ///
+        /// # Errors
+        ///
+        /// As [`step`](Self::step), and fails with [`AlecError::Protocol`]
+        /// if the log ends before `index` or `index` is already replayed.
+        pub fn replay_to(&mut self, index: u64) -> Result<u64> {
+            if index < self.next_index {
+                return Err(invalid(format!("input {} already replayed", index)));
+            }
+            while self.next_index <= index {
+                if self.step()?.is_none() {
+                    return Err(invalid(format!(
+                        "ends after {} inputs, before input {}",
+                        self.next_index, index
+                    )));
+                }
+            }
+            Ok(self.context.hash())
+        }
+
+        /// Index of the next input to replay
+        pub fn next_index(&self) -> u64 {
+            self.next_index
+        }
+
+        /// Decoder as rebuilt so far
+        pub fn decoder(&self) -> &Decoder {
+            &self.decoder
+        }
+
+        /// Context as rebuilt so far
+        pub fn context(&self) -> &Context {
+            &self.context
+        }
+
+        /// Decoder and context as rebuilt so far
+        pub fn into_parts(self) -> (Decoder, Context) {
+            (self.decoder, self.context)
+        }
+
+        /// Apply one input; the outer error is a log error, the inner one
+        /// the decoder's
+        fn apply(&mut self, input: SessionInput, payload: &[u8]) -> Result<Result<()>> {
+            let decoder = &mut self.decoder;
+            let context = &mut self.context;
+            Ok(match input {
+                SessionInput::Decode => decoder.decode_bytes(payload, context).map(drop),
+                SessionInput::DecodeAt => {
+                    if payload.len() < 8 {
+                        return Err(invalid("receive time missing"));
+                    }
+                    let (now, bytes) = payload.split_at(8);
+                    let now_ms = u64::from_le_bytes(now.try_into().expect("8 bytes"));
+                    decoder.decode_with_now(bytes, context, now_ms).map(drop)
+                }
+                SessionInput::DecodeItem => decoder.decode_bytes_item(payload, context).map(drop),
+                SessionInput::DecodeBuffer => decoder.decode_buffer(payload, context).map(drop),
+                SessionInput::DecodeInto => decoder.decode_into(payload, context).map(drop),
+                SessionInput::SyncDiff => decoder.apply_sync_diff(payload, context),
+                SessionInput::Import => decoder.import_context(payload, context),
+                SessionInput::Config => {
+                    decoder.set_config(config_from_bytes(payload)?);
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<()> {
+        reader.read_exact(buf).map_err(invalid)
+    }
+
+    /// LEB128 varint; `None` at a clean end of the log if `at_record_start`
+    fn read_varint(reader: &mut impl Read, at_record_start: bool) -> Result<Option<u64>> {
+        let mut bytes = Vec::with_capacity(10);
+        loop {
+            let mut byte = [0u8; 1];
+            if reader.read(&mut byte).map_err(invalid)? == 0 {
+                if bytes.is_empty() && at_record_start {
+                    return Ok(None);
+                }
+                return Err(invalid("truncated varint"));
+            }
+            bytes.push(byte[0]);
+            if byte[0] & 0x80 == 0 || bytes.len() == 10 {
+                break;
+            }
+        }
+        match read_varint_u64(&bytes) {
+            Some((value, _)) => Ok(Some(value)),
+            None => Err(invalid("varint too long")),
+        }
+    }
+
+    /// Length-prefixed block
+    fn read_block(reader: &mut impl Read) -> Result<Vec<u8>> {
+        let len = read_varint(reader, false)?.expect("not at record start");
+        let len = usize::try_from(len).map_err(|_| invalid("block too long"))?;
+        let mut block = Vec::new();
+        reader
+            .take(len as u64)
+            .read_to_end(&mut block)
+            .map_err(invalid)?;
+        if block.len() != len {
+            return Err(invalid("truncated record"));
+        }
+        Ok(block)
+    }
+
+    fn config_from_bytes(mut bytes: &[u8]) -> Result<DecoderConfig> {
+        let input = &mut bytes;
+        let byte = |input: &mut &[u8]| -> Result<u8> {
+            let mut byte = [0u8; 1];
+            input
+                .read_exact(&mut byte)
+                .map_err(|_| invalid("truncated configuration"))?;
+            Ok(byte[0])
+        };
+        let u64_le = |input: &mut &[u8]| -> Result<u64> {
+            let mut bytes = [0u8; 8];
+            input
+                .read_exact(&mut bytes)
+                .map_err(|_| invalid("truncated configuration"))?;
+            Ok(u64::from_le_bytes(bytes))
+        };
+        let u32_le = |input: &mut &[u8]| -> Result<u32> {
+            let mut bytes = [0u8; 4];
+            input
+                .read_exact(&mut bytes)
+                .map_err(|_| invalid("truncated configuration"))?;
+            Ok(u32::from_le_bytes(bytes))
+        };
+
+        let checksum_policy = match byte(input)? {
+            0 => ChecksumPolicy::Never,
+            1 => ChecksumPolicy::Always,
+            2 => ChecksumPolicy::ForPriorityAtOrAbove(
+                Priority::from_u8(byte(input)?).ok_or_else(|| invalid("unknown priority"))?,
+            ),
+            policy => return Err(invalid(format!("unknown checksum policy {}", policy))),
+        };
+        let max_values_per_message = u64_le(input)? as usize;
+        let max_expansion_bytes = u64_le(input)? as usize;
+        let max_decode_micros = u64_le(input)?;
+        let mut stale_thresholds = [None; 5];
+        for threshold in &mut stale_thresholds {
+            let action = match byte(input)? {
+                0 => continue,
+                1 => StaleAction::Flag,
+                2 => StaleAction::Reject,
+                action => return Err(invalid(format!("unknown stale action {}", action))),
+            };
+            *threshold = Some(StaleThreshold {
+                max_age_ms: u64_le(input)?,
+                action,
+            });
+        }
+
+        // Optional sections: a dialect (1), a timestamp unit (2), then a
+        // timestamp range (3)
+        let mut section = if input.is_empty() {
+            None
+        } else {
+            Some(byte(input)?)
+        };
+        let dialect = if section != Some(1) {
+            WireDialect::Standard
+        } else {
+            section = None;
+            let byte_order = match byte(input)? {
+                0 => ByteOrder::BigEndian,
+                1 => ByteOrder::LittleEndian,
+                order => return Err(invalid(format!("unknown byte order {}", order))),
+            };
+            let mut field_order = [HeaderField::Sequence; 3];
+            for field in &mut field_order {
+                *field = match byte(input)? {
+                    0 => HeaderField::Sequence,
+                    1 => HeaderField::Timestamp,
+                    2 => HeaderField::ContextVersion,
+                    field => return Err(invalid(format!("unknown header field {}", field))),
+                };
+            }
+            let profile = DialectProfile::new(byte_order, field_order)
+                .ok_or_else(|| invalid("incomplete header field order"))?;
+            WireDialect::Legacy(profile)
+        };
+        if section.is_none() && !input.is_empty() {
+            section = Some(byte(input)?);
+        }
+        let timestamp_unit = if section != Some(2) {
+            None
+        } else {
+            section = None;
+            Some(match byte(input)? {
+                0 => TimestampUnit::Seconds,
+                1 => TimestampUnit::Millis,
+                2 => TimestampUnit::Micros,
+                unit => return Err(invalid(format!("unknown timestamp unit {}", unit))),
+            })
+        };
+        if section.is_none() && !input.is_empty() {
+            section = Some(byte(input)?);
+        }
+        let timestamp_range = match section {
+            None => None,
+            Some(3) => Some((u32_le(input)?, u32_le(input)?)),
+            Some(_) => return Err(invalid("unknown configuration section")),
+        };
+
+        Ok(DecoderConfig {
+            checksum_policy,
+            max_values_per_message,
+            max_expansion_bytes,
+            max_decode_micros,
+            stale_thresholds,
+            dialect,
+            // Only affects statistics, which a replay does not compare
+            sequence_window: SequenceWindow::default(),
+            timestamp_unit,
+            timestamp_range,
+        })
+    }
+}
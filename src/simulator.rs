@@ -0,0 +1,763 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! End-to-end emitter/receiver simulation
+//!
+//! Runs a signal through the whole stack: an [`EmitterSim`] classifies,
+//! encodes and sends each sample over an uplink, a [`ReceiverSim`] decodes
+//! it and keeps its own context, and both sides exchange sync messages
+//! over a downlink. The result is a [`SimReport`].
+//!
+//! Both links are split channel halves ([`ChannelTx`] / [`ChannelRx`]), so
+//! any channel implementation can be plugged in with [`SimLink::new`];
+//! [`Simulation::in_memory`] wires two [`MemoryChannel`]s.
+//!
+//! # Protocol
+//!
+//! - Data messages carry consecutive sequence numbers starting at 0. Sync
+//!   messages travel as [`MessageType::Sync`] envelopes whose payload is
+//!   [`SyncMessage::to_bytes`]; the emitter puts its next data sequence in
+//!   the envelope header so a dropped final message is still noticed.
+//! - Every data message is kept in the emitter's [`DetailCache`]. When the
+//!   receiver sees a sequence gap it sends one `ReqDetail` per missing
+//!   message and holds later messages until the gap is filled, so both
+//!   contexts observe the same samples in the same order. Samples already
+//!   evicted from the cache are answered with `NotAvailable` and skipped.
+//! - Every `announce_interval` data messages the emitter sends an
+//!   `Announce`; the receiver requests a diff when its context disagrees.
+//!   A final announce is sent once the signal is exhausted.
+//!
+//! Fault injection only affects data messages on the uplink; sync traffic
+//! is delivered reliably.
+//!
+//! # Example
+//!
+//! ```
+//! use alec::simulator::{EmitterSim, ReceiverSim, SimConfig, Simulation};
+//! use alec::RawData;
+//!
+//! let signal = (0..500u64).map(|i| RawData::new(20.0 + (i % 7) as f64 * 0.1, i * 1000));
+//! let config = SimConfig {
+//!     drop_every: 10,
+//!     ..Default::default()
+//! };
+//! let mut sim = Simulation::in_memory(EmitterSim::new(signal, config), ReceiverSim::new());
+//! let report = sim.run().unwrap();
+//!
+//! assert_eq!(report.samples, 500);
+//! assert_eq!(report.messages_dropped, 50);
+//! assert!(report.contexts_match());
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::channel::{ChannelRx, ChannelTx, MemoryChannel};
+use crate::classifier::Classifier;
+use crate::context::Context;
+use crate::decoder::Decoder;
+use crate::encoder::{Encoder, EncoderConfig, MessageBuilder};
+use crate::error::{AlecError, ChannelError, Result};
+use crate::metrics::CompressionMetrics;
+use crate::protocol::{EncodedMessage, MessageType, RawData};
+use crate::sync::{DetailCache, SyncConfig, SyncMessage, Synchronizer};
+
+/// Upper bound on settle iterations after the signal is exhausted
+const MAX_SETTLE_ROUNDS: usize = 64;
+
+/// Simulation knobs
+#[derive(Debug, Clone, Copy)]
+pub struct SimConfig {
+    /// Samples the emitter sends per step before the receiver runs
+    pub messages_per_step: usize,
+    /// Data messages between sync announcements (0 = only the final one)
+    pub announce_interval: u32,
+    /// Drop every Nth data message on the uplink (0 = no drops)
+    pub drop_every: u32,
+    /// Data messages kept by the emitter for `ReqDetail` recovery
+    pub detail_cache_capacity: usize,
+    /// Encoder configuration used by the emitter
+    pub encoder: EncoderConfig,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            messages_per_step: 1,
+            announce_interval: 100,
+            drop_every: 0,
+            detail_cache_capacity: 256,
+            encoder: EncoderConfig::default(),
+        }
+    }
+}
+
+/// Outcome of a [`Simulation::run`]
+#[derive(Debug, Clone, Default)]
+pub struct SimReport {
+    /// Samples pulled from the signal
+    pub samples: u64,
+    /// Simulation steps run
+    pub steps: u64,
+    /// Compression statistics over every encoded data message
+    pub compression: CompressionMetrics,
+    /// Data messages lost on the uplink (fault injection or full buffer)
+    pub messages_dropped: u64,
+    /// Data messages the receiver decoded
+    pub messages_decoded: u64,
+    /// Messages the receiver failed to decode
+    pub decode_errors: u64,
+    /// Dropped samples recovered through `ReqDetail`
+    pub details_recovered: u64,
+    /// Dropped samples the emitter could no longer provide
+    pub samples_lost: u64,
+    /// Announcements sent by the emitter
+    pub announces_sent: u64,
+    /// Diffs applied by the receiver after an announce
+    pub diffs_applied: u64,
+    /// Completed request/response exchanges on the downlink
+    pub sync_round_trips: u64,
+    /// Emitter context hash at the end of the run
+    pub emitter_hash: u64,
+    /// Receiver context hash at the end of the run
+    pub receiver_hash: u64,
+    /// Emitter dictionary version at the end of the run
+    pub emitter_version: u32,
+    /// Receiver dictionary version at the end of the run
+    pub receiver_version: u32,
+}
+
+impl SimReport {
+    /// Raw bytes / encoded bytes over the data messages
+    pub fn compression_ratio(&self) -> f64 {
+        self.compression.compression_ratio()
+    }
+
+    /// Check if both contexts ended with the same hash
+    pub fn hashes_match(&self) -> bool {
+        self.emitter_hash == self.receiver_hash
+    }
+
+    /// Check if both contexts ended with the same hash and dictionary version
+    pub fn contexts_match(&self) -> bool {
+        self.hashes_match() && self.emitter_version == self.receiver_version
+    }
+}
+
+/// One direction of the simulated network
+pub struct SimLink {
+    tx: Box<dyn ChannelTx>,
+    rx: Box<dyn ChannelRx>,
+}
+
+impl SimLink {
+    /// Build a link from the two halves of any channel
+    pub fn new(tx: impl ChannelTx + 'static, rx: impl ChannelRx + 'static) -> Self {
+        Self {
+            tx: Box::new(tx),
+            rx: Box::new(rx),
+        }
+    }
+
+    /// In-memory link buffering up to `capacity` messages
+    pub fn memory(capacity: usize) -> Self {
+        let (tx, rx) = MemoryChannel::with_buffer_size(capacity).split();
+        Self::new(tx, rx)
+    }
+}
+
+impl core::fmt::Debug for SimLink {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SimLink")
+            .field("in_flight", &self.rx.len())
+            .field("capacity", &self.rx.capacity())
+            .finish()
+    }
+}
+
+/// Wrap a sync message for transport over a channel
+fn sync_envelope(message: &SyncMessage, sequence: u16) -> EncodedMessage {
+    MessageBuilder::new()
+        .message_type(MessageType::Sync)
+        .sequence(sequence)
+        .payload(message.to_bytes())
+        .build()
+}
+
+/// Receive without waiting, mapping "nothing ready" to `None`
+fn poll(rx: &mut dyn ChannelRx) -> Result<Option<EncodedMessage>> {
+    match rx.try_recv() {
+        Ok(message) => Ok(Some(message)),
+        Err(AlecError::Channel(ChannelError::WouldBlock)) => Ok(None),
+        Err(AlecError::Channel(ChannelError::Disconnected { .. })) if rx.is_empty() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Sensor side of a simulation
+pub struct EmitterSim<I> {
+    signal: I,
+    config: SimConfig,
+    encoder: Encoder,
+    classifier: Classifier,
+    context: Context,
+    synchronizer: Synchronizer,
+    detail_cache: DetailCache,
+    exhausted: bool,
+    data_sent: u64,
+    samples: u64,
+    compression: CompressionMetrics,
+    messages_dropped: u64,
+    announces_sent: u64,
+}
+
+impl<I: Iterator<Item = RawData>> EmitterSim<I> {
+    /// Create an emitter drawing samples from `signal`
+    pub fn new(signal: I, config: SimConfig) -> Self {
+        let sync_config = SyncConfig {
+            announce_interval: config.announce_interval,
+            max_detail_responses: 0,
+            ..Default::default()
+        };
+        Self {
+            signal,
+            config,
+            encoder: Encoder::with_config(config.encoder),
+            classifier: Classifier::default(),
+            context: Context::new(),
+            synchronizer: Synchronizer::with_config(sync_config),
+            detail_cache: DetailCache::new(config.detail_cache_capacity),
+            exhausted: false,
+            data_sent: 0,
+            samples: 0,
+            compression: CompressionMetrics::new(),
+            messages_dropped: 0,
+            announces_sent: 0,
+        }
+    }
+
+    /// Emitter-side context
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Check if the signal has run out
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Encode and send up to `messages_per_step` samples
+    ///
+    /// Returns the number of samples pulled from the signal.
+    pub fn emit(&mut self, uplink: &mut dyn ChannelTx) -> Result<usize> {
+        let mut emitted = 0;
+        while emitted < self.config.messages_per_step.max(1) {
+            let Some(data) = self.signal.next() else {
+                self.exhausted = true;
+                break;
+            };
+            emitted += 1;
+            self.samples += 1;
+
+            let classification = self.classifier.classify(&data, &self.context);
+            let message = self.encoder.encode_with_detail_cache(
+                &data,
+                &classification,
+                &self.context,
+                &mut self.detail_cache,
+            );
+            self.context.observe_classified(&data, &classification);
+            if let Some(encoding) = message.encoding_type() {
+                self.compression
+                    .record_encode(data.raw_size(), message.len(), encoding);
+            }
+
+            self.data_sent += 1;
+            let drop_every = self.config.drop_every as u64;
+            if drop_every > 0 && self.data_sent % drop_every == 0 {
+                self.messages_dropped += 1;
+            } else if let Err(e) = uplink.try_send(message) {
+                if !e.is_would_block() {
+                    return Err(e.into());
+                }
+                self.messages_dropped += 1;
+            }
+
+            if self.config.announce_interval > 0 && self.synchronizer.should_announce() {
+                self.announce(uplink)?;
+            }
+        }
+        Ok(emitted)
+    }
+
+    /// Send an announcement of the current context
+    pub fn announce(&mut self, uplink: &mut dyn ChannelTx) -> Result<()> {
+        let announce = Synchronizer::create_announce(&self.context);
+        uplink.try_send(sync_envelope(&announce, self.encoder.sequence()))?;
+        self.announces_sent += 1;
+        Ok(())
+    }
+
+    /// Answer every pending request from the receiver
+    pub fn process_downlink(
+        &mut self,
+        downlink: &mut dyn ChannelRx,
+        uplink: &mut dyn ChannelTx,
+    ) -> Result<()> {
+        while let Some(message) = poll(downlink)? {
+            if message.header.message_type != MessageType::Sync {
+                continue;
+            }
+            let replies = match SyncMessage::from_bytes(&message.payload) {
+                Some(SyncMessage::Request(request)) => self
+                    .synchronizer
+                    .handle_request(&request, &self.context)
+                    .into_iter()
+                    .collect(),
+                Some(SyncMessage::ReqDetail(sequence)) => self
+                    .synchronizer
+                    .handle_detail_request(sequence, &self.detail_cache)
+                    .into_iter()
+                    .collect(),
+                Some(SyncMessage::ReqRange(request)) => self
+                    .synchronizer
+                    .handle_range_request(&request, &self.detail_cache),
+                _ => Vec::new(),
+            };
+            for reply in replies {
+                uplink.try_send(sync_envelope(&reply, self.encoder.sequence()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I> core::fmt::Debug for EmitterSim<I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmitterSim")
+            .field("config", &self.config)
+            .field("samples", &self.samples)
+            .field("exhausted", &self.exhausted)
+            .finish()
+    }
+}
+
+/// A data message slot waiting to be applied in sequence order
+#[derive(Debug)]
+enum Slot {
+    /// Received data message
+    Message(EncodedMessage),
+    /// Missing message, `ReqDetail` sent
+    Requested,
+    /// Missing message recovered from the emitter's detail cache
+    Recovered(RawData),
+    /// Missing message the emitter no longer has
+    Lost,
+}
+
+/// Server side of a simulation
+#[derive(Debug)]
+pub struct ReceiverSim {
+    decoder: Decoder,
+    context: Context,
+    synchronizer: Synchronizer,
+    /// Unwrapped sequence of the next data message to apply
+    next_sequence: u64,
+    /// Data messages not applied yet, keyed by unwrapped sequence
+    slots: BTreeMap<u64, Slot>,
+    received: Vec<RawData>,
+    messages_decoded: u64,
+    decode_errors: u64,
+    details_recovered: u64,
+    samples_lost: u64,
+    diffs_applied: u64,
+    sync_round_trips: u64,
+}
+
+impl ReceiverSim {
+    /// Create a receiver with a fresh context
+    pub fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+            context: Context::new(),
+            synchronizer: Synchronizer::new(),
+            next_sequence: 0,
+            slots: BTreeMap::new(),
+            received: Vec::new(),
+            messages_decoded: 0,
+            decode_errors: 0,
+            details_recovered: 0,
+            samples_lost: 0,
+            diffs_applied: 0,
+            sync_round_trips: 0,
+        }
+    }
+
+    /// Receiver-side context
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Samples applied so far, in sequence order
+    ///
+    /// Decoded values for received messages, full-resolution values for
+    /// recovered ones.
+    pub fn received(&self) -> &[RawData] {
+        &self.received
+    }
+
+    /// Check if a sequence gap is still waiting for recovery
+    pub fn has_gap(&self) -> bool {
+        !self.slots.is_empty()
+    }
+
+    /// Handle every message waiting on the uplink
+    pub fn process_uplink(
+        &mut self,
+        uplink: &mut dyn ChannelRx,
+        downlink: &mut dyn ChannelTx,
+    ) -> Result<()> {
+        while let Some(message) = poll(uplink)? {
+            match message.header.message_type {
+                MessageType::Data => self.on_data(message, downlink)?,
+                MessageType::Sync => match SyncMessage::from_bytes(&message.payload) {
+                    Some(sync) => self.on_sync(sync, message.header.sequence, downlink)?,
+                    None => self.decode_errors += 1,
+                },
+                _ => self.decode_errors += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Map a 16-bit wire sequence to the nearest unwrapped sequence
+    fn unwrap_sequence(&self, sequence: u16) -> u64 {
+        let horizon = self.horizon();
+        let offset = sequence.wrapping_sub(horizon as u16) as i16;
+        (horizon as i64 + offset as i64).max(0) as u64
+    }
+
+    /// Unwrapped sequence following the newest known data message
+    fn horizon(&self) -> u64 {
+        self.slots
+            .keys()
+            .next_back()
+            .map_or(self.next_sequence, |last| last + 1)
+    }
+
+    /// Request every message between the horizon and `until` (exclusive)
+    fn request_missing(&mut self, until: u64, downlink: &mut dyn ChannelTx) -> Result<()> {
+        for missing in self.horizon()..until {
+            let request = Synchronizer::create_detail_request(missing as u16 as u32);
+            downlink.try_send(sync_envelope(&request, 0))?;
+            self.slots.insert(missing, Slot::Requested);
+        }
+        Ok(())
+    }
+
+    fn on_data(&mut self, message: EncodedMessage, downlink: &mut dyn ChannelTx) -> Result<()> {
+        let sequence = self.unwrap_sequence(message.header.sequence);
+        if sequence < self.next_sequence || self.slots.contains_key(&sequence) {
+            // Duplicate or late
+            return Ok(());
+        }
+
+        self.request_missing(sequence, downlink)?;
+        self.slots.insert(sequence, Slot::Message(message));
+        self.apply_ready();
+        Ok(())
+    }
+
+    /// Handle a sync message; `sequence` is its envelope's header sequence
+    fn on_sync(
+        &mut self,
+        message: SyncMessage,
+        sequence: u16,
+        downlink: &mut dyn ChannelTx,
+    ) -> Result<()> {
+        match message {
+            SyncMessage::Announce(announce) => {
+                // The envelope carries the next data sequence, which
+                // reveals messages dropped at the tail of the stream
+                let next = self.unwrap_sequence(sequence);
+                self.request_missing(next, downlink)?;
+
+                // A pending gap would make the comparison meaningless
+                if self.has_gap() {
+                    return Ok(());
+                }
+                if let Some(request) = self.synchronizer.check_sync_needed(
+                    announce.version,
+                    announce.hash,
+                    &self.context,
+                ) {
+                    downlink.try_send(sync_envelope(&request, 0))?;
+                }
+            }
+            SyncMessage::Diff(diff) => {
+                self.sync_round_trips += 1;
+                match self.synchronizer.handle_diff(&diff, &mut self.context) {
+                    Ok(()) => self.diffs_applied += 1,
+                    Err(_) => self.decode_errors += 1,
+                }
+            }
+            SyncMessage::DetailResponse(response) => {
+                self.sync_round_trips += 1;
+                self.fill_slot(response.sequence, Slot::Recovered(response.data));
+            }
+            SyncMessage::NotAvailable(sequence) => {
+                self.sync_round_trips += 1;
+                self.fill_slot(sequence, Slot::Lost);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Resolve the requested slot whose wire sequence matches
+    fn fill_slot(&mut self, sequence: u32, resolved: Slot) {
+        let slot = self
+            .slots
+            .iter_mut()
+            .find(|(key, slot)| **key as u16 as u32 == sequence && matches!(slot, Slot::Requested))
+            .map(|(_, slot)| slot);
+        if let Some(slot) = slot {
+            *slot = resolved;
+            self.apply_ready();
+        }
+    }
+
+    /// Apply consecutive resolved slots to the context
+    fn apply_ready(&mut self) {
+        let mut next = self.next_sequence;
+        while let Some(entry) = self.slots.first_entry() {
+            if *entry.key() != next || matches!(entry.get(), Slot::Requested) {
+                break;
+            }
+            match entry.remove() {
+                Slot::Message(message) => match self.decoder.decode(&message, &self.context) {
+                    Ok(decoded) => {
+                        let data = RawData::with_source(
+                            decoded.source_id,
+                            decoded.value,
+                            decoded.timestamp,
+                        );
+                        self.context.observe_with_priority(&data, decoded.priority);
+                        self.received.push(data);
+                        self.messages_decoded += 1;
+                    }
+                    Err(_) => self.decode_errors += 1,
+                },
+                Slot::Recovered(data) => {
+                    self.context.observe(&data);
+                    self.received.push(data);
+                    self.details_recovered += 1;
+                }
+                Slot::Lost => self.samples_lost += 1,
+                Slot::Requested => unreachable!(),
+            }
+            next += 1;
+        }
+        self.next_sequence = next;
+    }
+}
+
+impl Default for ReceiverSim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An emitter and a receiver connected by an uplink and a downlink
+#[derive(Debug)]
+pub struct Simulation<I> {
+    emitter: EmitterSim<I>,
+    receiver: ReceiverSim,
+    uplink: SimLink,
+    downlink: SimLink,
+    steps: u64,
+}
+
+impl<I: Iterator<Item = RawData>> Simulation<I> {
+    /// Connect `emitter` and `receiver` through the given links
+    pub fn new(
+        emitter: EmitterSim<I>,
+        receiver: ReceiverSim,
+        uplink: SimLink,
+        downlink: SimLink,
+    ) -> Self {
+        Self {
+            emitter,
+            receiver,
+            uplink,
+            downlink,
+            steps: 0,
+        }
+    }
+
+    /// Connect `emitter` and `receiver` through in-memory channels
+    ///
+    /// The uplink holds a few steps' worth of messages, so nothing is
+    /// lost to back-pressure unless fault injection says so.
+    pub fn in_memory(emitter: EmitterSim<I>, receiver: ReceiverSim) -> Self {
+        let capacity = emitter.config.messages_per_step.max(1) * 4 + 1024;
+        Self::new(
+            emitter,
+            receiver,
+            SimLink::memory(capacity),
+            SimLink::memory(capacity),
+        )
+    }
+
+    /// Emitter side
+    pub fn emitter(&self) -> &EmitterSim<I> {
+        &self.emitter
+    }
+
+    /// Receiver side
+    pub fn receiver(&self) -> &ReceiverSim {
+        &self.receiver
+    }
+
+    /// Run one step: emit, then deliver and answer on both links
+    ///
+    /// Returns `false` once the signal is exhausted.
+    pub fn step(&mut self) -> Result<bool> {
+        self.emitter.emit(&mut *self.uplink.tx)?;
+        self.exchange()?;
+        self.steps += 1;
+        Ok(!self.emitter.is_exhausted())
+    }
+
+    /// Run the whole signal, settle, and report
+    pub fn run(&mut self) -> Result<SimReport> {
+        while self.step()? {}
+
+        self.settle()?;
+        self.emitter.announce(&mut *self.uplink.tx)?;
+        self.settle()?;
+
+        Ok(self.report())
+    }
+
+    /// Current state as a report
+    pub fn report(&self) -> SimReport {
+        let emitter = &self.emitter;
+        let receiver = &self.receiver;
+        SimReport {
+            samples: emitter.samples,
+            steps: self.steps,
+            compression: emitter.compression.clone(),
+            messages_dropped: emitter.messages_dropped,
+            messages_decoded: receiver.messages_decoded,
+            decode_errors: receiver.decode_errors,
+            details_recovered: receiver.details_recovered,
+            samples_lost: receiver.samples_lost,
+            announces_sent: emitter.announces_sent,
+            diffs_applied: receiver.diffs_applied,
+            sync_round_trips: receiver.sync_round_trips,
+            emitter_hash: emitter.context.hash(),
+            receiver_hash: receiver.context.hash(),
+            emitter_version: emitter.context.dictionary_version(),
+            receiver_version: receiver.context.dictionary_version(),
+        }
+    }
+
+    /// Deliver uplink traffic, answer it, and deliver the answers
+    fn exchange(&mut self) -> Result<()> {
+        self.receiver
+            .process_uplink(&mut *self.uplink.rx, &mut *self.downlink.tx)?;
+        self.emitter
+            .process_downlink(&mut *self.downlink.rx, &mut *self.uplink.tx)?;
+        self.receiver
+            .process_uplink(&mut *self.uplink.rx, &mut *self.downlink.tx)
+    }
+
+    /// Exchange until both links are idle
+    fn settle(&mut self) -> Result<()> {
+        for _ in 0..MAX_SETTLE_ROUNDS {
+            if self.uplink.rx.is_empty() && self.downlink.rx.is_empty() {
+                break;
+            }
+            self.exchange()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(n: u64) -> impl Iterator<Item = RawData> {
+        (0..n).map(|i| RawData::new(20.0 + i as f64 * 0.01, i * 1000))
+    }
+
+    #[test]
+    fn test_lossless_run_matches() {
+        let emitter = EmitterSim::new(ramp(300), SimConfig::default());
+        let mut sim = Simulation::in_memory(emitter, ReceiverSim::new());
+        let report = sim.run().unwrap();
+
+        assert_eq!(report.samples, 300);
+        assert_eq!(report.messages_decoded, 300);
+        assert_eq!(report.messages_dropped, 0);
+        assert_eq!(report.details_recovered, 0);
+        assert_eq!(report.decode_errors, 0);
+        // 3 periodic announces plus the final one
+        assert_eq!(report.announces_sent, 4);
+        assert!(report.contexts_match());
+        assert!(report.compression_ratio() > 1.0);
+    }
+
+    #[test]
+    fn test_dropped_messages_are_recovered_in_order() {
+        let config = SimConfig {
+            drop_every: 5,
+            messages_per_step: 3,
+            ..Default::default()
+        };
+        let mut sim = Simulation::in_memory(EmitterSim::new(ramp(100), config), ReceiverSim::new());
+        let report = sim.run().unwrap();
+
+        assert_eq!(report.messages_dropped, 20);
+        assert_eq!(report.messages_decoded + report.details_recovered, 100);
+        assert!(report.sync_round_trips >= 19);
+        assert!(report.contexts_match());
+
+        let received = sim.receiver().received();
+        for (i, data) in received.iter().enumerate() {
+            assert!((data.value - (20.0 + i as f64 * 0.01)).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_evicted_details_are_lost() {
+        let config = SimConfig {
+            drop_every: 2,
+            messages_per_step: 8,
+            detail_cache_capacity: 2,
+            ..Default::default()
+        };
+        let mut sim = Simulation::in_memory(EmitterSim::new(ramp(64), config), ReceiverSim::new());
+        let report = sim.run().unwrap();
+
+        assert!(report.samples_lost > 0);
+        assert!(!sim.receiver().has_gap());
+        assert_eq!(
+            report.messages_decoded + report.details_recovered + report.samples_lost,
+            64
+        );
+    }
+
+    #[test]
+    fn test_sequence_unwrap_across_wraparound() {
+        let mut receiver = ReceiverSim::new();
+        receiver.next_sequence = u16::MAX as u64;
+        assert_eq!(receiver.unwrap_sequence(u16::MAX), u16::MAX as u64);
+        assert_eq!(receiver.unwrap_sequence(0), u16::MAX as u64 + 1);
+        assert_eq!(receiver.unwrap_sequence(3), u16::MAX as u64 + 4);
+    }
+}
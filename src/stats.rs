@@ -0,0 +1,309 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Streaming statistics shared across the ALEC crates
+//!
+//! - [`RunningStats`]: count, mean and variance of every value seen
+//!   (Welford), mergeable across partitions (Chan et al.)
+//! - [`Ema`]: exponential moving average
+//! - [`WindowedStats`]: the last N values, with their min, max, mean and
+//!   standard deviation
+//!
+//! The context's per-source prediction statistics are built on them. All
+//! three work without `std`.
+//!
+//! # Stability
+//!
+//! This API is stable: downstream crates can depend on it, and breaking
+//! changes only come with a major version.
+//!
+//! ```
+//! use alec::stats::{Ema, RunningStats, WindowedStats};
+//!
+//! let mut all = RunningStats::new();
+//! let mut ema = Ema::new(0.5);
+//! let mut recent = WindowedStats::new(3);
+//! for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+//!     all.push(value);
+//!     ema.push(value);
+//!     recent.push(value);
+//! }
+//! assert_eq!(all.mean(), 5.0);
+//! assert_eq!(all.population_variance(), 4.0);
+//! assert_eq!(ema.value(), Some(7.421875));
+//! assert_eq!(recent.mean(), Some(7.0));
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+#[cfg(feature = "std")]
+fn sqrt(value: f64) -> f64 {
+    value.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+fn sqrt(value: f64) -> f64 {
+    libm::sqrt(value)
+}
+
+/// Count, mean and variance of a stream of values
+///
+/// Welford's update keeps the variance accurate for values far from zero
+/// with a small spread, where the sum-of-squares formula cancels out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStats {
+    /// Empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Statistics from a count, a mean and a sum of squared differences
+    /// from the mean, as returned by [`count`](Self::count),
+    /// [`mean`](Self::mean) and [`m2`](Self::m2)
+    pub fn from_parts(count: u64, mean: f64, m2: f64) -> Self {
+        Self { count, mean, m2 }
+    }
+
+    /// Add a value
+    pub fn push(&mut self, value: f64) {
+        self.push_weighted(value, 1.0);
+    }
+
+    /// Add a value whose pull on the mean and the spread is scaled by
+    /// `weight` (0-1)
+    ///
+    /// The value still counts as one; a weight of 1.0 is
+    /// [`push`](Self::push).
+    pub fn push_weighted(&mut self, value: f64, weight: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += weight * delta / self.count as f64;
+        let delta2 = value - self.mean;
+        self.m2 += weight * delta * delta2;
+    }
+
+    /// Fold in the statistics of another part of the stream
+    ///
+    /// The result is that of pushing both parts into one.
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let (own, theirs) = (self.count as f64, other.count as f64);
+        self.mean += delta * theirs / count as f64;
+        self.m2 += other.m2 + delta * delta * own * theirs / count as f64;
+        self.count = count;
+    }
+
+    /// Number of values pushed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean of the values (0.0 when empty)
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sum of squared differences from the mean
+    pub fn m2(&self) -> f64 {
+        self.m2
+    }
+
+    /// Sample variance (0.0 below two values)
+    pub fn variance(&self) -> f64 {
+        if self.count > 1 {
+            self.m2 / (self.count - 1) as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Population variance (0.0 when empty)
+    pub fn population_variance(&self) -> f64 {
+        if self.count > 0 {
+            self.m2 / self.count as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Sample standard deviation (0.0 below two values)
+    pub fn std_dev(&self) -> f64 {
+        sqrt(self.variance())
+    }
+}
+
+/// Exponential moving average
+///
+/// The first value starts the average; each later one moves it by
+/// `alpha` of the difference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Empty average with smoothing factor `alpha` (0-1, higher follows
+    /// the values more closely)
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha, value: None }
+    }
+
+    /// Average with smoothing factor `alpha` at `value` (`None`: empty)
+    pub fn with_value(alpha: f64, value: Option<f64>) -> Self {
+        Self { alpha, value }
+    }
+
+    /// Add a value
+    pub fn push(&mut self, value: f64) {
+        self.push_weighted(value, 1.0);
+    }
+
+    /// Add a value with the smoothing factor scaled by `weight` (0-1)
+    ///
+    /// A first value starts the average whatever its weight.
+    pub fn push_weighted(&mut self, value: f64, weight: f64) {
+        self.value = Some(match self.value {
+            None => value,
+            Some(average) => {
+                let alpha = self.alpha * weight;
+                alpha * value + (1.0 - alpha) * average
+            }
+        });
+    }
+
+    /// Smoothing factor
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Current average, `None` before the first value
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// The last values of a stream, in a ring buffer
+///
+/// Holds at most `capacity` values, evicting the oldest. The buffer is
+/// allocated up front, so pushing never allocates. Window statistics
+/// are computed over the values on each call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowedStats {
+    values: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl WindowedStats {
+    /// Empty window of `capacity` values
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add a value, evicting the oldest one when the window is full
+    pub fn push(&mut self, value: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() >= self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// Maximum number of values kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of values in the window
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the window holds no value
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Drop every value
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Values of the window, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = f64> + ExactSizeIterator + '_ {
+        self.values.iter().copied()
+    }
+
+    /// Most recent value
+    pub fn last(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+
+    /// Smallest value of the window
+    pub fn min(&self) -> Option<f64> {
+        self.iter().reduce(f64::min)
+    }
+
+    /// Largest value of the window
+    pub fn max(&self) -> Option<f64> {
+        self.iter().reduce(f64::max)
+    }
+
+    /// Mean of the window
+    pub fn mean(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            return None;
+        }
+        Some(self.iter().sum::<f64>() / self.values.len() as f64)
+    }
+
+    /// Sample variance of the window, `None` below two values
+    ///
+    /// Deviations are taken from the window's mean, which keeps the
+    /// result accurate for values far from zero.
+    pub fn variance(&self) -> Option<f64> {
+        if self.values.len() < 2 {
+            return None;
+        }
+        let mean = self.mean()?;
+        let m2: f64 = self
+            .iter()
+            .map(|value| (value - mean) * (value - mean))
+            .sum();
+        Some(m2 / (self.values.len() - 1) as f64)
+    }
+
+    /// Sample standard deviation of the window, `None` below two values
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(sqrt)
+    }
+}
+
+impl Extend<f64> for WindowedStats {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
@@ -11,13 +11,15 @@
 //! - Sync message types for protocol communication
 //! - State machine for tracking synchronization status
 //! - Diff generation and application for incremental updates
+//! - A bounded [`DetailCache`] answering `ReqDetail` / `ReqRange` requests
+//! - [`VersionNegotiation`] settling the wire-format version of a link
 
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-use crate::context::{Context, Pattern};
+use crate::context::{dictionary_hash, Context, Pattern};
 use crate::error::{ContextError, Result};
-use crate::protocol::RawData;
+use crate::protocol::{RawData, WireVersion};
 
 /// Synchronization message types
 #[derive(Debug, Clone, PartialEq)]
@@ -34,12 +36,18 @@ pub enum SyncMessage {
     ReqRange(RangeRequest),
     /// Detail response with full data
     DetailResponse(DetailResponse),
+    /// Requested sequence is no longer available (evicted or never cached)
+    NotAvailable(u32),
+    /// Highest wire-format version the sender supports
+    VersionOffer(WireVersion),
+    /// Wire-format version the sender agreed to use
+    VersionAccept(WireVersion),
 }
 
 /// Announcement of current context state
 #[derive(Debug, Clone, PartialEq)]
 pub struct SyncAnnounce {
-    /// Current context version
+    /// Dictionary version of the sender (`Context::dictionary_version`)
     pub version: u32,
     /// Hash of the context for verification
     pub hash: u64,
@@ -71,6 +79,96 @@ pub struct SyncDiff {
     pub hash: u64,
 }
 
+impl SyncDiff {
+    /// Preview what applying this diff would change in `target`
+    ///
+    /// Removals are applied before additions, as in
+    /// [`Synchronizer::apply_diff`]: an addition at a code the diff also
+    /// removes counts as a new pattern, not a conflict. The post-apply hash
+    /// is computed over borrowed pattern bytes, without cloning `target`.
+    pub fn preview(&self, target: &Context) -> DiffPreview {
+        let mut removed_codes = self.removed.clone();
+        removed_codes.sort_unstable();
+        let is_removed = |code: &u32| removed_codes.binary_search(code).is_ok();
+
+        let mut preview = DiffPreview::default();
+        for code in &self.removed {
+            if target.has_pattern(*code) {
+                preview.removed.push(*code);
+            } else {
+                preview.already_absent.push(*code);
+            }
+        }
+
+        // Shadow dictionary: surviving entries, then additions
+        let mut entries: Vec<(u32, &[u8])> = target
+            .patterns_iter()
+            .filter(|(code, _)| !is_removed(code))
+            .map(|(code, pattern)| (*code, &pattern.data[..]))
+            .collect();
+
+        for (code, pattern) in &self.added {
+            match target.get_pattern(*code) {
+                Some(current) if !is_removed(code) => {
+                    if current.data != pattern.data {
+                        preview.conflicts.push(*code);
+                    } else {
+                        preview.unchanged += 1;
+                    }
+                }
+                _ => {
+                    preview.added += 1;
+                    preview.added_bytes += pattern.data.len();
+                }
+            }
+            entries.push((*code, &pattern.data[..]));
+        }
+
+        // Stable sort: for a repeated code the last entry wins, as with
+        // `set_pattern`
+        entries.sort_by_key(|(code, _)| *code);
+        let mut shadow: Vec<(u32, &[u8])> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match shadow.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => shadow.push(entry),
+            }
+        }
+        preview.hash = dictionary_hash(shadow.into_iter());
+
+        preview
+    }
+}
+
+/// What applying a [`SyncDiff`] would change in a context
+///
+/// Built by [`SyncDiff::preview`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiffPreview {
+    /// Additions at codes that are free (or removed by the diff)
+    pub added: usize,
+    /// Total pattern bytes of those additions
+    pub added_bytes: usize,
+    /// Additions identical to the pattern already at their code
+    pub unchanged: usize,
+    /// Codes whose current pattern an addition would overwrite with
+    /// different bytes
+    pub conflicts: Vec<u32>,
+    /// Removals of codes currently present
+    pub removed: Vec<u32>,
+    /// Removals of codes already absent
+    pub already_absent: Vec<u32>,
+    /// Hash of the context after applying the diff
+    pub hash: u64,
+}
+
+impl DiffPreview {
+    /// Check if no addition would overwrite a different pattern
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
 /// Request for a range of historical data
 #[derive(Debug, Clone, PartialEq)]
 pub struct RangeRequest {
@@ -99,7 +197,7 @@ pub enum SyncState {
     Synchronized,
     /// Waiting for sync response
     WaitingForSync {
-        /// When the request was made (observation count)
+        /// When the request was last sent, in the caller's milliseconds
         requested_at: u64,
     },
     /// Currently applying received diff
@@ -109,31 +207,58 @@ pub enum SyncState {
 }
 
 /// Configuration for synchronization behavior
+///
+/// Announcements fire on whichever trigger comes first: every
+/// `announce_interval` messages, or every `announce_interval_ms` of caller
+/// time (see [`Synchronizer::should_announce_at`]).
 #[derive(Debug, Clone)]
 pub struct SyncConfig {
     /// How often to send announcements (in messages)
     pub announce_interval: u32,
-    /// Max version gap before full resync
+    /// How often to send announcements (in ms), if time-based announcing is on
+    pub announce_interval_ms: Option<u64>,
+    /// Random spread of each time-based interval, in ± percent (capped at 100)
+    pub announce_jitter_percent: u8,
+    /// Seed for the jitter generator
+    ///
+    /// Give each emitter of a fleet a different seed (e.g. its device id)
+    /// so emitters started together drift apart.
+    pub jitter_seed: u64,
+    /// Max dictionary version gap before full resync
     pub max_version_gap: u32,
-    /// Timeout for sync requests (in observations)
+    /// Timeout for sync requests (in ms, on the caller's clock)
+    ///
+    /// Measured with the times given to
+    /// [`Synchronizer::check_sync_needed_at`] and
+    /// [`Synchronizer::check_timeout`].
     pub sync_timeout: u64,
+    /// Times an unanswered sync request is retried before the state
+    /// becomes [`SyncState::Diverged`]
+    pub max_sync_retries: u32,
     /// Whether to automatically request sync on mismatch
     pub auto_sync: bool,
+    /// Max detail responses per announce interval (0 = unlimited)
+    pub max_detail_responses: u32,
 }
 
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             announce_interval: 100,
+            announce_interval_ms: None,
+            announce_jitter_percent: 0,
+            jitter_seed: 0,
             max_version_gap: 10,
-            sync_timeout: 1000,
+            sync_timeout: 30_000,
+            max_sync_retries: 2,
             auto_sync: true,
+            max_detail_responses: 16,
         }
     }
 }
 
 /// Context synchronizer - manages sync state and operations
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Synchronizer {
     /// Current sync state
     state: SyncState,
@@ -143,8 +268,18 @@ pub struct Synchronizer {
     remote_version: Option<u32>,
     /// Number of messages since last announce
     messages_since_announce: u32,
-    /// Pending sync requests
-    pending_requests: Vec<SyncRequest>,
+    /// Latest time seen by `should_announce_at`
+    now_ms: u64,
+    /// Deadline of the time-based announce trigger
+    next_announce_ms: Option<u64>,
+    /// Jitter generator state
+    jitter_state: u64,
+    /// Detail responses sent since last announce
+    detail_responses_sent: u32,
+    /// Incremental sync request awaiting its diff
+    pending_request: Option<SyncRequest>,
+    /// Retries of the pending request so far
+    sync_retries: u32,
     /// Configuration
     config: SyncConfig,
 }
@@ -152,14 +287,7 @@ pub struct Synchronizer {
 impl Synchronizer {
     /// Create a new synchronizer with default configuration
     pub fn new() -> Self {
-        Self {
-            state: SyncState::Synchronized,
-            local_version: 0,
-            remote_version: None,
-            messages_since_announce: 0,
-            pending_requests: Vec::new(),
-            config: SyncConfig::default(),
-        }
+        Self::with_config(SyncConfig::default())
     }
 
     /// Create a synchronizer with custom configuration
@@ -169,7 +297,12 @@ impl Synchronizer {
             local_version: 0,
             remote_version: None,
             messages_since_announce: 0,
-            pending_requests: Vec::new(),
+            now_ms: 0,
+            next_announce_ms: None,
+            jitter_state: config.jitter_seed,
+            detail_responses_sent: 0,
+            pending_request: None,
+            sync_retries: 0,
             config,
         }
     }
@@ -190,32 +323,123 @@ impl Synchronizer {
     }
 
     /// Check if an announcement should be sent
+    ///
+    /// Message-count trigger only: equivalent to [`Self::should_announce_at`]
+    /// with the last time it was given, so the time-based trigger never
+    /// fires from here.
     pub fn should_announce(&mut self) -> bool {
-        self.messages_since_announce += 1;
-        if self.messages_since_announce >= self.config.announce_interval {
+        self.should_announce_at(self.now_ms)
+    }
+
+    /// Check if an announcement should be sent, once per message, at `now_ms`
+    ///
+    /// Fires when `announce_interval` messages have been counted or, with
+    /// `announce_interval_ms` set, when the current deadline has passed.
+    /// The first call only schedules the deadline. Firing restarts both
+    /// triggers, so they never fire twice for the same window, and starts
+    /// a new detail-response rate-limit window.
+    ///
+    /// `now_ms` is any monotonic millisecond clock chosen by the caller.
+    pub fn should_announce_at(&mut self, now_ms: u64) -> bool {
+        self.now_ms = self.now_ms.max(now_ms);
+        self.messages_since_announce = self.messages_since_announce.saturating_add(1);
+
+        let count_due = self.messages_since_announce >= self.config.announce_interval;
+        let time_due = match (self.config.announce_interval_ms, self.next_announce_ms) {
+            (Some(_), Some(deadline)) => self.now_ms >= deadline,
+            (Some(_), None) => {
+                self.schedule_announce();
+                false
+            }
+            (None, _) => false,
+        };
+
+        if count_due || time_due {
             self.messages_since_announce = 0;
+            self.detail_responses_sent = 0;
+            if self.config.announce_interval_ms.is_some() {
+                self.schedule_announce();
+            }
             true
         } else {
             false
         }
     }
 
+    /// Deadline of the time-based announce trigger, once scheduled
+    pub fn next_announce_ms(&self) -> Option<u64> {
+        self.next_announce_ms
+    }
+
+    /// Set the next time-based deadline one jittered interval from now
+    fn schedule_announce(&mut self) {
+        let Some(interval) = self.config.announce_interval_ms else {
+            return;
+        };
+        let percent = self.config.announce_jitter_percent.min(100) as u64;
+        let spread = (interval as u128 * percent as u128 / 100) as u64;
+        let interval = if spread == 0 {
+            interval
+        } else {
+            // Uniform in [interval - spread, interval + spread]
+            let offset = (self.next_jitter() as u128 % (2 * spread as u128 + 1)) as u64;
+            (interval - spread).saturating_add(offset)
+        };
+        self.next_announce_ms = Some(self.now_ms.saturating_add(interval));
+    }
+
+    /// Next value of the jitter generator (SplitMix64)
+    fn next_jitter(&mut self) -> u64 {
+        self.jitter_state = self.jitter_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.jitter_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
     /// Check if sync is needed based on received announcement
+    ///
+    /// Same as [`Self::check_sync_needed_at`] with the last time given to
+    /// the synchronizer.
     pub fn check_sync_needed(
         &mut self,
         remote_version: u32,
         remote_hash: u64,
         local: &Context,
     ) -> Option<SyncMessage> {
+        self.check_sync_needed_at(remote_version, remote_hash, local, self.now_ms)
+    }
+
+    /// Check if sync is needed based on an announcement received at `now_ms`
+    ///
+    /// An incremental request moves to [`SyncState::WaitingForSync`]
+    /// stamped with `now_ms` and is kept as the
+    /// [`pending_request`](Self::pending_request) until its diff arrives,
+    /// even with `auto_sync` off. Announcements that call for the request
+    /// already pending return `None` and leave its timer alone: re-sending
+    /// is driven by [`Self::check_timeout`]. `now_ms` is the same monotonic
+    /// millisecond clock as [`Self::should_announce_at`] and
+    /// [`Self::check_timeout`].
+    pub fn check_sync_needed_at(
+        &mut self,
+        remote_version: u32,
+        remote_hash: u64,
+        local: &Context,
+        now_ms: u64,
+    ) -> Option<SyncMessage> {
+        self.now_ms = self.now_ms.max(now_ms);
+        let pending = self.pending_request.take();
+        let retries = core::mem::take(&mut self.sync_retries);
+
         // Version and hash match = all good
-        if remote_version == local.version() && remote_hash == local.hash() {
+        if remote_version == local.dictionary_version() && remote_hash == local.hash() {
             self.state = SyncState::Synchronized;
             self.remote_version = Some(remote_version);
             return None;
         }
 
         // Calculate version gap
-        let gap = remote_version.abs_diff(local.version());
+        let gap = remote_version.abs_diff(local.dictionary_version());
 
         if gap > self.config.max_version_gap {
             // Too far behind, need full resync
@@ -227,7 +451,7 @@ impl Synchronizer {
         }
 
         // Hash mismatch with same version = diverged
-        if remote_version == local.version() && remote_hash != local.hash() {
+        if remote_version == local.dictionary_version() && remote_hash != local.hash() {
             self.state = SyncState::Diverged;
             return Some(SyncMessage::Request(SyncRequest {
                 from_version: 0,
@@ -236,14 +460,25 @@ impl Synchronizer {
         }
 
         // Request incremental sync
-        self.state = SyncState::WaitingForSync { requested_at: 0 };
         self.remote_version = Some(remote_version);
+        let request = SyncRequest {
+            from_version: local.dictionary_version(),
+            to_version: Some(remote_version),
+        };
+        if pending.as_ref() == Some(&request)
+            && matches!(self.state, SyncState::WaitingForSync { .. })
+        {
+            self.pending_request = pending;
+            self.sync_retries = retries;
+            return None;
+        }
+        self.state = SyncState::WaitingForSync {
+            requested_at: self.now_ms,
+        };
+        self.pending_request = Some(request.clone());
 
         if self.config.auto_sync {
-            Some(SyncMessage::Request(SyncRequest {
-                from_version: local.version(),
-                to_version: Some(remote_version),
-            }))
+            Some(SyncMessage::Request(request))
         } else {
             None
         }
@@ -275,8 +510,8 @@ impl Synchronizer {
             .collect();
 
         SyncDiff {
-            base_version: old_ctx.version(),
-            new_version: new_ctx.version(),
+            base_version: old_ctx.dictionary_version(),
+            new_version: new_ctx.dictionary_version(),
             added,
             removed,
             hash: new_ctx.hash(),
@@ -300,7 +535,7 @@ impl Synchronizer {
 
         SyncDiff {
             base_version: from_version,
-            new_version: ctx.version(),
+            new_version: ctx.dictionary_version(),
             added,
             removed: Vec::new(),
             hash: ctx.hash(),
@@ -308,19 +543,30 @@ impl Synchronizer {
     }
 
     /// Apply a diff to a context
+    ///
+    /// A diff that would leave more than `max_patterns` patterns, or
+    /// carries a pattern beyond the size limit, is refused with
+    /// [`ContextError::DictionaryFull`] or
+    /// [`ContextError::PatternTooLarge`] before the context is touched
+    /// (see [`Context::check_pattern_changes`]).
     pub fn apply_diff(context: &mut Context, diff: &SyncDiff) -> Result<()> {
+        context.check_pattern_changes(
+            &diff.removed,
+            diff.added.iter().map(|(id, pattern)| (*id, pattern)),
+        )?;
+
         // Remove old patterns
         for id in &diff.removed {
             context.remove_pattern(*id);
         }
 
-        // Add new patterns
+        // Add new patterns (within the limits checked above)
         for (id, pattern) in &diff.added {
-            context.set_pattern(*id, pattern.clone());
+            context.set_pattern(*id, pattern.clone())?;
         }
 
         // Update version
-        context.set_version(diff.new_version);
+        context.set_dictionary_version(diff.new_version);
 
         // Verify hash
         let actual_hash = context.hash();
@@ -335,6 +581,27 @@ impl Synchronizer {
         Ok(())
     }
 
+    /// Apply a diff after checking it with [`SyncDiff::preview`]
+    ///
+    /// Refuses with [`ContextError::DiffConflict`], leaving the context
+    /// untouched, when an addition would overwrite a different pattern,
+    /// unless `force` is set. Returns the preview of the applied diff.
+    pub fn apply_diff_checked(
+        context: &mut Context,
+        diff: &SyncDiff,
+        force: bool,
+    ) -> Result<DiffPreview> {
+        let preview = diff.preview(context);
+        if !force && !preview.is_clean() {
+            return Err(ContextError::DiffConflict {
+                conflicts: preview.conflicts.len(),
+            }
+            .into());
+        }
+        Self::apply_diff(context, diff)?;
+        Ok(preview)
+    }
+
     /// Handle a received diff
     pub fn handle_diff(&mut self, diff: &SyncDiff, context: &mut Context) -> Result<()> {
         self.state = SyncState::Applying;
@@ -342,11 +609,14 @@ impl Synchronizer {
         match Self::apply_diff(context, diff) {
             Ok(()) => {
                 self.state = SyncState::Synchronized;
-                self.local_version = context.version();
+                self.local_version = context.dictionary_version();
+                self.pending_request = None;
+                self.sync_retries = 0;
                 Ok(())
             }
             Err(e) => {
                 self.state = SyncState::Diverged;
+                self.pending_request = None;
                 Err(e)
             }
         }
@@ -355,7 +625,7 @@ impl Synchronizer {
     /// Create an announcement message for a context
     pub fn create_announce(context: &Context) -> SyncMessage {
         SyncMessage::Announce(SyncAnnounce {
-            version: context.version(),
+            version: context.dictionary_version(),
             hash: context.hash(),
             pattern_count: context.pattern_count() as u16,
         })
@@ -392,15 +662,100 @@ impl Synchronizer {
         SyncMessage::DetailResponse(DetailResponse { sequence, data })
     }
 
-    /// Check if sync has timed out
-    pub fn check_timeout(&mut self, current_time: u64) -> bool {
-        if let SyncState::WaitingForSync { requested_at } = self.state {
-            if current_time - requested_at > self.config.sync_timeout {
-                self.state = SyncState::Diverged;
-                return true;
+    /// Answer a `ReqDetail` from the emitter-side cache
+    ///
+    /// Returns a `DetailResponse` on a hit and `NotAvailable` when the
+    /// sequence was evicted. Returns `None` when the rate limit for the
+    /// current announce interval is exhausted.
+    pub fn handle_detail_request(
+        &mut self,
+        sequence: u32,
+        cache: &DetailCache,
+    ) -> Option<SyncMessage> {
+        if !self.take_detail_budget() {
+            return None;
+        }
+        Some(match cache.get(sequence) {
+            Some(entry) => Self::create_detail_response(entry.sequence, entry.data.clone()),
+            None => SyncMessage::NotAvailable(sequence),
+        })
+    }
+
+    /// Answer a `ReqRange` from the emitter-side cache
+    ///
+    /// Returns one `DetailResponse` per cached entry of the requested
+    /// source within `[from_timestamp, to_timestamp]`, oldest first.
+    /// Entries already evicted are simply absent. Each response counts
+    /// against the rate limit; the result is truncated when it runs out.
+    pub fn handle_range_request(
+        &mut self,
+        request: &RangeRequest,
+        cache: &DetailCache,
+    ) -> Vec<SyncMessage> {
+        let mut responses = Vec::new();
+        for entry in cache.range(
+            request.source_id,
+            request.from_timestamp,
+            request.to_timestamp,
+        ) {
+            if !self.take_detail_budget() {
+                break;
             }
+            responses.push(Self::create_detail_response(
+                entry.sequence,
+                entry.data.clone(),
+            ));
+        }
+        responses
+    }
+
+    /// Consume one detail response from the rate-limit budget
+    fn take_detail_budget(&mut self) -> bool {
+        let max = self.config.max_detail_responses;
+        if max != 0 && self.detail_responses_sent >= max {
+            return false;
+        }
+        self.detail_responses_sent += 1;
+        true
+    }
+
+    /// Check if the pending sync request has timed out at `now_ms`
+    ///
+    /// Returns `true` once `sync_timeout` ms have passed since the request
+    /// was sent. While `max_sync_retries` is not exhausted the request is
+    /// retried: the state stays [`SyncState::WaitingForSync`], restamped
+    /// with `now_ms`, and the caller re-sends
+    /// [`pending_request`](Self::pending_request). After the last retry the
+    /// state becomes [`SyncState::Diverged`] and the request is dropped.
+    pub fn check_timeout(&mut self, now_ms: u64) -> bool {
+        self.now_ms = self.now_ms.max(now_ms);
+        let SyncState::WaitingForSync { requested_at } = self.state else {
+            return false;
+        };
+        if now_ms.saturating_sub(requested_at) < self.config.sync_timeout {
+            return false;
+        }
+
+        if self.sync_retries < self.config.max_sync_retries {
+            self.sync_retries += 1;
+            self.state = SyncState::WaitingForSync {
+                requested_at: now_ms,
+            };
+        } else {
+            self.state = SyncState::Diverged;
+            self.pending_request = None;
         }
-        false
+        true
+    }
+
+    /// Incremental sync request still awaiting its diff, to (re-)send
+    pub fn pending_request(&self) -> Option<&SyncRequest> {
+        self.pending_request.as_ref()
+    }
+
+    /// Retries of the pending sync request so far
+    pub fn sync_retries(&self) -> u32 {
+        self.sync_retries
     }
 
     /// Reset synchronizer state
@@ -409,7 +764,154 @@ impl Synchronizer {
         self.local_version = 0;
         self.remote_version = None;
         self.messages_since_announce = 0;
-        self.pending_requests.clear();
+        self.next_announce_ms = None;
+        self.detail_responses_sent = 0;
+        self.pending_request = None;
+        self.sync_retries = 0;
+    }
+}
+
+/// One cached full-resolution sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailEntry {
+    /// Sequence number of the message that carried the sample
+    pub sequence: u32,
+    /// Full uncompressed data
+    pub data: RawData,
+}
+
+/// Emitter-side ring buffer of recent full-resolution samples
+///
+/// Answers `ReqDetail` (by sequence) and `ReqRange` (by timestamp)
+/// requests. All storage is allocated up front, so memory is bounded by
+/// `capacity × size_of::<Option<DetailEntry>>()` regardless of traffic.
+/// When full, the oldest entry is overwritten.
+///
+/// With a `max_age`, entries whose timestamp is more than `max_age`
+/// older than the newest cached sample are treated as evicted. The
+/// newest timestamp is the cache's only clock, which keeps it usable
+/// without a system time source.
+#[derive(Debug, Clone)]
+pub struct DetailCache {
+    /// Fixed-size slot storage
+    slots: Vec<Option<DetailEntry>>,
+    /// Next slot to write
+    head: usize,
+    /// Number of occupied slots
+    len: usize,
+    /// Maximum age relative to the newest sample (timestamp units)
+    max_age: Option<u64>,
+    /// Timestamp of the newest sample
+    newest_timestamp: u64,
+}
+
+impl DetailCache {
+    /// Create a cache holding at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize(capacity, None);
+        Self {
+            slots,
+            head: 0,
+            len: 0,
+            max_age: None,
+            newest_timestamp: 0,
+        }
+    }
+
+    /// Create a cache that also expires samples older than `max_age`
+    pub fn with_max_age(capacity: usize, max_age: u64) -> Self {
+        Self {
+            max_age: Some(max_age),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// Maximum number of samples
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of occupied slots (may include expired entries)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Upper bound on the memory used by the slot storage, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.capacity() * core::mem::size_of::<Option<DetailEntry>>()
+    }
+
+    /// Store a sample sent with the given sequence number
+    pub fn insert(&mut self, sequence: u32, data: RawData) {
+        if self.slots.is_empty() {
+            return;
+        }
+        self.newest_timestamp = self.newest_timestamp.max(data.timestamp);
+        self.slots[self.head] = Some(DetailEntry { sequence, data });
+        self.head = (self.head + 1) % self.slots.len();
+        self.len = (self.len + 1).min(self.slots.len());
+    }
+
+    /// Look up the most recent live entry for a sequence number
+    pub fn get(&self, sequence: u32) -> Option<&DetailEntry> {
+        self.iter_newest_first()
+            .find(|entry| entry.sequence == sequence)
+    }
+
+    /// Live entries of `source_id` with `from <= timestamp <= to`, oldest first
+    pub fn range(
+        &self,
+        source_id: u32,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> impl Iterator<Item = &DetailEntry> + '_ {
+        self.iter_oldest_first().filter(move |entry| {
+            entry.data.source_id == source_id
+                && entry.data.timestamp >= from_timestamp
+                && entry.data.timestamp <= to_timestamp
+        })
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            *slot = None;
+        }
+        self.head = 0;
+        self.len = 0;
+        self.newest_timestamp = 0;
+    }
+
+    /// Live entries from oldest to newest
+    fn iter_oldest_first(&self) -> impl Iterator<Item = &DetailEntry> + '_ {
+        let cap = self.slots.len();
+        let start = (self.head + cap - self.len) % cap.max(1);
+        (0..self.len).filter_map(move |i| self.live(start + i))
+    }
+
+    /// Live entries from newest to oldest
+    fn iter_newest_first(&self) -> impl Iterator<Item = &DetailEntry> + '_ {
+        let cap = self.slots.len();
+        (1..=self.len).filter_map(move |i| self.live(self.head + cap - i))
+    }
+
+    /// Entry in the slot at `index` (modulo capacity) if not expired
+    fn live(&self, index: usize) -> Option<&DetailEntry> {
+        let entry = self.slots[index % self.slots.len()].as_ref()?;
+        match self.max_age {
+            Some(max_age)
+                if entry.data.timestamp.saturating_add(max_age) < self.newest_timestamp =>
+            {
+                None
+            }
+            _ => Some(entry),
+        }
     }
 }
 
@@ -419,6 +921,91 @@ impl Default for Synchronizer {
     }
 }
 
+/// Wire-format version negotiation for bidirectional links
+///
+/// One side sends [`VersionNegotiation::offer`]; the other replies through
+/// [`VersionNegotiation::handle`] with a `VersionAccept` carrying the
+/// highest version both support. Both sides then pass
+/// [`VersionNegotiation::agreed`] to [`crate::Encoder::set_wire_version`].
+/// Until an agreement is reached, encoders should keep
+/// [`WireVersion::LEGACY`], which every decoder reads.
+///
+/// # Example
+///
+/// ```
+/// use alec::sync::VersionNegotiation;
+/// use alec::WireVersion;
+///
+/// let mut device = VersionNegotiation::new();
+/// let mut gateway = VersionNegotiation::with_max(WireVersion::LEGACY);
+///
+/// let reply = gateway.handle(&device.offer()).unwrap();
+/// device.handle(&reply);
+///
+/// assert_eq!(device.agreed(), Some(WireVersion::LEGACY));
+/// assert_eq!(gateway.agreed(), Some(WireVersion::LEGACY));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionNegotiation {
+    max: WireVersion,
+    agreed: Option<WireVersion>,
+}
+
+impl VersionNegotiation {
+    /// Negotiate up to [`WireVersion::CURRENT`]
+    pub fn new() -> Self {
+        Self::with_max(WireVersion::CURRENT)
+    }
+
+    /// Negotiate up to `max`
+    pub fn with_max(max: WireVersion) -> Self {
+        Self { max, agreed: None }
+    }
+
+    /// Highest version this side supports
+    pub fn max(&self) -> WireVersion {
+        self.max
+    }
+
+    /// Version settled with the peer, if any
+    pub fn agreed(&self) -> Option<WireVersion> {
+        self.agreed
+    }
+
+    /// Offer message announcing our highest version
+    pub fn offer(&self) -> SyncMessage {
+        SyncMessage::VersionOffer(self.max)
+    }
+
+    /// Answer a peer offer, settling on the highest common version
+    pub fn accept(&mut self, offered: WireVersion) -> SyncMessage {
+        let version = offered.min(self.max);
+        self.agreed = Some(version);
+        SyncMessage::VersionAccept(version)
+    }
+
+    /// Process a negotiation message from the peer
+    ///
+    /// Returns the reply to send for an offer. An accept is recorded only
+    /// if it does not exceed our maximum; other messages are ignored.
+    pub fn handle(&mut self, message: &SyncMessage) -> Option<SyncMessage> {
+        match message {
+            SyncMessage::VersionOffer(offered) => Some(self.accept(*offered)),
+            SyncMessage::VersionAccept(version) if *version <= self.max => {
+                self.agreed = Some(*version);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for VersionNegotiation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Message type identifier for serialization
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -429,6 +1016,9 @@ pub enum SyncMessageType {
     ReqDetail = 0x13,
     ReqRange = 0x14,
     DetailResponse = 0x15,
+    NotAvailable = 0x16,
+    VersionOffer = 0x17,
+    VersionAccept = 0x18,
 }
 
 impl SyncMessage {
@@ -441,6 +1031,9 @@ impl SyncMessage {
             SyncMessage::ReqDetail(_) => SyncMessageType::ReqDetail,
             SyncMessage::ReqRange(_) => SyncMessageType::ReqRange,
             SyncMessage::DetailResponse(_) => SyncMessageType::DetailResponse,
+            SyncMessage::NotAvailable(_) => SyncMessageType::NotAvailable,
+            SyncMessage::VersionOffer(_) => SyncMessageType::VersionOffer,
+            SyncMessage::VersionAccept(_) => SyncMessageType::VersionAccept,
         }
     }
 
@@ -496,6 +1089,18 @@ impl SyncMessage {
                 bytes.extend_from_slice(&d.data.timestamp.to_be_bytes());
                 bytes.extend_from_slice(&d.data.source_id.to_be_bytes());
             }
+            SyncMessage::NotAvailable(seq) => {
+                bytes.push(SyncMessageType::NotAvailable as u8);
+                bytes.extend_from_slice(&seq.to_be_bytes());
+            }
+            SyncMessage::VersionOffer(version) => {
+                bytes.push(SyncMessageType::VersionOffer as u8);
+                bytes.push(version.to_byte());
+            }
+            SyncMessage::VersionAccept(version) => {
+                bytes.push(SyncMessageType::VersionAccept as u8);
+                bytes.push(version.to_byte());
+            }
         }
 
         bytes
@@ -540,6 +1145,16 @@ impl SyncMessage {
                 let seq = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
                 Some(SyncMessage::ReqDetail(seq))
             }
+            0x16 if data.len() >= 4 => {
+                let seq = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                Some(SyncMessage::NotAvailable(seq))
+            }
+            0x17 if !data.is_empty() => {
+                Some(SyncMessage::VersionOffer(WireVersion::from_byte(data[0])))
+            }
+            0x18 if !data.is_empty() => {
+                Some(SyncMessage::VersionAccept(WireVersion::from_byte(data[0])))
+            }
             0x14 if data.len() >= 20 => {
                 let source_id = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
                 let from_timestamp = u64::from_be_bytes([
@@ -644,6 +1259,26 @@ impl SyncMessage {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_version_messages_roundtrip() {
+        for message in [
+            SyncMessage::VersionOffer(WireVersion::new(1, 3)),
+            SyncMessage::VersionAccept(WireVersion::LEGACY),
+        ] {
+            let bytes = message.to_bytes();
+            assert_eq!(bytes.len(), 2);
+            assert_eq!(SyncMessage::from_bytes(&bytes), Some(message));
+        }
+        assert_eq!(SyncMessage::from_bytes(&[0x17]), None);
+    }
+
+    #[test]
+    fn test_version_negotiation_ignores_unsupported_accept() {
+        let mut negotiation = VersionNegotiation::with_max(WireVersion::LEGACY);
+        negotiation.handle(&SyncMessage::VersionAccept(WireVersion::CURRENT));
+        assert_eq!(negotiation.agreed(), None);
+    }
+
     #[test]
     fn test_sync_announce_creation() {
         let context = Context::new();
@@ -716,7 +1351,7 @@ mod tests {
         assert_eq!(diff.added.len(), 1);
         assert_eq!(diff.removed.len(), 0);
         assert_eq!(diff.base_version, 0);
-        assert_eq!(diff.new_version, new_ctx.version());
+        assert_eq!(diff.new_version, new_ctx.dictionary_version());
     }
 
     #[test]
@@ -758,7 +1393,7 @@ mod tests {
         };
 
         // Need to set version first since diff expects version 5
-        local.set_version(5);
+        local.set_dictionary_version(5);
         let new_diff = SyncDiff {
             hash: local.hash(),
             ..diff
@@ -846,10 +1481,104 @@ mod tests {
         assert!(!sync.should_announce()); // 1 (reset)
     }
 
+    fn timed_sync(interval_ms: u64, jitter_percent: u8, seed: u64) -> Synchronizer {
+        Synchronizer::with_config(SyncConfig {
+            announce_interval: u32::MAX,
+            announce_interval_ms: Some(interval_ms),
+            announce_jitter_percent: jitter_percent,
+            jitter_seed: seed,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_should_announce_at_time_based() {
+        let mut sync = timed_sync(1000, 0, 0);
+
+        // First call schedules the deadline
+        assert!(!sync.should_announce_at(500));
+        assert_eq!(sync.next_announce_ms(), Some(1500));
+        assert!(!sync.should_announce_at(1499));
+        assert!(sync.should_announce_at(1500));
+        assert_eq!(sync.next_announce_ms(), Some(2500));
+
+        // A quiet emitter announces on its next message after the deadline
+        assert!(sync.should_announce_at(3_600_000));
+        assert_eq!(sync.next_announce_ms(), Some(3_601_000));
+    }
+
+    #[test]
+    fn test_should_announce_at_jitter_within_bounds() {
+        let mut sync = timed_sync(10_000, 20, 42);
+        let mut now = 0;
+        sync.should_announce_at(now);
+        let mut intervals = Vec::new();
+        for _ in 0..200 {
+            let deadline = sync.next_announce_ms().unwrap();
+            let interval = deadline - now;
+            assert!((8_000..=12_000).contains(&interval), "{}", interval);
+            intervals.push(interval);
+            now = deadline;
+            assert!(sync.should_announce_at(now));
+        }
+        assert!(intervals.iter().any(|&i| i != intervals[0]));
+
+        // Same seed, same schedule; another seed diverges
+        let mut same = timed_sync(10_000, 20, 42);
+        same.should_announce_at(0);
+        assert_eq!(same.next_announce_ms(), Some(intervals[0]));
+        let mut other = timed_sync(10_000, 20, 43);
+        other.should_announce_at(0);
+        assert_ne!(other.next_announce_ms(), Some(intervals[0]));
+    }
+
+    #[test]
+    fn test_should_announce_at_combined_triggers() {
+        let mut sync = Synchronizer::with_config(SyncConfig {
+            announce_interval: 3,
+            announce_interval_ms: Some(1000),
+            ..Default::default()
+        });
+
+        assert!(!sync.should_announce_at(0)); // 1, deadline 1000
+        assert!(!sync.should_announce_at(10)); // 2
+        assert!(sync.should_announce_at(20)); // 3 - count fires, deadline 1020
+        assert_eq!(sync.next_announce_ms(), Some(1020));
+
+        // Both triggers due at once fire a single announce
+        assert!(!sync.should_announce_at(30)); // 1
+        assert!(!sync.should_announce_at(40)); // 2
+        assert!(sync.should_announce_at(1020)); // 3 and deadline
+        assert_eq!(sync.next_announce_ms(), Some(2020));
+        assert!(!sync.should_announce_at(1020)); // 1
+        assert!(!sync.should_announce_at(1021)); // 2
+        assert!(sync.should_announce_at(1023)); // 3 - count again
+
+        // Time fires before the count
+        assert!(!sync.should_announce_at(1500));
+        assert!(sync.should_announce_at(2023));
+    }
+
+    #[test]
+    fn test_should_announce_delegates_without_time() {
+        let mut sync = Synchronizer::with_config(SyncConfig {
+            announce_interval: 100,
+            announce_interval_ms: Some(1000),
+            ..Default::default()
+        });
+        assert!(!sync.should_announce_at(5000));
+        // Message-count path never advances the clock
+        for _ in 0..98 {
+            assert!(!sync.should_announce());
+        }
+        assert!(sync.should_announce());
+    }
+
     #[test]
     fn test_sync_timeout() {
         let mut sync = Synchronizer::with_config(SyncConfig {
             sync_timeout: 100,
+            max_sync_retries: 0,
             ..Default::default()
         });
 
@@ -863,6 +1592,59 @@ mod tests {
         assert_eq!(sync.state, SyncState::Diverged);
     }
 
+    #[test]
+    fn test_sync_timeout_retries_before_diverging() {
+        let mut sync = Synchronizer::with_config(SyncConfig {
+            sync_timeout: 500,
+            max_sync_retries: 1,
+            ..Default::default()
+        });
+        let local = Context::new();
+
+        let request = sync.check_sync_needed_at(5, 12345, &local, 100);
+        assert!(matches!(request, Some(SyncMessage::Request(_))));
+        assert_eq!(sync.state, SyncState::WaitingForSync { requested_at: 100 });
+        let pending = sync.pending_request().cloned().unwrap();
+        assert_eq!(pending.to_version, Some(5));
+
+        // A repeated announce does not restart the timer
+        assert!(sync.check_sync_needed_at(5, 12345, &local, 300).is_none());
+        assert!(!sync.check_timeout(599));
+
+        // First timeout: one retry of the same request
+        assert!(sync.check_timeout(600));
+        assert_eq!(sync.state, SyncState::WaitingForSync { requested_at: 600 });
+        assert_eq!(sync.sync_retries(), 1);
+        assert_eq!(sync.pending_request(), Some(&pending));
+
+        // Retries exhausted
+        assert!(!sync.check_timeout(1099));
+        assert!(sync.check_timeout(1100));
+        assert_eq!(sync.state, SyncState::Diverged);
+        assert!(sync.pending_request().is_none());
+        assert!(!sync.check_timeout(5000));
+    }
+
+    #[test]
+    fn test_sync_diff_clears_pending_request() {
+        let mut sync = Synchronizer::new();
+        let mut local = Context::new();
+        let mut remote = Context::new();
+        remote
+            .register_pattern(Pattern::new(vec![1, 2, 3]))
+            .unwrap();
+
+        let remote_version = remote.dictionary_version();
+        sync.check_sync_needed_at(remote_version, remote.hash(), &local, 1_000);
+        assert!(sync.pending_request().is_some());
+
+        let diff = Synchronizer::generate_diff(&Context::new(), &remote);
+        sync.handle_diff(&diff, &mut local).unwrap();
+        assert!(sync.is_synchronized());
+        assert!(sync.pending_request().is_none());
+        assert!(!sync.check_timeout(1_000_000));
+    }
+
     #[test]
     fn test_synchronizer_reset() {
         let mut sync = Synchronizer::new();
@@ -876,4 +1658,134 @@ mod tests {
         assert_eq!(sync.local_version, 0);
         assert!(sync.remote_version.is_none());
     }
+
+    fn cache_with(samples: &[(u32, u32, u64)]) -> DetailCache {
+        let mut cache = DetailCache::new(4);
+        for &(seq, source_id, ts) in samples {
+            cache.insert(seq, RawData::with_source(source_id, ts as f64, ts));
+        }
+        cache
+    }
+
+    #[test]
+    fn test_detail_request_hit() {
+        let cache = cache_with(&[(1, 7, 100), (2, 7, 200)]);
+        let mut sync = Synchronizer::new();
+
+        match sync.handle_detail_request(2, &cache) {
+            Some(SyncMessage::DetailResponse(d)) => {
+                assert_eq!(d.sequence, 2);
+                assert_eq!(d.data, RawData::with_source(7, 200.0, 200));
+            }
+            other => panic!("Expected DetailResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_detail_request_evicted() {
+        // Capacity 4: sequences 1 and 2 are overwritten
+        let cache = cache_with(&[
+            (1, 7, 100),
+            (2, 7, 200),
+            (3, 7, 300),
+            (4, 7, 400),
+            (5, 7, 500),
+            (6, 7, 600),
+        ]);
+        assert_eq!(cache.len(), 4);
+        let mut sync = Synchronizer::new();
+
+        assert_eq!(
+            sync.handle_detail_request(1, &cache),
+            Some(SyncMessage::NotAvailable(1))
+        );
+        assert!(matches!(
+            sync.handle_detail_request(3, &cache),
+            Some(SyncMessage::DetailResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_detail_cache_max_age() {
+        let mut cache = DetailCache::with_max_age(8, 150);
+        cache.insert(1, RawData::new(1.0, 100));
+        cache.insert(2, RawData::new(2.0, 200));
+        assert!(cache.get(1).is_some());
+
+        cache.insert(3, RawData::new(3.0, 300));
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(2).is_some());
+    }
+
+    #[test]
+    fn test_range_request_partially_evicted() {
+        let cache = cache_with(&[
+            (1, 7, 100),
+            (2, 7, 200),
+            (3, 9, 300),
+            (4, 7, 400),
+            (5, 7, 500),
+            (6, 7, 600),
+        ]);
+        let mut sync = Synchronizer::new();
+
+        let request = RangeRequest {
+            source_id: 7,
+            from_timestamp: 0,
+            to_timestamp: 500,
+        };
+        let sequences: Vec<u32> = sync
+            .handle_range_request(&request, &cache)
+            .into_iter()
+            .map(|m| match m {
+                SyncMessage::DetailResponse(d) => d.sequence,
+                other => panic!("Expected DetailResponse, got {:?}", other),
+            })
+            .collect();
+
+        // 1 and 2 evicted, 3 is another source, 6 is out of range
+        assert_eq!(sequences, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_detail_rate_limit() {
+        let cache = cache_with(&[(1, 7, 100)]);
+        let mut sync = Synchronizer::with_config(SyncConfig {
+            announce_interval: 2,
+            max_detail_responses: 2,
+            ..Default::default()
+        });
+
+        assert!(sync.handle_detail_request(1, &cache).is_some());
+        assert!(sync.handle_detail_request(9, &cache).is_some());
+        assert!(sync.handle_detail_request(1, &cache).is_none());
+
+        // A new announce interval restores the budget
+        sync.should_announce();
+        assert!(sync.should_announce());
+        assert!(sync.handle_detail_request(1, &cache).is_some());
+    }
+
+    #[test]
+    fn test_detail_cache_memory_bound() {
+        let mut cache = DetailCache::new(16);
+        let bytes = cache.memory_bytes();
+        for i in 0..1000 {
+            cache.insert(i, RawData::new(i as f64, i as u64));
+        }
+        assert_eq!(cache.len(), 16);
+        assert_eq!(cache.capacity(), 16);
+        assert_eq!(cache.memory_bytes(), bytes);
+
+        let empty = DetailCache::new(0);
+        assert!(empty.get(0).is_none());
+        assert_eq!(empty.range(0, 0, u64::MAX).count(), 0);
+    }
+
+    #[test]
+    fn test_not_available_serialization() {
+        let msg = SyncMessage::NotAvailable(42);
+        let bytes = msg.to_bytes();
+        assert_eq!(SyncMessage::from_bytes(&bytes), Some(msg));
+    }
 }
@@ -8,7 +8,8 @@
 //!
 //! Provides TLS and DTLS configuration for ALEC channels.
 //! This module defines the interface - actual TLS implementation
-//! requires the `tls` feature flag.
+//! requires the `tls` feature flag. A UDP transport secured by DTLS,
+//! driven by [`DtlsConfig`], is provided by the `alec-net` crate.
 
 #[cfg(not(feature = "std"))]
 use alloc::{
@@ -91,11 +92,44 @@ impl TlsConfig {
     }
 }
 
+/// Pre-shared key for DTLS PSK cipher suites
+///
+/// The key is never printed by `Debug`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct PskConfig {
+    /// Identity sent by the client, checked by the server
+    pub identity: String,
+    /// Shared secret
+    pub key: Vec<u8>,
+}
+
+impl PskConfig {
+    /// Create a PSK from an identity and a key
+    pub fn new(identity: &str, key: &[u8]) -> Self {
+        Self {
+            identity: identity.to_string(),
+            key: key.to_vec(),
+        }
+    }
+}
+
+impl core::fmt::Debug for PskConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PskConfig")
+            .field("identity", &self.identity)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
 /// DTLS (Datagram TLS) configuration for UDP channels
 #[derive(Debug, Clone, Default)]
 pub struct DtlsConfig {
     /// Base TLS configuration
     pub tls: TlsConfig,
+    /// Pre-shared key; when set, the handshake uses PSK cipher suites
+    /// instead of certificates
+    pub psk: Option<PskConfig>,
     /// MTU for DTLS records
     pub mtu: Option<u16>,
     /// Enable replay protection
@@ -133,6 +167,12 @@ impl DtlsConfig {
         self.retransmit_timeout_ms = Some(timeout_ms);
         self
     }
+
+    /// Use a pre-shared key instead of certificates
+    pub fn with_psk(mut self, identity: &str, key: &[u8]) -> Self {
+        self.psk = Some(PskConfig::new(identity, key));
+        self
+    }
 }
 
 /// Connection state for TLS sessions
@@ -207,6 +247,19 @@ mod tests {
         assert_eq!(dtls.mtu, Some(1400));
         assert_eq!(dtls.retransmit_timeout_ms, Some(500));
         assert!(dtls.replay_protection);
+        assert!(dtls.psk.is_none());
+    }
+
+    #[test]
+    fn test_dtls_psk_redacted() {
+        let dtls = DtlsConfig::new().with_psk("sensor-7", b"secret");
+        let psk = dtls.psk.as_ref().unwrap();
+        assert_eq!(psk.identity, "sensor-7");
+        assert_eq!(psk.key, b"secret");
+        assert_eq!(
+            format!("{:?}", psk),
+            "PskConfig { identity: \"sensor-7\", key: \"<redacted>\" }"
+        );
     }
 
     #[test]
@@ -0,0 +1,93 @@
+//! Encoder-side adaptive encoding selection (`EncoderConfig::adaptive_selection`):
+//! - A slowly drifting source learns to prefer delta encoding
+//! - Learning never costs bytes against the greedy encoder on such a source
+//! - Messages produced from a learned preference still decode
+
+use alec::{Classifier, Context, Decoder, Encoder, EncoderConfig, EncodingType, RawData};
+
+const SOURCE: u32 = 3;
+
+fn adaptive_encoder() -> Encoder {
+    Encoder::with_config(EncoderConfig {
+        adaptive_selection: true,
+        ..Default::default()
+    })
+}
+
+/// Slow upward drift with a small deterministic wobble
+fn drifting_value(i: u64) -> f64 {
+    20.0 + i as f64 * 0.002 + 0.01 * (i as f64 * 0.37).sin()
+}
+
+/// Encode `values` with `encoder`, returning the total serialized size
+fn encode_all(encoder: &mut Encoder, values: &[f64]) -> usize {
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    let mut total = 0;
+    for (i, value) in values.iter().enumerate() {
+        let data = RawData::with_source(SOURCE, *value, i as u64 * 1000);
+        let classification = classifier.classify(&data, &context);
+        total += encoder
+            .encode_to_bytes(&data, &classification, &context)
+            .len();
+        context.observe(&data);
+    }
+    total
+}
+
+#[test]
+fn test_drifting_source_prefers_delta() {
+    let values: Vec<f64> = (0..10_000).map(drifting_value).collect();
+
+    let mut adaptive = adaptive_encoder();
+    let adaptive_bytes = encode_all(&mut adaptive, &values);
+    let mut greedy = Encoder::new();
+    let greedy_bytes = encode_all(&mut greedy, &values);
+
+    assert_eq!(
+        adaptive.preferred_encoding(SOURCE),
+        Some(EncodingType::Delta8)
+    );
+    assert!(
+        adaptive_bytes <= greedy_bytes,
+        "adaptive {} bytes > greedy {} bytes",
+        adaptive_bytes,
+        greedy_bytes
+    );
+}
+
+#[test]
+fn test_disabled_has_no_preference() {
+    let values: Vec<f64> = (0..100).map(drifting_value).collect();
+    let mut encoder = Encoder::new();
+    encode_all(&mut encoder, &values);
+
+    assert!(!encoder.adaptive_selection());
+    assert_eq!(encoder.preferred_encoding(SOURCE), None);
+}
+
+#[test]
+fn test_learned_preference_roundtrips() {
+    // Large jumps keep delta out of reach, so the source settles on raw
+    let values: Vec<f64> = (0..500)
+        .map(|i| if i % 2 == 0 { 4.0e7 } else { -4.0e7 } + 4.0 * i as f64)
+        .collect();
+
+    let mut encoder = adaptive_encoder();
+    let mut decoder = Decoder::new();
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    for (i, value) in values.iter().enumerate() {
+        let data = RawData::with_source(SOURCE, *value, i as u64 * 1000);
+        let classification = classifier.classify(&data, &context);
+        let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert!((decoded.value - value).abs() < 1e-3, "sample {}", i);
+        context.observe(&data);
+    }
+
+    assert_eq!(
+        encoder.preferred_encoding(SOURCE),
+        Some(EncodingType::Raw32)
+    );
+}
@@ -0,0 +1,134 @@
+//! Bulk dictionary import:
+//! - 1000 known patterns register in one pass with a single version bump
+//! - One pattern over `max_patterns` rejects the whole batch, leaving the
+//!   dictionary and its hash untouched
+//! - Numeric seeding shares the same all-or-nothing path
+
+use alec::context::{ContextConfig, Pattern};
+use alec::error::{BulkError, ContextError};
+use alec::Context;
+
+fn constants(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| format!("FW-{:04}", i).into_bytes())
+        .collect()
+}
+
+fn as_slices(patterns: &[Vec<u8>]) -> Vec<&[u8]> {
+    patterns.iter().map(Vec::as_slice).collect()
+}
+
+fn limited(max_patterns: usize) -> Context {
+    Context::with_config(ContextConfig {
+        max_patterns,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_bulk_register_1000_patterns() {
+    let patterns = constants(1000);
+    let mut ctx = limited(1000);
+
+    let codes = ctx.register_patterns_bulk(&as_slices(&patterns)).unwrap();
+
+    assert_eq!(codes.len(), 1000);
+    assert_eq!(ctx.pattern_count(), 1000);
+    assert_eq!(ctx.dictionary_version(), 1);
+    for (pattern, &code) in patterns.iter().zip(&codes) {
+        assert_eq!(ctx.find_pattern(pattern), Some(code));
+        assert_eq!(&ctx.get_pattern(code).unwrap().data[..], &pattern[..]);
+    }
+
+    // Same import on a fresh peer gives the same codes and hash
+    let mut peer = limited(1000);
+    assert_eq!(
+        peer.register_patterns_bulk(&as_slices(&patterns)).unwrap(),
+        codes
+    );
+    assert_eq!(peer.hash(), ctx.hash());
+}
+
+#[test]
+fn test_bulk_register_over_limit_inserts_nothing() {
+    let mut ctx = limited(1000);
+    ctx.register_pattern(Pattern::new(b"existing".to_vec()))
+        .unwrap();
+    let hash = ctx.hash();
+    let version = ctx.dictionary_version();
+
+    // 999 more fit; the 1000th new pattern is one too many
+    let patterns = constants(1000);
+    let err = ctx
+        .register_patterns_bulk(&as_slices(&patterns))
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        BulkError {
+            index: 999,
+            error: ContextError::DictionaryFull { max: 1000 },
+        }
+    );
+    assert_eq!(ctx.pattern_count(), 1);
+    assert_eq!(ctx.hash(), hash);
+    assert_eq!(ctx.dictionary_version(), version);
+    assert_eq!(ctx.find_pattern(&patterns[0]), None);
+}
+
+#[test]
+fn test_bulk_register_reuses_existing_codes() {
+    let mut ctx = Context::new();
+    let known = ctx
+        .register_pattern(Pattern::new(b"degC".to_vec()))
+        .unwrap();
+
+    let codes = ctx
+        .register_patterns_bulk(&[b"hPa", b"degC", b"hPa"])
+        .unwrap();
+
+    assert_eq!(codes[1], known);
+    assert_eq!(codes[0], codes[2]);
+    assert_ne!(codes[0], known);
+    assert_eq!(ctx.pattern_count(), 2);
+    assert_eq!(ctx.get_pattern(codes[0]).unwrap().frequency, 2);
+
+    // Nothing new: the dictionary version stays put
+    let version = ctx.dictionary_version();
+    ctx.register_patterns_bulk(&[b"hPa"]).unwrap();
+    assert_eq!(ctx.dictionary_version(), version);
+}
+
+#[test]
+fn test_bulk_register_rejects_oversize_and_memory() {
+    let mut ctx = Context::new();
+    let oversize = vec![0u8; 256];
+    let err = ctx.register_patterns_bulk(&[b"ok", &oversize]).unwrap_err();
+    assert_eq!(err.index, 1);
+    assert!(matches!(err.error, ContextError::PatternTooLarge { .. }));
+
+    let mut ctx = Context::with_config(ContextConfig {
+        max_memory: 900,
+        ..Default::default()
+    });
+    let big = [vec![7u8; 200], vec![8u8; 200], vec![9u8; 200]];
+    let patterns: Vec<&[u8]> = big.iter().map(Vec::as_slice).collect();
+    let err = ctx.register_patterns_bulk(&patterns).unwrap_err();
+    assert_eq!(err.index, 2);
+    assert!(matches!(
+        err.error,
+        ContextError::MemoryLimitExceeded { .. }
+    ));
+    assert_eq!(ctx.pattern_count(), 0);
+}
+
+#[test]
+fn test_seed_from_values() {
+    let mut ctx = Context::new();
+    let codes = ctx.seed_from_values(&[0.0, 100.0, -40.0]).unwrap();
+
+    assert_eq!(codes, vec![0, 1, 2]);
+    assert_eq!(ctx.get_pattern(codes[1]).unwrap().value, Some(100.0));
+    assert_eq!(ctx.find_pattern(&(-40.0f64).to_be_bytes()), Some(codes[2]));
+    assert_eq!(ctx.dictionary_version(), 1);
+}
@@ -0,0 +1,180 @@
+//! Capture analysis (`alec::analysis`):
+//! - A 500-message capture of mixed single and multi messages is tallied
+//!   per message type, encoding, priority, size, source and timestamp
+//! - Decoding with a context reports the success rate and compression ratio
+//! - A corrupted segment in the middle is skipped and reported with its
+//!   offset, and the messages after it are still read
+//! - The report serializes to JSON and displays as a table
+
+#![cfg(feature = "analysis")]
+
+use std::collections::BTreeMap;
+
+use alec::analysis::{analyze_capture, analyze_capture_with_policy, CaptureReport};
+use alec::protocol::ChecksumPolicy;
+use alec::{Classifier, Context, EncodedMessage, Encoder, Priority, RawData};
+
+const START_MS: u64 = 1_741_234_567_000;
+const MESSAGES: usize = 500;
+
+/// Capture of `MESSAGES` checksummed messages: every fifth one is a
+/// 3-channel multi message from source 9, the others come from sources
+/// 1 to 3. Also returns the byte offset of each message.
+fn build_capture() -> (Vec<u8>, Vec<EncodedMessage>, Vec<usize>) {
+    let mut encoder = Encoder::with_checksum();
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    let mut capture = Vec::new();
+    let mut messages = Vec::new();
+    let mut offsets = Vec::new();
+
+    for i in 0..MESSAGES {
+        let timestamp = START_MS + i as u64 * 1_000;
+        let message = if i % 5 == 4 {
+            let base = i as f64 * 0.01;
+            encoder
+                .encode_multi(
+                    &[(11, 20.0 + base), (12, 50.0 - base), (13, 1013.0)],
+                    9,
+                    timestamp,
+                    Priority::P2Important,
+                    &context,
+                )
+                .unwrap()
+        } else {
+            let source_id = (i % 3) as u32 + 1;
+            let value = match source_id {
+                1 => 21.5,
+                2 => 40.0 + (i % 7) as f64 * 0.1,
+                _ => (i as f64 * 0.37).sin() * 500.0,
+            };
+            let data = RawData::with_source(source_id, value, timestamp);
+            let classification = classifier.classify(&data, &context);
+            let message = encoder.encode(&data, &classification, &context);
+            context.observe(&data);
+            message
+        };
+
+        offsets.push(capture.len());
+        capture.extend(encoder.message_to_bytes(&message));
+        messages.push(message);
+    }
+
+    (capture, messages, offsets)
+}
+
+/// Count `key(message)` over `messages`
+fn tally(
+    messages: &[EncodedMessage],
+    key: impl Fn(&EncodedMessage) -> String,
+) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for message in messages {
+        *counts.entry(key(message)).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_capture_tallies() {
+    let (capture, messages, _) = build_capture();
+    let report = analyze_capture(&capture, Some(&Context::new()));
+
+    assert_eq!(report.total_bytes, capture.len());
+    assert_eq!(report.messages, MESSAGES as u64);
+    assert_eq!(report.message_types.get("Data"), Some(&(MESSAGES as u64)));
+    assert_eq!(report.encodings.get("Multi"), Some(&100));
+    assert_eq!(
+        report.encodings,
+        tally(&messages, |m| format!("{:?}", m.encoding_type().unwrap()))
+    );
+    assert_eq!(
+        report.priorities,
+        tally(&messages, |m| format!("{:?}", m.header.priority))
+    );
+    assert_eq!(
+        report.sources.iter().copied().collect::<Vec<_>>(),
+        [1, 2, 3, 9]
+    );
+    assert_eq!(report.first_timestamp, Some(START_MS / 1000));
+    assert_eq!(
+        report.last_timestamp,
+        Some(START_MS / 1000 + MESSAGES as u64 - 1)
+    );
+
+    // Sizes include the 4-byte checksum
+    let sizes: Vec<usize> = messages.iter().map(|m| m.len() + 4).collect();
+    assert_eq!(report.sizes.min, *sizes.iter().min().unwrap());
+    assert_eq!(report.sizes.max, *sizes.iter().max().unwrap());
+    let bucket_total: u64 = report.sizes.buckets.iter().map(|b| b.count).sum();
+    assert_eq!(bucket_total, MESSAGES as u64);
+
+    assert_eq!(report.checksums_verified, MESSAGES as u64);
+    assert_eq!(report.checksum_failures, 0);
+    assert_eq!(report.skipped_bytes, 0);
+    assert!(report.errors.is_empty());
+
+    let decode = report.decode.unwrap();
+    assert_eq!(decode.attempted, MESSAGES as u64);
+    assert_eq!(decode.success_rate(), 1.0);
+    assert_eq!(decode.values, 400 + 100 * 3);
+    assert!(decode.compression_ratio() > 1.0);
+}
+
+#[test]
+fn test_capture_without_context_skips_decoding() {
+    let (capture, _, _) = build_capture();
+    let report = analyze_capture(&capture, None);
+
+    assert_eq!(report.messages, MESSAGES as u64);
+    assert!(report.decode.is_none());
+}
+
+#[test]
+fn test_corrupted_segment_is_reported() {
+    let (mut capture, _, offsets) = build_capture();
+
+    // Overwrite message 250 with garbage and flip a byte in message 300
+    let garbage_start = offsets[250];
+    let garbage_end = offsets[251];
+    for byte in &mut capture[garbage_start..garbage_end] {
+        *byte = 0xFF;
+    }
+    let flipped = offsets[300] + 2;
+    capture[flipped] ^= 0x01;
+
+    let report = analyze_capture_with_policy(&capture, None, ChecksumPolicy::Always);
+
+    assert_eq!(report.messages, MESSAGES as u64 - 2);
+    assert_eq!(report.errors.len(), 2);
+    assert_eq!(report.errors[0].offset, garbage_start);
+    assert_eq!(report.errors[0].skipped, garbage_end - garbage_start);
+    assert_eq!(report.errors[1].offset, offsets[300]);
+    assert_eq!(report.errors[1].skipped, offsets[301] - offsets[300]);
+    assert_eq!(report.errors[1].reason, "checksum mismatch");
+    assert_eq!(report.checksum_failures, 1);
+    assert_eq!(
+        report.skipped_bytes,
+        (garbage_end - garbage_start) + (offsets[301] - offsets[300])
+    );
+    assert_eq!(report.checksums_verified, MESSAGES as u64 - 2);
+}
+
+#[test]
+fn test_report_json_and_display() {
+    let (capture, _, _) = build_capture();
+    let report = analyze_capture(&capture[..capture.len() - 3], Some(&Context::new()));
+
+    // The truncated last message is reported as an error
+    assert_eq!(report.errors.len(), 1);
+
+    let json = report.to_json().unwrap();
+    let parsed: CaptureReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, report);
+
+    let table = report.to_string();
+    assert!(table.starts_with("ALEC capture report"));
+    assert!(table.contains("Encodings"));
+    assert!(table.contains("Compression ratio"));
+    assert!(table.contains("Errors"));
+}
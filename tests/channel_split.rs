@@ -0,0 +1,105 @@
+//! Split channel halves across threads:
+//! - Producer thread with blocking send and consumer polling try_recv
+//! - Producer with try_send backpressure and consumer blocking recv
+//! - No deadlock, no loss, order preserved
+
+#![cfg(feature = "std")]
+
+use alec::channel::MemoryChannel;
+use alec::error::ChannelError;
+use alec::protocol::{EncodedMessage, HeaderLayout, MessageHeader, MessageType, Priority};
+use alec::{AlecError, ChannelRx, ChannelTx};
+use std::thread;
+use std::time::Duration;
+
+const MESSAGES: u32 = 10_000;
+
+fn message(seq: u32) -> EncodedMessage {
+    EncodedMessage::new(
+        MessageHeader {
+            layout: HeaderLayout::default(),
+            message_type: MessageType::Data,
+            priority: Priority::P3Normal,
+            sequence: seq as u16,
+            timestamp: seq,
+            context_version: 0,
+        },
+        vec![0x00, 0x00, 0x42],
+    )
+}
+
+#[test]
+fn test_producer_thread_consumer_try_recv() {
+    let (mut tx, mut rx) = MemoryChannel::with_buffer_size(64).split();
+
+    let producer = thread::spawn(move || {
+        for seq in 0..MESSAGES {
+            tx.send(message(seq)).unwrap();
+        }
+        // Dropping tx closes the sending side
+    });
+
+    let mut received = Vec::with_capacity(MESSAGES as usize);
+    let mut spins = 0u32;
+    loop {
+        match rx.try_recv() {
+            Ok(msg) => {
+                received.push(msg.header.timestamp);
+                spins = 0;
+            }
+            Err(AlecError::Channel(ChannelError::WouldBlock)) => {
+                spins += 1;
+                if spins < 100 {
+                    std::hint::spin_loop();
+                } else {
+                    thread::park_timeout(Duration::from_micros(50));
+                }
+            }
+            Err(AlecError::Channel(ChannelError::Disconnected { .. })) => break,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    producer.join().unwrap();
+    assert_eq!(received, (0..MESSAGES).collect::<Vec<_>>());
+    assert_eq!(rx.metrics().messages_received, MESSAGES as u64);
+}
+
+#[test]
+fn test_try_send_backpressure() {
+    let (mut tx, mut rx) = MemoryChannel::with_buffer_size(16).split();
+
+    let producer = thread::spawn(move || {
+        let mut would_block = 0u32;
+        for seq in 0..MESSAGES {
+            let mut pending = message(seq);
+            loop {
+                match tx.try_send(pending) {
+                    Ok(()) => break,
+                    Err(e) if e.is_would_block() => {
+                        would_block += 1;
+                        pending = e.into_inner();
+                        thread::yield_now();
+                    }
+                    Err(e) => panic!("unexpected error: {:?}", e),
+                }
+            }
+        }
+        would_block
+    });
+
+    let mut next = 0;
+    while next < MESSAGES {
+        let msg = rx.recv(Duration::from_secs(5)).unwrap();
+        assert_eq!(msg.header.timestamp, next);
+        next += 1;
+        if next % 1000 == 0 {
+            // Let the buffer fill up
+            thread::sleep(Duration::from_millis(2));
+        }
+    }
+
+    let would_block = producer.join().unwrap();
+    assert!(would_block > 0);
+    assert!(rx.is_empty());
+}
@@ -0,0 +1,136 @@
+//! Checksum algorithms (`ChecksumAlgorithm`, `EncoderConfig::crc32c_policy`):
+//! - xxHash32 and CRC-32C messages round-trip through the same decoder
+//! - CRC-32C is selected per priority and costs the header extension
+//! - Every 2-bit error in a CRC-32C message body is caught and reported
+//!   as `InvalidCrc32c`
+
+use alec::error::DecodeError;
+use alec::protocol::{FLAG_CRC32C, KNOWN_FLAGS};
+use alec::{
+    AlecError, ChecksumAlgorithm, ChecksumPolicy, Classifier, Context, Decoder, EncodedMessage,
+    Encoder, EncoderConfig, HeaderExtension, Priority, RawData,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+fn encoder(crc32c_policy: ChecksumPolicy) -> Encoder {
+    Encoder::with_config(EncoderConfig {
+        checksum_policy: ChecksumPolicy::Always,
+        crc32c_policy,
+        ..Default::default()
+    })
+}
+
+/// Encode a value at `priority` without the operator-override extension
+fn encode(encoder: &mut Encoder, value: f64, priority: Priority) -> EncodedMessage {
+    let context = Context::new();
+    let data = RawData::new(value, START_MS);
+    let mut classification = Classifier::default().classify(&data, &context);
+    classification.priority = priority;
+    encoder.encode(&data, &classification, &context)
+}
+
+#[test]
+fn test_crc32c_matches_standard() {
+    assert_eq!(KNOWN_FLAGS & FLAG_CRC32C, FLAG_CRC32C);
+    // CRC-32C check value, as computed by hardware CRC units
+    assert_eq!(
+        ChecksumAlgorithm::Crc32c.checksum(b"123456789"),
+        0xE306_9283
+    );
+}
+
+#[test]
+fn test_roundtrip_both_algorithms() {
+    let context = Context::new();
+    for (policy, algorithm) in [
+        (ChecksumPolicy::Never, ChecksumAlgorithm::Xxh32),
+        (ChecksumPolicy::Always, ChecksumAlgorithm::Crc32c),
+    ] {
+        let mut encoder = encoder(policy);
+        let data = RawData::new(21.5, START_MS);
+        let classification = Classifier::default().classify(&data, &context);
+        let message = encoder.encode(&data, &classification, &context);
+        assert_eq!(message.header.checksum_algorithm(), algorithm);
+
+        let bytes = encoder.message_to_bytes(&message);
+        let decoded = Decoder::with_checksum_verification()
+            .decode_bytes(&bytes, &context)
+            .unwrap();
+        assert_eq!(decoded.value, 21.5);
+    }
+}
+
+#[test]
+fn test_crc32c_per_priority() {
+    let mut encoder = encoder(ChecksumPolicy::ForPriorityAtOrAbove(Priority::P1Critical));
+    assert_eq!(
+        encoder.checksum_algorithm(Priority::P1Critical),
+        Some(ChecksumAlgorithm::Crc32c)
+    );
+    assert_eq!(
+        encoder.checksum_algorithm(Priority::P3Normal),
+        Some(ChecksumAlgorithm::Xxh32)
+    );
+
+    let critical = encode(&mut encoder, 21.5, Priority::P1Critical);
+    let normal = encode(&mut encoder, 21.5, Priority::P3Normal);
+    assert!(critical.header.extension().is_some());
+    assert!(normal.header.extension().is_none());
+    let critical = encoder.message_to_bytes(&critical);
+    let normal = encoder.message_to_bytes(&normal);
+    // Same payload; the CRC-32C flag needs a header extension
+    assert_eq!(critical.len(), normal.len() + HeaderExtension::SIZE);
+
+    let mut decoder = Decoder::with_checksum_verification();
+    let context = Context::new();
+    for bytes in [&critical, &normal] {
+        assert_eq!(decoder.decode_bytes(bytes, &context).unwrap().value, 21.5);
+    }
+
+    // CRC-32C is never used without a checksum
+    let unchecked = Encoder::with_config(EncoderConfig {
+        crc32c_policy: ChecksumPolicy::Always,
+        ..Default::default()
+    });
+    assert_eq!(unchecked.checksum_algorithm(Priority::P1Critical), None);
+}
+
+#[test]
+fn test_crc32c_catches_every_two_bit_error() {
+    let mut encoder = encoder(ChecksumPolicy::Always);
+    let message = encode(&mut encoder, 21.5, Priority::P1Critical);
+    let bytes = encoder.message_to_bytes(&message);
+    let context = Context::new();
+    let mut decoder = Decoder::with_checksum_verification();
+
+    // Flip every pair of bits after the header: payload and checksum
+    let header_len = message.header.encoded_len();
+    let bits = (bytes.len() - header_len) * 8;
+    let mut patterns = 0;
+    for first in 0..bits {
+        for second in first + 1..bits {
+            let mut corrupted = bytes.clone();
+            for bit in [first, second] {
+                corrupted[header_len + bit / 8] ^= 0x80 >> (bit % 8);
+            }
+            let err = decoder.decode_bytes(&corrupted, &context).unwrap_err();
+            assert!(
+                matches!(err, AlecError::Decode(DecodeError::InvalidCrc32c { .. })),
+                "bits {} and {}: {:?}",
+                first,
+                second,
+                err
+            );
+            patterns += 1;
+        }
+    }
+    assert_eq!(patterns, bits * (bits - 1) / 2);
+    assert!(decoder.recent_checksum_failures() > 0);
+
+    let err = DecodeError::InvalidCrc32c {
+        expected: 1,
+        actual: 2,
+    };
+    assert_eq!(err.checksum_algorithm(), Some(ChecksumAlgorithm::Crc32c));
+}
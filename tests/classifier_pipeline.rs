@@ -0,0 +1,417 @@
+//! Two-stage classification (`AnomalyScorer` then `PriorityMapper`):
+//! - With the default mapping, `Classifier::classify` gives the same
+//!   priority, reason, delta and confidence as the single-stage classifier
+//!   it replaced, over a corpus of sources, configurations and values
+//!   (none stuck: stuck sources are now escalated to P2)
+//! - The classification carries every label, the deciding one included
+//! - Custom tables diverge from the defaults: per-source and per-score
+//!   rules, remapped defaults, rules for the labels the default table
+//!   ignores (outlier) and for stuck sources
+
+use alec::classifier::{
+    AnomalyType, ClassifierConfig, CriticalThresholds, LabelKind, PriorityRule, TrendRule,
+};
+use alec::{
+    AnomalyLabel, Classification, ClassificationReason, Classifier, Context, Priority,
+    PriorityMapper, RawData,
+};
+
+/// The single-stage classifier, as it was before the split
+mod legacy {
+    use super::*;
+
+    pub fn classify(
+        config: &ClassifierConfig,
+        data: &RawData,
+        context: &Context,
+    ) -> Classification {
+        let prediction = match context.predict(data.source_id) {
+            Some(p) => p,
+            None => return Classification::no_prediction(),
+        };
+        let absolute = (data.value - prediction.value).abs();
+        let relative = if prediction.value.abs() > f64::EPSILON {
+            absolute / prediction.value.abs()
+        } else {
+            absolute
+        };
+
+        if let Some(thresholds) = config.critical_thresholds.get(&data.source_id) {
+            let violated = if data.value < thresholds.min {
+                Some(thresholds.min)
+            } else if data.value > thresholds.max {
+                Some(thresholds.max)
+            } else {
+                None
+            };
+            if let Some(threshold) = violated {
+                return Classification::new(
+                    Priority::P1Critical,
+                    ClassificationReason::ThresholdExceeded {
+                        threshold,
+                        actual: data.value,
+                    },
+                    relative,
+                    1.0,
+                );
+            }
+        }
+
+        let trend = trend(config, data, context, relative, prediction.confidence);
+        if let Some(c) = trend
+            .as_ref()
+            .filter(|c| c.priority == Priority::P1Critical)
+        {
+            return c.clone();
+        }
+
+        if relative > config.anomaly_threshold {
+            let (priority, anomaly_type) = if relative > config.critical_anomaly_threshold {
+                (Priority::P1Critical, AnomalyType::ExtremeDeviation)
+            } else {
+                (Priority::P2Important, AnomalyType::SignificantDeviation)
+            };
+            return Classification::new(
+                priority,
+                ClassificationReason::AnomalyDetected { anomaly_type },
+                relative,
+                prediction.confidence,
+            );
+        }
+
+        if let Some(c) = trend {
+            return c;
+        }
+
+        let (priority, reason) = if relative < config.minimum_delta_threshold {
+            (
+                Priority::P5Disposable,
+                ClassificationReason::BelowMinimumDelta,
+            )
+        } else if config.scheduled_interval > 0 {
+            (
+                Priority::P3Normal,
+                ClassificationReason::ScheduledTransmission,
+            )
+        } else {
+            (Priority::P4Deferred, ClassificationReason::NormalValue)
+        };
+        Classification::new(priority, reason, relative, prediction.confidence)
+    }
+
+    fn trend(
+        config: &ClassifierConfig,
+        data: &RawData,
+        context: &Context,
+        relative: f64,
+        confidence: f32,
+    ) -> Option<Classification> {
+        let rule = config.trend_rules.get(&data.source_id)?;
+        let window = rule.window.max(2);
+        let samples = context.samples(data.source_id);
+        if samples.len() + 1 < window {
+            return None;
+        }
+        let recent = &samples[samples.len() + 1 - window..];
+        let slope_per_sec = fit_slope(recent, data.timestamp, data.value)?;
+        let slope = slope_per_sec * 60.0;
+        let projected_crossing_secs = config
+            .critical_thresholds
+            .get(&data.source_id)
+            .and_then(|t| {
+                if slope_per_sec > 0.0 {
+                    Some((t.max - data.value) / slope_per_sec)
+                } else if slope_per_sec < 0.0 {
+                    Some((data.value - t.min) / -slope_per_sec)
+                } else {
+                    None
+                }
+            })
+            .filter(|secs| *secs >= 0.0);
+
+        let priority = if rule
+            .crossing_horizon_secs
+            .zip(projected_crossing_secs)
+            .is_some_and(|(horizon, secs)| secs <= horizon)
+        {
+            Priority::P1Critical
+        } else if rule.max_slope_per_min.is_some_and(|max| slope.abs() > max) {
+            Priority::P2Important
+        } else {
+            return None;
+        };
+        Some(Classification::new(
+            priority,
+            ClassificationReason::TrendDetected {
+                slope,
+                projected_crossing_secs,
+            },
+            relative,
+            confidence,
+        ))
+    }
+
+    fn fit_slope(samples: &[(u64, f64)], timestamp: u64, value: f64) -> Option<f64> {
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .copied()
+            .chain(std::iter::once((timestamp, value)))
+            .map(|(t, v)| ((t as f64 - timestamp as f64) / 1000.0, v))
+            .collect();
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_v = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (t, v) in points {
+            covariance += (t - mean_t) * (v - mean_v);
+            variance += (t - mean_t) * (t - mean_t);
+        }
+        (variance > 0.0).then(|| covariance / variance)
+    }
+}
+
+/// Configurations exercising every branch of the legacy classifier
+fn configs() -> Vec<ClassifierConfig> {
+    let mut thresholds = ClassifierConfig::default();
+    for source in 0..4 {
+        thresholds
+            .critical_thresholds
+            .insert(source, CriticalThresholds::new(0.0, 40.0));
+    }
+
+    let mut trends = thresholds.clone();
+    for source in 0..4 {
+        trends.trend_rules.insert(
+            source,
+            TrendRule {
+                window: 4,
+                max_slope_per_min: Some(6.0),
+                crossing_horizon_secs: Some(20.0),
+            },
+        );
+    }
+
+    vec![
+        ClassifierConfig::default(),
+        ClassifierConfig {
+            scheduled_interval: 60,
+            ..Default::default()
+        },
+        ClassifierConfig {
+            anomaly_threshold: 0.05,
+            critical_anomaly_threshold: 0.1,
+            minimum_delta_threshold: 0.02,
+            ..Default::default()
+        },
+        thresholds,
+        trends,
+    ]
+}
+
+/// Deterministic values for 4 sources: flat, ramps, steps, around zero
+fn corpus() -> Vec<RawData> {
+    let mut state = 7u64;
+    let mut jitter = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as f64 / (1u64 << 31) as f64 - 0.5
+    };
+    let mut data = Vec::new();
+    for i in 0..120u64 {
+        let t = i * 1000;
+        let step = if i % 17 == 16 { 12.0 } else { 0.0 };
+        data.push(RawData::with_source(0, 20.0 + jitter() * 0.4 + step, t));
+        data.push(RawData::with_source(1, 10.0 + i as f64 * 0.25, t));
+        data.push(RawData::with_source(2, 35.0 - i as f64 * 0.4 + jitter(), t));
+        data.push(RawData::with_source(3, jitter() * 0.05, t));
+    }
+    data
+}
+
+#[test]
+fn test_default_mapping_matches_legacy() {
+    for (n, config) in configs().into_iter().enumerate() {
+        let classifier = Classifier::with_config(config.clone());
+        let mut context = Context::new();
+        let mut priorities = [0usize; 5];
+        for (i, data) in corpus().iter().enumerate() {
+            let expected = legacy::classify(&config, data, &context);
+            let actual = classifier.classify(data, &context);
+            assert_eq!(
+                actual.priority, expected.priority,
+                "config {} value {}",
+                n, i
+            );
+            assert_eq!(actual.reason, expected.reason, "config {} value {}", n, i);
+            assert_eq!(actual.delta.to_bits(), expected.delta.to_bits());
+            assert_eq!(actual.confidence.to_bits(), expected.confidence.to_bits());
+            priorities[actual.priority as usize] += 1;
+            context.observe(data);
+        }
+        println!("config {}: priorities P1-P5 {:?}", n, priorities);
+    }
+}
+
+#[test]
+fn test_labels_explain_priority() {
+    let mut classifier = Classifier::new();
+    classifier.set_critical_thresholds(1, 0.0, 40.0);
+    let mut context = Context::new();
+    for i in 0..20 {
+        context.observe(&RawData::with_source(
+            1,
+            20.0 + (i % 2) as f64 * 0.1,
+            i * 1000,
+        ));
+    }
+
+    let classification = classifier.classify(&RawData::with_source(1, 45.0, 20_000), &context);
+    assert_eq!(classification.priority, Priority::P1Critical);
+    assert!(classification
+        .labels
+        .contains(&AnomalyLabel::ThresholdExceeded {
+            threshold: 40.0,
+            actual: 45.0
+        }));
+    // Also a critical deviation and far outside the history's spread
+    let kinds: Vec<LabelKind> = classification
+        .labels
+        .iter()
+        .map(AnomalyLabel::kind)
+        .collect();
+    assert!(kinds.contains(&LabelKind::CriticalDeviation));
+    assert!(kinds.contains(&LabelKind::Outlier));
+
+    let cold = classifier.classify(&RawData::with_source(9, 1.0, 0), &Context::new());
+    assert!(cold.labels.is_empty());
+    assert_eq!(cold.reason, ClassificationReason::NoPrediction);
+}
+
+/// Context where source `source` has read 20.0 ten times
+fn steady(source: u32) -> Context {
+    let mut context = Context::new();
+    for i in 0..10 {
+        context.observe(&RawData::with_source(source, 20.0, i * 1000));
+    }
+    context
+}
+
+#[test]
+fn test_custom_mapping_diverges() {
+    let context = steady(7);
+    let deviation = RawData::with_source(7, 24.0, 10_000); // 20%
+    let default = Classifier::new();
+    assert_eq!(
+        default.classify(&deviation, &context).priority,
+        Priority::P2Important
+    );
+
+    // Significant deviations of source 7 only, as P3
+    let mapper = PriorityMapper::default().with_rule(
+        PriorityRule::new(LabelKind::PredictionDeviation, Priority::P3Normal).for_source(7),
+    );
+    let custom = Classifier::with_stages(ClassifierConfig::default(), mapper);
+    let classification = custom.classify(&deviation, &context);
+    assert_eq!(classification.priority, Priority::P3Normal);
+    assert_eq!(
+        classification.reason,
+        ClassificationReason::AnomalyDetected {
+            anomaly_type: AnomalyType::SignificantDeviation
+        }
+    );
+    let other = steady(8);
+    assert_eq!(
+        custom
+            .classify(&RawData::with_source(8, 24.0, 10_000), &other)
+            .priority,
+        Priority::P2Important
+    );
+
+    // By score: only deviations above 25% are P2, the others P4
+    let mut mapper = PriorityMapper::default();
+    for rule in mapper.rules_mut() {
+        if rule.label == LabelKind::PredictionDeviation {
+            rule.min_score = 0.25;
+        }
+    }
+    mapper.push_rule(PriorityRule::new(
+        LabelKind::PredictionDeviation,
+        Priority::P4Deferred,
+    ));
+    let by_score = Classifier::with_stages(ClassifierConfig::default(), mapper);
+    assert_eq!(
+        by_score.classify(&deviation, &context).priority,
+        Priority::P4Deferred
+    );
+    let larger = RawData::with_source(7, 25.5, 10_000); // 27.5%
+    assert_eq!(
+        by_score.classify(&larger, &context).priority,
+        Priority::P2Important
+    );
+}
+
+#[test]
+fn test_ignored_labels_mapped() {
+    // Stuck for 11 samples: reported, P2 by default, P4 with a rule
+    let config = ClassifierConfig {
+        stuck_samples: 10,
+        stuck_min_span_ms: 0,
+        ..Default::default()
+    };
+    let context = steady(3);
+    let flat = RawData::with_source(3, 20.0, 10_000);
+    let default = Classifier::with_stages(config.clone(), PriorityMapper::default())
+        .classify(&flat, &context);
+    assert_eq!(default.priority, Priority::P2Important);
+    assert!(default.labels.contains(&AnomalyLabel::Stuck {
+        count: 11,
+        span_ms: 10_000
+    }));
+
+    let mapper = PriorityMapper::default()
+        .with_rule(PriorityRule::new(LabelKind::Stuck, Priority::P4Deferred).with_min_score(10.0));
+    let stuck = Classifier::with_stages(config, mapper).classify(&flat, &context);
+    assert_eq!(stuck.priority, Priority::P4Deferred);
+    assert_eq!(
+        stuck.reason,
+        ClassificationReason::AnomalyDetected {
+            anomaly_type: AnomalyType::Stuck
+        }
+    );
+
+    // A 10-sigma outlier within the deviation thresholds: reported, P4 by
+    // default, P1 with a rule
+    let mut context = Context::new();
+    for i in 0..10 {
+        let value = if i % 2 == 0 { 20.0 } else { 20.1 };
+        context.observe(&RawData::with_source(3, value, i * 1000));
+    }
+    let outlier = RawData::with_source(3, 20.6, 10_000);
+    let default = Classifier::new().classify(&outlier, &context);
+    assert_eq!(default.priority, Priority::P4Deferred);
+    assert!(default
+        .labels
+        .iter()
+        .any(|label| label.kind() == LabelKind::Outlier && label.score() >= 10.0));
+
+    let mut classifier = Classifier::new();
+    classifier.set_mapper(PriorityMapper::default().with_rule(
+        PriorityRule::new(LabelKind::Outlier, Priority::P1Critical).with_min_score(5.0),
+    ));
+    let flagged = classifier.classify(&outlier, &context);
+    assert_eq!(flagged.priority, Priority::P1Critical);
+    assert_eq!(
+        flagged.reason,
+        ClassificationReason::AnomalyDetected {
+            anomaly_type: AnomalyType::OutOfRange
+        }
+    );
+
+    // An empty table puts everything at the fallback
+    classifier.set_mapper(PriorityMapper::empty(Priority::P3Normal));
+    let classification = classifier.classify(&RawData::with_source(3, 30.0, 10_000), &context);
+    assert_eq!(classification.priority, Priority::P3Normal);
+    assert_eq!(classification.reason, ClassificationReason::NormalValue);
+    assert!(!classification.labels.is_empty());
+}
@@ -0,0 +1,179 @@
+//! Composite messages (`Encoder::encode_composite`):
+//! - Values of several channels round-trip under one header, each against
+//!   its own context
+//! - One header for all channels is smaller than one message per channel
+//! - An entry for a channel the receiver does not know fails on its own
+//! - Single-value decoding rejects a composite cleanly
+
+use alec::error::{AlecError, DecodeError};
+use alec::{
+    Classifier, CompositeEntry, Context, Decoder, EncodedMessage, Encoder, EncodingType, Priority,
+    RawData,
+};
+
+const SOURCE: u32 = 2;
+const START_MS: u64 = 1_741_234_567_000;
+
+/// Per-channel contexts after observing a few readings of each channel
+fn contexts(channels: usize) -> Vec<Context> {
+    (0..channels)
+        .map(|channel| {
+            let mut context = Context::new();
+            for i in 0..5 {
+                let value = 20.0 + channel as f64 + i as f64 * 0.1;
+                context.observe(&RawData::new(value, START_MS + i * 1000));
+            }
+            context
+        })
+        .collect()
+}
+
+fn entries<'a>(contexts: &'a [Context], values: &[f64]) -> Vec<CompositeEntry<'a>> {
+    contexts
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(channel, (context, &value))| CompositeEntry {
+            channel_id: channel as u32,
+            value,
+            context,
+        })
+        .collect()
+}
+
+#[test]
+fn test_composite_roundtrip() {
+    let contexts = contexts(4);
+    let values = [20.5, 21.5, 22.5, f64::NAN];
+    let message = Encoder::new()
+        .encode_composite(
+            SOURCE,
+            START_MS + 10_000,
+            Priority::P2Important,
+            &entries(&contexts, &values),
+        )
+        .unwrap();
+    assert!(message.is_composite());
+    assert_eq!(message.encoding_type(), Some(EncodingType::Composite));
+
+    // Survives the wire, checksum included
+    let bytes = message.to_bytes_with_checksum();
+    let message = EncodedMessage::from_bytes_with_checksum(&bytes).unwrap();
+
+    let decoded = Decoder::new()
+        .decode_composite(&message, |channel| contexts.get(channel as usize))
+        .unwrap();
+    assert_eq!(decoded.source_id, SOURCE);
+    assert_eq!(decoded.timestamp, (START_MS + 10_000) / 1000);
+    assert_eq!(decoded.priority, Priority::P2Important);
+    assert_eq!(decoded.values.len(), 4);
+    for (channel, (entry, expected)) in decoded.values.iter().zip(values).enumerate() {
+        assert_eq!(entry.channel_id, channel as u32);
+        let value = entry.value.clone().unwrap();
+        if expected.is_nan() {
+            assert!(value.is_nan());
+        } else {
+            assert!((value - expected).abs() < 0.01, "{} != {}", value, expected);
+        }
+    }
+}
+
+#[test]
+fn test_composite_is_smaller_than_separate_messages() {
+    let contexts = contexts(20);
+    let values: Vec<f64> = (0..20).map(|channel| 20.5 + channel as f64).collect();
+    let timestamp = START_MS + 10_000;
+
+    let mut encoder = Encoder::new();
+    let classifier = Classifier::default();
+    let separate: usize = contexts
+        .iter()
+        .zip(&values)
+        .map(|(context, &value)| {
+            let data = RawData::new(value, timestamp);
+            let classification = classifier.classify(&data, context);
+            encoder
+                .encode_to_bytes(&data, &classification, context)
+                .len()
+        })
+        .sum();
+    let composite = encoder
+        .encode_composite(
+            0,
+            timestamp,
+            Priority::P3Normal,
+            &entries(&contexts, &values),
+        )
+        .unwrap()
+        .len();
+
+    assert!(
+        composite * 2 < separate,
+        "{} bytes composite, {} bytes separate",
+        composite,
+        separate
+    );
+}
+
+#[test]
+fn test_composite_unknown_channel() {
+    let contexts = contexts(3);
+    let message = Encoder::new()
+        .encode_composite(
+            SOURCE,
+            START_MS,
+            Priority::P3Normal,
+            &entries(&contexts, &[20.0, 21.0, 22.0]),
+        )
+        .unwrap();
+
+    // The receiver only knows channels 0 and 2
+    let decoded = Decoder::new()
+        .decode_composite(&message, |channel| {
+            (channel != 1).then(|| &contexts[channel as usize])
+        })
+        .unwrap();
+    assert!(decoded.values[0].value.is_ok());
+    assert_eq!(
+        decoded.values[1].value,
+        Err(DecodeError::UnknownChannel { channel_id: 1 })
+    );
+    assert!(decoded.values[2].value.is_ok());
+}
+
+#[test]
+fn test_single_value_decode_rejects_composite() {
+    let contexts = contexts(2);
+    let message = Encoder::new()
+        .encode_composite(
+            SOURCE,
+            START_MS,
+            Priority::P3Normal,
+            &entries(&contexts, &[20.0, 21.0]),
+        )
+        .unwrap();
+
+    let err = Decoder::new()
+        .decode_bytes(&message.to_bytes(), &contexts[0])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Decode(DecodeError::MalformedMessage { .. })
+    ));
+
+    // A plain message is not a composite either
+    let err = Decoder::new()
+        .decode_composite(
+            &Encoder::new().encode(
+                &RawData::new(20.0, START_MS),
+                &Classifier::default().classify(&RawData::new(20.0, START_MS), &contexts[0]),
+                &contexts[0],
+            ),
+            |_| Some(&contexts[0]),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Decode(DecodeError::MalformedMessage { .. })
+    ));
+}
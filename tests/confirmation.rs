@@ -0,0 +1,158 @@
+//! Confirmations of unchanged values (`Encoder::encode_confirmation`):
+//! - Round-trip through bytes as `Decoded::Confirmation` with the source,
+//!   timestamp and the sequence of the message that carried the value
+//! - Confirmations do not consume sequence numbers
+//! - Compressed timestamps resolve, and later values stay anchored
+//! - A confirmation is smaller than the smallest value-bearing message
+//! - `Decoder::decode` refuses to fabricate a value from a confirmation
+
+use alec::{
+    ChecksumPolicy, Classifier, Context, Decoded, Decoder, EncodedMessage, Encoder, EncoderConfig,
+    EncodingType, Priority, RawData, TimestampMode,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+const SOURCE: u32 = 42;
+
+/// Encode `value` for `SOURCE` at `timestamp` and observe it
+fn encode_value(
+    encoder: &mut Encoder,
+    context: &mut Context,
+    value: f64,
+    timestamp: u64,
+) -> EncodedMessage {
+    let data = RawData::with_source(SOURCE, value, timestamp);
+    let classification = Classifier::default().classify(&data, context);
+    let message = encoder.encode(&data, &classification, context);
+    context.observe(&data);
+    message
+}
+
+#[test]
+fn test_confirmation_roundtrip() {
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+
+    let value = encode_value(&mut encoder, &mut context, 21.5, START_MS);
+    let bytes = encoder.message_to_bytes(&value);
+    match decoder.decode_bytes_item(&bytes, &context).unwrap() {
+        Decoded::Value(data) => assert_eq!(data.value, 21.5),
+        other => panic!("expected a value, got {:?}", other),
+    }
+
+    for i in 1..=3u64 {
+        let confirmation = encoder
+            .encode_confirmation(SOURCE, START_MS + i * 60_000, Priority::P3Normal, &context)
+            .unwrap();
+        let bytes = encoder.message_to_bytes(&confirmation);
+        assert_eq!(
+            decoder.decode_bytes_item(&bytes, &context).unwrap(),
+            Decoded::Confirmation {
+                source_id: SOURCE,
+                timestamp: START_MS / 1000 + i * 60,
+                ref_seq: value.header.sequence,
+            }
+        );
+    }
+
+    // Confirmations did not consume sequence numbers
+    let next = encode_value(&mut encoder, &mut context, 22.0, START_MS + 240_000);
+    assert_eq!(next.header.sequence, value.header.sequence.wrapping_add(1));
+}
+
+#[test]
+fn test_confirmation_with_compressed_timestamps_and_checksum() {
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        checksum_policy: ChecksumPolicy::Always,
+        timestamp_mode: TimestampMode::DeltaVarint { anchor_interval: 0 },
+        ..Default::default()
+    });
+    let mut decoder = Decoder::with_checksum_verification();
+    let mut context = Context::new();
+
+    let value = encode_value(&mut encoder, &mut context, 18.0, START_MS);
+    decoder
+        .decode_bytes_item(&encoder.message_to_bytes(&value), &context)
+        .unwrap();
+
+    let confirmation = encoder
+        .encode_confirmation(SOURCE, START_MS + 30_000, Priority::P2Important, &context)
+        .unwrap();
+    assert_ne!(confirmation.header.layout, value.header.layout);
+    let decoded = decoder
+        .decode_bytes_item(&encoder.message_to_bytes(&confirmation), &context)
+        .unwrap();
+    assert_eq!(
+        decoded,
+        Decoded::Confirmation {
+            source_id: SOURCE,
+            timestamp: START_MS / 1000 + 30,
+            ref_seq: value.header.sequence,
+        }
+    );
+
+    // The next value is compressed against the confirmation's timestamp
+    let next = encode_value(&mut encoder, &mut context, 18.5, START_MS + 45_000);
+    match decoder
+        .decode_bytes_item(&encoder.message_to_bytes(&next), &context)
+        .unwrap()
+    {
+        Decoded::Value(data) => assert_eq!(data.timestamp, START_MS / 1000 + 45),
+        other => panic!("expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_confirmation_smaller_than_smallest_value_message() {
+    for timestamp_mode in [
+        TimestampMode::Full,
+        TimestampMode::DeltaVarint { anchor_interval: 0 },
+    ] {
+        let mut encoder = Encoder::with_config(EncoderConfig {
+            timestamp_mode,
+            ..Default::default()
+        });
+        let mut context = Context::new();
+
+        // Repeat the same value until the encoder sends Repeated, the
+        // smallest value-bearing encoding
+        let mut smallest = usize::MAX;
+        let mut timestamp = START_MS;
+        for _ in 0..20 {
+            let message = encode_value(&mut encoder, &mut context, 20.0, timestamp);
+            if message.encoding_type() == Some(EncodingType::Repeated) {
+                smallest = smallest.min(message.len());
+            }
+            timestamp += 1_000;
+        }
+        assert_ne!(smallest, usize::MAX, "{:?}", timestamp_mode);
+
+        let confirmation = encoder
+            .encode_confirmation(SOURCE, timestamp, Priority::P3Normal, &context)
+            .unwrap();
+        assert!(
+            confirmation.len() < smallest,
+            "{:?}: confirmation {} bytes, smallest value {} bytes",
+            timestamp_mode,
+            confirmation.len(),
+            smallest
+        );
+    }
+}
+
+#[test]
+fn test_confirmation_needs_a_value_and_carries_none() {
+    let mut encoder = Encoder::new();
+    let mut context = Context::new();
+    assert!(encoder
+        .encode_confirmation(SOURCE, START_MS, Priority::P3Normal, &context)
+        .is_none());
+
+    encode_value(&mut encoder, &mut context, 5.0, START_MS);
+    let confirmation = encoder
+        .encode_confirmation(SOURCE, START_MS + 1_000, Priority::P3Normal, &context)
+        .unwrap();
+    assert!(confirmation.is_confirmation());
+    assert!(Decoder::new().decode(&confirmation, &context).is_err());
+}
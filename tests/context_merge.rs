@@ -0,0 +1,135 @@
+//! Context merging (`Context::merge`, `MergeStrategy`):
+//! - Dictionaries trained on overlapping values unite without duplicates,
+//!   summing the frequencies of shared patterns
+//! - Colliding codes are reassigned, and the merge bumps the version once
+//! - A source seen by both contexts predicts from the combined statistics
+//! - `max_patterns` is respected by evicting the lowest scores
+
+use alec::context::{
+    ContextConfig, EvolutionConfig, MergeReport, MergeStrategy, Pattern, PredictionModel,
+};
+use alec::{Context, RawData};
+
+fn context() -> Context {
+    Context::with_config(ContextConfig {
+        evolution: EvolutionConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+fn trained(values: &[f64]) -> Context {
+    let mut context = context();
+    for &value in values {
+        context.register_pattern(Pattern::numeric(value)).unwrap();
+    }
+    context
+}
+
+#[test]
+fn test_merge_dedupes_overlapping_dictionaries() {
+    let mut left = trained(&[1.0, 2.0, 3.0, 4.0]);
+    let right = trained(&[3.0, 4.0, 5.0]);
+    let version = left.version();
+    let hash = left.hash();
+
+    let report = left.merge(&right, MergeStrategy::PreferSelf);
+    assert_eq!(
+        report,
+        MergeReport {
+            patterns_added: 1,
+            patterns_deduped: 2,
+            patterns_evicted: 0,
+            sources_merged: 0,
+        }
+    );
+    assert_eq!(left.pattern_count(), 5);
+    assert!(left.pattern_count() <= 4 + right.pattern_count());
+    assert_eq!(left.version(), version + 1);
+    assert_ne!(left.hash(), hash);
+
+    // Shared patterns keep their code and sum their frequencies
+    let code = left.find_pattern(&3.0f64.to_be_bytes()).unwrap();
+    assert_eq!(code, 2);
+    assert_eq!(left.get_pattern(code).unwrap().frequency, 2);
+
+    // 5.0 came with code 2, taken: it gets the next free one
+    assert_eq!(left.find_pattern(&5.0f64.to_be_bytes()), Some(4));
+}
+
+#[test]
+fn test_merge_strategy_picks_pattern_metadata() {
+    let mut left = context();
+    let mut right = context();
+    left.register_pattern(Pattern::with_timestamp(b"ok".to_vec(), 10))
+        .unwrap();
+    let mut busy = Pattern::with_timestamp(b"ok".to_vec(), 99);
+    busy.frequency = 5;
+    right.register_pattern(busy).unwrap();
+
+    let mut preferred = left.clone();
+    preferred.merge(&right, MergeStrategy::PreferSelf);
+    assert_eq!(preferred.get_pattern(0).unwrap().last_used, 10);
+
+    left.merge(&right, MergeStrategy::HighestFrequency);
+    let pattern = left.get_pattern(0).unwrap();
+    assert_eq!(pattern.last_used, 99);
+    assert_eq!(pattern.frequency, 6);
+}
+
+#[test]
+fn test_merge_combines_source_statistics() {
+    let mut left = context();
+    let mut right = context();
+    for value in [20.0, 20.0] {
+        left.observe(&RawData::with_source(7, value, 0));
+    }
+    for value in [30.0, 30.0] {
+        right.observe(&RawData::with_source(7, value, 0));
+    }
+    // Each side alone has too few observations and no variance
+    let alone = left.predict(7).unwrap();
+    assert_eq!(alone.value, 20.0);
+    assert_eq!(alone.confidence, 0.95);
+
+    let report = left.merge(&right, MergeStrategy::PreferOther);
+    assert_eq!(report.sources_merged, 1);
+    assert_eq!(left.source_count(), 1);
+
+    // Four observations, spread between 20 and 30
+    let merged = left.predict(7).unwrap();
+    assert_eq!(merged.model_type, PredictionModel::MovingAverage);
+    assert_eq!(merged.value, 30.0);
+    assert_eq!(merged.confidence, 0.50);
+    assert_eq!(left.last_value(7), Some(30.0));
+    assert_eq!(left.moving_average(7, 4), Some(25.0));
+}
+
+#[test]
+fn test_merge_evicts_to_max_patterns() {
+    let config = || ContextConfig {
+        max_patterns: 4,
+        evolution: EvolutionConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut left = Context::with_config(config());
+    let mut right = Context::with_config(config());
+    for value in [1.0, 2.0, 3.0] {
+        left.register_pattern(Pattern::numeric(value)).unwrap();
+    }
+    for value in [3.0, 4.0, 5.0] {
+        right.register_pattern(Pattern::numeric(value)).unwrap();
+    }
+
+    let report = left.merge(&right, MergeStrategy::PreferSelf);
+    assert_eq!(report.patterns_added, 2);
+    assert_eq!(report.patterns_evicted, 1);
+    assert_eq!(left.pattern_count(), 4);
+    // The shared, twice-used pattern survives
+    assert!(left.find_pattern(&3.0f64.to_be_bytes()).is_some());
+}
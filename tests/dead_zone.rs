@@ -0,0 +1,152 @@
+//! Quantization dead-zone (`Encoder::set_dead_zone`), with a decoder that
+//! observes what it decodes:
+//! - A noisy but flat signal is mostly sent as "no change", with a fixed
+//!   dead-zone or one derived from the noise, and costs measurably fewer
+//!   bytes than without a dead-zone
+//! - A slow drift of 0.01 per sample is never lost: the decoded value
+//!   stays within the dead-zone of the true one, up to rounding
+//! - Genuine steps above the dead-zone are sent as they happen
+
+use alec::{Classifier, Context, DeadZone, Decoder, Encoder, EncodingType, RawData};
+
+const SOURCE: u32 = 1;
+/// Sensor noise amplitude (±)
+const NOISE: f64 = 0.03;
+/// One delta step at the default scale factor
+const STEP: f64 = 0.01;
+
+/// What a trace cost and how well it was reconstructed
+struct Run {
+    bytes: usize,
+    repeated: usize,
+    /// Largest |decoded - true| over the trace
+    max_error: f64,
+    /// Last decoded value
+    last: f64,
+}
+
+/// Noise in [-NOISE, NOISE], from a fixed-seed LCG
+fn noise(state: &mut u64) -> f64 {
+    *state = state
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    ((*state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0) * NOISE
+}
+
+fn noisy_flat(samples: usize) -> Vec<f64> {
+    let mut state = 42;
+    (0..samples).map(|_| 21.5 + noise(&mut state)).collect()
+}
+
+/// Encode `values` and decode them against a mirrored context
+fn run(values: &[f64], dead_zone: Option<DeadZone>) -> Run {
+    let classifier = Classifier::default();
+    let mut encoder = Encoder::new();
+    if let Some(dead_zone) = dead_zone {
+        encoder.set_dead_zone(SOURCE, dead_zone);
+    }
+    let mut decoder = Decoder::new();
+    let mut encoder_context = Context::new();
+    let mut decoder_context = Context::new();
+    let mut result = Run {
+        bytes: 0,
+        repeated: 0,
+        max_error: 0.0,
+        last: f64::NAN,
+    };
+
+    for (i, &value) in values.iter().enumerate() {
+        let data = RawData::with_source(SOURCE, value, i as u64 * 1000);
+        let classification = classifier.classify(&data, &encoder_context);
+        let message = encoder.encode(&data, &classification, &encoder_context);
+        encoder_context.observe(&data);
+
+        let bytes = message.to_bytes();
+        result.bytes += bytes.len();
+        if message.encoding_type() == Some(EncodingType::Repeated) {
+            result.repeated += 1;
+        }
+        let decoded = decoder.decode_bytes(&bytes, &decoder_context).unwrap();
+        decoder_context.observe(&RawData::with_source(
+            SOURCE,
+            decoded.value,
+            i as u64 * 1000,
+        ));
+        result.max_error = result.max_error.max((decoded.value - value).abs());
+        result.last = decoded.value;
+    }
+    result
+}
+
+#[test]
+fn test_noisy_flat_signal_mostly_unchanged() {
+    let values = noisy_flat(500);
+    let plain = run(&values, None);
+    for dead_zone in [DeadZone::Fixed(0.07), DeadZone::NoiseMultiple(4.0)] {
+        let held = run(&values, Some(dead_zone));
+        println!(
+            "{:?}: {} of {} repeated, {} bytes (without: {})",
+            dead_zone,
+            held.repeated,
+            values.len(),
+            held.bytes,
+            plain.bytes
+        );
+        assert!(
+            held.repeated * 10 >= values.len() * 9,
+            "{:?}: only {} repeated",
+            dead_zone,
+            held.repeated
+        );
+        assert!(held.bytes * 100 <= plain.bytes * 95, "{:?}", dead_zone);
+        assert!(held.max_error < 0.07 + STEP, "{:?}", dead_zone);
+    }
+}
+
+#[test]
+fn test_noise_multiple_width() {
+    let values = noisy_flat(200);
+    let mut context = Context::new();
+    let mut encoder = Encoder::new();
+    encoder.set_dead_zone(SOURCE, DeadZone::NoiseMultiple(4.0));
+    assert_eq!(encoder.dead_zone_width(SOURCE, &context), None);
+
+    for (i, &value) in values.iter().enumerate() {
+        context.observe(&RawData::with_source(SOURCE, value, i as u64 * 1000));
+    }
+    // Uniform noise of ±0.03 has a standard deviation of 0.017
+    let width = encoder.dead_zone_width(SOURCE, &context).unwrap();
+    assert!((0.05..0.09).contains(&width), "width {}", width);
+
+    encoder.clear_dead_zone(SOURCE);
+    assert_eq!(encoder.dead_zone(SOURCE), None);
+    assert_eq!(encoder.dead_zone_width(SOURCE, &context), None);
+}
+
+#[test]
+fn test_slow_drift_captured() {
+    const DEAD_ZONE: f64 = 0.05;
+    let values: Vec<f64> = (0..300).map(|i| 20.0 + i as f64 * STEP).collect();
+    let held = run(&values, Some(DeadZone::Fixed(DEAD_ZONE)));
+
+    // Every step is below the dead-zone, yet the drift gets through
+    assert!(held.repeated > 0);
+    // Plus the rounding of the 32-bit raw values sent after held ones
+    assert!(
+        held.max_error < DEAD_ZONE + 1e-4,
+        "decoder lagged by {}",
+        held.max_error
+    );
+    assert!((held.last - values[values.len() - 1]).abs() < DEAD_ZONE);
+    assert!(held.bytes < run(&values, None).bytes);
+}
+
+#[test]
+fn test_steps_above_dead_zone_sent() {
+    let mut values = vec![20.0; 10];
+    values.extend([20.1; 10]);
+    values.extend([19.8; 10]);
+    let held = run(&values, Some(DeadZone::Fixed(0.05)));
+    assert!(held.max_error < STEP);
+    assert!((held.last - 19.8).abs() < STEP);
+}
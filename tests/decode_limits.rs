@@ -0,0 +1,176 @@
+//! Decode resource limits:
+//! - A multi-value message claiming more values than allowed is rejected
+//!   before anything is allocated for them
+//! - The expansion limit bounds the decoded output of a message
+//! - The defaults never trip on the largest legitimate messages
+//! - FleetManager counts violations per emitter and quarantines repeat
+//!   offenders
+//!
+//! The multi-value count is a single byte on the wire, so the largest claim
+//! a message can make is 255 values; the tests lower the limits below it.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use alec::error::{DecodeError, ResourceLimit};
+use alec::fleet::{FleetConfig, FleetManager};
+use alec::{
+    AlecError, Classifier, Context, DecodedValue, Decoder, DecoderConfig, EncodedMessage, Encoder,
+    Priority, RawData,
+};
+
+/// Records the largest allocation made by the current thread
+struct LargestAllocation;
+
+thread_local! {
+    static LARGEST: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for LargestAllocation {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = LARGEST.try_with(|largest| largest.set(largest.get().max(layout.size())));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: LargestAllocation = LargestAllocation;
+
+/// Largest allocation made while running `f` on this thread
+fn largest_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    LARGEST.with(|largest| largest.set(0));
+    let result = f();
+    (result, LARGEST.with(Cell::get))
+}
+
+/// Multi-value message whose count byte claims `claimed` values, padded to
+/// a `payload_len`-byte payload
+fn claiming(claimed: u8, payload_len: usize) -> EncodedMessage {
+    let context = Context::new();
+    let mut message = Encoder::new()
+        .encode_multi(&[(1, 21.5)], 0, 60_000, Priority::P3Normal, &context)
+        .unwrap();
+    // Source id varint (1 byte), Multi encoding, count
+    message.payload[2] = claimed;
+    message.payload.resize(payload_len, 0);
+    message
+}
+
+fn limited(max_values_per_message: usize) -> Decoder {
+    Decoder::with_config(DecoderConfig {
+        max_values_per_message,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_claimed_count_rejected_before_allocation() {
+    let message = claiming(u8::MAX, 30);
+    let mut decoder = limited(64);
+
+    let (result, largest) =
+        largest_allocation(|| decoder.decode_multi_ex(&message, &Context::new()));
+
+    assert_eq!(
+        result.unwrap_err(),
+        AlecError::Decode(DecodeError::ResourceLimitExceeded {
+            which: ResourceLimit::ValuesPerMessage,
+            limit: 64,
+            attempted: 255,
+        })
+    );
+    assert!(largest < 64 * std::mem::size_of::<DecodedValue>());
+}
+
+#[test]
+fn test_truncated_claim_allocates_for_payload_only() {
+    // Within the limits, the allocation follows the payload, not the claim
+    let message = claiming(u8::MAX, 30);
+    let mut decoder = Decoder::new();
+
+    let (result, largest) =
+        largest_allocation(|| decoder.decode_multi_ex(&message, &Context::new()));
+
+    assert!(matches!(
+        result,
+        Err(AlecError::Decode(DecodeError::BufferTooShort { .. }))
+    ));
+    assert!(largest < 255 * std::mem::size_of::<DecodedValue>());
+}
+
+#[test]
+fn test_expansion_limit() {
+    let context = Context::new();
+    let values: Vec<(u8, f64)> = (0..20).map(|i| (i, i as f64)).collect();
+    let message = Encoder::new()
+        .encode_multi(&values, 0, 60_000, Priority::P3Normal, &context)
+        .unwrap();
+    let limit = 10 * std::mem::size_of::<DecodedValue>();
+    let mut decoder = Decoder::with_config(DecoderConfig {
+        max_expansion_bytes: limit,
+        ..Default::default()
+    });
+
+    match decoder.decode_multi_ex(&message, &context) {
+        Err(AlecError::Decode(DecodeError::ResourceLimitExceeded {
+            which: ResourceLimit::ExpansionBytes,
+            limit: reported,
+            attempted,
+        })) => {
+            assert_eq!(reported, limit as u64);
+            assert_eq!(attempted, 2 * limit as u64);
+        }
+        other => panic!("expected expansion limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_defaults_accept_largest_messages() {
+    let context = Context::new();
+    let values: Vec<(u8, f64)> = (0..u8::MAX).map(|i| (i, i as f64 * 0.5)).collect();
+    let message = Encoder::new()
+        .encode_multi(&values, 0, 60_000, Priority::P3Normal, &context)
+        .unwrap();
+
+    let decoded = Decoder::new().decode_multi_ex(&message, &context).unwrap();
+    assert_eq!(decoded.len(), 255);
+
+    let data = RawData::new(21.5, 1000);
+    let classification = Classifier::default().classify(&data, &context);
+    let single = Encoder::new().encode(&data, &classification, &context);
+    assert!(Decoder::new().decode(&single, &context).is_ok());
+}
+
+#[test]
+fn test_fleet_quarantines_repeat_offenders() {
+    let mut fleet = FleetManager::with_config(FleetConfig {
+        // No message fits: every decode trips the limit
+        decoder: DecoderConfig {
+            max_expansion_bytes: 1,
+            ..Default::default()
+        },
+        max_resource_violations: 3,
+        ..Default::default()
+    });
+    let context = Context::new();
+    let data = RawData::new(21.5, 1000);
+    let classification = Classifier::default().classify(&data, &context);
+    let message = Encoder::new().encode(&data, &classification, &context);
+
+    for attempt in 1..=3u64 {
+        let err = fleet.process_message(7, &message, attempt).unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            AlecError::Decode(DecodeError::ResourceLimitExceeded { .. })
+        ));
+        let emitter = fleet.get_emitter(7).unwrap();
+        assert_eq!(emitter.resource_limit_violations, attempt);
+        assert_eq!(emitter.is_anomalous, attempt == 3);
+    }
+    assert_eq!(fleet.anomalous_emitters(), vec![7]);
+    assert_eq!(fleet.stats().resource_limit_violations, 3);
+}
@@ -0,0 +1,120 @@
+//! Structured multi-value decoding (`Decoder::decode_multi_ex`):
+//! - Every value carries its name_id and the frame timestamp
+//! - Frames sent at different times report their own timestamps
+//! - A compressed header timestamp is reported as `None`
+//! - `decode_multi` stays a thin wrapper returning the same pairs
+//! - A frame with zero values decodes to an empty vec
+
+use alec::protocol::{HeaderLayout, TimestampFormat};
+use alec::{Context, DecodedValue, Decoder, Encoder, Priority};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+#[test]
+fn test_three_values_carry_frame_timestamp() {
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    let values = [(1u8, 22.5), (2, 65.0), (3, 1013.25)];
+    let message = encoder
+        .encode_multi(&values, 7, START_MS, Priority::P3Normal, &context)
+        .unwrap();
+
+    let decoded = decoder.decode_multi_ex(&message, &context).unwrap();
+    assert_eq!(decoded.len(), 3);
+    for ((name_id, value), out) in values.iter().zip(&decoded) {
+        assert_eq!(out.name_id, *name_id as u16);
+        assert!((out.value - value).abs() < 0.01);
+        assert_eq!(out.timestamp, Some(START_MS / 1000));
+        assert_eq!(out.quality, None);
+    }
+}
+
+#[test]
+fn test_mixed_timestamps() {
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    let first = encoder
+        .encode_multi(
+            &[(1, 20.0), (2, 40.0), (3, 60.0)],
+            7,
+            START_MS,
+            Priority::P3Normal,
+            &context,
+        )
+        .unwrap();
+    let second = encoder
+        .encode_multi(
+            &[(1, 21.0), (2, 41.0), (3, 61.0)],
+            7,
+            START_MS + 30_000,
+            Priority::P3Normal,
+            &context,
+        )
+        .unwrap();
+    let mut compressed = encoder
+        .encode_multi(
+            &[(1, 22.0), (2, 42.0), (3, 62.0)],
+            7,
+            START_MS + 60_000,
+            Priority::P3Normal,
+            &context,
+        )
+        .unwrap();
+    compressed.header.layout = HeaderLayout::Plain(TimestampFormat::Delta);
+    compressed.header.timestamp = TimestampFormat::pack_delta(30);
+
+    let timestamps: Vec<Vec<Option<u64>>> = [&first, &second, &compressed]
+        .iter()
+        .map(|message| {
+            decoder
+                .decode_multi_ex(message, &context)
+                .unwrap()
+                .iter()
+                .map(|v| v.timestamp)
+                .collect()
+        })
+        .collect();
+
+    assert_eq!(timestamps[0], vec![Some(START_MS / 1000); 3]);
+    assert_eq!(timestamps[1], vec![Some(START_MS / 1000 + 30); 3]);
+    assert_eq!(timestamps[2], vec![None; 3]);
+}
+
+#[test]
+fn test_decode_multi_wraps_ex() {
+    let mut encoder = Encoder::new();
+    let context = Context::new();
+    let message = encoder
+        .encode_multi(
+            &[(4, 1.5), (9, -3.0), (200, 7.25)],
+            0,
+            START_MS,
+            Priority::P2Important,
+            &context,
+        )
+        .unwrap();
+
+    let pairs = Decoder::new().decode_multi(&message, &context).unwrap();
+    let structured = Decoder::new().decode_multi_ex(&message, &context).unwrap();
+    let expected: Vec<(u8, f64)> = structured
+        .iter()
+        .map(|v: &DecodedValue| (v.name_id as u8, v.value))
+        .collect();
+    assert_eq!(pairs, expected);
+}
+
+#[test]
+fn test_empty_multi_message() {
+    let mut encoder = Encoder::new();
+    let context = Context::new();
+    let message = encoder
+        .encode_multi(&[], 7, START_MS, Priority::P3Normal, &context)
+        .unwrap();
+
+    let decoded = Decoder::new().decode_multi_ex(&message, &context).unwrap();
+    assert!(decoded.is_empty());
+}
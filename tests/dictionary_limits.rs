@@ -0,0 +1,213 @@
+//! Formal limits, checked on every construction and insertion path:
+//! - `register_pattern`, `set_pattern`, bulk registration and
+//!   `import_full` accept exactly `max_patterns` patterns and refuse one
+//!   more with `DictionaryFull`, leaving the dictionary untouched
+//! - `set_pattern` refuses a pattern beyond `MAX_PATTERN_SIZE` bytes
+//! - A sync diff that would push the dictionary over `max_patterns` is
+//!   refused without any of it being applied
+//! - `encode_multi` takes up to `MAX_MULTI_VALUES` values and
+//!   `encode_composite` up to `MAX_PAYLOAD_SIZE` payload bytes; beyond,
+//!   both fail with `PayloadTooLarge` without using a sequence number
+
+use alec::context::{ContextConfig, Pattern, MAX_PATTERN_SIZE};
+use alec::error::{ContextError, EncodeError};
+use alec::sync::Synchronizer;
+use alec::{
+    AlecError, CompositeEntry, Context, Decoder, Encoder, Priority, MAX_MULTI_VALUES,
+    MAX_PAYLOAD_SIZE,
+};
+
+const MAX: usize = 4;
+
+fn limited() -> Context {
+    Context::with_config(ContextConfig {
+        max_patterns: MAX,
+        ..Default::default()
+    })
+}
+
+/// Context holding `count` patterns at codes `0..count`
+fn filled(mut context: Context, count: usize) -> Context {
+    for i in 0..count {
+        context.register_pattern(pattern(i)).unwrap();
+    }
+    context
+}
+
+fn pattern(i: usize) -> Pattern {
+    Pattern::new(vec![0xA0, i as u8])
+}
+
+fn dictionary_full() -> AlecError {
+    AlecError::Context(ContextError::DictionaryFull { max: MAX })
+}
+
+#[test]
+fn test_register_and_set_pattern_at_limit() {
+    let mut context = filled(limited(), MAX);
+    assert_eq!(context.pattern_count(), MAX);
+    assert_eq!(
+        context.register_pattern(pattern(MAX)).unwrap_err(),
+        dictionary_full()
+    );
+
+    let hash = context.hash();
+    assert_eq!(
+        context.set_pattern(MAX as u32, pattern(MAX)).unwrap_err(),
+        dictionary_full()
+    );
+    assert_eq!(context.hash(), hash);
+    // Replacing an existing code is within the limit
+    context.set_pattern(0, pattern(MAX)).unwrap();
+    assert_eq!(context.pattern_count(), MAX);
+
+    let mut context = limited();
+    context
+        .set_pattern(0, Pattern::new(vec![1; MAX_PATTERN_SIZE]))
+        .unwrap();
+    assert_eq!(
+        context
+            .set_pattern(1, Pattern::new(vec![1; MAX_PATTERN_SIZE + 1]))
+            .unwrap_err(),
+        AlecError::Context(ContextError::PatternTooLarge {
+            size: MAX_PATTERN_SIZE + 1,
+            max: MAX_PATTERN_SIZE,
+        })
+    );
+    assert_eq!(context.pattern_count(), 1);
+}
+
+#[test]
+fn test_bulk_registration_at_limit() {
+    let data: Vec<Vec<u8>> = (0..=MAX).map(|i| pattern(i).data.to_vec()).collect();
+    let slices: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+
+    let mut context = limited();
+    let error = context.register_patterns_bulk(&slices).unwrap_err();
+    assert_eq!(error.index, MAX);
+    assert_eq!(error.error, ContextError::DictionaryFull { max: MAX });
+    assert_eq!(context.pattern_count(), 0);
+
+    assert_eq!(
+        context
+            .register_patterns_bulk(&slices[..MAX])
+            .unwrap()
+            .len(),
+        MAX
+    );
+}
+
+#[test]
+fn test_import_full_at_limit() {
+    let exported = filled(Context::new(), MAX).export_full();
+    let mut context = limited();
+    context.import_full(&exported).unwrap();
+    assert_eq!(context.pattern_count(), MAX);
+
+    let oversized = filled(Context::new(), MAX + 1).export_full();
+    let hash = context.hash();
+    assert_eq!(
+        context.import_full(&oversized).unwrap_err(),
+        dictionary_full()
+    );
+    assert_eq!(context.pattern_count(), MAX);
+    assert_eq!(context.hash(), hash);
+}
+
+#[test]
+fn test_sync_diff_over_limit_not_applied() {
+    let base = filled(Context::new(), MAX - 1);
+
+    let mut receiver = filled(limited(), MAX - 1);
+    let at_limit = Synchronizer::generate_diff(&base, &filled(Context::new(), MAX));
+    Synchronizer::apply_diff(&mut receiver, &at_limit).unwrap();
+    assert_eq!(receiver.pattern_count(), MAX);
+
+    let mut receiver = filled(limited(), MAX - 1);
+    let (hash, version) = (receiver.hash(), receiver.dictionary_version());
+    let over = Synchronizer::generate_diff(&base, &filled(Context::new(), MAX + 1));
+    assert_eq!(over.added.len(), 2);
+    assert_eq!(
+        Synchronizer::apply_diff(&mut receiver, &over).unwrap_err(),
+        dictionary_full()
+    );
+    assert_eq!(receiver.pattern_count(), MAX - 1);
+    assert_eq!(receiver.hash(), hash);
+    assert_eq!(receiver.dictionary_version(), version);
+
+    // A diff freeing room first fits
+    let mut shifted = over.clone();
+    shifted.removed = vec![0];
+    shifted.hash = {
+        let mut expected = filled(Context::new(), MAX + 1);
+        expected.remove_pattern(0);
+        expected.hash()
+    };
+    Synchronizer::apply_diff(&mut receiver, &shifted).unwrap();
+    assert_eq!(receiver.pattern_count(), MAX);
+}
+
+#[test]
+fn test_encode_multi_value_count() {
+    let context = Context::new();
+    let mut encoder = Encoder::new();
+    let values: Vec<(u8, f64)> = (0..MAX_MULTI_VALUES).map(|i| (i as u8, i as f64)).collect();
+
+    let message = encoder
+        .encode_multi(&values, 1, 60_000, Priority::P3Normal, &context)
+        .unwrap();
+    let decoded = Decoder::new().decode_multi(&message, &context).unwrap();
+    assert_eq!(decoded.len(), MAX_MULTI_VALUES);
+
+    let mut too_many = values.clone();
+    too_many.push((0, 0.0));
+    assert_eq!(
+        encoder
+            .encode_multi(&too_many, 1, 60_000, Priority::P3Normal, &context)
+            .unwrap_err(),
+        AlecError::Encode(EncodeError::PayloadTooLarge {
+            size: MAX_MULTI_VALUES + 1,
+            max: MAX_MULTI_VALUES,
+        })
+    );
+    let next = encoder
+        .encode_multi(&values[..1], 1, 60_000, Priority::P3Normal, &context)
+        .unwrap();
+    assert_eq!(next.header.sequence, message.header.sequence + 1);
+}
+
+#[test]
+fn test_encode_composite_payload_size() {
+    let context = Context::new();
+    // Non-finite values are raw 64-bit: 11 bytes per entry under a 1-byte
+    // channel id, 12 under a 2-byte one, after 4 bytes of source id,
+    // encoding and entry count
+    let entry = |channel_id| CompositeEntry {
+        channel_id,
+        value: f64::NAN,
+        context: &context,
+    };
+    let mut entries: Vec<CompositeEntry<'_>> = (0..5_953).map(|_| entry(0)).collect();
+    entries.extend((0..4).map(|_| entry(128)));
+
+    let mut encoder = Encoder::new();
+    let message = encoder
+        .encode_composite(0, 60_000, Priority::P3Normal, &entries)
+        .unwrap();
+    assert_eq!(message.payload.len(), MAX_PAYLOAD_SIZE);
+
+    entries.push(entry(0));
+    assert_eq!(
+        encoder
+            .encode_composite(0, 60_000, Priority::P3Normal, &entries)
+            .unwrap_err(),
+        AlecError::Encode(EncodeError::PayloadTooLarge {
+            size: MAX_PAYLOAD_SIZE + 11,
+            max: MAX_PAYLOAD_SIZE,
+        })
+    );
+    let next = encoder
+        .encode_composite(0, 60_000, Priority::P3Normal, &entries[..1])
+        .unwrap();
+    assert_eq!(next.header.sequence, message.header.sequence + 1);
+}
@@ -0,0 +1,98 @@
+//! Dictionary version vs observation-driven version:
+//! - 10k observations with no new patterns produce zero sync requests
+//!   between a matched encoder/decoder pair
+//! - A new pattern triggers a sync request, and applying the diff brings
+//!   the peers back in step
+
+use alec::context::Pattern;
+use alec::{
+    Classifier, Context, Decoder, Encoder, RawData, SyncAnnounce, SyncMessage, Synchronizer,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+fn announce(context: &Context) -> SyncAnnounce {
+    match Synchronizer::create_announce(context) {
+        SyncMessage::Announce(announce) => announce,
+        other => panic!("expected an announce, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_observations_trigger_no_sync() {
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let classifier = Classifier::default();
+    let mut emitter_ctx = Context::new();
+    let mut receiver_ctx = Context::new();
+    let mut synchronizer = Synchronizer::new();
+    let mut requests = 0;
+
+    for i in 0..10_000u64 {
+        let value = 20.0 + (i as f64 * 0.01).sin() * 5.0;
+        let data = RawData::new(value, START_MS + i * 1_000);
+        let classification = classifier.classify(&data, &emitter_ctx);
+        let message = encoder.encode(&data, &classification, &emitter_ctx);
+        emitter_ctx.observe(&data);
+
+        let decoded = decoder.decode(&message, &receiver_ctx).unwrap();
+        receiver_ctx.observe(&RawData::with_source(
+            decoded.source_id,
+            decoded.value,
+            decoded.timestamp,
+        ));
+
+        if i % 100 == 99 {
+            let announce = announce(&emitter_ctx);
+            if synchronizer
+                .check_sync_needed(announce.version, announce.hash, &receiver_ctx)
+                .is_some()
+            {
+                requests += 1;
+            }
+        }
+    }
+
+    assert_eq!(requests, 0);
+    assert!(synchronizer.is_synchronized());
+    assert!(emitter_ctx.version() > 10_000);
+    assert_eq!(emitter_ctx.dictionary_version(), 0);
+    assert_eq!(receiver_ctx.dictionary_version(), 0);
+}
+
+#[test]
+fn test_new_pattern_triggers_sync() {
+    let mut emitter_ctx = Context::new();
+    let mut receiver_ctx = Context::new();
+    let mut synchronizer = Synchronizer::new();
+
+    for i in 0..500u64 {
+        let data = RawData::new(20.0, START_MS + i * 1_000);
+        emitter_ctx.observe(&data);
+        receiver_ctx.observe(&data);
+    }
+    emitter_ctx
+        .register_pattern(Pattern::new(vec![1, 2, 3]))
+        .unwrap();
+
+    let announce = announce(&emitter_ctx);
+    let request =
+        match synchronizer.check_sync_needed(announce.version, announce.hash, &receiver_ctx) {
+            Some(SyncMessage::Request(request)) => request,
+            other => panic!("expected a sync request, got {:?}", other),
+        };
+    assert_eq!(request.from_version, 0);
+    assert_eq!(request.to_version, Some(1));
+
+    let Some(SyncMessage::Diff(diff)) = Synchronizer::new().handle_request(&request, &emitter_ctx)
+    else {
+        panic!("expected a diff");
+    };
+    synchronizer.handle_diff(&diff, &mut receiver_ctx).unwrap();
+
+    assert_eq!(receiver_ctx.dictionary_version(), 1);
+    assert_eq!(receiver_ctx.hash(), emitter_ctx.hash());
+    assert!(synchronizer
+        .check_sync_needed(announce.version, announce.hash, &receiver_ctx)
+        .is_none());
+}
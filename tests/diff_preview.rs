@@ -0,0 +1,110 @@
+//! Sync diff preview and checked application:
+//! - Additions are split into clean adds and conflicting overwrites
+//! - Removals are split into present and already absent codes
+//! - The predicted hash matches the context after applying the diff
+//! - Checked application refuses conflicts unless forced
+
+use alec::context::Pattern;
+use alec::error::ContextError;
+use alec::{AlecError, Context, SyncDiff, Synchronizer};
+
+fn receiver() -> Context {
+    let mut context = Context::new();
+    context.set_pattern(0, Pattern::numeric(20.0)).unwrap();
+    context.set_pattern(1, Pattern::numeric(21.0)).unwrap();
+    context.set_pattern(2, Pattern::numeric(22.0)).unwrap();
+    context
+}
+
+/// One clean add, one conflicting add and one removal of a missing code
+fn mixed_diff(base: &Context) -> SyncDiff {
+    let mut expected = base.clone();
+    expected.set_pattern(1, Pattern::numeric(99.0)).unwrap();
+    expected
+        .set_pattern(5, Pattern::from_slice(b"fresh"))
+        .unwrap();
+    SyncDiff {
+        base_version: base.dictionary_version(),
+        new_version: base.dictionary_version() + 1,
+        added: vec![
+            (5, Pattern::from_slice(b"fresh")),
+            (1, Pattern::numeric(99.0)),
+        ],
+        removed: vec![42],
+        hash: expected.hash(),
+    }
+}
+
+#[test]
+fn test_preview_categorizes_changes() {
+    let context = receiver();
+    let diff = mixed_diff(&context);
+    let preview = diff.preview(&context);
+
+    assert_eq!(preview.added, 1);
+    assert_eq!(preview.added_bytes, 5);
+    assert_eq!(preview.unchanged, 0);
+    assert_eq!(preview.conflicts, vec![1]);
+    assert!(preview.removed.is_empty());
+    assert_eq!(preview.already_absent, vec![42]);
+    assert!(!preview.is_clean());
+    assert_eq!(preview.hash, diff.hash);
+}
+
+#[test]
+fn test_preview_removal_before_addition() {
+    let context = receiver();
+    let diff = SyncDiff {
+        base_version: 0,
+        new_version: 1,
+        added: vec![(2, Pattern::numeric(30.0)), (0, Pattern::numeric(20.0))],
+        removed: vec![2, 1],
+        hash: 0,
+    };
+    let preview = diff.preview(&context);
+
+    // Code 2 is freed by the removal, code 0 already holds the pattern
+    assert_eq!(preview.added, 1);
+    assert_eq!(preview.unchanged, 1);
+    assert!(preview.is_clean());
+    assert_eq!(preview.removed, vec![2, 1]);
+
+    let mut applied = context.clone();
+    let _ = Synchronizer::apply_diff(&mut applied, &diff);
+    assert_eq!(preview.hash, applied.hash());
+}
+
+#[test]
+fn test_apply_checked_refuses_conflicts() {
+    let mut context = receiver();
+    let diff = mixed_diff(&context);
+    let hash = context.hash();
+
+    let err = Synchronizer::apply_diff_checked(&mut context, &diff, false).unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Context(ContextError::DiffConflict { conflicts: 1 })
+    ));
+    assert_eq!(context.hash(), hash);
+
+    let preview = Synchronizer::apply_diff_checked(&mut context, &diff, true).unwrap();
+    assert_eq!(preview.conflicts, vec![1]);
+    assert_eq!(context.hash(), diff.hash);
+    assert_eq!(context.get_pattern(1), Some(&Pattern::numeric(99.0)));
+}
+
+#[test]
+fn test_apply_checked_clean_diff() {
+    let base = receiver();
+    let mut target = base.clone();
+    target.set_pattern(3, Pattern::numeric(23.0)).unwrap();
+    target.remove_pattern(0);
+    target.set_dictionary_version(base.dictionary_version() + 1);
+    let diff = Synchronizer::generate_diff(&base, &target);
+
+    let mut context = base.clone();
+    let preview = Synchronizer::apply_diff_checked(&mut context, &diff, false).unwrap();
+    assert_eq!(preview.added, 1);
+    assert_eq!(preview.removed, vec![0]);
+    assert_eq!(context.hash(), target.hash());
+}
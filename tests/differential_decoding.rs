@@ -0,0 +1,682 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Differential decoding tests.
+//!
+//! Every generated frame is decoded by both the production `Decoder` and
+//! the reference decoder in `tests/reference_decoder`. They must agree on
+//! the header, the payload, every decoded value and timestamp, or on the
+//! class of error. Inputs cover:
+//! - Encoder output for every checksum policy and timestamp mode, with
+//!   periodic self-describing values
+//! - Handcrafted payloads for every encoding type
+//! - Multi-value and fixed-channel frames
+//! - Bit flips, truncation and trailing garbage
+//! - Non-canonical and overflowing varints
+
+mod reference_decoder;
+
+use alec::context::Pattern;
+use alec::error::DecodeError;
+use alec::{
+    AlecError, ChannelInput, ChecksumPolicy, Classifier, Context, Decoder, EncodedMessage, Encoder,
+    EncoderConfig, EncodingType, MessageType, Priority, RawData, TimestampMode,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use reference_decoder::{parse_message, RefError, RefMessage, ReferenceDecoder};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+const POLICIES: [ChecksumPolicy; 3] = [
+    ChecksumPolicy::Never,
+    ChecksumPolicy::Always,
+    ChecksumPolicy::ForPriorityAtOrAbove(Priority::P2Important),
+];
+
+const MODES: [TimestampMode; 3] = [
+    TimestampMode::Full,
+    TimestampMode::DeltaVarint { anchor_interval: 8 },
+    TimestampMode::TruncatedEpoch { bits: 12 },
+];
+
+const ENCODINGS: [u8; 10] = [0x00, 0x01, 0x10, 0x11, 0x12, 0x20, 0x21, 0x30, 0x31, 0x40];
+
+fn classify(error: &AlecError) -> RefError {
+    match error {
+        AlecError::Decode(DecodeError::BufferTooShort { .. }) => RefError::BufferTooShort,
+        AlecError::Decode(DecodeError::InvalidHeader) => RefError::InvalidHeader,
+        AlecError::Decode(DecodeError::InvalidChecksum { .. }) => RefError::InvalidChecksum,
+        AlecError::Decode(DecodeError::InvalidCrc32c { .. }) => RefError::InvalidCrc32c,
+        AlecError::Decode(DecodeError::UnknownEncodingType(_)) => RefError::UnknownEncodingType,
+        AlecError::Decode(DecodeError::MalformedMessage { .. }) => RefError::Malformed,
+        AlecError::Decode(DecodeError::UnknownPattern { .. }) => RefError::UnknownPattern,
+        AlecError::Decode(DecodeError::MissingTimestampAnchor { .. }) => {
+            RefError::MissingTimestampAnchor
+        }
+        AlecError::Decode(DecodeError::TimestampRegression { .. }) => RefError::TimestampRegression,
+        AlecError::Decode(DecodeError::UnsupportedVersion { .. }) => RefError::UnsupportedVersion,
+        AlecError::Decode(DecodeError::UnsupportedFlags { .. }) => RefError::UnsupportedFlags,
+        AlecError::Decode(DecodeError::PlanMissing { .. }) => RefError::PlanMissing,
+        other => panic!("unexpected production error: {other:?}"),
+    }
+}
+
+fn same_value(a: f64, b: f64) -> bool {
+    (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= 1e-9 * a.abs().max(1.0)
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Varint with `padding` extra continuation bytes (non-canonical but valid
+/// as long as the total stays within 5 bytes)
+fn write_padded_varint(value: u32, padding: usize, out: &mut Vec<u8>) {
+    let mut bytes = Vec::new();
+    write_varint(value as u64, &mut bytes);
+    if padding > 0 {
+        *bytes.last_mut().unwrap() |= 0x80;
+        bytes.extend(std::iter::repeat(0x80).take(padding - 1));
+        bytes.push(0x00);
+    }
+    out.extend_from_slice(&bytes);
+}
+
+fn mutate(rng: &mut StdRng, bytes: &mut Vec<u8>) {
+    match rng.gen_range(0..4) {
+        0 if !bytes.is_empty() => {
+            let i = rng.gen_range(0..bytes.len());
+            bytes[i] ^= 1 << rng.gen_range(0..8);
+        }
+        1 => bytes.truncate(rng.gen_range(0..=bytes.len())),
+        2 => {
+            for _ in 0..rng.gen_range(1..6) {
+                bytes.push(rng.gen());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Both decoders side by side, sharing one context
+struct Harness {
+    decoder: Decoder,
+    reference: ReferenceDecoder,
+    context: Context,
+    policy: ChecksumPolicy,
+    cases: usize,
+    successes: usize,
+}
+
+impl Harness {
+    fn new(policy: ChecksumPolicy) -> Self {
+        Self {
+            decoder: Decoder::with_checksum_policy(policy),
+            reference: ReferenceDecoder::new(),
+            context: Context::new(),
+            policy,
+            cases: 0,
+            successes: 0,
+        }
+    }
+
+    fn reference_expects_checksum(&self, bytes: &[u8]) -> bool {
+        bytes
+            .first()
+            .and_then(|byte| Priority::from_u8(byte & 0x07))
+            .is_some_and(|priority| self.policy.applies_to(priority))
+    }
+
+    /// Compare header parsing and payload extraction of checksum-less bytes
+    fn check_parse(&self, bytes: &[u8]) -> Option<RefMessage> {
+        let production = EncodedMessage::from_bytes(bytes);
+        let reference = parse_message(bytes, false);
+        match (&production, &reference) {
+            (Some(message), Ok(expected)) => {
+                let h = &message.header;
+                let e = &expected.header;
+                assert_eq!(h.version(), e.version, "version of {bytes:02x?}");
+                assert_eq!(
+                    h.extension().map(|x| (x.version.to_byte(), x.flags)),
+                    e.extension,
+                    "extension of {bytes:02x?}"
+                );
+                assert_eq!(h.message_type as u8, e.message_type, "type of {bytes:02x?}");
+                assert_eq!(h.priority as u8, e.priority, "priority of {bytes:02x?}");
+                assert_eq!(h.sequence, e.sequence, "sequence of {bytes:02x?}");
+                assert_eq!(h.timestamp, e.timestamp_field, "timestamp of {bytes:02x?}");
+                assert_eq!(
+                    h.extension().map_or(0, |x| x.subsec),
+                    e.subsec,
+                    "sub-second of {bytes:02x?}"
+                );
+                assert_eq!(h.context_version, e.context_version, "ctx of {bytes:02x?}");
+                assert_eq!(message.payload, expected.payload, "payload of {bytes:02x?}");
+            }
+            (None, Err(RefError::InvalidHeader)) => {}
+            _ => panic!("parse diverged on {bytes:02x?}: {production:?} vs {reference:?}"),
+        }
+        reference.ok()
+    }
+
+    /// Decode a single-value frame with both decoders
+    fn check_single(&mut self, bytes: &[u8]) {
+        self.cases += 1;
+        self.check_parse(bytes);
+
+        let production = self.decoder.decode_bytes(bytes, &self.context);
+        let with_checksum = self.reference_expects_checksum(bytes);
+        let reference = if bytes.first() == Some(&0xA5) {
+            self.reference.decode_plan_relative(bytes)
+        } else {
+            parse_message(bytes, with_checksum)
+                .and_then(|message| self.reference.decode(&message, &self.context))
+        };
+
+        match (&production, &reference) {
+            (Ok(decoded), Ok(expected)) => {
+                assert_eq!(decoded.source_id, expected.source_id, "{bytes:02x?}");
+                assert_eq!(decoded.timestamp, expected.timestamp, "{bytes:02x?}");
+                assert!(
+                    same_value(decoded.value, expected.value),
+                    "value of {bytes:02x?}: {} vs {}",
+                    decoded.value,
+                    expected.value
+                );
+                self.successes += 1;
+                self.context.observe(&RawData::with_source(
+                    decoded.source_id,
+                    decoded.value,
+                    (decoded.timestamp_micros() / 1000) as u64,
+                ));
+            }
+            (Err(error), Err(expected)) => {
+                assert_eq!(
+                    classify(error),
+                    *expected,
+                    "error on {bytes:02x?}: {error:?}"
+                );
+            }
+            _ => panic!("decode diverged on {bytes:02x?}: {production:?} vs {reference:?}"),
+        }
+    }
+
+    /// Decode a multi-value frame (never carries a checksum) with both
+    fn check_multi(&mut self, bytes: &[u8]) {
+        self.cases += 1;
+        let Some(expected_message) = self.check_parse(bytes) else {
+            return;
+        };
+        let message = EncodedMessage::from_bytes(bytes).unwrap();
+
+        let production = self.decoder.decode_multi(&message, &self.context);
+        let reference = self
+            .reference
+            .decode_multi(&expected_message, &self.context);
+        match (&production, &reference) {
+            (Ok(values), Ok(expected)) => {
+                assert_eq!(values.len(), expected.len(), "{bytes:02x?}");
+                for ((id, value), (expected_id, expected_value)) in values.iter().zip(expected) {
+                    assert_eq!(id, expected_id, "{bytes:02x?}");
+                    assert!(same_value(*value, *expected_value), "{bytes:02x?}");
+                }
+                self.successes += 1;
+                for (id, value) in values {
+                    self.context
+                        .observe(&RawData::with_source(*id as u32, *value, START_MS));
+                }
+            }
+            (Err(error), Err(expected)) => {
+                assert_eq!(
+                    classify(error),
+                    *expected,
+                    "error on {bytes:02x?}: {error:?}"
+                );
+            }
+            _ => panic!("multi diverged on {bytes:02x?}: {production:?} vs {reference:?}"),
+        }
+    }
+
+    /// Decode a fixed-channel frame with both decoders
+    fn check_fixed(&mut self, bytes: &[u8], channel_count: usize) {
+        self.cases += 1;
+        let mut output = vec![0.0; channel_count.max(1)];
+        let production =
+            self.decoder
+                .decode_multi_fixed(bytes, channel_count, &self.context, &mut output);
+        let reference = self
+            .reference
+            .decode_fixed(bytes, channel_count, &self.context);
+
+        match (&production, &reference) {
+            (Ok(info), Ok(expected)) => {
+                assert_eq!(info.keyframe, expected.keyframe, "{bytes:02x?}");
+                assert_eq!(info.sequence, expected.sequence, "{bytes:02x?}");
+                assert_eq!(
+                    info.context_version, expected.context_version,
+                    "{bytes:02x?}"
+                );
+                assert_eq!(info.gap_size, expected.gap_size, "{bytes:02x?}");
+                assert_eq!(
+                    info.context_mismatch, expected.context_mismatch,
+                    "{bytes:02x?}"
+                );
+                for (value, expected_value) in output.iter().zip(&expected.values) {
+                    assert!(same_value(*value, *expected_value), "{bytes:02x?}");
+                }
+                self.successes += 1;
+                for (i, value) in output[..channel_count].iter().enumerate() {
+                    self.context
+                        .observe(&RawData::with_source(i as u32 + 1, *value, START_MS));
+                }
+            }
+            (Err(error), Err(expected)) => {
+                assert_eq!(
+                    classify(error),
+                    *expected,
+                    "error on {bytes:02x?}: {error:?}"
+                );
+            }
+            _ => panic!("fixed diverged on {bytes:02x?}: {production:?} vs {reference:?}"),
+        }
+    }
+}
+
+/// Random walk per source with repeats, jumps and the occasional NaN
+fn next_value(rng: &mut StdRng, last: f64) -> f64 {
+    match rng.gen_range(0..100) {
+        0 => f64::NAN,
+        1..=5 => rng.gen_range(-1.0e6..1.0e6),
+        6..=35 => last,
+        _ => {
+            let base = if last.is_finite() { last } else { 20.0 };
+            base + rng.gen_range(-2.0..2.0)
+        }
+    }
+}
+
+#[test]
+fn test_encoder_streams_match_reference() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_0001);
+    let mut total = 0;
+    let mut successes = 0;
+
+    for policy in POLICIES {
+        for mode in MODES {
+            let mut encoder = Encoder::with_config(EncoderConfig {
+                checksum_policy: policy,
+                timestamp_mode: mode,
+                self_describing_interval: 16,
+                ..Default::default()
+            });
+            let classifier = Classifier::default();
+            let mut harness = Harness::new(policy);
+            let mut last_values = [20.0f64; 5];
+            let mut now = START_MS;
+
+            for i in 0..600 {
+                let source_id = rng.gen_range(1..=4u32);
+                let value = next_value(&mut rng, last_values[source_id as usize]);
+                last_values[source_id as usize] = value;
+                now += rng.gen_range(0..120_000);
+                // Occasionally jump far ahead so truncated timestamps wrap
+                if rng.gen_range(0..50) == 0 {
+                    now += rng.gen_range(0..10_000_000);
+                }
+
+                let data = RawData::with_source(source_id, value, now);
+                let classification = classifier.classify(&data, &harness.context);
+                let mut message = encoder.encode(&data, &classification, &harness.context);
+                if rng.gen_bool(0.3) {
+                    message.header.priority = Priority::from_u8(rng.gen_range(0..5)).unwrap();
+                }
+                if rng.gen_bool(0.1) {
+                    message.header.message_type =
+                        MessageType::from_u8(rng.gen_range(0..8)).unwrap();
+                }
+                let mut bytes = encoder.message_to_bytes(&message);
+                if rng.gen_bool(0.1) {
+                    mutate(&mut rng, &mut bytes);
+                }
+                if i % 97 == 96 {
+                    encoder.force_timestamp_anchor();
+                }
+                if i % 211 == 210 {
+                    // Decoder restart: compressed timestamps fail until the
+                    // next anchor, in both decoders
+                    harness.decoder.clear_timestamps();
+                    harness.decoder.clear_seeds();
+                    harness.reference = ReferenceDecoder::new();
+                }
+
+                harness.check_single(&bytes);
+            }
+            total += harness.cases;
+            successes += harness.successes;
+        }
+    }
+
+    assert!(total >= 5_000);
+    assert!(successes >= total / 2, "{successes} of {total} decoded");
+}
+
+/// Context with predictions for sources 1-3, one numeric and one
+/// non-numeric pattern
+fn seeded_context(harness: &mut Harness) -> (u32, u32) {
+    for i in 0..4u64 {
+        for source_id in 1..=3u32 {
+            harness.context.observe(&RawData::with_source(
+                source_id,
+                10.0 * source_id as f64 + i as f64,
+                START_MS + i * 1000,
+            ));
+        }
+    }
+    let numeric = harness
+        .context
+        .register_pattern(Pattern::numeric(42.25))
+        .unwrap();
+    let opaque = harness
+        .context
+        .register_pattern(Pattern::new(vec![0xDE, 0xAD]))
+        .unwrap();
+    (numeric, opaque)
+}
+
+fn random_header(rng: &mut StdRng, out: &mut Vec<u8>) {
+    let version = rng.gen_range(0..4u8);
+    // Priorities 5-7 are invalid and must be rejected by both decoders
+    let priority = if rng.gen_bool(0.9) {
+        rng.gen_range(0..5u8)
+    } else {
+        rng.gen_range(5..8u8)
+    };
+    out.push((version << 6) | (rng.gen_range(0..8u8) << 3) | priority);
+    if version == 0 {
+        // Mostly the supported major and known flags (bit 4 selects
+        // CRC-32C, bit 5 marks a self-describing value); sometimes a newer
+        // major or unknown critical flags
+        let major = if rng.gen_bool(0.8) { 1 } else { 2 };
+        let flags = if rng.gen_bool(0.8) {
+            rng.gen_range(0..64u8)
+        } else {
+            rng.gen()
+        };
+        out.push((major << 4) | rng.gen_range(0..16u8));
+        out.push(flags);
+    }
+    out.extend_from_slice(&rng.gen::<u16>().to_be_bytes());
+    if version < 2 {
+        out.extend_from_slice(&rng.gen_range(1_000_000u32..2_000_000).to_be_bytes());
+    } else {
+        let field = match rng.gen_range(0..4) {
+            // Small forward delta or truncated field
+            0 => rng.gen_range(0..64u32),
+            1 => (rng.gen_range(0..4096u32) << 5) | rng.gen_range(0..26u32),
+            2 => rng.gen(),
+            _ => rng.gen_range(0..8u32) * 2 + 1,
+        };
+        if rng.gen_bool(0.1) {
+            // 5-byte varint overflowing u32
+            out.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, rng.gen_range(0x10..0x80)]);
+        } else {
+            let canonical = {
+                let mut tmp = Vec::new();
+                write_varint(field as u64, &mut tmp);
+                tmp.len()
+            };
+            let padding = rng.gen_range(0..=5 - canonical);
+            write_padded_varint(field, padding, out);
+        }
+    }
+    out.extend_from_slice(&rng.gen::<[u8; 3]>());
+}
+
+fn random_value(rng: &mut StdRng, encoding: u8, pattern_ids: (u32, u32), out: &mut Vec<u8>) {
+    match encoding {
+        0x00 => out.extend_from_slice(&rng.gen::<u64>().to_be_bytes()),
+        0x01 => out.extend_from_slice(&rng.gen::<u32>().to_be_bytes()),
+        0x10 => out.push(rng.gen()),
+        0x11 => out.extend_from_slice(&rng.gen::<i16>().to_be_bytes()),
+        0x12 => out.extend_from_slice(&rng.gen::<i32>().to_be_bytes()),
+        0x20 | 0x21 => {
+            let id = match rng.gen_range(0..3) {
+                0 => pattern_ids.0,
+                1 => pattern_ids.1,
+                _ => rng.gen_range(0..1000),
+            };
+            write_padded_varint(id, rng.gen_range(0..2), out);
+            if encoding == 0x21 {
+                out.push(rng.gen());
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_handcrafted_payloads_match_reference() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_0002);
+
+    for policy in POLICIES {
+        let mut harness = Harness::new(policy);
+        let patterns = seeded_context(&mut harness);
+
+        // Anchor every source so compressed timestamps can resolve
+        for source_id in 0..8u32 {
+            let mut bytes = vec![0x40 | 2, 0, 0];
+            bytes.extend_from_slice(&1_500_000u32.to_be_bytes());
+            bytes.extend_from_slice(&[0, 0, 1]);
+            write_varint(source_id as u64, &mut bytes);
+            bytes.push(0x01);
+            bytes.extend_from_slice(&21.5f32.to_be_bytes());
+            if harness.reference_expects_checksum(&bytes) {
+                let checksum = reference_decoder::checksum(&bytes);
+                bytes.extend_from_slice(&checksum.to_be_bytes());
+            }
+            harness.check_single(&bytes);
+        }
+
+        for _ in 0..3000 {
+            let mut bytes = Vec::new();
+            random_header(&mut rng, &mut bytes);
+
+            let source_id = rng.gen_range(0..8u32);
+            if rng.gen_bool(0.05) {
+                bytes.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F]);
+            } else {
+                write_padded_varint(source_id, rng.gen_range(0..2), &mut bytes);
+            }
+            let encoding = if rng.gen_bool(0.9) {
+                ENCODINGS[rng.gen_range(0..ENCODINGS.len())]
+            } else {
+                rng.gen()
+            };
+            bytes.push(encoding);
+            random_value(&mut rng, encoding, patterns, &mut bytes);
+
+            if harness.reference_expects_checksum(&bytes) && rng.gen_bool(0.9) {
+                let checksum = reference_decoder::checksum(&bytes);
+                bytes.extend_from_slice(&checksum.to_be_bytes());
+            }
+            if rng.gen_bool(0.2) {
+                mutate(&mut rng, &mut bytes);
+            }
+            harness.check_single(&bytes);
+        }
+        assert!(harness.successes > 100, "{} successes", harness.successes);
+    }
+}
+
+#[test]
+fn test_multi_frames_match_reference() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_0003);
+    let mut encoder = Encoder::new();
+    let classifier = Classifier::default();
+    let mut harness = Harness::new(ChecksumPolicy::Never);
+    let patterns = seeded_context(&mut harness);
+
+    for i in 0..2000u64 {
+        let mut bytes = match rng.gen_range(0..3) {
+            0 => {
+                let values: Vec<(u8, f64)> = (0..rng.gen_range(0..6u8))
+                    .map(|id| (id + 1, next_value(&mut rng, 20.0)))
+                    .collect();
+                encoder
+                    .encode_multi(
+                        &values,
+                        rng.gen_range(0..300),
+                        START_MS + i * 1000,
+                        Priority::from_u8(rng.gen_range(0..5)).unwrap(),
+                        &harness.context,
+                    )
+                    .unwrap()
+                    .to_bytes()
+            }
+            1 => {
+                let channels: Vec<ChannelInput> = (1..=rng.gen_range(1..6u8))
+                    .map(|id| ChannelInput {
+                        name_id: id,
+                        source_id: id as u32,
+                        value: 10.0 * id as f64 + rng.gen_range(-3.0..3.0),
+                    })
+                    .collect();
+                let (message, _) = encoder
+                    .encode_multi_adaptive(
+                        &channels,
+                        START_MS + i * 1000,
+                        &harness.context,
+                        &classifier,
+                    )
+                    .unwrap();
+                message.to_bytes()
+            }
+            _ => {
+                let mut bytes = Vec::new();
+                random_header(&mut rng, &mut bytes);
+                write_varint(rng.gen_range(0..300), &mut bytes);
+                bytes.push(if rng.gen_bool(0.9) { 0x40 } else { rng.gen() });
+                let count = rng.gen_range(0..5u8);
+                bytes.push(count);
+                for _ in 0..count {
+                    bytes.push(rng.gen_range(0..8));
+                    let encoding = ENCODINGS[rng.gen_range(0..ENCODINGS.len())];
+                    bytes.push(encoding);
+                    random_value(&mut rng, encoding, patterns, &mut bytes);
+                }
+                bytes
+            }
+        };
+        if rng.gen_bool(0.2) {
+            mutate(&mut rng, &mut bytes);
+        }
+        harness.check_multi(&bytes);
+    }
+    assert!(harness.successes > 500, "{} successes", harness.successes);
+}
+
+#[test]
+fn test_fixed_frames_match_reference() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_0004);
+
+    for channel_count in [1usize, 3, 4, 5, 8, 17, 64] {
+        let mut encoder = Encoder::new();
+        let mut harness = Harness::new(ChecksumPolicy::Never);
+        let mut values = vec![20.0f64; channel_count];
+        let mut output = [0u8; 512];
+
+        for i in 0..300 {
+            for value in values.iter_mut() {
+                let next = next_value(&mut rng, *value);
+                *value = if next.is_nan() { 0.0 } else { next };
+            }
+            let keyframe = i == 0 || rng.gen_bool(0.1);
+            let len = encoder
+                .encode_multi_fixed(&values, &harness.context, keyframe, &mut output)
+                .unwrap();
+            let mut bytes = output[..len].to_vec();
+
+            if rng.gen_bool(0.05) {
+                // Simulate lost uplinks
+                let skip = rng.gen_range(1..400u16);
+                encoder.restore_sequence(encoder.sequence().wrapping_add(skip));
+            }
+            if rng.gen_bool(0.15) {
+                mutate(&mut rng, &mut bytes);
+            }
+            let decode_count = match rng.gen_range(0..20) {
+                0 => channel_count + 1,
+                1 => channel_count - 1,
+                _ => channel_count,
+            };
+            harness.check_fixed(&bytes, decode_count);
+        }
+        assert!(harness.successes > 150, "{} successes", harness.successes);
+    }
+}
+
+#[test]
+fn test_fixed_channel_count_limits() {
+    let mut harness = Harness::new(ChecksumPolicy::Never);
+    let frame = [0xA1, 0, 1, 0, 1, 0, 0];
+
+    // Out-of-range channel counts are rejected before any size check
+    harness.check_fixed(&frame, 0);
+    harness.check_fixed(&frame, 65);
+    harness.check_fixed(&[0xA1], 65);
+    harness.check_fixed(&[], 200);
+    assert_eq!(harness.successes, 0);
+}
+
+#[test]
+fn test_non_canonical_varints_match_reference() {
+    let mut harness = Harness::new(ChecksumPolicy::Always);
+
+    let anchor = {
+        let mut bytes = vec![0x40 | 1, 0, 1];
+        bytes.extend_from_slice(&1_000_000u32.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0, 1, 0x07, EncodingType::Raw32 as u8]);
+        bytes.extend_from_slice(&5.0f32.to_be_bytes());
+        bytes
+    };
+
+    // Delta timestamp of +3 s padded to 3 bytes, source id padded to 2
+    let mut padded = vec![0x80 | 1, 0, 2];
+    write_padded_varint(6, 2, &mut padded);
+    padded.extend_from_slice(&[0, 0, 1]);
+    write_padded_varint(7, 1, &mut padded);
+    padded.push(EncodingType::Repeated as u8);
+
+    // 5-byte timestamp varint overflowing u32
+    let mut overflow = vec![0x80 | 1, 0, 3, 0xFF, 0xFF, 0xFF, 0xFF, 0x1F, 0, 0, 1, 0x07];
+    overflow.push(EncodingType::Repeated as u8);
+
+    for body in [anchor, padded, overflow] {
+        let mut bytes = body.clone();
+        bytes.extend_from_slice(&reference_decoder::checksum(&body).to_be_bytes());
+        harness.check_single(&bytes);
+    }
+    // Anchor and padded message decode; the overflow is rejected
+    assert_eq!(harness.successes, 2);
+    assert_eq!(harness.cases, 3);
+}
+
+#[test]
+fn test_orphan_plan_frames_match_reference() {
+    let mut harness = Harness::new(ChecksumPolicy::Always);
+
+    // Truncated prefix, then complete frames for a plan never announced
+    harness.check_single(&[0xA5, 3]);
+    harness.check_single(&[0xA5, 3, 0, 1, 0x10]);
+    harness.check_single(&[0xA5, 0, 7, 0x80, 0x01, 0x10, 0x20]);
+    assert_eq!(harness.cases, 3);
+    assert_eq!(harness.successes, 0);
+}
@@ -0,0 +1,87 @@
+//! Error context (`ErrorContext`, `Decoder::decode_buffer`):
+//! - A buffer of concatenated messages decodes item by item
+//! - A corrupted message names its offset in the buffer, its source and
+//!   its sequence number, and still matches through `AlecError::inner`
+//! - A message that cannot be delimited names its offset
+
+use alec::error::{DecodeError, ErrorContext};
+use alec::{AlecError, Classifier, Context, Decoded, Decoder, Encoder, RawData};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+/// One message per source, each with its checksum
+fn messages(encoder: &mut Encoder, context: &Context) -> Vec<Vec<u8>> {
+    (1..=3u32)
+        .map(|source_id| {
+            let data = RawData::with_source(source_id, 20.0 + source_id as f64, START_MS);
+            let classification = Classifier::default().classify(&data, context);
+            encoder.encode_to_bytes(&data, &classification, context)
+        })
+        .collect()
+}
+
+#[test]
+fn test_decode_buffer() {
+    let context = Context::new();
+    let buffer = messages(&mut Encoder::with_checksum(), &context).concat();
+
+    let items = Decoder::with_checksum_verification()
+        .decode_buffer(&buffer, &context)
+        .unwrap();
+    let values: Vec<(u32, f64)> = items
+        .iter()
+        .map(|item| match item {
+            Decoded::Value(data) => (data.source_id, data.value),
+            other => panic!("unexpected item {:?}", other),
+        })
+        .collect();
+    assert_eq!(values, [(1, 21.0), (2, 22.0), (3, 23.0)]);
+}
+
+#[test]
+fn test_corrupted_message_context() {
+    let context = Context::new();
+    let mut encoder = Encoder::with_checksum();
+    let messages = messages(&mut encoder, &context);
+    let offset = messages[0].len();
+    let mut buffer = messages.concat();
+    // Last value byte of the second message, just before its checksum
+    buffer[offset + messages[1].len() - 5] ^= 0xFF;
+
+    let err = Decoder::with_checksum_verification()
+        .decode_buffer(&buffer, &context)
+        .unwrap_err();
+    assert_eq!(
+        err.context(),
+        Some(&ErrorContext {
+            source_id: Some(2),
+            sequence: Some(1),
+            offset: Some(offset),
+        })
+    );
+    assert!(matches!(
+        err.inner(),
+        AlecError::Decode(DecodeError::InvalidChecksum { .. })
+    ));
+    assert!(err
+        .to_string()
+        .ends_with(&format!("(source 2, sequence 1, offset {})", offset)));
+}
+
+#[test]
+fn test_truncated_message_context() {
+    let context = Context::new();
+    let messages = messages(&mut Encoder::new(), &context);
+    let offset = messages[0].len() + messages[1].len();
+    let mut buffer = messages.concat();
+    buffer.truncate(buffer.len() - 2);
+
+    let err = Decoder::new().decode_buffer(&buffer, &context).unwrap_err();
+    let location = err.context().unwrap();
+    assert_eq!(location.offset, Some(offset));
+    assert_eq!(location.source_id, Some(3));
+    assert!(matches!(
+        err.into_inner(),
+        AlecError::Decode(DecodeError::MalformedMessage { .. })
+    ));
+}
@@ -0,0 +1,154 @@
+//! Pluggable pattern eviction:
+//! - Under a bursty workload, `WindowedFrequency` keeps the burst patterns
+//!   across the idle gap while the default policy prunes them
+//! - Every policy keeps the dictionary within `max_patterns`, synced
+//!   patterns included
+//! - Ties are broken by code, so identical dictionaries evolve identically
+
+use alec::context::{
+    ContextConfig, EvictionPolicy, EvolutionConfig, FrequencyRecency, Lfu, Lru, Pattern,
+    WindowedFrequency,
+};
+use alec::{Context, RawData};
+
+const BURST_PATTERNS: usize = 16;
+const IDLE_GAP: u64 = 3_000;
+
+fn bursty_context(eviction: Box<dyn EvictionPolicy>) -> Context {
+    Context::with_config(ContextConfig {
+        evolution: EvolutionConfig {
+            max_age: 1_000,
+            evolution_interval: 100,
+            ..Default::default()
+        },
+        eviction,
+        ..Default::default()
+    })
+}
+
+fn burst_pattern(i: usize) -> Vec<u8> {
+    format!("BATCH-{:02}", i).into_bytes()
+}
+
+/// Nightly upload: a burst of heavily used patterns, then a long idle gap
+fn run_burst_and_gap(ctx: &mut Context) {
+    for i in 0..BURST_PATTERNS {
+        let mut pattern = Pattern::with_timestamp(burst_pattern(i), ctx.observation_count());
+        pattern.frequency = 50 + i as u64;
+        ctx.register_pattern(pattern).unwrap();
+    }
+    for t in 0..IDLE_GAP {
+        ctx.observe(&RawData::new(20.0, t));
+    }
+}
+
+fn burst_patterns_left(ctx: &Context) -> usize {
+    (0..BURST_PATTERNS)
+        .filter(|&i| ctx.find_pattern(&burst_pattern(i)).is_some())
+        .count()
+}
+
+#[test]
+fn test_windowed_frequency_survives_idle_gap() {
+    let mut default = bursty_context(Box::default());
+    run_burst_and_gap(&mut default);
+    assert_eq!(burst_patterns_left(&default), 0);
+
+    let mut windowed = bursty_context(Box::new(WindowedFrequency { window: 5_000 }));
+    run_burst_and_gap(&mut windowed);
+    assert_eq!(burst_patterns_left(&windowed), BURST_PATTERNS);
+
+    // Reordered by frequency: the most used burst pattern has code 0
+    assert_eq!(
+        windowed.find_pattern(&burst_pattern(BURST_PATTERNS - 1)),
+        Some(0)
+    );
+}
+
+fn policies() -> Vec<Box<dyn EvictionPolicy>> {
+    vec![
+        Box::new(FrequencyRecency),
+        Box::new(Lfu),
+        Box::new(Lru),
+        Box::new(WindowedFrequency { window: 10_000 }),
+    ]
+}
+
+#[test]
+fn test_all_policies_respect_max_patterns() {
+    for policy in policies() {
+        let mut ctx = Context::with_config(ContextConfig {
+            max_patterns: 32,
+            eviction: policy.clone(),
+            ..Default::default()
+        });
+        for i in 0..32u64 {
+            let mut pattern = Pattern::with_timestamp(i.to_be_bytes().to_vec(), i);
+            pattern.frequency = 2 + i;
+            ctx.register_pattern(pattern).unwrap();
+        }
+        assert!(ctx.register_pattern(Pattern::new(vec![0xFF])).is_err());
+
+        // Synced patterns are held to the limit as well
+        for i in 32..48u32 {
+            let mut pattern = Pattern::with_timestamp(vec![0xEE, i as u8], i as u64);
+            pattern.frequency = 2;
+            assert!(ctx.set_pattern(i, pattern).is_err());
+        }
+        assert_eq!(ctx.pattern_count(), 32);
+        ctx.evolve();
+        assert_eq!(ctx.pattern_count(), 32, "{:?}", policy);
+
+        let version = ctx.dictionary_version();
+        assert_eq!(ctx.evict_lowest(4), 4);
+        assert_eq!(ctx.pattern_count(), 28);
+        assert_eq!(ctx.dictionary_version(), version + 1);
+    }
+}
+
+#[test]
+fn test_policies_rank_differently() {
+    // Old but frequent vs. recent but rare
+    let mut frequent = Pattern::with_timestamp(vec![1], 0);
+    frequent.frequency = 100;
+    let mut recent = Pattern::with_timestamp(vec![2], 900);
+    recent.frequency = 3;
+
+    assert!(Lfu.score(&frequent, 1_000) > Lfu.score(&recent, 1_000));
+    assert!(Lru.score(&frequent, 1_000) < Lru.score(&recent, 1_000));
+
+    let windowed = WindowedFrequency { window: 500 };
+    assert_eq!(windowed.score(&frequent, 1_000), 0.0);
+    assert_eq!(windowed.score(&recent, 1_000), 3.0);
+}
+
+#[test]
+fn test_eviction_ties_are_deterministic() {
+    for policy in policies() {
+        let build = || {
+            let mut ctx = Context::with_config(ContextConfig {
+                max_patterns: 40,
+                eviction: policy.clone(),
+                ..Default::default()
+            });
+            // Equal frequency and age everywhere: only codes tell them apart
+            for i in 0..40u32 {
+                let mut pattern = Pattern::with_timestamp(i.to_be_bytes().to_vec(), 0);
+                pattern.frequency = 2;
+                ctx.set_pattern(i, pattern).unwrap();
+            }
+            ctx.evict_lowest(20);
+            ctx.evolve();
+            ctx
+        };
+
+        let a = build();
+        let b = build();
+        assert_eq!(a.pattern_count(), 20);
+        assert_eq!(a.hash(), b.hash());
+        // The lowest codes survive, in order
+        for i in 0..20u32 {
+            assert_eq!(a.find_pattern(&i.to_be_bytes()), Some(i), "{:?}", policy);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Fleet message routing (`FleetManager::subscribe` / `drain_matching`):
+//! - Two subscribers with disjoint priority filters each receive exactly
+//!   their share of 100 mixed-priority messages, in order
+//! - A panicking handler is counted and does not keep the next subscriber
+//!   from its messages
+//! - Emitter and message type filters, unsubscribing
+//! - Pull queues return the matching messages since the previous drain
+//!   and drop the oldest beyond their capacity
+
+use std::sync::{Arc, Mutex};
+
+use alec::fleet::{
+    FleetConfig, FleetManager, MessageHandler, ProcessedMessage, SubscriptionFilter,
+};
+use alec::{Context, EncodedMessage, Encoder, MessageType, Priority, RawData};
+
+const PRIORITIES: [Priority; 5] = [
+    Priority::P1Critical,
+    Priority::P2Important,
+    Priority::P3Normal,
+    Priority::P4Deferred,
+    Priority::P5Disposable,
+];
+
+/// Message `i` of a mixed-priority stream, with its priority
+fn message(encoder: &mut Encoder, i: usize) -> (EncodedMessage, Priority) {
+    let priority = PRIORITIES[(i * 7 + i / 3) % PRIORITIES.len()];
+    let data = RawData::new(20.0 + i as f64 * 0.1, i as u64 * 1000);
+    let message = encoder.encode_with_priority(&data, priority, &Context::new());
+    (message, priority)
+}
+
+/// (emitter, priority) of the messages a handler received
+type Received = Arc<Mutex<Vec<(u32, Priority)>>>;
+
+/// Handler recording what it receives
+fn recorder() -> (Received, MessageHandler) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let handler = Box::new(move |message: &ProcessedMessage| {
+        sink.lock()
+            .unwrap()
+            .push((message.emitter_id, message.priority));
+    });
+    (received, handler)
+}
+
+#[test]
+fn test_disjoint_subscribers_receive_their_subsets() {
+    let mut fleet = FleetManager::new();
+    let alerts_filter =
+        SubscriptionFilter::all().with_priorities([Priority::P1Critical, Priority::P2Important]);
+    let storage_filter = SubscriptionFilter::all().with_priorities([Priority::P3Normal]);
+    let (alerts, handler) = recorder();
+    fleet.subscribe(alerts_filter, handler);
+    let (storage, handler) = recorder();
+    fleet.subscribe(storage_filter, handler);
+
+    let mut encoder = Encoder::new();
+    let mut sent = Vec::new();
+    for i in 0..100 {
+        let (message, priority) = message(&mut encoder, i);
+        let emitter = i as u32 % 4;
+        let processed = fleet.process_message(emitter, &message, i as u64).unwrap();
+        assert_eq!(processed.priority, priority);
+        sent.push((emitter, priority));
+    }
+
+    let expected = |wanted: &[Priority]| -> Vec<(u32, Priority)> {
+        sent.iter()
+            .filter(|(_, p)| wanted.contains(p))
+            .copied()
+            .collect()
+    };
+    let alerts = alerts.lock().unwrap();
+    let storage = storage.lock().unwrap();
+    assert_eq!(
+        *alerts,
+        expected(&[Priority::P1Critical, Priority::P2Important])
+    );
+    assert_eq!(*storage, expected(&[Priority::P3Normal]));
+    assert!(!alerts.is_empty() && !storage.is_empty());
+    assert!(alerts.len() + storage.len() < 100);
+    assert_eq!(fleet.handler_panics(), 0);
+}
+
+#[test]
+fn test_panicking_handler_is_isolated() {
+    let mut fleet = FleetManager::new();
+    fleet.subscribe(
+        SubscriptionFilter::all(),
+        Box::new(|message: &ProcessedMessage| {
+            if message.priority == Priority::P1Critical {
+                panic!("handler failure");
+            }
+        }),
+    );
+    let (received, handler) = recorder();
+    fleet.subscribe(SubscriptionFilter::all(), handler);
+
+    let mut encoder = Encoder::new();
+    let mut critical = 0;
+    for i in 0..20 {
+        let (message, priority) = message(&mut encoder, i);
+        critical += (priority == Priority::P1Critical) as u64;
+        fleet.process_message(1, &message, i as u64).unwrap();
+    }
+
+    assert!(critical > 0);
+    assert_eq!(fleet.handler_panics(), critical);
+    assert_eq!(received.lock().unwrap().len(), 20);
+    // The manager keeps working
+    assert_eq!(fleet.stats().total_messages, 20);
+}
+
+#[test]
+fn test_emitter_and_type_filters() {
+    let mut fleet = FleetManager::new();
+    let (from_two, handler) = recorder();
+    fleet.subscribe(SubscriptionFilter::all().with_emitters([2]), handler);
+    let (heartbeats, handler) = recorder();
+    let heartbeat_id = fleet.subscribe(
+        SubscriptionFilter::all().with_message_types([MessageType::Heartbeat]),
+        handler,
+    );
+
+    let mut encoder = Encoder::new();
+    for i in 0..6 {
+        let (mut message, _) = message(&mut encoder, i);
+        if i % 2 == 0 {
+            message.header.message_type = MessageType::Heartbeat;
+            message.header.timestamp = i as u32;
+        }
+        let processed = fleet.process_message(i as u32 % 3, &message, i as u64);
+        // Heartbeats with a payload are confirmations, which carry no value
+        if i % 2 == 0 {
+            assert!(processed.is_err());
+        } else {
+            assert_eq!(processed.unwrap().message_type, MessageType::Data);
+        }
+    }
+    // Failed messages are not routed
+    assert!(heartbeats.lock().unwrap().is_empty());
+    assert_eq!(from_two.lock().unwrap().len(), 1);
+
+    assert!(fleet.unsubscribe(heartbeat_id));
+    assert!(!fleet.unsubscribe(heartbeat_id));
+}
+
+#[test]
+fn test_drain_matching_queues() {
+    let mut fleet = FleetManager::with_config(FleetConfig {
+        subscription_queue_capacity: 8,
+        ..Default::default()
+    });
+    let disposable = SubscriptionFilter::all().with_priorities([Priority::P5Disposable]);
+    assert!(fleet.drain_matching(&disposable).is_empty());
+
+    let mut encoder = Encoder::new();
+    let mut expected = Vec::new();
+    for i in 0..100 {
+        let (message, priority) = message(&mut encoder, i);
+        fleet.process_message(1, &message, i as u64).unwrap();
+        if priority == Priority::P5Disposable {
+            // Sent as raw f32
+            expected.push((20.0 + i as f64 * 0.1) as f32 as f64);
+        }
+    }
+
+    let drained: Vec<f64> = fleet
+        .drain_matching(&disposable)
+        .iter()
+        .map(|m| m.value)
+        .collect();
+    assert!(expected.len() > 8);
+    assert_eq!(drained, expected[expected.len() - 8..]);
+    assert_eq!(fleet.dropped_queued_messages(), expected.len() as u64 - 8);
+    assert!(fleet.drain_matching(&disposable).is_empty());
+
+    let (message, _) = message(&mut encoder, 4);
+    fleet.process_message(1, &message, 100).unwrap();
+    assert_eq!(fleet.drain_matching(&disposable).len(), 1);
+}
@@ -0,0 +1,120 @@
+//! Decoder-side gap filling (`Decoder::enable_gap_fill` / `estimate_at`):
+//! - Linear interpolation across a 5-minute gap in 1-minute data, with
+//!   confidence decreasing away from the last decoded value
+//! - No estimate beyond `max_gap_ms`, at decoded timestamps, or when disabled
+//! - Hold and Predict methods past the latest decoded value
+
+use alec::{Classifier, Context, Decoder, Encoder, GapFillConfig, GapFillMethod, RawData};
+
+const SOURCE: u32 = 2;
+const MINUTE: u64 = 60_000;
+
+/// Slow ramp sampled once a minute
+fn sample(minute: u64) -> RawData {
+    RawData::with_source(SOURCE, 20.0 + 0.5 * minute as f64, minute * MINUTE)
+}
+
+/// Decode minutes 0..=4 and 10..=12; minutes 5..=9 are lost
+fn decode_with_gap(config: Option<GapFillConfig>) -> (Decoder, Context) {
+    let mut encoder = Encoder::new();
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    let mut decoder = Decoder::new();
+    if let Some(config) = config {
+        decoder.enable_gap_fill(config);
+    }
+
+    for minute in (0..=4).chain(10..=12) {
+        let data = sample(minute);
+        let classification = classifier.classify(&data, &context);
+        let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.timestamp * 1000, data.timestamp);
+        context.observe(&data);
+    }
+    (decoder, context)
+}
+
+fn linear(max_gap_ms: u64) -> GapFillConfig {
+    GapFillConfig {
+        max_gap_ms,
+        method: GapFillMethod::Linear,
+    }
+}
+
+#[test]
+fn test_linear_fills_five_minute_gap() {
+    let (decoder, context) = decode_with_gap(Some(linear(10 * MINUTE)));
+
+    let mut last_confidence = 1.0;
+    for minute in 5..=9 {
+        let estimate = decoder
+            .estimate_at(SOURCE, minute * MINUTE, &context)
+            .unwrap();
+        let expected = sample(minute).value;
+        assert!(
+            (estimate.value - expected).abs() < 0.02,
+            "minute {}: {} vs {}",
+            minute,
+            estimate.value,
+            expected
+        );
+        assert_eq!(estimate.method, GapFillMethod::Linear);
+        assert!(estimate.confidence < last_confidence);
+        last_confidence = estimate.confidence;
+    }
+    assert!(last_confidence > 0.0);
+}
+
+#[test]
+fn test_no_estimate_beyond_max_gap() {
+    // The 6-minute span around the lost messages exceeds 5 minutes
+    let (decoder, context) = decode_with_gap(Some(linear(5 * MINUTE)));
+    assert!(decoder.estimate_at(SOURCE, 7 * MINUTE, &context).is_none());
+
+    // Past the latest value, up to max_gap_ms only
+    assert!(decoder.estimate_at(SOURCE, 17 * MINUTE, &context).is_some());
+    assert!(decoder
+        .estimate_at(SOURCE, 17 * MINUTE + 1, &context)
+        .is_none());
+}
+
+#[test]
+fn test_no_estimate_for_decoded_or_unknown() {
+    let (decoder, context) = decode_with_gap(Some(linear(10 * MINUTE)));
+    assert!(decoder.estimate_at(SOURCE, 4 * MINUTE, &context).is_none());
+    assert!(decoder
+        .estimate_at(SOURCE + 1, 5 * MINUTE, &context)
+        .is_none());
+
+    let (decoder, context) = decode_with_gap(None);
+    assert!(decoder.gap_fill().is_none());
+    assert!(decoder.estimate_at(SOURCE, 5 * MINUTE, &context).is_none());
+}
+
+#[test]
+fn test_open_gap_methods() {
+    let at = 14 * MINUTE;
+
+    let (decoder, context) = decode_with_gap(Some(linear(10 * MINUTE)));
+    let estimate = decoder.estimate_at(SOURCE, at, &context).unwrap();
+    assert_eq!(estimate.method, GapFillMethod::Linear);
+    assert!((estimate.value - sample(14).value).abs() < 0.05);
+
+    let (decoder, context) = decode_with_gap(Some(GapFillConfig {
+        max_gap_ms: 10 * MINUTE,
+        method: GapFillMethod::Hold,
+    }));
+    let estimate = decoder.estimate_at(SOURCE, at, &context).unwrap();
+    assert_eq!(estimate.method, GapFillMethod::Hold);
+    assert!((estimate.value - sample(12).value).abs() < 0.01);
+
+    let (decoder, context) = decode_with_gap(Some(GapFillConfig {
+        max_gap_ms: 10 * MINUTE,
+        method: GapFillMethod::Predict,
+    }));
+    let estimate = decoder.estimate_at(SOURCE, at, &context).unwrap();
+    assert_eq!(estimate.method, GapFillMethod::Predict);
+    assert_eq!(estimate.value, context.predict(SOURCE).unwrap().value);
+    assert!(estimate.confidence < 1.0);
+}
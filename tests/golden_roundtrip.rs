@@ -0,0 +1,323 @@
+// ALEC - Adaptive Lazy Evolving Compression
+// Copyright (c) 2025 David Martin Venti
+//
+// Dual-licensed under AGPL-3.0 and Commercial License.
+// See LICENSE file for details.
+
+//! Golden context round-trips:
+//! - Each recorded trace is encoded against its committed preload context
+//!   and decoded against an identically preloaded context, both observing
+//!   as a device and a gateway would
+//! - Decoded values match the trace within a few scale steps, and both
+//!   contexts end with the same dictionary
+//! - The encoded messages match the committed wire capture byte for byte
+//!
+//! Fixtures live in `tests/golden/`, three files per trace:
+//! - `<name>.trace`: the samples, `ALTR` then a version byte, a u32 LE
+//!   count and `[source_id: u32 LE] [timestamp_ms: u64 LE] [value: f64 LE]`
+//!   per sample
+//! - `<name>.alec-context`: the preload both peers start from
+//! - `<name>.wire`: the messages, `[len: u16 LE] [bytes]` each
+//!
+//! After an intentional wire format change, rewrite the captures with
+//!
+//! ```text
+//! ALEC_GOLDEN_REGEN=1 cargo test --test golden_roundtrip -- --nocapture
+//! ```
+//!
+//! which prints what changed for each trace. The same run records the
+//! trace and context of a fixture added to [`FIXTURES`] whose files are
+//! missing; existing traces and contexts are never rewritten.
+
+use std::fs;
+use std::path::PathBuf;
+
+use alec::{Classifier, Context, Decoder, Encoder, RawData};
+
+/// Start of every trace, on a whole second as headers carry seconds
+const START_MS: u64 = 1_741_234_567_000;
+
+const TRACE_MAGIC: &[u8; 4] = b"ALTR";
+const TRACE_VERSION: u8 = 1;
+
+/// Sample `i` of a signal; traces and training runs differ by seed
+type Signal = fn(usize, u64) -> f64;
+
+/// Committed fixtures: name, source id and signal generator
+const FIXTURES: &[(&str, u32, Signal)] =
+    &[("sine", 0, sine), ("steps", 3, steps), ("noise", 7, noise)];
+
+/// Samples per recorded trace and per training run
+const TRACE_LEN: usize = 400;
+
+/// Decoding error allowed, in scale steps
+///
+/// Deltas are rounded to half a step, and the receiver predicts from the
+/// decoded values while the sender predicts from the originals, so the
+/// two predictions drift apart by a fraction of a step.
+const TOLERANCE_STEPS: f64 = 3.0;
+
+/// Most frequent training values preloaded as patterns
+const PRELOAD_PATTERNS: usize = 32;
+
+/// Smooth temperature-like sine, two decimals
+fn sine(i: usize, seed: u64) -> f64 {
+    let phase = (i as f64 + seed as f64 * 17.0) * std::f64::consts::TAU / 120.0;
+    round2(21.0 + 4.0 * phase.sin())
+}
+
+/// Plateaus of 37 samples over a few set points
+fn steps(i: usize, seed: u64) -> f64 {
+    const LEVELS: [f64; 5] = [20.0, 25.0, 22.5, 30.0, 25.0];
+    LEVELS[(i / 37 + seed as usize) % LEVELS.len()]
+}
+
+/// Uniform noise in 40..60, two decimals
+fn noise(i: usize, seed: u64) -> f64 {
+    // SplitMix64, stable across toolchains and crate versions
+    let mut z = (seed << 32 | i as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    round2(40.0 + 20.0 * (z >> 11) as f64 / (1u64 << 53) as f64)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn regenerating() -> bool {
+    std::env::var_os("ALEC_GOLDEN_REGEN").is_some_and(|v| v != "0")
+}
+
+fn fixture_path(name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.{}", name, extension))
+}
+
+/// Read a fixture, recording it first in regeneration mode if missing
+fn read_or_record(name: &str, extension: &str, record: impl FnOnce() -> Vec<u8>) -> Vec<u8> {
+    let path = fixture_path(name, extension);
+    if !path.exists() && regenerating() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, record()).unwrap();
+        println!("{}: recorded {}", name, path.display());
+    }
+    fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {} ({}); run with ALEC_GOLDEN_REGEN=1 to record it",
+            name,
+            path.display(),
+            e
+        )
+    })
+}
+
+fn record_trace(source_id: u32, signal: Signal) -> Vec<u8> {
+    let mut out = TRACE_MAGIC.to_vec();
+    out.push(TRACE_VERSION);
+    out.extend_from_slice(&(TRACE_LEN as u32).to_le_bytes());
+    for i in 0..TRACE_LEN {
+        out.extend_from_slice(&source_id.to_le_bytes());
+        out.extend_from_slice(&(START_MS + i as u64 * 1000).to_le_bytes());
+        out.extend_from_slice(&signal(i, 1).to_le_bytes());
+    }
+    out
+}
+
+fn parse_trace(name: &str, bytes: &[u8]) -> Vec<RawData> {
+    assert!(
+        bytes.len() >= 9 && &bytes[..4] == TRACE_MAGIC && bytes[4] == TRACE_VERSION,
+        "{}: not a version {} trace",
+        name,
+        TRACE_VERSION
+    );
+    let count = u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize;
+    let samples = &bytes[9..];
+    assert_eq!(samples.len(), count * 20, "{}: truncated trace", name);
+    samples
+        .chunks_exact(20)
+        .map(|s| {
+            RawData::with_source(
+                u32::from_le_bytes(s[..4].try_into().unwrap()),
+                f64::from_le_bytes(s[12..].try_into().unwrap()),
+                u64::from_le_bytes(s[4..12].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
+/// Preload trained on another stretch of the same signal: its statistics
+/// and its most frequent values as patterns
+fn record_context(name: &str, source_id: u32, signal: Signal) -> Vec<u8> {
+    let mut context = Context::new();
+    let mut counts: Vec<(f64, usize)> = Vec::new();
+    for i in 0..TRACE_LEN {
+        let value = signal(i, 2);
+        context.observe(&RawData::with_source(source_id, value, i as u64 * 1000));
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    // Stable sort: equally frequent values keep their first-seen order
+    counts.sort_by_key(|c| std::cmp::Reverse(c.1));
+    let values: Vec<f64> = counts.iter().take(PRELOAD_PATTERNS).map(|c| c.0).collect();
+    context.seed_from_values(&values).unwrap();
+    context.to_preload_bytes(name).unwrap()
+}
+
+fn encode_wire(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for message in messages {
+        out.extend_from_slice(&(message.len() as u16).to_le_bytes());
+        out.extend_from_slice(message);
+    }
+    out
+}
+
+fn parse_wire(name: &str, mut bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    while !bytes.is_empty() {
+        assert!(bytes.len() >= 2, "{}: truncated wire capture", name);
+        let len = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        assert!(bytes.len() >= 2 + len, "{}: truncated wire capture", name);
+        messages.push(bytes[2..2 + len].to_vec());
+        bytes = &bytes[2 + len..];
+    }
+    messages
+}
+
+/// What changed between two captures, one line
+fn diff_summary(old: &[Vec<u8>], new: &[Vec<u8>]) -> String {
+    let changed: Vec<usize> = (0..old.len().max(new.len()))
+        .filter(|&i| old.get(i) != new.get(i))
+        .collect();
+    let size = |messages: &[Vec<u8>]| messages.iter().map(Vec::len).sum::<usize>();
+    match changed.first() {
+        None => "unchanged".to_string(),
+        Some(first) => format!(
+            "{} of {} messages changed (first at {}), {} -> {} bytes",
+            changed.len(),
+            new.len(),
+            first,
+            size(old),
+            size(new)
+        ),
+    }
+}
+
+/// Encode a trace as a device would and decode it as a gateway would,
+/// returning the messages
+fn roundtrip(name: &str, preload: &[u8], trace: &[RawData]) -> Vec<Vec<u8>> {
+    let mut encoder_context = Context::from_preload_bytes(preload).unwrap();
+    let mut decoder_context = Context::from_preload_bytes(preload).unwrap();
+    assert!(
+        encoder_context.pattern_count() > 0,
+        "{}: empty preload",
+        name
+    );
+    let tolerance = TOLERANCE_STEPS / encoder_context.scale_factor() as f64;
+
+    let classifier = Classifier::default();
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let mut messages = Vec::with_capacity(trace.len());
+
+    for (i, data) in trace.iter().enumerate() {
+        let classification = classifier.classify(data, &encoder_context);
+        let bytes = encoder.encode_to_bytes(data, &classification, &encoder_context);
+        encoder_context.observe_classified(data, &classification);
+
+        let decoded = decoder
+            .decode_bytes(&bytes, &decoder_context)
+            .unwrap_or_else(|e| panic!("{}: sample {} does not decode: {}", name, i, e));
+        assert_eq!(decoded.source_id, data.source_id, "{}: sample {}", name, i);
+        assert_eq!(
+            decoded.timestamp * 1000,
+            data.timestamp,
+            "{}: sample {}",
+            name,
+            i
+        );
+        assert!(
+            (decoded.value - data.value).abs() <= tolerance,
+            "{}: sample {} decodes to {} instead of {}",
+            name,
+            i,
+            decoded.value,
+            data.value
+        );
+        decoder_context.observe(&RawData::with_source(
+            decoded.source_id,
+            decoded.value,
+            decoded.timestamp * 1000,
+        ));
+        messages.push(bytes);
+    }
+
+    assert_eq!(
+        decoder_context.hash(),
+        encoder_context.hash(),
+        "{}: dictionaries diverged",
+        name
+    );
+    assert_eq!(decoder_context.version(), encoder_context.version());
+    messages
+}
+
+#[test]
+fn test_golden_roundtrips() {
+    let mut failures = Vec::new();
+
+    for &(name, source_id, signal) in FIXTURES {
+        let trace = read_or_record(name, "trace", || record_trace(source_id, signal));
+        let trace = parse_trace(name, &trace);
+        let preload = read_or_record(name, "alec-context", || {
+            record_context(name, source_id, signal)
+        });
+
+        let messages = roundtrip(name, &preload, &trace);
+        let path = fixture_path(name, "wire");
+        let golden = fs::read(&path).map(|bytes| parse_wire(name, &bytes));
+
+        if regenerating() {
+            let summary = match &golden {
+                Ok(old) => diff_summary(old, &messages),
+                Err(_) => "recorded".to_string(),
+            };
+            fs::write(&path, encode_wire(&messages)).unwrap();
+            println!("{}: {}", name, summary);
+            continue;
+        }
+
+        match golden {
+            Ok(golden) if golden == messages => {}
+            Ok(golden) => failures.push(format!("{}: {}", name, diff_summary(&golden, &messages))),
+            Err(e) => failures.push(format!("{}: {} ({})", name, path.display(), e)),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "wire captures differ, rerun with ALEC_GOLDEN_REGEN=1 if intended:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_wire_capture_format() {
+    let messages = vec![vec![0xA5; 3], vec![], vec![1, 2]];
+    let bytes = encode_wire(&messages);
+    assert_eq!(bytes.len(), 3 * 2 + 5);
+    assert_eq!(parse_wire("test", &bytes), messages);
+
+    let mut changed = messages.clone();
+    changed[2] = vec![1, 2, 3];
+    assert_eq!(
+        diff_summary(&messages, &changed),
+        "1 of 3 messages changed (first at 2), 5 -> 6 bytes"
+    );
+    assert_eq!(diff_summary(&messages, &messages), "unchanged");
+}
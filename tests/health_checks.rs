@@ -0,0 +1,130 @@
+//! `HealthCheckable` implementations for the codec:
+//! - Encoder degrades once raw fallbacks dominate its recent messages
+//! - Decoder turns unhealthy on a high recent error rate or on repeated
+//!   checksum failures
+//! - `HealthMonitor::check` applies the monitor's own thresholds
+
+use alec::{
+    Classifier, Context, Decoder, Encoder, HealthCheckable, HealthConfig, HealthMonitor,
+    HealthStatus, RawData,
+};
+
+/// Encode `values` in order, observing each into `context`
+fn encode_all(encoder: &mut Encoder, context: &mut Context, values: &[f64]) -> Vec<Vec<u8>> {
+    let classifier = Classifier::default();
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let data = RawData::new(value, i as u64 * 1000);
+            let classification = classifier.classify(&data, context);
+            let bytes = encoder.encode_to_bytes(&data, &classification, context);
+            context.observe(&data);
+            bytes
+        })
+        .collect()
+}
+
+#[test]
+fn test_encoder_degrades_on_fallback_rate() {
+    let mut encoder = Encoder::new();
+    let mut context = Context::new();
+
+    let check = encoder.health_check();
+    assert_eq!(check.component, "Encoder");
+    assert_eq!(check.status, HealthStatus::Healthy);
+
+    // Steady values encode as repeated
+    encode_all(&mut encoder, &mut context, &[21.5; 32]);
+    let check = encoder.health_check();
+    assert_eq!(check.status, HealthStatus::Healthy);
+    assert!(check.message.contains("Messages: 32"));
+    assert!(encoder.fallback_rate() < 0.1);
+
+    // Large jumps defeat the predictor and fall back to raw
+    let jumps: Vec<f64> = (0..64)
+        .map(|i| if i % 2 == 0 { 1.0e9 } else { -1.0e9 } * (i + 1) as f64)
+        .collect();
+    encode_all(&mut encoder, &mut context, &jumps);
+    assert!(encoder.fallback_rate() > 0.5);
+
+    let check = encoder.health_check();
+    assert_eq!(check.status, HealthStatus::Degraded);
+    assert!(check.message.contains("Fallback rate"), "{}", check.message);
+    assert_eq!(encoder.messages_encoded(), 96);
+    assert!(encoder.last_encode_latency().is_some());
+}
+
+#[test]
+fn test_decoder_unhealthy_on_error_rate() {
+    let mut encoder = Encoder::new();
+    let mut sender = Context::new();
+    let mut receiver = Context::new();
+    let mut decoder = Decoder::new();
+
+    let values: Vec<f64> = (0..20).map(|i| 20.0 + i as f64 * 0.1).collect();
+    for bytes in encode_all(&mut encoder, &mut sender, &values) {
+        let decoded = decoder.decode_bytes(&bytes, &receiver).unwrap();
+        receiver.observe(&RawData::new(decoded.value, decoded.timestamp * 1000));
+    }
+    assert_eq!(decoder.health_check().status, HealthStatus::Healthy);
+
+    // Truncated messages fail to parse
+    for _ in 0..4 {
+        assert!(decoder.decode_bytes(&[0x00], &receiver).is_err());
+    }
+    assert!(decoder.decode_error_rate() > 0.1);
+
+    let check = decoder.health_check();
+    assert_eq!(check.component, "Decoder");
+    assert_eq!(check.status, HealthStatus::Unhealthy);
+    assert!(check.message.contains("Error rate"), "{}", check.message);
+}
+
+#[test]
+fn test_decoder_unhealthy_on_checksum_failures() {
+    let mut encoder = Encoder::with_checksum();
+    let mut sender = Context::new();
+    let receiver = Context::new();
+    let mut decoder = Decoder::with_checksum_verification();
+
+    let mut messages = encode_all(&mut encoder, &mut sender, &[20.0; 4]);
+    for bytes in &mut messages {
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+    }
+
+    for bytes in &messages[..3] {
+        assert!(decoder.decode_bytes(bytes, &receiver).is_err());
+    }
+    // Three failures are tolerated by default
+    assert_eq!(decoder.recent_checksum_failures(), 3);
+    assert_eq!(decoder.health_check().status, HealthStatus::Healthy);
+
+    assert!(decoder.decode_bytes(&messages[3], &receiver).is_err());
+    let check = decoder.health_check();
+    assert_eq!(check.status, HealthStatus::Unhealthy);
+    assert!(
+        check.message.contains("Checksum failures 4"),
+        "{}",
+        check.message
+    );
+}
+
+#[test]
+fn test_monitor_check_applies_thresholds() {
+    let mut encoder = Encoder::new();
+    let mut context = Context::new();
+    encode_all(&mut encoder, &mut context, &[1.0, 1.0e9, -1.0e9, 2.0e9]);
+
+    // Too few messages for the default window
+    let mut monitor = HealthMonitor::new();
+    assert_eq!(monitor.check(&encoder), HealthStatus::Healthy);
+
+    let mut monitor = HealthMonitor::with_config(HealthConfig {
+        min_window_messages: 4,
+        ..Default::default()
+    });
+    assert_eq!(monitor.check(&encoder), HealthStatus::Degraded);
+    assert!(monitor.report().contains("Encoder"));
+}
@@ -0,0 +1,107 @@
+//! Incremental evolution:
+//! - A cycle spread over observations ends with the dictionary, codes,
+//!   hash and versions of an immediate evolution, pruning included
+//! - The dictionary stays the one the cycle started from until it ends
+//! - Changing the dictionary mid-cycle completes the cycle first
+
+use alec::context::{ContextConfig, EvolutionConfig, Pattern};
+use alec::{Context, RawData};
+
+const INTERVAL: u64 = 1000;
+
+/// 500 patterns with scattered frequencies, a tenth of them seen once:
+/// evolution prunes and renumbers
+fn context(step_patterns: usize) -> Context {
+    let mut context = Context::with_config(ContextConfig {
+        max_patterns: 500,
+        evolution: EvolutionConfig {
+            min_frequency: 2,
+            evolution_interval: INTERVAL,
+            step_patterns,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    for i in 0..500u32 {
+        let frequency = if i % 10 == 3 {
+            1
+        } else {
+            (i as u64 * 7919) % 31 + 2
+        };
+        context
+            .set_pattern(
+                i,
+                Pattern {
+                    frequency,
+                    ..Pattern::numeric(i as f64 * 0.5)
+                },
+            )
+            .unwrap();
+    }
+    context
+}
+
+fn observe(context: &mut Context, count: u64) {
+    for _ in 0..count {
+        let i = context.observation_count();
+        context.observe(&RawData::new(20.0 + (i % 7) as f64, i));
+    }
+}
+
+fn assert_same_dictionary(a: &Context, b: &Context) {
+    assert_eq!(a.hash(), b.hash());
+    assert_eq!(a.pattern_count(), b.pattern_count());
+    assert_eq!(a.version(), b.version());
+    assert_eq!(a.dictionary_version(), b.dictionary_version());
+    for (code, pattern) in a.patterns_iter() {
+        assert_eq!(b.get_pattern(*code), Some(pattern));
+        assert_eq!(b.find_pattern(&pattern.data), Some(*code));
+    }
+}
+
+#[test]
+fn test_cycle_matches_immediate_evolution() {
+    for step_patterns in [1, 7, 64, 1000] {
+        let mut immediate = context(0);
+        let mut incremental = context(step_patterns);
+        let before = incremental.hash();
+
+        observe(&mut immediate, INTERVAL);
+        observe(&mut incremental, INTERVAL);
+        assert!(incremental.evolution_pending());
+        assert_ne!(immediate.hash(), before);
+
+        let mut steps = 0;
+        while incremental.evolution_pending() {
+            assert_eq!(incremental.hash(), before);
+            observe(&mut incremental, 1);
+            steps += 1;
+        }
+        assert!(steps < INTERVAL, "{} steps at {}", steps, step_patterns);
+        observe(&mut immediate, steps);
+        // The patterns seen once are pruned
+        assert_eq!(immediate.pattern_count(), 450);
+        assert_same_dictionary(&immediate, &incremental);
+
+        // The next cycle also starts from the same dictionary
+        observe(&mut immediate, INTERVAL);
+        observe(&mut incremental, INTERVAL);
+        incremental.finish_evolution();
+        assert_same_dictionary(&immediate, &incremental);
+    }
+}
+
+#[test]
+fn test_mutation_completes_cycle() {
+    let mut immediate = context(0);
+    let mut incremental = context(16);
+    observe(&mut immediate, INTERVAL + 3);
+    observe(&mut incremental, INTERVAL + 3);
+    assert!(incremental.evolution_pending());
+
+    // Code 5 is another pattern once renumbered
+    incremental.remove_pattern(5);
+    assert!(!incremental.evolution_pending());
+    immediate.remove_pattern(5);
+    assert_same_dictionary(&immediate, &incremental);
+}
@@ -0,0 +1,137 @@
+//! Latency budgets (`alec::latency`):
+//! - A P1 message held 60 s in the transmit queue lands in the queue
+//!   stage's 30 s+ bucket once the encoder, queue and decoder logs are
+//!   merged, and the queue is its slowest stage
+//! - Messages sent promptly stay under 1 s on every stage
+//! - The decoder alone sees the delay as end-to-end latency
+//! - Without `enable_latency_tracking` nothing is recorded
+
+use alec::channel::PriorityQueue;
+use alec::latency::{LatencyLog, LatencyStage};
+use alec::{Context, Decoder, EncodedMessage, Encoder, Priority, RawData};
+
+const START_MS: u64 = 1_741_234_567_000;
+const SECOND: u64 = 1_000;
+
+/// Encode a reading measured at `measured_ms`, 20 ms later
+fn encode(encoder: &mut Encoder, priority: Priority, measured_ms: u64) -> EncodedMessage {
+    let data = RawData::new(21.5, measured_ms);
+    let message = encoder.encode_with_priority(&data, priority, &Context::new());
+    encoder.record_encoded(&message, measured_ms, measured_ms + 20);
+    message
+}
+
+/// Sender, queue and receiver logs of three P1 messages, the second of
+/// which waits a minute in the queue (e.g. while the link is down)
+fn run() -> (Encoder, PriorityQueue, Decoder) {
+    let mut encoder = Encoder::new();
+    encoder.enable_latency_tracking(64);
+    let mut queue = PriorityQueue::new(64 * 1024).with_latency_tracking(64);
+    let mut decoder = Decoder::new();
+    decoder.enable_latency_tracking(64);
+
+    for (i, held_ms) in [0, 60 * SECOND, 0].into_iter().enumerate() {
+        let measured_ms = START_MS + i as u64 * 120 * SECOND;
+        let message = encode(&mut encoder, Priority::P1Critical, measured_ms);
+        let enqueued_ms = measured_ms + 30;
+        queue.enqueue_at(message, Priority::P1Critical, enqueued_ms);
+
+        let dequeued_ms = enqueued_ms + held_ms + 10;
+        let (_, message) = queue.dequeue_highest_at(dequeued_ms).unwrap();
+        let bytes = encoder.message_to_bytes(&message);
+        decoder
+            .decode_with_now(&bytes, &Context::new(), dequeued_ms + 200)
+            .unwrap();
+    }
+    (encoder, queue, decoder)
+}
+
+#[test]
+fn test_delayed_p1_attributed_to_queue() {
+    let (encoder, queue, decoder) = run();
+
+    let mut merged = encoder.latency_log().unwrap().clone();
+    merged.merge(queue.latency_log().unwrap());
+    merged.merge(decoder.latency_log().unwrap());
+    assert_eq!(merged.len(), 3);
+
+    let report = merged.merged_report();
+    let queued = report.histogram(Priority::P1Critical, LatencyStage::Queue);
+    assert_eq!(queued.total(), 3);
+    assert_eq!(queued.at_or_above(30 * SECOND), 1);
+
+    let delayed: Vec<_> = merged
+        .iter()
+        .filter_map(|(sequence, _)| merged.breakdown(sequence))
+        .filter(|b| b.end_to_end_ms.unwrap() >= 30 * SECOND)
+        .collect();
+    assert_eq!(delayed.len(), 1);
+    assert_eq!(delayed[0].queue_ms, Some(60 * SECOND + 10));
+    assert_eq!(delayed[0].slowest_stage(), Some(LatencyStage::Queue));
+
+    // Only the delayed message missed the budget, and not in transit
+    let transit = report.histogram(Priority::P1Critical, LatencyStage::Transit);
+    assert_eq!(transit.counts[0], 3);
+    let end_to_end = report.histogram(Priority::P1Critical, LatencyStage::EndToEnd);
+    assert_eq!(end_to_end.counts[0], 2);
+    assert_eq!(end_to_end.at_or_above(30 * SECOND), 1);
+}
+
+#[test]
+fn test_each_side_reports_its_own_stages() {
+    let (encoder, queue, decoder) = run();
+
+    let encoded = encoder.latency_report().unwrap();
+    let encode = encoded.histogram(Priority::P1Critical, LatencyStage::Encode);
+    assert_eq!(encode.counts[0], 3);
+    let queue_stage = encoded.histogram(Priority::P1Critical, LatencyStage::Queue);
+    assert_eq!(queue_stage.total(), 0);
+
+    let queued = queue.latency_report().unwrap();
+    let queue_stage = queued.histogram(Priority::P1Critical, LatencyStage::Queue);
+    assert_eq!(queue_stage.counts[0], 2);
+    assert_eq!(queue_stage.at_or_above(30 * SECOND), 1);
+
+    let decoded = decoder.latency_report().unwrap();
+    let end_to_end = decoded.histogram(Priority::P1Critical, LatencyStage::EndToEnd);
+    assert_eq!(end_to_end.total(), 3);
+    assert_eq!(end_to_end.at_or_above(30 * SECOND), 1);
+    // Other priorities are untouched
+    let p3 = decoded.histogram(Priority::P3Normal, LatencyStage::EndToEnd);
+    assert_eq!(p3.total(), 0);
+}
+
+#[test]
+fn test_tracking_is_opt_in() {
+    let mut encoder = Encoder::new();
+    let mut queue: PriorityQueue = PriorityQueue::new(1024);
+    let mut decoder = Decoder::new();
+
+    let message = encode(&mut encoder, Priority::P1Critical, START_MS);
+    queue.enqueue_at(message, Priority::P1Critical, START_MS);
+    let (_, message) = queue.dequeue_highest_at(START_MS + SECOND).unwrap();
+    let bytes = encoder.message_to_bytes(&message);
+    decoder
+        .decode_with_now(&bytes, &Context::new(), START_MS + 2 * SECOND)
+        .unwrap();
+
+    assert!(encoder.latency_report().is_none());
+    assert!(queue.latency_log().is_none());
+    assert!(decoder.latency_report().is_none());
+}
+
+#[test]
+fn test_log_keeps_latest_messages() {
+    let mut log = LatencyLog::new(2);
+    for sequence in 0..3 {
+        log.record_encoded(sequence, Priority::P2Important, START_MS, START_MS + 5);
+    }
+    assert_eq!(log.len(), 2);
+    assert!(log.get(0).is_none());
+    assert!(log.get(2).is_some());
+    // The report still counts every message
+    let encode = log
+        .report()
+        .histogram(Priority::P2Important, LatencyStage::Encode);
+    assert_eq!(encode.total(), 3);
+}
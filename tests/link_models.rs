@@ -0,0 +1,308 @@
+//! Constrained links (`SimulatedLink` on a manual clock):
+//! - Duty-cycled: messages sent while asleep are held until the link
+//!   wakes up
+//! - Class A receive windows: downlinks only arrive after an uplink, one
+//!   per window; the rest wait for the next uplink
+//! - Asymmetric: each direction is delayed by its own transmission time,
+//!   and a zero rate drops
+//! - Context sync over Class A converges within a bounded number of
+//!   uplinks, even when the network's answer misses the receive windows,
+//!   with the number of round trips reported
+
+#![cfg(feature = "std-time")]
+
+use alec::context::Pattern;
+use alec::{
+    Context, LinkDirection, LinkModel, QueueItem, SimulatedLink, SyncConfig, SyncMessage,
+    Synchronizer,
+};
+
+use LinkDirection::{Downlink, Uplink};
+
+/// Time between two uplinks of the device
+const UPLINK_PERIOD_MS: u64 = 60_000;
+
+/// LoRaWAN-like RX1/RX2: 1 s windows, opening 1 s and 2 s after the uplink
+const CLASS_A: LinkModel = LinkModel::ReceiveWindowsAfterSend {
+    window_ms: 1_000,
+    count: 2,
+};
+
+/// Frames exchanged between the device and the network
+#[derive(Debug, Clone, PartialEq)]
+enum Frame {
+    /// Periodic device reading
+    Reading(u32),
+    /// Network command unrelated to sync
+    Command(u32),
+    Sync(SyncMessage),
+}
+
+impl QueueItem for Frame {
+    fn queued_size(&self) -> usize {
+        match self {
+            Frame::Reading(_) | Frame::Command(_) => 4,
+            Frame::Sync(message) => message.to_bytes().len(),
+        }
+    }
+}
+
+/// Everything delivered on `direction` by now
+fn receive_all(link: &mut SimulatedLink<Frame>, direction: LinkDirection) -> Vec<Frame> {
+    std::iter::from_fn(|| link.receive(direction)).collect()
+}
+
+#[test]
+fn test_duty_cycled_holds_until_awake() {
+    let mut link = SimulatedLink::new(LinkModel::DutyCycled {
+        on_ms: 10_000,
+        off_ms: 50_000,
+    });
+    link.advance_to(5_000);
+    assert!(link.send(Uplink, Frame::Reading(1)));
+    assert_eq!(link.receive(Uplink), Some(Frame::Reading(1)));
+
+    // Asleep from 10 s to 60 s
+    link.advance_to(20_000);
+    assert!(!link.is_awake_at(20_000));
+    assert!(link.send(Downlink, Frame::Command(1)));
+    assert!(link.send(Downlink, Frame::Command(2)));
+    assert_eq!(link.next_delivery_ms(Downlink), Some(60_000));
+    link.advance_to(59_999);
+    assert_eq!(link.receive(Downlink), None);
+    link.advance_to(60_000);
+    assert_eq!(
+        receive_all(&mut link, Downlink),
+        [Frame::Command(1), Frame::Command(2)]
+    );
+
+    let stats = link.stats(Downlink);
+    assert_eq!((stats.sent, stats.delivered, stats.delayed), (2, 2, 2));
+    assert_eq!(stats.max_delay_ms, 40_000);
+}
+
+#[test]
+fn test_receive_windows_after_uplink() {
+    let mut link = SimulatedLink::new(CLASS_A);
+    // No uplink yet: nothing can be delivered
+    for i in 0..3 {
+        assert!(link.send(Downlink, Frame::Command(i)));
+    }
+    assert_eq!(link.next_delivery_ms(Downlink), None);
+    link.advance_to(UPLINK_PERIOD_MS - 1);
+    assert_eq!(link.receive(Downlink), None);
+
+    // One downlink per window, RX1 then RX2
+    link.advance_to(UPLINK_PERIOD_MS);
+    assert!(link.send(Uplink, Frame::Reading(0)));
+    assert_eq!(link.receive(Uplink), Some(Frame::Reading(0)));
+    assert_eq!(
+        link.next_delivery_ms(Downlink),
+        Some(UPLINK_PERIOD_MS + 1_000)
+    );
+    link.advance_to(UPLINK_PERIOD_MS + 1_000);
+    assert_eq!(receive_all(&mut link, Downlink), [Frame::Command(0)]);
+    link.advance_to(UPLINK_PERIOD_MS + 2_000);
+    assert_eq!(receive_all(&mut link, Downlink), [Frame::Command(1)]);
+
+    // The third waits for the next uplink
+    link.advance_to(2 * UPLINK_PERIOD_MS - 1);
+    assert_eq!(link.receive(Downlink), None);
+    assert_eq!(link.pending(Downlink), 1);
+    link.advance_to(2 * UPLINK_PERIOD_MS);
+    link.send(Uplink, Frame::Reading(1));
+    link.advance_to(2 * UPLINK_PERIOD_MS + 1_000);
+    assert_eq!(receive_all(&mut link, Downlink), [Frame::Command(2)]);
+
+    // A downlink sent during an open window goes out at once
+    link.advance_to(2 * UPLINK_PERIOD_MS + 2_500);
+    link.send(Downlink, Frame::Command(3));
+    assert_eq!(receive_all(&mut link, Downlink), [Frame::Command(3)]);
+}
+
+#[test]
+fn test_asymmetric_transmission_time() {
+    let mut link = SimulatedLink::new(LinkModel::Asymmetric {
+        uplink_bps: 3_200,
+        downlink_bps: 0,
+    });
+    // 4 bytes at 3200 bit/s: 10 ms each, one after the other
+    link.send(Uplink, Frame::Reading(1));
+    link.send(Uplink, Frame::Reading(2));
+    assert_eq!(link.next_delivery_ms(Uplink), Some(10));
+    link.advance_to(15);
+    assert_eq!(receive_all(&mut link, Uplink), [Frame::Reading(1)]);
+    link.advance_to(20);
+    assert_eq!(receive_all(&mut link, Uplink), [Frame::Reading(2)]);
+    assert_eq!(link.stats(Uplink).max_delay_ms, 20);
+
+    assert!(!link.send(Downlink, Frame::Command(1)));
+    assert_eq!(link.stats(Downlink).dropped, 1);
+}
+
+#[test]
+fn test_capacity_and_loss_drop() {
+    let mut link = SimulatedLink::new(CLASS_A).with_capacity(2);
+    assert!(link.send(Downlink, Frame::Command(0)));
+    assert!(link.send(Downlink, Frame::Command(1)));
+    assert!(!link.send(Downlink, Frame::Command(2)));
+    assert_eq!(link.stats(Downlink).dropped, 1);
+
+    let mut link = SimulatedLink::new(LinkModel::Ideal).with_loss_rate(1.0);
+    assert!(!link.send(Uplink, Frame::Reading(0)));
+    assert_eq!(link.receive(Uplink), None);
+}
+
+/// Outcome of a sync scenario
+#[derive(Debug)]
+struct SyncRun {
+    /// Uplinks sent until the device converged
+    uplinks: u32,
+    /// Sync requests the device sent
+    round_trips: u32,
+}
+
+/// Network-side context and a device-side copy missing its last patterns
+fn diverged_contexts() -> (Context, Context) {
+    let mut device = Context::new();
+    for i in 0..8u8 {
+        device
+            .register_pattern(Pattern::new(vec![0xA0, i]))
+            .unwrap();
+    }
+    let mut network = device.clone();
+    for i in 8..12u8 {
+        network
+            .register_pattern(Pattern::new(vec![0xA0, i]))
+            .unwrap();
+    }
+    assert_ne!(device.hash(), network.hash());
+    (network, device)
+}
+
+/// Run the device's uplink schedule until its context matches the
+/// network's, or `max_uplinks` have been sent
+///
+/// The network starts with `backlog` commands queued, announces its
+/// context after the first uplink and answers sync requests
+/// `reply_delay_ms` after receiving them. The device sends a reading on
+/// every uplink, or its sync request when it has one to (re-)send.
+fn run_sync(backlog: u32, reply_delay_ms: u64, max_uplinks: u32) -> Option<SyncRun> {
+    let (network_ctx, mut device_ctx) = diverged_contexts();
+    let mut network_sync = Synchronizer::new();
+    let mut device_sync = Synchronizer::with_config(SyncConfig {
+        sync_timeout: UPLINK_PERIOD_MS / 2,
+        ..Default::default()
+    });
+    let mut link = SimulatedLink::new(CLASS_A);
+    let mut outbox = None;
+    let mut round_trips = 0;
+    let mut replies: Vec<(u64, SyncMessage)> = Vec::new();
+
+    for i in 0..backlog {
+        link.send(Downlink, Frame::Command(i));
+    }
+
+    for uplink in 0..max_uplinks {
+        let now = uplink as u64 * UPLINK_PERIOD_MS;
+        link.advance_to(now);
+        if device_sync.check_timeout(now) {
+            outbox = device_sync.pending_request().cloned();
+        }
+        match outbox.take() {
+            Some(request) => {
+                round_trips += 1;
+                link.send(Uplink, Frame::Sync(SyncMessage::Request(request)));
+            }
+            None => {
+                link.send(Uplink, Frame::Reading(uplink));
+            }
+        }
+
+        // Network side
+        for frame in receive_all(&mut link, Uplink) {
+            if let Frame::Sync(SyncMessage::Request(request)) = frame {
+                let reply = network_sync.handle_request(&request, &network_ctx).unwrap();
+                replies.push((now + reply_delay_ms, reply));
+            }
+        }
+        if uplink == 0 {
+            link.send(
+                Downlink,
+                Frame::Sync(Synchronizer::create_announce(&network_ctx)),
+            );
+        }
+
+        // Until the next uplink: replies go out when ready, the device
+        // listens in its receive windows
+        let next_uplink = now + UPLINK_PERIOD_MS;
+        loop {
+            let next_reply = replies.iter().map(|(at, _)| *at).min();
+            let next = [next_reply, link.next_delivery_ms(Downlink)]
+                .into_iter()
+                .flatten()
+                .min()
+                .filter(|at| *at < next_uplink);
+            let Some(at) = next else {
+                break;
+            };
+            link.advance_to(at);
+            let (ready, later) = replies.drain(..).partition(|(ready_at, _)| *ready_at <= at);
+            replies = later;
+            for (_, reply) in ready {
+                link.send(Downlink, Frame::Sync(reply));
+            }
+
+            for frame in receive_all(&mut link, Downlink) {
+                match frame {
+                    Frame::Sync(SyncMessage::Announce(announce)) => {
+                        if let Some(SyncMessage::Request(request)) = device_sync
+                            .check_sync_needed_at(announce.version, announce.hash, &device_ctx, at)
+                        {
+                            outbox = Some(request);
+                        }
+                    }
+                    Frame::Sync(SyncMessage::Diff(diff)) => {
+                        device_sync.handle_diff(&diff, &mut device_ctx).unwrap();
+                    }
+                    _ => {}
+                }
+            }
+            if device_sync.is_synchronized() && device_ctx.hash() == network_ctx.hash() {
+                return Some(SyncRun {
+                    uplinks: uplink + 1,
+                    round_trips,
+                });
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_class_a_sync_converges() {
+    let run = run_sync(0, 0, 10).expect("device never converged");
+    println!("Class A sync: {:?}", run);
+    // Announce after uplink 1, request on uplink 2, diff in its RX1
+    assert_eq!(run.uplinks, 2);
+    assert_eq!(run.round_trips, 1);
+}
+
+#[test]
+fn test_class_a_sync_behind_backlog() {
+    // Four commands ahead of the announce take the first two uplinks' windows
+    let run = run_sync(4, 0, 10).expect("device never converged");
+    println!("Class A sync behind 4 commands: {:?}", run);
+    assert_eq!(run.uplinks, 4);
+    assert_eq!(run.round_trips, 1);
+}
+
+#[test]
+fn test_class_a_sync_reply_misses_windows() {
+    // The answer is ready after both windows closed: it goes out after the
+    // next uplink, which also carries the device's retry
+    let run = run_sync(0, 5_000, 10).expect("device never converged");
+    println!("Class A sync with a late reply: {:?}", run);
+    assert_eq!(run.uplinks, 3);
+    assert_eq!(run.round_trips, 2);
+}
@@ -0,0 +1,190 @@
+//! Message age on arrival (`Decoder::decode_with_now`):
+//! - A P1 message an hour old fails with `Stale` under a 5-minute threshold
+//! - The same message at P4 decodes with its age and the stale flag set
+//! - Without a threshold the age is set and the message is current
+//! - `Decoder::stats` buckets every age, stale messages included
+//! - Messages recording milliseconds or microseconds age to the
+//!   millisecond and go stale against the same thresholds
+
+use alec::error::{AlecError, DecodeError};
+use alec::{
+    Classification, ClassificationReason, Context, Decoder, DecoderConfig, Encoder, EncoderConfig,
+    Priority, RawData, StaleAction, TimestampUnit,
+};
+
+const SOURCE: u32 = 4;
+const MINUTE: u64 = 60_000;
+const HOUR: u64 = 60 * MINUTE;
+const NOW_MS: u64 = 1_741_234_567_000;
+
+/// A reading taken `age_ms` before `NOW_MS`
+fn message(priority: Priority, age_ms: u64) -> Vec<u8> {
+    let data = RawData::with_source(SOURCE, 21.5, NOW_MS - age_ms);
+    let classification = Classification::new(priority, ClassificationReason::NormalValue, 0.0, 1.0);
+    Encoder::new().encode_to_bytes(&data, &classification, &Context::new())
+}
+
+/// A reading taken `age_ms` before `NOW_MS`, recording `unit`
+fn message_in(unit: TimestampUnit, priority: Priority, age_ms: u64) -> Vec<u8> {
+    let data = RawData::with_source(SOURCE, 21.5, NOW_MS - age_ms);
+    let classification = Classification::new(priority, ClassificationReason::NormalValue, 0.0, 1.0);
+    Encoder::with_config(EncoderConfig {
+        timestamp_unit: Some(unit),
+        ..Default::default()
+    })
+    .encode_to_bytes(&data, &classification, &Context::new())
+}
+
+fn decoder() -> Decoder {
+    Decoder::with_config(
+        DecoderConfig::default()
+            .with_stale_threshold(Priority::P1Critical, 5 * MINUTE, StaleAction::Reject)
+            .with_stale_threshold(Priority::P4Deferred, 5 * MINUTE, StaleAction::Flag),
+    )
+}
+
+#[test]
+fn test_stale_critical_message_rejected() {
+    let mut decoder = decoder();
+    let err = decoder
+        .decode_with_now(
+            &message(Priority::P1Critical, HOUR),
+            &Context::new(),
+            NOW_MS,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        AlecError::Decode(DecodeError::Stale {
+            source_id: SOURCE,
+            priority: Priority::P1Critical,
+            age_ms: HOUR,
+            max_age_ms: 5 * MINUTE,
+        })
+    );
+    assert_eq!(decoder.stats().stale_rejected, 1);
+    // Not counted as a corrupted message
+    assert_eq!(decoder.decode_error_rate(), 0.0);
+
+    // A fresh critical message is current
+    let decoded = decoder
+        .decode_with_now(
+            &message(Priority::P1Critical, MINUTE),
+            &Context::new(),
+            NOW_MS,
+        )
+        .unwrap();
+    assert_eq!(decoded.age_ms, Some(MINUTE));
+    assert!(!decoded.stale);
+}
+
+#[test]
+fn test_stale_deferred_message_flagged() {
+    let mut decoder = decoder();
+    let decoded = decoder
+        .decode_with_now(
+            &message(Priority::P4Deferred, HOUR),
+            &Context::new(),
+            NOW_MS,
+        )
+        .unwrap();
+    assert_eq!(decoded.priority, Priority::P4Deferred);
+    assert_eq!(decoded.value, 21.5);
+    assert_eq!(decoded.age_ms, Some(HOUR));
+    assert!(decoded.stale);
+    assert_eq!(decoder.stats().stale_flagged, 1);
+    assert_eq!(decoder.stats().stale_rejected, 0);
+}
+
+#[test]
+fn test_age_without_threshold() {
+    let mut decoder = decoder();
+    let context = Context::new();
+    let decoded = decoder
+        .decode_with_now(&message(Priority::P3Normal, HOUR), &context, NOW_MS)
+        .unwrap();
+    assert_eq!(decoded.age_ms, Some(HOUR));
+    assert!(!decoded.stale);
+
+    // Ahead of the receiver's clock
+    let decoded = decoder
+        .decode_with_now(&message(Priority::P3Normal, 0), &context, NOW_MS - MINUTE)
+        .unwrap();
+    assert_eq!(decoded.age_ms, Some(0));
+
+    // Plain decoding leaves the age unset
+    let decoded = Decoder::new()
+        .decode_bytes(&message(Priority::P3Normal, HOUR), &context)
+        .unwrap();
+    assert_eq!(decoded.age_ms, None);
+}
+
+#[test]
+fn test_age_histogram() {
+    let mut decoder = decoder();
+    let context = Context::new();
+    let arrivals = [
+        (Priority::P3Normal, 0),
+        (Priority::P3Normal, MINUTE),
+        (Priority::P3Normal, 2 * MINUTE),
+        (Priority::P4Deferred, 2 * HOUR),
+        (Priority::P1Critical, 2 * HOUR),
+        (Priority::P5Disposable, 48 * HOUR),
+    ];
+    for (priority, age_ms) in arrivals {
+        let _ = decoder.decode_with_now(&message(priority, age_ms), &context, NOW_MS);
+    }
+
+    let stats = decoder.stats();
+    assert_eq!(stats.age_histogram.counts, [2, 1, 0, 2, 1]);
+    assert_eq!(stats.age_histogram.total(), 6);
+    assert_eq!(stats.stale_flagged, 1);
+    assert_eq!(stats.stale_rejected, 1);
+}
+
+#[test]
+fn test_age_in_recorded_unit() {
+    let context = Context::new();
+    for unit in [TimestampUnit::Millis, TimestampUnit::Micros] {
+        let mut decoder = decoder();
+        let decoded = decoder
+            .decode_with_now(
+                &message_in(unit, Priority::P3Normal, MINUTE + 250),
+                &context,
+                NOW_MS,
+            )
+            .unwrap();
+        assert_eq!(decoded.timestamp_unit, Some(unit));
+        assert_eq!(decoded.age_ms, Some(MINUTE + 250), "{:?}", unit);
+        assert!(!decoded.stale);
+
+        let decoded = decoder
+            .decode_with_now(
+                &message_in(unit, Priority::P4Deferred, HOUR),
+                &context,
+                NOW_MS,
+            )
+            .unwrap();
+        assert_eq!(decoded.age_ms, Some(HOUR), "{:?}", unit);
+        assert!(decoded.stale);
+
+        let err = decoder
+            .decode_with_now(
+                &message_in(unit, Priority::P1Critical, HOUR),
+                &context,
+                NOW_MS,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            AlecError::Decode(DecodeError::Stale {
+                source_id: SOURCE,
+                priority: Priority::P1Critical,
+                age_ms: HOUR,
+                max_age_ms: 5 * MINUTE,
+            })
+        );
+        assert_eq!(decoder.stats().stale_flagged, 1);
+        assert_eq!(decoder.stats().stale_rejected, 1);
+    }
+}
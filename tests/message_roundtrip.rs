@@ -0,0 +1,182 @@
+//! `EncodedMessage` byte round-trips (`to_bytes` / `from_bytes`):
+//! - Every `MessageType` × `EncodingType` × `Priority` × header layout,
+//!   with payloads of 0, 1, 255 and `MAX_PAYLOAD_SIZE` bytes, comes back
+//!   field-for-field, with and without a checksum
+//! - Messages produced by the encoder (values, multi, composite, plans,
+//!   confirmations, overrides, compressed timestamps) come back unchanged,
+//!   context versions above 24 bits included
+
+use alec::protocol::{ChecksumPolicy, TimestampFormat, FLAG_CRC32C, FLAG_OPERATOR_OVERRIDE};
+use alec::{
+    Classifier, CompositeEntry, Context, EncodedMessage, Encoder, EncoderConfig, EncodingType,
+    HeaderExtension, HeaderLayout, MessageHeader, MessageType, Priority, RawData, TimestampMode,
+    WireVersion, MAX_PAYLOAD_SIZE,
+};
+
+const PAYLOAD_SIZES: [usize; 4] = [0, 1, 255, MAX_PAYLOAD_SIZE];
+const START_MS: u64 = 1_741_234_567_000;
+
+fn message_types() -> Vec<MessageType> {
+    (0..8).filter_map(MessageType::from_u8).collect()
+}
+
+fn encoding_types() -> Vec<EncodingType> {
+    (0..=u8::MAX).filter_map(EncodingType::from_u8).collect()
+}
+
+fn priorities() -> Vec<Priority> {
+    (0..8).filter_map(Priority::from_u8).collect()
+}
+
+/// Every header layout, with a timestamp field of the matching form
+fn layouts() -> Vec<(HeaderLayout, u32)> {
+    vec![
+        (HeaderLayout::Plain(TimestampFormat::Full), 1_741_234_567),
+        (
+            HeaderLayout::Plain(TimestampFormat::Delta),
+            TimestampFormat::pack_delta(-3),
+        ),
+        (
+            HeaderLayout::Plain(TimestampFormat::Truncated),
+            TimestampFormat::pack_truncated(0x1234_5678, 16),
+        ),
+        (
+            HeaderLayout::Extended(HeaderExtension::new(WireVersion::LEGACY)),
+            u32::MAX,
+        ),
+        (
+            HeaderLayout::Extended(HeaderExtension {
+                version: WireVersion::CURRENT,
+                flags: FLAG_OPERATOR_OVERRIDE | FLAG_CRC32C,
+                subsec: 0,
+            }),
+            0,
+        ),
+    ]
+}
+
+/// Payload of `size` bytes starting with a source id and `encoding`
+fn payload(size: usize, encoding: EncodingType) -> Vec<u8> {
+    let mut payload = vec![0xA5; size];
+    let prefix = [0x07, encoding as u8];
+    let n = size.min(prefix.len());
+    payload[..n].copy_from_slice(&prefix[..n]);
+    payload
+}
+
+fn assert_roundtrip(message: &EncodedMessage) {
+    let bytes = message.to_bytes();
+    assert_eq!(bytes.len(), message.len());
+    let restored = EncodedMessage::from_bytes(&bytes).expect("parses");
+    assert_eq!(&restored, message, "header {:?}", message.header);
+
+    let restored = EncodedMessage::from_bytes_with_checksum(&message.to_bytes_with_checksum())
+        .expect("checksum verifies");
+    assert_eq!(&restored, message, "header {:?}", message.header);
+}
+
+#[test]
+fn test_every_combination_roundtrips() {
+    let mut checked = 0;
+    for message_type in message_types() {
+        for priority in priorities() {
+            for (layout, timestamp) in layouts() {
+                let header = MessageHeader {
+                    layout,
+                    message_type,
+                    priority,
+                    sequence: 0xBEEF,
+                    timestamp,
+                    context_version: MessageHeader::MAX_CONTEXT_VERSION,
+                };
+                assert_eq!(
+                    MessageHeader::from_bytes(&header.to_bytes()),
+                    Some(header.clone())
+                );
+
+                for encoding in encoding_types() {
+                    for size in PAYLOAD_SIZES {
+                        let message = EncodedMessage::new(header.clone(), payload(size, encoding));
+                        if size > 1 {
+                            assert_eq!(message.encoding_type(), Some(encoding));
+                        }
+                        assert_roundtrip(&message);
+                        checked += 1;
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(checked, 8 * 5 * 5 * 12 * PAYLOAD_SIZES.len());
+}
+
+#[test]
+fn test_encoder_output_roundtrips() {
+    let mut context = Context::new();
+    // Past what the 24-bit header field holds
+    context.set_version(MessageHeader::MAX_CONTEXT_VERSION + 5);
+    let classifier = Classifier::default();
+    let mut messages = Vec::new();
+
+    for timestamp_mode in [
+        TimestampMode::Full,
+        TimestampMode::DeltaVarint { anchor_interval: 4 },
+        TimestampMode::TruncatedEpoch { bits: 12 },
+    ] {
+        let mut encoder = Encoder::with_config(EncoderConfig {
+            checksum_policy: ChecksumPolicy::Always,
+            crc32c_policy: ChecksumPolicy::ForPriorityAtOrAbove(Priority::P1Critical),
+            timestamp_mode,
+            ..Default::default()
+        });
+        for i in 0..6u64 {
+            let data = RawData::with_source(3, 20.0 + i as f64 * 0.5, START_MS + i * 1000);
+            let classification = classifier.classify(&data, &context);
+            messages.push(encoder.encode(&data, &classification, &context));
+            messages.push(encoder.encode_with_priority(&data, Priority::P1Critical, &context));
+            messages.push(encoder.encode_with_priority(&data, Priority::P5Disposable, &context));
+            context.observe(&data);
+        }
+        messages.extend(encoder.encode_confirmation(
+            3,
+            START_MS + 9000,
+            Priority::P3Normal,
+            &context,
+        ));
+        messages.extend(encoder.announce_plan(3, 4, &context));
+        messages.push(
+            encoder
+                .encode_multi(
+                    &[(1, 21.5), (2, 40.0)],
+                    9,
+                    START_MS,
+                    Priority::P2Important,
+                    &context,
+                )
+                .unwrap(),
+        );
+        messages.push(
+            encoder
+                .encode_composite(
+                    9,
+                    START_MS,
+                    Priority::P3Normal,
+                    &[CompositeEntry {
+                        channel_id: 1,
+                        value: 21.5,
+                        context: &context,
+                    }],
+                )
+                .unwrap(),
+        );
+    }
+
+    assert!(messages.iter().any(|m| m.header.extension().is_some()));
+    assert!(messages
+        .iter()
+        .any(|m| m.header.timestamp_format() != TimestampFormat::Full));
+    for message in &messages {
+        assert!(message.header.context_version <= MessageHeader::MAX_CONTEXT_VERSION);
+        assert_roundtrip(message);
+    }
+}
@@ -44,8 +44,9 @@ fn test_encode_multi_adaptive() {
     let drift: [f64; 5] = [0.5, -1.5, 15.0, 0.0, 1.0];
     let channels = make_channels(&base, &drift);
 
-    let (message, _classifications) =
-        encoder.encode_multi_adaptive(&channels, 100, &context, &classifier);
+    let (message, _classifications) = encoder
+        .encode_multi_adaptive(&channels, 100, &context, &classifier)
+        .unwrap();
 
     // Parse the multi payload to check encoding types
     let payload = &message.payload;
@@ -117,8 +118,9 @@ fn test_encode_multi_p5_suppression() {
     // Channels with *no* change → classifier should assign P5 (BelowMinimumDelta)
     let channels = make_channels(&base, &[0.0001, 0.0001, 0.0001, 0.0001, 0.0001]);
 
-    let (message, classifications) =
-        encoder.encode_multi_adaptive(&channels, 200, &context, &classifier);
+    let (message, classifications) = encoder
+        .encode_multi_adaptive(&channels, 200, &context, &classifier)
+        .unwrap();
 
     // Count how many were classified P5
     let p5_count = classifications
@@ -176,7 +178,9 @@ fn test_encode_multi_shared_header() {
     let channels = make_channels(&base, &drift);
 
     // Multi-channel encode: one shared header
-    let (multi_msg, _) = encoder_multi.encode_multi_adaptive(&channels, 300, &context, &classifier);
+    let (multi_msg, _) = encoder_multi
+        .encode_multi_adaptive(&channels, 300, &context, &classifier)
+        .unwrap();
     let multi_bytes = multi_msg.to_bytes();
 
     // Single-channel encode: 5 separate headers
@@ -243,7 +247,9 @@ fn test_encode_multi_adaptive_decode_roundtrip() {
     let drift: [f64; 5] = [0.5, -1.5, 15.0, 0.1, -1.0];
     let channels = make_channels(&base, &drift);
 
-    let (message, _) = encoder.encode_multi_adaptive(&channels, 100, &enc_ctx, &classifier);
+    let (message, _) = encoder
+        .encode_multi_adaptive(&channels, 100, &enc_ctx, &classifier)
+        .unwrap();
 
     // Decode
     let decoded = decoder.decode_multi(&message, &dec_ctx).unwrap();
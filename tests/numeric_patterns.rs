@@ -0,0 +1,130 @@
+//! Canonical numeric patterns (`Pattern::numeric`, `canonicalize`):
+//! - Legacy `f64` byte patterns built from a `RawData` stream hash like
+//!   canonical ones once migrated
+//! - Two peers reaching the same readings through different float paths
+//!   (accumulation vs division, `-0.0` vs `0.0`) agree after migration
+//! - Lookups with legacy bytes still find migrated patterns
+//! - Preload files restore the value of numeric patterns
+
+use alec::context::{
+    numeric_pattern_bytes, ContextConfig, EvolutionConfig, Pattern, NUMERIC_PATTERN_SIZE,
+};
+use alec::{Context, RawData};
+
+fn context() -> Context {
+    Context::with_config(ContextConfig {
+        evolution: EvolutionConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+/// Readings a sensor would report: accumulated in steps of 0.1
+fn stream() -> Vec<RawData> {
+    let mut value = 0.0;
+    (0..20)
+        .map(|i| {
+            let data = RawData::new(value, i * 1_000);
+            value += 0.1;
+            data
+        })
+        .collect()
+}
+
+fn build(stream: &[RawData], pattern: impl Fn(f64) -> Pattern) -> Context {
+    let mut context = context();
+    for data in stream {
+        context.register_pattern(pattern(data.value)).unwrap();
+    }
+    context
+}
+
+#[test]
+fn test_legacy_and_canonical_paths_agree_after_migration() {
+    let stream = stream();
+    let canonical = build(&stream, Pattern::numeric);
+    let mut legacy = build(&stream, Pattern::numeric_legacy);
+    assert_ne!(legacy.hash(), canonical.hash());
+
+    let version = legacy.version();
+    assert_eq!(legacy.migrate_numeric_patterns(), stream.len());
+    assert_eq!(legacy.hash(), canonical.hash());
+    assert_eq!(legacy.pattern_count(), canonical.pattern_count());
+    assert_eq!(legacy.version(), version + 1);
+
+    // Nothing left to migrate
+    assert_eq!(legacy.migrate_numeric_patterns(), 0);
+    assert_eq!(legacy.version(), version + 1);
+
+    // Accumulated 0.30000000000000004 is stored as 0.3
+    let code = legacy.find_numeric_pattern(0.3).unwrap();
+    let pattern = legacy.get_pattern(code).unwrap();
+    assert_eq!(pattern.data.len(), NUMERIC_PATTERN_SIZE);
+    assert_eq!(pattern.value, Some(0.3));
+}
+
+#[test]
+fn test_float_paths_agree_after_migration() {
+    let accumulated = stream();
+    // The same readings computed by division, with a negative zero start
+    let divided: Vec<RawData> = accumulated
+        .iter()
+        .enumerate()
+        .map(|(i, data)| {
+            let value = if i == 0 { -0.0 } else { i as f64 / 10.0 };
+            RawData::new(value, data.timestamp)
+        })
+        .collect();
+
+    let mut left = build(&accumulated, Pattern::numeric_legacy);
+    let mut right = build(&divided, Pattern::numeric_legacy);
+    assert_ne!(left.hash(), right.hash());
+
+    left.migrate_numeric_patterns();
+    right.migrate_numeric_patterns();
+    assert_eq!(left.hash(), right.hash());
+    assert_eq!(left.hash(), build(&divided, Pattern::numeric).hash());
+}
+
+#[test]
+fn test_migration_merges_collisions() {
+    let mut context = context();
+    for value in [21.0, 21.004, 22.0] {
+        context
+            .register_pattern(Pattern::numeric_legacy(value))
+            .unwrap();
+    }
+    assert_eq!(context.migrate_numeric_patterns(), 3);
+    assert_eq!(context.pattern_count(), 2);
+
+    // 21.004 folded into the lowest code
+    let code = context.find_pattern(&21.004f64.to_be_bytes()).unwrap();
+    assert_eq!(code, 0);
+    assert_eq!(context.get_pattern(code).unwrap().frequency, 2);
+    assert_eq!(context.find_numeric_pattern(22.0), Some(2));
+}
+
+#[test]
+fn test_preload_restores_numeric_values() {
+    let mut context = context();
+    context.register_pattern(Pattern::numeric(-40.256)).unwrap();
+    context
+        .register_pattern(Pattern::numeric_legacy(18.5))
+        .unwrap();
+    context
+        .register_pattern(Pattern::new(b"ok".to_vec()))
+        .unwrap();
+
+    let bytes = context.to_preload_bytes("temperature").unwrap();
+    let loaded = Context::from_preload_bytes(&bytes).unwrap();
+    assert_eq!(loaded.hash(), context.hash());
+    assert_eq!(loaded.get_pattern(0).unwrap().value, Some(-40.26));
+    assert_eq!(
+        loaded.get_pattern(0).unwrap().data[..],
+        numeric_pattern_bytes(-40.26, 100)[..]
+    );
+    assert_eq!(loaded.get_pattern(1).unwrap().value, Some(18.5));
+    assert_eq!(loaded.get_pattern(2).unwrap().value, None);
+}
@@ -0,0 +1,124 @@
+//! Application priority overrides (`Classification::manual`,
+//! `Encoder::encode_with_priority`):
+//! - A value the classifier rates P5 is sent and decoded as P1 when
+//!   overridden, with the operator-override flag set
+//! - Classifier-assigned priorities decode without the flag
+//! - Overrides mixed into a compressed-timestamp stream keep timestamps
+//!   anchored
+//! - `CompressionMetrics` and `MetricsObserver` count overridden messages
+
+use std::sync::Arc;
+
+use alec::observer::MetricsObserver;
+use alec::{
+    Classification, Classifier, CompressionMetrics, Context, Decoder, Encoder, EncoderConfig,
+    Priority, RawData, TimestampMode,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+/// Context that has seen a flat series long enough to predict it
+fn flat_context() -> Context {
+    let mut context = Context::new();
+    for i in 0..20u64 {
+        context.observe(&RawData::new(20.0, START_MS + i * 1_000));
+    }
+    context
+}
+
+#[test]
+fn test_override_to_p1_roundtrip() {
+    let context = flat_context();
+    let classifier = Classifier::default();
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+
+    let data = RawData::new(20.0, START_MS + 20_000);
+    let classification = classifier.classify(&data, &context);
+    assert_eq!(classification.priority, Priority::P5Disposable);
+    assert!(!classification.is_override());
+
+    let message = encoder.encode(&data, &classification, &context);
+    let decoded = decoder
+        .decode_bytes(&encoder.message_to_bytes(&message), &context)
+        .unwrap();
+    assert_eq!(decoded.priority, Priority::P5Disposable);
+    assert!(!decoded.operator_override);
+
+    let data = RawData::new(20.0, START_MS + 21_000);
+    let message = encoder.encode_with_priority(&data, Priority::P1Critical, &context);
+    assert!(message.header.is_operator_override());
+    let decoded = decoder
+        .decode_bytes(&encoder.message_to_bytes(&message), &context)
+        .unwrap();
+    assert_eq!(decoded.priority, Priority::P1Critical);
+    assert!(decoded.operator_override);
+    assert_eq!(decoded.value, 20.0);
+    assert_eq!(decoded.timestamp, (START_MS + 21_000) / 1000);
+}
+
+#[test]
+fn test_manual_classification_through_encode_to_bytes() {
+    let context = flat_context();
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+
+    let data = RawData::new(20.0, START_MS + 20_000);
+    let classification = Classification::manual(Priority::P2Important, "incident sampling");
+    let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+
+    let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+    assert_eq!(decoded.priority, Priority::P2Important);
+    assert!(decoded.operator_override);
+}
+
+#[test]
+fn test_override_keeps_compressed_timestamps_anchored() {
+    let mut context = Context::new();
+    let classifier = Classifier::default();
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        timestamp_mode: TimestampMode::DeltaVarint { anchor_interval: 0 },
+        ..Default::default()
+    });
+    let mut decoder = Decoder::new();
+
+    for i in 0..12u64 {
+        let data = RawData::new(20.0 + i as f64 * 0.1, START_MS + i * 60_000);
+        let message = if i % 4 == 2 {
+            encoder.encode_with_priority(&data, Priority::P1Critical, &context)
+        } else {
+            encoder.encode(&data, &classifier.classify(&data, &context), &context)
+        };
+        let decoded = decoder
+            .decode_bytes(&encoder.message_to_bytes(&message), &context)
+            .unwrap();
+        assert_eq!(decoded.timestamp, data.timestamp / 1000);
+        assert_eq!(decoded.operator_override, i % 4 == 2);
+        context.observe(&data);
+    }
+}
+
+#[test]
+fn test_overrides_are_counted() {
+    let context = flat_context();
+    let classifier = Classifier::default();
+    let observer = Arc::new(MetricsObserver::new());
+    let mut encoder = Encoder::new();
+    encoder.set_observer(Box::new(observer.clone()));
+    let mut metrics = CompressionMetrics::new();
+
+    for i in 0..6u64 {
+        let data = RawData::new(20.0, START_MS + (20 + i) * 1_000);
+        let classification = if i % 3 == 0 {
+            Classification::manual(Priority::P1Critical, "")
+        } else {
+            classifier.classify(&data, &context)
+        };
+        encoder.encode_with_metrics(&data, &classification, &context, &mut metrics);
+    }
+
+    assert_eq!(metrics.message_count, 6);
+    assert_eq!(metrics.overridden_messages, 2);
+    assert_eq!(observer.metrics().overridden_messages, 2);
+    assert!(metrics.report().contains("Operator overrides: 2"));
+}
@@ -0,0 +1,136 @@
+//! Priority-protected patterns (`EvolutionConfig::protect_priority_at_or_above`):
+//! - A rarely used pattern touched by a P1 encode survives an evolution
+//!   that prunes an identically used P5 pattern, by frequency or by age,
+//!   immediate or incremental
+//! - Evicting to honor `max_patterns`, unprotected patterns go first even
+//!   when they score higher; once only protected patterns are left they go
+//!   too, counted by `protection_overrides`, and the limit holds
+
+use alec::context::{ContextConfig, EvolutionConfig, Lru, MergeStrategy, Pattern};
+use alec::{Context, Priority, RawData};
+
+/// Context protecting P1 and P2 patterns, evolving only when asked to
+fn protecting(evolution: EvolutionConfig, max_patterns: usize) -> Context {
+    Context::with_config(config(
+        EvolutionConfig {
+            enabled: false,
+            ..evolution
+        },
+        max_patterns,
+    ))
+}
+
+fn config(evolution: EvolutionConfig, max_patterns: usize) -> ContextConfig {
+    ContextConfig {
+        max_patterns,
+        evolution: EvolutionConfig {
+            protect_priority_at_or_above: Some(Priority::P2Important),
+            ..evolution
+        },
+        ..Default::default()
+    }
+}
+
+/// Register `data` and report one use at `priority`
+fn used_once(context: &mut Context, data: &[u8], priority: Priority) -> u32 {
+    let code = context.register_pattern(Pattern::from_slice(data)).unwrap();
+    assert!(context.touch_pattern(code, Some(priority)));
+    code
+}
+
+#[test]
+fn test_rare_critical_pattern_survives_pruning() {
+    let mut context = protecting(
+        EvolutionConfig {
+            min_frequency: 5,
+            ..Default::default()
+        },
+        64,
+    );
+    let safety = used_once(&mut context, b"VALVE-OPEN", Priority::P1Critical);
+    used_once(&mut context, b"DEBUG-TICK", Priority::P5Disposable);
+    let safety_pattern = context.get_pattern(safety).unwrap();
+    assert_eq!(safety_pattern.frequency, 2);
+    assert_eq!(safety_pattern.priority, Some(Priority::P1Critical));
+
+    context.evolve();
+    assert!(context.find_pattern(b"VALVE-OPEN").is_some());
+    assert!(context.find_pattern(b"DEBUG-TICK").is_none());
+    assert_eq!(context.pattern_count(), 1);
+
+    // A lower-priority use does not lower the protection
+    let code = context.find_pattern(b"VALVE-OPEN").unwrap();
+    context.touch_pattern(code, Some(Priority::P4Deferred));
+    assert_eq!(
+        context.get_pattern(code).unwrap().priority,
+        Some(Priority::P1Critical)
+    );
+}
+
+#[test]
+fn test_protection_against_age_pruning() {
+    for step_patterns in [0, 1] {
+        // Scheduled evolution, at once or one pattern per observation
+        let evolution = EvolutionConfig {
+            max_age: 10,
+            evolution_interval: 50,
+            step_patterns,
+            ..Default::default()
+        };
+        let mut context = Context::with_config(ContextConfig {
+            eviction: Box::new(Lru),
+            ..config(evolution, 64)
+        });
+        used_once(&mut context, b"VALVE-OPEN", Priority::P1Critical);
+        used_once(&mut context, b"DEBUG-TICK", Priority::P5Disposable);
+        // Unprotected without a priority reported
+        context
+            .register_pattern(Pattern::from_slice(b"UNREPORTED"))
+            .unwrap();
+        for t in 0..100 {
+            context.observe(&RawData::new(20.0, t));
+        }
+
+        context.evolve();
+        assert!(context.find_pattern(b"VALVE-OPEN").is_some());
+        assert!(context.find_pattern(b"DEBUG-TICK").is_none());
+        assert!(context.find_pattern(b"UNREPORTED").is_none());
+    }
+}
+
+#[test]
+fn test_limits_hold_over_protection() {
+    const MAX: usize = 4;
+    let mut context = protecting(EvolutionConfig::default(), MAX);
+    used_once(&mut context, b"SAFETY-0", Priority::P1Critical);
+    used_once(&mut context, b"SAFETY-1", Priority::P1Critical);
+    for data in [b"BULK-0", b"BULK-1"] {
+        let code = used_once(&mut context, data, Priority::P3Normal);
+        for _ in 0..50 {
+            context.touch_pattern(code, Some(Priority::P3Normal));
+        }
+    }
+
+    let mut incoming = protecting(EvolutionConfig::default(), MAX);
+    used_once(&mut incoming, b"SAFETY-2", Priority::P1Critical);
+    used_once(&mut incoming, b"SAFETY-3", Priority::P1Critical);
+    let report = context.merge(&incoming, MergeStrategy::PreferSelf);
+    assert_eq!(report.patterns_evicted, 2);
+    assert_eq!(context.pattern_count(), MAX);
+    // The busy unprotected patterns went first
+    assert!(context.find_pattern(b"BULK-0").is_none());
+    assert!(context.find_pattern(b"BULK-1").is_none());
+    assert_eq!(context.protection_overrides(), 0);
+
+    // Only protected patterns left: the limit wins
+    let mut incoming = protecting(EvolutionConfig::default(), MAX);
+    used_once(&mut incoming, b"SAFETY-4", Priority::P1Critical);
+    used_once(&mut incoming, b"SAFETY-5", Priority::P2Important);
+    context.merge(&incoming, MergeStrategy::PreferSelf);
+    assert_eq!(context.pattern_count(), MAX);
+    assert_eq!(context.protection_overrides(), 2);
+
+    assert_eq!(context.evict_lowest(1), 1);
+    assert_eq!(context.pattern_count(), MAX - 1);
+    assert_eq!(context.protection_overrides(), 3);
+}
@@ -0,0 +1,144 @@
+//! Pattern storage and in-place evolution:
+//! - Numeric patterns are stored inline, long patterns spill to the heap
+//! - The streamed context hash matches the hash of the concatenated
+//!   dictionary
+//! - Reordering moves patterns to dense codes by score, keeping lookups
+//!   and the dictionary contents intact
+//! - A dictionary already in order is left untouched
+
+use alec::context::{EvolutionConfig, Pattern, PatternData, PATTERN_INLINE_SIZE};
+use alec::{Context, SyncDiff, SyncMessage, SyncRequest, Synchronizer};
+use xxhash_rust::xxh64::xxh64;
+
+/// Hash over the concatenated `code | len | bytes` entries, in code order
+fn reference_hash(context: &Context) -> u64 {
+    let mut entries: Vec<(u32, &Pattern)> = context
+        .patterns_iter()
+        .map(|(code, pattern)| (*code, pattern))
+        .collect();
+    entries.sort_by_key(|(code, _)| *code);
+
+    let mut data = Vec::new();
+    for (code, pattern) in entries {
+        data.extend_from_slice(&code.to_be_bytes());
+        data.extend_from_slice(&(pattern.data.len() as u16).to_be_bytes());
+        data.extend_from_slice(&pattern.data);
+    }
+    xxh64(&data, 0)
+}
+
+/// Dictionary with scattered frequencies and gaps in its codes
+fn scattered(count: u32) -> Context {
+    let mut context = Context::with_evolution(EvolutionConfig {
+        min_frequency: 1,
+        max_age: u64::MAX,
+        ..Default::default()
+    });
+    for i in 0..count {
+        let data = if i % 5 == 0 {
+            // Longer than the inline capacity
+            vec![i as u8; PATTERN_INLINE_SIZE + 4]
+        } else {
+            Pattern::numeric(i as f64 * 0.5).data.to_vec()
+        };
+        let pattern = Pattern {
+            frequency: (i as u64 * 37) % 11 + 1,
+            ..Pattern::new(data)
+        };
+        context.set_pattern(i * 3 + 1, pattern).unwrap();
+    }
+    context
+}
+
+#[test]
+fn test_numeric_patterns_stored_inline() {
+    assert!(!Pattern::numeric(21.5).data.spilled());
+    assert!(!Pattern::numeric_legacy(21.5).data.spilled());
+    assert!(!Pattern::from_slice(b"OK").data.spilled());
+    assert!(Pattern::new(vec![0; PATTERN_INLINE_SIZE + 1])
+        .data
+        .spilled());
+
+    let data: PatternData = b"abc".as_slice().into();
+    assert_eq!(Pattern::new(b"abc".to_vec()), Pattern::from_slice(&data));
+}
+
+#[test]
+fn test_hash_matches_concatenated_dictionary() {
+    let context = scattered(200);
+    assert_eq!(context.hash(), reference_hash(&context));
+    assert_eq!(Context::new().hash(), reference_hash(&Context::new()));
+}
+
+#[test]
+fn test_reorder_keeps_patterns_and_lookups() {
+    let mut context = scattered(500);
+    let now = context.observation_count();
+    let mut expected: Vec<(f64, u32, Pattern)> = context
+        .patterns_iter()
+        .map(|(code, pattern)| (pattern.score(now), *code, pattern.clone()))
+        .collect();
+    expected.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then(a.1.cmp(&b.1)));
+
+    context.evolve();
+
+    assert_eq!(context.pattern_count(), expected.len());
+    for (new_code, (_, _, pattern)) in expected.iter().enumerate() {
+        let new_code = new_code as u32;
+        assert_eq!(context.get_pattern(new_code), Some(pattern));
+        assert_eq!(context.find_pattern(&pattern.data), Some(new_code));
+    }
+    assert_eq!(context.hash(), reference_hash(&context));
+
+    // New patterns continue after the dense codes
+    let code = context
+        .register_pattern(Pattern::from_slice(b"fresh"))
+        .unwrap();
+    assert_eq!(code, expected.len() as u32);
+}
+
+#[test]
+fn test_reorder_in_order_dictionary_untouched() {
+    let mut context = scattered(100);
+    context.evolve();
+    let hash = context.hash();
+    let dictionary_version = context.dictionary_version();
+
+    context.evolve();
+    assert_eq!(context.hash(), hash);
+    assert_eq!(context.dictionary_version(), dictionary_version);
+}
+
+#[test]
+fn test_apply_diff_matches_source_dictionary() {
+    let base = scattered(50);
+    let mut target = base.clone();
+    target.evolve();
+    for i in 0..20u32 {
+        target
+            .register_pattern(Pattern::numeric(1000.0 + i as f64))
+            .unwrap();
+    }
+
+    // Full dictionary, as answered to a sync request
+    let Some(SyncMessage::Diff(diff)) = Synchronizer::new().handle_request(
+        &SyncRequest {
+            from_version: 0,
+            to_version: None,
+        },
+        &target,
+    ) else {
+        panic!("sync request not answered with a diff");
+    };
+    let mut receiver = base.clone();
+    let removed: Vec<u32> = receiver
+        .pattern_ids()
+        .filter(|code| !target.has_pattern(*code))
+        .collect();
+    Synchronizer::apply_diff(&mut receiver, &SyncDiff { removed, ..diff }).unwrap();
+
+    assert_eq!(receiver.hash(), target.hash());
+    for (code, pattern) in target.patterns_iter() {
+        assert_eq!(receiver.find_pattern(&pattern.data), Some(*code));
+    }
+}
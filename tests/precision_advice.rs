@@ -0,0 +1,126 @@
+//! Compression-aware precision advice (`Encoder::precision_advice`):
+//! - A quiet signal gets a fine scale factor at under 2 bytes per sample
+//! - A noisy signal gets a coarse scale factor
+//! - The estimates match the value bytes actually encoded at each scale
+//!   within 30%
+//! - No advice until the source has enough history
+
+use alec::encoder::{PRECISION_ADVICE_MIN_SAMPLES, PRECISION_SCALE_FACTORS};
+use alec::{Classifier, Context, Encoder, PrecisionAdvice, RawData};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const SOURCE: u32 = 1;
+const SAMPLES: usize = 100;
+
+/// `SAMPLES` readings around 21.0 with uniform noise of `amplitude`
+fn signal(rng: &mut StdRng, amplitude: f64) -> Vec<f64> {
+    (0..SAMPLES)
+        .map(|_| 21.0 + rng.gen_range(-amplitude..=amplitude))
+        .collect()
+}
+
+/// Context that has observed `values` at the default scale factor
+fn observed(values: &[f64]) -> Context {
+    let mut context = Context::new();
+    for (i, &value) in values.iter().enumerate() {
+        context.observe(&RawData::with_source(SOURCE, value, i as u64 * 1000));
+    }
+    context
+}
+
+/// Mean value bytes per sample encoding `values` at `scale`, the first
+/// (raw, unpredicted) sample left out
+fn encoded_bytes_per_sample(values: &[f64], scale: u32) -> f32 {
+    let mut encoder = Encoder::new();
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    context.set_scale_factor(scale);
+
+    let mut total = 0;
+    for (i, &value) in values.iter().enumerate() {
+        let data = RawData::with_source(SOURCE, value, i as u64 * 1000);
+        let classification = classifier.classify(&data, &context);
+        let message = encoder.encode(&data, &classification, &context);
+        if i > 0 {
+            // Source id varint and encoding byte
+            total += message.payload.len() - 2;
+        }
+        context.observe(&data);
+    }
+    total as f32 / (values.len() - 1) as f32
+}
+
+/// Check each estimate against a fresh run of the same signal
+fn assert_estimates_match(advice: &PrecisionAdvice, fresh: &[f64]) {
+    assert_eq!(
+        advice.est_bytes_per_sample_at.len(),
+        PRECISION_SCALE_FACTORS.len()
+    );
+    for &(scale, estimate) in &advice.est_bytes_per_sample_at {
+        let actual = encoded_bytes_per_sample(fresh, scale);
+        assert!(
+            (actual - estimate).abs() <= 0.3 * estimate,
+            "scale {}: estimated {} bytes, encoded {}",
+            scale,
+            estimate,
+            actual
+        );
+    }
+}
+
+#[test]
+fn test_quiet_signal_affords_more_decimals() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_3155);
+    let context = observed(&signal(&mut rng, 0.0005));
+    let advice = Encoder::new().precision_advice(SOURCE, &context).unwrap();
+    assert_eq!(advice.samples, SAMPLES - 1);
+
+    assert!(advice.recommended_scale_factor >= 1_000, "{:?}", advice);
+    let (_, cost) = advice
+        .est_bytes_per_sample_at
+        .iter()
+        .find(|&&(scale, _)| scale == advice.recommended_scale_factor)
+        .copied()
+        .unwrap();
+    assert!(cost < 2.0, "{:?}", advice);
+
+    assert_estimates_match(&advice, &signal(&mut rng, 0.0005));
+}
+
+#[test]
+fn test_noisy_signal_keeps_few_decimals() {
+    let mut rng = StdRng::seed_from_u64(0xA1EC_3156);
+    let context = observed(&signal(&mut rng, 20.0));
+    let advice = Encoder::new().precision_advice(SOURCE, &context).unwrap();
+
+    assert!(advice.recommended_scale_factor <= 10, "{:?}", advice);
+    // Costs only grow with the scale
+    for pair in advice.est_bytes_per_sample_at.windows(2) {
+        assert!(pair[0].1 <= pair[1].1, "{:?}", advice);
+    }
+
+    assert_estimates_match(&advice, &signal(&mut rng, 20.0));
+}
+
+#[test]
+fn test_no_advice_without_history() {
+    let encoder = Encoder::new();
+    assert!(encoder.precision_advice(SOURCE, &Context::new()).is_none());
+
+    let values = vec![21.0; PRECISION_ADVICE_MIN_SAMPLES];
+    assert!(encoder
+        .precision_advice(SOURCE, &observed(&values))
+        .is_none());
+
+    let values = vec![21.0; PRECISION_ADVICE_MIN_SAMPLES + 1];
+    let advice = encoder
+        .precision_advice(SOURCE, &observed(&values))
+        .unwrap();
+    // A constant signal repeats at every scale
+    assert!(advice
+        .est_bytes_per_sample_at
+        .iter()
+        .all(|&(_, bytes)| bytes == 0.0));
+    assert_eq!(advice.recommended_scale_factor, 1_000_000);
+}
@@ -111,9 +111,9 @@ fn test_version_mismatch_detection() {
     ctx1.register_pattern(Pattern::new(vec![0x42])).unwrap();
     ctx1.save_to_file(&path, "test").unwrap();
 
-    // Load and modify the context (incrementing version)
+    // Load and modify the dictionary (incrementing version)
     let mut ctx2 = Context::load_from_file(&path).unwrap();
-    ctx2.observe(&RawData::new(42.0, 0));
+    ctx2.register_pattern(Pattern::new(vec![0x43])).unwrap();
     let new_version = ctx2.context_version();
 
     // Original version from file should differ from modified context
@@ -230,18 +230,18 @@ fn test_context_version_increments() {
     let mut ctx = Context::new();
     let initial_version = ctx.context_version();
 
-    // Observe some data - version should increment
+    // Observe some data - the dictionary is unchanged
     ctx.observe(&RawData::new(42.0, 0));
-    assert!(
-        ctx.context_version() > initial_version,
-        "Version should increment on observe"
+    assert_eq!(
+        ctx.context_version(),
+        initial_version,
+        "Version should not change on observe"
     );
 
     // Register pattern - version should increment
-    let version_after_observe = ctx.context_version();
     ctx.register_pattern(Pattern::new(vec![0x42])).unwrap();
     assert!(
-        ctx.context_version() > version_after_observe,
+        ctx.context_version() > initial_version,
         "Version should increment on pattern registration"
     );
 }
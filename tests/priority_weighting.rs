@@ -0,0 +1,118 @@
+//! Priority-weighted observation:
+//! - With a P5 weight of 0.0, disposable outliers leave predictions alone
+//! - Normal-priority observations still move them
+//! - Skipped observations still count towards evolution scheduling
+//! - Default weights behave like a plain observation
+
+use alec::context::{ContextConfig, EvolutionConfig, Pattern};
+use alec::{Classification, ClassificationReason, Context, Priority, RawData};
+
+fn classified(priority: Priority) -> Classification {
+    Classification::new(priority, ClassificationReason::NormalValue, 0.0, 1.0)
+}
+
+fn ignoring_disposable() -> Context {
+    let mut priority_weights = [1.0; 5];
+    priority_weights[Priority::P5Disposable as usize] = 0.0;
+    Context::with_config(ContextConfig {
+        priority_weights,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_disposable_outliers_leave_prediction_unchanged() {
+    let mut context = ignoring_disposable();
+    for i in 0..20 {
+        let data = RawData::new(20.0 + (i % 3) as f64 * 0.1, i);
+        context.observe_classified(&data, &classified(Priority::P3Normal));
+    }
+    let before = context.predict(0).unwrap();
+
+    for i in 0..1000 {
+        let data = RawData::new(1000.0 + i as f64, 20 + i);
+        context.observe_classified(&data, &classified(Priority::P5Disposable));
+    }
+    let after = context.predict(0).unwrap();
+    assert_eq!(after.value.to_bits(), before.value.to_bits());
+    assert_eq!(after.confidence, before.confidence);
+    assert_eq!(context.last_value(0), Some(20.0 + (19 % 3) as f64 * 0.1));
+
+    context.observe_classified(&RawData::new(30.0, 2000), &classified(Priority::P3Normal));
+    assert!(context.predict(0).unwrap().value > before.value);
+}
+
+#[test]
+fn test_skipped_observations_schedule_evolution() {
+    let mut priority_weights = [1.0; 5];
+    priority_weights[Priority::P5Disposable as usize] = 0.0;
+    let mut context = Context::with_config(ContextConfig {
+        priority_weights,
+        evolution: EvolutionConfig {
+            evolution_interval: 10,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    context.register_pattern(Pattern::numeric(1.0)).unwrap();
+    let dictionary_version = context.dictionary_version();
+
+    for i in 0..10 {
+        context.observe_classified(&RawData::new(1.0, i), &classified(Priority::P5Disposable));
+    }
+    assert_eq!(context.observation_count(), 10);
+    assert!(context.predict(0).is_none());
+    // The single-use pattern is pruned by the scheduled evolution
+    assert_ne!(context.dictionary_version(), dictionary_version);
+}
+
+#[test]
+fn test_partial_weight_slows_the_ema() {
+    let mut priority_weights = [1.0; 5];
+    priority_weights[Priority::P4Deferred as usize] = 0.5;
+    let mut weighted = Context::with_config(ContextConfig {
+        priority_weights,
+        ..Default::default()
+    });
+    let mut plain = Context::new();
+
+    for i in 0..5 {
+        let data = RawData::new(20.0, i);
+        weighted.observe_classified(&data, &classified(Priority::P3Normal));
+        plain.observe(&data);
+    }
+    let data = RawData::new(30.0, 5);
+    weighted.observe_classified(&data, &classified(Priority::P4Deferred));
+    plain.observe(&data);
+
+    let weighted = weighted.predict(0).unwrap().value;
+    let plain = plain.predict(0).unwrap().value;
+    assert!(weighted > 20.0 && weighted < plain);
+}
+
+#[test]
+fn test_default_weights_match_plain_observe() {
+    let mut classified_ctx = Context::new();
+    let mut plain = Context::new();
+    let priorities = [
+        Priority::P1Critical,
+        Priority::P3Normal,
+        Priority::P5Disposable,
+        Priority::P2Important,
+        Priority::P4Deferred,
+    ];
+
+    for i in 0..50u64 {
+        let data = RawData::new(20.0 + (i % 7) as f64 * 0.3, i);
+        classified_ctx.observe_classified(&data, &classified(priorities[i as usize % 5]));
+        plain.observe(&data);
+    }
+
+    let (a, b) = (
+        classified_ctx.predict(0).unwrap(),
+        plain.predict(0).unwrap(),
+    );
+    assert_eq!(a.value.to_bits(), b.value.to_bits());
+    assert_eq!(a.confidence, b.confidence);
+    assert_eq!(classified_ctx.version(), plain.version());
+}
@@ -6,7 +6,9 @@
 //! - name_id serialized as u8 instead of u16 in multi-channel frame
 
 use alec::protocol::{ChannelInput, EncodingType};
-use alec::{Classifier, Context, Encoder, MessageHeader, MessageType, Priority, RawData};
+use alec::{
+    Classifier, Context, Encoder, HeaderLayout, MessageHeader, MessageType, Priority, RawData,
+};
 
 #[test]
 fn test_timestamp_seconds_not_ms() {
@@ -74,7 +76,7 @@ fn test_sequence_u16_rollover() {
 #[test]
 fn test_sequence_2_bytes_in_header() {
     let header = MessageHeader {
-        version: 1,
+        layout: HeaderLayout::default(),
         message_type: MessageType::Data,
         priority: Priority::P3Normal,
         sequence: 0x1234,
@@ -94,7 +96,7 @@ fn test_sequence_2_bytes_in_header() {
 #[test]
 fn test_context_version_u24_range() {
     let header = MessageHeader {
-        version: 1,
+        layout: HeaderLayout::default(),
         message_type: MessageType::Data,
         priority: Priority::P3Normal,
         sequence: 0,
@@ -110,7 +112,7 @@ fn test_context_version_u24_range() {
 #[test]
 fn test_context_version_3_bytes_in_header() {
     let header = MessageHeader {
-        version: 1,
+        layout: HeaderLayout::default(),
         message_type: MessageType::Data,
         priority: Priority::P3Normal,
         sequence: 0,
@@ -127,7 +129,7 @@ fn test_context_version_3_bytes_in_header() {
 #[test]
 fn test_header_roundtrip_all_fields() {
     let header = MessageHeader {
-        version: 1,
+        layout: HeaderLayout::default(),
         message_type: MessageType::Sync,
         priority: Priority::P2Important,
         sequence: 60_000,
@@ -138,7 +140,7 @@ fn test_header_roundtrip_all_fields() {
     let bytes = header.to_bytes();
     let restored = MessageHeader::from_bytes(&bytes).unwrap();
 
-    assert_eq!(restored.version, 1);
+    assert_eq!(restored.version(), 1);
     assert_eq!(restored.message_type, MessageType::Sync);
     assert_eq!(restored.priority, Priority::P2Important);
     assert_eq!(restored.sequence, 60_000);
@@ -198,7 +200,9 @@ fn test_name_id_1_byte_in_frame() {
         })
         .collect();
 
-    let (message, _) = encoder.encode_multi_adaptive(&channels, 1000, &context, &classifier);
+    let (message, _) = encoder
+        .encode_multi_adaptive(&channels, 1000, &context, &classifier)
+        .unwrap();
 
     let payload = &message.payload;
 
@@ -246,7 +250,9 @@ fn test_name_id_max_u8() {
         value: 42.0,
     }];
 
-    let (message, _) = encoder.encode_multi_adaptive(&channels, 1000, &context, &classifier);
+    let (message, _) = encoder
+        .encode_multi_adaptive(&channels, 1000, &context, &classifier)
+        .unwrap();
     let decoded = decoder.decode_multi(&message, &context).unwrap();
 
     assert_eq!(decoded.len(), 1);
@@ -283,7 +289,9 @@ fn test_name_id_roundtrip() {
         })
         .collect();
 
-    let (message, _) = encoder.encode_multi_adaptive(&channels, 2000, &context, &classifier);
+    let (message, _) = encoder
+        .encode_multi_adaptive(&channels, 2000, &context, &classifier)
+        .unwrap();
     let decoded = decoder.decode_multi(&message, &context).unwrap();
 
     assert_eq!(decoded.len(), 5);
@@ -0,0 +1,699 @@
+//! Reference ("slow") decoder for differential testing.
+//!
+//! Written from the wire format description below, independently of
+//! `src/protocol.rs` and `src/decoder.rs`: no shared parsing helpers,
+//! plain byte cursors, owned copies everywhere. Only the `Context`
+//! lookups (prediction, last value, patterns, scale factor) are shared
+//! with production, since they are decoder inputs rather than format.
+//!
+//! # Wire format
+//!
+//! Message (`Decoder::decode_bytes`):
+//!
+//! ```text
+//! byte 0      : version (bits 7-6) | type (bits 5-3) | priority (bits 2-0)
+//! [2 bytes]   : version 0 only: wire version (major << 4 | minor), flags
+//! [2/3 bytes] : version 0 only, flag bits 2-1 = 2 (ms) / 3 (us): the
+//!               sub-second part of the timestamp in that unit, BE
+//! 2 bytes     : sequence, u16 BE
+//! timestamp   : version 0/1 -> u32 BE seconds
+//!               version 2   -> LEB128 varint of zigzag(delta seconds)
+//!               version 3   -> LEB128 varint of (low_bits << 5) | bits
+//! 3 bytes     : context version, u24 BE
+//! payload     : source id (varint) | encoding (1 byte) | value
+//! [4 bytes]   : xxh32(seed 0) of all preceding bytes, BE, when the
+//!               checksum policy covers the header priority; CRC-32C
+//!               instead when flag bit 4 is set
+//! ```
+//!
+//! Varints are at most 5 bytes and must fit in u32. Type values 0-7 are
+//! all valid; priorities above 4 are invalid. Extended (version 0) messages
+//! with a wire major above 1, with any of flag bits 7-6 set, or with a
+//! sub-second part of a second or more, are rejected before the payload
+//! is read. With flag bits 2-1 set (1: s, 2: ms, 3: us), decoded
+//! timestamps are in that unit. A heartbeat (type 6) with a
+//! payload is a confirmation and carries no value; otherwise the value
+//! decoder does not interpret the message type, and trailing payload
+//! bytes are ignored.
+//!
+//! Values: Raw64 (f64 BE), Raw32 (f32 BE), Delta8/16/32 (signed BE,
+//! `prediction + delta / scale`), Pattern (varint id, pattern value),
+//! PatternDelta (varint id + i8 delta), Repeated (last value),
+//! Interpolated (prediction). With flag bit 5 (self-describing) a
+//! single-value message must be Raw64, and the value is followed by the
+//! sender's scale factor (varint), observation count (varint), EMA and
+//! EMA alpha (f64 BE). When the context cannot predict the source, or the
+//! source is already seeded, that state seeds the source: its later
+//! single-value messages decode against the seed (last value, and the EMA
+//! from the third observation on), which every decoded value of the
+//! source advances. Multi payloads hold a count byte followed
+//! by `name_id (1) | encoding (1) | value` entries, where `name_id` is the
+//! context source and Pattern encodings are not allowed. Encoding 0x50
+//! announces a transmission plan and carries no value; encoding 0x60
+//! holds a composite of channel values, which a single-value decode
+//! rejects.
+//!
+//! Plan-relative frames (`Decoder::decode_bytes`, first byte 0xA5):
+//!
+//! ```text
+//! byte 0      : 0xA5
+//! byte 1      : plan id
+//! byte 2      : index within the plan
+//! rest        : time offset (varint) | delta at the plan's width
+//! ```
+//!
+//! The reference decoder never records plans, so every complete frame
+//! references a missing plan.
+//!
+//! Fixed-channel frames (`Decoder::decode_multi_fixed`):
+//!
+//! ```text
+//! byte 0      : 0xA1 data / 0xA2 keyframe
+//! bytes 1-4   : sequence u16 BE, context version u16 BE
+//! bitmap      : 2 bits per channel, LSB first (00 Repeated, 01 Delta8,
+//!               10 Delta16, 11 Raw32), ceil(2 * channels / 8) bytes
+//! data        : per-channel values in channel order; channel i uses
+//!               context source i + 1
+//! ```
+
+#![allow(dead_code)]
+
+use alec::Context;
+use std::collections::HashMap;
+
+/// Error classes shared by the reference and production decoders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefError {
+    BufferTooShort,
+    InvalidHeader,
+    InvalidChecksum,
+    InvalidCrc32c,
+    UnknownEncodingType,
+    Malformed,
+    UnknownPattern,
+    MissingTimestampAnchor,
+    TimestampRegression,
+    UnsupportedVersion,
+    UnsupportedFlags,
+    PlanMissing,
+}
+
+/// Parsed message header, with the timestamp field as found on the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefHeader {
+    pub version: u8,
+    /// (wire version byte, flags) of an extended header
+    pub extension: Option<(u8, u8)>,
+    /// Sub-second part of the timestamp, 0 when not sent
+    pub subsec: u32,
+    pub message_type: u8,
+    pub priority: u8,
+    pub sequence: u16,
+    pub timestamp_field: u32,
+    pub context_version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefMessage {
+    pub header: RefHeader,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefData {
+    pub source_id: u32,
+    pub timestamp: u64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefFixedFrame {
+    pub keyframe: bool,
+    pub sequence: u16,
+    pub context_version: u16,
+    pub gap_size: u8,
+    pub context_mismatch: bool,
+    pub values: Vec<f64>,
+}
+
+struct Cursor {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.to_vec(),
+            pos: 0,
+        }
+    }
+
+    fn remaining(&self) -> Vec<u8> {
+        self.bytes[self.pos.min(self.bytes.len())..].to_vec()
+    }
+
+    fn take(&mut self, n: usize) -> Option<Vec<u8>> {
+        if self.pos + n > self.bytes.len() {
+            return None;
+        }
+        let out = self.bytes[self.pos..self.pos + n].to_vec();
+        self.pos += n;
+        Some(out)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    /// LEB128 varint: Err(true) when truncated, Err(false) when too long
+    /// or overflowing u32
+    fn varint(&mut self) -> Result<u32, bool> {
+        let mut value: u64 = 0;
+        for i in 0..5 {
+            let byte = self.byte().ok_or(true)?;
+            value += ((byte & 0x7F) as u64) * (1u64 << (7 * i));
+            if byte & 0x80 == 0 {
+                return if value > u32::MAX as u64 {
+                    Err(false)
+                } else {
+                    Ok(value as u32)
+                };
+            }
+        }
+        Err(false)
+    }
+}
+
+/// CRC-32C (Castagnoli, reflected polynomial 0x82F63B78), bit by bit
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Checksum of a message body, with the algorithm selected by its flags
+pub fn checksum(body: &[u8]) -> u32 {
+    let crc =
+        body.first().is_some_and(|b| b >> 6 == 0) && body.get(2).is_some_and(|f| f & 0x10 != 0);
+    if crc {
+        crc32c(body)
+    } else {
+        xxhash_rust::xxh32::xxh32(body, 0)
+    }
+}
+
+/// Parse a serialized message, optionally verifying a trailing checksum
+pub fn parse_message(bytes: &[u8], with_checksum: bool) -> Result<RefMessage, RefError> {
+    let mut body = bytes.to_vec();
+    let mut expected_checksum = None;
+    if with_checksum {
+        // Smallest header (7 bytes) plus the checksum
+        if body.len() < 7 + 4 {
+            return Err(RefError::BufferTooShort);
+        }
+        let tail = body.split_off(body.len() - 4);
+        expected_checksum = Some(u32::from_be_bytes([tail[0], tail[1], tail[2], tail[3]]));
+    }
+
+    let mut cursor = Cursor::new(&body);
+    if body.len() < 7 {
+        return Err(RefError::InvalidHeader);
+    }
+    let first = cursor.byte().unwrap();
+    let version = first >> 6;
+    let message_type = (first >> 3) & 0x07;
+    let priority = first & 0x07;
+    if priority > 4 {
+        return Err(RefError::InvalidHeader);
+    }
+    let extension = if version == 0 {
+        let ext = cursor.take(2).ok_or(RefError::InvalidHeader)?;
+        Some((ext[0], ext[1]))
+    } else {
+        None
+    };
+    let subsec_len = match extension.map(|(_, flags)| (flags >> 1) & 0x03) {
+        Some(2) => 2,
+        Some(3) => 3,
+        _ => 0,
+    };
+    let subsec = cursor
+        .take(subsec_len)
+        .ok_or(RefError::InvalidHeader)?
+        .iter()
+        .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+    let seq = cursor.take(2).ok_or(RefError::InvalidHeader)?;
+    let sequence = u16::from_be_bytes([seq[0], seq[1]]);
+    let timestamp_field = if version >= 2 {
+        cursor.varint().map_err(|_| RefError::InvalidHeader)?
+    } else {
+        let ts = cursor.take(4).ok_or(RefError::InvalidHeader)?;
+        u32::from_be_bytes([ts[0], ts[1], ts[2], ts[3]])
+    };
+    let cv = cursor.take(3).ok_or(RefError::InvalidHeader)?;
+    let context_version = ((cv[0] as u32) << 16) | ((cv[1] as u32) << 8) | cv[2] as u32;
+
+    if let Some(expected) = expected_checksum {
+        let crc = extension.is_some_and(|(_, flags)| flags & 0x10 != 0);
+        if crc && crc32c(&body) != expected {
+            return Err(RefError::InvalidCrc32c);
+        }
+        if !crc && xxhash_rust::xxh32::xxh32(&body, 0) != expected {
+            return Err(RefError::InvalidChecksum);
+        }
+    }
+
+    Ok(RefMessage {
+        header: RefHeader {
+            version,
+            extension,
+            subsec,
+            message_type,
+            priority,
+            sequence,
+            timestamp_field,
+            context_version,
+        },
+        payload: cursor.remaining(),
+    })
+}
+
+/// Reject extended headers from a newer major or with critical flags
+fn check_extension(header: &RefHeader) -> Result<(), RefError> {
+    if let Some((wire, flags)) = header.extension {
+        if wire >> 4 > 1 {
+            return Err(RefError::UnsupportedVersion);
+        }
+        if flags & 0xC0 != 0 {
+            return Err(RefError::UnsupportedFlags);
+        }
+        if header.subsec >= units_per_second(header).unwrap_or(1) {
+            return Err(RefError::InvalidHeader);
+        }
+    }
+    Ok(())
+}
+
+/// Timestamp units in one second, when the header records a unit
+fn units_per_second(header: &RefHeader) -> Option<u32> {
+    match header.extension.map(|(_, flags)| (flags >> 1) & 0x03) {
+        Some(1) => Some(1),
+        Some(2) => Some(1_000),
+        Some(3) => Some(1_000_000),
+        _ => None,
+    }
+}
+
+fn scaled(context: &Context, base: f64, delta: i64) -> f64 {
+    base + delta as f64 / context.scale_factor() as f64
+}
+
+fn predicted(context: &Context, source_id: u32) -> Result<f64, RefError> {
+    context
+        .predict(source_id)
+        .map(|p| p.value)
+        .ok_or(RefError::Malformed)
+}
+
+fn varint_error(truncated: bool) -> RefError {
+    if truncated {
+        RefError::BufferTooShort
+    } else {
+        RefError::Malformed
+    }
+}
+
+/// Decode one value; `allow_patterns` is false inside multi payloads
+fn decode_value(
+    cursor: &mut Cursor,
+    encoding: u8,
+    source_id: u32,
+    context: &Context,
+    allow_patterns: bool,
+) -> Result<f64, RefError> {
+    match encoding {
+        0x00 => {
+            let b = cursor.take(8).ok_or(RefError::BufferTooShort)?;
+            let mut raw = [0u8; 8];
+            raw.copy_from_slice(&b);
+            Ok(f64::from_be_bytes(raw))
+        }
+        0x01 => {
+            let b = cursor.take(4).ok_or(RefError::BufferTooShort)?;
+            Ok(f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64)
+        }
+        0x10 => {
+            let b = cursor.take(1).ok_or(RefError::BufferTooShort)?;
+            let base = predicted(context, source_id)?;
+            Ok(scaled(context, base, b[0] as i8 as i64))
+        }
+        0x11 => {
+            let b = cursor.take(2).ok_or(RefError::BufferTooShort)?;
+            let base = predicted(context, source_id)?;
+            Ok(scaled(
+                context,
+                base,
+                i16::from_be_bytes([b[0], b[1]]) as i64,
+            ))
+        }
+        0x12 => {
+            let b = cursor.take(4).ok_or(RefError::BufferTooShort)?;
+            let base = predicted(context, source_id)?;
+            let delta = i32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+            Ok(scaled(context, base, delta as i64))
+        }
+        0x20 | 0x21 if allow_patterns => {
+            let id = cursor.varint().map_err(varint_error)?;
+            let delta = if encoding == 0x21 {
+                cursor.byte().ok_or(RefError::BufferTooShort)? as i8 as i64
+            } else {
+                0
+            };
+            let pattern = context.get_pattern(id).ok_or(RefError::UnknownPattern)?;
+            let base = pattern.value.ok_or(RefError::Malformed)?;
+            Ok(if encoding == 0x21 {
+                scaled(context, base, delta)
+            } else {
+                base
+            })
+        }
+        0x30 => context.last_value(source_id).ok_or(RefError::Malformed),
+        0x31 => predicted(context, source_id),
+        // Multi (or patterns inside multi) in a value position
+        _ => Err(RefError::Malformed),
+    }
+}
+
+fn is_known_encoding(byte: u8) -> bool {
+    matches!(
+        byte,
+        0x00 | 0x01 | 0x10 | 0x11 | 0x12 | 0x20 | 0x21 | 0x30 | 0x31 | 0x40 | 0x50 | 0x60
+    )
+}
+
+/// Sender state carried by a self-describing message
+#[derive(Debug, Clone, Copy)]
+struct Seed {
+    last: f64,
+    ema: Option<f64>,
+    alpha: f64,
+    count: u64,
+    scale: u32,
+}
+
+impl Seed {
+    fn predicted(&self) -> f64 {
+        match self.ema {
+            Some(ema) if self.count >= 3 => ema,
+            _ => self.last,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.last = value;
+        self.ema = Some(match self.ema {
+            Some(ema) => self.alpha * value + (1.0 - self.alpha) * ema,
+            None => value,
+        });
+        self.count += 1;
+    }
+}
+
+/// Decode a delta, repeated or interpolated value against a seed; other
+/// encodings go through `decode_value`
+fn decode_seeded(
+    cursor: &mut Cursor,
+    encoding: u8,
+    seed: &Seed,
+    source_id: u32,
+    context: &Context,
+) -> Result<f64, RefError> {
+    let delta = match encoding {
+        0x10 => cursor.take(1).ok_or(RefError::BufferTooShort)?[0] as i8 as i64,
+        0x11 => {
+            let b = cursor.take(2).ok_or(RefError::BufferTooShort)?;
+            i16::from_be_bytes([b[0], b[1]]) as i64
+        }
+        0x12 => {
+            let b = cursor.take(4).ok_or(RefError::BufferTooShort)?;
+            i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as i64
+        }
+        0x30 => return Ok(seed.last),
+        0x31 => return Ok(seed.predicted()),
+        _ => return decode_value(cursor, encoding, source_id, context, true),
+    };
+    Ok(seed.predicted() + delta as f64 / seed.scale.max(1) as f64)
+}
+
+/// Stateful reference decoder (per-source timestamps, seeds and
+/// fixed-frame tracking)
+#[derive(Debug, Default)]
+pub struct ReferenceDecoder {
+    last_timestamps: HashMap<u32, u32>,
+    /// Sequence of the last message with a timestamp; any other next
+    /// sequence than this plus one drops every source's timestamp
+    last_sequence: Option<u16>,
+    seeds: HashMap<u32, Seed>,
+    last_fixed: Option<(u16, u16)>,
+}
+
+impl ReferenceDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn timestamp(&mut self, source_id: u32, header: &RefHeader) -> Result<u32, RefError> {
+        if self
+            .last_sequence
+            .is_some_and(|last| header.sequence != last.wrapping_add(1))
+        {
+            self.last_timestamps.clear();
+        }
+        self.last_sequence = Some(header.sequence);
+        if header.version < 2 {
+            return Ok(header.timestamp_field);
+        }
+        let last = *self
+            .last_timestamps
+            .get(&source_id)
+            .ok_or(RefError::MissingTimestampAnchor)?;
+        let forward: u64 = if header.version == 2 {
+            let field = header.timestamp_field as i64;
+            let delta = if field % 2 == 0 {
+                field / 2
+            } else {
+                -(field + 1) / 2
+            };
+            if delta < 0 {
+                return Err(RefError::TimestampRegression);
+            }
+            delta as u64
+        } else {
+            let bits = header.timestamp_field % 32;
+            let low = (header.timestamp_field / 32) as u64;
+            if bits == 0 || bits > 24 {
+                return Err(RefError::InvalidHeader);
+            }
+            let range = 1u64 << bits;
+            let forward = (low + range - (last as u64 % range)) % range;
+            if forward >= range / 2 {
+                return Err(RefError::TimestampRegression);
+            }
+            forward
+        };
+        let absolute = last as u64 + forward;
+        if absolute > u32::MAX as u64 {
+            return Err(RefError::InvalidHeader);
+        }
+        Ok(absolute as u32)
+    }
+
+    /// Decode a single-value message
+    pub fn decode(&mut self, message: &RefMessage, context: &Context) -> Result<RefData, RefError> {
+        check_extension(&message.header)?;
+        let mut cursor = Cursor::new(&message.payload);
+        if message.payload.is_empty() {
+            return Err(RefError::BufferTooShort);
+        }
+        if message.header.message_type == 6 {
+            return Err(RefError::Malformed);
+        }
+        let source_id = cursor.varint().map_err(varint_error)?;
+        let encoding = cursor.byte().ok_or(RefError::BufferTooShort)?;
+        if !is_known_encoding(encoding) {
+            return Err(RefError::UnknownEncodingType);
+        }
+        if encoding == 0x50 || encoding == 0x60 {
+            return Err(RefError::Malformed);
+        }
+        let self_describing = message
+            .header
+            .extension
+            .is_some_and(|(_, flags)| flags & 0x20 != 0);
+        if self_describing && encoding != 0x00 {
+            return Err(RefError::Malformed);
+        }
+        let timestamp = self.timestamp(source_id, &message.header)?;
+        let value = if self_describing {
+            let value = decode_value(&mut cursor, encoding, source_id, context, true)?;
+            let scale = cursor.varint().map_err(varint_error)?;
+            let count = cursor.varint().map_err(varint_error)?;
+            let b = cursor.take(16).ok_or(RefError::BufferTooShort)?;
+            let ema = f64::from_be_bytes(b[..8].try_into().unwrap());
+            let alpha = f64::from_be_bytes(b[8..].try_into().unwrap());
+            if context.predict(source_id).is_none() || self.seeds.contains_key(&source_id) {
+                let seed = Seed {
+                    last: value,
+                    ema: (count > 0).then_some(ema),
+                    alpha,
+                    count: count as u64,
+                    scale,
+                };
+                self.seeds.insert(source_id, seed);
+            }
+            value
+        } else if let Some(seed) = self.seeds.get(&source_id) {
+            decode_seeded(&mut cursor, encoding, seed, source_id, context)?
+        } else {
+            decode_value(&mut cursor, encoding, source_id, context, true)?
+        };
+        if let Some(seed) = self.seeds.get_mut(&source_id) {
+            seed.push(value);
+        }
+        self.last_timestamps.insert(source_id, timestamp);
+        let timestamp = match units_per_second(&message.header) {
+            Some(units) => timestamp as u64 * units as u64 + message.header.subsec as u64,
+            None => timestamp as u64,
+        };
+        Ok(RefData {
+            source_id,
+            timestamp,
+            value,
+        })
+    }
+
+    /// Decode a plan-relative frame (no plan is ever active)
+    pub fn decode_plan_relative(&self, input: &[u8]) -> Result<RefData, RefError> {
+        if input.len() < 3 {
+            return Err(RefError::BufferTooShort);
+        }
+        Err(RefError::PlanMissing)
+    }
+
+    /// Decode a multi-value message into (name_id, value) pairs
+    pub fn decode_multi(
+        &self,
+        message: &RefMessage,
+        context: &Context,
+    ) -> Result<Vec<(u8, f64)>, RefError> {
+        check_extension(&message.header)?;
+        let mut cursor = Cursor::new(&message.payload);
+        cursor.varint().map_err(varint_error)?;
+        let encoding = cursor.byte().ok_or(RefError::BufferTooShort)?;
+        if encoding != 0x40 {
+            return Err(RefError::Malformed);
+        }
+        let count = cursor.byte().ok_or(RefError::BufferTooShort)?;
+        let mut values = Vec::new();
+        for _ in 0..count {
+            let name_id = cursor.byte().ok_or(RefError::BufferTooShort)?;
+            let encoding = cursor.byte().ok_or(RefError::BufferTooShort)?;
+            if !is_known_encoding(encoding) {
+                return Err(RefError::UnknownEncodingType);
+            }
+            let value = decode_value(&mut cursor, encoding, name_id as u32, context, false)?;
+            values.push((name_id, value));
+        }
+        Ok(values)
+    }
+
+    /// Decode a fixed-channel frame
+    pub fn decode_fixed(
+        &mut self,
+        input: &[u8],
+        channel_count: usize,
+        context: &Context,
+    ) -> Result<RefFixedFrame, RefError> {
+        if channel_count == 0 || channel_count > 64 {
+            return Err(RefError::Malformed);
+        }
+        let bitmap_len = (channel_count * 2 + 7) / 8;
+        if input.len() < 5 + bitmap_len {
+            return Err(RefError::BufferTooShort);
+        }
+        let mut cursor = Cursor::new(input);
+        let keyframe = match cursor.byte().unwrap() {
+            0xA1 => false,
+            0xA2 => true,
+            _ => return Err(RefError::Malformed),
+        };
+        let h = cursor.take(4).unwrap();
+        let sequence = u16::from_be_bytes([h[0], h[1]]);
+        let context_version = u16::from_be_bytes([h[2], h[3]]);
+        let bitmap = cursor.take(bitmap_len).unwrap();
+
+        let codes: Vec<u8> = (0..channel_count)
+            .map(|i| (bitmap[i / 4] >> ((i % 4) * 2)) & 0b11)
+            .collect();
+        let needed: usize = codes.iter().map(|c| [0, 1, 2, 4][*c as usize]).sum();
+        if cursor.remaining().len() < needed {
+            return Err(RefError::BufferTooShort);
+        }
+
+        let mut values = Vec::new();
+        for (i, code) in codes.iter().enumerate() {
+            let source_id = i as u32 + 1;
+            let value = match code {
+                0 => context.last_value(source_id).ok_or(RefError::Malformed)?,
+                1 => {
+                    let d = cursor.byte().unwrap() as i8 as i64;
+                    scaled(context, predicted(context, source_id)?, d)
+                }
+                2 => {
+                    let b = cursor.take(2).unwrap();
+                    let d = i16::from_be_bytes([b[0], b[1]]) as i64;
+                    scaled(context, predicted(context, source_id)?, d)
+                }
+                _ => {
+                    let b = cursor.take(4).unwrap();
+                    f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64
+                }
+            };
+            values.push(value);
+        }
+
+        let (gap_size, context_mismatch) = match self.last_fixed {
+            None => (0, false),
+            Some((last_seq, last_cv)) => {
+                let step = sequence.wrapping_sub(last_seq) as u32;
+                let gap = if step == 0 {
+                    0
+                } else {
+                    (step - 1).min(255) as u8
+                };
+                let forward = context_version.wrapping_sub(last_cv);
+                (gap, !keyframe && forward > 256)
+            }
+        };
+        self.last_fixed = Some((sequence, context_version));
+
+        Ok(RefFixedFrame {
+            keyframe,
+            sequence,
+            context_version,
+            gap_size,
+            context_mismatch,
+            values,
+        })
+    }
+}
@@ -0,0 +1,262 @@
+//! Self-describing messages for cold-start receivers
+//! (`EncoderConfig::self_describing_interval`):
+//! - A receiver that missed the first 50 messages decodes the same values
+//!   as one that saw them all, from the first self-describing message on,
+//!   within one interval
+//! - Without them it fails on every delta until its context is synced
+//! - `Encoder::force_self_describing` sends one on demand; a receiver
+//!   whose context already predicts the source is not seeded by it
+//! - Buffers of concatenated self-describing messages delimit and decode
+//!   through `Decoder::decode_buffer`, checksums or not
+//! - Buffers of concatenated messages, self-describing ones included,
+//!   delimit and decode through `Decoder::decode_buffer_into`, checksums
+//!   or not
+
+use alec::protocol::FLAG_SELF_DESCRIBING;
+use alec::{
+    Classifier, Context, Decoded, Decoder, EncodedMessage, Encoder, EncoderConfig, EncodingType,
+    RawData, WireVersion,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+const MISSED: usize = 50;
+const INTERVAL: u16 = 16;
+
+/// Slowly varying signal, so values travel as deltas
+fn value(i: usize) -> f64 {
+    20.0 + (i as f64 * 0.1).sin() * 3.0 + (i % 7) as f64 * 0.01
+}
+
+/// Messages of source 1 with the sender's context observing each value
+fn stream(encoder: &mut Encoder, count: usize) -> (Vec<EncodedMessage>, Context) {
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    let mut messages = Vec::new();
+    for i in 0..count {
+        let data = RawData::with_source(1, value(i), START_MS + i as u64 * 1000);
+        let classification = classifier.classify(&data, &context);
+        messages.push(encoder.encode(&data, &classification, &context));
+        context.observe(&data);
+    }
+    (messages, context)
+}
+
+/// Receiver decoding messages and observing the values it gets
+struct Receiver {
+    decoder: Decoder,
+    context: Context,
+}
+
+impl Receiver {
+    fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+            context: Context::new(),
+        }
+    }
+
+    fn receive(&mut self, message: &EncodedMessage) -> Option<f64> {
+        let decoded = self.decoder.decode(message, &self.context).ok()?;
+        self.context.observe(&RawData::with_source(
+            decoded.source_id,
+            decoded.value,
+            decoded.timestamp * 1000,
+        ));
+        Some(decoded.value)
+    }
+}
+
+#[test]
+fn test_cold_start_recovers_within_interval() {
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        self_describing_interval: INTERVAL,
+        ..Default::default()
+    });
+    let (messages, _) = stream(&mut encoder, 200);
+    let self_describing: Vec<usize> = (0..messages.len())
+        .filter(|&i| messages[i].header.is_self_describing())
+        .collect();
+    assert_eq!(
+        self_describing,
+        (0..200).step_by(INTERVAL as usize).collect::<Vec<_>>()
+    );
+    let first = &messages[0];
+    assert_eq!(first.encoding_type(), Some(EncodingType::Raw64));
+    let extension = first.header.extension().unwrap();
+    assert_eq!(extension.flags & FLAG_SELF_DESCRIBING, FLAG_SELF_DESCRIBING);
+    assert_eq!(extension.version, WireVersion::CURRENT);
+    assert!(messages[MISSED..]
+        .iter()
+        .filter(|m| !m.header.is_self_describing())
+        .all(|m| m.encoding_type() != Some(EncodingType::Raw64)));
+
+    let mut healthy = Receiver::new();
+    let expected: Vec<f64> = messages
+        .iter()
+        .map(|m| healthy.receive(m).expect("healthy receiver decodes"))
+        .collect();
+
+    let mut cold = Receiver::new();
+    let mut recovered = None;
+    for (i, message) in messages.iter().enumerate().skip(MISSED) {
+        match cold.receive(message) {
+            Some(value) => {
+                recovered.get_or_insert(i);
+                assert_eq!(value, expected[i], "message {i}");
+            }
+            None => assert!(recovered.is_none(), "message {i} failed after recovery"),
+        }
+    }
+    let recovered = recovered.expect("cold receiver recovers");
+    assert!(messages[recovered].header.is_self_describing());
+    assert!(recovered < MISSED + INTERVAL as usize);
+    // The first message seeded the healthy receiver too
+    assert!(cold.decoder.is_seeded(1));
+    assert!(healthy.decoder.is_seeded(1));
+
+    // Values stay close to the originals
+    for (i, &decoded) in expected.iter().enumerate() {
+        assert!((decoded - value(i)).abs() < 0.05, "message {i}");
+    }
+}
+
+#[test]
+fn test_cold_start_without_self_describing_fails_until_sync() {
+    const SYNC_AT: usize = MISSED + 20;
+    let mut encoder = Encoder::new();
+    let (messages, _) = stream(&mut encoder, SYNC_AT + 20);
+    assert!(messages.iter().all(|m| !m.header.is_self_describing()));
+
+    let mut cold = Receiver::new();
+    for message in &messages[MISSED..SYNC_AT] {
+        assert!(cold.receive(message).is_none());
+    }
+
+    // A full context sync: the sender's context before message SYNC_AT
+    let (_, synced) = stream(&mut Encoder::new(), SYNC_AT);
+    cold.context = synced;
+    for (i, message) in messages.iter().enumerate().skip(SYNC_AT) {
+        let decoded = cold.receive(message).expect("synced receiver decodes");
+        assert!((decoded - value(i)).abs() < 0.05, "message {i}");
+    }
+}
+
+#[test]
+fn test_force_self_describing() {
+    let mut encoder = Encoder::new();
+    let (messages, mut context) = stream(&mut encoder, 10);
+    assert!(messages.iter().all(|m| !m.header.is_self_describing()));
+
+    encoder.force_self_describing(1);
+    let data = RawData::with_source(1, value(10), START_MS + 10_000);
+    let message = encoder.encode(
+        &data,
+        &Classifier::default().classify(&data, &context),
+        &context,
+    );
+    assert!(message.header.is_self_describing());
+    assert_eq!(message.encoding_type(), Some(EncodingType::Raw64));
+
+    // A receiver that already predicts the source decodes the value but
+    // keeps following its own context
+    let mut decoder = Decoder::new();
+    let decoded = decoder.decode(&message, &context).unwrap();
+    assert_eq!(decoded.value, value(10));
+    assert!(!decoder.is_seeded(1));
+    context.observe(&data);
+
+    // Only the next value of the source
+    let data = RawData::with_source(1, value(11), START_MS + 11_000);
+    let message = encoder.encode(
+        &data,
+        &Classifier::default().classify(&data, &context),
+        &context,
+    );
+    assert!(!message.header.is_self_describing());
+
+    // A cold receiver is seeded, until cleared
+    let mut cold = Decoder::new();
+    encoder.force_self_describing(1);
+    let data = RawData::with_source(1, value(12), START_MS + 12_000);
+    let message = encoder.encode(
+        &data,
+        &Classifier::default().classify(&data, &context),
+        &context,
+    );
+    cold.decode(&message, &Context::new()).unwrap();
+    assert!(cold.is_seeded(1));
+    cold.clear_seeds();
+    assert!(!cold.is_seeded(1));
+}
+
+#[test]
+fn test_buffer_of_self_describing_messages() {
+    for checksum in [false, true] {
+        // Every message carries the sender's state
+        let mut encoder = Encoder::with_config(EncoderConfig {
+            self_describing_interval: 1,
+            ..Default::default()
+        });
+        let (messages, _) = stream(&mut encoder, 20);
+        let mut buffer = Vec::new();
+        for message in &messages {
+            assert!(message.header.is_self_describing());
+            buffer.extend(if checksum {
+                message.to_bytes_with_checksum()
+            } else {
+                message.to_bytes()
+            });
+        }
+
+        let mut decoder = if checksum {
+            Decoder::with_checksum_verification()
+        } else {
+            Decoder::new()
+        };
+        let items = decoder.decode_buffer(&buffer, &Context::new()).unwrap();
+        assert_eq!(items.len(), messages.len());
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                Decoded::Value(data) => assert_eq!(data.value, value(i), "message {i}"),
+                other => panic!("message {i}: {:?}", other),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_buffer_with_self_describing_messages() {
+    for checksum in [false, true] {
+        let mut encoder = Encoder::with_config(EncoderConfig {
+            self_describing_interval: INTERVAL,
+            ..Default::default()
+        });
+        let (messages, _) = stream(&mut encoder, 40);
+        let buffer: Vec<u8> = messages
+            .iter()
+            .flat_map(|m| {
+                if checksum {
+                    m.to_bytes_with_checksum()
+                } else {
+                    m.to_bytes()
+                }
+            })
+            .collect();
+
+        let mut decoder = if checksum {
+            Decoder::with_checksum_verification()
+        } else {
+            Decoder::new()
+        };
+        let mut context = Context::new();
+        let decoded = decoder.decode_buffer_into(&buffer, &mut context).unwrap();
+
+        // As decoding them one by one
+        let mut receiver = Receiver::new();
+        assert_eq!(decoded.len(), messages.len());
+        for (i, (data, message)) in decoded.iter().zip(&messages).enumerate() {
+            assert_eq!(Some(data.value), receiver.receive(message), "message {i}");
+        }
+        assert_eq!(context.hash(), receiver.context.hash());
+    }
+}
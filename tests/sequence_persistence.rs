@@ -0,0 +1,134 @@
+//! Sequence persistence (`SequenceStore`, `Encoder::set_sequence_store`):
+//! - The counter is saved every `save_interval` messages, not every message
+//! - After a restart without a final save, numbering resumes past every
+//!   sequence number sent before it, and the decoder sees it move ahead
+//! - `FileSequenceStore` survives the process and rejects corrupt files
+
+use std::sync::{Arc, Mutex};
+
+use alec::{
+    AlecError, Classifier, Context, Decoder, EncodedMessage, Encoder, EncoderConfig,
+    FileSequenceStore, InMemorySequenceStore, RawData, SequencePersistence, SequenceStore,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+/// Store recording every save, shared with the test
+#[derive(Clone, Default)]
+struct SharedStore(Arc<Mutex<InMemorySequenceStore>>);
+
+impl SequenceStore for SharedStore {
+    fn load(&mut self, source_id: u32) -> Option<u32> {
+        self.0.lock().unwrap().load(source_id)
+    }
+
+    fn save(&mut self, source_id: u32, sequence: u32) -> Result<(), AlecError> {
+        self.0.lock().unwrap().save(source_id, sequence)
+    }
+}
+
+fn persistent_encoder(store: Box<dyn SequenceStore>) -> Encoder {
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        sequence_persistence: SequencePersistence {
+            source_id: 7,
+            save_interval: 10,
+            skip_ahead: 50,
+        },
+        ..Default::default()
+    });
+    encoder.set_sequence_store(store).unwrap();
+    encoder
+}
+
+fn encode(encoder: &mut Encoder, count: u64) -> Vec<EncodedMessage> {
+    let context = Context::new();
+    (0..count)
+        .map(|i| {
+            let data = RawData::new(20.0 + i as f64, START_MS + i * 1_000);
+            let classification = Classifier::default().classify(&data, &context);
+            encoder.encode(&data, &classification, &context)
+        })
+        .collect()
+}
+
+/// Whether `seq` comes after `prev` in wrapping order
+fn is_after(seq: u16, prev: u16) -> bool {
+    (1..0x8000).contains(&seq.wrapping_sub(prev))
+}
+
+#[test]
+fn test_saves_at_interval() {
+    let store = SharedStore::default();
+    let mut encoder = persistent_encoder(Box::new(store.clone()));
+    assert_eq!(store.0.lock().unwrap().saves(), 1);
+
+    encode(&mut encoder, 25);
+    let saved = store.0.lock().unwrap();
+    assert_eq!(saved.saves(), 3);
+    assert_eq!(saved.get(7), Some(20));
+}
+
+#[test]
+fn test_restart_resumes_past_sent_sequences() {
+    let store = SharedStore::default();
+    let mut encoder = persistent_encoder(Box::new(store.clone()));
+    let before = encode(&mut encoder, 25);
+
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+    for message in &before {
+        decoder.decode(message, &context).unwrap();
+    }
+
+    // Crash: sequences 20..=24 were never saved
+    drop(encoder);
+    let mut encoder = persistent_encoder(Box::new(store.clone()));
+    assert_eq!(encoder.sequence(), 70);
+
+    let after = encode(&mut encoder, 5);
+    let last_before = decoder.last_sequence().unwrap();
+    for message in &after {
+        let seq = message.header.sequence;
+        assert!(before.iter().all(|m| is_after(seq, m.header.sequence)));
+        decoder.decode(message, &context).unwrap();
+    }
+    assert!(is_after(decoder.last_sequence().unwrap(), last_before));
+}
+
+#[test]
+fn test_skip_covers_save_interval() {
+    let persistence = SequencePersistence {
+        source_id: 0,
+        save_interval: 500,
+        skip_ahead: 100,
+    };
+    assert_eq!(persistence.effective_skip(), 500);
+}
+
+#[test]
+fn test_file_store_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sequence.bin");
+
+    let mut encoder = persistent_encoder(Box::new(FileSequenceStore::open(&path).unwrap()));
+    let before = encode(&mut encoder, 12);
+    drop(encoder);
+
+    let mut store = FileSequenceStore::open(&path).unwrap();
+    assert_eq!(store.load(7), Some(10));
+    let mut encoder = persistent_encoder(Box::new(store));
+    let after = encode(&mut encoder, 1);
+    assert_eq!(after[0].header.sequence, 60);
+    assert!(before
+        .iter()
+        .all(|m| is_after(after[0].header.sequence, m.header.sequence)));
+}
+
+#[test]
+fn test_file_store_rejects_corrupt_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sequence.bin");
+    std::fs::write(&path, b"ASEQ\x01\x07\x00").unwrap();
+
+    assert!(FileSequenceStore::open(&path).is_err());
+}
@@ -0,0 +1,147 @@
+//! Sequence number wraparound (serial-number arithmetic):
+//! - 65534, 65535, 0, 1 are consecutive at 16 bits, with one wrap counted
+//! - The same holds at the top of the 32-bit space
+//! - A jump from 100 to 50000 is a gap, 100 then 90 a stale message, at
+//!   both widths
+//! - The decoder's sequence statistics and the fleet's per-emitter
+//!   tracking classify real messages the same way
+
+use alec::fleet::{FleetConfig, FleetManager};
+use alec::sequence::{SequenceStep, SequenceTracker, SequenceWidth, SequenceWindow};
+use alec::{Context, Decoder, EncodedMessage, Encoder, Priority, RawData};
+
+const WIDTHS: [SequenceWidth; 2] = [SequenceWidth::U16, SequenceWidth::U32];
+
+fn tracker(width: SequenceWidth) -> SequenceTracker {
+    SequenceTracker::new(SequenceWindow::new(width))
+}
+
+fn observe_all(tracker: &mut SequenceTracker, sequences: &[u32]) -> Vec<SequenceStep> {
+    sequences.iter().map(|&s| tracker.observe(s)).collect()
+}
+
+/// Messages numbered `sequences`, in order
+fn messages(sequences: &[u16]) -> Vec<EncodedMessage> {
+    let mut encoder = Encoder::new();
+    let context = Context::new();
+    sequences
+        .iter()
+        .enumerate()
+        .map(|(i, &sequence)| {
+            let data = RawData::new(20.0 + i as f64, 1_000 * i as u64);
+            let mut message = encoder.encode_with_priority(&data, Priority::P3Normal, &context);
+            message.header.sequence = sequence;
+            message
+        })
+        .collect()
+}
+
+#[test]
+fn test_wrap_is_consecutive() {
+    for width in WIDTHS {
+        let top = width.max();
+        let mut tracker = tracker(width);
+        let steps = observe_all(&mut tracker, &[top - 1, top, 0, 1]);
+        assert_eq!(
+            steps,
+            [
+                SequenceStep::First,
+                SequenceStep::Next,
+                SequenceStep::Next,
+                SequenceStep::Next
+            ]
+        );
+        let stats = tracker.stats();
+        assert_eq!(stats.gaps, 0);
+        assert_eq!(stats.missing, 0);
+        assert_eq!(stats.stale, 0);
+        assert_eq!(stats.wraps, 1);
+        assert_eq!(tracker.last(), Some(1));
+    }
+}
+
+#[test]
+fn test_jump_ahead_is_gap() {
+    for width in WIDTHS {
+        let mut tracker = tracker(width);
+        let steps = observe_all(&mut tracker, &[100, 50_000]);
+        assert_eq!(steps[1], SequenceStep::Gap { missing: 49_899 });
+        assert_eq!(tracker.stats().gaps, 1);
+        assert_eq!(tracker.stats().missing, 49_899);
+        assert_eq!(tracker.stats().wraps, 0);
+    }
+}
+
+#[test]
+fn test_step_back_is_stale() {
+    for width in WIDTHS {
+        let mut tracker = tracker(width);
+        let steps = observe_all(&mut tracker, &[100, 90, 100, 101]);
+        assert_eq!(
+            steps,
+            [
+                SequenceStep::First,
+                SequenceStep::Stale { behind: 10 },
+                SequenceStep::Duplicate,
+                SequenceStep::Next
+            ]
+        );
+        let stats = tracker.stats();
+        assert_eq!((stats.stale, stats.duplicates, stats.gaps), (1, 1, 0));
+        // A stale number does not move the tracker back
+        assert_eq!(tracker.last(), Some(101));
+    }
+}
+
+#[test]
+fn test_half_window() {
+    // RFC 1982: at 16 bits, 50000 is behind 100
+    let mut tracker = SequenceTracker::new(SequenceWindow::half(SequenceWidth::U16));
+    let steps = observe_all(&mut tracker, &[100, 50_000]);
+    assert_eq!(steps[1], SequenceStep::Stale { behind: 15_636 });
+}
+
+#[test]
+fn test_decoder_sequence_stats() {
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+    for message in messages(&[65534, 65535, 0, 1, 50_000, 49_990]) {
+        decoder.decode(&message, &context).unwrap();
+    }
+    let stats = decoder.sequence_stats();
+    assert_eq!(stats.observed, 6);
+    assert_eq!(stats.wraps, 1);
+    assert_eq!(stats.gaps, 1);
+    assert_eq!(stats.missing, 49_998);
+    assert_eq!(stats.stale, 1);
+    assert_eq!(decoder.last_sequence(), Some(49_990));
+}
+
+#[test]
+fn test_fleet_tracks_each_emitter() {
+    let mut fleet = FleetManager::with_config(FleetConfig::default());
+    let wrapping = messages(&[65534, 65535, 0, 1]);
+    let jumping = messages(&[100, 50_000, 49_990]);
+
+    for (i, message) in wrapping.iter().enumerate() {
+        // Interleaved with the other emitter
+        let processed = fleet.process_message(1, message, i as u64).unwrap();
+        assert!(matches!(
+            processed.sequence_step,
+            SequenceStep::First | SequenceStep::Next
+        ));
+        if let Some(message) = jumping.get(i) {
+            fleet.process_message(2, message, i as u64).unwrap();
+        }
+    }
+
+    let wrapped = fleet.get_emitter(1).unwrap().sequence.stats();
+    assert_eq!((wrapped.gaps, wrapped.wraps), (0, 1));
+    let jumped = fleet.get_emitter(2).unwrap().sequence.stats();
+    assert_eq!((jumped.gaps, jumped.stale), (1, 1));
+
+    let stats = fleet.stats().sequence;
+    assert_eq!(stats.observed, 7);
+    assert_eq!((stats.gaps, stats.stale, stats.wraps), (1, 1, 1));
+    assert!(fleet.anomalous_emitters().is_empty());
+}
@@ -0,0 +1,217 @@
+//! Decoder session recording and replay (`alec::session`):
+//! - A 500-message session with a sync diff halfway replays to the
+//!   midpoint and to the end with the context hashes of a fresh run
+//! - Each replayed step reports its input, its error and the context hash
+//! - A failing writer stops the log without failing the decodes
+//! - A truncated log fails the replay at the cut
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use alec::session::{SessionInput, SessionReplayer};
+use alec::{
+    AlecError, Classifier, Context, Decoder, DecoderConfig, Encoder, RawData, SyncMessage,
+    Synchronizer,
+};
+
+const MESSAGES: u64 = 500;
+const DIFF_AT: u64 = 250;
+
+/// Log shared between the recording decoder and the test
+#[derive(Clone, Default)]
+struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+impl SharedLog {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for SharedLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer failing after `capacity` bytes
+struct FullDisk {
+    capacity: usize,
+}
+
+impl Write for FullDisk {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.capacity {
+            return Err(io::Error::new(io::ErrorKind::Other, "disk full"));
+        }
+        self.capacity -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Session inputs as received: messages, with a serialized sync diff
+/// carrying patterns the sender learned after message `DIFF_AT`
+fn session_inputs() -> Vec<(SessionInput, Vec<u8>)> {
+    let mut encoder = Encoder::new();
+    let mut sender = Context::new();
+    let mut inputs = Vec::new();
+
+    for i in 0..MESSAGES {
+        if i == DIFF_AT {
+            let before = sender.clone();
+            sender.seed_from_values(&[18.0, 19.5, 24.25]).unwrap();
+            let diff = Synchronizer::generate_diff(&before, &sender);
+            inputs.push((SessionInput::SyncDiff, SyncMessage::Diff(diff).to_bytes()));
+        }
+        let value = 21.0 + ((i % 40) as f64 - 20.0).abs() * 0.1;
+        let data = RawData::with_source(3, value, 1_000_000 + i * 1000);
+        let classification = Classifier::default().classify(&data, &sender);
+        inputs.push((
+            SessionInput::DecodeInto,
+            encoder.encode_to_bytes(&data, &classification, &sender),
+        ));
+        sender.observe(&data);
+    }
+    inputs
+}
+
+/// Apply `inputs` to a decoder and context, as a receiver would
+fn receive(decoder: &mut Decoder, context: &mut Context, inputs: &[(SessionInput, Vec<u8>)]) {
+    for (input, bytes) in inputs {
+        match input {
+            SessionInput::SyncDiff => decoder.apply_sync_diff(bytes, context).unwrap(),
+            _ => {
+                decoder.decode_into(bytes, context).unwrap();
+            }
+        }
+    }
+}
+
+/// Context hash after the first `count` inputs of a fresh, unrecorded run
+fn reference_hash(inputs: &[(SessionInput, Vec<u8>)], count: usize) -> u64 {
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+    receive(&mut decoder, &mut context, &inputs[..count]);
+    context.hash()
+}
+
+#[test]
+fn test_replay_to_midpoint_and_end() {
+    let inputs = session_inputs();
+    assert_eq!(inputs.len() as u64, MESSAGES + 1);
+
+    let log = SharedLog::default();
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+    decoder.start_recording(log.clone(), &context).unwrap();
+    assert!(decoder.is_recording());
+    receive(&mut decoder, &mut context, &inputs);
+    decoder.stop_recording().unwrap();
+    assert!(!decoder.is_recording());
+
+    let log = log.bytes();
+    // Framing only: a few bytes per input on top of what was received
+    let received: usize = inputs.iter().map(|(_, bytes)| bytes.len()).sum();
+    assert!(log.len() < received + inputs.len() * 6 + 128);
+
+    // The diff changes the dictionary
+    let diff_index = DIFF_AT as usize;
+    assert_ne!(
+        reference_hash(&inputs, diff_index),
+        reference_hash(&inputs, diff_index + 1)
+    );
+
+    let mut replayer = SessionReplayer::new(log.as_slice()).unwrap();
+    let midpoint = MESSAGES / 2 + 10;
+    assert_eq!(
+        replayer.replay_to(midpoint).unwrap(),
+        reference_hash(&inputs, midpoint as usize + 1)
+    );
+    assert_eq!(replayer.next_index(), midpoint + 1);
+
+    let end = inputs.len() as u64 - 1;
+    assert_eq!(
+        replayer.replay_to(end).unwrap(),
+        reference_hash(&inputs, inputs.len())
+    );
+    assert_eq!(replayer.context().hash(), context.hash());
+    assert_eq!(replayer.decoder().last_sequence(), decoder.last_sequence());
+    assert!(replayer.step().unwrap().is_none());
+
+    let (_, replayed) = replayer.into_parts();
+    assert_eq!(replayed.pattern_count(), context.pattern_count());
+}
+
+#[test]
+fn test_replay_steps() {
+    let inputs = session_inputs();
+    let log = SharedLog::default();
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+    decoder.start_recording(log.clone(), &context).unwrap();
+
+    receive(&mut decoder, &mut context, &inputs[..3]);
+    // A message the decoder rejects is part of the session too
+    assert!(decoder.decode_bytes(&[0xFF], &context).is_err());
+    decoder.set_config(DecoderConfig {
+        max_values_per_message: 8,
+        ..Default::default()
+    });
+    decoder.stop_recording().unwrap();
+
+    let log = log.bytes();
+    let mut hashes = Vec::new();
+    let mut replayer = SessionReplayer::new(log.as_slice()).unwrap();
+    while let Some(step) = replayer.step().unwrap() {
+        assert_eq!(step.index, hashes.len() as u64);
+        hashes.push((step.input, step.error.is_some(), step.context_hash));
+    }
+    assert_eq!(hashes.len(), 5);
+    assert_eq!(hashes[0].0, SessionInput::DecodeInto);
+    assert_eq!(hashes[2].2, context.hash());
+    assert_eq!(hashes[3].0, SessionInput::Decode);
+    assert!(hashes[3].1);
+    assert_eq!(hashes[4].0, SessionInput::Config);
+    assert_eq!(replayer.decoder().config().max_values_per_message, 8);
+}
+
+#[test]
+fn test_write_failure_does_not_fail_decodes() {
+    let inputs = session_inputs();
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+    decoder
+        .start_recording(FullDisk { capacity: 512 }, &context)
+        .unwrap();
+
+    receive(&mut decoder, &mut context, &inputs[..100]);
+    let err = decoder.stop_recording().unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+}
+
+#[test]
+fn test_truncated_log() {
+    let inputs = session_inputs();
+    let log = SharedLog::default();
+    let mut decoder = Decoder::new();
+    let mut context = Context::new();
+    decoder.start_recording(log.clone(), &context).unwrap();
+    receive(&mut decoder, &mut context, &inputs[..10]);
+    decoder.stop_recording().unwrap();
+
+    let log = log.bytes();
+    let mut replayer = SessionReplayer::new(&log[..log.len() - 2]).unwrap();
+    assert!(matches!(replayer.replay_to(9), Err(AlecError::Protocol(_))));
+    assert!(matches!(
+        SessionReplayer::new(&b"ALSR"[..]),
+        Err(AlecError::Protocol(_))
+    ));
+}
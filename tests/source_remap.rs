@@ -0,0 +1,122 @@
+//! Source id remapping:
+//! - Remapped sources keep their predictions under the new ids, and the
+//!   old ids no longer predict
+//! - A colliding map is refused and leaves the context untouched, unless
+//!   merging is asked for
+//! - Source aliases travel with the preload bytes, so the importer can
+//!   translate to its own ids; contexts without aliases keep the
+//!   version 1 format
+
+use std::collections::HashMap;
+
+use alec::context::{MergeStrategy, RemapReport, ALCS_FORMAT_VERSION};
+use alec::error::ContextError;
+use alec::{AlecError, Context, RawData};
+
+/// Gateway context with compact sources 1 (~20) and 2 (~1000)
+fn gateway_context() -> Context {
+    let mut context = Context::new();
+    for i in 0..20u64 {
+        let wobble = (i % 3) as f64 * 0.1;
+        context.observe(&RawData::with_source(1, 20.0 + wobble, i));
+        context.observe(&RawData::with_source(2, 1000.0 + wobble, i));
+    }
+    context
+}
+
+#[test]
+fn test_remap_moves_predictions() {
+    let mut context = gateway_context();
+    let (one, two) = (context.predict(1).unwrap(), context.predict(2).unwrap());
+
+    let report = context
+        .remap_sources(&HashMap::from([(1, 100), (2, 200), (3, 300)]), None)
+        .unwrap();
+    assert_eq!(
+        report,
+        RemapReport {
+            sources_remapped: 2,
+            sources_merged: 0,
+            unknown_sources: 1,
+        }
+    );
+
+    assert_eq!(context.predict(100).unwrap().value, one.value);
+    assert_eq!(context.predict(200).unwrap().value, two.value);
+    assert!(context.predict(1).is_none());
+    assert!(context.predict(2).is_none());
+    assert_eq!(context.last_value(1), None);
+
+    // Swaps are not collisions
+    context
+        .remap_sources(&HashMap::from([(100, 200), (200, 100)]), None)
+        .unwrap();
+    assert_eq!(context.predict(200).unwrap().value, one.value);
+    assert_eq!(context.predict(100).unwrap().value, two.value);
+}
+
+#[test]
+fn test_collision_refused_unless_merging() {
+    let mut context = gateway_context();
+    context.set_source_alias(1, 0xA1);
+    let before = context.to_preload_bytes("gw").unwrap();
+    let version = context.version();
+
+    let collision = HashMap::from([(1, 5), (2, 5)]);
+    assert_eq!(
+        context.remap_sources(&collision, None).unwrap_err(),
+        AlecError::Context(ContextError::SourceCollision {
+            source_id: 5,
+            sources: 2,
+        })
+    );
+    assert_eq!(context.to_preload_bytes("gw").unwrap(), before);
+    assert_eq!(context.version(), version);
+
+    // Remapping onto a source that stays put collides too
+    assert!(context
+        .remap_sources(&HashMap::from([(1, 2)]), None)
+        .is_err());
+
+    let report = context
+        .remap_sources(&collision, Some(MergeStrategy::PreferSelf))
+        .unwrap();
+    assert_eq!(report.sources_merged, 1);
+    assert_eq!(context.source_count(), 1);
+    assert_eq!(context.source_alias(5), Some(0xA1));
+    // Source 1 landed first and keeps its last value
+    assert!((context.last_value(5).unwrap() - 20.0).abs() < 1.0);
+    assert!(context.predict(1).is_none() && context.predict(2).is_none());
+}
+
+#[test]
+fn test_aliases_translate_on_import() {
+    let mut gateway = gateway_context();
+    gateway.set_source_alias(1, 0xDEAD_0001);
+    gateway.set_source_alias(2, 0xDEAD_0002);
+    let (one, two) = (gateway.predict(1).unwrap(), gateway.predict(2).unwrap());
+
+    let bytes = gateway.to_preload_bytes("gw").unwrap();
+    assert_eq!(bytes[4..8], ALCS_FORMAT_VERSION.to_le_bytes());
+    assert!(Context::validate_preload_header(&bytes));
+
+    // The backend knows the devices under its own ids
+    let mut backend = Context::from_preload_bytes(&bytes).unwrap();
+    assert_eq!(backend.source_alias(2), Some(0xDEAD_0002));
+    let report = backend
+        .translate_sources(
+            &HashMap::from([(0xDEAD_0001, 7001), (0xDEAD_0002, 7002)]),
+            None,
+        )
+        .unwrap();
+    assert_eq!(report.sources_remapped, 2);
+    assert_eq!(backend.predict(7001).unwrap().value, one.value);
+    assert_eq!(backend.predict(7002).unwrap().value, two.value);
+    assert_eq!(backend.source_alias(7001), Some(0xDEAD_0001));
+    assert!(backend.predict(1).is_none());
+
+    // Without aliases, the bytes stay readable by version 1 readers
+    let plain = gateway_context().to_preload_bytes("gw").unwrap();
+    assert_eq!(plain[4..8], 1u32.to_le_bytes());
+    assert!(Context::from_preload_bytes(&plain).is_ok());
+}
@@ -0,0 +1,149 @@
+//! Streaming statistics (`alec::stats`):
+//! - `RunningStats` matches known means and variances, including values
+//!   around 1e9 with a spread of a few units
+//! - Merging partitions gives the statistics of the whole stream
+//! - `Ema` follows the recurrence from its first value, weights scaling
+//!   alpha
+//! - `WindowedStats` keeps the last values in order with their min, max,
+//!   mean and standard deviation
+
+use alec::{Ema, RunningStats, WindowedStats};
+use approx::assert_relative_eq;
+
+const SAMPLE: [f64; 8] = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+fn running(values: &[f64]) -> RunningStats {
+    let mut stats = RunningStats::new();
+    for &value in values {
+        stats.push(value);
+    }
+    stats
+}
+
+#[test]
+fn test_running_stats_known_sequence() {
+    let stats = running(&SAMPLE);
+    assert_eq!(stats.count(), 8);
+    assert_eq!(stats.mean(), 5.0);
+    assert_eq!(stats.m2(), 32.0);
+    assert_eq!(stats.population_variance(), 4.0);
+    assert_relative_eq!(stats.variance(), 32.0 / 7.0);
+    assert_relative_eq!(stats.std_dev(), (32.0f64 / 7.0).sqrt());
+
+    let empty = RunningStats::new();
+    assert_eq!(empty.variance(), 0.0);
+    assert_eq!(empty.population_variance(), 0.0);
+    assert_eq!(running(&[3.0]).variance(), 0.0);
+}
+
+#[test]
+fn test_running_stats_large_offset() {
+    // Sum-of-squares would lose every significant digit here
+    let values: Vec<f64> = [4.0, 7.0, 13.0, 16.0].iter().map(|v| 1e9 + v).collect();
+    let stats = running(&values);
+    assert_relative_eq!(stats.mean(), 1e9 + 10.0);
+    assert_relative_eq!(stats.variance(), 30.0, max_relative = 1e-9);
+
+    let values: Vec<f64> = (0..10_000)
+        .map(|i| 1e9 + if i % 2 == 0 { 1e-3 } else { -1e-3 })
+        .collect();
+    let stats = running(&values);
+    assert_relative_eq!(stats.population_variance(), 1e-6, max_relative = 1e-3);
+
+    let mut window = WindowedStats::new(4);
+    window.extend([4.0, 7.0, 13.0, 16.0].iter().map(|v| 1e9 + v));
+    assert_relative_eq!(window.variance().unwrap(), 30.0, max_relative = 1e-9);
+}
+
+#[test]
+fn test_running_stats_merge() {
+    let whole = running(&SAMPLE);
+    for split in 0..=SAMPLE.len() {
+        let mut left = running(&SAMPLE[..split]);
+        left.merge(&running(&SAMPLE[split..]));
+        assert_eq!(left.count(), whole.count());
+        assert_relative_eq!(left.mean(), whole.mean(), max_relative = 1e-12);
+        assert_relative_eq!(left.m2(), whole.m2(), max_relative = 1e-12);
+    }
+
+    // Far apart partitions
+    let mut low = running(&[1e9 + 1.0, 1e9 + 3.0]);
+    low.merge(&running(&[1e9 + 5.0, 1e9 + 7.0]));
+    assert_relative_eq!(low.variance(), 20.0 / 3.0, max_relative = 1e-9);
+
+    let restored = RunningStats::from_parts(whole.count(), whole.mean(), whole.m2());
+    assert_eq!(restored, whole);
+}
+
+#[test]
+fn test_running_stats_weighted() {
+    let mut weighted = RunningStats::new();
+    weighted.push_weighted(10.0, 1.0);
+    weighted.push_weighted(20.0, 0.5);
+    // One value's pull halved: the mean moves a quarter of the way
+    assert_eq!(weighted.count(), 2);
+    assert_eq!(weighted.mean(), 12.5);
+    assert_eq!(weighted.m2(), 0.5 * 10.0 * 7.5);
+
+    let mut plain = RunningStats::new();
+    let mut unit = RunningStats::new();
+    for &value in &SAMPLE {
+        plain.push(value);
+        unit.push_weighted(value, 1.0);
+    }
+    assert_eq!(plain, unit);
+}
+
+#[test]
+fn test_ema() {
+    let mut ema = Ema::new(0.5);
+    assert_eq!(ema.value(), None);
+    let expected = [2.0, 3.0, 3.5, 3.75, 4.375, 4.6875, 5.84375, 7.421875];
+    for (&value, &average) in SAMPLE.iter().zip(&expected) {
+        ema.push(value);
+        assert_eq!(ema.value(), Some(average));
+    }
+    assert_eq!(ema.alpha(), 0.5);
+
+    // The first value starts the average whatever its weight
+    let mut ema = Ema::new(0.5);
+    ema.push_weighted(10.0, 0.0);
+    assert_eq!(ema.value(), Some(10.0));
+    ema.push_weighted(20.0, 0.0);
+    assert_eq!(ema.value(), Some(10.0));
+    ema.push_weighted(20.0, 0.5);
+    assert_eq!(ema.value(), Some(12.5));
+
+    let resumed = Ema::with_value(0.5, Some(12.5));
+    assert_eq!(resumed, ema);
+}
+
+#[test]
+fn test_windowed_stats() {
+    let mut window = WindowedStats::new(3);
+    assert!(window.is_empty());
+    assert_eq!(window.mean(), None);
+    assert_eq!(window.min(), None);
+
+    window.push(5.0);
+    assert_eq!(window.std_dev(), None);
+    window.extend([1.0, 9.0, 3.0]);
+    assert_eq!(window.len(), 3);
+    assert_eq!(window.capacity(), 3);
+    assert_eq!(window.iter().collect::<Vec<_>>(), vec![1.0, 9.0, 3.0]);
+    assert_eq!(window.last(), Some(3.0));
+    assert_eq!(window.min(), Some(1.0));
+    assert_eq!(window.max(), Some(9.0));
+    assert_eq!(window.mean(), Some(13.0 / 3.0));
+    assert_relative_eq!(window.variance().unwrap(), 52.0 / 3.0);
+    assert_relative_eq!(window.std_dev().unwrap(), (52.0f64 / 3.0).sqrt());
+
+    window.clear();
+    assert!(window.is_empty());
+    assert_eq!(window.capacity(), 3);
+
+    // Nothing is kept without capacity
+    let mut none = WindowedStats::new(0);
+    none.push(1.0);
+    assert!(none.is_empty());
+}
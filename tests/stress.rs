@@ -135,6 +135,8 @@ fn stress_test_context_evolution() {
         evolution_interval: 100,
         promotion_threshold: 5,
         enabled: true,
+        step_patterns: 0,
+        protect_priority_at_or_above: None,
     });
 
     let iterations = 100_000;
@@ -239,8 +241,9 @@ fn stress_test_multi_value_encoding() {
         }
 
         // Encode multi-value
-        let message =
-            encoder.encode_multi(&values, i as u32, base_timestamp, Priority::P3Normal, &ctx);
+        let message = encoder
+            .encode_multi(&values, i as u32, base_timestamp, Priority::P3Normal, &ctx)
+            .unwrap();
 
         // Decode and verify
         let decoded = decoder.decode_multi(&message, &ctx).unwrap();
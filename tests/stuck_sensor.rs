@@ -0,0 +1,148 @@
+//! Stuck-sensor detection (`AnomalyLabel::Stuck`):
+//! - 500 identical readings over 2 hours are labelled stuck once the run
+//!   passes both thresholds, escalated to P2, and their messages flagged
+//!   suspect for the decoder
+//! - A quantized slow signal with occasional ±1 LSB changes is never stuck
+//! - A noisy flat signal held by a dead-zone is never stuck: the dead-zone
+//!   suppresses transmission, not observation
+//! - `stuck_epsilon` lets changes below it extend the run
+
+use alec::classifier::ClassifierConfig;
+use alec::context::ContextConfig;
+use alec::{
+    AnomalyLabel, Classification, Classifier, Context, DeadZone, Decoder, Encoder, Priority,
+    RawData,
+};
+
+const SOURCE: u32 = 4;
+/// 500 readings over 2 hours
+const INTERVAL_MS: u64 = 14_400;
+const HOUR_MS: u64 = 3_600_000;
+
+fn stuck_label(labels: &[AnomalyLabel]) -> Option<AnomalyLabel> {
+    labels
+        .iter()
+        .find(|label| matches!(label, AnomalyLabel::Stuck { .. }))
+        .cloned()
+}
+
+/// Classify, encode and decode `values`, returning the classifications
+/// and whether each decoded message was flagged suspect
+fn run(
+    values: &[f64],
+    context: &mut Context,
+    encoder: &mut Encoder,
+) -> Vec<(Classification, bool)> {
+    let classifier = Classifier::default();
+    let mut decoder = Decoder::new();
+    let mut decoder_context = Context::new();
+    let mut results = Vec::new();
+
+    for (i, &value) in values.iter().enumerate() {
+        let data = RawData::with_source(SOURCE, value, i as u64 * INTERVAL_MS);
+        let classification = classifier.classify(&data, context);
+        let message = encoder.encode(&data, &classification, context);
+        context.observe(&data);
+
+        let decoded = decoder
+            .decode_bytes(&message.to_bytes(), &decoder_context)
+            .unwrap();
+        decoder_context.observe(&RawData::with_source(SOURCE, decoded.value, data.timestamp));
+        results.push((classification, decoded.suspect));
+    }
+    results
+}
+
+#[test]
+fn test_identical_readings_flagged_stuck() {
+    let values = vec![21.5; 500];
+    let results = run(&values, &mut Context::new(), &mut Encoder::new());
+
+    let config = ClassifierConfig::default();
+    for (i, (classification, suspect)) in results.iter().enumerate() {
+        let span_ms = i as u64 * INTERVAL_MS;
+        match stuck_label(&classification.labels) {
+            Some(AnomalyLabel::Stuck {
+                count,
+                span_ms: span,
+            }) => {
+                assert_eq!(count, i as u64 + 1);
+                assert_eq!(span, span_ms);
+                assert!(count >= config.stuck_samples as u64 && span >= HOUR_MS);
+                assert!(classification.priority <= Priority::P2Important);
+                assert!(suspect, "sample {} not flagged", i);
+            }
+            _ => {
+                assert!(span_ms < HOUR_MS, "sample {} not stuck", i);
+                assert!(!suspect, "sample {} flagged early", i);
+            }
+        }
+    }
+    assert!(stuck_label(&results[499].0.labels).is_some());
+}
+
+#[test]
+fn test_quantized_slow_signal_not_stuck() {
+    // 0.1 LSB, one step up or down every 30 to 90 samples
+    let mut values = Vec::new();
+    let mut value = 20.0;
+    let mut state = 7u64;
+    while values.len() < 2000 {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let hold = 30 + (state >> 33) as usize % 61;
+        values.extend(std::iter::repeat(value).take(hold));
+        value += if (state >> 20) & 1 == 0 { 0.1 } else { -0.1 };
+    }
+
+    let results = run(&values, &mut Context::new(), &mut Encoder::new());
+    for (i, (classification, suspect)) in results.iter().enumerate() {
+        assert_eq!(stuck_label(&classification.labels), None, "sample {}", i);
+        assert!(!suspect);
+    }
+}
+
+#[test]
+fn test_dead_zone_not_stuck() {
+    // ±0.03 noise around a flat 21.5, all of it within the dead-zone
+    let mut state = 42u64;
+    let values: Vec<f64> = (0..1000)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            21.5 + ((state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0) * 0.03
+        })
+        .collect();
+    let mut encoder = Encoder::new();
+    encoder.set_dead_zone(SOURCE, DeadZone::Fixed(0.07));
+
+    let results = run(&values, &mut Context::new(), &mut encoder);
+    for (classification, suspect) in &results {
+        assert_eq!(stuck_label(&classification.labels), None);
+        assert!(!suspect);
+    }
+}
+
+#[test]
+fn test_epsilon_extends_run() {
+    let values: Vec<f64> = (0..500)
+        .map(|i| if i % 2 == 0 { 21.5 } else { 21.51 })
+        .collect();
+
+    let mut exact = Context::new();
+    let results = run(&values, &mut exact, &mut Encoder::new());
+    assert!(results
+        .iter()
+        .all(|(classification, _)| stuck_label(&classification.labels).is_none()));
+    assert_eq!(exact.constant_run(SOURCE).unwrap().count, 1);
+
+    let mut tolerant = Context::with_config(ContextConfig {
+        stuck_epsilon: 0.02,
+        ..ContextConfig::default()
+    });
+    let results = run(&values, &mut tolerant, &mut Encoder::new());
+    assert!(stuck_label(&results[499].0.labels).is_some());
+    assert_eq!(tolerant.constant_run(SOURCE).unwrap().count, 500);
+}
@@ -0,0 +1,217 @@
+//! Header timestamp compression (`TimestampMode`):
+//! - DeltaVarint with periodic full-timestamp anchors
+//! - TruncatedEpoch with reconstruction across low-bit wraparound
+//! - Decoder restart: compressed timestamps need a fresh anchor
+//! - Message loss: compressed timestamps after a sequence gap are
+//!   rejected until the next anchor instead of shifting
+//! - Negative deltas are rejected instead of travelling back in time
+
+use alec::error::DecodeError;
+use alec::protocol::{EncodedMessage, TimestampFormat, TimestampMode};
+use alec::{
+    AlecError, Classifier, Context, Decoder, Encoder, EncoderConfig, MessageHeader, RawData,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+fn encoder_with(mode: TimestampMode) -> Encoder {
+    Encoder::with_config(EncoderConfig {
+        timestamp_mode: mode,
+        ..Default::default()
+    })
+}
+
+fn encode(encoder: &mut Encoder, data: &RawData, context: &Context) -> Vec<u8> {
+    let classification = Classifier::default().classify(data, context);
+    encoder.encode_to_bytes(data, &classification, context)
+}
+
+#[test]
+fn test_delta_roundtrip_across_anchors() {
+    let mut encoder = encoder_with(TimestampMode::DeltaVarint { anchor_interval: 4 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    for i in 0..12u64 {
+        let data = RawData::new(21.0, START_MS + i * 5_000);
+        let bytes = encode(&mut encoder, &data, &context);
+
+        let header = MessageHeader::from_bytes(&bytes).unwrap();
+        if i % 5 == 0 {
+            // First message, then every 4 compressed messages
+            assert_eq!(header.timestamp_format(), TimestampFormat::Full);
+            assert_eq!(header.encoded_len(), MessageHeader::SIZE);
+        } else {
+            assert_eq!(header.timestamp_format(), TimestampFormat::Delta);
+            assert_eq!(header.encoded_len(), MessageHeader::MIN_SIZE);
+        }
+
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.timestamp, data.timestamp / 1000);
+    }
+}
+
+#[test]
+fn test_delta_tracks_sources_independently() {
+    let mut encoder = encoder_with(TimestampMode::DeltaVarint { anchor_interval: 0 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    for i in 0..6u64 {
+        for source in 1..=3u32 {
+            let ts = START_MS + i * 60_000 + source as u64 * 1_000;
+            let data = RawData::with_source(source, 10.0, ts);
+            let bytes = encode(&mut encoder, &data, &context);
+            let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+            assert_eq!(decoded.source_id, source);
+            assert_eq!(decoded.timestamp, ts / 1000);
+        }
+    }
+}
+
+#[test]
+fn test_clock_going_backwards_sends_anchor() {
+    let mut encoder = encoder_with(TimestampMode::DeltaVarint { anchor_interval: 0 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    for ts in [START_MS, START_MS + 10_000, START_MS - 50_000] {
+        let data = RawData::new(5.0, ts);
+        let bytes = encode(&mut encoder, &data, &context);
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.timestamp, ts / 1000);
+    }
+}
+
+#[test]
+fn test_truncated_roundtrip_across_wraparound() {
+    let mut encoder = encoder_with(TimestampMode::TruncatedEpoch { bits: 8 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    // 100 s steps cross the 256 s low-bit boundary repeatedly
+    for i in 0..20u64 {
+        let data = RawData::new(3.0, START_MS + i * 100_000);
+        let bytes = encode(&mut encoder, &data, &context);
+        let header = MessageHeader::from_bytes(&bytes).unwrap();
+        if i > 0 {
+            assert_eq!(header.timestamp_format(), TimestampFormat::Truncated);
+            assert!(header.encoded_len() < MessageHeader::SIZE);
+        }
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.timestamp, data.timestamp / 1000);
+    }
+
+    // A gap the low bits cannot express falls back to an anchor
+    let data = RawData::new(3.0, START_MS + 10_000_000);
+    let bytes = encode(&mut encoder, &data, &context);
+    let header = MessageHeader::from_bytes(&bytes).unwrap();
+    assert_eq!(header.timestamp_format(), TimestampFormat::Full);
+    let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+    assert_eq!(decoded.timestamp, data.timestamp / 1000);
+}
+
+#[test]
+fn test_decoder_restart_requires_anchor() {
+    let mut encoder = encoder_with(TimestampMode::DeltaVarint { anchor_interval: 0 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    for i in 0..3u64 {
+        let bytes = encode(
+            &mut encoder,
+            &RawData::new(1.0, START_MS + i * 1_000),
+            &context,
+        );
+        decoder.decode_bytes(&bytes, &context).unwrap();
+    }
+
+    // Simulated restart: per-source timestamps are lost
+    decoder.reset();
+    let bytes = encode(&mut encoder, &RawData::new(1.0, START_MS + 3_000), &context);
+    let result = decoder.decode_bytes(&bytes, &context);
+    assert!(matches!(
+        result,
+        Err(AlecError::Decode(DecodeError::MissingTimestampAnchor {
+            source_id: 0
+        }))
+    ));
+
+    // The receiver asks for an anchor; decoding resumes from it
+    encoder.force_timestamp_anchor();
+    for i in 4..8u64 {
+        let data = RawData::new(1.0, START_MS + i * 1_000);
+        let bytes = encode(&mut encoder, &data, &context);
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.timestamp, data.timestamp / 1000);
+    }
+}
+
+#[test]
+fn test_negative_delta_rejected() {
+    let mut encoder = encoder_with(TimestampMode::DeltaVarint { anchor_interval: 0 });
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    let bytes = encode(&mut encoder, &RawData::new(1.0, START_MS), &context);
+    decoder.decode_bytes(&bytes, &context).unwrap();
+
+    let bytes = encode(&mut encoder, &RawData::new(1.0, START_MS + 1_000), &context);
+    let mut message = EncodedMessage::from_bytes(&bytes).unwrap();
+    message.header.timestamp = TimestampFormat::pack_delta(-5);
+
+    let result = decoder.decode(&message, &context);
+    assert!(matches!(
+        result,
+        Err(AlecError::Decode(DecodeError::TimestampRegression {
+            source_id: 0,
+            last
+        })) if last == (START_MS / 1000) as u32
+    ));
+}
+
+#[test]
+fn test_message_loss_requires_anchor() {
+    for mode in [
+        TimestampMode::DeltaVarint { anchor_interval: 4 },
+        TimestampMode::TruncatedEpoch { bits: 8 },
+    ] {
+        let mut encoder = encoder_with(mode);
+        let mut decoder = Decoder::new();
+        let context = Context::new();
+
+        // Two sources sharing the encoder's sequence counter; message 4
+        // (source 1) is lost
+        let mut decoded = Vec::new();
+        for i in 0..16u64 {
+            let source = (i % 2) as u32;
+            let data = RawData::with_source(source, 7.0, START_MS + i * 60_000);
+            let bytes = encode(&mut encoder, &data, &context);
+            if i == 4 {
+                continue;
+            }
+            let header = MessageHeader::from_bytes(&bytes).unwrap();
+            match decoder.decode_bytes(&bytes, &context) {
+                Ok(value) => {
+                    assert_eq!(value.timestamp, data.timestamp / 1000, "{:?} {}", mode, i);
+                    decoded.push(i);
+                }
+                Err(AlecError::Decode(DecodeError::MissingTimestampAnchor { source_id })) => {
+                    assert_eq!(source_id, source);
+                    assert_ne!(header.timestamp_format(), TimestampFormat::Full);
+                    // The receiver asks for an anchor
+                    encoder.force_timestamp_anchor();
+                }
+                Err(e) => panic!("{:?} {}: {}", mode, i, e),
+            }
+        }
+        // The message after the gap is rejected, anchors resume decoding
+        assert_eq!(&decoded[..4], &[0, 1, 2, 3], "{:?}", mode);
+        assert!(!decoded.contains(&5), "{:?}", mode);
+        assert!(
+            decoded.ends_with(&[8, 9, 10, 11, 12, 13, 14, 15]),
+            "{:?}",
+            mode
+        );
+    }
+}
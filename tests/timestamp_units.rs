@@ -0,0 +1,170 @@
+//! Timestamp units (`alec::TimestampUnit`):
+//! - The same instant given in seconds and in microseconds normalizes to
+//!   the same microseconds, encodes to the same header timestamp and
+//!   decodes to equal timestamps
+//! - Milliseconds and microseconds keep their sub-second part through
+//!   the header and decode in the recorded unit
+//! - An encoder recording microseconds and a decoder expecting
+//!   milliseconds fail with `TimestampUnitMismatch`
+//! - Messages recording no unit are accepted whatever the decoder expects
+//! - A recorded session keeps the decoder's unit
+
+use alec::error::DecodeError;
+use alec::session::SessionReplayer;
+use alec::{
+    AlecError, Classifier, Context, Decoder, DecoderConfig, Encoder, EncoderConfig, Priority,
+    RawData, TimestampUnit,
+};
+
+/// 2024-02-01 00:00:00 UTC
+const INSTANT_SECS: u64 = 1_706_745_600;
+
+fn encoder(unit: Option<TimestampUnit>) -> Encoder {
+    Encoder::with_config(EncoderConfig {
+        timestamp_unit: unit,
+        ..Default::default()
+    })
+}
+
+fn decoder(unit: Option<TimestampUnit>) -> Decoder {
+    Decoder::with_config(DecoderConfig {
+        timestamp_unit: unit,
+        ..Default::default()
+    })
+}
+
+/// Bytes of one reading taken at `timestamp`, in `unit`
+fn reading(unit: TimestampUnit, timestamp: u64) -> Vec<u8> {
+    let mut encoder = encoder(Some(unit));
+    let data = RawData::new(21.5, 0).with_timestamp_in(unit, timestamp);
+    let message = encoder.encode_with_priority(&data, Priority::P3Normal, &Context::new());
+    encoder.message_to_bytes(&message)
+}
+
+#[test]
+fn test_same_instant_in_seconds_and_micros() {
+    let secs = RawData::new(21.5, 0).with_timestamp_in(TimestampUnit::Seconds, INSTANT_SECS);
+    let micros = RawData::new(21.5, 0)
+        .with_timestamp_in(TimestampUnit::Micros, INSTANT_SECS * 1_000_000 + 250_000);
+    assert_eq!(secs.timestamp_micros(), 1_706_745_600_000_000);
+    assert_eq!(micros.timestamp_micros(), secs.timestamp_micros() + 250_000);
+    assert_eq!(secs.timestamp_secs(), micros.timestamp_secs());
+    // Unspecified timestamps are milliseconds
+    assert_eq!(
+        RawData::new(21.5, INSTANT_SECS * 1_000).timestamp_micros(),
+        secs.timestamp_micros()
+    );
+
+    let context = Context::new();
+    let from_secs = decoder(Some(TimestampUnit::Seconds))
+        .decode_bytes(&reading(TimestampUnit::Seconds, INSTANT_SECS), &context)
+        .unwrap();
+    let from_micros = decoder(Some(TimestampUnit::Micros))
+        .decode_bytes(
+            &reading(TimestampUnit::Micros, INSTANT_SECS * 1_000_000),
+            &context,
+        )
+        .unwrap();
+    assert_eq!(from_secs.timestamp, INSTANT_SECS);
+    assert_eq!(from_secs.timestamp_micros(), from_micros.timestamp_micros());
+    assert_eq!(from_secs.timestamp_unit, Some(TimestampUnit::Seconds));
+    assert_eq!(from_micros.timestamp_unit, Some(TimestampUnit::Micros));
+}
+
+#[test]
+fn test_sub_second_round_trip() {
+    let millis = INSTANT_SECS * 1_000 + 250;
+    let bytes = reading(TimestampUnit::Millis, millis);
+    let decoded = decoder(Some(TimestampUnit::Millis))
+        .decode_bytes(&bytes, &Context::new())
+        .unwrap();
+    assert_eq!(decoded.timestamp, millis);
+    assert_eq!(decoded.timestamp_micros(), millis as i64 * 1_000);
+
+    let micros = INSTANT_SECS * 1_000_000 + 250_125;
+    let bytes = reading(TimestampUnit::Micros, micros);
+    let decoded = decoder(Some(TimestampUnit::Micros))
+        .decode_bytes(&bytes, &Context::new())
+        .unwrap();
+    assert_eq!(decoded.timestamp, micros);
+
+    // Data in a finer unit than the encoder's is truncated to it
+    let mut encoder = encoder(Some(TimestampUnit::Millis));
+    let data = RawData::new(21.5, 0).with_timestamp_in(TimestampUnit::Micros, micros);
+    let message = encoder.encode_with_priority(&data, Priority::P3Normal, &Context::new());
+    assert_eq!(message.header.timestamp_in_unit(), Some(millis));
+}
+
+#[test]
+fn test_unit_mismatch_detected() {
+    let bytes = reading(TimestampUnit::Micros, INSTANT_SECS * 1_000_000);
+    let err = decoder(Some(TimestampUnit::Millis))
+        .decode_bytes(&bytes, &Context::new())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Decode(DecodeError::TimestampUnitMismatch {
+            expected: TimestampUnit::Millis,
+            actual: TimestampUnit::Micros,
+        })
+    ));
+
+    // Bare timestamps are read in the encoder's unit as well
+    let mut encoder = encoder(Some(TimestampUnit::Seconds));
+    let message = encoder
+        .encode_multi(
+            &[(0, 1.0)],
+            3,
+            INSTANT_SECS,
+            Priority::P3Normal,
+            &Context::new(),
+        )
+        .unwrap();
+    assert_eq!(message.header.timestamp as u64, INSTANT_SECS);
+    assert_eq!(
+        message.header.timestamp_unit(),
+        Some(TimestampUnit::Seconds)
+    );
+}
+
+#[test]
+fn test_unrecorded_unit_accepted() {
+    let mut encoder = encoder(None);
+    let data = RawData::new(21.5, INSTANT_SECS * 1_000);
+    let context = Context::new();
+    let classification = Classifier::default().classify(&data, &context);
+    let message = encoder.encode(&data, &classification, &context);
+    assert_eq!(message.header.timestamp_unit(), None);
+
+    let decoded = decoder(Some(TimestampUnit::Micros))
+        .decode(&message, &context)
+        .unwrap();
+    assert_eq!(decoded.timestamp, INSTANT_SECS);
+    assert_eq!(decoded.timestamp_unit, None);
+
+    // A decoder without a unit takes any
+    let bytes = reading(TimestampUnit::Micros, INSTANT_SECS * 1_000_000);
+    assert!(decoder(None).decode_bytes(&bytes, &Context::new()).is_ok());
+}
+
+#[test]
+fn test_session_replay_keeps_unit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.log");
+    let mut decoder = decoder(Some(TimestampUnit::Seconds));
+    let context = Context::new();
+    decoder
+        .start_recording(std::fs::File::create(&path).unwrap(), &context)
+        .unwrap();
+    decoder
+        .decode_bytes(&reading(TimestampUnit::Seconds, INSTANT_SECS), &context)
+        .unwrap();
+    decoder.stop_recording().unwrap();
+
+    let log = std::fs::File::open(&path).unwrap();
+    let replayer = SessionReplayer::new(std::io::BufReader::new(log)).unwrap();
+    assert_eq!(
+        replayer.decoder().config().timestamp_unit,
+        Some(TimestampUnit::Seconds)
+    );
+}
@@ -0,0 +1,112 @@
+//! Preload training through `alec::context::trainer`:
+//! - A preload trained on a sine dataset survives serialization
+//! - A context loaded from it compresses the first 100 messages better
+//!   than an untrained context
+//! - CSV input in the `alec-testdata` layout trains the same way
+
+use alec::context::trainer::{ColumnMapping, Trainer, TrainerConfig};
+use alec::context::{Context, PreloadFile};
+use alec::{Classifier, Decoder, Encoder, RawData};
+
+const SOURCE: u32 = 7;
+const SAMPLES: u64 = 5000;
+
+/// Pressure-like sine: large offset and a phase keeping samples off
+/// round values, so raw values need 64 bits
+fn sine(i: u64) -> RawData {
+    let value = 50_000.0 + 50.0 * (i as f64 * core::f64::consts::TAU / 200.0 + 1.0).sin();
+    RawData::with_source(SOURCE, value, i * 1000)
+}
+
+/// Encode `samples` from `context`, checking they decode, and return the total size
+fn encoded_bytes(mut context: Context, mut receiver: Context, samples: &[RawData]) -> usize {
+    let mut encoder = Encoder::new();
+    let mut decoder = Decoder::new();
+    let classifier = Classifier::default();
+    let mut total = 0;
+    for sample in samples {
+        let classification = classifier.classify(sample, &context);
+        let bytes = encoder.encode_to_bytes(sample, &classification, &context);
+        let decoded = decoder.decode_bytes(&bytes, &receiver).unwrap();
+        assert!((decoded.value - sample.value).abs() < 0.01);
+        total += bytes.len();
+        context.observe(sample);
+        receiver.observe(sample);
+    }
+    total
+}
+
+/// Context loaded from serialized preload bytes, warmed for `SOURCE`
+fn load(bytes: &[u8]) -> Context {
+    let preload = PreloadFile::from_bytes(bytes).unwrap();
+    let mut context = Context::from_preload(&preload).unwrap();
+    context.warm_source(SOURCE, &preload.statistics.recent_values);
+    context
+}
+
+#[test]
+fn test_trained_preload_beats_untrained_context() {
+    let samples: Vec<RawData> = (0..SAMPLES).map(sine).collect();
+    let mut trainer = Trainer::new(TrainerConfig::default());
+    trainer.feed(&samples);
+    let preload = trainer.finish("pressure").unwrap();
+
+    let report = trainer.report().unwrap();
+    assert_eq!(report.passes, 2);
+    assert_eq!(report.training_samples, 4500);
+    assert_eq!(report.holdout_samples, 500);
+    assert!(report.patterns > 0);
+    assert!(report.expected_compression_ratio > 1.0);
+    assert_eq!(preload.training_samples, 4500);
+    assert!(!preload.statistics.recent_values.is_empty());
+
+    // First 100 messages after the training data
+    let live = &samples[4500..4600];
+    let bytes = preload.to_bytes();
+    let trained = encoded_bytes(load(&bytes), load(&bytes), live);
+    let untrained = encoded_bytes(Context::new(), Context::new(), live);
+
+    // The untrained context sends its first value raw (64-bit); after
+    // that both contexts settle on the same delta widths
+    assert!(
+        trained < untrained,
+        "trained {} bytes, untrained {} bytes",
+        trained,
+        untrained
+    );
+}
+
+#[test]
+fn test_feed_csv_matches_testdata_output() {
+    let mut csv = String::from("timestamp_ms,pressure\n");
+    // 2000 rows: the 1800 training rows span whole periods
+    for i in 0..2000 {
+        let sample = sine(i);
+        csv.push_str(&format!("{},{:.6}\n", sample.timestamp, sample.value));
+    }
+
+    let mut trainer = Trainer::new(TrainerConfig::default());
+    let mapping = ColumnMapping::new().column("pressure", SOURCE);
+    assert_eq!(trainer.feed_csv(csv.as_bytes(), &mapping).unwrap(), 2000);
+    let preload = trainer.finish("pressure").unwrap();
+
+    let report = trainer.report().unwrap();
+    assert_eq!(report.holdout_samples, 200);
+    assert!(report.expected_compression_ratio > 1.0);
+    assert!((preload.statistics.mean - 50_000.0).abs() < 1.0);
+}
+
+#[test]
+fn test_single_pass_uses_finest_quantization() {
+    let samples: Vec<RawData> = (0..1000).map(sine).collect();
+    let mut trainer = Trainer::new(TrainerConfig {
+        passes: 1,
+        ..Default::default()
+    });
+    trainer.feed(&samples);
+    trainer.finish("pressure").unwrap();
+
+    let report = trainer.report().unwrap();
+    assert_eq!(report.passes, 1);
+    assert!((report.quantization_step - 0.01).abs() < 1e-12);
+}
@@ -0,0 +1,205 @@
+//! Transmission plans (`Encoder::announce_plan`):
+//! - A delivered plan lets the next values travel as plan-relative frames
+//!   several bytes smaller than regular messages, decoded exactly
+//! - The plan ends after its message count; the next value is sent raw
+//! - A dropped plan makes its frames fail with `PlanMissing`, after which
+//!   regular messages decode again (exactly once the context is resynced)
+//! - Values the plan cannot represent and values past the timeout fall
+//!   back to regular messages
+
+use alec::error::DecodeError;
+use alec::protocol::PLAN_RELATIVE_MARKER;
+use alec::{
+    AlecError, Classification, Context, Decoded, Decoder, EncodedMessage, Encoder, EncodingType,
+    RawData,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+const SOURCE: u32 = 9;
+
+/// Encoder and decoder with their own contexts
+struct Link {
+    encoder: Encoder,
+    decoder: Decoder,
+    emitter: Context,
+    receiver: Context,
+}
+
+impl Link {
+    fn new() -> Self {
+        Self {
+            encoder: Encoder::new(),
+            decoder: Decoder::new(),
+            emitter: Context::new(),
+            receiver: Context::new(),
+        }
+    }
+
+    /// Encode `value` at `START_MS + secs` and observe it on the emitter
+    fn send(&mut self, value: f64, secs: u64) -> Vec<u8> {
+        let data = RawData::with_source(SOURCE, value, START_MS + secs * 1000);
+        let bytes =
+            self.encoder
+                .encode_to_bytes(&data, &Classification::no_prediction(), &self.emitter);
+        self.emitter.observe(&data);
+        bytes
+    }
+
+    /// Decode bytes and observe any value on the receiver
+    fn receive(&mut self, bytes: &[u8]) -> Result<Decoded, AlecError> {
+        let decoded = self.decoder.decode_bytes_item(bytes, &self.receiver)?;
+        if let Decoded::Value(data) = &decoded {
+            self.receiver.observe(&RawData::with_source(
+                data.source_id,
+                data.value,
+                data.timestamp * 1000,
+            ));
+        }
+        Ok(decoded)
+    }
+
+    /// Send and deliver a value, returning the bytes and decoded value
+    fn roundtrip(&mut self, value: f64, secs: u64) -> (Vec<u8>, f64) {
+        let bytes = self.send(value, secs);
+        match self.receive(&bytes).unwrap() {
+            Decoded::Value(data) => {
+                assert_eq!(data.source_id, SOURCE);
+                assert_eq!(data.timestamp, START_MS / 1000 + secs);
+                (bytes, data.value)
+            }
+            other => panic!("expected a value, got {:?}", other),
+        }
+    }
+
+    /// Deliver a few values so both contexts know the source
+    fn warm_up(&mut self) -> usize {
+        let mut last_len = 0;
+        for i in 0..10u64 {
+            let (bytes, _) = self.roundtrip(20.0 + i as f64 * 0.1, i * 60);
+            last_len = bytes.len();
+        }
+        last_len
+    }
+
+    fn announce(&mut self, messages: u8) -> Vec<u8> {
+        let plan = self
+            .encoder
+            .announce_plan(SOURCE, messages, &self.emitter)
+            .expect("plan");
+        assert!(plan.is_plan());
+        self.encoder.message_to_bytes(&plan)
+    }
+}
+
+#[test]
+fn test_plan_relative_frames_roundtrip() {
+    let mut link = Link::new();
+    let regular_len = link.warm_up();
+
+    let plan = link.announce(8);
+    match link.receive(&plan).unwrap() {
+        Decoded::Plan {
+            source_id,
+            messages,
+            ..
+        } => {
+            assert_eq!(source_id, SOURCE);
+            assert_eq!(messages, 8);
+        }
+        other => panic!("expected a plan, got {:?}", other),
+    }
+
+    for i in 0..8u64 {
+        let value = 20.95 + i as f64 * 0.1;
+        let (bytes, decoded) = link.roundtrip(value, 600 + i * 60);
+        assert_eq!(bytes[0], PLAN_RELATIVE_MARKER);
+        assert!(
+            bytes.len() + 3 <= regular_len,
+            "{} vs {}",
+            bytes.len(),
+            regular_len
+        );
+        assert!((decoded - value).abs() < 0.005, "{} vs {}", decoded, value);
+    }
+    assert!(link.encoder.active_plan(SOURCE).is_none());
+
+    // The plan is used up: the next value is a regular raw message
+    let (bytes, decoded) = link.roundtrip(23.5, 1200);
+    let message = EncodedMessage::from_bytes(&bytes).unwrap();
+    assert_eq!(message.encoding_type(), Some(EncodingType::Raw32));
+    assert_eq!(decoded, 23.5);
+}
+
+#[test]
+fn test_dropped_plan_degrades_to_regular_messages() {
+    let mut link = Link::new();
+    link.warm_up();
+
+    // The plan message is lost
+    let _ = link.announce(5);
+
+    for i in 0..5u64 {
+        let bytes = link.send(21.0 + i as f64 * 0.1, 600 + i * 60);
+        assert_eq!(bytes[0], PLAN_RELATIVE_MARKER);
+        match link.receive(&bytes) {
+            Err(AlecError::Decode(DecodeError::PlanMissing { .. })) => {}
+            other => panic!("expected PlanMissing, got {:?}", other),
+        }
+    }
+
+    // After the plan, the first value is sent in full
+    let (bytes, decoded) = link.roundtrip(22.5, 900);
+    assert_ne!(bytes[0], PLAN_RELATIVE_MARKER);
+    assert_eq!(decoded, 22.5);
+
+    // Like after any lost message, the receiver's prediction missed the
+    // plan's values: deltas decode, but offset until the context is
+    // resynchronized
+    for i in 1..5u64 {
+        let bytes = link.send(22.5 + i as f64 * 0.1, 900 + i * 60);
+        assert_ne!(bytes[0], PLAN_RELATIVE_MARKER);
+        assert!(link.receive(&bytes).is_ok());
+    }
+    link.receiver = link.emitter.clone();
+    for i in 5..10u64 {
+        let value = 22.5 + i as f64 * 0.1;
+        let (_, decoded) = link.roundtrip(value, 900 + i * 60);
+        assert!((decoded - value).abs() < 0.01, "{} vs {}", decoded, value);
+    }
+}
+
+#[test]
+fn test_unrepresentable_values_fall_back() {
+    let mut link = Link::new();
+    link.warm_up();
+    let plan = link.announce(4);
+    link.receive(&plan).unwrap();
+
+    // Far outside the plan's delta range: regular message, plan kept
+    let (bytes, decoded) = link.roundtrip(5_000.0, 600);
+    assert_ne!(bytes[0], PLAN_RELATIVE_MARKER);
+    assert!((decoded - 5_000.0).abs() < 0.01);
+    assert!(link.encoder.active_plan(SOURCE).is_some());
+
+    let (bytes, decoded) = link.roundtrip(21.5, 660);
+    assert_eq!(bytes[0], PLAN_RELATIVE_MARKER);
+    assert!((decoded - 21.5).abs() < 0.005);
+}
+
+#[test]
+fn test_plan_expires_after_timeout() {
+    let mut link = Link::new();
+    link.warm_up();
+    link.encoder.set_plan_timeout(120);
+    let plan = link.announce(10);
+    link.receive(&plan).unwrap();
+
+    // Last warm-up value was at 540 s
+    let (bytes, _) = link.roundtrip(21.0, 600);
+    assert_eq!(bytes[0], PLAN_RELATIVE_MARKER);
+
+    let (bytes, decoded) = link.roundtrip(21.25, 700);
+    assert_ne!(bytes[0], PLAN_RELATIVE_MARKER);
+    assert_eq!(decoded, 21.25);
+    assert!(link.encoder.active_plan(SOURCE).is_none());
+}
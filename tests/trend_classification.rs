@@ -0,0 +1,124 @@
+//! Rate-of-change classification:
+//! - A slow linear ramp towards a critical threshold escalates to P2, then
+//!   P1, long before the threshold is reached, with irregular sampling
+//! - A noisy flat signal never reports a trend
+//! - No trend is reported before the fit window is filled, nor without
+//!   timestamped samples in the context
+
+use alec::classifier::TrendRule;
+use alec::context::ContextConfig;
+use alec::{Classification, ClassificationReason, Classifier, Context, Priority, RawData};
+
+/// Battery temperature classifier: bounds 0-45 °C, P2 above 0.3 °C/min,
+/// P1 when 45 °C is less than 30 minutes away
+fn battery_classifier() -> Classifier {
+    let mut classifier = Classifier::new();
+    classifier.set_critical_thresholds(0, 0.0, 45.0);
+    classifier.set_trend_rule(
+        0,
+        TrendRule {
+            window: 8,
+            max_slope_per_min: Some(0.3),
+            crossing_horizon_secs: Some(30.0 * 60.0),
+        },
+    );
+    classifier
+}
+
+/// Classify then observe each `(timestamp_ms, value)` point
+fn run(
+    classifier: &Classifier,
+    context: &mut Context,
+    points: impl IntoIterator<Item = (u64, f64)>,
+) -> Vec<(f64, Classification)> {
+    points
+        .into_iter()
+        .map(|(timestamp, value)| {
+            let data = RawData::new(value, timestamp);
+            let classification = classifier.classify(&data, context);
+            context.observe_classified(&data, &classification);
+            (value, classification)
+        })
+        .collect()
+}
+
+/// 0.5 °C/min from 20 °C, sampled every 30 to 90 seconds
+fn ramp() -> Vec<(u64, f64)> {
+    let intervals = [30_000, 90_000, 45_000, 60_000, 75_000, 35_000];
+    let mut timestamp = 0;
+    (0..60)
+        .map(|i| {
+            timestamp += intervals[i % intervals.len()];
+            (timestamp, 20.0 + 0.5 * timestamp as f64 / 60_000.0)
+        })
+        .collect()
+}
+
+fn first_with(results: &[(f64, Classification)], priority: Priority) -> Option<f64> {
+    results
+        .iter()
+        .find(|(_, c)| c.priority == priority)
+        .map(|(value, _)| *value)
+}
+
+#[test]
+fn test_ramp_escalates_before_threshold() {
+    let classifier = battery_classifier();
+    let results = run(&classifier, &mut Context::new(), ramp());
+
+    let escalated = first_with(&results, Priority::P2Important).unwrap();
+    let critical = first_with(&results, Priority::P1Critical).unwrap();
+    assert!(escalated < 25.0, "P2 only at {}", escalated);
+    // 30 minutes before 45 °C at 0.5 °C/min
+    assert!((30.0..31.0).contains(&critical), "P1 at {}", critical);
+
+    let (value, classification) = results.iter().find(|(v, _)| *v == critical).unwrap();
+    match classification.reason {
+        ClassificationReason::TrendDetected {
+            slope,
+            projected_crossing_secs: Some(secs),
+        } => {
+            assert!((slope - 0.5).abs() < 1e-9);
+            assert!((secs - (45.0 - value) * 120.0).abs() < 1e-6);
+        }
+        ref other => panic!("expected a projected crossing, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_noisy_flat_signal_has_no_trend() {
+    let classifier = battery_classifier();
+    let mut seed: u32 = 12345;
+    let points = (0..200u64).map(|i| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        let noise = (seed >> 16) as f64 / 65536.0 - 0.5;
+        (i * 60_000, 20.0 + 0.4 * noise)
+    });
+
+    let results = run(&classifier, &mut Context::new(), points);
+    assert!(results
+        .iter()
+        .all(|(_, c)| !matches!(c.reason, ClassificationReason::TrendDetected { .. })));
+}
+
+#[test]
+fn test_trend_needs_a_full_window() {
+    let classifier = battery_classifier();
+    let mut context = Context::new();
+    let results = run(&classifier, &mut context, ramp().into_iter().take(7));
+    assert!(results
+        .iter()
+        .all(|(_, c)| !matches!(c.reason, ClassificationReason::TrendDetected { .. })));
+    assert_eq!(context.samples(0).len(), 7);
+
+    // Without timestamped samples, values alone are not enough
+    let mut context = Context::with_config(ContextConfig {
+        trend_samples: 0,
+        ..Default::default()
+    });
+    let results = run(&classifier, &mut context, ramp());
+    assert!(context.samples(0).is_empty());
+    assert!(results
+        .iter()
+        .all(|(_, c)| !matches!(c.reason, ClassificationReason::TrendDetected { .. })));
+}
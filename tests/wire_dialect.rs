@@ -0,0 +1,277 @@
+//! Wire dialects (`WireDialect`, `DecoderConfig::dialect`):
+//! - Byte fixtures of the same stream in the standard and a legacy
+//!   little-endian, reordered layout decode to identical `RawData`
+//! - A standard decoder rejects the legacy fixtures, and a legacy one the
+//!   standard fixtures, instead of returning values: by checksum, or
+//!   without checksums as malformed by the plausible timestamp range
+//! - An encoder set to the legacy dialect writes the legacy fixtures;
+//!   compressed and extended headers round-trip in a custom profile
+//! - Incomplete field orders are refused; recorded sessions keep the
+//!   dialect and timestamp range
+
+use alec::error::DecodeError;
+use alec::protocol::{ByteOrder, DialectProfile, HeaderField, TimestampFormat, WireDialect};
+use alec::session::SessionReplayer;
+use alec::{
+    AlecError, ChecksumPolicy, Classifier, Context, Decoder, DecoderConfig, Encoder, EncoderConfig,
+    HeaderExtension, HeaderLayout, MessageHeader, MessageType, Priority, RawData, WireVersion,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+/// A day either side of the stream, in seconds
+const PLAUSIBLE: (u32, u32) = (
+    (START_MS / 1000) as u32 - 86_400,
+    (START_MS / 1000) as u32 + 86_400,
+);
+const VALUES: [f64; 3] = [21.5, 21.75, 22.0];
+
+/// Source 7 sending `VALUES` one second apart, checksummed
+const STANDARD: [&[u8]; 3] = [
+    &[
+        0x42, 0x00, 0x00, 0x67, 0xC9, 0x21, 0x87, 0x00, 0x00, 0x00, 0x07, 0x01, 0x41, 0xAC, 0x00,
+        0x00, 0xC1, 0x4A, 0x01, 0x57,
+    ],
+    &[
+        0x43, 0x00, 0x01, 0x67, 0xC9, 0x21, 0x88, 0x00, 0x00, 0x01, 0x07, 0x10, 0x19, 0xB8, 0x47,
+        0xB4, 0xD6,
+    ],
+    &[
+        0x43, 0x00, 0x02, 0x67, 0xC9, 0x21, 0x89, 0x00, 0x00, 0x02, 0x07, 0x10, 0x19, 0x9E, 0xC0,
+        0x3B, 0x3A,
+    ],
+];
+
+/// The same stream with a little-endian timestamp, sequence and context
+/// version, in that order, and a little-endian checksum
+const LEGACY: [&[u8]; 3] = [
+    &[
+        0x42, 0x87, 0x21, 0xC9, 0x67, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x01, 0x41, 0xAC, 0x00,
+        0x00, 0x80, 0x5B, 0xF5, 0x04,
+    ],
+    &[
+        0x43, 0x88, 0x21, 0xC9, 0x67, 0x01, 0x00, 0x01, 0x00, 0x00, 0x07, 0x10, 0x19, 0x2A, 0xF8,
+        0xE9, 0x24,
+    ],
+    &[
+        0x43, 0x89, 0x21, 0xC9, 0x67, 0x02, 0x00, 0x02, 0x00, 0x00, 0x07, 0x10, 0x19, 0xEE, 0xE7,
+        0x0F, 0x6B,
+    ],
+];
+
+fn legacy_profile() -> DialectProfile {
+    DialectProfile::new(
+        ByteOrder::LittleEndian,
+        [
+            HeaderField::Timestamp,
+            HeaderField::Sequence,
+            HeaderField::ContextVersion,
+        ],
+    )
+    .unwrap()
+}
+
+fn decoder(dialect: WireDialect) -> Decoder {
+    Decoder::with_config(DecoderConfig {
+        checksum_policy: ChecksumPolicy::Always,
+        dialect,
+        ..Default::default()
+    })
+}
+
+/// Decode fixtures in order, observing each value
+fn decode_all(decoder: &mut Decoder, fixtures: &[&[u8]]) -> Vec<RawData> {
+    let mut context = Context::new();
+    fixtures
+        .iter()
+        .map(|bytes| {
+            let decoded = decoder.decode_into(bytes, &mut context).unwrap();
+            RawData::with_source(decoded.source_id, decoded.value, decoded.timestamp * 1000)
+        })
+        .collect()
+}
+
+#[test]
+fn test_fixtures_decode_identically() {
+    let standard = decode_all(&mut decoder(WireDialect::Standard), &STANDARD);
+    let legacy = decode_all(&mut decoder(WireDialect::Legacy(legacy_profile())), &LEGACY);
+    assert_eq!(legacy, standard);
+
+    let expected: Vec<RawData> = VALUES
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| RawData::with_source(7, value, START_MS + i as u64 * 1000))
+        .collect();
+    for (decoded, expected) in standard.iter().zip(&expected) {
+        assert_eq!(decoded.source_id, expected.source_id);
+        assert_eq!(decoded.timestamp, expected.timestamp);
+        assert!((decoded.value - expected.value).abs() < 0.01);
+    }
+}
+
+#[test]
+fn test_mismatched_dialect_rejected() {
+    let mut standard = decoder(WireDialect::Standard);
+    let mut legacy = decoder(WireDialect::Legacy(legacy_profile()));
+    let context = Context::new();
+    for (standard_bytes, legacy_bytes) in STANDARD.iter().zip(&LEGACY) {
+        for result in [
+            standard.decode_bytes(legacy_bytes, &context),
+            legacy.decode_bytes(standard_bytes, &context),
+        ] {
+            match result {
+                Err(AlecError::Decode(e)) => assert!(e.checksum_algorithm().is_some(), "{e}"),
+                other => panic!("expected a checksum error, got {other:?}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mismatched_dialect_rejected_without_checksum() {
+    let legacy_dialect = WireDialect::Legacy(legacy_profile());
+    let standard_stream = encode(WireDialect::Standard, ChecksumPolicy::Never);
+    let legacy_stream = encode(legacy_dialect, ChecksumPolicy::Never);
+    let plausible = |dialect| {
+        Decoder::with_config(DecoderConfig {
+            dialect,
+            timestamp_range: Some(PLAUSIBLE),
+            ..Default::default()
+        })
+    };
+
+    // Each stream is accepted in its own dialect
+    for (dialect, stream) in [
+        (WireDialect::Standard, &standard_stream),
+        (legacy_dialect, &legacy_stream),
+    ] {
+        let fixtures: Vec<&[u8]> = stream.iter().map(Vec::as_slice).collect();
+        let decoded = decode_all(&mut plausible(dialect), &fixtures);
+        assert_eq!(decoded[2].timestamp, START_MS + 2000);
+    }
+
+    let mut standard = plausible(WireDialect::Standard);
+    let mut legacy = plausible(legacy_dialect);
+    let context = Context::new();
+    for (standard_bytes, legacy_bytes) in standard_stream.iter().zip(&legacy_stream) {
+        for result in [
+            standard.decode_bytes(legacy_bytes, &context),
+            legacy.decode_bytes(standard_bytes, &context),
+        ] {
+            match result {
+                Err(AlecError::Decode(DecodeError::MalformedMessage { offset: 0, .. })) => {}
+                other => panic!("expected a malformed message, got {other:?}"),
+            }
+        }
+    }
+}
+
+/// Encode `VALUES` as the fixtures were encoded, in `dialect`
+fn encode(dialect: WireDialect, checksum_policy: ChecksumPolicy) -> Vec<Vec<u8>> {
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        checksum_policy,
+        dialect,
+        ..Default::default()
+    });
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    VALUES
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let data = RawData::with_source(7, value, START_MS + i as u64 * 1000);
+            let classification = classifier.classify(&data, &context);
+            let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+            context.observe(&data);
+            bytes
+        })
+        .collect()
+}
+
+#[test]
+fn test_encoder_writes_dialect() {
+    for (dialect, fixtures) in [
+        (WireDialect::Standard, STANDARD),
+        (WireDialect::Legacy(legacy_profile()), LEGACY),
+    ] {
+        let encoded = encode(dialect, ChecksumPolicy::Always);
+        for (i, (bytes, fixture)) in encoded.iter().zip(fixtures).enumerate() {
+            assert_eq!(bytes, fixture, "message {i}");
+        }
+    }
+}
+
+#[test]
+fn test_header_round_trip_in_profile() {
+    let profile = DialectProfile::new(
+        ByteOrder::BigEndian,
+        [
+            HeaderField::ContextVersion,
+            HeaderField::Timestamp,
+            HeaderField::Sequence,
+        ],
+    )
+    .unwrap();
+    let dialect = WireDialect::Legacy(profile);
+    let layouts = [
+        HeaderLayout::Plain(TimestampFormat::Full),
+        HeaderLayout::Plain(TimestampFormat::Delta),
+        HeaderLayout::Extended(HeaderExtension::new(WireVersion::CURRENT)),
+    ];
+    for layout in layouts {
+        let mut header = MessageHeader::new(MessageType::Data, Priority::P2Important);
+        header.layout = layout;
+        header.sequence = 0xBEEF;
+        header.timestamp = 300;
+        header.context_version = 0x01_0203;
+        let bytes = header.to_bytes_in(&dialect);
+        assert_eq!(bytes.len(), header.encoded_len());
+        assert_eq!(
+            MessageHeader::parse_in(&bytes, &dialect),
+            Some((header, bytes.len()))
+        );
+    }
+    // Context version first, most significant byte first
+    let mut header = MessageHeader::new(MessageType::Data, Priority::P2Important);
+    header.context_version = 0x01_0203;
+    assert_eq!(header.to_bytes_in(&dialect)[1..4], [0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn test_incomplete_field_order_refused() {
+    let order = [
+        HeaderField::Sequence,
+        HeaderField::Sequence,
+        HeaderField::ContextVersion,
+    ];
+    assert!(DialectProfile::new(ByteOrder::LittleEndian, order).is_none());
+    assert_eq!(
+        DialectProfile::little_endian().field_order(),
+        DialectProfile::STANDARD_FIELD_ORDER
+    );
+    assert_eq!(WireDialect::default(), WireDialect::Standard);
+}
+
+#[test]
+fn test_session_replay_keeps_dialect() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.log");
+    let dialect = WireDialect::Legacy(legacy_profile());
+    let mut decoder = Decoder::with_config(DecoderConfig {
+        timestamp_range: Some(PLAUSIBLE),
+        ..*decoder(dialect).config()
+    });
+    let mut context = Context::new();
+    decoder
+        .start_recording(std::fs::File::create(&path).unwrap(), &context)
+        .unwrap();
+    for bytes in LEGACY {
+        decoder.decode_into(bytes, &mut context).unwrap();
+    }
+    decoder.stop_recording().unwrap();
+
+    let log = std::fs::File::open(&path).unwrap();
+    let mut replayer = SessionReplayer::new(std::io::BufReader::new(log)).unwrap();
+    assert_eq!(replayer.decoder().config().dialect, dialect);
+    assert_eq!(replayer.decoder().config().timestamp_range, Some(PLAUSIBLE));
+    assert_eq!(replayer.replay_to(2).unwrap(), context.hash());
+}
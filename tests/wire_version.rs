@@ -0,0 +1,101 @@
+//! Wire-format versioning (`WireVersion`, `HeaderExtension`):
+//! - A newer major version is rejected with `UnsupportedVersion`
+//! - A newer minor version with ignorable flags still decodes
+//! - Unknown critical flags are rejected with `UnsupportedFlags`
+//! - `VersionNegotiation` settles on the highest common version, which
+//!   the encoder then writes and the decoder reads back
+
+use alec::error::DecodeError;
+use alec::protocol::{EncodedMessage, TimestampFormat, CRITICAL_FLAGS_MASK};
+use alec::sync::VersionNegotiation;
+use alec::{
+    AlecError, Classifier, Context, Decoder, Encoder, MessageHeader, RawData, SyncMessage,
+    WireVersion,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+/// Legacy-encoded message for `value`
+fn legacy_bytes(value: f64) -> Vec<u8> {
+    let context = Context::new();
+    let data = RawData::new(value, START_MS);
+    let classification = Classifier::default().classify(&data, &context);
+    Encoder::new().encode_to_bytes(&data, &classification, &context)
+}
+
+/// Rewrite a legacy message with an extended header by hand
+fn with_extension(bytes: &[u8], wire_version: u8, flags: u8) -> Vec<u8> {
+    let mut out = vec![bytes[0] & 0x3F, wire_version, flags];
+    out.extend_from_slice(&bytes[1..]);
+    out
+}
+
+#[test]
+fn test_newer_major_is_rejected() {
+    let bytes = with_extension(&legacy_bytes(21.5), 0x20, 0x00);
+    let mut decoder = Decoder::new();
+
+    let err = decoder.decode_bytes(&bytes, &Context::new()).unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Decode(DecodeError::UnsupportedVersion { ours: 1, theirs: 2 })
+    ));
+}
+
+#[test]
+fn test_newer_minor_with_ignorable_flag_decodes() {
+    let bytes = with_extension(&legacy_bytes(21.5), 0x15, 0x01);
+    assert_eq!(0x01 & CRITICAL_FLAGS_MASK, 0);
+
+    let message = EncodedMessage::from_bytes(&bytes).unwrap();
+    assert_eq!(message.header.wire_version(), WireVersion::new(1, 5));
+    assert_eq!(message.header.timestamp_format(), TimestampFormat::Full);
+
+    let mut decoder = Decoder::new();
+    let decoded = decoder.decode_bytes(&bytes, &Context::new()).unwrap();
+    assert_eq!(decoded.value, 21.5);
+    assert_eq!(decoded.timestamp, START_MS / 1000);
+}
+
+#[test]
+fn test_unknown_critical_flag_is_rejected() {
+    let bytes = with_extension(&legacy_bytes(21.5), 0x12, 0x40);
+    let mut decoder = Decoder::new();
+
+    let err = decoder.decode_bytes(&bytes, &Context::new()).unwrap_err();
+    assert!(matches!(
+        err,
+        AlecError::Decode(DecodeError::UnsupportedFlags { flags: 0x40 })
+    ));
+}
+
+#[test]
+fn test_negotiated_version_roundtrip() {
+    let mut device = VersionNegotiation::with_max(WireVersion::new(1, 6));
+    let mut gateway = VersionNegotiation::new();
+
+    let offer = SyncMessage::from_bytes(&device.offer().to_bytes()).unwrap();
+    let reply = gateway.handle(&offer).unwrap();
+    assert!(device.handle(&reply).is_none());
+    assert_eq!(device.agreed(), Some(WireVersion::CURRENT));
+    assert_eq!(gateway.agreed(), Some(WireVersion::CURRENT));
+
+    let mut encoder = Encoder::new();
+    encoder.set_wire_version(device.agreed().unwrap());
+    let mut decoder = Decoder::new();
+    let context = Context::new();
+
+    for i in 0..4u64 {
+        let data = RawData::new(20.0 + i as f64, START_MS + i * 1_000);
+        let classification = Classifier::default().classify(&data, &context);
+        let bytes = encoder.encode_to_bytes(&data, &classification, &context);
+
+        let header = MessageHeader::from_bytes(&bytes).unwrap();
+        assert_eq!(header.version(), MessageHeader::EXTENDED_VERSION);
+        assert_eq!(header.wire_version(), WireVersion::CURRENT);
+
+        let decoded = decoder.decode_bytes(&bytes, &context).unwrap();
+        assert_eq!(decoded.value, data.value);
+        assert_eq!(decoded.timestamp, data.timestamp / 1000);
+    }
+}
@@ -0,0 +1,174 @@
+//! Zero-copy message parsing (`EncodedMessageRef`, `Decoder::decode_ref`):
+//! - Owned and borrowed messages decode to the same values over a stream
+//!   of mixed encodings, with and without checksum
+//! - A corrupted checksum is rejected the same way on both paths
+//! - Composite messages decode identically through `decode_composite_ref`
+//! - `to_owned` and `as_borrowed` round-trip
+
+use std::collections::HashSet;
+
+use alec::{
+    ChecksumPolicy, Classifier, CompositeEntry, Context, Decoded, Decoder, EncodedMessage,
+    EncodedMessageRef, Encoder, EncoderConfig, Priority, RawData,
+};
+
+const START_MS: u64 = 1_741_234_567_000;
+
+/// Signal mixing small deltas, jumps and repeated values
+fn value(i: usize) -> f64 {
+    match i % 10 {
+        0 => 100.0 + i as f64,
+        1..=3 => 20.0,
+        _ => 20.0 + (i as f64 * 0.3).sin() * 2.0,
+    }
+}
+
+/// Wire bytes of a stream of messages, with the sender's context
+/// observing each value
+fn stream(checksum_policy: ChecksumPolicy, count: usize) -> Vec<Vec<u8>> {
+    let mut encoder = Encoder::with_config(EncoderConfig {
+        checksum_policy,
+        ..Default::default()
+    });
+    let classifier = Classifier::default();
+    let mut context = Context::new();
+    (0..count)
+        .map(|i| {
+            let data = RawData::with_source(3, value(i), START_MS + i as u64 * 1000);
+            let classification = classifier.classify(&data, &context);
+            let message = encoder.encode(&data, &classification, &context);
+            context.observe(&data);
+            encoder.message_to_bytes(&message)
+        })
+        .collect()
+}
+
+/// Decoder and the context it follows
+struct Receiver {
+    decoder: Decoder,
+    context: Context,
+}
+
+impl Receiver {
+    fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+            context: Context::new(),
+        }
+    }
+
+    fn observe(&mut self, decoded: &Decoded) {
+        if let Decoded::Value(data) = decoded {
+            self.context.observe(&RawData::with_source(
+                data.source_id,
+                data.value,
+                data.timestamp * 1000,
+            ));
+        }
+    }
+
+    fn owned(&mut self, bytes: &[u8], checksum: bool) -> Decoded {
+        let message = if checksum {
+            EncodedMessage::from_bytes_with_checksum(bytes).unwrap()
+        } else {
+            EncodedMessage::from_bytes(bytes).unwrap()
+        };
+        let decoded = self.decoder.decode_item(&message, &self.context).unwrap();
+        self.observe(&decoded);
+        decoded
+    }
+
+    fn borrowed(&mut self, bytes: &[u8], checksum: bool) -> Decoded {
+        let message = if checksum {
+            EncodedMessageRef::from_bytes_with_checksum(bytes).unwrap()
+        } else {
+            EncodedMessageRef::from_bytes(bytes).unwrap()
+        };
+        let decoded = self
+            .decoder
+            .decode_item_ref(&message, &self.context)
+            .unwrap();
+        self.observe(&decoded);
+        decoded
+    }
+}
+
+#[test]
+fn test_owned_and_borrowed_decode_identically() {
+    for (policy, checksum) in [
+        (ChecksumPolicy::Never, false),
+        (ChecksumPolicy::Always, true),
+    ] {
+        let messages = stream(policy, 200);
+        let mut owned = Receiver::new();
+        let mut borrowed = Receiver::new();
+        let mut encodings = HashSet::new();
+        for (i, bytes) in messages.iter().enumerate() {
+            let expected = owned.owned(bytes, checksum);
+            assert_eq!(borrowed.borrowed(bytes, checksum), expected, "message {i}");
+            encodings.insert(EncodedMessage::from_bytes(bytes).unwrap().encoding_type());
+        }
+        assert!(encodings.len() > 2, "{encodings:?}");
+    }
+}
+
+#[test]
+fn test_corrupted_checksum_rejected_on_both_paths() {
+    let messages = stream(ChecksumPolicy::Always, 5);
+    let mut bytes = messages[4].clone();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x40;
+
+    let owned = EncodedMessage::from_bytes_with_checksum(&bytes).unwrap_err();
+    let borrowed = EncodedMessageRef::from_bytes_with_checksum(&bytes).unwrap_err();
+    assert_eq!(borrowed, owned);
+    assert!(EncodedMessageRef::from_bytes(&[]).is_none());
+}
+
+#[test]
+fn test_composite_decode_ref() {
+    let (temperature, humidity) = (Context::new(), Context::new());
+    let entries = [
+        CompositeEntry {
+            channel_id: 0,
+            value: 21.5,
+            context: &temperature,
+        },
+        CompositeEntry {
+            channel_id: 1,
+            value: 48.0,
+            context: &humidity,
+        },
+    ];
+    let message = Encoder::new()
+        .encode_composite(9, START_MS, Priority::P3Normal, &entries)
+        .unwrap();
+    let bytes = message.to_bytes();
+    let contexts = [&temperature, &humidity];
+
+    let owned = Decoder::new()
+        .decode_composite(&EncodedMessage::from_bytes(&bytes).unwrap(), |channel| {
+            contexts.get(channel as usize).copied()
+        })
+        .unwrap();
+    let parsed = EncodedMessageRef::from_bytes(&bytes).unwrap();
+    assert!(parsed.is_composite());
+    let borrowed = Decoder::new()
+        .decode_composite_ref(&parsed, |channel| contexts.get(channel as usize).copied())
+        .unwrap();
+    assert_eq!(borrowed, owned);
+    assert_eq!(borrowed.values[1].value, Ok(48.0));
+}
+
+#[test]
+fn test_to_owned_round_trip() {
+    for bytes in stream(ChecksumPolicy::Never, 20) {
+        let parsed = EncodedMessageRef::from_bytes(&bytes).unwrap();
+        let owned = parsed.to_owned();
+        assert_eq!(owned, EncodedMessage::from_bytes(&bytes).unwrap());
+        assert_eq!(owned.as_borrowed(), parsed);
+        assert_eq!(parsed.len(), bytes.len());
+        assert_eq!(parsed.to_bytes(), bytes);
+        assert_eq!(parsed.encoding_type(), owned.encoding_type());
+    }
+}